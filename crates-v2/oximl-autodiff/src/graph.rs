@@ -33,6 +33,7 @@ pub struct Node {
 /// 
 /// Uses `Arc<RwLock<Vec<Node>>>` to allow the graph to be shared safely
 /// across threads, enabling PyTorch-style multi-threaded DDP (Distributed Data Parallel) training.
+#[deprecated(note = "use oxidize_ml_autodiff::Graph")]
 #[derive(Debug, Clone)]
 pub struct Graph {
     nodes: Arc<RwLock<Vec<Node>>>,
@@ -69,6 +70,17 @@ impl Graph {
         nodes.get(id).cloned()
     }
 
+    /// Replace the value held by node `id`, e.g. a parameter whose weights
+    /// were loaded from disk, so later ops and `backward` read the new one.
+    pub fn set_data(&self, id: NodeId, data: Tensor) -> oximl_core::TensorResult<()> {
+        let mut nodes = self.nodes.write().unwrap();
+        let node = nodes.get_mut(id).ok_or_else(|| {
+            oximl_core::TensorError::InvalidOperation(format!("Node {} is not in the graph", id))
+        })?;
+        node.data = data;
+        Ok(())
+    }
+
     pub fn len(&self) -> usize {
         self.nodes.read().unwrap().len()
     }
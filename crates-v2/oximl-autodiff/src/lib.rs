@@ -1,3 +1,8 @@
+//! Deprecated: superseded by `oxidize-ml-autodiff`, whose `Graph` is now the
+//! same explicit, thread-safe `Arc<RwLock<..>>` handle and whose `Variable`
+//! works with every layer in `oxidize-ml-nn`. New code should use that crate.
+#![allow(deprecated)]
+
 pub mod graph;
 pub mod variable;
 pub mod no_grad;
@@ -4,6 +4,7 @@ use crate::no_grad::is_grad_enabled;
 use std::sync::Arc;
 
 /// A differentiable variable backed by the thread-safe computation graph.
+#[deprecated(note = "use oxidize_ml_autodiff::Variable")]
 #[derive(Debug, Clone)]
 pub struct Variable {
     pub node_id: NodeId,
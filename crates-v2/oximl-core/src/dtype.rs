@@ -3,6 +3,10 @@ use serde::{Serialize, Deserialize};
 /// Dynamic Data Types for Tensors.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DType {
+    /// IEEE 754 half precision (`half::f16`).
+    Float16,
+    /// bfloat16: f32's exponent range with an 8-bit mantissa (`half::bf16`).
+    BFloat16,
     Float32,
     Float64,
     Int32,
@@ -12,7 +16,7 @@ pub enum DType {
 
 impl DType {
     pub fn is_float(&self) -> bool {
-        matches!(self, DType::Float32 | DType::Float64)
+        matches!(self, DType::Float16 | DType::BFloat16 | DType::Float32 | DType::Float64)
     }
 
     pub fn is_int(&self) -> bool {
@@ -1,7 +1,9 @@
 pub mod dtype;
 pub mod tensor;
 pub mod ops;
+pub mod safetensors;
 
 pub use dtype::*;
 pub use tensor::*;
 pub use ops::*;
+pub use safetensors::*;
@@ -1,4 +1,5 @@
 use crate::{DType, Tensor, TensorResult, TensorError};
+use half::{bf16, f16};
 use ndarray::{ArrayD, IxDyn};
 use std::ops::{Add, Sub, Mul, Div};
 
@@ -11,6 +12,8 @@ impl Add for &Tensor {
             return Err(TensorError::TypeMismatch{ expected: self.dtype(), got: rhs.dtype() });
         }
         match (self, rhs) {
+            (Tensor::Float16(a), Tensor::Float16(b)) => Ok(Tensor::Float16((a + b).into_shared())),
+            (Tensor::BFloat16(a), Tensor::BFloat16(b)) => Ok(Tensor::BFloat16((a + b).into_shared())),
             (Tensor::Float32(a), Tensor::Float32(b)) => Ok(Tensor::Float32((a + b).into_shared())),
             (Tensor::Float64(a), Tensor::Float64(b)) => Ok(Tensor::Float64((a + b).into_shared())),
             (Tensor::Int32(a), Tensor::Int32(b)) => Ok(Tensor::Int32((a + b).into_shared())),
@@ -29,6 +32,8 @@ impl Mul for &Tensor {
             return Err(TensorError::TypeMismatch{ expected: self.dtype(), got: rhs.dtype() });
         }
         match (self, rhs) {
+            (Tensor::Float16(a), Tensor::Float16(b)) => Ok(Tensor::Float16((a * b).into_shared())),
+            (Tensor::BFloat16(a), Tensor::BFloat16(b)) => Ok(Tensor::BFloat16((a * b).into_shared())),
             (Tensor::Float32(a), Tensor::Float32(b)) => Ok(Tensor::Float32((a * b).into_shared())),
             (Tensor::Float64(a), Tensor::Float64(b)) => Ok(Tensor::Float64((a * b).into_shared())),
             (Tensor::Int32(a), Tensor::Int32(b)) => Ok(Tensor::Int32((a * b).into_shared())),
@@ -47,6 +52,8 @@ impl Div for &Tensor {
             return Err(TensorError::TypeMismatch{ expected: self.dtype(), got: rhs.dtype() });
         }
         match (self, rhs) {
+            (Tensor::Float16(a), Tensor::Float16(b)) => Ok(Tensor::Float16((a / b).into_shared())),
+            (Tensor::BFloat16(a), Tensor::BFloat16(b)) => Ok(Tensor::BFloat16((a / b).into_shared())),
             (Tensor::Float32(a), Tensor::Float32(b)) => Ok(Tensor::Float32((a / b).into_shared())),
             (Tensor::Float64(a), Tensor::Float64(b)) => Ok(Tensor::Float64((a / b).into_shared())),
             (Tensor::Int32(a), Tensor::Int32(b)) => Ok(Tensor::Int32((a / b).into_shared())),
@@ -58,6 +65,11 @@ impl Div for &Tensor {
 }
 
 impl Tensor {
+    /// Run a float op on an F16/BF16 tensor in f32 and round the result back.
+    fn via_f32(&self, op: impl FnOnce(&Tensor) -> TensorResult<Tensor>) -> TensorResult<Tensor> {
+        Ok(op(&self.to_dtype(DType::Float32))?.to_dtype(self.dtype()))
+    }
+
     /// Matrix multiplication. Extremely fast due to `ndarray` backend hitting BLAS directly.
     /// Operates on 2D Tensors.
     pub fn matmul(&self, rhs: &Tensor) -> TensorResult<Tensor> {
@@ -69,6 +81,10 @@ impl Tensor {
         }
 
         match (self, rhs) {
+            // Half precision accumulates in f32; BLAS has no f16/bf16 kernels.
+            (Tensor::Float16(_), _) | (Tensor::BFloat16(_), _) => {
+                self.via_f32(|a| a.matmul(&rhs.to_dtype(DType::Float32)))
+            }
             (Tensor::Float32(a), Tensor::Float32(b)) => {
                 let a2 = a.view().into_dimensionality::<ndarray::Ix2>().unwrap();
                 let b2 = b.view().into_dimensionality::<ndarray::Ix2>().unwrap();
@@ -101,6 +117,14 @@ impl Tensor {
     /// Compute ReLU element-wise
     pub fn relu(&self) -> TensorResult<Tensor> {
         match self {
+            Tensor::Float16(a) => {
+                let out = a.mapv(|x| if x > f16::ZERO { x } else { f16::ZERO });
+                Ok(Tensor::Float16(out.into_shared()))
+            }
+            Tensor::BFloat16(a) => {
+                let out = a.mapv(|x| if x > bf16::ZERO { x } else { bf16::ZERO });
+                Ok(Tensor::BFloat16(out.into_shared()))
+            }
             Tensor::Float32(a) => {
                 let out = a.mapv(|x| x.max(0.0));
                 Ok(Tensor::Float32(out.into_shared()))
@@ -116,6 +140,9 @@ impl Tensor {
     /// Compute ReLU backward pass element-wise (1 if x > 0 else 0) * grad
     pub fn relu_backward(&self, grad: &Tensor) -> TensorResult<Tensor> {
         match (self, grad) {
+            (Tensor::Float16(_), Tensor::Float16(_)) | (Tensor::BFloat16(_), Tensor::BFloat16(_)) => {
+                self.via_f32(|a| a.relu_backward(&grad.to_dtype(DType::Float32)))
+            }
             (Tensor::Float32(a), Tensor::Float32(g)) => {
                 let mut out = g.to_owned();
                 out.zip_mut_with(a, |out_val, x_val| {
@@ -137,6 +164,7 @@ impl Tensor {
     /// Compute Exponential element-wise
     pub fn exp(&self) -> TensorResult<Tensor> {
         match self {
+            Tensor::Float16(_) | Tensor::BFloat16(_) => self.via_f32(Tensor::exp),
             Tensor::Float32(a) => Ok(Tensor::Float32(a.mapv(|x| x.exp()).into_shared())),
             Tensor::Float64(a) => Ok(Tensor::Float64(a.mapv(|x| x.exp()).into_shared())),
             _ => Err(TensorError::InvalidOperation("Exp requires float dtype".into())),
@@ -146,6 +174,7 @@ impl Tensor {
     /// Compute Natural Logarithm element-wise
     pub fn ln(&self) -> TensorResult<Tensor> {
         match self {
+            Tensor::Float16(_) | Tensor::BFloat16(_) => self.via_f32(Tensor::ln),
             Tensor::Float32(a) => Ok(Tensor::Float32(a.mapv(|x| x.ln()).into_shared())),
             Tensor::Float64(a) => Ok(Tensor::Float64(a.mapv(|x| x.ln()).into_shared())),
             _ => Err(TensorError::InvalidOperation("Ln requires float dtype".into())),
@@ -155,6 +184,8 @@ impl Tensor {
     /// Multiply tensor by a scalar value
     pub fn scalar_mul(&self, scalar: f64) -> TensorResult<Tensor> {
         match self {
+            Tensor::Float16(a) => Ok(Tensor::Float16(a.mapv(|x| x * f16::from_f64(scalar)).into_shared())),
+            Tensor::BFloat16(a) => Ok(Tensor::BFloat16(a.mapv(|x| x * bf16::from_f64(scalar)).into_shared())),
             Tensor::Float32(a) => Ok(Tensor::Float32((a * scalar as f32).into_shared())),
             Tensor::Float64(a) => Ok(Tensor::Float64((a * scalar).into_shared())),
             Tensor::Int32(a) => Ok(Tensor::Int32((a * scalar as i32).into_shared())),
@@ -170,6 +201,7 @@ impl Tensor {
         }
         
         match self {
+            Tensor::Float16(_) | Tensor::BFloat16(_) => self.via_f32(Tensor::softmax),
             Tensor::Float32(a) => {
                 let mut out = a.to_owned();
                 // Simple numerical stability map: exp(x - max(x)) / sum
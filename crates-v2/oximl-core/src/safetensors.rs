@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use half::{bf16, f16};
+use ndarray::{ArrayD, IxDyn};
+use serde_json::{json, Map, Value};
+
+use crate::{DType, Tensor, TensorError, TensorResult};
+
+/// Named tensors, ordered by key so files are written deterministically.
+pub type TensorMap = BTreeMap<String, Tensor>;
+
+fn io_error(context: &str, e: impl std::fmt::Display) -> TensorError {
+    TensorError::InvalidOperation(format!("safetensors {}: {}", context, e))
+}
+
+fn dtype_tag(dtype: DType) -> &'static str {
+    match dtype {
+        DType::Float16 => "F16",
+        DType::BFloat16 => "BF16",
+        DType::Float32 => "F32",
+        DType::Float64 => "F64",
+        DType::Int32 => "I32",
+        DType::Int64 => "I64",
+        DType::UInt8 => "U8",
+    }
+}
+
+/// Bytes of a tensor in row-major, little-endian order.
+fn tensor_bytes(t: &Tensor) -> Vec<u8> {
+    match t {
+        Tensor::Float16(a) => a.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        Tensor::BFloat16(a) => a.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        Tensor::Float32(a) => a.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        Tensor::Float64(a) => a.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        Tensor::Int32(a) => a.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        Tensor::Int64(a) => a.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        Tensor::UInt8(a) => a.iter().copied().collect(),
+    }
+}
+
+fn tensor_from_bytes(tag: &str, shape: &[usize], bytes: &[u8]) -> TensorResult<Tensor> {
+    fn build<T, const N: usize>(shape: &[usize], bytes: &[u8], f: fn([u8; N]) -> T) -> TensorResult<ArrayD<T>> {
+        let data: Vec<T> = bytes.chunks_exact(N).map(|c| f(c.try_into().unwrap())).collect();
+        ArrayD::from_shape_vec(IxDyn(shape), data).map_err(|e| io_error("shape mismatch", e))
+    }
+    Ok(match tag {
+        "F16" => Tensor::Float16(build(shape, bytes, f16::from_le_bytes)?.into_shared()),
+        "BF16" => Tensor::BFloat16(build(shape, bytes, bf16::from_le_bytes)?.into_shared()),
+        "F32" => Tensor::Float32(build(shape, bytes, f32::from_le_bytes)?.into_shared()),
+        "F64" => Tensor::Float64(build(shape, bytes, f64::from_le_bytes)?.into_shared()),
+        "I32" => Tensor::Int32(build(shape, bytes, i32::from_le_bytes)?.into_shared()),
+        "I64" => Tensor::Int64(build(shape, bytes, i64::from_le_bytes)?.into_shared()),
+        "U8" => Tensor::UInt8(build(shape, bytes, u8::from_le_bytes)?.into_shared()),
+        other => return Err(io_error("unsupported dtype", other)),
+    })
+}
+
+/// Encode tensors in the safetensors layout: an 8-byte little-endian header
+/// length, a JSON header mapping each name to `{dtype, shape, data_offsets}`,
+/// then the concatenated raw buffers.
+pub fn serialize_safetensors(tensors: &TensorMap) -> TensorResult<Vec<u8>> {
+    let mut header = Map::new();
+    let mut body = Vec::new();
+    for (name, t) in tensors {
+        let bytes = tensor_bytes(t);
+        header.insert(
+            name.clone(),
+            json!({
+                "dtype": dtype_tag(t.dtype()),
+                "shape": t.shape(),
+                "data_offsets": [body.len(), body.len() + bytes.len()],
+            }),
+        );
+        body.extend_from_slice(&bytes);
+    }
+    let mut header = serde_json::to_vec(&Value::Object(header)).map_err(|e| io_error("header", e))?;
+    // The data buffer must start on an 8-byte boundary; pad with spaces.
+    while header.len() % 8 != 0 {
+        header.push(b' ');
+    }
+
+    let mut out = Vec::with_capacity(8 + header.len() + body.len());
+    out.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Decode a safetensors buffer. The optional `__metadata__` entry is ignored.
+pub fn deserialize_safetensors(bytes: &[u8]) -> TensorResult<TensorMap> {
+    let header_len = bytes
+        .get(..8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| io_error("parse error", "file shorter than 8 bytes"))?;
+    // The length comes straight from the file, so a corrupt one must not
+    // overflow the offset arithmetic.
+    let header_end = usize::try_from(header_len)
+        .ok()
+        .and_then(|len| len.checked_add(8))
+        .filter(|&end| end <= bytes.len())
+        .ok_or_else(|| io_error("parse error", format!("header length {} exceeds the file", header_len)))?;
+    let (header, body) = (&bytes[8..header_end], &bytes[header_end..]);
+    let header: Map<String, Value> = serde_json::from_slice(header).map_err(|e| io_error("header", e))?;
+
+    let mut tensors = TensorMap::new();
+    for (name, info) in header {
+        if name == "__metadata__" {
+            continue;
+        }
+        let field = |key: &str| info.get(key).ok_or_else(|| io_error("header", format!("'{}' has no {}", name, key)));
+        let tag = field("dtype")?.as_str().unwrap_or_default().to_string();
+        let as_usizes = |v: &Value| -> TensorResult<Vec<usize>> {
+            v.as_array()
+                .and_then(|a| a.iter().map(|d| d.as_u64().map(|d| d as usize)).collect())
+                .ok_or_else(|| io_error("header", format!("'{}' has a malformed entry", name)))
+        };
+        let shape = as_usizes(field("shape")?)?;
+        let offsets = as_usizes(field("data_offsets")?)?;
+        let data = match offsets[..] {
+            [start, end] if start <= end => body.get(start..end),
+            _ => None,
+        }
+        .ok_or_else(|| io_error("parse error", format!("'{}' has out-of-range data offsets", name)))?;
+        tensors.insert(name, tensor_from_bytes(&tag, &shape, data)?);
+    }
+    Ok(tensors)
+}
+
+/// Write tensors to a `.safetensors` file.
+pub fn save_safetensors<P: AsRef<Path>>(path: P, tensors: &TensorMap) -> TensorResult<()> {
+    std::fs::write(path, serialize_safetensors(tensors)?).map_err(|e| io_error("save error", e))
+}
+
+/// Read every tensor from a `.safetensors` file.
+pub fn load_safetensors<P: AsRef<Path>>(path: P) -> TensorResult<TensorMap> {
+    let bytes = std::fs::read(path).map_err(|e| io_error("load error", e))?;
+    deserialize_safetensors(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> TensorMap {
+        let mut tensors = TensorMap::new();
+        let f32s = ArrayD::from_shape_vec(IxDyn(&[2, 3]), vec![1.5f32, -2.0, 0.25, 3.0, 4.5, -0.5]).unwrap();
+        tensors.insert("layer.weight".to_string(), Tensor::Float32(f32s.into_shared()));
+        let f64s = ArrayD::from_shape_vec(IxDyn(&[3]), vec![0.1f64, 0.2, 0.3]).unwrap();
+        tensors.insert("layer.bias".to_string(), Tensor::Float64(f64s.into_shared()));
+        let i64s = ArrayD::from_shape_vec(IxDyn(&[2, 1]), vec![-7i64, 9]).unwrap();
+        tensors.insert("steps".to_string(), Tensor::Int64(i64s.into_shared()));
+        tensors.insert("mask".to_string(), Tensor::Int32(ArrayD::from_elem(IxDyn(&[0]), 0).into_shared()));
+        tensors.insert("bytes".to_string(), Tensor::UInt8(ArrayD::from_elem(IxDyn(&[4]), 200u8).into_shared()));
+        let halves = ArrayD::from_shape_vec(IxDyn(&[2]), vec![f16::ONE, f16::from_f32(-0.1)]).unwrap();
+        tensors.insert("half".to_string(), Tensor::Float16(halves.into_shared()));
+        let bfloats = ArrayD::from_shape_vec(IxDyn(&[1, 2]), vec![bf16::MAX, bf16::from_f32(1e-3)]).unwrap();
+        tensors.insert("bfloat".to_string(), Tensor::BFloat16(bfloats.into_shared()));
+        tensors
+    }
+
+    fn same(a: &Tensor, b: &Tensor) -> bool {
+        a.dtype() == b.dtype() && a.shape() == b.shape() && tensor_bytes(a) == tensor_bytes(b)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tensors = sample();
+        let bytes = serialize_safetensors(&tensors).unwrap();
+        let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        assert_eq!(header_len % 8, 0);
+        // "half" is stored as the little-endian IEEE bits, 1.0 = 0x3c00.
+        assert_eq!(tensor_bytes(&tensors["half"])[..2], [0x00, 0x3c]);
+        let loaded = deserialize_safetensors(&bytes).unwrap();
+        assert_eq!(loaded.len(), tensors.len());
+        for (name, t) in &tensors {
+            assert!(same(t, &loaded[name]), "{}", name);
+        }
+
+        let path = std::env::temp_dir().join(format!("oximl_safetensors_{}.safetensors", std::process::id()));
+        save_safetensors(&path, &tensors).unwrap();
+        let loaded = load_safetensors(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(same(&tensors["layer.weight"], &loaded["layer.weight"]));
+    }
+
+    #[test]
+    fn test_metadata_is_ignored() {
+        let header = br#"{"__metadata__":{"format":"pt"},"x":{"dtype":"U8","shape":[2],"data_offsets":[0,2]}}"#;
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header);
+        bytes.extend_from_slice(&[5, 6]);
+        let loaded = deserialize_safetensors(&bytes).unwrap();
+        assert_eq!(loaded.keys().collect::<Vec<_>>(), ["x"]);
+    }
+
+    #[test]
+    fn test_malformed_files_are_errors() {
+        let good = serialize_safetensors(&sample()).unwrap();
+        assert!(deserialize_safetensors(&good[..5]).is_err());
+        // Header lengths past the end of the file, up to one that would
+        // overflow `8 + len`.
+        for len in [good.len() as u64, u64::MAX - 3, u64::MAX] {
+            let mut bad = good.clone();
+            bad[..8].copy_from_slice(&len.to_le_bytes());
+            assert!(deserialize_safetensors(&bad).is_err(), "{}", len);
+        }
+        let header_len = u64::from_le_bytes(good[..8].try_into().unwrap()) as usize;
+        assert!(deserialize_safetensors(&good[..8 + header_len - 1]).is_err());
+        // Data that ends past the body.
+        assert!(deserialize_safetensors(&good[..good.len() - 1]).is_err());
+
+        let entry = |info: &str| {
+            let header = format!("{{\"x\":{}}}", info);
+            let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+            bytes.extend_from_slice(header.as_bytes());
+            bytes.extend_from_slice(&[0; 8]);
+            deserialize_safetensors(&bytes)
+        };
+        assert!(entry(r#"{"dtype":"F32","shape":[2],"data_offsets":[0,8]}"#).is_ok());
+        assert!(entry(r#"{"dtype":"F32","shape":[3],"data_offsets":[0,8]}"#).is_err());
+        assert!(entry(r#"{"dtype":"F32","shape":[2],"data_offsets":[8,0]}"#).is_err());
+        assert!(entry(r#"{"dtype":"F32","shape":[2],"data_offsets":[0]}"#).is_err());
+        assert!(entry(r#"{"dtype":"BOOL","shape":[8],"data_offsets":[0,8]}"#).is_err());
+        assert!(entry(r#"{"shape":[2],"data_offsets":[0,8]}"#).is_err());
+    }
+}
@@ -1,4 +1,5 @@
-use ndarray::{s, ArrayD, ArcArray, IxDyn, LinalgScalar};
+use half::{bf16, f16};
+use ndarray::{s, ArrayD, ArcArray, Axis, IxDyn, LinalgScalar};
 use thiserror::Error;
 use serde::{Serialize, Deserialize};
 use crate::DType;
@@ -20,6 +21,8 @@ pub type TensorResult<T> = Result<T, TensorError>;
 /// and zero-copy views, mirroring PyTorch's architecture.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Tensor {
+    Float16(ArcArray<f16, IxDyn>),
+    BFloat16(ArcArray<bf16, IxDyn>),
     Float32(ArcArray<f32, IxDyn>),
     Float64(ArcArray<f64, IxDyn>),
     Int32(ArcArray<i32, IxDyn>),
@@ -30,6 +33,8 @@ pub enum Tensor {
 impl Tensor {
     pub fn dtype(&self) -> DType {
         match self {
+            Tensor::Float16(_) => DType::Float16,
+            Tensor::BFloat16(_) => DType::BFloat16,
             Tensor::Float32(_) => DType::Float32,
             Tensor::Float64(_) => DType::Float64,
             Tensor::Int32(_) => DType::Int32,
@@ -40,6 +45,8 @@ impl Tensor {
 
     pub fn shape(&self) -> &[usize] {
         match self {
+            Tensor::Float16(a) => a.shape(),
+            Tensor::BFloat16(a) => a.shape(),
             Tensor::Float32(a) => a.shape(),
             Tensor::Float64(a) => a.shape(),
             Tensor::Int32(a) => a.shape(),
@@ -60,6 +67,8 @@ impl Tensor {
     pub fn zeros(shape: &[usize], dtype: DType) -> Self {
         let dyn_shape = IxDyn(shape);
         match dtype {
+            DType::Float16 => Tensor::Float16(ArrayD::from_elem(dyn_shape, f16::ZERO).into_shared()),
+            DType::BFloat16 => Tensor::BFloat16(ArrayD::from_elem(dyn_shape, bf16::ZERO).into_shared()),
             DType::Float32 => Tensor::Float32(ArrayD::<f32>::zeros(dyn_shape).into_shared()),
             DType::Float64 => Tensor::Float64(ArrayD::<f64>::zeros(dyn_shape).into_shared()),
             DType::Int32 => Tensor::Int32(ArrayD::<i32>::zeros(dyn_shape).into_shared()),
@@ -72,6 +81,8 @@ impl Tensor {
     pub fn ones(shape: &[usize], dtype: DType) -> Self {
         let dyn_shape = IxDyn(shape);
         match dtype {
+            DType::Float16 => Tensor::Float16(ArrayD::from_elem(dyn_shape, f16::ONE).into_shared()),
+            DType::BFloat16 => Tensor::BFloat16(ArrayD::from_elem(dyn_shape, bf16::ONE).into_shared()),
             DType::Float32 => Tensor::Float32(ArrayD::<f32>::ones(dyn_shape).into_shared()),
             DType::Float64 => Tensor::Float64(ArrayD::<f64>::ones(dyn_shape).into_shared()),
             DType::Int32 => Tensor::Int32(ArrayD::<i32>::ones(dyn_shape).into_shared()),
@@ -82,6 +93,8 @@ impl Tensor {
 
     pub fn into_owned(self) -> Self {
         match self {
+            Tensor::Float16(a) => Tensor::Float16(a.into_owned().into_shared()),
+            Tensor::BFloat16(a) => Tensor::BFloat16(a.into_owned().into_shared()),
             Tensor::Float32(a) => Tensor::Float32(a.into_owned().into_shared()),
             Tensor::Float64(a) => Tensor::Float64(a.into_owned().into_shared()),
             Tensor::Int32(a) => Tensor::Int32(a.into_owned().into_shared()),
@@ -93,6 +106,8 @@ impl Tensor {
     /// Transpose (Zero-copy in ndarray)
     pub fn t(&self) -> Self {
         match self {
+            Tensor::Float16(a) => Tensor::Float16(a.clone().reversed_axes()),
+            Tensor::BFloat16(a) => Tensor::BFloat16(a.clone().reversed_axes()),
             Tensor::Float32(a) => Tensor::Float32(a.clone().reversed_axes()),
             Tensor::Float64(a) => Tensor::Float64(a.clone().reversed_axes()),
             Tensor::Int32(a) => Tensor::Int32(a.clone().reversed_axes()),
@@ -113,6 +128,8 @@ impl Tensor {
         
         let shape_dyn = IxDyn(new_shape);
         match self {
+            Tensor::Float16(a) => Ok(Tensor::Float16(a.clone().into_shape(shape_dyn).unwrap())),
+            Tensor::BFloat16(a) => Ok(Tensor::BFloat16(a.clone().into_shape(shape_dyn).unwrap())),
             Tensor::Float32(a) => Ok(Tensor::Float32(a.clone().into_shape(shape_dyn).unwrap())),
             Tensor::Float64(a) => Ok(Tensor::Float64(a.clone().into_shape(shape_dyn).unwrap())),
             Tensor::Int32(a) => Ok(Tensor::Int32(a.clone().into_shape(shape_dyn).unwrap())),
@@ -132,6 +149,14 @@ impl Tensor {
         }
 
         match self {
+            Tensor::Float16(a) => {
+                let sliced = a.slice(s![start..end, ..]).into_owned().into_dyn().into_shared();
+                Ok(Tensor::Float16(sliced))
+            }
+            Tensor::BFloat16(a) => {
+                let sliced = a.slice(s![start..end, ..]).into_owned().into_dyn().into_shared();
+                Ok(Tensor::BFloat16(sliced))
+            }
             Tensor::Float32(a) => {
                 let sliced = a.slice(s![start..end, ..]).into_owned().into_dyn().into_shared();
                 Ok(Tensor::Float32(sliced))
@@ -154,4 +179,150 @@ impl Tensor {
             }
         }
     }
+    /// Gather rows `indices` along the first dimension, in that order (repeats allowed).
+    pub fn select_rows(&self, indices: &[usize]) -> TensorResult<Self> {
+        let n = *self.shape().first().ok_or_else(|| {
+            TensorError::InvalidOperation("Cannot select rows of a zero-dimensional tensor".into())
+        })?;
+        if let Some(&bad) = indices.iter().find(|&&i| i >= n) {
+            return Err(TensorError::InvalidOperation(format!("Row index {} out of bounds for dimension 0 size {}", bad, n)));
+        }
+        Ok(match self {
+            Tensor::Float16(a) => Tensor::Float16(a.select(Axis(0), indices).into_shared()),
+            Tensor::BFloat16(a) => Tensor::BFloat16(a.select(Axis(0), indices).into_shared()),
+            Tensor::Float32(a) => Tensor::Float32(a.select(Axis(0), indices).into_shared()),
+            Tensor::Float64(a) => Tensor::Float64(a.select(Axis(0), indices).into_shared()),
+            Tensor::Int32(a) => Tensor::Int32(a.select(Axis(0), indices).into_shared()),
+            Tensor::Int64(a) => Tensor::Int64(a.select(Axis(0), indices).into_shared()),
+            Tensor::UInt8(a) => Tensor::UInt8(a.select(Axis(0), indices).into_shared()),
+        })
+    }
+
+    /// Every element widened to f64; exact for every dtype but large Int64s.
+    pub fn to_f64_array(&self) -> ArrayD<f64> {
+        match self {
+            Tensor::Float16(a) => a.mapv(f64::from).into_owned(),
+            Tensor::BFloat16(a) => a.mapv(f64::from).into_owned(),
+            Tensor::Float32(a) => a.mapv(f64::from).into_owned(),
+            Tensor::Float64(a) => a.to_owned(),
+            Tensor::Int32(a) => a.mapv(f64::from).into_owned(),
+            Tensor::Int64(a) => a.mapv(|v| v as f64).into_owned(),
+            Tensor::UInt8(a) => a.mapv(f64::from).into_owned(),
+        }
+    }
+
+    /// Convert to `dtype`. Casts to F16/BF16 round to nearest even; float to
+    /// integer casts truncate toward zero and saturate (NaN becomes 0) and
+    /// integer to integer casts wrap, all as Rust's `as` does.
+    pub fn to_dtype(&self, dtype: DType) -> Tensor {
+        if self.dtype() == dtype {
+            return self.clone();
+        }
+        if self.dtype().is_int() && dtype.is_int() {
+            let a = match self {
+                Tensor::Int32(a) => a.mapv(i64::from),
+                Tensor::Int64(a) => a.to_owned(),
+                Tensor::UInt8(a) => a.mapv(i64::from),
+                _ => unreachable!(),
+            };
+            return match dtype {
+                DType::Int32 => Tensor::Int32(a.mapv(|v| v as i32).into_shared()),
+                DType::Int64 => Tensor::Int64(a.into_shared()),
+                DType::UInt8 => Tensor::UInt8(a.mapv(|v| v as u8).into_shared()),
+                _ => unreachable!(),
+            };
+        }
+        let a = self.to_f64_array();
+        match dtype {
+            DType::Float16 => Tensor::Float16(a.mapv(f16::from_f64).into_shared()),
+            DType::BFloat16 => Tensor::BFloat16(a.mapv(bf16::from_f64).into_shared()),
+            DType::Float32 => Tensor::Float32(a.mapv(|v| v as f32).into_shared()),
+            DType::Float64 => Tensor::Float64(a.into_shared()),
+            DType::Int32 => Tensor::Int32(a.mapv(|v| v as i32).into_shared()),
+            DType::Int64 => Tensor::Int64(a.mapv(|v| v as i64).into_shared()),
+            DType::UInt8 => Tensor::UInt8(a.mapv(|v| v as u8).into_shared()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f64s(values: &[f64]) -> Tensor {
+        Tensor::Float64(ArrayD::from_shape_vec(IxDyn(&[values.len()]), values.to_vec()).unwrap().into_shared())
+    }
+
+    #[test]
+    fn test_half_casts_round_to_nearest() {
+        let t = f64s(&[1.0, 1.0 / 3.0, 65504.0, 1e6, 1e-8]);
+        let h = t.to_dtype(DType::Float16);
+        assert_eq!(h.dtype(), DType::Float16);
+        let back = h.to_f64_array();
+        assert_eq!(back[0], 1.0);
+        assert_eq!(back[1], f16::from_f64(1.0 / 3.0).to_f64());
+        assert!((back[1] - 1.0 / 3.0).abs() < 1e-4);
+        assert_eq!(back[2], 65504.0);
+        // Past f16's range overflows to infinity; below its subnormals flushes to zero.
+        assert_eq!(back[3], f64::INFINITY);
+        assert_eq!(back[4], 0.0);
+
+        // bf16 keeps f32's range but only ~3 significant digits.
+        let b = t.to_dtype(DType::BFloat16).to_f64_array();
+        assert_eq!(b[0], 1.0);
+        assert!((b[1] - 1.0 / 3.0).abs() < 2e-3);
+        assert!((b[3] - 1e6).abs() / 1e6 < 4e-3);
+        assert!(b[4] > 0.0);
+    }
+
+    #[test]
+    fn test_casts_between_dtypes() {
+        let t = f64s(&[-1.7, 2.5, 300.0, f64::NAN]);
+        let same = t.to_dtype(DType::Float64);
+        assert_eq!(same.to_f64_array().iter().take(3).copied().collect::<Vec<_>>(), [-1.7, 2.5, 300.0]);
+        // Float to integer truncates and saturates; NaN becomes zero.
+        match t.to_dtype(DType::UInt8) {
+            Tensor::UInt8(a) => assert_eq!(a.iter().copied().collect::<Vec<_>>(), [0, 2, 255, 0]),
+            other => panic!("{:?}", other.dtype()),
+        }
+        match t.to_dtype(DType::Int32) {
+            Tensor::Int32(a) => assert_eq!(a.iter().copied().collect::<Vec<_>>(), [-1, 2, 300, 0]),
+            other => panic!("{:?}", other.dtype()),
+        }
+        let ints = Tensor::Int64(ArrayD::from_shape_vec(IxDyn(&[2]), vec![3i64, 2049]).unwrap().into_shared());
+        assert_eq!(ints.to_dtype(DType::Float16).to_f64_array().as_slice().unwrap(), [3.0, 2048.0]);
+        match ints.to_dtype(DType::UInt8) {
+            Tensor::UInt8(a) => assert_eq!(a.iter().copied().collect::<Vec<_>>(), [3, 1]),
+            other => panic!("{:?}", other.dtype()),
+        }
+        let h = f64s(&[0.5]).to_dtype(DType::Float16);
+        assert_eq!(h.to_dtype(DType::BFloat16).to_f64_array()[0], 0.5);
+        assert_eq!(h.to_dtype(DType::Float32).dtype(), DType::Float32);
+    }
+
+    #[test]
+    fn test_half_tensors_support_core_ops() {
+        assert!(DType::Float16.is_float() && DType::BFloat16.is_float());
+        assert!(!DType::Float16.is_int());
+        let z = Tensor::zeros(&[2, 3], DType::BFloat16);
+        assert_eq!((z.dtype(), z.shape()), (DType::BFloat16, &[2usize, 3][..]));
+        assert_eq!(Tensor::ones(&[2], DType::Float16).to_f64_array().as_slice().unwrap(), [1.0, 1.0]);
+
+        let a = f64s(&[1.0, -2.0, 3.0, 4.0]).reshape(&[2, 2]).unwrap().to_dtype(DType::Float16);
+        let sum = (&a + &a).unwrap();
+        assert_eq!(sum.dtype(), DType::Float16);
+        assert_eq!(sum.to_f64_array().as_slice().unwrap(), [2.0, -4.0, 6.0, 8.0]);
+        let product = a.matmul(&a).unwrap();
+        assert_eq!(product.dtype(), DType::Float16);
+        assert_eq!(product.to_f64_array().as_slice().unwrap(), [-5.0, -10.0, 15.0, 10.0]);
+        assert_eq!(a.relu().unwrap().to_f64_array().as_slice().unwrap(), [1.0, 0.0, 3.0, 4.0]);
+        assert_eq!(a.scalar_mul(0.5).unwrap().to_f64_array().as_slice().unwrap(), [0.5, -1.0, 1.5, 2.0]);
+        let softmax = a.to_dtype(DType::BFloat16).softmax().unwrap();
+        assert_eq!(softmax.dtype(), DType::BFloat16);
+        assert!((softmax.to_f64_array().sum() - 1.0).abs() < 1e-2);
+
+        let mixed = &a + &a.to_dtype(DType::Float32);
+        assert!(matches!(mixed, Err(TensorError::TypeMismatch { .. })));
+        assert!(a.matmul(&a.to_dtype(DType::BFloat16)).is_err());
+    }
 }
@@ -1,10 +1,20 @@
 use oximl_core::{Tensor, TensorError, TensorResult};
 
+use crate::sampler::{RandomSampler, Sampler, SequentialSampler};
+
 /// A PyTorch-style DataLoader that slices a Dataset into iterable mini-batches.
+///
+/// Samples are visited in the order given by a `Sampler` (sequential by
+/// default), which is asked for a new order at every `reset`. Batches are
+/// `TensorResult`s, so a sampler yielding an out-of-range index surfaces as
+/// an error rather than a panic.
 pub struct DataLoader {
     features: Tensor,
     labels: Tensor,
     batch_size: usize,
+    drop_last: bool,
+    sampler: Box<dyn Sampler>,
+    indices: Vec<usize>,
     current_idx: usize,
     num_samples: usize,
 }
@@ -24,41 +34,178 @@ impl DataLoader {
             )));
         }
 
+        if batch_size == 0 {
+            return Err(TensorError::InvalidOperation("Batch size must be positive".into()));
+        }
+
+        let num_samples = f_shape[0];
         Ok(DataLoader {
-            num_samples: f_shape[0],
+            num_samples,
             features,
             labels,
             batch_size,
+            drop_last: false,
+            sampler: Box::new(SequentialSampler),
+            indices: (0..num_samples).collect(),
             current_idx: 0,
         })
     }
+
+    /// Reshuffle every epoch. A seed makes the sequence of epochs reproducible.
+    pub fn with_shuffle(self, seed: Option<u64>) -> TensorResult<Self> {
+        self.with_sampler(RandomSampler::new(seed))
+    }
+
+    /// Visit samples in the order chosen by `sampler`, which must not be
+    /// tied to a dataset of a different size.
+    pub fn with_sampler<S: Sampler + 'static>(mut self, sampler: S) -> TensorResult<Self> {
+        if let Some(len) = sampler.dataset_len().filter(|&len| len != self.num_samples) {
+            return Err(TensorError::InvalidOperation(format!(
+                "Sampler was built for {} samples but the dataset has {}",
+                len, self.num_samples
+            )));
+        }
+        self.sampler = Box::new(sampler);
+        self.reset()?;
+        Ok(self)
+    }
+
+    /// Skip the final batch when it is smaller than `batch_size`.
+    pub fn with_drop_last(mut self, drop_last: bool) -> Self {
+        self.drop_last = drop_last;
+        self
+    }
     
-    /// Reset the iterator for the next epoch.
-    pub fn reset(&mut self) {
+    /// Reset the iterator for the next epoch, drawing a new sample order.
+    pub fn reset(&mut self) -> TensorResult<()> {
+        self.indices = self.sampler.epoch_indices(self.num_samples)?;
         self.current_idx = 0;
+        Ok(())
     }
     
+    /// Number of batches per epoch.
     pub fn len(&self) -> usize {
-        (self.num_samples as f64 / self.batch_size as f64).ceil() as usize
+        if self.drop_last {
+            self.indices.len() / self.batch_size
+        } else {
+            self.indices.len().div_ceil(self.batch_size)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
 impl Iterator for DataLoader {
-    type Item = (Tensor, Tensor);
+    type Item = TensorResult<(Tensor, Tensor)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_idx >= self.num_samples {
+        let remaining = self.indices.len().saturating_sub(self.current_idx);
+        if remaining == 0 || (self.drop_last && remaining < self.batch_size) {
             return None;
         }
 
-        let end_idx = std::cmp::min(self.current_idx + self.batch_size, self.num_samples);
-        
-        // We know these slices shouldn't fail bounds logic due to constructor validation
-        let batch_x = self.features.slice(self.current_idx, end_idx).unwrap();
-        let batch_y = self.labels.slice(self.current_idx, end_idx).unwrap();
+        let end_idx = self.current_idx + remaining.min(self.batch_size);
+        let batch = &self.indices[self.current_idx..end_idx];
+
+        let batch_x = self.features.select_rows(batch);
+        let batch_y = self.labels.select_rows(batch);
 
         self.current_idx = end_idx;
 
-        Some((batch_x, batch_y))
+        Some(batch_x.and_then(|x| Ok((x, batch_y?))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampler::WeightedRandomSampler;
+    use ndarray::{ArrayD, IxDyn};
+
+    /// Row `i` of the features is `[i, -i]` and its label `i`.
+    fn loader(n: usize, batch_size: usize) -> DataLoader {
+        let features: Vec<f64> = (0..n).flat_map(|i| [i as f64, -(i as f64)]).collect();
+        let features = Tensor::Float64(ArrayD::from_shape_vec(IxDyn(&[n, 2]), features).unwrap().into_shared());
+        let labels: Vec<f64> = (0..n).map(|i| i as f64).collect();
+        let labels = Tensor::Float64(ArrayD::from_shape_vec(IxDyn(&[n, 1]), labels).unwrap().into_shared());
+        DataLoader::new(features, labels, batch_size).unwrap()
+    }
+
+    fn label_order(loader: &mut DataLoader) -> Vec<Vec<usize>> {
+        loader
+            .by_ref()
+            .map(|batch| match batch.unwrap().1 {
+                Tensor::Float64(a) => a.iter().map(|&v| v as usize).collect(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_drop_last_batch_counts() {
+        let mut keep = loader(10, 3);
+        assert_eq!(keep.len(), 4);
+        let sizes: Vec<usize> = label_order(&mut keep).iter().map(|b| b.len()).collect();
+        assert_eq!(sizes, [3, 3, 3, 1]);
+
+        let mut drop = loader(10, 3).with_drop_last(true);
+        assert_eq!(drop.len(), 3);
+        assert_eq!(label_order(&mut drop), [vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8]]);
+
+        // Nothing is dropped when the batches divide evenly.
+        assert_eq!(loader(9, 3).with_drop_last(true).len(), 3);
+        assert!(loader(2, 3).with_drop_last(true).is_empty());
+    }
+
+    #[test]
+    fn test_seeded_shuffle_is_reproducible() {
+        let mut a = loader(12, 5).with_shuffle(Some(42)).unwrap();
+        let mut b = loader(12, 5).with_shuffle(Some(42)).unwrap();
+        let first = label_order(&mut a);
+        assert_eq!(first, label_order(&mut b));
+        let mut seen: Vec<usize> = first.concat();
+        seen.sort();
+        assert_eq!(seen, (0..12).collect::<Vec<_>>());
+        assert_ne!(first.concat(), (0..12).collect::<Vec<_>>());
+
+        a.reset().unwrap();
+        b.reset().unwrap();
+        let second = label_order(&mut a);
+        assert_ne!(first, second);
+        assert_eq!(second, label_order(&mut b));
+    }
+
+    #[test]
+    fn test_batches_follow_the_sampler() {
+        let mut weighted = loader(4, 1000)
+            .with_sampler(WeightedRandomSampler::new(vec![0.0, 1.0, 0.0, 3.0], 4000, true, Some(1)).unwrap())
+            .unwrap();
+        let batches = label_order(&mut weighted);
+        assert_eq!(batches.len(), 4);
+        let drawn = batches.concat();
+        assert!(drawn.iter().all(|&i| i == 1 || i == 3));
+        let share = drawn.iter().filter(|&&i| i == 3).count() as f64 / drawn.len() as f64;
+        assert!((share - 0.75).abs() < 0.03, "{}", share);
+    }
+
+    struct OutOfRange;
+
+    impl Sampler for OutOfRange {
+        fn epoch_indices(&mut self, num_samples: usize) -> TensorResult<Vec<usize>> {
+            Ok(vec![0, num_samples])
+        }
+    }
+
+    #[test]
+    fn test_bad_samplers_are_errors() {
+        let sampler = WeightedRandomSampler::new(vec![1.0; 5], 5, true, None).unwrap();
+        assert!(loader(6, 2).with_sampler(sampler).is_err());
+
+        let mut bad = loader(6, 1).with_sampler(OutOfRange).unwrap();
+        assert!(bad.next().unwrap().is_ok());
+        assert!(bad.next().unwrap().is_err());
+        assert!(bad.next().is_none());
     }
 }
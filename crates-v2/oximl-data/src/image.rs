@@ -0,0 +1,1008 @@
+use oximl_core::{Tensor, TensorError, TensorResult};
+use ndarray::{ArrayD, IxDyn};
+use std::path::Path;
+
+/// A decoded 8-bit-per-channel image (16-bit PNGs are reduced to 8 bits),
+/// row-major with interleaved channels: 1 (gray), 3 (RGB) or 4 (RGBA).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub channels: usize,
+    pub pixels: Vec<u8>,
+}
+
+fn err(msg: impl Into<String>) -> TensorError {
+    TensorError::InvalidOperation(msg.into())
+}
+
+impl Image {
+    /// Decode a PNG or baseline JPEG, detected from its signature.
+    pub fn decode(bytes: &[u8]) -> TensorResult<Image> {
+        if bytes.starts_with(PNG_SIGNATURE) {
+            decode_png(bytes)
+        } else if bytes.starts_with(&[0xff, 0xd8]) {
+            decode_jpeg(bytes)
+        } else {
+            Err(err("Unrecognised image format (expected PNG or JPEG)"))
+        }
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> TensorResult<Image> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| err(format!("File Open Error: {}: {}", path.display(), e)))?;
+        Image::decode(&bytes).map_err(|e| match e {
+            TensorError::InvalidOperation(msg) => err(format!("{}: {}", path.display(), msg)),
+            other => other,
+        })
+    }
+
+    /// Convert to `channels` = 1 (luma), 2 (luma and alpha), 3 (RGB) or 4
+    /// (RGBA). Alpha is dropped for 1 and 3 channels, and opaque when the
+    /// source has none.
+    pub fn to_channels(&self, channels: usize) -> Image {
+        assert!((1..=4).contains(&channels), "to_channels: {} channels, expected 1 to 4", channels);
+        let src = self.channels;
+        let mut pixels = Vec::with_capacity(self.width * self.height * channels);
+        for px in self.pixels.chunks_exact(src) {
+            let (r, g, b) = if src < 3 { (px[0], px[0], px[0]) } else { (px[0], px[1], px[2]) };
+            if channels < 3 {
+                let y = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+                pixels.push(y.round().min(255.0) as u8);
+            } else {
+                pixels.extend([r, g, b]);
+            }
+            if matches!(channels, 2 | 4) {
+                pixels.push(if matches!(src, 2 | 4) { px[src - 1] } else { 255 });
+            }
+        }
+        Image { width: self.width, height: self.height, channels, pixels }
+    }
+
+    /// Bilinear resize to `width × height`, sampling pixel centres.
+    pub fn resize(&self, width: usize, height: usize) -> Image {
+        if width == self.width && height == self.height {
+            return self.clone();
+        }
+        let c = self.channels;
+        if self.width == 0 || self.height == 0 {
+            // Nothing to sample: black (and transparent) at the new size.
+            return Image { width, height, channels: c, pixels: vec![0; width * height * c] };
+        }
+        let sx = self.width as f32 / width as f32;
+        let sy = self.height as f32 / height as f32;
+        let mut pixels = vec![0u8; width * height * c];
+        for y in 0..height {
+            let fy = ((y as f32 + 0.5) * sy - 0.5).clamp(0.0, (self.height - 1) as f32);
+            let (y0, wy) = (fy.floor() as usize, fy - fy.floor());
+            let y1 = (y0 + 1).min(self.height - 1);
+            for x in 0..width {
+                let fx = ((x as f32 + 0.5) * sx - 0.5).clamp(0.0, (self.width - 1) as f32);
+                let (x0, wx) = (fx.floor() as usize, fx - fx.floor());
+                let x1 = (x0 + 1).min(self.width - 1);
+                for ch in 0..c {
+                    let p = |xx: usize, yy: usize| self.pixels[(yy * self.width + xx) * c + ch] as f32;
+                    let top = p(x0, y0) * (1.0 - wx) + p(x1, y0) * wx;
+                    let bottom = p(x0, y1) * (1.0 - wx) + p(x1, y1) * wx;
+                    pixels[(y * width + x) * c + ch] = (top * (1.0 - wy) + bottom * wy).round() as u8;
+                }
+            }
+        }
+        Image { width, height, channels: c, pixels }
+    }
+
+    /// `[C, H, W]` Float32 tensor with values scaled to `[0, 1]`.
+    pub fn to_tensor(&self) -> Tensor {
+        let (w, h, c) = (self.width, self.height, self.channels);
+        let mut data = vec![0f32; c * h * w];
+        for (i, px) in self.pixels.chunks_exact(c).enumerate() {
+            for ch in 0..c {
+                data[ch * h * w + i] = px[ch] as f32 / 255.0;
+            }
+        }
+        Tensor::Float32(ArrayD::from_shape_vec(IxDyn(&[c, h, w]), data).unwrap().into_shared())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// zlib / DEFLATE (RFC 1950 / 1951)
+// ---------------------------------------------------------------------------
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, n: u32) -> TensorResult<u32> {
+        let mut v = 0;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos).ok_or_else(|| err("Truncated DEFLATE stream"))?;
+            v |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(v)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// Canonical Huffman code as (count of codes per length, symbols by code).
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &l in lengths {
+            counts[l as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for i in 1..15 {
+            offsets[i + 1] = offsets[i] + counts[i];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (s, &l) in lengths.iter().enumerate() {
+            if l != 0 {
+                symbols[offsets[l as usize] as usize] = s as u16;
+                offsets[l as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, r: &mut BitReader) -> TensorResult<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= r.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(err("Invalid Huffman code in DEFLATE stream"))
+    }
+}
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097,
+    6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+fn inflate(data: &[u8]) -> TensorResult<Vec<u8>> {
+    let mut r = BitReader { data, pos: 0, bit: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = r.bits(1)? == 1;
+        match r.bits(2)? {
+            0 => {
+                r.align();
+                let header = data.get(r.pos..r.pos + 4).ok_or_else(|| err("Truncated DEFLATE stream"))?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if len != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err(err("Corrupt stored DEFLATE block"));
+                }
+                let block = data.get(r.pos + 4..r.pos + 4 + len).ok_or_else(|| err("Truncated DEFLATE stream"))?;
+                out.extend_from_slice(block);
+                r.pos += 4 + len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                inflate_block(&mut r, &mut out, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let hlit = r.bits(5)? as usize + 257;
+                let hdist = r.bits(5)? as usize + 1;
+                let hclen = r.bits(4)? as usize + 4;
+                const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+                let mut cl = [0u8; 19];
+                for &i in &ORDER[..hclen] {
+                    cl[i] = r.bits(3)? as u8;
+                }
+                let cl = Huffman::new(&cl);
+                let mut lengths = Vec::with_capacity(hlit + hdist);
+                while lengths.len() < hlit + hdist {
+                    let sym = cl.decode(&mut r)?;
+                    let (value, repeat) = match sym {
+                        0..=15 => (sym as u8, 1),
+                        16 => (*lengths.last().ok_or_else(|| err("Corrupt DEFLATE code lengths"))?, 3 + r.bits(2)?),
+                        17 => (0, 3 + r.bits(3)?),
+                        _ => (0, 11 + r.bits(7)?),
+                    };
+                    lengths.extend(std::iter::repeat_n(value, repeat as usize));
+                }
+                if lengths.len() > hlit + hdist {
+                    return Err(err("Corrupt DEFLATE code lengths"));
+                }
+                let lit = Huffman::new(&lengths[..hlit]);
+                let dist = Huffman::new(&lengths[hlit..]);
+                inflate_block(&mut r, &mut out, &lit, &dist)?;
+            }
+            _ => return Err(err("Invalid DEFLATE block type")),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn inflate_block(r: &mut BitReader, out: &mut Vec<u8>, lit: &Huffman, dist: &Huffman) -> TensorResult<()> {
+    loop {
+        let sym = lit.decode(r)? as usize;
+        match sym {
+            0..=255 => out.push(sym as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = sym - 257;
+                if i >= 29 {
+                    return Err(err("Invalid DEFLATE length code"));
+                }
+                let len = LEN_BASE[i] as usize + r.bits(LEN_EXTRA[i] as u32)? as usize;
+                let d = dist.decode(r)? as usize;
+                if d >= 30 {
+                    return Err(err("Invalid DEFLATE distance code"));
+                }
+                let distance = DIST_BASE[d] as usize + r.bits(DIST_EXTRA[d] as u32)? as usize;
+                if distance > out.len() {
+                    return Err(err("DEFLATE distance reaches before the start of the stream"));
+                }
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+        }
+    }
+}
+
+fn zlib_decompress(data: &[u8]) -> TensorResult<Vec<u8>> {
+    if data.len() < 6 || data[0] & 0x0f != 8 || !(((data[0] as u16) << 8) | data[1] as u16).is_multiple_of(31) {
+        return Err(err("Corrupt zlib header"));
+    }
+    if data[1] & 0x20 != 0 {
+        return Err(err("zlib preset dictionaries are not supported"));
+    }
+    let out = inflate(&data[2..])?;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in &out {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    let expected = u32::from_be_bytes(data[data.len() - 4..].try_into().unwrap());
+    if (b << 16) | a != expected {
+        return Err(err("zlib checksum mismatch"));
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// PNG
+// ---------------------------------------------------------------------------
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn decode_png(bytes: &[u8]) -> TensorResult<Image> {
+    let mut pos = PNG_SIGNATURE.len();
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut idat = Vec::new();
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let body = bytes.get(pos + 8..pos + 8 + len).ok_or_else(|| err("Truncated PNG chunk"))?;
+        pos += 12 + len;
+        match kind {
+            b"IHDR" if len == 13 => header = Some(body),
+            b"PLTE" => palette = body,
+            b"tRNS" => transparency = body,
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+    let h = header.ok_or_else(|| err("PNG has no IHDR chunk"))?;
+    let width = u32::from_be_bytes(h[0..4].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(h[4..8].try_into().unwrap()) as usize;
+    let (depth, color, interlace) = (h[8] as usize, h[9], h[12]);
+    let samples = match color {
+        0 => 1,
+        2 => 3,
+        3 => 1,
+        4 => 2,
+        6 => 4,
+        _ => return Err(err(format!("Invalid PNG color type {}", color))),
+    };
+    if ![1, 2, 4, 8, 16].contains(&depth) || width == 0 || height == 0 {
+        return Err(err("Invalid PNG header"));
+    }
+    let raw = zlib_decompress(&idat)?;
+
+    // Unfilter every pass into a grid of raw samples (at the original bit depth).
+    let bits_pp = samples * depth;
+    let bpp = bits_pp.div_ceil(8);
+    let passes: &[(usize, usize, usize, usize)] = if interlace == 1 {
+        &[(0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4), (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2)]
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+    let mut values = vec![0u16; width * height * samples];
+    let mut offset = 0;
+    for &(x0, y0, dx, dy) in passes {
+        if x0 >= width || y0 >= height {
+            continue;
+        }
+        let pw = (width - x0).div_ceil(dx);
+        let ph = (height - y0).div_ceil(dy);
+        let stride = (pw * bits_pp).div_ceil(8);
+        let mut prev = vec![0u8; stride];
+        for row in 0..ph {
+            let line = raw.get(offset..offset + 1 + stride).ok_or_else(|| err("Truncated PNG image data"))?;
+            offset += 1 + stride;
+            let mut cur = line[1..].to_vec();
+            unfilter(line[0], &mut cur, &prev, bpp)?;
+            for col in 0..pw {
+                for s in 0..samples {
+                    let idx = col * samples + s;
+                    let v = match depth {
+                        16 => u16::from_be_bytes([cur[idx * 2], cur[idx * 2 + 1]]),
+                        8 => cur[idx] as u16,
+                        d => {
+                            let bit = idx * d;
+                            ((cur[bit / 8] >> (8 - d - bit % 8)) & ((1 << d) - 1) as u8) as u16
+                        }
+                    };
+                    let (x, y) = (x0 + col * dx, y0 + row * dy);
+                    values[(y * width + x) * samples + s] = v;
+                }
+            }
+            prev = cur;
+        }
+    }
+
+    // Expand palettes and scale samples to 8 bits.
+    let max = ((1u32 << depth) - 1) as f32;
+    let to8 = |v: u16| -> u8 {
+        match depth {
+            8 => v as u8,
+            16 => (v >> 8) as u8,
+            _ => (v as f32 * 255.0 / max).round() as u8,
+        }
+    };
+    let (channels, pixels) = if color == 3 {
+        let has_alpha = !transparency.is_empty();
+        let c = if has_alpha { 4 } else { 3 };
+        let mut px = Vec::with_capacity(width * height * c);
+        for &i in &values {
+            let i = i as usize;
+            let rgb = palette.get(i * 3..i * 3 + 3).ok_or_else(|| err("PNG palette index out of range"))?;
+            px.extend_from_slice(rgb);
+            if has_alpha {
+                px.push(*transparency.get(i).unwrap_or(&255));
+            }
+        }
+        (c, px)
+    } else if samples == 2 {
+        // Gray + alpha: keep as RGBA so alpha stays distinguishable from color.
+        let mut px = Vec::with_capacity(width * height * 4);
+        for ga in values.chunks_exact(2) {
+            let g = to8(ga[0]);
+            px.extend([g, g, g, to8(ga[1])]);
+        }
+        (4, px)
+    } else {
+        (samples, values.into_iter().map(to8).collect())
+    };
+    Ok(Image { width, height, channels, pixels })
+}
+
+fn unfilter(filter: u8, cur: &mut [u8], prev: &[u8], bpp: usize) -> TensorResult<()> {
+    for i in 0..cur.len() {
+        let a = if i >= bpp { cur[i - bpp] as i16 } else { 0 };
+        let b = prev[i] as i16;
+        let c = if i >= bpp { prev[i - bpp] as i16 } else { 0 };
+        let add = match filter {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => (a + b) / 2,
+            4 => {
+                let p = a + b - c;
+                let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+                if pa <= pb && pa <= pc {
+                    a
+                } else if pb <= pc {
+                    b
+                } else {
+                    c
+                }
+            }
+            f => return Err(err(format!("Invalid PNG filter type {}", f))),
+        };
+        cur[i] = cur[i].wrapping_add(add as u8);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// JPEG (baseline and extended sequential Huffman)
+// ---------------------------------------------------------------------------
+
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21,
+    28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61,
+    54, 47, 55, 62, 63,
+];
+
+/// JPEG Huffman table as per-length code ranges (ITU T.81 F.2.2.3).
+#[derive(Clone, Default)]
+struct JpegHuffman {
+    max_code: [i32; 17],
+    val_ptr: [i32; 17],
+    min_code: [i32; 17],
+    values: Vec<u8>,
+}
+
+impl JpegHuffman {
+    fn new(counts: &[u8], values: &[u8]) -> JpegHuffman {
+        let mut t = JpegHuffman { max_code: [-1; 17], values: values.to_vec(), ..Default::default() };
+        let (mut code, mut k) = (0i32, 0i32);
+        for len in 1..=16 {
+            let n = counts[len - 1] as i32;
+            if n > 0 {
+                t.val_ptr[len] = k;
+                t.min_code[len] = code;
+                code += n;
+                k += n;
+                t.max_code[len] = code - 1;
+            }
+            code <<= 1;
+        }
+        t
+    }
+}
+
+/// Entropy-coded segment reader: MSB-first bits with 0xFF00 unstuffing.
+struct JpegBits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u32,
+    n: u32,
+}
+
+impl<'a> JpegBits<'a> {
+    fn bit(&mut self) -> u32 {
+        if self.n == 0 {
+            let mut byte = 0;
+            if self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                if byte == 0xff {
+                    match self.data.get(self.pos + 1) {
+                        Some(0) => self.pos += 2,
+                        // A marker: feed zeros without consuming it.
+                        _ => byte = 0,
+                    }
+                } else {
+                    self.pos += 1;
+                }
+            }
+            self.acc = byte as u32;
+            self.n = 8;
+        }
+        self.n -= 1;
+        (self.acc >> self.n) & 1
+    }
+
+    fn bits(&mut self, n: u32) -> u32 {
+        (0..n).fold(0, |v, _| (v << 1) | self.bit())
+    }
+
+    fn decode(&mut self, t: &JpegHuffman) -> TensorResult<u8> {
+        let mut code = 0i32;
+        for len in 1..=16 {
+            code = (code << 1) | self.bit() as i32;
+            if code <= t.max_code[len] {
+                let i = t.val_ptr[len] + code - t.min_code[len];
+                return t.values.get(i as usize).copied().ok_or_else(|| err("Corrupt JPEG Huffman table"));
+            }
+        }
+        Err(err("Invalid JPEG Huffman code"))
+    }
+
+    /// Receive `s` bits and sign-extend them (T.81 F.2.2.1).
+    fn extend(&mut self, s: u8) -> i32 {
+        if s == 0 {
+            return 0;
+        }
+        let v = self.bits(s as u32) as i32;
+        if v < 1 << (s - 1) {
+            v - (1 << s) + 1
+        } else {
+            v
+        }
+    }
+
+    /// Skip to the next RSTn marker after a restart interval.
+    fn restart(&mut self) {
+        self.n = 0;
+        while self.pos + 1 < self.data.len() {
+            if self.data[self.pos] == 0xff && (0xd0..=0xd7).contains(&self.data[self.pos + 1]) {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    tq: usize,
+    /// Blocks per line and per column, padded to whole MCUs.
+    bw: usize,
+    bh: usize,
+    plane: Vec<u8>,
+    dc_pred: i32,
+}
+
+fn idct_block(coef: &[i32; 64], q: &[u16; 64], out: &mut [u8], stride: usize) {
+    use std::f32::consts::PI;
+    let mut cos = [[0f32; 8]; 8];
+    for (x, row) in cos.iter_mut().enumerate() {
+        for (u, c) in row.iter_mut().enumerate() {
+            let cu = if u == 0 { std::f32::consts::FRAC_1_SQRT_2 } else { 1.0 };
+            *c = cu * (((2 * x + 1) as f32 * u as f32 * PI) / 16.0).cos();
+        }
+    }
+    let mut f = [0f32; 64];
+    for i in 0..64 {
+        f[i] = (coef[i] * q[i] as i32) as f32;
+    }
+    // Rows then columns of the separable 2-D inverse DCT.
+    let mut tmp = [0f32; 64];
+    for y in 0..8 {
+        for x in 0..8 {
+            tmp[y * 8 + x] = (0..8).map(|u| cos[x][u] * f[y * 8 + u]).sum::<f32>() / 2.0;
+        }
+    }
+    for x in 0..8 {
+        for y in 0..8 {
+            let v = (0..8).map(|v| cos[y][v] * tmp[v * 8 + x]).sum::<f32>() / 2.0;
+            out[y * stride + x] = (v + 128.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+fn decode_jpeg(bytes: &[u8]) -> TensorResult<Image> {
+    let mut qt = [[0u16; 64]; 4];
+    let mut dc_tables: [JpegHuffman; 4] = Default::default();
+    let mut ac_tables: [JpegHuffman; 4] = Default::default();
+    let mut comps: Vec<Component> = Vec::new();
+    let (mut width, mut height) = (0usize, 0usize);
+    let (mut hmax, mut vmax) = (1usize, 1usize);
+    let mut restart_interval = 0usize;
+    let mut adobe_transform: Option<u8> = None;
+    let mut pos = 2;
+
+    let seg = |pos: usize| -> TensorResult<&[u8]> {
+        let len = bytes.get(pos..pos + 2).ok_or_else(|| err("Truncated JPEG"))?;
+        let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+        bytes.get(pos + 2..pos + len).ok_or_else(|| err("Truncated JPEG segment"))
+    };
+
+    loop {
+        while bytes.get(pos) == Some(&0xff) && bytes.get(pos + 1) == Some(&0xff) {
+            pos += 1;
+        }
+        let marker = match bytes.get(pos..pos + 2) {
+            Some([0xff, m]) => *m,
+            _ => return Err(err("Corrupt JPEG: expected a marker")),
+        };
+        pos += 2;
+        match marker {
+            0xd9 => break,
+            0xd0..=0xd7 | 0x01 => continue,
+            _ => {}
+        }
+        let body = seg(pos)?;
+        let seg_end = pos + 2 + body.len();
+        match marker {
+            0xc0 | 0xc1 => {
+                if body[0] != 8 {
+                    return Err(err("Only 8-bit JPEGs are supported"));
+                }
+                height = u16::from_be_bytes([body[1], body[2]]) as usize;
+                width = u16::from_be_bytes([body[3], body[4]]) as usize;
+                let n = body[5] as usize;
+                if n != 1 && n != 3 {
+                    return Err(err(format!("JPEGs with {} components are not supported", n)));
+                }
+                for c in body[6..6 + 3 * n].chunks_exact(3) {
+                    comps.push(Component {
+                        id: c[0],
+                        h: (c[1] >> 4).max(1) as usize,
+                        v: (c[1] & 15).max(1) as usize,
+                        tq: (c[2] & 3) as usize,
+                        bw: 0,
+                        bh: 0,
+                        plane: Vec::new(),
+                        dc_pred: 0,
+                    });
+                }
+                hmax = comps.iter().map(|c| c.h).max().unwrap();
+                vmax = comps.iter().map(|c| c.v).max().unwrap();
+                let (mcux, mcuy) = (width.div_ceil(8 * hmax), height.div_ceil(8 * vmax));
+                for c in comps.iter_mut() {
+                    c.bw = mcux * c.h;
+                    c.bh = mcuy * c.v;
+                    c.plane = vec![0; c.bw * 8 * c.bh * 8];
+                }
+            }
+            0xc2 | 0xc3 | 0xc5..=0xc7 | 0xc9..=0xcb | 0xcd..=0xcf => {
+                return Err(err("Progressive, lossless and arithmetic-coded JPEGs are not supported"));
+            }
+            0xdb => {
+                let mut p = 0;
+                while p < body.len() {
+                    let (precision, id) = (body[p] >> 4, (body[p] & 3) as usize);
+                    p += 1;
+                    for &z in ZIGZAG.iter() {
+                        qt[id][z] = if precision == 0 {
+                            p += 1;
+                            body[p - 1] as u16
+                        } else {
+                            p += 2;
+                            u16::from_be_bytes([body[p - 2], body[p - 1]])
+                        };
+                    }
+                }
+            }
+            0xc4 => {
+                let mut p = 0;
+                while p + 17 <= body.len() {
+                    let (class, id) = (body[p] >> 4, (body[p] & 3) as usize);
+                    let counts = &body[p + 1..p + 17];
+                    let total: usize = counts.iter().map(|&c| c as usize).sum();
+                    let values = body.get(p + 17..p + 17 + total).ok_or_else(|| err("Truncated JPEG Huffman table"))?;
+                    let table = JpegHuffman::new(counts, values);
+                    if class == 0 {
+                        dc_tables[id] = table;
+                    } else {
+                        ac_tables[id] = table;
+                    }
+                    p += 17 + total;
+                }
+            }
+            0xdd => restart_interval = u16::from_be_bytes([body[0], body[1]]) as usize,
+            0xee if body.starts_with(b"Adobe") && body.len() >= 12 => adobe_transform = Some(body[11]),
+            0xda => {
+                if comps.is_empty() {
+                    return Err(err("JPEG scan before frame header"));
+                }
+                let ns = body[0] as usize;
+                let mut scan = Vec::with_capacity(ns);
+                for s in body[1..1 + 2 * ns].chunks_exact(2) {
+                    let ci = comps.iter().position(|c| c.id == s[0]).ok_or_else(|| err("JPEG scan references an unknown component"))?;
+                    scan.push((ci, (s[1] >> 4) as usize & 3, (s[1] & 15) as usize & 3));
+                }
+                comps.iter_mut().for_each(|c| c.dc_pred = 0);
+                let mut bits = JpegBits { data: bytes, pos: seg_end, acc: 0, n: 0 };
+
+                // Interleaved scans code whole MCUs; a single-component scan
+                // codes that component's blocks covering the image only.
+                let (units_x, units_y) = if ns == 1 {
+                    let c = &comps[scan[0].0];
+                    ((width * c.h).div_ceil(hmax).div_ceil(8), (height * c.v).div_ceil(vmax).div_ceil(8))
+                } else {
+                    (width.div_ceil(8 * hmax), height.div_ceil(8 * vmax))
+                };
+                let mut coef = [0i32; 64];
+                for unit in 0..units_x * units_y {
+                    if restart_interval > 0 && unit > 0 && unit % restart_interval == 0 {
+                        bits.restart();
+                        comps.iter_mut().for_each(|c| c.dc_pred = 0);
+                    }
+                    let (ux, uy) = (unit % units_x, unit / units_x);
+                    for &(ci, td, ta) in &scan {
+                        let c = &mut comps[ci];
+                        let blocks: Vec<(usize, usize)> = if ns == 1 {
+                            vec![(ux, uy)]
+                        } else {
+                            let (h, v) = (c.h, c.v);
+                            (0..v).flat_map(|by| (0..h).map(move |bx| (ux * h + bx, uy * v + by))).collect()
+                        };
+                        for (bx, by) in blocks {
+                            coef.fill(0);
+                            let s = bits.decode(&dc_tables[td])?;
+                            c.dc_pred += bits.extend(s);
+                            coef[0] = c.dc_pred;
+                            let mut k = 1;
+                            while k < 64 {
+                                let rs = bits.decode(&ac_tables[ta])?;
+                                let (r, s) = ((rs >> 4) as usize, rs & 15);
+                                if s == 0 {
+                                    if r != 15 {
+                                        break;
+                                    }
+                                    k += 16;
+                                    continue;
+                                }
+                                k += r;
+                                if k > 63 {
+                                    return Err(err("Corrupt JPEG block"));
+                                }
+                                coef[ZIGZAG[k]] = bits.extend(s);
+                                k += 1;
+                            }
+                            let stride = c.bw * 8;
+                            let start = by * 8 * stride + bx * 8;
+                            idct_block(&coef, &qt[c.tq], &mut c.plane[start..], stride);
+                        }
+                    }
+                }
+                // Resume marker parsing after the entropy-coded data.
+                pos = bits.pos;
+                while pos + 1 < bytes.len() && !(bytes[pos] == 0xff && bytes[pos + 1] != 0 && !(0xd0..=0xd7).contains(&bytes[pos + 1])) {
+                    pos += 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        pos = seg_end;
+    }
+
+    if comps.is_empty() || width == 0 || height == 0 {
+        return Err(err("JPEG has no image data"));
+    }
+    let channels = comps.len();
+    let ycc = channels == 3 && adobe_transform != Some(0);
+    // Subsampled components are upsampled bilinearly between sample
+    // centres, like libjpeg's "fancy" upsampling.
+    let coord = |p: usize, factor: usize, max: usize, size: usize| -> (usize, usize, f32) {
+        let n = (size * factor).div_ceil(max);
+        let f = ((p as f32 + 0.5) * factor as f32 / max as f32 - 0.5).clamp(0.0, (n - 1) as f32);
+        let i = f.floor() as usize;
+        (i, (i + 1).min(n - 1), f - i as f32)
+    };
+    let mut pixels = Vec::with_capacity(width * height * channels);
+    for y in 0..height {
+        for x in 0..width {
+            let sample = |c: &Component| {
+                let stride = c.bw * 8;
+                if c.h == hmax && c.v == vmax {
+                    return c.plane[y * stride + x] as f32;
+                }
+                let (x0, x1, wx) = coord(x, c.h, hmax, width);
+                let (y0, y1, wy) = coord(y, c.v, vmax, height);
+                let p = |xx: usize, yy: usize| c.plane[yy * stride + xx] as f32;
+                let top = p(x0, y0) * (1.0 - wx) + p(x1, y0) * wx;
+                let bottom = p(x0, y1) * (1.0 - wx) + p(x1, y1) * wx;
+                top * (1.0 - wy) + bottom * wy
+            };
+            if channels == 1 {
+                pixels.push(sample(&comps[0]).round() as u8);
+            } else if ycc {
+                let (yy, cb, cr) = (sample(&comps[0]), sample(&comps[1]) - 128.0, sample(&comps[2]) - 128.0);
+                pixels.push((yy + 1.402 * cr).round().clamp(0.0, 255.0) as u8);
+                pixels.push((yy - 0.344136 * cb - 0.714136 * cr).round().clamp(0.0, 255.0) as u8);
+                pixels.push((yy + 1.772 * cb).round().clamp(0.0, 255.0) as u8);
+            } else {
+                pixels.extend(comps.iter().map(|c| sample(c).round() as u8));
+            }
+        }
+    }
+    Ok(Image { width, height, channels, pixels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sample `s` of pixel `(x, y)` in the PNG fixtures (`tests/fixtures/make_png.py`).
+    fn pattern(x: usize, y: usize, s: usize) -> u8 {
+        ((x * 17 + y * 29 + s * 71 + x * y * 3) % 256) as u8
+    }
+
+    fn check(name: &str, bytes: &[u8], channels: usize, expected: impl Fn(usize, usize, usize) -> u8) {
+        let img = Image::decode(bytes).unwrap_or_else(|e| panic!("{}: {:?}", name, e));
+        assert_eq!((img.width, img.height, img.channels), (13, 11, channels), "{}", name);
+        for y in 0..11 {
+            for x in 0..13 {
+                for s in 0..channels {
+                    let got = img.pixels[(y * 13 + x) * channels + s];
+                    assert_eq!(got, expected(x, y, s), "{} at ({}, {}) sample {}", name, x, y, s);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_png_color_types_and_depths() {
+        // Low bit depths are stored as pattern * max / 255 and scaled back up.
+        let reduced = |depth: u32| {
+            move |x, y, s| {
+                let max = (1u32 << depth) - 1;
+                let v = pattern(x, y, s) as u32 * max / 255;
+                (v as f32 * 255.0 / max as f32).round() as u8
+            }
+        };
+        check("gray8", include_bytes!("../tests/fixtures/gray8.png"), 1, pattern);
+        check("gray4", include_bytes!("../tests/fixtures/gray4.png"), 1, reduced(4));
+        check("rgb8", include_bytes!("../tests/fixtures/rgb8.png"), 3, pattern);
+        check("rgb16", include_bytes!("../tests/fixtures/rgb16.png"), 3, pattern);
+        check("rgba8", include_bytes!("../tests/fixtures/rgba8.png"), 4, pattern);
+        // Gray + alpha expands to RGBA.
+        check("gray_alpha8", include_bytes!("../tests/fixtures/gray_alpha8.png"), 4, |x, y, s| {
+            pattern(x, y, if s == 3 { 1 } else { 0 })
+        });
+        // 4-bit palette indices (x + 2y) % 16 with tRNS alpha for the first five entries.
+        check("palette4", include_bytes!("../tests/fixtures/palette4.png"), 4, |x, y, s| {
+            let i = (x + 2 * y) % 16;
+            match s {
+                3 if i < 5 => 40 * i as u8,
+                3 => 255,
+                _ => pattern(i, 0, s),
+            }
+        });
+    }
+
+    #[test]
+    fn test_png_interlaced() {
+        check("rgb8_interlaced", include_bytes!("../tests/fixtures/rgb8_interlaced.png"), 3, pattern);
+        check("gray2_interlaced", include_bytes!("../tests/fixtures/gray2_interlaced.png"), 1, |x, y, _| {
+            (pattern(x, y, 0) as u32 * 3 / 255 * 85) as u8
+        });
+    }
+
+    #[test]
+    fn test_png_filter_types() {
+        // The fixtures cycle through all five filters; make sure that held.
+        let bytes = include_bytes!("../tests/fixtures/rgb8.png");
+        let mut idat = Vec::new();
+        let mut pos = PNG_SIGNATURE.len();
+        while pos < bytes.len() {
+            let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+            if &bytes[pos + 4..pos + 8] == b"IDAT" {
+                idat.extend_from_slice(&bytes[pos + 8..pos + 8 + len]);
+            }
+            pos += 12 + len;
+        }
+        let raw = zlib_decompress(&idat).unwrap();
+        let stride = 1 + 13 * 3;
+        let filters: Vec<u8> = raw.chunks(stride).map(|row| row[0]).collect();
+        assert_eq!(filters, vec![0, 1, 2, 3, 4, 0, 1, 2, 3, 4, 0]);
+
+        let mut cur = [10, 20, 30, 40];
+        assert!(unfilter(5, &mut cur, &[0; 4], 1).is_err());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let png = include_bytes!("../tests/fixtures/rgb8.png");
+        assert!(Image::decode(b"GIF89a").is_err());
+        assert!(Image::decode(&png[..60]).is_err());
+        let jpeg = include_bytes!("../tests/fixtures/rgb444.jpg");
+        assert!(Image::decode(&jpeg[..100]).is_err());
+    }
+
+    /// Mean and largest absolute error of a decoded JPEG fixture against
+    /// the gradient it was encoded from (`tests/fixtures/MakeJpeg.java`).
+    fn jpeg_error(img: &Image) -> (f32, u8) {
+        let (mut sum, mut max) = (0.0, 0);
+        for y in 0..17 {
+            for x in 0..21 {
+                let rgb = [40 + 8 * x, 30 + 9 * y, 200 - 4 * x - 3 * y];
+                let want: Vec<u8> = if img.channels == 1 { vec![rgb[1] as u8] } else { rgb.map(|v| v as u8).to_vec() };
+                for (s, &w) in want.iter().enumerate() {
+                    let e = img.pixels[(y * 21 + x) * img.channels + s].abs_diff(w);
+                    sum += e as f32;
+                    max = max.max(e);
+                }
+            }
+        }
+        (sum / (21 * 17 * img.channels) as f32, max)
+    }
+
+    #[test]
+    fn test_jpeg_subsampling() {
+        // Bounds sit just above the JDK decoder's own error on these files
+        // (mean 1.17 / max 8 for 4:2:0, 0.68 / 3 for 4:4:4, exact for gray).
+        for (name, bytes, channels, mean_bound, max_bound) in [
+            ("rgb420", &include_bytes!("../tests/fixtures/rgb420.jpg")[..], 3, 1.5, 9),
+            ("rgb444", &include_bytes!("../tests/fixtures/rgb444.jpg")[..], 3, 1.0, 4),
+            ("gray", &include_bytes!("../tests/fixtures/gray.jpg")[..], 1, 0.5, 1),
+        ] {
+            let img = Image::decode(bytes).unwrap();
+            assert_eq!((img.width, img.height, img.channels), (21, 17, channels), "{}", name);
+            let (mean, max) = jpeg_error(&img);
+            assert!(mean < mean_bound && max <= max_bound, "{}: mean error {}, max {}", name, mean, max);
+        }
+    }
+
+    fn image(width: usize, height: usize, channels: usize, pixels: Vec<u8>) -> Image {
+        Image { width, height, channels, pixels }
+    }
+
+    #[test]
+    fn test_to_channels() {
+        let rgba = image(2, 1, 4, vec![255, 0, 0, 128, 10, 20, 30, 255]);
+        assert_eq!(rgba.to_channels(3).pixels, vec![255, 0, 0, 10, 20, 30]);
+        assert_eq!(rgba.to_channels(1).pixels, vec![76, 18]);
+        assert_eq!(rgba.to_channels(2).pixels, vec![76, 128, 18, 255]);
+        assert_eq!(rgba.to_channels(4), rgba);
+
+        let gray = image(2, 1, 1, vec![7, 200]);
+        assert_eq!(gray.to_channels(3).pixels, vec![7, 7, 7, 200, 200, 200]);
+        assert_eq!(gray.to_channels(4).pixels, vec![7, 7, 7, 255, 200, 200, 200, 255]);
+        assert_eq!(gray.to_channels(3).to_channels(1), gray);
+
+        let empty = image(0, 3, 3, vec![]).to_channels(4);
+        assert_eq!((empty.width, empty.height, empty.channels, empty.pixels.len()), (0, 3, 4, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 1 to 4")]
+    fn test_to_channels_rejects_five() {
+        image(1, 1, 1, vec![0]).to_channels(5);
+    }
+
+    #[test]
+    fn test_resize() {
+        let img = image(2, 2, 1, vec![0, 100, 200, 40]);
+        assert_eq!(img.resize(2, 2), img);
+        // Upsampling by two: edge pixels clamp, inner ones interpolate.
+        let up = img.resize(4, 4);
+        assert_eq!(up.pixels[..4], [0, 25, 75, 100]);
+        assert_eq!(up.pixels[12..], [200, 160, 80, 40]);
+        // Downsampling samples the centre of each 2x2 block.
+        let big = image(4, 2, 1, vec![0, 40, 80, 120, 0, 40, 80, 120]);
+        assert_eq!(big.resize(2, 1).pixels, vec![20, 100]);
+
+        let rgba = image(1, 1, 4, vec![1, 2, 3, 4]);
+        let r = rgba.resize(3, 2);
+        assert_eq!((r.channels, r.pixels.len()), (4, 24));
+        assert!(r.pixels.chunks(4).all(|p| p == [1, 2, 3, 4]));
+
+        let none = img.resize(0, 5);
+        assert_eq!((none.width, none.height, none.pixels.len()), (0, 5, 0));
+        let from_empty = image(0, 0, 3, vec![]).resize(2, 1);
+        assert_eq!(from_empty.pixels, vec![0; 6]);
+    }
+
+    #[test]
+    fn test_to_tensor_is_chw() {
+        let t = image(2, 1, 3, vec![0, 51, 255, 255, 0, 51]).to_tensor();
+        assert_eq!(t.shape(), &[3, 1, 2]);
+        if let Tensor::Float32(a) = t {
+            assert_eq!(a.iter().copied().collect::<Vec<_>>(), vec![0.0, 1.0, 0.2, 0.0, 1.0, 0.2]);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use oximl_core::{Tensor, TensorError, TensorResult};
+use ndarray::{ArrayD, IxDyn};
+use std::path::{Path, PathBuf};
+
+use crate::image::Image;
+
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Decode a PNG or JPEG file into a `[C, H, W]` Float32 tensor in `[0, 1]`
+/// (1 channel for grayscale images, 3 otherwise; alpha is dropped).
+pub fn load_image<P: AsRef<Path>>(path: P) -> TensorResult<Tensor> {
+    let img = Image::open(path)?;
+    let channels = if img.channels < 3 { 1 } else { 3 };
+    Ok(img.to_channels(channels).to_tensor())
+}
+
+/// A torchvision-style image dataset laid out one directory per class:
+///
+/// ```text
+/// root/cat/001.png
+/// root/cat/002.jpg
+/// root/dog/001.jpeg
+/// ```
+///
+/// Classes are the sorted subdirectory names and label `i` is `classes()[i]`.
+/// Images are decoded on access into `[C, H, W]` Float32 tensors with values
+/// in `[0, 1]`, then optionally normalized per channel.
+pub struct ImageFolderDataset {
+    classes: Vec<String>,
+    samples: Vec<(PathBuf, usize)>,
+    resize: Option<(usize, usize)>,
+    grayscale: bool,
+    normalize: Option<(Vec<f32>, Vec<f32>)>,
+}
+
+impl ImageFolderDataset {
+    pub fn new<P: AsRef<Path>>(root: P) -> TensorResult<Self> {
+        let root = root.as_ref();
+        let io_err = |e: std::io::Error| TensorError::InvalidOperation(format!("{}: {}", root.display(), e));
+
+        let mut class_dirs: Vec<PathBuf> = std::fs::read_dir(root)
+            .map_err(io_err)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_dir())
+            .collect();
+        class_dirs.sort();
+
+        let mut classes = Vec::with_capacity(class_dirs.len());
+        let mut samples = Vec::new();
+        for (label, dir) in class_dirs.iter().enumerate() {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+                .map_err(io_err)?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.is_file()
+                        && p.extension()
+                            .and_then(|e| e.to_str())
+                            .is_some_and(|e| EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+                })
+                .collect();
+            files.sort();
+            classes.push(dir.file_name().unwrap().to_string_lossy().into_owned());
+            samples.extend(files.into_iter().map(|f| (f, label)));
+        }
+
+        if samples.is_empty() {
+            return Err(TensorError::InvalidOperation(format!(
+                "No PNG or JPEG images found under class directories of {}",
+                root.display()
+            )));
+        }
+        Ok(ImageFolderDataset { classes, samples, resize: None, grayscale: false, normalize: None })
+    }
+
+    /// Resize every image to `height × width` with bilinear interpolation.
+    pub fn with_resize(mut self, height: usize, width: usize) -> Self {
+        self.resize = Some((height, width));
+        self
+    }
+
+    /// Convert to a single luma channel instead of RGB.
+    pub fn with_grayscale(mut self, grayscale: bool) -> Self {
+        self.grayscale = grayscale;
+        self
+    }
+
+    /// `(x - mean) / std` per channel, applied after scaling to `[0, 1]`.
+    pub fn with_normalize(mut self, mean: Vec<f32>, std: Vec<f32>) -> Self {
+        self.normalize = Some((mean, std));
+        self
+    }
+
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    /// Image paths and their labels, in dataset order.
+    pub fn samples(&self) -> &[(PathBuf, usize)] {
+        &self.samples
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Decode image `index`, returning it with its class label.
+    pub fn get(&self, index: usize) -> TensorResult<(Tensor, usize)> {
+        let (path, label) = self.samples.get(index).ok_or_else(|| {
+            TensorError::InvalidOperation(format!("Index {} out of bounds for {} images", index, self.samples.len()))
+        })?;
+        let mut img = Image::open(path)?.to_channels(if self.grayscale { 1 } else { 3 });
+        if let Some((h, w)) = self.resize {
+            img = img.resize(w, h);
+        }
+        let mut tensor = img.to_tensor();
+        if let Some((mean, std)) = &self.normalize {
+            if mean.len() != img.channels || std.len() != img.channels {
+                return Err(TensorError::InvalidOperation(format!(
+                    "Normalize has {} means and {} stds for {} channels",
+                    mean.len(),
+                    std.len(),
+                    img.channels
+                )));
+            }
+            let plane = img.width * img.height;
+            if let Tensor::Float32(a) = &mut tensor {
+                for (i, v) in a.iter_mut().enumerate() {
+                    *v = (*v - mean[i / plane]) / std[i / plane];
+                }
+            }
+        }
+        Ok((tensor, *label))
+    }
+
+    /// Decode every image into `([N, C, H, W] images, [N, num_classes] one-hot labels)`,
+    /// both Float32, ready for `DataLoader::new` and `CrossEntropyLoss`. Images
+    /// must all have the same size unless `with_resize` is set.
+    pub fn to_tensors(&self) -> TensorResult<(Tensor, Tensor)> {
+        let n = self.samples.len();
+        let k = self.classes.len();
+        let mut images = Vec::new();
+        let mut shape: Option<Vec<usize>> = None;
+        let mut labels = vec![0f32; n * k];
+        for i in 0..n {
+            let (img, label) = self.get(i)?;
+            let dims = img.shape().to_vec();
+            match &shape {
+                None => shape = Some(dims),
+                Some(s) if *s != dims => {
+                    return Err(TensorError::InvalidOperation(format!(
+                        "{} has shape {:?} but earlier images have {:?}; use with_resize to batch mixed sizes",
+                        self.samples[i].0.display(),
+                        dims,
+                        s
+                    )));
+                }
+                _ => {}
+            }
+            if let Tensor::Float32(a) = img {
+                images.extend(a.iter().copied());
+            }
+            labels[i * k + label] = 1.0;
+        }
+        let mut dims = vec![n];
+        dims.extend(shape.unwrap());
+        let images = ArrayD::from_shape_vec(IxDyn(&dims), images)
+            .map_err(|e| TensorError::InvalidOperation(e.to_string()))?;
+        let labels = ArrayD::from_shape_vec(IxDyn(&[n, k]), labels)
+            .map_err(|e| TensorError::InvalidOperation(e.to_string()))?;
+        Ok((Tensor::Float32(images.into_shared()), Tensor::Float32(labels.into_shared())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RGB: &[u8] = include_bytes!("../tests/fixtures/rgb8.png");
+    const GRAY: &[u8] = include_bytes!("../tests/fixtures/gray8.png");
+    const JPEG: &[u8] = include_bytes!("../tests/fixtures/rgb444.jpg");
+
+    /// `root/{dog,cat,bird,empty}` with images written in unsorted order.
+    fn folder(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, bytes) in [
+            ("dog/b.png", RGB),
+            ("dog/a.JPG", JPEG),
+            ("cat/z.png", GRAY),
+            ("cat/notes.txt", &b"not an image"[..]),
+            ("bird/c.jpeg", JPEG),
+            ("bird/a.png", RGB),
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, bytes).unwrap();
+        }
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_classes_and_labels_are_sorted() {
+        let root = folder("oximl_image_folder_order");
+        let ds = ImageFolderDataset::new(&root).unwrap();
+        // An empty class directory still takes a label.
+        assert_eq!(ds.classes(), &["bird", "cat", "dog", "empty"]);
+        let samples: Vec<(String, usize)> = ds
+            .samples()
+            .iter()
+            .map(|(p, label)| (p.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/"), *label))
+            .collect();
+        let expected = [("bird/a.png", 0), ("bird/c.jpeg", 0), ("cat/z.png", 1), ("dog/a.JPG", 2), ("dog/b.png", 2)];
+        assert_eq!(samples, expected.map(|(p, l)| (p.to_string(), l)));
+        assert_eq!(ds.len(), 5);
+
+        let (img, label) = ds.get(2).unwrap();
+        assert_eq!((img.shape(), label), (&[3, 11, 13][..], 1));
+        assert!(ds.get(5).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_transforms_and_batching() {
+        let root = folder("oximl_image_folder_batch");
+        let ds = ImageFolderDataset::new(&root).unwrap();
+        // 13x11 PNGs and 21x17 JPEGs cannot be stacked without resizing.
+        assert!(ds.to_tensors().is_err());
+
+        let ds = ds.with_resize(8, 6).with_grayscale(true).with_normalize(vec![0.5], vec![0.5]);
+        let (x, y) = ds.to_tensors().unwrap();
+        assert_eq!(x.shape(), &[5, 1, 8, 6]);
+        assert_eq!(y.shape(), &[5, 4]);
+        if let (Tensor::Float32(x), Tensor::Float32(y)) = (&x, &y) {
+            assert!(x.iter().all(|v| (-1.0..=1.0).contains(v)));
+            assert_eq!(y.iter().skip(12).take(4).copied().collect::<Vec<_>>(), vec![0.0, 0.0, 1.0, 0.0]);
+        }
+
+        let bad = ImageFolderDataset::new(&root).unwrap().with_normalize(vec![0.5], vec![0.5]);
+        assert!(bad.get(0).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(ImageFolderDataset::new(&root).is_err());
+    }
+}
@@ -1,7 +1,13 @@
 pub mod polars_ext;
 pub mod onnx;
 pub mod dataloader;
+pub mod sampler;
+pub mod image;
+pub mod image_folder;
 
 pub use polars_ext::*;
 pub use onnx::*;
 pub use dataloader::*;
+pub use sampler::*;
+pub use image::*;
+pub use image_folder::*;
@@ -0,0 +1,229 @@
+use oximl_core::{Tensor, TensorError, TensorResult};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// Decides which samples a `DataLoader` visits in an epoch, and in what order.
+/// Called once per epoch, so random samplers give a fresh order every time.
+pub trait Sampler {
+    /// Row indices to visit this epoch, for a dataset of `num_samples` rows.
+    fn epoch_indices(&mut self, num_samples: usize) -> TensorResult<Vec<usize>>;
+
+    /// The dataset size the sampler was built for, if it is tied to one
+    /// (one weight per sample, say). `DataLoader::with_sampler` checks it.
+    fn dataset_len(&self) -> Option<usize> {
+        None
+    }
+}
+
+fn make_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Visits every sample once, in order.
+pub struct SequentialSampler;
+
+impl Sampler for SequentialSampler {
+    fn epoch_indices(&mut self, num_samples: usize) -> TensorResult<Vec<usize>> {
+        Ok((0..num_samples).collect())
+    }
+}
+
+/// Visits every sample once, in a new random order each epoch.
+pub struct RandomSampler {
+    rng: StdRng,
+}
+
+impl RandomSampler {
+    /// With a seed the sequence of epoch orders is reproducible.
+    pub fn new(seed: Option<u64>) -> Self {
+        RandomSampler { rng: make_rng(seed) }
+    }
+}
+
+impl Sampler for RandomSampler {
+    fn epoch_indices(&mut self, num_samples: usize) -> TensorResult<Vec<usize>> {
+        let mut indices: Vec<usize> = (0..num_samples).collect();
+        indices.shuffle(&mut self.rng);
+        Ok(indices)
+    }
+}
+
+/// Draws `num_samples` indices per epoch with probability proportional to
+/// `weights[i]`, mirroring PyTorch's `WeightedRandomSampler`.
+pub struct WeightedRandomSampler {
+    weights: Vec<f64>,
+    num_samples: usize,
+    replacement: bool,
+    rng: StdRng,
+}
+
+impl WeightedRandomSampler {
+    pub fn new(weights: Vec<f64>, num_samples: usize, replacement: bool, seed: Option<u64>) -> TensorResult<Self> {
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(TensorError::InvalidOperation("Sampling weights must be finite and non-negative".into()));
+        }
+        let positive = weights.iter().filter(|&&w| w > 0.0).count();
+        if positive == 0 {
+            return Err(TensorError::InvalidOperation("At least one sampling weight must be positive".into()));
+        }
+        if !replacement && num_samples > positive {
+            return Err(TensorError::InvalidOperation(format!(
+                "Cannot draw {} samples without replacement from {} with positive weight",
+                num_samples, positive
+            )));
+        }
+        Ok(WeightedRandomSampler { weights, num_samples, replacement, rng: make_rng(seed) })
+    }
+
+    /// Class-balanced sampling: each sample is weighted by the inverse
+    /// frequency of its label, so every class is drawn about equally often.
+    /// Draws `labels.len()` samples per epoch with replacement.
+    pub fn balanced(labels: &Tensor, seed: Option<u64>) -> TensorResult<Self> {
+        let classes = class_keys(labels)?;
+        let mut counts = std::collections::HashMap::new();
+        for c in &classes {
+            *counts.entry(*c).or_insert(0usize) += 1;
+        }
+        let weights = classes.iter().map(|c| 1.0 / counts[c] as f64).collect();
+        Self::new(weights, classes.len(), true, seed)
+    }
+}
+
+impl Sampler for WeightedRandomSampler {
+    fn epoch_indices(&mut self, num_samples: usize) -> TensorResult<Vec<usize>> {
+        if self.weights.len() != num_samples {
+            return Err(TensorError::InvalidOperation(format!(
+                "WeightedRandomSampler has {} weights for a dataset of {} samples",
+                self.weights.len(),
+                num_samples
+            )));
+        }
+        if self.replacement {
+            let dist = WeightedIndex::new(&self.weights).expect("weights validated in new");
+            Ok((0..self.num_samples).map(|_| dist.sample(&mut self.rng)).collect())
+        } else {
+            // Efraimidis–Spirakis: keep the k largest keys u^(1/w).
+            let mut keys: Vec<(f64, usize)> = self
+                .weights
+                .iter()
+                .enumerate()
+                .filter(|(_, &w)| w > 0.0)
+                .map(|(i, &w)| (self.rng.gen::<f64>().powf(1.0 / w), i))
+                .collect();
+            keys.sort_by(|a, b| b.0.total_cmp(&a.0));
+            Ok(keys.into_iter().take(self.num_samples).map(|(_, i)| i).collect())
+        }
+    }
+
+    fn dataset_len(&self) -> Option<usize> {
+        Some(self.weights.len())
+    }
+}
+
+/// One integer class per row of a label tensor (1-D, or 2-D with one column).
+fn class_keys(labels: &Tensor) -> TensorResult<Vec<i64>> {
+    let shape = labels.shape();
+    let n = *shape.first().unwrap_or(&0);
+    if labels.numel() != n {
+        return Err(TensorError::InvalidOperation(format!(
+            "Class labels must have one value per sample, got shape {:?}",
+            shape
+        )));
+    }
+    Ok(match labels {
+        Tensor::Float16(a) => a.iter().map(|&v| v.to_f32().round() as i64).collect(),
+        Tensor::BFloat16(a) => a.iter().map(|&v| v.to_f32().round() as i64).collect(),
+        Tensor::Float32(a) => a.iter().map(|&v| v.round() as i64).collect(),
+        Tensor::Float64(a) => a.iter().map(|&v| v.round() as i64).collect(),
+        Tensor::Int32(a) => a.iter().map(|&v| v as i64).collect(),
+        Tensor::Int64(a) => a.iter().copied().collect(),
+        Tensor::UInt8(a) => a.iter().map(|&v| v as i64).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{ArrayD, IxDyn};
+
+    fn frequencies(indices: &[usize], n: usize) -> Vec<f64> {
+        let mut counts = vec![0.0; n];
+        for &i in indices {
+            counts[i] += 1.0;
+        }
+        counts.iter().map(|c| c / indices.len() as f64).collect()
+    }
+
+    #[test]
+    fn test_random_sampler_is_reproducible() {
+        let (mut a, mut b) = (RandomSampler::new(Some(7)), RandomSampler::new(Some(7)));
+        let first = a.epoch_indices(20).unwrap();
+        assert_eq!(first, b.epoch_indices(20).unwrap());
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+        // A new order each epoch, still the same for equal seeds.
+        let second = a.epoch_indices(20).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(second, b.epoch_indices(20).unwrap());
+        assert_ne!(first, RandomSampler::new(Some(8)).epoch_indices(20).unwrap());
+    }
+
+    #[test]
+    fn test_weighted_sampling_follows_weights() {
+        let mut sampler = WeightedRandomSampler::new(vec![1.0, 2.0, 0.0, 7.0], 20_000, true, Some(3)).unwrap();
+        let f = frequencies(&sampler.epoch_indices(4).unwrap(), 4);
+        for (got, want) in f.iter().zip([0.1, 0.2, 0.0, 0.7]) {
+            assert!((got - want).abs() < 0.015, "{:?}", f);
+        }
+
+        // Without replacement every draw is distinct and zero weights are
+        // never drawn, while heavy weights come first more often.
+        let weights = vec![8.0, 0.0, 1.0, 1.0, 1.0];
+        let mut sampler = WeightedRandomSampler::new(weights, 3, false, Some(5)).unwrap();
+        let mut firsts = vec![0usize; 5];
+        for _ in 0..2000 {
+            let drawn = sampler.epoch_indices(5).unwrap();
+            assert_eq!(drawn.len(), 3);
+            assert!(!drawn.contains(&1));
+            let mut unique = drawn.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(unique.len(), 3);
+            firsts[drawn[0]] += 1;
+        }
+        // P(first = 0) = 8 / 11.
+        assert!((firsts[0] as f64 / 2000.0 - 8.0 / 11.0).abs() < 0.04, "{:?}", firsts);
+    }
+
+    #[test]
+    fn test_balanced_sampling_evens_out_classes() {
+        let labels: Vec<f64> = (0..100).map(|i| if i < 90 { 0.0 } else { 1.0 }).collect();
+        let labels = Tensor::Float64(ArrayD::from_shape_vec(IxDyn(&[100, 1]), labels).unwrap().into_shared());
+        let mut sampler = WeightedRandomSampler::balanced(&labels, Some(11)).unwrap();
+        let mut drawn = Vec::new();
+        for _ in 0..100 {
+            let epoch = sampler.epoch_indices(100).unwrap();
+            assert_eq!(epoch.len(), 100);
+            drawn.extend(epoch);
+        }
+        let minority = drawn.iter().filter(|&&i| i >= 90).count() as f64 / drawn.len() as f64;
+        assert!((minority - 0.5).abs() < 0.02, "{}", minority);
+    }
+
+    #[test]
+    fn test_weighted_sampler_errors() {
+        assert!(WeightedRandomSampler::new(vec![1.0, -1.0], 2, true, None).is_err());
+        assert!(WeightedRandomSampler::new(vec![1.0, f64::NAN], 2, true, None).is_err());
+        assert!(WeightedRandomSampler::new(vec![0.0, 0.0], 2, true, None).is_err());
+        assert!(WeightedRandomSampler::new(vec![1.0, 0.0, 1.0], 3, false, None).is_err());
+        let mut sampler = WeightedRandomSampler::new(vec![1.0, 1.0], 2, true, None).unwrap();
+        assert_eq!(sampler.dataset_len(), Some(2));
+        assert!(sampler.epoch_indices(3).is_err());
+    }
+}
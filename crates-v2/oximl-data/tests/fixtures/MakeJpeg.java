@@ -0,0 +1,78 @@
+// Write the JPEG fixtures read by `image.rs`'s tests with the JDK's
+// baseline encoder (libjpeg-derived):
+//
+//   javac MakeJpeg.java && java MakeJpeg
+//
+// Each image is 21 x 17 with the smooth gradient of `rgb` below, so the
+// tests can compare the decoded pixels against it within JPEG's error.
+// `rgb420.jpg` uses the default 2x2 luma sampling and a restart interval
+// of two MCUs, `rgb444.jpg` samples every component at full resolution,
+// and `gray.jpg` has a single component.
+
+import java.awt.image.BufferedImage;
+import java.io.File;
+import javax.imageio.IIOImage;
+import javax.imageio.ImageIO;
+import javax.imageio.ImageTypeSpecifier;
+import javax.imageio.ImageWriteParam;
+import javax.imageio.ImageWriter;
+import javax.imageio.metadata.IIOMetadata;
+import javax.imageio.metadata.IIOMetadataNode;
+import javax.imageio.stream.ImageOutputStream;
+import org.w3c.dom.Element;
+import org.w3c.dom.Node;
+import org.w3c.dom.NodeList;
+
+public class MakeJpeg {
+    static final int W = 21, H = 17;
+
+    static int[] rgb(int x, int y) {
+        return new int[] {40 + 8 * x, 30 + 9 * y, 200 - 4 * x - 3 * y};
+    }
+
+    static void write(String path, BufferedImage image, boolean fullChroma, int restartInterval) throws Exception {
+        ImageWriter writer = ImageIO.getImageWritersByFormatName("jpeg").next();
+        ImageWriteParam param = writer.getDefaultWriteParam();
+        param.setCompressionMode(ImageWriteParam.MODE_EXPLICIT);
+        param.setCompressionQuality(0.95f);
+        IIOMetadata meta = writer.getDefaultImageMetadata(new ImageTypeSpecifier(image), param);
+        String format = "javax_imageio_jpeg_image_1.0";
+        Node root = meta.getAsTree(format);
+        if (fullChroma) {
+            NodeList comps = ((Element) root).getElementsByTagName("componentSpec");
+            for (int i = 0; i < comps.getLength(); i++) {
+                Element c = (Element) comps.item(i);
+                c.setAttribute("HsamplingFactor", "1");
+                c.setAttribute("VsamplingFactor", "1");
+            }
+        }
+        if (restartInterval > 0) {
+            Node markers = ((Element) root).getElementsByTagName("markerSequence").item(0);
+            IIOMetadataNode dri = new IIOMetadataNode("dri");
+            dri.setAttribute("interval", Integer.toString(restartInterval));
+            markers.insertBefore(dri, markers.getFirstChild());
+        }
+        meta.setFromTree(format, root);
+        new File(path).delete();
+        try (ImageOutputStream out = ImageIO.createImageOutputStream(new File(path))) {
+            writer.setOutput(out);
+            writer.write(null, new IIOImage(image, null, meta), param);
+        }
+        writer.dispose();
+    }
+
+    public static void main(String[] args) throws Exception {
+        BufferedImage color = new BufferedImage(W, H, BufferedImage.TYPE_3BYTE_BGR);
+        BufferedImage gray = new BufferedImage(W, H, BufferedImage.TYPE_BYTE_GRAY);
+        for (int y = 0; y < H; y++) {
+            for (int x = 0; x < W; x++) {
+                int[] p = rgb(x, y);
+                color.getRaster().setPixel(x, y, p);
+                gray.getRaster().setSample(x, y, 0, p[1]);
+            }
+        }
+        write("rgb420.jpg", color, false, 2);
+        write("rgb444.jpg", color, true, 0);
+        write("gray.jpg", gray, false, 0);
+    }
+}
@@ -0,0 +1,121 @@
+#!/usr/bin/env python3
+"""Write the PNG fixtures read by `image.rs`'s tests.
+
+Every image is `W × H` = 13 × 11 with sample `s` of pixel `(x, y)` equal to
+`pattern(x, y, s)`, reduced to the bit depth of the file, so the tests
+can recompute the expected pixels. Rows cycle through the five filter
+types (None, Sub, Up, Average, Paeth), and the zlib streams use stored,
+fixed-Huffman and dynamic-Huffman blocks.
+
+Run from this directory: `python3 make_png.py`.
+"""
+
+import struct
+import zlib
+
+W, H = 13, 11
+
+
+def pattern(x, y, s):
+    return (x * 17 + y * 29 + s * 71 + x * y * 3) % 256
+
+
+def chunk(kind, body):
+    crc = zlib.crc32(kind + body) & 0xFFFFFFFF
+    return struct.pack(">I", len(body)) + kind + body + struct.pack(">I", crc)
+
+
+def paeth(a, b, c):
+    p = a + b - c
+    pa, pb, pc = abs(p - a), abs(p - b), abs(p - c)
+    if pa <= pb and pa <= pc:
+        return a
+    return b if pb <= pc else c
+
+
+def filter_row(kind, row, prev, bpp):
+    out = bytearray()
+    for i, v in enumerate(row):
+        a = row[i - bpp] if i >= bpp else 0
+        b = prev[i]
+        c = prev[i - bpp] if i >= bpp else 0
+        pred = [0, a, b, (a + b) // 2, paeth(a, b, c)][kind]
+        out.append((v - pred) & 0xFF)
+    return bytes([kind]) + bytes(out)
+
+
+def pack(samples, depth):
+    """Pack one row of samples at `depth` bits, MSB first."""
+    if depth == 16:
+        return b"".join(struct.pack(">H", v) for v in samples)
+    if depth == 8:
+        return bytes(samples)
+    out, acc, n = bytearray(), 0, 0
+    for v in samples:
+        acc = (acc << depth) | v
+        n += depth
+        if n == 8:
+            out.append(acc)
+            acc, n = 0, 0
+    if n:
+        out.append(acc << (8 - n))
+    return bytes(out)
+
+
+def scanlines(rows, depth, bpp, row_counter):
+    """Filter rows of samples, continuing the filter cycle across passes."""
+    out, prev = bytearray(), None
+    for samples in rows:
+        raw = pack(samples, depth)
+        prev = prev or bytes(len(raw))
+        out += filter_row(row_counter[0] % 5, raw, prev, bpp)
+        row_counter[0] += 1
+        prev = raw
+    return bytes(out)
+
+
+ADAM7 = [(0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4), (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2)]
+
+
+def png(path, color, depth, samples, value, interlace=False, level=6, strategy=zlib.Z_DEFAULT_STRATEGY,
+        extra=b""):
+    """`value(x, y, s)` is the stored sample (a palette index for color 3)."""
+    bpp = max(1, samples * depth // 8)
+    counter = [0]
+    passes = ADAM7 if interlace else [(0, 0, 1, 1)]
+    data = bytearray()
+    for x0, y0, dx, dy in passes:
+        xs, ys = range(x0, W, dx), range(y0, H, dy)
+        if not xs or not ys:
+            continue
+        rows = [[value(x, y, s) for x in xs for s in range(samples)] for y in ys]
+        data += scanlines(rows, depth, bpp, counter)
+    z = zlib.compressobj(level, zlib.DEFLATED, 15, 9, strategy)
+    idat = z.compress(bytes(data)) + z.flush()
+    ihdr = struct.pack(">IIBBBBB", W, H, depth, color, 0, 0, int(interlace))
+    # Split the image data over two IDAT chunks, as large encoders do.
+    half = len(idat) // 2
+    body = (chunk(b"IHDR", ihdr) + extra + chunk(b"IDAT", idat[:half]) + chunk(b"IDAT", idat[half:])
+            + chunk(b"IEND", b""))
+    with open(path, "wb") as f:
+        f.write(b"\x89PNG\r\n\x1a\n" + body)
+
+
+def scaled(depth):
+    return lambda x, y, s: pattern(x, y, s) * ((1 << depth) - 1) // 255
+
+
+if __name__ == "__main__":
+    png("gray8.png", 0, 8, 1, scaled(8), level=0)
+    png("gray4.png", 0, 4, 1, scaled(4), strategy=zlib.Z_FIXED)
+    png("gray_alpha8.png", 4, 8, 2, scaled(8))
+    png("rgb8.png", 2, 8, 3, scaled(8), level=9)
+    png("rgb16.png", 2, 16, 3, lambda x, y, s: pattern(x, y, s) * 256 + x)
+    png("rgba8.png", 6, 8, 4, scaled(8))
+    # 16 palette entries: entry i is pattern(i, 0, s); alpha for the first 5.
+    palette = b"".join(bytes(pattern(i, 0, s) for s in range(3)) for i in range(16))
+    trns = bytes(40 * i for i in range(5))
+    png("palette4.png", 3, 4, 1, lambda x, y, s: (x + 2 * y) % 16,
+        extra=chunk(b"PLTE", palette) + chunk(b"tRNS", trns))
+    png("rgb8_interlaced.png", 2, 8, 3, scaled(8), interlace=True)
+    png("gray2_interlaced.png", 0, 2, 1, scaled(2), interlace=True)
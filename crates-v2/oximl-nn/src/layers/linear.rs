@@ -2,6 +2,7 @@ use std::sync::Arc;
 use oximl_autodiff::{Variable, Graph};
 use oximl_core::{Tensor, DType, TensorResult};
 use crate::modules::Module;
+use crate::modules::state_dict::{weight_bias, weight_bias_mut};
 
 /// A fully connected linear (dense) layer: Y = X @ W + B
 pub struct Linear {
@@ -43,11 +44,11 @@ impl Module for Linear {
         Ok(out)
     }
 
-    fn parameters(&self) -> Vec<Variable> {
-        let mut params = vec![self.weight.clone()];
-        if let Some(b) = &self.bias {
-            params.push(b.clone());
-        }
-        params
+    fn named_parameters(&self) -> Vec<(String, Variable)> {
+        weight_bias(&self.weight, &self.bias)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)> {
+        weight_bias_mut(&mut self.weight, &mut self.bias)
     }
 }
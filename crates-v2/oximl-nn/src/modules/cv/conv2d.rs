@@ -2,6 +2,7 @@ use std::sync::Arc;
 use oximl_autodiff::{Variable, Graph};
 use oximl_core::{Tensor, DType, TensorResult, TensorError};
 use crate::modules::Module;
+use crate::modules::state_dict::{weight_bias, weight_bias_mut};
 
 /// A 2D Convolutional Layer for Computer Vision.
 pub struct Conv2d {
@@ -84,11 +85,11 @@ impl Module for Conv2d {
         Ok(out)
     }
 
-    fn parameters(&self) -> Vec<Variable> {
-        let mut params = vec![self.weight.clone()];
-        if let Some(b) = &self.bias {
-            params.push(b.clone());
-        }
-        params
+    fn named_parameters(&self) -> Vec<(String, Variable)> {
+        weight_bias(&self.weight, &self.bias)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)> {
+        weight_bias_mut(&mut self.weight, &mut self.bias)
     }
 }
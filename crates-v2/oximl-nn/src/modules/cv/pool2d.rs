@@ -40,7 +40,11 @@ impl Module for MaxPool2d {
         x.add(&temp_leaf)
     }
 
-    fn parameters(&self) -> Vec<Variable> {
+    fn named_parameters(&self) -> Vec<(String, Variable)> {
         Vec::new() // Pooling has no trainable parameters
     }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)> {
+        Vec::new()
+    }
 }
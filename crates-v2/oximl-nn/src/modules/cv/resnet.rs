@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use oximl_autodiff::{Variable, Graph};
 use oximl_core::TensorResult;
-use crate::modules::Module;
+use crate::modules::{prefixed, Module};
 use super::conv2d::Conv2d;
 
 /// Structural ResNet block implementing residual spatial pathways.
@@ -37,10 +37,17 @@ impl Module for ResNetBlock {
         self.forward(x)
     }
 
-    fn parameters(&self) -> Vec<Variable> {
+    fn named_parameters(&self) -> Vec<(String, Variable)> {
         let mut params = Vec::new();
-        params.extend(self.conv1.parameters());
-        params.extend(self.conv2.parameters());
+        params.extend(prefixed("conv1", self.conv1.named_parameters()));
+        params.extend(prefixed("conv2", self.conv2.named_parameters()));
+        params
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)> {
+        let mut params = Vec::new();
+        params.extend(prefixed("conv1", self.conv1.named_parameters_mut()));
+        params.extend(prefixed("conv2", self.conv2.named_parameters_mut()));
         params
     }
 }
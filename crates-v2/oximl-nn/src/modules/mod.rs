@@ -1,5 +1,8 @@
 use oximl_autodiff::Variable;
-use oximl_core::TensorResult;
+use oximl_core::{TensorError, TensorResult};
+
+pub mod state_dict;
+pub use state_dict::{prefixed, LoadReport, StateDict};
 
 /// The base trait for all neural network modules.
 /// It defines the standard forward pass and parameter extraction.
@@ -7,38 +10,86 @@ pub trait Module {
     /// Perform the mathematical forward pass of this module.
     fn forward(&self, x: &Variable) -> TensorResult<Variable>;
 
+    /// All trainable parameters keyed by their dotted path within this module
+    /// (e.g. `attention.w_q.weight`), in a stable order.
+    fn named_parameters(&self) -> Vec<(String, Variable)>;
+
+    /// Mutable access to the same parameters, in the same order.
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)>;
+
     /// Return all trainable parameters registered within this module and its submodules.
     /// This allows optimizers to easily collect and update weights.
-    fn parameters(&self) -> Vec<Variable>;
+    fn parameters(&self) -> Vec<Variable> {
+        self.named_parameters().into_iter().map(|(_, v)| v).collect()
+    }
 
-    /// Extract the raw inner Tensors and save them to a binary `.oximl` file on disk, mimicking a state_dict.
-    fn save<P: AsRef<std::path::Path>>(&self, path: P) -> TensorResult<()> {
-        let params = self.parameters();
-        let tensors: Vec<oximl_core::Tensor> = params.into_iter().map(|v| v.data.clone()).collect();
-        let file = std::fs::File::create(path).map_err(|e| oximl_core::TensorError::InvalidOperation(format!("Save error: {}", e)))?;
-        bincode::serialize_into(file, &tensors).map_err(|e| oximl_core::TensorError::InvalidOperation(format!("Bincode error: {}", e)))?;
-        Ok(())
-    }
-
-    /// Load serialized weights from a binary `.oximl` file sequentially into this module's parameters.
-    fn load<P: AsRef<std::path::Path>>(&self, path: P) -> TensorResult<()> {
-        let file = std::fs::File::open(path).map_err(|e| oximl_core::TensorError::InvalidOperation(format!("Load error: {}", e)))?;
-        let tensors: Vec<oximl_core::Tensor> = bincode::deserialize_from(file)
-            .map_err(|e| oximl_core::TensorError::InvalidOperation(format!("Bincode parse error: {}", e)))?;
-        
-        let mut params = self.parameters();
-        if params.len() != tensors.len() {
-            return Err(oximl_core::TensorError::InvalidOperation(
-                format!("Model architecture mismatch. Expected {} parameter blocks, file contains {}", params.len(), tensors.len())
-            ));
+    /// Snapshot of every parameter tensor keyed by its path, mimicking PyTorch's `state_dict()`.
+    fn state_dict(&self) -> StateDict {
+        self.named_parameters()
+            .into_iter()
+            .map(|(name, v)| (name, v.data))
+            .collect()
+    }
+
+    /// Copy tensors from `state` into the parameters with matching names.
+    ///
+    /// With `strict`, any missing or unexpected key is an error and nothing is
+    /// loaded. Otherwise matching keys are loaded and the mismatches are
+    /// listed in the returned report. A shape or dtype mismatch on a matching
+    /// key is always an error.
+    fn load_state_dict(&mut self, state: &StateDict, strict: bool) -> TensorResult<LoadReport> {
+        let mut params = self.named_parameters_mut();
+        let mut report = LoadReport::default();
+        for (name, param) in &params {
+            match state.get(name) {
+                None => report.missing_keys.push(name.clone()),
+                Some(t) if t.shape() != param.data.shape() => {
+                    return Err(TensorError::ShapeMismatch {
+                        expected: param.data.shape().to_vec(),
+                        got: t.shape().to_vec(),
+                    })
+                }
+                Some(t) if t.dtype() != param.data.dtype() => {
+                    return Err(TensorError::TypeMismatch {
+                        expected: param.data.dtype(),
+                        got: t.dtype(),
+                    })
+                }
+                Some(_) => {}
+            }
         }
-        
-        // Directly overwrite the memory pointers mimicking PyTorch `.load_state_dict(strict=True)`
-        for (param, loaded_tensor) in params.iter_mut().zip(tensors.into_iter()) {
-            param.data = loaded_tensor;
+        report.unexpected_keys = state
+            .keys()
+            .filter(|k| !params.iter().any(|(name, _)| name == *k))
+            .cloned()
+            .collect();
+
+        if strict && !report.is_exact() {
+            return Err(TensorError::InvalidOperation(format!(
+                "Error(s) in loading state dict: {}",
+                report
+            )));
         }
-        
-        Ok(())
+        for (name, param) in params.iter_mut() {
+            if let Some(t) = state.get(name) {
+                // The graph keeps its own copy of each leaf's value for backward.
+                param.graph.set_data(param.node_id, t.clone())?;
+                param.data = t.clone();
+            }
+        }
+        Ok(report)
+    }
+
+    /// Save the state dict to a `.safetensors` file.
+    fn save<P: AsRef<std::path::Path>>(&self, path: P) -> TensorResult<()> {
+        oximl_core::save_safetensors(path, &self.state_dict())
+    }
+
+    /// Load a `.safetensors` file written by `save` (or by PyTorch / safetensors
+    /// with matching parameter names). See `load_state_dict` for `strict`.
+    fn load<P: AsRef<std::path::Path>>(&mut self, path: P, strict: bool) -> TensorResult<LoadReport> {
+        let state = oximl_core::load_safetensors(path)?;
+        self.load_state_dict(&state, strict)
     }
 }
 
@@ -47,3 +98,107 @@ pub use loss::{MSELoss, CrossEntropyLoss};
 
 pub mod nlp;
 pub mod cv;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::Linear;
+    use oximl_autodiff::Graph;
+    use oximl_core::{DType, Tensor};
+    use std::sync::Arc;
+
+    fn tensor(data: Vec<f64>, shape: &[usize]) -> Tensor {
+        Tensor::Float64(ndarray::ArrayD::from_shape_vec(ndarray::IxDyn(shape), data).unwrap().into_shared())
+    }
+
+    fn values(t: &Tensor) -> Vec<f64> {
+        match t {
+            Tensor::Float64(a) => a.iter().copied().collect(),
+            other => panic!("expected f64, got {:?}", other.dtype()),
+        }
+    }
+
+    fn trained() -> StateDict {
+        let mut state = StateDict::new();
+        state.insert("weight".to_string(), tensor(vec![0.5, -1.0, 2.0, 0.25, 1.5, -0.75], &[2, 3]));
+        state.insert("bias".to_string(), tensor(vec![0.1, 0.2, 0.3], &[1, 3]));
+        state
+    }
+
+    fn layer() -> Linear {
+        Linear::new(2, 3, true, Arc::new(Graph::new()))
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut source = layer();
+        source.load_state_dict(&trained(), true).unwrap();
+        let path = std::env::temp_dir().join(format!("oximl_module_{}.safetensors", std::process::id()));
+        source.save(&path).unwrap();
+
+        let mut target = layer();
+        let report = target.load(&path, true).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(report.is_exact());
+        assert_eq!(values(&target.weight.data), values(&trained()["weight"]));
+        assert_eq!(values(&target.bias.as_ref().unwrap().data), values(&trained()["bias"]));
+        // The graph node behind the leaf holds the loaded weights too.
+        let node = target.weight.graph.get_node(target.weight.node_id).unwrap();
+        assert_eq!(values(&node.data), values(&trained()["weight"]));
+    }
+
+    #[test]
+    fn test_backward_uses_loaded_weights() {
+        let mut model = layer();
+        model.load_state_dict(&trained(), true).unwrap();
+        let graph = model.weight.graph.clone();
+        let x = Variable::param(tensor(vec![1.0, 2.0], &[1, 2]), graph.clone());
+        let ones = Variable::input(Tensor::ones(&[3, 1], DType::Float64), graph);
+        model.forward(&x).unwrap().matmul(&ones).unwrap().backward().unwrap();
+        // d/dx of sum(x @ W + b) is the row sums of the loaded W, not of
+        // the all-ones initial weights.
+        assert_eq!(values(&x.grad().unwrap()), vec![1.5, 1.0]);
+    }
+
+    #[test]
+    fn test_strict_load_rejects_missing_and_unexpected_keys() {
+        let mut missing = trained();
+        missing.remove("bias");
+        let mut unexpected = trained();
+        unexpected.insert("extra".to_string(), tensor(vec![1.0], &[1]));
+        for state in [missing, unexpected] {
+            let mut model = layer();
+            let err = model.load_state_dict(&state, true).unwrap_err().to_string();
+            assert!(err.contains("missing keys: [bias]") || err.contains("unexpected keys: [extra]"), "{}", err);
+            // Nothing was loaded.
+            assert_eq!(values(&model.weight.data), vec![1.0; 6]);
+        }
+    }
+
+    #[test]
+    fn test_non_strict_load_reports_mismatched_keys() {
+        let mut state = trained();
+        state.remove("bias");
+        state.insert("extra".to_string(), tensor(vec![1.0], &[1]));
+        let mut model = layer();
+        let report = model.load_state_dict(&state, false).unwrap();
+        assert_eq!(report.missing_keys, ["bias"]);
+        assert_eq!(report.unexpected_keys, ["extra"]);
+        assert_eq!(report.to_string(), "missing keys: [bias]; unexpected keys: [extra]");
+        assert_eq!(values(&model.weight.data), values(&trained()["weight"]));
+        assert_eq!(values(&model.bias.as_ref().unwrap().data), vec![1.0; 3]);
+    }
+
+    #[test]
+    fn test_shape_and_dtype_mismatches_are_errors() {
+        let mut state = trained();
+        state.insert("weight".to_string(), tensor(vec![0.0; 6], &[3, 2]));
+        let err = layer().load_state_dict(&state, false).unwrap_err();
+        assert!(matches!(err, TensorError::ShapeMismatch { ref expected, ref got } if expected == &[2, 3] && got == &[3, 2]));
+
+        let mut state = trained();
+        state.insert("bias".to_string(), Tensor::zeros(&[1, 3], DType::Float32));
+        let err = layer().load_state_dict(&state, false).unwrap_err();
+        assert!(matches!(err, TensorError::TypeMismatch { expected: DType::Float64, got: DType::Float32 }));
+    }
+}
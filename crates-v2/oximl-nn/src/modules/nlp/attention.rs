@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use oximl_autodiff::{Variable, Graph};
 use oximl_core::{Tensor, DType, TensorResult};
-use crate::modules::Module;
+use crate::modules::{prefixed, Module};
 use crate::layers::Linear;
 
 /// Multi-Head Attention Mechanism
@@ -66,12 +66,21 @@ impl Module for MultiHeadAttention {
         self.forward(x)
     }
 
-    fn parameters(&self) -> Vec<Variable> {
+    fn named_parameters(&self) -> Vec<(String, Variable)> {
         let mut params = Vec::new();
-        params.extend(self.w_q.parameters());
-        params.extend(self.w_k.parameters());
-        params.extend(self.w_v.parameters());
-        params.extend(self.w_o.parameters());
+        params.extend(prefixed("w_q", self.w_q.named_parameters()));
+        params.extend(prefixed("w_k", self.w_k.named_parameters()));
+        params.extend(prefixed("w_v", self.w_v.named_parameters()));
+        params.extend(prefixed("w_o", self.w_o.named_parameters()));
+        params
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)> {
+        let mut params = Vec::new();
+        params.extend(prefixed("w_q", self.w_q.named_parameters_mut()));
+        params.extend(prefixed("w_k", self.w_k.named_parameters_mut()));
+        params.extend(prefixed("w_v", self.w_v.named_parameters_mut()));
+        params.extend(prefixed("w_o", self.w_o.named_parameters_mut()));
         params
     }
 }
@@ -56,7 +56,11 @@ impl Module for Embedding {
         Err(TensorError::InvalidOperation("Use .forward(&Tensor) for Embeddings".into()))
     }
 
-    fn parameters(&self) -> Vec<Variable> {
-        vec![self.weight.clone()]
+    fn named_parameters(&self) -> Vec<(String, Variable)> {
+        vec![("weight".to_string(), self.weight.clone())]
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)> {
+        vec![("weight".to_string(), &mut self.weight)]
     }
 }
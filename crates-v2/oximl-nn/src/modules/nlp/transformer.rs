@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use oximl_autodiff::{Variable, Graph};
 use oximl_core::TensorResult;
-use crate::modules::Module;
+use crate::modules::{prefixed, Module};
 use crate::layers::Linear;
 use super::attention::MultiHeadAttention;
 
@@ -50,11 +50,19 @@ impl Module for TransformerBlock {
         self.forward(x)
     }
 
-    fn parameters(&self) -> Vec<Variable> {
+    fn named_parameters(&self) -> Vec<(String, Variable)> {
         let mut params = Vec::new();
-        params.extend(self.attention.parameters());
-        params.extend(self.ff1.parameters());
-        params.extend(self.ff2.parameters());
+        params.extend(prefixed("attention", self.attention.named_parameters()));
+        params.extend(prefixed("ff1", self.ff1.named_parameters()));
+        params.extend(prefixed("ff2", self.ff2.named_parameters()));
+        params
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable)> {
+        let mut params = Vec::new();
+        params.extend(prefixed("attention", self.attention.named_parameters_mut()));
+        params.extend(prefixed("ff1", self.ff1.named_parameters_mut()));
+        params.extend(prefixed("ff2", self.ff2.named_parameters_mut()));
         params
     }
 }
@@ -0,0 +1,60 @@
+use std::fmt;
+
+use oximl_autodiff::Variable;
+pub use oximl_core::TensorMap as StateDict;
+
+/// Outcome of `Module::load_state_dict`: keys the module expected but the
+/// state dict lacked, and keys the state dict had but the module doesn't.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    pub missing_keys: Vec<String>,
+    pub unexpected_keys: Vec<String>,
+}
+
+impl LoadReport {
+    /// True when every key matched on both sides.
+    pub fn is_exact(&self) -> bool {
+        self.missing_keys.is_empty() && self.unexpected_keys.is_empty()
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_exact() {
+            return write!(f, "all keys matched");
+        }
+        let mut parts = Vec::new();
+        if !self.missing_keys.is_empty() {
+            parts.push(format!("missing keys: [{}]", self.missing_keys.join(", ")));
+        }
+        if !self.unexpected_keys.is_empty() {
+            parts.push(format!("unexpected keys: [{}]", self.unexpected_keys.join(", ")));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// Prefix every parameter name with `prefix.`, for nesting submodules.
+pub fn prefixed<V>(prefix: &str, params: Vec<(String, V)>) -> Vec<(String, V)> {
+    params.into_iter().map(|(name, v)| (format!("{}.{}", prefix, name), v)).collect()
+}
+
+/// `weight` / optional `bias` pair shared by Linear and Conv2d.
+pub(crate) fn weight_bias(weight: &Variable, bias: &Option<Variable>) -> Vec<(String, Variable)> {
+    let mut params = vec![("weight".to_string(), weight.clone())];
+    if let Some(b) = bias {
+        params.push(("bias".to_string(), b.clone()));
+    }
+    params
+}
+
+pub(crate) fn weight_bias_mut<'a>(
+    weight: &'a mut Variable,
+    bias: &'a mut Option<Variable>,
+) -> Vec<(String, &'a mut Variable)> {
+    let mut params = vec![("weight".to_string(), weight)];
+    if let Some(b) = bias {
+        params.push(("bias".to_string(), b));
+    }
+    params
+}
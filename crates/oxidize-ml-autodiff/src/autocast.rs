@@ -0,0 +1,53 @@
+use std::cell::Cell;
+use oxidize_ml_core::HalfType;
+
+thread_local! {
+    // The reduced precision matmuls on this thread run in, if any.
+    static AUTOCAST: Cell<Option<HalfType>> = const { Cell::new(None) };
+}
+
+/// Run `f` with matrix products (and so `linear` and `conv2d`, which are
+/// built on them) computed at `dtype`: their operands and result are
+/// rounded to the 16-bit format, as by a kernel that reads and writes half
+/// precision and accumulates in full. Element-wise ops, reductions and
+/// losses stay in full precision, and gradients flow through the rounding
+/// unchanged.
+///
+/// The values are emulated on `f32`/`f64`; it reproduces the numerics of
+/// mixed precision, not its speed.
+pub fn autocast<R>(dtype: HalfType, f: impl FnOnce() -> R) -> R {
+    let previous = AUTOCAST.with(|cell| cell.replace(Some(dtype)));
+    let result = f();
+    AUTOCAST.with(|cell| cell.set(previous));
+    result
+}
+
+/// The precision of an enclosing `autocast` region on this thread.
+pub fn autocast_dtype() -> Option<HalfType> {
+    AUTOCAST.with(|cell| cell.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variable::Variable;
+    use oxidize_ml_core::Tensor;
+
+    #[test]
+    fn test_autocast_rounds_matmul_only_inside_the_region() {
+        let x = Variable::input(Tensor::new(vec![0.1, 0.2], vec![1, 2]).unwrap());
+        let w = Variable::param(Tensor::new(vec![1.0, 1.0], vec![2, 1]).unwrap());
+        let full: f64 = x.matmul(&w).data.data()[0];
+        let half = autocast(HalfType::F16, || {
+            assert_eq!(autocast_dtype(), Some(HalfType::F16));
+            x.matmul(&w)
+        });
+        assert!(autocast_dtype().is_none());
+        assert!((full - 0.3).abs() < 1e-12);
+        let v = half.data.data()[0];
+        assert_eq!(v, HalfType::F16.round(v));
+        assert!(v != full && (v - 0.3).abs() < 1e-3);
+        let grads = crate::backward::backward(&half.sum());
+        assert_eq!(grads[&w.node_id].data(), x.data.t().unwrap().data());
+    }
+}
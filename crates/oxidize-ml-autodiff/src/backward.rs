@@ -1,156 +1,470 @@
 use std::collections::HashMap;
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
 use oxidize_ml_core::{Float, Tensor};
-use crate::graph::{NodeId, Op, with_graph};
+use crate::checkpoint::Recomputed;
+use crate::conv::{col2im, im2col};
+use crate::graph::{NodeId, Op};
 use crate::variable::Variable;
 
 /// Compute gradients via reverse-mode automatic differentiation.
 ///
 /// Returns a map from NodeId to gradient Tensor for all nodes that require grad.
-pub fn backward(loss: &Variable) -> HashMap<NodeId, Tensor<f64>> {
-    with_graph(|graph| {
-        let n = graph.len();
-        let mut grads: HashMap<NodeId, Tensor<f64>> = HashMap::new();
-
-        // Seed: gradient of loss w.r.t. itself is 1
-        let loss_shape = graph.get(loss.node_id).shape.clone();
-        let seed = if loss_shape.is_empty() || (loss_shape.len() == 1 && loss_shape[0] == 1) {
-            Tensor::scalar(1.0)
-        } else {
-            Tensor::ones(loss_shape)
+/// A loss computed under `no_grad` has no graph, and gets an empty map.
+pub fn backward<T: Float>(loss: &Variable<T>) -> HashMap<NodeId, Tensor<T>> {
+    let mut grads: HashMap<NodeId, Tensor<T>> = HashMap::new();
+    if !loss.is_tracked() {
+        return grads;
+    }
+    let graph = loss.graph.read();
+    let mut values = Recomputed::new();
+
+    // Seed: gradient of loss w.r.t. itself is 1
+    let loss_shape = graph.get(loss.node_id).shape.clone();
+    let seed = if loss_shape.is_empty() || (loss_shape.len() == 1 && loss_shape[0] == 1) {
+        Tensor::scalar(T::ONE)
+    } else {
+        Tensor::ones(loss_shape)
+    };
+    grads.insert(loss.node_id, seed);
+
+    // Reverse topological order (nodes are added in forward order)
+    for idx in (0..=loss.node_id.0).rev() {
+        let node_id = NodeId(idx);
+        let grad = match grads.get(&node_id) {
+            Some(g) => g.clone(),
+            None => continue,
         };
-        grads.insert(loss.node_id, seed);
-
-        // Reverse topological order (nodes are added in forward order)
-        for idx in (0..n).rev() {
-            let node_id = NodeId(idx);
-            let grad = match grads.get(&node_id) {
-                Some(g) => g.clone(),
-                None => continue,
-            };
 
-            let op = graph.get(node_id).op.clone();
+        // Checkpointed segments are recomputed when first reached and freed
+        // once the pass is below them.
+        let op = graph.get(node_id).op.clone();
+        values.release_after(idx);
+        for id in std::iter::once(node_id).chain(op.inputs()) {
+            values.ensure(&graph, id);
+        }
 
-            match op {
-                Op::Leaf => {
-                    // Leaf nodes accumulate gradients — already stored
-                }
-                Op::Add(a, b) => {
-                    accumulate_grad(&mut grads, a, &grad, &graph.get(a).shape);
-                    accumulate_grad(&mut grads, b, &grad, &graph.get(b).shape);
-                }
-                Op::Sub(a, b) => {
-                    accumulate_grad(&mut grads, a, &grad, &graph.get(a).shape);
-                    let neg_grad = grad.mul_scalar(-1.0);
-                    accumulate_grad(&mut grads, b, &neg_grad, &graph.get(b).shape);
-                }
-                Op::Mul(a, b) => {
-                    // d/da (a*b) = b * grad
-                    let ga = grad.mul(&graph.get(b).value).expect("mul grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                    // d/db (a*b) = a * grad
-                    let gb = grad.mul(&graph.get(a).value).expect("mul grad");
-                    accumulate_grad(&mut grads, b, &gb, &graph.get(b).shape);
-                }
-                Op::Div(a, b) => {
-                    // d/da (a/b) = grad / b
-                    let ga = grad.div(&graph.get(b).value).expect("div grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                    // d/db (a/b) = -a * grad / b²
-                    let neg_a = graph.get(a).value.mul_scalar(-1.0);
-                    let b_sq = graph.get(b).value.mul(&graph.get(b).value).expect("b²");
-                    let gb = neg_a.mul(&grad).expect("neg_a * grad").div(&b_sq).expect("/ b²");
-                    accumulate_grad(&mut grads, b, &gb, &graph.get(b).shape);
-                }
-                Op::MatMul(a, b) => {
-                    // d/dA (A @ B) = grad @ Bᵀ
-                    let bt = graph.get(b).value.t().expect("transpose B");
-                    let ga = grad.matmul(&bt).expect("grad @ Bᵀ");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                    // d/dB (A @ B) = Aᵀ @ grad
-                    let at = graph.get(a).value.t().expect("transpose A");
-                    let gb = at.matmul(&grad).expect("Aᵀ @ grad");
-                    accumulate_grad(&mut grads, b, &gb, &graph.get(b).shape);
-                }
-                Op::Neg(a) => {
-                    let ga = grad.mul_scalar(-1.0);
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::Exp(a) => {
-                    // d/da exp(a) = exp(a) * grad
-                    let ga = graph.get(node_id).value.mul(&grad).expect("exp grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::Ln(a) => {
-                    // d/da ln(a) = grad / a
-                    let ga = grad.div(&graph.get(a).value).expect("ln grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::Pow(a, n) => {
-                    // d/da a^n = n * a^(n-1) * grad
-                    let am1 = graph.get(a).value.powf(Float::from_f64(n - 1.0));
-                    let ga = am1.mul_scalar(n).mul(&grad).expect("pow grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::Relu(a) => {
-                    // d/da relu(a) = (a > 0) * grad
-                    let mask = graph.get(a).value.apply(|x| {
-                        if x > 0.0 { 1.0 } else { 0.0 }
-                    });
-                    let ga = mask.mul(&grad).expect("relu grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::Sigmoid(a) => {
-                    // d/da σ(a) = σ(a) * (1 - σ(a)) * grad
-                    let sig = &graph.get(node_id).value;
-                    let one_minus = sig.apply(|x| 1.0 - x);
-                    let ga = sig.mul(&one_minus).expect("sig*(1-sig)")
-                        .mul(&grad).expect("* grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::Tanh(a) => {
-                    // d/da tanh(a) = (1 - tanh²(a)) * grad
-                    let th = &graph.get(node_id).value;
-                    let th_sq = th.mul(th).expect("tanh²");
-                    let one_minus = th_sq.apply(|x| 1.0 - x);
-                    let ga = one_minus.mul(&grad).expect("tanh grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::SumAll(a) => {
-                    // Gradient of sum: ones with the shape of a
-                    let ga = Tensor::ones(graph.get(a).shape.clone());
-                    let ga = ga.mul_scalar(grad.item().unwrap_or(1.0));
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::MeanAll(a) => {
-                    let numel = graph.get(a).value.numel();
-                    let scale = 1.0 / numel as f64;
-                    let ga = Tensor::full(graph.get(a).shape.clone(), scale);
-                    let ga = ga.mul_scalar(grad.item().unwrap_or(1.0));
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
-                }
-                Op::Transpose(a) => {
-                    let ga = grad.t().expect("transpose grad");
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+        match op {
+            Op::Leaf => {
+                // Leaf nodes accumulate gradients — already stored
+            }
+            Op::Add(a, b) => {
+                accumulate_grad(&mut grads, a, &grad, &graph.get(a).shape);
+                accumulate_grad(&mut grads, b, &grad, &graph.get(b).shape);
+            }
+            Op::Sub(a, b) => {
+                accumulate_grad(&mut grads, a, &grad, &graph.get(a).shape);
+                let neg_grad = grad.mul_scalar(T::NEG_ONE);
+                accumulate_grad(&mut grads, b, &neg_grad, &graph.get(b).shape);
+            }
+            Op::Mul(a, b) => {
+                // d/da (a*b) = b * grad
+                let ga = grad.mul(values.value(&graph, b)).expect("mul grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+                // d/db (a*b) = a * grad
+                let gb = grad.mul(values.value(&graph, a)).expect("mul grad");
+                accumulate_grad(&mut grads, b, &gb, &graph.get(b).shape);
+            }
+            Op::Div(a, b) => {
+                // d/da (a/b) = grad / b
+                let ga = grad.div(values.value(&graph, b)).expect("div grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+                // d/db (a/b) = -a * grad / b²
+                let neg_a = values.value(&graph, a).mul_scalar(T::NEG_ONE);
+                let b_sq = values.value(&graph, b).mul(values.value(&graph, b)).expect("b²");
+                let gb = neg_a.mul(&grad).expect("neg_a * grad").div(&b_sq).expect("/ b²");
+                accumulate_grad(&mut grads, b, &gb, &graph.get(b).shape);
+            }
+            Op::MatMul(a, b) => {
+                // d/dA (A @ B) = grad @ Bᵀ
+                let bt = values.value(&graph, b).t().expect("transpose B");
+                let ga = grad.matmul(&bt).expect("grad @ Bᵀ");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+                // d/dB (A @ B) = Aᵀ @ grad
+                let at = values.value(&graph, a).t().expect("transpose A");
+                let gb = at.matmul(&grad).expect("Aᵀ @ grad");
+                accumulate_grad(&mut grads, b, &gb, &graph.get(b).shape);
+            }
+            Op::Neg(a) => {
+                let ga = grad.mul_scalar(T::NEG_ONE);
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Exp(a) => {
+                // d/da exp(a) = exp(a) * grad
+                let ga = values.value(&graph, node_id).mul(&grad).expect("exp grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Ln(a) => {
+                // d/da ln(a) = grad / a
+                let ga = grad.div(values.value(&graph, a)).expect("ln grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Pow(a, n) => {
+                // d/da a^n = n * a^(n-1) * grad
+                let am1 = values.value(&graph, a).powf(T::from_f64(n - 1.0));
+                let ga = am1.mul_scalar(T::from_f64(n)).mul(&grad).expect("pow grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Relu(a) => {
+                // d/da relu(a) = (a > 0) * grad
+                let mask = values.value(&graph, a).apply(|x| {
+                    if x > T::ZERO { T::ONE } else { T::ZERO }
+                });
+                let ga = mask.mul(&grad).expect("relu grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Sigmoid(a) => {
+                // d/da σ(a) = σ(a) * (1 - σ(a)) * grad
+                let sig = values.value(&graph, node_id);
+                let one_minus = sig.apply(|x| T::ONE - x);
+                let ga = sig.mul(&one_minus).expect("sig*(1-sig)")
+                    .mul(&grad).expect("* grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Tanh(a) => {
+                // d/da tanh(a) = (1 - tanh²(a)) * grad
+                let th = values.value(&graph, node_id);
+                let th_sq = th.mul(th).expect("tanh²");
+                let one_minus = th_sq.apply(|x| T::ONE - x);
+                let ga = one_minus.mul(&grad).expect("tanh grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Erf(a) | Op::Gelu(a) | Op::GeluTanh(a) | Op::Silu(a) | Op::Elu(a, _) | Op::Softplus(a, _) | Op::Mish(a) => {
+                // d/da f(a) = f'(a) * grad
+                let ga = activation_grad(&graph.get(node_id).op, values.value(&graph, a));
+                let ga = ga.mul(&grad).expect("activation grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::SumAll(a) => {
+                // Gradient of sum: ones with the shape of a
+                let ga = Tensor::ones(graph.get(a).shape.clone());
+                let ga = ga.mul_scalar(grad.item().unwrap_or(T::ONE));
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::MeanAll(a) => {
+                let numel = values.value(&graph, a).numel();
+                let scale = T::ONE / T::from_usize(numel);
+                let ga = Tensor::full(graph.get(a).shape.clone(), scale);
+                let ga = ga.mul_scalar(grad.item().unwrap_or(T::ONE));
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Transpose(a) => {
+                let ga = grad.t().expect("transpose grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::MulScalar(a, s) => {
+                let ga = grad.mul_scalar(T::from_f64(s));
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::AddScalar(a, _s) => {
+                accumulate_grad(&mut grads, a, &grad, &graph.get(a).shape);
+            }
+            Op::SumTo(a) => {
+                // Broadcast the gradient back up to a's shape
+                let ga = Tensor::zeros(graph.get(a).shape.clone()).add(&grad).expect("sum_to grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::SumAxis(a, axis) | Op::MeanAxis(a, axis) | Op::MaxAxis(a, axis) => {
+                // Put the reduced axis back as size 1 and broadcast over it
+                let shape = &graph.get(a).shape;
+                let kept = grad.reshape(kept_dims(shape, axis)).expect("axis grad");
+                let mut ga = Tensor::zeros(shape.clone()).add(&kept).expect("axis grad");
+                if let Op::MeanAxis(..) = op {
+                    ga = ga.mul_scalar(T::ONE / T::from_usize(shape[axis]));
+                } else if let Op::MaxAxis(..) = op {
+                    ga = ga.mul(&max_mask(values.value(&graph, a), axis)).expect("max grad");
                 }
-                Op::MulScalar(a, s) => {
-                    let ga = grad.mul_scalar(s);
-                    accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+                accumulate_grad(&mut grads, a, &ga, shape);
+            }
+            Op::Reshape(a) => {
+                let ga = grad.reshape(graph.get(a).shape.clone()).expect("reshape grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Concat(ids, axis) => {
+                // Each input gets its own stretch of the concatenated axis
+                let mut offset = 0;
+                for id in ids {
+                    let len = graph.get(id).shape[axis];
+                    let range: Vec<usize> = (offset..offset + len).collect();
+                    let ga = grad.index_select(axis, &range).expect("concat grad");
+                    accumulate_grad(&mut grads, id, &ga, &graph.get(id).shape);
+                    offset += len;
                 }
-                Op::AddScalar(a, _s) => {
-                    accumulate_grad(&mut grads, a, &grad, &graph.get(a).shape);
+            }
+            Op::IndexSelect(a, axis, indices) => {
+                let ga = index_add(&grad, axis, &indices, graph.get(a).shape[axis]);
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::IndexAdd(a, axis, indices) => {
+                let ga = grad.index_select(axis, &indices).expect("index_add grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Softmax(a) => {
+                // d/da softmax(a) = y * (grad - Σ grad * y)
+                let y = values.value(&graph, node_id);
+                let dot = sum_last_kept(&grad.mul(y).expect("softmax grad"));
+                let ga = y.mul(&grad.sub(&dot).expect("softmax grad")).expect("softmax grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::LogSoftmax(a) => {
+                // d/da log_softmax(a) = grad - softmax(a) * Σ grad
+                let sm = values.value(&graph, node_id).exp();
+                let ga = grad.sub(&sm.mul(&sum_last_kept(&grad)).expect("log_softmax grad")).expect("log_softmax grad");
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::CrossEntropy(a, targets) => {
+                // d/da = (softmax(a) - one_hot) / batch
+                let logits = values.value(&graph, a);
+                let scale = grad.item().unwrap_or(T::ONE) / T::from_usize(targets.len());
+                let ga = logits.softmax().expect("cross_entropy grad")
+                    .sub(&targets_one_hot(logits, &targets)).expect("cross_entropy grad")
+                    .mul_scalar(scale);
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Im2Col(a, geometry) => {
+                let ga = col2im(&grad, &geometry);
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::Col2Im(a, geometry) => {
+                let ga = im2col(&grad, &geometry);
+                accumulate_grad(&mut grads, a, &ga, &graph.get(a).shape);
+            }
+            Op::MaxPool2d(a, argmax) => {
+                // Each output's gradient goes to the input it was taken from
+                let shape = &graph.get(a).shape;
+                let flat = grad.reshape(vec![argmax.len()]).expect("max_pool2d grad");
+                let ga = index_add(&flat, 0, &argmax, shape.iter().product());
+                let ga = ga.reshape(shape.clone()).expect("max_pool2d grad");
+                accumulate_grad(&mut grads, a, &ga, shape);
+            }
+        }
+    }
+
+    grads
+}
+
+/// Gradients of `output` with respect to each of `inputs`, recorded as
+/// graph operations.
+///
+/// Unlike `backward`, the returned gradients are themselves variables on
+/// the output's graph, so they can be combined into a new loss and
+/// differentiated again: gradient penalties, Hessian-vector products,
+/// meta-learning. An input `output` does not depend on gets zeros.
+pub fn grad<T: Float>(output: &Variable<T>, inputs: &[&Variable<T>]) -> Vec<Variable<T>> {
+    if !output.is_tracked() {
+        return inputs.iter().map(|x| output.constant(Tensor::zeros(x.shape_vec()))).collect();
+    }
+    // Snapshot the forward tape: the backward ops are appended to the same
+    // graph, which needs the write lock.
+    let (mut nodes, recomputed) = {
+        let tape = output.graph.read();
+        let mut values = Recomputed::new();
+        for id in 0..=output.node_id.0 {
+            values.ensure(&tape, NodeId(id));
+        }
+        (tape.nodes[..=output.node_id.0].to_vec(), values.into_values())
+    };
+    for (id, value) in recomputed {
+        nodes[id.0].value = value;
+    }
+    let var = |id: NodeId| Variable {
+        node_id: id,
+        data: nodes[id.0].value.clone(),
+        graph: output.graph.clone(),
+    };
+
+    // Only nodes on a path to one of the inputs need a gradient.
+    let mut needed = vec![false; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        needed[i] = inputs.iter().any(|x| x.node_id.0 == i)
+            || node.op.inputs().iter().any(|p| needed[p.0]);
+    }
+
+    let mut grads: HashMap<NodeId, Variable<T>> = HashMap::new();
+    if needed[output.node_id.0] {
+        grads.insert(output.node_id, output.constant(Tensor::ones(output.shape_vec())));
+    }
+
+    for idx in (0..nodes.len()).rev() {
+        let node_id = NodeId(idx);
+        let g = match grads.get(&node_id) {
+            Some(g) => g.clone(),
+            None => continue,
+        };
+        let mut push = |id: NodeId, ga: Variable<T>| {
+            if !needed[id.0] {
+                return;
+            }
+            let ga = ga.sum_to(&nodes[id.0].shape);
+            let total = match grads.remove(&id) {
+                Some(existing) => existing.add(&ga),
+                None => ga,
+            };
+            grads.insert(id, total);
+        };
+
+        match nodes[idx].op.clone() {
+            Op::Leaf => {}
+            Op::Add(a, b) => {
+                push(a, g.clone());
+                push(b, g);
+            }
+            Op::Sub(a, b) => {
+                push(a, g.clone());
+                push(b, g.neg());
+            }
+            Op::Mul(a, b) => {
+                push(a, g.mul(&var(b)));
+                push(b, g.mul(&var(a)));
+            }
+            Op::Div(a, b) => {
+                let (va, vb) = (var(a), var(b));
+                push(a, g.div(&vb));
+                push(b, g.mul(&va).neg().div(&vb.mul(&vb)));
+            }
+            Op::MatMul(a, b) => {
+                push(a, g.matmul(&var(b).t()));
+                push(b, var(a).t().matmul(&g));
+            }
+            Op::Neg(a) => push(a, g.neg()),
+            Op::Exp(a) => push(a, var(node_id).mul(&g)),
+            Op::Ln(a) => push(a, g.div(&var(a))),
+            Op::Pow(a, n) => push(a, var(a).pow(n - 1.0).mul_scalar(n).mul(&g)),
+            Op::Relu(a) => {
+                // The mask is piecewise constant, so it carries no gradient
+                let mask = nodes[a.0].value.apply(|x| if x > T::ZERO { T::ONE } else { T::ZERO });
+                push(a, g.mul(&output.constant(mask)));
+            }
+            Op::Sigmoid(a) => {
+                let sig = var(node_id);
+                push(a, sig.mul(&sig.neg().add_scalar(1.0)).mul(&g));
+            }
+            Op::Tanh(a) => {
+                let th = var(node_id);
+                push(a, th.mul(&th).neg().add_scalar(1.0).mul(&g));
+            }
+            Op::Erf(a) => {
+                let x = var(a);
+                push(a, x.mul(&x).neg().exp().mul_scalar(2.0 / PI.sqrt()).mul(&g));
+            }
+            Op::Gelu(a) => {
+                // Φ(x) + x·φ(x)
+                let x = var(a);
+                let cdf = x.mul_scalar(FRAC_1_SQRT_2).erf().add_scalar(1.0).mul_scalar(0.5);
+                let pdf = x.mul(&x).mul_scalar(-0.5).exp().mul_scalar(1.0 / (2.0 * PI).sqrt());
+                push(a, cdf.add(&x.mul(&pdf)).mul(&g));
+            }
+            Op::GeluTanh(a) => {
+                // 0.5·(1 + t) + 0.5·x·(1 - t²)·u', t = tanh(u), u = c·(x + 0.044715·x³)
+                let x = var(a);
+                let c = (2.0 / PI).sqrt();
+                let t = x.pow(3.0).mul_scalar(0.044715).add(&x).mul_scalar(c).tanh_act();
+                let du = x.mul(&x).mul_scalar(3.0 * 0.044715 * c).add_scalar(c);
+                let slope = x.mul_scalar(0.5).mul(&t.mul(&t).neg().add_scalar(1.0)).mul(&du);
+                push(a, t.add_scalar(1.0).mul_scalar(0.5).add(&slope).mul(&g));
+            }
+            Op::Silu(a) => {
+                // σ(x)·(1 + x·(1 - σ(x)))
+                let x = var(a);
+                let s = x.sigmoid();
+                push(a, s.mul(&x.mul(&s.neg().add_scalar(1.0)).add_scalar(1.0)).mul(&g));
+            }
+            Op::Elu(a, alpha) => {
+                // 1 for x > 0, alpha·exp(x) otherwise; the branch mask carries no gradient
+                let positive = nodes[a.0].value.apply(|x| if x > T::ZERO { T::ONE } else { T::ZERO });
+                let negative = positive.apply(|m| T::ONE - m);
+                let slope = var(a).exp().mul_scalar(alpha).mul(&output.constant(negative));
+                push(a, slope.add(&output.constant(positive)).mul(&g));
+            }
+            Op::Softplus(a, beta) => push(a, var(a).mul_scalar(beta).sigmoid().mul(&g)),
+            Op::Mish(a) => {
+                // tanh(sp(x)) + x·(1 - tanh²(sp(x)))·σ(x)
+                let x = var(a);
+                let t = x.softplus(1.0).tanh_act();
+                let slope = x.mul(&t.mul(&t).neg().add_scalar(1.0)).mul(&x.sigmoid());
+                push(a, t.add(&slope).mul(&g));
+            }
+            Op::SumAll(a) => push(a, broadcast_to(&g, &nodes[a.0].shape)),
+            Op::MeanAll(a) => {
+                let scale = 1.0 / nodes[a.0].value.numel() as f64;
+                push(a, broadcast_to(&g, &nodes[a.0].shape).mul_scalar(scale));
+            }
+            Op::Transpose(a) => push(a, g.t()),
+            Op::MulScalar(a, s) => push(a, g.mul_scalar(s)),
+            Op::AddScalar(a, _s) => push(a, g),
+            Op::SumTo(a) => push(a, broadcast_to(&g, &nodes[a.0].shape)),
+            Op::SumAxis(a, axis) => {
+                let shape = &nodes[a.0].shape;
+                push(a, broadcast_to(&g.reshape(kept_dims(shape, axis)), shape));
+            }
+            Op::MeanAxis(a, axis) => {
+                let shape = &nodes[a.0].shape;
+                let ga = broadcast_to(&g.reshape(kept_dims(shape, axis)), shape);
+                push(a, ga.mul_scalar(1.0 / shape[axis] as f64));
+            }
+            Op::MaxAxis(a, axis) => {
+                let shape = &nodes[a.0].shape;
+                let mask = output.constant(max_mask(&nodes[a.0].value, axis));
+                push(a, broadcast_to(&g.reshape(kept_dims(shape, axis)), shape).mul(&mask));
+            }
+            Op::Reshape(a) => push(a, g.reshape(nodes[a.0].shape.clone())),
+            Op::Concat(ids, axis) => {
+                let mut offset = 0;
+                for id in ids {
+                    let len = nodes[id.0].shape[axis];
+                    push(id, g.slice(axis, offset, offset + len));
+                    offset += len;
                 }
             }
+            Op::IndexSelect(a, axis, indices) => push(a, g.index_add(axis, &indices, nodes[a.0].shape[axis])),
+            Op::IndexAdd(a, axis, indices) => push(a, g.index_select(axis, &indices)),
+            Op::Softmax(a) => {
+                let y = var(node_id);
+                let last = y.shape_vec().len() - 1;
+                let dot = g.mul(&y).sum_axis(last).reshape(kept_dims(&nodes[a.0].shape, last));
+                push(a, y.mul(&g.sub(&dot)));
+            }
+            Op::LogSoftmax(a) => {
+                let sm = var(node_id).exp();
+                let last = sm.shape_vec().len() - 1;
+                let total = g.sum_axis(last).reshape(kept_dims(&nodes[a.0].shape, last));
+                push(a, g.sub(&sm.mul(&total)));
+            }
+            Op::CrossEntropy(a, targets) => {
+                let one_hot = output.constant(targets_one_hot(&nodes[a.0].value, &targets));
+                let ga = var(a).softmax().sub(&one_hot).mul_scalar(1.0 / targets.len() as f64);
+                push(a, ga.mul(&g));
+            }
+            Op::Im2Col(a, geometry) => push(a, g.col2im(&geometry)),
+            Op::Col2Im(a, geometry) => push(a, g.im2col(&geometry)),
+            Op::MaxPool2d(a, argmax) => {
+                let shape = nodes[a.0].shape.clone();
+                let flat = g.reshape(vec![argmax.len()]).index_add(0, &argmax, shape.iter().product());
+                push(a, flat.reshape(shape));
+            }
         }
+    }
 
-        grads
-    })
+    inputs
+        .iter()
+        .map(|x| {
+            grads
+                .get(&x.node_id)
+                .cloned()
+                .unwrap_or_else(|| output.constant(Tensor::zeros(x.shape_vec())))
+        })
+        .collect()
+}
+
+/// Differentiable broadcast of `g` up to `shape`.
+fn broadcast_to<T: Float>(g: &Variable<T>, shape: &[usize]) -> Variable<T> {
+    g.constant(Tensor::zeros(shape.to_vec())).add(g)
 }
 
 /// Accumulate gradient into the map, handling broadcasting reduction.
-fn accumulate_grad(
-    grads: &mut HashMap<NodeId, Tensor<f64>>,
+fn accumulate_grad<T: Float>(
+    grads: &mut HashMap<NodeId, Tensor<T>>,
     node_id: NodeId,
-    incoming_grad: &Tensor<f64>,
+    incoming_grad: &Tensor<T>,
     target_shape: &[usize],
 ) {
     // Reduce grad if it was broadcast
@@ -164,8 +478,119 @@ fn accumulate_grad(
         .or_insert(grad);
 }
 
+/// `shape` with `axis` set to 1, the shape a reduction over `axis` keeps.
+/// Element-wise derivative of an activation op at its input `x`.
+fn activation_grad<T: Float>(op: &Op, x: &Tensor<T>) -> Tensor<T> {
+    let f = |g: &dyn Fn(f64) -> f64| x.apply(|v| T::from_f64(g(v.to_f64())));
+    let sigmoid = |v: f64| 1.0 / (1.0 + (-v).exp());
+    match *op {
+        Op::Erf(_) => f(&|v| 2.0 / PI.sqrt() * (-v * v).exp()),
+        Op::Gelu(_) => {
+            // Φ(x) + x·φ(x)
+            let mut d = x.mul_scalar(T::from_f64(FRAC_1_SQRT_2)).erf().add_scalar(T::ONE).mul_scalar(T::from_f64(0.5));
+            d.zip_apply_mut(x, |cdf, v| {
+                let v = v.to_f64();
+                cdf + T::from_f64(v * (-0.5 * v * v).exp() / (2.0 * PI).sqrt())
+            })
+            .expect("gelu grad");
+            d
+        }
+        Op::GeluTanh(_) => f(&|v| {
+            let c = (2.0 / PI).sqrt();
+            let t = (c * (v + 0.044715 * v * v * v)).tanh();
+            0.5 * (1.0 + t) + 0.5 * v * (1.0 - t * t) * c * (1.0 + 3.0 * 0.044715 * v * v)
+        }),
+        Op::Silu(_) => f(&|v| sigmoid(v) * (1.0 + v * (1.0 - sigmoid(v)))),
+        Op::Elu(_, alpha) => f(&|v| if v > 0.0 { 1.0 } else { alpha * v.exp() }),
+        Op::Softplus(_, beta) => f(&|v| sigmoid(beta * v)),
+        Op::Mish(_) => f(&|v| {
+            let sp = v.max(0.0) + (-v.abs()).exp().ln_1p();
+            let t = sp.tanh();
+            t + v * (1.0 - t * t) * sigmoid(v)
+        }),
+        _ => unreachable!("not an activation op"),
+    }
+}
+
+fn kept_dims(shape: &[usize], axis: usize) -> Vec<usize> {
+    let mut kept = shape.to_vec();
+    kept[axis] = 1;
+    kept
+}
+
+/// Sum over the last axis, keeping it as size 1.
+fn sum_last_kept<T: Float>(x: &Tensor<T>) -> Tensor<T> {
+    let shape = x.shape_vec();
+    let last = shape.len() - 1;
+    x.sum_axis(last).and_then(|s| s.reshape(kept_dims(&shape, last))).expect("sum over last axis")
+}
+
+/// Mean of `-log_softmax(logits)[i, targets[i]]` over the rows.
+pub(crate) fn cross_entropy_value<T: Float>(logits: &Tensor<T>, targets: &[usize]) -> Tensor<T> {
+    let classes = logits.shape_vec()[1];
+    let log_probs = logits.log_softmax().expect("log_softmax");
+    let total = targets
+        .iter()
+        .enumerate()
+        .fold(T::ZERO, |acc, (i, &t)| acc - log_probs.data()[i * classes + t]);
+    Tensor::scalar(total / T::from_usize(targets.len()))
+}
+
+/// `[batch, classes]` one-hot rows of the target class ids.
+fn targets_one_hot<T: Float>(logits: &Tensor<T>, targets: &[usize]) -> Tensor<T> {
+    let classes = logits.shape_vec()[1];
+    let mut data = vec![T::ZERO; targets.len() * classes];
+    for (i, &t) in targets.iter().enumerate() {
+        data[i * classes + t] = T::ONE;
+    }
+    Tensor::new(data, vec![targets.len(), classes]).expect("one-hot targets")
+}
+
+/// Where `x` attains its max along `axis`, as weights that split the
+/// gradient evenly between ties.
+fn max_mask<T: Float>(x: &Tensor<T>, axis: usize) -> Tensor<T> {
+    let shape = x.shape_vec();
+    let max = x.max_axis(axis).and_then(|m| m.reshape(kept_dims(&shape, axis))).expect("max mask");
+    let max = Tensor::zeros(shape.clone()).add(&max).expect("max mask");
+    let hits: Vec<T> = x
+        .data()
+        .iter()
+        .zip(max.data())
+        .map(|(&v, &m)| if v == m { T::ONE } else { T::ZERO })
+        .collect();
+    let hits = Tensor::new(hits, shape.clone()).expect("max mask");
+    let counts = hits.sum_axis(axis).and_then(|c| c.reshape(kept_dims(&shape, axis))).expect("max mask");
+    hits.div(&counts).expect("max mask")
+}
+
+/// Add slice `i` of `src` along `axis` into position `indices[i]` of a zero
+/// tensor whose `axis` has length `size`.
+pub(crate) fn index_add<T: Float>(src: &Tensor<T>, axis: usize, indices: &[usize], size: usize) -> Tensor<T> {
+    let dims = src.shape_vec();
+    assert!(axis < dims.len(), "index_add: invalid axis {}", axis);
+    assert_eq!(indices.len(), dims[axis], "index_add: one index per slice");
+    if let Some(&bad) = indices.iter().find(|&&i| i >= size) {
+        panic!("index_add: index {} out of range for size {}", bad, size);
+    }
+    let outer: usize = dims[..axis].iter().product();
+    let inner: usize = dims[axis + 1..].iter().product();
+    let data = src.data();
+    let mut out = vec![T::ZERO; outer * size * inner];
+    for o in 0..outer {
+        for (i, &idx) in indices.iter().enumerate() {
+            let (from, to) = ((o * indices.len() + i) * inner, (o * size + idx) * inner);
+            for j in 0..inner {
+                out[to + j] += data[from + j];
+            }
+        }
+    }
+    let mut out_dims = dims;
+    out_dims[axis] = size;
+    Tensor::new(out, out_dims).expect("index_add shape")
+}
+
 /// Reduce a gradient tensor to match the target shape (undo broadcasting).
-fn reduce_broadcast(grad: &Tensor<f64>, target_shape: &[usize]) -> Tensor<f64> {
+pub(crate) fn reduce_broadcast<T: Float>(grad: &Tensor<T>, target_shape: &[usize]) -> Tensor<T> {
     let grad_shape = grad.shape_vec();
     if grad_shape == target_shape {
         return grad.clone();
@@ -199,7 +624,7 @@ fn reduce_broadcast(grad: &Tensor<f64>, target_shape: &[usize]) -> Tensor<f64> {
     // Final reshape to match target
     if result.shape_vec() != target_shape {
         result = result.reshape(target_shape.to_vec()).unwrap_or_else(|_| {
-            Tensor::full(target_shape.to_vec(), result.sum_all() / result.numel() as f64)
+            Tensor::full(target_shape.to_vec(), result.sum_all() / T::from_usize(result.numel()))
         });
     }
 
@@ -280,4 +705,228 @@ mod tests {
         // σ(0) = 0.5, σ'(0) = 0.5 * 0.5 = 0.25
         assert!((dx.item().unwrap() - 0.25).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_f32_graph_is_separate() {
+        reset_graph();
+
+        let x64 = Variable::param(Tensor::scalar(3.0f64));
+        let x32 = Variable::param(Tensor::scalar(3.0f32));
+        assert_eq!(x64.node_id, x32.node_id); // each type has its own arena
+
+        let y = x32.mul(&x32).mul_scalar(0.5);
+        let grads = backward(&y);
+        let dx: &Tensor<f32> = grads.get(&x32.node_id).unwrap();
+        assert!((dx.item().unwrap() - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_explicit_graph_shared_across_threads() {
+        use crate::graph::Graph;
+
+        let graph = Graph::new();
+        let w = graph.param(Tensor::new(vec![1.0, 2.0], vec![2, 1]).unwrap());
+        // Built on another thread, recorded on the same graph through `w`.
+        let loss = std::thread::spawn({
+            let w = w.clone();
+            move || {
+                let x = w.constant(Tensor::new(vec![3.0, 4.0], vec![1, 2]).unwrap());
+                x.matmul(&w).pow(2.0).sum()
+            }
+        })
+        .join()
+        .unwrap();
+        let grads = backward(&loss);
+        // d/dw (x·w)² = 2 (x·w) x = 22 · [3, 4]
+        assert_eq!(grads.get(&w.node_id).unwrap().data(), &[66.0, 88.0]);
+
+        // Leaves built inside `scope` land on that graph, not the thread's own.
+        let b = graph.scope(|| Variable::param(Tensor::scalar(1.0)));
+        assert!(b.graph.same(&graph));
+        assert!(!Variable::input(Tensor::scalar(1.0)).graph.same(&graph));
+    }
+
+    #[test]
+    fn test_grad_of_grad() {
+        let graph = crate::graph::Graph::new();
+        // y = x³: dy/dx = 3x² = 12, d²y/dx² = 6x = 12 at x = 2
+        let x = graph.param(Tensor::scalar(2.0));
+        let dy = &grad(&x.pow(3.0), &[&x])[0];
+        assert!((dy.data.item().unwrap() - 12.0).abs() < 1e-10);
+        let d2y = &grad(dy, &[&x])[0];
+        assert!((d2y.data.item().unwrap() - 12.0).abs() < 1e-10);
+        assert!((backward(dy)[&x.node_id].item().unwrap() - 12.0).abs() < 1e-10);
+
+        // Hessian-vector product of f(w) = |Aw|², whose Hessian is 2AᵀA.
+        let a = graph.input(Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap());
+        let w = graph.param(Tensor::new(vec![0.5, -1.0], vec![2, 1]).unwrap());
+        let gw = &grad(&a.matmul(&w).pow(2.0).sum(), &[&w])[0];
+        let v = w.constant(Tensor::new(vec![1.0, 0.0], vec![2, 1]).unwrap());
+        let hvp = backward(&gw.mul(&v).sum());
+        assert_eq!(hvp[&w.node_id].data(), &[20.0, 28.0]);
+    }
+
+    #[test]
+    fn test_gradient_penalty_matches_finite_differences() {
+        // P(W) = |∂/∂x Σ tanh(xW + b)|², differentiated with respect to W.
+        let penalty = |w_data: &Tensor<f64>| {
+            let graph = crate::graph::Graph::new();
+            let x = graph.param(Tensor::new(vec![0.3, -0.7, 1.1, 0.2], vec![2, 2]).unwrap());
+            let w = graph.param(w_data.clone());
+            let b = graph.param(Tensor::new(vec![0.1, -0.2], vec![1, 2]).unwrap());
+            let out = x.matmul(&w).add(&b).tanh_act().sum();
+            let gx = &grad(&out, &[&x])[0];
+            let p = gx.pow(2.0).sum();
+            let dw = backward(&p).remove(&w.node_id).unwrap();
+            (p.data.item().unwrap(), dw)
+        };
+        let w = Tensor::new(vec![0.5, -0.4, 0.8, 0.9], vec![2, 2]).unwrap();
+        let (_, dw) = penalty(&w);
+        let h = 1e-6;
+        for i in 0..4 {
+            let (mut up, mut down) = (w.data().to_vec(), w.data().to_vec());
+            up[i] += h;
+            down[i] -= h;
+            let fd = (penalty(&Tensor::new(up, vec![2, 2]).unwrap()).0
+                - penalty(&Tensor::new(down, vec![2, 2]).unwrap()).0)
+                / (2.0 * h);
+            assert!((dw.data()[i] - fd).abs() < 1e-6, "{} vs {}", dw.data()[i], fd);
+        }
+    }
+
+    #[test]
+    fn test_accumulated_grads_match_full_batch() {
+        let graph = crate::graph::Graph::new();
+        let w = graph.param(Tensor::new(vec![0.5, -1.0], vec![2, 1]).unwrap());
+        let x = Tensor::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0], vec![4, 2]).unwrap();
+        let loss = |rows: Tensor<f64>| w.constant(rows).matmul(&w).pow(2.0).sum();
+
+        let full = backward(&loss(x.clone()))[&w.node_id].clone();
+        assert!(w.grad().is_none());
+        loss(Tensor::new(x.data()[..4].to_vec(), vec![2, 2]).unwrap()).backward();
+        loss(Tensor::new(x.data()[4..].to_vec(), vec![2, 2]).unwrap()).backward();
+        assert_eq!(w.grad().unwrap().data(), full.data());
+
+        w.zero_grad();
+        assert!(w.grad().is_none());
+        // Only trainable leaves keep gradients.
+        let y = w.mul_scalar(2.0);
+        y.sum().backward();
+        assert!(y.grad().is_none());
+        assert_eq!(w.grad().unwrap().data(), &[2.0, 2.0]);
+    }
+
+    #[test]
+    fn test_shape_ops_match_finite_differences() {
+        // Every shape op in one loss, checked against central differences,
+        // with `grad` agreeing with `backward`.
+        let f = |x: &Variable| {
+            let top = x.slice(0, 0, 1); // [1, 3]
+            let picked = x.index_select(1, &[2, 0, 2]); // [2, 3]
+            let joined = Variable::concat(&[&top, &picked], 0); // [3, 3]
+            let stacked = Variable::stack(&[&joined, &joined.mul(&joined)], 0); // [2, 3, 3]
+            let rows = stacked.reshape(vec![6, 3]).max_axis(1); // [6]
+            let scattered = rows.reshape(vec![2, 3]).index_add(1, &[1, 0, 1], 4); // [2, 4]
+            scattered.sum_axis(1).mul(&x.mean_axis(1).exp()).sum()
+        };
+        let x0 = Tensor::new(vec![0.3, -1.2, 0.8, 1.5, 0.1, -0.4], vec![2, 3]).unwrap();
+        let graph = crate::graph::Graph::new();
+        let x = graph.param(x0.clone());
+        let out = f(&x);
+        let dx = backward(&out)[&x.node_id].clone();
+        assert_eq!(grad(&out, &[&x])[0].data.data(), dx.data());
+
+        let h = 1e-6;
+        for i in 0..6 {
+            let eval = |delta: f64| {
+                let mut d = x0.data().to_vec();
+                d[i] += delta;
+                f(&graph.input(Tensor::new(d, vec![2, 3]).unwrap())).data.item().unwrap()
+            };
+            let fd = (eval(h) - eval(-h)) / (2.0 * h);
+            assert!((dx.data()[i] - fd).abs() < 1e-5, "{}: {} vs {}", i, dx.data()[i], fd);
+        }
+    }
+
+    #[test]
+    fn test_softmax_ops_and_fused_cross_entropy() {
+        let graph = crate::graph::Graph::new();
+        let logits = Tensor::new(vec![2.0, -1.0, 0.5, 0.1, 3.0, -2.0], vec![2, 3]).unwrap();
+        let x = graph.param(logits.clone());
+        let targets = [0, 2];
+
+        // The fused op agrees with the composed log_softmax in value and grad.
+        let fused = x.cross_entropy(&targets);
+        let one_hot = x.constant(Tensor::new(vec![1.0, 0.0, 0.0, 0.0, 0.0, 1.0], vec![2, 3]).unwrap());
+        let composed = x.log_softmax().mul(&one_hot).sum().mul_scalar(-0.5);
+        assert!((fused.data.item().unwrap() - composed.data.item().unwrap()).abs() < 1e-12);
+        let (gf, gc) = (backward(&fused), backward(&composed));
+        for (a, b) in gf[&x.node_id].data().iter().zip(gc[&x.node_id].data()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+
+        // Softmax backward against central differences of a weighted sum.
+        let w = [0.3, -1.0, 2.0, 0.7, 0.2, -0.5];
+        let f = |t: Tensor<f64>| {
+            let s = graph.input(t).softmax();
+            s.mul(&s.constant(Tensor::new(w.to_vec(), vec![2, 3]).unwrap())).sum()
+        };
+        let ds = backward(&x.softmax().mul(&x.constant(Tensor::new(w.to_vec(), vec![2, 3]).unwrap())).sum());
+        for i in 0..6 {
+            let (mut up, mut down) = (logits.data().to_vec(), logits.data().to_vec());
+            up[i] += 1e-6;
+            down[i] -= 1e-6;
+            let fd = (f(Tensor::new(up, vec![2, 3]).unwrap()).data.item().unwrap()
+                - f(Tensor::new(down, vec![2, 3]).unwrap()).data.item().unwrap())
+                / 2e-6;
+            assert!((ds[&x.node_id].data()[i] - fd).abs() < 1e-6);
+        }
+        // Second derivatives pass through the fused op too.
+        let dx = &grad(&fused, &[&x])[0];
+        assert!(backward(&dx.pow(2.0).sum())[&x.node_id].data().iter().all(|v| v.is_finite()));
+
+        // Logits far apart still give a finite loss and gradient.
+        let wide = graph.param(Tensor::new(vec![1000.0, -1000.0], vec![1, 2]).unwrap());
+        let loss = wide.cross_entropy(&[1]);
+        assert!((loss.data.item().unwrap() - 2000.0).abs() < 1e-9);
+        assert_eq!(backward(&loss)[&wide.node_id].data(), &[1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_activations_match_finite_differences() {
+        type Act = fn(&Variable) -> Variable;
+        let acts: [(&str, Act); 7] = [
+            ("erf", |x| x.erf()),
+            ("gelu", |x| x.gelu()),
+            ("gelu_tanh", |x| x.gelu_tanh()),
+            ("silu", |x| x.silu()),
+            ("elu", |x| x.elu(0.7)),
+            ("softplus", |x| x.softplus(2.0)),
+            ("mish", |x| x.mish()),
+        ];
+        let points = vec![-2.5, -0.8, -0.1, 0.3, 1.2, 3.0];
+        let graph = crate::graph::Graph::new();
+        // First derivative by `backward`, and a differentiable one by `grad`.
+        let first = |f: &dyn Fn(&Variable) -> Variable, at: &[f64]| {
+            let x = graph.param(Tensor::new(at.to_vec(), vec![at.len()]).unwrap());
+            let d = grad(&f(&x).sum(), &[&x]).remove(0);
+            let second = backward(&d.sum()).remove(&x.node_id).unwrap();
+            (backward(&f(&x).sum())[&x.node_id].clone(), d.data, second)
+        };
+        let h = 1e-5;
+        for (name, f) in acts {
+            let (d, d_graph, d2) = first(&f, &points);
+            assert!(d.data().iter().zip(d_graph.data()).all(|(a, b)| (a - b).abs() < 1e-12), "{}", name);
+            let shift = |delta: f64| points.iter().map(|p| p + delta).collect::<Vec<_>>();
+            let value = |at: Vec<f64>| f(&graph.input(Tensor::new(at, vec![6]).unwrap())).data;
+            let (up, down) = (value(shift(h)), value(shift(-h)));
+            let (d_up, d_down) = (first(&f, &shift(h)).0, first(&f, &shift(-h)).0);
+            for i in 0..6 {
+                let fd = (up.data()[i] - down.data()[i]) / (2.0 * h);
+                assert!((d.data()[i] - fd).abs() < 1e-5, "{} f' at {}: {} vs {}", name, points[i], d.data()[i], fd);
+                let fd2 = (d_up.data()[i] - d_down.data()[i]) / (2.0 * h);
+                assert!((d2.data()[i] - fd2).abs() < 1e-4, "{} f'' at {}: {} vs {}", name, points[i], d2.data()[i], fd2);
+            }
+        }
+    }
 }
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use oxidize_ml_core::{Float, Tensor};
+use crate::backward::{cross_entropy_value, index_add, reduce_broadcast};
+use crate::conv::{col2im, im2col};
+use crate::graph::{NodeId, Op, Tape};
+use crate::variable::Variable;
+
+/// Gradient checkpointing: run `f` on `inputs`, then drop the values of the
+/// intermediate nodes it recorded, keeping only the ops.
+///
+/// The output is an ordinary variable. When `backward` reaches the segment,
+/// the dropped values are recomputed from the recorded ops, used, and freed
+/// again once the pass has moved below the segment, so about one segment's
+/// intermediates are held at a time (`grad` recomputes them all up front).
+/// Leaves created inside `f` (constants, dropout masks) keep their values.
+pub fn checkpoint<T: Float>(
+    f: impl FnOnce(&[Variable<T>]) -> Variable<T>,
+    inputs: &[&Variable<T>],
+) -> Variable<T> {
+    let graph = inputs.first().expect("checkpoint: no inputs").graph.clone();
+    let start = graph.len();
+    let owned: Vec<Variable<T>> = inputs.iter().map(|v| (*v).clone()).collect();
+    let out = f(&owned);
+    if out.is_tracked() && out.node_id.0 > start {
+        graph.discard(start..out.node_id.0);
+    }
+    out
+}
+
+/// Node values recomputed for checkpointed segments during a backward pass.
+pub(crate) struct Recomputed<T: Float> {
+    values: HashMap<NodeId, Tensor<T>>,
+    live: Vec<Range<usize>>,
+}
+
+impl<T: Float> Recomputed<T> {
+    pub(crate) fn new() -> Self {
+        Recomputed { values: HashMap::new(), live: Vec::new() }
+    }
+
+    /// The value of `id`, recomputed if it was checkpointed.
+    pub(crate) fn value<'a>(&'a self, tape: &'a Tape<T>, id: NodeId) -> &'a Tensor<T> {
+        self.values.get(&id).unwrap_or(&tape.get(id).value)
+    }
+
+    /// Make sure `id`'s value is available, replaying its segment if needed.
+    pub(crate) fn ensure(&mut self, tape: &Tape<T>, id: NodeId) {
+        let range = match tape.checkpoint_of(id) {
+            Some(range) if !self.live.contains(range) => range.clone(),
+            _ => return,
+        };
+        self.live.push(range.clone());
+        for j in range {
+            let node = tape.get(NodeId(j));
+            if matches!(node.op, Op::Leaf) {
+                continue;
+            }
+            for input in node.op.inputs() {
+                self.ensure(tape, input);
+            }
+            let value = recompute(&node.op, &node.shape, |k| self.value(tape, k));
+            self.values.insert(node.id, value);
+        }
+    }
+
+    /// Free the segments that start after node `idx`; a reverse pass at
+    /// `idx` can no longer read them.
+    pub(crate) fn release_after(&mut self, idx: usize) {
+        let values = &mut self.values;
+        self.live.retain(|range| {
+            if range.start > idx {
+                for j in range.clone() {
+                    values.remove(&NodeId(j));
+                }
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Every recomputed value, for callers that snapshot the whole tape.
+    pub(crate) fn into_values(self) -> HashMap<NodeId, Tensor<T>> {
+        self.values
+    }
+}
+
+/// Recompute a node's value from its op, the values of its inputs and its
+/// recorded shape.
+fn recompute<'a, T: Float>(op: &Op, shape: &[usize], value: impl Fn(NodeId) -> &'a Tensor<T>) -> Tensor<T> {
+    match op {
+        Op::Leaf => unreachable!("leaves are never discarded"),
+        Op::Add(a, b) => value(*a).add(value(*b)).expect("replay add"),
+        Op::Sub(a, b) => value(*a).sub(value(*b)).expect("replay sub"),
+        Op::Mul(a, b) => value(*a).mul(value(*b)).expect("replay mul"),
+        Op::Div(a, b) => value(*a).div(value(*b)).expect("replay div"),
+        Op::MatMul(a, b) => value(*a).matmul(value(*b)).expect("replay matmul"),
+        Op::Neg(a) => value(*a).mul_scalar(T::NEG_ONE),
+        Op::Exp(a) => value(*a).exp(),
+        Op::Ln(a) => value(*a).ln(),
+        Op::Pow(a, n) => value(*a).powf(T::from_f64(*n)),
+        Op::Relu(a) => value(*a).relu(),
+        Op::Sigmoid(a) => value(*a).sigmoid(),
+        Op::Tanh(a) => value(*a).tanh_elem(),
+        Op::Erf(a) => value(*a).erf(),
+        Op::Gelu(a) => value(*a).gelu(),
+        Op::GeluTanh(a) => value(*a).gelu_tanh(),
+        Op::Silu(a) => value(*a).silu(),
+        Op::Elu(a, alpha) => value(*a).elu(T::from_f64(*alpha)),
+        Op::Softplus(a, beta) => value(*a).softplus(T::from_f64(*beta)),
+        Op::Mish(a) => value(*a).mish(),
+        Op::SumAll(a) => Tensor::scalar(value(*a).sum_all()),
+        Op::MeanAll(a) => Tensor::scalar(value(*a).mean_all()),
+        Op::Transpose(a) => value(*a).t().expect("replay transpose"),
+        Op::MulScalar(a, s) => value(*a).mul_scalar(T::from_f64(*s)),
+        Op::AddScalar(a, s) => value(*a).add_scalar(T::from_f64(*s)),
+        Op::SumTo(a) => reduce_broadcast(value(*a), shape).reshape(shape.to_vec()).expect("replay sum_to"),
+        Op::SumAxis(a, axis) => value(*a).sum_axis(*axis).expect("replay sum_axis"),
+        Op::MeanAxis(a, axis) => value(*a).mean_axis(*axis).expect("replay mean_axis"),
+        Op::MaxAxis(a, axis) => value(*a).max_axis(*axis).expect("replay max_axis"),
+        Op::Reshape(a) => value(*a).reshape(shape.to_vec()).expect("replay reshape"),
+        Op::Concat(ids, axis) => {
+            let tensors: Vec<&Tensor<T>> = ids.iter().map(|&id| value(id)).collect();
+            Tensor::concatenate(&tensors, *axis).expect("replay concat")
+        }
+        Op::IndexSelect(a, axis, indices) => value(*a).index_select(*axis, indices).expect("replay index_select"),
+        Op::IndexAdd(a, axis, indices) => index_add(value(*a), *axis, indices, shape[*axis]),
+        Op::Softmax(a) => value(*a).softmax().expect("replay softmax"),
+        Op::LogSoftmax(a) => value(*a).log_softmax().expect("replay log_softmax"),
+        Op::CrossEntropy(a, targets) => cross_entropy_value(value(*a), targets),
+        Op::Im2Col(a, geometry) => im2col(value(*a), geometry),
+        Op::Col2Im(a, geometry) => col2im(value(*a), geometry),
+        Op::MaxPool2d(a, argmax) => {
+            let x = value(*a).data();
+            Tensor::new(argmax.iter().map(|&i| x[i]).collect(), shape.to_vec()).expect("replay max_pool2d")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backward::{backward, grad};
+    use crate::graph::Graph;
+
+    /// A tanh RNN over `steps` inputs, optionally checkpointing each step.
+    fn unroll(graph: &Graph, steps: usize, checkpointed: bool) -> (Variable, Variable) {
+        let w = graph.param(Tensor::new(vec![0.5, -0.3, 0.8, 0.2], vec![2, 2]).unwrap());
+        let mut h = graph.input(Tensor::new(vec![1.0, -1.0], vec![1, 2]).unwrap());
+        for t in 0..steps {
+            let x = graph.input(Tensor::new(vec![t as f64 * 0.1, 0.2], vec![1, 2]).unwrap());
+            let step = |vs: &[Variable]| vs[0].matmul(&w).add(&vs[1]).tanh_act().mul_scalar(1.1).add_scalar(0.01);
+            h = if checkpointed { checkpoint(step, &[&h, &x]) } else { step(&[h.clone(), x]) };
+        }
+        (w, h.pow(2.0).sum())
+    }
+
+    #[test]
+    fn test_checkpoint_matches_plain_backward_with_less_memory() {
+        let stored = |g: &Graph| g.read().nodes.iter().map(|n| n.value.numel()).sum::<usize>();
+        let (plain, ckpt) = (Graph::new(), Graph::new());
+        let (w1, loss1) = unroll(&plain, 200, false);
+        let (w2, loss2) = unroll(&ckpt, 200, true);
+        assert_eq!(loss1.data.data(), loss2.data.data());
+        assert!(stored(&ckpt) * 2 < stored(&plain));
+
+        let (g1, g2) = (backward(&loss1), backward(&loss2));
+        assert_eq!(g1[&w1.node_id].data(), g2[&w2.node_id].data());
+        assert_eq!(grad(&loss2, &[&w2])[0].data.data(), g2[&w2.node_id].data());
+    }
+}
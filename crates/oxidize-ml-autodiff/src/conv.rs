@@ -0,0 +1,429 @@
+use oxidize_ml_core::{Float, Tensor};
+use crate::graph::Op;
+use crate::variable::Variable;
+
+/// Sliding-window layout of a 2-D convolution or pooling over one
+/// `[channels, height, width]` sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvGeometry {
+    pub channels: usize,
+    pub height: usize,
+    pub width: usize,
+    pub kernel: (usize, usize),
+    pub stride: (usize, usize),
+    pub padding: (usize, usize),
+    /// Spacing between kernel taps; `(1, 1)` is a dense kernel.
+    pub dilation: (usize, usize),
+}
+
+impl ConvGeometry {
+    /// Geometry for windows of `kernel` over `[batch, channels, height, width]`.
+    pub fn new(input_shape: &[usize], kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize)) -> Self {
+        assert_eq!(input_shape.len(), 4, "expected [batch, channels, height, width], got {:?}", input_shape);
+        let geometry = ConvGeometry {
+            channels: input_shape[1],
+            height: input_shape[2],
+            width: input_shape[3],
+            kernel,
+            stride,
+            padding,
+            dilation: (1, 1),
+        };
+        geometry.check();
+        geometry
+    }
+
+    /// Space the kernel taps `dilation` apart.
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        self.dilation = dilation;
+        self.check();
+        self
+    }
+
+    /// Extent of the (dilated) kernel, `(d·(k - 1) + 1)` per axis.
+    pub fn kernel_extent(&self) -> (usize, usize) {
+        (
+            self.dilation.0 * (self.kernel.0 - 1) + 1,
+            self.dilation.1 * (self.kernel.1 - 1) + 1,
+        )
+    }
+
+    fn check(&self) {
+        let (eh, ew) = self.kernel_extent();
+        assert!(
+            self.height + 2 * self.padding.0 >= eh && self.width + 2 * self.padding.1 >= ew,
+            "kernel extent {:?} larger than padded input {:?}",
+            (eh, ew),
+            (self.height, self.width)
+        );
+    }
+
+    /// Output `(height, width)`.
+    pub fn out_size(&self) -> (usize, usize) {
+        let (eh, ew) = self.kernel_extent();
+        (
+            (self.height + 2 * self.padding.0 - eh) / self.stride.0 + 1,
+            (self.width + 2 * self.padding.1 - ew) / self.stride.1 + 1,
+        )
+    }
+
+    /// Length of one unrolled window, `channels · kh · kw`.
+    pub fn window_len(&self) -> usize {
+        self.channels * self.kernel.0 * self.kernel.1
+    }
+
+    /// For every window (row of `im2col`) and window position, the flat
+    /// index within the sample it reads, or `None` in the padding.
+    fn for_each_tap(&self, mut f: impl FnMut(usize, usize, Option<usize>)) {
+        let (out_h, out_w) = self.out_size();
+        let (kh, kw) = self.kernel;
+        for oh in 0..out_h {
+            for ow in 0..out_w {
+                let row = oh * out_w + ow;
+                for c in 0..self.channels {
+                    for i in 0..kh {
+                        for j in 0..kw {
+                            let y = (oh * self.stride.0 + i * self.dilation.0) as isize - self.padding.0 as isize;
+                            let x = (ow * self.stride.1 + j * self.dilation.1) as isize - self.padding.1 as isize;
+                            let inside = y >= 0 && x >= 0 && (y as usize) < self.height && (x as usize) < self.width;
+                            let src = inside.then(|| (c * self.height + y as usize) * self.width + x as usize);
+                            f(row, (c * kh + i) * kw + j, src);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Stride, padding, dilation and groups of `Variable::conv2d` and
+/// `Variable::conv_transpose2d`.
+///
+/// With `groups = g`, the input and output channels are split into `g`
+/// groups and each output group only sees its input group; `groups` equal
+/// to the input channels is a depthwise convolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conv2dOptions {
+    pub stride: (usize, usize),
+    pub padding: (usize, usize),
+    pub dilation: (usize, usize),
+    pub groups: usize,
+    /// Extra rows/columns added to one side of a transposed convolution's
+    /// output, to pick among the sizes a strided convolution maps to the
+    /// same input size. Must be smaller than the stride; unused by `conv2d`.
+    pub output_padding: (usize, usize),
+}
+
+impl Conv2dOptions {
+    pub fn new() -> Self {
+        Conv2dOptions { stride: (1, 1), padding: (0, 0), dilation: (1, 1), groups: 1, output_padding: (0, 0) }
+    }
+
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: (usize, usize)) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        self.dilation = dilation;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: usize) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    pub fn with_output_padding(mut self, output_padding: (usize, usize)) -> Self {
+        self.output_padding = output_padding;
+        self
+    }
+}
+
+impl Default for Conv2dOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Unroll the windows of `x` (`[batch, channels, height, width]`) into
+/// rows: `[batch · out_h · out_w, channels · kh · kw]`, zeros in the padding.
+pub fn im2col<T: Float>(x: &Tensor<T>, geometry: &ConvGeometry) -> Tensor<T> {
+    let sample = geometry.channels * geometry.height * geometry.width;
+    let batch = x.numel() / sample;
+    let (out_h, out_w) = geometry.out_size();
+    let (rows, cols) = (out_h * out_w, geometry.window_len());
+    let data = x.data();
+    let mut out = vec![T::ZERO; batch * rows * cols];
+    for b in 0..batch {
+        let (src, dst) = (&data[b * sample..(b + 1) * sample], &mut out[b * rows * cols..(b + 1) * rows * cols]);
+        geometry.for_each_tap(|row, col, idx| {
+            if let Some(idx) = idx {
+                dst[row * cols + col] = src[idx];
+            }
+        });
+    }
+    Tensor::new(out, vec![batch * rows, cols]).expect("im2col")
+}
+
+/// The adjoint of `im2col`: add every unrolled value back into the input
+/// position it was read from, giving `[batch, channels, height, width]`.
+pub fn col2im<T: Float>(cols: &Tensor<T>, geometry: &ConvGeometry) -> Tensor<T> {
+    let sample = geometry.channels * geometry.height * geometry.width;
+    let (out_h, out_w) = geometry.out_size();
+    let (rows, width) = (out_h * out_w, geometry.window_len());
+    let batch = cols.numel() / (rows * width);
+    let data = cols.data();
+    let mut out = vec![T::ZERO; batch * sample];
+    for b in 0..batch {
+        let (src, dst) = (&data[b * rows * width..(b + 1) * rows * width], &mut out[b * sample..(b + 1) * sample]);
+        geometry.for_each_tap(|row, col, idx| {
+            if let Some(idx) = idx {
+                dst[idx] += src[row * width + col];
+            }
+        });
+    }
+    Tensor::new(out, vec![batch, geometry.channels, geometry.height, geometry.width]).expect("col2im")
+}
+
+/// Max over each window of `x`, per channel, with the flat input index of
+/// the first maximum of every window.
+fn max_pool<T: Float>(x: &Tensor<T>, geometry: &ConvGeometry) -> (Tensor<T>, Vec<usize>) {
+    let shape = x.shape_vec();
+    let (out_h, out_w) = geometry.out_size();
+    let (planes, plane) = (shape[0] * shape[1], geometry.height * geometry.width);
+    let single = ConvGeometry { channels: 1, ..geometry.clone() };
+    let data = x.data();
+    let mut values = vec![T::NEG_INFINITY; planes * out_h * out_w];
+    let mut argmax = vec![0; planes * out_h * out_w];
+    for p in 0..planes {
+        let base = p * out_h * out_w;
+        single.for_each_tap(|row, _, idx| {
+            if let Some(idx) = idx {
+                let v = data[p * plane + idx];
+                if v > values[base + row] {
+                    values[base + row] = v;
+                    argmax[base + row] = p * plane + idx;
+                }
+            }
+        });
+    }
+    let out = Tensor::new(values, vec![shape[0], shape[1], out_h, out_w]).expect("max_pool2d");
+    (out, argmax)
+}
+
+impl<T: Float> Variable<T> {
+    /// Differentiable `im2col`; its gradient is `col2im`.
+    pub fn im2col(&self, geometry: &ConvGeometry) -> Variable<T> {
+        self.unary(|a| Op::Im2Col(a, geometry.clone()), im2col(&self.data, geometry))
+    }
+
+    /// Differentiable `col2im`; its gradient is `im2col`.
+    pub fn col2im(&self, geometry: &ConvGeometry) -> Variable<T> {
+        self.unary(|a| Op::Col2Im(a, geometry.clone()), col2im(&self.data, geometry))
+    }
+
+    /// 2-D cross-correlation of `[batch, in_ch, h, w]` with a
+    /// `[out_ch, in_ch / groups, kh, kw]` kernel plus an optional `[out_ch]`
+    /// bias, giving `[batch, out_ch, out_h, out_w]`.
+    ///
+    /// Unrolled with `im2col` into one matmul per group, so gradients reach
+    /// the input, kernel and bias.
+    pub fn conv2d(&self, weight: &Variable<T>, bias: Option<&Variable<T>>, options: &Conv2dOptions) -> Variable<T> {
+        let (shape, w_shape) = (self.shape_vec(), weight.shape_vec());
+        let groups = options.groups;
+        assert!(
+            w_shape.len() == 4 && shape.len() == 4 && shape[1] == w_shape[1] * groups && w_shape[0].is_multiple_of(groups),
+            "conv2d: kernel {:?} with {} groups does not match input {:?}",
+            w_shape,
+            groups,
+            shape
+        );
+        let geometry = ConvGeometry::new(&shape, (w_shape[2], w_shape[3]), options.stride, options.padding)
+            .with_dilation(options.dilation);
+        let (out_h, out_w) = geometry.out_size();
+        let out_ch = w_shape[0];
+
+        // Columns are ordered by input channel, so each group's are a
+        // contiguous block, and so are its kernel rows.
+        let cols = self.im2col(&geometry);
+        let (group_cols, group_out) = (geometry.window_len() / groups, out_ch / groups);
+        let kernel = weight.reshape(vec![out_ch, group_cols]);
+        let outs: Vec<Variable<T>> = (0..groups)
+            .map(|g| {
+                let x = if groups == 1 { cols.clone() } else { cols.slice(1, g * group_cols, (g + 1) * group_cols) };
+                let w = if groups == 1 { kernel.clone() } else { kernel.slice(0, g * group_out, (g + 1) * group_out) };
+                x.matmul(&w.t())
+            })
+            .collect();
+        let mut out = if groups == 1 { outs[0].clone() } else { Variable::concat(&outs.iter().collect::<Vec<_>>(), 1) };
+        if let Some(bias) = bias {
+            out = out.add(&bias.reshape(vec![1, out_ch]));
+        }
+        out.reshape(vec![shape[0], out_h * out_w, out_ch]).t().reshape(vec![shape[0], out_ch, out_h, out_w])
+    }
+
+    /// Transposed 2-D convolution (the gradient of `conv2d` with respect to
+    /// its input) of `[batch, in_ch, h, w]` with an
+    /// `[in_ch, out_ch / groups, kh, kw]` kernel plus an optional
+    /// `[out_ch]` bias. Upsamples by the stride:
+    /// `out = (h - 1)·s - 2p + d·(k - 1) + output_padding + 1`.
+    pub fn conv_transpose2d(&self, weight: &Variable<T>, bias: Option<&Variable<T>>, options: &Conv2dOptions) -> Variable<T> {
+        let (shape, w_shape) = (self.shape_vec(), weight.shape_vec());
+        let groups = options.groups;
+        assert!(
+            w_shape.len() == 4 && shape.len() == 4 && shape[1] == w_shape[0] && w_shape[0].is_multiple_of(groups),
+            "conv_transpose2d: kernel {:?} with {} groups does not match input {:?}",
+            w_shape,
+            groups,
+            shape
+        );
+        let (op, s) = (options.output_padding, options.stride);
+        assert!(op.0 < s.0.max(options.dilation.0) && op.1 < s.1.max(options.dilation.1), "output_padding must be smaller than stride or dilation");
+        let (batch, in_ch, h, w) = (shape[0], shape[1], shape[2], shape[3]);
+        let (kh, kw) = (w_shape[2], w_shape[3]);
+        let out_ch = w_shape[1] * groups;
+        let size = |n: usize, s: usize, p: usize, d: usize, k: usize, op: usize| (n - 1) * s + d * (k - 1) + op + 1 - 2 * p;
+        let out_h = size(h, s.0, options.padding.0, options.dilation.0, kh, op.0);
+        let out_w = size(w, s.1, options.padding.1, options.dilation.1, kw, op.1);
+        // The windows of a convolution over the output land exactly on the
+        // input's pixels, so scattering them back with col2im transposes it.
+        let geometry = ConvGeometry::new(&[batch, out_ch, out_h, out_w], (kh, kw), s, options.padding)
+            .with_dilation(options.dilation);
+        debug_assert_eq!(geometry.out_size(), (h, w));
+
+        let rows = self.reshape(vec![batch, in_ch, h * w]).t().reshape(vec![batch * h * w, in_ch]);
+        let (group_in, group_cols) = (in_ch / groups, geometry.window_len() / groups);
+        let kernel = weight.reshape(vec![in_ch, group_cols]);
+        let cols: Vec<Variable<T>> = (0..groups)
+            .map(|g| {
+                let x = if groups == 1 { rows.clone() } else { rows.slice(1, g * group_in, (g + 1) * group_in) };
+                let k = if groups == 1 { kernel.clone() } else { kernel.slice(0, g * group_in, (g + 1) * group_in) };
+                x.matmul(&k)
+            })
+            .collect();
+        let cols = if groups == 1 { cols[0].clone() } else { Variable::concat(&cols.iter().collect::<Vec<_>>(), 1) };
+        let out = cols.col2im(&geometry);
+        match bias {
+            Some(bias) => out.add(&bias.reshape(vec![1, out_ch, 1, 1])),
+            None => out,
+        }
+    }
+
+    /// Max pooling over `[batch, channels, h, w]`; padded taps never win.
+    /// The gradient of each output goes to the first maximum of its window.
+    pub fn max_pool2d(&self, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize)) -> Variable<T> {
+        let geometry = ConvGeometry::new(&self.shape_vec(), kernel, stride, padding);
+        let (out, argmax) = max_pool(&self.data, &geometry);
+        self.unary(|a| Op::MaxPool2d(a, argmax), out)
+    }
+
+    /// Average pooling over `[batch, channels, h, w]`.
+    pub fn avg_pool2d(&self, kernel: (usize, usize), stride: (usize, usize)) -> Variable<T> {
+        let shape = self.shape_vec();
+        let planes = self.reshape(vec![shape[0] * shape[1], 1, shape[2], shape[3]]);
+        let geometry = ConvGeometry::new(&planes.shape_vec(), kernel, stride, (0, 0));
+        let (out_h, out_w) = geometry.out_size();
+        planes.im2col(&geometry).mean_axis(1).reshape(vec![shape[0], shape[1], out_h, out_w])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backward::{backward, grad};
+    use crate::graph::Graph;
+
+    #[test]
+    fn test_conv2d_matches_finite_differences() {
+        let x0: Tensor<f64> = Tensor::rand(vec![2, 2, 5, 4], Some(1));
+        let w0: Tensor<f64> = Tensor::rand(vec![3, 2, 3, 2], Some(2));
+        let graph = Graph::new();
+        let loss = |x: &Variable, w: &Variable, b: &Variable| {
+            let out = x.conv2d(w, Some(b), &Conv2dOptions::new().with_stride((2, 1)).with_padding((1, 1)));
+            out.mul(&out.constant(Tensor::rand(out.shape_vec(), Some(3)))).sum()
+        };
+        let (x, w) = (graph.param(x0.clone()), graph.param(w0.clone()));
+        let b = graph.param(Tensor::new(vec![0.1, -0.2, 0.3], vec![3]).unwrap());
+        let out = loss(&x, &w, &b);
+        let grads = backward(&out);
+        // `grad` (through col2im/im2col) agrees with `backward`.
+        let dx = &grad(&out, &[&x])[0];
+        for (a, b) in dx.data.data().iter().zip(grads[&x.node_id].data()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+
+        let h = 1e-6;
+        for (param, value, is_x) in [(&x, &x0, true), (&w, &w0, false)] {
+            for i in (0..value.numel()).step_by(5) {
+                let eval = |delta: f64| {
+                    let mut d = value.data().to_vec();
+                    d[i] += delta;
+                    let moved = graph.input(Tensor::new(d, value.shape_vec()).unwrap());
+                    let (xs, ws) = if is_x { (&moved, &w) } else { (&x, &moved) };
+                    loss(xs, ws, &b).data.item().unwrap()
+                };
+                let fd = (eval(h) - eval(-h)) / (2.0 * h);
+                let g = grads[&param.node_id].data()[i];
+                assert!((g - fd).abs() < 1e-6, "{}: {} vs {}", i, g, fd);
+            }
+        }
+        // Each bias gradient sums that channel's output weights.
+        assert_eq!(grads[&b.node_id].shape_vec(), vec![3]);
+    }
+
+    #[test]
+    fn test_max_pool_routes_to_argmax() {
+        let graph = Graph::new();
+        let x = graph.param(Tensor::new(vec![1.0, 3.0, 2.0, 0.0, 5.0, 5.0, 4.0, 1.0, 0.0], vec![1, 1, 3, 3]).unwrap());
+        let out = x.max_pool2d((2, 2), (1, 1), (0, 0));
+        assert_eq!(out.data.data(), &[5.0, 5.0, 5.0, 5.0]);
+        // Ties go to the first maximum; in every window that is index 4.
+        let grads = backward(&out.sum());
+        assert_eq!(grads[&x.node_id].data(), &[0.0, 0.0, 0.0, 0.0, 4.0, 0.0, 0.0, 0.0, 0.0]);
+        let dx = &grad(&out.pow(2.0).sum(), &[&x])[0];
+        assert_eq!(dx.data.data()[4], 40.0);
+        let padded = x.mul_scalar(-1.0).max_pool2d((2, 2), (2, 2), (1, 1));
+        assert_eq!(padded.data.data(), &[-1.0, -2.0, 0.0, 0.0]);
+
+        let avg = x.avg_pool2d((3, 3), (1, 1));
+        assert!((avg.data.item().unwrap() - 21.0 / 9.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_grouped_dilated_and_transposed_convolutions() {
+        let graph = Graph::new();
+        let x = graph.param(Tensor::rand(vec![1, 4, 7, 6], Some(6)));
+        let w = graph.param(Tensor::rand(vec![6, 2, 3, 2], Some(7)));
+        // Two groups equal two independent convolutions on the channel halves.
+        let options = Conv2dOptions::new().with_groups(2).with_dilation((2, 1)).with_padding((1, 0));
+        let grouped = x.conv2d(&w, None, &options);
+        let single = Conv2dOptions::new().with_dilation((2, 1)).with_padding((1, 0));
+        let halves: Vec<Variable> = (0..2)
+            .map(|g| x.slice(1, 2 * g, 2 * g + 2).conv2d(&w.slice(0, 3 * g, 3 * g + 3), None, &single))
+            .collect();
+        let expected = Variable::concat(&[&halves[0], &halves[1]], 1);
+        assert_eq!(grouped.shape_vec(), vec![1, 6, 5, 5]);
+        assert!(grouped.data.data().iter().zip(expected.data.data()).all(|(a, b)| (a - b).abs() < 1e-12));
+
+        // ⟨conv(x), y⟩ = ⟨x, conv_transpose(y)⟩ for the same kernel.
+        let options = Conv2dOptions::new().with_stride((2, 3)).with_padding((1, 1)).with_groups(2).with_output_padding((1, 0));
+        let k = graph.param(Tensor::rand(vec![4, 3, 3, 3], Some(8)));
+        let y = graph.param(Tensor::rand(vec![1, 4, 3, 2], Some(9)));
+        let up = y.conv_transpose2d(&k, None, &options);
+        let image = graph.input(Tensor::rand(up.shape_vec(), Some(10)));
+        let down = image.conv2d(&k, None, &Conv2dOptions { output_padding: (0, 0), ..options });
+        assert_eq!(up.shape_vec(), vec![1, 6, 6, 4]);
+        assert_eq!(down.shape_vec(), y.shape_vec());
+        let lhs = down.mul(&y).sum().data.item().unwrap();
+        let rhs = image.mul(&up).sum().data.item().unwrap();
+        assert!((lhs - rhs).abs() < 1e-10, "{} vs {}", lhs, rhs);
+        let grads = backward(&up.pow(2.0).sum());
+        assert_eq!(grads[&k.node_id].shape_vec(), vec![4, 3, 3, 3]);
+    }
+}
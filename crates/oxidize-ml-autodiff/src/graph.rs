@@ -1,10 +1,23 @@
-use oxidize_ml_core::Tensor;
+use oxidize_ml_core::{Float, Tensor};
+use std::any::{Any, TypeId};
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use crate::conv::ConvGeometry;
+use crate::variable::Variable;
 
 /// Unique identifier for a node in the computation graph.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NodeId(pub usize);
 
+impl NodeId {
+    /// The id of a variable computed under `no_grad`, which has no node.
+    pub const UNTRACKED: NodeId = NodeId(usize::MAX);
+}
+
 /// The operation that produced a node.
 #[derive(Debug, Clone)]
 pub enum Op {
@@ -34,6 +47,20 @@ pub enum Op {
     Sigmoid(NodeId),
     /// Tanh.
     Tanh(NodeId),
+    /// Error function.
+    Erf(NodeId),
+    /// Exact GELU, x·Φ(x).
+    Gelu(NodeId),
+    /// GELU, tanh approximation.
+    GeluTanh(NodeId),
+    /// SiLU (Swish), x·σ(x).
+    Silu(NodeId),
+    /// ELU with the given alpha.
+    Elu(NodeId, f64),
+    /// Softplus with the given beta.
+    Softplus(NodeId, f64),
+    /// Mish, x·tanh(softplus(x)).
+    Mish(NodeId),
     /// Sum all elements to scalar.
     SumAll(NodeId),
     /// Mean all elements.
@@ -44,34 +71,197 @@ pub enum Op {
     MulScalar(NodeId, f64),
     /// Add scalar.
     AddScalar(NodeId, f64),
+    /// Sum down to this node's (broadcast-compatible) shape.
+    SumTo(NodeId),
+    /// Sum along an axis, removing it.
+    SumAxis(NodeId, usize),
+    /// Mean along an axis, removing it.
+    MeanAxis(NodeId, usize),
+    /// Max along an axis, removing it.
+    MaxAxis(NodeId, usize),
+    /// Reshape to this node's shape.
+    Reshape(NodeId),
+    /// Concatenation along an axis.
+    Concat(Vec<NodeId>, usize),
+    /// Entries at the given positions along an axis.
+    IndexSelect(NodeId, usize, Vec<usize>),
+    /// Slices along an axis added into the given positions of a zero tensor
+    /// with this node's shape (the reverse of `IndexSelect`).
+    IndexAdd(NodeId, usize, Vec<usize>),
+    /// Softmax along the last axis.
+    Softmax(NodeId),
+    /// Log-softmax along the last axis.
+    LogSoftmax(NodeId),
+    /// Mean cross-entropy of `[batch, classes]` logits against class ids.
+    CrossEntropy(NodeId, Vec<usize>),
+    /// Convolution windows unrolled into rows.
+    Im2Col(NodeId, ConvGeometry),
+    /// Unrolled windows summed back into an image (the adjoint of `Im2Col`).
+    Col2Im(NodeId, ConvGeometry),
+    /// Max pooling; the flat input index each output was taken from.
+    MaxPool2d(NodeId, Vec<usize>),
+}
+
+impl Op {
+    /// The nodes this operation reads.
+    pub fn inputs(&self) -> Vec<NodeId> {
+        match *self {
+            Op::Leaf => vec![],
+            Op::Add(a, b) | Op::Sub(a, b) | Op::Mul(a, b) | Op::Div(a, b) | Op::MatMul(a, b) => vec![a, b],
+            Op::Concat(ref ids, _) => ids.clone(),
+            Op::Neg(a)
+            | Op::Exp(a)
+            | Op::Ln(a)
+            | Op::Pow(a, _)
+            | Op::Relu(a)
+            | Op::Sigmoid(a)
+            | Op::Tanh(a)
+            | Op::Erf(a)
+            | Op::Gelu(a)
+            | Op::GeluTanh(a)
+            | Op::Silu(a)
+            | Op::Elu(a, _)
+            | Op::Softplus(a, _)
+            | Op::Mish(a)
+            | Op::SumAll(a)
+            | Op::MeanAll(a)
+            | Op::Transpose(a)
+            | Op::MulScalar(a, _)
+            | Op::AddScalar(a, _)
+            | Op::SumTo(a)
+            | Op::SumAxis(a, _)
+            | Op::MeanAxis(a, _)
+            | Op::MaxAxis(a, _)
+            | Op::Reshape(a)
+            | Op::IndexSelect(a, _, _)
+            | Op::IndexAdd(a, _, _)
+            | Op::Softmax(a)
+            | Op::LogSoftmax(a)
+            | Op::CrossEntropy(a, _)
+            | Op::Im2Col(a, _)
+            | Op::Col2Im(a, _)
+            | Op::MaxPool2d(a, _) => vec![a],
+        }
+    }
 }
 
 /// A node in the computation graph.
 #[derive(Debug, Clone)]
-pub struct Node {
+pub struct Node<T: Float = f64> {
     pub id: NodeId,
     pub op: Op,
     pub shape: Vec<usize>,
-    pub value: Tensor<f64>,
+    pub value: Tensor<T>,
     pub requires_grad: bool,
 }
 
-/// The computation graph — arena of nodes.
+/// The node arena behind a `Graph`, in the order the nodes were recorded,
+/// plus the gradients accumulated on its trainable leaves.
 #[derive(Debug)]
-pub struct Graph {
-    pub nodes: Vec<Node>,
+pub struct Tape<T: Float = f64> {
+    pub nodes: Vec<Node<T>>,
+    pub grads: HashMap<NodeId, Tensor<T>>,
+    /// Node ranges whose non-leaf values were dropped by `checkpoint`.
+    pub checkpoints: Vec<Range<usize>>,
+}
+
+impl<T: Float> Tape<T> {
+    pub fn get(&self, id: NodeId) -> &Node<T> {
+        &self.nodes[id.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The checkpointed range holding `id`, if its value was dropped.
+    pub fn checkpoint_of(&self, id: NodeId) -> Option<&Range<usize>> {
+        if matches!(self.get(id).op, Op::Leaf) {
+            return None;
+        }
+        self.checkpoints.iter().find(|range| range.contains(&id.0))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// The computation graph — a shared, thread-safe arena of nodes.
+///
+/// `Graph` is a handle: clones refer to the same tape, and every `Variable`
+/// carries the handle of the graph it was recorded on, so variables can be
+/// sent to other threads and combined there. Operands of one operation must
+/// share a graph.
+///
+/// Leaves created without an explicit graph (`Variable::param`,
+/// `Variable::input`) go on the calling thread's current graph for their
+/// element type; `scope` makes a given graph current for a closure.
+pub struct Graph<T: Float = f64> {
+    tape: Arc<RwLock<Tape<T>>>,
 }
 
-impl Graph {
+impl<T: Float> Graph<T> {
     pub fn new() -> Self {
-        Graph { nodes: Vec::new() }
+        Graph {
+            tape: Arc::new(RwLock::new(Tape {
+                nodes: Vec::new(),
+                grads: HashMap::new(),
+                checkpoints: Vec::new(),
+            })),
+        }
+    }
+
+    /// The calling thread's current graph for element type `T`.
+    pub fn current() -> Self {
+        CURRENT_GRAPHS.with(|graphs| {
+            graphs
+                .borrow_mut()
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Box::new(Graph::<T>::new()))
+                .downcast_ref::<Graph<T>>()
+                .expect("graph registered under its own TypeId")
+                .clone()
+        })
+    }
+
+    /// Run `f` with this graph as the thread's current graph, so layers and
+    /// leaves built inside it are recorded here.
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = set_current(self.clone());
+        let result = f();
+        set_current(previous);
+        result
+    }
+
+    /// Run `f` with this graph current and recording off (see `no_grad`).
+    pub fn inference_mode<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.scope(|| crate::no_grad::no_grad(f))
+    }
+
+    /// Drop every node recorded after the last trainable leaf, together with
+    /// their stored gradients, so a training loop can call this once per
+    /// step and keep the tape the size of the model. Parameters created
+    /// before the first forward pass survive; other variables recorded on
+    /// this graph must not be used afterwards.
+    pub fn clear(&self) {
+        let mut tape = self.tape.write().expect("graph lock poisoned");
+        let keep = tape
+            .nodes
+            .iter()
+            .rposition(|n| n.requires_grad && matches!(n.op, Op::Leaf))
+            .map_or(0, |i| i + 1);
+        tape.nodes.truncate(keep);
+        tape.grads.retain(|id, _| id.0 < keep);
+        tape.checkpoints.retain(|range| range.end <= keep);
     }
 
     /// Add a node and return its ID.
-    pub fn add_node(&mut self, op: Op, value: Tensor<f64>, requires_grad: bool) -> NodeId {
-        let id = NodeId(self.nodes.len());
+    pub fn add_node(&self, op: Op, value: Tensor<T>, requires_grad: bool) -> NodeId {
+        let mut tape = self.tape.write().expect("graph lock poisoned");
+        let id = NodeId(tape.nodes.len());
         let shape = value.shape_vec();
-        self.nodes.push(Node {
+        tape.nodes.push(Node {
             id,
             op,
             shape,
@@ -81,41 +271,125 @@ impl Graph {
         id
     }
 
-    pub fn get(&self, id: NodeId) -> &Node {
-        &self.nodes[id.0]
+    /// A trainable leaf on this graph.
+    pub fn param(&self, data: Tensor<T>) -> Variable<T> {
+        Variable::leaf(self, data, true)
+    }
+
+    /// A constant leaf on this graph (inputs, targets, masks).
+    pub fn input(&self, data: Tensor<T>) -> Variable<T> {
+        Variable::leaf(self, data, false)
+    }
+
+    /// Shared read access to the recorded nodes.
+    pub fn read(&self) -> RwLockReadGuard<'_, Tape<T>> {
+        self.tape.read().expect("graph lock poisoned")
+    }
+
+    /// The gradient accumulated on a trainable leaf, if any.
+    pub fn grad(&self, id: NodeId) -> Option<Tensor<T>> {
+        self.read().grads.get(&id).cloned()
+    }
+
+    /// Add `grads` into the stored gradients of the trainable leaves among
+    /// them; gradients of intermediate nodes and constants are dropped.
+    pub fn accumulate_grads(&self, grads: HashMap<NodeId, Tensor<T>>) {
+        let mut tape = self.tape.write().expect("graph lock poisoned");
+        for (id, grad) in grads {
+            let node = tape.get(id);
+            if !(node.requires_grad && matches!(node.op, Op::Leaf)) {
+                continue;
+            }
+            match tape.grads.get_mut(&id) {
+                Some(existing) => existing.add_(&grad).expect("grad accumulation"),
+                None => {
+                    tape.grads.insert(id, grad);
+                }
+            }
+        }
+    }
+
+    /// Replace the value of a recorded node.
+    pub(crate) fn set_value(&self, id: NodeId, value: Tensor<T>) {
+        let mut tape = self.tape.write().expect("graph lock poisoned");
+        let node = &mut tape.nodes[id.0];
+        node.shape = value.shape_vec();
+        node.value = value;
+    }
+
+    /// Set whether a recorded node requires grad.
+    pub(crate) fn set_requires_grad(&self, id: NodeId, requires_grad: bool) {
+        self.tape.write().expect("graph lock poisoned").nodes[id.0].requires_grad = requires_grad;
+    }
+
+    /// Drop the values of the non-leaf nodes in `range`; backward passes
+    /// recompute them from the recorded ops.
+    pub(crate) fn discard(&self, range: Range<usize>) {
+        let mut tape = self.tape.write().expect("graph lock poisoned");
+        for node in &mut tape.nodes[range.clone()] {
+            if !matches!(node.op, Op::Leaf) {
+                node.value = Tensor::zeros(vec![0]);
+            }
+        }
+        tape.checkpoints.push(range);
+    }
+
+    /// Forget the stored gradient of one leaf.
+    pub fn zero_grad_node(&self, id: NodeId) {
+        self.tape.write().expect("graph lock poisoned").grads.remove(&id);
+    }
+
+    /// Forget every stored gradient on this graph.
+    pub fn zero_grad(&self) {
+        self.tape.write().expect("graph lock poisoned").grads.clear();
     }
 
     pub fn len(&self) -> usize {
-        self.nodes.len()
+        self.read().len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.nodes.is_empty()
+        self.read().is_empty()
+    }
+
+    /// Whether both handles refer to the same tape.
+    pub fn same(&self, other: &Graph<T>) -> bool {
+        Arc::ptr_eq(&self.tape, &other.tape)
+    }
+}
+
+impl<T: Float> Clone for Graph<T> {
+    fn clone(&self) -> Self {
+        Graph { tape: Arc::clone(&self.tape) }
     }
 }
 
-impl Default for Graph {
+impl<T: Float> Default for Graph<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-// Thread-local graph for convenient usage
+impl<T: Float> fmt::Debug for Graph<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Graph").field("nodes", &self.len()).finish()
+    }
+}
+
+// Each thread's current graph, one per element type.
 thread_local! {
-    static CURRENT_GRAPH: RefCell<Graph> = RefCell::new(Graph::new());
+    static CURRENT_GRAPHS: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
 }
 
-/// Execute a closure with the current thread-local graph.
-pub fn with_graph<F, R>(f: F) -> R
-where
-    F: FnOnce(&mut Graph) -> R,
-{
-    CURRENT_GRAPH.with(|g| f(&mut g.borrow_mut()))
+/// Make `graph` current for its element type, returning the previous one.
+fn set_current<T: Float>(graph: Graph<T>) -> Graph<T> {
+    let previous = Graph::<T>::current();
+    CURRENT_GRAPHS.with(|graphs| graphs.borrow_mut().insert(TypeId::of::<T>(), Box::new(graph)));
+    previous
 }
 
-/// Reset the thread-local graph.
+/// Give the calling thread fresh, empty current graphs (all element types).
+/// Variables recorded earlier keep their own graphs alive.
 pub fn reset_graph() {
-    CURRENT_GRAPH.with(|g| {
-        *g.borrow_mut() = Graph::new();
-    });
+    CURRENT_GRAPHS.with(|graphs| graphs.borrow_mut().clear());
 }
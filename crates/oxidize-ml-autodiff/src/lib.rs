@@ -1,6 +1,13 @@
 pub mod graph;
 pub mod variable;
 pub mod backward;
+pub mod checkpoint;
+pub mod no_grad;
+pub mod conv;
+pub mod autocast;
 
 pub use variable::Variable;
 pub use graph::Graph;
+pub use conv::{Conv2dOptions, ConvGeometry};
+pub use no_grad::{is_grad_enabled, no_grad};
+pub use autocast::{autocast, autocast_dtype};
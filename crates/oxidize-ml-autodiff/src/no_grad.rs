@@ -0,0 +1,62 @@
+use std::cell::Cell;
+
+thread_local! {
+    // Whether ops on this thread are recorded on the graph.
+    static GRAD_ENABLED: Cell<bool> = const { Cell::new(true) };
+}
+
+/// Run `f` without recording: ops and constant leaves created inside
+/// produce untracked variables that hold values but add no graph nodes, so
+/// evaluation loops do not grow the tape. Parameters are still recorded.
+///
+/// An untracked variable used later in a recorded op enters the graph as a
+/// constant.
+pub fn no_grad<R>(f: impl FnOnce() -> R) -> R {
+    let previous = GRAD_ENABLED.with(|flag| flag.replace(false));
+    let result = f();
+    GRAD_ENABLED.with(|flag| flag.set(previous));
+    result
+}
+
+/// Whether ops on the calling thread are currently recorded.
+pub fn is_grad_enabled() -> bool {
+    GRAD_ENABLED.with(|flag| flag.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backward::backward;
+    use crate::graph::Graph;
+    use crate::variable::Variable;
+    use oxidize_ml_core::Tensor;
+
+    #[test]
+    fn test_no_grad_and_clear_keep_the_tape_bounded() {
+        let graph = Graph::new();
+        let w = graph.param(Tensor::new(vec![1.0, 2.0], vec![2, 1]).unwrap());
+        let before = graph.len();
+
+        // Evaluation records nothing, whether by `no_grad` or inference mode.
+        let preds: Vec<Variable> = (0..10)
+            .map(|i| graph.inference_mode(|| Variable::input(Tensor::new(vec![i as f64, 1.0], vec![1, 2]).unwrap()).matmul(&w)))
+            .collect();
+        assert_eq!(graph.len(), before);
+        assert_eq!(preds[3].data.data(), &[5.0]);
+        assert!(!preds[3].is_tracked() && backward(&preds[3]).is_empty());
+
+        // An untracked value used with grad on is a constant.
+        let loss = preds[3].mul(&w.sum()).sum();
+        assert!(is_grad_enabled() && loss.is_tracked());
+        assert_eq!(backward(&loss)[&w.node_id].data(), &[5.0, 5.0]);
+
+        // Clearing after each step keeps only the parameter.
+        for _ in 0..3 {
+            let x = graph.input(Tensor::new(vec![3.0, 4.0], vec![1, 2]).unwrap());
+            x.matmul(&w).pow(2.0).sum().backward();
+            graph.clear();
+            assert_eq!(graph.len(), before);
+        }
+        assert_eq!(w.grad().unwrap().data(), &[198.0, 264.0]);
+    }
+}
@@ -1,30 +1,98 @@
-use oxidize_ml_core::Tensor;
-use crate::graph::{Graph, NodeId, Op, with_graph};
+use oxidize_ml_core::{Float, Tensor};
+use crate::backward::{cross_entropy_value, index_add, reduce_broadcast};
+use crate::graph::{Graph, NodeId, Op};
+use crate::no_grad::is_grad_enabled;
+use crate::autocast::autocast_dtype;
 
 /// A variable in the computation graph — wraps a tensor with grad tracking.
+///
+/// Generic over the element type; `f32` and `f64` variables live on separate
+/// graphs. Each variable holds a handle to the graph it was recorded on.
 #[derive(Debug, Clone)]
-pub struct Variable {
+pub struct Variable<T: Float = f64> {
     pub node_id: NodeId,
-    pub data: Tensor<f64>,
+    pub data: Tensor<T>,
+    pub graph: Graph<T>,
 }
 
-impl Variable {
-    /// Create a new leaf variable (parameter) that requires gradients.
-    pub fn new(data: Tensor<f64>, requires_grad: bool) -> Self {
-        let node_id = with_graph(|g| g.add_node(Op::Leaf, data.clone(), requires_grad));
-        Variable { node_id, data }
+impl<T: Float> Variable<T> {
+    /// Create a new leaf variable on the current graph.
+    pub fn new(data: Tensor<T>, requires_grad: bool) -> Self {
+        Self::leaf(&Graph::current(), data, requires_grad)
     }
 
-    /// Create a parameter (requires grad by default).
-    pub fn param(data: Tensor<f64>) -> Self {
+    /// Create a parameter (requires grad by default) on the current graph.
+    pub fn param(data: Tensor<T>) -> Self {
         Self::new(data, true)
     }
 
-    /// Create an input (no grad by default).
-    pub fn input(data: Tensor<f64>) -> Self {
+    /// Create an input (no grad by default) on the current graph.
+    pub fn input(data: Tensor<T>) -> Self {
         Self::new(data, false)
     }
 
+    pub(crate) fn leaf(graph: &Graph<T>, data: Tensor<T>, requires_grad: bool) -> Self {
+        // Parameters are always recorded; constants are not under `no_grad`.
+        let node_id = if requires_grad || is_grad_enabled() {
+            graph.add_node(Op::Leaf, data.clone(), requires_grad)
+        } else {
+            NodeId::UNTRACKED
+        };
+        Variable { node_id, data, graph: graph.clone() }
+    }
+
+    /// Whether this variable has a node on its graph; results computed
+    /// under `no_grad` do not.
+    pub fn is_tracked(&self) -> bool {
+        self.node_id != NodeId::UNTRACKED
+    }
+
+    /// A constant leaf on this variable's graph, for masks and other
+    /// tensors combined with it.
+    pub fn constant(&self, data: Tensor<T>) -> Variable<T> {
+        self.graph.input(data)
+    }
+
+    /// Replace this variable's value in place, keeping its node: optimizers
+    /// and stored gradients keyed by `node_id` stay valid. Meant for leaves
+    /// (loading or updating parameters).
+    pub fn set_data(&mut self, data: Tensor<T>) {
+        if self.is_tracked() {
+            self.graph.set_value(self.node_id, data.clone());
+        }
+        self.data = data;
+    }
+
+    /// Whether `Variable::backward` stores a gradient for this leaf.
+    pub fn requires_grad(&self) -> bool {
+        self.is_tracked() && self.graph.read().get(self.node_id).requires_grad
+    }
+
+    /// Freeze (`false`) or unfreeze a parameter. Frozen leaves get no stored
+    /// gradient, so `Optimizer::step_params` leaves them unchanged.
+    pub fn set_requires_grad(&self, requires_grad: bool) {
+        if self.is_tracked() {
+            self.graph.set_requires_grad(self.node_id, requires_grad);
+        }
+    }
+
+    /// Run reverse-mode AD from this variable and add the gradients of the
+    /// trainable leaves into their stored `grad`. Repeated calls accumulate
+    /// until `zero_grad`.
+    pub fn backward(&self) {
+        self.graph.accumulate_grads(crate::backward::backward(self));
+    }
+
+    /// The gradient accumulated on this variable by `backward`, if any.
+    pub fn grad(&self) -> Option<Tensor<T>> {
+        self.graph.grad(self.node_id)
+    }
+
+    /// Clear this variable's accumulated gradient.
+    pub fn zero_grad(&self) {
+        self.graph.zero_grad_node(self.node_id);
+    }
+
     pub fn shape_vec(&self) -> Vec<usize> {
         self.data.shape_vec()
     }
@@ -33,195 +101,270 @@ impl Variable {
         self.data.numel()
     }
 
+    /// This variable's node, recording an untracked value as a constant
+    /// leaf first.
+    fn tracked_id(&self) -> NodeId {
+        if self.is_tracked() {
+            self.node_id
+        } else {
+            self.graph.add_node(Op::Leaf, self.data.clone(), false)
+        }
+    }
+
+    /// Record `result` as produced by `op`, or leave it untracked under
+    /// `no_grad`.
+    fn record(&self, op: impl FnOnce() -> Op, result: Tensor<T>) -> Variable<T> {
+        let node_id = if is_grad_enabled() {
+            self.graph.add_node(op(), result.clone(), true)
+        } else {
+            NodeId::UNTRACKED
+        };
+        Variable { node_id, data: result, graph: self.graph.clone() }
+    }
+
+    /// Record `result = op(self)`.
+    pub(crate) fn unary(&self, op: impl FnOnce(NodeId) -> Op, result: Tensor<T>) -> Variable<T> {
+        self.record(|| op(self.tracked_id()), result)
+    }
+
+    /// Record `result = op(self, other)`; both must be on the same graph.
+    fn binary(&self, other: &Variable<T>, op: impl FnOnce(NodeId, NodeId) -> Op, result: Tensor<T>) -> Variable<T> {
+        assert!(self.graph.same(&other.graph), "operands were recorded on different graphs");
+        self.record(|| op(self.tracked_id(), other.tracked_id()), result)
+    }
+
     /// Element-wise addition.
-    pub fn add(&self, other: &Variable) -> Variable {
+    pub fn add(&self, other: &Variable<T>) -> Variable<T> {
         let result = self.data.add(&other.data).expect("add: shape mismatch");
-        let node_id = with_graph(|g| {
-            g.add_node(Op::Add(self.node_id, other.node_id), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
-        }
+        self.binary(other, Op::Add, result)
     }
 
     /// Element-wise subtraction.
-    pub fn sub(&self, other: &Variable) -> Variable {
+    pub fn sub(&self, other: &Variable<T>) -> Variable<T> {
         let result = self.data.sub(&other.data).expect("sub: shape mismatch");
-        let node_id = with_graph(|g| {
-            g.add_node(Op::Sub(self.node_id, other.node_id), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
-        }
+        self.binary(other, Op::Sub, result)
     }
 
     /// Element-wise multiplication.
-    pub fn mul(&self, other: &Variable) -> Variable {
+    pub fn mul(&self, other: &Variable<T>) -> Variable<T> {
         let result = self.data.mul(&other.data).expect("mul: shape mismatch");
-        let node_id = with_graph(|g| {
-            g.add_node(Op::Mul(self.node_id, other.node_id), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
-        }
+        self.binary(other, Op::Mul, result)
     }
 
     /// Element-wise division.
-    pub fn div(&self, other: &Variable) -> Variable {
+    pub fn div(&self, other: &Variable<T>) -> Variable<T> {
         let result = self.data.div(&other.data).expect("div: shape mismatch");
-        let node_id = with_graph(|g| {
-            g.add_node(Op::Div(self.node_id, other.node_id), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
-        }
+        self.binary(other, Op::Div, result)
     }
 
-    /// Matrix multiplication.
-    pub fn matmul(&self, other: &Variable) -> Variable {
-        let result = self.data.matmul(&other.data).expect("matmul: shape mismatch");
-        let node_id = with_graph(|g| {
-            g.add_node(Op::MatMul(self.node_id, other.node_id), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
+    /// Matrix multiplication; at reduced precision inside `autocast`.
+    pub fn matmul(&self, other: &Variable<T>) -> Variable<T> {
+        let result = match autocast_dtype() {
+            Some(dtype) => self.data.round_to(dtype).matmul(&other.data.round_to(dtype)).map(|r| r.round_to(dtype)),
+            None => self.data.matmul(&other.data),
         }
+        .expect("matmul: shape mismatch");
+        self.binary(other, Op::MatMul, result)
     }
 
     /// Negation.
-    pub fn neg(&self) -> Variable {
-        let result = self.data.mul_scalar(oxidize_ml_core::Float::NEG_ONE);
-        let node_id = with_graph(|g| g.add_node(Op::Neg(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn neg(&self) -> Variable<T> {
+        self.unary(Op::Neg, self.data.mul_scalar(T::NEG_ONE))
     }
 
     /// Exponential.
-    pub fn exp(&self) -> Variable {
-        let result = self.data.exp();
-        let node_id = with_graph(|g| g.add_node(Op::Exp(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn exp(&self) -> Variable<T> {
+        self.unary(Op::Exp, self.data.exp())
     }
 
     /// Natural logarithm.
-    pub fn ln(&self) -> Variable {
-        let result = self.data.ln();
-        let node_id = with_graph(|g| g.add_node(Op::Ln(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn ln(&self) -> Variable<T> {
+        self.unary(Op::Ln, self.data.ln())
     }
 
     /// ReLU activation.
-    pub fn relu(&self) -> Variable {
-        let result = self.data.relu();
-        let node_id = with_graph(|g| g.add_node(Op::Relu(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn relu(&self) -> Variable<T> {
+        self.unary(Op::Relu, self.data.relu())
     }
 
     /// Sigmoid activation.
-    pub fn sigmoid(&self) -> Variable {
-        let result = self.data.sigmoid();
-        let node_id =
-            with_graph(|g| g.add_node(Op::Sigmoid(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn sigmoid(&self) -> Variable<T> {
+        self.unary(Op::Sigmoid, self.data.sigmoid())
     }
 
     /// Tanh activation.
-    pub fn tanh_act(&self) -> Variable {
-        let result = self.data.tanh_elem();
-        let node_id = with_graph(|g| g.add_node(Op::Tanh(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn tanh_act(&self) -> Variable<T> {
+        self.unary(Op::Tanh, self.data.tanh_elem())
+    }
+
+    /// Error function.
+    pub fn erf(&self) -> Variable<T> {
+        self.unary(Op::Erf, self.data.erf())
+    }
+
+    /// Exact GELU activation, x·Φ(x).
+    pub fn gelu(&self) -> Variable<T> {
+        self.unary(Op::Gelu, self.data.gelu())
+    }
+
+    /// GELU activation with the tanh approximation.
+    pub fn gelu_tanh(&self) -> Variable<T> {
+        self.unary(Op::GeluTanh, self.data.gelu_tanh())
+    }
+
+    /// SiLU (Swish) activation, x·σ(x).
+    pub fn silu(&self) -> Variable<T> {
+        self.unary(Op::Silu, self.data.silu())
+    }
+
+    /// ELU activation.
+    pub fn elu(&self, alpha: f64) -> Variable<T> {
+        self.unary(|a| Op::Elu(a, alpha), self.data.elu(T::from_f64(alpha)))
+    }
+
+    /// Softplus activation, ln(1 + exp(beta·x)) / beta.
+    pub fn softplus(&self, beta: f64) -> Variable<T> {
+        self.unary(|a| Op::Softplus(a, beta), self.data.softplus(T::from_f64(beta)))
+    }
+
+    /// Mish activation, x·tanh(softplus(x)).
+    pub fn mish(&self) -> Variable<T> {
+        self.unary(Op::Mish, self.data.mish())
     }
 
     /// Multiply by scalar.
-    pub fn mul_scalar(&self, s: f64) -> Variable {
-        let result = self.data.mul_scalar(s);
-        let node_id = with_graph(|g| {
-            g.add_node(Op::MulScalar(self.node_id, s), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn mul_scalar(&self, s: f64) -> Variable<T> {
+        self.unary(|a| Op::MulScalar(a, s), self.data.mul_scalar(T::from_f64(s)))
     }
 
     /// Add scalar.
-    pub fn add_scalar(&self, s: f64) -> Variable {
-        let result = self.data.add_scalar(s);
-        let node_id = with_graph(|g| {
-            g.add_node(Op::AddScalar(self.node_id, s), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn add_scalar(&self, s: f64) -> Variable<T> {
+        self.unary(|a| Op::AddScalar(a, s), self.data.add_scalar(T::from_f64(s)))
     }
 
     /// Sum all elements to a scalar.
-    pub fn sum(&self) -> Variable {
-        let s = self.data.sum_all();
-        let result = Tensor::scalar(s);
-        let node_id =
-            with_graph(|g| g.add_node(Op::SumAll(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn sum(&self) -> Variable<T> {
+        self.unary(Op::SumAll, Tensor::scalar(self.data.sum_all()))
     }
 
     /// Mean of all elements.
-    pub fn mean(&self) -> Variable {
-        let m = self.data.mean_all();
-        let result = Tensor::scalar(m);
-        let node_id =
-            with_graph(|g| g.add_node(Op::MeanAll(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
-        }
+    pub fn mean(&self) -> Variable<T> {
+        self.unary(Op::MeanAll, Tensor::scalar(self.data.mean_all()))
     }
 
     /// Transpose last two dims.
-    pub fn t(&self) -> Variable {
-        let result = self.data.t().expect("transpose failed");
-        let node_id =
-            with_graph(|g| g.add_node(Op::Transpose(self.node_id), result.clone(), true));
-        Variable {
-            node_id,
-            data: result,
+    pub fn t(&self) -> Variable<T> {
+        self.unary(Op::Transpose, self.data.t().expect("transpose failed"))
+    }
+
+    /// Sum a broadcast result back down to `shape` (the reverse of
+    /// broadcasting).
+    pub fn sum_to(&self, shape: &[usize]) -> Variable<T> {
+        if self.shape_vec() == shape {
+            return self.clone();
         }
+        let result = reduce_broadcast(&self.data, shape)
+            .reshape(shape.to_vec())
+            .expect("sum_to: shapes are not broadcast-compatible");
+        self.unary(Op::SumTo, result)
     }
 
-    /// Power (element-wise, scalar exponent).
-    pub fn pow(&self, n: f64) -> Variable {
-        let result = self.data.powf(oxidize_ml_core::Float::from_f64(n));
-        let node_id = with_graph(|g| {
-            g.add_node(Op::Pow(self.node_id, n), result.clone(), true)
-        });
-        Variable {
-            node_id,
-            data: result,
+    /// Sum along `axis`, removing it.
+    pub fn sum_axis(&self, axis: usize) -> Variable<T> {
+        let result = self.data.sum_axis(axis).expect("sum_axis: invalid axis");
+        self.unary(|a| Op::SumAxis(a, axis), result)
+    }
+
+    /// Mean along `axis`, removing it.
+    pub fn mean_axis(&self, axis: usize) -> Variable<T> {
+        let result = self.data.mean_axis(axis).expect("mean_axis: invalid axis");
+        self.unary(|a| Op::MeanAxis(a, axis), result)
+    }
+
+    /// Max along `axis`, removing it. Tied maxima share the gradient equally.
+    pub fn max_axis(&self, axis: usize) -> Variable<T> {
+        let result = self.data.max_axis(axis).expect("max_axis: invalid axis");
+        self.unary(|a| Op::MaxAxis(a, axis), result)
+    }
+
+    /// Reshape (same number of elements).
+    pub fn reshape(&self, shape: Vec<usize>) -> Variable<T> {
+        let result = self.data.reshape(shape).expect("reshape: element count mismatch");
+        self.unary(Op::Reshape, result)
+    }
+
+    /// Concatenate along an existing `axis`.
+    pub fn concat(vars: &[&Variable<T>], axis: usize) -> Variable<T> {
+        let first = vars.first().expect("concat: no variables");
+        assert!(
+            vars.iter().all(|v| v.graph.same(&first.graph)),
+            "operands of concat were recorded on different graphs"
+        );
+        let tensors: Vec<&Tensor<T>> = vars.iter().map(|v| &v.data).collect();
+        let result = Tensor::concatenate(&tensors, axis).expect("concat: shape mismatch");
+        first.record(|| Op::Concat(vars.iter().map(|v| v.tracked_id()).collect(), axis), result)
+    }
+
+    /// Stack along a new `axis`.
+    pub fn stack(vars: &[&Variable<T>], axis: usize) -> Variable<T> {
+        let expanded: Vec<Variable<T>> = vars
+            .iter()
+            .map(|v| {
+                let mut shape = v.shape_vec();
+                assert!(axis <= shape.len(), "stack: invalid axis {}", axis);
+                shape.insert(axis, 1);
+                v.reshape(shape)
+            })
+            .collect();
+        Self::concat(&expanded.iter().collect::<Vec<_>>(), axis)
+    }
+
+    /// Entries `start..end` along `axis`.
+    pub fn slice(&self, axis: usize, start: usize, end: usize) -> Variable<T> {
+        assert!(start <= end, "slice: start {} is past end {}", start, end);
+        self.index_select(axis, &(start..end).collect::<Vec<_>>())
+    }
+
+    /// Entries at `indices` along `axis` (duplicates allowed).
+    pub fn index_select(&self, axis: usize, indices: &[usize]) -> Variable<T> {
+        let result = self.data.index_select(axis, indices).expect("index_select: invalid axis or index");
+        self.unary(|a| Op::IndexSelect(a, axis, indices.to_vec()), result)
+    }
+
+    /// Add slice `i` along `axis` into position `indices[i]` of a zero
+    /// tensor whose `axis` has length `size`; the reverse of `index_select`.
+    pub fn index_add(&self, axis: usize, indices: &[usize], size: usize) -> Variable<T> {
+        let result = index_add(&self.data, axis, indices, size);
+        self.unary(|a| Op::IndexAdd(a, axis, indices.to_vec()), result)
+    }
+
+    /// Softmax along the last axis.
+    pub fn softmax(&self) -> Variable<T> {
+        self.unary(Op::Softmax, self.data.softmax().expect("softmax"))
+    }
+
+    /// Log-softmax along the last axis, stable for large logits.
+    pub fn log_softmax(&self) -> Variable<T> {
+        self.unary(Op::LogSoftmax, self.data.log_softmax().expect("log_softmax"))
+    }
+
+    /// Mean cross-entropy of `[batch, classes]` logits against the class
+    /// index of each row, fused so the forward uses log-sum-exp and the
+    /// backward is `(softmax - one_hot) / batch`.
+    pub fn cross_entropy(&self, targets: &[usize]) -> Variable<T> {
+        let shape = self.shape_vec();
+        assert!(shape.len() == 2, "cross_entropy: logits must be [batch, classes]");
+        let (batch, classes) = (shape[0], shape[1]);
+        assert_eq!(targets.len(), batch, "cross_entropy: one target per row");
+        if let Some(&bad) = targets.iter().find(|&&t| t >= classes) {
+            panic!("cross_entropy: class {} out of range for {} classes", bad, classes);
         }
+        let result = cross_entropy_value(&self.data, targets);
+        self.unary(|a| Op::CrossEntropy(a, targets.to_vec()), result)
+    }
+
+    /// Power (element-wise, scalar exponent).
+    pub fn pow(&self, n: f64) -> Variable<T> {
+        self.unary(|a| Op::Pow(a, n), self.data.powf(T::from_f64(n)))
     }
 }
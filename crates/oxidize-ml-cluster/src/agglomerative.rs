@@ -1,22 +1,31 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
 
 /// Agglomerative (Hierarchical) Clustering.
 ///
 /// Bottom-up approach: starts with each point as its own cluster,
-/// then iteratively merges the closest pair of clusters.
+/// then iteratively merges the closest pair of clusters. The full merge
+/// tree is kept, so the dendrogram can be cut again at any height or
+/// cluster count without refitting.
 pub struct AgglomerativeClustering<T: Float> {
     pub n_clusters: usize,
     pub linkage: Linkage,
+    /// Distance between points; Euclidean by default.
+    pub metric: DistanceMetric<T>,
+    /// When set, cut the tree at this merge distance instead of at
+    /// `n_clusters`: clusters closer than it are merged.
+    pub distance_threshold: Option<f64>,
     labels: Option<Vec<usize>>,
-    _marker: std::marker::PhantomData<T>,
+    children: Option<Vec<(usize, usize)>>,
+    distances: Option<Vec<f64>>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Linkage {
     Single,    // min distance between clusters
     Complete,  // max distance between clusters
     Average,   // mean distance between clusters
+    Ward,      // smallest increase in within-cluster variance; Euclidean only
 }
 
 impl<T: Float> AgglomerativeClustering<T> {
@@ -24,109 +33,149 @@ impl<T: Float> AgglomerativeClustering<T> {
         AgglomerativeClustering {
             n_clusters,
             linkage,
+            metric: DistanceMetric::Euclidean,
+            distance_threshold: None,
             labels: None,
-            _marker: std::marker::PhantomData,
+            children: None,
+            distances: None,
         }
     }
 
-    fn euclidean_dist(x: &Tensor<T>, i: usize, j: usize, p: usize) -> f64 {
-        let mut sum = 0.0;
-        for k in 0..p {
-            let diff = x.get(&[i, k]).unwrap().to_f64() - x.get(&[j, k]).unwrap().to_f64();
-            sum += diff * diff;
-        }
-        sum.sqrt()
+    pub fn with_metric(mut self, metric: DistanceMetric<T>) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn with_distance_threshold(mut self, threshold: f64) -> Self {
+        self.distance_threshold = Some(threshold);
+        self
     }
 
     pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
+        if self.linkage == Linkage::Ward && !matches!(self.metric, DistanceMetric::Euclidean) {
+            return Err(TensorError::InvalidOperation("Ward linkage requires the Euclidean metric".into()));
+        }
+        if self.distance_threshold.is_none() && (self.n_clusters == 0 || self.n_clusters > n) {
+            return Err(TensorError::InvalidOperation(format!(
+                "n_clusters must be between 1 and the number of points ({}), got {}", n, self.n_clusters
+            )));
+        }
 
         // Compute distance matrix
         let mut dist = vec![vec![0.0f64; n]; n];
         for i in 0..n {
             for j in i + 1..n {
-                let d = Self::euclidean_dist(x, i, j, p);
+                let d = self.metric.distance(&x.data()[i * p..(i + 1) * p], &x.data()[j * p..(j + 1) * p]).to_f64();
                 dist[i][j] = d;
                 dist[j][i] = d;
             }
         }
 
-        // Each point starts as its own cluster
-        let mut clusters: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+        // Slot `i` holds tree node `node[i]` of `size[i]` points while active.
+        let mut active = vec![true; n];
+        let mut node: Vec<usize> = (0..n).collect();
+        let mut size = vec![1usize; n];
+        let mut children = Vec::with_capacity(n.saturating_sub(1));
+        let mut distances = Vec::with_capacity(n.saturating_sub(1));
 
-        while clusters.len() > self.n_clusters {
+        for step in 0..n.saturating_sub(1) {
             // Find closest pair of clusters
             let mut min_dist = f64::INFINITY;
-            let mut merge_a = 0;
-            let mut merge_b = 1;
-
-            for i in 0..clusters.len() {
-                for j in i + 1..clusters.len() {
-                    let d = self.cluster_distance(&dist, &clusters[i], &clusters[j]);
-                    if d < min_dist {
-                        min_dist = d;
+            let mut merge_a = usize::MAX;
+            let mut merge_b = usize::MAX;
+            for i in (0..n).filter(|&i| active[i]) {
+                for j in (i + 1..n).filter(|&j| active[j]) {
+                    if merge_a == usize::MAX || dist[i][j] < min_dist {
+                        min_dist = dist[i][j];
                         merge_a = i;
                         merge_b = j;
                     }
                 }
             }
 
-            // Merge clusters
-            let merged = clusters[merge_b].clone();
-            clusters[merge_a].extend(merged);
-            clusters.remove(merge_b);
-        }
-
-        // Assign labels
-        let mut labels = vec![0usize; n];
-        for (cluster_id, cluster) in clusters.iter().enumerate() {
-            for &point_id in cluster {
-                labels[point_id] = cluster_id;
-            }
-        }
-        self.labels = Some(labels);
-        Ok(())
-    }
-
-    fn cluster_distance(&self, dist: &[Vec<f64>], a: &[usize], b: &[usize]) -> f64 {
-        match self.linkage {
-            Linkage::Single => {
-                let mut min = f64::INFINITY;
-                for &i in a {
-                    for &j in b {
-                        if dist[i][j] < min { min = dist[i][j]; }
-                    }
-                }
-                min
-            }
-            Linkage::Complete => {
-                let mut max = 0.0;
-                for &i in a {
-                    for &j in b {
-                        if dist[i][j] > max { max = dist[i][j]; }
+            // Merge b into a, updating distances with Lance–Williams.
+            let (na, nb) = (size[merge_a] as f64, size[merge_b] as f64);
+            for k in (0..n).filter(|&k| active[k] && k != merge_a && k != merge_b) {
+                let (dka, dkb) = (dist[k][merge_a], dist[k][merge_b]);
+                let d = match self.linkage {
+                    Linkage::Single => dka.min(dkb),
+                    Linkage::Complete => dka.max(dkb),
+                    Linkage::Average => (na * dka + nb * dkb) / (na + nb),
+                    Linkage::Ward => {
+                        let nk = size[k] as f64;
+                        let sq = ((na + nk) * dka * dka + (nb + nk) * dkb * dkb - nk * min_dist * min_dist) / (na + nb + nk);
+                        sq.max(0.0).sqrt()
                     }
-                }
-                max
-            }
-            Linkage::Average => {
-                let mut sum = 0.0;
-                let mut count = 0;
-                for &i in a {
-                    for &j in b {
-                        sum += dist[i][j];
-                        count += 1;
-                    }
-                }
-                sum / count as f64
+                };
+                dist[k][merge_a] = d;
+                dist[merge_a][k] = d;
             }
+            children.push((node[merge_a], node[merge_b]));
+            distances.push(min_dist);
+            active[merge_b] = false;
+            size[merge_a] += size[merge_b];
+            node[merge_a] = n + step;
         }
+
+        self.children = Some(children);
+        self.distances = Some(distances);
+        self.labels = match self.distance_threshold {
+            Some(threshold) => self.labels_at_height(threshold),
+            None => self.labels_for(self.n_clusters),
+        };
+        Ok(())
     }
 
     pub fn labels(&self) -> Option<&[usize]> {
         self.labels.as_deref()
     }
 
+    /// The two nodes joined by each merge, in merge order. Nodes below
+    /// `n_samples` are points; node `n_samples + i` is the `i`-th merge.
+    pub fn children(&self) -> Option<&[(usize, usize)]> {
+        self.children.as_deref()
+    }
+
+    /// Linkage distance of each merge; non-decreasing.
+    pub fn distances(&self) -> Option<&[f64]> {
+        self.distances.as_deref()
+    }
+
+    /// The tree as a SciPy-style linkage matrix for plotting: one row
+    /// `[left, right, distance, size]` per merge.
+    pub fn linkage_matrix(&self) -> Option<Vec<[f64; 4]>> {
+        let (children, distances) = (self.children.as_ref()?, self.distances.as_ref()?);
+        let n = children.len() + 1;
+        let mut sizes = vec![1usize; n];
+        Some(
+            children
+                .iter()
+                .zip(distances)
+                .map(|(&(a, b), &d)| {
+                    let size = sizes[a] + sizes[b];
+                    sizes.push(size);
+                    [a as f64, b as f64, d, size as f64]
+                })
+                .collect(),
+        )
+    }
+
+    /// Labels from cutting the fitted tree into `n_clusters` clusters.
+    pub fn labels_for(&self, n_clusters: usize) -> Option<Vec<usize>> {
+        let children = self.children.as_ref()?;
+        let n = children.len() + 1;
+        Some(cut_tree(children, n - n_clusters.clamp(1, n)))
+    }
+
+    /// Labels from cutting the fitted tree at `height`: only merges closer
+    /// than it are kept.
+    pub fn labels_at_height(&self, height: f64) -> Option<Vec<usize>> {
+        let (children, distances) = (self.children.as_ref()?, self.distances.as_ref()?);
+        Some(cut_tree(children, distances.iter().filter(|&&d| d < height).count()))
+    }
+
     pub fn fit_predict(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
         self.fit(x)?;
         let labels = self.labels.as_ref().unwrap();
@@ -135,6 +184,33 @@ impl<T: Float> AgglomerativeClustering<T> {
     }
 }
 
+/// Apply the first `n_merges` merges and label the resulting clusters in
+/// order of their lowest point.
+fn cut_tree(children: &[(usize, usize)], n_merges: usize) -> Vec<usize> {
+    let n = children.len() + 1;
+    let mut parent: Vec<usize> = (0..n + children.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for (step, &(a, b)) in children.iter().take(n_merges).enumerate() {
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        parent[ra] = n + step;
+        parent[rb] = n + step;
+    }
+    let mut label_of = std::collections::HashMap::new();
+    (0..n)
+        .map(|i| {
+            let root = find(&mut parent, i);
+            let next = label_of.len();
+            *label_of.entry(root).or_insert(next)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,4 +234,31 @@ mod tests {
         assert_eq!(l2, l3);
         assert_ne!(l0, l2);
     }
+
+    #[test]
+    fn test_agglomerative_tree() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![0.0, 0.0], vec![1.0, 0.0],
+            vec![10.0, 0.0], vec![12.0, 0.0],
+            vec![30.0, 0.0],
+        ]).unwrap();
+
+        let mut ward = AgglomerativeClustering::new(2, Linkage::Ward);
+        ward.fit(&x).unwrap();
+        assert_eq!(ward.children().unwrap()[..2], [(0, 1), (2, 3)]);
+        let dist = ward.distances().unwrap();
+        assert!(dist.windows(2).all(|w| w[0] <= w[1]));
+        assert!((dist[0] - 1.0).abs() < 1e-12);
+        assert_eq!(ward.labels_for(3).unwrap(), vec![0, 0, 1, 1, 2]);
+        assert_eq!(ward.linkage_matrix().unwrap().last().unwrap()[3], 5.0);
+
+        let mut single = AgglomerativeClustering::new(1, Linkage::Single).with_distance_threshold(5.0);
+        single.fit(&x).unwrap();
+        assert_eq!(single.labels().unwrap(), &[0, 0, 1, 1, 2]);
+        assert_eq!(single.labels_at_height(15.0).unwrap(), vec![0, 0, 0, 0, 1]);
+        assert_eq!(single.distances().unwrap(), &[1.0, 2.0, 9.0, 18.0]);
+
+        let mut cosine = AgglomerativeClustering::new(2, Linkage::Ward).with_metric(DistanceMetric::Cosine);
+        assert!(cosine.fit(&x).is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{DistanceMetric, Float, Tensor};
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
 
@@ -6,6 +6,8 @@ use rand::distributions::{Distribution, Standard};
 pub struct DBSCAN<T: Float> {
     pub eps: T,
     pub min_samples: usize,
+    /// Distance between points; Euclidean by default.
+    pub metric: DistanceMetric<T>,
     pub labels: Option<Tensor<T>>,
 }
 
@@ -17,23 +19,20 @@ where
         DBSCAN {
             eps,
             min_samples,
+            metric: DistanceMetric::Euclidean,
             labels: None,
         }
     }
 
-    fn euclidean_dist(x: &Tensor<T>, i: usize, j: usize, d: usize) -> TensorResult<T> {
-        let mut dist = T::ZERO;
-        for k in 0..d {
-            let diff = x.get(&[i, k])? - x.get(&[j, k])?;
-            dist = dist + diff * diff;
-        }
-        Ok(dist.sqrt())
+    pub fn with_metric(mut self, metric: DistanceMetric<T>) -> Self {
+        self.metric = metric;
+        self
     }
 
     fn region_query(&self, x: &Tensor<T>, i: usize, n: usize, d: usize) -> TensorResult<Vec<usize>> {
         let mut neighbors = Vec::new();
         for j in 0..n {
-            if Self::euclidean_dist(x, i, j, d)? <= self.eps {
+            if self.metric.distance(&x.data()[i * d..(i + 1) * d], &x.data()[j * d..(j + 1) * d]) <= self.eps {
                 neighbors.push(j);
             }
         }
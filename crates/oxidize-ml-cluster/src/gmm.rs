@@ -0,0 +1,407 @@
+use oxidize_ml_core::{Float, RngHandle, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_linalg::cholesky;
+use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
+
+use crate::kmeans::kmeans_plus_plus;
+
+/// Shape of each mixture component's covariance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CovarianceType {
+    /// A full `[d, d]` matrix per component.
+    Full,
+    /// Per-feature variances (`[d]`) per component.
+    Diag,
+    /// One variance per component.
+    Spherical,
+}
+
+/// Gaussian mixture model fitted with expectation–maximization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct GaussianMixture<T: Float> {
+    pub n_components: usize,
+    pub covariance_type: CovarianceType,
+    pub max_iter: usize,
+    /// Stop once the mean log-likelihood improves by less than this.
+    pub tol: f64,
+    /// Added to every variance to keep the covariances positive definite.
+    pub reg_covar: f64,
+    pub seed: Option<u64>,
+    /// Mixing weights, `[n_components]`.
+    pub weights: Option<Tensor<T>>,
+    /// Component means, `[n_components, d]`.
+    pub means: Option<Tensor<T>>,
+    /// `[n_components, d, d]` for `Full`, `[n_components, d]` for `Diag`,
+    /// `[n_components]` for `Spherical`.
+    pub covariances: Option<Tensor<T>>,
+    pub converged: bool,
+    pub n_iter: usize,
+}
+
+impl<T: Float> GaussianMixture<T>
+where
+    Standard: Distribution<T>,
+{
+    pub fn new(n_components: usize) -> Self {
+        GaussianMixture {
+            n_components,
+            covariance_type: CovarianceType::Full,
+            max_iter: 100,
+            tol: 1e-3,
+            reg_covar: 1e-6,
+            seed: None,
+            weights: None,
+            means: None,
+            covariances: None,
+            converged: false,
+            n_iter: 0,
+        }
+    }
+
+    pub fn with_covariance_type(mut self, covariance_type: CovarianceType) -> Self {
+        self.covariance_type = covariance_type;
+        self
+    }
+
+    /// Fit the mixture. Responsibilities start from a hard assignment to
+    /// k-means++ centres.
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let d = x.shape().dim(1)?;
+        let k = self.n_components;
+        if k == 0 || n < k {
+            return Err(TensorError::InvalidOperation(format!(
+                "GaussianMixture needs at least {} points for {} components, got {}", k.max(1), k, n
+            )));
+        }
+        let data: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+
+        let mut rng = RngHandle::new(self.seed);
+        let centres: Vec<f64> = kmeans_plus_plus(x, k, &mut rng)?.iter().map(|v| v.to_f64()).collect();
+        let mut resp = vec![0.0; n * k];
+        for i in 0..n {
+            let row = &data[i * d..(i + 1) * d];
+            let nearest = (0..k)
+                .map(|c| (c, sq_dist(row, &centres[c * d..(c + 1) * d])))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(0, |(c, _)| c);
+            resp[i * k + nearest] = 1.0;
+        }
+
+        let mut params = self.m_step(&data, &resp, n, d);
+        let mut lower_bound = f64::NEG_INFINITY;
+        self.converged = false;
+        self.n_iter = 0;
+        for iter in 1..=self.max_iter {
+            let mut log_resp = weighted_log_prob(&data, n, d, &params, self.covariance_type)?;
+            let mean_ll = normalize(&mut log_resp, k).iter().sum::<f64>() / n as f64;
+            resp = log_resp.iter().map(|v| v.exp()).collect();
+            params = self.m_step(&data, &resp, n, d);
+            self.n_iter = iter;
+            let improvement = mean_ll - lower_bound;
+            lower_bound = mean_ll;
+            if improvement.abs() < self.tol {
+                self.converged = true;
+                break;
+            }
+        }
+
+        let cov_shape = match self.covariance_type {
+            CovarianceType::Full => vec![k, d, d],
+            CovarianceType::Diag => vec![k, d],
+            CovarianceType::Spherical => vec![k],
+        };
+        let to_t = |v: &[f64]| v.iter().map(|&x| T::from_f64(x)).collect::<Vec<T>>();
+        self.weights = Some(Tensor::new(to_t(&params.weights), vec![k])?);
+        self.means = Some(Tensor::new(to_t(&params.means), vec![k, d])?);
+        self.covariances = Some(Tensor::new(to_t(&params.covariances), cov_shape)?);
+        Ok(())
+    }
+
+    fn m_step(&self, data: &[f64], resp: &[f64], n: usize, d: usize) -> Params {
+        let k = self.n_components;
+        let mut nk = vec![10.0 * f64::EPSILON; k];
+        let mut means = vec![0.0; k * d];
+        for i in 0..n {
+            for c in 0..k {
+                let r = resp[i * k + c];
+                nk[c] += r;
+                for j in 0..d {
+                    means[c * d + j] += r * data[i * d + j];
+                }
+            }
+        }
+        for c in 0..k {
+            for j in 0..d {
+                means[c * d + j] /= nk[c];
+            }
+        }
+
+        let covariances = match self.covariance_type {
+            CovarianceType::Full => {
+                let mut cov = vec![0.0; k * d * d];
+                for i in 0..n {
+                    for c in 0..k {
+                        let r = resp[i * k + c];
+                        for a in 0..d {
+                            let da = data[i * d + a] - means[c * d + a];
+                            for b in 0..=a {
+                                cov[c * d * d + a * d + b] += r * da * (data[i * d + b] - means[c * d + b]);
+                            }
+                        }
+                    }
+                }
+                for c in 0..k {
+                    for a in 0..d {
+                        for b in 0..=a {
+                            let v = cov[c * d * d + a * d + b] / nk[c] + if a == b { self.reg_covar } else { 0.0 };
+                            cov[c * d * d + a * d + b] = v;
+                            cov[c * d * d + b * d + a] = v;
+                        }
+                    }
+                }
+                cov
+            }
+            CovarianceType::Diag | CovarianceType::Spherical => {
+                let mut var = vec![0.0; k * d];
+                for i in 0..n {
+                    for c in 0..k {
+                        let r = resp[i * k + c];
+                        for j in 0..d {
+                            let diff = data[i * d + j] - means[c * d + j];
+                            var[c * d + j] += r * diff * diff;
+                        }
+                    }
+                }
+                for c in 0..k {
+                    for j in 0..d {
+                        var[c * d + j] = var[c * d + j] / nk[c] + self.reg_covar;
+                    }
+                }
+                if self.covariance_type == CovarianceType::Spherical {
+                    (0..k).map(|c| var[c * d..(c + 1) * d].iter().sum::<f64>() / d.max(1) as f64).collect()
+                } else {
+                    var
+                }
+            }
+        };
+
+        Params { weights: nk.iter().map(|&w| w / n as f64).collect(), means, covariances }
+    }
+
+    fn fitted_params(&self) -> TensorResult<Params> {
+        let (weights, means, covariances) = match (&self.weights, &self.means, &self.covariances) {
+            (Some(w), Some(m), Some(c)) => (w, m, c),
+            _ => return Err(TensorError::InvalidOperation("Model not fitted".into())),
+        };
+        let to_f64 = |t: &Tensor<T>| t.data().iter().map(|v| v.to_f64()).collect();
+        Ok(Params { weights: to_f64(weights), means: to_f64(means), covariances: to_f64(covariances) })
+    }
+
+    /// Per-row log responsibilities (`[n * k]`) and per-row log-likelihoods.
+    fn log_resp(&self, x: &Tensor<T>) -> TensorResult<(Vec<f64>, Vec<f64>)> {
+        let params = self.fitted_params()?;
+        let n = x.shape().dim(0)?;
+        let d = x.shape().dim(1)?;
+        if d * self.n_components != params.means.len() {
+            return Err(TensorError::InvalidOperation(format!(
+                "expected {} features, got {}", params.means.len() / self.n_components.max(1), d
+            )));
+        }
+        let data: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+        let mut log_resp = weighted_log_prob(&data, n, d, &params, self.covariance_type)?;
+        let row_ll = normalize(&mut log_resp, self.n_components);
+        Ok((log_resp, row_ll))
+    }
+
+    /// Probability of each component for each row, `[n, n_components]`.
+    pub fn predict_proba(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let (log_resp, row_ll) = self.log_resp(x)?;
+        let proba = log_resp.iter().map(|v| T::from_f64(v.exp())).collect();
+        Tensor::new(proba, vec![row_ll.len(), self.n_components])
+    }
+
+    /// Most probable component of each row.
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let (log_resp, row_ll) = self.log_resp(x)?;
+        let k = self.n_components;
+        let labels = log_resp
+            .chunks(k)
+            .map(|row| {
+                let best = row.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).map_or(0, |(c, _)| c);
+                T::from_usize(best)
+            })
+            .collect();
+        Tensor::new(labels, vec![row_ll.len()])
+    }
+
+    /// Log-likelihood of each row under the mixture.
+    pub fn score_samples(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let (_, row_ll) = self.log_resp(x)?;
+        let n = row_ll.len();
+        Tensor::new(row_ll.into_iter().map(T::from_f64).collect(), vec![n])
+    }
+
+    /// Mean log-likelihood of the rows of `x`.
+    pub fn score(&self, x: &Tensor<T>) -> TensorResult<f64> {
+        let (_, row_ll) = self.log_resp(x)?;
+        Ok(row_ll.iter().sum::<f64>() / row_ll.len().max(1) as f64)
+    }
+
+    /// Number of free parameters: weights, means and covariances.
+    pub fn n_parameters(&self) -> TensorResult<usize> {
+        let means = self.means.as_ref().ok_or_else(|| TensorError::InvalidOperation("Model not fitted".into()))?;
+        let k = self.n_components;
+        let d = means.shape().dim(1)?;
+        let cov = match self.covariance_type {
+            CovarianceType::Full => k * d * (d + 1) / 2,
+            CovarianceType::Diag => k * d,
+            CovarianceType::Spherical => k,
+        };
+        Ok(cov + k * d + k - 1)
+    }
+
+    /// Akaike information criterion on `x`; lower is better.
+    pub fn aic(&self, x: &Tensor<T>) -> TensorResult<f64> {
+        let n = x.shape().dim(0)? as f64;
+        Ok(-2.0 * self.score(x)? * n + 2.0 * self.n_parameters()? as f64)
+    }
+
+    /// Bayesian information criterion on `x`; lower is better.
+    pub fn bic(&self, x: &Tensor<T>) -> TensorResult<f64> {
+        let n = x.shape().dim(0)? as f64;
+        Ok(-2.0 * self.score(x)? * n + self.n_parameters()? as f64 * n.ln())
+    }
+}
+
+/// Mixture parameters in flat `f64` form, laid out like the public tensors.
+struct Params {
+    weights: Vec<f64>,
+    means: Vec<f64>,
+    covariances: Vec<f64>,
+}
+
+fn sq_dist(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn log_sum_exp(values: &[f64]) -> f64 {
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+/// Turn weighted log densities (`[n * k]`, in place) into log
+/// responsibilities; returns each row's log-likelihood.
+fn normalize(log_prob: &mut [f64], k: usize) -> Vec<f64> {
+    log_prob
+        .chunks_mut(k)
+        .map(|row| {
+            let lse = log_sum_exp(row);
+            row.iter_mut().for_each(|v| *v -= lse);
+            lse
+        })
+        .collect()
+}
+
+/// `log(weight_c) + log N(x_i | mean_c, cov_c)` for every row and
+/// component, `[n * k]`.
+fn weighted_log_prob(data: &[f64], n: usize, d: usize, params: &Params, covariance_type: CovarianceType) -> TensorResult<Vec<f64>> {
+    let k = params.weights.len();
+    let log_2pi = d as f64 * (2.0 * std::f64::consts::PI).ln();
+    let mut out = vec![0.0; n * k];
+    for c in 0..k {
+        let mean = &params.means[c * d..(c + 1) * d];
+        let log_weight = params.weights[c].ln();
+        match covariance_type {
+            CovarianceType::Full => {
+                let cov = Tensor::new(params.covariances[c * d * d..(c + 1) * d * d].to_vec(), vec![d, d])?;
+                let chol = cholesky(&cov)
+                    .map_err(|_| TensorError::InvalidOperation(format!("covariance of component {} is not positive definite; increase reg_covar", c)))?;
+                let l = chol.l.data();
+                let log_det: f64 = (0..d).map(|j| 2.0 * l[j * d + j].ln()).sum();
+                let mut z = vec![0.0; d];
+                for i in 0..n {
+                    // Solve L z = x - mean; the Mahalanobis term is |z|².
+                    for a in 0..d {
+                        let mut v = data[i * d + a] - mean[a];
+                        for b in 0..a {
+                            v -= l[a * d + b] * z[b];
+                        }
+                        z[a] = v / l[a * d + a];
+                    }
+                    let maha: f64 = z.iter().map(|v| v * v).sum();
+                    out[i * k + c] = log_weight - 0.5 * (log_2pi + log_det + maha);
+                }
+            }
+            CovarianceType::Diag | CovarianceType::Spherical => {
+                let var: Vec<f64> = if covariance_type == CovarianceType::Diag {
+                    params.covariances[c * d..(c + 1) * d].to_vec()
+                } else {
+                    vec![params.covariances[c]; d]
+                };
+                let log_det: f64 = var.iter().map(|v| v.ln()).sum();
+                for i in 0..n {
+                    let maha: f64 = (0..d).map(|j| (data[i * d + j] - mean[j]).powi(2) / var[j]).sum();
+                    out[i * k + c] = log_weight - 0.5 * (log_2pi + log_det + maha);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaussian_mixture() {
+        // A round blob at the origin and a stretched one along x at (10, 10).
+        let mut rows = Vec::new();
+        for i in 0..5 {
+            for j in 0..5 {
+                rows.push(vec![i as f64 * 0.2 - 0.4, j as f64 * 0.2 - 0.4]);
+                rows.push(vec![10.0 + i as f64 - 2.0, 10.0 + j as f64 * 0.1 - 0.2]);
+            }
+        }
+        let x: Tensor<f64> = Tensor::from_vec2d(&rows).unwrap();
+
+        for cov in [CovarianceType::Full, CovarianceType::Diag, CovarianceType::Spherical] {
+            let mut gmm = GaussianMixture::new(2).with_covariance_type(cov);
+            gmm.seed = Some(7);
+            gmm.fit(&x).unwrap();
+            let labels = gmm.predict(&x).unwrap();
+            let (a, b) = (labels.data()[0], labels.data()[1]);
+            assert_ne!(a, b);
+            for pair in labels.data().chunks(2) {
+                assert_eq!(pair, &[a, b]);
+            }
+            let proba = gmm.predict_proba(&x).unwrap();
+            for row in proba.data().chunks(2) {
+                assert!((row[0] + row[1] - 1.0).abs() < 1e-9);
+            }
+        }
+
+        let mut diag = GaussianMixture::new(2).with_covariance_type(CovarianceType::Diag);
+        diag.seed = Some(7);
+        diag.fit(&x).unwrap();
+        let cov = diag.covariances.as_ref().unwrap().data();
+        let stretched = if diag.means.as_ref().unwrap().data()[0] > 5.0 { 0 } else { 1 };
+        assert!(cov[stretched * 2] > 10.0 * cov[stretched * 2 + 1]);
+
+        let mut one = GaussianMixture::new(1);
+        one.seed = Some(7);
+        one.fit(&x).unwrap();
+        let mut two = GaussianMixture::new(2);
+        two.seed = Some(7);
+        two.fit(&x).unwrap();
+        assert!(two.bic(&x).unwrap() < one.bic(&x).unwrap());
+        assert!(two.aic(&x).unwrap() < one.aic(&x).unwrap());
+        assert_eq!(two.n_parameters().unwrap(), 11);
+    }
+}
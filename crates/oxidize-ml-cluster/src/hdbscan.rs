@@ -0,0 +1,302 @@
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_neighbors::Algorithm;
+
+use crate::optics::core_distances;
+
+/// A cluster of the condensed tree.
+struct CondensedCluster {
+    parent: Option<usize>,
+    /// `1 / distance` at which the cluster split off its parent.
+    birth: f64,
+    stability: f64,
+    children: Vec<usize>,
+}
+
+/// `a - b`, treating two infinite lambdas as equal.
+fn lambda_gap(a: f64, b: f64) -> f64 {
+    if a == b { 0.0 } else { a - b }
+}
+
+/// Whether condensed cluster `c` is `ancestor` or lies below it.
+fn descends(clusters: &[CondensedCluster], mut c: usize, ancestor: usize) -> bool {
+    loop {
+        if c == ancestor {
+            return true;
+        }
+        match clusters[c].parent {
+            Some(p) => c = p,
+            None => return false,
+        }
+    }
+}
+
+/// HDBSCAN — hierarchical density-based clustering. Builds the single-linkage
+/// tree of mutual reachability distances, condenses it into clusters of at
+/// least `min_cluster_size` points and keeps the most stable ones, so
+/// clusters of different densities are found without choosing an `eps`.
+pub struct HDBSCAN<T: Float> {
+    pub min_cluster_size: usize,
+    /// Neighbours used for core distances; `min_cluster_size` when `None`.
+    pub min_samples: Option<usize>,
+    /// Distance between points; Euclidean by default.
+    pub metric: DistanceMetric<T>,
+    pub algorithm: Algorithm,
+    /// Allow the whole data set to come out as one cluster.
+    pub allow_single_cluster: bool,
+    /// Cluster of each point; -1 marks noise.
+    pub labels: Option<Tensor<T>>,
+    /// How strongly each point belongs to its cluster, in `[0, 1]`; 0 for
+    /// noise.
+    pub probabilities: Option<Tensor<T>>,
+}
+
+impl<T: Float> HDBSCAN<T> {
+    pub fn new(min_cluster_size: usize) -> Self {
+        HDBSCAN {
+            min_cluster_size,
+            min_samples: None,
+            metric: DistanceMetric::Euclidean,
+            algorithm: Algorithm::Auto,
+            allow_single_cluster: false,
+            labels: None,
+            probabilities: None,
+        }
+    }
+
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = Some(min_samples);
+        self
+    }
+
+    pub fn with_metric(mut self, metric: DistanceMetric<T>) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_allow_single_cluster(mut self, allow: bool) -> Self {
+        self.allow_single_cluster = allow;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let d = x.shape().dim(1)?;
+        if self.min_cluster_size < 2 {
+            return Err(TensorError::InvalidOperation("min_cluster_size must be at least 2".into()));
+        }
+        let min_samples = self.min_samples.unwrap_or(self.min_cluster_size).min(n);
+        let (_, core) = core_distances(x, min_samples, &self.metric, self.algorithm)?;
+        let row = |i: usize| &x.data()[i * d..(i + 1) * d];
+
+        // Prim's minimum spanning tree on mutual reachability distances.
+        let mut in_tree = vec![false; n];
+        let mut best = vec![f64::INFINITY; n];
+        let mut best_from = vec![0usize; n];
+        let mut edges = Vec::with_capacity(n.saturating_sub(1));
+        let mut current = 0;
+        for _ in 1..n {
+            in_tree[current] = true;
+            for j in 0..n {
+                if in_tree[j] {
+                    continue;
+                }
+                let dist = self.metric.distance(row(current), row(j)).to_f64();
+                let mutual = dist.max(core[current]).max(core[j]);
+                if mutual < best[j] {
+                    best[j] = mutual;
+                    best_from[j] = current;
+                }
+            }
+            let next = (0..n)
+                .filter(|&j| !in_tree[j])
+                .min_by(|&a, &b| best[a].total_cmp(&best[b]))
+                .unwrap();
+            edges.push((best_from[next], next, best[next]));
+            current = next;
+        }
+        edges.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+        // Single-linkage tree: node `n + i` is the `i`-th merge.
+        let mut parent: Vec<usize> = (0..2 * n).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        let mut merges: Vec<(usize, usize, f64)> = Vec::with_capacity(edges.len());
+        let mut size = vec![1usize; 2 * n];
+        for &(a, b, dist) in &edges {
+            let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+            let node = n + merges.len();
+            parent[ra] = node;
+            parent[rb] = node;
+            size[node] = size[ra] + size[rb];
+            merges.push((ra, rb, dist));
+        }
+
+        // Condense the tree: walking down from the root, a split only makes
+        // new clusters when both sides have `min_cluster_size` points;
+        // otherwise the smaller side's points fall out of the cluster.
+        let leaves = |node: usize| {
+            let mut out = Vec::new();
+            let mut stack = vec![node];
+            while let Some(v) = stack.pop() {
+                if v < n {
+                    out.push(v);
+                } else {
+                    let (a, b, _) = merges[v - n];
+                    stack.push(a);
+                    stack.push(b);
+                }
+            }
+            out
+        };
+        let mcs = self.min_cluster_size;
+        let mut clusters = vec![CondensedCluster { parent: None, birth: 0.0, stability: 0.0, children: Vec::new() }];
+        // (point, cluster it fell out of, lambda).
+        let mut fallen: Vec<(usize, usize, f64)> = Vec::with_capacity(n);
+        let mut stack = Vec::new();
+        if n == 1 {
+            fallen.push((0, 0, f64::INFINITY));
+        } else if n > 1 {
+            stack.push((2 * n - 2, 0usize));
+        }
+        while let Some((node, cluster)) = stack.pop() {
+            let (a, b, dist) = merges[node - n];
+            let lambda = if dist > 0.0 { 1.0 / dist } else { f64::INFINITY };
+            let (big_a, big_b) = (size[a] >= mcs, size[b] >= mcs);
+            if big_a && big_b {
+                for child in [a, b] {
+                    let id = clusters.len();
+                    clusters.push(CondensedCluster { parent: Some(cluster), birth: lambda, stability: 0.0, children: Vec::new() });
+                    clusters[cluster].children.push(id);
+                    stack.push((child, id));
+                }
+                continue;
+            }
+            for child in [a, b] {
+                if size[child] >= mcs {
+                    stack.push((child, cluster));
+                } else if child < n {
+                    fallen.push((child, cluster, lambda));
+                } else {
+                    fallen.extend(leaves(child).into_iter().map(|p| (p, cluster, lambda)));
+                }
+            }
+        }
+
+        // Stability: how long (in lambda) each point stays in the cluster.
+        for &(_, c, lambda) in &fallen {
+            clusters[c].stability += lambda_gap(lambda, clusters[c].birth);
+        }
+        for c in 1..clusters.len() {
+            let p = clusters[c].parent.unwrap();
+            let child_size = fallen.iter().filter(|&&(_, fc, _)| descends(&clusters, fc, c)).count();
+            clusters[p].stability += lambda_gap(clusters[c].birth, clusters[p].birth) * child_size as f64;
+        }
+
+        // Excess of mass: keep a cluster when it is more stable than the
+        // best selection among its descendants. Children have higher ids.
+        let mut selected = vec![false; clusters.len()];
+        let mut best_stability: Vec<f64> = clusters.iter().map(|c| c.stability).collect();
+        let first = if self.allow_single_cluster { 0 } else { 1 };
+        for c in (first..clusters.len()).rev() {
+            let children_total: f64 = clusters[c].children.iter().map(|&ch| best_stability[ch]).sum();
+            if clusters[c].children.is_empty() || clusters[c].stability >= children_total {
+                selected[c] = true;
+                let mut below = clusters[c].children.clone();
+                while let Some(ch) = below.pop() {
+                    selected[ch] = false;
+                    below.extend(clusters[ch].children.iter().copied());
+                }
+            } else {
+                best_stability[c] = children_total;
+            }
+        }
+
+        let mut label_of = vec![-1i64; clusters.len()];
+        let mut next_label = 0;
+        for c in 0..clusters.len() {
+            if selected[c] {
+                label_of[c] = next_label;
+                next_label += 1;
+            }
+        }
+        let mut labels = vec![-1i64; n];
+        let mut point_lambda = vec![0.0; n];
+        let mut owner = vec![None; n];
+        for &(p, c, lambda) in &fallen {
+            let mut up = Some(c);
+            while let Some(u) = up {
+                if selected[u] {
+                    labels[p] = label_of[u];
+                    owner[p] = Some(u);
+                    point_lambda[p] = lambda;
+                    break;
+                }
+                up = clusters[u].parent;
+            }
+        }
+        let mut max_lambda = vec![0.0f64; clusters.len()];
+        for p in 0..n {
+            if let Some(u) = owner[p] {
+                max_lambda[u] = max_lambda[u].max(point_lambda[p]);
+            }
+        }
+        let probabilities: Vec<f64> = (0..n)
+            .map(|p| match owner[p] {
+                Some(u) if point_lambda[p] >= max_lambda[u] => 1.0,
+                Some(u) => point_lambda[p] / max_lambda[u],
+                None => 0.0,
+            })
+            .collect();
+
+        self.labels = Some(Tensor::new(labels.into_iter().map(|l| T::from_f64(l as f64)).collect(), vec![n])?);
+        self.probabilities = Some(Tensor::new(probabilities.into_iter().map(T::from_f64).collect(), vec![n])?);
+        Ok(())
+    }
+
+    pub fn fit_predict(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        Ok(self.labels.clone().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hdbscan_variable_density() {
+        // A tight and a loose blob; no single DBSCAN eps suits both.
+        let mut rows = Vec::new();
+        for i in 0..4 {
+            for j in 0..4 {
+                rows.push(vec![i as f64 * 0.05, j as f64 * 0.05]);
+                rows.push(vec![10.0 + i as f64, j as f64]);
+            }
+        }
+        rows.push(vec![50.0, 50.0]);
+        let x: Tensor<f64> = Tensor::from_vec2d(&rows).unwrap();
+
+        let mut model = HDBSCAN::new(5);
+        let labels = model.fit_predict(&x).unwrap();
+        let l = labels.data();
+        assert!(l[0] >= 0.0 && l[1] >= 0.0 && l[0] != l[1]);
+        for pair in l[..32].chunks(2) {
+            assert_eq!(pair, &[l[0], l[1]]);
+        }
+        assert_eq!(l[32], -1.0);
+        let probabilities = model.probabilities.as_ref().unwrap().data();
+        assert_eq!(probabilities[32], 0.0);
+        assert!(probabilities[..32].iter().all(|&p| p > 0.0 && p <= 1.0));
+    }
+}
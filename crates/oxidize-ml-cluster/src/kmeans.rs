@@ -1,10 +1,64 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{Float, RngHandle, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
-use rand::rngs::StdRng;
-use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+/// Pick `n_clusters` rows of `x` as starting centres with k-means++: each
+/// after the first is drawn with probability proportional to its squared
+/// distance from the nearest centre already chosen. Returns them flat
+/// (`[n_clusters * d]`).
+pub(crate) fn kmeans_plus_plus<T: Float>(x: &Tensor<T>, n_clusters: usize, rng: &mut RngHandle) -> TensorResult<Vec<T>> {
+    let n = x.shape().dim(0)?;
+    let d = x.shape().dim(1)?;
+    if n == 0 {
+        return Err(TensorError::InvalidOperation("cannot initialize centres from no points".into()));
+    }
+    let mut centroids = Vec::with_capacity(n_clusters * d);
+
+    // Pick first centroid randomly
+    let first = (rand::Rng::gen::<f64>(rng) * n as f64) as usize;
+    let first = first.min(n - 1);
+    centroids.extend_from_slice(x.index_select(0, &[first])?.data());
+
+    // Pick remaining centroids proportional to distance²
+    for _k in 1..n_clusters {
+        let mut distances = vec![T::INFINITY; n];
+        let n_existing = centroids.len() / d.max(1);
+
+        for i in 0..n {
+            for c in 0..n_existing {
+                let mut dist = T::ZERO;
+                for j in 0..d {
+                    let diff = x.get(&[i, j])? - centroids[c * d + j];
+                    dist += diff * diff;
+                }
+                if dist < distances[i] {
+                    distances[i] = dist;
+                }
+            }
+        }
+
+        let total: T = distances.iter().copied().sum();
+        let threshold = T::from_f64(rand::Rng::gen::<f64>(rng)) * total;
+        let mut cumulative = T::ZERO;
+        let mut selected = 0;
+        for (i, &d) in distances.iter().enumerate() {
+            cumulative += d;
+            if cumulative >= threshold {
+                selected = i;
+                break;
+            }
+        }
+
+        centroids.extend_from_slice(x.index_select(0, &[selected])?.data());
+    }
+
+    Ok(centroids)
+}
 
 /// K-Means clustering with k-means++ initialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct KMeans<T: Float> {
     pub n_clusters: usize,
     pub max_iter: usize,
@@ -24,7 +78,7 @@ where
             n_clusters,
             max_iter,
             tol: T::from_f64(1e-4),
-            seed: Some(42),
+            seed: None,
             centroids: None,
             labels: None,
             inertia: None,
@@ -37,7 +91,8 @@ where
         let d = x.shape().dim(1)?;
 
         // K-means++ initialization
-        let mut centroids = self.init_centroids_pp(x, n, d)?;
+        let mut rng = RngHandle::new(self.seed);
+        let mut centroids = kmeans_plus_plus(x, self.n_clusters, &mut rng)?;
         let mut labels = vec![0usize; n];
 
         for _iter in 0..self.max_iter {
@@ -49,7 +104,7 @@ where
                     let mut dist = T::ZERO;
                     for j in 0..d {
                         let diff = x.get(&[i, j])? - centroids[k * d + j];
-                        dist = dist + diff * diff;
+                        dist += diff * diff;
                     }
                     if dist < best_dist {
                         best_dist = dist;
@@ -66,13 +121,13 @@ where
                 let k = labels[i];
                 counts[k] += 1;
                 for j in 0..d {
-                    new_centroids[k * d + j] = new_centroids[k * d + j] + x.get(&[i, j])?;
+                    new_centroids[k * d + j] += x.get(&[i, j])?;
                 }
             }
             for k in 0..self.n_clusters {
                 if counts[k] > 0 {
                     for j in 0..d {
-                        new_centroids[k * d + j] = new_centroids[k * d + j] / T::from_usize(counts[k]);
+                        new_centroids[k * d + j] /= T::from_usize(counts[k]);
                     }
                 }
             }
@@ -98,7 +153,7 @@ where
             let k = labels[i];
             for j in 0..d {
                 let diff = x.get(&[i, j])? - centroids[k * d + j];
-                inertia = inertia + diff * diff;
+                inertia += diff * diff;
             }
         }
 
@@ -110,59 +165,6 @@ where
         Ok(())
     }
 
-    fn init_centroids_pp(&self, x: &Tensor<T>, n: usize, d: usize) -> TensorResult<Vec<T>> {
-        let mut rng = match self.seed {
-            Some(s) => StdRng::seed_from_u64(s),
-            None => StdRng::from_entropy(),
-        };
-
-        let mut centroids = Vec::with_capacity(self.n_clusters * d);
-
-        // Pick first centroid randomly
-        let first = (rand::Rng::gen::<f64>(&mut rng) * n as f64) as usize;
-        let first = first.min(n - 1);
-        for j in 0..d {
-            centroids.push(x.get(&[first, j])?);
-        }
-
-        // Pick remaining centroids proportional to distance²
-        for _k in 1..self.n_clusters {
-            let mut distances = vec![T::INFINITY; n];
-            let n_existing = centroids.len() / d;
-
-            for i in 0..n {
-                for c in 0..n_existing {
-                    let mut dist = T::ZERO;
-                    for j in 0..d {
-                        let diff = x.get(&[i, j])? - centroids[c * d + j];
-                        dist = dist + diff * diff;
-                    }
-                    if dist < distances[i] {
-                        distances[i] = dist;
-                    }
-                }
-            }
-
-            let total: T = distances.iter().copied().sum();
-            let threshold = T::from_f64(rand::Rng::gen::<f64>(&mut rng)) * total;
-            let mut cumulative = T::ZERO;
-            let mut selected = 0;
-            for (i, &d) in distances.iter().enumerate() {
-                cumulative = cumulative + d;
-                if cumulative >= threshold {
-                    selected = i;
-                    break;
-                }
-            }
-
-            for j in 0..d {
-                centroids.push(x.get(&[selected, j])?);
-            }
-        }
-
-        Ok(centroids)
-    }
-
     /// Predict cluster labels for new data.
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
         let centroids = self.centroids.as_ref().ok_or_else(|| {
@@ -179,7 +181,7 @@ where
                 let mut dist = T::ZERO;
                 for j in 0..d {
                     let diff = x.get(&[i, j])? - centroids.get(&[k, j])?;
-                    dist = dist + diff * diff;
+                    dist += diff * diff;
                 }
                 if dist < best_dist {
                     best_dist = dist;
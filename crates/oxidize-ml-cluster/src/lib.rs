@@ -1,7 +1,13 @@
 pub mod kmeans;
 pub mod dbscan;
 pub mod agglomerative;
+pub mod gmm;
+pub mod optics;
+pub mod hdbscan;
 
 pub use kmeans::*;
 pub use dbscan::*;
 pub use agglomerative::*;
+pub use gmm::*;
+pub use optics::*;
+pub use hdbscan::*;
@@ -0,0 +1,185 @@
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_neighbors::{Algorithm, NearestNeighbors};
+
+/// Fit a neighbour index on `x` and return it with each point's core
+/// distance: the distance to its `min_samples`-th nearest point, counting
+/// the point itself.
+pub(crate) fn core_distances<T: Float>(
+    x: &Tensor<T>,
+    min_samples: usize,
+    metric: &DistanceMetric<T>,
+    algorithm: Algorithm,
+) -> TensorResult<(NearestNeighbors<T>, Vec<f64>)> {
+    let n = x.shape().dim(0)?;
+    if min_samples == 0 || min_samples > n {
+        return Err(TensorError::InvalidOperation(format!(
+            "min_samples must be between 1 and the number of points ({}), got {}", n, min_samples
+        )));
+    }
+    let mut index = NearestNeighbors::new(min_samples, metric.clone()).with_algorithm(algorithm);
+    index.fit(x)?;
+    let (_, distances) = index.kneighbors(x)?;
+    let core = distances.data().chunks(min_samples).map(|row| row[min_samples - 1].to_f64()).collect();
+    Ok((index, core))
+}
+
+/// OPTICS — orders the points so that density-connected points sit next to
+/// each other and records each one's reachability distance. Clusters are
+/// read off the reachability plot, e.g. with `extract_dbscan`, for any
+/// `eps` up to `max_eps` without refitting.
+pub struct OPTICS<T: Float> {
+    pub min_samples: usize,
+    /// Neighbourhoods are cut at this radius; unbounded by default.
+    pub max_eps: T,
+    /// Distance between points; Euclidean by default.
+    pub metric: DistanceMetric<T>,
+    pub algorithm: Algorithm,
+    /// Points in visiting order.
+    pub ordering: Option<Vec<usize>>,
+    /// Reachability distance of each point (indexed by point, infinite for
+    /// the first point of each density region).
+    pub reachability: Option<Tensor<T>>,
+    /// Distance to each point's `min_samples`-th neighbour; infinite past
+    /// `max_eps`.
+    pub core_distances: Option<Tensor<T>>,
+    /// The point each one was reached from, if any.
+    pub predecessor: Option<Vec<Option<usize>>>,
+}
+
+impl<T: Float> OPTICS<T> {
+    pub fn new(min_samples: usize) -> Self {
+        OPTICS {
+            min_samples,
+            max_eps: T::INFINITY,
+            metric: DistanceMetric::Euclidean,
+            algorithm: Algorithm::Auto,
+            ordering: None,
+            reachability: None,
+            core_distances: None,
+            predecessor: None,
+        }
+    }
+
+    pub fn with_max_eps(mut self, max_eps: T) -> Self {
+        self.max_eps = max_eps;
+        self
+    }
+
+    pub fn with_metric(mut self, metric: DistanceMetric<T>) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let d = x.shape().dim(1)?;
+        let (index, mut core) = core_distances(x, self.min_samples, &self.metric, self.algorithm)?;
+        let max_eps = self.max_eps.to_f64();
+        for c in &mut core {
+            if *c > max_eps {
+                *c = f64::INFINITY;
+            }
+        }
+
+        let mut reach = vec![f64::INFINITY; n];
+        let mut predecessor = vec![None; n];
+        let mut processed = vec![false; n];
+        let mut ordering = Vec::with_capacity(n);
+        for _ in 0..n {
+            // Visit the unprocessed point with the smallest reachability;
+            // ties (including "unreached") go to the lowest index.
+            let p = (0..n)
+                .filter(|&i| !processed[i])
+                .min_by(|&a, &b| reach[a].total_cmp(&reach[b]).then(a.cmp(&b)))
+                .unwrap();
+            processed[p] = true;
+            ordering.push(p);
+            if core[p].is_infinite() {
+                continue;
+            }
+            for (o, dist) in index.query_radius(&x.data()[p * d..(p + 1) * d], self.max_eps)? {
+                if processed[o] {
+                    continue;
+                }
+                let r = core[p].max(dist.to_f64());
+                if r < reach[o] {
+                    reach[o] = r;
+                    predecessor[o] = Some(p);
+                }
+            }
+        }
+
+        let to_tensor = |v: Vec<f64>| Tensor::new(v.into_iter().map(T::from_f64).collect(), vec![n]);
+        self.reachability = Some(to_tensor(reach)?);
+        self.core_distances = Some(to_tensor(core)?);
+        self.ordering = Some(ordering);
+        self.predecessor = Some(predecessor);
+        Ok(())
+    }
+
+    /// Cluster labels equivalent to DBSCAN with radius `eps` (at most
+    /// `max_eps`); -1 marks noise.
+    pub fn extract_dbscan(&self, eps: T) -> TensorResult<Tensor<T>> {
+        let (ordering, reach, core) = match (&self.ordering, &self.reachability, &self.core_distances) {
+            (Some(o), Some(r), Some(c)) => (o, r.data(), c.data()),
+            _ => return Err(TensorError::InvalidOperation("Model not fitted".into())),
+        };
+        if eps > self.max_eps {
+            return Err(TensorError::InvalidOperation("eps must not exceed max_eps".into()));
+        }
+        let mut labels = vec![-1.0; ordering.len()];
+        let mut cluster = -1.0;
+        for &p in ordering {
+            if reach[p] > eps {
+                // Not reachable from the current cluster: starts a new one
+                // if it is itself a core point.
+                if core[p] <= eps {
+                    cluster += 1.0;
+                    labels[p] = cluster;
+                }
+            } else {
+                labels[p] = cluster;
+            }
+        }
+        Tensor::new(labels.into_iter().map(T::from_f64).collect(), vec![ordering.len()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optics_extract_dbscan() {
+        // A tight and a loose cluster plus one far-off point.
+        let mut rows = Vec::new();
+        for i in 0..6 {
+            rows.push(vec![i as f64 * 0.1, 0.0]);
+            rows.push(vec![20.0 + i as f64, 0.0]);
+        }
+        rows.push(vec![100.0, 0.0]);
+        let x: Tensor<f64> = Tensor::from_vec2d(&rows).unwrap();
+
+        let mut optics = OPTICS::new(3);
+        optics.fit(&x).unwrap();
+        assert_eq!(optics.ordering.as_ref().unwrap().len(), 13);
+
+        let tight = optics.extract_dbscan(0.2).unwrap();
+        let t = tight.data();
+        assert!(t[0] >= 0.0);
+        assert!((0..6).all(|i| t[2 * i] == t[0]));
+        assert!((0..6).all(|i| t[2 * i + 1] == -1.0));
+
+        let loose = optics.extract_dbscan(2.0).unwrap();
+        let l = loose.data();
+        assert!(l[0] >= 0.0 && l[1] >= 0.0 && l[0] != l[1]);
+        assert!((0..6).all(|i| l[2 * i] == l[0] && l[2 * i + 1] == l[1]));
+        assert_eq!(l[12], -1.0);
+    }
+}
@@ -0,0 +1,53 @@
+//! `HalfTensor` against plain `f32` storage: the cost of widening 16-bit
+//! weights before use, on its own and in front of a matmul.
+//!
+//! Run with `cargo bench -p oxidize-ml-core --bench half`. Half storage
+//! halves memory against `f32`; arithmetic always runs in `f32` after
+//! decoding, so the numbers here are the overhead paid for that saving.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oxidize_ml_core::{HalfType, Tensor};
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("half_decode");
+    let n = 1 << 20;
+    let x: Tensor<f32> = Tensor::randn(vec![n], Some(1));
+    group.throughput(Throughput::Elements(n as u64));
+
+    // Baseline: an f32 tensor is "decoded" by copying its buffer (`clone`
+    // only bumps a reference count).
+    group.bench_function("f32", |bench| {
+        bench.iter(|| black_box(Tensor::new(x.data().to_vec(), x.shape_vec()).unwrap()))
+    });
+    for dtype in [HalfType::F16, HalfType::BF16] {
+        let half = x.to_half(dtype);
+        group.bench_function(BenchmarkId::new("to_tensor", format!("{:?}", dtype)), |bench| {
+            bench.iter(|| black_box(half.to_tensor::<f32>().unwrap()))
+        });
+        group.bench_function(BenchmarkId::new("from_tensor", format!("{:?}", dtype)), |bench| {
+            bench.iter(|| black_box(x.to_half(dtype)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_matmul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("half_matmul");
+    group.sample_size(20);
+    let size = 256;
+    let a: Tensor<f32> = Tensor::randn(vec![size, size], Some(2));
+    let w: Tensor<f32> = Tensor::randn(vec![size, size], Some(3));
+    group.throughput(Throughput::Elements((2 * size * size * size) as u64));
+
+    group.bench_function("f32", |bench| bench.iter(|| black_box(a.matmul(&w).unwrap())));
+    for dtype in [HalfType::F16, HalfType::BF16] {
+        let half = w.to_half(dtype);
+        group.bench_function(BenchmarkId::new("decode_then_matmul", format!("{:?}", dtype)), |bench| {
+            bench.iter(|| black_box(a.matmul(&half.to_tensor::<f32>().unwrap()).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode, bench_matmul);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+//! Matmul throughput: blocked kernel vs. the old naive triple loop.
+//!
+//! Run with `cargo bench -p oxidize-ml-core` (add `--features rayon` for the
+//! multi-threaded numbers).
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oxidize_ml_core::Tensor;
+
+/// The pre-blocking `Tensor::matmul` loop, kept here as the baseline.
+fn naive_matmul(a: &[f64], b: &[f64], m: usize, k: usize, n: usize) -> Vec<f64> {
+    let mut c = vec![0.0; m * n];
+    for i in 0..m {
+        for j in 0..n {
+            let mut sum = 0.0;
+            for p in 0..k {
+                sum += a[i * k + p] * b[p * n + j];
+            }
+            c[i * n + j] = sum;
+        }
+    }
+    c
+}
+
+fn bench_matmul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("matmul_f64");
+    group.sample_size(10);
+
+    for &size in &[512usize, 2048] {
+        let a: Tensor<f64> = Tensor::randn(vec![size, size], Some(1));
+        let b: Tensor<f64> = Tensor::randn(vec![size, size], Some(2));
+        group.throughput(Throughput::Elements((2 * size * size * size) as u64));
+
+        group.bench_with_input(BenchmarkId::new("blocked", size), &size, |bench, _| {
+            bench.iter(|| black_box(a.matmul(&b).unwrap()))
+        });
+
+        // The naive loop takes minutes per iteration at 2048; only run it at 512.
+        if size <= 512 {
+            group.bench_with_input(BenchmarkId::new("naive", size), &size, |bench, &s| {
+                bench.iter(|| black_box(naive_matmul(a.data(), b.data(), s, s, s)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_matmul);
+criterion_main!(benches);
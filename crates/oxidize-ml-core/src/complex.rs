@@ -0,0 +1,382 @@
+use crate::dtype::Float;
+use crate::error::{TensorError, TensorResult};
+use crate::shape::Shape;
+use crate::tensor::Tensor;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Complex number with `Float` components.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct Complex<T: Float> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T: Float> Complex<T> {
+    pub fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn from_real(re: T) -> Self {
+        Complex { re, im: T::ZERO }
+    }
+
+    /// `r · e^{iθ}`.
+    pub fn from_polar(r: T, theta: T) -> Self {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// Squared magnitude `re² + im²`.
+    pub fn norm_sqr(self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// Magnitude `|z|`.
+    pub fn abs(self) -> T {
+        // Scale by the larger component to avoid overflow in re² + im².
+        let (a, b) = (self.re.abs(), self.im.abs());
+        let (big, small) = if a > b { (a, b) } else { (b, a) };
+        if big == T::ZERO {
+            return T::ZERO;
+        }
+        let r = small / big;
+        big * (T::ONE + r * r).sqrt()
+    }
+
+    /// Phase angle in `(-π, π]`.
+    pub fn arg(self) -> T {
+        T::from_f64(self.im.to_f64().atan2(self.re.to_f64()))
+    }
+
+    pub fn scale(self, s: T) -> Self {
+        Complex::new(self.re * s, self.im * s)
+    }
+}
+
+impl<T: Float> Add for Complex<T> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl<T: Float> Sub for Complex<T> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl<T: Float> Mul for Complex<T> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl<T: Float> Div for Complex<T> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let d = rhs.norm_sqr();
+        let n = self * rhs.conj();
+        Complex::new(n.re / d, n.im / d)
+    }
+}
+
+impl<T: Float> Neg for Complex<T> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl<T: Float> fmt::Debug for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.im < T::ZERO {
+            write!(f, "{}-{}i", self.re, -self.im)
+        } else {
+            write!(f, "{}+{}i", self.re, self.im)
+        }
+    }
+}
+
+/// N-dimensional tensor of complex numbers (row-major).
+///
+/// Real-valued tensors convert in with `from_real`; `re`, `im`, `abs` and
+/// `arg` convert back. FFTs run along the last axis — see `fft`, `ifft`
+/// and `Tensor::rfft`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct ComplexTensor<T: Float> {
+    data: Vec<Complex<T>>,
+    shape: Shape,
+}
+
+impl<T: Float> ComplexTensor<T> {
+    // ─── Construction ───────────────────────────────────────────────────────
+
+    pub fn new(data: Vec<Complex<T>>, shape: Vec<usize>) -> TensorResult<Self> {
+        let s = Shape::new(shape);
+        if data.len() != s.numel() {
+            return Err(TensorError::ShapeMismatch {
+                expected: s.to_vec(),
+                got: vec![data.len()],
+            });
+        }
+        Ok(ComplexTensor { data, shape: s })
+    }
+
+    pub fn zeros(shape: Vec<usize>) -> Self {
+        let s = Shape::new(shape);
+        ComplexTensor {
+            data: vec![Complex::default(); s.numel()],
+            shape: s,
+        }
+    }
+
+    /// Real tensor with zero imaginary part.
+    pub fn from_real(re: &Tensor<T>) -> Self {
+        ComplexTensor {
+            data: re.data().iter().map(|&r| Complex::from_real(r)).collect(),
+            shape: re.shape().clone(),
+        }
+    }
+
+    /// Combine real and imaginary parts of the same shape.
+    pub fn from_re_im(re: &Tensor<T>, im: &Tensor<T>) -> TensorResult<Self> {
+        if re.shape() != im.shape() {
+            return Err(TensorError::ShapeMismatch {
+                expected: re.shape_vec(),
+                got: im.shape_vec(),
+            });
+        }
+        Ok(ComplexTensor {
+            data: re.data().iter().zip(im.data()).map(|(&r, &i)| Complex::new(r, i)).collect(),
+            shape: re.shape().clone(),
+        })
+    }
+
+    // ─── Accessors ──────────────────────────────────────────────────────────
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn shape_vec(&self) -> Vec<usize> {
+        self.shape.to_vec()
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.shape.ndim()
+    }
+
+    pub fn numel(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn data(&self) -> &[Complex<T>] {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut [Complex<T>] {
+        &mut self.data
+    }
+
+    pub fn into_data(self) -> Vec<Complex<T>> {
+        self.data
+    }
+
+    // ─── Element-wise Operations ────────────────────────────────────────────
+
+    fn map_real<F: Fn(Complex<T>) -> T>(&self, f: F) -> Tensor<T> {
+        let data = self.data.iter().map(|&z| f(z)).collect();
+        Tensor::new(data, self.shape.to_vec()).expect("same number of elements")
+    }
+
+    pub fn re(&self) -> Tensor<T> { self.map_real(|z| z.re) }
+    pub fn im(&self) -> Tensor<T> { self.map_real(|z| z.im) }
+    pub fn abs(&self) -> Tensor<T> { self.map_real(Complex::abs) }
+    pub fn arg(&self) -> Tensor<T> { self.map_real(Complex::arg) }
+
+    pub fn apply<F: Fn(Complex<T>) -> Complex<T>>(&self, f: F) -> ComplexTensor<T> {
+        ComplexTensor {
+            data: self.data.iter().map(|&z| f(z)).collect(),
+            shape: self.shape.clone(),
+        }
+    }
+
+    pub fn conj(&self) -> ComplexTensor<T> {
+        self.apply(Complex::conj)
+    }
+
+    pub fn mul_scalar(&self, s: T) -> ComplexTensor<T> {
+        self.apply(|z| z.scale(s))
+    }
+
+    fn zip_with<F: Fn(Complex<T>, Complex<T>) -> Complex<T>>(
+        &self,
+        other: &ComplexTensor<T>,
+        f: F,
+    ) -> TensorResult<ComplexTensor<T>> {
+        if self.shape != other.shape {
+            return Err(TensorError::ShapeMismatch {
+                expected: self.shape_vec(),
+                got: other.shape_vec(),
+            });
+        }
+        Ok(ComplexTensor {
+            data: self.data.iter().zip(&other.data).map(|(&a, &b)| f(a, b)).collect(),
+            shape: self.shape.clone(),
+        })
+    }
+
+    pub fn add(&self, other: &ComplexTensor<T>) -> TensorResult<ComplexTensor<T>> {
+        self.zip_with(other, |a, b| a + b)
+    }
+
+    pub fn sub(&self, other: &ComplexTensor<T>) -> TensorResult<ComplexTensor<T>> {
+        self.zip_with(other, |a, b| a - b)
+    }
+
+    pub fn mul(&self, other: &ComplexTensor<T>) -> TensorResult<ComplexTensor<T>> {
+        self.zip_with(other, |a, b| a * b)
+    }
+
+    pub fn div(&self, other: &ComplexTensor<T>) -> TensorResult<ComplexTensor<T>> {
+        self.zip_with(other, |a, b| a / b)
+    }
+
+    // ─── FFT ────────────────────────────────────────────────────────────────
+
+    /// Discrete Fourier transform along the last axis (unnormalized).
+    pub fn fft(&self) -> ComplexTensor<T> {
+        self.map_lanes(|lane| crate::fft::fft(lane, false))
+    }
+
+    /// Inverse DFT along the last axis, scaled by `1/n`.
+    pub fn ifft(&self) -> ComplexTensor<T> {
+        self.map_lanes(|lane| crate::fft::fft(lane, true))
+    }
+
+    /// Inverse of `Tensor::rfft`: rebuild a real signal of length `n` along
+    /// the last axis from its `n / 2 + 1` non-negative frequency bins.
+    pub fn irfft(&self, n: usize) -> TensorResult<Tensor<T>> {
+        let bins = self.shape.dims().last().copied().unwrap_or(1);
+        if bins != n / 2 + 1 {
+            return Err(TensorError::DimensionMismatch(format!(
+                "irfft of length {} needs {} bins, got {}",
+                n,
+                n / 2 + 1,
+                bins
+            )));
+        }
+        let mut dims = self.shape.to_vec();
+        if let Some(last) = dims.last_mut() {
+            *last = n;
+        }
+        let mut out = Vec::with_capacity(self.numel() / bins.max(1) * n);
+        for lane in self.data.chunks(bins.max(1)) {
+            // Rebuild the Hermitian-symmetric full spectrum.
+            let mut full = Vec::with_capacity(n);
+            full.extend_from_slice(&lane[..bins.min(n)]);
+            for k in bins..n {
+                full.push(lane[n - k].conj());
+            }
+            out.extend(crate::fft::fft(&full, true).into_iter().map(|z| z.re));
+        }
+        Tensor::new(out, dims)
+    }
+
+    fn map_lanes<F: Fn(&[Complex<T>]) -> Vec<Complex<T>>>(&self, f: F) -> ComplexTensor<T> {
+        let n = self.shape.dims().last().copied().unwrap_or(1);
+        if n == 0 {
+            return self.clone();
+        }
+        let data = self.data.chunks(n).flat_map(f).collect();
+        ComplexTensor {
+            data,
+            shape: self.shape.clone(),
+        }
+    }
+}
+
+impl<T: Float> Tensor<T> {
+    /// DFT of a real signal along the last axis, keeping the `n / 2 + 1`
+    /// non-negative frequency bins.
+    pub fn rfft(&self) -> ComplexTensor<T> {
+        let n = self.shape().dims().last().copied().unwrap_or(1);
+        let bins = n / 2 + 1;
+        let full = ComplexTensor::from_real(self).fft();
+        if n == 0 {
+            return full;
+        }
+        let data = full.data.chunks(n).flat_map(|lane| lane[..bins].to_vec()).collect();
+        let mut dims = self.shape_vec();
+        if let Some(last) = dims.last_mut() {
+            *last = bins;
+        }
+        ComplexTensor {
+            data,
+            shape: Shape::new(dims),
+        }
+    }
+}
+
+impl<T: Float> fmt::Debug for ComplexTensor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComplexTensor")
+            .field("data", &self.data)
+            .field("shape", &self.shape)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complex_arithmetic() {
+        let a = Complex::new(1.0, 2.0);
+        let b = Complex::new(3.0, -1.0);
+        assert_eq!(a * b, Complex::new(5.0, 5.0));
+        let q = (a * b) / b;
+        assert!((q.re - 1.0).abs() < 1e-12 && (q.im - 2.0).abs() < 1e-12);
+        assert_eq!(a.conj(), Complex::new(1.0, -2.0));
+        assert!((Complex::new(3.0, 4.0).abs() - 5.0_f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_complex_tensor_parts() {
+        let re: Tensor<f64> = Tensor::from_slice(&[1.0, 0.0]);
+        let im: Tensor<f64> = Tensor::from_slice(&[0.0, -2.0]);
+        let z = ComplexTensor::from_re_im(&re, &im).unwrap();
+        assert_eq!(z.abs().data(), &[1.0, 2.0]);
+        assert_eq!(z.conj().im().data(), &[0.0, 2.0]);
+        let zz = z.mul(&z.conj()).unwrap();
+        assert_eq!(zz.re().data(), &[1.0, 4.0]);
+    }
+
+    #[test]
+    fn test_rfft_roundtrip() {
+        for n in [1usize, 4, 7, 12] {
+            let x: Tensor<f64> = Tensor::randn(vec![2, n], Some(n as u64));
+            let spec = x.rfft();
+            assert_eq!(spec.shape_vec(), vec![2, n / 2 + 1]);
+            let back = spec.irfft(n).unwrap();
+            for (a, b) in back.data().iter().zip(x.data()) {
+                assert!((a - b).abs() < 1e-9, "n={}", n);
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Distances between points, shared by neighbour search and clustering.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::dtype::Float;
+use crate::tensor::Tensor;
+
+/// A user-supplied distance between two points of equal length.
+pub type DistanceFn<T> = Arc<dyn Fn(&[T], &[T]) -> T + Send + Sync>;
+
+/// Distance metric between two feature vectors.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub enum DistanceMetric<T: Float> {
+    Euclidean,
+    Manhattan,
+    /// `(Σ |a_i - b_i|^p)^(1/p)`, for `p >= 1`.
+    Minkowski(f64),
+    /// Largest coordinate difference.
+    Chebyshev,
+    /// `1 - cos(a, b)`; 1 when either point is zero. Not a true metric.
+    Cosine,
+    /// Fraction of coordinates that differ.
+    Hamming,
+    /// `sqrt((a - b)ᵀ VI (a - b))` for the inverse covariance matrix `VI`
+    /// (`[d, d]`).
+    Mahalanobis(Tensor<T>),
+    /// Any other distance. Cannot be saved with a model.
+    #[serde(skip)]
+    Custom(DistanceFn<T>),
+}
+
+impl<T: Float> DistanceMetric<T> {
+    /// Wrap a closure as a `Custom` metric.
+    pub fn custom<F: Fn(&[T], &[T]) -> T + Send + Sync + 'static>(f: F) -> Self {
+        DistanceMetric::Custom(Arc::new(f))
+    }
+
+    /// Distance between two points of equal length.
+    pub fn distance(&self, a: &[T], b: &[T]) -> T {
+        match self {
+            DistanceMetric::Euclidean => {
+                let mut dist = T::ZERO;
+                for (&x, &y) in a.iter().zip(b) {
+                    let diff = x - y;
+                    dist += diff * diff;
+                }
+                dist.sqrt()
+            }
+            DistanceMetric::Manhattan => a.iter().zip(b).map(|(&x, &y)| (x - y).abs()).sum(),
+            DistanceMetric::Minkowski(p) => {
+                let sum: f64 = a.iter().zip(b).map(|(&x, &y)| (x - y).abs().to_f64().powf(*p)).sum();
+                T::from_f64(sum.powf(1.0 / p))
+            }
+            DistanceMetric::Chebyshev => a
+                .iter()
+                .zip(b)
+                .map(|(&x, &y)| (x - y).abs())
+                .fold(T::ZERO, |m, v| if v > m { v } else { m }),
+            DistanceMetric::Cosine => {
+                let (mut dot, mut na, mut nb) = (T::ZERO, T::ZERO, T::ZERO);
+                for (&x, &y) in a.iter().zip(b) {
+                    dot += x * y;
+                    na += x * x;
+                    nb += y * y;
+                }
+                if na <= T::ZERO || nb <= T::ZERO {
+                    return T::ONE;
+                }
+                T::ONE - dot / (na.sqrt() * nb.sqrt())
+            }
+            DistanceMetric::Hamming => {
+                if a.is_empty() {
+                    return T::ZERO;
+                }
+                let differ = a.iter().zip(b).filter(|(x, y)| x != y).count();
+                T::from_usize(differ) / T::from_usize(a.len())
+            }
+            DistanceMetric::Mahalanobis(vi) => {
+                let d = a.len();
+                let diff: Vec<T> = a.iter().zip(b).map(|(&x, &y)| x - y).collect();
+                let m = vi.data();
+                let mut sum = T::ZERO;
+                for i in 0..d {
+                    for j in 0..d {
+                        sum += diff[i] * m[i * d + j] * diff[j];
+                    }
+                }
+                if sum > T::ZERO { sum.sqrt() } else { T::ZERO }
+            }
+            DistanceMetric::Custom(f) => f(a, b),
+        }
+    }
+
+    /// Whether the gap along any one coordinate is a lower bound on the
+    /// distance, which KD-tree pruning relies on.
+    pub fn axis_bounded(&self) -> bool {
+        match self {
+            DistanceMetric::Euclidean | DistanceMetric::Manhattan | DistanceMetric::Chebyshev => true,
+            DistanceMetric::Minkowski(p) => *p >= 1.0,
+            _ => false,
+        }
+    }
+
+    /// Whether the distance obeys the triangle inequality, which ball-tree
+    /// pruning relies on. Unknown for `Custom`, so false.
+    pub fn is_metric(&self) -> bool {
+        match self {
+            DistanceMetric::Cosine | DistanceMetric::Custom(_) => false,
+            DistanceMetric::Minkowski(p) => *p >= 1.0,
+            _ => true,
+        }
+    }
+}
+
+impl<T: Float> fmt::Debug for DistanceMetric<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistanceMetric::Euclidean => write!(f, "Euclidean"),
+            DistanceMetric::Manhattan => write!(f, "Manhattan"),
+            DistanceMetric::Minkowski(p) => write!(f, "Minkowski({})", p),
+            DistanceMetric::Chebyshev => write!(f, "Chebyshev"),
+            DistanceMetric::Cosine => write!(f, "Cosine"),
+            DistanceMetric::Hamming => write!(f, "Hamming"),
+            DistanceMetric::Mahalanobis(vi) => write!(f, "Mahalanobis({:?})", vi.shape_vec()),
+            DistanceMetric::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let (a, b) = ([0.0, 3.0, 1.0], [4.0, 0.0, 1.0]);
+        assert_eq!(DistanceMetric::Euclidean.distance(&a, &b), 5.0);
+        assert_eq!(DistanceMetric::Manhattan.distance(&a, &b), 7.0);
+        assert!((DistanceMetric::Minkowski(2.0).distance(&a, &b) - 5.0).abs() < 1e-12);
+        assert_eq!(DistanceMetric::Chebyshev.distance(&a, &b), 4.0);
+        assert!((DistanceMetric::Cosine.distance(&[1.0, 0.0], &[0.0, 2.0]) - 1.0).abs() < 1e-12);
+        assert!(DistanceMetric::Cosine.distance(&[1.0, 1.0], &[2.0, 2.0]).abs() < 1e-12);
+        assert!((DistanceMetric::Hamming.distance(&a, &b) - 2.0 / 3.0).abs() < 1e-12);
+
+        // With VI = diag(1/4, 1, 1) the first axis counts half.
+        let vi = Tensor::from_vec2d(&[vec![0.25, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]]).unwrap();
+        assert!((DistanceMetric::Mahalanobis(vi).distance(&a, &b) - 13f64.sqrt()).abs() < 1e-12);
+
+        let custom = DistanceMetric::custom(|a: &[f64], b: &[f64]| (a[0] - b[0]).abs());
+        assert_eq!(custom.distance(&a, &b), 4.0);
+        assert!(serde_json::to_string(&custom).is_err());
+        assert_eq!(serde_json::to_string(&DistanceMetric::<f64>::Minkowski(3.0)).unwrap(), r#"{"Minkowski":3.0}"#);
+    }
+}
@@ -0,0 +1,299 @@
+//! Einstein-summation contraction (`Tensor::einsum`).
+//!
+//! Two-operand contractions without repeated or dangling labels are lowered
+//! to a permute + batched `matmul`, so they run on the blocked GEMM kernel.
+//! Everything else (traces, diagonals, sums over a label that appears in a
+//! single operand, three or more operands) goes through a direct loop over
+//! every label combination.
+
+use crate::dtype::Float;
+use crate::error::{TensorError, TensorResult};
+use crate::shape::Shape;
+use crate::tensor::Tensor;
+
+/// Parsed einsum subscripts.
+struct Spec {
+    inputs: Vec<Vec<char>>,
+    output: Vec<char>,
+}
+
+impl Spec {
+    fn parse(spec: &str) -> TensorResult<Spec> {
+        let spec: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+        let (lhs, rhs) = match spec.split_once("->") {
+            Some((l, r)) => (l, Some(r)),
+            None => (spec.as_str(), None),
+        };
+
+        let inputs: Vec<Vec<char>> = lhs.split(',').map(|t| t.chars().collect()).collect();
+        let check = |term: &[char]| match term.iter().find(|c| !c.is_ascii_alphabetic()) {
+            Some(c) => Err(invalid(format!("unsupported subscript '{}'", c))),
+            None => Ok(()),
+        };
+        for term in &inputs {
+            check(term)?;
+        }
+
+        let output = match rhs {
+            Some(r) => {
+                let out: Vec<char> = r.chars().collect();
+                check(&out)?;
+                for (i, c) in out.iter().enumerate() {
+                    if out[..i].contains(c) {
+                        return Err(invalid(format!("output subscript '{}' repeated", c)));
+                    }
+                    if !inputs.iter().any(|t| t.contains(c)) {
+                        return Err(invalid(format!("output subscript '{}' not in any input", c)));
+                    }
+                }
+                out
+            }
+            // Implicit mode: labels that occur exactly once, in alphabetical order.
+            None => {
+                let mut once: Vec<char> = inputs.iter().flatten().copied().collect();
+                once.sort_unstable();
+                let mut out: Vec<char> = once
+                    .iter()
+                    .copied()
+                    .filter(|c| once.iter().filter(|d| *d == c).count() == 1)
+                    .collect();
+                out.dedup();
+                out
+            }
+        };
+        Ok(Spec { inputs, output })
+    }
+}
+
+fn invalid(msg: String) -> TensorError {
+    TensorError::InvalidOperation(format!("einsum: {}", msg))
+}
+
+impl<T: Float> Tensor<T> {
+    /// Einstein summation over `operands`, e.g. `"bij,bjk->bik"` for a
+    /// batched matmul, `"ii->"` for a trace or `"ij->ji"` for a transpose.
+    ///
+    /// Subscripts are single ASCII letters. Without `->` the output holds
+    /// every label that appears exactly once, in alphabetical order.
+    pub fn einsum(spec: &str, operands: &[&Tensor<T>]) -> TensorResult<Tensor<T>> {
+        let spec = Spec::parse(spec)?;
+        if spec.inputs.len() != operands.len() {
+            return Err(invalid(format!(
+                "{} subscript terms for {} operands",
+                spec.inputs.len(),
+                operands.len()
+            )));
+        }
+
+        // Size of every label, checked for consistency across operands.
+        let mut labels: Vec<char> = spec.output.clone();
+        let mut sizes: Vec<usize> = Vec::new();
+        for (term, op) in spec.inputs.iter().zip(operands) {
+            if term.len() != op.ndim() {
+                return Err(TensorError::DimensionMismatch(format!(
+                    "einsum: term '{}' has {} subscripts but operand has {} dimensions",
+                    term.iter().collect::<String>(),
+                    term.len(),
+                    op.ndim()
+                )));
+            }
+            for &c in term {
+                if !labels.contains(&c) {
+                    labels.push(c);
+                }
+            }
+        }
+        for &c in &labels {
+            let mut size = None;
+            for (term, op) in spec.inputs.iter().zip(operands) {
+                for (&t, &d) in term.iter().zip(op.shape().dims()) {
+                    if t != c {
+                        continue;
+                    }
+                    match size {
+                        None => size = Some(d),
+                        Some(s) if s != d => {
+                            return Err(invalid(format!(
+                                "label '{}' has sizes {} and {}",
+                                c, s, d
+                            )))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            sizes.push(size.unwrap_or(0));
+        }
+
+        if let Some(result) = contract_pair(&spec, operands, &labels, &sizes)? {
+            return Ok(result);
+        }
+        contract_loop(&spec, operands, &labels, &sizes)
+    }
+}
+
+/// Lower a two-operand contraction to `permute → reshape → matmul`.
+///
+/// Returns `None` when the subscripts don't fit that pattern.
+fn contract_pair<T: Float>(
+    spec: &Spec,
+    operands: &[&Tensor<T>],
+    labels: &[char],
+    sizes: &[usize],
+) -> TensorResult<Option<Tensor<T>>> {
+    if operands.len() != 2 {
+        return Ok(None);
+    }
+    let (ta, tb, out) = (&spec.inputs[0], &spec.inputs[1], &spec.output);
+    let distinct = |t: &[char]| t.iter().enumerate().all(|(i, c)| !t[..i].contains(c));
+    if !distinct(ta) || !distinct(tb) {
+        return Ok(None);
+    }
+    // Every label must be kept or shared, so nothing needs a pre-reduction.
+    if !labels.iter().all(|c| out.contains(c) || (ta.contains(c) && tb.contains(c))) {
+        return Ok(None);
+    }
+
+    let size = |c: &char| sizes[labels.iter().position(|l| l == c).unwrap()];
+    let batch: Vec<char> = out.iter().copied().filter(|c| ta.contains(c) && tb.contains(c)).collect();
+    let free_a: Vec<char> = out.iter().copied().filter(|c| ta.contains(c) && !tb.contains(c)).collect();
+    let free_b: Vec<char> = out.iter().copied().filter(|c| tb.contains(c) && !ta.contains(c)).collect();
+    let contracted: Vec<char> = ta.iter().copied().filter(|c| tb.contains(c) && !out.contains(c)).collect();
+
+    let prod = |ls: &[char]| ls.iter().map(size).product::<usize>();
+    let (nb, m, k, n) = (prod(&batch), prod(&free_a), prod(&contracted), prod(&free_b));
+
+    let arrange = |t: &Tensor<T>, term: &[char], order: Vec<char>, shape: Vec<usize>| {
+        let axes: Vec<usize> = order.iter().map(|c| term.iter().position(|t| t == c).unwrap()).collect();
+        t.view().permute(&axes)?.contiguous().reshape(shape)
+    };
+    let a = arrange(operands[0], ta, [&batch[..], &free_a, &contracted].concat(), vec![nb, m, k])?;
+    let b = arrange(operands[1], tb, [&batch[..], &contracted, &free_b].concat(), vec![nb, k, n])?;
+    let c = a.matmul(&b)?;
+
+    // `c` is laid out as batch + free_a + free_b; permute to the requested order.
+    let c_labels = [&batch[..], &free_a, &free_b].concat();
+    let c = c.reshape(c_labels.iter().map(size).collect())?;
+    let axes: Vec<usize> = out.iter().map(|o| c_labels.iter().position(|c| c == o).unwrap()).collect();
+    Ok(Some(c.view().permute(&axes)?.contiguous()))
+}
+
+/// Direct summation over every combination of label values.
+fn contract_loop<T: Float>(
+    spec: &Spec,
+    operands: &[&Tensor<T>],
+    labels: &[char],
+    sizes: &[usize],
+) -> TensorResult<Tensor<T>> {
+    let n_out = spec.output.len();
+    let out_dims: Vec<usize> = sizes[..n_out].to_vec();
+    let out_shape = if n_out == 0 { Shape::scalar() } else { Shape::new(out_dims) };
+    let mut out = vec![T::ZERO; out_shape.numel()];
+    if sizes.contains(&0) {
+        return Tensor::new(out, out_shape.to_vec());
+    }
+
+    // Per-operand stride of each label; repeated labels add up (diagonals).
+    let op_strides: Vec<Vec<usize>> = spec
+        .inputs
+        .iter()
+        .zip(operands)
+        .map(|(term, op)| {
+            let strides = op.shape().strides();
+            labels
+                .iter()
+                .map(|l| term.iter().zip(&strides).filter(|(t, _)| *t == l).map(|(_, s)| s).sum())
+                .collect()
+        })
+        .collect();
+    let data: Vec<&[T]> = operands.iter().map(|op| op.data()).collect();
+
+    let mut index = vec![0usize; labels.len()];
+    let mut offsets = vec![0usize; operands.len()];
+    let total: usize = sizes.iter().product();
+    // Labels are ordered output-first, so the output position only changes
+    // when one of the leading `n_out` counters ticks.
+    let inner: usize = sizes[n_out..].iter().product();
+    for step in 0..total {
+        let prod = offsets.iter().zip(&data).fold(T::ONE, |acc, (&o, d)| acc * d[o]);
+        out[step / inner] += prod;
+
+        for d in (0..labels.len()).rev() {
+            index[d] += 1;
+            for (o, s) in offsets.iter_mut().zip(&op_strides) {
+                *o += s[d];
+            }
+            if index[d] < sizes[d] {
+                break;
+            }
+            for (o, s) in offsets.iter_mut().zip(&op_strides) {
+                *o -= index[d] * s[d];
+            }
+            index[d] = 0;
+        }
+    }
+    Tensor::new(out, out_shape.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seq(shape: Vec<usize>) -> Tensor<f64> {
+        let n = shape.iter().product();
+        Tensor::new((0..n).map(|i| i as f64).collect(), shape).unwrap()
+    }
+
+    #[test]
+    fn test_matmul_and_batched() {
+        let a = seq(vec![2, 3]);
+        let b = seq(vec![3, 4]);
+        assert_eq!(Tensor::einsum("ij,jk->ik", &[&a, &b]).unwrap(), a.matmul(&b).unwrap());
+
+        let a = seq(vec![2, 3, 4]);
+        let b = seq(vec![2, 4, 5]);
+        assert_eq!(Tensor::einsum("bij,bjk->bik", &[&a, &b]).unwrap(), a.matmul(&b).unwrap());
+    }
+
+    #[test]
+    fn test_transpose_trace_and_diagonal() {
+        let a = seq(vec![3, 3]);
+        assert_eq!(Tensor::einsum("ij->ji", &[&a]).unwrap(), a.t().unwrap());
+        assert_eq!(Tensor::einsum("ii->", &[&a]).unwrap().item().unwrap(), 12.0);
+        assert_eq!(Tensor::einsum("ii->i", &[&a]).unwrap().data(), &[0.0, 4.0, 8.0]);
+        assert_eq!(Tensor::einsum("ij->j", &[&a]).unwrap(), a.sum_axis(0).unwrap());
+    }
+
+    #[test]
+    fn test_pair_path_matches_loop() {
+        // Attention-style scores with the key axis transposed: "bqd,bkd->bqk".
+        let q: Tensor<f64> = Tensor::randn(vec![2, 3, 4], Some(1));
+        let k: Tensor<f64> = Tensor::randn(vec![2, 5, 4], Some(2));
+        let spec = Spec::parse("bqd,bkd->bqk").unwrap();
+        let labels = ['b', 'q', 'k', 'd'];
+        let sizes = [2, 3, 5, 4];
+        let fast = contract_pair(&spec, &[&q, &k], &labels, &sizes).unwrap().unwrap();
+        let slow = contract_loop(&spec, &[&q, &k], &labels, &sizes).unwrap();
+        assert_eq!(fast.shape_vec(), vec![2, 3, 5]);
+        for (a, b) in fast.data().iter().zip(slow.data()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_implicit_output_and_errors() {
+        let a = seq(vec![2, 3]);
+        let b = seq(vec![3, 2]);
+        // Implicit: "ij,jk" contracts j and keeps i, k.
+        assert_eq!(Tensor::einsum("ij,jk", &[&a, &b]).unwrap(), a.matmul(&b).unwrap());
+        // Outer product via three-way loop path.
+        let v = Tensor::from_slice(&[1.0, 2.0]);
+        let outer = Tensor::einsum("i,j,k->ijk", &[&v, &v, &v]).unwrap();
+        assert_eq!(outer.shape_vec(), vec![2, 2, 2]);
+        assert_eq!(outer.get(&[1, 1, 1]).unwrap(), 8.0);
+
+        assert!(Tensor::einsum("ij,jk->ik", &[&a, &a]).is_err()); // j: 3 vs 2
+        assert!(Tensor::einsum("ij->ik", &[&a]).is_err());
+        assert!(Tensor::einsum("ijk->i", &[&a]).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Fast Fourier transform kernels for `ComplexTensor`.
+//!
+//! Power-of-two lengths use an iterative radix-2 Cooley–Tukey transform.
+//! Other lengths are rewritten as a convolution (Bluestein's algorithm) and
+//! evaluated with radix-2 transforms of the next power of two, so every
+//! length runs in O(n log n).
+
+use crate::complex::Complex;
+use crate::dtype::Float;
+
+/// DFT of `x`; the inverse transform is scaled by `1/n`.
+pub(crate) fn fft<T: Float>(x: &[Complex<T>], inverse: bool) -> Vec<Complex<T>> {
+    let n = x.len();
+    let mut out = if n <= 1 {
+        x.to_vec()
+    } else if n.is_power_of_two() {
+        let mut buf = x.to_vec();
+        radix2(&mut buf, inverse);
+        buf
+    } else {
+        bluestein(x, inverse)
+    };
+    if inverse && n > 1 {
+        let scale = T::ONE / T::from_usize(n);
+        for z in &mut out {
+            *z = z.scale(scale);
+        }
+    }
+    out
+}
+
+/// `e^{∓2πi k / n}`, computed in f64 for accuracy.
+fn twiddle<T: Float>(k: usize, n: usize, inverse: bool) -> Complex<T> {
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let theta = sign * 2.0 * std::f64::consts::PI * k as f64 / n as f64;
+    Complex::new(T::from_f64(theta.cos()), T::from_f64(theta.sin()))
+}
+
+/// In-place unnormalized radix-2 FFT; `buf.len()` must be a power of two.
+fn radix2<T: Float>(buf: &mut [Complex<T>], inverse: bool) {
+    let n = buf.len();
+    let bits = n.trailing_zeros();
+
+    // Bit-reversal permutation.
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let w: Vec<Complex<T>> = (0..half).map(|k| twiddle(k, len, inverse)).collect();
+        for start in (0..n).step_by(len) {
+            for k in 0..half {
+                let u = buf[start + k];
+                let v = buf[start + k + half] * w[k];
+                buf[start + k] = u + v;
+                buf[start + k + half] = u - v;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Arbitrary-length unnormalized DFT via Bluestein's chirp-z transform.
+fn bluestein<T: Float>(x: &[Complex<T>], inverse: bool) -> Vec<Complex<T>> {
+    let n = x.len();
+    let m = (2 * n - 1).next_power_of_two();
+
+    // chirp[k] = e^{∓πi k² / n}; k² is reduced mod 2n to keep the angle small.
+    let chirp: Vec<Complex<T>> = (0..n).map(|k| twiddle((k * k) % (2 * n), 2 * n, inverse)).collect();
+
+    let mut a = vec![Complex::default(); m];
+    for k in 0..n {
+        a[k] = x[k] * chirp[k];
+    }
+    let mut b = vec![Complex::default(); m];
+    b[0] = chirp[0].conj();
+    for k in 1..n {
+        b[k] = chirp[k].conj();
+        b[m - k] = chirp[k].conj();
+    }
+
+    radix2(&mut a, false);
+    radix2(&mut b, false);
+    for (ai, &bi) in a.iter_mut().zip(&b) {
+        *ai = *ai * bi;
+    }
+    radix2(&mut a, true);
+
+    let scale = T::ONE / T::from_usize(m);
+    (0..n).map(|k| (a[k] * chirp[k]).scale(scale)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_dft(x: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        let n = x.len();
+        (0..n)
+            .map(|k| {
+                x.iter()
+                    .enumerate()
+                    .fold(Complex::default(), |acc, (j, &v)| acc + v * twiddle((j * k) % n, n, false))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fft_matches_naive_dft() {
+        for n in [2usize, 3, 5, 8, 9, 16, 30] {
+            let x: Vec<Complex<f64>> = (0..n)
+                .map(|i| Complex::new((i as f64 * 0.7).sin(), (i as f64 * 1.3).cos()))
+                .collect();
+            let fast = fft(&x, false);
+            let slow = naive_dft(&x);
+            for (a, b) in fast.iter().zip(&slow) {
+                assert!((*a - *b).abs() < 1e-9, "n={}", n);
+            }
+            let back = fft(&fast, true);
+            for (a, b) in back.iter().zip(&x) {
+                assert!((*a - *b).abs() < 1e-9, "n={}", n);
+            }
+        }
+    }
+}
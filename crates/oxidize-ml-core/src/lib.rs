@@ -2,8 +2,26 @@ pub mod tensor;
 pub mod shape;
 pub mod dtype;
 pub mod error;
+mod gemm;
+pub mod parallel;
+pub mod view;
+mod einsum;
+pub mod complex;
+mod fft;
+pub mod sparse;
+pub mod rng;
+pub mod weights;
+pub mod distance;
+pub mod precision;
 
 pub use tensor::Tensor;
 pub use shape::Shape;
 pub use dtype::Float;
 pub use error::TensorError;
+pub use view::TensorView;
+pub use complex::{Complex, ComplexTensor};
+pub use sparse::{CooTensor, SparseTensor};
+pub use rng::{global_rng_state, set_global_rng_state, set_global_seed, RngHandle, RngState};
+pub use weights::{sample_weights, ClassWeight};
+pub use distance::{DistanceFn, DistanceMetric};
+pub use precision::{HalfTensor, HalfType};
@@ -0,0 +1,170 @@
+//! Optional multi-threading for tensor kernels.
+//!
+//! With the `rayon` feature enabled, matmul, broadcast binary ops and axis
+//! reductions split their output across a thread pool once the amount of
+//! work exceeds `parallel_threshold()`. Without the feature every function
+//! here still exists and kernels simply run on the calling thread.
+
+use crate::error::TensorResult;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "rayon")]
+use crate::error::TensorError;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "rayon")]
+use std::sync::{Arc, RwLock};
+
+/// Default minimum work (roughly, scalar multiply-adds) before a kernel goes parallel.
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 1 << 15;
+
+static THRESHOLD: AtomicUsize = AtomicUsize::new(DEFAULT_PARALLEL_THRESHOLD);
+
+#[cfg(feature = "rayon")]
+static POOL: RwLock<Option<Arc<rayon::ThreadPool>>> = RwLock::new(None);
+
+/// Whether the crate was built with the `rayon` feature.
+pub fn is_enabled() -> bool {
+    cfg!(feature = "rayon")
+}
+
+/// Use a dedicated pool of `n` threads for tensor kernels.
+///
+/// `n = 0` drops the dedicated pool and falls back to rayon's global pool.
+/// Without the `rayon` feature this is a no-op.
+pub fn set_num_threads(n: usize) -> TensorResult<()> {
+    #[cfg(feature = "rayon")]
+    {
+        let pool = if n == 0 {
+            None
+        } else {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map_err(|e| TensorError::InvalidOperation(format!("thread pool: {}", e)))?;
+            Some(Arc::new(pool))
+        };
+        *POOL.write().unwrap_or_else(|e| e.into_inner()) = pool;
+        Ok(())
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let _ = n;
+        Ok(())
+    }
+}
+
+/// Number of threads tensor kernels will use.
+pub fn num_threads() -> usize {
+    #[cfg(feature = "rayon")]
+    {
+        match POOL.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            Some(pool) => pool.current_num_threads(),
+            None => rayon::current_num_threads(),
+        }
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        1
+    }
+}
+
+/// Set the minimum work size for parallel execution.
+pub fn set_parallel_threshold(n: usize) {
+    THRESHOLD.store(n, Ordering::Relaxed);
+}
+
+pub fn parallel_threshold() -> usize {
+    THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// Run `f` on the pool set by `set_num_threads`, so rayon work inside it
+/// (in this crate or a downstream one) uses that many threads. With no
+/// dedicated pool, or without the `rayon` feature, `f` runs directly.
+pub fn install<R: Send, F: FnOnce() -> R + Send>(f: F) -> R {
+    #[cfg(feature = "rayon")]
+    {
+        let pool = POOL.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(pool) = pool {
+            return pool.install(f);
+        }
+    }
+    f()
+}
+
+/// Build a `Vec` of `n` elements from `f(index)`.
+pub(crate) fn map_range<T, F>(n: usize, work: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    if work >= parallel_threshold() && n > 1 {
+        return install(|| (0..n).into_par_iter().map(f).collect());
+    }
+    let _ = work;
+    (0..n).map(f).collect()
+}
+
+/// Call `f(chunk_index, chunk)` for each `chunk`-sized piece of `out`.
+pub(crate) fn for_each_chunk<T, F>(out: &mut [T], chunk: usize, work: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync + Send,
+{
+    if chunk == 0 {
+        return;
+    }
+    #[cfg(feature = "rayon")]
+    if work >= parallel_threshold() && out.len() > chunk {
+        install(|| {
+            out.par_chunks_mut(chunk)
+                .enumerate()
+                .for_each(|(i, c)| f(i, c))
+        });
+        return;
+    }
+    let _ = work;
+    for (i, c) in out.chunks_mut(chunk).enumerate() {
+        f(i, c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tensor;
+
+    #[test]
+    fn test_kernels_match_below_and_above_threshold() {
+        let a: Tensor<f64> = Tensor::rand(vec![37, 23], Some(1));
+        let b: Tensor<f64> = Tensor::rand(vec![23, 41], Some(2));
+        let row: Tensor<f64> = Tensor::rand(vec![1, 41], Some(3));
+
+        set_parallel_threshold(usize::MAX);
+        let mm_seq = a.matmul(&b).unwrap();
+        let add_seq = mm_seq.add(&row).unwrap();
+        let sum_seq = add_seq.sum_axis(0).unwrap();
+
+        set_parallel_threshold(0);
+        let mm_par = a.matmul(&b).unwrap();
+        let add_par = mm_par.add(&row).unwrap();
+        let sum_par = add_par.sum_axis(0).unwrap();
+        set_parallel_threshold(DEFAULT_PARALLEL_THRESHOLD);
+
+        assert_eq!(mm_seq, mm_par);
+        assert_eq!(add_seq, add_par);
+        assert_eq!(sum_seq, sum_par);
+    }
+
+    #[test]
+    fn test_set_num_threads() {
+        set_num_threads(2).unwrap();
+        if is_enabled() {
+            assert_eq!(num_threads(), 2);
+        } else {
+            assert_eq!(num_threads(), 1);
+        }
+        set_num_threads(0).unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+use half::{bf16, f16};
+use serde::{Deserialize, Serialize};
+
+use crate::dtype::Float;
+use crate::error::TensorResult;
+use crate::tensor::Tensor;
+
+/// 16-bit floating-point formats.
+///
+/// `F16` (IEEE half) keeps 10 mantissa bits but overflows past 65504;
+/// `BF16` keeps the range of `f32` with only 7 mantissa bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HalfType {
+    F16,
+    BF16,
+}
+
+impl HalfType {
+    pub fn encode(self, v: f64) -> u16 {
+        match self {
+            HalfType::F16 => f16::from_f64(v).to_bits(),
+            HalfType::BF16 => bf16::from_f64(v).to_bits(),
+        }
+    }
+
+    pub fn decode(self, bits: u16) -> f64 {
+        match self {
+            HalfType::F16 => f16::from_bits(bits).to_f64(),
+            HalfType::BF16 => bf16::from_bits(bits).to_f64(),
+        }
+    }
+
+    /// The nearest value this format can hold.
+    pub fn round(self, v: f64) -> f64 {
+        self.decode(self.encode(v))
+    }
+
+    /// The largest finite value.
+    pub fn max_value(self) -> f64 {
+        match self {
+            HalfType::F16 => f16::MAX.to_f64(),
+            HalfType::BF16 => bf16::MAX.to_f64(),
+        }
+    }
+}
+
+/// A tensor stored as 16-bit floats, at a quarter of the memory of `f64`.
+///
+/// Arithmetic is done by decoding to a `Tensor`; this is a storage format
+/// (for weights, activations kept for backward, or checkpoints), not a
+/// compute type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HalfTensor {
+    pub dtype: HalfType,
+    pub bits: Vec<u16>,
+    pub shape: Vec<usize>,
+}
+
+impl HalfTensor {
+    /// Round every element of `tensor` to `dtype`.
+    pub fn from_tensor<T: Float>(tensor: &Tensor<T>, dtype: HalfType) -> Self {
+        HalfTensor {
+            dtype,
+            bits: tensor.data().iter().map(|v| dtype.encode(v.to_f64())).collect(),
+            shape: tensor.shape_vec(),
+        }
+    }
+
+    pub fn to_tensor<T: Float>(&self) -> TensorResult<Tensor<T>> {
+        let data = self.bits.iter().map(|&b| T::from_f64(self.dtype.decode(b))).collect();
+        Tensor::new(data, self.shape.clone())
+    }
+
+    pub fn shape_vec(&self) -> Vec<usize> {
+        self.shape.clone()
+    }
+
+    pub fn numel(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Bytes of element storage.
+    pub fn nbytes(&self) -> usize {
+        self.bits.len() * 2
+    }
+}
+
+impl<T: Float> Tensor<T> {
+    pub fn to_half(&self, dtype: HalfType) -> HalfTensor {
+        HalfTensor::from_tensor(self, dtype)
+    }
+
+    /// Round every element to the nearest value of `dtype`, keeping the
+    /// element type: what a round trip through 16-bit storage gives.
+    pub fn round_to(&self, dtype: HalfType) -> Tensor<T> {
+        self.apply(|v| T::from_f64(dtype.round(v.to_f64())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_round_trip_and_range() {
+        let t = Tensor::new(vec![1.0, 0.1, -3.5, 1e5], vec![2, 2]).unwrap();
+        let h = t.to_half(HalfType::F16);
+        assert_eq!((h.numel(), h.nbytes(), h.shape_vec()), (4, 8, vec![2, 2]));
+        let back: Tensor<f64> = h.to_tensor().unwrap();
+        assert_eq!(back.data()[0], 1.0);
+        assert_eq!(back.data()[2], -3.5);
+        assert!((back.data()[1] - 0.1).abs() < 1e-4 && back.data()[1] != 0.1);
+        // Past 65504 f16 overflows; bf16 keeps the range but not the digits.
+        assert!(back.data()[3].is_infinite());
+        let b = t.round_to(HalfType::BF16);
+        assert!((b.data()[3] - 1e5).abs() / 1e5 < 1e-2);
+        assert_eq!(HalfType::F16.max_value(), 65504.0);
+    }
+}
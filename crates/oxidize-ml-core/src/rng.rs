@@ -0,0 +1,176 @@
+//! Workspace-wide random number generation.
+//!
+//! Every stochastic component (weight init, dropout, bootstrapping, k-means
+//! seeding, shuffling, splitting) takes an `Option<u64>` seed and builds an
+//! `RngHandle` from it. An explicit seed gives a dedicated stream; `None`
+//! forks a stream from the process-wide generator, which is seeded from
+//! entropy until `set_global_seed` is called. Seeding once at the start of a
+//! program therefore makes the whole run reproducible, provided the
+//! components are created and used in the same order. `RngState` captures
+//! a stream's exact position, for checkpoints that resume a run mid-way.
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+// ChaCha12, the algorithm behind `rand::rngs::StdRng`, used directly so its
+// position can be saved.
+static GLOBAL: Mutex<Option<ChaCha12Rng>> = Mutex::new(None);
+
+/// Reseed the process-wide generator.
+pub fn set_global_seed(seed: u64) {
+    *lock() = Some(ChaCha12Rng::seed_from_u64(seed));
+}
+
+/// Position of the process-wide generator, initializing it from entropy if
+/// `set_global_seed` was never called.
+pub fn global_rng_state() -> RngState {
+    RngState::of(lock().get_or_insert_with(ChaCha12Rng::from_entropy))
+}
+
+/// Put the process-wide generator back at a position from
+/// `global_rng_state`.
+pub fn set_global_rng_state(state: &RngState) {
+    *lock() = Some(state.to_rng());
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<ChaCha12Rng>> {
+    // A panic while holding the lock cannot leave the generator invalid.
+    GLOBAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Next seed from the global generator, initializing it from entropy if
+/// `set_global_seed` was never called.
+fn next_global_seed() -> u64 {
+    lock().get_or_insert_with(ChaCha12Rng::from_entropy).next_u64()
+}
+
+/// The exact position of a random stream: its seed, stream and the word
+/// it has reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RngState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+impl RngState {
+    fn of(rng: &ChaCha12Rng) -> Self {
+        RngState { seed: rng.get_seed(), stream: rng.get_stream(), word_pos: rng.get_word_pos() }
+    }
+
+    fn to_rng(self) -> ChaCha12Rng {
+        let mut rng = ChaCha12Rng::from_seed(self.seed);
+        rng.set_stream(self.stream);
+        rng.set_word_pos(self.word_pos);
+        rng
+    }
+}
+
+/// A random stream: seeded explicitly, or forked from the global generator.
+///
+/// Implements `RngCore`, so it works with every `rand` API
+/// (`Rng::gen_range`, `SliceRandom::shuffle`, distributions, …).
+#[derive(Debug, Clone)]
+pub struct RngHandle {
+    inner: ChaCha12Rng,
+}
+
+impl RngHandle {
+    /// `Some(seed)` gives a stream that depends only on `seed`; `None` forks
+    /// one from the global generator.
+    pub fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(next_global_seed);
+        RngHandle {
+            inner: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Continue the stream at a position saved by `state`.
+    pub fn from_state(state: &RngState) -> Self {
+        RngHandle { inner: state.to_rng() }
+    }
+
+    pub fn state(&self) -> RngState {
+        RngState::of(&self.inner)
+    }
+
+    /// Fork from the global generator (same as `RngHandle::new(None)`).
+    pub fn global() -> Self {
+        Self::new(None)
+    }
+
+    /// Derive an independent child stream, e.g. one per tree in a forest.
+    pub fn fork(&mut self) -> RngHandle {
+        RngHandle {
+            inner: ChaCha12Rng::seed_from_u64(self.inner.next_u64()),
+        }
+    }
+}
+
+impl Default for RngHandle {
+    fn default() -> Self {
+        Self::global()
+    }
+}
+
+impl RngCore for RngHandle {
+    fn next_u32(&mut self) -> u32 {
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_explicit_seed_is_deterministic() {
+        let a: Vec<u64> = (0..4).map(|_| RngHandle::new(Some(7)).gen()).collect();
+        assert!(a.iter().all(|&v| v == a[0]));
+
+        let mut parent = RngHandle::new(Some(7));
+        let (mut c1, mut c2) = (parent.fork(), parent.fork());
+        assert_ne!(c1.gen::<u64>(), c2.gen::<u64>());
+    }
+
+    #[test]
+    fn test_global_seed_replays_streams() {
+        let run = || {
+            set_global_seed(3);
+            let a: u64 = RngHandle::global().gen();
+            let b: u64 = RngHandle::new(None).gen();
+            (a, b)
+        };
+        let (a, b) = run();
+        assert_ne!(a, b);
+        assert_eq!(run(), (a, b));
+    }
+
+    #[test]
+    fn test_state_resumes_stream() {
+        let mut rng = RngHandle::new(Some(11));
+        let _: [u32; 3] = rng.gen();
+        let state = rng.state();
+        let ahead: Vec<u64> = (0..4).map(|_| rng.gen()).collect();
+        let mut resumed = RngHandle::from_state(&state);
+        assert_eq!((0..4).map(|_| resumed.gen()).collect::<Vec<u64>>(), ahead);
+        // Same generator as `StdRng`.
+        let std: u64 = rand::rngs::StdRng::seed_from_u64(5).gen();
+        assert_eq!(RngHandle::new(Some(5)).gen::<u64>(), std);
+    }
+}
@@ -0,0 +1,468 @@
+use crate::dtype::Float;
+use crate::error::{TensorError, TensorResult};
+use crate::parallel;
+use crate::tensor::Tensor;
+
+use serde::{Deserialize, Serialize};
+
+/// Sparse matrix in coordinate (triplet) format.
+///
+/// Cheap to build incrementally; convert to `SparseTensor` (CSR) for
+/// arithmetic. Duplicate entries are summed on conversion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct CooTensor<T: Float> {
+    n_rows: usize,
+    n_cols: usize,
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T: Float> CooTensor<T> {
+    /// Empty `n_rows × n_cols` matrix.
+    pub fn new(n_rows: usize, n_cols: usize) -> Self {
+        CooTensor {
+            n_rows,
+            n_cols,
+            rows: Vec::new(),
+            cols: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    pub fn from_triplets(
+        n_rows: usize,
+        n_cols: usize,
+        rows: Vec<usize>,
+        cols: Vec<usize>,
+        values: Vec<T>,
+    ) -> TensorResult<Self> {
+        if rows.len() != values.len() || cols.len() != values.len() {
+            return Err(TensorError::DimensionMismatch(format!(
+                "triplet arrays differ in length: {} rows, {} cols, {} values",
+                rows.len(),
+                cols.len(),
+                values.len()
+            )));
+        }
+        let mut coo = CooTensor::new(n_rows, n_cols);
+        for ((r, c), v) in rows.into_iter().zip(cols).zip(values) {
+            coo.push(r, c, v)?;
+        }
+        Ok(coo)
+    }
+
+    /// Append one entry.
+    pub fn push(&mut self, row: usize, col: usize, value: T) -> TensorResult<()> {
+        if row >= self.n_rows {
+            return Err(TensorError::IndexOutOfBounds {
+                index: row,
+                axis: 0,
+                size: self.n_rows,
+            });
+        }
+        if col >= self.n_cols {
+            return Err(TensorError::IndexOutOfBounds {
+                index: col,
+                axis: 1,
+                size: self.n_cols,
+            });
+        }
+        self.rows.push(row);
+        self.cols.push(col);
+        self.values.push(value);
+        Ok(())
+    }
+
+    pub fn shape_vec(&self) -> Vec<usize> {
+        vec![self.n_rows, self.n_cols]
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// `(row, col, value)` triplets in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, T)> + '_ {
+        self.rows
+            .iter()
+            .zip(&self.cols)
+            .zip(&self.values)
+            .map(|((&r, &c), &v)| (r, c, v))
+    }
+
+    pub fn to_csr(&self) -> SparseTensor<T> {
+        // Counting sort by row, then sort + merge columns within each row.
+        let mut indptr = vec![0usize; self.n_rows + 1];
+        for &r in &self.rows {
+            indptr[r + 1] += 1;
+        }
+        for i in 0..self.n_rows {
+            indptr[i + 1] += indptr[i];
+        }
+        let mut next = indptr.clone();
+        let mut entries = vec![(0usize, T::ZERO); self.nnz()];
+        for (r, c, v) in self.iter() {
+            entries[next[r]] = (c, v);
+            next[r] += 1;
+        }
+
+        let mut out_indptr = Vec::with_capacity(self.n_rows + 1);
+        let mut indices = Vec::with_capacity(self.nnz());
+        let mut values = Vec::with_capacity(self.nnz());
+        out_indptr.push(0);
+        for i in 0..self.n_rows {
+            let row = &mut entries[indptr[i]..indptr[i + 1]];
+            row.sort_by_key(|&(c, _)| c);
+            for &(c, v) in row.iter() {
+                if indices.len() > *out_indptr.last().unwrap() && *indices.last().unwrap() == c {
+                    *values.last_mut().unwrap() += v;
+                } else {
+                    indices.push(c);
+                    values.push(v);
+                }
+            }
+            out_indptr.push(indices.len());
+        }
+        SparseTensor {
+            n_rows: self.n_rows,
+            n_cols: self.n_cols,
+            indptr: out_indptr,
+            indices,
+            values,
+        }
+    }
+
+    pub fn to_dense(&self) -> Tensor<T> {
+        let mut data = vec![T::ZERO; self.n_rows * self.n_cols];
+        for (r, c, v) in self.iter() {
+            data[r * self.n_cols + c] += v;
+        }
+        Tensor::new(data, self.shape_vec()).expect("dense buffer matches shape")
+    }
+}
+
+/// Sparse matrix in compressed sparse row (CSR) format.
+///
+/// Row `i` stores its column indices in `indices[indptr[i]..indptr[i + 1]]`
+/// (sorted, no duplicates) and the matching values in `values`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct SparseTensor<T: Float> {
+    n_rows: usize,
+    n_cols: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T: Float> SparseTensor<T> {
+    // ─── Construction ───────────────────────────────────────────────────────
+
+    /// Build from raw CSR arrays, validating their structure.
+    pub fn new(
+        n_rows: usize,
+        n_cols: usize,
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        values: Vec<T>,
+    ) -> TensorResult<Self> {
+        if indptr.len() != n_rows + 1 || indptr[0] != 0 || *indptr.last().unwrap() != values.len() {
+            return Err(TensorError::InvalidOperation(
+                "CSR indptr must have n_rows + 1 entries, start at 0 and end at nnz".into(),
+            ));
+        }
+        if indices.len() != values.len() {
+            return Err(TensorError::DimensionMismatch(format!(
+                "CSR has {} indices but {} values",
+                indices.len(),
+                values.len()
+            )));
+        }
+        for i in 0..n_rows {
+            if indptr[i] > indptr[i + 1] {
+                return Err(TensorError::InvalidOperation("CSR indptr must be non-decreasing".into()));
+            }
+            let row = &indices[indptr[i]..indptr[i + 1]];
+            if row.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(TensorError::InvalidOperation(format!(
+                    "CSR column indices of row {} must be strictly increasing",
+                    i
+                )));
+            }
+            if let Some(&c) = row.last().filter(|&&c| c >= n_cols) {
+                return Err(TensorError::IndexOutOfBounds {
+                    index: c,
+                    axis: 1,
+                    size: n_cols,
+                });
+            }
+        }
+        Ok(SparseTensor {
+            n_rows,
+            n_cols,
+            indptr,
+            indices,
+            values,
+        })
+    }
+
+    /// Keep the non-zero entries of a 2-D dense tensor.
+    pub fn from_dense(dense: &Tensor<T>) -> TensorResult<Self> {
+        if dense.ndim() != 2 {
+            return Err(TensorError::InvalidOperation(
+                "SparseTensor::from_dense requires a 2D tensor".into(),
+            ));
+        }
+        let (n_rows, n_cols) = (dense.shape().dims()[0], dense.shape().dims()[1]);
+        let mut indptr = Vec::with_capacity(n_rows + 1);
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+        indptr.push(0);
+        if n_cols > 0 {
+            for row in dense.data().chunks(n_cols) {
+                for (j, &v) in row.iter().enumerate() {
+                    if v != T::ZERO {
+                        indices.push(j);
+                        values.push(v);
+                    }
+                }
+                indptr.push(indices.len());
+            }
+        } else {
+            indptr.resize(n_rows + 1, 0);
+        }
+        Ok(SparseTensor {
+            n_rows,
+            n_cols,
+            indptr,
+            indices,
+            values,
+        })
+    }
+
+    pub fn from_coo(coo: &CooTensor<T>) -> Self {
+        coo.to_csr()
+    }
+
+    pub fn to_coo(&self) -> CooTensor<T> {
+        let mut rows = Vec::with_capacity(self.nnz());
+        for i in 0..self.n_rows {
+            rows.extend(std::iter::repeat_n(i, self.indptr[i + 1] - self.indptr[i]));
+        }
+        CooTensor {
+            n_rows: self.n_rows,
+            n_cols: self.n_cols,
+            rows,
+            cols: self.indices.clone(),
+            values: self.values.clone(),
+        }
+    }
+
+    pub fn to_dense(&self) -> Tensor<T> {
+        let mut data = vec![T::ZERO; self.n_rows * self.n_cols];
+        for i in 0..self.n_rows {
+            let (cols, vals) = self.row(i);
+            for (&j, &v) in cols.iter().zip(vals) {
+                data[i * self.n_cols + j] = v;
+            }
+        }
+        Tensor::new(data, self.shape_vec()).expect("dense buffer matches shape")
+    }
+
+    // ─── Accessors ──────────────────────────────────────────────────────────
+
+    pub fn shape_vec(&self) -> Vec<usize> {
+        vec![self.n_rows, self.n_cols]
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Fraction of entries that are stored.
+    pub fn density(&self) -> f64 {
+        let total = self.n_rows * self.n_cols;
+        if total == 0 {
+            0.0
+        } else {
+            self.nnz() as f64 / total as f64
+        }
+    }
+
+    pub fn indptr(&self) -> &[usize] {
+        &self.indptr
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Column indices and values of the stored entries in row `i`.
+    pub fn row(&self, i: usize) -> (&[usize], &[T]) {
+        let (start, end) = (self.indptr[i], self.indptr[i + 1]);
+        (&self.indices[start..end], &self.values[start..end])
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> TensorResult<T> {
+        if i >= self.n_rows || j >= self.n_cols {
+            return Err(TensorError::IndexOutOfBounds {
+                index: if i >= self.n_rows { i } else { j },
+                axis: if i >= self.n_rows { 0 } else { 1 },
+                size: if i >= self.n_rows { self.n_rows } else { self.n_cols },
+            });
+        }
+        let (cols, vals) = self.row(i);
+        Ok(cols.binary_search(&j).map(|k| vals[k]).unwrap_or(T::ZERO))
+    }
+
+    // ─── Operations ─────────────────────────────────────────────────────────
+
+    /// CSR of the transpose (equivalently, the CSC layout of `self`).
+    pub fn transpose(&self) -> SparseTensor<T> {
+        let mut indptr = vec![0usize; self.n_cols + 1];
+        for &j in &self.indices {
+            indptr[j + 1] += 1;
+        }
+        for j in 0..self.n_cols {
+            indptr[j + 1] += indptr[j];
+        }
+        let mut next = indptr.clone();
+        let mut indices = vec![0usize; self.nnz()];
+        let mut values = vec![T::ZERO; self.nnz()];
+        // Rows are visited in order, so each output row stays sorted.
+        for i in 0..self.n_rows {
+            let (cols, vals) = self.row(i);
+            for (&j, &v) in cols.iter().zip(vals) {
+                indices[next[j]] = i;
+                values[next[j]] = v;
+                next[j] += 1;
+            }
+        }
+        SparseTensor {
+            n_rows: self.n_cols,
+            n_cols: self.n_rows,
+            indptr,
+            indices,
+            values,
+        }
+    }
+
+    /// Sparse × dense product. `rhs` is `[n_cols]` (giving `[n_rows]`) or
+    /// `[n_cols, k]` (giving `[n_rows, k]`).
+    pub fn spmm(&self, rhs: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let dims = rhs.shape().dims();
+        let (inner, k, out_shape) = match dims.len() {
+            1 => (dims[0], 1, vec![self.n_rows]),
+            2 => (dims[0], dims[1], vec![self.n_rows, dims[1]]),
+            _ => {
+                return Err(TensorError::InvalidOperation(
+                    "spmm requires a 1D or 2D dense operand".into(),
+                ))
+            }
+        };
+        if inner != self.n_cols {
+            return Err(TensorError::DimensionMismatch(format!(
+                "spmm: sparse matrix has {} columns but dense operand has {} rows",
+                self.n_cols, inner
+            )));
+        }
+        let b = rhs.data();
+        let mut out = vec![T::ZERO; self.n_rows * k];
+        parallel::for_each_chunk(&mut out, k, self.nnz() * k, |i, out_row| {
+            let (cols, vals) = self.row(i);
+            for (&j, &v) in cols.iter().zip(vals) {
+                for (o, &bv) in out_row.iter_mut().zip(&b[j * k..(j + 1) * k]) {
+                    *o += v * bv;
+                }
+            }
+        });
+        Tensor::new(out, out_shape)
+    }
+
+    /// Sum of squares of each column.
+    pub fn col_sq_norms(&self) -> Vec<T> {
+        let mut out = vec![T::ZERO; self.n_cols];
+        for (&j, &v) in self.indices.iter().zip(&self.values) {
+            out[j] += v * v;
+        }
+        out
+    }
+
+    pub fn mul_scalar(&self, s: T) -> SparseTensor<T> {
+        let mut out = self.clone();
+        for v in &mut out.values {
+            *v *= s;
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tensor<f64> {
+        Tensor::from_vec2d(&[
+            vec![0.0, 2.0, 0.0],
+            vec![0.0, 0.0, 0.0],
+            vec![1.0, 0.0, 3.0],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dense_roundtrip_and_transpose() {
+        let dense = sample();
+        let csr = SparseTensor::from_dense(&dense).unwrap();
+        assert_eq!(csr.nnz(), 3);
+        assert_eq!(csr.indptr(), &[0, 1, 1, 3]);
+        assert_eq!(csr.to_dense(), dense);
+        assert_eq!(csr.get(2, 2).unwrap(), 3.0);
+        assert_eq!(csr.get(1, 1).unwrap(), 0.0);
+        assert_eq!(csr.transpose().to_dense(), dense.t().unwrap());
+        assert_eq!(csr.to_coo().to_csr(), csr);
+    }
+
+    #[test]
+    fn test_coo_sums_duplicates() {
+        let coo = CooTensor::from_triplets(2, 2, vec![1, 0, 1], vec![0, 1, 0], vec![1.0, 5.0, 2.0]).unwrap();
+        let csr = coo.to_csr();
+        assert_eq!(csr.nnz(), 2);
+        assert_eq!(csr.get(1, 0).unwrap(), 3.0);
+        assert_eq!(csr.to_dense(), coo.to_dense());
+        assert!(CooTensor::<f64>::new(2, 2).push(2, 0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_spmm_matches_dense() {
+        let dense = sample();
+        let csr = SparseTensor::from_dense(&dense).unwrap();
+        let b: Tensor<f64> = Tensor::randn(vec![3, 4], Some(1));
+        assert_eq!(csr.spmm(&b).unwrap(), dense.matmul(&b).unwrap());
+
+        let v: Tensor<f64> = Tensor::from_slice(&[1.0, 2.0, 3.0]);
+        assert_eq!(csr.spmm(&v).unwrap().data(), &[4.0, 0.0, 10.0]);
+        assert!(csr.spmm(&Tensor::<f64>::zeros(vec![2, 2])).is_err());
+    }
+
+    #[test]
+    fn test_new_validates_structure() {
+        assert!(SparseTensor::new(2, 2, vec![0, 1, 2], vec![1, 0], vec![1.0, 2.0]).is_ok());
+        assert!(SparseTensor::new(2, 2, vec![0, 2, 2], vec![1, 0], vec![1.0, 2.0]).is_err());
+        assert!(SparseTensor::new(1, 2, vec![0, 1], vec![2], vec![1.0]).is_err());
+    }
+}
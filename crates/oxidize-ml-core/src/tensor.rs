@@ -1,27 +1,81 @@
 use crate::dtype::Float;
 use crate::error::{TensorError, TensorResult};
+use crate::gemm;
+use crate::parallel;
+use crate::rng::RngHandle;
 use crate::shape::Shape;
 
 use rand::distributions::{Distribution, Standard};
-use rand::rngs::StdRng;
-use rand::SeedableRng;
+use crate::view::TensorView;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::iter::{Copied, StepBy, Take};
 use std::ops;
+use std::slice;
+use std::sync::Arc;
+
+/// Elements of one lane along a reduction axis.
+type Lane<'a, T> = Copied<Take<StepBy<slice::Iter<'a, T>>>>;
 
 /// N-dimensional tensor — the fundamental data structure of OxidizeML.
 ///
-/// Stores data in a flat contiguous `Vec<T>` with row-major (C-order) layout.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(bound = "T: Float")]
+/// Stores data as a contiguous row-major (C-order) window into a shared,
+/// reference-counted buffer. Reshaping and row slicing share the buffer
+/// instead of copying it; the first mutation through `data_mut` (or any
+/// in-place method) copies the window so other tensors are never affected.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float", try_from = "TensorRepr<T>", into = "TensorRepr<T>")]
 pub struct Tensor<T: Float> {
+    storage: Arc<Vec<T>>,
+    offset: usize,
+    shape: Shape,
+}
+
+/// Serialized form of a tensor: only the visible elements, never the
+/// whole shared buffer.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+struct TensorRepr<T: Float> {
     data: Vec<T>,
     shape: Shape,
 }
 
+impl<T: Float> From<Tensor<T>> for TensorRepr<T> {
+    fn from(t: Tensor<T>) -> Self {
+        let shape = t.shape.clone();
+        TensorRepr { data: t.into_data(), shape }
+    }
+}
+
+impl<T: Float> TryFrom<TensorRepr<T>> for Tensor<T> {
+    type Error = TensorError;
+    fn try_from(r: TensorRepr<T>) -> TensorResult<Self> {
+        Tensor::new(r.data, r.shape.to_vec())
+    }
+}
+
 // ─── Construction ───────────────────────────────────────────────────────────
 
 impl<T: Float> Tensor<T> {
+    /// Wrap an owned buffer without validation (caller guarantees length).
+    fn from_parts(data: Vec<T>, shape: Shape) -> Self {
+        Tensor {
+            storage: Arc::new(data),
+            offset: 0,
+            shape,
+        }
+    }
+
+    /// Build a tensor that shares `storage`, starting at `offset`.
+    pub(crate) fn from_storage(storage: Arc<Vec<T>>, offset: usize, shape: Shape) -> Self {
+        debug_assert!(offset + shape.numel() <= storage.len());
+        Tensor {
+            storage,
+            offset,
+            shape,
+        }
+    }
+
     /// Create a tensor from raw data and shape.
     pub fn new(data: Vec<T>, shape: Vec<usize>) -> TensorResult<Self> {
         let s = Shape::new(shape);
@@ -31,50 +85,35 @@ impl<T: Float> Tensor<T> {
                 got: vec![data.len()],
             });
         }
-        Ok(Tensor { data, shape: s })
+        Ok(Tensor::from_parts(data, s))
     }
 
     /// Create a tensor filled with zeros.
     pub fn zeros(shape: Vec<usize>) -> Self {
         let s = Shape::new(shape);
-        Tensor {
-            data: vec![T::ZERO; s.numel()],
-            shape: s,
-        }
+        Tensor::from_parts(vec![T::ZERO; s.numel()], s)
     }
 
     /// Create a tensor filled with ones.
     pub fn ones(shape: Vec<usize>) -> Self {
         let s = Shape::new(shape);
-        Tensor {
-            data: vec![T::ONE; s.numel()],
-            shape: s,
-        }
+        Tensor::from_parts(vec![T::ONE; s.numel()], s)
     }
 
     /// Create a tensor filled with a constant value.
     pub fn full(shape: Vec<usize>, value: T) -> Self {
         let s = Shape::new(shape);
-        Tensor {
-            data: vec![value; s.numel()],
-            shape: s,
-        }
+        Tensor::from_parts(vec![value; s.numel()], s)
     }
 
     /// Create a scalar tensor (0-d).
     pub fn scalar(value: T) -> Self {
-        Tensor {
-            data: vec![value],
-            shape: Shape::scalar(),
-        }
+        Tensor::from_parts(vec![value], Shape::scalar())
     }
 
     /// Create a 1-D tensor from a slice.
     pub fn from_slice(data: &[T]) -> Self {
-        Tensor {
-            data: data.to_vec(),
-            shape: Shape::new(vec![data.len()]),
-        }
+        Tensor::from_parts(data.to_vec(), Shape::new(vec![data.len()]))
     }
 
     /// Create a 2-D tensor from a nested slice.
@@ -101,29 +140,20 @@ impl<T: Float> Tensor<T> {
         for i in 0..n {
             data[i * n + i] = T::ONE;
         }
-        Tensor {
-            data,
-            shape: Shape::new(vec![n, n]),
-        }
+        Tensor::from_parts(data, Shape::new(vec![n, n]))
     }
 
     /// Linearly spaced values from `start` to `end` (inclusive), `n` points.
     pub fn linspace(start: T, end: T, n: usize) -> Self {
         if n == 0 {
-            return Tensor {
-                data: vec![],
-                shape: Shape::new(vec![0]),
-            };
+            return Tensor::from_parts(vec![], Shape::new(vec![0]));
         }
         if n == 1 {
             return Tensor::from_slice(&[start]);
         }
         let step = (end - start) / T::from_usize(n - 1);
         let data: Vec<T> = (0..n).map(|i| start + step * T::from_usize(i)).collect();
-        Tensor {
-            data,
-            shape: Shape::new(vec![n]),
-        }
+        Tensor::from_parts(data, Shape::new(vec![n]))
     }
 
     /// Range of values from `start` to `end` (exclusive) with step.
@@ -133,41 +163,34 @@ impl<T: Float> Tensor<T> {
         if step.to_f64() > 0.0 {
             while val.to_f64() < end.to_f64() {
                 data.push(val);
-                val = val + step;
+                val += step;
             }
         } else if step.to_f64() < 0.0 {
             while val.to_f64() > end.to_f64() {
                 data.push(val);
-                val = val + step;
+                val += step;
             }
         }
         let len = data.len();
-        Tensor {
-            data,
-            shape: Shape::new(vec![len]),
-        }
+        Tensor::from_parts(data, Shape::new(vec![len]))
     }
 
     /// Random tensor with uniform distribution in [0, 1).
+    ///
+    /// `None` draws from the global generator (see `rng::set_global_seed`).
     pub fn rand(shape: Vec<usize>, seed: Option<u64>) -> Self {
         let s = Shape::new(shape);
-        let mut rng = match seed {
-            Some(s) => StdRng::seed_from_u64(s),
-            None => StdRng::from_entropy(),
-        };
+        let mut rng = RngHandle::new(seed);
         let data: Vec<T> = (0..s.numel())
             .map(|_| T::from_f64(rand::Rng::gen::<f64>(&mut rng)))
             .collect();
-        Tensor { data, shape: s }
+        Tensor::from_parts(data, s)
     }
 
     /// Random tensor with standard normal distribution (approximate via Box-Muller).
     pub fn randn(shape: Vec<usize>, seed: Option<u64>) -> Self {
         let s = Shape::new(shape);
-        let mut rng = match seed {
-            Some(s) => StdRng::seed_from_u64(s),
-            None => StdRng::from_entropy(),
-        };
+        let mut rng = RngHandle::new(seed);
         let n = s.numel();
         let mut data = Vec::with_capacity(n);
 
@@ -185,7 +208,7 @@ impl<T: Float> Tensor<T> {
             i += 2;
         }
         data.truncate(n);
-        Tensor { data, shape: s }
+        Tensor::from_parts(data, s)
     }
 
     // ─── Accessors ──────────────────────────────────────────────────────────
@@ -203,19 +226,59 @@ impl<T: Float> Tensor<T> {
     }
 
     pub fn numel(&self) -> usize {
-        self.data.len()
+        self.shape.numel()
     }
 
     pub fn data(&self) -> &[T] {
-        &self.data
+        &self.storage[self.offset..self.offset + self.numel()]
     }
 
+    /// Mutable access to the elements. Copies the data first if the buffer
+    /// is shared with another tensor or view (copy-on-write).
     pub fn data_mut(&mut self) -> &mut [T] {
-        &mut self.data
+        let n = self.numel();
+        if self.offset != 0 || self.storage.len() != n {
+            self.storage = Arc::new(self.data().to_vec());
+            self.offset = 0;
+        }
+        &mut Arc::make_mut(&mut self.storage)[..]
     }
 
     pub fn into_data(self) -> Vec<T> {
-        self.data
+        let n = self.numel();
+        if self.offset == 0 && self.storage.len() == n {
+            Arc::try_unwrap(self.storage).unwrap_or_else(|s| s.as_ref().clone())
+        } else {
+            self.storage[self.offset..self.offset + n].to_vec()
+        }
+    }
+
+    /// Whether `self` and `other` are backed by the same buffer.
+    pub fn shares_storage(&self, other: &Tensor<T>) -> bool {
+        Arc::ptr_eq(&self.storage, &other.storage)
+    }
+
+    /// Zero-copy strided view over this tensor.
+    pub fn view(&self) -> TensorView<T> {
+        TensorView::new(
+            Arc::clone(&self.storage),
+            self.offset,
+            self.shape.to_vec(),
+            self.shape.strides(),
+        )
+    }
+
+    /// Zero-copy transpose of the last two dimensions.
+    ///
+    /// Use `contiguous()` on the result to get a `Tensor` back.
+    pub fn t_view(&self) -> TensorResult<TensorView<T>> {
+        if self.ndim() < 2 {
+            return Err(TensorError::InvalidOperation(
+                "Cannot transpose tensor with fewer than 2 dimensions".to_string(),
+            ));
+        }
+        let n = self.ndim();
+        self.view().swap_axes(n - 2, n - 1)
     }
 
     pub fn is_scalar(&self) -> bool {
@@ -224,13 +287,13 @@ impl<T: Float> Tensor<T> {
 
     /// Get a single element (scalar value).
     pub fn item(&self) -> TensorResult<T> {
-        if self.data.len() != 1 {
+        if self.data().len() != 1 {
             return Err(TensorError::InvalidOperation(format!(
                 "item() requires exactly 1 element, got {}",
-                self.data.len()
+                self.data().len()
             )));
         }
-        Ok(self.data[0])
+        Ok(self.data()[0])
     }
 
     /// Multi-dimensional indexing: compute flat offset from indices.
@@ -255,7 +318,7 @@ impl<T: Float> Tensor<T> {
             }
             offset += idx * strides[i];
         }
-        Ok(self.data[offset])
+        Ok(self.data()[offset])
     }
 
     /// Set a single element.
@@ -280,11 +343,11 @@ impl<T: Float> Tensor<T> {
             }
             offset += idx * strides[i];
         }
-        self.data[offset] = value;
+        self.data_mut()[offset] = value;
         Ok(())
     }
 
-    /// Extract a row from a 2D tensor.
+    /// Extract a row from a 2D tensor (shares the buffer).
     pub fn row(&self, i: usize) -> TensorResult<Tensor<T>> {
         if self.ndim() != 2 {
             return Err(TensorError::InvalidOperation(
@@ -294,17 +357,18 @@ impl<T: Float> Tensor<T> {
         let cols = self.shape.dim(1)?;
         let start = i * cols;
         let end = start + cols;
-        if end > self.data.len() {
+        if end > self.data().len() {
             return Err(TensorError::IndexOutOfBounds {
                 index: i,
                 axis: 0,
                 size: self.shape.dim(0)?,
             });
         }
-        Ok(Tensor {
-            data: self.data[start..end].to_vec(),
-            shape: Shape::new(vec![cols]),
-        })
+        Ok(Tensor::from_storage(
+            Arc::clone(&self.storage),
+            self.offset + start,
+            Shape::new(vec![cols]),
+        ))
     }
 
     /// Extract a column from a 2D tensor.
@@ -323,16 +387,13 @@ impl<T: Float> Tensor<T> {
                 size: cols,
             });
         }
-        let data: Vec<T> = (0..rows).map(|i| self.data[i * cols + j]).collect();
-        Ok(Tensor {
-            data,
-            shape: Shape::new(vec![rows]),
-        })
+        let data: Vec<T> = (0..rows).map(|i| self.data()[i * cols + j]).collect();
+        Ok(Tensor::from_parts(data, Shape::new(vec![rows])))
     }
 
     // ─── Shape Manipulation ─────────────────────────────────────────────────
 
-    /// Reshape the tensor (data remains the same, only shape changes).
+    /// Reshape the tensor (shares the buffer, only the shape changes).
     pub fn reshape(&self, new_shape: Vec<usize>) -> TensorResult<Tensor<T>> {
         let ns = Shape::new(new_shape);
         if self.numel() != ns.numel() {
@@ -341,18 +402,17 @@ impl<T: Float> Tensor<T> {
                 got: self.shape_vec(),
             });
         }
-        Ok(Tensor {
-            data: self.data.clone(),
-            shape: ns,
-        })
+        Ok(self.with_shape(ns))
     }
 
     /// Flatten to 1-D.
     pub fn flatten(&self) -> Tensor<T> {
-        Tensor {
-            data: self.data.clone(),
-            shape: Shape::new(vec![self.numel()]),
-        }
+        self.with_shape(Shape::new(vec![self.numel()]))
+    }
+
+    /// Same buffer, new shape. Caller guarantees `shape.numel() == self.numel()`.
+    fn with_shape(&self, shape: Shape) -> Tensor<T> {
+        Tensor::from_storage(Arc::clone(&self.storage), self.offset, shape)
     }
 
     /// Transpose the last two dimensions.
@@ -370,13 +430,10 @@ impl<T: Float> Tensor<T> {
             let mut data = vec![T::ZERO; self.numel()];
             for i in 0..rows {
                 for j in 0..cols {
-                    data[j * rows + i] = self.data[i * cols + j];
+                    data[j * rows + i] = self.data()[i * cols + j];
                 }
             }
-            return Ok(Tensor {
-                data,
-                shape: self.shape.transposed()?,
-            });
+            return Ok(Tensor::from_parts(data, self.shape.transposed()?));
         }
 
         // Batched transpose: transpose last two dims
@@ -387,14 +444,11 @@ impl<T: Float> Tensor<T> {
             let offset = b * mat_size;
             for i in 0..rows {
                 for j in 0..cols {
-                    data[offset + j * rows + i] = self.data[offset + i * cols + j];
+                    data[offset + j * rows + i] = self.data()[offset + i * cols + j];
                 }
             }
         }
-        Ok(Tensor {
-            data,
-            shape: self.shape.transposed()?,
-        })
+        Ok(Tensor::from_parts(data, self.shape.transposed()?))
     }
 
     /// Add a dimension of size 1 at the given axis.
@@ -407,20 +461,14 @@ impl<T: Float> Tensor<T> {
             });
         }
         dims.insert(axis, 1);
-        Ok(Tensor {
-            data: self.data.clone(),
-            shape: Shape::new(dims),
-        })
+        Ok(self.with_shape(Shape::new(dims)))
     }
 
     /// Remove all dimensions of size 1.
     pub fn squeeze(&self) -> Tensor<T> {
         let dims: Vec<usize> = self.shape.dims().iter().copied().filter(|&d| d != 1).collect();
         let dims = if dims.is_empty() { vec![1] } else { dims };
-        Tensor {
-            data: self.data.clone(),
-            shape: Shape::new(dims),
-        }
+        self.with_shape(Shape::new(dims))
     }
 
     /// Concatenate tensors along axis.
@@ -456,7 +504,7 @@ impl<T: Float> Tensor<T> {
             let mut data = Vec::new();
             let mut total_rows = 0usize;
             for t in tensors {
-                data.extend_from_slice(&t.data);
+                data.extend_from_slice(t.data());
                 total_rows += t.shape.dim(0)?;
             }
             let mut new_shape = ref_shape;
@@ -479,7 +527,7 @@ impl<T: Float> Tensor<T> {
                 let t_axis = t.shape.dim(axis)?;
                 let t_stride: usize = t_axis * inner;
                 let start = o * t_stride;
-                data.extend_from_slice(&t.data[start..start + t_stride]);
+                data.extend_from_slice(&t.data()[start..start + t_stride]);
             }
         }
 
@@ -488,17 +536,135 @@ impl<T: Float> Tensor<T> {
         Tensor::new(data, new_shape)
     }
 
+    // ─── Padding, Tiling & Flipping ─────────────────────────────────────────
+
+    /// Pad every axis with `pad_width[axis] = (before, after)` copies of `value`.
+    pub fn pad(&self, pad_width: &[(usize, usize)], value: T) -> TensorResult<Tensor<T>> {
+        let dims = self.shape.dims();
+        if pad_width.len() != dims.len() {
+            return Err(TensorError::DimensionMismatch(format!(
+                "pad expects {} (before, after) pairs, got {}",
+                dims.len(),
+                pad_width.len()
+            )));
+        }
+        let out_dims: Vec<usize> = dims.iter().zip(pad_width).map(|(&d, &(b, a))| b + d + a).collect();
+        let out_shape = Shape::new(out_dims);
+        let mut out = vec![value; out_shape.numel()];
+        if self.numel() == 0 {
+            return Ok(Tensor::from_parts(out, out_shape));
+        }
+        if dims.is_empty() {
+            out[0] = self.data()[0];
+            return Ok(Tensor::from_parts(out, out_shape));
+        }
+
+        // Copy one contiguous input row (last axis) at a time.
+        let ndim = dims.len();
+        let row = dims[ndim - 1];
+        let out_strides = out_shape.strides();
+        let data = self.data();
+        let mut index = vec![0usize; ndim - 1];
+        for src in data.chunks(row) {
+            let dst: usize = (0..ndim)
+                .map(|d| (index.get(d).copied().unwrap_or(0) + pad_width[d].0) * out_strides[d])
+                .sum();
+            out[dst..dst + row].copy_from_slice(src);
+            for d in (0..ndim - 1).rev() {
+                index[d] += 1;
+                if index[d] < dims[d] {
+                    break;
+                }
+                index[d] = 0;
+            }
+        }
+        Ok(Tensor::from_parts(out, out_shape))
+    }
+
+    /// Repeat the whole tensor `reps[axis]` times along each axis (like `np.tile`).
+    ///
+    /// If `reps` is longer than the rank, the shape is padded with leading 1s;
+    /// if shorter, `reps` is.
+    pub fn tile(&self, reps: &[usize]) -> Tensor<T> {
+        let ndim = self.ndim().max(reps.len());
+        let mut dims = vec![1usize; ndim - self.ndim()];
+        dims.extend_from_slice(self.shape.dims());
+        let mut r = vec![1usize; ndim - reps.len()];
+        r.extend_from_slice(reps);
+
+        let out_shape = Shape::new(dims.iter().zip(&r).map(|(&d, &n)| d * n).collect());
+        let src_strides = Shape::new(dims.clone()).strides();
+        let out_dims = out_shape.to_vec();
+        let data = self.data();
+        let out = (0..out_shape.numel())
+            .map(|flat| {
+                let mut rem = flat;
+                let mut src = 0;
+                for d in (0..ndim).rev() {
+                    src += (rem % out_dims[d]) % dims[d] * src_strides[d];
+                    rem /= out_dims[d];
+                }
+                data[src]
+            })
+            .collect();
+        Tensor::from_parts(out, out_shape)
+    }
+
+    /// Reverse the order of elements along `axis`.
+    pub fn flip(&self, axis: usize) -> TensorResult<Tensor<T>> {
+        let (outer, size, inner) = self.axis_blocks(axis)?;
+        let data = self.data();
+        let mut out = Vec::with_capacity(data.len());
+        for o in 0..outer {
+            for i in (0..size).rev() {
+                let start = (o * size + i) * inner;
+                out.extend_from_slice(&data[start..start + inner]);
+            }
+        }
+        Ok(Tensor::from_parts(out, self.shape.clone()))
+    }
+
+    /// Shift elements along `axis` by `shift` places, wrapping around the end.
+    pub fn roll(&self, axis: usize, shift: isize) -> TensorResult<Tensor<T>> {
+        let (outer, size, inner) = self.axis_blocks(axis)?;
+        if size == 0 {
+            return Ok(self.clone());
+        }
+        let shift = shift.rem_euclid(size as isize) as usize;
+        let data = self.data();
+        let mut out = Vec::with_capacity(data.len());
+        for o in 0..outer {
+            for i in 0..size {
+                let src = (i + size - shift) % size;
+                let start = (o * size + src) * inner;
+                out.extend_from_slice(&data[start..start + inner]);
+            }
+        }
+        Ok(Tensor::from_parts(out, self.shape.clone()))
+    }
+
+    /// `(outer, size, inner)` block sizes around `axis` for row-major loops.
+    fn axis_blocks(&self, axis: usize) -> TensorResult<(usize, usize, usize)> {
+        let dims = self.shape.dims();
+        if axis >= dims.len() {
+            return Err(TensorError::InvalidAxis {
+                axis,
+                ndim: self.ndim(),
+            });
+        }
+        let outer = dims[..axis].iter().product();
+        let inner = dims[axis + 1..].iter().product();
+        Ok((outer, dims[axis], inner))
+    }
+
     // ─── Element-wise Unary Operations ──────────────────────────────────────
 
     pub fn apply<F: Fn(T) -> T>(&self, f: F) -> Tensor<T> {
-        Tensor {
-            data: self.data.iter().map(|&x| f(x)).collect(),
-            shape: self.shape.clone(),
-        }
+        Tensor::from_parts(self.data().iter().map(|&x| f(x)).collect(), self.shape.clone())
     }
 
     pub fn apply_mut<F: Fn(T) -> T>(&mut self, f: F) {
-        for x in self.data.iter_mut() {
+        for x in self.data_mut().iter_mut() {
             *x = f(*x);
         }
     }
@@ -539,6 +705,48 @@ impl<T: Float> Tensor<T> {
         self.apply(|x| T::ONE / (T::ONE + (-x).exp()))
     }
 
+    /// Error function, element-wise (absolute error below 1.2e-7).
+    pub fn erf(&self) -> Tensor<T> {
+        self.apply(|x| T::from_f64(erf(x.to_f64())))
+    }
+
+    /// GELU: x·Φ(x), with Φ the standard normal CDF.
+    pub fn gelu(&self) -> Tensor<T> {
+        self.apply(|x| {
+            let x = x.to_f64();
+            T::from_f64(0.5 * x * (1.0 + erf(x / std::f64::consts::SQRT_2)))
+        })
+    }
+
+    /// GELU with the tanh approximation:
+    /// 0.5·x·(1 + tanh(√(2/π)·(x + 0.044715·x³))).
+    pub fn gelu_tanh(&self) -> Tensor<T> {
+        self.apply(|x| {
+            let x = x.to_f64();
+            T::from_f64(0.5 * x * (1.0 + (GELU_TANH_SCALE * (x + 0.044715 * x * x * x)).tanh()))
+        })
+    }
+
+    /// SiLU (Swish): x·σ(x).
+    pub fn silu(&self) -> Tensor<T> {
+        self.apply(|x| x / (T::ONE + (-x).exp()))
+    }
+
+    /// ELU: x for x > 0, alpha·(exp(x) - 1) otherwise.
+    pub fn elu(&self, alpha: T) -> Tensor<T> {
+        self.apply(|x| if x > T::ZERO { x } else { alpha * (x.exp() - T::ONE) })
+    }
+
+    /// Softplus: ln(1 + exp(beta·x)) / beta, computed without overflow.
+    pub fn softplus(&self, beta: T) -> Tensor<T> {
+        self.apply(|x| softplus(x, beta))
+    }
+
+    /// Mish: x·tanh(softplus(x)).
+    pub fn mish(&self) -> Tensor<T> {
+        self.apply(|x| x * softplus(x, T::ONE).tanh())
+    }
+
     // ─── Scalar Operations ──────────────────────────────────────────────────
 
     pub fn add_scalar(&self, s: T) -> Tensor<T> { self.apply(|x| x + s) }
@@ -548,23 +756,19 @@ impl<T: Float> Tensor<T> {
 
     // ─── Element-wise Binary Operations (with broadcasting) ─────────────────
 
-    fn broadcast_binary_op<F: Fn(T, T) -> T>(
+    fn broadcast_binary_op<F: Fn(T, T) -> T + Sync + Send>(
         &self,
         other: &Tensor<T>,
         op: F,
     ) -> TensorResult<Tensor<T>> {
+        let a_data = self.data();
+        let b_data = other.data();
+
         // Fast path: same shape
         if self.shape == other.shape {
-            let data: Vec<T> = self
-                .data
-                .iter()
-                .zip(other.data.iter())
-                .map(|(&a, &b)| op(a, b))
-                .collect();
-            return Ok(Tensor {
-                data,
-                shape: self.shape.clone(),
-            });
+            let n = a_data.len();
+            let data = parallel::map_range(n, n, |i| op(a_data[i], b_data[i]));
+            return Ok(Tensor::from_parts(data, self.shape.clone()));
         }
 
         let out_shape = Shape::broadcast_shape(&self.shape, &other.shape)?;
@@ -577,9 +781,7 @@ impl<T: Float> Tensor<T> {
         let out_dims = out_shape.dims();
         let ndim = out_dims.len();
 
-        let mut data = Vec::with_capacity(out_numel);
-
-        for flat_idx in 0..out_numel {
+        let data = parallel::map_range(out_numel, out_numel * ndim, |flat_idx| {
             // Convert flat index to multi-dim index
             let mut remaining = flat_idx;
             let mut a_offset = 0usize;
@@ -608,13 +810,10 @@ impl<T: Float> Tensor<T> {
                 }
             }
 
-            data.push(op(self.data[a_offset], other.data[b_offset]));
-        }
+            op(a_data[a_offset], b_data[b_offset])
+        });
 
-        Ok(Tensor {
-            data,
-            shape: out_shape,
-        })
+        Ok(Tensor::from_parts(data, out_shape))
     }
 
     pub fn add(&self, other: &Tensor<T>) -> TensorResult<Tensor<T>> {
@@ -633,11 +832,77 @@ impl<T: Float> Tensor<T> {
         self.broadcast_binary_op(other, |a, b| a / b)
     }
 
+    // ─── In-place Operations ────────────────────────────────────────────────
+
+    /// `self[i] = f(self[i], other[i])` without allocating a result.
+    ///
+    /// `other` must broadcast to `self`'s shape; the shape of `self` never changes.
+    pub fn zip_apply_mut<F: Fn(T, T) -> T>(&mut self, other: &Tensor<T>, f: F) -> TensorResult<()> {
+        if self.shape == other.shape {
+            let b = other.data();
+            for (a, &b) in self.data_mut().iter_mut().zip(b) {
+                *a = f(*a, b);
+            }
+            return Ok(());
+        }
+
+        if Shape::broadcast_shape(&self.shape, &other.shape)? != self.shape {
+            return Err(TensorError::BroadcastError {
+                a: self.shape_vec(),
+                b: other.shape_vec(),
+            });
+        }
+        let out_dims = self.shape.to_vec();
+        let b_dims = other.shape.dims();
+        let b_strides = other.shape.strides();
+        let b_data = other.data();
+        let lead = out_dims.len() - b_dims.len();
+        for (flat, a) in self.data_mut().iter_mut().enumerate() {
+            let mut rem = flat;
+            let mut b_offset = 0;
+            for d in (lead..out_dims.len()).rev() {
+                let idx = rem % out_dims[d];
+                rem /= out_dims[d];
+                if b_dims[d - lead] > 1 {
+                    b_offset += idx * b_strides[d - lead];
+                }
+            }
+            *a = f(*a, b_data[b_offset]);
+        }
+        Ok(())
+    }
+
+    pub fn add_(&mut self, other: &Tensor<T>) -> TensorResult<()> {
+        self.zip_apply_mut(other, |a, b| a + b)
+    }
+
+    pub fn sub_(&mut self, other: &Tensor<T>) -> TensorResult<()> {
+        self.zip_apply_mut(other, |a, b| a - b)
+    }
+
+    pub fn mul_(&mut self, other: &Tensor<T>) -> TensorResult<()> {
+        self.zip_apply_mut(other, |a, b| a * b)
+    }
+
+    pub fn div_(&mut self, other: &Tensor<T>) -> TensorResult<()> {
+        self.zip_apply_mut(other, |a, b| a / b)
+    }
+
+    /// `self += alpha * other` (axpy).
+    pub fn add_scaled_(&mut self, other: &Tensor<T>, alpha: T) -> TensorResult<()> {
+        self.zip_apply_mut(other, |a, b| a + alpha * b)
+    }
+
+    pub fn add_scalar_(&mut self, s: T) { self.apply_mut(|x| x + s) }
+    pub fn sub_scalar_(&mut self, s: T) { self.apply_mut(|x| x - s) }
+    pub fn mul_scalar_(&mut self, s: T) { self.apply_mut(|x| x * s) }
+    pub fn div_scalar_(&mut self, s: T) { self.apply_mut(|x| x / s) }
+
     // ─── Reduction Operations ───────────────────────────────────────────────
 
     /// Sum of all elements.
     pub fn sum_all(&self) -> T {
-        self.data.iter().copied().sum()
+        self.data().iter().copied().sum()
     }
 
     /// Mean of all elements.
@@ -647,7 +912,7 @@ impl<T: Float> Tensor<T> {
 
     /// Max of all elements.
     pub fn max_all(&self) -> TensorResult<T> {
-        self.data
+        self.data()
             .iter()
             .copied()
             .reduce(T::max)
@@ -656,7 +921,7 @@ impl<T: Float> Tensor<T> {
 
     /// Min of all elements.
     pub fn min_all(&self) -> TensorResult<T> {
-        self.data
+        self.data()
             .iter()
             .copied()
             .reduce(T::min)
@@ -665,12 +930,12 @@ impl<T: Float> Tensor<T> {
 
     /// Argmax of all elements (flat index).
     pub fn argmax_all(&self) -> TensorResult<usize> {
-        if self.data.is_empty() {
+        if self.data().is_empty() {
             return Err(TensorError::EmptyTensor);
         }
         let mut best = 0;
-        for (i, &v) in self.data.iter().enumerate() {
-            if v > self.data[best] {
+        for (i, &v) in self.data().iter().enumerate() {
+            if v > self.data()[best] {
                 best = i;
             }
         }
@@ -679,20 +944,23 @@ impl<T: Float> Tensor<T> {
 
     /// Argmin of all elements (flat index).
     pub fn argmin_all(&self) -> TensorResult<usize> {
-        if self.data.is_empty() {
+        if self.data().is_empty() {
             return Err(TensorError::EmptyTensor);
         }
         let mut best = 0;
-        for (i, &v) in self.data.iter().enumerate() {
-            if v < self.data[best] {
+        for (i, &v) in self.data().iter().enumerate() {
+            if v < self.data()[best] {
                 best = i;
             }
         }
         Ok(best)
     }
 
-    /// Sum along a specific axis, collapsing that dimension.
-    pub fn sum_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
+    /// Reduce every lane along `axis` to a single value, removing that dimension.
+    fn reduce_axis<F>(&self, axis: usize, f: F) -> TensorResult<Tensor<T>>
+    where
+        F: Fn(Lane<'_, T>) -> T + Sync + Send,
+    {
         let dims = self.shape.dims();
         if axis >= dims.len() {
             return Err(TensorError::InvalidAxis {
@@ -701,9 +969,9 @@ impl<T: Float> Tensor<T> {
             });
         }
 
-        let outer: usize = dims[..axis].iter().product();
         let axis_size = dims[axis];
         let inner: usize = dims[axis + 1..].iter().product();
+        let outer: usize = dims[..axis].iter().product();
 
         let mut new_dims: Vec<usize> = dims.to_vec();
         new_dims.remove(axis);
@@ -711,20 +979,22 @@ impl<T: Float> Tensor<T> {
             new_dims.push(1);
         }
 
-        let mut result = vec![T::ZERO; outer * inner];
-        for o in 0..outer {
-            for a in 0..axis_size {
-                for i in 0..inner {
-                    let src = o * axis_size * inner + a * inner + i;
-                    let dst = o * inner + i;
-                    result[dst] = result[dst] + self.data[src];
-                }
-            }
-        }
+        let data = self.data();
+        let result = parallel::map_range(outer * inner, self.numel(), |dst| {
+            let (o, i) = (dst / inner, dst % inner);
+            let start = o * axis_size * inner + i;
+            let lane = data[start..].iter().step_by(inner).take(axis_size).copied();
+            f(lane)
+        });
 
         Tensor::new(result, new_dims)
     }
 
+    /// Sum along a specific axis, collapsing that dimension.
+    pub fn sum_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
+        self.reduce_axis(axis, |lane| lane.fold(T::ZERO, |acc, v| acc + v))
+    }
+
     /// Mean along a specific axis.
     pub fn mean_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
         let axis_size = self.shape.dim(axis)?;
@@ -734,6 +1004,70 @@ impl<T: Float> Tensor<T> {
 
     /// Argmax along axis — returns tensor of indices.
     pub fn argmax_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
+        self.reduce_axis(axis, |mut lane| {
+            let Some(mut best_val) = lane.next() else {
+                return T::ZERO;
+            };
+            let mut best_idx = 0usize;
+            for (a, v) in lane.enumerate() {
+                if v > best_val {
+                    best_val = v;
+                    best_idx = a + 1;
+                }
+            }
+            T::from_usize(best_idx)
+        })
+    }
+
+    /// Argmin along axis — returns tensor of indices.
+    pub fn argmin_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
+        self.reduce_axis(axis, |mut lane| {
+            let Some(mut best_val) = lane.next() else {
+                return T::ZERO;
+            };
+            let mut best_idx = 0usize;
+            for (a, v) in lane.enumerate() {
+                if v < best_val {
+                    best_val = v;
+                    best_idx = a + 1;
+                }
+            }
+            T::from_usize(best_idx)
+        })
+    }
+
+    /// Median along axis (mean of the two middle values for even lengths).
+    pub fn median_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
+        self.reduce_axis(axis, |lane| median_of(lane.collect()))
+    }
+
+    /// Median of all elements.
+    pub fn median_all(&self) -> TensorResult<T> {
+        if self.numel() == 0 {
+            return Err(TensorError::EmptyTensor);
+        }
+        Ok(median_of(self.data().to_vec()))
+    }
+
+    /// Variance along axis (population variance).
+    pub fn var_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
+        let n = T::from_usize(self.shape.dim(axis)?);
+        self.reduce_axis(axis, |lane| {
+            let mu = lane.clone().fold(T::ZERO, |acc, v| acc + v) / n;
+            lane.fold(T::ZERO, |acc, v| acc + (v - mu) * (v - mu)) / n
+        })
+    }
+
+    /// Standard deviation along axis.
+    pub fn std_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
+        let var = self.var_axis(axis)?;
+        Ok(var.sqrt())
+    }
+
+    // ─── Sorting & Uniqueness ───────────────────────────────────────────────
+
+    /// Apply `f(lane, out)` to every lane along `axis`, keeping the shape.
+    fn map_lanes<F: Fn(&[T], &mut [T])>(&self, axis: usize, f: F) -> TensorResult<Tensor<T>> {
         let dims = self.shape.dims();
         if axis >= dims.len() {
             return Err(TensorError::InvalidAxis {
@@ -741,77 +1075,79 @@ impl<T: Float> Tensor<T> {
                 ndim: self.ndim(),
             });
         }
-
-        let outer: usize = dims[..axis].iter().product();
         let axis_size = dims[axis];
         let inner: usize = dims[axis + 1..].iter().product();
+        let outer: usize = dims[..axis].iter().product();
 
-        let mut new_dims: Vec<usize> = dims.to_vec();
-        new_dims.remove(axis);
-        if new_dims.is_empty() {
-            new_dims.push(1);
-        }
-
-        let mut result = vec![T::ZERO; outer * inner];
+        let data = self.data();
+        let mut result = vec![T::ZERO; self.numel()];
+        let mut lane = vec![T::ZERO; axis_size];
+        let mut out = vec![T::ZERO; axis_size];
         for o in 0..outer {
             for i in 0..inner {
-                let mut best_idx = 0usize;
-                let mut best_val = self.data[o * axis_size * inner + i];
-                for a in 1..axis_size {
-                    let v = self.data[o * axis_size * inner + a * inner + i];
-                    if v > best_val {
-                        best_val = v;
-                        best_idx = a;
-                    }
+                let start = o * axis_size * inner + i;
+                for a in 0..axis_size {
+                    lane[a] = data[start + a * inner];
+                }
+                f(&lane, &mut out);
+                for a in 0..axis_size {
+                    result[start + a * inner] = out[a];
                 }
-                result[o * inner + i] = T::from_usize(best_idx);
             }
         }
-
-        Tensor::new(result, new_dims)
+        Ok(Tensor::from_parts(result, self.shape.clone()))
     }
 
-    /// Variance along axis (population variance).
-    pub fn var_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
-        let mean = self.mean_axis(axis)?;
-        // Expand mean back along the axis
-        let dims = self.shape.dims();
-        let outer: usize = dims[..axis].iter().product();
-        let axis_size = dims[axis];
-        let inner: usize = dims[axis + 1..].iter().product();
+    /// Sort every lane along `axis`. NaN sorts after all other values in
+    /// either direction.
+    pub fn sort_axis(&self, axis: usize, descending: bool) -> TensorResult<Tensor<T>> {
+        self.map_lanes(axis, |lane, out| {
+            out.copy_from_slice(lane);
+            out.sort_by(|a, b| cmp_sort(*a, *b, descending));
+        })
+    }
 
-        let mut result = vec![T::ZERO; outer * inner];
-        for o in 0..outer {
-            for a in 0..axis_size {
-                for i in 0..inner {
-                    let src = o * axis_size * inner + a * inner + i;
-                    let mu = mean.data[o * inner + i];
-                    let diff = self.data[src] - mu;
-                    result[o * inner + i] = result[o * inner + i] + diff * diff;
-                }
+    /// Indices that would sort every lane along `axis` (stable, NaN last).
+    pub fn argsort_axis(&self, axis: usize, descending: bool) -> TensorResult<Tensor<T>> {
+        self.map_lanes(axis, |lane, out| {
+            for (o, i) in out.iter_mut().zip(argsort_slice(lane, descending)) {
+                *o = T::from_usize(i);
             }
-        }
-        for v in result.iter_mut() {
-            *v = *v / T::from_usize(axis_size);
-        }
+        })
+    }
 
-        let mut new_dims: Vec<usize> = dims.to_vec();
-        new_dims.remove(axis);
-        if new_dims.is_empty() {
-            new_dims.push(1);
-        }
-        Tensor::new(result, new_dims)
+    /// Flat indices that would sort all elements (stable, NaN last).
+    pub fn argsort(&self, descending: bool) -> Vec<usize> {
+        argsort_slice(self.data(), descending)
     }
 
-    /// Standard deviation along axis.
-    pub fn std_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
-        let var = self.var_axis(axis)?;
-        Ok(var.sqrt())
+    /// Sorted distinct values as a 1-D tensor.
+    pub fn unique(&self) -> Tensor<T> {
+        self.unique_counts().0
+    }
+
+    /// Sorted distinct values and how often each occurs. All NaNs count as
+    /// one value, placed last, as in numpy.
+    pub fn unique_counts(&self) -> (Tensor<T>, Vec<usize>) {
+        let mut sorted = self.data().to_vec();
+        sorted.sort_by(|a, b| cmp_total(*a, *b));
+        let mut values: Vec<T> = Vec::new();
+        let mut counts: Vec<usize> = Vec::new();
+        for v in sorted {
+            match values.last() {
+                Some(&last) if last == v || (last.is_nan() && v.is_nan()) => *counts.last_mut().unwrap() += 1,
+                _ => {
+                    values.push(v);
+                    counts.push(1);
+                }
+            }
+        }
+        (Tensor::from_slice(&values), counts)
     }
 
     // ─── Slicing ────────────────────────────────────────────────────────────
 
-    /// Slice rows from a 2D tensor: returns rows[start..end].
+    /// Slice rows from a 2D tensor: returns rows[start..end] (shares the buffer).
     pub fn slice_rows(&self, start: usize, end: usize) -> TensorResult<Tensor<T>> {
         if self.ndim() != 2 {
             return Err(TensorError::InvalidOperation(
@@ -827,8 +1163,11 @@ impl<T: Float> Tensor<T> {
                 size: rows,
             });
         }
-        let data = self.data[start * cols..end * cols].to_vec();
-        Tensor::new(data, vec![end - start, cols])
+        Ok(Tensor::from_storage(
+            Arc::clone(&self.storage),
+            self.offset + start * cols,
+            Shape::new(vec![end - start, cols]),
+        ))
     }
 
     /// Slice columns from a 2D tensor.
@@ -851,12 +1190,135 @@ impl<T: Float> Tensor<T> {
         let mut data = Vec::with_capacity(rows * new_cols);
         for i in 0..rows {
             for j in start..end {
-                data.push(self.data[i * cols + j]);
+                data.push(self.data()[i * cols + j]);
             }
         }
         Tensor::new(data, vec![rows, new_cols])
     }
 
+    // ─── Fancy Indexing ─────────────────────────────────────────────────────
+
+    /// Pick entries `indices` along `axis` (duplicates allowed).
+    pub fn index_select(&self, axis: usize, indices: &[usize]) -> TensorResult<Tensor<T>> {
+        let dims = self.shape.dims();
+        if axis >= dims.len() {
+            return Err(TensorError::InvalidAxis {
+                axis,
+                ndim: self.ndim(),
+            });
+        }
+        let axis_size = dims[axis];
+        if let Some(&bad) = indices.iter().find(|&&i| i >= axis_size) {
+            return Err(TensorError::IndexOutOfBounds {
+                index: bad,
+                axis,
+                size: axis_size,
+            });
+        }
+        let outer: usize = dims[..axis].iter().product();
+        let inner: usize = dims[axis + 1..].iter().product();
+
+        let data = self.data();
+        let mut result = Vec::with_capacity(outer * indices.len() * inner);
+        for o in 0..outer {
+            for &idx in indices {
+                let start = (o * axis_size + idx) * inner;
+                result.extend_from_slice(&data[start..start + inner]);
+            }
+        }
+        let mut new_dims = dims.to_vec();
+        new_dims[axis] = indices.len();
+        Ok(Tensor::from_parts(result, Shape::new(new_dims)))
+    }
+
+    /// Elements where `mask > 0`, flattened to 1-D in row-major order.
+    pub fn masked_select(&self, mask: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        if self.shape != mask.shape {
+            return Err(TensorError::ShapeMismatch {
+                expected: self.shape_vec(),
+                got: mask.shape_vec(),
+            });
+        }
+        let data: Vec<T> = self
+            .data()
+            .iter()
+            .zip(mask.data())
+            .filter(|(_, &m)| m > T::ZERO)
+            .map(|(&v, _)| v)
+            .collect();
+        Ok(Tensor::from_slice(&data))
+    }
+
+    /// Gather values along `axis`: `out[.., i, ..] = self[.., index[.., i, ..], ..]`.
+    ///
+    /// `index` holds integer positions, has the same rank as `self`, and its
+    /// size on every other axis must not exceed `self`'s. The output has
+    /// the shape of `index`.
+    pub fn gather(&self, axis: usize, index: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.check_index_tensor(axis, index)?;
+        let strides = self.shape.strides();
+        let idx_dims = index.shape.dims();
+        let data = self.data();
+        let out = index
+            .data()
+            .iter()
+            .enumerate()
+            .map(|(flat, &pos)| {
+                let pos = to_index(pos, axis, self.shape.dims()[axis])?;
+                Ok(data[scatter_offset(flat, idx_dims, &strides, axis, pos)])
+            })
+            .collect::<TensorResult<Vec<T>>>()?;
+        Ok(Tensor::from_parts(out, index.shape.clone()))
+    }
+
+    /// Return a copy of `self` with `src` accumulated at the positions along
+    /// `axis` given by `index` (the inverse of `gather`).
+    ///
+    /// `index` and `src` must have the same shape.
+    pub fn scatter_add(&self, axis: usize, index: &Tensor<T>, src: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.check_index_tensor(axis, index)?;
+        if index.shape != src.shape {
+            return Err(TensorError::ShapeMismatch {
+                expected: index.shape_vec(),
+                got: src.shape_vec(),
+            });
+        }
+        let strides = self.shape.strides();
+        let idx_dims = index.shape.dims();
+        let axis_size = self.shape.dims()[axis];
+        let mut out = self.data().to_vec();
+        for (flat, (&pos, &v)) in index.data().iter().zip(src.data()).enumerate() {
+            let pos = to_index(pos, axis, axis_size)?;
+            out[scatter_offset(flat, idx_dims, &strides, axis, pos)] += v;
+        }
+        Ok(Tensor::from_parts(out, self.shape.clone()))
+    }
+
+    fn check_index_tensor(&self, axis: usize, index: &Tensor<T>) -> TensorResult<()> {
+        if axis >= self.ndim() {
+            return Err(TensorError::InvalidAxis {
+                axis,
+                ndim: self.ndim(),
+            });
+        }
+        if index.ndim() != self.ndim() {
+            return Err(TensorError::DimensionMismatch(format!(
+                "index must have {} dimensions, got {}",
+                self.ndim(),
+                index.ndim()
+            )));
+        }
+        for (d, (&i, &s)) in index.shape.dims().iter().zip(self.shape.dims()).enumerate() {
+            if d != axis && i > s {
+                return Err(TensorError::ShapeMismatch {
+                    expected: self.shape_vec(),
+                    got: index.shape_vec(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     // ─── Comparisons ────────────────────────────────────────────────────────
 
     /// Element-wise comparison, returns tensor of 1.0 / 0.0.
@@ -894,14 +1356,14 @@ impl<T: Float> Tensor<T> {
             });
         }
         Ok(self
-            .data
+            .data()
             .iter()
-            .zip(other.data.iter())
+            .zip(other.data().iter())
             .map(|(&a, &b)| a * b)
             .sum())
     }
 
-    /// Matrix multiply: supports 2D×2D and batched.
+    /// Matrix multiply: supports 2D×2D and batched (cache-blocked kernel).
     pub fn matmul(&self, other: &Tensor<T>) -> TensorResult<Tensor<T>> {
         if self.ndim() < 2 || other.ndim() < 2 {
             return Err(TensorError::InvalidOperation(
@@ -923,18 +1385,12 @@ impl<T: Float> Tensor<T> {
             )));
         }
 
+        let a_data = self.data();
+        let b_data = other.data();
+
         if self.ndim() == 2 && other.ndim() == 2 {
-            // Standard 2D matmul
             let mut data = vec![T::ZERO; m * n];
-            for i in 0..m {
-                for j in 0..n {
-                    let mut sum = T::ZERO;
-                    for p in 0..k {
-                        sum = sum + self.data[i * k + p] * other.data[p * n + j];
-                    }
-                    data[i * n + j] = sum;
-                }
-            }
+            gemm::gemm(a_data, b_data, &mut data, m, k, n);
             return Tensor::new(data, vec![m, n]);
         }
 
@@ -946,25 +1402,19 @@ impl<T: Float> Tensor<T> {
         let mut data = vec![T::ZERO; batch * m * n];
         let a_mat = m * k;
         let b_mat = k * n;
-        let c_mat = m * n;
 
-        for b_idx in 0..batch {
+        parallel::for_each_chunk(&mut data, m * n, batch * m * n * k, |b_idx, c| {
             let a_off = if batch_a == 1 { 0 } else { b_idx * a_mat };
             let b_off = if batch_b == 1 { 0 } else { b_idx * b_mat };
-            let c_off = b_idx * c_mat;
-
-            for i in 0..m {
-                for j in 0..n {
-                    let mut sum = T::ZERO;
-                    for p in 0..k {
-                        sum = sum
-                            + self.data[a_off + i * k + p]
-                                * other.data[b_off + p * n + j];
-                    }
-                    data[c_off + i * n + j] = sum;
-                }
-            }
-        }
+            gemm::gemm(
+                &a_data[a_off..a_off + a_mat],
+                &b_data[b_off..b_off + b_mat],
+                c,
+                m,
+                k,
+                n,
+            );
+        });
 
         let mut out_shape = if batch_a > batch_b {
             a_dims[..a_dims.len() - 2].to_vec()
@@ -988,7 +1438,7 @@ impl<T: Float> Tensor<T> {
         let outer: usize = dims[..last_axis].iter().product();
         let axis_size = dims[last_axis];
 
-        let mut data = self.data.clone();
+        let mut data = self.data().to_vec();
         for o in 0..outer {
             let start = o * axis_size;
             let end = start + axis_size;
@@ -999,23 +1449,32 @@ impl<T: Float> Tensor<T> {
             let mut sum = T::ZERO;
             for i in start..end {
                 data[i] = (data[i] - max_val).exp();
-                sum = sum + data[i];
+                sum += data[i];
             }
             for i in start..end {
-                data[i] = data[i] / sum;
+                data[i] /= sum;
             }
         }
 
-        Ok(Tensor {
-            data,
-            shape: self.shape.clone(),
-        })
+        Ok(Tensor::from_parts(data, self.shape.clone()))
     }
 
-    /// Log-softmax along the last axis.
+    /// Log-softmax along the last axis, computed as `x - max - ln Σ exp(x - max)`
+    /// so large negative logits stay finite.
     pub fn log_softmax(&self) -> TensorResult<Tensor<T>> {
-        let sm = self.softmax()?;
-        Ok(sm.ln())
+        if self.ndim() == 0 {
+            return Ok(Tensor::scalar(T::ZERO));
+        }
+        let axis_size = self.shape.dims()[self.ndim() - 1];
+        let mut data = self.data().to_vec();
+        for lane in data.chunks_mut(axis_size.max(1)) {
+            let max_val = lane.iter().copied().reduce(T::max).unwrap_or(T::ZERO);
+            let lse = lane.iter().fold(T::ZERO, |acc, &v| acc + (v - max_val).exp()).ln() + max_val;
+            for v in lane.iter_mut() {
+                *v -= lse;
+            }
+        }
+        Ok(Tensor::from_parts(data, self.shape.clone()))
     }
 
     /// Softmax along an arbitrary axis.
@@ -1027,7 +1486,7 @@ impl<T: Float> Tensor<T> {
         let outer: usize = dims[..axis].iter().product();
         let axis_size = dims[axis];
         let inner: usize = dims[axis + 1..].iter().product();
-        let mut data = self.data.clone();
+        let mut data = self.data().to_vec();
 
         for o in 0..outer {
             for i in 0..inner {
@@ -1042,16 +1501,16 @@ impl<T: Float> Tensor<T> {
                 for a in 0..axis_size {
                     let idx = o * axis_size * inner + a * inner + i;
                     data[idx] = (data[idx] - max_val).exp();
-                    sum = sum + data[idx];
+                    sum += data[idx];
                 }
                 // Normalize
                 for a in 0..axis_size {
                     let idx = o * axis_size * inner + a * inner + i;
-                    data[idx] = data[idx] / sum;
+                    data[idx] /= sum;
                 }
             }
         }
-        Ok(Tensor { data, shape: self.shape.clone() })
+        Ok(Tensor::from_parts(data, self.shape.clone()))
     }
 
     /// Element-wise negation.
@@ -1085,16 +1544,16 @@ impl<T: Float> Tensor<T> {
                 expected: self.shape_vec(), got: mask.shape_vec(),
             });
         }
-        let data: Vec<T> = mask.data.iter().zip(self.data.iter().zip(other.data.iter()))
+        let data: Vec<T> = mask.data().iter().zip(self.data().iter().zip(other.data().iter()))
             .map(|(&m, (&s, &o))| if m > T::ZERO { s } else { o })
             .collect();
-        Ok(Tensor { data, shape: self.shape.clone() })
+        Ok(Tensor::from_parts(data, self.shape.clone()))
     }
 
     /// Top-k values and indices along the last axis of a 1D/2D tensor.
     pub fn topk(&self, k: usize) -> TensorResult<(Tensor<T>, Vec<Vec<usize>>)> {
         if self.ndim() == 1 {
-            let mut indexed: Vec<(T, usize)> = self.data.iter().copied()
+            let mut indexed: Vec<(T, usize)> = self.data().iter().copied()
                 .enumerate().map(|(i, v)| (v, i)).collect();
             indexed.sort_by(|a, b| b.0.to_f64().partial_cmp(&a.0.to_f64()).unwrap());
             let k = k.min(indexed.len());
@@ -1109,7 +1568,7 @@ impl<T: Float> Tensor<T> {
             let mut all_indices = Vec::with_capacity(rows);
             for r in 0..rows {
                 let mut indexed: Vec<(T, usize)> = (0..cols)
-                    .map(|c| (self.data[r * cols + c], c)).collect();
+                    .map(|c| (self.data()[r * cols + c], c)).collect();
                 indexed.sort_by(|a, b| b.0.to_f64().partial_cmp(&a.0.to_f64()).unwrap());
                 all_values.extend(indexed[..k].iter().map(|&(v, _)| v));
                 all_indices.push(indexed[..k].iter().map(|&(_, i)| i).collect());
@@ -1123,20 +1582,20 @@ impl<T: Float> Tensor<T> {
     /// L2 (Frobenius) norm of the entire tensor.
     pub fn norm(&self) -> T {
         let mut sum = T::ZERO;
-        for &v in &self.data {
-            sum = sum + v * v;
+        for &v in self.data() {
+            sum += v * v;
         }
         sum.sqrt()
     }
 
     /// L1 norm.
     pub fn norm_l1(&self) -> T {
-        self.data.iter().map(|&v| v.abs()).sum()
+        self.data().iter().map(|&v| v.abs()).sum()
     }
 
     /// Clip (clamp) values in-place.
     pub fn clip_mut(&mut self, min: T, max: T) {
-        for v in self.data.iter_mut() {
+        for v in self.data_mut().iter_mut() {
             *v = v.max(min).min(max);
         }
     }
@@ -1149,7 +1608,7 @@ impl<T: Float> Tensor<T> {
         let n = self.numel();
         let mut data = vec![T::ZERO; n * n_classes];
         for i in 0..n {
-            let cls = self.data[i].to_f64().round() as usize;
+            let cls = self.data()[i].to_f64().round() as usize;
             if cls < n_classes {
                 data[i * n_classes + cls] = T::ONE;
             }
@@ -1167,7 +1626,7 @@ impl<T: Float> Tensor<T> {
         let mut data = Vec::with_capacity(m * n);
         for i in 0..m {
             for j in 0..n {
-                data.push(self.data[i] * other.data[j]);
+                data.push(self.data()[i] * other.data()[j]);
             }
         }
         Tensor::new(data, vec![m, n])
@@ -1175,66 +1634,26 @@ impl<T: Float> Tensor<T> {
 
     /// Product of all elements.
     pub fn prod_all(&self) -> T {
-        self.data.iter().copied().fold(T::ONE, |acc, x| acc * x)
+        self.data().iter().copied().fold(T::ONE, |acc, x| acc * x)
     }
 
     /// Max along axis, returning tensor with that dimension removed.
     pub fn max_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
-        let dims = self.shape.dims();
-        if axis >= dims.len() {
-            return Err(TensorError::InvalidAxis { axis, ndim: self.ndim() });
-        }
-        let outer: usize = dims[..axis].iter().product();
-        let axis_size = dims[axis];
-        let inner: usize = dims[axis + 1..].iter().product();
-        let mut result = vec![T::NEG_INFINITY; outer * inner];
-        for o in 0..outer {
-            for a in 0..axis_size {
-                for i in 0..inner {
-                    let src = o * axis_size * inner + a * inner + i;
-                    let dst = o * inner + i;
-                    if self.data[src] > result[dst] {
-                        result[dst] = self.data[src];
-                    }
-                }
-            }
-        }
-        let mut new_dims: Vec<usize> = dims.to_vec();
-        new_dims.remove(axis);
-        if new_dims.is_empty() { new_dims.push(1); }
-        Tensor::new(result, new_dims)
+        self.reduce_axis(axis, |lane| {
+            lane.fold(T::NEG_INFINITY, |m, v| if v > m { v } else { m })
+        })
     }
 
     /// Min along axis.
     pub fn min_axis(&self, axis: usize) -> TensorResult<Tensor<T>> {
-        let dims = self.shape.dims();
-        if axis >= dims.len() {
-            return Err(TensorError::InvalidAxis { axis, ndim: self.ndim() });
-        }
-        let outer: usize = dims[..axis].iter().product();
-        let axis_size = dims[axis];
-        let inner: usize = dims[axis + 1..].iter().product();
-        let mut result = vec![T::INFINITY; outer * inner];
-        for o in 0..outer {
-            for a in 0..axis_size {
-                for i in 0..inner {
-                    let src = o * axis_size * inner + a * inner + i;
-                    let dst = o * inner + i;
-                    if self.data[src] < result[dst] {
-                        result[dst] = self.data[src];
-                    }
-                }
-            }
-        }
-        let mut new_dims: Vec<usize> = dims.to_vec();
-        new_dims.remove(axis);
-        if new_dims.is_empty() { new_dims.push(1); }
-        Tensor::new(result, new_dims)
+        self.reduce_axis(axis, |lane| {
+            lane.fold(T::INFINITY, |m, v| if v < m { v } else { m })
+        })
     }
 
     /// Check if any element is NaN.
     pub fn has_nan(&self) -> bool {
-        self.data.iter().any(|v| v.to_f64().is_nan())
+        self.data().iter().any(|v| v.to_f64().is_nan())
     }
 
     /// Replace NaN with a given value.
@@ -1250,7 +1669,7 @@ impl<T: Float> Tensor<T> {
 
     /// Cumulative sum along axis 0 for 1D/2D tensors.
     pub fn cumsum(&self) -> Tensor<T> {
-        let mut data = self.data.clone();
+        let mut data = self.data().to_vec();
         if self.ndim() == 1 {
             for i in 1..data.len() {
                 data[i] = data[i] + data[i - 1];
@@ -1263,7 +1682,70 @@ impl<T: Float> Tensor<T> {
                 }
             }
         }
-        Tensor { data, shape: self.shape.clone() }
+        Tensor::from_parts(data, self.shape.clone())
+    }
+}
+
+// ─── Indexing Helpers ───────────────────────────────────────────────────────
+
+/// Interpret a stored index value, checking it against the axis size.
+fn to_index<T: Float>(v: T, axis: usize, size: usize) -> TensorResult<usize> {
+    let f = v.to_f64().round();
+    if f < 0.0 || f as usize >= size {
+        return Err(TensorError::IndexOutOfBounds {
+            index: f.max(0.0) as usize,
+            axis,
+            size,
+        });
+    }
+    Ok(f as usize)
+}
+
+/// Offset in the target tensor for element `flat` of an index tensor with
+/// dims `idx_dims`, with its coordinate on `axis` replaced by `pos`.
+fn scatter_offset(flat: usize, idx_dims: &[usize], strides: &[usize], axis: usize, pos: usize) -> usize {
+    let mut rem = flat;
+    let mut offset = 0;
+    for d in (0..idx_dims.len()).rev() {
+        let coord = rem % idx_dims[d];
+        rem /= idx_dims[d];
+        offset += if d == axis { pos } else { coord } * strides[d];
+    }
+    offset
+}
+
+// ─── Sorting Helpers ────────────────────────────────────────────────────────
+
+/// Total order for sorting: NaN compares greater than every other value.
+fn cmp_total<T: Float>(a: T, b: T) -> std::cmp::Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+/// Sort order in either direction, keeping NaN after every other value.
+fn cmp_sort<T: Float>(a: T, b: T, descending: bool) -> std::cmp::Ordering {
+    match (descending, a.partial_cmp(&b)) {
+        (true, Some(order)) => order.reverse(),
+        _ => cmp_total(a, b),
+    }
+}
+
+fn argsort_slice<T: Float>(data: &[T], descending: bool) -> Vec<usize> {
+    let mut idx: Vec<usize> = (0..data.len()).collect();
+    idx.sort_by(|&a, &b| cmp_sort(data[a], data[b], descending));
+    idx
+}
+
+fn median_of<T: Float>(mut values: Vec<T>) -> T {
+    if values.is_empty() {
+        return T::ZERO;
+    }
+    values.sort_by(|a, b| cmp_total(*a, *b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        (values[mid - 1] + values[mid]) / T::TWO
     }
 }
 
@@ -1309,9 +1791,18 @@ where
     }
 }
 
+impl<T: Float> fmt::Debug for Tensor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tensor")
+            .field("data", &self.data())
+            .field("shape", &self.shape)
+            .finish()
+    }
+}
+
 impl<T: Float> PartialEq for Tensor<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.shape == other.shape && self.data == other.data
+        self.shape == other.shape && self.data() == other.data()
     }
 }
 
@@ -1323,11 +1814,11 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.is_scalar() {
-            return write!(f, "tensor({})", self.data[0]);
+            return write!(f, "tensor({})", self.data()[0]);
         }
         if self.ndim() == 1 {
             write!(f, "tensor([")?;
-            for (i, v) in self.data.iter().enumerate() {
+            for (i, v) in self.data().iter().enumerate() {
                 if i > 0 {
                     write!(f, ", ")?;
                 }
@@ -1349,7 +1840,7 @@ where
                     if j > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "{:.4}", self.data[i * cols + j])?;
+                    write!(f, "{:.4}", self.data()[i * cols + j])?;
                 }
                 if cols > 8 {
                     write!(f, ", ...")?;
@@ -1365,6 +1856,30 @@ where
     }
 }
 
+
+/// √(2/π), the inner scale of the tanh approximation to GELU.
+const GELU_TANH_SCALE: f64 = 0.7978845608028654;
+
+/// Error function (Numerical Recipes' Chebyshev fit of erfc).
+fn erf(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let erfc = t * poly.exp();
+    if x >= 0.0 { 1.0 - erfc } else { erfc - 1.0 }
+}
+
+/// ln(1 + exp(βx)) / β as max(βx, 0) + ln(1 + exp(-|βx|)), over β.
+fn softplus<T: Float>(x: T, beta: T) -> T {
+    let bx = beta * x;
+    (bx.max(T::ZERO) + (T::ONE + (-bx.abs()).exp()).ln()) / beta
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1502,4 +2017,193 @@ mod tests {
         assert!(min >= 0.0);
         assert!(max < 1.0);
     }
+
+    #[test]
+    fn test_argmin_argsort_axis() {
+        let a: Tensor<f64> = Tensor::new(vec![3.0, 1.0, 2.0, 0.0, 5.0, 4.0], vec![2, 3]).unwrap();
+        assert_eq!(a.argmin_axis(1).unwrap().data(), &[1.0, 0.0]);
+        assert_eq!(a.argmin_axis(0).unwrap().data(), &[1.0, 0.0, 0.0]);
+        assert_eq!(a.argsort_axis(1, false).unwrap().data(), &[1.0, 2.0, 0.0, 0.0, 2.0, 1.0]);
+        assert_eq!(a.sort_axis(1, true).unwrap().data(), &[3.0, 2.0, 1.0, 5.0, 4.0, 0.0]);
+        assert_eq!(a.sort_axis(0, false).unwrap().data(), &[0.0, 1.0, 2.0, 3.0, 5.0, 4.0]);
+        assert_eq!(a.argsort(false), vec![3, 1, 2, 0, 5, 4]);
+        assert_eq!(a.argsort(true), vec![4, 5, 0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_unique_and_median() {
+        let a: Tensor<f64> = Tensor::from_slice(&[2.0, 1.0, 2.0, 3.0, 1.0, 2.0]);
+        let (vals, counts) = a.unique_counts();
+        assert_eq!(vals.data(), &[1.0, 2.0, 3.0]);
+        assert_eq!(counts, vec![2, 3, 1]);
+        assert_eq!(a.median_all().unwrap(), 2.0);
+
+        let m: Tensor<f64> = Tensor::new(vec![1.0, 9.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        assert_eq!(m.median_axis(0).unwrap().data(), &[2.0, 6.5]);
+    }
+
+    #[test]
+    fn test_sort_puts_nan_last() {
+        let a: Tensor<f64> = Tensor::from_slice(&[f64::NAN, 1.0, -1.0]);
+        let s = a.sort_axis(0, false).unwrap();
+        assert_eq!(&s.data()[..2], &[-1.0, 1.0]);
+        assert!(s.data()[2].is_nan());
+        let s = a.sort_axis(0, true).unwrap();
+        assert_eq!(&s.data()[..2], &[1.0, -1.0]);
+        assert!(s.data()[2].is_nan());
+
+        let b: Tensor<f64> = Tensor::new(vec![2.0, f64::NAN, 5.0, f64::NAN, 3.0, 0.0], vec![2, 3]).unwrap();
+        let s = b.sort_axis(1, true).unwrap();
+        assert_eq!(&s.data()[..2], &[5.0, 2.0]);
+        assert!(s.data()[2].is_nan());
+        assert_eq!(&s.data()[3..5], &[3.0, 0.0]);
+        assert!(s.data()[5].is_nan());
+        assert_eq!(b.argsort_axis(1, true).unwrap().data(), &[2.0, 0.0, 1.0, 1.0, 2.0, 0.0]);
+        assert_eq!(b.argsort(true), vec![2, 4, 0, 5, 1, 3]);
+        assert_eq!(b.argsort(false), vec![5, 0, 4, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_unique_collapses_nan() {
+        let a: Tensor<f64> = Tensor::from_slice(&[f64::NAN, 1.0, f64::NAN, -1.0, 1.0, f64::NAN]);
+        let (vals, counts) = a.unique_counts();
+        assert_eq!(&vals.data()[..2], &[-1.0, 1.0]);
+        assert!(vals.data()[2].is_nan());
+        assert_eq!(vals.numel(), 3);
+        assert_eq!(counts, vec![1, 2, 3]);
+        assert_eq!(a.unique().numel(), 3);
+        let all_nan: Tensor<f32> = Tensor::from_slice(&[f32::NAN, f32::NAN]);
+        assert_eq!(all_nan.unique_counts().1, vec![2]);
+    }
+
+    #[test]
+    fn test_pad() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let p = a.pad(&[(1, 0), (0, 2)], 0.0).unwrap();
+        assert_eq!(p.shape_vec(), vec![3, 4]);
+        assert_eq!(
+            p.data(),
+            &[0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 0.0, 0.0, 3.0, 4.0, 0.0, 0.0]
+        );
+        assert!(a.pad(&[(1, 1)], 0.0).is_err());
+    }
+
+    #[test]
+    fn test_tile_flip_roll() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let t = a.tile(&[2, 1]);
+        assert_eq!(t.shape_vec(), vec![4, 2]);
+        assert_eq!(t.data(), &[1.0, 2.0, 3.0, 4.0, 1.0, 2.0, 3.0, 4.0]);
+        let v: Tensor<f64> = Tensor::from_slice(&[1.0, 2.0]);
+        assert_eq!(v.tile(&[2, 2]).data(), &[1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 1.0, 2.0]);
+
+        assert_eq!(a.flip(1).unwrap().data(), &[2.0, 1.0, 4.0, 3.0]);
+        assert_eq!(a.flip(0).unwrap().data(), &[3.0, 4.0, 1.0, 2.0]);
+
+        let r: Tensor<f64> = Tensor::from_slice(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(r.roll(0, 1).unwrap().data(), &[4.0, 1.0, 2.0, 3.0]);
+        assert_eq!(r.roll(0, -1).unwrap().data(), &[2.0, 3.0, 4.0, 1.0]);
+        assert!(r.roll(1, 1).is_err());
+    }
+
+    #[test]
+    fn test_inplace_ops() {
+        let mut a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let b: Tensor<f64> = Tensor::new(vec![1.0, 1.0, 2.0, 2.0], vec![2, 2]).unwrap();
+        a.add_(&b).unwrap();
+        assert_eq!(a.data(), &[2.0, 3.0, 5.0, 6.0]);
+        a.mul_scalar_(2.0);
+        assert_eq!(a.data(), &[4.0, 6.0, 10.0, 12.0]);
+
+        // Broadcast a row into every row of `a`.
+        let row: Tensor<f64> = Tensor::new(vec![2.0, 3.0], vec![1, 2]).unwrap();
+        a.div_(&row).unwrap();
+        assert_eq!(a.data(), &[2.0, 2.0, 5.0, 4.0]);
+        a.add_scaled_(&b, -2.0).unwrap();
+        assert_eq!(a.data(), &[0.0, 0.0, 1.0, 0.0]);
+
+        // The result shape must stay that of `self`.
+        let mut small = row.clone();
+        assert!(small.add_(&b).is_err());
+    }
+
+    #[test]
+    fn test_inplace_op_does_not_touch_shared_buffer() {
+        let a: Tensor<f64> = Tensor::from_slice(&[1.0, 2.0]);
+        let mut b = a.clone();
+        b.sub_scalar_(1.0);
+        assert_eq!(a.data(), &[1.0, 2.0]);
+        assert_eq!(b.data(), &[0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_index_select() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![3, 2]).unwrap();
+        let rows = a.index_select(0, &[2, 0, 2]).unwrap();
+        assert_eq!(rows.shape_vec(), vec![3, 2]);
+        assert_eq!(rows.data(), &[5.0, 6.0, 1.0, 2.0, 5.0, 6.0]);
+        let cols = a.index_select(1, &[1]).unwrap();
+        assert_eq!(cols.data(), &[2.0, 4.0, 6.0]);
+        assert!(a.index_select(0, &[3]).is_err());
+    }
+
+    #[test]
+    fn test_masked_select() {
+        let a: Tensor<f64> = Tensor::from_slice(&[1.0, 2.0, 3.0, 4.0]);
+        let mask = a.gt(&Tensor::scalar(2.0)).unwrap();
+        assert_eq!(a.masked_select(&mask).unwrap().data(), &[3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_gather_scatter_add() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let idx: Tensor<f64> = Tensor::new(vec![1.0, 0.0, 0.0, 0.0], vec![2, 2]).unwrap();
+        let g = a.gather(1, &idx).unwrap();
+        assert_eq!(g.data(), &[2.0, 1.0, 3.0, 3.0]);
+
+        let zeros: Tensor<f64> = Tensor::zeros(vec![2, 2]);
+        let s = zeros.scatter_add(1, &idx, &Tensor::ones(vec![2, 2])).unwrap();
+        assert_eq!(s.data(), &[1.0, 1.0, 2.0, 0.0]);
+
+        // Column gather along axis 0 with a narrower index.
+        let col_idx: Tensor<f64> = Tensor::new(vec![1.0], vec![1, 1]).unwrap();
+        assert_eq!(a.gather(0, &col_idx).unwrap().data(), &[3.0]);
+        assert!(a.gather(1, &Tensor::new(vec![2.0], vec![1, 1]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_reshape_and_slice_share_buffer() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![3, 2]).unwrap();
+        let r = a.reshape(vec![2, 3]).unwrap();
+        let s = a.slice_rows(1, 3).unwrap();
+        assert!(r.shares_storage(&a));
+        assert!(s.shares_storage(&a));
+        assert_eq!(s.data(), &[3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(a.row(2).unwrap().data(), &[5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_copy_on_write() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let mut s = a.slice_rows(1, 2).unwrap();
+        s.set(&[0, 0], 10.0).unwrap();
+        assert!(!s.shares_storage(&a));
+        assert_eq!(s.data(), &[10.0, 4.0]);
+        assert_eq!(a.data(), &[1.0, 2.0, 3.0, 4.0]);
+
+        let mut b = a.flatten();
+        b.apply_mut(|x| x * 2.0);
+        assert_eq!(a.data(), &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(b.data(), &[2.0, 4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn test_serde_roundtrip_of_slice() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let s = a.slice_rows(1, 2).unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, r#"{"data":[3.0,4.0],"shape":{"dims":[1,2]}}"#);
+        let back: Tensor<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, s);
+    }
 }
@@ -0,0 +1,336 @@
+use crate::dtype::Float;
+use crate::error::{TensorError, TensorResult};
+use crate::shape::Shape;
+use crate::tensor::Tensor;
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Read-only strided view into a tensor's buffer.
+///
+/// Transposes, permutations and slices along any axis only rewrite the
+/// offset/strides, so they never copy. Call `contiguous()` to get a
+/// `Tensor` back — this is free when the view is already row-major.
+#[derive(Clone)]
+pub struct TensorView<T: Float> {
+    storage: Arc<Vec<T>>,
+    offset: usize,
+    dims: Vec<usize>,
+    strides: Vec<usize>,
+}
+
+impl<T: Float> TensorView<T> {
+    pub(crate) fn new(
+        storage: Arc<Vec<T>>,
+        offset: usize,
+        dims: Vec<usize>,
+        strides: Vec<usize>,
+    ) -> Self {
+        TensorView {
+            storage,
+            offset,
+            dims,
+            strides,
+        }
+    }
+
+    // ─── Accessors ──────────────────────────────────────────────────────────
+
+    pub fn shape(&self) -> Shape {
+        Shape::from_slice(&self.dims)
+    }
+
+    pub fn dims(&self) -> &[usize] {
+        &self.dims
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.dims.len()
+    }
+
+    pub fn numel(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    /// True if the view is laid out in row-major order without gaps.
+    pub fn is_contiguous(&self) -> bool {
+        let mut expected = 1usize;
+        for d in (0..self.ndim()).rev() {
+            if self.dims[d] != 1 && self.strides[d] != expected {
+                return false;
+            }
+            expected *= self.dims[d];
+        }
+        true
+    }
+
+    /// Element at a multi-dimensional index.
+    pub fn get(&self, indices: &[usize]) -> TensorResult<T> {
+        if indices.len() != self.ndim() {
+            return Err(TensorError::DimensionMismatch(format!(
+                "Expected {} indices, got {}",
+                self.ndim(),
+                indices.len()
+            )));
+        }
+        let mut pos = self.offset;
+        for (axis, &idx) in indices.iter().enumerate() {
+            if idx >= self.dims[axis] {
+                return Err(TensorError::IndexOutOfBounds {
+                    index: idx,
+                    axis,
+                    size: self.dims[axis],
+                });
+            }
+            pos += idx * self.strides[axis];
+        }
+        Ok(self.storage[pos])
+    }
+
+    /// Iterate over elements in logical row-major order.
+    pub fn iter(&self) -> StridedIter<'_, T> {
+        StridedIter {
+            view: self,
+            index: vec![0; self.ndim()],
+            pos: self.offset,
+            remaining: self.numel(),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+
+    // ─── Zero-copy Layout Changes ───────────────────────────────────────────
+
+    /// Swap two axes.
+    pub fn swap_axes(&self, a: usize, b: usize) -> TensorResult<TensorView<T>> {
+        let ndim = self.ndim();
+        if a >= ndim || b >= ndim {
+            return Err(TensorError::InvalidAxis {
+                axis: a.max(b),
+                ndim,
+            });
+        }
+        let mut v = self.clone();
+        v.dims.swap(a, b);
+        v.strides.swap(a, b);
+        Ok(v)
+    }
+
+    /// Transpose the last two dimensions.
+    pub fn t(&self) -> TensorResult<TensorView<T>> {
+        if self.ndim() < 2 {
+            return Err(TensorError::InvalidOperation(
+                "Cannot transpose tensor with fewer than 2 dimensions".to_string(),
+            ));
+        }
+        let n = self.ndim();
+        self.swap_axes(n - 2, n - 1)
+    }
+
+    /// Reorder axes: output axis `i` is input axis `axes[i]`.
+    pub fn permute(&self, axes: &[usize]) -> TensorResult<TensorView<T>> {
+        let ndim = self.ndim();
+        if axes.len() != ndim {
+            return Err(TensorError::DimensionMismatch(format!(
+                "permute expects {} axes, got {}",
+                ndim,
+                axes.len()
+            )));
+        }
+        let mut seen = vec![false; ndim];
+        for &a in axes {
+            if a >= ndim || seen[a] {
+                return Err(TensorError::InvalidOperation(format!(
+                    "permute: {:?} is not a permutation of 0..{}",
+                    axes, ndim
+                )));
+            }
+            seen[a] = true;
+        }
+        Ok(TensorView {
+            storage: Arc::clone(&self.storage),
+            offset: self.offset,
+            dims: axes.iter().map(|&a| self.dims[a]).collect(),
+            strides: axes.iter().map(|&a| self.strides[a]).collect(),
+        })
+    }
+
+    /// Restrict `axis` to `start..end`.
+    pub fn slice_axis(&self, axis: usize, start: usize, end: usize) -> TensorResult<TensorView<T>> {
+        let ndim = self.ndim();
+        if axis >= ndim {
+            return Err(TensorError::InvalidAxis { axis, ndim });
+        }
+        let size = self.dims[axis];
+        if start > end || end > size {
+            return Err(TensorError::IndexOutOfBounds {
+                index: end,
+                axis,
+                size,
+            });
+        }
+        let mut v = self.clone();
+        v.offset += start * self.strides[axis];
+        v.dims[axis] = end - start;
+        Ok(v)
+    }
+
+    /// Pick a single index along `axis`, removing that dimension.
+    pub fn select(&self, axis: usize, index: usize) -> TensorResult<TensorView<T>> {
+        let mut v = self.slice_axis(axis, index, index + 1)?;
+        v.dims.remove(axis);
+        v.strides.remove(axis);
+        Ok(v)
+    }
+
+    /// Reshape without copying. Only valid for contiguous views.
+    pub fn reshape(&self, new_shape: Vec<usize>) -> TensorResult<TensorView<T>> {
+        let ns = Shape::new(new_shape);
+        if ns.numel() != self.numel() {
+            return Err(TensorError::ShapeMismatch {
+                expected: ns.to_vec(),
+                got: self.dims.clone(),
+            });
+        }
+        if !self.is_contiguous() {
+            return Err(TensorError::InvalidOperation(
+                "reshape of a non-contiguous view; call contiguous() first".to_string(),
+            ));
+        }
+        Ok(TensorView {
+            storage: Arc::clone(&self.storage),
+            offset: self.offset,
+            strides: ns.strides(),
+            dims: ns.to_vec(),
+        })
+    }
+
+    // ─── Materialization ────────────────────────────────────────────────────
+
+    /// Convert back to a `Tensor`, sharing the buffer when the layout allows
+    /// and copying otherwise.
+    pub fn contiguous(&self) -> Tensor<T> {
+        let shape = self.shape();
+        if self.is_contiguous() {
+            Tensor::from_storage(Arc::clone(&self.storage), self.offset, shape)
+        } else {
+            Tensor::new(self.to_vec(), shape.to_vec()).expect("view length matches its shape")
+        }
+    }
+}
+
+impl<T: Float> fmt::Debug for TensorView<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TensorView")
+            .field("dims", &self.dims)
+            .field("strides", &self.strides)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+/// Row-major iterator over a strided view.
+pub struct StridedIter<'a, T: Float> {
+    view: &'a TensorView<T>,
+    index: Vec<usize>,
+    pos: usize,
+    remaining: usize,
+}
+
+impl<T: Float> Iterator for StridedIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let value = self.view.storage[self.pos];
+        self.remaining -= 1;
+
+        // Advance the multi-index like an odometer, last axis fastest.
+        for d in (0..self.index.len()).rev() {
+            self.index[d] += 1;
+            self.pos += self.view.strides[d];
+            if self.index[d] < self.view.dims[d] {
+                break;
+            }
+            self.pos -= self.index[d] * self.view.strides[d];
+            self.index[d] = 0;
+        }
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Float> ExactSizeIterator for StridedIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tensor<f64> {
+        Tensor::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2, 3]).unwrap()
+    }
+
+    #[test]
+    fn test_transpose_view_zero_copy() {
+        let a = sample();
+        let v = a.t_view().unwrap();
+        assert_eq!(v.dims(), &[3, 2]);
+        assert!(!v.is_contiguous());
+        assert_eq!(v.get(&[2, 1]).unwrap(), 6.0);
+        assert_eq!(v.to_vec(), a.t().unwrap().data().to_vec());
+    }
+
+    #[test]
+    fn test_slice_and_select() {
+        let a = sample();
+        let cols = a.view().slice_axis(1, 1, 3).unwrap();
+        assert_eq!(cols.to_vec(), vec![2.0, 3.0, 5.0, 6.0]);
+        let col = a.view().select(1, 2).unwrap();
+        assert_eq!(col.to_vec(), vec![3.0, 6.0]);
+    }
+
+    #[test]
+    fn test_permute() {
+        let a: Tensor<f64> = Tensor::new((0..24).map(|x| x as f64).collect(), vec![2, 3, 4]).unwrap();
+        let p = a.view().permute(&[2, 0, 1]).unwrap();
+        assert_eq!(p.dims(), &[4, 2, 3]);
+        assert_eq!(p.get(&[3, 1, 2]).unwrap(), a.get(&[1, 2, 3]).unwrap());
+        assert!(a.view().permute(&[0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_contiguous_shares_buffer() {
+        let a = sample();
+        let row = a.view().select(0, 1).unwrap();
+        assert!(row.is_contiguous());
+        let t = row.contiguous();
+        assert!(t.shares_storage(&a));
+        assert_eq!(t.data(), &[4.0, 5.0, 6.0]);
+
+        let copied = a.t_view().unwrap().contiguous();
+        assert!(!copied.shares_storage(&a));
+        assert_eq!(copied.shape_vec(), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_reshape_requires_contiguous() {
+        let a = sample();
+        assert!(a.view().reshape(vec![3, 2]).is_ok());
+        assert!(a.t_view().unwrap().reshape(vec![6]).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Per-row training weights for classifiers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::dtype::Float;
+use crate::error::{TensorError, TensorResult};
+use crate::tensor::Tensor;
+
+/// Reweighting of classes when fitting a classifier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClassWeight {
+    /// Weight each class by `n_samples / (n_classes * class_count)`, so every
+    /// class contributes equally to the loss.
+    Balanced,
+    /// Explicit `(label, weight)` pairs; unlisted labels get weight 1.
+    Custom(Vec<(f64, f64)>),
+}
+
+/// Combine `class_weight` and `sample_weight` into one weight per row of
+/// `y` (their product). Both `None` gives all ones.
+pub fn sample_weights<T: Float>(
+    y: &Tensor<T>,
+    class_weight: Option<&ClassWeight>,
+    sample_weight: Option<&Tensor<T>>,
+) -> TensorResult<Vec<T>> {
+    let n = y.numel();
+    let mut weights = match sample_weight {
+        Some(w) => {
+            if w.numel() != n {
+                return Err(TensorError::ShapeMismatch {
+                    expected: vec![n],
+                    got: w.shape_vec(),
+                });
+            }
+            if w.data().iter().any(|&v| v < T::ZERO || !v.to_f64().is_finite()) {
+                return Err(TensorError::InvalidOperation(
+                    "sample weights must be finite and non-negative".into(),
+                ));
+            }
+            w.data().to_vec()
+        }
+        None => vec![T::ONE; n],
+    };
+
+    let labels = y.data();
+    match class_weight {
+        None => {}
+        Some(ClassWeight::Balanced) => {
+            let mut classes: Vec<f64> = labels.iter().map(|v| v.to_f64()).collect();
+            classes.sort_by(|a, b| a.total_cmp(b));
+            classes.dedup();
+            let counts: Vec<usize> = classes
+                .iter()
+                .map(|&c| labels.iter().filter(|v| v.to_f64() == c).count())
+                .collect();
+            for (w, label) in weights.iter_mut().zip(labels) {
+                let k = classes.iter().position(|&c| c == label.to_f64()).unwrap_or(0);
+                *w *= T::from_f64(n as f64 / (classes.len() * counts[k]) as f64);
+            }
+        }
+        Some(ClassWeight::Custom(pairs)) => {
+            for (w, label) in weights.iter_mut().zip(labels) {
+                if let Some(&(_, cw)) = pairs.iter().find(|(c, _)| *c == label.to_f64()) {
+                    *w *= T::from_f64(cw);
+                }
+            }
+        }
+    }
+    Ok(weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_weights() {
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(sample_weights(&y, None, None).unwrap(), vec![1.0; 4]);
+
+        let balanced = sample_weights(&y, Some(&ClassWeight::Balanced), None).unwrap();
+        assert_eq!(balanced, vec![4.0 / 6.0, 4.0 / 6.0, 4.0 / 6.0, 2.0]);
+
+        let sw = Tensor::from_slice(&[1.0, 2.0, 0.0, 1.0]);
+        let custom = ClassWeight::Custom(vec![(1.0, 3.0)]);
+        assert_eq!(sample_weights(&y, Some(&custom), Some(&sw)).unwrap(), vec![1.0, 2.0, 0.0, 3.0]);
+
+        assert!(sample_weights(&y, None, Some(&Tensor::from_slice(&[1.0]))).is_err());
+        assert!(sample_weights(&y, None, Some(&Tensor::from_slice(&[1.0, -1.0, 1.0, 1.0]))).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! A minimal Arrow-style columnar `RecordBatch` and its conversion to tensors.
+//!
+//! Mirrors the parts of Arrow's data model that tabular ML input needs:
+//! named, typed, nullable columns of equal length. The Parquet reader yields
+//! one batch per row group.
+
+use oxidize_ml_core::{Float, Tensor};
+use std::error::Error;
+use std::fmt;
+
+/// Logical type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Boolean,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Utf8,
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataType::Boolean => "bool",
+            DataType::Int32 => "int32",
+            DataType::Int64 => "int64",
+            DataType::Float32 => "float32",
+            DataType::Float64 => "float64",
+            DataType::Utf8 => "utf8",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Name, type and nullability of one column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub data_type: DataType,
+    pub nullable: bool,
+}
+
+impl Field {
+    pub fn new(name: &str, data_type: DataType, nullable: bool) -> Self {
+        Field {
+            name: name.to_string(),
+            data_type,
+            nullable,
+        }
+    }
+}
+
+/// A column of values; `None` is null.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Array {
+    Boolean(Vec<Option<bool>>),
+    Int32(Vec<Option<i32>>),
+    Int64(Vec<Option<i64>>),
+    Float32(Vec<Option<f32>>),
+    Float64(Vec<Option<f64>>),
+    Utf8(Vec<Option<String>>),
+}
+
+impl Array {
+    pub fn len(&self) -> usize {
+        match self {
+            Array::Boolean(v) => v.len(),
+            Array::Int32(v) => v.len(),
+            Array::Int64(v) => v.len(),
+            Array::Float32(v) => v.len(),
+            Array::Float64(v) => v.len(),
+            Array::Utf8(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Array::Boolean(_) => DataType::Boolean,
+            Array::Int32(_) => DataType::Int32,
+            Array::Int64(_) => DataType::Int64,
+            Array::Float32(_) => DataType::Float32,
+            Array::Float64(_) => DataType::Float64,
+            Array::Utf8(_) => DataType::Utf8,
+        }
+    }
+
+    pub fn null_count(&self) -> usize {
+        fn nulls<V>(v: &[Option<V>]) -> usize {
+            v.iter().filter(|x| x.is_none()).count()
+        }
+        match self {
+            Array::Boolean(v) => nulls(v),
+            Array::Int32(v) => nulls(v),
+            Array::Int64(v) => nulls(v),
+            Array::Float32(v) => nulls(v),
+            Array::Float64(v) => nulls(v),
+            Array::Utf8(v) => nulls(v),
+        }
+    }
+
+    /// The column as floats: booleans become 0/1, nulls become NaN.
+    /// `None` for non-numeric columns.
+    pub fn to_f64_vec(&self) -> Option<Vec<f64>> {
+        fn map<V: Copy>(v: &[Option<V>], f: impl Fn(V) -> f64) -> Vec<f64> {
+            v.iter().map(|x| x.map_or(f64::NAN, &f)).collect()
+        }
+        Some(match self {
+            Array::Boolean(v) => map(v, |b| if b { 1.0 } else { 0.0 }),
+            Array::Int32(v) => map(v, |x| x as f64),
+            Array::Int64(v) => map(v, |x| x as f64),
+            Array::Float32(v) => map(v, |x| x as f64),
+            Array::Float64(v) => map(v, |x| x),
+            Array::Utf8(_) => return None,
+        })
+    }
+}
+
+/// Equal-length columns with a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordBatch {
+    schema: Vec<Field>,
+    columns: Vec<Array>,
+}
+
+impl RecordBatch {
+    /// Checks that there is one column per field, of the field's type and
+    /// all of the same length.
+    pub fn new(schema: Vec<Field>, columns: Vec<Array>) -> Result<Self, Box<dyn Error>> {
+        if schema.len() != columns.len() {
+            return Err(format!("{} fields but {} columns", schema.len(), columns.len()).into());
+        }
+        for (field, col) in schema.iter().zip(&columns) {
+            if field.data_type != col.data_type() {
+                return Err(format!(
+                    "column '{}' is declared {} but holds {}",
+                    field.name,
+                    field.data_type,
+                    col.data_type()
+                )
+                .into());
+            }
+            if col.len() != columns[0].len() {
+                return Err(format!("column '{}' has {} rows, expected {}", field.name, col.len(), columns[0].len()).into());
+            }
+        }
+        Ok(RecordBatch { schema, columns })
+    }
+
+    pub fn schema(&self) -> &[Field] {
+        &self.schema
+    }
+
+    pub fn columns(&self) -> &[Array] {
+        &self.columns
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.columns.first().map_or(0, |c| c.len())
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn column(&self, i: usize) -> &Array {
+        &self.columns[i]
+    }
+
+    pub fn column_by_name(&self, name: &str) -> Option<&Array> {
+        self.schema.iter().position(|f| f.name == name).map(|i| &self.columns[i])
+    }
+
+    /// A batch with only the named columns, in the given order.
+    pub fn project(&self, names: &[&str]) -> Result<RecordBatch, Box<dyn Error>> {
+        let mut schema = Vec::with_capacity(names.len());
+        let mut columns = Vec::with_capacity(names.len());
+        for name in names {
+            let i = self
+                .schema
+                .iter()
+                .position(|f| f.name == *name)
+                .ok_or_else(|| format!("no column named '{}'", name))?;
+            schema.push(self.schema[i].clone());
+            columns.push(self.columns[i].clone());
+        }
+        RecordBatch::new(schema, columns)
+    }
+}
+
+/// Convert `batches` to a `[rows, columns]` tensor, stacking batches
+/// vertically. `columns` selects and orders columns by name (`None` keeps
+/// all). Integers and floats are cast to `T`, booleans become 0/1 and nulls
+/// become NaN; string columns are an error.
+pub fn record_batches_to_tensor<T: Float>(
+    batches: &[RecordBatch],
+    columns: Option<&[&str]>,
+) -> Result<Tensor<T>, Box<dyn Error>> {
+    let names: Vec<&str> = match columns {
+        Some(c) => c.to_vec(),
+        None => match batches.first() {
+            Some(b) => b.schema.iter().map(|f| f.name.as_str()).collect(),
+            None => Vec::new(),
+        },
+    };
+    let n_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    let n_cols = names.len();
+
+    let mut data = vec![T::ZERO; n_rows * n_cols];
+    let mut row0 = 0;
+    for batch in batches {
+        for (j, name) in names.iter().enumerate() {
+            let col = batch.column_by_name(name).ok_or_else(|| format!("no column named '{}'", name))?;
+            let values = col
+                .to_f64_vec()
+                .ok_or_else(|| format!("column '{}' has non-numeric type {}", name, col.data_type()))?;
+            for (i, v) in values.into_iter().enumerate() {
+                data[(row0 + i) * n_cols + j] = T::from_f64(v);
+            }
+        }
+        row0 += batch.num_rows();
+    }
+    Tensor::new(data, vec![n_rows, n_cols]).map_err(|e| format!("Failed to create tensor: {:?}", e).into())
+}
+
+/// Convert one batch to a `[rows, columns]` tensor; see `record_batches_to_tensor`.
+pub fn record_batch_to_tensor<T: Float>(
+    batch: &RecordBatch,
+    columns: Option<&[&str]>,
+) -> Result<Tensor<T>, Box<dyn Error>> {
+    record_batches_to_tensor(std::slice::from_ref(batch), columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        RecordBatch::new(
+            vec![
+                Field::new("a", DataType::Int64, false),
+                Field::new("b", DataType::Float32, true),
+                Field::new("c", DataType::Boolean, false),
+                Field::new("name", DataType::Utf8, false),
+            ],
+            vec![
+                Array::Int64(vec![Some(1), Some(2)]),
+                Array::Float32(vec![Some(0.5), None]),
+                Array::Boolean(vec![Some(true), Some(false)]),
+                Array::Utf8(vec![Some("x".into()), Some("y".into())]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_batch_to_tensor() {
+        let b = batch();
+        let t: Tensor<f64> = record_batch_to_tensor(&b, Some(&["c", "a", "b"])).unwrap();
+        assert_eq!(t.shape_vec(), vec![2, 3]);
+        assert_eq!(&t.data()[..5], &[1.0, 1.0, 0.5, 0.0, 2.0]);
+        assert!(t.data()[5].is_nan());
+
+        assert!(record_batch_to_tensor::<f64>(&b, None).is_err());
+        assert!(record_batch_to_tensor::<f64>(&b, Some(&["missing"])).is_err());
+
+        let two: Tensor<f32> = record_batches_to_tensor(&[b.clone(), b], Some(&["a"])).unwrap();
+        assert_eq!(two.data(), &[1.0, 2.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_batch_validation() {
+        let bad = RecordBatch::new(
+            vec![Field::new("a", DataType::Int32, false), Field::new("b", DataType::Int32, false)],
+            vec![Array::Int32(vec![Some(1)]), Array::Int32(vec![])],
+        );
+        assert!(bad.is_err());
+        assert_eq!(batch().project(&["name"]).unwrap().num_columns(), 1);
+    }
+}
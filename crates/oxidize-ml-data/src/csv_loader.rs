@@ -0,0 +1,208 @@
+use oxidize_ml_core::{RngHandle, Tensor};
+use rand::Rng;
+use std::error::Error;
+use std::fs::File;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+/// Features and label of one row.
+type Row = (Vec<f64>, f64);
+/// Features `[batch, n_features]` and labels `[batch]`.
+pub type Batch = (Tensor<f64>, Tensor<f64>);
+
+/// Streams `(features, labels)` batches from a CSV file with a header row,
+/// holding only the shuffle buffer and the current batch in memory.
+///
+/// Without a shuffle buffer rows come out in file order. With one, rows are
+/// drawn at random from a buffer that is refilled as it drains, so the
+/// randomness is local to windows of about `buffer_size` rows; files sorted
+/// by label need a buffer large compared to each label's run. Empty fields
+/// are read as NaN; any other non-numeric field is an error.
+pub struct CsvDataLoader {
+    path: String,
+    batch_size: usize,
+    label_column: usize,
+    buffer_size: usize,
+    rng: RngHandle,
+    headers: Vec<String>,
+    records: csv::StringRecordsIntoIter<File>,
+    buffer: Vec<Row>,
+    line: usize,
+}
+
+impl CsvDataLoader {
+    /// `label` names the target column; every other column is a feature.
+    pub fn new(path: &str, batch_size: usize, label: &str) -> Result<Self> {
+        assert!(batch_size > 0, "batch_size must be positive");
+        let mut reader = csv::Reader::from_path(path)?;
+        let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+        let label_column = headers
+            .iter()
+            .position(|h| h == label)
+            .ok_or_else(|| format!("no column named '{}'", label))?;
+        Ok(CsvDataLoader {
+            path: path.to_string(),
+            batch_size,
+            label_column,
+            buffer_size: 0,
+            rng: RngHandle::global(),
+            headers,
+            records: reader.into_records(),
+            buffer: Vec::new(),
+            line: 1,
+        })
+    }
+
+    /// Shuffle through a buffer of `rows` rows (0 or 1 disables shuffling).
+    pub fn with_shuffle_buffer(mut self, rows: usize) -> Self {
+        self.buffer_size = rows;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = RngHandle::new(Some(seed));
+        self
+    }
+
+    /// Names of the feature columns, in order.
+    pub fn feature_names(&self) -> Vec<&str> {
+        self.headers
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != self.label_column)
+            .map(|(_, h)| h.as_str())
+            .collect()
+    }
+
+    /// Rewind to the start of the file for another epoch. The shuffle order
+    /// differs between epochs.
+    pub fn reset(&mut self) -> Result<()> {
+        self.records = csv::Reader::from_path(&self.path)?.into_records();
+        self.buffer.clear();
+        self.line = 1;
+        Ok(())
+    }
+
+    /// The next row of the file, or `None` at the end.
+    fn read_row(&mut self) -> Result<Option<Row>> {
+        let record = match self.records.next() {
+            Some(r) => r?,
+            None => return Ok(None),
+        };
+        self.line += 1;
+        let mut features = Vec::with_capacity(record.len().saturating_sub(1));
+        let mut label = f64::NAN;
+        for (j, field) in record.iter().enumerate() {
+            let field = field.trim();
+            let v = if field.is_empty() {
+                f64::NAN
+            } else {
+                field.parse().map_err(|_| {
+                    format!("line {}, column '{}': '{}' is not a number", self.line, self.headers[j], field)
+                })?
+            };
+            if j == self.label_column {
+                label = v;
+            } else {
+                features.push(v);
+            }
+        }
+        Ok(Some((features, label)))
+    }
+
+    /// The next row in shuffled (or file) order.
+    fn next_row(&mut self) -> Result<Option<Row>> {
+        if self.buffer_size <= 1 {
+            return self.read_row();
+        }
+        while self.buffer.len() < self.buffer_size {
+            match self.read_row()? {
+                Some(row) => self.buffer.push(row),
+                None => break,
+            }
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let i = self.rng.gen_range(0..self.buffer.len());
+        Ok(Some(self.buffer.swap_remove(i)))
+    }
+
+    fn next_batch(&mut self) -> Result<Option<Batch>> {
+        let n_features = self.headers.len() - 1;
+        let mut x_data = Vec::with_capacity(self.batch_size * n_features);
+        let mut y_data = Vec::with_capacity(self.batch_size);
+        while y_data.len() < self.batch_size {
+            match self.next_row()? {
+                Some((xi, yi)) => {
+                    x_data.extend(xi);
+                    y_data.push(yi);
+                }
+                None => break,
+            }
+        }
+        if y_data.is_empty() {
+            return Ok(None);
+        }
+        let n = y_data.len();
+        let x = Tensor::new(x_data, vec![n, n_features]).map_err(|e| format!("{:?}", e))?;
+        let y = Tensor::new(y_data, vec![n]).map_err(|e| format!("{:?}", e))?;
+        Ok(Some((x, y)))
+    }
+}
+
+impl Iterator for CsvDataLoader {
+    type Item = Result<Batch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_csv(name: &str, rows: usize) -> String {
+        let path = std::env::temp_dir().join(name);
+        let mut text = String::from("a,label,b\n");
+        for i in 0..rows {
+            text.push_str(&format!("{},{},{}\n", i, i * 10, if i == 2 { String::new() } else { (i * 2).to_string() }));
+        }
+        std::fs::write(&path, text).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_sequential_batches() {
+        let path = write_csv("oxidize_ml_csv_loader_seq.csv", 25);
+        let loader = CsvDataLoader::new(&path, 10, "label").unwrap();
+        assert_eq!(loader.feature_names(), vec!["a", "b"]);
+        let batches: Vec<_> = loader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches.iter().map(|(_, y)| y.numel()).collect::<Vec<_>>(), vec![10, 10, 5]);
+        assert_eq!(batches[0].0.shape_vec(), vec![10, 2]);
+        assert_eq!(&batches[0].0.data()[..4], &[0.0, 0.0, 1.0, 2.0]);
+        assert!(batches[0].0.data()[5].is_nan());
+        assert_eq!(batches[2].1.data(), &[200.0, 210.0, 220.0, 230.0, 240.0]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_shuffle_buffer_covers_every_row() {
+        let path = write_csv("oxidize_ml_csv_loader_shuffle.csv", 50);
+        let mut loader = CsvDataLoader::new(&path, 8, "label").unwrap().with_shuffle_buffer(16).with_seed(0);
+        let epoch = |loader: &mut CsvDataLoader| -> Vec<f64> {
+            loader.flat_map(|b| b.unwrap().1.data().to_vec()).collect()
+        };
+        let first = epoch(&mut loader);
+        let mut sorted = first.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(sorted, (0..50).map(|i| i as f64 * 10.0).collect::<Vec<_>>());
+        assert_ne!(first, sorted);
+
+        loader.reset().unwrap();
+        let second = epoch(&mut loader);
+        assert_eq!(second.len(), 50);
+        assert_ne!(first, second);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
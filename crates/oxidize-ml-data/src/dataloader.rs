@@ -1,8 +1,6 @@
-use oxidize_ml_core::Tensor;
+use oxidize_ml_core::{RngHandle, RngState, Tensor};
 use crate::dataset::Dataset;
-use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::SeedableRng;
 
 /// DataLoader for batching and shuffling datasets.
 pub struct DataLoader<'a, D: Dataset> {
@@ -11,13 +9,14 @@ pub struct DataLoader<'a, D: Dataset> {
     shuffle: bool,
     indices: Vec<usize>,
     current: usize,
+    rng: RngHandle,
 }
 
 impl<'a, D: Dataset> DataLoader<'a, D> {
     pub fn new(dataset: &'a D, batch_size: usize, shuffle: bool) -> Self {
         let mut indices: Vec<usize> = (0..dataset.len()).collect();
+        let mut rng = RngHandle::global();
         if shuffle {
-            let mut rng = StdRng::seed_from_u64(42);
             indices.shuffle(&mut rng);
         }
         DataLoader {
@@ -26,17 +25,30 @@ impl<'a, D: Dataset> DataLoader<'a, D> {
             shuffle,
             indices,
             current: 0,
+            rng,
         }
     }
 
-    /// Reset the iterator (reshuffle if needed).
+    /// Reset the iterator (reshuffle if needed). Each shuffle starts from
+    /// the dataset order, so it depends only on the shuffling stream.
     pub fn reset(&mut self) {
         self.current = 0;
         if self.shuffle {
-            let mut rng = StdRng::from_entropy();
-            self.indices.shuffle(&mut rng);
+            self.indices.sort_unstable();
+            self.indices.shuffle(&mut self.rng);
         }
     }
+
+    /// Position of the shuffling stream, for checkpoints.
+    pub fn rng_state(&self) -> RngState {
+        self.rng.state()
+    }
+
+    /// Continue shuffling from a saved position; takes effect at the next
+    /// `reset`.
+    pub fn set_rng_state(&mut self, state: &RngState) {
+        self.rng = RngHandle::from_state(state);
+    }
 }
 
 impl<'a, D: Dataset> Iterator for DataLoader<'a, D> {
@@ -51,9 +63,11 @@ impl<'a, D: Dataset> Iterator for DataLoader<'a, D> {
         let batch_indices = &self.indices[self.current..end];
         let batch_size = batch_indices.len();
 
-        // Get first sample to determine feature size
-        let (first_x, first_y) = self.dataset.get(batch_indices[0]);
+        // Get first sample to determine feature shape
+        let (first_x, _first_y) = self.dataset.get(batch_indices[0]);
         let n_features = first_x.numel();
+        let mut x_shape = vec![batch_size];
+        x_shape.extend(first_x.shape_vec());
 
         let mut x_data = Vec::with_capacity(batch_size * n_features);
         let mut y_data = Vec::with_capacity(batch_size);
@@ -66,7 +80,8 @@ impl<'a, D: Dataset> Iterator for DataLoader<'a, D> {
 
         self.current = end;
 
-        let x = Tensor::new(x_data, vec![batch_size, n_features]).ok()?;
+        // `[batch, ...sample shape]`, e.g. `[batch, C, H, W]` for images.
+        let x = Tensor::new(x_data, x_shape).ok()?;
         let y = Tensor::new(y_data, vec![batch_size]).ok()?;
         Some((x, y))
     }
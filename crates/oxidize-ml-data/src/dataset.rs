@@ -1,5 +1,7 @@
 use oxidize_ml_core::Tensor;
 
+use crate::transform::{MappedDataset, Transform};
+
 /// Trait for datasets.
 pub trait Dataset {
     fn len(&self) -> usize;
@@ -7,9 +9,18 @@ pub trait Dataset {
         self.len() == 0
     }
     fn get(&self, idx: usize) -> (Tensor<f64>, Tensor<f64>);
+
+    /// Apply `transform` to the features of every sample as it is accessed.
+    fn map<F: Transform>(self, transform: F) -> MappedDataset<Self, F>
+    where
+        Self: Sized,
+    {
+        MappedDataset { dataset: self, transform }
+    }
 }
 
-/// A dataset wrapping feature and label tensors.
+/// A dataset wrapping feature and label tensors. Sample `i` is
+/// `features[i, ..]`, so features of shape `[N, C, H, W]` yield `[C, H, W]` images.
 pub struct TensorDataset {
     pub features: Tensor<f64>,
     pub labels: Tensor<f64>,
@@ -28,7 +39,10 @@ impl Dataset for TensorDataset {
     }
 
     fn get(&self, idx: usize) -> (Tensor<f64>, Tensor<f64>) {
-        let row = self.features.row(idx).expect("row access");
+        let dims = self.features.shape().dims();
+        let per_sample = self.features.numel() / dims[0];
+        let data = &self.features.data()[idx * per_sample..(idx + 1) * per_sample];
+        let row = Tensor::new(data.to_vec(), dims[1..].to_vec()).expect("row access");
         let label = Tensor::scalar(self.labels.data()[idx]);
         (row, label)
     }
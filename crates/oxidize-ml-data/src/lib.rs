@@ -1,4 +1,14 @@
 pub mod dataset;
 pub mod dataloader;
+pub mod transform;
+pub mod mix;
+pub mod csv_loader;
+pub mod arrow;
+pub mod parquet;
 pub use dataset::*;
 pub use dataloader::*;
+pub use transform::*;
+pub use mix::*;
+pub use csv_loader::*;
+pub use arrow::*;
+pub use parquet::*;
@@ -0,0 +1,203 @@
+//! Batch augmentations that blend pairs of samples: mixup (Zhang et al.)
+//! averages whole inputs, cutmix (Yun et al.) pastes a box from one image
+//! into another. Both pair each sample with a random other one, draw the
+//! mixing weight `λ ~ Beta(α, α)` once per batch, and mix the targets in
+//! the same proportion, so the loss needs soft targets
+//! (`CrossEntropyLoss::forward_soft`).
+
+use oxidize_ml_core::{RngHandle, Tensor};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::sync::Mutex;
+
+/// Mixed inputs with their target distributions `[batch, classes]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixedBatch {
+    pub x: Tensor<f64>,
+    pub y: Tensor<f64>,
+    /// Weight of the original sample in every mixed one.
+    pub lambda: f64,
+}
+
+/// Targets as distributions: class labels `[batch]` become one-hot rows;
+/// `[batch, classes]` targets are taken as they are.
+fn soft_targets(labels: &Tensor<f64>, num_classes: usize) -> Tensor<f64> {
+    if labels.ndim() == 2 {
+        assert_eq!(labels.shape_vec()[1], num_classes, "targets have {} classes, expected {}", labels.shape_vec()[1], num_classes);
+        return labels.clone();
+    }
+    let mut one_hot = Tensor::zeros(vec![labels.numel(), num_classes]);
+    for (i, &label) in labels.data().iter().enumerate() {
+        let c = label.round() as usize;
+        assert!(c < num_classes, "class {} out of range for {} classes", c, num_classes);
+        one_hot.data_mut()[i * num_classes + c] = 1.0;
+    }
+    one_hot
+}
+
+/// `λ a + (1 - λ) b`, row by row with `b` the rows of `a` at `perm`.
+fn blend(a: &Tensor<f64>, perm: &[usize], lambda: f64) -> Tensor<f64> {
+    let row = a.numel() / perm.len();
+    let data = a.data();
+    let mixed = (0..a.numel())
+        .map(|k| lambda * data[k] + (1.0 - lambda) * data[perm[k / row] * row + k % row])
+        .collect();
+    Tensor::new(mixed, a.shape_vec()).expect("same shape")
+}
+
+/// A standard normal draw (Box–Muller).
+fn normal(rng: &mut RngHandle) -> f64 {
+    let u: f64 = 1.0 - rng.gen::<f64>();
+    (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * rng.gen::<f64>()).cos()
+}
+
+/// A `Gamma(shape, 1)` draw (Marsaglia and Tsang), boosted for `shape < 1`.
+fn gamma(rng: &mut RngHandle, shape: f64) -> f64 {
+    if shape < 1.0 {
+        let u: f64 = rng.gen();
+        return gamma(rng, shape + 1.0) * u.powf(1.0 / shape);
+    }
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let z = normal(rng);
+        let v = (1.0 + c * z).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u: f64 = rng.gen();
+        if u.ln() < 0.5 * z * z + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+/// `λ ~ Beta(α, α)` and a random pairing of the batch.
+fn draw(rng: &Mutex<RngHandle>, alpha: f64, batch: usize) -> (f64, Vec<usize>) {
+    let mut rng = rng.lock().unwrap();
+    let (a, b) = (gamma(&mut rng, alpha), gamma(&mut rng, alpha));
+    let mut perm: Vec<usize> = (0..batch).collect();
+    perm.shuffle(&mut *rng);
+    (a / (a + b), perm)
+}
+
+/// Mixup: `x = λ x_i + (1 - λ) x_j`, `y = λ y_i + (1 - λ) y_j`.
+pub struct Mixup {
+    pub alpha: f64,
+    pub num_classes: usize,
+    rng: Mutex<RngHandle>,
+}
+
+impl Mixup {
+    pub fn new(alpha: f64, num_classes: usize, seed: Option<u64>) -> Self {
+        assert!(alpha > 0.0, "Mixup: alpha must be positive");
+        Mixup { alpha, num_classes, rng: Mutex::new(RngHandle::new(seed)) }
+    }
+
+    /// Mix a batch `x [batch, ...]` with labels `[batch]` or soft targets
+    /// `[batch, classes]`.
+    pub fn apply(&self, x: &Tensor<f64>, labels: &Tensor<f64>) -> MixedBatch {
+        let batch = x.shape_vec()[0];
+        assert_eq!(labels.shape_vec()[0], batch, "Mixup: one target per sample");
+        let (lambda, perm) = draw(&self.rng, self.alpha, batch);
+        let y = soft_targets(labels, self.num_classes);
+        MixedBatch { x: blend(x, &perm, lambda), y: blend(&y, &perm, lambda), lambda }
+    }
+}
+
+/// CutMix: a random box covering a `1 - λ` share of each image
+/// (`[batch, C, H, W]` or `[batch, H, W]`) is replaced by the same box of
+/// its pair. `λ` is then recomputed from the box actually pasted (clipped at
+/// the borders) and the targets mixed by it.
+pub struct CutMix {
+    pub alpha: f64,
+    pub num_classes: usize,
+    rng: Mutex<RngHandle>,
+}
+
+impl CutMix {
+    pub fn new(alpha: f64, num_classes: usize, seed: Option<u64>) -> Self {
+        assert!(alpha > 0.0, "CutMix: alpha must be positive");
+        CutMix { alpha, num_classes, rng: Mutex::new(RngHandle::new(seed)) }
+    }
+
+    pub fn apply(&self, x: &Tensor<f64>, labels: &Tensor<f64>) -> MixedBatch {
+        let dims = x.shape_vec();
+        assert!(dims.len() >= 3, "CutMix expects images of shape [batch, C, H, W] or [batch, H, W]");
+        let (batch, h, w) = (dims[0], dims[dims.len() - 2], dims[dims.len() - 1]);
+        assert_eq!(labels.shape_vec()[0], batch, "CutMix: one target per sample");
+        let (lambda, perm) = draw(&self.rng, self.alpha, batch);
+
+        // A box of side √(1 - λ) times the image's, centred uniformly.
+        let side = (1.0 - lambda).sqrt();
+        let (cut_h, cut_w) = ((h as f64 * side) as usize, (w as f64 * side) as usize);
+        let (cy, cx) = {
+            let mut rng = self.rng.lock().unwrap();
+            (rng.gen_range(0..h), rng.gen_range(0..w))
+        };
+        let (top, bottom) = (cy.saturating_sub(cut_h / 2), (cy + cut_h / 2).min(h));
+        let (left, right) = (cx.saturating_sub(cut_w / 2), (cx + cut_w / 2).min(w));
+
+        let plane = h * w;
+        let sample = x.numel() / batch;
+        let src = x.data();
+        let mut mixed = src.to_vec();
+        for (i, &j) in perm.iter().enumerate() {
+            for p in 0..sample / plane {
+                for r in top..bottom {
+                    let row = p * plane + r * w;
+                    mixed[i * sample + row + left..i * sample + row + right]
+                        .copy_from_slice(&src[j * sample + row + left..j * sample + row + right]);
+                }
+            }
+        }
+        let lambda = 1.0 - ((bottom - top) * (right - left)) as f64 / plane as f64;
+        let y = soft_targets(labels, self.num_classes);
+        MixedBatch { x: Tensor::new(mixed, dims).expect("same shape"), y: blend(&y, &perm, lambda), lambda }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixup_blends_inputs_and_targets() {
+        let x = Tensor::new(vec![0.0, 0.0, 10.0, 10.0, 20.0, 20.0], vec![3, 2]).unwrap();
+        let labels = Tensor::from_slice(&[0.0, 1.0, 2.0]);
+        let batch = Mixup::new(0.4, 3, Some(7)).apply(&x, &labels);
+        assert!((0.0..=1.0).contains(&batch.lambda));
+        assert_eq!(batch.y.shape_vec(), vec![3, 3]);
+        for i in 0..3 {
+            let row = &batch.y.data()[i * 3..i * 3 + 3];
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            // Sample i keeps at least its λ share of its own class.
+            assert!(row[i] >= batch.lambda - 1e-12);
+            // Mixed inputs match the mixed targets: x = 10 · E[class].
+            let expected = 10.0 * (row[1] + 2.0 * row[2]);
+            assert!((batch.x.data()[2 * i] - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_cutmix_pastes_a_box() {
+        // Image i is filled with i, so each pixel names its source image.
+        let x = Tensor::new((0..2 * 8 * 8).map(|k| (k / 64) as f64).collect(), vec![2, 1, 8, 8]).unwrap();
+        let labels = Tensor::from_slice(&[0.0, 1.0]);
+        let cutmix = CutMix::new(1.0, 2, Some(3));
+        for _ in 0..5 {
+            let batch = cutmix.apply(&x, &labels);
+            for i in 0..2 {
+                let pixels = &batch.x.data()[i * 64..(i + 1) * 64];
+                let own = pixels.iter().filter(|&&v| v == i as f64).count() as f64 / 64.0;
+                // The pasted share matches λ unless the image was paired with itself.
+                let y_own = batch.y.data()[i * 2 + i];
+                if own < 1.0 {
+                    assert!((own - batch.lambda).abs() < 1e-12 && (y_own - batch.lambda).abs() < 1e-12);
+                } else {
+                    assert!((y_own - 1.0).abs() < 1e-12);
+                }
+            }
+        }
+    }
+}
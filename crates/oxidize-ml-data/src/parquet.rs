@@ -0,0 +1,1107 @@
+//! Parquet reading (and basic writing) without external dependencies.
+//!
+//! Reads flat schemas of BOOLEAN, INT32, INT64, FLOAT, DOUBLE and BYTE_ARRAY
+//! (string) columns, required or optional, from v1 and v2 data pages that
+//! are PLAIN- or dictionary-encoded and uncompressed or Snappy-compressed,
+//! which covers the defaults of pyarrow, pandas and Spark. INT32/INT64
+//! decimals are scaled to Float64. Nested and repeated columns, INT96
+//! timestamps and the other codecs and encodings are reported as errors
+//! when selected.
+
+use crate::arrow::{record_batches_to_tensor, Array, DataType, Field, RecordBatch};
+use crate::dataset::TensorDataset;
+use oxidize_ml_core::{Float, Tensor};
+use std::error::Error;
+use std::fs;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+const MAGIC: &[u8] = b"PAR1";
+
+// Physical types.
+const BOOLEAN: i64 = 0;
+const INT32: i64 = 1;
+const INT64: i64 = 2;
+const INT96: i64 = 3;
+const FLOAT: i64 = 4;
+const DOUBLE: i64 = 5;
+const BYTE_ARRAY: i64 = 6;
+
+// Repetition.
+const REQUIRED: i64 = 0;
+const OPTIONAL: i64 = 1;
+const REPEATED: i64 = 2;
+
+// Converted types.
+const UTF8: i64 = 0;
+const DECIMAL: i64 = 5;
+
+// Encodings.
+const PLAIN: i64 = 0;
+const PLAIN_DICTIONARY: i64 = 2;
+const RLE: i64 = 3;
+const RLE_DICTIONARY: i64 = 8;
+
+// Codecs.
+const UNCOMPRESSED: i64 = 0;
+const SNAPPY: i64 = 1;
+
+// Page types.
+const DATA_PAGE: i64 = 0;
+const DICTIONARY_PAGE: i64 = 2;
+const DATA_PAGE_V2: i64 = 3;
+
+/// Read every row group of a Parquet file, keeping only `columns` (all when `None`).
+pub fn read_parquet(path: &str, columns: Option<&[&str]>) -> Result<Vec<RecordBatch>> {
+    let reader = ParquetReader::open(path)?;
+    (0..reader.num_row_groups()).map(|i| reader.read_row_group(i, columns)).collect()
+}
+
+/// Load the selected columns of a Parquet file into a `[rows, columns]`
+/// tensor, with the conversions of `record_batches_to_tensor`.
+pub fn load_parquet_to_tensor<T: Float>(path: &str, columns: Option<&[&str]>) -> Result<Tensor<T>> {
+    let batches = read_parquet(path, columns)?;
+    if batches.is_empty() {
+        // No row groups: still honour the column count.
+        let names = ParquetReader::open(path)?.select(columns)?.len();
+        return Ok(Tensor::zeros(vec![0, names]));
+    }
+    record_batches_to_tensor(&batches, columns)
+}
+
+impl TensorDataset {
+    /// Build a dataset from the feature and label columns of a Parquet file.
+    pub fn from_parquet(path: &str, features: &[&str], label: &str) -> Result<Self> {
+        let mut names = features.to_vec();
+        names.push(label);
+        let batches = read_parquet(path, Some(&names))?;
+        let x = record_batches_to_tensor(&batches, Some(features))?;
+        let y = record_batches_to_tensor(&batches, Some(&[label]))?;
+        let n = y.numel();
+        Ok(TensorDataset::new(x, y.reshape(vec![n]).map_err(|e| format!("{:?}", e))?))
+    }
+}
+
+/// A leaf column of the file schema.
+struct Column {
+    field: Field,
+    physical: i64,
+    max_def: u8,
+    repeated: bool,
+    /// Power of ten dividing decimal values, `None` if not a decimal.
+    decimal_scale: Option<i32>,
+}
+
+/// Location of one column chunk within the file.
+struct Chunk {
+    codec: i64,
+    num_values: usize,
+    start: usize,
+    len: usize,
+}
+
+struct RowGroup {
+    num_rows: usize,
+    chunks: Vec<Chunk>,
+}
+
+/// A Parquet file held in memory, with its footer decoded.
+pub struct ParquetReader {
+    bytes: Vec<u8>,
+    columns: Vec<Column>,
+    row_groups: Vec<RowGroup>,
+    num_rows: usize,
+}
+
+impl ParquetReader {
+    pub fn open(path: &str) -> Result<Self> {
+        Self::from_bytes(fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        let n = bytes.len();
+        if n < 12 || &bytes[..4] != MAGIC || &bytes[n - 4..] != MAGIC {
+            return Err("not a Parquet file (bad magic)".into());
+        }
+        let meta_len = u32::from_le_bytes(bytes[n - 8..n - 4].try_into().unwrap()) as usize;
+        let meta_start = (n - 8).checked_sub(meta_len).ok_or("corrupt Parquet footer")?;
+        let meta = ThriftReader::new(&bytes[meta_start..n - 8]).read_struct(0)?;
+
+        let schema = meta.req(2)?.list()?;
+        let mut columns = Vec::new();
+        let root_children = schema.first().ok_or("empty Parquet schema")?.opt_int(5).unwrap_or(0);
+        let mut pos = 1;
+        for _ in 0..root_children {
+            walk_schema(schema, &mut pos, "", 0, false, &mut columns)?;
+        }
+
+        let mut row_groups = Vec::new();
+        for rg in meta.req(4)?.list()? {
+            let mut chunks = Vec::new();
+            for cc in rg.req(1)?.list()? {
+                let md = cc.field(3).ok_or("column chunk without metadata (external files are not supported)")?;
+                let data_offset = md.req(9)?.int()?;
+                let start = match md.opt_int(11) {
+                    Some(d) if d > 0 && d < data_offset => d,
+                    _ => data_offset,
+                } as usize;
+                let len = md.req(7)?.int()? as usize;
+                if start.checked_add(len).is_none_or(|end| end > n) {
+                    return Err("column chunk extends past end of file".into());
+                }
+                chunks.push(Chunk {
+                    codec: md.req(4)?.int()?,
+                    num_values: md.req(5)?.int()? as usize,
+                    start,
+                    len,
+                });
+            }
+            if chunks.len() != columns.len() {
+                return Err(format!("row group has {} column chunks, schema has {} columns", chunks.len(), columns.len()).into());
+            }
+            row_groups.push(RowGroup {
+                num_rows: rg.req(3)?.int()? as usize,
+                chunks,
+            });
+        }
+
+        Ok(ParquetReader {
+            bytes,
+            columns,
+            row_groups,
+            num_rows: meta.req(3)?.int()? as usize,
+        })
+    }
+
+    /// Fields of the leaf columns, in file order. Nested columns are named
+    /// by their dotted path.
+    pub fn schema(&self) -> Vec<Field> {
+        self.columns.iter().map(|c| c.field.clone()).collect()
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_row_groups(&self) -> usize {
+        self.row_groups.len()
+    }
+
+    /// Indices of the selected columns.
+    fn select(&self, columns: Option<&[&str]>) -> Result<Vec<usize>> {
+        match columns {
+            None => Ok((0..self.columns.len()).collect()),
+            Some(names) => names
+                .iter()
+                .map(|name| {
+                    self.columns
+                        .iter()
+                        .position(|c| c.field.name == *name)
+                        .ok_or_else(|| format!("no column named '{}'", name).into())
+                })
+                .collect(),
+        }
+    }
+
+    /// Decode row group `i`, keeping only `columns` (all when `None`).
+    pub fn read_row_group(&self, i: usize, columns: Option<&[&str]>) -> Result<RecordBatch> {
+        let rg = self.row_groups.get(i).ok_or_else(|| format!("row group {} out of range", i))?;
+        let mut fields = Vec::new();
+        let mut arrays = Vec::new();
+        for j in self.select(columns)? {
+            let col = &self.columns[j];
+            let array = self
+                .read_chunk(col, &rg.chunks[j])
+                .map_err(|e| format!("column '{}': {}", col.field.name, e))?;
+            if array.len() != rg.num_rows {
+                return Err(format!("column '{}' has {} values, expected {}", col.field.name, array.len(), rg.num_rows).into());
+            }
+            fields.push(col.field.clone());
+            arrays.push(array);
+        }
+        RecordBatch::new(fields, arrays)
+    }
+
+    fn read_chunk(&self, col: &Column, chunk: &Chunk) -> Result<Array> {
+        if col.repeated {
+            return Err("repeated (list) columns are not supported".into());
+        }
+        if col.physical == INT96 {
+            return Err("INT96 timestamps are not supported".into());
+        }
+        if ![BOOLEAN, INT32, INT64, FLOAT, DOUBLE, BYTE_ARRAY].contains(&col.physical) {
+            return Err(format!("physical type {} is not supported", col.physical).into());
+        }
+
+        let mut data = &self.bytes[chunk.start..chunk.start + chunk.len];
+        let mut dict: Option<Values> = None;
+        let mut values = Values::new(col.physical);
+        let mut defs: Vec<u32> = Vec::new();
+        let mut levels_read = 0;
+
+        while levels_read < chunk.num_values {
+            let mut r = ThriftReader::new(data);
+            let header = r.read_struct(0)?;
+            data = &data[r.pos..];
+            let compressed_size = header.req(3)?.int()? as usize;
+            let uncompressed_size = header.req(2)?.int()? as usize;
+            let page = data.get(..compressed_size).ok_or("truncated page")?;
+            data = &data[compressed_size..];
+
+            match header.req(1)?.int()? {
+                DICTIONARY_PAGE => {
+                    let dh = header.req(7)?;
+                    let body = decompress(chunk.codec, page, uncompressed_size)?;
+                    let mut d = Values::new(col.physical);
+                    d.decode_plain(&body, dh.req(1)?.int()? as usize)?;
+                    dict = Some(d);
+                }
+                DATA_PAGE => {
+                    let dh = header.req(5)?;
+                    let n = dh.req(1)?.int()? as usize;
+                    let body = decompress(chunk.codec, page, uncompressed_size)?;
+                    let mut rest = &body[..];
+                    let page_defs = if col.max_def > 0 {
+                        let len = u32::from_le_bytes(rest.get(..4).ok_or("truncated levels")?.try_into().unwrap()) as usize;
+                        let levels = rest.get(4..4 + len).ok_or("truncated levels")?;
+                        rest = &rest[4 + len..];
+                        decode_rle_hybrid(levels, bit_width(col.max_def as u32), n)?
+                    } else {
+                        vec![0; n]
+                    };
+                    let present = page_defs.iter().filter(|&&d| d == col.max_def as u32).count();
+                    values.decode_page(dh.req(2)?.int()?, rest, present, dict.as_ref())?;
+                    defs.extend(page_defs);
+                    levels_read += n;
+                }
+                DATA_PAGE_V2 => {
+                    let dh = header.req(8)?;
+                    let n = dh.req(1)?.int()? as usize;
+                    let def_len = dh.req(5)?.int()? as usize;
+                    let rep_len = dh.req(6)?.int()? as usize;
+                    let levels = page.get(rep_len..rep_len + def_len).ok_or("truncated levels")?;
+                    let page_defs = if col.max_def > 0 {
+                        decode_rle_hybrid(levels, bit_width(col.max_def as u32), n)?
+                    } else {
+                        vec![0; n]
+                    };
+                    let raw = &page[rep_len + def_len..];
+                    let body = match dh.field(7) {
+                        Some(Thrift::Bool(false)) => raw.to_vec(),
+                        _ => decompress(chunk.codec, raw, uncompressed_size - rep_len - def_len)?,
+                    };
+                    let present = page_defs.iter().filter(|&&d| d == col.max_def as u32).count();
+                    values.decode_page(dh.req(4)?.int()?, &body, present, dict.as_ref())?;
+                    defs.extend(page_defs);
+                    levels_read += n;
+                }
+                // Index pages and unknown page types carry no values.
+                _ => {}
+            }
+            if data.is_empty() && levels_read < chunk.num_values {
+                return Err("column chunk ended early".into());
+            }
+        }
+        values.into_array(&defs, col)
+    }
+}
+
+/// Depth-first walk of the flattened schema, collecting leaf columns.
+fn walk_schema(
+    schema: &[Thrift],
+    pos: &mut usize,
+    prefix: &str,
+    def: u8,
+    repeated: bool,
+    out: &mut Vec<Column>,
+) -> Result<()> {
+    let e = schema.get(*pos).ok_or("truncated Parquet schema")?;
+    *pos += 1;
+    let name = String::from_utf8_lossy(e.req(4)?.binary()?).into_owned();
+    let name = if prefix.is_empty() { name } else { format!("{}.{}", prefix, name) };
+    let repetition = e.opt_int(3).unwrap_or(0);
+    let def = def + (repetition != 0) as u8;
+    let repeated = repeated || repetition == REPEATED;
+
+    match e.opt_int(5) {
+        Some(children) if children > 0 => {
+            for _ in 0..children {
+                walk_schema(schema, pos, &name, def, repeated, out)?;
+            }
+        }
+        _ => {
+            let physical = e.req(1)?.int()?;
+            let converted = e.opt_int(6);
+            let decimal_scale = match converted {
+                Some(DECIMAL) if physical == INT32 || physical == INT64 => Some(e.opt_int(7).unwrap_or(0) as i32),
+                _ => None,
+            };
+            let data_type = match physical {
+                _ if decimal_scale.is_some() => DataType::Float64,
+                BOOLEAN => DataType::Boolean,
+                INT32 => DataType::Int32,
+                INT64 => DataType::Int64,
+                FLOAT => DataType::Float32,
+                DOUBLE => DataType::Float64,
+                // Strings, and anything else, are kept as text.
+                _ => DataType::Utf8,
+            };
+            out.push(Column {
+                field: Field::new(&name, data_type, def > 0),
+                physical,
+                max_def: def,
+                repeated,
+                decimal_scale,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn bit_width(max: u32) -> u8 {
+    (32 - max.leading_zeros()) as u8
+}
+
+/// Non-null values of one column, in physical representation.
+enum Values {
+    Bool(Vec<bool>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    Bytes(Vec<Vec<u8>>),
+}
+
+impl Values {
+    fn new(physical: i64) -> Self {
+        match physical {
+            BOOLEAN => Values::Bool(Vec::new()),
+            INT32 => Values::I32(Vec::new()),
+            INT64 => Values::I64(Vec::new()),
+            FLOAT => Values::F32(Vec::new()),
+            DOUBLE => Values::F64(Vec::new()),
+            _ => Values::Bytes(Vec::new()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Values::Bool(v) => v.len(),
+            Values::I32(v) => v.len(),
+            Values::I64(v) => v.len(),
+            Values::F32(v) => v.len(),
+            Values::F64(v) => v.len(),
+            Values::Bytes(v) => v.len(),
+        }
+    }
+
+    /// Append `n` PLAIN-encoded values.
+    fn decode_plain(&mut self, buf: &[u8], n: usize) -> Result<()> {
+        fn fixed<V, const N: usize>(out: &mut Vec<V>, buf: &[u8], n: usize, f: fn([u8; N]) -> V) -> Result<()> {
+            let bytes = buf.get(..n * N).ok_or("truncated PLAIN values")?;
+            out.extend(bytes.chunks_exact(N).map(|c| f(c.try_into().unwrap())));
+            Ok(())
+        }
+        match self {
+            Values::Bool(v) => {
+                let bytes = buf.get(..n.div_ceil(8)).ok_or("truncated PLAIN values")?;
+                v.extend((0..n).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1));
+                Ok(())
+            }
+            Values::I32(v) => fixed(v, buf, n, i32::from_le_bytes),
+            Values::I64(v) => fixed(v, buf, n, i64::from_le_bytes),
+            Values::F32(v) => fixed(v, buf, n, f32::from_le_bytes),
+            Values::F64(v) => fixed(v, buf, n, f64::from_le_bytes),
+            Values::Bytes(v) => {
+                let mut pos = 0;
+                for _ in 0..n {
+                    let len = buf.get(pos..pos + 4).ok_or("truncated PLAIN values")?;
+                    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+                    v.push(buf.get(pos + 4..pos + 4 + len).ok_or("truncated PLAIN values")?.to_vec());
+                    pos += 4 + len;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Append the values at `indices` of `dict`.
+    fn gather(&mut self, dict: &Values, indices: &[u32]) -> Result<()> {
+        fn take<V: Clone>(out: &mut Vec<V>, dict: &[V], indices: &[u32]) -> Result<()> {
+            for &i in indices {
+                out.push(dict.get(i as usize).ok_or("dictionary index out of range")?.clone());
+            }
+            Ok(())
+        }
+        match (self, dict) {
+            (Values::Bool(o), Values::Bool(d)) => take(o, d, indices),
+            (Values::I32(o), Values::I32(d)) => take(o, d, indices),
+            (Values::I64(o), Values::I64(d)) => take(o, d, indices),
+            (Values::F32(o), Values::F32(d)) => take(o, d, indices),
+            (Values::F64(o), Values::F64(d)) => take(o, d, indices),
+            (Values::Bytes(o), Values::Bytes(d)) => take(o, d, indices),
+            _ => Err("dictionary type does not match column".into()),
+        }
+    }
+
+    /// Append the `n` non-null values of a data page.
+    fn decode_page(&mut self, encoding: i64, buf: &[u8], n: usize, dict: Option<&Values>) -> Result<()> {
+        match encoding {
+            PLAIN => self.decode_plain(buf, n),
+            PLAIN_DICTIONARY | RLE_DICTIONARY => {
+                let dict = dict.ok_or("dictionary-encoded page without a dictionary page")?;
+                if n == 0 {
+                    return Ok(());
+                }
+                let width = *buf.first().ok_or("truncated dictionary indices")?;
+                let indices = decode_rle_hybrid(&buf[1..], width, n)?;
+                self.gather(dict, &indices)
+            }
+            RLE if matches!(self, Values::Bool(_)) => {
+                // Booleans may be RLE-encoded with a 4-byte length prefix.
+                let bits = decode_rle_hybrid(buf.get(4..).ok_or("truncated RLE values")?, 1, n)?;
+                if let Values::Bool(v) = self {
+                    v.extend(bits.iter().map(|&b| b == 1));
+                }
+                Ok(())
+            }
+            e => Err(format!("encoding {} is not supported", e).into()),
+        }
+    }
+
+    /// Interleave the values with nulls where the definition level is below
+    /// the maximum.
+    fn into_array(self, defs: &[u32], col: &Column) -> Result<Array> {
+        let present = defs.iter().filter(|&&d| d == col.max_def as u32).count();
+        if present != self.len() {
+            return Err(format!("{} values for {} non-null slots", self.len(), present).into());
+        }
+        fn spread<V>(values: Vec<V>, defs: &[u32], max: u32) -> Vec<Option<V>> {
+            let mut it = values.into_iter();
+            defs.iter().map(|&d| if d == max { it.next() } else { None }).collect()
+        }
+        let max = col.max_def as u32;
+        if let Some(scale) = col.decimal_scale {
+            let div = 10f64.powi(scale);
+            let unscaled: Vec<f64> = match self {
+                Values::I32(v) => v.into_iter().map(|x| x as f64 / div).collect(),
+                Values::I64(v) => v.into_iter().map(|x| x as f64 / div).collect(),
+                _ => return Err("decimal column with non-integer storage".into()),
+            };
+            return Ok(Array::Float64(spread(unscaled, defs, max)));
+        }
+        Ok(match self {
+            Values::Bool(v) => Array::Boolean(spread(v, defs, max)),
+            Values::I32(v) => Array::Int32(spread(v, defs, max)),
+            Values::I64(v) => Array::Int64(spread(v, defs, max)),
+            Values::F32(v) => Array::Float32(spread(v, defs, max)),
+            Values::F64(v) => Array::Float64(spread(v, defs, max)),
+            Values::Bytes(v) => Array::Utf8(spread(
+                v.into_iter().map(|b| String::from_utf8_lossy(&b).into_owned()).collect(),
+                defs,
+                max,
+            )),
+        })
+    }
+}
+
+/// Decode `n` values of Parquet's RLE / bit-packed hybrid encoding.
+fn decode_rle_hybrid(buf: &[u8], bit_width: u8, n: usize) -> Result<Vec<u32>> {
+    if bit_width > 32 {
+        return Err(format!("invalid bit width {}", bit_width).into());
+    }
+    let width = bit_width as usize;
+    let mut out = Vec::with_capacity(n);
+    let mut pos = 0;
+    while out.len() < n {
+        let header = read_varint(buf, &mut pos)?;
+        if header & 1 == 0 {
+            // RLE run: one value repeated.
+            let count = (header >> 1) as usize;
+            let nbytes = width.div_ceil(8);
+            let bytes = buf.get(pos..pos + nbytes).ok_or("truncated RLE run")?;
+            pos += nbytes;
+            let value = bytes.iter().rev().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+            out.extend(std::iter::repeat_n(value, count.min(n - out.len())));
+        } else {
+            // Bit-packed groups of 8 values, least significant bit first.
+            let groups = (header >> 1) as usize;
+            let nbytes = groups * width;
+            let bytes = buf.get(pos..pos + nbytes).ok_or("truncated bit-packed run")?;
+            pos += nbytes;
+            for i in 0..(groups * 8).min(n - out.len()) {
+                let mut v = 0u32;
+                for b in 0..width {
+                    let bit = i * width + b;
+                    v |= ((bytes[bit / 8] >> (bit % 8) & 1) as u32) << b;
+                }
+                out.push(v);
+            }
+        }
+        if pos >= buf.len() && out.len() < n {
+            return Err("truncated RLE/bit-packed data".into());
+        }
+    }
+    Ok(out)
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut v = 0u64;
+    for shift in (0..64).step_by(7) {
+        let b = *buf.get(*pos).ok_or("truncated varint")?;
+        *pos += 1;
+        v |= ((b & 0x7f) as u64) << shift;
+        if b < 0x80 {
+            return Ok(v);
+        }
+    }
+    Err("varint too long".into())
+}
+
+fn decompress(codec: i64, buf: &[u8], uncompressed_size: usize) -> Result<Vec<u8>> {
+    match codec {
+        UNCOMPRESSED => Ok(buf.to_vec()),
+        SNAPPY => {
+            let out = snappy_decompress(buf)?;
+            if out.len() != uncompressed_size {
+                return Err("Snappy page has the wrong uncompressed size".into());
+            }
+            Ok(out)
+        }
+        c => Err(format!("compression codec {} is not supported (only uncompressed and Snappy)", c).into()),
+    }
+}
+
+/// Decompress a raw (unframed) Snappy block.
+fn snappy_decompress(buf: &[u8]) -> Result<Vec<u8>> {
+    let mut pos = 0;
+    let len = read_varint(buf, &mut pos)? as usize;
+    let mut out: Vec<u8> = Vec::with_capacity(len);
+    while pos < buf.len() {
+        let tag = buf[pos];
+        pos += 1;
+        let (length, offset) = match tag & 3 {
+            0 => {
+                let mut n = (tag >> 2) as usize;
+                if n >= 60 {
+                    let extra = n - 59;
+                    let bytes = buf.get(pos..pos + extra).ok_or("truncated Snappy literal")?;
+                    n = bytes.iter().rev().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+                    pos += extra;
+                }
+                let lit = buf.get(pos..pos + n + 1).ok_or("truncated Snappy literal")?;
+                out.extend_from_slice(lit);
+                pos += n + 1;
+                continue;
+            }
+            1 => {
+                let b = *buf.get(pos).ok_or("truncated Snappy copy")? as usize;
+                pos += 1;
+                (4 + ((tag >> 2) & 7) as usize, ((tag as usize >> 5) << 8) | b)
+            }
+            2 => {
+                let b = buf.get(pos..pos + 2).ok_or("truncated Snappy copy")?;
+                pos += 2;
+                ((tag >> 2) as usize + 1, u16::from_le_bytes([b[0], b[1]]) as usize)
+            }
+            _ => {
+                let b = buf.get(pos..pos + 4).ok_or("truncated Snappy copy")?;
+                pos += 4;
+                ((tag >> 2) as usize + 1, u32::from_le_bytes(b.try_into().unwrap()) as usize)
+            }
+        };
+        if offset == 0 || offset > out.len() {
+            return Err("invalid Snappy copy offset".into());
+        }
+        // Copies may overlap their own output, so go byte by byte.
+        let start = out.len() - offset;
+        for i in 0..length {
+            out.push(out[start + i]);
+        }
+    }
+    if out.len() != len {
+        return Err("Snappy block has the wrong length".into());
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Writing
+// ---------------------------------------------------------------------------
+
+/// Write `batch` as a single-row-group Parquet file with PLAIN,
+/// uncompressed pages. Nullable fields become OPTIONAL columns.
+pub fn write_parquet(path: &str, batch: &RecordBatch) -> Result<()> {
+    fs::write(path, encode_parquet(batch)?)?;
+    Ok(())
+}
+
+fn encode_parquet(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let n_rows = batch.num_rows();
+    let mut out = MAGIC.to_vec();
+    let mut schema = vec![Thrift::Struct(vec![
+        (4, Thrift::Binary(b"schema".to_vec())),
+        (5, Thrift::I32(batch.num_columns() as i32)),
+    ])];
+    let mut chunks = Vec::new();
+
+    for (field, col) in batch.schema().iter().zip(batch.columns()) {
+        if !field.nullable && col.null_count() > 0 {
+            return Err(format!("column '{}' is not nullable but has nulls", field.name).into());
+        }
+        let (physical, valid, values) = plain_values(col);
+
+        let mut body = Vec::new();
+        if field.nullable {
+            // Definition levels as bit-packed groups of 8, width 1.
+            let groups = n_rows.div_ceil(8);
+            let mut levels = Vec::new();
+            let mut header = Vec::new();
+            write_varint(&mut header, ((groups as u64) << 1) | 1);
+            levels.extend(header);
+            let mut bits = vec![0u8; groups];
+            for (i, &v) in valid.iter().enumerate() {
+                bits[i / 8] |= (v as u8) << (i % 8);
+            }
+            levels.extend(bits);
+            body.extend((levels.len() as u32).to_le_bytes());
+            body.extend(levels);
+        }
+        body.extend(values);
+
+        let page_header = Thrift::Struct(vec![
+            (1, Thrift::I32(DATA_PAGE as i32)),
+            (2, Thrift::I32(body.len() as i32)),
+            (3, Thrift::I32(body.len() as i32)),
+            (
+                5,
+                Thrift::Struct(vec![
+                    (1, Thrift::I32(n_rows as i32)),
+                    (2, Thrift::I32(PLAIN as i32)),
+                    (3, Thrift::I32(RLE as i32)),
+                    (4, Thrift::I32(RLE as i32)),
+                ]),
+            ),
+        ]);
+        let offset = out.len();
+        page_header.write(&mut out);
+        out.extend(body);
+        let size = (out.len() - offset) as i64;
+
+        let mut element = vec![
+            (1, Thrift::I32(physical as i32)),
+            (3, Thrift::I32(if field.nullable { OPTIONAL } else { REQUIRED } as i32)),
+            (4, Thrift::Binary(field.name.as_bytes().to_vec())),
+        ];
+        if physical == BYTE_ARRAY {
+            element.push((6, Thrift::I32(UTF8 as i32)));
+        }
+        schema.push(Thrift::Struct(element));
+
+        let meta = Thrift::Struct(vec![
+            (1, Thrift::I32(physical as i32)),
+            (2, Thrift::List(vec![Thrift::I32(PLAIN as i32), Thrift::I32(RLE as i32)])),
+            (3, Thrift::List(vec![Thrift::Binary(field.name.as_bytes().to_vec())])),
+            (4, Thrift::I32(UNCOMPRESSED as i32)),
+            (5, Thrift::I64(n_rows as i64)),
+            (6, Thrift::I64(size)),
+            (7, Thrift::I64(size)),
+            (9, Thrift::I64(offset as i64)),
+        ]);
+        chunks.push(Thrift::Struct(vec![(2, Thrift::I64(offset as i64)), (3, meta)]));
+    }
+
+    let total = (out.len() - MAGIC.len()) as i64;
+    let row_group = Thrift::Struct(vec![
+        (1, Thrift::List(chunks)),
+        (2, Thrift::I64(total)),
+        (3, Thrift::I64(n_rows as i64)),
+    ]);
+    let meta = Thrift::Struct(vec![
+        (1, Thrift::I32(1)),
+        (2, Thrift::List(schema)),
+        (3, Thrift::I64(n_rows as i64)),
+        (4, Thrift::List(vec![row_group])),
+        (6, Thrift::Binary(b"oxidize-ml".to_vec())),
+    ]);
+    let meta_start = out.len();
+    meta.write(&mut out);
+    let meta_len = (out.len() - meta_start) as u32;
+    out.extend(meta_len.to_le_bytes());
+    out.extend_from_slice(MAGIC);
+    Ok(out)
+}
+
+/// Physical type, validity and PLAIN bytes of the non-null values of a column.
+fn plain_values(col: &Array) -> (i64, Vec<bool>, Vec<u8>) {
+    fn fixed<V: Copy, const N: usize>(v: &[Option<V>], f: fn(V) -> [u8; N]) -> (Vec<bool>, Vec<u8>) {
+        (v.iter().map(|x| x.is_some()).collect(), v.iter().flatten().flat_map(|&x| f(x)).collect())
+    }
+    match col {
+        Array::Boolean(v) => {
+            let present: Vec<bool> = v.iter().flatten().copied().collect();
+            let mut bytes = vec![0u8; present.len().div_ceil(8)];
+            for (i, &b) in present.iter().enumerate() {
+                bytes[i / 8] |= (b as u8) << (i % 8);
+            }
+            (BOOLEAN, v.iter().map(|x| x.is_some()).collect(), bytes)
+        }
+        Array::Int32(v) => {
+            let (valid, bytes) = fixed(v, i32::to_le_bytes);
+            (INT32, valid, bytes)
+        }
+        Array::Int64(v) => {
+            let (valid, bytes) = fixed(v, i64::to_le_bytes);
+            (INT64, valid, bytes)
+        }
+        Array::Float32(v) => {
+            let (valid, bytes) = fixed(v, f32::to_le_bytes);
+            (FLOAT, valid, bytes)
+        }
+        Array::Float64(v) => {
+            let (valid, bytes) = fixed(v, f64::to_le_bytes);
+            (DOUBLE, valid, bytes)
+        }
+        Array::Utf8(v) => {
+            let mut bytes = Vec::new();
+            for s in v.iter().flatten() {
+                bytes.extend((s.len() as u32).to_le_bytes());
+                bytes.extend(s.as_bytes());
+            }
+            (BYTE_ARRAY, v.iter().map(|x| x.is_some()).collect(), bytes)
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+// ---------------------------------------------------------------------------
+// Thrift compact protocol (the encoding of Parquet's metadata)
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+enum Thrift {
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    Double(f64),
+    Binary(Vec<u8>),
+    List(Vec<Thrift>),
+    Struct(Vec<(i16, Thrift)>),
+}
+
+impl Thrift {
+    fn field(&self, id: i16) -> Option<&Thrift> {
+        match self {
+            Thrift::Struct(fields) => fields.iter().find(|(i, _)| *i == id).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// A required field.
+    fn req(&self, id: i16) -> Result<&Thrift> {
+        self.field(id).ok_or_else(|| format!("corrupt Parquet metadata: missing field {}", id).into())
+    }
+
+    fn opt_int(&self, id: i16) -> Option<i64> {
+        self.field(id).and_then(|v| v.int().ok())
+    }
+
+    fn int(&self) -> Result<i64> {
+        match self {
+            Thrift::I32(v) => Ok(*v as i64),
+            Thrift::I64(v) => Ok(*v),
+            _ => Err("corrupt Parquet metadata: expected an integer".into()),
+        }
+    }
+
+    fn binary(&self) -> Result<&[u8]> {
+        match self {
+            Thrift::Binary(v) => Ok(v),
+            _ => Err("corrupt Parquet metadata: expected a string".into()),
+        }
+    }
+
+    fn list(&self) -> Result<&[Thrift]> {
+        match self {
+            Thrift::List(v) => Ok(v),
+            _ => Err("corrupt Parquet metadata: expected a list".into()),
+        }
+    }
+
+    fn wire_type(&self) -> u8 {
+        match self {
+            Thrift::Bool(true) => 1,
+            Thrift::Bool(false) => 2,
+            Thrift::I32(_) => 5,
+            Thrift::I64(_) => 6,
+            Thrift::Double(_) => 7,
+            Thrift::Binary(_) => 8,
+            Thrift::List(_) => 9,
+            Thrift::Struct(_) => 12,
+        }
+    }
+
+    /// Encode the value body (booleans inside a struct live in the field header).
+    fn write(&self, out: &mut Vec<u8>) {
+        let zigzag = |v: i64| ((v << 1) ^ (v >> 63)) as u64;
+        match self {
+            Thrift::Bool(b) => out.push(if *b { 1 } else { 2 }),
+            Thrift::I32(v) => write_varint(out, zigzag(*v as i64)),
+            Thrift::I64(v) => write_varint(out, zigzag(*v)),
+            Thrift::Double(v) => out.extend(v.to_le_bytes()),
+            Thrift::Binary(b) => {
+                write_varint(out, b.len() as u64);
+                out.extend_from_slice(b);
+            }
+            Thrift::List(items) => {
+                let ty = items.first().map_or(12, |v| match v {
+                    Thrift::Bool(_) => 1,
+                    v => v.wire_type(),
+                });
+                if items.len() < 15 {
+                    out.push((items.len() as u8) << 4 | ty);
+                } else {
+                    out.push(0xf0 | ty);
+                    write_varint(out, items.len() as u64);
+                }
+                items.iter().for_each(|v| v.write(out));
+            }
+            Thrift::Struct(fields) => {
+                let mut last = 0i16;
+                for (id, v) in fields {
+                    let delta = id - last;
+                    if (1..=15).contains(&delta) {
+                        out.push((delta as u8) << 4 | v.wire_type());
+                    } else {
+                        out.push(v.wire_type());
+                        write_varint(out, zigzag(*id as i64));
+                    }
+                    last = *id;
+                    if !matches!(v, Thrift::Bool(_)) {
+                        v.write(out);
+                    }
+                }
+                out.push(0);
+            }
+        }
+    }
+}
+
+struct ThriftReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ThriftReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        ThriftReader { buf, pos: 0 }
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        let b = *self.buf.get(self.pos).ok_or("truncated Parquet metadata")?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn zigzag(&mut self) -> Result<i64> {
+        let v = read_varint(self.buf, &mut self.pos)?;
+        Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+    }
+
+    fn value(&mut self, ty: u8, depth: usize) -> Result<Thrift> {
+        if depth > 64 {
+            return Err("Parquet metadata nested too deeply".into());
+        }
+        Ok(match ty {
+            1 | 2 => Thrift::Bool(self.byte()? == 1),
+            3 => Thrift::I32(self.byte()? as i8 as i32),
+            4 | 5 => Thrift::I32(self.zigzag()? as i32),
+            6 => Thrift::I64(self.zigzag()?),
+            7 => {
+                let b = self.buf.get(self.pos..self.pos + 8).ok_or("truncated Parquet metadata")?;
+                self.pos += 8;
+                Thrift::Double(f64::from_le_bytes(b.try_into().unwrap()))
+            }
+            8 => {
+                let len = read_varint(self.buf, &mut self.pos)? as usize;
+                let b = self.buf.get(self.pos..self.pos + len).ok_or("truncated Parquet metadata")?;
+                self.pos += len;
+                Thrift::Binary(b.to_vec())
+            }
+            9 | 10 => {
+                let h = self.byte()?;
+                let mut n = (h >> 4) as usize;
+                if n == 15 {
+                    n = read_varint(self.buf, &mut self.pos)? as usize;
+                }
+                let items = (0..n).map(|_| self.value(h & 0xf, depth + 1)).collect::<Result<_>>()?;
+                Thrift::List(items)
+            }
+            11 => {
+                // Maps do not appear in Parquet metadata; decode to skip them.
+                let n = read_varint(self.buf, &mut self.pos)? as usize;
+                let mut items = Vec::new();
+                if n > 0 {
+                    let kv = self.byte()?;
+                    for _ in 0..n {
+                        items.push(self.value(kv >> 4, depth + 1)?);
+                        items.push(self.value(kv & 0xf, depth + 1)?);
+                    }
+                }
+                Thrift::List(items)
+            }
+            12 => self.read_struct(depth + 1)?,
+            t => return Err(format!("corrupt Parquet metadata: unknown thrift type {}", t).into()),
+        })
+    }
+
+    fn read_struct(&mut self, depth: usize) -> Result<Thrift> {
+        let mut fields = Vec::new();
+        let mut last = 0i16;
+        loop {
+            let h = self.byte()?;
+            if h == 0 {
+                return Ok(Thrift::Struct(fields));
+            }
+            let ty = h & 0xf;
+            let id = match h >> 4 {
+                0 => self.zigzag()? as i16,
+                delta => last + delta as i16,
+            };
+            last = id;
+            let v = match ty {
+                1 => Thrift::Bool(true),
+                2 => Thrift::Bool(false),
+                _ => self.value(ty, depth)?,
+            };
+            fields.push((id, v));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rle_hybrid_and_snappy() {
+        // Spec example: 0..=7 bit-packed with width 3, then a run of five 4s.
+        let buf = [0x03, 0x88, 0xc6, 0xfa, 0x0a, 0x04];
+        assert_eq!(decode_rle_hybrid(&buf, 3, 13).unwrap(), vec![0, 1, 2, 3, 4, 5, 6, 7, 4, 4, 4, 4, 4]);
+
+        // "abcd" literal, then an overlapping 1-byte-offset copy of 8 bytes at distance 4.
+        let block = [12, 0x0c, b'a', b'b', b'c', b'd', 0x11, 0x04];
+        assert_eq!(snappy_decompress(&block).unwrap(), b"abcdabcdabcd");
+        assert!(decompress(2, &block, 12).is_err());
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let batch = RecordBatch::new(
+            vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("x", DataType::Float64, true),
+                Field::new("flag", DataType::Boolean, false),
+                Field::new("city", DataType::Utf8, true),
+                Field::new("y", DataType::Int32, false),
+            ],
+            vec![
+                Array::Int64((0..10).map(Some).collect()),
+                Array::Float64((0..10).map(|i| if i == 3 { None } else { Some(i as f64 * 0.5) }).collect()),
+                Array::Boolean((0..10).map(|i| Some(i % 3 == 0)).collect()),
+                Array::Utf8((0..10).map(|i| if i % 4 == 0 { None } else { Some(format!("c{}", i)) }).collect()),
+                Array::Int32((0..10).map(|i| Some(i % 2)).collect()),
+            ],
+        )
+        .unwrap();
+
+        let reader = ParquetReader::from_bytes(encode_parquet(&batch).unwrap()).unwrap();
+        assert_eq!(reader.num_rows(), 10);
+        assert_eq!(reader.schema(), batch.schema());
+        assert_eq!(reader.read_row_group(0, None).unwrap(), batch);
+
+        let path = std::env::temp_dir().join("oxidize_ml_parquet_test.parquet");
+        let path = path.to_str().unwrap();
+        write_parquet(path, &batch).unwrap();
+        let t: Tensor<f64> = load_parquet_to_tensor(path, Some(&["x", "flag"])).unwrap();
+        assert_eq!(t.shape_vec(), vec![10, 2]);
+        assert_eq!(t.get(&[2, 0]).unwrap(), 1.0);
+        assert!(t.get(&[3, 0]).unwrap().is_nan());
+        assert_eq!(t.get(&[3, 1]).unwrap(), 1.0);
+        assert!(load_parquet_to_tensor::<f64>(path, Some(&["city"])).is_err());
+
+        let ds = TensorDataset::from_parquet(path, &["id", "flag"], "y").unwrap();
+        assert_eq!(ds.labels.data()[..3], [0.0, 1.0, 0.0]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Written by `tests/fixtures/make_parquet.py` in pyarrow's layout:
+    /// three row groups of Snappy-compressed, dictionary-encoded v2 pages
+    /// with nulls, including a dictionary that falls back to PLAIN.
+    const MIXED: &[u8] = include_bytes!("../tests/fixtures/mixed.parquet");
+
+    #[test]
+    fn test_pyarrow_layout_fixture() {
+        let reader = ParquetReader::from_bytes(MIXED.to_vec()).unwrap();
+        assert_eq!(reader.num_rows(), 10);
+        assert_eq!(reader.num_row_groups(), 3);
+        assert_eq!(
+            reader.schema(),
+            vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("x", DataType::Float64, true),
+                Field::new("name", DataType::Utf8, true),
+                Field::new("flag", DataType::Boolean, false),
+                Field::new("y", DataType::Int32, false),
+            ]
+        );
+
+        let cities = ["paris", "tokyo", "lima"];
+        for g in 0..3 {
+            let batch = reader.read_row_group(g, None).unwrap();
+            let range = 4 * g..(4 * g + 4).min(10);
+            let expected = [
+                Array::Int64(range.clone().map(|i| Some(100 + i as i64)).collect()),
+                Array::Float64(range.clone().map(|i| (i != 3 && i != 7).then_some(i as f64 * 0.25)).collect()),
+                Array::Utf8(
+                    range
+                        .clone()
+                        .map(|i| (i % 4 != 0 && i != 9).then(|| format!("city_{}", cities[i % 3])))
+                        .collect(),
+                ),
+                Array::Boolean(range.clone().map(|i| Some(i % 3 == 0)).collect()),
+                Array::Int32(range.map(|i| Some(i as i32 % 2)).collect()),
+            ];
+            for (name, want) in ["id", "x", "name", "flag", "y"].iter().zip(&expected) {
+                assert_eq!(batch.column_by_name(name).unwrap(), want, "column {} of row group {}", name, g);
+            }
+        }
+        // The last row group's strings are all null, behind an empty dictionary.
+        let last = reader.read_row_group(2, Some(&["name"])).unwrap();
+        assert_eq!(last.column(0), &Array::Utf8(vec![None, None]));
+    }
+
+    /// A GZIP-compressed chunk (`gzip`) and a DELTA_BINARY_PACKED page
+    /// (`delta`) next to a plain column (`a`).
+    const UNSUPPORTED: &[u8] = include_bytes!("../tests/fixtures/unsupported.parquet");
+
+    #[test]
+    fn test_unsupported_codec_and_encoding() {
+        let reader = ParquetReader::from_bytes(UNSUPPORTED.to_vec()).unwrap();
+        let batch = reader.read_row_group(0, Some(&["a"])).unwrap();
+        assert_eq!(batch.column_by_name("a").unwrap(), &Array::Int32(vec![Some(1), Some(2), Some(3)]));
+
+        let err = reader.read_row_group(0, Some(&["gzip"])).unwrap_err().to_string();
+        assert!(err.contains("codec 2 is not supported"), "{}", err);
+        let err = reader.read_row_group(0, Some(&["delta"])).unwrap_err().to_string();
+        assert!(err.contains("encoding 5 is not supported"), "{}", err);
+        assert!(reader.read_row_group(0, None).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use oxidize_ml_core::{RngHandle, Tensor};
+use rand::Rng;
+use std::sync::Mutex;
+
+use crate::dataset::Dataset;
+
+/// A per-sample feature transform, applied lazily by `Dataset::map`.
+///
+/// Random transforms draw fresh randomness on every call, so each epoch sees
+/// a different augmentation of the same sample. Shape mismatches panic, like
+/// indexing into a `Dataset` out of range.
+pub trait Transform {
+    fn apply(&self, x: Tensor<f64>) -> Tensor<f64>;
+}
+
+impl<F: Fn(Tensor<f64>) -> Tensor<f64>> Transform for F {
+    fn apply(&self, x: Tensor<f64>) -> Tensor<f64> {
+        self(x)
+    }
+}
+
+/// A dataset whose features are passed through a transform on access.
+pub struct MappedDataset<D: Dataset, F: Transform> {
+    pub dataset: D,
+    pub transform: F,
+}
+
+impl<D: Dataset, F: Transform> Dataset for MappedDataset<D, F> {
+    fn len(&self) -> usize {
+        self.dataset.len()
+    }
+
+    fn get(&self, idx: usize) -> (Tensor<f64>, Tensor<f64>) {
+        let (x, y) = self.dataset.get(idx);
+        (self.transform.apply(x), y)
+    }
+}
+
+/// Transforms applied in sequence.
+#[derive(Default)]
+pub struct Compose {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Compose {
+    pub fn new() -> Self {
+        Compose { transforms: Vec::new() }
+    }
+
+    /// Append a transform to the end of the chain.
+    pub fn then<F: Transform + 'static>(mut self, transform: F) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+}
+
+impl Transform for Compose {
+    fn apply(&self, x: Tensor<f64>) -> Tensor<f64> {
+        self.transforms.iter().fold(x, |x, t| t.apply(x))
+    }
+}
+
+/// `(x - mean) / std`. For 1-D samples the statistics are per feature; for
+/// `[C, H, W]` images they are per channel. A single value applies to every
+/// element.
+pub struct Normalize {
+    pub mean: Vec<f64>,
+    pub std: Vec<f64>,
+}
+
+impl Normalize {
+    pub fn new(mean: Vec<f64>, std: Vec<f64>) -> Self {
+        assert_eq!(mean.len(), std.len(), "mean and std must have the same length");
+        Normalize { mean, std }
+    }
+}
+
+impl Transform for Normalize {
+    fn apply(&self, x: Tensor<f64>) -> Tensor<f64> {
+        let dims = x.shape_vec();
+        let groups = if self.mean.len() == 1 { 1 } else { dims[0] };
+        assert_eq!(
+            self.mean.len(),
+            groups,
+            "Normalize has {} statistics for a sample of shape {:?}",
+            self.mean.len(),
+            dims
+        );
+        let per_group = x.numel() / groups;
+        let data = x
+            .data()
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let g = i / per_group;
+                (v - self.mean[g]) / self.std[g]
+            })
+            .collect();
+        Tensor::new(data, dims).expect("same shape")
+    }
+}
+
+/// Adds `N(0, std²)` noise to every element.
+pub struct GaussianNoise {
+    pub std: f64,
+    rng: Mutex<RngHandle>,
+}
+
+impl GaussianNoise {
+    pub fn new(std: f64, seed: Option<u64>) -> Self {
+        GaussianNoise {
+            std,
+            rng: Mutex::new(RngHandle::new(seed)),
+        }
+    }
+}
+
+impl Transform for GaussianNoise {
+    fn apply(&self, x: Tensor<f64>) -> Tensor<f64> {
+        let seed = self.rng.lock().unwrap().gen();
+        let noise = Tensor::<f64>::randn(x.shape_vec(), Some(seed));
+        let data = x.data().iter().zip(noise.data()).map(|(v, n)| v + self.std * n).collect();
+        Tensor::new(data, x.shape_vec()).expect("same shape")
+    }
+}
+
+/// Mirrors an image (`[C, H, W]` or `[H, W]`) left to right with probability `p`.
+pub struct RandomHorizontalFlip {
+    pub p: f64,
+    rng: Mutex<RngHandle>,
+}
+
+impl RandomHorizontalFlip {
+    pub fn new(p: f64, seed: Option<u64>) -> Self {
+        RandomHorizontalFlip {
+            p,
+            rng: Mutex::new(RngHandle::new(seed)),
+        }
+    }
+}
+
+impl Transform for RandomHorizontalFlip {
+    fn apply(&self, x: Tensor<f64>) -> Tensor<f64> {
+        if self.rng.lock().unwrap().gen::<f64>() < self.p {
+            x.flip(x.ndim() - 1).expect("flip last axis")
+        } else {
+            x
+        }
+    }
+}
+
+/// Crops a random `height × width` window from an image (`[C, H, W]` or
+/// `[H, W]`) after zero-padding each spatial border by `padding`.
+pub struct RandomCrop {
+    pub height: usize,
+    pub width: usize,
+    pub padding: usize,
+    rng: Mutex<RngHandle>,
+}
+
+impl RandomCrop {
+    pub fn new(height: usize, width: usize, padding: usize, seed: Option<u64>) -> Self {
+        RandomCrop {
+            height,
+            width,
+            padding,
+            rng: Mutex::new(RngHandle::new(seed)),
+        }
+    }
+}
+
+impl Transform for RandomCrop {
+    fn apply(&self, x: Tensor<f64>) -> Tensor<f64> {
+        let nd = x.ndim();
+        assert!(nd >= 2, "RandomCrop expects an image of shape [C, H, W] or [H, W]");
+        let mut pad = vec![(0, 0); nd];
+        pad[nd - 2] = (self.padding, self.padding);
+        pad[nd - 1] = (self.padding, self.padding);
+        let padded = x.pad(&pad, 0.0).expect("pad image");
+
+        let dims = padded.shape_vec();
+        let (h, w) = (dims[nd - 2], dims[nd - 1]);
+        assert!(
+            self.height <= h && self.width <= w,
+            "crop {}x{} is larger than the padded image {}x{}",
+            self.height,
+            self.width,
+            h,
+            w
+        );
+        let mut rng = self.rng.lock().unwrap();
+        let top = rng.gen_range(0..=h - self.height);
+        let left = rng.gen_range(0..=w - self.width);
+        padded
+            .view()
+            .slice_axis(nd - 2, top, top + self.height)
+            .and_then(|v| v.slice_axis(nd - 1, left, left + self.width))
+            .expect("crop window within bounds")
+            .contiguous()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::TensorDataset;
+
+    #[test]
+    fn test_map_and_compose() {
+        let x = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let y = Tensor::new(vec![0.0, 1.0], vec![2]).unwrap();
+        let ds = TensorDataset::new(x, y).map(
+            Compose::new()
+                .then(Normalize::new(vec![1.0, 2.0], vec![2.0, 2.0]))
+                .then(|x: Tensor<f64>| x.mul_scalar(10.0)),
+        );
+        assert_eq!(ds.len(), 2);
+        let (x1, y1) = ds.get(1);
+        assert_eq!(x1.data(), &[10.0, 10.0]);
+        assert_eq!(y1.item().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_image_augmentation() {
+        // One 2x3 grayscale image.
+        let img = Tensor::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![1, 2, 3]).unwrap();
+
+        let flipped = RandomHorizontalFlip::new(1.0, Some(0)).apply(img.clone());
+        assert_eq!(flipped.data(), &[3.0, 2.0, 1.0, 6.0, 5.0, 4.0]);
+        assert_eq!(RandomHorizontalFlip::new(0.0, Some(0)).apply(img.clone()).data(), img.data());
+
+        let crop = RandomCrop::new(2, 3, 1, Some(5));
+        for _ in 0..10 {
+            let c = crop.apply(img.clone());
+            assert_eq!(c.shape_vec(), vec![1, 2, 3]);
+            assert!(c.data().iter().all(|v| (0.0..=6.0).contains(v)));
+        }
+
+        let noisy = GaussianNoise::new(0.1, Some(1));
+        let (a, b) = (noisy.apply(img.clone()), noisy.apply(img.clone()));
+        assert_ne!(a.data(), b.data());
+        assert!(a.data().iter().zip(img.data()).all(|(n, o)| (n - o).abs() < 1.0));
+
+        // Batches keep the per-sample image shape.
+        let images = Tensor::new((0..12).map(|v| v as f64).collect(), vec![3, 1, 2, 2]).unwrap();
+        let labels = Tensor::new(vec![0.0, 1.0, 2.0], vec![3]).unwrap();
+        let ds = TensorDataset::new(images, labels).map(RandomHorizontalFlip::new(1.0, None));
+        let (x, _) = crate::DataLoader::new(&ds, 2, false).next().unwrap();
+        assert_eq!(x.shape_vec(), vec![2, 1, 2, 2]);
+        assert_eq!(&x.data()[..4], &[1.0, 0.0, 3.0, 2.0]);
+    }
+}
@@ -0,0 +1,343 @@
+#!/usr/bin/env python3
+"""Write the Parquet fixtures read by `parquet.rs`'s tests.
+
+No Parquet library is needed: the files are assembled byte by byte, which
+keeps them independent of the reader under test. `mixed.parquet` follows
+what pyarrow writes for
+
+    pq.write_table(table, path, row_group_size=4, compression="snappy",
+                   data_page_version="2.0")
+
+dictionary pages for every non-boolean column, RLE_DICTIONARY v2 data
+pages with Snappy-compressed values, RLE booleans, statistics and
+encoding stats in the chunk metadata, and pyarrow's fallback to PLAIN
+pages once a dictionary is full (column `id` in the second row group).
+`unsupported.parquet` has a GZIP chunk and a DELTA_BINARY_PACKED page,
+which the reader rejects.
+
+Run from this directory: `python3 make_parquet.py`.
+"""
+
+import struct
+
+# Physical types, repetitions, encodings, codecs, page types.
+BOOLEAN, INT32, INT64, DOUBLE, BYTE_ARRAY = 0, 1, 2, 5, 6
+REQUIRED, OPTIONAL = 0, 1
+PLAIN, PLAIN_DICTIONARY, RLE, DELTA_BINARY_PACKED, RLE_DICTIONARY = 0, 2, 3, 5, 8
+UNCOMPRESSED, SNAPPY, GZIP = 0, 1, 2
+DATA_PAGE_V2, DICTIONARY_PAGE = 3, 2
+UTF8 = 0
+
+
+# Thrift compact protocol. A struct is a list of (id, type, value); types
+# are the compact wire types.
+T_TRUE, T_FALSE, T_I16, T_I32, T_I64, T_BINARY, T_LIST, T_STRUCT = 1, 2, 4, 5, 6, 8, 9, 12
+
+
+def varint(v):
+    out = bytearray()
+    while True:
+        b = v & 0x7F
+        v >>= 7
+        if v:
+            out.append(b | 0x80)
+        else:
+            out.append(b)
+            return bytes(out)
+
+
+def zigzag(v):
+    return varint((v << 1) ^ (v >> 63))
+
+
+def value(ty, v):
+    if ty in (T_I16, T_I32, T_I64):
+        return zigzag(v)
+    if ty == T_BINARY:
+        v = v.encode() if isinstance(v, str) else v
+        return varint(len(v)) + v
+    if ty == T_STRUCT:
+        return struct_(v)
+    if ty == T_LIST:
+        elem, items = v
+        head = bytes([(len(items) << 4) | elem]) if len(items) < 15 else bytes([0xF0 | elem]) + varint(len(items))
+        return head + b"".join(value(elem, x) for x in items)
+    raise ValueError(ty)
+
+
+def struct_(fields):
+    out, last = bytearray(), 0
+    for fid, ty, v in fields:
+        if v is None:
+            continue
+        wire = (T_TRUE if v else T_FALSE) if ty == T_TRUE else ty
+        delta = fid - last
+        if 0 < delta <= 15:
+            out.append((delta << 4) | wire)
+        else:
+            out.append(wire)
+            out += zigzag(fid)
+        if ty != T_TRUE:
+            out += value(ty, v)
+        last = fid
+    out.append(0)
+    return bytes(out)
+
+
+# RLE/bit-packed hybrid, as parquet-cpp emits it: runs of eight or more
+# equal values become RLE runs, everything else groups of eight bit-packed.
+def rle_hybrid(values, width):
+    def run_at(i):
+        r = 1
+        while i + r < len(values) and values[i + r] == values[i]:
+            r += 1
+        return r
+
+    out, i, n = bytearray(), 0, len(values)
+    while i < n:
+        run = run_at(i)
+        if run >= 8:
+            out += varint(run << 1) + values[i].to_bytes((width + 7) // 8, "little")
+            i += run
+            continue
+        # Bit-pack whole groups of eight until a long run starts at a group boundary.
+        j = i + 8
+        while j < n and run_at(j) < 8:
+            j += 8
+        j = min(j, n)
+        groups = (j - i + 7) // 8
+        bits = 0
+        for k, v in enumerate(values[i:j]):
+            bits |= v << (k * width)
+        out += varint((groups << 1) | 1) + bits.to_bytes(groups * width, "little")
+        i = j
+    return bytes(out)
+
+
+# Snappy: greedy 4-byte hash matching into literals and 1/2-byte-offset copies.
+def snappy(data):
+    out = bytearray(varint(len(data)))
+
+    def literal(lit):
+        n = len(lit) - 1
+        if n < 60:
+            out.append(n << 2)
+        else:
+            out.append(60 << 2)
+            out.append(n)
+        out.extend(lit)
+
+    table, i, start = {}, 0, 0
+    while i + 4 <= len(data):
+        key = data[i:i + 4]
+        cand = table.get(key)
+        table[key] = i
+        if cand is None or i - cand > 0xFFFF:
+            i += 1
+            continue
+        length = 4
+        while i + length < len(data) and data[cand + length] == data[i + length] and length < 64:
+            length += 1
+        if start < i:
+            for k in range(start, i, 60):
+                literal(data[k:min(k + 60, i)])
+        offset = i - cand
+        if 4 <= length <= 11 and offset < 2048:
+            out.append(((offset >> 8) << 5) | ((length - 4) << 2) | 1)
+            out.append(offset & 0xFF)
+        else:
+            out.append(((length - 1) << 2) | 2)
+            out += struct.pack("<H", offset)
+        i += length
+        start = i
+    for k in range(start, len(data), 60):
+        literal(data[k:min(k + 60, len(data))])
+    return bytes(out)
+
+
+def plain(physical, values):
+    if physical == BOOLEAN:
+        bits = 0
+        for k, v in enumerate(values):
+            bits |= int(v) << k
+        return bits.to_bytes((len(values) + 7) // 8, "little")
+    if physical == INT32:
+        return b"".join(struct.pack("<i", v) for v in values)
+    if physical == INT64:
+        return b"".join(struct.pack("<q", v) for v in values)
+    if physical == DOUBLE:
+        return b"".join(struct.pack("<d", v) for v in values)
+    return b"".join(struct.pack("<I", len(v.encode())) + v.encode() for v in values)
+
+
+def compress(codec, data):
+    if codec == SNAPPY:
+        return snappy(data)
+    if codec == GZIP:
+        import gzip
+        return gzip.compress(data, mtime=0)
+    return data
+
+
+def page(ptype, uncompressed, body, header_field, header):
+    return struct_([
+        (1, T_I32, ptype),
+        (2, T_I32, uncompressed),
+        (3, T_I32, len(body)),
+        (header_field, T_STRUCT, header),
+    ]) + body
+
+
+def v2_page(values, optional, physical, codec, encoding, dictionary=None):
+    """A data page v2 for `values` (None for null), returning its bytes."""
+    present = [v for v in values if v is not None]
+    levels = rle_hybrid([int(v is not None) for v in values], 1) if optional else b""
+    if encoding == RLE_DICTIONARY:
+        indices = [dictionary.index(v) for v in present]
+        width = max(1, (len(dictionary) - 1).bit_length()) if dictionary else 0
+        raw = bytes([width]) + (rle_hybrid(indices, width) if indices else b"")
+    elif encoding == RLE:
+        packed = rle_hybrid([int(v) for v in present], 1)
+        raw = struct.pack("<I", len(packed)) + packed
+    elif encoding == DELTA_BINARY_PACKED:
+        raw = b"\x80\x01\x04" + varint(len(present)) + zigzag(present[0] if present else 0)
+    else:
+        raw = plain(physical, present)
+    body = compress(codec, raw)
+    header = [
+        (1, T_I32, len(values)),
+        (2, T_I32, len(values) - len(present)),
+        (3, T_I32, len(values)),
+        (4, T_I32, encoding),
+        (5, T_I32, len(levels)),
+        (6, T_I32, 0),
+        (7, T_TRUE, codec != UNCOMPRESSED),
+    ]
+    return page(DATA_PAGE_V2, len(levels) + len(raw), levels + body, 8, header)
+
+
+def dict_page(physical, dictionary, codec):
+    raw = plain(physical, dictionary)
+    header = [(1, T_I32, len(dictionary)), (2, T_I32, PLAIN), (3, T_TRUE, False)]
+    return page(DICTIONARY_PAGE, len(raw), compress(codec, raw), 7, header)
+
+
+def write(path, columns, rows, row_group_size, chunk_plan):
+    """`columns` are (name, physical, optional, converted); `rows[c]` the
+    values of column c; `chunk_plan(c, g, values)` returns the pages of a
+    chunk as (is_dictionary, bytes) plus its codec and encodings."""
+    out = bytearray(b"PAR1")
+    row_groups = []
+    n = len(rows[0])
+    for g, lo in enumerate(range(0, n, row_group_size)):
+        hi = min(lo + row_group_size, n)
+        chunks, group_bytes = [], 0
+        for c, (name, physical, optional, _) in enumerate(columns):
+            pages, codec, encodings = chunk_plan(c, g, rows[c][lo:hi])
+            start = len(out)
+            dict_offset = start if pages[0][0] else None
+            data_offset = start + (len(pages[0][1]) if pages[0][0] else 0)
+            for _, p in pages:
+                out += p
+            size = len(out) - start
+            group_bytes += size
+            present = [v for v in rows[c][lo:hi] if v is not None]
+            stats = None
+            if present and physical != BOOLEAN:
+                lo_v, hi_v = min(present), max(present)
+                stats = [(5, T_BINARY, plain(physical, [hi_v])), (6, T_BINARY, plain(physical, [lo_v])),
+                         (3, T_I64, hi - lo - len(present))]
+                stats.sort()
+            meta = [
+                (1, T_I32, physical),
+                (2, T_LIST, (T_I32, encodings)),
+                (3, T_LIST, (T_BINARY, [name])),
+                (4, T_I32, codec),
+                (5, T_I64, hi - lo),
+                (6, T_I64, size),
+                (7, T_I64, size),
+                (9, T_I64, data_offset),
+                (11, T_I64, dict_offset),
+                (12, T_STRUCT, stats),
+                (13, T_LIST, (T_STRUCT, [[(1, T_I32, DATA_PAGE_V2), (2, T_I32, e), (3, T_I32, 1)]
+                                         for e in encodings if e != RLE])),
+            ]
+            chunks.append([(2, T_I64, start), (3, T_STRUCT, meta)])
+        row_groups.append([
+            (1, T_LIST, (T_STRUCT, chunks)),
+            (2, T_I64, group_bytes),
+            (3, T_I64, hi - lo),
+            (7, T_I16, g),
+        ])
+    schema = [[(4, T_BINARY, "schema"), (5, T_I32, len(columns))]]
+    for name, physical, optional, converted in columns:
+        element = [(1, T_I32, physical), (3, T_I32, OPTIONAL if optional else REQUIRED), (4, T_BINARY, name)]
+        if converted is not None:
+            # ConvertedType UTF8 and LogicalType STRING (an empty struct in a union).
+            element += [(6, T_I32, converted), (10, T_STRUCT, [(1, T_STRUCT, [])])]
+        schema.append(element)
+    footer = struct_([
+        (1, T_I32, 2),
+        (2, T_LIST, (T_STRUCT, schema)),
+        (3, T_I64, n),
+        (4, T_LIST, (T_STRUCT, row_groups)),
+        (5, T_LIST, (T_STRUCT, [[(1, T_BINARY, "writer"), (2, T_BINARY, "make_parquet.py")]])),
+        (6, T_BINARY, "make_parquet.py (pyarrow layout)"),
+    ])
+    out += footer + struct.pack("<I", len(footer)) + b"PAR1"
+    with open(path, "wb") as f:
+        f.write(out)
+
+
+CITIES = ["paris", "tokyo", "lima"]
+MIXED_COLUMNS = [
+    ("id", INT64, False, None),
+    ("x", DOUBLE, True, None),
+    ("name", BYTE_ARRAY, True, UTF8),
+    ("flag", BOOLEAN, False, None),
+    ("y", INT32, False, None),
+]
+MIXED_ROWS = [
+    [100 + i for i in range(10)],
+    [None if i in (3, 7) else i * 0.25 for i in range(10)],
+    [None if i % 4 == 0 or i == 9 else "city_" + CITIES[i % 3] for i in range(10)],
+    [i % 3 == 0 for i in range(10)],
+    [i % 2 for i in range(10)],
+]
+
+
+def mixed_plan(c, g, values):
+    name, physical, optional, _ = MIXED_COLUMNS[c]
+    if physical == BOOLEAN:
+        return [(False, v2_page(values, optional, physical, SNAPPY, RLE))], SNAPPY, [RLE]
+    dictionary = []
+    for v in values:
+        if v is not None and v not in dictionary:
+            dictionary.append(v)
+    if name == "id" and g == 1:
+        # The dictionary filled up after two values; the rest is PLAIN.
+        dictionary = dictionary[:2]
+        pages = [
+            (True, dict_page(physical, dictionary, SNAPPY)),
+            (False, v2_page(values[:2], optional, physical, SNAPPY, RLE_DICTIONARY, dictionary)),
+            (False, v2_page(values[2:], optional, physical, SNAPPY, PLAIN)),
+        ]
+        return pages, SNAPPY, [PLAIN, RLE, RLE_DICTIONARY]
+    pages = [
+        (True, dict_page(physical, dictionary, SNAPPY)),
+        (False, v2_page(values, optional, physical, SNAPPY, RLE_DICTIONARY, dictionary)),
+    ]
+    return pages, SNAPPY, [PLAIN, RLE, RLE_DICTIONARY]
+
+
+UNSUPPORTED_COLUMNS = [("a", INT32, False, None), ("gzip", INT32, False, None), ("delta", INT32, False, None)]
+
+
+def unsupported_plan(c, g, values):
+    codec, encoding = [(UNCOMPRESSED, PLAIN), (GZIP, PLAIN), (UNCOMPRESSED, DELTA_BINARY_PACKED)][c]
+    return [(False, v2_page(values, False, INT32, codec, encoding))], codec, [encoding]
+
+
+if __name__ == "__main__":
+    write("mixed.parquet", MIXED_COLUMNS, MIXED_ROWS, 4, mixed_plan)
+    write("unsupported.parquet", UNSUPPORTED_COLUMNS, [[1, 2, 3]] * 3, 3, unsupported_plan)
@@ -0,0 +1,344 @@
+//! Just enough of gzip, tar and MD5 to unpack the benchmark dataset archives.
+
+use std::error::Error;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+// ---------------------------------------------------------------------------
+// DEFLATE (RFC 1951) and gzip (RFC 1952)
+// ---------------------------------------------------------------------------
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> Result<u32> {
+        let mut v = 0;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos).ok_or("truncated deflate stream")?;
+            v |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(v)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// Canonical Huffman code: the number of codes of each length and the
+/// symbols in code order.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &l in lengths {
+            counts[l as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for i in 1..15 {
+            offsets[i + 1] = offsets[i] + counts[i];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (s, &l) in lengths.iter().enumerate() {
+            if l != 0 {
+                symbols[offsets[l as usize] as usize] = s as u16;
+                offsets[l as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, r: &mut BitReader) -> Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= r.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code in deflate stream".into())
+    }
+}
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097,
+    6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Decompress a raw DEFLATE stream, returning the output and the number of
+/// input bytes consumed.
+pub fn inflate(data: &[u8]) -> Result<(Vec<u8>, usize)> {
+    let mut r = BitReader { data, pos: 0, bit: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = r.bits(1)? == 1;
+        match r.bits(2)? {
+            0 => {
+                r.align();
+                let header = data.get(r.pos..r.pos + 4).ok_or("truncated deflate stream")?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if len != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err("corrupt stored deflate block".into());
+                }
+                out.extend_from_slice(data.get(r.pos + 4..r.pos + 4 + len).ok_or("truncated deflate stream")?);
+                r.pos += 4 + len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                inflate_block(&mut r, &mut out, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let hlit = r.bits(5)? as usize + 257;
+                let hdist = r.bits(5)? as usize + 1;
+                let hclen = r.bits(4)? as usize + 4;
+                const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+                let mut cl = [0u8; 19];
+                for &i in &ORDER[..hclen] {
+                    cl[i] = r.bits(3)? as u8;
+                }
+                let cl = Huffman::new(&cl);
+                let mut lengths = Vec::with_capacity(hlit + hdist);
+                while lengths.len() < hlit + hdist {
+                    let sym = cl.decode(&mut r)?;
+                    let (value, repeat) = match sym {
+                        0..=15 => (sym as u8, 1),
+                        16 => (*lengths.last().ok_or("corrupt deflate code lengths")?, 3 + r.bits(2)?),
+                        17 => (0, 3 + r.bits(3)?),
+                        _ => (0, 11 + r.bits(7)?),
+                    };
+                    lengths.extend(std::iter::repeat_n(value, repeat as usize));
+                }
+                if lengths.len() > hlit + hdist {
+                    return Err("corrupt deflate code lengths".into());
+                }
+                let lit = Huffman::new(&lengths[..hlit]);
+                let dist = Huffman::new(&lengths[hlit..]);
+                inflate_block(&mut r, &mut out, &lit, &dist)?;
+            }
+            _ => return Err("invalid deflate block type".into()),
+        }
+        if last {
+            r.align();
+            return Ok((out, r.pos));
+        }
+    }
+}
+
+fn inflate_block(r: &mut BitReader, out: &mut Vec<u8>, lit: &Huffman, dist: &Huffman) -> Result<()> {
+    loop {
+        let sym = lit.decode(r)? as usize;
+        match sym {
+            0..=255 => out.push(sym as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = sym - 257;
+                if i >= 29 {
+                    return Err("invalid deflate length code".into());
+                }
+                let len = LEN_BASE[i] as usize + r.bits(LEN_EXTRA[i] as u32)? as usize;
+                let d = dist.decode(r)? as usize;
+                if d >= 30 {
+                    return Err("invalid deflate distance code".into());
+                }
+                let distance = DIST_BASE[d] as usize + r.bits(DIST_EXTRA[d] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("deflate distance reaches before the start of the output".into());
+                }
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+        }
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let mut c = i as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *entry = c;
+    }
+    !data.iter().fold(!0u32, |c, &b| table[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8))
+}
+
+/// Decompress a gzip file (all members, so concatenated `.gz` files work),
+/// checking each member's CRC-32 and length.
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let header = data.get(pos..pos + 10).ok_or("truncated gzip header")?;
+        if header[0] != 0x1f || header[1] != 0x8b || header[2] != 8 {
+            return Err("not a gzip file".into());
+        }
+        let flags = header[3];
+        pos += 10;
+        if flags & 4 != 0 {
+            let xlen = data.get(pos..pos + 2).ok_or("truncated gzip header")?;
+            pos += 2 + u16::from_le_bytes([xlen[0], xlen[1]]) as usize;
+        }
+        for flag in [8, 16] {
+            if flags & flag != 0 {
+                let end = data[pos.min(data.len())..].iter().position(|&b| b == 0).ok_or("truncated gzip header")?;
+                pos += end + 1;
+            }
+        }
+        if flags & 2 != 0 {
+            pos += 2;
+        }
+        let (member, used) = inflate(data.get(pos..).ok_or("truncated gzip file")?)?;
+        pos += used;
+        let trailer = data.get(pos..pos + 8).ok_or("truncated gzip trailer")?;
+        let crc = u32::from_le_bytes(trailer[..4].try_into().unwrap());
+        let size = u32::from_le_bytes(trailer[4..].try_into().unwrap());
+        if crc != crc32(&member) || size != member.len() as u32 {
+            return Err("gzip checksum mismatch".into());
+        }
+        pos += 8;
+        out.extend(member);
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// tar (ustar)
+// ---------------------------------------------------------------------------
+
+/// The regular files in a tar archive as `(path, contents)` pairs.
+pub fn untar(data: &[u8]) -> Result<Vec<(String, &[u8])>> {
+    let mut files = Vec::new();
+    let mut pos = 0;
+    while pos + 512 <= data.len() {
+        let header = &data[pos..pos + 512];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let field = |range: std::ops::Range<usize>| {
+            let f = &header[range];
+            String::from_utf8_lossy(&f[..f.iter().position(|&b| b == 0).unwrap_or(f.len())]).into_owned()
+        };
+        let size_field = field(124..136);
+        let size = usize::from_str_radix(size_field.trim(), 8).map_err(|_| format!("bad tar size '{}'", size_field))?;
+        let mut name = field(0..100);
+        let prefix = field(345..500);
+        if &header[257..262] == b"ustar" && !prefix.is_empty() {
+            name = format!("{}/{}", prefix, name);
+        }
+        let body = data.get(pos + 512..pos + 512 + size).ok_or("truncated tar archive")?;
+        if matches!(header[156], 0 | b'0') {
+            files.push((name, body));
+        }
+        pos += 512 + size.div_ceil(512) * 512;
+    }
+    Ok(files)
+}
+
+// ---------------------------------------------------------------------------
+// MD5 (RFC 1321) — the checksum the dataset mirrors publish
+// ---------------------------------------------------------------------------
+
+/// Lower-case hex MD5 digest of `data`.
+pub fn md5_hex(data: &[u8]) -> String {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+        14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15,
+        21, 6, 10, 15, 21,
+    ];
+    let k: Vec<u32> = (0..64).map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32).collect();
+    let mut state = [0x67452301u32, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend(((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for block in msg.chunks_exact(64) {
+        let m: Vec<u32> = block.chunks_exact(4).map(|w| u32::from_le_bytes(w.try_into().unwrap())).collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a.wrapping_add(f).wrapping_add(k[i]).wrapping_add(m[g]).rotate_left(S[i]);
+            (a, d, c, b) = (d, c, b, b.wrapping_add(rotated));
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+    state.iter().flat_map(|s| s.to_le_bytes()).map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_md5_vectors() {
+        assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(md5_hex(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            md5_hex(b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"),
+            "57edf4a22be3c955ac49da2e2107b67a"
+        );
+    }
+
+    #[test]
+    fn test_gunzip_and_untar() {
+        // gzip -9 of "hello hello hello hello\n" (a fixed-Huffman block).
+        let gz = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8,
+            0x40, 0x27, 0xb9, 0x00, 0x00, 0x88, 0x59, 0x0b, 0x18, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(gunzip(&gz).unwrap(), b"hello hello hello hello\n");
+        assert!(gunzip(&gz[..gz.len() - 1]).is_err());
+
+        let mut tar = vec![0u8; 512];
+        tar[..9].copy_from_slice(b"dir/a.bin");
+        tar[124..135].copy_from_slice(b"00000000003");
+        tar[156] = b'0';
+        tar.extend(b"xyz");
+        tar.resize(2048, 0);
+        let files = untar(&tar).unwrap();
+        assert_eq!(files, vec![("dir/a.bin".to_string(), &b"xyz"[..])]);
+    }
+}
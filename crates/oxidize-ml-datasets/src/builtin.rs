@@ -1,6 +1,4 @@
-use oxidize_ml_core::Tensor;
-use rand::rngs::StdRng;
-use rand::SeedableRng;
+use oxidize_ml_core::{RngHandle, Tensor};
 
 /// Load the Iris dataset (150 samples, 4 features, 3 classes).
 pub fn load_iris() -> (Tensor<f64>, Tensor<f64>) {
@@ -40,10 +38,7 @@ pub fn make_blobs(
     cluster_std: f64,
     seed: Option<u64>,
 ) -> (Tensor<f64>, Tensor<f64>) {
-    let mut rng = match seed {
-        Some(s) => StdRng::seed_from_u64(s),
-        None => StdRng::from_entropy(),
-    };
+    let mut rng = RngHandle::new(seed);
 
     // Generate center positions spread out
     let mut centers = vec![0.0; n_centers * n_features];
@@ -90,10 +85,7 @@ pub fn make_regression(
     noise: f64,
     seed: Option<u64>,
 ) -> (Tensor<f64>, Tensor<f64>) {
-    let mut rng = match seed {
-        Some(s) => StdRng::seed_from_u64(s),
-        None => StdRng::from_entropy(),
-    };
+    let mut rng = RngHandle::new(seed);
 
     // Random true weights
     let true_weights: Vec<f64> = (0..n_features)
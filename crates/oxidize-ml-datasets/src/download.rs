@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::archive::md5_hex;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Root directory for downloaded datasets: `$OXIDIZE_ML_DATA` if set, else
+/// `$HOME/.cache/oxidize-ml`, else `./oxidize-ml-data`.
+pub fn default_data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("OXIDIZE_ML_DATA") {
+        return PathBuf::from(dir);
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => Path::new(&home).join(".cache").join("oxidize-ml"),
+        None => PathBuf::from("oxidize-ml-data"),
+    }
+}
+
+/// Whether downloads are allowed by default; setting `OXIDIZE_ML_OFFLINE`
+/// to anything but `0` turns them off.
+fn downloads_enabled() -> bool {
+    std::env::var("OXIDIZE_ML_OFFLINE").map_or(true, |v| v == "0")
+}
+
+/// Where benchmark datasets are cached and whether missing files may be
+/// fetched from the network.
+///
+/// Each dataset lives in its own subdirectory of `root`. Files already there
+/// are used as is, so pointing `root` at a directory holding the original
+/// archives (or their extracted contents) works without network access.
+/// Archives are checked against their published MD5 before use.
+///
+/// Downloading runs the `curl` program, or `wget` if curl is missing, so
+/// one of them must be on `PATH`; no HTTP client is linked in. Without
+/// either, fetching fails with an error naming both, and the files have to
+/// be placed under `root` by hand.
+#[derive(Debug, Clone)]
+pub struct DatasetDownloader {
+    pub root: PathBuf,
+    pub download: bool,
+}
+
+impl Default for DatasetDownloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DatasetDownloader {
+    pub fn new() -> Self {
+        DatasetDownloader {
+            root: default_data_dir(),
+            download: downloads_enabled(),
+        }
+    }
+
+    pub fn with_root<P: AsRef<Path>>(mut self, root: P) -> Self {
+        self.root = root.as_ref().to_path_buf();
+        self
+    }
+
+    /// Allow or forbid network access; when forbidden, missing files are an error.
+    pub fn with_download(mut self, download: bool) -> Self {
+        self.download = download;
+        self
+    }
+
+    /// Path to `file` in `dataset`'s cache directory, downloading it from the
+    /// first working mirror if it is missing. The file must match `md5`; a
+    /// cached copy that does not is fetched again when downloads are allowed.
+    pub(crate) fn archive(&self, dataset: &str, file: &str, mirrors: &[&str], md5: &str) -> Result<PathBuf> {
+        let dir = self.root.join(dataset);
+        let path = dir.join(file);
+        if path.exists() {
+            if md5_hex(&std::fs::read(&path)?) == md5 {
+                return Ok(path);
+            }
+            if !self.download {
+                return Err(format!("{} does not match its MD5 checksum {}", path.display(), md5).into());
+            }
+        } else if !self.download {
+            return Err(format!(
+                "{} not found and downloads are disabled; place the file there or enable downloads",
+                path.display()
+            )
+            .into());
+        }
+
+        let program = find_program(DOWNLOADERS).ok_or_else(|| {
+            format!(
+                "downloading {} needs curl or wget, and neither was found on PATH; \
+                 install one or place the file at {}",
+                file,
+                path.display()
+            )
+        })?;
+        std::fs::create_dir_all(&dir)?;
+        let partial = dir.join(format!("{}.part", file));
+        let mut errors = Vec::new();
+        for mirror in mirrors {
+            let url = format!("{}{}", mirror, file);
+            match fetch(program, &url, &partial) {
+                Ok(()) => {
+                    let digest = md5_hex(&std::fs::read(&partial)?);
+                    if digest == md5 {
+                        std::fs::rename(&partial, &path)?;
+                        return Ok(path);
+                    }
+                    errors.push(format!("{}: MD5 {} does not match {}", url, digest, md5));
+                }
+                Err(e) => errors.push(format!("{}: {}", url, e)),
+            }
+        }
+        let _ = std::fs::remove_file(&partial);
+        Err(format!("could not download {}:\n  {}", file, errors.join("\n  ")).into())
+    }
+}
+
+/// Programs that can fetch a URL, in order of preference.
+const DOWNLOADERS: &[&str] = &["curl", "wget"];
+
+/// The first of `programs` that can be run.
+fn find_program(programs: &[&'static str]) -> Option<&'static str> {
+    programs.iter().copied().find(|program| {
+        Command::new(program)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok()
+    })
+}
+
+/// Download `url` to `dest` with `program`, one of `DOWNLOADERS`.
+fn fetch(program: &str, url: &str, dest: &Path) -> Result<()> {
+    let args: &[&str] = match program {
+        "curl" => &["-fsSL", "--retry", "3", "-o"],
+        _ => &["-q", "--tries=3", "-O"],
+    };
+    match Command::new(program).args(args).arg(dest).arg(url).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("{} exited with {}", program, status).into()),
+        Err(e) => Err(format!("{}: {}", program, e).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_program() {
+        assert_eq!(find_program(&["oxidize-ml-no-such-program"]), None);
+        assert_eq!(find_program(&[]), None);
+        // `cargo` is on PATH whenever the tests run.
+        assert_eq!(find_program(&["oxidize-ml-no-such-program", "cargo"]), Some("cargo"));
+    }
+}
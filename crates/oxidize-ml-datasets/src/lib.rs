@@ -1,2 +1,7 @@
+pub mod archive;
 pub mod builtin;
+pub mod download;
+pub mod vision;
 pub use builtin::*;
+pub use download::*;
+pub use vision::*;
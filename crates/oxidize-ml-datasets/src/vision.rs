@@ -0,0 +1,240 @@
+use oxidize_ml_core::{Float, Tensor};
+use std::error::Error;
+
+use crate::archive::{gunzip, untar};
+use crate::download::DatasetDownloader;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Images scaled to `[0, 1]` with shape `[N, C, H, W]` and class-index labels
+/// `[N]`. Flatten with `reshape(vec![n, c * h * w])` for dense models.
+#[derive(Debug, Clone)]
+pub struct TrainTestSplit<T: Float> {
+    pub x_train: Tensor<T>,
+    pub y_train: Tensor<T>,
+    pub x_test: Tensor<T>,
+    pub y_test: Tensor<T>,
+}
+
+pub const MNIST_CLASSES: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+pub const FASHION_MNIST_CLASSES: [&str; 10] = [
+    "T-shirt/top", "Trouser", "Pullover", "Dress", "Coat", "Sandal", "Shirt", "Sneaker", "Bag", "Ankle boot",
+];
+
+pub const CIFAR10_CLASSES: [&str; 10] = [
+    "airplane", "automobile", "bird", "cat", "deer", "dog", "frog", "horse", "ship", "truck",
+];
+
+const MNIST_MIRRORS: [&str; 2] = ["https://ossci-datasets.s3.amazonaws.com/mnist/", "http://yann.lecun.com/exdb/mnist/"];
+const MNIST_FILES: [(&str, &str); 4] = [
+    ("train-images-idx3-ubyte", "f68b3c2dcbeaaa9fbdd348bbdeb94873"),
+    ("train-labels-idx1-ubyte", "d53e105ee54ea40749a09fcbcd1e9432"),
+    ("t10k-images-idx3-ubyte", "9fb629c4189551a2d022fa330f9573f3"),
+    ("t10k-labels-idx1-ubyte", "ec29112dd5afa0611ce80d1b7f02629c"),
+];
+
+const FASHION_MNIST_MIRRORS: [&str; 1] = ["http://fashion-mnist.s3-website.eu-central-1.amazonaws.com/"];
+const FASHION_MNIST_FILES: [(&str, &str); 4] = [
+    ("train-images-idx3-ubyte", "8d4fb7e6c68d591d4c3dfef9ec88bf0d"),
+    ("train-labels-idx1-ubyte", "25c81989df183df01b3e8a0aad5dffbe"),
+    ("t10k-images-idx3-ubyte", "bef4ecab320f06d8554ea6380940ec79"),
+    ("t10k-labels-idx1-ubyte", "bb300cfdad3c16e7a12a480ee83cd310"),
+];
+
+const CIFAR10_MIRRORS: [&str; 1] = ["https://www.cs.toronto.edu/~kriz/"];
+const CIFAR10_ARCHIVE: (&str, &str) = ("cifar-10-binary.tar.gz", "c32a1d4ab5d03f1284b67883e8d87530");
+const CIFAR10_DIR: &str = "cifar-10-batches-bin";
+const CIFAR10_TRAIN: [&str; 5] = ["data_batch_1.bin", "data_batch_2.bin", "data_batch_3.bin", "data_batch_4.bin", "data_batch_5.bin"];
+const CIFAR10_TEST: &str = "test_batch.bin";
+
+impl DatasetDownloader {
+    /// MNIST handwritten digits: 60k train / 10k test images of `[1, 28, 28]`.
+    pub fn mnist<T: Float>(&self) -> Result<TrainTestSplit<T>> {
+        self.idx_dataset("mnist", &MNIST_MIRRORS, &MNIST_FILES)
+    }
+
+    /// Fashion-MNIST (Zalando article images), a drop-in replacement for MNIST.
+    pub fn fashion_mnist<T: Float>(&self) -> Result<TrainTestSplit<T>> {
+        self.idx_dataset("fashion-mnist", &FASHION_MNIST_MIRRORS, &FASHION_MNIST_FILES)
+    }
+
+    /// CIFAR-10: 50k train / 10k test color images of `[3, 32, 32]`.
+    pub fn cifar10<T: Float>(&self) -> Result<TrainTestSplit<T>> {
+        let dir = self.root.join("cifar-10").join(CIFAR10_DIR);
+        if !CIFAR10_TRAIN.iter().chain(&[CIFAR10_TEST]).all(|f| dir.join(f).exists()) {
+            let (file, md5) = CIFAR10_ARCHIVE;
+            let archive = self.archive("cifar-10", file, &CIFAR10_MIRRORS, md5)?;
+            let tar = gunzip(&std::fs::read(archive)?)?;
+            std::fs::create_dir_all(&dir)?;
+            for (name, body) in untar(&tar)? {
+                if let Some(base) = name.strip_prefix(&format!("{}/", CIFAR10_DIR)) {
+                    std::fs::write(dir.join(base), body)?;
+                }
+            }
+        }
+
+        let mut train = Vec::new();
+        for f in CIFAR10_TRAIN {
+            train.extend(std::fs::read(dir.join(f))?);
+        }
+        let test = std::fs::read(dir.join(CIFAR10_TEST))?;
+        let (x_train, y_train) = parse_cifar(&train)?;
+        let (x_test, y_test) = parse_cifar(&test)?;
+        Ok(TrainTestSplit { x_train, y_train, x_test, y_test })
+    }
+
+    fn idx_dataset<T: Float>(&self, name: &str, mirrors: &[&str], files: &[(&str, &str); 4]) -> Result<TrainTestSplit<T>> {
+        let mut raw = Vec::with_capacity(4);
+        for (file, md5) in files {
+            let extracted = self.root.join(name).join(file);
+            let bytes = if extracted.exists() {
+                std::fs::read(&extracted)?
+            } else {
+                let archive = self.archive(name, &format!("{}.gz", file), mirrors, md5)?;
+                let bytes = gunzip(&std::fs::read(archive)?)?;
+                std::fs::write(&extracted, &bytes)?;
+                bytes
+            };
+            raw.push((extracted, bytes));
+        }
+        let mut parts = Vec::with_capacity(4);
+        for (path, bytes) in &raw {
+            parts.push(parse_idx(bytes).map_err(|e| format!("{}: {}", path.display(), e))?);
+        }
+        let [x_train, y_train, x_test, y_test]: [_; 4] = parts.try_into().unwrap();
+        Ok(TrainTestSplit {
+            x_train: images(x_train)?,
+            y_train: labels(y_train)?,
+            x_test: images(x_test)?,
+            y_test: labels(y_test)?,
+        })
+    }
+}
+
+/// An IDX file (the MNIST container format) as `(dims, bytes)`. Only unsigned
+/// byte data is supported.
+fn parse_idx(bytes: &[u8]) -> Result<(Vec<usize>, &[u8])> {
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 || bytes[2] != 0x08 {
+        return Err("not an unsigned-byte IDX file".into());
+    }
+    let ndim = bytes[3] as usize;
+    let header = 4 + 4 * ndim;
+    let dims: Vec<usize> = bytes
+        .get(4..header)
+        .ok_or("truncated IDX header")?
+        .chunks_exact(4)
+        .map(|d| u32::from_be_bytes(d.try_into().unwrap()) as usize)
+        .collect();
+    let data = &bytes[header..];
+    if data.len() != dims.iter().product::<usize>() {
+        return Err(format!("IDX dims {:?} do not match {} data bytes", dims, data.len()).into());
+    }
+    Ok((dims, data))
+}
+
+fn images<T: Float>((dims, data): (Vec<usize>, &[u8])) -> Result<Tensor<T>> {
+    if dims.len() != 3 {
+        return Err(format!("expected [N, H, W] images, got {:?}", dims).into());
+    }
+    let pixels = data.iter().map(|&p| T::from_f64(p as f64 / 255.0)).collect();
+    Tensor::new(pixels, vec![dims[0], 1, dims[1], dims[2]]).map_err(|e| format!("{:?}", e).into())
+}
+
+fn labels<T: Float>((dims, data): (Vec<usize>, &[u8])) -> Result<Tensor<T>> {
+    if dims.len() != 1 {
+        return Err(format!("expected [N] labels, got {:?}", dims).into());
+    }
+    Tensor::new(data.iter().map(|&l| T::from_f64(l as f64)).collect(), dims).map_err(|e| format!("{:?}", e).into())
+}
+
+/// CIFAR-10 binary batches: each record is a label byte then 3072 bytes of
+/// channel-major 32x32 RGB.
+fn parse_cifar<T: Float>(bytes: &[u8]) -> Result<(Tensor<T>, Tensor<T>)> {
+    const RECORD: usize = 1 + 3 * 32 * 32;
+    if !bytes.len().is_multiple_of(RECORD) {
+        return Err(format!("CIFAR-10 batch of {} bytes is not a whole number of records", bytes.len()).into());
+    }
+    let n = bytes.len() / RECORD;
+    let mut pixels = Vec::with_capacity(n * (RECORD - 1));
+    let mut labels = Vec::with_capacity(n);
+    for record in bytes.chunks_exact(RECORD) {
+        labels.push(T::from_f64(record[0] as f64));
+        pixels.extend(record[1..].iter().map(|&p| T::from_f64(p as f64 / 255.0)));
+    }
+    let x = Tensor::new(pixels, vec![n, 3, 32, 32]).map_err(|e| format!("{:?}", e))?;
+    let y = Tensor::new(labels, vec![n]).map_err(|e| format!("{:?}", e))?;
+    Ok((x, y))
+}
+
+/// MNIST from the default cache directory, downloading it on first use.
+pub fn load_mnist<T: Float>() -> Result<TrainTestSplit<T>> {
+    DatasetDownloader::new().mnist()
+}
+
+/// Fashion-MNIST from the default cache directory, downloading it on first use.
+pub fn load_fashion_mnist<T: Float>() -> Result<TrainTestSplit<T>> {
+    DatasetDownloader::new().fashion_mnist()
+}
+
+/// CIFAR-10 from the default cache directory, downloading it on first use.
+pub fn load_cifar10<T: Float>() -> Result<TrainTestSplit<T>> {
+    DatasetDownloader::new().cifar10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idx(dims: &[u32], data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0, 0, 0x08, dims.len() as u8];
+        for d in dims {
+            out.extend(d.to_be_bytes());
+        }
+        out.extend(data);
+        out
+    }
+
+    #[test]
+    fn test_offline_mnist_from_extracted_files() {
+        let root = std::env::temp_dir().join("oxidize_ml_datasets_mnist");
+        let dir = root.join("mnist");
+        std::fs::create_dir_all(&dir).unwrap();
+        let pixels: Vec<u8> = (0..2 * 4).map(|i| (i * 36) as u8).collect();
+        std::fs::write(dir.join("train-images-idx3-ubyte"), idx(&[2, 2, 2], &pixels)).unwrap();
+        std::fs::write(dir.join("train-labels-idx1-ubyte"), idx(&[2], &[3, 7])).unwrap();
+        std::fs::write(dir.join("t10k-images-idx3-ubyte"), idx(&[1, 2, 2], &[0, 0, 255, 255])).unwrap();
+        std::fs::write(dir.join("t10k-labels-idx1-ubyte"), idx(&[1], &[9])).unwrap();
+
+        let split: TrainTestSplit<f32> = DatasetDownloader::new().with_root(&root).with_download(false).mnist().unwrap();
+        assert_eq!(split.x_train.shape_vec(), vec![2, 1, 2, 2]);
+        assert_eq!(split.y_train.data(), &[3.0, 7.0]);
+        assert_eq!(split.x_test.data(), &[0.0, 0.0, 1.0, 1.0]);
+        assert!((split.x_train.data()[1] - 36.0 / 255.0).abs() < 1e-6);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_offline_missing_or_corrupt_archive() {
+        let root = std::env::temp_dir().join("oxidize_ml_datasets_missing");
+        let offline = DatasetDownloader::new().with_root(&root).with_download(false);
+        let err = offline.fashion_mnist::<f64>().unwrap_err().to_string();
+        assert!(err.contains("downloads are disabled"), "{}", err);
+
+        std::fs::create_dir_all(root.join("cifar-10")).unwrap();
+        std::fs::write(root.join("cifar-10").join(CIFAR10_ARCHIVE.0), b"not the archive").unwrap();
+        let err = offline.cifar10::<f64>().unwrap_err().to_string();
+        assert!(err.contains("MD5"), "{}", err);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_cifar_records() {
+        let mut bytes = vec![4u8];
+        bytes.extend(std::iter::repeat_n(255u8, 3072));
+        let (x, y) = parse_cifar::<f64>(&bytes).unwrap();
+        assert_eq!(x.shape_vec(), vec![1, 3, 32, 32]);
+        assert_eq!(y.data(), &[4.0]);
+        assert!(parse_cifar::<f64>(&bytes[..100]).is_err());
+    }
+}
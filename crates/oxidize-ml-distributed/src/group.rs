@@ -0,0 +1,201 @@
+//! The processes of a run, connected in a ring over TCP.
+//!
+//! Every rank listens on its own address, connects to the next rank and
+//! accepts the previous one, so each holds one outgoing and one incoming
+//! stream. `all_reduce_sum` is the bandwidth-optimal ring algorithm: a
+//! reduce-scatter then an all-gather, each `world_size - 1` steps of one
+//! chunk, so every rank sends about twice its buffer whatever the world
+//! size.
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `connect` keeps retrying a rank that is not listening yet.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct ProcessGroup {
+    pub rank: usize,
+    pub world_size: usize,
+    /// Streams to the next rank and from the previous one; `None` when
+    /// running alone.
+    ring: Option<(TcpStream, TcpStream)>,
+}
+
+impl ProcessGroup {
+    /// Join the run of `addrs.len()` ranks as `rank`, listening on
+    /// `addrs[rank]`. Blocks until the neighbours are connected.
+    pub fn connect(rank: usize, addrs: &[SocketAddr]) -> io::Result<Self> {
+        let listener = TcpListener::bind(addrs.get(rank).ok_or_else(|| invalid("rank out of range"))?)?;
+        Self::from_listener(rank, listener, addrs)
+    }
+
+    /// `connect` with the listening socket already bound (to port 0, say,
+    /// with the chosen addresses shared out of band).
+    pub fn from_listener(rank: usize, listener: TcpListener, addrs: &[SocketAddr]) -> io::Result<Self> {
+        let world_size = addrs.len();
+        if rank >= world_size {
+            return Err(invalid("rank out of range"));
+        }
+        if world_size == 1 {
+            return Ok(ProcessGroup { rank, world_size, ring: None });
+        }
+        let next_addr = addrs[(rank + 1) % world_size];
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        let mut next = loop {
+            match TcpStream::connect(next_addr) {
+                Ok(stream) => break stream,
+                Err(e) if Instant::now() >= deadline => return Err(e),
+                Err(_) => thread::sleep(Duration::from_millis(20)),
+            }
+        };
+        next.set_nodelay(true)?;
+        next.write_all(&(rank as u64).to_le_bytes())?;
+
+        let (mut prev, _) = listener.accept()?;
+        prev.set_nodelay(true)?;
+        let mut peer = [0u8; 8];
+        prev.read_exact(&mut peer)?;
+        let expected = (rank + world_size - 1) % world_size;
+        if u64::from_le_bytes(peer) != expected as u64 {
+            return Err(invalid(&format!("expected rank {} to connect, got {}", expected, u64::from_le_bytes(peer))));
+        }
+        Ok(ProcessGroup { rank, world_size, ring: Some((next, prev)) })
+    }
+
+    pub fn is_main(&self) -> bool {
+        self.rank == 0
+    }
+
+    /// Replace `data` on every rank by its element-wise sum over all ranks.
+    pub fn all_reduce_sum(&mut self, data: &mut [f64]) -> io::Result<()> {
+        let n = self.world_size;
+        if n == 1 {
+            return Ok(());
+        }
+        let bounds: Vec<usize> = (0..=n).map(|i| i * data.len() / n).collect();
+        let chunk = |i: usize| bounds[i % n]..bounds[i % n + 1];
+        let mut buffer = Vec::new();
+        // Reduce-scatter: afterwards this rank holds the full sum of chunk
+        // `rank + 1`.
+        for step in 0..n - 1 {
+            let (send, recv) = (chunk(self.rank + n - step), chunk(self.rank + n - step - 1));
+            buffer.resize(recv.len(), 0.0);
+            self.exchange(&data[send], &mut buffer)?;
+            for (d, b) in data[recv].iter_mut().zip(&buffer) {
+                *d += b;
+            }
+        }
+        // All-gather: pass the finished chunks around the ring.
+        for step in 0..n - 1 {
+            let (send, recv) = (chunk(self.rank + 1 + n - step), chunk(self.rank + n - step));
+            buffer.clear();
+            buffer.extend_from_slice(&data[send]);
+            self.exchange(&buffer, &mut data[recv])?;
+        }
+        Ok(())
+    }
+
+    /// Replace `data` by its element-wise mean over all ranks.
+    pub fn all_reduce_mean(&mut self, data: &mut [f64]) -> io::Result<()> {
+        self.all_reduce_sum(data)?;
+        let n = self.world_size as f64;
+        data.iter_mut().for_each(|v| *v /= n);
+        Ok(())
+    }
+
+    /// Copy `data` from rank `root` to every rank, hop by hop round the ring.
+    pub fn broadcast(&mut self, data: &mut [f64], root: usize) -> io::Result<()> {
+        let n = self.world_size;
+        let Some((next, prev)) = self.ring.as_mut() else { return Ok(()) };
+        if self.rank != root {
+            read_f64s(prev, data)?;
+        }
+        if (self.rank + 1) % n != root {
+            write_f64s(next, data)?;
+        }
+        Ok(())
+    }
+
+    /// Wait until every rank has reached this point.
+    pub fn barrier(&mut self) -> io::Result<()> {
+        self.all_reduce_sum(&mut [0.0])
+    }
+
+    /// Send `send` to the next rank while filling `recv` from the previous
+    /// one; doing both at once keeps large chunks from deadlocking on full
+    /// socket buffers.
+    fn exchange(&mut self, send: &[f64], recv: &mut [f64]) -> io::Result<()> {
+        let (next, prev) = self.ring.as_mut().expect("exchange needs a ring");
+        thread::scope(|s| {
+            let writer = s.spawn(|| write_f64s(next, send));
+            let read = read_f64s(prev, recv);
+            writer.join().expect("ring writer panicked")?;
+            read
+        })
+    }
+}
+
+fn write_f64s(stream: &mut TcpStream, values: &[f64]) -> io::Result<()> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    stream.write_all(&bytes)
+}
+
+fn read_f64s(stream: &mut TcpStream, values: &mut [f64]) -> io::Result<()> {
+    let mut bytes = vec![0u8; values.len() * 8];
+    stream.read_exact(&mut bytes)?;
+    for (v, b) in values.iter_mut().zip(bytes.chunks_exact(8)) {
+        *v = f64::from_le_bytes(b.try_into().expect("8 bytes"));
+    }
+    Ok(())
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.to_string())
+}
+
+/// Run `f` on `world_size` ranks in threads of this process, connected
+/// over loopback, and collect what each returns, in rank order.
+#[cfg(test)]
+pub(crate) fn run_local<R: Send>(world_size: usize, f: impl Fn(ProcessGroup) -> R + Sync) -> Vec<R> {
+    let listeners: Vec<TcpListener> = (0..world_size).map(|_| TcpListener::bind("127.0.0.1:0").unwrap()).collect();
+    let addrs: Vec<SocketAddr> = listeners.iter().map(|l| l.local_addr().unwrap()).collect();
+    thread::scope(|s| {
+        let handles: Vec<_> = listeners
+            .into_iter()
+            .enumerate()
+            .map(|(rank, listener)| {
+                let (addrs, f) = (&addrs, &f);
+                s.spawn(move || f(ProcessGroup::from_listener(rank, listener, addrs).unwrap()))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_reduce_and_broadcast() {
+        let results = run_local(3, |mut group| {
+            let r = group.rank as f64;
+            // Fewer elements than ranks leaves one chunk empty.
+            let mut small = vec![r, 10.0 * r];
+            group.all_reduce_sum(&mut small).unwrap();
+            let mut large: Vec<f64> = (0..100_000).map(|i| i as f64 + r).collect();
+            group.all_reduce_mean(&mut large).unwrap();
+            let mut shared = if group.rank == 1 { vec![7.0, 8.0] } else { vec![0.0, 0.0] };
+            group.broadcast(&mut shared, 1).unwrap();
+            group.barrier().unwrap();
+            (small, large[99_999], shared)
+        });
+        for (small, last, shared) in results {
+            assert_eq!(small, vec![3.0, 30.0]);
+            assert_eq!(last, 100_000.0);
+            assert_eq!(shared, vec![7.0, 8.0]);
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod group;
+pub mod shard;
+pub mod trainer;
+
+pub use group::*;
+pub use shard::*;
+pub use trainer::*;
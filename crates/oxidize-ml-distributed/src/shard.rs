@@ -0,0 +1,54 @@
+use oxidize_ml_core::Tensor;
+use oxidize_ml_data::Dataset;
+
+/// The part of a dataset one rank trains on: samples `rank`,
+/// `rank + world_size`, `rank + 2·world_size`, … of the full dataset.
+///
+/// Every shard has `ceil(len / world_size)` samples, the short ones wrapping
+/// round to the start, so all ranks run the same number of steps (which
+/// the all-reduce after each needs).
+pub struct ShardedDataset<'a, D: Dataset> {
+    dataset: &'a D,
+    pub rank: usize,
+    pub world_size: usize,
+}
+
+impl<'a, D: Dataset> ShardedDataset<'a, D> {
+    pub fn new(dataset: &'a D, rank: usize, world_size: usize) -> Self {
+        assert!(rank < world_size, "ShardedDataset: rank {} out of range for {} ranks", rank, world_size);
+        ShardedDataset { dataset, rank, world_size }
+    }
+
+    /// The index in the full dataset of sample `idx` of this shard.
+    pub fn source_index(&self, idx: usize) -> usize {
+        (idx * self.world_size + self.rank) % self.dataset.len()
+    }
+}
+
+impl<D: Dataset> Dataset for ShardedDataset<'_, D> {
+    fn len(&self) -> usize {
+        self.dataset.len().div_ceil(self.world_size)
+    }
+
+    fn get(&self, idx: usize) -> (Tensor<f64>, Tensor<f64>) {
+        self.dataset.get(self.source_index(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_data::TensorDataset;
+
+    #[test]
+    fn test_shards_cover_the_dataset_evenly() {
+        let data = TensorDataset::new(Tensor::new((0..7).map(f64::from).collect(), vec![7, 1]).unwrap(), Tensor::zeros(vec![7]));
+        let shards: Vec<Vec<usize>> = (0..3)
+            .map(|rank| {
+                let shard = ShardedDataset::new(&data, rank, 3);
+                (0..shard.len()).map(|i| shard.get(i).0.data()[0] as usize).collect()
+            })
+            .collect();
+        assert_eq!(shards, vec![vec![0, 3, 6], vec![1, 4, 0], vec![2, 5, 1]]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Synchronous data-parallel training: every rank runs the same `Trainer`
+//! on its shard, and gradients are averaged over the ring before each step,
+//! so the ranks' models stay identical.
+
+use std::collections::HashMap;
+use std::io;
+use oxidize_ml_core::Tensor;
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_autodiff::graph::NodeId;
+use oxidize_ml_autodiff::Variable;
+use oxidize_ml_data::{DataLoader, Dataset};
+use oxidize_ml_nn::Layer;
+use oxidize_ml_optim::{Optimizer, OptimizerState};
+use oxidize_ml_train::{Callback, History, Trainer};
+
+use crate::group::ProcessGroup;
+use crate::shard::ShardedDataset;
+
+/// Overwrite every parameter of `model` with rank `root`'s values.
+pub fn broadcast_parameters(group: &mut ProcessGroup, model: &mut dyn Layer, root: usize) -> io::Result<()> {
+    let mut params = model.named_parameters_mut();
+    let mut flat: Vec<f64> = params.iter().flat_map(|(_, p)| p.data.data().to_vec()).collect();
+    group.broadcast(&mut flat, root)?;
+    let mut offset = 0;
+    for (_, p) in params.iter_mut() {
+        let n = p.data.numel();
+        let value = Tensor::new(flat[offset..offset + n].to_vec(), p.shape_vec()).expect("parameter shape");
+        p.set_data(value);
+        offset += n;
+    }
+    Ok(())
+}
+
+/// An optimizer that averages gradients over the process group, then
+/// steps `inner` with them.
+///
+/// Every rank must step in lockstep with the same parameters. A parameter
+/// without a gradient on some rank counts as a zero gradient there.
+pub struct DistributedOptimizer<O: Optimizer> {
+    pub inner: O,
+    pub group: ProcessGroup,
+    params: Vec<(NodeId, Vec<usize>)>,
+}
+
+impl<O: Optimizer> DistributedOptimizer<O> {
+    /// `params` are the ones `inner` was built over, in the same order.
+    pub fn new(inner: O, group: ProcessGroup, params: &[Variable]) -> Self {
+        DistributedOptimizer { inner, group, params: params.iter().map(|p| (p.node_id, p.shape_vec())).collect() }
+    }
+}
+
+impl<O: Optimizer> Optimizer for DistributedOptimizer<O> {
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<f64>>) -> Vec<Tensor<f64>> {
+        let mut flat = Vec::new();
+        for (id, shape) in &self.params {
+            match grads.get(id) {
+                Some(g) => flat.extend_from_slice(g.data()),
+                None => flat.resize(flat.len() + shape.iter().product::<usize>(), 0.0),
+            }
+        }
+        self.group.all_reduce_mean(&mut flat).expect("DistributedOptimizer: gradient all-reduce failed");
+        let mut offset = 0;
+        let averaged = self
+            .params
+            .iter()
+            .map(|(id, shape)| {
+                let n = shape.iter().product::<usize>();
+                offset += n;
+                (*id, Tensor::new(flat[offset - n..offset].to_vec(), shape.clone()).expect("gradient shape"))
+            })
+            .collect();
+        self.inner.step(&averaged)
+    }
+
+    fn lr(&self) -> f64 {
+        self.inner.lr()
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.inner.set_lr(lr);
+    }
+
+    fn state(&self) -> OptimizerState<f64> {
+        self.inner.state()
+    }
+
+    fn load_state(&mut self, state: &OptimizerState<f64>) -> TensorResult<()> {
+        self.inner.load_state(state)
+    }
+}
+
+/// A `Trainer` run as one rank of a distributed job.
+///
+/// Construction broadcasts rank 0's initial weights before the optimizer
+/// is built, so every rank starts from the same model. Train on
+/// `shard(&dataset)`; losses and metrics in the history are this rank's
+/// own, and checkpoints need only be written by the main rank.
+pub struct DistributedTrainer<M: Layer, O: Optimizer> {
+    pub trainer: Trainer<M, DistributedOptimizer<O>>,
+}
+
+impl<M: Layer, O: Optimizer> DistributedTrainer<M, O> {
+    /// `optimizer` builds the local optimizer over the model's parameters.
+    pub fn new(
+        mut model: M,
+        mut group: ProcessGroup,
+        optimizer: impl FnOnce(&[Variable]) -> O,
+        loss: impl Fn(&Variable, &Tensor<f64>) -> Variable + 'static,
+    ) -> io::Result<Self> {
+        broadcast_parameters(&mut group, &mut model, 0)?;
+        let params = model.parameters();
+        let optimizer = DistributedOptimizer::new(optimizer(&params), group, &params);
+        Ok(DistributedTrainer { trainer: Trainer::new(model, optimizer, loss) })
+    }
+
+    pub fn with_epochs(mut self, epochs: usize) -> Self {
+        self.trainer = self.trainer.with_epochs(epochs);
+        self
+    }
+
+    pub fn with_metric(mut self, name: &str, metric: impl Fn(&Tensor<f64>, &Tensor<f64>) -> f64 + 'static) -> Self {
+        self.trainer = self.trainer.with_metric(name, metric);
+        self
+    }
+
+    pub fn with_callback(mut self, callback: impl Callback + 'static) -> Self {
+        self.trainer = self.trainer.with_callback(callback);
+        self
+    }
+
+    pub fn rank(&self) -> usize {
+        self.trainer.optimizer.group.rank
+    }
+
+    pub fn world_size(&self) -> usize {
+        self.trainer.optimizer.group.world_size
+    }
+
+    pub fn is_main(&self) -> bool {
+        self.trainer.optimizer.group.is_main()
+    }
+
+    /// This rank's part of `dataset`.
+    pub fn shard<'a, D: Dataset>(&self, dataset: &'a D) -> ShardedDataset<'a, D> {
+        ShardedDataset::new(dataset, self.rank(), self.world_size())
+    }
+
+    /// `Trainer::fit` over this rank's loader; all ranks must call it with
+    /// loaders of the same length and batch size.
+    pub fn fit<D: Dataset>(&mut self, train: &mut DataLoader<'_, D>) -> &History {
+        self.trainer.fit(train)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::run_local;
+    use oxidize_ml_core::set_global_seed;
+    use oxidize_ml_data::TensorDataset;
+    use oxidize_ml_loss::mse_loss;
+    use oxidize_ml_nn::{Linear, Sequential};
+    use oxidize_ml_optim::Adam;
+
+    #[test]
+    fn test_ranks_train_in_lockstep() {
+        let x: Vec<f64> = (0..48).map(|k| (k as f64 * 0.61).cos()).collect();
+        let y = x.chunks(2).map(|p| 3.0 * p[0] - p[1] + 0.5).collect();
+        let data = TensorDataset::new(Tensor::new(x, vec![24, 2]).unwrap(), Tensor::new(y, vec![24]).unwrap());
+
+        let results = run_local(2, |group| {
+            // Different seeds: the broadcast has to line the models up.
+            set_global_seed(group.rank as u64);
+            let model: Sequential = Sequential::new().add(Box::new(Linear::new(2, 1)));
+            let adam = |params: &[Variable]| {
+                Adam::new(params.iter().map(|p| p.node_id).collect(), params.iter().map(|p| p.data.clone()).collect(), 0.1)
+            };
+            let mse = |out: &Variable, y: &Tensor<f64>| mse_loss(out, &out.constant(y.reshape(vec![y.numel(), 1]).unwrap()));
+            let mut trainer = DistributedTrainer::new(model, group, adam, mse).unwrap().with_epochs(40);
+            let shard = trainer.shard(&data);
+            let mut loader = DataLoader::new(&shard, 4, true);
+            let history = trainer.fit(&mut loader);
+            let losses = history.get("loss");
+            (losses[0], losses[39], trainer.trainer.model.state_dict())
+        });
+        assert_eq!(results[0].2, results[1].2);
+        for (first, last, _) in &results {
+            assert!(last < &(first / 10.0), "loss {} -> {}", first, last);
+        }
+    }
+}
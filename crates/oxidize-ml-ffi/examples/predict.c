@@ -0,0 +1,52 @@
+/* Load an .oxml model and predict for samples read from stdin, one per
+ * line, comma-separated.
+ *
+ *   cargo build --release -p oxidize-ml-ffi
+ *   cc crates/oxidize-ml-ffi/examples/predict.c -Icrates/oxidize-ml-ffi/include \
+ *      -Ltarget/release -loxidize_ml_ffi -o predict
+ *   echo "1.0,2.0" | LD_LIBRARY_PATH=target/release ./predict model.oxml
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include "oxidize_ml.h"
+
+#define MAX_FEATURES 1024
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s model.oxml < samples.csv\n", argv[0]);
+        return 2;
+    }
+    OxmlModel *model = oxml_load_model(argv[1]);
+    if (model == NULL) {
+        fprintf(stderr, "error: %s\n", oxml_last_error());
+        return 1;
+    }
+    printf("oxidize-ml %s\n", oxml_version());
+
+    char line[65536];
+    double sample[MAX_FEATURES];
+    while (fgets(line, sizeof line, stdin) != NULL) {
+        size_t cols = 0;
+        for (char *field = strtok(line, ",\n"); field != NULL && cols < MAX_FEATURES; field = strtok(NULL, ",\n")) {
+            sample[cols++] = strtod(field, NULL);
+        }
+        if (cols == 0) {
+            continue;
+        }
+        double *output = NULL;
+        size_t len = 0;
+        if (oxml_predict(model, sample, 1, cols, &output, &len) != OXML_OK) {
+            fprintf(stderr, "error: %s\n", oxml_last_error());
+            continue;
+        }
+        for (size_t i = 0; i < len; i++) {
+            printf(i == 0 ? "%g" : ",%g", output[i]);
+        }
+        printf("\n");
+        oxml_free_buffer(output, len);
+    }
+    oxml_free(model);
+    return 0;
+}
@@ -0,0 +1,49 @@
+/* C API for running OxidizeML models saved as .oxml files.
+ *
+ * Link against liboxidize_ml_ffi (built by
+ * `cargo build --release -p oxidize-ml-ffi`). Functions returning int give
+ * OXML_OK or OXML_ERROR; oxml_last_error() explains the last failure on
+ * the calling thread.
+ */
+#ifndef OXIDIZE_ML_H
+#define OXIDIZE_ML_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define OXML_OK 0
+#define OXML_ERROR -1
+
+/* A loaded model. */
+typedef struct OxmlModel OxmlModel;
+
+/* Load a pipeline or estimator saved as .oxml. Returns NULL on failure. */
+OxmlModel *oxml_load_model(const char *path);
+
+/* Predict for `rows` samples of `cols` features, row-major in `input`.
+ * On success *output points to *output_len values, one row per sample,
+ * to be released with oxml_free_buffer. */
+int oxml_predict(const OxmlModel *model, const double *input, size_t rows, size_t cols,
+                 double **output, size_t *output_len);
+
+/* Release a model. NULL is ignored. */
+void oxml_free(OxmlModel *model);
+
+/* Release an oxml_predict output. NULL is ignored. */
+void oxml_free_buffer(double *data, size_t len);
+
+/* The last error on this thread, or NULL. Valid until the next failing
+ * call on the same thread. */
+const char *oxml_last_error(void);
+
+/* The library version, e.g. "0.1.0". */
+const char *oxml_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OXIDIZE_ML_H */
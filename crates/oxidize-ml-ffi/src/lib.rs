@@ -0,0 +1,177 @@
+//! A C API for running saved `.oxml` models from C, C++, Go or anything
+//! else with a C FFI. The declarations are in `include/oxidize_ml.h`.
+//!
+//! Functions returning `int` give `OXML_OK` (0) or `OXML_ERROR` (-1); the
+//! reason for the last failure on the calling thread is available from
+//! `oxml_last_error`. Rust panics are caught at the boundary and reported
+//! the same way.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use oxidize_ml_core::Tensor;
+use oxidize_ml_serve::{load_oxml, Model};
+
+pub const OXML_OK: c_int = 0;
+pub const OXML_ERROR: c_int = -1;
+
+/// An opaque handle to a loaded model.
+pub struct OxmlModel {
+    model: Box<dyn Model>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: impl Into<String>) {
+    // Interior NULs would truncate the message; drop them instead.
+    let message = CString::new(message.into().replace('\0', "")).expect("NULs removed");
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Run `f`, turning an `Err` or a panic into `on_error` plus the thread's
+/// last error.
+fn guard<R>(on_error: R, f: impl FnOnce() -> Result<R, String>) -> R {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_error(message);
+            on_error
+        }
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_error(format!("panic: {}", message));
+            on_error
+        }
+    }
+}
+
+/// Load a pipeline or estimator saved as `.oxml`. Returns NULL on failure.
+///
+/// # Safety
+/// `path` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn oxml_load_model(path: *const c_char) -> *mut OxmlModel {
+    guard(ptr::null_mut(), || {
+        if path.is_null() {
+            return Err("path is NULL".to_string());
+        }
+        let path = CStr::from_ptr(path).to_str().map_err(|_| "path is not valid UTF-8".to_string())?;
+        let model = load_oxml(path).map_err(|e| format!("cannot load '{}': {}", path, e))?;
+        Ok(Box::into_raw(Box::new(OxmlModel { model })))
+    })
+}
+
+/// Predict for `rows` samples of `cols` features, row-major in `input`.
+/// On success `*output` points to `*output_len` values (one row per
+/// sample) to be released with `oxml_free_buffer`.
+///
+/// # Safety
+/// `model` must come from `oxml_load_model`, `input` must hold
+/// `rows * cols` doubles, and `output` and `output_len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn oxml_predict(
+    model: *const OxmlModel,
+    input: *const f64,
+    rows: usize,
+    cols: usize,
+    output: *mut *mut f64,
+    output_len: *mut usize,
+) -> c_int {
+    guard(OXML_ERROR, || {
+        if model.is_null() || input.is_null() || output.is_null() || output_len.is_null() {
+            return Err("NULL argument".to_string());
+        }
+        let n = rows.checked_mul(cols).ok_or("rows * cols overflows")?;
+        let x = Tensor::new(std::slice::from_raw_parts(input, n).to_vec(), vec![rows, cols]).map_err(|e| e.to_string())?;
+        let y = (*model).model.predict(&x).map_err(|e| e.to_string())?;
+        let values = y.data().to_vec().into_boxed_slice();
+        *output_len = values.len();
+        *output = Box::into_raw(values) as *mut f64;
+        Ok(OXML_OK)
+    })
+}
+
+/// Release a model. NULL is ignored.
+///
+/// # Safety
+/// `model` must come from `oxml_load_model` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn oxml_free(model: *mut OxmlModel) {
+    if !model.is_null() {
+        drop(Box::from_raw(model));
+    }
+}
+
+/// Release an `oxml_predict` output. NULL is ignored.
+///
+/// # Safety
+/// `data` and `len` must be exactly as returned by `oxml_predict`.
+#[no_mangle]
+pub unsafe extern "C" fn oxml_free_buffer(data: *mut f64, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+/// The last error on this thread, or NULL. Valid until the next failing
+/// call on the same thread.
+#[no_mangle]
+pub extern "C" fn oxml_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// The crate version, e.g. `"0.1.0"`.
+#[no_mangle]
+pub extern "C" fn oxml_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_linear::LinearRegression;
+    use oxidize_ml_pipeline::Persist;
+
+    #[test]
+    fn test_load_predict_free() {
+        let x = Tensor::new(vec![0.0, 1.0, 1.0, 0.0, 2.0, 1.0, 3.0, 2.0], vec![4, 2]).unwrap();
+        let y = Tensor::new(vec![1.0, 3.0, 5.0, 8.0], vec![4]).unwrap();
+        let mut reg = LinearRegression::new(true);
+        reg.fit(&x, &y).unwrap();
+        let path = std::env::temp_dir().join(format!("oxidize_ml_ffi_{}.oxml", std::process::id()));
+        reg.save(&path).unwrap();
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let model = oxml_load_model(c_path.as_ptr());
+            std::fs::remove_file(&path).ok();
+            assert!(!model.is_null());
+            let (mut out, mut len) = (ptr::null_mut(), 0);
+            assert_eq!(oxml_predict(model, x.data().as_ptr(), 4, 2, &mut out, &mut len), OXML_OK);
+            assert_eq!(std::slice::from_raw_parts(out, len), reg.predict(&x).unwrap().data());
+            oxml_free_buffer(out, len);
+
+            // Wrong width: an error code and a message, not a crash.
+            assert_eq!(oxml_predict(model, x.data().as_ptr(), 2, 4, &mut out, &mut len), OXML_ERROR);
+            assert!(!oxml_last_error().is_null());
+            oxml_free(model);
+
+            let missing = CString::new("/nonexistent/model.oxml").unwrap();
+            assert!(oxml_load_model(missing.as_ptr()).is_null());
+            assert!(CStr::from_ptr(oxml_last_error()).to_str().unwrap().contains("cannot load"));
+        }
+
+        // The header declares every exported function.
+        let header = include_str!("../include/oxidize_ml.h");
+        for name in ["oxml_load_model", "oxml_predict", "oxml_free(", "oxml_free_buffer", "oxml_last_error", "oxml_version"] {
+            assert!(header.contains(name), "header is missing {}", name);
+        }
+    }
+}
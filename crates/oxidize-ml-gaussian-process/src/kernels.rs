@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+/// Covariance function of a Gaussian process.
+///
+/// Kernels combine with `+` and `*`, e.g.
+/// `GPKernel::constant(1.0) * GPKernel::rbf(1.0) + GPKernel::white(0.1)`
+/// for a scaled RBF with observation noise. Hyperparameters are tuned in log
+/// space, in the order `params()` lists them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GPKernel {
+    /// `exp(-r² / 2l²)`.
+    RBF { length_scale: f64 },
+    /// Matérn with smoothness `nu` of 0.5, 1.5 or 2.5 (other values are
+    /// treated as ∞, i.e. RBF). Only `length_scale` is tuned.
+    Matern { length_scale: f64, nu: f64 },
+    /// A constant covariance; as a factor, the signal variance.
+    Constant { value: f64 },
+    /// Independent noise of variance `noise` on the training points.
+    White { noise: f64 },
+    Sum(Box<GPKernel>, Box<GPKernel>),
+    Product(Box<GPKernel>, Box<GPKernel>),
+}
+
+impl GPKernel {
+    pub fn rbf(length_scale: f64) -> Self {
+        GPKernel::RBF { length_scale }
+    }
+
+    pub fn matern(length_scale: f64, nu: f64) -> Self {
+        GPKernel::Matern { length_scale, nu }
+    }
+
+    pub fn constant(value: f64) -> Self {
+        GPKernel::Constant { value }
+    }
+
+    pub fn white(noise: f64) -> Self {
+        GPKernel::White { noise }
+    }
+
+    /// `k(a, b)`. `same` says `a` and `b` are the same training point, the
+    /// only case where `White` contributes.
+    pub fn compute(&self, a: &[f64], b: &[f64], same: bool) -> f64 {
+        match self {
+            GPKernel::RBF { length_scale } => (-0.5 * sq_dist(a, b) / (length_scale * length_scale)).exp(),
+            GPKernel::Matern { length_scale, nu } => {
+                let r = sq_dist(a, b).sqrt() / length_scale;
+                matern(r, *nu).0
+            }
+            GPKernel::Constant { value } => *value,
+            GPKernel::White { noise } => {
+                if same {
+                    *noise
+                } else {
+                    0.0
+                }
+            }
+            GPKernel::Sum(k1, k2) => k1.compute(a, b, same) + k2.compute(a, b, same),
+            GPKernel::Product(k1, k2) => k1.compute(a, b, same) * k2.compute(a, b, same),
+        }
+    }
+
+    /// Derivatives of `k(a, b)` with respect to each log hyperparameter.
+    pub fn gradient(&self, a: &[f64], b: &[f64], same: bool) -> Vec<f64> {
+        match self {
+            GPKernel::RBF { length_scale } => {
+                let s = sq_dist(a, b) / (length_scale * length_scale);
+                vec![(-0.5 * s).exp() * s]
+            }
+            GPKernel::Matern { length_scale, nu } => {
+                let r = sq_dist(a, b).sqrt() / length_scale;
+                vec![matern(r, *nu).1]
+            }
+            GPKernel::Constant { value } => vec![*value],
+            GPKernel::White { noise } => vec![if same { *noise } else { 0.0 }],
+            GPKernel::Sum(k1, k2) => {
+                let mut g = k1.gradient(a, b, same);
+                g.extend(k2.gradient(a, b, same));
+                g
+            }
+            GPKernel::Product(k1, k2) => {
+                let (v1, v2) = (k1.compute(a, b, same), k2.compute(a, b, same));
+                let mut g: Vec<f64> = k1.gradient(a, b, same).into_iter().map(|d| d * v2).collect();
+                g.extend(k2.gradient(a, b, same).into_iter().map(|d| d * v1));
+                g
+            }
+        }
+    }
+
+    /// Log hyperparameters, depth first.
+    pub fn params(&self) -> Vec<f64> {
+        match self {
+            GPKernel::RBF { length_scale } | GPKernel::Matern { length_scale, .. } => vec![length_scale.ln()],
+            GPKernel::Constant { value } => vec![value.ln()],
+            GPKernel::White { noise } => vec![noise.ln()],
+            GPKernel::Sum(k1, k2) | GPKernel::Product(k1, k2) => {
+                let mut p = k1.params();
+                p.extend(k2.params());
+                p
+            }
+        }
+    }
+
+    pub fn n_params(&self) -> usize {
+        match self {
+            GPKernel::Sum(k1, k2) | GPKernel::Product(k1, k2) => k1.n_params() + k2.n_params(),
+            _ => 1,
+        }
+    }
+
+    /// The same kernel with log hyperparameters `params`, in `params()`
+    /// order.
+    pub fn with_params(&self, params: &[f64]) -> GPKernel {
+        match self {
+            GPKernel::RBF { .. } => GPKernel::rbf(params[0].exp()),
+            GPKernel::Matern { nu, .. } => GPKernel::matern(params[0].exp(), *nu),
+            GPKernel::Constant { .. } => GPKernel::constant(params[0].exp()),
+            GPKernel::White { .. } => GPKernel::white(params[0].exp()),
+            GPKernel::Sum(k1, k2) => {
+                let (p1, p2) = params.split_at(k1.n_params());
+                GPKernel::Sum(Box::new(k1.with_params(p1)), Box::new(k2.with_params(p2)))
+            }
+            GPKernel::Product(k1, k2) => {
+                let (p1, p2) = params.split_at(k1.n_params());
+                GPKernel::Product(Box::new(k1.with_params(p1)), Box::new(k2.with_params(p2)))
+            }
+        }
+    }
+}
+
+impl std::ops::Add for GPKernel {
+    type Output = GPKernel;
+
+    fn add(self, rhs: GPKernel) -> GPKernel {
+        GPKernel::Sum(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl std::ops::Mul for GPKernel {
+    type Output = GPKernel;
+
+    fn mul(self, rhs: GPKernel) -> GPKernel {
+        GPKernel::Product(Box::new(self), Box::new(rhs))
+    }
+}
+
+fn sq_dist(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(u, v)| (u - v) * (u - v)).sum()
+}
+
+/// Matérn value at scaled distance `r = d / l`, and its derivative with
+/// respect to `log l`.
+fn matern(r: f64, nu: f64) -> (f64, f64) {
+    if nu == 0.5 {
+        let e = (-r).exp();
+        (e, r * e)
+    } else if nu == 1.5 {
+        let s = 3f64.sqrt() * r;
+        let e = (-s).exp();
+        ((1.0 + s) * e, s * s * e)
+    } else if nu == 2.5 {
+        let s = 5f64.sqrt() * r;
+        let e = (-s).exp();
+        ((1.0 + s + s * s / 3.0) * e, s * s * (1.0 + s) / 3.0 * e)
+    } else {
+        let e = (-0.5 * r * r).exp();
+        (e, r * r * e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_gradient_matches_finite_differences() {
+        let kernel = GPKernel::constant(2.0) * GPKernel::matern(0.7, 2.5) + GPKernel::rbf(1.3) + GPKernel::white(0.1);
+        let (a, b) = ([0.2, -1.0], [0.9, 0.4]);
+        let params = kernel.params();
+        assert_eq!(params.len(), 4);
+        let grad = kernel.gradient(&a, &b, true);
+        for i in 0..params.len() {
+            let mut up = params.clone();
+            let mut down = params.clone();
+            up[i] += 1e-6;
+            down[i] -= 1e-6;
+            let numeric = (kernel.with_params(&up).compute(&a, &b, true) - kernel.with_params(&down).compute(&a, &b, true)) / 2e-6;
+            assert!((grad[i] - numeric).abs() < 1e-6, "param {}: {} vs {}", i, grad[i], numeric);
+        }
+        assert!((kernel.compute(&a, &a, false) + 0.1 - kernel.compute(&a, &a, true)).abs() < 1e-12);
+    }
+}
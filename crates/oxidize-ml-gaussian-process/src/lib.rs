@@ -0,0 +1,5 @@
+pub mod kernels;
+pub mod regressor;
+
+pub use kernels::*;
+pub use regressor::*;
@@ -0,0 +1,370 @@
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::{Float, RngHandle, Tensor, TensorError};
+use oxidize_ml_linalg::{solve_triangular, Triangle};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::kernels::GPKernel;
+
+/// Log hyperparameters stay within `[1e-5, 1e5]` during optimization.
+const LOG_BOUND: f64 = 11.512925464970229;
+
+/// Cholesky factor, `α = K⁻¹ y` and log marginal likelihood of a GP with
+/// `kernel` on rows `x` (`p` columns) and targets `y`.
+struct Posterior {
+    l: Vec<f64>,
+    alpha: Vec<f64>,
+    lml: f64,
+}
+
+fn posterior(kernel: &GPKernel, x: &[f64], p: usize, y: &[f64], jitter: f64) -> TensorResult<Posterior> {
+    let n = y.len();
+    let mut k = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..=i {
+            let v = kernel.compute(&x[i * p..(i + 1) * p], &x[j * p..(j + 1) * p], i == j);
+            k[i * n + j] = v;
+            k[j * n + i] = v;
+        }
+        k[i * n + i] += jitter;
+    }
+    let factor = oxidize_ml_linalg::cholesky(&Tensor::new(k, vec![n, n])?)
+        .map_err(|_| {
+            TensorError::InvalidOperation(
+                "kernel matrix is not positive definite; increase alpha or add a White kernel".into(),
+            )
+        })?
+        .l;
+    let z = solve_triangular(&factor, &Tensor::new(y.to_vec(), vec![n])?, Triangle::Lower, false)?;
+    let alpha = solve_triangular(&factor, &z, Triangle::Lower, true)?.data().to_vec();
+    let l = factor.data().to_vec();
+    let log_det: f64 = (0..n).map(|i| l[i * n + i].ln()).sum();
+    let fit: f64 = y.iter().zip(&alpha).map(|(a, b)| a * b).sum();
+    let lml = -0.5 * fit - log_det - 0.5 * n as f64 * (2.0 * std::f64::consts::PI).ln();
+    Ok(Posterior { l, alpha, lml })
+}
+
+/// Gradient of the log marginal likelihood with respect to the kernel's
+/// log hyperparameters: `½ tr((ααᵀ - K⁻¹) ∂K/∂θ)`.
+fn lml_gradient(kernel: &GPKernel, x: &[f64], p: usize, post: &Posterior) -> TensorResult<Vec<f64>> {
+    let n = post.alpha.len();
+    let l = Tensor::new(post.l.clone(), vec![n, n])?;
+    let l_inv = solve_triangular(&l, &Tensor::<f64>::eye(n), Triangle::Lower, false)?;
+    let k_inv = solve_triangular(&l, &l_inv, Triangle::Lower, true)?;
+    let k_inv = k_inv.data();
+    let mut grad = vec![0.0; kernel.n_params()];
+    for i in 0..n {
+        for j in 0..=i {
+            let w = post.alpha[i] * post.alpha[j] - k_inv[i * n + j];
+            // Off-diagonal terms appear twice in the trace.
+            let weight = if i == j { 0.5 * w } else { w };
+            let dk = kernel.gradient(&x[i * p..(i + 1) * p], &x[j * p..(j + 1) * p], i == j);
+            for (g, d) in grad.iter_mut().zip(dk) {
+                *g += weight * d;
+            }
+        }
+    }
+    Ok(grad)
+}
+
+/// Gaussian process regression.
+///
+/// The posterior is computed exactly through a Cholesky factor of the
+/// training kernel matrix, so fitting is `O(n³)`. With `optimize` the
+/// kernel hyperparameters are first tuned to maximize the log marginal
+/// likelihood, by gradient ascent in log space from the initial kernel and
+/// `n_restarts` random starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct GaussianProcessRegressor<T: Float> {
+    /// Initial kernel; see `fitted_kernel` for the tuned one.
+    pub kernel: GPKernel,
+    /// Added to the diagonal of the training kernel matrix, for numerical
+    /// stability or as known noise variance.
+    pub alpha: f64,
+    /// Fit to targets scaled to zero mean and unit variance.
+    pub normalize_y: bool,
+    /// Tune the kernel hyperparameters.
+    pub optimize: bool,
+    /// Extra optimizer runs from random hyperparameters.
+    pub n_restarts: usize,
+    pub max_iter: usize,
+    pub seed: Option<u64>,
+    // Fitted state
+    pub fitted_kernel: Option<GPKernel>,
+    x_train: Vec<f64>,
+    n_features: usize,
+    l: Vec<f64>,
+    dual_coef: Vec<f64>,
+    y_mean: f64,
+    y_std: f64,
+    y_train: Vec<f64>,
+    lml: Option<f64>,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Float> GaussianProcessRegressor<T> {
+    pub fn new(kernel: GPKernel) -> Self {
+        GaussianProcessRegressor {
+            kernel,
+            alpha: 1e-10,
+            normalize_y: false,
+            optimize: true,
+            n_restarts: 0,
+            max_iter: 100,
+            seed: None,
+            fitted_kernel: None,
+            x_train: Vec::new(),
+            n_features: 0,
+            l: Vec::new(),
+            dual_coef: Vec::new(),
+            y_mean: 0.0,
+            y_std: 1.0,
+            y_train: Vec::new(),
+            lml: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_normalize_y(mut self, normalize_y: bool) -> Self {
+        self.normalize_y = normalize_y;
+        self
+    }
+
+    pub fn with_optimize(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+
+    pub fn with_n_restarts(mut self, n_restarts: usize) -> Self {
+        self.n_restarts = n_restarts;
+        self
+    }
+
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (n, p) = (x.shape().dim(0)?, x.shape().dim(1)?);
+        if y.numel() != n {
+            return Err(TensorError::InvalidOperation(format!(
+                "x has {} rows but y has {} targets", n, y.numel()
+            )));
+        }
+        if n == 0 {
+            return Err(TensorError::InvalidOperation("GaussianProcessRegressor needs at least one sample".into()));
+        }
+        let xs: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+        let mut ys: Vec<f64> = y.data().iter().map(|v| v.to_f64()).collect();
+        let (mut mean, mut std) = (0.0, 1.0);
+        if self.normalize_y {
+            mean = ys.iter().sum::<f64>() / n as f64;
+            let var = ys.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n as f64;
+            std = if var > 0.0 { var.sqrt() } else { 1.0 };
+            ys.iter_mut().for_each(|v| *v = (*v - mean) / std);
+        }
+
+        let mut kernel = self.kernel.clone();
+        if self.optimize {
+            let mut rng = RngHandle::new(self.seed);
+            let mut best: Option<(f64, Vec<f64>)> = None;
+            for run in 0..=self.n_restarts {
+                let start = if run == 0 {
+                    kernel.params()
+                } else {
+                    (0..kernel.n_params()).map(|_| rng.gen_range(-LOG_BOUND..LOG_BOUND)).collect()
+                };
+                if let Some((lml, params)) = self.ascend(&xs, p, &ys, start) {
+                    if best.as_ref().is_none_or(|(b, _)| lml > *b) {
+                        best = Some((lml, params));
+                    }
+                }
+            }
+            match best {
+                Some((_, params)) => kernel = kernel.with_params(&params),
+                None => {
+                    return Err(TensorError::InvalidOperation(
+                        "kernel matrix is not positive definite at any start; increase alpha".into(),
+                    ))
+                }
+            }
+        }
+
+        let post = posterior(&kernel, &xs, p, &ys, self.alpha)?;
+        self.fitted_kernel = Some(kernel);
+        self.x_train = xs;
+        self.n_features = p;
+        self.l = post.l;
+        self.dual_coef = post.alpha;
+        self.y_mean = mean;
+        self.y_std = std;
+        self.y_train = ys;
+        self.lml = Some(post.lml);
+        Ok(())
+    }
+
+    /// Gradient ascent on the log marginal likelihood from log
+    /// hyperparameters `params`, with a backtracking step size. `None` if
+    /// the start itself is not positive definite.
+    fn ascend(&self, x: &[f64], p: usize, y: &[f64], mut params: Vec<f64>) -> Option<(f64, Vec<f64>)> {
+        let clamp = |v: &mut Vec<f64>| v.iter_mut().for_each(|t| *t = t.clamp(-LOG_BOUND, LOG_BOUND));
+        clamp(&mut params);
+        let kernel = self.kernel.with_params(&params);
+        let mut post = posterior(&kernel, x, p, y, self.alpha).ok()?;
+        let mut grad = lml_gradient(&kernel, x, p, &post).ok()?;
+        let mut step = 1.0;
+        for _ in 0..self.max_iter {
+            let norm2: f64 = grad.iter().map(|g| g * g).sum();
+            if norm2.sqrt() < 1e-5 {
+                break;
+            }
+            let mut accepted = false;
+            while step > 1e-10 {
+                let mut trial: Vec<f64> = params.iter().zip(&grad).map(|(t, g)| t + step * g).collect();
+                clamp(&mut trial);
+                let kernel = self.kernel.with_params(&trial);
+                if let Ok(next) = posterior(&kernel, x, p, y, self.alpha) {
+                    if next.lml > post.lml + 1e-4 * step * norm2 {
+                        grad = lml_gradient(&kernel, x, p, &next).ok()?;
+                        params = trial;
+                        post = next;
+                        step *= 2.0;
+                        accepted = true;
+                        break;
+                    }
+                }
+                step *= 0.5;
+            }
+            if !accepted {
+                break;
+            }
+        }
+        Some((post.lml, params))
+    }
+
+    /// Log marginal likelihood of the training data under the fitted kernel.
+    pub fn log_marginal_likelihood_value(&self) -> Option<f64> {
+        self.lml
+    }
+
+    /// Log marginal likelihood of the training data under the kernel with
+    /// log hyperparameters `params` (see `GPKernel::params`).
+    pub fn log_marginal_likelihood(&self, params: &[f64]) -> TensorResult<f64> {
+        if self.lml.is_none() {
+            return Err(TensorError::InvalidOperation("GaussianProcessRegressor is not fitted".into()));
+        }
+        if params.len() != self.kernel.n_params() {
+            return Err(TensorError::InvalidOperation(format!(
+                "kernel has {} hyperparameters, got {}", self.kernel.n_params(), params.len()
+            )));
+        }
+        let kernel = self.kernel.with_params(params);
+        Ok(posterior(&kernel, &self.x_train, self.n_features, &self.y_train, self.alpha)?.lml)
+    }
+
+    /// Posterior mean at each row of `x`.
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        Ok(self.predict_with_variance(x)?.0)
+    }
+
+    /// Posterior mean and variance at each row of `x`. The variance
+    /// includes any `White` noise in the kernel, i.e. it is the variance of
+    /// a new observation rather than of the latent function.
+    pub fn predict_with_variance(&self, x: &Tensor<T>) -> TensorResult<(Tensor<T>, Tensor<T>)> {
+        let kernel = self
+            .fitted_kernel
+            .as_ref()
+            .ok_or_else(|| TensorError::InvalidOperation("GaussianProcessRegressor is not fitted".into()))?;
+        let (m, p) = (x.shape().dim(0)?, x.shape().dim(1)?);
+        if p != self.n_features {
+            return Err(TensorError::ShapeMismatch {
+                expected: vec![m, self.n_features],
+                got: x.shape_vec(),
+            });
+        }
+        let n = self.dual_coef.len();
+        let xs: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+        // K*ᵀ as [n, m]; V = L⁻¹ K*ᵀ gives every predictive variance at once.
+        let mut k_star = vec![0.0; n * m];
+        for (r, row) in xs.chunks(p.max(1)).take(m).enumerate() {
+            for j in 0..n {
+                k_star[j * m + r] = kernel.compute(row, &self.x_train[j * p..(j + 1) * p], false);
+            }
+        }
+        let l = Tensor::new(self.l.clone(), vec![n, n])?;
+        let v = solve_triangular(&l, &Tensor::new(k_star.clone(), vec![n, m])?, Triangle::Lower, false)?;
+        let v = v.data();
+        let (mut means, mut vars) = (Vec::with_capacity(m), Vec::with_capacity(m));
+        for (r, row) in xs.chunks(p.max(1)).take(m).enumerate() {
+            let mean: f64 = (0..n).map(|j| k_star[j * m + r] * self.dual_coef[j]).sum();
+            let var = kernel.compute(row, row, true) - (0..n).map(|j| v[j * m + r].powi(2)).sum::<f64>();
+            means.push(T::from_f64(mean * self.y_std + self.y_mean));
+            vars.push(T::from_f64(var.max(0.0) * self.y_std * self.y_std));
+        }
+        Ok((Tensor::new(means, vec![m])?, Tensor::new(vars, vec![m])?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(n: usize) -> (Tensor<f64>, Tensor<f64>) {
+        let xs: Vec<f64> = (0..n).map(|i| i as f64 * 6.0 / (n - 1) as f64).collect();
+        let ys = xs.iter().map(|v| v.sin()).collect();
+        (Tensor::new(xs, vec![n, 1]).unwrap(), Tensor::new(ys, vec![n]).unwrap())
+    }
+
+    #[test]
+    fn test_gp_interpolates_with_fixed_kernel() {
+        let (x, y) = sine(8);
+        let mut gp = GaussianProcessRegressor::new(GPKernel::rbf(1.0)).with_optimize(false);
+        gp.fit(&x, &y).unwrap();
+        let (mean, var) = gp.predict_with_variance(&x).unwrap();
+        for (m, t) in mean.data().iter().zip(y.data()) {
+            assert!((m - t).abs() < 1e-6);
+        }
+        assert!(var.data().iter().all(|&v| v < 1e-6));
+
+        // Far from the data the prior takes over again.
+        let far = Tensor::new(vec![50.0], vec![1, 1]).unwrap();
+        let (mean, var) = gp.predict_with_variance(&far).unwrap();
+        assert!(mean.data()[0].abs() < 1e-6 && (var.data()[0] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_gp_optimizes_marginal_likelihood() {
+        let (x, y) = sine(25);
+        let kernel = GPKernel::constant(1.0) * GPKernel::matern(0.1, 2.5) + GPKernel::white(1.0);
+        let mut fixed = GaussianProcessRegressor::new(kernel.clone()).with_optimize(false);
+        fixed.fit(&x, &y).unwrap();
+        let mut gp = GaussianProcessRegressor::new(kernel).with_n_restarts(2).with_seed(3).with_normalize_y(true);
+        gp.fit(&x, &y).unwrap();
+
+        let tuned = gp.fitted_kernel.clone().unwrap().params();
+        let lml = gp.log_marginal_likelihood_value().unwrap();
+        assert!((gp.log_marginal_likelihood(&tuned).unwrap() - lml).abs() < 1e-9);
+        assert!(lml > fixed.log_marginal_likelihood_value().unwrap());
+        // Noise-free data: the fitted noise collapses and the length scale
+        // grows well past its start.
+        assert!(tuned[2].exp() < 1e-2 && tuned[1].exp() > 0.5, "{:?}", tuned);
+
+        let test = Tensor::new(vec![1.3, 4.1], vec![2, 1]).unwrap();
+        let (mean, var) = gp.predict_with_variance(&test).unwrap();
+        assert!((mean.data()[0] - 1.3f64.sin()).abs() < 1e-2 && (mean.data()[1] - 4.1f64.sin()).abs() < 1e-2);
+        assert!(var.data().iter().all(|&v| v < 1e-2));
+    }
+}
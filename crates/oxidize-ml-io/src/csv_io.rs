@@ -1,11 +1,18 @@
 use oxidize_ml_core::Tensor;
 use std::error::Error;
+#[cfg(feature = "fs")]
 use std::path::Path;
 
 /// Read a CSV file into a tensor and column headers.
 /// Assumes all values are numeric. Skips the header row.
+#[cfg(feature = "fs")]
 pub fn read_csv(path: &str) -> Result<(Tensor<f64>, Vec<String>), Box<dyn Error>> {
-    let mut rdr = csv::Reader::from_path(Path::new(path))?;
+    parse_csv(&std::fs::read(Path::new(path))?)
+}
+
+/// `read_csv` on CSV text already in memory.
+pub fn parse_csv(bytes: &[u8]) -> Result<(Tensor<f64>, Vec<String>), Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_reader(bytes);
     let headers: Vec<String> = rdr.headers()?.iter().map(|h| h.to_string()).collect();
 
     let mut data = Vec::new();
@@ -20,7 +27,7 @@ pub fn read_csv(path: &str) -> Result<(Tensor<f64>, Vec<String>), Box<dyn Error>
         n_rows += 1;
     }
 
-    let n_cols = if n_rows > 0 { data.len() / n_rows } else { 0 };
+    let n_cols = data.len().checked_div(n_rows).unwrap_or(0);
     let tensor = Tensor::new(data, vec![n_rows, n_cols])
         .map_err(|e| format!("Failed to create tensor: {:?}", e))?;
 
@@ -28,6 +35,7 @@ pub fn read_csv(path: &str) -> Result<(Tensor<f64>, Vec<String>), Box<dyn Error>
 }
 
 /// Write a tensor to a CSV file with optional headers.
+#[cfg(feature = "fs")]
 pub fn write_csv(path: &str, data: &Tensor<f64>, headers: Option<&[String]>) -> Result<(), Box<dyn Error>> {
     let mut wtr = csv::Writer::from_path(Path::new(path))?;
 
@@ -0,0 +1,851 @@
+//! GGUF, the weight format of llama.cpp and ggml.
+//!
+//! A file is a `GGUF` magic and version, typed key/value metadata, a table
+//! of tensor descriptions and then the aligned tensor data. Tensors may be
+//! stored as `F32`, `F16`, `BF16` or `F64`, or block-quantized (`Q4_0`,
+//! `Q4_1`, `Q5_0`, `Q5_1`, `Q8_0` and the k-quants `Q4_K`, `Q5_K`,
+//! `Q6_K`); all are dequantized to the element type when read.
+//!
+//! ggml lists dimensions fastest-first, so `shape` here is that list
+//! reversed: a matrix written from a PyTorch `[out, in]` weight reads back
+//! as `[out, in]`. `bert_encoder` maps llama.cpp's `bert` architecture onto
+//! `BertEncoder`, transposing the projections into `Linear`'s `[in, out]`.
+//!
+//! `bert` is the only architecture that can be loaded as a model. The
+//! decoder LMs llama.cpp is mostly used for (llama, mistral, gpt2, …) have
+//! no counterpart in `oxidize-ml-nn`; their tensors and metadata can still
+//! be read and dequantized, but not assembled into a network.
+
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_nn::nlp::tokenizer::Vocab;
+use oxidize_ml_nn::{BertConfig, BertEncoder, Layer, StateDict};
+use std::collections::BTreeMap;
+use std::error::Error;
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use crate::safetensors::f16_to_f64;
+
+const MAGIC: &[u8; 4] = b"GGUF";
+const DEFAULT_ALIGNMENT: usize = 32;
+/// Marks a word-initial token in llama.cpp's BERT vocabularies.
+const PHANTOM_SPACE: char = '\u{2581}';
+
+/// A metadata value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GgufValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    String(String),
+    Array(Vec<GgufValue>),
+}
+
+impl GgufValue {
+    /// The value of a non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            GgufValue::U8(v) => Some(v as u64),
+            GgufValue::U16(v) => Some(v as u64),
+            GgufValue::U32(v) => Some(v as u64),
+            GgufValue::U64(v) => Some(v),
+            GgufValue::I8(v) => u64::try_from(v).ok(),
+            GgufValue::I16(v) => u64::try_from(v).ok(),
+            GgufValue::I32(v) => u64::try_from(v).ok(),
+            GgufValue::I64(v) => u64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    /// The value of any number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            GgufValue::F32(v) => Some(v as f64),
+            GgufValue::F64(v) => Some(v),
+            GgufValue::I8(v) => Some(v as f64),
+            GgufValue::I16(v) => Some(v as f64),
+            GgufValue::I32(v) => Some(v as f64),
+            GgufValue::I64(v) => Some(v as f64),
+            _ => self.as_u64().map(|v| v as f64),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            GgufValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[GgufValue]> {
+        match self {
+            GgufValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Storage type of a tensor.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GgmlType {
+    F32,
+    F16,
+    BF16,
+    F64,
+    Q4_0,
+    Q4_1,
+    Q5_0,
+    Q5_1,
+    Q8_0,
+    Q4_K,
+    Q5_K,
+    Q6_K,
+    /// A type id this reader cannot dequantize.
+    Other(u32),
+}
+
+impl GgmlType {
+    fn from_id(id: u32) -> Self {
+        match id {
+            0 => GgmlType::F32,
+            1 => GgmlType::F16,
+            2 => GgmlType::Q4_0,
+            3 => GgmlType::Q4_1,
+            6 => GgmlType::Q5_0,
+            7 => GgmlType::Q5_1,
+            8 => GgmlType::Q8_0,
+            12 => GgmlType::Q4_K,
+            13 => GgmlType::Q5_K,
+            14 => GgmlType::Q6_K,
+            28 => GgmlType::F64,
+            30 => GgmlType::BF16,
+            other => GgmlType::Other(other),
+        }
+    }
+
+    /// `(values per block, bytes per block)`.
+    fn block(self) -> Option<(usize, usize)> {
+        Some(match self {
+            GgmlType::F32 => (1, 4),
+            GgmlType::F16 | GgmlType::BF16 => (1, 2),
+            GgmlType::F64 => (1, 8),
+            GgmlType::Q4_0 => (32, 18),
+            GgmlType::Q4_1 => (32, 20),
+            GgmlType::Q5_0 => (32, 22),
+            GgmlType::Q5_1 => (32, 24),
+            GgmlType::Q8_0 => (32, 34),
+            GgmlType::Q4_K => (256, 144),
+            GgmlType::Q5_K => (256, 176),
+            GgmlType::Q6_K => (256, 210),
+            GgmlType::Other(_) => return None,
+        })
+    }
+}
+
+/// One entry of the tensor table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgufTensorInfo {
+    pub name: String,
+    /// Row-major (slowest dimension first).
+    pub shape: Vec<usize>,
+    pub dtype: GgmlType,
+    /// Byte offset into the data section.
+    pub offset: usize,
+}
+
+/// A parsed GGUF file. Tensors are dequantized on demand.
+#[derive(Debug, Clone)]
+pub struct Gguf {
+    pub version: u32,
+    pub metadata: BTreeMap<String, GgufValue>,
+    pub tensors: Vec<GgufTensorInfo>,
+    bytes: Vec<u8>,
+    data_start: usize,
+}
+
+/// Parse a GGUF buffer (format versions 2 and 3).
+pub fn parse_gguf(bytes: &[u8]) -> Result<Gguf, Box<dyn Error>> {
+    Gguf::from_vec(bytes.to_vec())
+}
+
+/// Read a `.gguf` file.
+#[cfg(feature = "fs")]
+pub fn load_gguf(path: &str) -> Result<Gguf, Box<dyn Error>> {
+    Gguf::from_vec(fs::read(Path::new(path))?)
+}
+
+fn too_large(name: &str) -> Box<dyn Error> {
+    format!("gguf: tensor too large: '{}'", name).into()
+}
+
+/// Elements in `shape`; dimensions come from the file, so the product is
+/// checked.
+fn numel(name: &str, shape: &[usize]) -> Result<usize, Box<dyn Error>> {
+    shape.iter().try_fold(1usize, |n, &d| n.checked_mul(d)).ok_or_else(|| too_large(name))
+}
+
+impl Gguf {
+    fn from_vec(bytes: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        let mut r = Reader { bytes: &bytes, pos: 0 };
+        if r.take(4)? != MAGIC {
+            return Err("gguf: not a GGUF file".into());
+        }
+        let version = r.u32()?;
+        if !(2..=3).contains(&version) {
+            return Err(format!("gguf: unsupported version {}", version).into());
+        }
+        let n_tensors = r.u64()?;
+        let n_metadata = r.u64()?;
+
+        let mut metadata = BTreeMap::new();
+        for _ in 0..n_metadata {
+            let key = r.string()?;
+            let ty = r.u32()?;
+            metadata.insert(key, r.value(ty)?);
+        }
+        let mut tensors = Vec::new();
+        for _ in 0..n_tensors {
+            let name = r.string()?;
+            let n_dims = r.u32()?;
+            let mut shape = (0..n_dims).map(|_| r.size(&name)).collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+            shape.reverse();
+            numel(&name, &shape)?;
+            let dtype = GgmlType::from_id(r.u32()?);
+            let offset = r.size(&name)?;
+            tensors.push(GgufTensorInfo { name, shape, dtype, offset });
+        }
+
+        let alignment = match metadata.get("general.alignment") {
+            Some(v) => v
+                .as_u64()
+                .filter(|&a| a > 0)
+                .and_then(|a| usize::try_from(a).ok())
+                .ok_or("gguf: invalid general.alignment")?,
+            None => DEFAULT_ALIGNMENT,
+        };
+        let data_start = r.pos.checked_next_multiple_of(alignment).ok_or("gguf: general.alignment too large")?;
+        Ok(Gguf { version, metadata, tensors, bytes, data_start })
+    }
+
+    /// `general.architecture`, e.g. `"llama"` or `"bert"`.
+    pub fn architecture(&self) -> Option<&str> {
+        self.metadata.get("general.architecture")?.as_str()
+    }
+
+    pub fn tensor_info(&self, name: &str) -> Option<&GgufTensorInfo> {
+        self.tensors.iter().find(|t| t.name == name)
+    }
+
+    /// Dequantize one tensor.
+    pub fn tensor<T: Float>(&self, name: &str) -> Result<Tensor<T>, Box<dyn Error>> {
+        let info = self.tensor_info(name).ok_or_else(|| format!("gguf: missing tensor '{}'", name))?;
+        let (block_len, block_bytes) =
+            info.dtype.block().ok_or_else(|| format!("gguf: '{}' has unsupported type {:?}", name, info.dtype))?;
+        let numel = numel(name, &info.shape)?;
+        if !numel.is_multiple_of(block_len) {
+            return Err(format!("gguf: '{}' has {} values, not whole {:?} blocks", name, numel, info.dtype).into());
+        }
+        let len = (numel / block_len).checked_mul(block_bytes).ok_or_else(|| too_large(name))?;
+        let raw = self
+            .data_start
+            .checked_add(info.offset)
+            .and_then(|start| self.bytes.get(start..start.checked_add(len)?))
+            .ok_or_else(|| format!("gguf: '{}' lies outside the file", name))?;
+        let mut values = Vec::with_capacity(numel);
+        for block in raw.chunks_exact(block_bytes) {
+            dequantize_block(info.dtype, block, &mut values);
+        }
+        Ok(Tensor::new(values.into_iter().map(T::from_f64).collect(), info.shape.clone())?)
+    }
+
+    /// Every tensor, under its GGUF name.
+    pub fn state_dict<T: Float>(&self) -> Result<StateDict<T>, Box<dyn Error>> {
+        self.tensors.iter().map(|t| Ok((t.name.clone(), self.tensor(&t.name)?))).collect()
+    }
+
+    /// The vocabulary in `tokenizer.ggml.tokens`, row `i` of the token
+    /// embedding being token `i`. llama.cpp rewrites BERT's WordPiece
+    /// tokens (a leading `▁` for word starts, no `##` on continuations);
+    /// for `tokenizer.ggml.model = "bert"` that is undone, so the result
+    /// works with `WordPiece`.
+    pub fn vocab(&self) -> Result<Vocab, Box<dyn Error>> {
+        let tokens = self
+            .metadata
+            .get("tokenizer.ggml.tokens")
+            .and_then(GgufValue::as_array)
+            .ok_or("gguf: missing tokenizer.ggml.tokens")?;
+        let wordpiece = self.metadata.get("tokenizer.ggml.model").and_then(GgufValue::as_str) == Some("bert");
+        let mut vocab = Vocab::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let token = token.as_str().ok_or("gguf: tokenizer.ggml.tokens holds a non-string")?;
+            let token = match token.strip_prefix(PHANTOM_SPACE) {
+                _ if !wordpiece || (token.starts_with('[') && token.ends_with(']')) => token.to_string(),
+                Some(word) => word.to_string(),
+                None => format!("##{}", token),
+            };
+            if vocab.add(&token) != i {
+                return Err(format!("gguf: duplicate token '{}'", token).into());
+            }
+        }
+        Ok(vocab)
+    }
+
+    fn usize_meta(&self, key: &str) -> Result<usize, Box<dyn Error>> {
+        let value = self.metadata.get(key).ok_or_else(|| format!("gguf: missing metadata '{}'", key))?;
+        Ok(value.as_u64().ok_or_else(|| format!("gguf: '{}' is not an integer", key))? as usize)
+    }
+
+    /// Build a `BertEncoder` from a `bert` file (e.g. a sentence-embedding
+    /// model converted by llama.cpp). Other architectures (llama, gpt2, …)
+    /// use RoPE, RMSNorm or pre-norm blocks, which `TransformerBlock` does
+    /// not share, and are rejected.
+    pub fn bert_encoder<T: Float>(&self) -> Result<BertEncoder<T>, Box<dyn Error>> {
+        let arch = self.architecture().ok_or("gguf: missing general.architecture")?;
+        if arch != "bert" {
+            return Err(format!("gguf: architecture '{}' is not supported; only 'bert' maps onto TransformerBlock", arch).into());
+        }
+        let rows = |name: &str| -> Result<usize, Box<dyn Error>> {
+            let info = self.tensor_info(name).ok_or_else(|| format!("gguf: missing tensor '{}'", name))?;
+            info.shape.first().copied().ok_or_else(|| format!("gguf: '{}' is a scalar", name).into())
+        };
+        let eps = self.metadata.get("bert.attention.layer_norm_epsilon").and_then(GgufValue::as_f64).unwrap_or(1e-12);
+        let config = BertConfig::new(
+            rows("token_embd.weight")?,
+            self.usize_meta("bert.embedding_length")?,
+            self.usize_meta("bert.attention.head_count")?,
+            self.usize_meta("bert.feed_forward_length")?,
+            self.usize_meta("bert.block_count")?,
+        )
+        .with_max_positions(rows("position_embd.weight")?)
+        .with_type_vocab_size(rows("token_types.weight")?)
+        .with_layer_norm_eps(eps);
+
+        let mut state = StateDict::new();
+        let copy = |state: &mut StateDict<T>, from: &str, to: &str| -> Result<(), Box<dyn Error>> {
+            state.insert(to.to_string(), self.tensor(from)?);
+            Ok(())
+        };
+        copy(&mut state, "token_embd.weight", "token_embedding.weight")?;
+        copy(&mut state, "position_embd.weight", "position_embedding.weight")?;
+        copy(&mut state, "token_types.weight", "token_type_embedding.weight")?;
+        copy(&mut state, "token_embd_norm.weight", "embedding_norm.gamma")?;
+        copy(&mut state, "token_embd_norm.bias", "embedding_norm.beta")?;
+        for i in 0..config.num_layers {
+            let (from, to) = (format!("blk.{}.", i), format!("encoder.layers.{}.", i));
+            for (gguf, ours) in [
+                ("attn_q", "attention.w_q"),
+                ("attn_k", "attention.w_k"),
+                ("attn_v", "attention.w_v"),
+                ("attn_output", "attention.w_o"),
+                ("ffn_up", "ff1"),
+                ("ffn_down", "ff2"),
+            ] {
+                let weight: Tensor<T> = self.tensor(&format!("{}{}.weight", from, gguf))?;
+                let bias: Tensor<T> = self.tensor(&format!("{}{}.bias", from, gguf))?;
+                let out = bias.numel();
+                state.insert(format!("{}{}.weight", to, ours), weight.t()?);
+                state.insert(format!("{}{}.bias", to, ours), bias.reshape(vec![1, out])?);
+            }
+            for (gguf, ours) in [("attn_output_norm", "norm1"), ("layer_output_norm", "norm2")] {
+                copy(&mut state, &format!("{}{}.weight", from, gguf), &format!("{}{}.gamma", to, ours))?;
+                copy(&mut state, &format!("{}{}.bias", from, gguf), &format!("{}{}.beta", to, ours))?;
+            }
+        }
+        let mut model = BertEncoder::new(&config);
+        model.load_state_dict(&state, true)?;
+        Ok(model)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len()).ok_or("gguf: truncated file")?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Box<dyn Error>> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Box<dyn Error>> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// A tensor dimension or offset of tensor `name`.
+    fn size(&mut self, name: &str) -> Result<usize, Box<dyn Error>> {
+        usize::try_from(self.u64()?).map_err(|_| too_large(name))
+    }
+
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        let len = usize::try_from(self.u64()?)?;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+
+    fn value(&mut self, ty: u32) -> Result<GgufValue, Box<dyn Error>> {
+        Ok(match ty {
+            0 => GgufValue::U8(self.array::<1>()?[0]),
+            1 => GgufValue::I8(self.array::<1>()?[0] as i8),
+            2 => GgufValue::U16(u16::from_le_bytes(self.array()?)),
+            3 => GgufValue::I16(i16::from_le_bytes(self.array()?)),
+            4 => GgufValue::U32(self.u32()?),
+            5 => GgufValue::I32(i32::from_le_bytes(self.array()?)),
+            6 => GgufValue::F32(f32::from_le_bytes(self.array()?)),
+            7 => GgufValue::Bool(self.array::<1>()?[0] != 0),
+            8 => GgufValue::String(self.string()?),
+            9 => {
+                let (elem, len) = (self.u32()?, self.u64()?);
+                // Every element takes at least a byte; bail out before
+                // allocating for a corrupt length.
+                if len > (self.bytes.len() - self.pos) as u64 {
+                    return Err("gguf: truncated file".into());
+                }
+                GgufValue::Array((0..len).map(|_| self.value(elem)).collect::<Result<_, _>>()?)
+            }
+            10 => GgufValue::U64(self.u64()?),
+            11 => GgufValue::I64(i64::from_le_bytes(self.array()?)),
+            12 => GgufValue::F64(f64::from_le_bytes(self.array()?)),
+            other => return Err(format!("gguf: unknown metadata type {}", other).into()),
+        })
+    }
+}
+
+fn f16(raw: &[u8]) -> f64 {
+    f16_to_f64(u16::from_le_bytes([raw[0], raw[1]]))
+}
+
+/// Scale and min of sub-block `j` from the 12 packed 6-bit values of a
+/// `Q4_K`/`Q5_K` super-block.
+fn scale_min_k4(j: usize, q: &[u8]) -> (f64, f64) {
+    let (d, m) = if j < 4 {
+        (q[j] & 63, q[j + 4] & 63)
+    } else {
+        ((q[j + 4] & 0xf) | ((q[j - 4] >> 6) << 4), (q[j + 4] >> 4) | ((q[j] >> 6) << 4))
+    };
+    (d as f64, m as f64)
+}
+
+/// Append the values of one block, laid out as in ggml's
+/// `dequantize_row_*`.
+fn dequantize_block(dtype: GgmlType, b: &[u8], out: &mut Vec<f64>) {
+    match dtype {
+        GgmlType::F32 => out.push(f32::from_le_bytes(b.try_into().unwrap()) as f64),
+        GgmlType::F64 => out.push(f64::from_le_bytes(b.try_into().unwrap())),
+        GgmlType::F16 => out.push(f16(b)),
+        GgmlType::BF16 => out.push(f32::from_bits((u16::from_le_bytes([b[0], b[1]]) as u32) << 16) as f64),
+        GgmlType::Q8_0 => {
+            let d = f16(b);
+            out.extend(b[2..].iter().map(|&q| q as i8 as f64 * d));
+        }
+        GgmlType::Q4_0 | GgmlType::Q4_1 => {
+            let d = f16(b);
+            let (min, qs) = match dtype {
+                GgmlType::Q4_1 => (f16(&b[2..]), &b[4..]),
+                _ => (-8.0 * d, &b[2..]),
+            };
+            out.extend(qs.iter().map(|&q| (q & 0xf) as f64 * d + min));
+            out.extend(qs.iter().map(|&q| (q >> 4) as f64 * d + min));
+        }
+        GgmlType::Q5_0 | GgmlType::Q5_1 => {
+            let d = f16(b);
+            let (min, rest) = match dtype {
+                GgmlType::Q5_1 => (f16(&b[2..]), &b[4..]),
+                _ => (-16.0 * d, &b[2..]),
+            };
+            let qh = u32::from_le_bytes(rest[..4].try_into().unwrap());
+            let qs = &rest[4..];
+            out.extend((0..16).map(|j| ((qs[j] & 0xf) as u32 | ((qh >> j) & 1) << 4) as f64 * d + min));
+            out.extend((0..16).map(|j| ((qs[j] >> 4) as u32 | ((qh >> (j + 16)) & 1) << 4) as f64 * d + min));
+        }
+        GgmlType::Q4_K | GgmlType::Q5_K => {
+            let (d, dmin) = (f16(b), f16(&b[2..]));
+            let scales = &b[4..16];
+            let (qh, qs) = match dtype {
+                GgmlType::Q5_K => (Some(&b[16..48]), &b[48..]),
+                _ => (None, &b[16..]),
+            };
+            for chunk in 0..4 {
+                let ql = &qs[chunk * 32..(chunk + 1) * 32];
+                for half in 0..2 {
+                    let sub = chunk * 2 + half;
+                    let (sc, m) = scale_min_k4(sub, scales);
+                    out.extend((0..32).map(|l| {
+                        let low = if half == 0 { ql[l] & 0xf } else { ql[l] >> 4 };
+                        let high = qh.map_or(0, |qh| ((qh[l] >> sub) & 1) << 4);
+                        d * sc * (low | high) as f64 - dmin * m
+                    }));
+                }
+            }
+        }
+        GgmlType::Q6_K => {
+            let (ql, qh, scales, d) = (&b[..128], &b[128..192], &b[192..208], f16(&b[208..]));
+            for n in 0..2 {
+                let (ql, qh, sc) = (&ql[n * 64..], &qh[n * 32..], &scales[n * 8..]);
+                let mut values = [0.0; 128];
+                for l in 0..32 {
+                    let is = l / 16;
+                    let q = |low: u8, shift: u32| ((low | (((qh[l] >> shift) & 3) << 4)) as i32 - 32) as f64;
+                    values[l] = d * sc[is] as i8 as f64 * q(ql[l] & 0xf, 0);
+                    values[l + 32] = d * sc[is + 2] as i8 as f64 * q(ql[l + 32] & 0xf, 2);
+                    values[l + 64] = d * sc[is + 4] as i8 as f64 * q(ql[l] >> 4, 4);
+                    values[l + 96] = d * sc[is + 6] as i8 as f64 * q(ql[l + 32] >> 4, 6);
+                }
+                out.extend(values);
+            }
+        }
+        GgmlType::Other(_) => unreachable!("checked by GgmlType::block"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_autodiff::Variable;
+
+    fn put_string(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    fn put_value(out: &mut Vec<u8>, value: &GgufValue) {
+        match value {
+            GgufValue::U8(v) => out.push(*v),
+            GgufValue::I8(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::U16(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::I16(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::U32(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::I32(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::U64(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::I64(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::F32(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::F64(v) => out.extend_from_slice(&v.to_le_bytes()),
+            GgufValue::Bool(v) => out.push(*v as u8),
+            GgufValue::String(s) => put_string(out, s),
+            GgufValue::Array(values) => {
+                // An empty array still needs an element type; any will do.
+                out.extend_from_slice(&values.first().map_or(0, type_id).to_le_bytes());
+                out.extend_from_slice(&(values.len() as u64).to_le_bytes());
+                values.iter().for_each(|v| put_value(out, v));
+            }
+        }
+    }
+
+    fn type_id(value: &GgufValue) -> u32 {
+        match value {
+            GgufValue::U8(_) => 0,
+            GgufValue::I8(_) => 1,
+            GgufValue::U16(_) => 2,
+            GgufValue::I16(_) => 3,
+            GgufValue::U32(_) => 4,
+            GgufValue::I32(_) => 5,
+            GgufValue::F32(_) => 6,
+            GgufValue::Bool(_) => 7,
+            GgufValue::String(_) => 8,
+            GgufValue::Array(_) => 9,
+            GgufValue::U64(_) => 10,
+            GgufValue::I64(_) => 11,
+            GgufValue::F64(_) => 12,
+        }
+    }
+
+    /// A GGUF v3 file; `shape`s are row-major and `raw` already encoded.
+    fn gguf(metadata: &[(&str, GgufValue)], tensors: &[(&str, Vec<usize>, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut out = b"GGUF".to_vec();
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&(tensors.len() as u64).to_le_bytes());
+        out.extend_from_slice(&(metadata.len() as u64).to_le_bytes());
+        for (key, value) in metadata {
+            put_string(&mut out, key);
+            out.extend_from_slice(&type_id(value).to_le_bytes());
+            put_value(&mut out, value);
+        }
+        let mut data: Vec<u8> = Vec::new();
+        for (name, shape, ty, raw) in tensors {
+            put_string(&mut out, name);
+            out.extend_from_slice(&(shape.len() as u32).to_le_bytes());
+            for &d in shape.iter().rev() {
+                out.extend_from_slice(&(d as u64).to_le_bytes());
+            }
+            out.extend_from_slice(&ty.to_le_bytes());
+            out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            data.extend_from_slice(raw);
+            data.resize(data.len().div_ceil(32) * 32, 0);
+        }
+        out.resize(out.len().div_ceil(32) * 32, 0);
+        out.extend_from_slice(&data);
+        out
+    }
+
+    fn f32_bytes(values: &[f64]) -> Vec<u8> {
+        values.iter().flat_map(|&v| (v as f32).to_le_bytes()).collect()
+    }
+
+    /// Deterministic filler bytes.
+    fn noise(n: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_metadata_and_float_tensors() {
+        let tokens = GgufValue::Array(vec![GgufValue::String("a".into()), GgufValue::String("b".into())]);
+        let bytes = gguf(
+            &[
+                ("general.architecture", GgufValue::String("test".into())),
+                ("test.block_count", GgufValue::U32(3)),
+                ("test.eps", GgufValue::F32(0.25)),
+                ("test.flag", GgufValue::Bool(true)),
+                ("tokenizer.ggml.tokens", tokens),
+            ],
+            &[
+                ("w", vec![2, 3], 0, f32_bytes(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0])),
+                // F16 [1.0, -2.0] and BF16 [0.5].
+                ("h", vec![2], 1, vec![0x00, 0x3c, 0x00, 0xc0]),
+                ("b", vec![1], 30, vec![0x00, 0x3f]),
+                ("q", vec![1, 64], 99, vec![0; 8]),
+            ],
+        );
+        let file = parse_gguf(&bytes).unwrap();
+        assert_eq!(file.version, 3);
+        assert_eq!(file.architecture(), Some("test"));
+        assert_eq!(file.metadata["test.block_count"].as_u64(), Some(3));
+        assert_eq!(file.metadata["test.eps"].as_f64(), Some(0.25));
+        assert_eq!(file.metadata["test.flag"], GgufValue::Bool(true));
+        assert_eq!(file.vocab().unwrap().tokens(), &["a", "b"]);
+
+        let w: Tensor<f64> = file.tensor("w").unwrap();
+        assert_eq!(w.shape_vec(), vec![2, 3]);
+        assert_eq!(w.data(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(file.tensor::<f32>("h").unwrap().data(), &[1.0, -2.0]);
+        assert_eq!(file.tensor::<f64>("b").unwrap().data(), &[0.5]);
+        assert_eq!(file.tensor_info("q").unwrap().dtype, GgmlType::Other(99));
+        assert!(file.tensor::<f64>("q").is_err());
+        assert!(file.tensor::<f64>("missing").is_err());
+        assert!(file.state_dict::<f64>().is_err());
+
+        assert!(parse_gguf(&bytes[..40]).is_err());
+        assert!(parse_gguf(b"GGML\x03\0\0\0").is_err());
+    }
+
+    #[test]
+    fn test_oversized_shapes_and_alignment() {
+        let error = |bytes: &[u8]| parse_gguf(bytes).err().map(|e| e.to_string());
+        // Dimensions whose product overflows are refused while parsing.
+        for shape in [vec![usize::MAX, 2], vec![1 << 33, 1 << 33, 4]] {
+            let bytes = gguf(&[], &[("w", shape, 0, vec![])]);
+            assert_eq!(error(&bytes).as_deref(), Some("gguf: tensor too large: 'w'"));
+        }
+        // A Q8_0 tensor whose element count fits but whose byte length
+        // does not.
+        let bytes = gguf(&[], &[("q", vec![usize::MAX / 32 * 32], 8, vec![])]);
+        let file = parse_gguf(&bytes).unwrap();
+        assert_eq!(file.tensor::<f64>("q").unwrap_err().to_string(), "gguf: tensor too large: 'q'");
+        // Values that fit but lie past the end of the file.
+        let bytes = gguf(&[], &[("w", vec![1 << 40], 0, vec![])]);
+        assert!(parse_gguf(&bytes).unwrap().tensor::<f64>("w").unwrap_err().to_string().contains("outside the file"));
+
+        // A huge alignment puts the tensor data past any file.
+        for alignment in [1 << 63, u64::MAX] {
+            let metadata = [("general.alignment", GgufValue::U64(alignment))];
+            let bytes = gguf(&metadata, &[("w", vec![2], 0, f32_bytes(&[1.0, 2.0]))]);
+            let err = parse_gguf(&bytes).unwrap().tensor::<f64>("w").unwrap_err().to_string();
+            assert!(err.contains("outside the file"), "{}", err);
+        }
+        let bytes = gguf(&[("general.alignment", GgufValue::U32(0))], &[]);
+        assert_eq!(error(&bytes).as_deref(), Some("gguf: invalid general.alignment"));
+    }
+
+    #[test]
+    fn test_metadata_value_types() {
+        let values = [
+            GgufValue::U8(200),
+            GgufValue::I8(-3),
+            GgufValue::U16(60_000),
+            GgufValue::I16(-300),
+            GgufValue::U32(4_000_000_000),
+            GgufValue::I32(-70_000),
+            GgufValue::U64(1 << 40),
+            GgufValue::I64(-(1 << 40)),
+            GgufValue::F32(1.5),
+            GgufValue::F64(-2.25),
+            GgufValue::Bool(false),
+            GgufValue::String("x".into()),
+            GgufValue::Array(vec![GgufValue::I16(1), GgufValue::I16(-1)]),
+            GgufValue::Array(vec![]),
+        ];
+        let keys: Vec<String> = (0..values.len()).map(|k| format!("k{:02}", k)).collect();
+        let metadata: Vec<(&str, GgufValue)> = keys.iter().map(|k| k.as_str()).zip(values.iter().cloned()).collect();
+        let file = parse_gguf(&gguf(&metadata, &[])).unwrap();
+        for (key, value) in keys.iter().zip(&values) {
+            assert_eq!(&file.metadata[key], value);
+        }
+        assert_eq!(file.metadata["k06"].as_u64(), Some(1 << 40));
+        assert_eq!(file.metadata["k07"].as_u64(), None);
+        assert_eq!(file.metadata["k09"].as_f64(), Some(-2.25));
+    }
+
+    #[test]
+    fn test_dequantize_blocks() {
+        // Each type's expected values follow its element-wise definition
+        // rather than the loop structure of `dequantize_block`.
+        let half = |v: f64| -> [u8; 2] {
+            match v {
+                0.5 => [0x00, 0x38],
+                0.25 => [0x00, 0x34],
+                2.0 => [0x00, 0x40],
+                -1.0 => [0x00, 0xbc],
+                _ => unreachable!(),
+            }
+        };
+        let decode = |ty: GgmlType, block: &[u8]| {
+            let mut out = Vec::new();
+            dequantize_block(ty, block, &mut out);
+            out
+        };
+        let nibble = |q: &[u8], i: usize, high: bool| if high { q[i] >> 4 } else { q[i] & 0xf } as f64;
+
+        let qs = noise(32, 1);
+        let block = [&half(0.5)[..], &qs].concat();
+        let expected: Vec<f64> = qs.iter().map(|&q| q as i8 as f64 * 0.5).collect();
+        assert_eq!(decode(GgmlType::Q8_0, &block), expected);
+
+        let qs = noise(16, 2);
+        let q4 = |e: usize| nibble(&qs, e % 16, e >= 16);
+        let block = [&half(2.0)[..], &qs].concat();
+        assert_eq!(decode(GgmlType::Q4_0, &block), (0..32).map(|e| (q4(e) - 8.0) * 2.0).collect::<Vec<_>>());
+        let block = [&half(2.0)[..], &half(-1.0), &qs].concat();
+        assert_eq!(decode(GgmlType::Q4_1, &block), (0..32).map(|e| q4(e) * 2.0 - 1.0).collect::<Vec<_>>());
+
+        let qh = noise(4, 3);
+        let high = |e: usize| ((u32::from_le_bytes(qh[..].try_into().unwrap()) >> e) & 1) as f64 * 16.0;
+        let block = [&half(0.5)[..], &qh, &qs].concat();
+        assert_eq!(decode(GgmlType::Q5_0, &block), (0..32).map(|e| (q4(e) + high(e) - 16.0) * 0.5).collect::<Vec<_>>());
+        let block = [&half(0.5)[..], &half(-1.0), &qh, &qs].concat();
+        assert_eq!(decode(GgmlType::Q5_1, &block), (0..32).map(|e| (q4(e) + high(e)) * 0.5 - 1.0).collect::<Vec<_>>());
+
+        // k-quants: 8 sub-blocks of 32 with 6-bit scales and mins. Values
+        // below 16 keep the packed upper bits zero, so sub-block j < 4 reads
+        // scales[j] / scales[j + 4] and j >= 4 the nibbles of scales[j + 4].
+        let (sc, mn) = ([3u8, 7, 1, 12, 5, 9, 2, 14], [4u8, 0, 11, 6, 8, 1, 15, 3]);
+        let mut scales = [0u8; 12];
+        for j in 0..4 {
+            scales[j] = sc[j];
+            scales[j + 4] = mn[j];
+            scales[j + 8] = sc[j + 4] | (mn[j + 4] << 4);
+        }
+        let (qh, qs) = (noise(32, 4), noise(128, 5));
+        let k_low = |e: usize| nibble(&qs, (e / 64) * 32 + e % 32, (e / 32) % 2 == 1);
+        let k_value = |e: usize, high: f64| 0.5 * sc[e / 32] as f64 * (k_low(e) + high) - 0.25 * mn[e / 32] as f64;
+        let block = [&half(0.5)[..], &half(0.25), &scales, &qs].concat();
+        assert_eq!(decode(GgmlType::Q4_K, &block), (0..256).map(|e| k_value(e, 0.0)).collect::<Vec<_>>());
+        let q5_high = |e: usize| ((qh[e % 32] >> (e / 32)) & 1) as f64 * 16.0;
+        let block = [&half(0.5)[..], &half(0.25), &scales, &qh, &qs].concat();
+        assert_eq!(decode(GgmlType::Q5_K, &block), (0..256).map(|e| k_value(e, q5_high(e))).collect::<Vec<_>>());
+
+        // Q6_K: element e of a 128-value half n sits at quarter q = r / 32.
+        let (ql, qh, scales, d) = (noise(128, 6), noise(64, 7), noise(16, 8), -1.0);
+        let q6 = |e: usize| {
+            let (n, r) = (e / 128, e % 128);
+            let (q, l) = (r / 32, r % 32);
+            let low = nibble(&ql, n * 64 + l + 32 * (q % 2), q >= 2);
+            let high = ((qh[n * 32 + l] >> (2 * q)) & 3) as f64 * 16.0;
+            d * scales[n * 8 + l / 16 + 2 * q] as i8 as f64 * (low + high - 32.0)
+        };
+        let block = [&ql[..], &qh, &scales, &half(-1.0)].concat();
+        assert_eq!(decode(GgmlType::Q6_K, &block), (0..256).map(q6).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bert_encoder_from_gguf() {
+        let config = BertConfig::new(7, 8, 2, 16, 2).with_max_positions(6).with_layer_norm_eps(1e-6);
+        let source: BertEncoder<f64> = BertEncoder::new(&config);
+
+        // Write the model the way llama.cpp's converter lays out BERT:
+        // PyTorch `[out, in]` projections and 1-D biases.
+        let state = source.state_dict();
+        let mut tensors = Vec::new();
+        let mut put = |name: String, t: &Tensor<f64>| tensors.push((name, t.shape_vec(), 0, f32_bytes(t.data())));
+        for (ours, gguf) in [
+            ("token_embedding.weight", "token_embd.weight"),
+            ("position_embedding.weight", "position_embd.weight"),
+            ("token_type_embedding.weight", "token_types.weight"),
+            ("embedding_norm.gamma", "token_embd_norm.weight"),
+            ("embedding_norm.beta", "token_embd_norm.bias"),
+        ] {
+            put(gguf.to_string(), &state[ours]);
+        }
+        for i in 0..2 {
+            let layer = |name: &str| &state[&format!("encoder.layers.{}.{}", i, name)];
+            for (ours, gguf) in [
+                ("attention.w_q", "attn_q"),
+                ("attention.w_k", "attn_k"),
+                ("attention.w_v", "attn_v"),
+                ("attention.w_o", "attn_output"),
+                ("ff1", "ffn_up"),
+                ("ff2", "ffn_down"),
+            ] {
+                put(format!("blk.{}.{}.weight", i, gguf), &layer(&format!("{}.weight", ours)).t().unwrap());
+                let bias = layer(&format!("{}.bias", ours));
+                put(format!("blk.{}.{}.bias", i, gguf), &bias.reshape(vec![bias.numel()]).unwrap());
+            }
+            for (ours, gguf) in [("norm1", "attn_output_norm"), ("norm2", "layer_output_norm")] {
+                put(format!("blk.{}.{}.weight", i, gguf), layer(&format!("{}.gamma", ours)));
+                put(format!("blk.{}.{}.bias", i, gguf), layer(&format!("{}.beta", ours)));
+            }
+        }
+        let tokens = ["[PAD]", "[UNK]", "[CLS]", "[SEP]", "\u{2581}play", "ing", "\u{2581}the"];
+        let metadata = [
+            ("general.architecture", GgufValue::String("bert".into())),
+            ("bert.embedding_length", GgufValue::U32(8)),
+            ("bert.feed_forward_length", GgufValue::U32(16)),
+            ("bert.block_count", GgufValue::U32(2)),
+            ("bert.attention.head_count", GgufValue::U32(2)),
+            ("bert.attention.layer_norm_epsilon", GgufValue::F32(1e-6)),
+            ("tokenizer.ggml.model", GgufValue::String("bert".into())),
+            ("tokenizer.ggml.tokens", GgufValue::Array(tokens.iter().map(|t| GgufValue::String(t.to_string())).collect())),
+        ];
+        let borrowed: Vec<(&str, Vec<usize>, u32, Vec<u8>)> =
+            tensors.iter().map(|(n, s, t, r)| (n.as_str(), s.clone(), *t, r.clone())).collect();
+        let file = parse_gguf(&gguf(&metadata, &borrowed)).unwrap();
+
+        let model: BertEncoder<f64> = file.bert_encoder().unwrap();
+        assert_eq!(model.position_embedding.num_embeddings, 6);
+        assert!((model.encoder.layers[1].norm1.eps - 1e-6).abs() < 1e-12);
+        let ids = Variable::input(Tensor::new(vec![2.0, 4.0, 5.0, 6.0, 3.0], vec![5]).unwrap());
+        let (a, b) = (source.forward(&ids).data, model.forward(&ids).data);
+        assert_eq!(a.shape_vec(), b.shape_vec());
+        // The file holds f32 weights.
+        assert!(a.data().iter().zip(b.data()).all(|(x, y)| (x - y).abs() < 1e-4));
+
+        let vocab = file.vocab().unwrap();
+        assert_eq!(vocab.tokens(), &["[PAD]", "[UNK]", "[CLS]", "[SEP]", "play", "##ing", "the"]);
+
+        let llama = gguf(&[("general.architecture", GgufValue::String("llama".into()))], &[]);
+        assert!(parse_gguf(&llama).unwrap().bert_encoder::<f64>().err().unwrap().to_string().contains("llama"));
+    }
+}
@@ -1,4 +1,14 @@
 pub mod csv_io;
+pub mod gguf;
 pub mod model_io;
+pub mod npy;
+pub mod onnx;
+pub mod pmml;
+pub mod safetensors;
 pub use csv_io::*;
+pub use gguf::*;
 pub use model_io::*;
+pub use npy::*;
+pub use onnx::*;
+pub use pmml::*;
+pub use safetensors::*;
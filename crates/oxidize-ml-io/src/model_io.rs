@@ -1,7 +1,11 @@
 use oxidize_ml_core::Tensor;
+use oxidize_ml_nn::StateDict;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
 use std::error::Error;
+#[cfg(feature = "fs")]
 use std::fs;
+#[cfg(feature = "fs")]
 use std::path::Path;
 
 /// Serializable model wrapper for saving/loading model weights.
@@ -30,6 +34,24 @@ impl ModelWeights {
             Tensor::new(data.clone(), shape.clone()).expect("load tensor")
         })
     }
+
+    /// Weights named by a layer's `state_dict`, so saving no longer depends
+    /// on parameter order.
+    pub fn from_state_dict(state: &StateDict<f64>) -> Self {
+        let mut weights = ModelWeights::new();
+        for (name, tensor) in state {
+            weights.add(name, tensor);
+        }
+        weights
+    }
+
+    /// The weights as a state dict, for `Layer::load_state_dict`.
+    pub fn to_state_dict(&self) -> StateDict<f64> {
+        self.tensors
+            .iter()
+            .map(|(name, data, shape)| (name.clone(), Tensor::new(data.clone(), shape.clone()).expect("load tensor")))
+            .collect()
+    }
 }
 
 impl Default for ModelWeights {
@@ -39,6 +61,7 @@ impl Default for ModelWeights {
 }
 
 /// Save model weights to a JSON file.
+#[cfg(feature = "fs")]
 pub fn save_model(weights: &ModelWeights, path: &str) -> Result<(), Box<dyn Error>> {
     let json = serde_json::to_string_pretty(weights)?;
     fs::write(Path::new(path), json)?;
@@ -46,6 +69,7 @@ pub fn save_model(weights: &ModelWeights, path: &str) -> Result<(), Box<dyn Erro
 }
 
 /// Load model weights from a JSON file.
+#[cfg(feature = "fs")]
 pub fn load_model(path: &str) -> Result<ModelWeights, Box<dyn Error>> {
     let json = fs::read_to_string(Path::new(path))?;
     let weights: ModelWeights = serde_json::from_str(&json)?;
@@ -0,0 +1,391 @@
+//! NumPy `.npy` / `.npz` interchange.
+//!
+//! `.npy` files of any little- or big-endian float, integer or bool dtype
+//! are read and converted to the tensor's element type; tensors are written
+//! as `<f8` (f64) or `<f4` (f32). `.npz` archives are written uncompressed,
+//! like `np.savez`; reading `np.savez_compressed` output is not supported.
+
+use oxidize_ml_core::{Float, Tensor};
+use std::error::Error;
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Arrays of an `.npz` archive, keyed by name.
+pub type NamedTensors<T> = Vec<(String, Tensor<T>)>;
+
+/// Name and raw contents of one archive member.
+type ZipEntry<'a> = (String, &'a [u8]);
+
+/// `.npy` round-tripping as methods on `Tensor`.
+#[cfg(feature = "fs")]
+pub trait NpyExt: Sized {
+    fn to_npy(&self, path: &str) -> Result<(), Box<dyn Error>>;
+    fn from_npy(path: &str) -> Result<Self, Box<dyn Error>>;
+}
+
+#[cfg(feature = "fs")]
+impl<T: Float> NpyExt for Tensor<T> {
+    fn to_npy(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        save_npy(path, self)
+    }
+
+    fn from_npy(path: &str) -> Result<Self, Box<dyn Error>> {
+        load_npy(path)
+    }
+}
+
+/// Write a tensor to a `.npy` file.
+#[cfg(feature = "fs")]
+pub fn save_npy<T: Float>(path: &str, tensor: &Tensor<T>) -> Result<(), Box<dyn Error>> {
+    fs::write(Path::new(path), npy_bytes(tensor))?;
+    Ok(())
+}
+
+/// Read a `.npy` file into a tensor.
+#[cfg(feature = "fs")]
+pub fn load_npy<T: Float>(path: &str) -> Result<Tensor<T>, Box<dyn Error>> {
+    parse_npy(&fs::read(Path::new(path))?)
+}
+
+/// Write named tensors to an uncompressed `.npz` archive.
+#[cfg(feature = "fs")]
+pub fn save_npz<T: Float>(path: &str, arrays: &[(&str, &Tensor<T>)]) -> Result<(), Box<dyn Error>> {
+    fs::write(Path::new(path), npz_bytes(arrays)?)?;
+    Ok(())
+}
+
+/// Read every array of a `.npz` archive, in archive order.
+#[cfg(feature = "fs")]
+pub fn load_npz<T: Float>(path: &str) -> Result<NamedTensors<T>, Box<dyn Error>> {
+    parse_npz(&fs::read(Path::new(path))?)
+}
+
+/// Named tensors encoded as the contents of an uncompressed `.npz` archive.
+pub fn npz_bytes<T: Float>(arrays: &[(&str, &Tensor<T>)]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let entries: Vec<(String, Vec<u8>)> = arrays
+        .iter()
+        .map(|(name, t)| (format!("{}.npy", name), npy_bytes(t)))
+        .collect();
+    zip_store(&entries)
+}
+
+/// Decode the contents of an `.npz` archive, in archive order.
+pub fn parse_npz<T: Float>(bytes: &[u8]) -> Result<NamedTensors<T>, Box<dyn Error>> {
+    zip_entries(bytes)?
+        .into_iter()
+        .map(|(name, data)| {
+            let name = name.strip_suffix(".npy").unwrap_or(&name).to_string();
+            Ok((name, parse_npy(data)?))
+        })
+        .collect()
+}
+
+// ─── .npy encoding ──────────────────────────────────────────────────────────
+
+/// A tensor encoded as the contents of a `.npy` file.
+pub fn npy_bytes<T: Float>(tensor: &Tensor<T>) -> Vec<u8> {
+    let is_f32 = std::mem::size_of::<T>() == 4;
+    let shape = match tensor.shape_vec().as_slice() {
+        [d] => format!("({},)", d),
+        dims => format!("({})", dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        if is_f32 { "<f4" } else { "<f8" },
+        shape
+    );
+    // Pad so the data starts on a 64-byte boundary; the header ends in '\n'.
+    let unpadded = MAGIC.len() + 4 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut out = Vec::with_capacity(MAGIC.len() + 4 + header.len() + tensor.numel() * 8);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&[1, 0]);
+    out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    out.extend_from_slice(header.as_bytes());
+    for &v in tensor.data() {
+        if is_f32 {
+            out.extend_from_slice(&(v.to_f64() as f32).to_le_bytes());
+        } else {
+            out.extend_from_slice(&v.to_f64().to_le_bytes());
+        }
+    }
+    out
+}
+
+/// Decode the contents of a `.npy` file.
+pub fn parse_npy<T: Float>(bytes: &[u8]) -> Result<Tensor<T>, Box<dyn Error>> {
+    if bytes.len() < 10 || &bytes[..6] != MAGIC {
+        return Err("not a .npy file (bad magic)".into());
+    }
+    let (header_len, start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 => {
+            let len = bytes.get(8..12).ok_or("truncated .npy header")?;
+            (u32::from_le_bytes(len.try_into()?) as usize, 12)
+        }
+        v => return Err(format!("unsupported .npy version {}", v).into()),
+    };
+    let header = std::str::from_utf8(bytes.get(start..start + header_len).ok_or("truncated .npy header")?)?;
+    let descr = header_value(header, "descr")?
+        .trim_matches(|c| c == '\'' || c == '"')
+        .to_string();
+    let fortran = header_value(header, "fortran_order")? == "True";
+    let shape: Vec<usize> = header_value(header, "shape")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>())
+        .collect::<Result<_, _>>()?;
+
+    let dtype = Dtype::parse(&descr)?;
+    let numel: usize = shape.iter().product();
+    let body = &bytes[start + header_len..];
+    if body.len() < numel * dtype.size {
+        return Err(format!("truncated .npy data: need {} bytes, have {}", numel * dtype.size, body.len()).into());
+    }
+    let data: Vec<T> = body
+        .chunks_exact(dtype.size)
+        .take(numel)
+        .map(|c| T::from_f64(dtype.decode(c)))
+        .collect();
+
+    if fortran && shape.len() > 1 {
+        // Column-major on disk: load with reversed dims, then reverse the axes.
+        let reversed: Vec<usize> = shape.iter().rev().copied().collect();
+        let axes: Vec<usize> = (0..shape.len()).rev().collect();
+        let t = Tensor::new(data, reversed)?;
+        return Ok(t.view().permute(&axes)?.contiguous());
+    }
+    Ok(Tensor::new(data, shape)?)
+}
+
+/// Raw text of `key`'s value in a `.npy` header dict.
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, Box<dyn Error>> {
+    let pat = format!("'{}':", key);
+    let rest = header[header.find(&pat).ok_or_else(|| format!(".npy header has no '{}'", key))? + pat.len()..].trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|i| i + 1)
+    } else if let Some(q) = rest.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        rest[1..].find(q).map(|i| i + 2)
+    } else {
+        rest.find([',', '}'])
+    };
+    Ok(rest[..end.ok_or("malformed .npy header")?].trim())
+}
+
+/// Element type of a `.npy` array (`descr` such as `<f8`, `|u1`, `>i4`).
+struct Dtype {
+    kind: char,
+    size: usize,
+    big_endian: bool,
+}
+
+impl Dtype {
+    fn parse(descr: &str) -> Result<Dtype, Box<dyn Error>> {
+        let mut chars = descr.chars();
+        let (order, kind) = (chars.next(), chars.next());
+        let size: usize = chars.as_str().parse().map_err(|_| format!("unsupported dtype '{}'", descr))?;
+        let dtype = Dtype {
+            kind: kind.unwrap_or(' '),
+            size,
+            big_endian: order == Some('>'),
+        };
+        let ok = match dtype.kind {
+            'f' => matches!(size, 4 | 8),
+            'i' | 'u' => matches!(size, 1 | 2 | 4 | 8),
+            'b' => size == 1,
+            _ => false,
+        };
+        if !ok || !matches!(order, Some('<' | '>' | '|' | '=')) {
+            return Err(format!("unsupported dtype '{}'", descr).into());
+        }
+        Ok(dtype)
+    }
+
+    fn decode(&self, raw: &[u8]) -> f64 {
+        let mut b = [0u8; 8];
+        b[..self.size].copy_from_slice(raw);
+        if self.big_endian {
+            b[..self.size].reverse();
+        }
+        match (self.kind, self.size) {
+            ('f', 4) => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            ('f', _) => f64::from_le_bytes(b),
+            ('i', 1) => b[0] as i8 as f64,
+            ('i', 2) => i16::from_le_bytes([b[0], b[1]]) as f64,
+            ('i', 4) => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            ('i', _) => i64::from_le_bytes(b) as f64,
+            // Zero-extended, so little-endian u64 decoding covers every width.
+            _ => u64::from_le_bytes(b) as f64,
+        }
+    }
+}
+
+// ─── Minimal ZIP container (stored entries only) ───────────────────────────
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn zip_store(entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in entries {
+        let (offset, size) = (u32::try_from(out.len()), u32::try_from(data.len()));
+        let (Ok(offset), Ok(size)) = (offset, size) else {
+            return Err(".npz archives over 4 GiB are not supported".into());
+        };
+        let crc = crc32(data);
+        // Fields shared by the local header and the central directory entry:
+        // version needed, flags, method (0 = stored), mod time, mod date,
+        // crc, compressed size, uncompressed size, name length, extra length.
+        let mut common = Vec::with_capacity(26);
+        for half in [20u16, 0, 0, 0, 0x21] {
+            common.extend_from_slice(&half.to_le_bytes());
+        }
+        for word in [crc, size, size] {
+            common.extend_from_slice(&word.to_le_bytes());
+        }
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&common);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&common);
+        // comment length, disk number, internal attrs, external attrs
+        central.extend_from_slice(&[0u8; 10]);
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+    let cd_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0u8; 4]); // disk numbers
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&cd_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    Ok(out)
+}
+
+fn zip_entries(bytes: &[u8]) -> Result<Vec<ZipEntry<'_>>, Box<dyn Error>> {
+    let u16_at = |i: usize| -> Result<usize, Box<dyn Error>> {
+        let b = bytes.get(i..i + 2).ok_or("truncated .npz archive")?;
+        Ok(u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |i: usize| -> Result<u32, Box<dyn Error>> {
+        let b = bytes.get(i..i + 4).ok_or("truncated .npz archive")?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    // End-of-central-directory record; scan back over a possible comment.
+    let eocd = (0..bytes.len().saturating_sub(21))
+        .rev()
+        .find(|&i| bytes[i..].starts_with(&0x0605_4b50u32.to_le_bytes()))
+        .ok_or("not a .npz archive (no end of central directory)")?;
+    let count = u16_at(eocd + 10)?;
+    let mut pos = u32_at(eocd + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(pos)? != 0x0201_4b50 {
+            return Err("corrupt .npz central directory".into());
+        }
+        let method = u16_at(pos + 10)?;
+        let crc = u32_at(pos + 16)?;
+        let size = u32_at(pos + 20)? as usize;
+        let (name_len, extra_len, comment_len) = (u16_at(pos + 28)?, u16_at(pos + 30)?, u16_at(pos + 32)?);
+        let local = u32_at(pos + 42)? as usize;
+        let name = String::from_utf8(bytes.get(pos + 46..pos + 46 + name_len).ok_or("truncated .npz archive")?.to_vec())?;
+        pos += 46 + name_len + extra_len + comment_len;
+
+        if method != 0 {
+            return Err(format!("'{}' is compressed; only uncompressed .npz (np.savez) is supported", name).into());
+        }
+        let data_start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+        let data = bytes.get(data_start..data_start + size).ok_or("truncated .npz archive")?;
+        if crc32(data) != crc {
+            return Err(format!("CRC mismatch for '{}'", name).into());
+        }
+        entries.push((name, data));
+    }
+    Ok(entries)
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+
+    fn tmp(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("oxidize_ml_{}_{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_npy_roundtrip() {
+        let path = tmp("roundtrip.npy");
+        let t: Tensor<f64> = Tensor::new((0..6).map(|i| i as f64 * 0.5).collect(), vec![2, 3]).unwrap();
+        t.to_npy(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!((bytes.iter().position(|&b| b == b'\n').unwrap() + 1) % 64, 0);
+        assert_eq!(Tensor::<f64>::from_npy(&path).unwrap(), t);
+
+        let v: Tensor<f32> = Tensor::from_slice(&[1.5, -2.0]);
+        save_npy(&path, &v).unwrap();
+        assert_eq!(load_npy::<f32>(&path).unwrap(), v);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_foreign_dtypes() {
+        // Big-endian int16, Fortran order, shape (2, 2): column-major [1, 2, 3, 4].
+        let header = "{'descr': '>i2', 'fortran_order': True, 'shape': (2, 2), }\n";
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for v in [1i16, 2, 3, 4] {
+            bytes.extend_from_slice(&v.to_be_bytes());
+        }
+        let t: Tensor<f64> = parse_npy(&bytes).unwrap();
+        assert_eq!(t.data(), &[1.0, 3.0, 2.0, 4.0]);
+        assert!(Dtype::parse("<c16").is_err());
+    }
+
+    #[test]
+    fn test_npz_roundtrip() {
+        let path = tmp("arrays.npz");
+        let a: Tensor<f64> = Tensor::randn(vec![3, 2], Some(1));
+        let b: Tensor<f64> = Tensor::scalar(7.0);
+        save_npz(&path, &[("weights", &a), ("bias", &b)]).unwrap();
+        let loaded = load_npz::<f64>(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0], ("weights".to_string(), a));
+        assert_eq!(loaded[1].0, "bias");
+        assert_eq!(loaded[1].1.item().unwrap(), 7.0);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,718 @@
+//! ONNX export for trained models, for serving with onnxruntime or Triton.
+//!
+//! Exported graphs take one `float` input named `input` whose first
+//! dimension is the symbolic batch size `N`. Neural networks and linear
+//! models use the default opset 13; trees use `TreeEnsembleRegressor` /
+//! `TreeEnsembleClassifier` from `ai.onnx.ml` opset 3. Weights are stored as
+//! `float32`, so outputs match the Rust models to f32 precision. The
+//! protobuf encoding is written by hand to avoid a codegen dependency.
+
+use oxidize_ml_core::Float;
+use oxidize_ml_linear::{LinearRegression, LogisticRegression};
+use oxidize_ml_nn::{LayerKind, Sequential};
+use oxidize_ml_tree::{
+    DecisionTreeClassifier, DecisionTreeRegressor, FlatNode, GradientBoostingClassifier,
+    GradientBoostingRegressor, RandomForestClassifier, RandomForestRegressor,
+};
+use std::error::Error;
+#[cfg(feature = "fs")]
+use std::fs;
+
+const IR_VERSION: u64 = 7;
+const OPSET: u64 = 13;
+const ML_DOMAIN: &str = "ai.onnx.ml";
+const ML_OPSET: u64 = 3;
+
+const DTYPE_FLOAT: u64 = 1;
+const DTYPE_INT64: u64 = 7;
+
+/// Export a trained model as a serialized ONNX `ModelProto`.
+pub trait ToOnnx {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>>;
+
+    /// Write the model to an `.onnx` file.
+    #[cfg(feature = "fs")]
+    fn save_onnx(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_onnx_bytes()?)?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Protobuf wire format
+// ---------------------------------------------------------------------------
+
+/// An encoded protobuf message under construction.
+#[derive(Default)]
+struct Message(Vec<u8>);
+
+impl Message {
+    fn raw_varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.0.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.0.push(v as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.raw_varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn int(&mut self, field: u32, v: i64) {
+        self.key(field, 0);
+        // Negative int64 values are sent as their 10-byte two's complement.
+        self.raw_varint(v as u64);
+    }
+
+    fn float(&mut self, field: u32, v: f32) {
+        self.key(field, 5);
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn bytes(&mut self, field: u32, b: &[u8]) {
+        self.key(field, 2);
+        self.raw_varint(b.len() as u64);
+        self.0.extend_from_slice(b);
+    }
+
+    fn string(&mut self, field: u32, s: &str) {
+        self.bytes(field, s.as_bytes());
+    }
+
+    fn message(&mut self, field: u32, m: &Message) {
+        self.bytes(field, &m.0);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ONNX messages
+// ---------------------------------------------------------------------------
+
+enum Attr<'a> {
+    Int(i64),
+    Float(f32),
+    Str(&'a str),
+    Ints(Vec<i64>),
+    Floats(Vec<f32>),
+    Strs(Vec<&'a str>),
+}
+
+fn attribute(name: &str, value: &Attr) -> Message {
+    let mut m = Message::default();
+    m.string(1, name);
+    let ty = match value {
+        Attr::Float(f) => {
+            m.float(2, *f);
+            1
+        }
+        Attr::Int(i) => {
+            m.int(3, *i);
+            2
+        }
+        Attr::Str(s) => {
+            m.string(4, s);
+            3
+        }
+        Attr::Floats(fs) => {
+            fs.iter().for_each(|&f| m.float(7, f));
+            6
+        }
+        Attr::Ints(is) => {
+            is.iter().for_each(|&i| m.int(8, i));
+            7
+        }
+        Attr::Strs(ss) => {
+            ss.iter().for_each(|s| m.string(9, s));
+            8
+        }
+    };
+    m.int(20, ty);
+    m
+}
+
+/// A dimension of a graph input: fixed, or a named symbolic size.
+enum Dim<'a> {
+    Fixed(usize),
+    Param(&'a str),
+}
+
+fn value_info(name: &str, elem_type: u64, dims: Option<&[Dim]>) -> Message {
+    let mut tensor_type = Message::default();
+    tensor_type.int(1, elem_type as i64);
+    if let Some(dims) = dims {
+        let mut shape = Message::default();
+        for d in dims {
+            let mut dim = Message::default();
+            match d {
+                Dim::Fixed(v) => dim.int(1, *v as i64),
+                Dim::Param(p) => dim.string(2, p),
+            }
+            shape.message(1, &dim);
+        }
+        tensor_type.message(2, &shape);
+    }
+    let mut ty = Message::default();
+    ty.message(1, &tensor_type);
+
+    let mut m = Message::default();
+    m.string(1, name);
+    m.message(2, &ty);
+    m
+}
+
+/// Accumulates the nodes, weights and signature of one graph.
+struct GraphBuilder {
+    nodes: Vec<Message>,
+    initializers: Vec<Message>,
+    inputs: Vec<Message>,
+    outputs: Vec<Message>,
+    uses_ml: bool,
+    counter: usize,
+}
+
+impl GraphBuilder {
+    fn new() -> Self {
+        GraphBuilder {
+            nodes: Vec::new(),
+            initializers: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            uses_ml: false,
+            counter: 0,
+        }
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.counter += 1;
+        format!("{}_{}", prefix, self.counter)
+    }
+
+    fn tensor(&mut self, prefix: &str, dims: &[usize], data_type: u64, raw: Vec<u8>) -> String {
+        let name = self.fresh(prefix);
+        let mut t = Message::default();
+        dims.iter().for_each(|&d| t.int(1, d as i64));
+        t.int(2, data_type as i64);
+        t.string(8, &name);
+        t.bytes(9, &raw);
+        self.initializers.push(t);
+        name
+    }
+
+    /// Add a float32 initializer and return its name.
+    fn weight<T: Float>(&mut self, prefix: &str, dims: &[usize], data: &[T]) -> String {
+        let raw = data.iter().flat_map(|v| (v.to_f64() as f32).to_le_bytes()).collect();
+        self.tensor(prefix, dims, DTYPE_FLOAT, raw)
+    }
+
+    fn int64s(&mut self, prefix: &str, data: &[i64]) -> String {
+        let raw = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.tensor(prefix, &[data.len()], DTYPE_INT64, raw)
+    }
+
+    /// Add a node and return the names of its outputs.
+    fn node(&mut self, op: &str, inputs: &[&str], n_outputs: usize, attrs: &[(&str, Attr)]) -> Vec<String> {
+        let ml = op.starts_with("TreeEnsemble");
+        self.uses_ml |= ml;
+        let name = self.fresh(op);
+        let outputs: Vec<String> = (0..n_outputs).map(|i| format!("{}_out{}", name, i)).collect();
+
+        let mut m = Message::default();
+        inputs.iter().for_each(|i| m.string(1, i));
+        outputs.iter().for_each(|o| m.string(2, o));
+        m.string(3, &name);
+        m.string(4, op);
+        for (k, v) in attrs {
+            m.message(5, &attribute(k, v));
+        }
+        if ml {
+            m.string(7, ML_DOMAIN);
+        }
+        self.nodes.push(m);
+        outputs
+    }
+
+    /// Single-output node.
+    fn op(&mut self, op: &str, inputs: &[&str], attrs: &[(&str, Attr)]) -> String {
+        self.node(op, inputs, 1, attrs).remove(0)
+    }
+
+    fn input(&mut self, dims: &[Dim]) -> String {
+        self.inputs.push(value_info("input", DTYPE_FLOAT, Some(dims)));
+        "input".to_string()
+    }
+
+    /// Expose `value` as a graph output called `name` via an `Identity` node.
+    fn output(&mut self, value: &str, name: &str, elem_type: u64) {
+        let mut m = Message::default();
+        m.string(1, value);
+        m.string(2, name);
+        m.string(3, &self.fresh("Identity"));
+        m.string(4, "Identity");
+        self.nodes.push(m);
+        self.outputs.push(value_info(name, elem_type, None));
+    }
+
+    fn finish(self, graph_name: &str) -> Vec<u8> {
+        let mut graph = Message::default();
+        self.nodes.iter().for_each(|n| graph.message(1, n));
+        graph.string(2, graph_name);
+        self.initializers.iter().for_each(|t| graph.message(5, t));
+        self.inputs.iter().for_each(|i| graph.message(11, i));
+        self.outputs.iter().for_each(|o| graph.message(12, o));
+
+        let mut model = Message::default();
+        model.int(1, IR_VERSION as i64);
+        model.string(2, "oxidize-ml");
+        model.string(3, env!("CARGO_PKG_VERSION"));
+        model.message(7, &graph);
+        let mut opset = Message::default();
+        opset.string(1, "");
+        opset.int(2, OPSET as i64);
+        model.message(8, &opset);
+        if self.uses_ml {
+            let mut ml = Message::default();
+            ml.string(1, ML_DOMAIN);
+            ml.int(2, ML_OPSET as i64);
+            model.message(8, &ml);
+        }
+        model.0
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Neural networks
+// ---------------------------------------------------------------------------
+
+/// Supports `Linear`, `Conv2D`, `ConvTranspose2D`, `Flatten`, `Dropout` (as
+/// `Identity`) and the `ReLU`, `LeakyReLU`, `Sigmoid` and `Tanh`
+/// activations. The input is `[N, in_features]`, or `[N, C, H, W]` when the
+/// first layer is a convolution.
+impl<T: Float> ToOnnx for Sequential<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let layers = self.layers();
+        let first = layers.first().ok_or("cannot export an empty Sequential")?;
+        let mut g = GraphBuilder::new();
+        let mut x = match first.kind() {
+            LayerKind::Linear => {
+                let in_features = first.parameters()[0].data.shape().dim(0)?;
+                g.input(&[Dim::Param("N"), Dim::Fixed(in_features)])
+            }
+            LayerKind::Conv2D { groups, .. } => {
+                let in_channels = first.parameters()[0].data.shape().dim(1)? * groups;
+                g.input(&[Dim::Param("N"), Dim::Fixed(in_channels), Dim::Param("H"), Dim::Param("W")])
+            }
+            LayerKind::ConvTranspose2D { .. } => {
+                let in_channels = first.parameters()[0].data.shape().dim(0)?;
+                g.input(&[Dim::Param("N"), Dim::Fixed(in_channels), Dim::Param("H"), Dim::Param("W")])
+            }
+            _ => g.input(&[Dim::Param("N"), Dim::Param("F")]),
+        };
+
+        for (i, layer) in layers.iter().enumerate() {
+            x = match layer.kind() {
+                LayerKind::Linear => {
+                    let params = layer.parameters();
+                    let (w, b) = (&params[0].data, &params[1].data);
+                    let w = g.weight("weight", &w.shape_vec(), w.data());
+                    let b = g.weight("bias", &[b.numel()], b.data());
+                    g.op("Gemm", &[&x, &w, &b], &[])
+                }
+                LayerKind::Conv2D { kernel_size, stride, padding, dilation, groups } => {
+                    let params = layer.parameters();
+                    let (w, b) = (&params[0].data, &params[1].data);
+                    let w = g.weight("weight", &w.shape_vec(), w.data());
+                    let b = g.weight("bias", &[b.numel()], b.data());
+                    let attrs = conv_attrs(kernel_size, stride, padding, dilation, groups);
+                    g.op("Conv", &[&x, &w, &b], &attrs)
+                }
+                LayerKind::ConvTranspose2D { kernel_size, stride, padding, output_padding, dilation, groups } => {
+                    let params = layer.parameters();
+                    let (w, b) = (&params[0].data, &params[1].data);
+                    let w = g.weight("weight", &w.shape_vec(), w.data());
+                    let b = g.weight("bias", &[b.numel()], b.data());
+                    let mut attrs = conv_attrs(kernel_size, stride, padding, dilation, groups);
+                    attrs.push(("output_padding", pair(output_padding)));
+                    g.op("ConvTranspose", &[&x, &w, &b], &attrs)
+                }
+                LayerKind::ReLU => g.op("Relu", &[&x], &[]),
+                LayerKind::Sigmoid => g.op("Sigmoid", &[&x], &[]),
+                LayerKind::Tanh => g.op("Tanh", &[&x], &[]),
+                LayerKind::LeakyReLU { alpha } => {
+                    g.op("LeakyRelu", &[&x], &[("alpha", Attr::Float(alpha as f32))])
+                }
+                LayerKind::Dropout => g.op("Identity", &[&x], &[]),
+                LayerKind::Flatten => g.op("Flatten", &[&x], &[("axis", Attr::Int(1))]),
+                LayerKind::Other => {
+                    return Err(format!("layer {} has no ONNX equivalent", i).into());
+                }
+            };
+        }
+        g.output(&x, "output", DTYPE_FLOAT);
+        Ok(g.finish("sequential"))
+    }
+}
+
+fn pair((a, b): (usize, usize)) -> Attr<'static> {
+    Attr::Ints(vec![a as i64, b as i64])
+}
+
+/// The attributes `Conv` and `ConvTranspose` share; ONNX pads are
+/// `[top, left, bottom, right]`.
+fn conv_attrs(
+    kernel_size: (usize, usize),
+    stride: (usize, usize),
+    padding: (usize, usize),
+    dilation: (usize, usize),
+    groups: usize,
+) -> Vec<(&'static str, Attr<'static>)> {
+    let (ph, pw) = (padding.0 as i64, padding.1 as i64);
+    vec![
+        ("kernel_shape", pair(kernel_size)),
+        ("strides", pair(stride)),
+        ("pads", Attr::Ints(vec![ph, pw, ph, pw])),
+        ("dilations", pair(dilation)),
+        ("group", Attr::Int(groups as i64)),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// Linear models
+// ---------------------------------------------------------------------------
+
+/// `x·w + b` as `MatMul` + `Add`, producing `[N]`.
+fn linear_graph<T: Float>(g: &mut GraphBuilder, weights: &[T], bias: Option<T>) -> String {
+    let x = g.input(&[Dim::Param("N"), Dim::Fixed(weights.len())]);
+    let w = g.weight("coef", &[weights.len()], weights);
+    let z = g.op("MatMul", &[&x, &w], &[]);
+    match bias {
+        Some(b) => {
+            let b = g.weight("intercept", &[], &[b]);
+            g.op("Add", &[&z, &b], &[])
+        }
+        None => z,
+    }
+}
+
+/// Outputs the predictions `[N]` as `output`.
+impl<T: Float> ToOnnx for LinearRegression<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let w = self.weights.as_ref().ok_or("Model not fitted")?;
+        let mut g = GraphBuilder::new();
+        let y = linear_graph(&mut g, w.data(), self.bias);
+        g.output(&y, "output", DTYPE_FLOAT);
+        Ok(g.finish("linear_regression"))
+    }
+}
+
+/// Outputs `P(y = 1)` `[N]` as `probability`, like `predict_proba`.
+impl<T: Float> ToOnnx for LogisticRegression<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let w = self.weights.as_ref().ok_or("Model not fitted")?;
+        let mut g = GraphBuilder::new();
+        let z = linear_graph(&mut g, w.data(), self.bias);
+        let p = g.op("Sigmoid", &[&z], &[]);
+        g.output(&p, "probability", DTYPE_FLOAT);
+        Ok(g.finish("logistic_regression"))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tree ensembles
+// ---------------------------------------------------------------------------
+
+/// One fitted tree of an ensemble: its nodes, the input column of each of
+/// its features (forests train trees on feature subsets) and the weight its
+/// leaves contribute.
+struct EnsembleTree<'a, T: Float> {
+    nodes: Vec<FlatNode<T>>,
+    features: Option<&'a [usize]>,
+    weight: f64,
+}
+
+/// A leaf of an ensemble: its tree and node ids, value and tree weight.
+struct Leaf {
+    tree: i64,
+    node: i64,
+    value: f64,
+    weight: f64,
+}
+
+type Attrs = Vec<(&'static str, Attr<'static>)>;
+
+/// The `nodes_*` attributes shared by both tree ensemble operators, plus
+/// every leaf.
+struct TreeAttrs {
+    treeids: Vec<i64>,
+    nodeids: Vec<i64>,
+    featureids: Vec<i64>,
+    values: Vec<f32>,
+    modes: Vec<&'static str>,
+    truenodeids: Vec<i64>,
+    falsenodeids: Vec<i64>,
+    missing_tracks_true: Vec<i64>,
+    leaves: Vec<Leaf>,
+}
+
+fn tree_attrs<T: Float>(trees: &[EnsembleTree<T>]) -> Result<TreeAttrs, Box<dyn Error>> {
+    let mut a = TreeAttrs {
+        treeids: Vec::new(),
+        nodeids: Vec::new(),
+        featureids: Vec::new(),
+        values: Vec::new(),
+        modes: Vec::new(),
+        truenodeids: Vec::new(),
+        falsenodeids: Vec::new(),
+        missing_tracks_true: Vec::new(),
+        leaves: Vec::new(),
+    };
+    for (t, tree) in trees.iter().enumerate() {
+        if tree.nodes.is_empty() {
+            return Err("Model not fitted".into());
+        }
+        for (id, node) in tree.nodes.iter().enumerate() {
+            a.treeids.push(t as i64);
+            a.nodeids.push(id as i64);
+            match *node {
+                FlatNode::Categorical { .. } => {
+                    return Err("categorical splits cannot be exported to ONNX".into());
+                }
+                FlatNode::Split { feature, threshold, left, right, missing_left } => {
+                    let column = tree.features.map_or(feature, |f| f[feature]);
+                    a.featureids.push(column as i64);
+                    a.values.push(threshold.to_f64() as f32);
+                    a.modes.push("BRANCH_LEQ");
+                    a.truenodeids.push(left as i64);
+                    a.falsenodeids.push(right as i64);
+                    a.missing_tracks_true.push(missing_left as i64);
+                }
+                FlatNode::Leaf { value } => {
+                    a.featureids.push(0);
+                    a.values.push(0.0);
+                    a.modes.push("LEAF");
+                    a.truenodeids.push(0);
+                    a.falsenodeids.push(0);
+                    a.missing_tracks_true.push(0);
+                    a.leaves.push(Leaf { tree: t as i64, node: id as i64, value: value.to_f64(), weight: tree.weight });
+                }
+            }
+        }
+    }
+    Ok(a)
+}
+
+impl TreeAttrs {
+    fn into_attrs(self) -> (Attrs, Vec<Leaf>) {
+        let attrs = vec![
+            ("nodes_treeids", Attr::Ints(self.treeids)),
+            ("nodes_nodeids", Attr::Ints(self.nodeids)),
+            ("nodes_featureids", Attr::Ints(self.featureids)),
+            ("nodes_values", Attr::Floats(self.values)),
+            ("nodes_modes", Attr::Strs(self.modes)),
+            ("nodes_truenodeids", Attr::Ints(self.truenodeids)),
+            ("nodes_falsenodeids", Attr::Ints(self.falsenodeids)),
+            ("nodes_missing_value_tracks_true", Attr::Ints(self.missing_tracks_true)),
+        ];
+        (attrs, self.leaves)
+    }
+}
+
+/// `TreeEnsembleRegressor` summing `weight * leaf` over trees plus `base`,
+/// reshaped to `[N]`.
+fn regressor_graph<T: Float>(g: &mut GraphBuilder, trees: &[EnsembleTree<T>], base: f64) -> Result<String, Box<dyn Error>> {
+    let (mut attrs, leaves) = tree_attrs(trees)?.into_attrs();
+    attrs.extend([
+        ("target_treeids", Attr::Ints(leaves.iter().map(|l| l.tree).collect())),
+        ("target_nodeids", Attr::Ints(leaves.iter().map(|l| l.node).collect())),
+        ("target_ids", Attr::Ints(vec![0; leaves.len()])),
+        ("target_weights", Attr::Floats(leaves.iter().map(|l| (l.value * l.weight) as f32).collect())),
+        ("n_targets", Attr::Int(1)),
+        ("aggregate_function", Attr::Str("SUM")),
+        ("base_values", Attr::Floats(vec![base as f32])),
+        ("post_transform", Attr::Str("NONE")),
+    ]);
+    let x = g.input(&[Dim::Param("N"), Dim::Param("F")]);
+    let y = g.op("TreeEnsembleRegressor", &[&x], &attrs);
+    let shape = g.int64s("shape", &[-1]);
+    Ok(g.op("Reshape", &[&y, &shape], &[]))
+}
+
+/// `TreeEnsembleClassifier` whose leaves vote `weight` for their class.
+/// Outputs `label` `[N]` (int64) and `probabilities` `[N, n_classes]`.
+fn classifier_graph<T: Float>(
+    g: &mut GraphBuilder,
+    trees: &[EnsembleTree<T>],
+    n_classes: usize,
+) -> Result<(), Box<dyn Error>> {
+    let (mut attrs, leaves) = tree_attrs(trees)?.into_attrs();
+    attrs.extend([
+        ("class_treeids", Attr::Ints(leaves.iter().map(|l| l.tree).collect())),
+        ("class_nodeids", Attr::Ints(leaves.iter().map(|l| l.node).collect())),
+        ("class_ids", Attr::Ints(leaves.iter().map(|l| l.value as i64).collect())),
+        ("class_weights", Attr::Floats(leaves.iter().map(|l| l.weight as f32).collect())),
+        ("classlabels_int64s", Attr::Ints((0..n_classes.max(1) as i64).collect())),
+        ("post_transform", Attr::Str("NONE")),
+    ]);
+    let x = g.input(&[Dim::Param("N"), Dim::Param("F")]);
+    let outs = g.node("TreeEnsembleClassifier", &[&x], 2, &attrs);
+    g.output(&outs[0], "label", DTYPE_INT64);
+    g.output(&outs[1], "probabilities", DTYPE_FLOAT);
+    Ok(())
+}
+
+fn single<T: Float>(nodes: Vec<FlatNode<T>>) -> Vec<EnsembleTree<'static, T>> {
+    vec![EnsembleTree { nodes, features: None, weight: 1.0 }]
+}
+
+impl<T: Float> ToOnnx for DecisionTreeRegressor<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut g = GraphBuilder::new();
+        let y = regressor_graph(&mut g, &single(self.nodes()), 0.0)?;
+        g.output(&y, "output", DTYPE_FLOAT);
+        Ok(g.finish("decision_tree_regressor"))
+    }
+}
+
+impl<T: Float> ToOnnx for DecisionTreeClassifier<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut g = GraphBuilder::new();
+        classifier_graph(&mut g, &single(self.nodes()), self.n_classes)?;
+        Ok(g.finish("decision_tree_classifier"))
+    }
+}
+
+impl<T: Float> ToOnnx for RandomForestRegressor<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let n = self.estimators().count() as f64;
+        let trees: Vec<_> = self
+            .estimators()
+            .map(|(tree, features)| EnsembleTree { nodes: tree.nodes(), features: Some(features), weight: 1.0 / n })
+            .collect();
+        if trees.is_empty() {
+            return Err("Model not fitted".into());
+        }
+        let mut g = GraphBuilder::new();
+        let y = regressor_graph(&mut g, &trees, 0.0)?;
+        g.output(&y, "output", DTYPE_FLOAT);
+        Ok(g.finish("random_forest_regressor"))
+    }
+}
+
+/// Probabilities are the fraction of trees voting for each class, so the
+/// label is the forest's majority vote.
+impl<T: Float> ToOnnx for RandomForestClassifier<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let n = self.estimators().count() as f64;
+        let trees: Vec<_> = self
+            .estimators()
+            .map(|(tree, features)| EnsembleTree { nodes: tree.nodes(), features: Some(features), weight: 1.0 / n })
+            .collect();
+        if trees.is_empty() {
+            return Err("Model not fitted".into());
+        }
+        let mut g = GraphBuilder::new();
+        classifier_graph(&mut g, &trees, self.n_classes)?;
+        Ok(g.finish("random_forest_classifier"))
+    }
+}
+
+fn boosted_trees<T: Float>(trees: &[DecisionTreeRegressor<T>], learning_rate: T) -> Result<Vec<EnsembleTree<'static, T>>, Box<dyn Error>> {
+    if trees.is_empty() {
+        return Err("Model not fitted".into());
+    }
+    Ok(trees
+        .iter()
+        .map(|t| EnsembleTree { nodes: t.nodes(), features: None, weight: learning_rate.to_f64() })
+        .collect())
+}
+
+impl<T: Float> ToOnnx for GradientBoostingRegressor<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let trees = boosted_trees(self.estimators(), self.learning_rate)?;
+        let mut g = GraphBuilder::new();
+        let y = regressor_graph(&mut g, &trees, self.initial_prediction().to_f64())?;
+        g.output(&y, "output", DTYPE_FLOAT);
+        Ok(g.finish("gradient_boosting_regressor"))
+    }
+}
+
+/// Outputs `P(y = 1)` `[N]` as `probability`, like `predict_proba`.
+impl<T: Float> ToOnnx for GradientBoostingClassifier<T> {
+    fn to_onnx_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let trees = boosted_trees(self.estimators(), self.learning_rate)?;
+        let mut g = GraphBuilder::new();
+        let z = regressor_graph(&mut g, &trees, self.initial_log_odds().to_f64())?;
+        let p = g.op("Sigmoid", &[&z], &[]);
+        g.output(&p, "probability", DTYPE_FLOAT);
+        Ok(g.finish("gradient_boosting_classifier"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::Tensor;
+    use oxidize_ml_nn::{Conv2D, ConvTranspose2D, Linear, ReLULayer, SigmoidLayer};
+
+    fn contains(haystack: &[u8], needle: &str) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle.as_bytes())
+    }
+
+    #[test]
+    fn test_varint_encoding() {
+        let mut m = Message::default();
+        m.int(1, 300);
+        assert_eq!(m.0, vec![0x08, 0xac, 0x02]);
+
+        let mut m = Message::default();
+        m.int(1, -1);
+        assert_eq!(m.0.len(), 11);
+        assert_eq!(m.0[10], 0x01);
+    }
+
+    #[test]
+    fn test_export_sequential() {
+        let model = Sequential::<f64>::new()
+            .add(Box::new(Linear::new(4, 8)))
+            .add(Box::new(ReLULayer))
+            .add(Box::new(Linear::new(8, 1)))
+            .add(Box::new(SigmoidLayer));
+        let bytes = model.to_onnx_bytes().unwrap();
+        for op in ["Gemm", "Relu", "Sigmoid", "oxidize-ml"] {
+            assert!(contains(&bytes, op), "missing {}", op);
+        }
+        assert!(!contains(&bytes, ML_DOMAIN));
+        assert!(Sequential::<f64>::new().to_onnx_bytes().is_err());
+
+        let decoder = Sequential::<f64>::new()
+            .add(Box::new(Conv2D::new(4, 4, 3, 1, 1).with_groups(4)))
+            .add(Box::new(ConvTranspose2D::new(4, 2, 2, 2, 0)));
+        let bytes = decoder.to_onnx_bytes().unwrap();
+        for attr in ["Conv", "ConvTranspose", "group", "dilations", "output_padding"] {
+            assert!(contains(&bytes, attr), "missing {}", attr);
+        }
+    }
+
+    #[test]
+    fn test_export_trees_and_linear() {
+        let x = Tensor::new(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], vec![6, 1]).unwrap();
+        let y = Tensor::new(vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0], vec![6]).unwrap();
+
+        let mut tree = DecisionTreeClassifier::<f64>::new(3, 2, 1);
+        assert!(tree.to_onnx_bytes().is_err());
+        tree.fit(&x, &y).unwrap();
+        let bytes = tree.to_onnx_bytes().unwrap();
+        assert!(contains(&bytes, "TreeEnsembleClassifier"));
+        assert!(contains(&bytes, "BRANCH_LEQ"));
+        assert!(contains(&bytes, ML_DOMAIN));
+
+        let mut gb = GradientBoostingRegressor::<f64>::new(5, 0.1, 2, 2, 1.0);
+        gb.fit(&x, &y).unwrap();
+        assert!(contains(&gb.to_onnx_bytes().unwrap(), "TreeEnsembleRegressor"));
+
+        let mut lr = LinearRegression::<f64>::new(true);
+        lr.fit(&x, &y).unwrap();
+        let bytes = lr.to_onnx_bytes().unwrap();
+        assert!(contains(&bytes, "MatMul") && contains(&bytes, "Add"));
+    }
+}
@@ -0,0 +1,1172 @@
+//! PMML 4.4 export, for scoring with PMML engines (JPMML, Zementis and
+//! the like).
+//!
+//! `PmmlExport` writes one document from a chain of fitted scalers and an
+//! optional final model. Scalers become `DerivedField`s in the
+//! `TransformationDictionary`, linear models `RegressionModel`s, decision
+//! trees `TreeModel`s and random forests a `MiningModel` voting or
+//! averaging over its trees. Inputs are continuous `double` fields named
+//! `x0`, `x1`, … unless renamed with `with_feature_names`. Numbers are
+//! written at full f64 precision, so a conforming evaluator reproduces the
+//! Rust predictions. The XML is written by hand to avoid a dependency.
+
+use oxidize_ml_core::Float;
+use oxidize_ml_linear::{LinearRegression, LogisticRegression, MultiClass};
+use oxidize_ml_preprocessing::{MaxAbsScaler, MinMaxScaler, RobustScaler, StandardScaler};
+use oxidize_ml_tree::{
+    DecisionTreeClassifier, DecisionTreeRegressor, FlatNode, RandomForestClassifier, RandomForestRegressor,
+};
+use std::error::Error;
+#[cfg(feature = "fs")]
+use std::fs;
+
+const NAMESPACE: &str = "http://www.dmg.org/PMML-4_4";
+const VERSION: &str = "4.4";
+
+/// A fitted scaler or model that can be one step of a PMML document.
+pub trait ToPmml {
+    /// Input columns the step reads. Trees do not record their width, so
+    /// for them this is one past the highest column they split on.
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>>;
+
+    /// The step's PMML, reading the fields named by `inputs`.
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>>;
+
+    /// A document holding just this step.
+    fn to_pmml(&self) -> Result<String, Box<dyn Error>>
+    where
+        Self: Sized,
+    {
+        PmmlExport::new().with_step(self).build()
+    }
+
+    /// Write `to_pmml` to a `.pmml` file.
+    #[cfg(feature = "fs")]
+    fn save_pmml(&self, path: &str) -> Result<(), Box<dyn Error>>
+    where
+        Self: Sized,
+    {
+        fs::write(path, self.to_pmml()?)?;
+        Ok(())
+    }
+}
+
+/// One exported step: the derived fields of a scaler, or a model element.
+pub struct PmmlStep {
+    kind: StepKind,
+}
+
+enum StepKind {
+    Transform { fields: Vec<Element>, outputs: Vec<String> },
+    Model { model: Element, target: Target },
+}
+
+enum Target {
+    Continuous,
+    /// Class labels and their `dataType`.
+    Categorical(Vec<String>, &'static str),
+}
+
+/// Builds a PMML document from scalers followed by at most one model.
+pub struct PmmlExport<'a> {
+    steps: Vec<&'a dyn ToPmml>,
+    pub feature_names: Option<Vec<String>>,
+    pub target_name: String,
+}
+
+impl Default for PmmlExport<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> PmmlExport<'a> {
+    pub fn new() -> Self {
+        PmmlExport { steps: Vec::new(), feature_names: None, target_name: "y".to_string() }
+    }
+
+    /// Append a step; a model must come last.
+    pub fn with_step(mut self, step: &'a dyn ToPmml) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn with_feature_names(mut self, names: &[&str]) -> Self {
+        self.feature_names = Some(names.iter().map(|n| n.to_string()).collect());
+        self
+    }
+
+    pub fn with_target_name(mut self, name: &str) -> Self {
+        self.target_name = name.to_string();
+        self
+    }
+
+    /// The document as XML text.
+    pub fn build(&self) -> Result<String, Box<dyn Error>> {
+        let first = self.steps.first().ok_or("nothing to export")?;
+        let width = first.pmml_inputs()?;
+        let raw: Vec<String> = match &self.feature_names {
+            Some(names) if names.len() < width => {
+                return Err(format!("{} feature names for a model reading {} columns", names.len(), width).into())
+            }
+            Some(names) => names.clone(),
+            None => (0..width).map(|i| format!("x{}", i)).collect(),
+        };
+        if raw.contains(&self.target_name) {
+            return Err(format!("target name '{}' is also a feature name", self.target_name).into());
+        }
+
+        let (mut derived, mut current, mut model) = (Vec::new(), raw.clone(), None);
+        for step in &self.steps {
+            if model.is_some() {
+                return Err("a model must be the last step".into());
+            }
+            let needed = step.pmml_inputs()?;
+            if needed > current.len() {
+                return Err(format!("step reads {} columns but only {} are available", needed, current.len()).into());
+            }
+            match step.pmml_step(&current)?.kind {
+                StepKind::Transform { fields, outputs } => {
+                    derived.extend(fields);
+                    current = outputs;
+                }
+                StepKind::Model { model: m, target } => model = Some((m, target)),
+            }
+        }
+
+        let mut dictionary: Vec<Element> = raw
+            .iter()
+            .map(|name| Element::new("DataField").attr("name", name).attr("optype", "continuous").attr("dataType", "double"))
+            .collect();
+        let mut schema = Element::new("MiningSchema")
+            .children(raw.iter().map(|name| Element::new("MiningField").attr("name", name)));
+        if let Some((_, target)) = &model {
+            let field = Element::new("DataField").attr("name", &self.target_name);
+            dictionary.push(match target {
+                Target::Continuous => field.attr("optype", "continuous").attr("dataType", "double"),
+                Target::Categorical(labels, data_type) => field
+                    .attr("optype", "categorical")
+                    .attr("dataType", *data_type)
+                    .children(labels.iter().map(|l| Element::new("Value").attr("value", l))),
+            });
+            schema = schema.child(Element::new("MiningField").attr("name", &self.target_name).attr("usageType", "target"));
+        }
+
+        let mut pmml = Element::new("PMML")
+            .attr("xmlns", NAMESPACE)
+            .attr("version", VERSION)
+            .child(
+                Element::new("Header")
+                    .attr("description", "Exported by OxidizeML")
+                    .child(Element::new("Application").attr("name", "OxidizeML").attr("version", env!("CARGO_PKG_VERSION"))),
+            )
+            .child(Element::new("DataDictionary").attr("numberOfFields", dictionary.len()).children(dictionary));
+        if !derived.is_empty() {
+            pmml = pmml.child(Element::new("TransformationDictionary").children(derived));
+        }
+        if let Some((mut m, _)) = model {
+            add_schema(&mut m, &schema);
+            pmml = pmml.child(m);
+        }
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        pmml.write(&mut out, 0);
+        Ok(out)
+    }
+
+    /// Write `build` to a `.pmml` file.
+    #[cfg(feature = "fs")]
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.build()?)?;
+        Ok(())
+    }
+}
+
+/// Every model element, nested ones included, opens with the schema.
+fn add_schema(element: &mut Element, schema: &Element) {
+    for child in &mut element.children {
+        add_schema(child, schema);
+    }
+    if matches!(element.name, "RegressionModel" | "TreeModel" | "MiningModel") {
+        element.children.insert(0, schema.clone());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// XML
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct Element {
+    name: &'static str,
+    attrs: Vec<(&'static str, String)>,
+    children: Vec<Element>,
+    text: Option<String>,
+}
+
+impl Element {
+    fn new(name: &'static str) -> Self {
+        Element { name, attrs: Vec::new(), children: Vec::new(), text: None }
+    }
+
+    fn attr(mut self, key: &'static str, value: impl ToString) -> Self {
+        self.attrs.push((key, value.to_string()));
+        self
+    }
+
+    fn child(mut self, child: Element) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Element>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    fn text(mut self, text: String) -> Self {
+        self.text = Some(text);
+        self
+    }
+
+    fn write(&self, out: &mut String, depth: usize) {
+        out.push_str(&"  ".repeat(depth));
+        out.push('<');
+        out.push_str(self.name);
+        for (key, value) in &self.attrs {
+            out.push_str(&format!(" {}=\"{}\"", key, escape(value)));
+        }
+        match (&self.text, self.children.is_empty()) {
+            (Some(text), _) => out.push_str(&format!(">{}</{}>\n", escape(text), self.name)),
+            (None, true) => out.push_str("/>\n"),
+            (None, false) => {
+                out.push_str(">\n");
+                for child in &self.children {
+                    child.write(out, depth + 1);
+                }
+                out.push_str(&format!("{}</{}>\n", "  ".repeat(depth), self.name));
+            }
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// An `xs:double` that parses back to the same f64.
+fn num(v: f64) -> String {
+    match v {
+        v if v.is_nan() => "NaN".to_string(),
+        f64::INFINITY => "INF".to_string(),
+        f64::NEG_INFINITY => "-INF".to_string(),
+        v => format!("{:?}", v),
+    }
+}
+
+/// A class label as the category string: `1` rather than `1.0`.
+fn label(v: f64) -> String {
+    format!("{}", v)
+}
+
+fn label_type(labels: &[String]) -> &'static str {
+    if labels.iter().all(|l| l.parse::<i64>().is_ok()) { "integer" } else { "double" }
+}
+
+fn categorical(labels: Vec<String>) -> Target {
+    let data_type = label_type(&labels);
+    Target::Categorical(labels, data_type)
+}
+
+// ---------------------------------------------------------------------------
+// Scalers
+// ---------------------------------------------------------------------------
+
+fn check_width(inputs: &[String], expected: usize) -> Result<(), Box<dyn Error>> {
+    if inputs.len() != expected {
+        return Err(format!("fitted on {} columns but given {}", expected, inputs.len()).into());
+    }
+    Ok(())
+}
+
+fn constant(v: f64) -> Element {
+    Element::new("Constant").attr("dataType", "double").text(num(v))
+}
+
+/// `(x - shift) / divisor` per column, skipping no-op operations so the
+/// arithmetic matches the scaler's exactly.
+fn scale_step(prefix: &str, inputs: &[String], shift: &[f64], divisor: &[f64]) -> Result<PmmlStep, Box<dyn Error>> {
+    check_width(inputs, shift.len())?;
+    let mut fields = Vec::with_capacity(inputs.len());
+    let mut outputs = Vec::with_capacity(inputs.len());
+    for (j, input) in inputs.iter().enumerate() {
+        let mut expr = Element::new("FieldRef").attr("field", input);
+        if shift[j] != 0.0 {
+            expr = Element::new("Apply").attr("function", "-").child(expr).child(constant(shift[j]));
+        }
+        if divisor[j] != 1.0 {
+            expr = Element::new("Apply").attr("function", "/").child(expr).child(constant(divisor[j]));
+        }
+        let name = format!("{}({})", prefix, input);
+        fields.push(
+            Element::new("DerivedField")
+                .attr("name", &name)
+                .attr("optype", "continuous")
+                .attr("dataType", "double")
+                .child(expr),
+        );
+        outputs.push(name);
+    }
+    Ok(PmmlStep { kind: StepKind::Transform { fields, outputs } })
+}
+
+fn values<T: Float>(t: &Option<oxidize_ml_core::Tensor<T>>) -> Result<Vec<f64>, Box<dyn Error>> {
+    Ok(t.as_ref().ok_or("Model not fitted")?.data().iter().map(|v| v.to_f64()).collect())
+}
+
+/// The scalers' guard against dividing by a zero spread.
+fn safe(v: f64, eps: f64) -> f64 {
+    if v.abs() < eps { 1.0 } else { v }
+}
+
+impl<T: Float> ToPmml for StandardScaler<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(values(&self.mean)?.len())
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let eps = T::EPSILON.to_f64();
+        let std: Vec<f64> = values(&self.std)?.into_iter().map(|s| safe(s, eps)).collect();
+        scale_step("standard", inputs, &values(&self.mean)?, &std)
+    }
+}
+
+impl<T: Float> ToPmml for MinMaxScaler<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(values(&self.min)?.len())
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let (min, max) = (values(&self.min)?, values(&self.max)?);
+        let eps = T::EPSILON.to_f64();
+        let range: Vec<f64> = min.iter().zip(&max).map(|(lo, hi)| safe(hi - lo, eps)).collect();
+        scale_step("minmax", inputs, &min, &range)
+    }
+}
+
+impl<T: Float> ToPmml for RobustScaler<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(values(&self.center)?.len())
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        scale_step("robust", inputs, &values(&self.center)?, &values(&self.scale)?)
+    }
+}
+
+impl<T: Float> ToPmml for MaxAbsScaler<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(values(&self.max_abs)?.len())
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let max_abs: Vec<f64> = values(&self.max_abs)?.into_iter().map(|m| safe(m, f64::EPSILON)).collect();
+        scale_step("maxabs", inputs, &vec![0.0; max_abs.len()], &max_abs)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Linear models
+// ---------------------------------------------------------------------------
+
+fn regression_table(inputs: &[String], coefs: impl Iterator<Item = f64>, intercept: f64) -> Element {
+    Element::new("RegressionTable").attr("intercept", num(intercept)).children(
+        inputs
+            .iter()
+            .zip(coefs)
+            .map(|(name, c)| Element::new("NumericPredictor").attr("name", name).attr("coefficient", num(c))),
+    )
+}
+
+/// `prediction` plus, if `probabilities`, `probability(label)` per class.
+fn class_output(labels: &[String], probabilities: bool) -> Element {
+    let predicted = Element::new("OutputField")
+        .attr("name", "prediction")
+        .attr("optype", "categorical")
+        .attr("dataType", label_type(labels))
+        .attr("feature", "predictedValue");
+    let probs = labels.iter().filter(|_| probabilities).map(|l| {
+        Element::new("OutputField")
+            .attr("name", format!("probability({})", l))
+            .attr("optype", "continuous")
+            .attr("dataType", "double")
+            .attr("feature", "probability")
+            .attr("value", l)
+    });
+    Element::new("Output").child(predicted).children(probs)
+}
+
+impl<T: Float> ToPmml for LinearRegression<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(values(&self.weights)?.len())
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let w = values(&self.weights)?;
+        check_width(inputs, w.len())?;
+        let bias = self.bias.map_or(0.0, |b| b.to_f64());
+        let model = Element::new("RegressionModel")
+            .attr("functionName", "regression")
+            .attr("modelName", "LinearRegression")
+            .child(regression_table(inputs, w.into_iter(), bias));
+        Ok(PmmlStep { kind: StepKind::Model { model, target: Target::Continuous } })
+    }
+}
+
+/// Binary models use a `logit` table for class 1; multinomial ones a
+/// `softmax` table per class. One-vs-rest renormalizes independent
+/// sigmoids, which PMML cannot express, and is refused.
+impl<T: Float> ToPmml for LogisticRegression<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        match (&self.coef, &self.weights) {
+            (Some(coef), _) => Ok(coef.shape_vec()[0]),
+            (None, w) => Ok(values(w)?.len()),
+        }
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let model = Element::new("RegressionModel")
+            .attr("functionName", "classification")
+            .attr("modelName", "LogisticRegression");
+        if let (Some(coef), Some(intercept), Some(classes)) = (&self.coef, &self.intercept, &self.classes) {
+            if self.multi_class == MultiClass::Ovr {
+                return Err("one-vs-rest logistic regression cannot be exported to PMML".into());
+            }
+            let (p, k) = (coef.shape_vec()[0], intercept.numel());
+            check_width(inputs, p)?;
+            let labels: Vec<String> = classes.iter().map(|c| label(c.to_f64())).collect();
+            let coef = coef.data();
+            let tables = (0..k).map(|c| {
+                regression_table(inputs, (0..p).map(|j| coef[j * k + c].to_f64()), intercept.data()[c].to_f64())
+                    .attr("targetCategory", &labels[c])
+            });
+            let model = model
+                .attr("normalizationMethod", "softmax")
+                .child(class_output(&labels, true))
+                .children(tables);
+            return Ok(PmmlStep { kind: StepKind::Model { model, target: categorical(labels) } });
+        }
+        let w = values(&self.weights)?;
+        check_width(inputs, w.len())?;
+        let bias = self.bias.map_or(0.0, |b| b.to_f64());
+        let labels = vec!["0".to_string(), "1".to_string()];
+        let model = model
+            .attr("normalizationMethod", "logit")
+            .child(class_output(&labels, true))
+            .child(regression_table(inputs, w.into_iter(), bias).attr("targetCategory", "1"))
+            .child(Element::new("RegressionTable").attr("intercept", "0.0").attr("targetCategory", "0"));
+        Ok(PmmlStep { kind: StepKind::Model { model, target: categorical(labels) } })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Trees
+// ---------------------------------------------------------------------------
+
+/// A fitted tree and the input column of each of its features (forests
+/// train trees on feature subsets).
+struct Tree<'a, T: Float> {
+    nodes: Vec<FlatNode<T>>,
+    features: Option<&'a [usize]>,
+}
+
+impl<T: Float> Tree<'_, T> {
+    fn column(&self, feature: usize) -> usize {
+        self.features.map_or(feature, |f| f[feature])
+    }
+
+    /// One past the highest column split on.
+    fn width(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter_map(|node| match *node {
+                FlatNode::Split { feature, .. } | FlatNode::Categorical { feature, .. } => Some(self.column(feature) + 1),
+                FlatNode::Leaf { .. } => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The `Node` for `nodes[id]`, entered when `predicate` holds.
+    fn node(&self, id: usize, predicate: Element, inputs: &[String], classification: bool) -> Element {
+        let node = Element::new("Node").attr("id", id);
+        match &self.nodes[id] {
+            FlatNode::Leaf { value } => {
+                let score = if classification { label(value.to_f64()) } else { num(value.to_f64()) };
+                node.attr("score", score).child(predicate)
+            }
+            FlatNode::Split { feature, threshold, left, right, missing_left } => {
+                let field = &inputs[self.column(*feature)];
+                let test = |op: &str| {
+                    Element::new("SimplePredicate").attr("field", field).attr("operator", op).attr("value", num(threshold.to_f64()))
+                };
+                node.attr("defaultChild", if *missing_left { left } else { right })
+                    .child(predicate)
+                    .child(self.node(*left, test("lessOrEqual"), inputs, classification))
+                    .child(self.node(*right, test("greaterThan"), inputs, classification))
+            }
+            FlatNode::Categorical { feature, categories, left, right, missing_left } => {
+                let field = &inputs[self.column(*feature)];
+                let set = |op: &str| {
+                    let array: Vec<String> = categories.iter().map(|c| num(c.to_f64())).collect();
+                    Element::new("SimpleSetPredicate").attr("field", field).attr("booleanOperator", op).child(
+                        Element::new("Array").attr("n", categories.len()).attr("type", "real").text(array.join(" ")),
+                    )
+                };
+                node.attr("defaultChild", if *missing_left { left } else { right })
+                    .child(predicate)
+                    .child(self.node(*left, set("isIn"), inputs, classification))
+                    .child(self.node(*right, set("isNotIn"), inputs, classification))
+            }
+        }
+    }
+
+    fn model(&self, inputs: &[String], classification: bool) -> Result<Element, Box<dyn Error>> {
+        if self.nodes.is_empty() {
+            return Err("Model not fitted".into());
+        }
+        if self.width() > inputs.len() {
+            return Err(format!("tree splits on column {} but given {}", self.width() - 1, inputs.len()).into());
+        }
+        Ok(Element::new("TreeModel")
+            .attr("functionName", if classification { "classification" } else { "regression" })
+            .attr("missingValueStrategy", "defaultChild")
+            .attr("noTrueChildStrategy", "returnLastPrediction")
+            .attr("splitCharacteristic", "binarySplit")
+            .child(self.node(0, Element::new("True"), inputs, classification)))
+    }
+}
+
+fn class_labels(n_classes: usize) -> Vec<String> {
+    (0..n_classes.max(1)).map(|c| c.to_string()).collect()
+}
+
+impl<T: Float> ToPmml for DecisionTreeRegressor<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(Tree { nodes: self.nodes(), features: None }.width())
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let model = Tree { nodes: self.nodes(), features: None }.model(inputs, false)?.attr("modelName", "DecisionTreeRegressor");
+        Ok(PmmlStep { kind: StepKind::Model { model, target: Target::Continuous } })
+    }
+}
+
+impl<T: Float> ToPmml for DecisionTreeClassifier<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        Ok(Tree { nodes: self.nodes(), features: None }.width())
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let labels = class_labels(self.n_classes);
+        let model = Tree { nodes: self.nodes(), features: None }
+            .model(inputs, true)?
+            .attr("modelName", "DecisionTreeClassifier");
+        let model = with_output(model, class_output(&labels, false));
+        Ok(PmmlStep { kind: StepKind::Model { model, target: categorical(labels) } })
+    }
+}
+
+/// `output` ahead of the model's content; `PmmlExport` puts the
+/// `MiningSchema` before it.
+fn with_output(mut model: Element, output: Element) -> Element {
+    model.children.insert(0, output);
+    model
+}
+
+/// Segments of `(tree, feature subset)` pairs combined by `method`.
+fn forest<'a, T: Float + 'a>(
+    name: &str,
+    trees: impl Iterator<Item = (Vec<FlatNode<T>>, &'a [usize])>,
+    inputs: &[String],
+    classification: bool,
+) -> Result<Element, Box<dyn Error>> {
+    let segments = trees
+        .enumerate()
+        .map(|(i, (nodes, features))| {
+            let tree = Tree { nodes, features: Some(features) }.model(inputs, classification)?;
+            Ok(Element::new("Segment").attr("id", i + 1).child(Element::new("True")).child(tree))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    if segments.is_empty() {
+        return Err("Model not fitted".into());
+    }
+    let method = if classification { "majorityVote" } else { "average" };
+    Ok(Element::new("MiningModel")
+        .attr("functionName", if classification { "classification" } else { "regression" })
+        .attr("modelName", name)
+        .child(Element::new("Segmentation").attr("multipleModelMethod", method).children(segments)))
+}
+
+fn forest_width<T: Float>(trees: &[Tree<T>]) -> usize {
+    trees.iter().map(|t| t.width()).max().unwrap_or(0)
+}
+
+impl<T: Float> ToPmml for RandomForestRegressor<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        let trees: Vec<_> = self.estimators().map(|(t, f)| Tree { nodes: t.nodes(), features: Some(f) }).collect();
+        Ok(forest_width(&trees))
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let model = forest("RandomForestRegressor", self.estimators().map(|(t, f)| (t.nodes(), f)), inputs, false)?;
+        Ok(PmmlStep { kind: StepKind::Model { model, target: Target::Continuous } })
+    }
+}
+
+/// Probabilities are the share of trees voting for each class, as in
+/// `predict_proba`. PMML leaves tied votes to the evaluator, while
+/// `predict` picks the highest tied class.
+impl<T: Float> ToPmml for RandomForestClassifier<T> {
+    fn pmml_inputs(&self) -> Result<usize, Box<dyn Error>> {
+        let trees: Vec<_> = self.estimators().map(|(t, f)| Tree { nodes: t.nodes(), features: Some(f) }).collect();
+        Ok(forest_width(&trees))
+    }
+
+    fn pmml_step(&self, inputs: &[String]) -> Result<PmmlStep, Box<dyn Error>> {
+        let labels = class_labels(self.n_classes);
+        let model = forest("RandomForestClassifier", self.estimators().map(|(t, f)| (t.nodes(), f)), inputs, true)?;
+        let model = with_output(model, class_output(&labels, true));
+        Ok(PmmlStep { kind: StepKind::Model { model, target: categorical(labels) } })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::error::TensorResult;
+    use oxidize_ml_core::Tensor;
+    use std::collections::HashMap;
+
+    /// A parsed XML element.
+    struct Xml {
+        name: String,
+        attrs: HashMap<String, String>,
+        children: Vec<Xml>,
+        text: String,
+    }
+
+    impl Xml {
+        fn attr(&self, key: &str) -> Option<&str> {
+            self.attrs.get(key).map(|s| s.as_str())
+        }
+
+        fn child(&self, name: &str) -> Option<&Xml> {
+            self.children.iter().find(|c| c.name == name)
+        }
+
+        fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Xml> + 'a {
+            self.children.iter().filter(move |c| c.name == name)
+        }
+    }
+
+    fn unescape(s: &str) -> String {
+        s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+    }
+
+    /// Just enough XML for the documents written here.
+    fn parse(doc: &str) -> Xml {
+        let body = doc.trim_start();
+        let body = match body.strip_prefix("<?") {
+            Some(rest) => &rest[rest.find("?>").unwrap() + 2..],
+            None => body,
+        };
+        let (root, rest) = element(body.trim_start());
+        assert!(rest.trim().is_empty());
+        root
+    }
+
+    fn element(s: &str) -> (Xml, &str) {
+        let s = s.strip_prefix('<').unwrap();
+        let mut in_quote = false;
+        let tag_end = s
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    in_quote = !in_quote;
+                }
+                !in_quote && c == '>'
+            })
+            .unwrap()
+            .0;
+        let mut head = s[..tag_end].trim_end_matches('/');
+        let name = head.split_whitespace().next().unwrap().to_string();
+        head = &head[name.len()..];
+        let mut attrs = HashMap::new();
+        while let Some(eq) = head.find('=') {
+            let key = head[..eq].trim().to_string();
+            let rest = &head[eq + 2..];
+            let close = rest.find('"').unwrap();
+            attrs.insert(key, unescape(&rest[..close]));
+            head = &rest[close + 1..];
+        }
+        let mut xml = Xml { name, attrs, children: Vec::new(), text: String::new() };
+        if s[..tag_end].ends_with('/') {
+            return (xml, &s[tag_end + 1..]);
+        }
+        let mut rest = &s[tag_end + 1..];
+        loop {
+            let next = rest.find('<').unwrap();
+            xml.text.push_str(&unescape(rest[..next].trim()));
+            rest = &rest[next..];
+            if let Some(after) = rest.strip_prefix("</") {
+                return (xml, &after[after.find('>').unwrap() + 1..]);
+            }
+            let (child, after) = element(rest);
+            xml.children.push(child);
+            rest = after;
+        }
+    }
+
+    /// What a model scores for one record.
+    #[derive(Debug)]
+    enum Score {
+        Value(f64),
+        Class(String, HashMap<String, f64>),
+    }
+
+    /// A reference evaluator for the PMML subset the exporter writes:
+    /// arithmetic `Apply`s, `RegressionModel` (regression, logit, softmax),
+    /// `TreeModel` with `defaultChild` missing-value handling and
+    /// `MiningModel` voting or averaging. NaN inputs are missing values.
+    fn evaluate(doc: &Xml, row: &[f64]) -> Score {
+        let target = doc
+            .children
+            .iter()
+            .find_map(|m| m.child("MiningSchema")?.all("MiningField").find(|f| f.attr("usageType") == Some("target")))
+            .and_then(|f| f.attr("name"));
+        let mut fields: HashMap<String, f64> = HashMap::new();
+        let inputs = doc.child("DataDictionary").unwrap().all("DataField").filter(|f| f.attr("name") != target);
+        for (field, &v) in inputs.zip(row) {
+            fields.insert(field.attr("name").unwrap().to_string(), v);
+        }
+        if let Some(dict) = doc.child("TransformationDictionary") {
+            for derived in dict.all("DerivedField") {
+                let v = expression(&derived.children[0], &fields);
+                fields.insert(derived.attr("name").unwrap().to_string(), v);
+            }
+        }
+        let model = doc.children.iter().find(|c| c.name.ends_with("Model")).unwrap();
+        score(model, &fields)
+    }
+
+    fn expression(e: &Xml, fields: &HashMap<String, f64>) -> f64 {
+        match e.name.as_str() {
+            "FieldRef" => fields[e.attr("field").unwrap()],
+            "Constant" => e.text.parse().unwrap(),
+            "Apply" => {
+                let (a, b) = (expression(&e.children[0], fields), expression(&e.children[1], fields));
+                match e.attr("function").unwrap() {
+                    "-" => a - b,
+                    "/" => a / b,
+                    other => panic!("unsupported function {}", other),
+                }
+            }
+            other => panic!("unsupported expression {}", other),
+        }
+    }
+
+    fn score(model: &Xml, fields: &HashMap<String, f64>) -> Score {
+        let classification = model.attr("functionName") == Some("classification");
+        match model.name.as_str() {
+            "RegressionModel" => {
+                let tables: Vec<(String, f64)> = model
+                    .all("RegressionTable")
+                    .map(|t| {
+                        let y = t.all("NumericPredictor").fold(t.attr("intercept").unwrap().parse::<f64>().unwrap(), |acc, p| {
+                            acc + p.attr("coefficient").unwrap().parse::<f64>().unwrap() * fields[p.attr("name").unwrap()]
+                        });
+                        (t.attr("targetCategory").unwrap_or("").to_string(), y)
+                    })
+                    .collect();
+                if !classification {
+                    return Score::Value(tables[0].1);
+                }
+                let probs: Vec<f64> = match model.attr("normalizationMethod").unwrap() {
+                    "logit" => {
+                        let p = 1.0 / (1.0 + (-tables[0].1).exp());
+                        vec![p, 1.0 - p]
+                    }
+                    "softmax" => {
+                        let max = tables.iter().map(|t| t.1).fold(f64::NEG_INFINITY, f64::max);
+                        let e: Vec<f64> = tables.iter().map(|t| (t.1 - max).exp()).collect();
+                        let sum: f64 = e.iter().sum();
+                        e.iter().map(|v| v / sum).collect()
+                    }
+                    other => panic!("unsupported normalization {}", other),
+                };
+                let best = (0..probs.len()).fold(0, |b, i| if probs[i] > probs[b] { i } else { b });
+                let probs = tables.iter().map(|t| t.0.clone()).zip(probs).collect();
+                Score::Class(tables[best].0.clone(), probs)
+            }
+            "TreeModel" => {
+                let mut node = model.child("Node").unwrap();
+                loop {
+                    let children: Vec<&Xml> = node.all("Node").collect();
+                    let mut next = None;
+                    for child in &children {
+                        match predicate(&child.children[0], fields) {
+                            Some(true) => next = Some(*child),
+                            Some(false) => continue,
+                            None => next = children.iter().copied().find(|c| c.attr("id") == node.attr("defaultChild")),
+                        }
+                        break;
+                    }
+                    match next {
+                        Some(child) => node = child,
+                        None => break,
+                    }
+                }
+                let s = node.attr("score").unwrap();
+                if classification { Score::Class(s.to_string(), HashMap::new()) } else { Score::Value(s.parse().unwrap()) }
+            }
+            "MiningModel" => {
+                let scores: Vec<Score> =
+                    model.child("Segmentation").unwrap().all("Segment").map(|s| score(&s.children[1], fields)).collect();
+                let n = scores.len() as f64;
+                if !classification {
+                    return Score::Value(scores.iter().map(|s| if let Score::Value(v) = s { *v } else { 0.0 }).sum::<f64>() / n);
+                }
+                let mut votes: Vec<(String, f64)> = Vec::new();
+                for s in &scores {
+                    let Score::Class(l, _) = s else { unreachable!() };
+                    match votes.iter_mut().find(|(v, _)| v == l) {
+                        Some((_, c)) => *c += 1.0,
+                        None => votes.push((l.clone(), 1.0)),
+                    }
+                }
+                let best = votes.iter().fold(&votes[0], |b, v| if v.1 > b.1 { v } else { b }).0.clone();
+                Score::Class(best, votes.into_iter().map(|(l, c)| (l, c / n)).collect())
+            }
+            other => panic!("unsupported model {}", other),
+        }
+    }
+
+    /// `None` when the predicate is unknown because its field is missing.
+    fn predicate(p: &Xml, fields: &HashMap<String, f64>) -> Option<bool> {
+        if p.name == "True" {
+            return Some(true);
+        }
+        let v = fields[p.attr("field").unwrap()];
+        if v.is_nan() {
+            return None;
+        }
+        Some(match p.name.as_str() {
+            "SimplePredicate" => {
+                let t: f64 = p.attr("value").unwrap().parse().unwrap();
+                match p.attr("operator").unwrap() {
+                    "lessOrEqual" => v <= t,
+                    "greaterThan" => v > t,
+                    other => panic!("unsupported operator {}", other),
+                }
+            }
+            "SimpleSetPredicate" => {
+                let set: Vec<f64> = p.child("Array").unwrap().text.split_whitespace().map(|s| s.parse().unwrap()).collect();
+                set.contains(&v) == (p.attr("booleanOperator") == Some("isIn"))
+            }
+            other => panic!("unsupported predicate {}", other),
+        })
+    }
+
+    fn rows(x: &Tensor<f64>) -> Vec<Vec<f64>> {
+        x.data().chunks(x.shape_vec()[1]).map(|r| r.to_vec()).collect()
+    }
+
+    fn features() -> Tensor<f64> {
+        let data: Vec<f64> = (0..40)
+            .flat_map(|i| {
+                let t = i as f64;
+                [(t * 0.37).sin() * 4.0 + t * 0.1, (t * 0.71).cos() * 2.0 - 1.0, (i % 3) as f64]
+            })
+            .collect();
+        Tensor::new(data, vec![40, 3]).unwrap()
+    }
+
+    #[test]
+    fn test_scalers_and_linear_models_match_evaluator() {
+        let x = features();
+        let reg_y = Tensor::new(rows(&x).iter().map(|r| 2.0 * r[0] - 0.5 * r[1] + r[2] + 1.0).collect(), vec![40]).unwrap();
+        let mut minmax = MinMaxScaler::new();
+        let x1 = minmax.fit_transform(&x).unwrap();
+        let mut robust = RobustScaler::new();
+        let x2 = robust.fit_transform(&x1).unwrap();
+        let mut maxabs = MaxAbsScaler::new();
+        let x3 = maxabs.fit_transform(&x2).unwrap();
+        let mut reg = LinearRegression::new(true);
+        reg.fit(&x3, &reg_y).unwrap();
+        let doc = PmmlExport::new()
+            .with_step(&minmax)
+            .with_step(&robust)
+            .with_step(&maxabs)
+            .with_step(&reg)
+            .with_feature_names(&["age", "income & bonus", "group"])
+            .build()
+            .unwrap();
+        assert!(doc.contains("xmlns=\"http://www.dmg.org/PMML-4_4\" version=\"4.4\""));
+        assert!(doc.contains("name=\"maxabs(robust(minmax(income &amp; bonus)))\""));
+        let parsed = parse(&doc);
+        for (row, expected) in rows(&x).iter().zip(reg.predict(&x3).unwrap().data()) {
+            let Score::Value(v) = evaluate(&parsed, row) else { panic!() };
+            assert!((v - expected).abs() < 1e-9, "{} vs {}", v, expected);
+        }
+
+        let class_y = Tensor::new(reg_y.data().iter().map(|&v| if v > 6.0 { 1.0 } else { 0.0 }).collect(), vec![40]).unwrap();
+        let mut standard = StandardScaler::new();
+        let xs = standard.fit_transform(&x).unwrap();
+        let mut logistic = LogisticRegression::new(0.5, 300);
+        logistic.fit(&xs, &class_y).unwrap();
+        let parsed = parse(&PmmlExport::new().with_step(&standard).with_step(&logistic).build().unwrap());
+        let (labels, probs) = (logistic.predict(&xs).unwrap(), logistic.predict_proba(&xs).unwrap());
+        for (i, row) in rows(&x).iter().enumerate() {
+            let Score::Class(l, p) = evaluate(&parsed, row) else { panic!() };
+            assert_eq!(l, label(labels.data()[i]));
+            assert!((p["1"] - probs.data()[i]).abs() < 1e-9);
+        }
+
+        let three = Tensor::new((0..40).map(|i| (i % 3) as f64).collect(), vec![40]).unwrap();
+        let mut multi = LogisticRegression::new(0.5, 300).with_multi_class(MultiClass::Multinomial);
+        multi.fit(&xs, &three).unwrap();
+        let parsed = parse(&multi.to_pmml().unwrap());
+        let (labels, probs) = (multi.predict(&xs).unwrap(), multi.predict_proba(&xs).unwrap());
+        for (i, row) in rows(&xs).iter().enumerate() {
+            let Score::Class(l, p) = evaluate(&parsed, row) else { panic!() };
+            assert_eq!(l, label(labels.data()[i]));
+            assert!((p["2"] - probs.data()[i * 3 + 2]).abs() < 1e-9);
+        }
+        let mut ovr = LogisticRegression::new(0.5, 50).with_multi_class(MultiClass::Ovr);
+        ovr.fit(&xs, &three).unwrap();
+        assert!(ovr.to_pmml().is_err());
+        assert!(LinearRegression::<f64>::new(true).to_pmml().is_err());
+    }
+
+    #[test]
+    fn test_trees_and_forests_match_evaluator() {
+        let mut x = features();
+        let y_class = Tensor::new(rows(&x).iter().map(|r| if r[0] + r[2] > 2.5 { 1.0 } else { 0.0 }).collect(), vec![40]).unwrap();
+        let y_reg = Tensor::new(rows(&x).iter().map(|r| r[0] * r[1] + r[2]).collect(), vec![40]).unwrap();
+        let mut tree = DecisionTreeClassifier::new(6, 2, 1).with_categorical_features(&[2]);
+        tree.fit(&x, &y_class).unwrap();
+        let mut tree_reg = DecisionTreeRegressor::new(5, 2, 1);
+        tree_reg.fit(&x, &y_reg).unwrap();
+        let mut forest = RandomForestClassifier::new(7, 4, 0.7);
+        forest.fit(&x, &y_class).unwrap();
+        let mut forest_reg = RandomForestRegressor::new(5, 4, 0.7);
+        forest_reg.fit(&x, &y_reg).unwrap();
+
+        // Missing values take each split's learned default direction.
+        let mut data = x.data().to_vec();
+        data[3 * 5] = f64::NAN;
+        data[3 * 9 + 2] = f64::NAN;
+        x = Tensor::new(data, vec![40, 3]).unwrap();
+
+        let docs = [tree.to_pmml(), tree_reg.to_pmml(), forest.to_pmml(), forest_reg.to_pmml()].map(|d| d.unwrap());
+        assert!(docs[0].contains("<SimpleSetPredicate field=\"x2\""));
+        let docs = docs.map(|d| parse(&d));
+        let expected = [tree.predict(&x), tree_reg.predict(&x), forest.predict(&x), forest_reg.predict(&x)].map(|p| p.unwrap());
+        let forest_probs = forest.predict_proba(&x).unwrap();
+        for (i, row) in rows(&x).iter().enumerate() {
+            for (doc, want) in docs.iter().zip(&expected) {
+                match evaluate(doc, row) {
+                    Score::Value(v) => assert!((v - want.data()[i]).abs() < 1e-9),
+                    Score::Class(l, _) => assert_eq!(l, label(want.data()[i])),
+                }
+            }
+            let Score::Class(_, p) = evaluate(&docs[2], row) else { panic!() };
+            assert!((p.get("1").copied().unwrap_or(0.0) - forest_probs.data()[i * 2 + 1]).abs() < 1e-12);
+        }
+        assert!(DecisionTreeClassifier::<f64>::new(3, 2, 1).to_pmml().is_err());
+        assert!(PmmlExport::new().with_step(&tree).with_step(&forest).build().is_err());
+    }
+
+    /// The integer-valued training set behind the tree fixtures, so the
+    /// fitted splits and leaves do not depend on floating-point rounding.
+    fn fixture_training_data() -> (Tensor<f64>, Tensor<f64>, Tensor<f64>) {
+        let rows: Vec<[f64; 3]> =
+            (0..40).map(|i| [((i * 7) % 11 - 3) as f64, ((i * 5) % 9 - 4) as f64, (i % 3) as f64]).collect();
+        let y_class = rows.iter().map(|r| if r[0] + r[1] + 2.0 * r[2] > 3.0 { 1.0 } else { 0.0 }).collect();
+        let y_reg = rows.iter().map(|r| r[0] * r[1] + r[2]).collect();
+        let x = Tensor::new(rows.concat(), vec![40, 3]).unwrap();
+        (x, Tensor::new(y_class, vec![40]).unwrap(), Tensor::new(y_reg, vec![40]).unwrap())
+    }
+
+    fn tensor(data: &[f64], shape: &[usize]) -> Option<Tensor<f64>> {
+        Some(Tensor::new(data.to_vec(), shape.to_vec()).unwrap())
+    }
+
+    /// What the Rust model predicts for each fixture row.
+    enum Predictions {
+        Values(Vec<f64>),
+        /// Labels and, when the document has probability outputs, each
+        /// class's probability per row.
+        Classes(Vec<f64>, Option<Vec<Vec<(String, f64)>>>),
+    }
+
+    fn binary_probs(p: &Tensor<f64>) -> Option<Vec<Vec<(String, f64)>>> {
+        Some(p.data().iter().map(|&p| vec![("0".to_string(), 1.0 - p), ("1".to_string(), p)]).collect())
+    }
+
+    fn class_probs(p: &Tensor<f64>) -> Option<Vec<Vec<(String, f64)>>> {
+        let k = p.shape_vec()[1];
+        Some(p.data().chunks(k).map(|row| row.iter().enumerate().map(|(c, &v)| (c.to_string(), v)).collect()).collect())
+    }
+
+    /// Each fixture's document and the Rust predictions for `x`. Linear
+    /// models and scalers get exactly representable parameters instead of
+    /// being fitted.
+    fn fixture_documents(x: &Tensor<f64>) -> Vec<(&'static str, String, Predictions)> {
+        let mut minmax = MinMaxScaler::new();
+        minmax.min = tensor(&[-2.0, 0.0, 0.0], &[3]);
+        minmax.max = tensor(&[6.0, 4.0, 2.0], &[3]);
+        let mut robust = RobustScaler::new();
+        robust.center = tensor(&[0.5, 0.25, 0.5], &[3]);
+        robust.scale = tensor(&[0.5, 0.25, 1.0], &[3]);
+        let mut maxabs = MaxAbsScaler::new();
+        maxabs.max_abs = tensor(&[2.0, 4.0, 1.0], &[3]);
+        let mut linear = LinearRegression::new(true);
+        linear.weights = tensor(&[1.5, -0.75, 0.25], &[3]);
+        linear.bias = Some(2.0);
+        let scaled = maxabs.transform(&robust.transform(&minmax.transform(x).unwrap()).unwrap()).unwrap();
+        let linear_doc = PmmlExport::new()
+            .with_step(&minmax)
+            .with_step(&robust)
+            .with_step(&maxabs)
+            .with_step(&linear)
+            .with_feature_names(&["age", "income & bonus", "group"])
+            .build()
+            .unwrap();
+
+        let mut standard = StandardScaler::new();
+        standard.mean = tensor(&[1.0, 0.5, 1.0], &[3]);
+        standard.std = tensor(&[2.0, 0.5, 1.0], &[3]);
+        let mut logistic = LogisticRegression::new(0.5, 1);
+        logistic.weights = tensor(&[0.5, -1.0, 0.25], &[3]);
+        logistic.bias = Some(-0.25);
+        let standardized = standard.transform(x).unwrap();
+        let logistic_doc = PmmlExport::new().with_step(&standard).with_step(&logistic).build().unwrap();
+
+        let mut softmax = LogisticRegression::new(0.5, 1).with_multi_class(MultiClass::Multinomial);
+        softmax.coef = tensor(&[0.5, -0.25, 0.0, 1.0, 0.5, -1.5, -0.75, 0.25, 0.5], &[3, 3]);
+        softmax.intercept = tensor(&[0.25, 0.0, -0.5], &[3]);
+        softmax.classes = Some(vec![0.0, 1.0, 2.0]);
+
+        let (train, y_class, y_reg) = fixture_training_data();
+        let mut tree = DecisionTreeClassifier::new(4, 2, 1).with_categorical_features(&[2]);
+        tree.fit(&train, &y_class).unwrap();
+        let mut tree_reg = DecisionTreeRegressor::new(3, 2, 1);
+        tree_reg.fit(&train, &y_reg).unwrap();
+        let mut forest = RandomForestClassifier::new(3, 3, 0.7);
+        forest.seed = Some(3);
+        forest.fit(&train, &y_class).unwrap();
+        let mut forest_reg = RandomForestRegressor::new(3, 3, 0.7);
+        forest_reg.seed = Some(5);
+        forest_reg.fit(&train, &y_reg).unwrap();
+
+        let values = |p: TensorResult<Tensor<f64>>| Predictions::Values(p.unwrap().data().to_vec());
+        vec![
+            ("linear", linear_doc, values(linear.predict(&scaled))),
+            (
+                "logistic",
+                logistic_doc,
+                Predictions::Classes(
+                    logistic.predict(&standardized).unwrap().data().to_vec(),
+                    binary_probs(&logistic.predict_proba(&standardized).unwrap()),
+                ),
+            ),
+            (
+                "softmax",
+                softmax.to_pmml().unwrap(),
+                Predictions::Classes(
+                    softmax.predict(x).unwrap().data().to_vec(),
+                    class_probs(&softmax.predict_proba(x).unwrap()),
+                ),
+            ),
+            ("tree", tree.to_pmml().unwrap(), Predictions::Classes(tree.predict(x).unwrap().data().to_vec(), None)),
+            ("tree_regression", tree_reg.to_pmml().unwrap(), values(tree_reg.predict(x))),
+            (
+                "forest",
+                forest.to_pmml().unwrap(),
+                Predictions::Classes(
+                    forest.predict(x).unwrap().data().to_vec(),
+                    class_probs(&forest.predict_proba(x).unwrap()),
+                ),
+            ),
+            ("forest_regression", forest_reg.to_pmml().unwrap(), values(forest_reg.predict(x))),
+        ]
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-12 * a.abs().max(1.0)
+    }
+
+    /// `tests/fixtures/pmml` holds the documents exported above, validated
+    /// against the PMML 4.4 schema and scored by an evaluator written from
+    /// the specification rather than from this module (`score.py`). After a
+    /// deliberate change to the output, rerun with `OXIDIZE_ML_BLESS_PMML=1`
+    /// to rewrite the documents, then rerun `score.py`.
+    #[test]
+    fn test_fixture_documents_and_scores() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pmml");
+        let read = |name: &str| std::fs::read_to_string(format!("{}/{}", dir, name)).unwrap();
+        let inputs: Vec<f64> = read("inputs.csv")
+            .lines()
+            .skip(1)
+            .flat_map(|line| line.split(',').map(|v| v.parse().unwrap_or(f64::NAN)).collect::<Vec<_>>())
+            .collect();
+        let x = Tensor::new(inputs.clone(), vec![inputs.len() / 3, 3]).unwrap();
+        let bless = std::env::var_os("OXIDIZE_ML_BLESS_PMML").is_some();
+
+        let mut scores: HashMap<(String, usize), (String, String)> = HashMap::new();
+        for line in read("scores.csv").lines().skip(1) {
+            let f: Vec<&str> = line.split(',').collect();
+            scores.insert((f[0].to_string(), f[1].parse().unwrap()), (f[2].to_string(), f[3].to_string()));
+        }
+
+        for (name, doc, predictions) in fixture_documents(&x) {
+            let path = format!("{}.pmml", name);
+            if bless {
+                std::fs::write(format!("{}/{}", dir, path), &doc).unwrap();
+                continue;
+            }
+            assert_eq!(doc, read(&path), "{} changed; see this test's docs", path);
+            for row in 0..x.shape_vec()[0] {
+                let (prediction, probabilities) = &scores[&(name.to_string(), row)];
+                let context = format!("{} row {}", name, row);
+                match &predictions {
+                    Predictions::Values(v) if prediction.is_empty() => assert!(v[row].is_nan(), "{}", context),
+                    Predictions::Values(v) => assert!(close(v[row], prediction.parse().unwrap()), "{}: {}", context, v[row]),
+                    Predictions::Classes(_, probs) if prediction.is_empty() => {
+                        // Rust scores a missing input as NaN probabilities.
+                        let probs = probs.as_ref().expect("only regression tables leave a prediction missing");
+                        assert!(probs[row].iter().all(|(_, p)| p.is_nan()), "{}", context);
+                    }
+                    Predictions::Classes(labels, probs) => {
+                        assert_eq!(&label(labels[row]), prediction, "{}", context);
+                        let expected: Vec<(&str, f64)> = probabilities
+                            .split(';')
+                            .filter(|p| !p.is_empty())
+                            .map(|p| {
+                                let (l, v) = p.split_once(':').unwrap();
+                                (l, v.parse().unwrap())
+                            })
+                            .collect();
+                        let probs = probs.as_ref().map_or(&[][..], |p| &p[row][..]);
+                        assert_eq!(expected.len(), probs.len(), "{}", context);
+                        for ((want_label, want), (got_label, got)) in expected.iter().zip(probs) {
+                            assert_eq!(want_label, got_label, "{}", context);
+                            assert!(close(*got, *want), "{}: p({}) = {} vs {}", context, got_label, got, want);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! `.safetensors` state dicts.
+//!
+//! The format is an 8-byte little-endian header length, a JSON header
+//! mapping each tensor name to `{dtype, shape, data_offsets}`, then the raw
+//! row-major little-endian buffers. Float (`F64`, `F32`, `F16`, `BF16`),
+//! integer and bool tensors are read and converted to the element type;
+//! tensors are written as `F64` or `F32` to match it.
+
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_nn::StateDict;
+#[cfg(feature = "fs")]
+use oxidize_ml_nn::{Layer, LoadReport};
+use serde_json::{json, Map, Value};
+use std::error::Error;
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Encode a state dict, in key order.
+pub fn safetensors_bytes<T: Float>(state: &StateDict<T>) -> Result<Vec<u8>, Box<dyn Error>> {
+    let is_f32 = std::mem::size_of::<T>() == 4;
+    let mut header = Map::new();
+    let mut body = Vec::new();
+    for (name, tensor) in state {
+        let start = body.len();
+        for &v in tensor.data() {
+            if is_f32 {
+                body.extend_from_slice(&(v.to_f64() as f32).to_le_bytes());
+            } else {
+                body.extend_from_slice(&v.to_f64().to_le_bytes());
+            }
+        }
+        header.insert(
+            name.clone(),
+            json!({
+                "dtype": if is_f32 { "F32" } else { "F64" },
+                "shape": tensor.shape_vec(),
+                "data_offsets": [start, body.len()],
+            }),
+        );
+    }
+    let mut header = serde_json::to_vec(&Value::Object(header))?;
+    // The buffers start on an 8-byte boundary; the header is padded with spaces.
+    while !header.len().is_multiple_of(8) {
+        header.push(b' ');
+    }
+
+    let mut out = Vec::with_capacity(8 + header.len() + body.len());
+    out.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Decode a safetensors buffer. The `__metadata__` entry is ignored.
+pub fn parse_safetensors<T: Float>(bytes: &[u8]) -> Result<StateDict<T>, Box<dyn Error>> {
+    let header_len = bytes.get(..8).ok_or("safetensors: file shorter than 8 bytes")?;
+    let header_len = u64::from_le_bytes(header_len.try_into()?) as usize;
+    let header = bytes.get(8..8 + header_len).ok_or("safetensors: truncated header")?;
+    let body = &bytes[8 + header_len..];
+    let header: Map<String, Value> = serde_json::from_slice(header)?;
+
+    let mut state = StateDict::new();
+    for (name, info) in header {
+        if name == "__metadata__" {
+            continue;
+        }
+        let malformed = || format!("safetensors: malformed header entry '{}'", name);
+        let usizes = |key: &str| -> Option<Vec<usize>> {
+            info.get(key)?.as_array()?.iter().map(|d| d.as_u64().map(|d| d as usize)).collect()
+        };
+        let dtype = info.get("dtype").and_then(Value::as_str).ok_or_else(malformed)?;
+        let (shape, offsets) = (usizes("shape").ok_or_else(malformed)?, usizes("data_offsets").ok_or_else(malformed)?);
+        let raw = match offsets[..] {
+            [start, end] if start <= end => body.get(start..end),
+            _ => None,
+        }
+        .ok_or_else(|| format!("safetensors: '{}' has out-of-range data offsets", name))?;
+
+        let width = dtype_width(dtype).ok_or_else(|| format!("safetensors: unsupported dtype {}", dtype))?;
+        let numel: usize = shape.iter().product();
+        if raw.len() != numel * width {
+            return Err(format!("safetensors: '{}' holds {} bytes, expected {}", name, raw.len(), numel * width).into());
+        }
+        let data = raw.chunks_exact(width).map(|c| T::from_f64(decode(dtype, c))).collect();
+        state.insert(name, Tensor::new(data, shape)?);
+    }
+    Ok(state)
+}
+
+/// Write a state dict to a `.safetensors` file.
+#[cfg(feature = "fs")]
+pub fn save_safetensors<T: Float>(path: &str, state: &StateDict<T>) -> Result<(), Box<dyn Error>> {
+    fs::write(Path::new(path), safetensors_bytes(state)?)?;
+    Ok(())
+}
+
+/// Read every tensor of a `.safetensors` file.
+#[cfg(feature = "fs")]
+pub fn load_safetensors<T: Float>(path: &str) -> Result<StateDict<T>, Box<dyn Error>> {
+    parse_safetensors(&fs::read(Path::new(path))?)
+}
+
+/// Load a `.safetensors` file into `model` by name; see
+/// `Layer::load_state_dict` for `strict`.
+#[cfg(feature = "fs")]
+pub fn load_safetensors_into<T: Float, L: Layer<T> + ?Sized>(
+    model: &mut L,
+    path: &str,
+    strict: bool,
+) -> Result<LoadReport, Box<dyn Error>> {
+    Ok(model.load_state_dict(&load_safetensors(path)?, strict)?)
+}
+
+fn dtype_width(dtype: &str) -> Option<usize> {
+    Some(match dtype {
+        "F64" | "I64" | "U64" => 8,
+        "F32" | "I32" | "U32" => 4,
+        "F16" | "BF16" | "I16" | "U16" => 2,
+        "I8" | "U8" | "BOOL" => 1,
+        _ => return None,
+    })
+}
+
+fn decode(dtype: &str, raw: &[u8]) -> f64 {
+    let bytes = |n: usize| -> [u8; 8] {
+        let mut b = [0; 8];
+        b[..n].copy_from_slice(raw);
+        b
+    };
+    match dtype {
+        "F64" => f64::from_le_bytes(bytes(8)),
+        "F32" => f32::from_le_bytes(raw.try_into().unwrap()) as f64,
+        // bfloat16 is the top half of an f32.
+        "BF16" => f32::from_bits((u16::from_le_bytes(raw.try_into().unwrap()) as u32) << 16) as f64,
+        "F16" => f16_to_f64(u16::from_le_bytes(raw.try_into().unwrap())),
+        "I64" => i64::from_le_bytes(bytes(8)) as f64,
+        "I32" => i32::from_le_bytes(raw.try_into().unwrap()) as f64,
+        "I16" => i16::from_le_bytes(raw.try_into().unwrap()) as f64,
+        "I8" => raw[0] as i8 as f64,
+        _ => u64::from_le_bytes(bytes(raw.len())) as f64,
+    }
+}
+
+/// IEEE half precision: 1 sign, 5 exponent and 10 mantissa bits.
+pub(crate) fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits >> 15 == 1 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    sign * match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        e => (1.0 + mantissa / 1024.0) * 2f64.powi(e - 15),
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+    use oxidize_ml_nn::models::lenet5;
+    use oxidize_ml_nn::Sequential;
+
+    #[test]
+    fn test_safetensors_round_trip_into_model() {
+        let path = std::env::temp_dir()
+            .join(format!("oxidize_ml_{}_lenet.safetensors", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let model: Sequential = lenet5();
+        save_safetensors(&path, &model.state_dict()).unwrap();
+        let mut copy: Sequential = lenet5();
+        let report = load_safetensors_into(&mut copy, &path, true).unwrap();
+        assert!(report.is_exact());
+        assert_eq!(copy.state_dict(), model.state_dict());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_half_precision_and_metadata() {
+        // F16 [1.0, -2.0] and BF16 [0.5], as written by the Python library.
+        let header = br#"{"__metadata__":{"format":"pt"},"a":{"dtype":"F16","shape":[2],"data_offsets":[0,4]},"b":{"dtype":"BF16","shape":[1,1],"data_offsets":[4,6]}}"#;
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header);
+        bytes.extend_from_slice(&[0x00, 0x3c, 0x00, 0xc0, 0x00, 0x3f]);
+        let state: StateDict<f32> = parse_safetensors(&bytes).unwrap();
+        assert_eq!(state["a"].data(), &[1.0, -2.0]);
+        assert_eq!(state["b"].shape_vec(), vec![1, 1]);
+        assert_eq!(state["b"].data(), &[0.5]);
+        assert!(parse_safetensors::<f64>(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
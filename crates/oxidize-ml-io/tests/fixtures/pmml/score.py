@@ -0,0 +1,259 @@
+#!/usr/bin/env python3
+"""Validate the exported documents and score them for `pmml.rs`'s tests.
+
+Each `*.pmml` here is checked against `pmml-4-4-subset.xsd` with
+`xmllint --schema`, then scored on every row of `inputs.csv` (an empty
+cell is a missing value) by the evaluator below, which follows the PMML
+4.4 specification for the elements it supports rather than the Rust
+exporter. The results go to `scores.csv`: one line per document and row
+with the predicted value (empty when the model returns a missing
+result) and, for classifiers with probability outputs, `label:p` pairs.
+
+Run from this directory: `python3 score.py`.
+"""
+
+import csv
+import glob
+import math
+import subprocess
+import xml.etree.ElementTree as ET
+
+NS = "{http://www.dmg.org/PMML-4_4}"
+MISSING = None
+
+
+class ScoreError(Exception):
+    pass
+
+
+def children(element, name):
+    return element.findall(NS + name)
+
+
+def tag(element):
+    return element.tag[len(NS):]
+
+
+# -- Expressions (spec: Transformations, Built-in Functions) ---------------
+
+ARITHMETIC = {
+    "+": lambda a, b: a + b,
+    "-": lambda a, b: a - b,
+    "*": lambda a, b: a * b,
+    "/": lambda a, b: a / b,
+}
+
+
+def expression(e, fields):
+    kind = tag(e)
+    if kind == "FieldRef":
+        v = fields[e.get("field")]
+        if v is MISSING and e.get("mapMissingTo") is not None:
+            return float(e.get("mapMissingTo"))
+        return v
+    if kind == "Constant":
+        return MISSING if e.get("missing") == "true" else float(e.text)
+    if kind == "Apply":
+        args = [expression(a, fields) for a in e if tag(a) != "Extension"]
+        function = e.get("function")
+        if function not in ARITHMETIC or len(args) != 2:
+            raise ScoreError("unsupported Apply %s/%d" % (function, len(args)))
+        # Arithmetic on a missing argument is missing.
+        if MISSING in args:
+            return float(e.get("mapMissingTo")) if e.get("mapMissingTo") is not None else MISSING
+        return ARITHMETIC[function](*args)
+    raise ScoreError("unsupported expression " + kind)
+
+
+# -- Predicates (spec: TreeModel, Predicates) -------------------------------
+
+
+def parse_array(array):
+    kind = array.get("type")
+    tokens = array.text.split() if array.text else []
+    if kind == "string":
+        return tokens
+    return [float(t) for t in tokens]
+
+
+def predicate(p, fields):
+    """True, False or MISSING (the spec's UNKNOWN)."""
+    kind = tag(p)
+    if kind == "True":
+        return True
+    if kind == "False":
+        return False
+    v = fields[p.get("field")]
+    if kind == "SimplePredicate":
+        op = p.get("operator")
+        if op == "isMissing":
+            return v is MISSING
+        if op == "isNotMissing":
+            return v is not MISSING
+        if v is MISSING:
+            return MISSING
+        t = float(p.get("value"))
+        return {
+            "equal": v == t,
+            "notEqual": v != t,
+            "lessThan": v < t,
+            "lessOrEqual": v <= t,
+            "greaterThan": v > t,
+            "greaterOrEqual": v >= t,
+        }[op]
+    if kind == "SimpleSetPredicate":
+        if v is MISSING:
+            return MISSING
+        inside = v in parse_array(p.find(NS + "Array"))
+        return inside if p.get("booleanOperator") == "isIn" else not inside
+    raise ScoreError("unsupported predicate " + kind)
+
+
+def first_predicate(element):
+    for child in element:
+        if tag(child) in ("True", "False", "SimplePredicate", "SimpleSetPredicate"):
+            return child
+    raise ScoreError("no predicate in " + tag(element))
+
+
+# -- Models -----------------------------------------------------------------
+
+
+def regression_model(model, fields):
+    """(prediction, {category: probability})."""
+    tables = []
+    for table in children(model, "RegressionTable"):
+        y = float(table.get("intercept"))
+        for predictor in children(table, "NumericPredictor"):
+            x = fields[predictor.get("name")]
+            if x is MISSING:
+                # Without mapMissingTo, a missing input makes the result missing.
+                return MISSING, {}
+            y += float(predictor.get("coefficient")) * x ** int(predictor.get("exponent", "1"))
+        tables.append((table.get("targetCategory"), y))
+
+    method = model.get("normalizationMethod", "none")
+    if model.get("functionName") == "regression":
+        if method != "none":
+            raise ScoreError("unsupported regression normalization " + method)
+        return tables[0][1], {}
+
+    ys = [y for _, y in tables]
+    if method == "softmax":
+        top = max(ys)
+        e = [math.exp(y - top) for y in ys]
+        probs = [v / sum(e) for v in e]
+    elif method == "logit":
+        # Every table but the last is a logistic; the last takes the remainder.
+        probs = [1 / (1 + math.exp(-y)) for y in ys[:-1]]
+        probs.append(1 - sum(probs))
+    else:
+        raise ScoreError("unsupported classification normalization " + method)
+    probabilities = {c: p for (c, _), p in zip(tables, probs)}
+    best = max(range(len(tables)), key=lambda i: (probs[i], -i))
+    return tables[best][0], probabilities
+
+
+def tree_model(model, fields):
+    missing_strategy = model.get("missingValueStrategy", "none")
+    no_true_child = model.get("noTrueChildStrategy", "returnNullPrediction")
+    root = model.find(NS + "Node")
+    if predicate(first_predicate(root), fields) is not True:
+        return MISSING, {}
+    node = root
+    while True:
+        nodes = children(node, "Node")
+        if not nodes:
+            break
+        chosen = None
+        for child in nodes:
+            result = predicate(first_predicate(child), fields)
+            if result is MISSING:
+                if missing_strategy != "defaultChild":
+                    raise ScoreError("unsupported missingValueStrategy " + missing_strategy)
+                chosen = next(c for c in nodes if c.get("id") == node.get("defaultChild"))
+                break
+            if result:
+                chosen = child
+                break
+        if chosen is None:
+            if no_true_child == "returnLastPrediction" and node.get("score") is not None:
+                break
+            return MISSING, {}
+        node = chosen
+    score = node.get("score")
+    if model.get("functionName") == "regression":
+        return float(score), {}
+    return score, {}
+
+
+def mining_model(model, fields):
+    segmentation = model.find(NS + "Segmentation")
+    method = segmentation.get("multipleModelMethod")
+    results = []
+    for segment in children(segmentation, "Segment"):
+        if predicate(first_predicate(segment), fields) is not True:
+            continue
+        inner = next(c for c in segment if tag(c) in MODELS)
+        results.append(MODELS[tag(inner)](inner, fields)[0])
+    if method == "average":
+        if MISSING in results:
+            return MISSING, {}
+        return sum(results) / len(results), {}
+    if method == "majorityVote":
+        votes = {}
+        for r in results:
+            votes[r] = votes.get(r, 0) + 1
+        top = max(votes.values())
+        winners = [c for c, v in votes.items() if v == top]
+        if len(winners) > 1:
+            raise ScoreError("tied vote; pick fixture rows without ties")
+        return winners[0], {c: v / len(results) for c, v in votes.items()}
+    raise ScoreError("unsupported multipleModelMethod " + method)
+
+
+MODELS = {"RegressionModel": regression_model, "TreeModel": tree_model, "MiningModel": mining_model}
+
+
+def score(doc, row):
+    dictionary = doc.find(NS + "DataDictionary")
+    model = next(c for c in doc if tag(c) in MODELS)
+    schema = model.find(NS + "MiningSchema")
+    targets = {f.get("name") for f in children(schema, "MiningField") if f.get("usageType") == "target"}
+    inputs = [f.get("name") for f in children(dictionary, "DataField") if f.get("name") not in targets]
+    fields = dict(zip(inputs, row))
+    transforms = doc.find(NS + "TransformationDictionary")
+    for derived in children(transforms, "DerivedField") if transforms is not None else []:
+        fields[derived.get("name")] = expression(next(c for c in derived if tag(c) != "Extension"), fields)
+
+    prediction, probabilities = MODELS[tag(model)](model, fields)
+    output = model.find(NS + "Output")
+    reported = []
+    for field in children(output, "OutputField") if output is not None else []:
+        if field.get("feature") == "probability" and prediction is not MISSING:
+            reported.append((field.get("value"), probabilities.get(field.get("value"), 0.0)))
+    return prediction, reported
+
+
+def validate(path):
+    subprocess.run(["xmllint", "--noout", "--schema", "pmml-4-4-subset.xsd", path], check=True)
+
+
+def main():
+    with open("inputs.csv") as f:
+        rows = [[float(v) if v else MISSING for v in line] for line in list(csv.reader(f))[1:]]
+    lines = ["document,row,prediction,probabilities"]
+    for path in sorted(glob.glob("*.pmml")):
+        validate(path)
+        doc = ET.parse(path).getroot()
+        for i, row in enumerate(rows):
+            prediction, probabilities = score(doc, row)
+            text = "" if prediction is MISSING else prediction if isinstance(prediction, str) else repr(prediction)
+            probs = ";".join("%s:%r" % (c, p) for c, p in probabilities)
+            lines.append("%s,%d,%s,%s" % (path[: -len(".pmml")], i, text, probs))
+    with open("scores.csv", "w") as f:
+        f.write("\n".join(lines) + "\n")
+
+
+if __name__ == "__main__":
+    main()
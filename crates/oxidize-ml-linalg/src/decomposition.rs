@@ -66,15 +66,11 @@ where
         if max_row != k {
             pivot.swap(k, max_row);
             for j in 0..n {
-                let tmp = u_data[k * n + j];
-                u_data[k * n + j] = u_data[max_row * n + j];
-                u_data[max_row * n + j] = tmp;
+                u_data.swap(k * n + j, max_row * n + j);
             }
             // Swap L rows for already computed columns
             for j in 0..k {
-                let tmp = l_data[k * n + j];
-                l_data[k * n + j] = l_data[max_row * n + j];
-                l_data[max_row * n + j] = tmp;
+                l_data.swap(k * n + j, max_row * n + j);
             }
         }
 
@@ -125,7 +121,7 @@ where
         // Compute Householder vector
         let mut norm_x = T::ZERO;
         for &v in &x {
-            norm_x = norm_x + v * v;
+            norm_x += v * v;
         }
         norm_x = norm_x.sqrt();
 
@@ -134,29 +130,29 @@ where
         }
 
         let sign = if x[0] >= T::ZERO { T::ONE } else { T::NEG_ONE };
-        x[0] = x[0] + sign * norm_x;
+        x[0] += sign * norm_x;
 
         // Normalize
         let mut norm_v = T::ZERO;
         for &v in &x {
-            norm_v = norm_v + v * v;
+            norm_v += v * v;
         }
         norm_v = norm_v.sqrt();
         if norm_v < T::EPSILON {
             continue;
         }
         for v in x.iter_mut() {
-            *v = *v / norm_v;
+            *v /= norm_v;
         }
 
         // Apply H = I - 2*v*vᵀ to R (columns j..n)
         for col in j..n {
             let mut dot = T::ZERO;
             for i in j..m {
-                dot = dot + x[i - j] * r_data[i * n + col];
+                dot += x[i - j] * r_data[i * n + col];
             }
             for i in j..m {
-                r_data[i * n + col] = r_data[i * n + col] - T::TWO * x[i - j] * dot;
+                r_data[i * n + col] -= T::TWO * x[i - j] * dot;
             }
         }
 
@@ -164,10 +160,10 @@ where
         for row in 0..m {
             let mut dot = T::ZERO;
             for i in j..m {
-                dot = dot + q_data[row * m + i] * x[i - j];
+                dot += q_data[row * m + i] * x[i - j];
             }
             for i in j..m {
-                q_data[row * m + i] = q_data[row * m + i] - T::TWO * dot * x[i - j];
+                q_data[row * m + i] -= T::TWO * dot * x[i - j];
             }
         }
     }
@@ -211,7 +207,7 @@ where
         for j in 0..=i {
             let mut sum = T::ZERO;
             for k in 0..j {
-                sum = sum + l_data[i * n + k] * l_data[j * n + k];
+                sum += l_data[i * n + k] * l_data[j * n + k];
             }
 
             if i == j {
@@ -242,7 +238,7 @@ where
     let n = a.shape().dim(0)?;
     let mut d = T::ONE;
     for i in 0..n {
-        d = d * decomp.u.get(&[i, i])?;
+        d *= decomp.u.get(&[i, i])?;
     }
 
     // Count transpositions in the permutation
@@ -275,7 +271,7 @@ where
     Standard: Distribution<T>,
 {
     let n = a.shape().dim(0)?;
-    let identity = Tensor::<T>::eye(n);
+    let _identity = Tensor::<T>::eye(n);
     let mut result_data = vec![T::ZERO; n * n];
 
     let decomp = lu(a)?;
@@ -296,7 +292,7 @@ where
         for i in 0..n {
             let mut sum = T::ZERO;
             for j in 0..i {
-                sum = sum + decomp.l.get(&[i, j])? * y[j];
+                sum += decomp.l.get(&[i, j])? * y[j];
             }
             y[i] = pb[i] - sum;
         }
@@ -306,7 +302,7 @@ where
         for i in (0..n).rev() {
             let mut sum = T::ZERO;
             for j in (i + 1)..n {
-                sum = sum + decomp.u.get(&[i, j])? * x[j];
+                sum += decomp.u.get(&[i, j])? * x[j];
             }
             let diag = decomp.u.get(&[i, i])?;
             if diag.abs() < T::EPSILON {
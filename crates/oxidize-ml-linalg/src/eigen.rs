@@ -0,0 +1,463 @@
+use oxidize_ml_core::{Complex, ComplexTensor, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+
+/// Eigendecomposition of a general real matrix: `A v_j = λ_j v_j`.
+///
+/// Complex eigenvalues come in conjugate pairs. They are sorted by
+/// decreasing real part, with the positive-imaginary member of a pair first.
+pub struct EigDecomposition<T: Float> {
+    /// Eigenvalues, `[n]`.
+    pub values: ComplexTensor<T>,
+    /// Eigenvectors as columns, `[n, n]`, unit-norm with their largest
+    /// entry real and positive.
+    pub vectors: ComplexTensor<T>,
+}
+
+fn square_data<T: Float>(a: &Tensor<T>, name: &str) -> TensorResult<(Vec<f64>, usize)> {
+    if a.ndim() != 2 || a.shape().dim(0)? != a.shape().dim(1)? {
+        return Err(TensorError::InvalidOperation(format!("{} requires a square 2D tensor", name)));
+    }
+    let n = a.shape().dim(0)?;
+    Ok((a.data().iter().map(|v| v.to_f64()).collect(), n))
+}
+
+fn to_tensor<T: Float>(v: Vec<f64>, shape: Vec<usize>) -> TensorResult<Tensor<T>> {
+    Tensor::new(v.into_iter().map(T::from_f64).collect(), shape)
+}
+
+/// Eigendecomposition of a symmetric matrix by cyclic Jacobi rotations.
+/// Returns `(eigenvalues [n], eigenvectors [n, n])`, eigenvalues descending
+/// and eigenvectors as orthonormal columns.
+pub fn eig_sym<T: Float>(a: &Tensor<T>) -> TensorResult<(Tensor<T>, Tensor<T>)> {
+    let (mut data, n) = square_data(a, "eig_sym")?;
+    let scale = data.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+    for i in 0..n {
+        for j in 0..i {
+            if (data[i * n + j] - data[j * n + i]).abs() > 1e-10 * scale {
+                return Err(TensorError::InvalidOperation("eig_sym requires a symmetric matrix".into()));
+            }
+        }
+    }
+    let (values, vectors) = symmetric_eigen(&mut data, n);
+    Ok((to_tensor(values, vec![n])?, to_tensor(vectors, vec![n, n])?))
+}
+
+/// Eigenvalues and eigenvectors of the symmetric `n × n` matrix `a`
+/// (row-major, destroyed) by cyclic Jacobi sweeps run to convergence.
+/// Returns them by decreasing eigenvalue, eigenvectors as columns.
+pub(crate) fn symmetric_eigen(a: &mut [f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut vecs = vec![0.0; n * n];
+    for i in 0..n {
+        vecs[i * n + i] = 1.0;
+    }
+    let scale: f64 = a.iter().map(|v| v * v).sum::<f64>().sqrt();
+    for _sweep in 0..100 {
+        let off: f64 = (0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j))).map(|(i, j)| a[i * n + j].powi(2)).sum();
+        if off.sqrt() <= 1e-15 * scale.max(f64::MIN_POSITIVE) {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+                if apq.abs() < f64::MIN_POSITIVE {
+                    continue;
+                }
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[k * n + p], a[k * n + q]);
+                    a[k * n + p] = c * akp - s * akq;
+                    a[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[p * n + k], a[q * n + k]);
+                    a[p * n + k] = c * apk - s * aqk;
+                    a[q * n + k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (vecs[k * n + p], vecs[k * n + q]);
+                    vecs[k * n + p] = c * vkp - s * vkq;
+                    vecs[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[j * n + j].total_cmp(&a[i * n + i]));
+    let values = order.iter().map(|&i| a[i * n + i]).collect();
+    let mut sorted = vec![0.0; n * n];
+    for (col, &i) in order.iter().enumerate() {
+        for k in 0..n {
+            sorted[k * n + col] = vecs[k * n + i];
+        }
+    }
+    (values, sorted)
+}
+
+/// Eigenvalues and eigenvectors of a general square matrix. The matrix is
+/// reduced to upper Hessenberg form by Householder reflections, its
+/// eigenvalues found by Francis double-shift QR iteration, and each
+/// eigenvector recovered by inverse iteration on `A - λI`. A repeated
+/// eigenvalue gets independent eigenvectors as far as its eigenspace allows;
+/// those of a defective matrix share one.
+pub fn eig<T: Float>(a: &Tensor<T>) -> TensorResult<EigDecomposition<T>> {
+    let (data, n) = square_data(a, "eig")?;
+    let mut h = data.clone();
+    hessenberg(&mut h, n);
+    let (re, im) = hessenberg_qr(&mut h, n)?;
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| re[j].total_cmp(&re[i]).then(im[j].total_cmp(&im[i])));
+    let norm = data.iter().map(|v| v.abs()).fold(0.0, f64::max).max(f64::MIN_POSITIVE);
+
+    let to_t = |z: Complex<f64>| Complex::new(T::from_f64(z.re), T::from_f64(z.im));
+    let mut values = Vec::with_capacity(n);
+    let mut vectors = vec![Complex::default(); n * n];
+    let mut found: Vec<(Complex<f64>, Vec<Complex<f64>>)> = Vec::with_capacity(n);
+    for (col, &i) in order.iter().enumerate() {
+        let lambda = Complex::new(re[i], im[i]);
+        values.push(to_t(lambda));
+        let previous: Vec<&Vec<Complex<f64>>> = found
+            .iter()
+            .filter(|(mu, _)| (*mu - lambda).abs() <= 1e-8 * norm)
+            .map(|(_, v)| v)
+            .collect();
+        let v = inverse_iteration(&data, n, lambda, norm, &previous);
+        for (row, &z) in v.iter().enumerate() {
+            vectors[row * n + col] = to_t(z);
+        }
+        found.push((lambda, v));
+    }
+    Ok(EigDecomposition {
+        values: ComplexTensor::new(values, vec![n])?,
+        vectors: ComplexTensor::new(vectors, vec![n, n])?,
+    })
+}
+
+/// Reduce the row-major `n × n` matrix `a` in place to upper Hessenberg
+/// form `QᵀAQ` with Householder reflections; entries below the first
+/// subdiagonal are zeroed.
+fn hessenberg(a: &mut [f64], n: usize) {
+    for k in 0..n.saturating_sub(2) {
+        let mut v: Vec<f64> = (k + 1..n).map(|i| a[i * n + k]).collect();
+        let alpha = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if alpha == 0.0 {
+            continue;
+        }
+        v[0] += if v[0] >= 0.0 { alpha } else { -alpha };
+        let v_norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if v_norm == 0.0 {
+            continue;
+        }
+        v.iter_mut().for_each(|x| *x /= v_norm);
+
+        // A ← H A: rows k+1.. ; then A ← A H: columns k+1..
+        for j in 0..n {
+            let dot: f64 = v.iter().enumerate().map(|(t, vt)| vt * a[(k + 1 + t) * n + j]).sum();
+            for (t, vt) in v.iter().enumerate() {
+                a[(k + 1 + t) * n + j] -= 2.0 * vt * dot;
+            }
+        }
+        for i in 0..n {
+            let dot: f64 = v.iter().enumerate().map(|(t, vt)| vt * a[i * n + k + 1 + t]).sum();
+            for (t, vt) in v.iter().enumerate() {
+                a[i * n + k + 1 + t] -= 2.0 * vt * dot;
+            }
+        }
+        for i in k + 2..n {
+            a[i * n + k] = 0.0;
+        }
+    }
+}
+
+/// Eigenvalues `(re, im)` of the upper Hessenberg matrix `a` (destroyed)
+/// by Francis double-shift QR with deflation, as in EISPACK's `hqr`.
+fn hessenberg_qr(a: &mut [f64], n: usize) -> TensorResult<(Vec<f64>, Vec<f64>)> {
+    let idx = |i: isize, j: isize| i as usize * n + j as usize;
+    let sign = |a: f64, b: f64| if b >= 0.0 { a.abs() } else { -a.abs() };
+    let mut wr = vec![0.0; n];
+    let mut wi = vec![0.0; n];
+    let mut anorm = 0.0;
+    for i in 0..n {
+        for j in i.saturating_sub(1)..n {
+            anorm += a[i * n + j].abs();
+        }
+    }
+
+    let mut nn = n as isize - 1;
+    let mut t = 0.0;
+    while nn >= 0 {
+        let mut its = 0;
+        loop {
+            // Look for a negligible subdiagonal element to split at.
+            let mut l = nn;
+            while l >= 1 {
+                let mut s = a[idx(l - 1, l - 1)].abs() + a[idx(l, l)].abs();
+                if s == 0.0 {
+                    s = anorm;
+                }
+                if a[idx(l, l - 1)].abs() + s == s {
+                    a[idx(l, l - 1)] = 0.0;
+                    break;
+                }
+                l -= 1;
+            }
+            let mut x = a[idx(nn, nn)];
+            if l == nn {
+                // One root found.
+                wr[nn as usize] = x + t;
+                wi[nn as usize] = 0.0;
+                nn -= 1;
+                break;
+            }
+            let mut y = a[idx(nn - 1, nn - 1)];
+            let mut w = a[idx(nn, nn - 1)] * a[idx(nn - 1, nn)];
+            if l == nn - 1 {
+                // Two roots from the trailing 2 × 2 block.
+                let p = 0.5 * (y - x);
+                let q = p * p + w;
+                let z = q.abs().sqrt();
+                x += t;
+                let (i0, i1) = ((nn - 1) as usize, nn as usize);
+                if q >= 0.0 {
+                    let z = p + sign(z, p);
+                    wr[i0] = x + z;
+                    wr[i1] = if z != 0.0 { x - w / z } else { x + z };
+                    wi[i0] = 0.0;
+                    wi[i1] = 0.0;
+                } else {
+                    wr[i0] = x + p;
+                    wr[i1] = x + p;
+                    wi[i0] = z;
+                    wi[i1] = -z;
+                }
+                nn -= 2;
+                break;
+            }
+            if its == 60 {
+                return Err(TensorError::InvalidOperation("eig: QR iteration did not converge".into()));
+            }
+            if its == 10 || its == 20 {
+                // Exceptional shift to break cycles.
+                t += x;
+                for i in 0..=nn {
+                    a[idx(i, i)] -= x;
+                }
+                let s = a[idx(nn, nn - 1)].abs() + a[idx(nn - 1, nn - 2)].abs();
+                x = 0.75 * s;
+                y = x;
+                w = -0.4375 * s * s;
+            }
+            its += 1;
+
+            // Find where two consecutive small subdiagonals let the bulge start.
+            let mut m = nn - 2;
+            let (mut p, mut q, mut r);
+            loop {
+                let z = a[idx(m, m)];
+                let rr = x - z;
+                let s = y - z;
+                p = (rr * s - w) / a[idx(m + 1, m)] + a[idx(m, m + 1)];
+                q = a[idx(m + 1, m + 1)] - z - rr - s;
+                r = a[idx(m + 2, m + 1)];
+                let s = p.abs() + q.abs() + r.abs();
+                p /= s;
+                q /= s;
+                r /= s;
+                if m == l {
+                    break;
+                }
+                let u = a[idx(m, m - 1)].abs() * (q.abs() + r.abs());
+                let v = p.abs() * (a[idx(m - 1, m - 1)].abs() + z.abs() + a[idx(m + 1, m + 1)].abs());
+                if u + v == v {
+                    break;
+                }
+                m -= 1;
+            }
+            for i in m + 2..=nn {
+                a[idx(i, i - 2)] = 0.0;
+                if i != m + 2 {
+                    a[idx(i, i - 3)] = 0.0;
+                }
+            }
+
+            // Chase the bulge down the subdiagonal.
+            let mut k = m;
+            while k < nn {
+                let mut xk = 0.0;
+                if k != m {
+                    p = a[idx(k, k - 1)];
+                    q = a[idx(k + 1, k - 1)];
+                    r = if k != nn - 1 { a[idx(k + 2, k - 1)] } else { 0.0 };
+                    xk = p.abs() + q.abs() + r.abs();
+                    if xk != 0.0 {
+                        p /= xk;
+                        q /= xk;
+                        r /= xk;
+                    }
+                }
+                let s = sign((p * p + q * q + r * r).sqrt(), p);
+                if s != 0.0 {
+                    if k == m {
+                        if l != m {
+                            a[idx(k, k - 1)] = -a[idx(k, k - 1)];
+                        }
+                    } else {
+                        a[idx(k, k - 1)] = -s * xk;
+                    }
+                    p += s;
+                    let (xs, ys, zs) = (p / s, q / s, r / s);
+                    q /= p;
+                    r /= p;
+                    for j in k..=nn {
+                        let mut pj = a[idx(k, j)] + q * a[idx(k + 1, j)];
+                        if k != nn - 1 {
+                            pj += r * a[idx(k + 2, j)];
+                            a[idx(k + 2, j)] -= pj * zs;
+                        }
+                        a[idx(k + 1, j)] -= pj * ys;
+                        a[idx(k, j)] -= pj * xs;
+                    }
+                    let mmin = if nn < k + 3 { nn } else { k + 3 };
+                    for i in l..=mmin {
+                        let mut pi = xs * a[idx(i, k)] + ys * a[idx(i, k + 1)];
+                        if k != nn - 1 {
+                            pi += zs * a[idx(i, k + 2)];
+                            a[idx(i, k + 2)] -= pi * r;
+                        }
+                        a[idx(i, k + 1)] -= pi * q;
+                        a[idx(i, k)] -= pi;
+                    }
+                }
+                k += 1;
+            }
+        }
+    }
+    Ok((wr, wi))
+}
+
+/// Eigenvector for `lambda` by inverse iteration: LU-factor `A - λI` with
+/// partial pivoting, replacing vanishing pivots by `ε‖A‖`, then repeatedly
+/// solve and normalize. Vectors already found for the same eigenvalue are
+/// projected out unless that leaves nothing (a defective eigenvalue).
+fn inverse_iteration(a: &[f64], n: usize, lambda: Complex<f64>, norm: f64, previous: &[&Vec<Complex<f64>>]) -> Vec<Complex<f64>> {
+    let tiny = f64::EPSILON * norm;
+    let mut lu: Vec<Complex<f64>> = a.iter().map(|&v| Complex::new(v, 0.0)).collect();
+    for i in 0..n {
+        lu[i * n + i] = lu[i * n + i] - lambda;
+    }
+    let mut perm: Vec<usize> = (0..n).collect();
+    for k in 0..n {
+        let pivot = (k..n).fold(k, |p, i| if lu[i * n + k].abs() > lu[p * n + k].abs() { i } else { p });
+        if pivot != k {
+            for j in 0..n {
+                lu.swap(k * n + j, pivot * n + j);
+            }
+            perm.swap(k, pivot);
+        }
+        if lu[k * n + k].abs() < tiny {
+            lu[k * n + k] = Complex::new(tiny, 0.0);
+        }
+        for i in k + 1..n {
+            let factor = lu[i * n + k] / lu[k * n + k];
+            lu[i * n + k] = factor;
+            for j in k + 1..n {
+                lu[i * n + j] = lu[i * n + j] - factor * lu[k * n + j];
+            }
+        }
+    }
+
+    // Start from a vector unlikely to be orthogonal to the eigenvector,
+    // varied per repeat so a multiple eigenvalue explores its eigenspace.
+    let freq = 1.3 + previous.len() as f64;
+    let mut v: Vec<Complex<f64>> = (0..n).map(|i| Complex::new(1.0 + 0.5 * (1.0 + i as f64 * freq).sin(), 0.0)).collect();
+    for _ in 0..3 {
+        let mut z: Vec<Complex<f64>> = perm.iter().map(|&p| v[p]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                z[i] = z[i] - lu[i * n + j] * z[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                z[i] = z[i] - lu[i * n + j] * z[j];
+            }
+            z[i] = z[i] / lu[i * n + i];
+        }
+        let full = z.iter().map(|c| c.abs().powi(2)).sum::<f64>().sqrt();
+        let mut projected = z.clone();
+        for p in previous {
+            let dot = p.iter().zip(&projected).fold(Complex::new(0.0, 0.0), |acc, (pi, zi)| acc + pi.conj() * *zi);
+            for (zi, pi) in projected.iter_mut().zip(p.iter()) {
+                *zi = *zi - dot * *pi;
+            }
+        }
+        let len = projected.iter().map(|c| c.abs().powi(2)).sum::<f64>().sqrt();
+        let (z, len) = if len > 1e-8 * full { (projected, len) } else { (z, full) };
+        v = z.into_iter().map(|c| c.scale(1.0 / len)).collect();
+    }
+
+    // Fix the phase: make the largest entry real and positive.
+    let big = (0..n).fold(0, |b, i| if v[i].abs() > v[b].abs() { i } else { b });
+    let phase = v[big].conj().scale(1.0 / v[big].abs());
+    v.into_iter().map(|c| c * phase).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eig_sym() {
+        let a: Tensor<f64> = Tensor::new(vec![4.0, 1.0, 2.0, 1.0, 3.0, 0.5, 2.0, 0.5, 5.0], vec![3, 3]).unwrap();
+        let (values, vectors) = eig_sym(&a).unwrap();
+        let (l, v) = (values.data(), vectors.data());
+        assert!(l.windows(2).all(|w| w[0] >= w[1]));
+        for j in 0..3 {
+            for i in 0..3 {
+                let av: f64 = (0..3).map(|k| a.data()[i * 3 + k] * v[k * 3 + j]).sum();
+                assert!((av - l[j] * v[i * 3 + j]).abs() < 1e-10);
+            }
+            let dot: f64 = (0..3).map(|k| v[k * 3 + j] * v[k * 3 + (j + 1) % 3]).sum();
+            assert!(dot.abs() < 1e-10);
+        }
+        assert!(eig_sym(&Tensor::<f64>::new(vec![1.0, 2.0, 0.0, 1.0], vec![2, 2]).unwrap()).is_err());
+
+        // A repeated eigenvalue still gets independent eigenvectors from `eig`.
+        let d: Tensor<f64> = Tensor::new(vec![2.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 1.0], vec![3, 3]).unwrap();
+        let v = eig(&d).unwrap().vectors.re();
+        let dot: f64 = (0..3).map(|k| v.data()[k * 3] * v.data()[k * 3 + 1]).sum();
+        assert!(dot.abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_eig_general() {
+        // Rotation-scaling block (eigenvalues 1 ± 2i) coupled to real
+        // eigenvalues 3 and -0.5.
+        let a: Tensor<f64> = Tensor::new(vec![
+            1.0, -2.0, 0.3, 0.0,
+            2.0, 1.0, 0.0, 0.1,
+            0.0, 0.0, 3.0, 1.0,
+            0.0, 0.0, 0.0, -0.5,
+        ], vec![4, 4]).unwrap();
+        let eig = eig(&a).unwrap();
+        let values = eig.values.data();
+        let expected = [(3.0, 0.0), (1.0, 2.0), (1.0, -2.0), (-0.5, 0.0)];
+        for (j, &(er, ei)) in expected.iter().enumerate() {
+            assert!((values[j] - Complex::new(er, ei)).abs() < 1e-9, "{:?}", values);
+        }
+
+        // A v = λ v for every pair, including the complex ones.
+        let v = eig.vectors.data();
+        for j in 0..4 {
+            for i in 0..4 {
+                let av = (0..4).fold(Complex::default(), |acc, k| acc + v[k * 4 + j].scale(a.data()[i * 4 + k]));
+                assert!((av - values[j] * v[i * 4 + j]).abs() < 1e-8, "column {} row {}", j, i);
+            }
+        }
+    }
+}
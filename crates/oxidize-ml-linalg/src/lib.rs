@@ -1,7 +1,11 @@
 pub mod decomposition;
+pub mod eigen;
+pub mod matrix;
 pub mod solve;
 pub mod svd;
 
 pub use decomposition::*;
+pub use eigen::*;
+pub use matrix::*;
 pub use solve::*;
 pub use svd::*;
@@ -0,0 +1,157 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+
+/// Rows and columns of a 1-D (as a single row) or 2-D tensor.
+fn rows_cols<T: Float>(a: &Tensor<T>, name: &str) -> TensorResult<(usize, usize)> {
+    match a.ndim() {
+        1 => Ok((1, a.numel())),
+        2 => Ok((a.shape().dim(0)?, a.shape().dim(1)?)),
+        _ => Err(TensorError::InvalidOperation(format!("{} requires a 1D or 2D tensor", name))),
+    }
+}
+
+fn matrix_dims<T: Float>(a: &Tensor<T>, name: &str) -> TensorResult<(usize, usize)> {
+    if a.ndim() != 2 {
+        return Err(TensorError::InvalidOperation(format!("{} requires a 2D tensor", name)));
+    }
+    Ok((a.shape().dim(0)?, a.shape().dim(1)?))
+}
+
+/// Kronecker product `A ⊗ B`: block `(i, j)` of the result is `a[i, j] · B`.
+/// 1-D inputs act as single rows; two 1-D inputs give a 1-D result.
+pub fn kron<T: Float>(a: &Tensor<T>, b: &Tensor<T>) -> TensorResult<Tensor<T>> {
+    let (ma, na) = rows_cols(a, "kron")?;
+    let (mb, nb) = rows_cols(b, "kron")?;
+    let (rows, cols) = (ma * mb, na * nb);
+    let (ad, bd) = (a.data(), b.data());
+    let mut out = Vec::with_capacity(rows * cols);
+    for i in 0..ma {
+        for k in 0..mb {
+            for j in 0..na {
+                let s = ad[i * na + j];
+                out.extend(bd[k * nb..(k + 1) * nb].iter().map(|&v| s * v));
+            }
+        }
+    }
+    if a.ndim() == 1 && b.ndim() == 1 {
+        Tensor::new(out, vec![cols])
+    } else {
+        Tensor::new(out, vec![rows, cols])
+    }
+}
+
+/// Block-diagonal matrix with `blocks` along the diagonal and zeros
+/// elsewhere. 1-D blocks act as single rows.
+pub fn block_diag<T: Float>(blocks: &[&Tensor<T>]) -> TensorResult<Tensor<T>> {
+    let dims = blocks.iter().map(|b| rows_cols(b, "block_diag")).collect::<TensorResult<Vec<_>>>()?;
+    let rows: usize = dims.iter().map(|d| d.0).sum();
+    let cols: usize = dims.iter().map(|d| d.1).sum();
+    let mut out = vec![T::ZERO; rows * cols];
+    let (mut r0, mut c0) = (0, 0);
+    for (block, &(m, n)) in blocks.iter().zip(&dims) {
+        for i in 0..m {
+            let start = (r0 + i) * cols + c0;
+            out[start..start + n].copy_from_slice(&block.data()[i * n..(i + 1) * n]);
+        }
+        r0 += m;
+        c0 += n;
+    }
+    Tensor::new(out, vec![rows, cols])
+}
+
+/// Sum of the main diagonal of a (possibly rectangular) matrix.
+pub fn trace<T: Float>(a: &Tensor<T>) -> TensorResult<T> {
+    let (m, n) = matrix_dims(a, "trace")?;
+    Ok((0..m.min(n)).fold(T::ZERO, |acc, i| acc + a.data()[i * n + i]))
+}
+
+/// For a 1-D `a`, the square matrix with `a` on its diagonal; for a 2-D
+/// `a`, its main diagonal as a 1-D tensor.
+pub fn diag<T: Float>(a: &Tensor<T>) -> TensorResult<Tensor<T>> {
+    match a.ndim() {
+        1 => {
+            let n = a.numel();
+            let mut out = vec![T::ZERO; n * n];
+            for (i, &v) in a.data().iter().enumerate() {
+                out[i * n + i] = v;
+            }
+            Tensor::new(out, vec![n, n])
+        }
+        2 => {
+            let (m, n) = matrix_dims(a, "diag")?;
+            let k = m.min(n);
+            Tensor::new((0..k).map(|i| a.data()[i * n + i]).collect(), vec![k])
+        }
+        _ => Err(TensorError::InvalidOperation("diag requires a 1D or 2D tensor".into())),
+    }
+}
+
+/// Copy of `a` keeping entries where `keep(j - i)` holds.
+fn triangle<T: Float>(a: &Tensor<T>, name: &str, keep: impl Fn(isize) -> bool) -> TensorResult<Tensor<T>> {
+    let (m, n) = matrix_dims(a, name)?;
+    let mut out = a.data().to_vec();
+    for i in 0..m {
+        for j in 0..n {
+            if !keep(j as isize - i as isize) {
+                out[i * n + j] = T::ZERO;
+            }
+        }
+    }
+    Tensor::new(out, vec![m, n])
+}
+
+/// Upper triangle of `a`: entries on and above the `k`-th diagonal
+/// (`k = 0` main, `k > 0` above, `k < 0` below) are kept, the rest zeroed.
+pub fn triu<T: Float>(a: &Tensor<T>, k: isize) -> TensorResult<Tensor<T>> {
+    triangle(a, "triu", |offset| offset >= k)
+}
+
+/// Lower triangle of `a`: entries on and below the `k`-th diagonal are
+/// kept, the rest zeroed.
+pub fn tril<T: Float>(a: &Tensor<T>, k: isize) -> TensorResult<Tensor<T>> {
+    triangle(a, "tril", |offset| offset <= k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kron_and_block_diag() {
+        let a: Tensor<f64> = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]).unwrap();
+        let b: Tensor<f64> = Tensor::new(vec![0.0, 5.0, 6.0, 7.0, 1.0, 0.0], vec![2, 3]).unwrap();
+        let k = kron(&a, &b).unwrap();
+        assert_eq!(k.shape_vec(), vec![4, 6]);
+        for i in 0..4 {
+            for j in 0..6 {
+                let expected = a.data()[(i / 2) * 2 + j / 3] * b.data()[(i % 2) * 3 + j % 3];
+                assert_eq!(k.data()[i * 6 + j], expected);
+            }
+        }
+        // (A ⊗ B)ᵀ = Aᵀ ⊗ Bᵀ
+        assert_eq!(k.t().unwrap().data(), kron(&a.t().unwrap(), &b.t().unwrap()).unwrap().data());
+        assert_eq!(kron(&Tensor::from_slice(&[1.0, 2.0]), &Tensor::from_slice(&[1.0, -1.0])).unwrap().data(), &[1.0, -1.0, 2.0, -2.0]);
+
+        let d = block_diag(&[&a, &Tensor::from_slice(&[9.0])]).unwrap();
+        assert_eq!(d.shape_vec(), vec![3, 3]);
+        assert_eq!(d.data(), &[1.0, 2.0, 0.0, 3.0, 4.0, 0.0, 0.0, 0.0, 9.0]);
+    }
+
+    #[test]
+    fn test_trace_diag_triangles() {
+        let a: Tensor<f64> = Tensor::new((1..=6).map(|v| v as f64).collect(), vec![2, 3]).unwrap();
+        assert_eq!(trace(&a).unwrap(), 6.0);
+        assert_eq!(diag(&a).unwrap().data(), &[1.0, 5.0]);
+        let d = diag(&Tensor::from_slice(&[2.0, 3.0])).unwrap();
+        assert_eq!(d.data(), &[2.0, 0.0, 0.0, 3.0]);
+        assert_eq!(diag(&d).unwrap().data(), &[2.0, 3.0]);
+
+        assert_eq!(triu(&a, 0).unwrap().data(), &[1.0, 2.0, 3.0, 0.0, 5.0, 6.0]);
+        assert_eq!(triu(&a, 1).unwrap().data(), &[0.0, 2.0, 3.0, 0.0, 0.0, 6.0]);
+        assert_eq!(tril(&a, 0).unwrap().data(), &[1.0, 0.0, 0.0, 4.0, 5.0, 0.0]);
+        assert_eq!(tril(&a, -1).unwrap().data(), &[0.0, 0.0, 0.0, 4.0, 0.0, 0.0]);
+        // The two halves partition the matrix.
+        let sum = triu(&a, 1).unwrap().add(&tril(&a, 0).unwrap()).unwrap();
+        assert_eq!(sum.data(), a.data());
+    }
+}
@@ -3,6 +3,7 @@ use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
 
 use crate::decomposition::{lu, qr};
+use crate::svd::thin_svd;
 
 /// Solve the linear system Ax = b using LU decomposition.
 pub fn solve<T: Float>(a: &Tensor<T>, b: &Tensor<T>) -> TensorResult<Tensor<T>>
@@ -54,7 +55,7 @@ where
         for i in 0..n {
             let mut sum = T::ZERO;
             for j in 0..i {
-                sum = sum + decomp.l.get(&[i, j])? * y[j];
+                sum += decomp.l.get(&[i, j])? * y[j];
             }
             y[i] = pb[i] - sum;
         }
@@ -64,7 +65,7 @@ where
         for i in (0..n).rev() {
             let mut sum = T::ZERO;
             for j in (i + 1)..n {
-                sum = sum + decomp.u.get(&[i, j])? * x[j];
+                sum += decomp.u.get(&[i, j])? * x[j];
             }
             let diag = decomp.u.get(&[i, i])?;
             if diag.abs() < T::EPSILON {
@@ -86,46 +87,190 @@ where
 }
 
 /// Least-squares solution: minimize ||Ax - b||² using QR decomposition.
-/// Works for overdetermined systems (m > n).
+/// Falls back to [`lstsq_svd`] (minimum-norm solution) when `A` is wider
+/// than tall or QR finds it numerically rank deficient.
 pub fn lstsq<T: Float>(a: &Tensor<T>, b: &Tensor<T>) -> TensorResult<Tensor<T>>
 where
     Standard: Distribution<T>,
 {
+    let (m, n) = lstsq_shapes(a, b)?;
+    if m < n {
+        return Ok(lstsq_svd(a, b, None)?.0);
+    }
+
+    let decomp = qr(a)?;
+    let r = decomp.r.data();
+    let max_diag = (0..n).map(|i| r[i * n + i].to_f64().abs()).fold(0.0, f64::max);
+    let tol = max_diag * m.max(n) as f64 * f64::EPSILON;
+    if max_diag == 0.0 || (0..n).any(|i| r[i * n + i].to_f64().abs() <= tol) {
+        return Ok(lstsq_svd(a, b, None)?.0);
+    }
+
+    // Compute Qᵀ * b, then back-substitute R x = Qᵀb.
+    let qtb = decomp.q.t()?.matmul(&b.reshape(vec![m, 1])?)?;
+    let r_square = Tensor::new(r[..n * n].to_vec(), vec![n, n])?;
+    solve_triangular(&r_square, &qtb.reshape(vec![n])?, Triangle::Upper, false)
+}
+
+/// Default relative cutoff of [`lstsq_svd`]. The SVD is computed from the
+/// Gram matrix, so an exactly zero singular value comes out near
+/// `1e-8 · σ_max`; anything below `1e-7 · σ_max` is treated as zero.
+pub const LSTSQ_RCOND: f64 = 1e-7;
+
+/// Minimum-norm least-squares solution via the thin SVD, discarding
+/// singular values below `rcond · σ_max` (default [`LSTSQ_RCOND`]).
+/// Returns the solution, the effective rank and the singular values.
+pub fn lstsq_svd<T: Float>(a: &Tensor<T>, b: &Tensor<T>, rcond: Option<f64>) -> TensorResult<(Tensor<T>, usize, Tensor<T>)> {
+    let (m, n) = lstsq_shapes(a, b)?;
+    let (u, sigma, v) = thin_svd(a)?;
+    let k = sigma.numel();
+    let s: Vec<f64> = sigma.data().iter().map(|v| v.to_f64()).collect();
+    let cutoff = rcond.unwrap_or(LSTSQ_RCOND) * s.first().copied().unwrap_or(0.0);
+    let rank = s.iter().filter(|&&v| v > cutoff).count();
+
+    let (u, v) = (u.data(), v.data());
+    let mut x = vec![0.0; n];
+    for j in 0..rank {
+        let uty: f64 = (0..m).map(|i| u[i * k + j].to_f64() * b.data()[i].to_f64()).sum();
+        for (i, xi) in x.iter_mut().enumerate() {
+            *xi += v[i * k + j].to_f64() * uty / s[j];
+        }
+    }
+    Ok((Tensor::new(x.into_iter().map(T::from_f64).collect(), vec![n])?, rank, sigma))
+}
+
+fn lstsq_shapes<T: Float>(a: &Tensor<T>, b: &Tensor<T>) -> TensorResult<(usize, usize)> {
     if a.ndim() != 2 {
         return Err(TensorError::InvalidOperation("lstsq: A must be 2D".into()));
     }
     let m = a.shape().dim(0)?;
-    let n = a.shape().dim(1)?;
-
     if b.ndim() != 1 || b.numel() != m {
         return Err(TensorError::DimensionMismatch(format!(
             "lstsq: b must be 1D with {} elements",
             m
         )));
     }
+    Ok((m, a.shape().dim(1)?))
+}
 
-    let decomp = qr(a)?;
+/// Which triangle of a matrix holds its entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Triangle {
+    Lower,
+    Upper,
+}
+
+/// Right-hand side `b` (1-D of length `n` or 2-D `[n, k]`) as row-major data
+/// and its column count.
+fn rhs_columns<T: Float>(b: &Tensor<T>, n: usize, name: &str) -> TensorResult<(Vec<T>, usize)> {
+    let cols = match b.ndim() {
+        1 => 1,
+        2 => b.shape().dim(1)?,
+        _ => return Err(TensorError::InvalidOperation(format!("{}: b must be 1D or 2D", name))),
+    };
+    if b.shape().dim(0)? != n {
+        return Err(TensorError::DimensionMismatch(format!(
+            "{}: b has {} rows but A has {}", name, b.shape().dim(0)?, n
+        )));
+    }
+    Ok((b.data().to_vec(), cols))
+}
+
+/// Solve `A x = b`, or `Aᵀ x = b` with `transpose`, for triangular `A` by
+/// forward or back substitution. Only the given `triangle` of `a` is read;
+/// `b` may be 1-D or hold one right-hand side per column.
+pub fn solve_triangular<T: Float>(a: &Tensor<T>, b: &Tensor<T>, triangle: Triangle, transpose: bool) -> TensorResult<Tensor<T>> {
+    if a.ndim() != 2 || a.shape().dim(0)? != a.shape().dim(1)? {
+        return Err(TensorError::InvalidOperation("solve_triangular: A must be square".into()));
+    }
+    let n = a.shape().dim(0)?;
+    let (mut x, cols) = rhs_columns(b, n, "solve_triangular")?;
+    let a = a.data();
+    if (0..n).any(|i| a[i * n + i] == T::ZERO) {
+        return Err(TensorError::SingularMatrix);
+    }
+    // Transposing swaps which triangle is used, so Lᵀ solves backwards.
+    let at = |i: usize, j: usize| if transpose { a[j * n + i] } else { a[i * n + j] };
+    let forward = (triangle == Triangle::Lower) != transpose;
+
+    for c in 0..cols {
+        for step in 0..n {
+            let i = if forward { step } else { n - 1 - step };
+            let mut sum = x[i * cols + c];
+            for j in if forward { 0..i } else { i + 1..n } {
+                sum -= at(i, j) * x[j * cols + c];
+            }
+            x[i * cols + c] = sum / at(i, i);
+        }
+    }
+    Tensor::new(x, b.shape_vec())
+}
 
-    // Compute Qᵀ * b
-    let qt = decomp.q.t()?;
-    let b_2d = b.reshape(vec![m, 1])?;
-    let qtb = qt.matmul(&b_2d)?;
+/// Solve `A x = b` for a banded `A` with `lower` sub- and `upper`
+/// super-diagonals, given in LAPACK band storage: `ab` is
+/// `[lower + upper + 1, n]` with `ab[upper + i - j, j] = A[i, j]`. Gaussian
+/// elimination with partial pivoting costs `O(n · lower · (lower + upper))`.
+pub fn solve_banded<T: Float>(ab: &Tensor<T>, lower: usize, upper: usize, b: &Tensor<T>) -> TensorResult<Tensor<T>> {
+    if ab.ndim() != 2 || ab.shape().dim(0)? != lower + upper + 1 {
+        return Err(TensorError::InvalidOperation(format!(
+            "solve_banded: ab must have {} rows for ({}, {}) bands", lower + upper + 1, lower, upper
+        )));
+    }
+    let n = ab.shape().dim(1)?;
+    let (mut x, cols) = rhs_columns(b, n, "solve_banded")?;
 
-    // Back-substitution on R * x = Qᵀb (first n rows)
-    let mut x = vec![T::ZERO; n];
-    for i in (0..n).rev() {
-        let mut sum = T::ZERO;
-        for j in (i + 1)..n {
-            sum = sum + decomp.r.get(&[i, j])? * x[j];
+    // Row i keeps columns i - lower ..= i + lower + upper: its own band plus
+    // room for the fill-in that row swaps bring in.
+    let width = 2 * lower + upper + 1;
+    let mut band = vec![T::ZERO; n * width];
+    let slot = |i: usize, j: usize| i * width + j + lower - i;
+    for j in 0..n {
+        for i in j.saturating_sub(upper)..(j + lower + 1).min(n) {
+            band[slot(i, j)] = ab.data()[(upper + i - j) * n + j];
         }
-        let diag = decomp.r.get(&[i, i])?;
-        if diag.abs() < T::EPSILON {
+    }
+
+    for k in 0..n {
+        let last_row = (k + lower).min(n - 1);
+        let last_col = (k + lower + upper).min(n - 1);
+        let pivot = (k..=last_row).fold(k, |p, i| if band[slot(i, k)].abs() > band[slot(p, k)].abs() { i } else { p });
+        if band[slot(pivot, k)] == T::ZERO {
             return Err(TensorError::SingularMatrix);
         }
-        x[i] = (qtb.get(&[i, 0])? - sum) / diag;
+        if pivot != k {
+            for j in k..=last_col {
+                band.swap(slot(k, j), slot(pivot, j));
+            }
+            for c in 0..cols {
+                x.swap(k * cols + c, pivot * cols + c);
+            }
+        }
+        for i in k + 1..=last_row {
+            let factor = band[slot(i, k)] / band[slot(k, k)];
+            if factor == T::ZERO {
+                continue;
+            }
+            for j in k..=last_col {
+                band[slot(i, j)] = band[slot(i, j)] - factor * band[slot(k, j)];
+            }
+            for c in 0..cols {
+                x[i * cols + c] = x[i * cols + c] - factor * x[k * cols + c];
+            }
+        }
     }
 
-    Tensor::new(x, vec![n])
+    // Back substitution on the upper band of width lower + upper.
+    for k in (0..n).rev() {
+        let last_col = (k + lower + upper).min(n - 1);
+        for c in 0..cols {
+            let mut sum = x[k * cols + c];
+            for j in k + 1..=last_col {
+                sum -= band[slot(k, j)] * x[j * cols + c];
+            }
+            x[k * cols + c] = sum / band[slot(k, k)];
+        }
+    }
+    Tensor::new(x, b.shape_vec())
 }
 
 #[cfg(test)]
@@ -158,5 +303,54 @@ mod tests {
         // Should get approximately [1, 2] (intercept=1, slope=2)
         assert!((x.data()[0] - 1.0).abs() < 1e-10);
         assert!((x.data()[1] - 2.0).abs() < 1e-10);
+
+        // A duplicated column is rank deficient: lstsq falls back to the
+        // minimum-norm solution, splitting the slope between the copies.
+        let a: Tensor<f64> = Tensor::new(
+            vec![1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 1.0, 3.0, 3.0],
+            vec![3, 3],
+        ).unwrap();
+        let x = lstsq(&a, &b).unwrap();
+        for (xi, e) in x.data().iter().zip(&[1.0, 1.0, 1.0]) {
+            assert!((xi - e).abs() < 1e-7, "{:?}", x.data());
+        }
+        assert_eq!(lstsq_svd(&a, &b, None).unwrap().1, 2);
+    }
+
+    #[test]
+    fn test_solve_triangular_and_banded() {
+        let l: Tensor<f64> = Tensor::new(vec![2.0, 0.0, 0.0, 1.0, 3.0, 0.0, -1.0, 0.5, 4.0], vec![3, 3]).unwrap();
+        let b: Tensor<f64> = Tensor::new(vec![2.0, 1.0, 7.0, 2.0, 3.5, -1.0], vec![3, 2]).unwrap();
+        for transpose in [false, true] {
+            let x = solve_triangular(&l, &b, Triangle::Lower, transpose).unwrap();
+            let lhs = if transpose { l.t().unwrap() } else { l.clone() }.matmul(&x).unwrap();
+            for (a, e) in lhs.data().iter().zip(b.data()) {
+                assert!((a - e).abs() < 1e-12);
+            }
+            // The upper triangle of a transposed lower matrix gives the same answer.
+            let u = l.t().unwrap();
+            let y = solve_triangular(&u, &b, Triangle::Upper, !transpose).unwrap();
+            assert_eq!(x.data(), y.data());
+        }
+
+        // Tridiagonal system (1 sub, 1 super) with a zero leading diagonal
+        // that needs pivoting, against the dense solver.
+        let dense: Tensor<f64> = Tensor::new(vec![
+            0.0, 2.0, 0.0, 0.0,
+            1.0, 3.0, 1.0, 0.0,
+            0.0, 4.0, 1.0, 2.0,
+            0.0, 0.0, 1.0, 5.0,
+        ], vec![4, 4]).unwrap();
+        let ab: Tensor<f64> = Tensor::new(vec![
+            0.0, 2.0, 1.0, 2.0,
+            0.0, 3.0, 1.0, 5.0,
+            1.0, 4.0, 1.0, 0.0,
+        ], vec![3, 4]).unwrap();
+        let rhs: Tensor<f64> = Tensor::from_slice(&[1.0, 2.0, 3.0, 4.0]);
+        let banded = solve_banded(&ab, 1, 1, &rhs).unwrap();
+        let reference = solve(&dense, &rhs).unwrap();
+        for (a, e) in banded.data().iter().zip(reference.data()) {
+            assert!((a - e).abs() < 1e-12);
+        }
     }
 }
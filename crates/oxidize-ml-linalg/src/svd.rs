@@ -1,13 +1,16 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{Float, SparseTensor, Tensor};
 use oxidize_ml_core::error::TensorResult;
 
+use crate::eigen::symmetric_eigen;
+
 /// Singular Value Decomposition (SVD) using one-sided Jacobi rotations.
 ///
 /// Decomposes A = U Σ Vᵀ where:
 /// - U: [m, k] left singular vectors
 /// - Σ: [k] singular values (descending)
 /// - V: [n, k] right singular vectors
-/// k = min(m, n)
+///
+/// where k = min(m, n).
 pub fn svd<T: Float>(a: &Tensor<T>) -> TensorResult<(Tensor<T>, Tensor<T>, Tensor<T>)> {
     let m = a.shape().dim(0)?;
     let n = a.shape().dim(1)?;
@@ -121,6 +124,156 @@ pub fn svd<T: Float>(a: &Tensor<T>) -> TensorResult<(Tensor<T>, Tensor<T>, Tenso
     Ok((u, sigma, v))
 }
 
+/// Thin SVD of `a` (`[m, n]`) from the eigendecomposition of its smaller
+/// Gram matrix, iterated to convergence. Returns `(U [m, k], Σ [k], V [n, k])`
+/// with `k = min(m, n)` and singular values descending. Squaring the matrix
+/// limits accuracy for singular values below about `1e-8 · σ_max`.
+pub fn thin_svd<T: Float>(a: &Tensor<T>) -> TensorResult<(Tensor<T>, Tensor<T>, Tensor<T>)> {
+    let m = a.shape().dim(0)?;
+    let n = a.shape().dim(1)?;
+    let k = m.min(n);
+    let data: Vec<f64> = a.data().iter().map(|v| v.to_f64()).collect();
+
+    // Eigenvectors of the Gram matrix are the singular vectors on the
+    // short side; the long side follows from A V = U Σ.
+    let wide = m < n;
+    let (rows, cols) = if wide { (n, m) } else { (m, n) };
+    let at = |r: usize, c: usize| if wide { data[c * n + r] } else { data[r * n + c] };
+    let mut gram = vec![0.0; cols * cols];
+    for i in 0..cols {
+        for j in i..cols {
+            let sum: f64 = (0..rows).map(|r| at(r, i) * at(r, j)).sum();
+            gram[i * cols + j] = sum;
+            gram[j * cols + i] = sum;
+        }
+    }
+    let (values, vecs) = symmetric_eigen(&mut gram, cols);
+    let sigma: Vec<f64> = values[..k].iter().map(|v| v.max(0.0).sqrt()).collect();
+
+    let mut short = vec![0.0; cols * k];
+    for i in 0..cols {
+        short[i * k..(i + 1) * k].copy_from_slice(&vecs[i * cols..i * cols + k]);
+    }
+    let mut long = vec![0.0; rows * k];
+    for r in 0..rows {
+        for j in 0..k {
+            if sigma[j] > 1e-12 * sigma[0].max(f64::MIN_POSITIVE) {
+                let dot: f64 = (0..cols).map(|c| at(r, c) * short[c * k + j]).sum();
+                long[r * k + j] = dot / sigma[j];
+            }
+        }
+    }
+
+    let to_t = |v: Vec<f64>, shape: Vec<usize>| Tensor::new(v.into_iter().map(T::from_f64).collect(), shape);
+    let (u, v) = if wide { (short, long) } else { (long, short) };
+    Ok((to_t(u, vec![m, k])?, to_t(sigma, vec![k])?, to_t(v, vec![n, k])?))
+}
+
+/// Orthonormalize the columns of `y` (`[m, l]`, row-major) in place with
+/// modified Gram–Schmidt; dependent columns become zero.
+fn orthonormalize_columns(y: &mut [f64], m: usize, l: usize) {
+    for j in 0..l {
+        for prev in 0..j {
+            let dot: f64 = (0..m).map(|i| y[i * l + j] * y[i * l + prev]).sum();
+            for i in 0..m {
+                y[i * l + j] -= dot * y[i * l + prev];
+            }
+        }
+        let norm: f64 = (0..m).map(|i| y[i * l + j].powi(2)).sum::<f64>().sqrt();
+        for i in 0..m {
+            y[i * l + j] = if norm > 1e-12 { y[i * l + j] / norm } else { 0.0 };
+        }
+    }
+}
+
+/// Truncated SVD by random projection (Halko, Martinsson & Tropp): sketch
+/// the range of `a` with `n_components + n_oversamples` random directions,
+/// sharpen it with `n_iter` power iterations, and take the exact SVD of the
+/// small projected matrix. Returns `(U [m, k], Σ [k], V [n, k])` for
+/// `k = min(n_components, m, n)`; cost is linear in `m · n`.
+pub fn randomized_svd<T: Float>(
+    a: &Tensor<T>,
+    n_components: usize,
+    n_oversamples: usize,
+    n_iter: usize,
+    seed: Option<u64>,
+) -> TensorResult<(Tensor<T>, Tensor<T>, Tensor<T>)> {
+    let m = a.shape().dim(0)?;
+    let n = a.shape().dim(1)?;
+    let a64 = Tensor::new(a.data().iter().map(|v| v.to_f64()).collect(), vec![m, n])?;
+    let at64 = a64.t()?;
+    randomized_svd_by(
+        (m, n),
+        (n_components, n_oversamples, n_iter),
+        seed,
+        |x| a64.matmul(x),
+        |y| at64.matmul(y),
+    )
+}
+
+/// `randomized_svd` for a CSR matrix, touching only its non-zeros; cost is
+/// linear in `nnz`.
+pub fn randomized_svd_sparse<T: Float>(
+    a: &SparseTensor<T>,
+    n_components: usize,
+    n_oversamples: usize,
+    n_iter: usize,
+    seed: Option<u64>,
+) -> TensorResult<(Tensor<T>, Tensor<T>, Tensor<T>)> {
+    let a64 = SparseTensor::new(
+        a.n_rows(),
+        a.n_cols(),
+        a.indptr().to_vec(),
+        a.indices().to_vec(),
+        a.values().iter().map(|v| v.to_f64()).collect(),
+    )?;
+    let at64 = a64.transpose();
+    randomized_svd_by(
+        (a.n_rows(), a.n_cols()),
+        (n_components, n_oversamples, n_iter),
+        seed,
+        |x| a64.spmm(x),
+        |y| at64.spmm(y),
+    )
+}
+
+/// Shared body of the randomized SVDs, given products with `A` (`mul`, on
+/// `[n, l]` blocks) and `Aᵀ` (`mul_t`, on `[m, l]` blocks).
+fn randomized_svd_by<T: Float>(
+    (m, n): (usize, usize),
+    (n_components, n_oversamples, n_iter): (usize, usize, usize),
+    seed: Option<u64>,
+    mul: impl Fn(&Tensor<f64>) -> TensorResult<Tensor<f64>>,
+    mul_t: impl Fn(&Tensor<f64>) -> TensorResult<Tensor<f64>>,
+) -> TensorResult<(Tensor<T>, Tensor<T>, Tensor<T>)> {
+    let k = n_components.min(m).min(n);
+    let l = (k + n_oversamples).min(m).min(n);
+
+    let omega: Tensor<f64> = Tensor::randn(vec![n, l], seed);
+    let mut q = mul(&omega)?.data().to_vec();
+    orthonormalize_columns(&mut q, m, l);
+    for _ in 0..n_iter {
+        let mut z = mul_t(&Tensor::new(q, vec![m, l])?)?.data().to_vec();
+        orthonormalize_columns(&mut z, n, l);
+        q = mul(&Tensor::new(z, vec![n, l])?)?.data().to_vec();
+        orthonormalize_columns(&mut q, m, l);
+    }
+
+    // B = Qᵀ A = (Aᵀ Q)ᵀ is small ([l, n]); its SVD gives A's through U = Q U_B.
+    let q = Tensor::new(q, vec![m, l])?;
+    let b = mul_t(&q)?.t()?;
+    let (ub, sigma, v) = thin_svd(&b)?;
+    let u = q.matmul(&ub)?;
+
+    let take = |t: &Tensor<f64>, rows: usize| -> TensorResult<Tensor<T>> {
+        let cols = t.shape().dim(1)?;
+        let data = (0..rows).flat_map(|r| t.data()[r * cols..r * cols + k].to_vec()).map(T::from_f64).collect();
+        Tensor::new(data, vec![rows, k])
+    };
+    let s = Tensor::new(sigma.data()[..k].iter().map(|&v| T::from_f64(v)).collect(), vec![k])?;
+    Ok((take(&u, m)?, s, take(&v, n)?))
+}
+
 /// Compute matrix norm (Frobenius by default).
 pub fn frobenius_norm<T: Float>(a: &Tensor<T>) -> f64 {
     a.data().iter().map(|&v| v.to_f64() * v.to_f64()).sum::<f64>().sqrt()
@@ -146,7 +299,7 @@ pub fn pinv<T: Float>(a: &Tensor<T>) -> TensorResult<Tensor<T>> {
     let (u, sigma, v) = svd(a)?;
     let k = sigma.numel();
     let m = u.shape().dim(0)?;
-    let n = v.shape().dim(0)?;
+    let _n = v.shape().dim(0)?;
 
     // Σ⁺ = diag(1/σ_i for σ_i > tol)
     let tol = 1e-10;
@@ -184,7 +337,7 @@ mod tests {
             vec![0.0, 4.0],
         ]).unwrap();
 
-        let (u, sigma, v) = svd(&a).unwrap();
+        let (_u, sigma, _v) = svd(&a).unwrap();
         // Singular values should be 4 and 3
         let s = sigma.data();
         assert!((s[0] - 4.0).abs() < 0.1, "σ₁ = {}", s[0]);
@@ -207,4 +360,42 @@ mod tests {
         assert!((ata.get(&[0, 0]).unwrap() - 1.0).abs() < 0.1);
         assert!((ata.get(&[1, 1]).unwrap() - 1.0).abs() < 0.1);
     }
+
+    #[test]
+    fn test_randomized_svd() {
+        // Rank-2 matrix: outer products with singular values 10 and 3.
+        let (m, n) = (40, 30);
+        let mut data = vec![0.0; m * n];
+        for i in 0..m {
+            for j in 0..n {
+                let (u1, v1) = ((i as f64 + 1.0).sin(), (j as f64 * 0.7).cos());
+                let (u2, v2) = ((i as f64 * 0.3).cos(), (j as f64 + 2.0).sin());
+                data[i * n + j] = 10.0 * u1 * v1 + 3.0 * u2 * v2;
+            }
+        }
+        let a: Tensor<f64> = Tensor::new(data, vec![m, n]).unwrap();
+
+        let (_, exact, _) = thin_svd(&a).unwrap();
+        let (u, sigma, v) = randomized_svd(&a, 2, 5, 2, Some(3)).unwrap();
+        assert_eq!(u.shape_vec(), vec![40, 2]);
+        assert_eq!(v.shape_vec(), vec![30, 2]);
+        for j in 0..2 {
+            assert!((sigma.data()[j] - exact.data()[j]).abs() < 1e-8 * exact.data()[0]);
+        }
+        assert!(exact.data()[2] < 1e-6 * exact.data()[0]);
+
+        // U Σ Vᵀ reconstructs the rank-2 matrix.
+        for i in 0..m {
+            for j in 0..n {
+                let approx: f64 = (0..2).map(|c| u.data()[i * 2 + c] * sigma.data()[c] * v.data()[j * 2 + c]).sum();
+                assert!((approx - a.data()[i * n + j]).abs() < 1e-8);
+            }
+        }
+
+        // The sparse path sees the same matrix and the same sketch.
+        let (_, sparse_sigma, _) = randomized_svd_sparse(&SparseTensor::from_dense(&a).unwrap(), 2, 5, 2, Some(3)).unwrap();
+        for (s, d) in sparse_sigma.data().iter().zip(sigma.data()) {
+            assert!((s - d).abs() < 1e-8 * d);
+        }
+    }
 }
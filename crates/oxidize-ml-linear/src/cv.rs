@@ -0,0 +1,559 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+use crate::regression::fit_least_squares;
+use crate::solver::{design_matrix, ridge_path, LinearSolver};
+
+/// Contiguous, unshuffled K-fold test ranges; the first `n % k` folds get
+/// one extra sample.
+fn fold_ranges(n: usize, k: usize) -> TensorResult<Vec<(usize, usize)>> {
+    if k < 2 || k > n {
+        return Err(TensorError::InvalidOperation(format!(
+            "cv needs 2 <= folds <= n_samples, got {} folds for {} samples", k, n
+        )));
+    }
+    let mut start = 0;
+    Ok((0..k)
+        .map(|f| {
+            let len = n / k + usize::from(f < n % k);
+            start += len;
+            (start - len, start)
+        })
+        .collect())
+}
+
+/// Index of the alpha with the lowest mean fold error.
+fn best_alpha(mse_path: &[Vec<f64>]) -> (usize, f64) {
+    mse_path
+        .iter()
+        .map(|folds| folds.iter().sum::<f64>() / folds.len() as f64)
+        .enumerate()
+        .fold((0, f64::INFINITY), |best, (i, m)| if m < best.1 { (i, m) } else { best })
+}
+
+fn check_targets<T: Float>(x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<(usize, usize, Vec<f64>)> {
+    let n = x.shape().dim(0)?;
+    let p = x.shape().dim(1)?;
+    if y.numel() != n {
+        return Err(TensorError::DimensionMismatch(format!(
+            "x has {} rows but y has {} targets", n, y.numel()
+        )));
+    }
+    Ok((n, p, y.data().iter().map(|v| v.to_f64()).collect()))
+}
+
+/// Ridge regression with `alpha` chosen by K-fold cross-validation.
+///
+/// Each fold takes one thin SVD of its training rows and reads off the
+/// whole path of ridge solutions from it, then the model is refitted on
+/// all data with the best `alpha`. The objective matches [`crate::Ridge`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct RidgeCV<T: Float> {
+    /// Candidate penalties; defaults to `10⁻³ … 10³`, one per decade.
+    pub alphas: Vec<f64>,
+    pub cv: usize,
+    pub fit_intercept: bool,
+    /// Validation MSE per alpha and fold, `[n_alphas][cv]`.
+    pub mse_path: Vec<Vec<f64>>,
+    /// The selected penalty.
+    pub alpha: Option<f64>,
+    pub weights: Option<Tensor<T>>,
+    pub bias: Option<T>,
+}
+
+impl<T: Float> RidgeCV<T> {
+    pub fn new(fit_intercept: bool) -> Self {
+        RidgeCV {
+            alphas: (-3..=3).map(|e| 10f64.powi(e)).collect(),
+            cv: 5,
+            fit_intercept,
+            mse_path: Vec::new(),
+            alpha: None,
+            weights: None,
+            bias: None,
+        }
+    }
+
+    pub fn with_alphas(mut self, alphas: Vec<f64>) -> Self {
+        self.alphas = alphas;
+        self
+    }
+
+    pub fn with_cv(mut self, cv: usize) -> Self {
+        self.cv = cv;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (n, _, targets) = check_targets(x, y)?;
+        if self.alphas.is_empty() || self.alphas.iter().any(|&a| a < 0.0) {
+            return Err(TensorError::InvalidOperation("alphas must be non-empty and non-negative".into()));
+        }
+        let (design, dim) = design_matrix(x, self.fit_intercept)?;
+
+        let mut mse_path = vec![Vec::with_capacity(self.cv); self.alphas.len()];
+        for (start, end) in fold_ranges(n, self.cv)? {
+            let train: Vec<usize> = (0..start).chain(end..n).collect();
+            let train_x: Vec<f64> = train.iter().flat_map(|&r| design[r * dim..(r + 1) * dim].iter().copied()).collect();
+            let train_y: Vec<f64> = train.iter().map(|&r| targets[r]).collect();
+            let path = ridge_path(&train_x, train.len(), dim, &train_y, &self.alphas)?;
+            for (errors, w) in mse_path.iter_mut().zip(&path) {
+                let sse: f64 = (start..end)
+                    .map(|r| {
+                        let pred: f64 = design[r * dim..(r + 1) * dim].iter().zip(w).map(|(a, b)| a * b).sum();
+                        (targets[r] - pred).powi(2)
+                    })
+                    .sum();
+                errors.push(sse / (end - start) as f64);
+            }
+        }
+
+        let alpha = self.alphas[best_alpha(&mse_path).0];
+        let (weights, bias) = fit_least_squares(x, y, self.fit_intercept, alpha, LinearSolver::Auto)?;
+        self.mse_path = mse_path;
+        self.alpha = Some(alpha);
+        self.weights = Some(weights);
+        self.bias = bias;
+        Ok(())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        linear_predict(x, self.weights.as_ref(), self.bias)
+    }
+}
+
+fn linear_predict<T: Float>(x: &Tensor<T>, weights: Option<&Tensor<T>>, bias: Option<T>) -> TensorResult<Tensor<T>> {
+    let w = weights.ok_or_else(|| TensorError::InvalidOperation("Model not fitted".into()))?;
+    let n = x.shape().dim(0)?;
+    let p = x.shape().dim(1)?;
+    let pred = x.matmul(&w.reshape(vec![p, 1])?)?;
+    let pred = match bias {
+        Some(b) => pred.add_scalar(b),
+        None => pred,
+    };
+    pred.reshape(vec![n])
+}
+
+/// Settings shared by the coordinate-descent path of `LassoCV` and
+/// `ElasticNetCV`.
+struct PathSpec {
+    n_alphas: usize,
+    eps: f64,
+    max_iter: usize,
+    tol: f64,
+}
+
+/// Feature-major columns of `x` (`[p][n]`) for the rows in `rows`.
+fn columns(x: &[f64], p: usize, rows: &[usize]) -> Vec<f64> {
+    let mut cols = vec![0.0; p * rows.len()];
+    for (i, &r) in rows.iter().enumerate() {
+        for j in 0..p {
+            cols[j * rows.len() + i] = x[r * p + j];
+        }
+    }
+    cols
+}
+
+/// Log-spaced grid from the smallest `α` that zeroes every coefficient,
+/// `max_j |x̃_jᵀ(y - ȳ)| / (n · l1_ratio)` with centered columns, down to
+/// `eps` times that.
+fn alpha_grid(cols: &[f64], n: usize, p: usize, y: &[f64], l1_ratio: f64, spec: &PathSpec) -> TensorResult<Vec<f64>> {
+    if l1_ratio <= 0.0 {
+        return Err(TensorError::InvalidOperation(
+            "an automatic alpha grid needs l1_ratio > 0; pass alphas explicitly".into(),
+        ));
+    }
+    let y_mean = y.iter().sum::<f64>() / n as f64;
+    let max_corr = (0..p)
+        .map(|j| {
+            let col = &cols[j * n..(j + 1) * n];
+            let x_mean = col.iter().sum::<f64>() / n as f64;
+            col.iter().zip(y).map(|(xi, yi)| (xi - x_mean) * (yi - y_mean)).sum::<f64>().abs()
+        })
+        .fold(0.0, f64::max);
+    let alpha_max = (max_corr / (n as f64 * l1_ratio)).max(f64::MIN_POSITIVE);
+    let k = spec.n_alphas.max(1);
+    Ok((0..k)
+        .map(|i| {
+            let t = if k == 1 { 0.0 } else { i as f64 / (k - 1) as f64 };
+            alpha_max * spec.eps.powf(t)
+        })
+        .collect())
+}
+
+/// Elastic-net coordinate descent over `alphas` in order, warm-starting
+/// each fit from the previous one. Same objective as [`crate::ElasticNet`],
+/// `(1/2n)||y - Xw - b||² + α·l1_ratio·||w||₁ + α·(1-l1_ratio)/2·||w||²`.
+fn enet_path(cols: &[f64], n: usize, p: usize, y: &[f64], alphas: &[f64], l1_ratio: f64, spec: &PathSpec) -> Vec<(Vec<f64>, f64)> {
+    let n_f = n as f64;
+    let xj_sq: Vec<f64> = (0..p).map(|j| cols[j * n..(j + 1) * n].iter().map(|v| v * v).sum::<f64>() / n_f).collect();
+    let mut w = vec![0.0; p];
+    let mut b = 0.0;
+    let mut residual = y.to_vec();
+
+    alphas
+        .iter()
+        .map(|&alpha| {
+            let (l1, l2) = (alpha * l1_ratio, alpha * (1.0 - l1_ratio));
+            for _ in 0..spec.max_iter {
+                let shift = residual.iter().sum::<f64>() / n_f;
+                b += shift;
+                residual.iter_mut().for_each(|r| *r -= shift);
+
+                let mut max_change: f64 = 0.0;
+                for j in 0..p {
+                    if xj_sq[j] == 0.0 {
+                        continue;
+                    }
+                    let col = &cols[j * n..(j + 1) * n];
+                    let rho = col.iter().zip(&residual).map(|(a, r)| a * r).sum::<f64>() / n_f + xj_sq[j] * w[j];
+                    let new_w = rho.signum() * (rho.abs() - l1).max(0.0) / (xj_sq[j] + l2);
+                    let delta = new_w - w[j];
+                    if delta != 0.0 {
+                        residual.iter_mut().zip(col).for_each(|(r, a)| *r -= a * delta);
+                        w[j] = new_w;
+                        max_change = max_change.max(delta.abs());
+                    }
+                }
+                if max_change < spec.tol {
+                    break;
+                }
+            }
+            (w.clone(), b)
+        })
+        .collect()
+}
+
+/// Row-major design `x` (`[n, p]`, no intercept column) and targets `y`.
+#[derive(Clone, Copy)]
+struct Design<'a> {
+    x: &'a [f64],
+    n: usize,
+    p: usize,
+    y: &'a [f64],
+}
+
+/// Result of cross-validating one `l1_ratio`: the grid, the fold errors
+/// along it, and the winning index and mean error.
+type PathScores = (Vec<f64>, Vec<Vec<f64>>, usize, f64);
+
+fn cross_validate_path(data: &Design, l1_ratio: f64, alphas: Option<&[f64]>, cv: usize, spec: &PathSpec) -> TensorResult<PathScores> {
+    let Design { x, n, p, y } = *data;
+    let all: Vec<usize> = (0..n).collect();
+    let alphas = match alphas {
+        Some(a) => a.to_vec(),
+        None => alpha_grid(&columns(x, p, &all), n, p, y, l1_ratio, spec)?,
+    };
+    let mut mse_path = vec![Vec::with_capacity(cv); alphas.len()];
+    for (start, end) in fold_ranges(n, cv)? {
+        let train: Vec<usize> = (0..start).chain(end..n).collect();
+        let train_y: Vec<f64> = train.iter().map(|&r| y[r]).collect();
+        let path = enet_path(&columns(x, p, &train), train.len(), p, &train_y, &alphas, l1_ratio, spec);
+        for (errors, (w, b)) in mse_path.iter_mut().zip(&path) {
+            let sse: f64 = (start..end)
+                .map(|r| {
+                    let pred: f64 = b + x[r * p..(r + 1) * p].iter().zip(w).map(|(a, c)| a * c).sum::<f64>();
+                    (y[r] - pred).powi(2)
+                })
+                .sum();
+            errors.push(sse / (end - start) as f64);
+        }
+    }
+    let (best, score) = best_alpha(&mse_path);
+    Ok((alphas, mse_path, best, score))
+}
+
+/// Refit on all rows, walking the path down to `alphas[best]` so the final
+/// fit is warm-started the same way as the folds.
+fn refit<T: Float>(data: &Design, alphas: &[f64], best: usize, l1_ratio: f64, spec: &PathSpec) -> TensorResult<(Tensor<T>, T)> {
+    let Design { x, n, p, y } = *data;
+    let all: Vec<usize> = (0..n).collect();
+    let path = enet_path(&columns(x, p, &all), n, p, y, &alphas[..=best], l1_ratio, spec);
+    let (w, b) = path.into_iter().next_back().unwrap_or((vec![0.0; p], 0.0));
+    Ok((Tensor::new(w.into_iter().map(T::from_f64).collect(), vec![p])?, T::from_f64(b)))
+}
+
+/// Lasso with `alpha` chosen by K-fold cross-validation along a
+/// warm-started regularization path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct LassoCV<T: Float> {
+    /// Length of the automatic grid, from the smallest all-zero `α` down
+    /// by a factor `eps`.
+    pub n_alphas: usize,
+    pub eps: f64,
+    /// Explicit grid, tried in the order given; replaces the automatic one.
+    pub alpha_grid: Option<Vec<f64>>,
+    pub cv: usize,
+    pub max_iter: usize,
+    pub tol: f64,
+    /// The grid used by the last fit.
+    pub alphas: Vec<f64>,
+    /// Validation MSE per alpha and fold, `[n_alphas][cv]`.
+    pub mse_path: Vec<Vec<f64>>,
+    /// The selected penalty.
+    pub alpha: Option<f64>,
+    pub weights: Option<Tensor<T>>,
+    pub bias: Option<T>,
+}
+
+impl<T: Float> LassoCV<T> {
+    pub fn new() -> Self {
+        LassoCV {
+            n_alphas: 100,
+            eps: 1e-3,
+            alpha_grid: None,
+            cv: 5,
+            max_iter: 1000,
+            tol: 1e-6,
+            alphas: Vec::new(),
+            mse_path: Vec::new(),
+            alpha: None,
+            weights: None,
+            bias: None,
+        }
+    }
+
+    pub fn with_n_alphas(mut self, n_alphas: usize, eps: f64) -> Self {
+        self.n_alphas = n_alphas;
+        self.eps = eps;
+        self
+    }
+
+    pub fn with_alphas(mut self, alphas: Vec<f64>) -> Self {
+        self.alpha_grid = Some(alphas);
+        self
+    }
+
+    pub fn with_cv(mut self, cv: usize) -> Self {
+        self.cv = cv;
+        self
+    }
+
+    pub fn with_max_iter(mut self, max_iter: usize, tol: f64) -> Self {
+        self.max_iter = max_iter;
+        self.tol = tol;
+        self
+    }
+
+    fn spec(&self) -> PathSpec {
+        PathSpec { n_alphas: self.n_alphas, eps: self.eps, max_iter: self.max_iter, tol: self.tol }
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (n, p, targets) = check_targets(x, y)?;
+        let (design, _) = design_matrix(x, false)?;
+        let data = Design { x: &design, n, p, y: &targets };
+        let spec = self.spec();
+        let (alphas, mse_path, best, _) = cross_validate_path(&data, 1.0, self.alpha_grid.as_deref(), self.cv, &spec)?;
+        let (weights, bias) = refit(&data, &alphas, best, 1.0, &spec)?;
+        self.alpha = Some(alphas[best]);
+        self.alphas = alphas;
+        self.mse_path = mse_path;
+        self.weights = Some(weights);
+        self.bias = Some(bias);
+        Ok(())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        linear_predict(x, self.weights.as_ref(), self.bias)
+    }
+}
+
+impl<T: Float> Default for LassoCV<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// ElasticNet with `alpha` and `l1_ratio` chosen by K-fold
+/// cross-validation; every candidate `l1_ratio` gets its own
+/// warm-started path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct ElasticNetCV<T: Float> {
+    /// Candidate L1 mixing ratios, each in `[0, 1]`.
+    pub l1_ratios: Vec<f64>,
+    pub n_alphas: usize,
+    pub eps: f64,
+    /// Explicit grid shared by every `l1_ratio`; replaces the automatic one.
+    pub alpha_grid: Option<Vec<f64>>,
+    pub cv: usize,
+    pub max_iter: usize,
+    pub tol: f64,
+    /// The grid used for each `l1_ratio`, `[n_l1_ratios][n_alphas]`.
+    pub alphas: Vec<Vec<f64>>,
+    /// Validation MSE, `[n_l1_ratios][n_alphas][cv]`.
+    pub mse_path: Vec<Vec<Vec<f64>>>,
+    /// The selected penalty and mixing ratio.
+    pub alpha: Option<f64>,
+    pub l1_ratio: Option<f64>,
+    pub weights: Option<Tensor<T>>,
+    pub bias: Option<T>,
+}
+
+impl<T: Float> ElasticNetCV<T> {
+    pub fn new(l1_ratios: Vec<f64>) -> Self {
+        ElasticNetCV {
+            l1_ratios,
+            n_alphas: 100,
+            eps: 1e-3,
+            alpha_grid: None,
+            cv: 5,
+            max_iter: 1000,
+            tol: 1e-6,
+            alphas: Vec::new(),
+            mse_path: Vec::new(),
+            alpha: None,
+            l1_ratio: None,
+            weights: None,
+            bias: None,
+        }
+    }
+
+    pub fn with_n_alphas(mut self, n_alphas: usize, eps: f64) -> Self {
+        self.n_alphas = n_alphas;
+        self.eps = eps;
+        self
+    }
+
+    pub fn with_alphas(mut self, alphas: Vec<f64>) -> Self {
+        self.alpha_grid = Some(alphas);
+        self
+    }
+
+    pub fn with_cv(mut self, cv: usize) -> Self {
+        self.cv = cv;
+        self
+    }
+
+    pub fn with_max_iter(mut self, max_iter: usize, tol: f64) -> Self {
+        self.max_iter = max_iter;
+        self.tol = tol;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (n, p, targets) = check_targets(x, y)?;
+        if self.l1_ratios.is_empty() || self.l1_ratios.iter().any(|r| !(0.0..=1.0).contains(r)) {
+            return Err(TensorError::InvalidOperation("l1_ratios must be non-empty and within [0, 1]".into()));
+        }
+        let (design, _) = design_matrix(x, false)?;
+        let data = Design { x: &design, n, p, y: &targets };
+        let spec = PathSpec { n_alphas: self.n_alphas, eps: self.eps, max_iter: self.max_iter, tol: self.tol };
+
+        let mut alphas = Vec::with_capacity(self.l1_ratios.len());
+        let mut mse_path = Vec::with_capacity(self.l1_ratios.len());
+        let mut best = (0, 0, f64::INFINITY);
+        for (r, &l1_ratio) in self.l1_ratios.iter().enumerate() {
+            let (grid, errors, idx, score) =
+                cross_validate_path(&data, l1_ratio, self.alpha_grid.as_deref(), self.cv, &spec)?;
+            if score < best.2 {
+                best = (r, idx, score);
+            }
+            alphas.push(grid);
+            mse_path.push(errors);
+        }
+
+        let (r, idx, _) = best;
+        let l1_ratio = self.l1_ratios[r];
+        let (weights, bias) = refit(&data, &alphas[r], idx, l1_ratio, &spec)?;
+        self.alpha = Some(alphas[r][idx]);
+        self.l1_ratio = Some(l1_ratio);
+        self.alphas = alphas;
+        self.mse_path = mse_path;
+        self.weights = Some(weights);
+        self.bias = Some(bias);
+        Ok(())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        linear_predict(x, self.weights.as_ref(), self.bias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Lasso, Ridge};
+
+    /// y = 3·x0 - 2·x1 + 1 plus small noise; x2 and x3 are irrelevant.
+    fn regression_data() -> (Tensor<f64>, Tensor<f64>) {
+        let rows: Vec<Vec<f64>> = (0..40)
+            .map(|i| {
+                let t = i as f64;
+                vec![(t * 0.37).sin() * 2.0, (t * 0.71).cos(), ((i * 7) % 13) as f64 / 6.0 - 1.0, ((i * 5) % 9) as f64 / 4.0 - 1.0]
+            })
+            .collect();
+        let y: Vec<f64> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, r)| 3.0 * r[0] - 2.0 * r[1] + 1.0 + ((i * 37) % 11) as f64 * 0.02 - 0.1)
+            .collect();
+        (Tensor::from_vec2d(&rows).unwrap(), Tensor::from_slice(&y))
+    }
+
+    #[test]
+    fn test_ridge_cv_matches_ridge_at_selected_alpha() {
+        let (x, y) = regression_data();
+        let mut model = RidgeCV::new(true).with_cv(4);
+        model.fit(&x, &y).unwrap();
+        assert_eq!(model.mse_path.len(), 7);
+        assert!(model.mse_path.iter().all(|folds| folds.len() == 4));
+
+        let alpha = model.alpha.unwrap();
+        assert_eq!(model.alphas[best_alpha(&model.mse_path).0], alpha);
+        assert!(alpha <= 1.0, "low-noise data should pick a small alpha, got {}", alpha);
+        let mut ridge = Ridge::new(alpha, true);
+        ridge.fit(&x, &y).unwrap();
+        for (a, b) in model.predict(&x).unwrap().data().iter().zip(ridge.predict(&x).unwrap().data()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_lasso_cv_path() {
+        let (x, y) = regression_data();
+        let mut model = LassoCV::new().with_n_alphas(30, 1e-3).with_cv(5);
+        model.fit(&x, &y).unwrap();
+        assert_eq!(model.alphas.len(), 30);
+        assert!(model.alphas.windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(model.mse_path.len(), 30);
+
+        // The largest alpha zeroes every coefficient.
+        let mut top = Lasso::new(model.alphas[0] * 1.0001, 1000);
+        top.fit(&x, &y).unwrap();
+        assert!(top.weights.as_ref().unwrap().data().iter().all(|w| w.abs() < 1e-10));
+
+        // Warm-started refit agrees with a cold Lasso fit at the chosen alpha.
+        let mut lasso = Lasso::new(model.alpha.unwrap(), 5000);
+        lasso.tol = 1e-10;
+        lasso.fit(&x, &y).unwrap();
+        let w = model.weights.as_ref().unwrap().data();
+        for (a, b) in w.iter().zip(lasso.weights.as_ref().unwrap().data()) {
+            assert!((a - b).abs() < 1e-4, "{:?} vs {:?}", w, lasso.weights);
+        }
+        assert!((w[0] - 3.0).abs() < 0.2 && (w[1] + 2.0).abs() < 0.2);
+        assert!(w[2].abs() < 0.1 && w[3].abs() < 0.1);
+    }
+
+    #[test]
+    fn test_elastic_net_cv() {
+        let (x, y) = regression_data();
+        let mut model = ElasticNetCV::new(vec![0.2, 0.9]).with_n_alphas(20, 1e-3).with_cv(4);
+        model.fit(&x, &y).unwrap();
+        assert_eq!(model.alphas.len(), 2);
+        assert_eq!(model.mse_path[1].len(), 20);
+        assert!(model.mse_path[0].iter().all(|folds| folds.len() == 4));
+        // The automatic grid scales with 1 / l1_ratio.
+        assert!((model.alphas[0][0] * 0.2 - model.alphas[1][0] * 0.9).abs() < 1e-9);
+
+        let pred = model.predict(&x).unwrap();
+        let mse: f64 = pred.data().iter().zip(y.data()).map(|(a, b)| (a - b).powi(2)).sum::<f64>() / 40.0;
+        assert!(mse < 0.05, "mse {}", mse);
+        assert!(model.l1_ratios.contains(&model.l1_ratio.unwrap()));
+        assert!(ElasticNetCV::<f64>::new(vec![1.5]).fit(&x, &y).is_err());
+    }
+}
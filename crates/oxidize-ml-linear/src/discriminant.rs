@@ -0,0 +1,559 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+use oxidize_ml_linalg::{solve_triangular, Triangle};
+
+/// Shrinkage of covariance estimates toward a scaled identity,
+/// `(1 - λ) Σ + λ (tr Σ / p) I`. Helps when there are few samples per
+/// feature, and makes singular covariances invertible.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Shrinkage {
+    /// The empirical covariance.
+    #[default]
+    None,
+    /// A fixed `λ` in `[0, 1]`.
+    Fixed(f64),
+    /// `λ` estimated from the data by the Ledoit–Wolf formula.
+    LedoitWolf,
+}
+
+/// Covariance `[p, p]` of the centered rows `x` (`[n, p]`), divided by
+/// `n - ddof`, then shrunk.
+fn covariance(x: &[f64], n: usize, p: usize, ddof: usize, shrinkage: Shrinkage) -> TensorResult<Vec<f64>> {
+    let mut cov = vec![0.0; p * p];
+    for row in x.chunks(p.max(1)).take(n) {
+        for i in 0..p {
+            for j in 0..=i {
+                cov[i * p + j] += row[i] * row[j];
+            }
+        }
+    }
+    let denom = n.saturating_sub(ddof).max(1) as f64;
+    for i in 0..p {
+        for j in 0..=i {
+            cov[i * p + j] /= denom;
+            cov[j * p + i] = cov[i * p + j];
+        }
+    }
+    let lambda = match shrinkage {
+        Shrinkage::None => return Ok(cov),
+        Shrinkage::Fixed(lambda) if (0.0..=1.0).contains(&lambda) => lambda,
+        Shrinkage::Fixed(lambda) => {
+            return Err(TensorError::InvalidOperation(format!("shrinkage must be in [0, 1], got {}", lambda)))
+        }
+        Shrinkage::LedoitWolf => ledoit_wolf(x, n, p),
+    };
+    let mu = (0..p).map(|i| cov[i * p + i]).sum::<f64>() / p as f64;
+    cov.iter_mut().for_each(|c| *c *= 1.0 - lambda);
+    for i in 0..p {
+        cov[i * p + i] += lambda * mu;
+    }
+    Ok(cov)
+}
+
+/// Ledoit–Wolf shrinkage intensity for centered rows `x`.
+fn ledoit_wolf(x: &[f64], n: usize, p: usize) -> f64 {
+    let nf = n as f64;
+    let mut emp = vec![0.0; p * p];
+    let mut sq = vec![0.0; p * p];
+    for row in x.chunks(p.max(1)).take(n) {
+        for i in 0..p {
+            for j in 0..p {
+                emp[i * p + j] += row[i] * row[j];
+                sq[i * p + j] += row[i] * row[i] * row[j] * row[j];
+            }
+        }
+    }
+    let mu = (0..p).map(|i| emp[i * p + i]).sum::<f64>() / (nf * p as f64);
+    let delta_: f64 = emp.iter().map(|v| v * v).sum::<f64>() / (nf * nf);
+    let beta_: f64 = sq.iter().sum();
+    let beta = (beta_ / nf - delta_) / (p as f64 * nf);
+    let delta = (delta_ - 2.0 * mu * mu * p as f64 + p as f64 * mu * mu) / p as f64;
+    let beta = beta.min(delta);
+    if beta <= 0.0 {
+        0.0
+    } else {
+        beta / delta
+    }
+}
+
+/// Cholesky factor `L` (row-major `[p, p]`) of `cov`. Pivots below `1e-10`
+/// of the largest variance count as singular, since rounding leaves
+/// exactly singular covariances barely positive.
+fn cholesky_factor(cov: Vec<f64>, p: usize) -> TensorResult<Vec<f64>> {
+    let scale = (0..p).map(|i| cov[i * p + i]).fold(0.0, f64::max);
+    let singular = || TensorError::InvalidOperation("covariance matrix is singular; use shrinkage".into());
+    let l = oxidize_ml_linalg::cholesky(&Tensor::new(cov, vec![p, p])?).map_err(|_| singular())?.l.data().to_vec();
+    if (0..p).any(|i| l[i * p + i] * l[i * p + i] <= 1e-10 * scale) {
+        return Err(singular());
+    }
+    Ok(l)
+}
+
+/// Solve `L Z = B`, or `Lᵀ Z = B` with `transpose`, for the `p × p`
+/// Cholesky factor `l` and row-major `B` of shape `[p, k]`.
+fn solve_factor(l: &[f64], p: usize, b: Vec<f64>, k: usize, transpose: bool) -> TensorResult<Vec<f64>> {
+    let l = Tensor::new(l.to_vec(), vec![p, p])?;
+    Ok(solve_triangular(&l, &Tensor::new(b, vec![p, k])?, Triangle::Lower, transpose)?.data().to_vec())
+}
+
+/// Sorted distinct labels of `y` and the class index of each row.
+fn encode_classes<T: Float>(y: &Tensor<T>, n: usize) -> TensorResult<(Vec<T>, Vec<usize>)> {
+    if y.numel() != n {
+        return Err(TensorError::DimensionMismatch(format!("x has {} rows but y has {} labels", n, y.numel())));
+    }
+    let mut classes = y.data().to_vec();
+    classes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    classes.dedup();
+    if classes.len() < 2 {
+        return Err(TensorError::InvalidOperation("discriminant analysis needs at least two classes".into()));
+    }
+    let labels = y.data().iter().map(|v| classes.iter().position(|c| c == v).unwrap()).collect();
+    Ok((classes, labels))
+}
+
+/// Class priors: the given ones, or the class frequencies.
+fn class_priors(given: Option<&[f64]>, counts: &[usize], n: usize) -> TensorResult<Vec<f64>> {
+    match given {
+        Some(priors) if priors.len() != counts.len() => Err(TensorError::InvalidOperation(format!(
+            "{} priors given for {} classes", priors.len(), counts.len()
+        ))),
+        Some(priors) => {
+            let total: f64 = priors.iter().sum();
+            Ok(priors.iter().map(|p| p / total).collect())
+        }
+        None => Ok(counts.iter().map(|&c| c as f64 / n as f64).collect()),
+    }
+}
+
+/// Per-class means `[k][p]` and counts.
+fn class_means(x: &[f64], labels: &[usize], k: usize, p: usize) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut means = vec![vec![0.0; p]; k];
+    let mut counts = vec![0usize; k];
+    for (row, &c) in x.chunks(p.max(1)).zip(labels) {
+        counts[c] += 1;
+        means[c].iter_mut().zip(row).for_each(|(m, v)| *m += v);
+    }
+    for (m, &c) in means.iter_mut().zip(&counts) {
+        m.iter_mut().for_each(|v| *v /= c as f64);
+    }
+    (means, counts)
+}
+
+/// Row-wise softmax of `[n, k]` scores.
+fn softmax_rows<T: Float>(scores: &[f64], n: usize, k: usize) -> TensorResult<Tensor<T>> {
+    let mut out = Vec::with_capacity(n * k);
+    for row in scores.chunks(k) {
+        let max = row.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let exps: Vec<f64> = row.iter().map(|s| (s - max).exp()).collect();
+        let sum: f64 = exps.iter().sum();
+        out.extend(exps.iter().map(|e| T::from_f64(e / sum)));
+    }
+    Tensor::new(out, vec![n, k])
+}
+
+fn argmax_labels<T: Float>(scores: &[f64], k: usize, classes: &[T]) -> TensorResult<Tensor<T>> {
+    let labels: Vec<T> = scores
+        .chunks(k)
+        .map(|row| classes[(0..k).fold(0, |best, c| if row[c] > row[best] { c } else { best })])
+        .collect();
+    let n = labels.len();
+    Tensor::new(labels, vec![n])
+}
+
+fn check_features<T: Float>(x: &Tensor<T>, p: usize) -> TensorResult<usize> {
+    let n = x.shape().dim(0)?;
+    if x.shape().dim(1)? != p {
+        return Err(TensorError::ShapeMismatch { expected: vec![n, p], got: x.shape_vec() });
+    }
+    Ok(n)
+}
+
+/// Linear Discriminant Analysis.
+///
+/// Models each class as a Gaussian with its own mean and a covariance
+/// shared by all classes, which gives linear decision boundaries. Also a
+/// supervised dimensionality reduction: `transform` projects onto the
+/// `n_components ≤ k - 1` directions that best separate the class means
+/// relative to the within-class spread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct LinearDiscriminantAnalysis<T: Float> {
+    pub shrinkage: Shrinkage,
+    /// Class priors, in sorted label order; `None` uses class frequencies.
+    pub priors: Option<Vec<f64>>,
+    /// Output dimensions of `transform`; `None` keeps `min(k - 1, p)`.
+    pub n_components: Option<usize>,
+    // Fitted state
+    pub classes: Option<Vec<T>>,
+    /// Class means, `[k, p]`.
+    pub means: Option<Tensor<T>>,
+    /// Shared within-class covariance, `[p, p]`.
+    pub covariance: Option<Tensor<T>>,
+    /// Decision weights, `[k, p]`.
+    pub coef: Option<Tensor<T>>,
+    pub intercept: Option<Tensor<T>>,
+    /// Projection for `transform`, `[p, n_components]`.
+    pub scalings: Option<Tensor<T>>,
+    /// Between-class variance captured by each projected dimension.
+    pub explained_variance_ratio: Option<Vec<f64>>,
+    xbar: Vec<f64>,
+}
+
+impl<T: Float> LinearDiscriminantAnalysis<T> {
+    pub fn new() -> Self {
+        LinearDiscriminantAnalysis {
+            shrinkage: Shrinkage::None,
+            priors: None,
+            n_components: None,
+            classes: None,
+            means: None,
+            covariance: None,
+            coef: None,
+            intercept: None,
+            scalings: None,
+            explained_variance_ratio: None,
+            xbar: Vec::new(),
+        }
+    }
+
+    pub fn with_shrinkage(mut self, shrinkage: Shrinkage) -> Self {
+        self.shrinkage = shrinkage;
+        self
+    }
+
+    pub fn with_priors(mut self, priors: Vec<f64>) -> Self {
+        self.priors = Some(priors);
+        self
+    }
+
+    pub fn with_n_components(mut self, n_components: usize) -> Self {
+        self.n_components = Some(n_components);
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (n, p) = (x.shape().dim(0)?, x.shape().dim(1)?);
+        let (classes, labels) = encode_classes(y, n)?;
+        let k = classes.len();
+        let xs: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+        let (means, counts) = class_means(&xs, &labels, k, p);
+        let priors = class_priors(self.priors.as_deref(), &counts, n)?;
+
+        // Shared covariance: prior-weighted average of the class covariances.
+        let mut cov = vec![0.0; p * p];
+        for c in 0..k {
+            let centered: Vec<f64> = xs
+                .chunks(p.max(1))
+                .zip(&labels)
+                .filter(|(_, &l)| l == c)
+                .flat_map(|(row, _)| row.iter().zip(&means[c]).map(|(v, m)| v - m))
+                .collect();
+            let class_cov = covariance(&centered, counts[c], p, 0, self.shrinkage)?;
+            cov.iter_mut().zip(class_cov).for_each(|(s, v)| *s += priors[c] * v);
+        }
+        let l = cholesky_factor(cov.clone(), p)?;
+
+        // w_c = Σ⁻¹ μ_c, b_c = -½ μ_cᵀ Σ⁻¹ μ_c + ln π_c
+        let mut coef = Vec::with_capacity(k * p);
+        let mut intercept = Vec::with_capacity(k);
+        let means_t: Vec<f64> = (0..p).flat_map(|i| means.iter().map(move |m| m[i])).collect();
+        let w_t = solve_factor(&l, p, solve_factor(&l, p, means_t, k, false)?, k, true)?;
+        for c in 0..k {
+            let w: Vec<f64> = (0..p).map(|i| w_t[i * k + c]).collect();
+            let quad: f64 = w.iter().zip(&means[c]).map(|(a, b)| a * b).sum();
+            intercept.push(-0.5 * quad + priors[c].ln());
+            coef.extend(w);
+        }
+
+        // Discriminant directions: eigenvectors of L⁻¹ S_b L⁻ᵀ, mapped back
+        // by L⁻ᵀ. The matrix is symmetric PSD, so its SVD is its
+        // eigendecomposition.
+        let xbar: Vec<f64> = (0..p).map(|j| (0..k).map(|c| priors[c] * means[c][j]).sum()).collect();
+        let centered_t: Vec<f64> = (0..p).flat_map(|i| means.iter().map(|m| m[i] - xbar[i]).collect::<Vec<_>>()).collect();
+        let z = solve_factor(&l, p, centered_t, k, false)?;
+        let mut between = vec![0.0; p * p];
+        for c in 0..k {
+            for i in 0..p {
+                for j in 0..p {
+                    between[i * p + j] += priors[c] * z[i * k + c] * z[j * k + c];
+                }
+            }
+        }
+        let (u, sigma, _) = oxidize_ml_linalg::thin_svd(&Tensor::new(between, vec![p, p])?)?;
+        let rank = (k - 1).min(p);
+        let d = self.n_components.unwrap_or(rank).min(rank);
+        let directions: Vec<f64> = (0..p).flat_map(|i| u.data()[i * p..i * p + d].to_vec()).collect();
+        let scalings = solve_factor(&l, p, directions, d, true)?;
+        let total: f64 = sigma.data()[..rank].iter().sum();
+        self.explained_variance_ratio = Some(sigma.data()[..d].iter().map(|s| if total > 0.0 { s / total } else { 0.0 }).collect());
+
+        let to_t = |v: Vec<f64>| v.into_iter().map(T::from_f64).collect::<Vec<T>>();
+        self.means = Some(Tensor::new(to_t(means.concat()), vec![k, p])?);
+        self.covariance = Some(Tensor::new(to_t(cov), vec![p, p])?);
+        self.coef = Some(Tensor::new(to_t(coef), vec![k, p])?);
+        self.intercept = Some(Tensor::new(to_t(intercept), vec![k])?);
+        self.scalings = Some(Tensor::new(to_t(scalings), vec![p, d])?);
+        self.classes = Some(classes);
+        self.xbar = xbar;
+        Ok(())
+    }
+
+    /// Per-class linear scores `[n, k]`: log posterior up to a constant.
+    pub fn decision_function(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let (coef, intercept) = match (&self.coef, &self.intercept) {
+            (Some(c), Some(i)) => (c, i),
+            _ => return Err(TensorError::InvalidOperation("LinearDiscriminantAnalysis is not fitted".into())),
+        };
+        let (k, p) = (coef.shape().dim(0)?, coef.shape().dim(1)?);
+        let n = check_features(x, p)?;
+        let mut scores = Vec::with_capacity(n * k);
+        for row in x.data().chunks(p.max(1)).take(n) {
+            for c in 0..k {
+                let w = &coef.data()[c * p..(c + 1) * p];
+                scores.push(row.iter().zip(w).map(|(&a, &b)| a * b).sum::<T>() + intercept.data()[c]);
+            }
+        }
+        Tensor::new(scores, vec![n, k])
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let scores: Vec<f64> = self.decision_function(x)?.data().iter().map(|v| v.to_f64()).collect();
+        let classes = self.classes.as_ref().unwrap();
+        argmax_labels(&scores, classes.len(), classes)
+    }
+
+    /// Class probabilities `[n, k]`, in sorted label order.
+    pub fn predict_proba(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let scores = self.decision_function(x)?;
+        let (n, k) = (scores.shape().dim(0)?, scores.shape().dim(1)?);
+        softmax_rows(&scores.data().iter().map(|v| v.to_f64()).collect::<Vec<_>>(), n, k)
+    }
+
+    /// Project `x` onto the discriminant directions, `[n, n_components]`.
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let scalings = self
+            .scalings
+            .as_ref()
+            .ok_or_else(|| TensorError::InvalidOperation("LinearDiscriminantAnalysis is not fitted".into()))?;
+        let (p, d) = (scalings.shape().dim(0)?, scalings.shape().dim(1)?);
+        let n = check_features(x, p)?;
+        let mut out = vec![T::ZERO; n * d];
+        for (i, row) in x.data().chunks(p.max(1)).take(n).enumerate() {
+            for j in 0..p {
+                let v = T::from_f64(row[j].to_f64() - self.xbar[j]);
+                for c in 0..d {
+                    out[i * d + c] += v * scalings.data()[j * d + c];
+                }
+            }
+        }
+        Tensor::new(out, vec![n, d])
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x, y)?;
+        self.transform(x)
+    }
+}
+
+impl<T: Float> Default for LinearDiscriminantAnalysis<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Quadratic Discriminant Analysis.
+///
+/// Models each class as a Gaussian with its own mean and covariance, which
+/// gives quadratic decision boundaries. Needs more samples per class than
+/// LDA; `shrinkage` regularizes each class covariance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct QuadraticDiscriminantAnalysis<T: Float> {
+    pub shrinkage: Shrinkage,
+    /// Class priors, in sorted label order; `None` uses class frequencies.
+    pub priors: Option<Vec<f64>>,
+    // Fitted state
+    pub classes: Option<Vec<T>>,
+    /// Class means, `[k, p]`.
+    pub means: Option<Tensor<T>>,
+    /// Class covariances, each `[p, p]`.
+    pub covariances: Vec<Tensor<T>>,
+    n_features: usize,
+    log_priors: Vec<f64>,
+    /// Cholesky factor of each class covariance.
+    factors: Vec<Vec<f64>>,
+    log_dets: Vec<f64>,
+}
+
+impl<T: Float> QuadraticDiscriminantAnalysis<T> {
+    pub fn new() -> Self {
+        QuadraticDiscriminantAnalysis {
+            shrinkage: Shrinkage::None,
+            priors: None,
+            classes: None,
+            means: None,
+            covariances: Vec::new(),
+            n_features: 0,
+            log_priors: Vec::new(),
+            factors: Vec::new(),
+            log_dets: Vec::new(),
+        }
+    }
+
+    pub fn with_shrinkage(mut self, shrinkage: Shrinkage) -> Self {
+        self.shrinkage = shrinkage;
+        self
+    }
+
+    pub fn with_priors(mut self, priors: Vec<f64>) -> Self {
+        self.priors = Some(priors);
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (n, p) = (x.shape().dim(0)?, x.shape().dim(1)?);
+        let (classes, labels) = encode_classes(y, n)?;
+        let k = classes.len();
+        let xs: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+        let (means, counts) = class_means(&xs, &labels, k, p);
+        let priors = class_priors(self.priors.as_deref(), &counts, n)?;
+
+        self.covariances.clear();
+        self.factors.clear();
+        self.log_dets.clear();
+        for c in 0..k {
+            let centered: Vec<f64> = xs
+                .chunks(p.max(1))
+                .zip(&labels)
+                .filter(|(_, &l)| l == c)
+                .flat_map(|(row, _)| row.iter().zip(&means[c]).map(|(v, m)| v - m))
+                .collect();
+            let cov = covariance(&centered, counts[c], p, 1, self.shrinkage)?;
+            let l = cholesky_factor(cov.clone(), p)?;
+            self.log_dets.push(2.0 * (0..p).map(|i| l[i * p + i].ln()).sum::<f64>());
+            self.factors.push(l);
+            self.covariances.push(Tensor::new(cov.into_iter().map(T::from_f64).collect(), vec![p, p])?);
+        }
+        self.means = Some(Tensor::new(means.concat().into_iter().map(T::from_f64).collect(), vec![k, p])?);
+        self.log_priors = priors.iter().map(|p| p.ln()).collect();
+        self.n_features = p;
+        self.classes = Some(classes);
+        Ok(())
+    }
+
+    /// Per-class log posteriors `[n, k]`, up to a shared constant.
+    pub fn decision_function(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let means = self
+            .means
+            .as_ref()
+            .ok_or_else(|| TensorError::InvalidOperation("QuadraticDiscriminantAnalysis is not fitted".into()))?;
+        let p = self.n_features;
+        let k = self.factors.len();
+        let n = check_features(x, p)?;
+        let x = x.data();
+        let mut scores = vec![T::ZERO; n * k];
+        for c in 0..k {
+            // Columns of Z = L_c⁻¹ (x_r - μ_c) give each row's Mahalanobis distance.
+            let diff_t: Vec<f64> = (0..p)
+                .flat_map(|j| (0..n).map(move |r| x[r * p + j].to_f64() - means.data()[c * p + j].to_f64()))
+                .collect();
+            let z = solve_factor(&self.factors[c], p, diff_t, n, false)?;
+            for r in 0..n {
+                let mahalanobis: f64 = (0..p).map(|j| z[j * n + r].powi(2)).sum();
+                scores[r * k + c] = T::from_f64(-0.5 * (self.log_dets[c] + mahalanobis) + self.log_priors[c]);
+            }
+        }
+        Tensor::new(scores, vec![n, k])
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let scores: Vec<f64> = self.decision_function(x)?.data().iter().map(|v| v.to_f64()).collect();
+        let classes = self.classes.as_ref().unwrap();
+        argmax_labels(&scores, classes.len(), classes)
+    }
+
+    /// Class probabilities `[n, k]`, in sorted label order.
+    pub fn predict_proba(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let scores = self.decision_function(x)?;
+        let (n, k) = (scores.shape().dim(0)?, scores.shape().dim(1)?);
+        softmax_rows(&scores.data().iter().map(|v| v.to_f64()).collect::<Vec<_>>(), n, k)
+    }
+}
+
+impl<T: Float> Default for QuadraticDiscriminantAnalysis<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three elongated blobs in 3-D; the third feature is noise.
+    fn blobs() -> (Tensor<f64>, Tensor<f64>) {
+        let centers = [(0.0, 0.0), (4.0, 1.0), (1.0, 4.0)];
+        let (mut rows, mut labels) = (Vec::new(), Vec::new());
+        for i in 0..60 {
+            let c = i % 3;
+            let t = (i / 3) as f64;
+            let (dx, dy) = ((t * 0.37).sin() * 1.2, (t * 0.91).cos() * 0.5);
+            rows.push(vec![centers[c].0 + dx, centers[c].1 + dy, (t * 1.7).sin()]);
+            labels.push(c as f64 * 2.0);
+        }
+        (Tensor::from_vec2d(&rows).unwrap(), Tensor::new(labels, vec![60]).unwrap())
+    }
+
+    #[test]
+    fn test_lda_classifies_and_projects() {
+        let (x, y) = blobs();
+        for shrinkage in [Shrinkage::None, Shrinkage::Fixed(0.2), Shrinkage::LedoitWolf] {
+            let mut lda = LinearDiscriminantAnalysis::new().with_shrinkage(shrinkage);
+            lda.fit(&x, &y).unwrap();
+            assert_eq!(lda.predict(&x).unwrap().data(), y.data());
+            let proba = lda.predict_proba(&x).unwrap();
+            assert!(proba.data().chunks(3).all(|r| (r.iter().sum::<f64>() - 1.0).abs() < 1e-9));
+        }
+
+        let mut lda = LinearDiscriminantAnalysis::new();
+        let z = lda.fit_transform(&x, &y).unwrap();
+        assert_eq!(z.shape_vec(), vec![60, 2]);
+        let ratio = lda.explained_variance_ratio.clone().unwrap();
+        assert!((ratio.iter().sum::<f64>() - 1.0).abs() < 1e-9 && ratio[0] >= ratio[1]);
+        // Projected data has unit pooled within-class variance per axis.
+        let labels = y.data();
+        for axis in 0..2 {
+            let mut within = 0.0;
+            for c in [0.0, 2.0, 4.0] {
+                let vals: Vec<f64> = (0..60).filter(|&i| labels[i] == c).map(|i| z.data()[i * 2 + axis]).collect();
+                let mean = vals.iter().sum::<f64>() / vals.len() as f64;
+                within += vals.iter().map(|v| (v - mean).powi(2)).sum::<f64>();
+            }
+            assert!((within / 60.0 - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_qda() {
+        // Same centre, different spreads: only QDA can separate them.
+        let (mut rows, mut labels) = (Vec::new(), Vec::new());
+        for i in 0..40 {
+            let angle = i as f64 * 0.7;
+            let radius = if i % 2 == 0 { 0.5 } else { 3.0 };
+            rows.push(vec![radius * angle.cos(), radius * angle.sin()]);
+            labels.push((i % 2) as f64);
+        }
+        let x = Tensor::from_vec2d(&rows).unwrap();
+        let y = Tensor::new(labels, vec![40]).unwrap();
+        let mut qda = QuadraticDiscriminantAnalysis::new().with_shrinkage(Shrinkage::Fixed(0.1));
+        qda.fit(&x, &y).unwrap();
+        assert_eq!(qda.predict(&x).unwrap().data(), y.data());
+        assert_eq!(qda.predict_proba(&x).unwrap().shape_vec(), vec![40, 2]);
+
+        let singular = Tensor::from_vec2d(&[vec![0.0, 0.0], vec![1.0, 1.0], vec![5.0, 5.0], vec![6.0, 6.0]]).unwrap();
+        let y = Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0]);
+        assert!(QuadraticDiscriminantAnalysis::new().fit(&singular, &y).is_err());
+        assert!(QuadraticDiscriminantAnalysis::new().with_shrinkage(Shrinkage::Fixed(0.1)).fit(&singular, &y).is_ok());
+    }
+}
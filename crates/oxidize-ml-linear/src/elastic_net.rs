@@ -1,5 +1,6 @@
 use oxidize_ml_core::{Float, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
 
 /// ElasticNet regression combines L1 (Lasso) and L2 (Ridge) penalties.
 ///
@@ -7,6 +8,8 @@ use oxidize_ml_core::error::TensorResult;
 ///
 /// When l1_ratio = 1.0, equivalent to Lasso.
 /// When l1_ratio = 0.0, equivalent to Ridge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct ElasticNet<T: Float> {
     pub alpha: T,
     pub l1_ratio: T,
@@ -46,11 +49,11 @@ impl<T: Float> ElasticNet<T> {
             for i in 0..n {
                 let mut pred = b;
                 for j in 0..p {
-                    pred = pred + x.get(&[i, j])? * w[j];
+                    pred += x.get(&[i, j])? * w[j];
                 }
-                residual_sum = residual_sum + (y.data()[i] - pred);
+                residual_sum += y.data()[i] - pred ;
             }
-            b = b + residual_sum / n_t;
+            b += residual_sum / n_t;
 
             // Coordinate descent for each feature
             for j in 0..p {
@@ -59,20 +62,20 @@ impl<T: Float> ElasticNet<T> {
                     let mut pred = b;
                     for k in 0..p {
                         if k != j {
-                            pred = pred + x.get(&[i, k])? * w[k];
+                            pred += x.get(&[i, k])? * w[k];
                         }
                     }
                     let residual = y.data()[i] - pred;
-                    rho = rho + x.get(&[i, j])? * residual;
+                    rho += x.get(&[i, j])? * residual;
                 }
-                rho = rho / n_t;
+                rho /= n_t;
 
                 let mut xj_sq = T::ZERO;
                 for i in 0..n {
                     let xij = x.get(&[i, j])?;
-                    xj_sq = xj_sq + xij * xij;
+                    xj_sq += xij * xij;
                 }
-                xj_sq = xj_sq / n_t;
+                xj_sq /= n_t;
 
                 // Soft thresholding with L1 + L2 penalty
                 let denom = xj_sq + l2_penalty;
@@ -117,6 +120,8 @@ impl<T: Float> ElasticNet<T> {
 /// Perceptron — a simple linear binary classifier.
 ///
 /// Uses the perceptron update rule: if misclassified, w += η·y·x
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct Perceptron<T: Float> {
     pub weights: Option<Tensor<T>>,
     pub bias: Option<T>,
@@ -142,7 +147,7 @@ impl<T: Float> Perceptron<T> {
                 // Compute prediction: sign(w·x + b)
                 let mut score = b;
                 for j in 0..p {
-                    score = score + w[j] * x.get(&[i, j])?;
+                    score += w[j] * x.get(&[i, j])?;
                 }
                 let pred = if score >= T::ZERO { T::ONE } else { T::ZERO };
                 let yi = y.data()[i];
@@ -151,9 +156,9 @@ impl<T: Float> Perceptron<T> {
                     // Misclassified: update
                     let label = if yi > T::HALF { T::ONE } else { T::NEG_ONE };
                     for j in 0..p {
-                        w[j] = w[j] + self.learning_rate * label * x.get(&[i, j])?;
+                        w[j] += self.learning_rate * label * x.get(&[i, j])?;
                     }
-                    b = b + self.learning_rate * label;
+                    b += self.learning_rate * label;
                     errors += 1;
                 }
             }
@@ -176,7 +181,7 @@ impl<T: Float> Perceptron<T> {
         for i in 0..n {
             let mut score = self.bias.unwrap_or(T::ZERO);
             for j in 0..p {
-                score = score + w.data()[j] * x.get(&[i, j])?;
+                score += w.data()[j] * x.get(&[i, j])?;
             }
             preds.push(if score >= T::ZERO { T::ONE } else { T::ZERO });
         }
@@ -1,7 +1,17 @@
 pub mod regression;
 pub mod logistic;
 pub mod elastic_net;
+pub mod discriminant;
+pub mod glm;
+pub mod robust;
+pub mod solver;
+pub mod cv;
 
 pub use regression::*;
 pub use logistic::*;
 pub use elastic_net::*;
+pub use discriminant::*;
+pub use glm::*;
+pub use robust::*;
+pub use solver::LinearSolver;
+pub use cv::*;
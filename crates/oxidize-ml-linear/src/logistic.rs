@@ -1,14 +1,44 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{sample_weights, ClassWeight, Float, RngHandle, SparseTensor, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 
-/// Logistic Regression — binary classification via gradient descent.
+/// How `LogisticRegression` handles more than two classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MultiClass {
+    /// One softmax model trained on the cross-entropy loss.
+    Multinomial,
+    /// One binary model per class (one-vs-rest).
+    Ovr,
+}
+
+/// Logistic Regression via gradient descent.
+///
+/// Two-class targets (labels 0/1) fit a single sigmoid model into `weights`
+/// and `bias`. With more classes, `multi_class` picks a softmax model or
+/// one-vs-rest; the fitted parameters go into `coef` and `intercept`, and
+/// `predict` returns labels from `classes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct LogisticRegression<T: Float> {
     pub weights: Option<Tensor<T>>,
     pub bias: Option<T>,
     pub learning_rate: T,
     pub max_iter: usize,
     pub tol: T,
+    pub multi_class: MultiClass,
+    /// Rows per gradient step in multinomial mode; `None` is full-batch.
+    pub batch_size: Option<usize>,
+    /// Seed for mini-batch shuffling.
+    pub seed: Option<u64>,
+    /// Sorted class labels of a multi-class fit.
+    pub classes: Option<Vec<T>>,
+    /// Multi-class weights, `[n_features, n_classes]`.
+    pub coef: Option<Tensor<T>>,
+    /// Multi-class intercepts, `[n_classes]`.
+    pub intercept: Option<Tensor<T>>,
+    pub class_weight: Option<ClassWeight>,
 }
 
 impl<T: Float> LogisticRegression<T>
@@ -22,99 +52,306 @@ where
             learning_rate,
             max_iter,
             tol: T::from_f64(1e-6),
+            multi_class: MultiClass::Multinomial,
+            batch_size: None,
+            seed: None,
+            classes: None,
+            coef: None,
+            intercept: None,
+            class_weight: None,
         }
     }
 
+    /// Reweight classes in the loss, e.g. `ClassWeight::Balanced` for
+    /// imbalanced targets.
+    pub fn with_class_weight(mut self, class_weight: ClassWeight) -> Self {
+        self.class_weight = Some(class_weight);
+        self
+    }
+
+    /// Strategy for targets with more than two classes.
+    pub fn with_multi_class(mut self, multi_class: MultiClass) -> Self {
+        self.multi_class = multi_class;
+        self
+    }
+
+    /// Mini-batch gradient descent in multinomial mode.
+    pub fn with_batch_size(mut self, batch_size: usize, seed: Option<u64>) -> Self {
+        self.batch_size = Some(batch_size);
+        self.seed = seed;
+        self
+    }
+
     fn sigmoid_val(x: T) -> T {
         T::ONE / (T::ONE + (-x).exp())
     }
 
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.fit_weighted(x, y, None)
+    }
+
+    /// Fit with a per-row weight on the loss (combined with `class_weight`).
+    pub fn fit_weighted(&mut self, x: &Tensor<T>, y: &Tensor<T>, sample_weight: Option<&Tensor<T>>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
-        let n_t = T::from_usize(n);
-
-        let mut w = vec![T::ZERO; p];
-        let mut b = T::ZERO;
+        let weights = sample_weights(y, self.class_weight.as_ref(), sample_weight)?;
+        let mut classes = y.data().to_vec();
+        classes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        classes.dedup();
+        if classes.len() > 2 {
+            return self.fit_multiclass(x, y, &weights, classes);
+        }
+        self.classes = None;
+        self.coef = None;
+        self.intercept = None;
+        let xt = x.t()?;
+        let (w, b) = self.gradient_descent(
+            n,
+            p,
+            y,
+            &weights,
+            |w| x.matmul(&Tensor::new(w.to_vec(), vec![p, 1])?),
+            |err| xt.matmul(&Tensor::new(err.to_vec(), vec![n, 1])?),
+        )?;
+        self.weights = Some(Tensor::new(w, vec![p])?);
+        self.bias = Some(b);
+        Ok(())
+    }
 
-        for _iter in 0..self.max_iter {
-            let mut dw = vec![T::ZERO; p];
-            let mut db = T::ZERO;
-            let mut total_loss = T::ZERO;
-
-            for i in 0..n {
-                // Compute z = w·x + b
-                let mut z = b;
-                for j in 0..p {
-                    z = z + w[j] * x.get(&[i, j])?;
+    fn fit_multiclass(&mut self, x: &Tensor<T>, y: &Tensor<T>, weights: &[T], classes: Vec<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        let k = classes.len();
+        if y.numel() != n {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} rows but y has {} labels", n, y.numel()
+            )));
+        }
+        let labels: Vec<usize> = y.data().iter()
+            .map(|v| classes.iter().position(|c| c == v).unwrap_or(0))
+            .collect();
+        let (coef, intercept) = match self.multi_class {
+            MultiClass::Multinomial => self.softmax_descent(x, &labels, weights, k)?,
+            MultiClass::Ovr => {
+                let xt = x.t()?;
+                let mut coef = vec![T::ZERO; p * k];
+                let mut intercept = vec![T::ZERO; k];
+                for c in 0..k {
+                    let yc: Vec<T> = labels.iter().map(|&l| if l == c { T::ONE } else { T::ZERO }).collect();
+                    let (w, b) = self.gradient_descent(
+                        n,
+                        p,
+                        &Tensor::new(yc, vec![n])?,
+                        weights,
+                        |w| x.matmul(&Tensor::new(w.to_vec(), vec![p, 1])?),
+                        |err| xt.matmul(&Tensor::new(err.to_vec(), vec![n, 1])?),
+                    )?;
+                    for j in 0..p {
+                        coef[j * k + c] = w[j];
+                    }
+                    intercept[c] = b;
                 }
-                let a = Self::sigmoid_val(z); // prediction
-                let yi = y.data()[i];
-                let error = a - yi;
+                (coef, intercept)
+            }
+        };
+        self.coef = Some(Tensor::new(coef, vec![p, k])?);
+        self.intercept = Some(Tensor::new(intercept, vec![k])?);
+        self.classes = Some(classes);
+        self.weights = None;
+        self.bias = None;
+        Ok(())
+    }
 
-                for j in 0..p {
-                    dw[j] = dw[j] + error * x.get(&[i, j])?;
-                }
-                db = db + error;
+    /// Gradient descent on the mean softmax cross-entropy, full-batch or in
+    /// shuffled mini-batches of `batch_size` rows. Returns `(W, b)` with `W`
+    /// flattened from `[p, k]`.
+    fn softmax_descent(&self, x: &Tensor<T>, labels: &[usize], weights: &[T], k: usize) -> TensorResult<(Vec<T>, Vec<T>)> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        let batch_size = self.batch_size.unwrap_or(n).clamp(1, n.max(1));
+        let mut rng = RngHandle::new(self.seed);
+        let mut order: Vec<usize> = (0..n).collect();
+        let mut w = vec![T::ZERO; p * k];
+        let mut b = vec![T::ZERO; k];
 
-                // BCE loss for monitoring
-                let eps = T::from_f64(1e-15);
-                let loss = -(yi * (a + eps).ln() + (T::ONE - yi) * (T::ONE - a + eps).ln());
-                total_loss = total_loss + loss;
+        for _epoch in 0..self.max_iter {
+            if batch_size < n {
+                order.shuffle(&mut rng);
+            }
+            let mut max_grad = T::ZERO;
+            for batch in order.chunks(batch_size) {
+                let xb = if batch_size < n { x.index_select(0, batch)? } else { x.clone() };
+                let total = batch.iter().fold(T::ZERO, |acc, &i| acc + weights[i]);
+                // G = diag(w) (softmax(Xb·W + b) - onehot(y)) / Σw
+                let mut g = xb.matmul(&Tensor::new(w.clone(), vec![p, k])?)?.data().to_vec();
+                for (r, &row) in batch.iter().enumerate() {
+                    let z = &mut g[r * k..(r + 1) * k];
+                    let max = z.iter().fold(z[0] + b[0], |acc, &v| acc.max(v));
+                    let mut sum = T::ZERO;
+                    for c in 0..k {
+                        z[c] = (z[c] + b[c] - max).exp();
+                        sum += z[c];
+                    }
+                    for c in 0..k {
+                        z[c] /= sum;
+                    }
+                    z[labels[row]] -= T::ONE;
+                    let scale = if total > T::ZERO { weights[row] / total } else { T::ZERO };
+                    for v in z.iter_mut() {
+                        *v *= scale;
+                    }
+                }
+                let g = Tensor::new(g, vec![batch.len(), k])?;
+                let dw = xb.t()?.matmul(&g)?;
+                for (wi, &gi) in w.iter_mut().zip(dw.data()) {
+                    *wi -= self.learning_rate * gi;
+                    max_grad = max_grad.max(gi.abs());
+                }
+                let db = g.sum_axis(0)?;
+                for (bi, &gi) in b.iter_mut().zip(db.data()) {
+                    *bi -= self.learning_rate * gi;
+                    max_grad = max_grad.max(gi.abs());
+                }
+            }
+            if max_grad < self.tol {
+                break;
             }
+        }
+        Ok((w, b))
+    }
+
+    /// Fit a binary model on a sparse design matrix; each iteration costs O(nnz).
+    pub fn fit_sparse(&mut self, x: &SparseTensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (n, p) = (x.n_rows(), x.n_cols());
+        let xt = x.transpose();
+        let weights = sample_weights(y, self.class_weight.as_ref(), None)?;
+        let (w, b) = self.gradient_descent(
+            n,
+            p,
+            y,
+            &weights,
+            |w| x.spmm(&Tensor::from_slice(w)),
+            |err| xt.spmm(&Tensor::from_slice(err)),
+        )?;
+        self.weights = Some(Tensor::new(w, vec![p])?);
+        self.bias = Some(b);
+        Ok(())
+    }
+
+    /// Full-batch gradient descent on the weighted mean BCE loss, given `X·w`
+    /// and `Xᵀ·e` as closures. Returns `(w, b)`.
+    fn gradient_descent<F, G>(&self, n: usize, p: usize, y: &Tensor<T>, weights: &[T], forward: F, backward: G) -> TensorResult<(Vec<T>, T)>
+    where
+        F: Fn(&[T]) -> TensorResult<Tensor<T>>,
+        G: Fn(&[T]) -> TensorResult<Tensor<T>>,
+    {
+        if y.numel() != n {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} rows but y has {} labels", n, y.numel()
+            )));
+        }
+        let total = weights.iter().fold(T::ZERO, |acc, &w| acc + w);
+        if total <= T::ZERO {
+            return Err(TensorError::InvalidOperation("sample weights sum to zero".into()));
+        }
+
+        let mut w = vec![T::ZERO; p];
+        let mut b = T::ZERO;
+
+        for _iter in 0..self.max_iter {
+            // error_i = weight_i · (σ(w·x_i + b) - y_i)
+            let z = forward(&w)?;
+            let error: Vec<T> = z.data().iter().zip(y.data()).zip(weights)
+                .map(|((&zi, &yi), &wi)| wi * (Self::sigmoid_val(zi + b) - yi))
+                .collect();
+            let db = error.iter().fold(T::ZERO, |acc, &e| acc + e);
+            let dw = backward(&error)?;
 
             // Update weights
             let mut max_grad = T::ZERO;
             for j in 0..p {
-                let grad = dw[j] / n_t;
-                w[j] = w[j] - self.learning_rate * grad;
+                let grad = dw.data()[j] / total;
+                w[j] -= self.learning_rate * grad;
                 if grad.abs() > max_grad {
                     max_grad = grad.abs();
                 }
             }
-            b = b - self.learning_rate * (db / n_t);
+            b -= self.learning_rate * (db / total);
 
             if max_grad < self.tol {
                 break;
             }
         }
 
-        self.weights = Some(Tensor::new(w, vec![p])?);
-        self.bias = Some(b);
-        Ok(())
+        Ok((w, b))
     }
 
-    /// Predict probabilities.
+    /// Predict probabilities: `[n]` for the positive class of a binary
+    /// model, `[n, n_classes]` for a multi-class one.
     pub fn predict_proba(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
-        let w = self.weights.as_ref().ok_or_else(|| {
-            TensorError::InvalidOperation("Model not fitted".into())
-        })?;
+        if let (Some(coef), Some(intercept)) = (&self.coef, &self.intercept) {
+            return self.multiclass_proba(x, coef, intercept);
+        }
+        let w = self.fitted_weights()?;
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
+        let z = x.matmul(&w.reshape(vec![p, 1])?)?;
+        self.sigmoid_with_bias(z, n)
+    }
+
+    pub fn predict_proba_sparse(&self, x: &SparseTensor<T>) -> TensorResult<Tensor<T>> {
+        let z = x.spmm(self.fitted_weights()?)?;
+        self.sigmoid_with_bias(z, x.n_rows())
+    }
+
+    pub fn predict_sparse(&self, x: &SparseTensor<T>) -> TensorResult<Tensor<T>> {
+        Ok(Self::threshold(&self.predict_proba_sparse(x)?))
+    }
+
+    fn fitted_weights(&self) -> TensorResult<&Tensor<T>> {
+        self.weights.as_ref().ok_or_else(|| {
+            TensorError::InvalidOperation("Model not fitted".into())
+        })
+    }
+
+    fn sigmoid_with_bias(&self, z: Tensor<T>, n: usize) -> TensorResult<Tensor<T>> {
         let b = self.bias.unwrap_or(T::ZERO);
+        z.apply(|v| Self::sigmoid_val(v + b)).reshape(vec![n])
+    }
 
-        let mut proba = Vec::with_capacity(n);
-        for i in 0..n {
-            let mut z = b;
-            for j in 0..p {
-                z = z + w.data()[j] * x.get(&[i, j])?;
+    fn threshold(proba: &Tensor<T>) -> Tensor<T> {
+        proba.apply(|p| if p >= T::HALF { T::ONE } else { T::ZERO })
+    }
+
+    fn multiclass_proba(&self, x: &Tensor<T>, coef: &Tensor<T>, intercept: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let k = intercept.numel();
+        let mut z = x.matmul(coef)?.data().to_vec();
+        for row in z.chunks_mut(k) {
+            for (v, &b) in row.iter_mut().zip(intercept.data()) {
+                *v += b;
+            }
+            if self.multi_class == MultiClass::Ovr {
+                // Independent per-class sigmoids, renormalized per row.
+                row.iter_mut().for_each(|v| *v = Self::sigmoid_val(*v));
+                let sum = row.iter().fold(T::ZERO, |acc, &v| acc + v);
+                row.iter_mut().for_each(|v| *v /= sum);
             }
-            proba.push(Self::sigmoid_val(z));
         }
-
-        Tensor::new(proba, vec![n])
+        let z = Tensor::new(z, vec![x.shape().dim(0)?, k])?;
+        match self.multi_class {
+            MultiClass::Multinomial => z.softmax(),
+            MultiClass::Ovr => Ok(z),
+        }
     }
 
-    /// Predict class labels (threshold = 0.5).
+    /// Predict class labels: threshold 0.5 for a binary model, the most
+    /// probable class for a multi-class one.
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
-        let proba = self.predict_proba(x)?;
-        let labels: Vec<T> = proba
-            .data()
-            .iter()
-            .map(|&p| if p >= T::HALF { T::ONE } else { T::ZERO })
-            .collect();
-        let n = labels.len();
-        Tensor::new(labels, vec![n])
+        if let Some(classes) = &self.classes {
+            let best = self.predict_proba(x)?.argmax_axis(1)?;
+            return Ok(best.apply(|i| classes[i.to_f64() as usize]));
+        }
+        Ok(Self::threshold(&self.predict_proba(x)?))
     }
 }
 
@@ -147,4 +384,84 @@ mod tests {
             assert!(pred.data()[i] > 0.5, "Expected 1 at {}", i);
         }
     }
+
+    #[test]
+    fn test_logistic_regression_sparse_matches_dense() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 2.0],
+            vec![0.0, 3.0, 0.0],
+            vec![0.0, 2.0, 1.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0]);
+        let xs = SparseTensor::from_dense(&x).unwrap();
+
+        let mut dense = LogisticRegression::new(0.5, 200);
+        dense.fit(&x, &y).unwrap();
+        let mut sparse = LogisticRegression::new(0.5, 200);
+        sparse.fit_sparse(&xs, &y).unwrap();
+
+        let (wd, ws) = (dense.weights.unwrap(), sparse.weights.clone().unwrap());
+        for (a, b) in wd.data().iter().zip(ws.data()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+        assert_eq!(sparse.predict_sparse(&xs).unwrap().data(), y.data());
+    }
+
+    #[test]
+    fn test_logistic_regression_multiclass() {
+        // Three well-separated blobs labelled 1, 2, 5.
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+        for (c, (cx, cy)) in [(1.0, (0.0, 0.0)), (2.0, (4.0, 0.0)), (5.0, (0.0, 4.0))] {
+            for i in 0..10 {
+                let d = i as f64 * 0.05;
+                rows.push(vec![cx + d, cy - d]);
+                labels.push(c);
+            }
+        }
+        let x: Tensor<f64> = Tensor::from_vec2d(&rows).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&labels);
+
+        let models = [
+            LogisticRegression::new(0.5, 500),
+            LogisticRegression::new(0.1, 100).with_batch_size(8, Some(0)),
+            LogisticRegression::new(0.5, 500).with_multi_class(MultiClass::Ovr),
+        ];
+        for mut model in models {
+            model.fit(&x, &y).unwrap();
+            assert_eq!(model.classes.as_deref(), Some(&[1.0, 2.0, 5.0][..]));
+            let proba = model.predict_proba(&x).unwrap();
+            assert_eq!(proba.shape_vec(), vec![30, 3]);
+            for row in proba.data().chunks(3) {
+                assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            }
+            assert_eq!(model.predict(&x).unwrap().data(), y.data());
+        }
+    }
+
+    #[test]
+    fn test_logistic_regression_weights() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![0.0], vec![1.0], vec![2.0], vec![3.0], vec![2.5], vec![-1.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
+
+        // Zero-weight rows are the same as leaving them out.
+        let sw = Tensor::from_slice(&[1.0, 1.0, 1.0, 1.0, 0.0, 0.0]);
+        let mut weighted = LogisticRegression::new(0.5, 300);
+        weighted.fit_weighted(&x, &y, Some(&sw)).unwrap();
+        let mut subset = LogisticRegression::new(0.5, 300);
+        subset.fit(&x.index_select(0, &[0, 1, 2, 3]).unwrap(), &Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0])).unwrap();
+        assert!((weighted.weights.unwrap().data()[0] - subset.weights.unwrap().data()[0]).abs() < 1e-12);
+        assert!((weighted.bias.unwrap() - subset.bias.unwrap()).abs() < 1e-12);
+
+        // Up-weighting the positive class raises every positive probability.
+        let mut plain = LogisticRegression::new(0.5, 300);
+        plain.fit(&x, &y).unwrap();
+        let mut boosted = LogisticRegression::new(0.5, 300).with_class_weight(ClassWeight::Custom(vec![(1.0, 5.0)]));
+        boosted.fit(&x, &y).unwrap();
+        let (p0, p1) = (plain.predict_proba(&x).unwrap(), boosted.predict_proba(&x).unwrap());
+        assert!(p0.data().iter().zip(p1.data()).all(|(a, b)| b > a));
+    }
 }
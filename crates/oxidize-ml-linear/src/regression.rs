@@ -1,15 +1,43 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{Float, SparseTensor, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
-use oxidize_ml_linalg::{inv, solve};
 use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
+
+use crate::solver::{design_matrix, least_squares, LinearSolver};
+
+/// Fit `y = Xw + b` minimizing `||y - Xw - b||² + α ||(b, w)||²` with
+/// `solver`; as in the augmented normal equations, the intercept is
+/// penalized too.
+pub(crate) fn fit_least_squares<T: Float>(
+    x: &Tensor<T>,
+    y: &Tensor<T>,
+    fit_intercept: bool,
+    alpha: f64,
+    solver: LinearSolver,
+) -> TensorResult<(Tensor<T>, Option<T>)> {
+    let n = x.shape().dim(0)?;
+    let p = x.shape().dim(1)?;
+    let offset = usize::from(fit_intercept);
+    let (design, dim) = design_matrix(x, fit_intercept)?;
+    let targets: Vec<f64> = y.data().iter().map(|v| v.to_f64()).collect();
+    let w = least_squares(&design, n, dim, &targets, alpha, solver)?;
+
+    let weights = Tensor::new(w[offset..].iter().map(|&v| T::from_f64(v)).collect(), vec![p])?;
+    Ok((weights, fit_intercept.then(|| T::from_f64(w[0]))))
+}
 
 /// Ordinary Least Squares linear regression.
 ///
-/// Fits `y = Xw + b` using the normal equation: `w = (XᵀX)⁻¹Xᵀy`.
+/// Fits `y = Xw + b` by minimizing `||y - Xw - b||²`; see [`LinearSolver`]
+/// for the available solvers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct LinearRegression<T: Float> {
     pub weights: Option<Tensor<T>>,
     pub bias: Option<T>,
     pub fit_intercept: bool,
+    #[serde(default)]
+    pub solver: LinearSolver,
 }
 
 impl<T: Float> LinearRegression<T>
@@ -21,44 +49,20 @@ where
             weights: None,
             bias: None,
             fit_intercept,
+            solver: LinearSolver::Auto,
         }
     }
 
-    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
-        let n = x.shape().dim(0)?;
-        let p = x.shape().dim(1)?;
-
-        // Optionally prepend column of ones for intercept
-        let x_aug = if self.fit_intercept {
-            let ones_col = Tensor::ones(vec![n, 1]);
-            Tensor::concatenate(&[&ones_col, x], 1)?
-        } else {
-            x.clone()
-        };
-
-        // Normal equation: w = (XᵀX)⁻¹ Xᵀy
-        let xt = x_aug.t()?;
-        let xtx = xt.matmul(&x_aug)?;
-        let xty = xt.matmul(&y.reshape(vec![n, 1])?)?;
-        let xtx_inv = inv(&xtx)?;
-        let w = xtx_inv.matmul(&xty)?;
-
-        if self.fit_intercept {
-            self.bias = Some(w.get(&[0, 0])?);
-            let mut weights = Vec::with_capacity(p);
-            for i in 0..p {
-                weights.push(w.get(&[i + 1, 0])?);
-            }
-            self.weights = Some(Tensor::new(weights, vec![p])?);
-        } else {
-            let mut weights = Vec::with_capacity(p);
-            for i in 0..p {
-                weights.push(w.get(&[i, 0])?);
-            }
-            self.weights = Some(Tensor::new(weights, vec![p])?);
-            self.bias = None;
-        }
+    /// Choose how the least-squares problem is solved.
+    pub fn with_solver(mut self, solver: LinearSolver) -> Self {
+        self.solver = solver;
+        self
+    }
 
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (weights, bias) = fit_least_squares(x, y, self.fit_intercept, 0.0, self.solver)?;
+        self.weights = Some(weights);
+        self.bias = bias;
         Ok(())
     }
 
@@ -83,12 +87,17 @@ where
 
 /// Ridge regression (L2-regularized).
 ///
-/// Fits using: `w = (XᵀX + αI)⁻¹Xᵀy`.
+/// Fits `w = (XᵀX + αI)⁻¹Xᵀy` without forming the inverse; see
+/// [`LinearSolver`] for the available solvers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct Ridge<T: Float> {
     pub alpha: T,
     pub weights: Option<Tensor<T>>,
     pub bias: Option<T>,
     pub fit_intercept: bool,
+    #[serde(default)]
+    pub solver: LinearSolver,
 }
 
 impl<T: Float> Ridge<T>
@@ -101,45 +110,20 @@ where
             weights: None,
             bias: None,
             fit_intercept,
+            solver: LinearSolver::Auto,
         }
     }
 
-    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
-        let n = x.shape().dim(0)?;
-        let p = x.shape().dim(1)?;
-
-        let x_aug = if self.fit_intercept {
-            let ones_col = Tensor::ones(vec![n, 1]);
-            Tensor::concatenate(&[&ones_col, x], 1)?
-        } else {
-            x.clone()
-        };
-
-        let dim = x_aug.shape().dim(1)?;
-        let xt = x_aug.t()?;
-        let xtx = xt.matmul(&x_aug)?;
-        let reg = Tensor::<T>::eye(dim).mul_scalar(self.alpha);
-        let xtx_reg = xtx.add(&reg)?;
-        let xty = xt.matmul(&y.reshape(vec![n, 1])?)?;
-        let xtx_inv = inv(&xtx_reg)?;
-        let w = xtx_inv.matmul(&xty)?;
-
-        if self.fit_intercept {
-            self.bias = Some(w.get(&[0, 0])?);
-            let mut weights = Vec::with_capacity(p);
-            for i in 0..p {
-                weights.push(w.get(&[i + 1, 0])?);
-            }
-            self.weights = Some(Tensor::new(weights, vec![p])?);
-        } else {
-            let mut weights = Vec::with_capacity(p);
-            for i in 0..p {
-                weights.push(w.get(&[i, 0])?);
-            }
-            self.weights = Some(Tensor::new(weights, vec![p])?);
-            self.bias = None;
-        }
+    /// Choose how the regularized least-squares problem is solved.
+    pub fn with_solver(mut self, solver: LinearSolver) -> Self {
+        self.solver = solver;
+        self
+    }
 
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let (weights, bias) = fit_least_squares(x, y, self.fit_intercept, self.alpha.to_f64(), self.solver)?;
+        self.weights = Some(weights);
+        self.bias = bias;
         Ok(())
     }
 
@@ -159,6 +143,8 @@ where
 }
 
 /// Lasso regression (L1-regularized) via coordinate descent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct Lasso<T: Float> {
     pub alpha: T,
     pub weights: Option<Tensor<T>>,
@@ -184,55 +170,78 @@ where
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
+        // Row j of Xᵀ is column j of X.
+        let xt = x.t()?;
+        let cols = xt.data();
+        self.coordinate_descent(n, p, y, |j| {
+            (0..n).map(move |i| (i, cols[j * n + i]))
+        })
+    }
+
+    /// Fit on a sparse design matrix; each sweep costs O(nnz).
+    pub fn fit_sparse(&mut self, x: &SparseTensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let xt = x.transpose();
+        self.coordinate_descent(x.n_rows(), x.n_cols(), y, |j| {
+            let (rows, vals) = xt.row(j);
+            rows.iter().copied().zip(vals.iter().copied())
+        })
+    }
+
+    /// Cyclic coordinate descent keeping the residual `r = y - Xw - b` up to
+    /// date. `column(j)` yields the `(row, value)` entries of feature `j`.
+    fn coordinate_descent<F, I>(&mut self, n: usize, p: usize, y: &Tensor<T>, column: F) -> TensorResult<()>
+    where
+        F: Fn(usize) -> I,
+        I: Iterator<Item = (usize, T)>,
+    {
+        if y.numel() != n {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} rows but y has {} targets", n, y.numel()
+            )));
+        }
         let n_t = T::from_usize(n);
 
         let mut w = vec![T::ZERO; p];
         let mut b = T::ZERO;
+        let mut residual = y.data().to_vec();
+        let xj_sq: Vec<T> = (0..p)
+            .map(|j| column(j).fold(T::ZERO, |acc, (_, v)| acc + v * v) / n_t)
+            .collect();
 
         for _iter in 0..self.max_iter {
             let old_w = w.clone();
 
             // Update intercept
-            let mut residual_sum = T::ZERO;
-            for i in 0..n {
-                let mut pred = b;
-                for j in 0..p {
-                    pred = pred + x.get(&[i, j])? * w[j];
-                }
-                residual_sum = residual_sum + (y.data()[i] - pred);
+            let shift = residual.iter().fold(T::ZERO, |acc, &r| acc + r) / n_t;
+            b += shift;
+            for r in &mut residual {
+                *r -= shift;
             }
-            b = b + residual_sum / n_t;
 
             // Coordinate descent for each feature
             for j in 0..p {
-                let mut rho = T::ZERO;
-                for i in 0..n {
-                    let mut pred = b;
-                    for k in 0..p {
-                        if k != j {
-                            pred = pred + x.get(&[i, k])? * w[k];
-                        }
-                    }
-                    let residual = y.data()[i] - pred;
-                    rho = rho + x.get(&[i, j])? * residual;
+                if xj_sq[j] == T::ZERO {
+                    continue;
                 }
-                rho = rho / n_t;
+                // rho = x_jᵀ(y - b - Σ_{k≠j} x_k w_k) / n
+                let rho = column(j).fold(T::ZERO, |acc, (i, v)| acc + v * residual[i]) / n_t
+                    + xj_sq[j] * w[j];
 
                 // Soft thresholding
-                let mut xj_sq = T::ZERO;
-                for i in 0..n {
-                    let xij = x.get(&[i, j])?;
-                    xj_sq = xj_sq + xij * xij;
-                }
-                xj_sq = xj_sq / n_t;
-
-                w[j] = if rho > self.alpha {
-                    (rho - self.alpha) / xj_sq
+                let new_w = if rho > self.alpha {
+                    (rho - self.alpha) / xj_sq[j]
                 } else if rho < -self.alpha {
-                    (rho + self.alpha) / xj_sq
+                    (rho + self.alpha) / xj_sq[j]
                 } else {
                     T::ZERO
                 };
+                let delta = new_w - w[j];
+                if delta != T::ZERO {
+                    for (i, v) in column(j) {
+                        residual[i] -= v * delta;
+                    }
+                    w[j] = new_w;
+                }
             }
 
             // Check convergence
@@ -253,6 +262,17 @@ where
         Ok(())
     }
 
+    pub fn predict_sparse(&self, x: &SparseTensor<T>) -> TensorResult<Tensor<T>> {
+        let w = self.weights.as_ref().ok_or_else(|| {
+            TensorError::InvalidOperation("Model not fitted".into())
+        })?;
+        let pred = x.spmm(w)?;
+        Ok(match self.bias {
+            Some(b) => pred.add_scalar(b),
+            None => pred,
+        })
+    }
+
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
         let w = self.weights.as_ref().ok_or_else(|| {
             TensorError::InvalidOperation("Model not fitted".into())
@@ -290,12 +310,14 @@ mod tests {
             2.0*5.0 + 3.0*5.0 + 1.0,  // 26.0
         ]);
 
-        let mut model = LinearRegression::new(true);
-        model.fit(&x, &y).unwrap();
+        for solver in [LinearSolver::Auto, LinearSolver::Cholesky, LinearSolver::Qr, LinearSolver::Svd, LinearSolver::Lsqr] {
+            let mut model = LinearRegression::new(true).with_solver(solver);
+            model.fit(&x, &y).unwrap();
 
-        let pred = model.predict(&x).unwrap();
-        for i in 0..5 {
-            assert!((pred.data()[i] - y.data()[i]).abs() < 1e-6);
+            let pred = model.predict(&x).unwrap();
+            for i in 0..5 {
+                assert!((pred.data()[i] - y.data()[i]).abs() < 1e-6, "{:?}", solver);
+            }
         }
     }
 
@@ -334,4 +356,31 @@ mod tests {
         let w = model.weights.as_ref().unwrap();
         assert!(w.data()[1].abs() < 0.1);
     }
+
+    #[test]
+    fn test_lasso_sparse_matches_dense() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 2.0, 0.0],
+            vec![3.0, 0.0, 1.0],
+            vec![0.0, 1.0, 0.0],
+            vec![2.0, 0.0, 0.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[2.0, -1.0, 6.5, -0.5, 4.0]);
+        let xs = SparseTensor::from_dense(&x).unwrap();
+
+        let mut dense = Lasso::new(0.05, 500);
+        dense.fit(&x, &y).unwrap();
+        let mut sparse = Lasso::new(0.05, 500);
+        sparse.fit_sparse(&xs, &y).unwrap();
+
+        let (wd, ws) = (dense.weights.as_ref().unwrap(), sparse.weights.as_ref().unwrap());
+        for (a, b) in wd.data().iter().zip(ws.data()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+        let (pd, ps) = (dense.predict(&x).unwrap(), sparse.predict_sparse(&xs).unwrap());
+        for (a, b) in pd.data().iter().zip(ps.data()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+    }
 }
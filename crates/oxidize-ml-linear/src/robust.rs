@@ -0,0 +1,298 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+/// Residuals below this count as zero when reweighting, which keeps the
+/// weights of exactly fitted points finite.
+const RESIDUAL_FLOOR: f64 = 1e-8;
+
+/// Weighted, diagonally penalized least squares:
+/// `(Xᵀ W X + diag(penalty)) β = Xᵀ W y`, with the intercept (unpenalized)
+/// last in `β` when `fit_intercept`.
+fn weighted_lstsq(xs: &[f64], p: usize, fit_intercept: bool, sample_w: &[f64], y: &[f64], penalty: &[f64]) -> TensorResult<Vec<f64>> {
+    let dim = p + usize::from(fit_intercept);
+    let mut lhs = vec![0.0; dim * dim];
+    let mut rhs = vec![0.0; dim];
+    for (i, (&w, &t)) in sample_w.iter().zip(y).enumerate() {
+        let row = &xs[i * p..(i + 1) * p];
+        let feature = |j: usize| if j < p { row[j] } else { 1.0 };
+        for a in 0..dim {
+            let fa = feature(a);
+            rhs[a] += w * fa * t;
+            for b in 0..=a {
+                lhs[a * dim + b] += w * fa * feature(b);
+            }
+        }
+    }
+    for a in 0..dim {
+        for b in 0..a {
+            lhs[b * dim + a] = lhs[a * dim + b];
+        }
+        if a < p {
+            lhs[a * dim + a] += penalty[a];
+        }
+    }
+    let beta = oxidize_ml_linalg::solve(&Tensor::new(lhs, vec![dim, dim])?, &Tensor::new(rhs, vec![dim, 1])?)?;
+    Ok(beta.data().to_vec())
+}
+
+fn residuals(xs: &[f64], p: usize, fit_intercept: bool, y: &[f64], beta: &[f64]) -> Vec<f64> {
+    let b = if fit_intercept { beta[p] } else { 0.0 };
+    y.iter()
+        .enumerate()
+        .map(|(i, t)| t - xs[i * p..(i + 1) * p].iter().zip(beta).map(|(a, w)| a * w).sum::<f64>() - b)
+        .collect()
+}
+
+fn to_f64<T: Float>(x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<(Vec<f64>, Vec<f64>, usize)> {
+    let (n, p) = (x.shape().dim(0)?, x.shape().dim(1)?);
+    if y.numel() != n {
+        return Err(TensorError::DimensionMismatch(format!("x has {} rows but y has {} targets", n, y.numel())));
+    }
+    Ok((x.data().iter().map(|v| v.to_f64()).collect(), y.data().iter().map(|v| v.to_f64()).collect(), p))
+}
+
+fn linear_predict<T: Float>(weights: &Option<Tensor<T>>, bias: Option<T>, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+    let w = weights.as_ref().ok_or_else(|| TensorError::InvalidOperation("Model not fitted".into()))?;
+    let n = x.shape().dim(0)?;
+    let p = x.shape().dim(1)?;
+    let mut pred = x.matmul(&w.reshape(vec![p, 1])?)?;
+    if let Some(b) = bias {
+        pred = pred.add_scalar(b);
+    }
+    pred.reshape(vec![n])
+}
+
+/// Linear quantile regression.
+///
+/// Minimizes the pinball loss `1/n Σ ρ_τ(yᵢ - xᵢw - b) + α‖w‖₁`, where
+/// `ρ_τ(r)` is `τ r` above the fit and `(τ - 1) r` below, so the fit is the
+/// conditional `quantile` of `y` (0.5 is median regression, which ignores
+/// how far outliers lie). Solved by iteratively reweighted least squares,
+/// each step minimizing a quadratic upper bound of the objective; this
+/// converges to the linear-programming solution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct QuantileRegressor<T: Float> {
+    pub quantile: f64,
+    /// L1 penalty on the weights.
+    pub alpha: T,
+    pub fit_intercept: bool,
+    pub max_iter: usize,
+    pub tol: T,
+    pub weights: Option<Tensor<T>>,
+    pub bias: Option<T>,
+    pub n_iter: usize,
+}
+
+impl<T: Float> QuantileRegressor<T> {
+    pub fn new(quantile: f64, alpha: T) -> Self {
+        QuantileRegressor {
+            quantile,
+            alpha,
+            fit_intercept: true,
+            max_iter: 500,
+            tol: T::from_f64(1e-8),
+            weights: None,
+            bias: None,
+            n_iter: 0,
+        }
+    }
+
+    pub fn with_fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        if !(self.quantile > 0.0 && self.quantile < 1.0) {
+            return Err(TensorError::InvalidOperation(format!("quantile must be in (0, 1), got {}", self.quantile)));
+        }
+        let (xs, ys, p) = to_f64(x, y)?;
+        let n = ys.len() as f64;
+        let (tau, alpha, tol) = (self.quantile, self.alpha.to_f64(), self.tol.to_f64());
+
+        // Start from least squares.
+        let mut beta = weighted_lstsq(&xs, p, self.fit_intercept, &vec![1.0; ys.len()], &ys, &vec![1e-10; p])?;
+        let mut n_iter = 0;
+        for _ in 0..self.max_iter {
+            n_iter += 1;
+            // |r| ≤ r²/(2|r₀|) + |r₀|/2, weighted by τ or 1 - τ.
+            let r = residuals(&xs, p, self.fit_intercept, &ys, &beta);
+            let sample_w: Vec<f64> = r
+                .iter()
+                .map(|&r| (if r >= 0.0 { tau } else { 1.0 - tau }) / (n * r.abs().max(RESIDUAL_FLOOR)))
+                .collect();
+            let penalty: Vec<f64> = beta[..p].iter().map(|w| alpha / w.abs().max(RESIDUAL_FLOOR)).collect();
+            let next = weighted_lstsq(&xs, p, self.fit_intercept, &sample_w, &ys, &penalty)?;
+            let change = next.iter().zip(&beta).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            beta = next;
+            if change < tol {
+                break;
+            }
+        }
+
+        self.bias = self.fit_intercept.then(|| T::from_f64(beta[p]));
+        // Weights driven to the floor by the L1 penalty are exactly zero.
+        let w: Vec<T> = beta[..p].iter().map(|&w| T::from_f64(if w.abs() < 1e-7 { 0.0 } else { w })).collect();
+        self.weights = Some(Tensor::new(w, vec![p])?);
+        self.n_iter = n_iter;
+        Ok(())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        linear_predict(&self.weights, self.bias, x)
+    }
+}
+
+/// Huber robust linear regression.
+///
+/// Squared loss for residuals within `epsilon` robust standard deviations
+/// and absolute loss beyond, so a few wild targets only pull the fit
+/// linearly. Fitted by iteratively reweighted least squares, re-estimating
+/// the residual scale from the median absolute deviation at each step;
+/// `alpha` is an L2 penalty on the weights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct HuberRegressor<T: Float> {
+    pub epsilon: f64,
+    pub alpha: T,
+    pub fit_intercept: bool,
+    pub max_iter: usize,
+    pub tol: T,
+    pub weights: Option<Tensor<T>>,
+    pub bias: Option<T>,
+    /// Robust residual scale of the fit.
+    pub scale: f64,
+    /// Training rows whose residual exceeds `epsilon · scale`.
+    pub outliers: Vec<bool>,
+    pub n_iter: usize,
+}
+
+impl<T: Float> HuberRegressor<T> {
+    pub fn new(epsilon: f64, alpha: T) -> Self {
+        HuberRegressor {
+            epsilon,
+            alpha,
+            fit_intercept: true,
+            max_iter: 100,
+            tol: T::from_f64(1e-8),
+            weights: None,
+            bias: None,
+            scale: 1.0,
+            outliers: Vec::new(),
+            n_iter: 0,
+        }
+    }
+
+    pub fn with_fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    pub fn with_max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        if self.epsilon < 1.0 {
+            return Err(TensorError::InvalidOperation(format!("epsilon must be at least 1, got {}", self.epsilon)));
+        }
+        let (xs, ys, p) = to_f64(x, y)?;
+        let penalty = vec![self.alpha.to_f64().max(1e-10); p];
+        let tol = self.tol.to_f64();
+
+        let mut beta = weighted_lstsq(&xs, p, self.fit_intercept, &vec![1.0; ys.len()], &ys, &penalty)?;
+        let mut scale = 1.0;
+        let mut n_iter = 0;
+        for _ in 0..self.max_iter {
+            n_iter += 1;
+            let r = residuals(&xs, p, self.fit_intercept, &ys, &beta);
+            let mut abs: Vec<f64> = r.iter().map(|v| v.abs()).collect();
+            abs.sort_by(f64::total_cmp);
+            let mid = abs.len() / 2;
+            let mad = if abs.len() % 2 == 1 { abs[mid] } else { 0.5 * (abs[mid - 1] + abs[mid]) };
+            scale = (mad / 0.6745).max(RESIDUAL_FLOOR);
+            let sample_w: Vec<f64> = r
+                .iter()
+                .map(|v| {
+                    let u = v.abs() / scale;
+                    if u <= self.epsilon { 1.0 } else { self.epsilon / u }
+                })
+                .collect();
+            let next = weighted_lstsq(&xs, p, self.fit_intercept, &sample_w, &ys, &penalty)?;
+            let change = next.iter().zip(&beta).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+            beta = next;
+            if change < tol {
+                break;
+            }
+        }
+
+        let r = residuals(&xs, p, self.fit_intercept, &ys, &beta);
+        self.outliers = r.iter().map(|v| v.abs() > self.epsilon * scale).collect();
+        self.scale = scale;
+        self.bias = self.fit_intercept.then(|| T::from_f64(beta[p]));
+        self.weights = Some(Tensor::new(beta[..p].iter().map(|&w| T::from_f64(w)).collect(), vec![p])?);
+        self.n_iter = n_iter;
+        Ok(())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        linear_predict(&self.weights, self.bias, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `y = 1 + 2x` with small distinct noise and two gross outliers.
+    fn contaminated() -> (Tensor<f64>, Tensor<f64>) {
+        let xs: Vec<f64> = (0..40).map(|i| i as f64 * 0.25).collect();
+        let mut ys: Vec<f64> = xs.iter().enumerate().map(|(i, x)| 1.0 + 2.0 * x + ((i * 17) % 40) as f64 * 0.01 - 0.2).collect();
+        ys[5] += 60.0;
+        ys[30] += 80.0;
+        (Tensor::new(xs, vec![40, 1]).unwrap(), Tensor::new(ys, vec![40]).unwrap())
+    }
+
+    #[test]
+    fn test_quantile_regressor() {
+        let (x, y) = contaminated();
+        let mut median = QuantileRegressor::new(0.5, 0.0);
+        median.fit(&x, &y).unwrap();
+        assert!((median.weights.clone().unwrap().data()[0] - 2.0).abs() < 0.05);
+
+        for tau in [0.1, 0.9] {
+            let mut q = QuantileRegressor::new(tau, 0.0);
+            q.fit(&x, &y).unwrap();
+            let below = q.predict(&x).unwrap().data().iter().zip(y.data()).filter(|(p, t)| t <= p).count();
+            assert!((below as f64 / 40.0 - tau).abs() <= 0.1, "tau {}: {} below", tau, below);
+        }
+
+        // A strong L1 penalty zeroes the slope.
+        let mut sparse = QuantileRegressor::new(0.5, 10.0);
+        sparse.fit(&x, &y).unwrap();
+        assert_eq!(sparse.weights.unwrap().data()[0], 0.0);
+    }
+
+    #[test]
+    fn test_huber_regressor_resists_outliers() {
+        let (x, y) = contaminated();
+        let mut huber = HuberRegressor::new(1.35, 1e-4);
+        huber.fit(&x, &y).unwrap();
+        let w = huber.weights.clone().unwrap().data()[0];
+        assert!((w - 2.0).abs() < 0.05, "slope {}", w);
+        assert!(huber.outliers[5] && huber.outliers[30]);
+        assert!(huber.outliers.iter().filter(|&&o| o).count() < 10);
+
+        let mut ols = crate::LinearRegression::new(true);
+        ols.fit(&x, &y).unwrap();
+        assert!((ols.weights.unwrap().data()[0] - 2.0).abs() > (w - 2.0).abs());
+    }
+}
@@ -0,0 +1,261 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_linalg::{cholesky, lstsq, thin_svd};
+use serde::{Deserialize, Serialize};
+
+/// How `LinearRegression` and `Ridge` solve their least-squares problem
+/// `min ||Xw - y||² + α ||w||²`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LinearSolver {
+    /// Cholesky when `α > 0`; otherwise QR, falling back to SVD when `X`
+    /// is rank deficient or wider than tall.
+    #[default]
+    Auto,
+    /// Cholesky factorization of `XᵀX + αI`. Fastest, but squares the
+    /// condition number and needs a positive-definite system.
+    Cholesky,
+    /// Householder QR of `X`, with `√α I` stacked below it for ridge.
+    /// Needs at least as many rows as columns.
+    Qr,
+    /// Thin SVD of `X`; discards directions with negligible singular
+    /// values, so rank-deficient problems get the minimum-norm solution.
+    Svd,
+    /// LSQR (Paige & Saunders): Golub–Kahan bidiagonalization using only
+    /// products with `X` and `Xᵀ`. Suited to very wide problems; from a
+    /// zero start it converges to the minimum-norm solution.
+    Lsqr,
+}
+
+/// Relative singular value below which the SVD solver drops a direction;
+/// see `LSTSQ_RCOND` for why it cannot be much smaller.
+const RCOND: f64 = oxidize_ml_linalg::LSTSQ_RCOND;
+
+/// Solve `min ||Xw - y||² + α ||w||²` for row-major `x` (`[rows, cols]`).
+pub(crate) fn least_squares(
+    x: &[f64],
+    rows: usize,
+    cols: usize,
+    y: &[f64],
+    alpha: f64,
+    solver: LinearSolver,
+) -> TensorResult<Vec<f64>> {
+    if y.len() != rows {
+        return Err(TensorError::DimensionMismatch(format!(
+            "x has {} rows but y has {} targets", rows, y.len()
+        )));
+    }
+    match solver {
+        LinearSolver::Auto if alpha > 0.0 => solve_cholesky(x, rows, cols, y, alpha),
+        LinearSolver::Auto if rows >= cols => solve_qr(x, rows, cols, y, alpha)
+            .or_else(|_| solve_svd(x, rows, cols, y, alpha)),
+        LinearSolver::Auto => solve_svd(x, rows, cols, y, alpha),
+        LinearSolver::Cholesky => solve_cholesky(x, rows, cols, y, alpha),
+        LinearSolver::Qr => solve_qr(x, rows, cols, y, alpha),
+        LinearSolver::Svd => solve_svd(x, rows, cols, y, alpha),
+        LinearSolver::Lsqr => Ok(lsqr(x, rows, cols, y, alpha)),
+    }
+}
+
+fn solve_cholesky(x: &[f64], rows: usize, cols: usize, y: &[f64], alpha: f64) -> TensorResult<Vec<f64>> {
+    let mut gram = vec![0.0; cols * cols];
+    let mut rhs = vec![0.0; cols];
+    for r in 0..rows {
+        let row = &x[r * cols..(r + 1) * cols];
+        for i in 0..cols {
+            rhs[i] += row[i] * y[r];
+            for j in 0..=i {
+                gram[i * cols + j] += row[i] * row[j];
+            }
+        }
+    }
+    for i in 0..cols {
+        gram[i * cols + i] += alpha;
+        for j in 0..i {
+            gram[j * cols + i] = gram[i * cols + j];
+        }
+    }
+    let l = cholesky(&Tensor::new(gram, vec![cols, cols])?)
+        .map_err(|_| TensorError::SingularMatrix)?
+        .l;
+    let l = l.data();
+
+    // L z = Xᵀy, then Lᵀ w = z.
+    for i in 0..cols {
+        let sum: f64 = (0..i).map(|k| l[i * cols + k] * rhs[k]).sum();
+        rhs[i] = (rhs[i] - sum) / l[i * cols + i];
+    }
+    for i in (0..cols).rev() {
+        let sum: f64 = (i + 1..cols).map(|k| l[k * cols + i] * rhs[k]).sum();
+        rhs[i] = (rhs[i] - sum) / l[i * cols + i];
+    }
+    Ok(rhs)
+}
+
+fn solve_qr(x: &[f64], rows: usize, cols: usize, y: &[f64], alpha: f64) -> TensorResult<Vec<f64>> {
+    let (mut a, mut b) = (x.to_vec(), y.to_vec());
+    let mut m = rows;
+    if alpha > 0.0 {
+        for j in 0..cols {
+            let mut row = vec![0.0; cols];
+            row[j] = alpha.sqrt();
+            a.extend(row);
+        }
+        b.resize(rows + cols, 0.0);
+        m += cols;
+    }
+    if m < cols {
+        return Err(TensorError::InvalidOperation(format!(
+            "QR solver needs at least as many rows as columns ({} < {}); use Svd or Lsqr", m, cols
+        )));
+    }
+    let w = lstsq(&Tensor::new(a, vec![m, cols])?, &Tensor::new(b, vec![m])?)?;
+    Ok(w.data().to_vec())
+}
+
+fn solve_svd(x: &[f64], rows: usize, cols: usize, y: &[f64], alpha: f64) -> TensorResult<Vec<f64>> {
+    Ok(ridge_path(x, rows, cols, y, &[alpha])?.remove(0))
+}
+
+/// Ridge solutions for every `α` in `alphas` from a single thin SVD of `x`:
+/// `w = Σ_j v_j · σ_j / (σ_j² + α) · u_jᵀ y`.
+pub(crate) fn ridge_path(x: &[f64], rows: usize, cols: usize, y: &[f64], alphas: &[f64]) -> TensorResult<Vec<Vec<f64>>> {
+    let (u, sigma, v) = thin_svd(&Tensor::new(x.to_vec(), vec![rows, cols])?)?;
+    let (u, sigma, v) = (u.data(), sigma.data(), v.data());
+    let k = sigma.len();
+    let cutoff = RCOND * sigma.first().copied().unwrap_or(0.0);
+    let uty: Vec<f64> = (0..k).map(|j| (0..rows).map(|r| u[r * k + j] * y[r]).sum()).collect();
+
+    Ok(alphas
+        .iter()
+        .map(|&alpha| {
+            let mut w = vec![0.0; cols];
+            for j in (0..k).filter(|&j| sigma[j] > cutoff) {
+                let scale = sigma[j] / (sigma[j] * sigma[j] + alpha) * uty[j];
+                for (i, wi) in w.iter_mut().enumerate() {
+                    *wi += scale * v[i * k + j];
+                }
+            }
+            w
+        })
+        .collect())
+}
+
+/// Row-major `f64` copy of `x` (`[n, p]`), with a leading column of ones
+/// when `fit_intercept` is set. Returns the data and its column count.
+pub(crate) fn design_matrix<T: Float>(x: &Tensor<T>, fit_intercept: bool) -> TensorResult<(Vec<f64>, usize)> {
+    let n = x.shape().dim(0)?;
+    let p = x.shape().dim(1)?;
+    let dim = p + usize::from(fit_intercept);
+    let mut design = Vec::with_capacity(n * dim);
+    for i in 0..n {
+        if fit_intercept {
+            design.push(1.0);
+        }
+        design.extend(x.data()[i * p..(i + 1) * p].iter().map(|v| v.to_f64()));
+    }
+    Ok((design, dim))
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|a| a * a).sum::<f64>().sqrt()
+}
+
+/// LSQR with damping `√α`, stopping once the estimated `||Xᵀr - αw||` has
+/// fallen by a factor of `1e-12` or after `4 · max(cols, 10)` iterations.
+fn lsqr(x: &[f64], rows: usize, cols: usize, y: &[f64], alpha: f64) -> Vec<f64> {
+    let damp = alpha.sqrt();
+    let mut w = vec![0.0; cols];
+
+    let mut u = y.to_vec();
+    let mut beta = norm(&u);
+    if beta == 0.0 {
+        return w;
+    }
+    u.iter_mut().for_each(|ui| *ui /= beta);
+    let mut v: Vec<f64> = (0..cols)
+        .map(|j| (0..rows).map(|r| x[r * cols + j] * u[r]).sum())
+        .collect();
+    let mut a = norm(&v);
+    if a == 0.0 {
+        return w;
+    }
+    v.iter_mut().for_each(|vi| *vi /= a);
+    let mut dir = v.clone();
+    let (mut phibar, mut rhobar) = (beta, a);
+    let initial = a * beta;
+
+    for _ in 0..4 * cols.max(10) {
+        // Bidiagonalization step: β u = X v - α u, α v = Xᵀ u - β v.
+        for (r, ur) in u.iter_mut().enumerate() {
+            let xv: f64 = (0..cols).map(|j| x[r * cols + j] * v[j]).sum();
+            *ur = xv - a * *ur;
+        }
+        beta = norm(&u);
+        if beta > 0.0 {
+            u.iter_mut().for_each(|ui| *ui /= beta);
+            for (j, vj) in v.iter_mut().enumerate() {
+                let xtu: f64 = (0..rows).map(|r| x[r * cols + j] * u[r]).sum();
+                *vj = xtu - beta * *vj;
+            }
+            a = norm(&v);
+            if a > 0.0 {
+                v.iter_mut().for_each(|vi| *vi /= a);
+            }
+        }
+
+        // Rotate out the damping term, then the subdiagonal.
+        let rhobar1 = rhobar.hypot(damp);
+        let cs1 = rhobar / rhobar1;
+        phibar *= cs1;
+        let rho = rhobar1.hypot(beta);
+        let (cs, sn) = (rhobar1 / rho, beta / rho);
+        let theta = sn * a;
+        rhobar = -cs * a;
+        let phi = cs * phibar;
+        phibar *= sn;
+
+        for j in 0..cols {
+            w[j] += phi / rho * dir[j];
+            dir[j] = v[j] - theta / rho * dir[j];
+        }
+        if beta == 0.0 || a == 0.0 || (phibar * a * cs).abs() <= 1e-12 * initial {
+            break;
+        }
+    }
+    w
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solvers_agree() {
+        let x = [1.0, 0.5, 2.0, 1.0, -1.0, 3.0, 2.0, 2.0, 1.0, 0.0, 1.5, -2.0, 1.0, 4.0, 0.5];
+        let y = [1.0, 2.0, -0.5, 3.0, 0.7];
+        for alpha in [0.0, 0.3] {
+            let reference = least_squares(&x, 5, 3, &y, alpha, LinearSolver::Svd).unwrap();
+            for solver in [LinearSolver::Cholesky, LinearSolver::Qr, LinearSolver::Lsqr, LinearSolver::Auto] {
+                let w = least_squares(&x, 5, 3, &y, alpha, solver).unwrap();
+                for (a, b) in w.iter().zip(&reference) {
+                    assert!((a - b).abs() < 1e-8, "{:?} alpha={}: {:?} vs {:?}", solver, alpha, w, reference);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_problem_gets_minimum_norm_solution() {
+        // Two equations, four unknowns: w = Xᵀ (X Xᵀ)⁻¹ y.
+        let x = [1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0];
+        let y = [2.0, 4.0];
+        for solver in [LinearSolver::Svd, LinearSolver::Lsqr, LinearSolver::Auto] {
+            let w = least_squares(&x, 2, 4, &y, 0.0, solver).unwrap();
+            for (a, b) in w.iter().zip(&[1.0, 2.0, 1.0, 2.0]) {
+                assert!((a - b).abs() < 1e-8, "{:?}: {:?}", solver, w);
+            }
+        }
+        assert!(least_squares(&x, 2, 4, &y, 0.0, LinearSolver::Qr).is_err());
+        assert!(least_squares(&x, 2, 4, &y, 0.0, LinearSolver::Cholesky).is_err());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod loss;
+pub mod metric_learning;
 pub use loss::*;
+pub use metric_learning::*;
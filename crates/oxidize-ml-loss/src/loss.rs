@@ -1,8 +1,8 @@
 use oxidize_ml_autodiff::Variable;
-use oxidize_ml_core::Tensor;
+use oxidize_ml_core::{Float, Tensor};
 
 /// Mean Squared Error loss: L = mean((pred - target)²).
-pub fn mse_loss(pred: &Variable, target: &Variable) -> Variable {
+pub fn mse_loss<T: Float>(pred: &Variable<T>, target: &Variable<T>) -> Variable<T> {
     let diff = pred.sub(target);
     let sq = diff.mul(&diff);
     sq.mean()
@@ -10,16 +10,16 @@ pub fn mse_loss(pred: &Variable, target: &Variable) -> Variable {
 
 /// Binary Cross-Entropy loss.
 /// pred should be probabilities in (0, 1).
-pub fn bce_loss(pred: &Variable, target: &Variable) -> Variable {
-    let eps = Variable::input(Tensor::scalar(1e-7));
+pub fn bce_loss<T: Float>(pred: &Variable<T>, target: &Variable<T>) -> Variable<T> {
+    let eps = pred.constant(Tensor::scalar(T::from_f64(1e-7)));
 
     let pred_safe = pred.add(&eps);
     let log_pred = pred_safe.ln();
     let term1 = target.mul(&log_pred);
 
-    let one = Variable::input(Tensor::scalar(1.0));
+    let one = pred.constant(Tensor::scalar(T::ONE));
     let one_minus_target = one.sub(target);
-    let one_minus_pred = Variable::input(Tensor::scalar(1.0)).sub(pred);
+    let one_minus_pred = pred.constant(Tensor::scalar(T::ONE)).sub(pred);
     let one_minus_pred_safe = one_minus_pred.add(&eps);
     let log_one_minus = one_minus_pred_safe.ln();
     let term2 = one_minus_target.mul(&log_one_minus);
@@ -33,11 +33,9 @@ pub fn bce_loss(pred: &Variable, target: &Variable) -> Variable {
 /// L = 0.5 * (y - f)² if |y - f| <= δ
 /// L = δ * |y - f| - 0.5 * δ²  otherwise
 ///
-/// Note: Simplified using MSE since conditional branching in AD graphs is complex.
-/// For practical use, the MSE approximation works well for small residuals.
-pub fn huber_loss(pred: &Variable, target: &Variable, _delta: f64) -> Variable {
-    // Simplified: use MSE (good approximation when residuals are small)
-    mse_loss(pred, target)
+/// Mean over elements; see `HuberLoss` for other reductions.
+pub fn huber_loss<T: Float>(pred: &Variable<T>, target: &Variable<T>, delta: f64) -> Variable<T> {
+    HuberLoss::new(delta).forward(pred, target)
 }
 
 /// Cross-Entropy loss for multi-class classification.
@@ -46,7 +44,7 @@ pub fn huber_loss(pred: &Variable, target: &Variable, _delta: f64) -> Variable {
 /// target: integer class labels [batch_size] stored as f64
 ///
 /// L = -mean(log(softmax(logits)[correct_class]))
-pub fn cross_entropy_loss(logits: &Tensor<f64>, targets: &Tensor<f64>) -> f64 {
+pub fn cross_entropy_loss<T: Float>(logits: &Tensor<T>, targets: &Tensor<T>) -> f64 {
     let batch_size = logits.shape().dim(0).unwrap();
     let n_classes = logits.shape().dim(1).unwrap();
 
@@ -54,25 +52,454 @@ pub fn cross_entropy_loss(logits: &Tensor<f64>, targets: &Tensor<f64>) -> f64 {
 
     let mut total_loss = 0.0;
     for i in 0..batch_size {
-        let target_class = targets.data()[i].round() as usize;
+        let target_class = targets.data()[i].to_f64().round() as usize;
         if target_class < n_classes {
-            let p = probs.get(&[i, target_class]).unwrap().max(1e-15);
+            let p = probs.get(&[i, target_class]).unwrap().to_f64().max(1e-15);
             total_loss -= p.ln();
         }
     }
     total_loss / batch_size as f64
 }
 
+/// Cross-entropy of raw logits `[batch_size, n_classes]` against integer
+/// class labels `[batch_size]` stored as floats, recorded on the graph.
+///
+/// Uses the fused `Variable::cross_entropy`: a log-sum-exp forward and a
+/// `(softmax - one_hot) / batch` backward, so no probabilities are routed
+/// through `ln`.
+pub fn cross_entropy<T: Float>(logits: &Variable<T>, targets: &Tensor<T>) -> Variable<T> {
+    let classes: Vec<usize> = targets.data().iter().map(|t| t.to_f64().round() as usize).collect();
+    logits.cross_entropy(&classes)
+}
+
+/// How a loss combines its per-element (or per-sample) values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reduction {
+    #[default]
+    Mean,
+    Sum,
+    /// Keep the unreduced values.
+    None,
+}
+
+impl Reduction {
+    /// Apply to per-element losses.
+    pub fn reduce<T: Float>(self, losses: &Variable<T>) -> Variable<T> {
+        match self {
+            Reduction::Mean => losses.mean(),
+            Reduction::Sum => losses.sum(),
+            Reduction::None => losses.clone(),
+        }
+    }
+}
+
+/// `|x|`, with gradient `sign(x)` (0 at 0).
+fn abs<T: Float>(x: &Variable<T>) -> Variable<T> {
+    let sign = x.data.apply(|v| if v > T::ZERO { T::ONE } else if v < T::ZERO { -T::ONE } else { T::ZERO });
+    x.mul(&x.constant(sign))
+}
+
+/// Class labels stored as floats, checked against `classes` (labels equal
+/// to `ignore_index` may be out of range).
+fn class_labels<T: Float>(targets: &Tensor<T>, classes: usize, ignore_index: Option<usize>) -> Vec<usize> {
+    let labels: Vec<usize> = targets.data().iter().map(|t| t.to_f64().round() as usize).collect();
+    if let Some(&bad) = labels.iter().find(|&&c| c >= classes && Some(c) != ignore_index) {
+        panic!("class {} out of range for {} classes", bad, classes);
+    }
+    labels
+}
+
+/// Negative log-likelihood of `[batch, classes]` log-probabilities against
+/// class labels `[batch]` stored as floats.
+///
+/// Sample `i` costs `-w[y_i] · log_probs[i, y_i]`. `Mean` divides the sum by
+/// the total weight of the counted samples, as PyTorch does; samples
+/// labelled `ignore_index` cost nothing and carry no weight.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NLLLoss {
+    /// Per-class weights; all ones when `None`.
+    pub weight: Option<Vec<f64>>,
+    pub ignore_index: Option<usize>,
+    pub reduction: Reduction,
+}
+
+impl NLLLoss {
+    pub fn new() -> Self {
+        NLLLoss::default()
+    }
+
+    pub fn with_weight(mut self, weight: Vec<f64>) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_ignore_index(mut self, index: usize) -> Self {
+        self.ignore_index = Some(index);
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, log_probs: &Variable<T>, targets: &Tensor<T>) -> Variable<T> {
+        let shape = log_probs.shape_vec();
+        assert!(shape.len() == 2, "NLLLoss: input must be [batch, classes]");
+        let (batch, classes) = (shape[0], shape[1]);
+        let labels = class_labels(targets, classes, self.ignore_index);
+        assert_eq!(labels.len(), batch, "NLLLoss: one target per row");
+        if let Some(w) = &self.weight {
+            assert_eq!(w.len(), classes, "NLLLoss: one weight per class");
+        }
+        // `pick[i, c] = w[y_i]` at `c = y_i`, so `-(log_probs * pick)` summed
+        // over classes is the weighted per-sample loss.
+        let mut pick = Tensor::zeros(vec![batch, classes]);
+        let mut total_weight = 0.0;
+        for (i, &c) in labels.iter().enumerate() {
+            if Some(c) == self.ignore_index {
+                continue;
+            }
+            let w = self.weight.as_ref().map_or(1.0, |w| w[c]);
+            pick.data_mut()[i * classes + c] = T::from_f64(w);
+            total_weight += w;
+        }
+        let losses = log_probs.mul(&log_probs.constant(pick)).sum_axis(1).neg();
+        reduce_weighted(&losses, self.reduction, total_weight)
+    }
+}
+
+/// `Mean` over per-sample losses divided by their total weight rather than
+/// their count.
+fn reduce_weighted<T: Float>(losses: &Variable<T>, reduction: Reduction, total_weight: f64) -> Variable<T> {
+    match reduction {
+        Reduction::Mean if total_weight > 0.0 => losses.sum().mul_scalar(1.0 / total_weight),
+        // Every sample ignored: a zero that still reaches the graph.
+        Reduction::Mean => losses.sum(),
+        reduction => reduction.reduce(losses),
+    }
+}
+
+/// Cross-entropy of raw `[batch, classes]` logits against class labels:
+/// `NLLLoss` on their log-softmax, with the same weights, `ignore_index`
+/// and reductions.
+///
+/// With `label_smoothing = ε` the target is `(1 - ε) one_hot + ε / C`, so
+/// every sample also pays `ε` times its mean `-w_c log p_c` over the `C`
+/// classes. `forward_soft` takes a target distribution per sample instead,
+/// such as the mixed targets of mixup and cutmix.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CrossEntropyLoss {
+    pub weight: Option<Vec<f64>>,
+    pub ignore_index: Option<usize>,
+    pub label_smoothing: f64,
+    pub reduction: Reduction,
+}
+
+impl CrossEntropyLoss {
+    pub fn new() -> Self {
+        CrossEntropyLoss::default()
+    }
+
+    pub fn with_weight(mut self, weight: Vec<f64>) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn with_ignore_index(mut self, index: usize) -> Self {
+        self.ignore_index = Some(index);
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn with_label_smoothing(mut self, epsilon: f64) -> Self {
+        assert!((0.0..=1.0).contains(&epsilon), "CrossEntropyLoss: label smoothing must be in [0, 1]");
+        self.label_smoothing = epsilon;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, logits: &Variable<T>, targets: &Tensor<T>) -> Variable<T> {
+        let log_probs = logits.log_softmax();
+        let nll = NLLLoss { weight: self.weight.clone(), ignore_index: self.ignore_index, reduction: self.reduction };
+        let epsilon = self.label_smoothing;
+        if epsilon == 0.0 {
+            return nll.forward(&log_probs, targets);
+        }
+        let hard = NLLLoss { reduction: Reduction::None, ..nll }.forward(&log_probs, targets);
+
+        let shape = logits.shape_vec();
+        let (batch, classes) = (shape[0], shape[1]);
+        let weight = |c: usize| self.weight.as_ref().map_or(1.0, |w| w[c]);
+        let mut spread = Tensor::zeros(vec![batch, classes]);
+        let mut total_weight = 0.0;
+        for (i, &y) in class_labels(targets, classes, self.ignore_index).iter().enumerate() {
+            if Some(y) == self.ignore_index {
+                continue;
+            }
+            for c in 0..classes {
+                spread.data_mut()[i * classes + c] = T::from_f64(weight(c) / classes as f64);
+            }
+            total_weight += weight(y);
+        }
+        let smooth = log_probs.mul(&log_probs.constant(spread)).sum_axis(1).neg();
+        let losses = hard.mul_scalar(1.0 - epsilon).add(&smooth.mul_scalar(epsilon));
+        reduce_weighted(&losses, self.reduction, total_weight)
+    }
+
+    /// Cross-entropy against target distributions `[batch, classes]`:
+    /// `-Σ_c w_c p_c log q_c` per sample, after label smoothing of `p`.
+    /// `ignore_index` does not apply; `Mean` averages over the batch.
+    pub fn forward_soft<T: Float>(&self, logits: &Variable<T>, probs: &Tensor<T>) -> Variable<T> {
+        let shape = logits.shape_vec();
+        assert!(shape.len() == 2, "CrossEntropyLoss: logits must be [batch, classes]");
+        assert_eq!(probs.shape_vec(), shape, "CrossEntropyLoss: one target distribution per row");
+        let classes = shape[1];
+        let epsilon = self.label_smoothing;
+        let target: Vec<T> = probs
+            .data()
+            .iter()
+            .enumerate()
+            .map(|(k, &p)| {
+                let w = self.weight.as_ref().map_or(1.0, |w| w[k % classes]);
+                T::from_f64(w * ((1.0 - epsilon) * p.to_f64() + epsilon / classes as f64))
+            })
+            .collect();
+        let target = Tensor::new(target, shape).expect("target shape");
+        let log_probs = logits.log_softmax();
+        self.reduction.reduce(&log_probs.mul(&log_probs.constant(target)).sum_axis(1).neg())
+    }
+}
+
+/// Mean absolute error: `|pred - target|` per element.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct L1Loss {
+    pub reduction: Reduction,
+}
+
+impl L1Loss {
+    pub fn new() -> Self {
+        L1Loss::default()
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, pred: &Variable<T>, target: &Variable<T>) -> Variable<T> {
+        self.reduction.reduce(&abs(&pred.sub(target)))
+    }
+}
+
+/// Huber loss per element, with `d = pred - target`:
+/// `0.5 d²` where `|d| <= delta`, `delta (|d| - 0.5 delta)` elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HuberLoss {
+    pub delta: f64,
+    pub reduction: Reduction,
+}
+
+impl HuberLoss {
+    pub fn new(delta: f64) -> Self {
+        assert!(delta > 0.0, "HuberLoss: delta must be positive");
+        HuberLoss { delta, reduction: Reduction::Mean }
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, pred: &Variable<T>, target: &Variable<T>) -> Variable<T> {
+        let diff = pred.sub(target);
+        let abs_diff = abs(&diff);
+        let delta = T::from_f64(self.delta);
+        let quadratic = diff.data.apply(|d| if d.abs() <= delta { T::ONE } else { T::ZERO });
+        let linear = quadratic.apply(|q| T::ONE - q);
+        let small = diff.mul(&diff).mul_scalar(0.5).mul(&diff.constant(quadratic));
+        let large = abs_diff.add_scalar(-0.5 * self.delta).mul_scalar(self.delta).mul(&diff.constant(linear));
+        self.reduction.reduce(&small.add(&large))
+    }
+}
+
+impl Default for HuberLoss {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Smooth L1 loss: Huber loss divided by `beta`, so it tends to L1 as
+/// `beta → 0` (`0.5 d² / beta` where `|d| < beta`, `|d| - 0.5 beta`
+/// elsewhere).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothL1Loss {
+    pub beta: f64,
+    pub reduction: Reduction,
+}
+
+impl SmoothL1Loss {
+    pub fn new(beta: f64) -> Self {
+        assert!(beta > 0.0, "SmoothL1Loss: beta must be positive");
+        SmoothL1Loss { beta, reduction: Reduction::Mean }
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, pred: &Variable<T>, target: &Variable<T>) -> Variable<T> {
+        let huber = HuberLoss { delta: self.beta, reduction: Reduction::None }.forward(pred, target);
+        self.reduction.reduce(&huber.mul_scalar(1.0 / self.beta))
+    }
+}
+
+impl Default for SmoothL1Loss {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Focal loss (Lin et al.) on raw `[batch, classes]` logits:
+/// `-alpha[y] (1 - p_y)^gamma log p_y` per sample, which down-weights
+/// well-classified samples. `gamma = 0` without `alpha` is cross-entropy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocalLoss {
+    pub gamma: f64,
+    /// Per-class weights; all ones when `None`.
+    pub alpha: Option<Vec<f64>>,
+    pub reduction: Reduction,
+}
+
+impl FocalLoss {
+    pub fn new(gamma: f64) -> Self {
+        assert!(gamma >= 0.0, "FocalLoss: gamma must be non-negative");
+        FocalLoss { gamma, alpha: None, reduction: Reduction::Mean }
+    }
+
+    pub fn with_alpha(mut self, alpha: Vec<f64>) -> Self {
+        self.alpha = Some(alpha);
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, logits: &Variable<T>, targets: &Tensor<T>) -> Variable<T> {
+        let shape = logits.shape_vec();
+        assert!(shape.len() == 2, "FocalLoss: logits must be [batch, classes]");
+        let (batch, classes) = (shape[0], shape[1]);
+        let labels = class_labels(targets, classes, None);
+        let mut one_hot = Tensor::zeros(vec![batch, classes]);
+        let mut alpha = Tensor::zeros(vec![batch]);
+        for (i, &c) in labels.iter().enumerate() {
+            one_hot.data_mut()[i * classes + c] = T::ONE;
+            alpha.data_mut()[i] = T::from_f64(self.alpha.as_ref().map_or(1.0, |a| a[c]));
+        }
+        let log_p = logits.log_softmax().mul(&logits.constant(one_hot)).sum_axis(1);
+        let mut losses = log_p.mul(&logits.constant(alpha)).neg();
+        if self.gamma > 0.0 {
+            let modulating = log_p.exp().neg().add_scalar(1.0).pow(self.gamma);
+            losses = losses.mul(&modulating);
+        }
+        self.reduction.reduce(&losses)
+    }
+}
+
+impl Default for FocalLoss {
+    fn default() -> Self {
+        Self::new(2.0)
+    }
+}
+
+/// Hinge loss for scores against `±1` labels: `max(0, margin - y · pred)`
+/// per element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HingeLoss {
+    pub margin: f64,
+    pub reduction: Reduction,
+}
+
+impl HingeLoss {
+    pub fn new() -> Self {
+        HingeLoss { margin: 1.0, reduction: Reduction::Mean }
+    }
+
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, pred: &Variable<T>, target: &Variable<T>) -> Variable<T> {
+        self.reduction.reduce(&pred.mul(target).neg().add_scalar(self.margin).relu())
+    }
+}
+
+impl Default for HingeLoss {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// KL divergence `KL(target ‖ input)` with `input` given as
+/// log-probabilities: `target (ln target - input)` per element (0 where
+/// `target = 0`). With `log_target` the target is log-probabilities too.
+/// `Mean` averages over all elements; `Sum` divided by the batch size is
+/// the per-sample divergence.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KLDivLoss {
+    pub log_target: bool,
+    pub reduction: Reduction,
+}
+
+impl KLDivLoss {
+    pub fn new() -> Self {
+        KLDivLoss::default()
+    }
+
+    pub fn with_log_target(mut self, log_target: bool) -> Self {
+        self.log_target = log_target;
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, input: &Variable<T>, target: &Variable<T>) -> Variable<T> {
+        let losses = if self.log_target {
+            target.exp().mul(&target.sub(input))
+        } else {
+            // ln 0 is replaced by 0, where the term vanishes anyway.
+            let log_target = target.data.apply(|t| if t > T::ZERO { t.ln() } else { T::ZERO });
+            target.mul(&target.constant(log_target).sub(input))
+        };
+        self.reduction.reduce(&losses)
+    }
+}
+
 /// Hinge loss for SVM-style classification.
 ///
 /// L = mean(max(0, 1 - y * f(x)))
 /// where y ∈ {-1, +1}
-pub fn hinge_loss(pred: &Tensor<f64>, target: &Tensor<f64>) -> f64 {
+pub fn hinge_loss<T: Float>(pred: &Tensor<T>, target: &Tensor<T>) -> f64 {
     let n = pred.numel();
     let mut total_loss = 0.0;
     for i in 0..n {
-        let y = target.data()[i]; // should be -1 or +1
-        let f = pred.data()[i];
+        let y = target.data()[i].to_f64(); // should be -1 or +1
+        let f = pred.data()[i].to_f64();
         total_loss += (1.0 - y * f).max(0.0);
     }
     total_loss / n as f64
@@ -82,11 +509,11 @@ pub fn hinge_loss(pred: &Tensor<f64>, target: &Tensor<f64>) -> f64 {
 ///
 /// L = 0.5 * x² if |x| < 1
 /// L = |x| - 0.5 otherwise
-pub fn smooth_l1_loss(pred: &Tensor<f64>, target: &Tensor<f64>) -> f64 {
+pub fn smooth_l1_loss<T: Float>(pred: &Tensor<T>, target: &Tensor<T>) -> f64 {
     let n = pred.numel();
     let mut total = 0.0;
     for i in 0..n {
-        let diff = (pred.data()[i] - target.data()[i]).abs();
+        let diff = (pred.data()[i] - target.data()[i]).abs().to_f64();
         if diff < 1.0 {
             total += 0.5 * diff * diff;
         } else {
@@ -97,12 +524,12 @@ pub fn smooth_l1_loss(pred: &Tensor<f64>, target: &Tensor<f64>) -> f64 {
 }
 
 /// KL Divergence loss: KL(P || Q) = Σ P(x) * log(P(x)/Q(x))
-pub fn kl_divergence(p: &Tensor<f64>, q: &Tensor<f64>) -> f64 {
+pub fn kl_divergence<T: Float>(p: &Tensor<T>, q: &Tensor<T>) -> f64 {
     assert_eq!(p.numel(), q.numel());
     let mut kl = 0.0;
     for i in 0..p.numel() {
-        let pi = p.data()[i].max(1e-15);
-        let qi = q.data()[i].max(1e-15);
+        let pi = p.data()[i].to_f64().max(1e-15);
+        let qi = q.data()[i].to_f64().max(1e-15);
         kl += pi * (pi / qi).ln();
     }
     kl
@@ -123,6 +550,10 @@ mod tests {
 
         let loss = cross_entropy_loss(&logits, &targets);
         assert!(loss > 0.0 && loss < 2.0, "CE loss = {}", loss);
+
+        // The graph version agrees with the tensor one.
+        let on_graph = cross_entropy(&Variable::param(logits), &targets);
+        assert!((on_graph.data.item().unwrap() - loss).abs() < 1e-12);
     }
 
     #[test]
@@ -144,4 +575,78 @@ mod tests {
         // average = (0.125 + 1.5) / 2 = 0.8125
         assert!((loss - 0.8125).abs() < 0.001, "smooth L1 = {}", loss);
     }
+
+    #[test]
+    fn test_weighted_cross_entropy_with_ignore_index() {
+        let logits = Variable::param(Tensor::from_vec2d(&[
+            vec![2.0, 1.0, 0.1],
+            vec![0.1, 2.0, 1.0],
+            vec![0.5, 0.5, 3.0],
+        ]).unwrap());
+        let targets: Tensor<f64> = Tensor::from_slice(&[0.0, 1.0, 2.0]);
+        // Unweighted, it is the fused cross-entropy.
+        let plain = CrossEntropyLoss::new().forward(&logits, &targets).data.item().unwrap();
+        assert!((plain - cross_entropy(&logits, &targets).data.item().unwrap()).abs() < 1e-12);
+
+        let per_sample = CrossEntropyLoss::new().with_reduction(Reduction::None).forward(&logits, &targets);
+        let l = per_sample.data.data().to_vec();
+        let weighted = CrossEntropyLoss::new().with_weight(vec![1.0, 3.0, 1.0]).with_ignore_index(2).forward(&logits, &targets);
+        // (1·l0 + 3·l1) / (1 + 3); the ignored sample has no weight.
+        assert!((weighted.data.item().unwrap() - (l[0] + 3.0 * l[1]) / 4.0).abs() < 1e-12);
+        weighted.backward();
+        assert!(logits.grad().unwrap().data()[6..].iter().all(|&g| g == 0.0));
+
+        // gamma = 0 focal loss is cross-entropy; gamma > 0 is smaller.
+        let focal0 = FocalLoss::new(0.0).forward(&logits, &targets).data.item().unwrap();
+        let focal2 = FocalLoss::new(2.0).forward(&logits, &targets).data.item().unwrap();
+        assert!((focal0 - plain).abs() < 1e-12 && focal2 < plain);
+    }
+
+    #[test]
+    fn test_regression_and_divergence_losses() {
+        let pred = Variable::param(Tensor::from_slice(&[0.5, 2.0, -3.0]));
+        let target = Variable::input(Tensor::from_slice(&[0.0, 0.0, 0.0]));
+        let item = |v: Variable<f64>| v.data.item().unwrap();
+        assert!((item(L1Loss::new().forward(&pred, &target)) - 5.5 / 3.0).abs() < 1e-12);
+        // 0.125 + 1.5 + 2.5, as the tensor smooth L1.
+        let smooth = SmoothL1Loss::default().with_reduction(Reduction::Sum).forward(&pred, &target);
+        assert!((item(smooth) - 4.125).abs() < 1e-12);
+        // δ = 2: 0.125 + 2.0 + 2·(3 - 1) = 6.125.
+        let huber = HuberLoss::new(2.0).with_reduction(Reduction::Sum).forward(&pred, &target);
+        assert!((item(huber.clone()) - 6.125).abs() < 1e-12);
+        huber.backward();
+        assert_eq!(pred.grad().unwrap().data(), &[0.5, 2.0, -2.0]);
+
+        let labels = Variable::input(Tensor::from_slice(&[1.0, 1.0, -1.0]));
+        let hinge = HingeLoss::new().with_reduction(Reduction::None).forward(&pred, &labels);
+        assert_eq!(hinge.data.data(), &[0.5, 0.0, 0.0]);
+
+        let p: Tensor<f64> = Tensor::from_slice(&[0.5, 0.5, 0.0]);
+        let q: Tensor<f64> = Tensor::from_slice(&[0.25, 0.25, 0.5]);
+        let log_q = Variable::param(q.apply(f64::ln));
+        let kl = KLDivLoss::new().with_reduction(Reduction::Sum).forward(&log_q, &Variable::input(p.clone()));
+        assert!((item(kl) - kl_divergence(&p, &q)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_label_smoothing_and_soft_targets() {
+        let logits = Variable::param(Tensor::from_vec2d(&[vec![2.0, 1.0, 0.1], vec![0.1, 2.0, 1.0]]).unwrap());
+        let targets: Tensor<f64> = Tensor::from_slice(&[0.0, 1.0]);
+        let eps = 0.1;
+        let ce = CrossEntropyLoss::new().forward(&logits, &targets).data.item().unwrap();
+        let smoothed = CrossEntropyLoss::new().with_label_smoothing(eps).forward(&logits, &targets);
+        // (1 - ε) CE + ε · mean over samples and classes of -log p.
+        let log_p = logits.data.log_softmax().unwrap();
+        let uniform = -log_p.data().iter().sum::<f64>() / 6.0;
+        assert!((smoothed.data.item().unwrap() - ((1.0 - eps) * ce + eps * uniform)).abs() < 1e-12);
+
+        // One-hot soft targets reproduce the hard loss, smoothing included.
+        let one_hot: Tensor<f64> = Tensor::from_vec2d(&[vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]).unwrap();
+        let soft = CrossEntropyLoss::new().with_label_smoothing(eps).forward_soft(&logits, &one_hot);
+        assert!((soft.data.item().unwrap() - smoothed.data.item().unwrap()).abs() < 1e-12);
+        let mixed: Tensor<f64> = Tensor::from_vec2d(&[vec![0.7, 0.3, 0.0], vec![0.0, 1.0, 0.0]]).unwrap();
+        let per_sample = CrossEntropyLoss::new().with_reduction(Reduction::None).forward_soft(&logits, &mixed);
+        let expected = -(0.7 * log_p.data()[0] + 0.3 * log_p.data()[1]);
+        assert!((per_sample.data.data()[0] - expected).abs() < 1e-12);
+    }
 }
@@ -0,0 +1,207 @@
+//! Losses for learning embeddings, where what matters is the distance
+//! between rows of `[batch, dim]` embeddings rather than a target value.
+//! Trained embeddings can be searched with the neighbors crate.
+
+use oxidize_ml_autodiff::Variable;
+use oxidize_ml_core::{Float, Tensor};
+
+use crate::loss::Reduction;
+
+/// Row-wise Euclidean distance `[batch]`; `eps` keeps the gradient finite
+/// at zero distance.
+fn pairwise_distance<T: Float>(a: &Variable<T>, b: &Variable<T>, eps: f64) -> Variable<T> {
+    let diff = a.sub(b);
+    diff.mul(&diff).sum_axis(1).add_scalar(eps).pow(0.5)
+}
+
+/// Row-wise cosine similarity `[batch]`.
+fn cosine_similarity<T: Float>(a: &Variable<T>, b: &Variable<T>, eps: f64) -> Variable<T> {
+    let dot = a.mul(b).sum_axis(1);
+    let norms = a.mul(a).sum_axis(1).mul(&b.mul(b).sum_axis(1)).add_scalar(eps).pow(0.5);
+    dot.div(&norms)
+}
+
+fn check_rows<T: Float>(name: &str, a: &Variable<T>, b: &Variable<T>) {
+    assert!(a.shape_vec().len() == 2, "{}: embeddings must be [batch, dim]", name);
+    assert_eq!(a.shape_vec(), b.shape_vec(), "{}: embedding shapes differ", name);
+}
+
+/// `max(0, d(anchor, positive) - d(anchor, negative) + margin)` per
+/// triplet, with Euclidean `d`.
+///
+/// With `swap`, the negative's distance is the smaller of its distances to
+/// the anchor and to the positive (Balntas et al.).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TripletMarginLoss {
+    pub margin: f64,
+    pub swap: bool,
+    pub eps: f64,
+    pub reduction: Reduction,
+}
+
+impl TripletMarginLoss {
+    pub fn new(margin: f64) -> Self {
+        TripletMarginLoss { margin, swap: false, eps: 1e-12, reduction: Reduction::Mean }
+    }
+
+    pub fn with_swap(mut self, swap: bool) -> Self {
+        self.swap = swap;
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, anchor: &Variable<T>, positive: &Variable<T>, negative: &Variable<T>) -> Variable<T> {
+        check_rows("TripletMarginLoss", anchor, positive);
+        check_rows("TripletMarginLoss", anchor, negative);
+        let d_pos = pairwise_distance(anchor, positive, self.eps);
+        let mut d_neg = pairwise_distance(anchor, negative, self.eps);
+        if self.swap {
+            let d_swap = pairwise_distance(positive, negative, self.eps);
+            let (use_swap, keep): (Vec<T>, Vec<T>) = d_swap
+                .data
+                .data()
+                .iter()
+                .zip(d_neg.data.data())
+                .map(|(&s, &n)| if s < n { (T::ONE, T::ZERO) } else { (T::ZERO, T::ONE) })
+                .unzip();
+            let mask = |m: Vec<T>| d_neg.constant(Tensor::from_slice(&m));
+            d_neg = d_swap.mul(&mask(use_swap)).add(&d_neg.mul(&mask(keep)));
+        }
+        self.reduction.reduce(&d_pos.sub(&d_neg).add_scalar(self.margin).relu())
+    }
+}
+
+impl Default for TripletMarginLoss {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Contrastive loss (Hadsell et al.) over pairs labelled 1 (similar) or 0
+/// (dissimilar): `y d² + (1 - y) max(0, margin - d)²`, with Euclidean `d`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContrastiveLoss {
+    pub margin: f64,
+    pub eps: f64,
+    pub reduction: Reduction,
+}
+
+impl ContrastiveLoss {
+    pub fn new(margin: f64) -> Self {
+        ContrastiveLoss { margin, eps: 1e-12, reduction: Reduction::Mean }
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, a: &Variable<T>, b: &Variable<T>, similar: &Tensor<T>) -> Variable<T> {
+        check_rows("ContrastiveLoss", a, b);
+        assert_eq!(similar.numel(), a.shape_vec()[0], "ContrastiveLoss: one label per pair");
+        let labels = similar.reshape(vec![similar.numel()]).expect("labels");
+        let d = pairwise_distance(a, b, self.eps);
+        let pull = d.mul(&d).mul(&d.constant(labels.clone()));
+        let push = d.neg().add_scalar(self.margin).relu().pow(2.0).mul(&d.constant(labels.apply(|y| T::ONE - y)));
+        self.reduction.reduce(&pull.add(&push))
+    }
+}
+
+impl Default for ContrastiveLoss {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Cosine embedding loss over pairs labelled 1 (similar) or -1
+/// (dissimilar): `1 - cos(a, b)` for similar pairs,
+/// `max(0, cos(a, b) - margin)` for dissimilar ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CosineEmbeddingLoss {
+    pub margin: f64,
+    pub eps: f64,
+    pub reduction: Reduction,
+}
+
+impl CosineEmbeddingLoss {
+    pub fn new() -> Self {
+        CosineEmbeddingLoss { margin: 0.0, eps: 1e-12, reduction: Reduction::Mean }
+    }
+
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    pub fn forward<T: Float>(&self, a: &Variable<T>, b: &Variable<T>, labels: &Tensor<T>) -> Variable<T> {
+        check_rows("CosineEmbeddingLoss", a, b);
+        assert_eq!(labels.numel(), a.shape_vec()[0], "CosineEmbeddingLoss: one label per pair");
+        let labels = labels.reshape(vec![labels.numel()]).expect("labels");
+        let positive = labels.apply(|y| if y > T::ZERO { T::ONE } else { T::ZERO });
+        let negative = positive.apply(|p| T::ONE - p);
+        let cos = cosine_similarity(a, b, self.eps);
+        let pull = cos.neg().add_scalar(1.0).mul(&cos.constant(positive));
+        let push = cos.add_scalar(-self.margin).relu().mul(&cos.constant(negative));
+        self.reduction.reduce(&pull.add(&push))
+    }
+}
+
+impl Default for CosineEmbeddingLoss {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(data: &[[f64; 2]]) -> Variable {
+        Variable::param(Tensor::from_vec2d(&data.iter().map(|r| r.to_vec()).collect::<Vec<_>>()).unwrap())
+    }
+
+    #[test]
+    fn test_pair_and_triplet_losses() {
+        let anchor = rows(&[[0.0, 0.0], [0.0, 0.0]]);
+        let positive = rows(&[[3.0, 4.0], [1.0, 0.0]]);
+        let negative = rows(&[[3.0, 5.0], [0.0, 6.0]]);
+        let triplet = TripletMarginLoss::new(1.0).with_reduction(Reduction::None).forward(&anchor, &positive, &negative);
+        // max(0, 5 - √34 + 1) and max(0, 1 - 6 + 1).
+        let t = triplet.data.data();
+        assert!((t[0] - (6.0 - 34f64.sqrt())).abs() < 1e-9 && t[1] == 0.0);
+        // The first negative is 1 from its positive, so swapping uses that.
+        let swapped = TripletMarginLoss::new(1.0).with_swap(true).with_reduction(Reduction::None).forward(&anchor, &positive, &negative);
+        assert!((swapped.data.data()[0] - 5.0).abs() < 1e-9 && swapped.data.data()[1] == 0.0);
+
+        let similar: Tensor<f64> = Tensor::from_slice(&[1.0, 0.0]);
+        let contrastive = ContrastiveLoss::new(2.0).with_reduction(Reduction::Sum).forward(&anchor, &positive, &similar);
+        // 5² for the similar pair; the dissimilar pair is 1 apart: (2 - 1)².
+        assert!((contrastive.data.item().unwrap() - 26.0).abs() < 1e-9);
+
+        let labels: Tensor<f64> = Tensor::from_slice(&[1.0, -1.0]);
+        let other = rows(&[[4.0, 3.0], [0.0, 6.0]]);
+        let cosine = CosineEmbeddingLoss::new().with_reduction(Reduction::None).forward(&positive, &other, &labels);
+        // cos = 0.96 and 0: 1 - 0.96 and max(0, 0 - 0).
+        assert!((cosine.data.data()[0] - 0.04).abs() < 1e-9 && cosine.data.data()[1].abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_triplet_gradient_pulls_positive_closer() {
+        let anchor = rows(&[[0.0, 0.0]]);
+        let positive = rows(&[[2.0, 0.0]]);
+        let negative = rows(&[[0.0, 1.0]]);
+        TripletMarginLoss::default().forward(&anchor, &positive, &negative).backward();
+        // The positive moves toward the anchor, the negative away from it.
+        assert!(positive.grad().unwrap().data()[0] > 0.0);
+        assert!(negative.grad().unwrap().data()[1] < 0.0);
+    }
+}
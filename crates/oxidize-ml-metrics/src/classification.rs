@@ -1,4 +1,4 @@
-use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_core::{DistanceMetric, Float, Tensor};
 
 /// Compute accuracy: fraction of correct predictions.
 pub fn accuracy<T: Float>(y_true: &Tensor<T>, y_pred: &Tensor<T>) -> f64 {
@@ -151,18 +151,39 @@ pub fn log_loss<T: Float>(y_true: &Tensor<T>, y_pred_proba: &Tensor<T>) -> f64 {
     total / n as f64
 }
 
+/// Reliability curve of binary probabilities: `[0, 1]` is cut into
+/// `n_bins` equal bins and, for each bin holding any predictions, the
+/// fraction of positives is paired with the mean predicted probability.
+///
+/// Returns `(prob_true, prob_pred)`; a calibrated model has them equal.
+pub fn calibration_curve<T: Float>(y_true: &Tensor<T>, y_prob: &Tensor<T>, n_bins: usize) -> (Vec<f64>, Vec<f64>) {
+    let n_bins = n_bins.max(1);
+    let mut positives = vec![0.0; n_bins];
+    let mut prob_sum = vec![0.0; n_bins];
+    let mut counts = vec![0usize; n_bins];
+    for (y, p) in y_true.data().iter().zip(y_prob.data()) {
+        let p = p.to_f64().clamp(0.0, 1.0);
+        let bin = ((p * n_bins as f64) as usize).min(n_bins - 1);
+        positives[bin] += y.to_f64();
+        prob_sum[bin] += p;
+        counts[bin] += 1;
+    }
+    (0..n_bins)
+        .filter(|&b| counts[b] > 0)
+        .map(|b| (positives[b] / counts[b] as f64, prob_sum[b] / counts[b] as f64))
+        .unzip()
+}
+
 /// ROC-AUC for binary classification.
 ///
 /// Computes the Area Under the Receiver Operating Characteristic Curve
 /// using the trapezoidal rule over all thresholds.
 pub fn roc_auc<T: Float>(y_true: &Tensor<T>, y_scores: &Tensor<T>) -> f64 {
     let n = y_true.numel();
-    // Create (score, label) pairs and sort by score descending
-    let mut pairs: Vec<(f64, f64)> = y_scores.data().iter()
-        .zip(y_true.data().iter())
-        .map(|(&s, &t)| (s.to_f64(), t.to_f64().round()))
+    // Create (score, label) pairs sorted by score descending
+    let pairs: Vec<(f64, f64)> = y_scores.argsort(true).into_iter()
+        .map(|i| (y_scores.data()[i].to_f64(), y_true.data()[i].to_f64().round()))
         .collect();
-    pairs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
 
     let total_pos = pairs.iter().filter(|(_, t)| *t > 0.5).count() as f64;
     let total_neg = n as f64 - total_pos;
@@ -235,6 +256,11 @@ pub fn mcc_binary<T: Float>(y_true: &Tensor<T>, y_pred: &Tensor<T>) -> f64 {
 /// - a = mean distance to points in the same cluster
 /// - b = mean distance to points in the nearest other cluster
 pub fn silhouette_score<T: Float>(x: &Tensor<T>, labels: &Tensor<T>) -> f64 {
+    silhouette_score_with_metric(x, labels, &DistanceMetric::Euclidean)
+}
+
+/// Silhouette score with distances measured by `metric`.
+pub fn silhouette_score_with_metric<T: Float>(x: &Tensor<T>, labels: &Tensor<T>, metric: &DistanceMetric<T>) -> f64 {
     let n = x.shape().dim(0).unwrap();
     let p = x.shape().dim(1).unwrap();
 
@@ -252,13 +278,7 @@ pub fn silhouette_score<T: Float>(x: &Tensor<T>, labels: &Tensor<T>) -> f64 {
             if i == j { continue; }
             let label_j = labels.data()[j].to_f64().round() as usize;
 
-            // Euclidean distance
-            let mut dist = 0.0;
-            for k in 0..p {
-                let diff = x.get(&[i, k]).unwrap().to_f64() - x.get(&[j, k]).unwrap().to_f64();
-                dist += diff * diff;
-            }
-            dist = dist.sqrt();
+            let dist = metric.distance(&x.data()[i * p..(i + 1) * p], &x.data()[j * p..(j + 1) * p]).to_f64();
 
             let entry = n_classes.entry(label_j).or_insert((0.0, 0));
             entry.0 += dist;
@@ -0,0 +1,205 @@
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::{RngHandle, Tensor, TensorError};
+use oxidize_ml_pipeline::{DecisionScorer, Estimator};
+use oxidize_ml_svm::platt_scaling;
+use rand::seq::SliceRandom;
+
+/// How `CalibratedClassifier` maps scores to probabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationMethod {
+    /// Platt scaling: a logistic curve `1 / (1 + exp(a·s + b))`. Needs
+    /// little data; suits scores with a sigmoid-shaped distortion, such as
+    /// SVM margins.
+    Sigmoid,
+    /// Isotonic regression: any non-decreasing step function. More
+    /// flexible, but overfits small calibration sets.
+    Isotonic,
+}
+
+/// Fitted score-to-probability map.
+#[derive(Debug, Clone)]
+enum Calibrator {
+    Sigmoid { a: f64, b: f64 },
+    /// Block scores and probabilities, interpolated linearly in between.
+    Isotonic { scores: Vec<f64>, probs: Vec<f64> },
+}
+
+impl Calibrator {
+    fn predict(&self, s: f64) -> f64 {
+        match self {
+            Calibrator::Sigmoid { a, b } => 1.0 / (1.0 + (a * s + b).exp()),
+            Calibrator::Isotonic { scores, probs } => {
+                let n = scores.len();
+                if s <= scores[0] {
+                    return probs[0];
+                }
+                if s >= scores[n - 1] {
+                    return probs[n - 1];
+                }
+                let hi = scores.partition_point(|&v| v <= s);
+                let lo = hi - 1;
+                probs[lo] + (probs[hi] - probs[lo]) * (s - scores[lo]) / (scores[hi] - scores[lo])
+            }
+        }
+    }
+}
+
+/// Pool-adjacent-violators fit of a non-decreasing map from score to label.
+fn fit_isotonic(scores: &[f64], labels: &[f64]) -> Calibrator {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&i, &j| scores[i].total_cmp(&scores[j]));
+    // Blocks of (mean score, mean label, weight).
+    let mut blocks: Vec<(f64, f64, f64)> = Vec::with_capacity(order.len());
+    for i in order {
+        blocks.push((scores[i], labels[i], 1.0));
+        while blocks.len() > 1 && blocks[blocks.len() - 2].1 >= blocks[blocks.len() - 1].1 {
+            let (s2, y2, w2) = blocks.pop().unwrap();
+            let (s1, y1, w1) = blocks.pop().unwrap();
+            let w = w1 + w2;
+            blocks.push(((s1 * w1 + s2 * w2) / w, (y1 * w1 + y2 * w2) / w, w));
+        }
+    }
+    Calibrator::Isotonic {
+        scores: blocks.iter().map(|b| b.0).collect(),
+        probs: blocks.iter().map(|b| b.1).collect(),
+    }
+}
+
+/// Wraps a binary classifier so that `predict_proba` returns calibrated
+/// probabilities: a share of the training rows (stratified by class) is
+/// held out, the classifier is fitted on the rest, and a sigmoid or
+/// isotonic map from its decision scores to the held-out labels is fitted.
+///
+/// Labels must be 0 and 1.
+pub struct CalibratedClassifier {
+    pub(crate) base: Box<dyn DecisionScorer>,
+    pub method: CalibrationMethod,
+    /// Share of training rows held out to fit the calibration map.
+    pub calibration_ratio: f64,
+    pub seed: Option<u64>,
+    calibrator: Option<Calibrator>,
+}
+
+impl CalibratedClassifier {
+    pub fn new(base: Box<dyn DecisionScorer>, method: CalibrationMethod) -> Self {
+        CalibratedClassifier {
+            base,
+            method,
+            calibration_ratio: 0.25,
+            seed: None,
+            calibrator: None,
+        }
+    }
+
+    pub fn with_calibration_ratio(mut self, ratio: f64) -> Self {
+        self.calibration_ratio = ratio;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The wrapped classifier, fitted on the rows not held out.
+    pub fn base(&self) -> &dyn DecisionScorer {
+        self.base.as_ref()
+    }
+
+    /// Calibrated probability of class 1 for each row, `[n]`.
+    pub fn predict_proba(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        let calibrator = self
+            .calibrator
+            .as_ref()
+            .ok_or_else(|| TensorError::InvalidOperation("CalibratedClassifier not fitted".into()))?;
+        let scores = self.base.decision_scores(x)?;
+        Tensor::new(scores.data().iter().map(|&s| calibrator.predict(s)).collect(), vec![scores.numel()])
+    }
+}
+
+impl Estimator for CalibratedClassifier {
+    fn fit(&mut self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        if y.numel() != n {
+            return Err(TensorError::ShapeMismatch {
+                expected: vec![n],
+                got: y.shape_vec(),
+            });
+        }
+        if y.data().iter().any(|&v| v != 0.0 && v != 1.0) {
+            return Err(TensorError::InvalidOperation("CalibratedClassifier needs 0/1 labels".into()));
+        }
+
+        // Stratified hold-out: each class gives the same share of its rows.
+        let mut rng = RngHandle::new(self.seed);
+        let (mut train, mut held) = (Vec::new(), Vec::new());
+        for class in [0.0, 1.0] {
+            let mut rows: Vec<usize> = (0..n).filter(|&i| y.data()[i] == class).collect();
+            if rows.len() < 2 {
+                return Err(TensorError::InvalidOperation(
+                    "CalibratedClassifier needs at least 2 rows of each class".into(),
+                ));
+            }
+            rows.shuffle(&mut rng);
+            let k = ((rows.len() as f64 * self.calibration_ratio).round() as usize).clamp(1, rows.len() - 1);
+            held.extend_from_slice(&rows[..k]);
+            train.extend_from_slice(&rows[k..]);
+        }
+        train.sort_unstable();
+        held.sort_unstable();
+
+        self.base.fit(&x.index_select(0, &train)?, &y.index_select(0, &train)?)?;
+        let scores = self.base.decision_scores(&x.index_select(0, &held)?)?;
+        let labels = y.index_select(0, &held)?;
+        self.calibrator = Some(match self.method {
+            CalibrationMethod::Sigmoid => {
+                let (a, b) = platt_scaling(scores.data(), labels.data());
+                Calibrator::Sigmoid { a, b }
+            }
+            CalibrationMethod::Isotonic => fit_isotonic(scores.data(), labels.data()),
+        });
+        Ok(())
+    }
+
+    fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        Ok(self.predict_proba(x)?.apply(|p| if p >= 0.5 { 1.0 } else { 0.0 }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_svm::{Kernel, SVC};
+    use oxidize_ml_metrics::{calibration_curve, log_loss};
+
+    #[test]
+    fn test_calibrated_classifier() {
+        // Overlapping classes: P(y = 1 | x) = x on a grid over [0, 1].
+        let (mut xs, mut ys) = (Vec::new(), Vec::new());
+        for i in 0..200 {
+            let v = (i % 20) as f64 / 19.0;
+            xs.push(v);
+            ys.push(if ((i / 20) as f64) < v * 10.0 { 1.0 } else { 0.0 });
+        }
+        let x = Tensor::new(xs, vec![200, 1]).unwrap();
+        let y = Tensor::new(ys, vec![200]).unwrap();
+        let svc = || Box::new(SVC::new(1.0, Kernel::Linear, 50));
+
+        for method in [CalibrationMethod::Sigmoid, CalibrationMethod::Isotonic] {
+            let mut model = CalibratedClassifier::new(svc(), method).with_seed(Some(0));
+            model.fit(&x, &y).unwrap();
+            let proba = model.predict_proba(&x).unwrap();
+            assert!(proba.data().iter().all(|&p| (0.0..=1.0).contains(&p)));
+            assert!(log_loss(&y, &proba) < 0.55);
+
+            let (prob_true, prob_pred) = calibration_curve(&y, &proba, 5);
+            assert_eq!(prob_true.len(), prob_pred.len());
+            let gap = prob_true.iter().zip(&prob_pred).map(|(t, p)| (t - p).abs()).fold(0.0, f64::max);
+            assert!(gap < 0.2, "{:?}: calibration gap {}", method, gap);
+        }
+
+        let one_class = Tensor::new(vec![0.0; 200], vec![200]).unwrap();
+        let mut model = CalibratedClassifier::new(svc(), CalibrationMethod::Sigmoid);
+        assert!(model.fit(&x, &one_class).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod split;
+pub mod validation;
+pub mod search;
+pub mod calibration;
+
+pub use split::*;
+pub use validation::*;
+pub use search::*;
+pub use calibration::*;
@@ -0,0 +1,575 @@
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::{RngHandle, Tensor, TensorError};
+use oxidize_ml_pipeline::Estimator;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::split::{CrossValidator, Fold};
+use crate::validation::Scoring;
+
+/// A single hyperparameter value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Float(f64),
+    Int(i64),
+    Bool(bool),
+    Str(String),
+}
+
+impl ParamValue {
+    /// The value as a float; integers are converted.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ParamValue::Float(v) => Some(v),
+            ParamValue::Int(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ParamValue::Int(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            ParamValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ParamValue::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Float(v) => write!(f, "{}", v),
+            ParamValue::Int(v) => write!(f, "{}", v),
+            ParamValue::Bool(v) => write!(f, "{}", v),
+            ParamValue::Str(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl From<f64> for ParamValue {
+    fn from(v: f64) -> Self {
+        ParamValue::Float(v)
+    }
+}
+
+impl From<i64> for ParamValue {
+    fn from(v: i64) -> Self {
+        ParamValue::Int(v)
+    }
+}
+
+impl From<i32> for ParamValue {
+    fn from(v: i32) -> Self {
+        ParamValue::Int(v as i64)
+    }
+}
+
+impl From<usize> for ParamValue {
+    fn from(v: usize) -> Self {
+        ParamValue::Int(v as i64)
+    }
+}
+
+impl From<bool> for ParamValue {
+    fn from(v: bool) -> Self {
+        ParamValue::Bool(v)
+    }
+}
+
+impl From<&str> for ParamValue {
+    fn from(v: &str) -> Self {
+        ParamValue::Str(v.to_string())
+    }
+}
+
+/// One candidate's named hyperparameters, in the order they were declared.
+///
+/// The typed getters panic if the parameter is missing or has another type:
+/// that is a mismatch between the search space and the model factory.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Params(pub Vec<(String, ParamValue)>);
+
+impl Params {
+    pub fn get(&self, name: &str) -> Option<&ParamValue> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    fn expect<'a, V>(&'a self, name: &str, kind: &str, f: impl Fn(&'a ParamValue) -> Option<V>) -> V {
+        let value = self.get(name).unwrap_or_else(|| panic!("no parameter named '{}' in {}", name, self));
+        f(value).unwrap_or_else(|| panic!("parameter '{}' = {} is not {}", name, value, kind))
+    }
+
+    pub fn f64(&self, name: &str) -> f64 {
+        self.expect(name, "a number", ParamValue::as_f64)
+    }
+
+    pub fn usize(&self, name: &str) -> usize {
+        self.expect(name, "a non-negative integer", |v| v.as_i64().and_then(|i| usize::try_from(i).ok()))
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        self.expect(name, "a bool", ParamValue::as_bool)
+    }
+
+    pub fn str(&self, name: &str) -> &str {
+        self.expect(name, "a string", ParamValue::as_str)
+    }
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.0.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+        write!(f, "{{{}}}", parts.join(", "))
+    }
+}
+
+/// Lists of values to try for each parameter; the grid is their Cartesian product.
+#[derive(Debug, Clone, Default)]
+pub struct ParamGrid {
+    params: Vec<(String, Vec<ParamValue>)>,
+}
+
+impl ParamGrid {
+    pub fn new() -> Self {
+        ParamGrid { params: Vec::new() }
+    }
+
+    pub fn add<V: Into<ParamValue>>(mut self, name: &str, values: Vec<V>) -> Self {
+        self.params.push((name.to_string(), values.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Every combination, varying the last-added parameter fastest.
+    pub fn candidates(&self) -> Vec<Params> {
+        let mut out = vec![Params::default()];
+        for (name, values) in &self.params {
+            out = out
+                .iter()
+                .flat_map(|p| {
+                    values.iter().map(move |v| {
+                        let mut p = p.clone();
+                        p.0.push((name.clone(), v.clone()));
+                        p
+                    })
+                })
+                .collect();
+        }
+        out
+    }
+}
+
+/// How `RandomizedSearchCV` samples one parameter.
+#[derive(Debug, Clone)]
+pub enum ParamDistribution {
+    /// One of the listed values, uniformly.
+    Choice(Vec<ParamValue>),
+    /// A float uniform on `[low, high)`.
+    Uniform { low: f64, high: f64 },
+    /// A float whose logarithm is uniform on `[ln low, ln high)`; suits
+    /// scale parameters such as `alpha`, `C` or `gamma`.
+    LogUniform { low: f64, high: f64 },
+    /// An integer uniform on `low..=high`.
+    IntUniform { low: i64, high: i64 },
+}
+
+impl ParamDistribution {
+    fn sample(&self, rng: &mut RngHandle) -> ParamValue {
+        match self {
+            ParamDistribution::Choice(values) => values.choose(rng).expect("Choice needs at least one value").clone(),
+            ParamDistribution::Uniform { low, high } => ParamValue::Float(rng.gen_range(*low..*high)),
+            ParamDistribution::LogUniform { low, high } => {
+                ParamValue::Float(rng.gen_range(low.ln()..high.ln()).exp())
+            }
+            ParamDistribution::IntUniform { low, high } => ParamValue::Int(rng.gen_range(*low..=*high)),
+        }
+    }
+}
+
+/// Distributions to sample each parameter from.
+#[derive(Debug, Clone, Default)]
+pub struct ParamDistributions {
+    params: Vec<(String, ParamDistribution)>,
+}
+
+impl ParamDistributions {
+    pub fn new() -> Self {
+        ParamDistributions { params: Vec::new() }
+    }
+
+    pub fn add(mut self, name: &str, distribution: ParamDistribution) -> Self {
+        self.params.push((name.to_string(), distribution));
+        self
+    }
+
+    /// `n_iter` candidates. When every parameter is a `Choice`, candidates
+    /// are drawn from the grid without replacement (so at most the grid size).
+    pub fn candidates(&self, n_iter: usize, seed: Option<u64>) -> Vec<Params> {
+        let mut rng = RngHandle::new(seed);
+        let choices: Option<Vec<(String, Vec<ParamValue>)>> = self
+            .params
+            .iter()
+            .map(|(n, d)| match d {
+                ParamDistribution::Choice(v) => Some((n.clone(), v.clone())),
+                _ => None,
+            })
+            .collect();
+        if let Some(params) = choices {
+            let mut grid = ParamGrid { params }.candidates();
+            grid.shuffle(&mut rng);
+            grid.truncate(n_iter);
+            return grid;
+        }
+        (0..n_iter)
+            .map(|_| Params(self.params.iter().map(|(n, d)| (n.clone(), d.sample(&mut rng))).collect()))
+            .collect()
+    }
+}
+
+/// Cross-validation outcome of one candidate.
+#[derive(Debug, Clone)]
+pub struct CvResult {
+    pub params: Params,
+    /// One score per fold.
+    pub scores: Vec<f64>,
+    pub mean_score: f64,
+    pub std_score: f64,
+    /// 1 for the best mean score.
+    pub rank: usize,
+}
+
+/// Evaluate every candidate on the same folds, fitting `n_jobs` models at a time.
+fn evaluate<E, F>(
+    factory: &F,
+    candidates: Vec<Params>,
+    x: &Tensor<f64>,
+    y: &Tensor<f64>,
+    folds: &[Fold],
+    scoring: Scoring,
+    n_jobs: usize,
+) -> TensorResult<Vec<CvResult>>
+where
+    E: Estimator,
+    F: Fn(&Params) -> E + Sync,
+{
+    if candidates.is_empty() {
+        return Err(TensorError::InvalidOperation("parameter search has no candidates".into()));
+    }
+    let n_tasks = candidates.len() * folds.len();
+    let next = AtomicUsize::new(0);
+    let scores: Mutex<Vec<Option<TensorResult<f64>>>> = Mutex::new((0..n_tasks).map(|_| None).collect());
+
+    let run = |task: usize| -> TensorResult<f64> {
+        let (train, test) = &folds[task % folds.len()];
+        let mut model = factory(&candidates[task / folds.len()]);
+        model.fit(&x.index_select(0, train)?, &y.index_select(0, train)?)?;
+        let y_pred = model.predict(&x.index_select(0, test)?)?;
+        Ok(scoring.score(&y.index_select(0, test)?, &y_pred))
+    };
+    let worker = || loop {
+        let task = next.fetch_add(1, Ordering::Relaxed);
+        if task >= n_tasks {
+            break;
+        }
+        let score = run(task);
+        scores.lock().unwrap()[task] = Some(score);
+    };
+    std::thread::scope(|s| {
+        for _ in 1..n_jobs.clamp(1, n_tasks) {
+            s.spawn(worker);
+        }
+        worker();
+    });
+
+    let scores = scores.into_inner().unwrap();
+    let mut results = Vec::with_capacity(candidates.len());
+    for (params, chunk) in candidates.into_iter().zip(scores.chunks(folds.len())) {
+        let fold_scores = chunk.iter().map(|s| s.clone().expect("every task ran")).collect::<TensorResult<Vec<f64>>>()?;
+        let n = fold_scores.len() as f64;
+        let mean = fold_scores.iter().sum::<f64>() / n;
+        let std = (fold_scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n).sqrt();
+        results.push(CvResult { params, scores: fold_scores, mean_score: mean, std_score: std, rank: 0 });
+    }
+    // Rank by mean score, NaN last, ties keeping candidate order.
+    let mut order: Vec<usize> = (0..results.len()).collect();
+    order.sort_by(|&a, &b| {
+        let key = |i: usize| {
+            let m = results[i].mean_score;
+            if m.is_nan() {
+                f64::NEG_INFINITY
+            } else {
+                m
+            }
+        };
+        key(b).total_cmp(&key(a))
+    });
+    for (rank, i) in order.into_iter().enumerate() {
+        results[i].rank = rank + 1;
+    }
+    Ok(results)
+}
+
+fn default_n_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// State shared by both searches after `fit`.
+struct Fitted<E> {
+    results: Vec<CvResult>,
+    best: usize,
+    estimator: Option<E>,
+}
+
+impl<E: Estimator> Fitted<E> {
+    fn run<F>(
+        factory: &F,
+        candidates: Vec<Params>,
+        x: &Tensor<f64>,
+        y: &Tensor<f64>,
+        folds: &[Fold],
+        scoring: Scoring,
+        n_jobs: usize,
+    ) -> TensorResult<Self>
+    where
+        F: Fn(&Params) -> E + Sync,
+    {
+        let results = evaluate(factory, candidates, x, y, folds, scoring, n_jobs)?;
+        let best = results.iter().position(|r| r.rank == 1).expect("one candidate ranks first");
+        Ok(Fitted { results, best, estimator: None })
+    }
+
+    /// Fit the best candidate on all of the data.
+    fn refit<F: Fn(&Params) -> E>(&mut self, factory: &F, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<()> {
+        let mut model = factory(&self.results[self.best].params);
+        model.fit(x, y)?;
+        self.estimator = Some(model);
+        Ok(())
+    }
+}
+
+fn not_fitted() -> TensorError {
+    TensorError::InvalidOperation("search has not been fitted".into())
+}
+
+macro_rules! search_accessors {
+    () => {
+        /// Worker threads used for fitting (defaults to the number of CPUs).
+        pub fn with_n_jobs(mut self, n_jobs: usize) -> Self {
+            self.n_jobs = n_jobs.max(1);
+            self
+        }
+
+        /// Whether to refit the best candidate on all of the data (default `true`).
+        pub fn with_refit(mut self, refit: bool) -> Self {
+            self.refit = refit;
+            self
+        }
+
+        /// Parameters of the best candidate (`best_params_`).
+        pub fn best_params(&self) -> Option<&Params> {
+            self.fitted.as_ref().map(|f| &f.results[f.best].params)
+        }
+
+        /// Mean cross-validated score of the best candidate (`best_score_`).
+        pub fn best_score(&self) -> Option<f64> {
+            self.fitted.as_ref().map(|f| f.results[f.best].mean_score)
+        }
+
+        /// The full results table, one row per candidate (`cv_results_`).
+        pub fn cv_results(&self) -> &[CvResult] {
+            self.fitted.as_ref().map_or(&[], |f| &f.results)
+        }
+
+        /// The best candidate refitted on all of the data, if `refit` is on.
+        pub fn best_estimator(&self) -> Option<&E> {
+            self.fitted.as_ref().and_then(|f| f.estimator.as_ref())
+        }
+
+        /// Predict with the refitted best estimator.
+        pub fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+            self.best_estimator().ok_or_else(not_fitted)?.predict(x)
+        }
+    };
+}
+
+/// Exhaustive search over a `ParamGrid`, scoring each candidate by cross-validation.
+///
+/// Models are built by `factory` from each candidate's `Params`, e.g.
+/// `|p: &Params| Ridge::new(p.f64("alpha"), true)`. All candidates are scored
+/// on the same folds.
+pub struct GridSearchCV<E, F, C> {
+    pub factory: F,
+    pub param_grid: ParamGrid,
+    pub cv: C,
+    pub scoring: Scoring,
+    pub n_jobs: usize,
+    pub refit: bool,
+    fitted: Option<Fitted<E>>,
+}
+
+impl<E, F, C> GridSearchCV<E, F, C>
+where
+    E: Estimator,
+    F: Fn(&Params) -> E + Sync,
+    C: CrossValidator,
+{
+    pub fn new(factory: F, param_grid: ParamGrid, cv: C, scoring: Scoring) -> Self {
+        GridSearchCV {
+            factory,
+            param_grid,
+            cv,
+            scoring,
+            n_jobs: default_n_jobs(),
+            refit: true,
+            fitted: None,
+        }
+    }
+
+    pub fn fit(&mut self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<()> {
+        let candidates = self.param_grid.candidates();
+        let folds = self.cv.split(x, y)?;
+        let mut fitted = Fitted::run(&self.factory, candidates, x, y, &folds, self.scoring, self.n_jobs)?;
+        if self.refit {
+            fitted.refit(&self.factory, x, y)?;
+        }
+        self.fitted = Some(fitted);
+        Ok(())
+    }
+
+    search_accessors!();
+}
+
+/// Search over `n_iter` candidates sampled from `ParamDistributions`; see
+/// `GridSearchCV` for the factory convention.
+pub struct RandomizedSearchCV<E, F, C> {
+    pub factory: F,
+    pub param_distributions: ParamDistributions,
+    pub n_iter: usize,
+    pub cv: C,
+    pub scoring: Scoring,
+    pub seed: Option<u64>,
+    pub n_jobs: usize,
+    pub refit: bool,
+    fitted: Option<Fitted<E>>,
+}
+
+impl<E, F, C> RandomizedSearchCV<E, F, C>
+where
+    E: Estimator,
+    F: Fn(&Params) -> E + Sync,
+    C: CrossValidator,
+{
+    pub fn new(factory: F, param_distributions: ParamDistributions, n_iter: usize, cv: C, scoring: Scoring) -> Self {
+        RandomizedSearchCV {
+            factory,
+            param_distributions,
+            n_iter,
+            cv,
+            scoring,
+            seed: None,
+            n_jobs: default_n_jobs(),
+            refit: true,
+            fitted: None,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<()> {
+        let candidates = self.param_distributions.candidates(self.n_iter, self.seed);
+        let folds = self.cv.split(x, y)?;
+        let mut fitted = Fitted::run(&self.factory, candidates, x, y, &folds, self.scoring, self.n_jobs)?;
+        if self.refit {
+            fitted.refit(&self.factory, x, y)?;
+        }
+        self.fitted = Some(fitted);
+        Ok(())
+    }
+
+    search_accessors!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split::KFold;
+    use oxidize_ml_linear::Ridge;
+    use oxidize_ml_neighbors::{DistanceMetric, KNNClassifier};
+
+    #[test]
+    fn test_grid_search_picks_smallest_penalty() {
+        let n = 30;
+        let x = Tensor::new((0..n).map(|i| i as f64 / 10.0).collect(), vec![n, 1]).unwrap();
+        let y = Tensor::new((0..n).map(|i| 2.0 * i as f64 / 10.0 + 1.0).collect(), vec![n]).unwrap();
+        let grid = ParamGrid::new().add("alpha", vec![100.0, 0.001, 10.0]);
+        assert_eq!(grid.candidates().len(), 3);
+
+        let mut search = GridSearchCV::new(
+            |p: &Params| Ridge::new(p.f64("alpha"), true),
+            grid,
+            KFold::new(3).with_shuffle(Some(0)),
+            Scoring::NegMeanSquaredError,
+        )
+        .with_n_jobs(3);
+        search.fit(&x, &y).unwrap();
+        assert_eq!(search.best_params().unwrap().f64("alpha"), 0.001);
+        assert!(search.best_score().unwrap() > -1e-3);
+        assert_eq!(search.cv_results().iter().map(|r| r.rank).collect::<Vec<_>>(), vec![3, 1, 2]);
+        assert_eq!(search.cv_results()[0].scores.len(), 3);
+        assert_eq!(search.predict(&x).unwrap().numel(), n);
+    }
+
+    #[test]
+    fn test_randomized_search_is_reproducible() {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        for i in 0..40 {
+            xs.push(i as f64);
+            ys.push((i >= 20) as u8 as f64);
+        }
+        let x = Tensor::new(xs, vec![40, 1]).unwrap();
+        let y = Tensor::new(ys, vec![40]).unwrap();
+        let space = ParamDistributions::new()
+            .add("k", ParamDistribution::IntUniform { low: 1, high: 7 })
+            .add("scale", ParamDistribution::LogUniform { low: 0.1, high: 10.0 });
+        let run = |n_jobs| {
+            let mut search = RandomizedSearchCV::new(
+                |p: &Params| KNNClassifier::new(p.usize("k"), DistanceMetric::Euclidean),
+                space.clone(),
+                6,
+                KFold::new(4).with_shuffle(Some(1)),
+                Scoring::Accuracy,
+            )
+            .with_seed(3)
+            .with_n_jobs(n_jobs)
+            .with_refit(false);
+            search.fit(&x, &y).unwrap();
+            search.cv_results().iter().map(|r| (r.params.clone(), r.mean_score)).collect::<Vec<_>>()
+        };
+        let serial = run(1);
+        assert_eq!(serial.len(), 6);
+        assert!(serial.iter().all(|(p, _)| (1..=7).contains(&p.usize("k")) && (0.1..10.0).contains(&p.f64("scale"))));
+        assert_eq!(serial, run(4));
+
+        let choices = ParamDistributions::new().add("k", ParamDistribution::Choice(vec![1.into(), 3.into()]));
+        assert_eq!(choices.candidates(10, Some(0)).len(), 2);
+    }
+}
@@ -0,0 +1,326 @@
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::{RngHandle, Tensor, TensorError};
+use rand::seq::SliceRandom;
+
+/// Row indices of one fold: `(train, test)`.
+pub type Fold = (Vec<usize>, Vec<usize>);
+
+/// A cross-validation strategy: partitions the rows of `x` into folds.
+pub trait CrossValidator {
+    fn split(&self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<Vec<Fold>>;
+}
+
+/// Build folds from each row's test-fold assignment.
+fn folds_from_assignment(assignment: &[usize], n_splits: usize) -> Vec<Fold> {
+    (0..n_splits)
+        .map(|k| {
+            let (test, train): (Vec<usize>, Vec<usize>) = (0..assignment.len()).partition(|&i| assignment[i] == k);
+            (train, test)
+        })
+        .collect()
+}
+
+fn check_splits(n_splits: usize, n_samples: usize) -> TensorResult<()> {
+    if n_splits < 2 || n_splits > n_samples {
+        return Err(TensorError::InvalidOperation(format!(
+            "n_splits must be between 2 and the number of samples ({}), got {}",
+            n_samples, n_splits
+        )));
+    }
+    Ok(())
+}
+
+/// Splits rows into `n_splits` contiguous folds of near-equal size (the first
+/// `n % n_splits` folds get one extra row). Each fold is the test set once.
+#[derive(Debug, Clone)]
+pub struct KFold {
+    pub n_splits: usize,
+    pub shuffle: bool,
+    pub seed: Option<u64>,
+}
+
+impl KFold {
+    pub fn new(n_splits: usize) -> Self {
+        KFold {
+            n_splits,
+            shuffle: false,
+            seed: None,
+        }
+    }
+
+    /// Shuffle rows before splitting into folds.
+    pub fn with_shuffle(mut self, seed: Option<u64>) -> Self {
+        self.shuffle = true;
+        self.seed = seed;
+        self
+    }
+}
+
+impl CrossValidator for KFold {
+    fn split(&self, x: &Tensor<f64>, _y: &Tensor<f64>) -> TensorResult<Vec<Fold>> {
+        let n = x.shape().dim(0)?;
+        check_splits(self.n_splits, n)?;
+        let mut order: Vec<usize> = (0..n).collect();
+        if self.shuffle {
+            order.shuffle(&mut RngHandle::new(self.seed));
+        }
+        let mut assignment = vec![0; n];
+        let (base, extra) = (n / self.n_splits, n % self.n_splits);
+        let mut start = 0;
+        for k in 0..self.n_splits {
+            let size = base + usize::from(k < extra);
+            for &i in &order[start..start + size] {
+                assignment[i] = k;
+            }
+            start += size;
+        }
+        Ok(folds_from_assignment(&assignment, self.n_splits))
+    }
+}
+
+/// K-fold that keeps each class's proportion roughly equal in every fold.
+///
+/// Rows of each class are dealt to the folds in turn, continuing from class
+/// to class, so fold sizes differ by at most one row.
+#[derive(Debug, Clone)]
+pub struct StratifiedKFold {
+    pub n_splits: usize,
+    pub shuffle: bool,
+    pub seed: Option<u64>,
+}
+
+impl StratifiedKFold {
+    pub fn new(n_splits: usize) -> Self {
+        StratifiedKFold {
+            n_splits,
+            shuffle: false,
+            seed: None,
+        }
+    }
+
+    /// Shuffle rows within each class before dealing them to folds.
+    pub fn with_shuffle(mut self, seed: Option<u64>) -> Self {
+        self.shuffle = true;
+        self.seed = seed;
+        self
+    }
+}
+
+impl CrossValidator for StratifiedKFold {
+    fn split(&self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<Vec<Fold>> {
+        let n = x.shape().dim(0)?;
+        if y.numel() != n {
+            return Err(TensorError::ShapeMismatch {
+                expected: vec![n],
+                got: y.shape_vec(),
+            });
+        }
+        check_splits(self.n_splits, n)?;
+
+        let labels = y.data();
+        let mut classes: Vec<f64> = labels.to_vec();
+        classes.sort_by(|a, b| a.total_cmp(b));
+        classes.dedup();
+
+        let mut rng = RngHandle::new(self.seed);
+        let mut assignment = vec![0; n];
+        let mut next = 0;
+        for class in classes {
+            let mut members: Vec<usize> = (0..n).filter(|&i| labels[i] == class).collect();
+            if self.shuffle {
+                members.shuffle(&mut rng);
+            }
+            for i in members {
+                assignment[i] = next % self.n_splits;
+                next += 1;
+            }
+        }
+        Ok(folds_from_assignment(&assignment, self.n_splits))
+    }
+}
+
+/// Random train/test splits that never put rows of the same group on both
+/// sides, e.g. several measurements of one patient. Each split sends
+/// `test_ratio` of the groups (at least one, at most all but one) to the
+/// test set.
+#[derive(Debug, Clone)]
+pub struct GroupShuffleSplit {
+    pub n_splits: usize,
+    pub test_ratio: f64,
+    /// Group of each row; rows with equal values stay together.
+    pub groups: Vec<f64>,
+    pub seed: Option<u64>,
+}
+
+impl GroupShuffleSplit {
+    pub fn new(n_splits: usize, test_ratio: f64, groups: Vec<f64>) -> Self {
+        GroupShuffleSplit {
+            n_splits,
+            test_ratio,
+            groups,
+            seed: None,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl CrossValidator for GroupShuffleSplit {
+    fn split(&self, x: &Tensor<f64>, _y: &Tensor<f64>) -> TensorResult<Vec<Fold>> {
+        let n = x.shape().dim(0)?;
+        if self.groups.len() != n {
+            return Err(TensorError::ShapeMismatch {
+                expected: vec![n],
+                got: vec![self.groups.len()],
+            });
+        }
+        let mut unique = self.groups.clone();
+        unique.sort_by(|a, b| a.total_cmp(b));
+        unique.dedup();
+        if unique.len() < 2 {
+            return Err(TensorError::InvalidOperation("GroupShuffleSplit needs at least 2 groups".into()));
+        }
+        let n_test = ((unique.len() as f64 * self.test_ratio).round() as usize).clamp(1, unique.len() - 1);
+
+        let mut rng = RngHandle::new(self.seed);
+        Ok((0..self.n_splits)
+            .map(|_| {
+                unique.shuffle(&mut rng);
+                let test_groups = &unique[..n_test];
+                let (test, train): (Vec<usize>, Vec<usize>) =
+                    (0..n).partition(|&i| test_groups.contains(&self.groups[i]));
+                (train, test)
+            })
+            .collect())
+    }
+}
+
+/// Expanding-window folds for ordered data: each test block follows its
+/// training rows in time, so no fold trains on the future. The rows are
+/// cut into `n_splits + 1` blocks; fold `k` tests on block `k + 1` and
+/// trains on everything before it.
+#[derive(Debug, Clone)]
+pub struct TimeSeriesSplit {
+    pub n_splits: usize,
+    /// Keep only the most recent rows for training (a sliding window).
+    pub max_train_size: Option<usize>,
+    /// Rows left out between the end of training and the test block.
+    pub gap: usize,
+}
+
+impl TimeSeriesSplit {
+    pub fn new(n_splits: usize) -> Self {
+        TimeSeriesSplit {
+            n_splits,
+            max_train_size: None,
+            gap: 0,
+        }
+    }
+
+    pub fn with_max_train_size(mut self, max_train_size: usize) -> Self {
+        self.max_train_size = Some(max_train_size);
+        self
+    }
+
+    pub fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+}
+
+impl CrossValidator for TimeSeriesSplit {
+    fn split(&self, x: &Tensor<f64>, _y: &Tensor<f64>) -> TensorResult<Vec<Fold>> {
+        let n = x.shape().dim(0)?;
+        check_splits(self.n_splits + 1, n)?;
+        let test_size = n / (self.n_splits + 1);
+        let first_test = n - self.n_splits * test_size;
+        if first_test <= self.gap {
+            return Err(TensorError::InvalidOperation(format!(
+                "gap {} leaves no training rows for the first fold", self.gap
+            )));
+        }
+        Ok((0..self.n_splits)
+            .map(|k| {
+                let test_start = first_test + k * test_size;
+                let train_end = test_start - self.gap;
+                let train_start = self.max_train_size.map_or(0, |m| train_end.saturating_sub(m));
+                ((train_start..train_end).collect(), (test_start..test_start + test_size).collect())
+            })
+            .collect())
+    }
+}
+
+/// Each row is the test set once; equivalent to `KFold::new(n_samples)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeaveOneOut;
+
+impl CrossValidator for LeaveOneOut {
+    fn split(&self, x: &Tensor<f64>, _y: &Tensor<f64>) -> TensorResult<Vec<Fold>> {
+        let n = x.shape().dim(0)?;
+        check_splits(n, n)?;
+        Ok((0..n).map(|i| ((0..n).filter(|&j| j != i).collect(), vec![i])).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(n: usize) -> (Tensor<f64>, Tensor<f64>) {
+        let x = Tensor::new((0..n).map(|i| i as f64).collect(), vec![n, 1]).unwrap();
+        let y = Tensor::new((0..n).map(|i| (i % 3 == 0) as u8 as f64).collect(), vec![n]).unwrap();
+        (x, y)
+    }
+
+    #[test]
+    fn test_kfold_partitions_rows() {
+        let (x, y) = data(10);
+        let folds = KFold::new(3).split(&x, &y).unwrap();
+        assert_eq!(folds.iter().map(|(_, t)| t.len()).collect::<Vec<_>>(), vec![4, 3, 3]);
+        assert_eq!(folds[0].1, vec![0, 1, 2, 3]);
+        let mut seen: Vec<usize> = folds.iter().flat_map(|(_, t)| t.clone()).collect();
+        seen.sort();
+        assert_eq!(seen, (0..10).collect::<Vec<_>>());
+        for (train, test) in &folds {
+            assert_eq!(train.len() + test.len(), 10);
+            assert!(test.iter().all(|i| !train.contains(i)));
+        }
+
+        let shuffled = KFold::new(3).with_shuffle(Some(0)).split(&x, &y).unwrap();
+        assert_ne!(shuffled[0].1, folds[0].1);
+        assert!(KFold::new(11).split(&x, &y).is_err());
+        assert_eq!(LeaveOneOut.split(&x, &y).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_stratified_kfold_keeps_class_ratio() {
+        let (x, y) = data(12); // 4 positives, 8 negatives
+        let folds = StratifiedKFold::new(4).with_shuffle(Some(1)).split(&x, &y).unwrap();
+        for (_, test) in &folds {
+            assert_eq!(test.len(), 3);
+            assert_eq!(test.iter().filter(|&&i| y.data()[i] == 1.0).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_group_and_time_series_splits() {
+        let (x, y) = data(12);
+        let groups: Vec<f64> = (0..12).map(|i| (i / 3) as f64).collect();
+        let folds = GroupShuffleSplit::new(5, 0.25, groups.clone()).with_seed(Some(2)).split(&x, &y).unwrap();
+        assert_eq!(folds.len(), 5);
+        for (train, test) in &folds {
+            assert_eq!(test.len(), 3);
+            assert!(test.iter().all(|&i| train.iter().all(|&j| groups[i] != groups[j])));
+        }
+
+        let folds = TimeSeriesSplit::new(3).split(&x, &y).unwrap();
+        assert_eq!(folds[0], ((0..3).collect(), (3..6).collect()));
+        assert_eq!(folds[2], ((0..9).collect(), (9..12).collect()));
+        let windowed = TimeSeriesSplit::new(3).with_max_train_size(2).with_gap(1).split(&x, &y).unwrap();
+        assert_eq!(windowed[2], (vec![6, 7], (9..12).collect()));
+        assert!(TimeSeriesSplit::new(12).split(&x, &y).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::Tensor;
+use oxidize_ml_metrics::{accuracy, f1_macro, mae, mse, r2_score};
+use oxidize_ml_pipeline::Estimator;
+
+use crate::split::CrossValidator;
+
+/// How a fitted model is scored on held-out data. Higher is always better,
+/// so errors are negated.
+#[derive(Debug, Clone, Copy)]
+pub enum Scoring {
+    Accuracy,
+    F1Macro,
+    R2,
+    NegMeanSquaredError,
+    NegMeanAbsoluteError,
+    /// `f(y_true, y_pred)`.
+    Custom(fn(&Tensor<f64>, &Tensor<f64>) -> f64),
+}
+
+impl Scoring {
+    pub fn score(&self, y_true: &Tensor<f64>, y_pred: &Tensor<f64>) -> f64 {
+        match self {
+            Scoring::Accuracy => accuracy(y_true, y_pred),
+            Scoring::F1Macro => {
+                let n_classes = y_true.data().iter().chain(y_pred.data()).fold(0.0f64, |m, &v| m.max(v)) as usize + 1;
+                f1_macro(y_true, y_pred, n_classes)
+            }
+            Scoring::R2 => r2_score(y_true, y_pred),
+            Scoring::NegMeanSquaredError => -mse(y_true, y_pred),
+            Scoring::NegMeanAbsoluteError => -mae(y_true, y_pred),
+            Scoring::Custom(f) => f(y_true, y_pred),
+        }
+    }
+}
+
+/// Score `estimator` on every fold of `cv`: a fresh clone is fitted on each
+/// training split and scored on the matching test split. Returns one score
+/// per fold.
+pub fn cross_val_score<E, C>(
+    estimator: &E,
+    x: &Tensor<f64>,
+    y: &Tensor<f64>,
+    cv: &C,
+    scoring: Scoring,
+) -> TensorResult<Vec<f64>>
+where
+    E: Estimator + Clone,
+    C: CrossValidator + ?Sized,
+{
+    let mut scores = Vec::new();
+    for (train, test) in cv.split(x, y)? {
+        let mut model = estimator.clone();
+        model.fit(&x.index_select(0, &train)?, &y.index_select(0, &train)?)?;
+        let y_test = y.index_select(0, &test)?;
+        let y_pred = model.predict(&x.index_select(0, &test)?)?;
+        scores.push(scoring.score(&y_test, &y_pred));
+    }
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split::{KFold, StratifiedKFold};
+    use oxidize_ml_linear::LinearRegression;
+    use oxidize_ml_neighbors::{DistanceMetric, KNNClassifier};
+
+    #[test]
+    fn test_cross_val_score_regression() {
+        let n = 20;
+        let x = Tensor::new((0..n).map(|i| i as f64).collect(), vec![n, 1]).unwrap();
+        let y = Tensor::new((0..n).map(|i| 3.0 * i as f64 - 1.0).collect(), vec![n]).unwrap();
+        let cv = KFold::new(4).with_shuffle(Some(7));
+        let scores = cross_val_score(&LinearRegression::new(true), &x, &y, &cv, Scoring::R2).unwrap();
+        assert_eq!(scores.len(), 4);
+        assert!(scores.iter().all(|&s| s > 0.999), "{:?}", scores);
+        let mse = cross_val_score(&LinearRegression::new(true), &x, &y, &cv, Scoring::NegMeanSquaredError).unwrap();
+        assert!(mse.iter().all(|&s| s <= 0.0 && s > -1e-6));
+    }
+
+    #[test]
+    fn test_cross_val_score_classification() {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        for i in 0..30 {
+            let class = i % 2;
+            xs.extend([class as f64 * 10.0 + (i % 5) as f64 * 0.1, 1.0]);
+            ys.push(class as f64);
+        }
+        let x = Tensor::new(xs, vec![30, 2]).unwrap();
+        let y = Tensor::new(ys, vec![30]).unwrap();
+        let knn = KNNClassifier::new(3, DistanceMetric::Euclidean);
+        let scores = cross_val_score(&knn, &x, &y, &StratifiedKFold::new(5), Scoring::Accuracy).unwrap();
+        assert_eq!(scores, vec![1.0; 5]);
+    }
+}
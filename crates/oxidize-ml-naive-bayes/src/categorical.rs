@@ -0,0 +1,157 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+use crate::multinomial::class_labels;
+
+/// Categorical Naive Bayes classifier.
+///
+/// Each feature holds a category code `0, 1, 2, …` and gets its own
+/// categorical distribution per class, smoothed by `alpha`:
+/// `P(x_j = v | y = c) = (N_cjv + α) / (N_c + α · n_categories_j)`.
+/// A code never seen in training counts as zero occurrences.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct CategoricalNB<T: Float> {
+    pub alpha: T,
+    class_count: Vec<f64>,
+    category_count: Vec<Vec<Vec<f64>>>,  // [n_features][n_classes][n_categories]
+}
+
+impl<T: Float> CategoricalNB<T> {
+    pub fn new(alpha: T) -> Self {
+        CategoricalNB {
+            alpha,
+            class_count: Vec::new(),
+            category_count: Vec::new(),
+        }
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.class_count.clear();
+        self.category_count.clear();
+        self.partial_fit(x, y)
+    }
+
+    /// Update the counts with one more batch. New labels add classes and
+    /// new codes add categories.
+    pub fn partial_fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        if !self.category_count.is_empty() && p != self.category_count.len() {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} features but earlier batches had {}", p, self.category_count.len()
+            )));
+        }
+        let labels = class_labels(y, n)?;
+        let codes = Self::codes(x, n, p)?;
+
+        let k = labels.iter().map(|&c| c + 1).max().unwrap_or(0).max(self.class_count.len());
+        self.class_count.resize(k, 0.0);
+        self.category_count.resize(p, Vec::new());
+        for (j, per_class) in self.category_count.iter_mut().enumerate() {
+            let width = (0..n).map(|i| codes[i * p + j] + 1).max().unwrap_or(0)
+                .max(per_class.first().map_or(0, |c| c.len()));
+            per_class.resize(k, Vec::new());
+            per_class.iter_mut().for_each(|c| c.resize(width, 0.0));
+        }
+
+        for (i, &cls) in labels.iter().enumerate() {
+            self.class_count[cls] += 1.0;
+            for j in 0..p {
+                self.category_count[j][cls][codes[i * p + j]] += 1.0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Category codes of `x`, row-major.
+    fn codes(x: &Tensor<T>, n: usize, p: usize) -> TensorResult<Vec<usize>> {
+        let mut codes = Vec::with_capacity(n * p);
+        for i in 0..n {
+            for j in 0..p {
+                let v = x.get(&[i, j])?.to_f64().round();
+                if v < 0.0 {
+                    return Err(TensorError::InvalidOperation(format!(
+                        "category codes must be non-negative, got {} in column {}", v, j
+                    )));
+                }
+                codes.push(v as usize);
+            }
+        }
+        Ok(codes)
+    }
+
+    /// Number of categories seen for each feature.
+    pub fn n_categories(&self) -> Vec<usize> {
+        self.category_count.iter().map(|c| c.first().map_or(0, |c| c.len())).collect()
+    }
+
+    /// Unnormalized log posterior of each class, per row.
+    pub fn predict_log_proba(&self, x: &Tensor<T>) -> TensorResult<Vec<Vec<f64>>> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        if p != self.category_count.len() {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} features but the model was fitted on {}", p, self.category_count.len()
+            )));
+        }
+        let alpha = self.alpha.to_f64();
+        let total: f64 = self.class_count.iter().sum();
+        let codes = Self::codes(x, n, p)?;
+        Ok((0..n)
+            .map(|i| {
+                (0..self.class_count.len())
+                    .map(|cls| {
+                        let mut score = (self.class_count[cls] / total).ln();
+                        for (j, per_class) in self.category_count.iter().enumerate() {
+                            let counts = &per_class[cls];
+                            let seen = counts.get(codes[i * p + j]).copied().unwrap_or(0.0);
+                            score += ((seen + alpha) / (self.class_count[cls] + alpha * counts.len() as f64)).ln();
+                        }
+                        score
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let predictions: Vec<T> = self.predict_log_proba(x)?.iter()
+            .map(|row| {
+                let best = (0..row.len()).fold(0, |best, c| if row[c] > row[best] { c } else { best });
+                T::from_usize(best)
+            })
+            .collect();
+        let n = predictions.len();
+        Tensor::new(predictions, vec![n])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_categorical_nb() {
+        // Feature 0: colour code, feature 1: size code.
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![0.0, 1.0], vec![0.0, 2.0], vec![1.0, 1.0],
+            vec![2.0, 0.0], vec![2.0, 0.0], vec![1.0, 0.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        let mut model = CategoricalNB::new(1.0);
+        model.fit(&x, &y).unwrap();
+        assert_eq!(model.predict(&x).unwrap().data(), y.data());
+        assert_eq!(model.n_categories(), vec![3, 3]);
+
+        // Class 0, colour 0: (2 + 1) / (3 + 3); size 1: (2 + 1) / (3 + 3).
+        let log_proba = model.predict_log_proba(&Tensor::from_vec2d(&[vec![0.0, 1.0]]).unwrap()).unwrap();
+        assert!((log_proba[0][0] - (0.5f64 * 0.5 * 0.5).ln()).abs() < 1e-12);
+
+        // A later batch can bring a new category.
+        model.partial_fit(&Tensor::from_vec2d(&[vec![3.0, 2.0]]).unwrap(), &Tensor::from_slice(&[1.0])).unwrap();
+        assert_eq!(model.n_categories(), vec![4, 3]);
+        assert_eq!(model.predict(&Tensor::from_vec2d(&[vec![9.0, 0.0]]).unwrap()).unwrap().data(), &[1.0]);
+    }
+}
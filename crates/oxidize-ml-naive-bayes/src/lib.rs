@@ -1,5 +1,7 @@
 pub mod naive_bayes;
 pub mod multinomial;
+pub mod categorical;
 
 pub use naive_bayes::*;
 pub use multinomial::*;
+pub use categorical::*;
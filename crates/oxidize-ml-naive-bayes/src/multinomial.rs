@@ -1,15 +1,58 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{Float, SparseTensor, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+/// Class index of each of the `n` labels in `y`.
+pub(crate) fn class_labels<T: Float>(y: &Tensor<T>, n: usize) -> TensorResult<Vec<usize>> {
+    if y.numel() != n {
+        return Err(TensorError::DimensionMismatch(format!(
+            "x has {} rows but y has {} labels", n, y.numel()
+        )));
+    }
+    y.data().iter()
+        .map(|v| {
+            let v = v.to_f64().round();
+            if v < 0.0 {
+                Err(TensorError::InvalidOperation(format!("class labels must be non-negative, got {}", v)))
+            } else {
+                Ok(v as usize)
+            }
+        })
+        .collect()
+}
+
+/// Make room in per-class counts for every label in `labels`, with `p`
+/// features each; errors if earlier batches had a different `p`.
+fn grow_counts(class_count: &mut Vec<f64>, feature_count: &mut Vec<Vec<f64>>, labels: &[usize], p: usize) -> TensorResult<()> {
+    if let Some(first) = feature_count.first() {
+        if first.len() != p {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} features but earlier batches had {}", p, first.len()
+            )));
+        }
+    }
+    let k = labels.iter().map(|&c| c + 1).max().unwrap_or(0).max(class_count.len());
+    class_count.resize(k, 0.0);
+    feature_count.resize(k, vec![0.0; p]);
+    Ok(())
+}
 
 /// Multinomial Naive Bayes classifier.
 ///
 /// Suitable for discrete features (e.g., word counts in text classification).
 /// P(x_i | y) follows a multinomial distribution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct MultinomialNB<T: Float> {
     pub alpha: T,  // Laplace smoothing parameter
     class_log_prior: Vec<f64>,
     feature_log_prob: Vec<Vec<f64>>,  // [n_classes][n_features]
     n_classes: usize,
+    // Raw counts, kept for `partial_fit`
+    #[serde(default)]
+    class_count: Vec<f64>,
+    #[serde(default)]
+    feature_count: Vec<Vec<f64>>,
 }
 
 impl<T: Float> MultinomialNB<T> {
@@ -19,74 +62,90 @@ impl<T: Float> MultinomialNB<T> {
             class_log_prior: Vec::new(),
             feature_log_prob: Vec::new(),
             n_classes: 0,
+            class_count: Vec::new(),
+            feature_count: Vec::new(),
         }
     }
 
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.class_count.clear();
+        self.feature_count.clear();
+        self.partial_fit(x, y)
+    }
+
+    /// Update the counts with one more batch, for data that arrives in
+    /// chunks. Labels not seen before add classes.
+    pub fn partial_fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
-        let alpha = self.alpha.to_f64();
+        let labels = class_labels(y, n)?;
+        grow_counts(&mut self.class_count, &mut self.feature_count, &labels, p)?;
 
-        // Determine classes
-        let max_label = y.data().iter()
-            .map(|v| v.to_f64().round() as usize)
-            .max().unwrap_or(0);
-        self.n_classes = max_label + 1;
+        for (i, &cls) in labels.iter().enumerate() {
+            self.class_count[cls] += 1.0;
+            for j in 0..p {
+                self.feature_count[cls][j] += x.get(&[i, j])?.to_f64();
+            }
+        }
 
-        // Count class occurrences and feature sums per class
-        let mut class_counts = vec![0.0f64; self.n_classes];
-        let mut feature_counts = vec![vec![0.0f64; p]; self.n_classes];
+        self.finish_fit();
+        Ok(())
+    }
 
-        for i in 0..n {
-            let cls = y.data()[i].to_f64().round() as usize;
-            if cls >= self.n_classes { continue; }
-            class_counts[cls] += 1.0;
-            for j in 0..p {
-                feature_counts[cls][j] += x.get(&[i, j])?.to_f64();
+    /// Fit on a sparse count matrix, touching only its stored entries.
+    pub fn fit_sparse(&mut self, x: &SparseTensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.class_count.clear();
+        self.feature_count.clear();
+        self.partial_fit_sparse(x, y)
+    }
+
+    /// `partial_fit` for a sparse count matrix.
+    pub fn partial_fit_sparse(&mut self, x: &SparseTensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let n = x.n_rows();
+        let labels = class_labels(y, n)?;
+        grow_counts(&mut self.class_count, &mut self.feature_count, &labels, x.n_cols())?;
+
+        for (i, &cls) in labels.iter().enumerate() {
+            self.class_count[cls] += 1.0;
+            let (cols, vals) = x.row(i);
+            for (&j, &v) in cols.iter().zip(vals) {
+                self.feature_count[cls][j] += v.to_f64();
             }
         }
 
+        self.finish_fit();
+        Ok(())
+    }
+
+    fn finish_fit(&mut self) {
+        let alpha = self.alpha.to_f64();
+        self.n_classes = self.class_count.len();
+
         // Compute log priors
-        self.class_log_prior = class_counts.iter()
-            .map(|&c| (c / n as f64).ln())
+        let n: f64 = self.class_count.iter().sum();
+        self.class_log_prior = self.class_count.iter()
+            .map(|&c| (c / n).ln())
             .collect();
 
         // Compute log probabilities with Laplace smoothing
         self.feature_log_prob = Vec::with_capacity(self.n_classes);
-        for cls in 0..self.n_classes {
-            let total: f64 = feature_counts[cls].iter().sum::<f64>() + alpha * p as f64;
-            let log_probs: Vec<f64> = feature_counts[cls].iter()
+        for counts in &self.feature_count {
+            let total: f64 = counts.iter().sum::<f64>() + alpha * counts.len() as f64;
+            let log_probs: Vec<f64> = counts.iter()
                 .map(|&c| ((c + alpha) / total).ln())
                 .collect();
             self.feature_log_prob.push(log_probs);
         }
-
-        Ok(())
     }
 
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
-        let n = x.shape().dim(0)?;
-        let p = x.shape().dim(1)?;
-        let mut predictions = Vec::with_capacity(n);
-
-        for i in 0..n {
-            let mut best_class = 0;
-            let mut best_score = f64::NEG_INFINITY;
-
-            for cls in 0..self.n_classes {
-                let mut score = self.class_log_prior[cls];
-                for j in 0..p {
-                    score += x.get(&[i, j])?.to_f64() * self.feature_log_prob[cls][j];
-                }
-                if score > best_score {
-                    best_score = score;
-                    best_class = cls;
-                }
-            }
-            predictions.push(T::from_usize(best_class));
-        }
+        let log_proba = self.predict_log_proba(x)?;
+        Self::argmax_rows(&log_proba)
+    }
 
-        Tensor::new(predictions, vec![n])
+    pub fn predict_sparse(&self, x: &SparseTensor<T>) -> TensorResult<Tensor<T>> {
+        let log_proba = self.predict_log_proba_sparse(x)?;
+        Self::argmax_rows(&log_proba)
     }
 
     pub fn predict_log_proba(&self, x: &Tensor<T>) -> TensorResult<Vec<Vec<f64>>> {
@@ -95,29 +154,76 @@ impl<T: Float> MultinomialNB<T> {
         let mut results = Vec::with_capacity(n);
 
         for i in 0..n {
-            let mut log_probs = Vec::with_capacity(self.n_classes);
-            for cls in 0..self.n_classes {
-                let mut score = self.class_log_prior[cls];
-                for j in 0..p {
-                    score += x.get(&[i, j])?.to_f64() * self.feature_log_prob[cls][j];
-                }
-                log_probs.push(score);
-            }
-            results.push(log_probs);
+            let row = (0..p).map(|j| x.get(&[i, j]).map(|v| (j, v))).collect::<TensorResult<Vec<_>>>()?;
+            results.push(self.joint_log_likelihood(row));
         }
         Ok(results)
     }
+
+    pub fn predict_log_proba_sparse(&self, x: &SparseTensor<T>) -> TensorResult<Vec<Vec<f64>>> {
+        if x.n_cols() != self.feature_log_prob.first().map_or(0, |f| f.len()) {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} features but the model was fitted on {}",
+                x.n_cols(),
+                self.feature_log_prob.first().map_or(0, |f| f.len())
+            )));
+        }
+        Ok((0..x.n_rows())
+            .map(|i| {
+                let (cols, vals) = x.row(i);
+                self.joint_log_likelihood(cols.iter().copied().zip(vals.iter().copied()))
+            })
+            .collect())
+    }
+
+    /// Unnormalized log posterior of one sample, given its `(feature, count)` pairs.
+    fn joint_log_likelihood(&self, row: impl IntoIterator<Item = (usize, T)> + Clone) -> Vec<f64> {
+        (0..self.n_classes)
+            .map(|cls| {
+                let mut score = self.class_log_prior[cls];
+                for (j, v) in row.clone() {
+                    score += v.to_f64() * self.feature_log_prob[cls][j];
+                }
+                score
+            })
+            .collect()
+    }
+
+    fn argmax_rows(scores: &[Vec<f64>]) -> TensorResult<Tensor<T>> {
+        let predictions: Vec<T> = scores.iter()
+            .map(|row| {
+                let mut best_class = 0;
+                let mut best_score = f64::NEG_INFINITY;
+                for (cls, &score) in row.iter().enumerate() {
+                    if score > best_score {
+                        best_score = score;
+                        best_class = cls;
+                    }
+                }
+                T::from_usize(best_class)
+            })
+            .collect();
+        let n = predictions.len();
+        Tensor::new(predictions, vec![n])
+    }
 }
 
 /// Bernoulli Naive Bayes classifier.
 ///
 /// For binary/boolean features. P(x_i | y) follows a Bernoulli distribution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct BernoulliNB<T: Float> {
     pub alpha: T,
     class_log_prior: Vec<f64>,
     feature_log_prob: Vec<Vec<f64>>,     // log P(x_i=1 | y)
     feature_log_neg_prob: Vec<Vec<f64>>,  // log P(x_i=0 | y)
     n_classes: usize,
+    // Raw counts, kept for `partial_fit`
+    #[serde(default)]
+    class_count: Vec<f64>,
+    #[serde(default)]
+    feature_count: Vec<Vec<f64>>,
 }
 
 impl<T: Float> BernoulliNB<T> {
@@ -128,45 +234,49 @@ impl<T: Float> BernoulliNB<T> {
             feature_log_prob: Vec::new(),
             feature_log_neg_prob: Vec::new(),
             n_classes: 0,
+            class_count: Vec::new(),
+            feature_count: Vec::new(),
         }
     }
 
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.class_count.clear();
+        self.feature_count.clear();
+        self.partial_fit(x, y)
+    }
+
+    /// Update the counts with one more batch. Labels not seen before add
+    /// classes.
+    pub fn partial_fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
         let alpha = self.alpha.to_f64();
+        let labels = class_labels(y, n)?;
+        grow_counts(&mut self.class_count, &mut self.feature_count, &labels, p)?;
 
-        let max_label = y.data().iter()
-            .map(|v| v.to_f64().round() as usize)
-            .max().unwrap_or(0);
-        self.n_classes = max_label + 1;
-
-        let mut class_counts = vec![0.0f64; self.n_classes];
-        let mut feature_counts = vec![vec![0.0f64; p]; self.n_classes];
-
-        for i in 0..n {
-            let cls = y.data()[i].to_f64().round() as usize;
-            if cls >= self.n_classes { continue; }
-            class_counts[cls] += 1.0;
+        for (i, &cls) in labels.iter().enumerate() {
+            self.class_count[cls] += 1.0;
             for j in 0..p {
                 if x.get(&[i, j])?.to_f64() > 0.5 {
-                    feature_counts[cls][j] += 1.0;
+                    self.feature_count[cls][j] += 1.0;
                 }
             }
         }
 
-        self.class_log_prior = class_counts.iter()
-            .map(|&c| (c / n as f64).ln())
+        self.n_classes = self.class_count.len();
+        let total: f64 = self.class_count.iter().sum();
+        self.class_log_prior = self.class_count.iter()
+            .map(|&c| (c / total).ln())
             .collect();
 
         self.feature_log_prob = Vec::with_capacity(self.n_classes);
         self.feature_log_neg_prob = Vec::with_capacity(self.n_classes);
         for cls in 0..self.n_classes {
-            let n_cls = class_counts[cls] + 2.0 * alpha;
-            let log_p: Vec<f64> = feature_counts[cls].iter()
+            let n_cls = self.class_count[cls] + 2.0 * alpha;
+            let log_p: Vec<f64> = self.feature_count[cls].iter()
                 .map(|&c| ((c + alpha) / n_cls).ln())
                 .collect();
-            let log_np: Vec<f64> = feature_counts[cls].iter()
+            let log_np: Vec<f64> = self.feature_count[cls].iter()
                 .map(|&c| ((n_cls - c - alpha) / n_cls).ln())
                 .collect();
             self.feature_log_prob.push(log_p);
@@ -206,6 +316,110 @@ impl<T: Float> BernoulliNB<T> {
     }
 }
 
+/// Complement Naive Bayes classifier.
+///
+/// A multinomial model that estimates each class's weights from the counts
+/// of all *other* classes, which keeps small classes from getting noisy
+/// estimates; better than `MultinomialNB` on imbalanced text data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct ComplementNB<T: Float> {
+    pub alpha: T,
+    /// Scale each class's weights by their total, as in the original paper.
+    pub norm: bool,
+    class_log_prior: Vec<f64>,
+    feature_weights: Vec<Vec<f64>>,  // [n_classes][n_features]
+    class_count: Vec<f64>,
+    feature_count: Vec<Vec<f64>>,
+}
+
+impl<T: Float> ComplementNB<T> {
+    pub fn new(alpha: T) -> Self {
+        ComplementNB {
+            alpha,
+            norm: false,
+            class_log_prior: Vec::new(),
+            feature_weights: Vec::new(),
+            class_count: Vec::new(),
+            feature_count: Vec::new(),
+        }
+    }
+
+    pub fn with_norm(mut self, norm: bool) -> Self {
+        self.norm = norm;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.class_count.clear();
+        self.feature_count.clear();
+        self.partial_fit(x, y)
+    }
+
+    /// Update the counts with one more batch. Labels not seen before add
+    /// classes.
+    pub fn partial_fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        let alpha = self.alpha.to_f64();
+        let labels = class_labels(y, n)?;
+        grow_counts(&mut self.class_count, &mut self.feature_count, &labels, p)?;
+
+        for (i, &cls) in labels.iter().enumerate() {
+            self.class_count[cls] += 1.0;
+            for j in 0..p {
+                self.feature_count[cls][j] += x.get(&[i, j])?.to_f64();
+            }
+        }
+
+        let total: f64 = self.class_count.iter().sum();
+        self.class_log_prior = self.class_count.iter()
+            .map(|&c| (c / total).ln())
+            .collect();
+
+        let all: Vec<f64> = (0..p)
+            .map(|j| self.feature_count.iter().map(|counts| counts[j]).sum())
+            .collect();
+        self.feature_weights = self.feature_count.iter()
+            .map(|counts| {
+                let complement: Vec<f64> = all.iter().zip(counts).map(|(a, c)| a - c + alpha).collect();
+                let sum: f64 = complement.iter().sum();
+                let logged: Vec<f64> = complement.iter().map(|c| (c / sum).ln()).collect();
+                if self.norm {
+                    let scale: f64 = logged.iter().sum();
+                    logged.iter().map(|w| w / scale).collect()
+                } else {
+                    logged.iter().map(|w| -w).collect()
+                }
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Per-class scores of each row; the highest is the prediction.
+    pub fn decision_scores(&self, x: &Tensor<T>) -> TensorResult<Vec<Vec<f64>>> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        let mut results = Vec::with_capacity(n);
+        for i in 0..n {
+            let row = (0..p).map(|j| x.get(&[i, j]).map(|v| v.to_f64())).collect::<TensorResult<Vec<_>>>()?;
+            results.push(self.feature_weights.iter().enumerate()
+                .map(|(cls, w)| {
+                    let score: f64 = row.iter().zip(w).map(|(v, w)| v * w).sum();
+                    // With one class the complement is empty; fall back on
+                    // the prior.
+                    if self.feature_weights.len() == 1 { score + self.class_log_prior[cls] } else { score }
+                })
+                .collect());
+        }
+        Ok(results)
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        MultinomialNB::<T>::argmax_rows(&self.decision_scores(x)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,6 +448,30 @@ mod tests {
         assert!(correct >= 4, "MultinomialNB accuracy: {}/6", correct);
     }
 
+    #[test]
+    fn test_multinomial_nb_sparse_matches_dense() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![3.0, 0.0, 1.0, 0.0],
+            vec![2.0, 0.0, 0.0, 0.0],
+            vec![0.0, 3.0, 0.0, 1.0],
+            vec![0.0, 2.0, 0.0, 0.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0]);
+        let xs = SparseTensor::from_dense(&x).unwrap();
+
+        let mut dense = MultinomialNB::new(1.0);
+        dense.fit(&x, &y).unwrap();
+        let mut sparse = MultinomialNB::new(1.0);
+        sparse.fit_sparse(&xs, &y).unwrap();
+
+        assert_eq!(dense.feature_log_prob, sparse.feature_log_prob);
+        assert_eq!(dense.predict(&x).unwrap(), sparse.predict_sparse(&xs).unwrap());
+        for (a, b) in dense.predict_log_proba(&x).unwrap().iter().flatten()
+            .zip(sparse.predict_log_proba_sparse(&xs).unwrap().iter().flatten()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
     #[test]
     fn test_bernoulli_nb() {
         let x: Tensor<f64> = Tensor::from_vec2d(&[
@@ -251,4 +489,38 @@ mod tests {
             .count();
         assert!(correct >= 4, "BernoulliNB accuracy: {}/6", correct);
     }
+
+    #[test]
+    fn test_complement_nb_and_partial_fit() {
+        // Class 0 is rare; its rows lean on feature 2.
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![5.0, 1.0, 0.0], vec![4.0, 2.0, 0.0], vec![6.0, 0.0, 1.0], vec![5.0, 1.0, 1.0],
+            vec![0.0, 4.0, 2.0], vec![1.0, 5.0, 1.0], vec![0.0, 6.0, 0.0], vec![1.0, 1.0, 6.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 0.0]);
+        for norm in [false, true] {
+            let mut model = ComplementNB::new(1.0).with_norm(norm);
+            model.fit(&x, &y).unwrap();
+            assert_eq!(model.predict(&x).unwrap().data(), y.data());
+        }
+
+        // Batches in any order give the counts of one fit.
+        let first = Tensor::from_vec2d(&[vec![5.0, 1.0, 0.0], vec![0.0, 4.0, 2.0]]).unwrap();
+        let rest = Tensor::from_vec2d(&[
+            vec![4.0, 2.0, 0.0], vec![6.0, 0.0, 1.0], vec![5.0, 1.0, 1.0],
+            vec![1.0, 5.0, 1.0], vec![0.0, 6.0, 0.0], vec![1.0, 1.0, 6.0],
+        ]).unwrap();
+        let mut streamed = MultinomialNB::new(1.0);
+        streamed.partial_fit(&first, &Tensor::from_slice(&[1.0, 2.0])).unwrap();
+        streamed.partial_fit(&rest, &Tensor::from_slice(&[1.0, 1.0, 1.0, 2.0, 2.0, 0.0])).unwrap();
+        let mut whole = MultinomialNB::new(1.0);
+        whole.fit(&x, &y).unwrap();
+        assert_eq!(streamed.class_count, whole.class_count);
+        for (a, b) in streamed.feature_log_prob.iter().flatten().zip(whole.feature_log_prob.iter().flatten()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        let mut bernoulli = BernoulliNB::new(1.0);
+        bernoulli.partial_fit(&first, &Tensor::from_slice(&[1.0, 2.0])).unwrap();
+        assert!(bernoulli.partial_fit(&Tensor::from_vec2d(&[vec![1.0, 0.0]]).unwrap(), &Tensor::from_slice(&[0.0])).is_err());
+    }
 }
@@ -1,13 +1,24 @@
-use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_core::{Float, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+use crate::multinomial::class_labels;
 
 /// Gaussian Naive Bayes classifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct GaussianNB<T: Float> {
     pub class_priors: Vec<T>,
     pub class_means: Vec<Vec<T>>,
     pub class_vars: Vec<Vec<T>>,
     pub n_classes: usize,
     pub n_features: usize,
+    // Per-class sample counts and variances before the epsilon floor,
+    // kept for `partial_fit`
+    #[serde(default)]
+    class_counts: Vec<f64>,
+    #[serde(default)]
+    raw_vars: Vec<Vec<f64>>,
 }
 
 impl<T: Float> GaussianNB<T> {
@@ -18,61 +29,81 @@ impl<T: Float> GaussianNB<T> {
             class_vars: Vec::new(),
             n_classes: 0,
             n_features: 0,
+            class_counts: Vec::new(),
+            raw_vars: Vec::new(),
         }
     }
 
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        *self = Self::new();
+        self.partial_fit(x, y)
+    }
+
+    /// Update the per-class means and variances with one more batch,
+    /// combining them exactly with the statistics so far. Labels not seen
+    /// before add classes.
+    pub fn partial_fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
+        if !self.class_counts.is_empty() && p != self.n_features {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} features but earlier batches had {}", p, self.n_features
+            )));
+        }
         self.n_features = p;
-
-        let max_label = y.data().iter().map(|v| v.to_f64().round() as usize).max().unwrap_or(0);
-        self.n_classes = max_label + 1;
-
-        self.class_priors = vec![T::ZERO; self.n_classes];
-        self.class_means = vec![vec![T::ZERO; p]; self.n_classes];
-        self.class_vars = vec![vec![T::ZERO; p]; self.n_classes];
-        let mut class_counts = vec![0usize; self.n_classes];
-
-        // Compute means
-        for i in 0..n {
-            let cls = y.data()[i].to_f64().round() as usize;
-            class_counts[cls] += 1;
+        let labels = class_labels(y, n)?;
+        let k = labels.iter().map(|&c| c + 1).max().unwrap_or(0).max(self.n_classes);
+        self.n_classes = k;
+        self.class_counts.resize(k, 0.0);
+        self.class_means.resize(k, vec![T::ZERO; p]);
+        self.raw_vars.resize(k, vec![0.0; p]);
+
+        // Batch statistics
+        let mut batch_counts = vec![0.0f64; k];
+        let mut batch_means = vec![vec![0.0f64; p]; k];
+        let mut batch_vars = vec![vec![0.0f64; p]; k];
+        for (i, &cls) in labels.iter().enumerate() {
+            batch_counts[cls] += 1.0;
             for j in 0..p {
-                self.class_means[cls][j] = self.class_means[cls][j] + x.get(&[i, j])?;
+                batch_means[cls][j] += x.get(&[i, j])?.to_f64();
             }
         }
-        for c in 0..self.n_classes {
-            if class_counts[c] > 0 {
-                let cnt = T::from_usize(class_counts[c]);
-                for j in 0..p {
-                    self.class_means[c][j] = self.class_means[c][j] / cnt;
-                }
+        for c in 0..k {
+            if batch_counts[c] > 0.0 {
+                batch_means[c].iter_mut().for_each(|m| *m /= batch_counts[c]);
             }
-            self.class_priors[c] = T::from_usize(class_counts[c]) / T::from_usize(n);
         }
-
-        // Compute variances
-        for i in 0..n {
-            let cls = y.data()[i].to_f64().round() as usize;
+        for (i, &cls) in labels.iter().enumerate() {
             for j in 0..p {
-                let diff = x.get(&[i, j])? - self.class_means[cls][j];
-                self.class_vars[cls][j] = self.class_vars[cls][j] + diff * diff;
+                let diff = x.get(&[i, j])?.to_f64() - batch_means[cls][j];
+                batch_vars[cls][j] += diff * diff;
             }
         }
-        for c in 0..self.n_classes {
-            if class_counts[c] > 0 {
-                let cnt = T::from_usize(class_counts[c]);
-                for j in 0..p {
-                    self.class_vars[c][j] = self.class_vars[c][j] / cnt;
-                    // Add small epsilon to prevent division by zero
-                    if self.class_vars[c][j] < T::from_f64(1e-9) {
-                        self.class_vars[c][j] = T::from_f64(1e-9);
-                    }
-                }
+
+        // Merge with the running statistics (Chan et al.)
+        for c in 0..k {
+            let (n_old, n_new) = (self.class_counts[c], batch_counts[c]);
+            if n_new == 0.0 {
+                continue;
             }
+            let total = n_old + n_new;
+            for j in 0..p {
+                let old_mean = self.class_means[c][j].to_f64();
+                let delta = batch_means[c][j] - old_mean;
+                let m2 = self.raw_vars[c][j] * n_old + batch_vars[c][j] + delta * delta * n_old * n_new / total;
+                self.class_means[c][j] = T::from_f64(old_mean + delta * n_new / total);
+                self.raw_vars[c][j] = m2 / total;
+            }
+            self.class_counts[c] = total;
         }
 
+        let seen: f64 = self.class_counts.iter().sum();
+        self.class_priors = self.class_counts.iter().map(|&c| T::from_f64(c / seen)).collect();
+        // Add small epsilon to prevent division by zero
+        self.class_vars = self.raw_vars.iter()
+            .map(|vars| vars.iter().map(|&v| T::from_f64(v.max(1e-9))).collect())
+            .collect();
+
         Ok(())
     }
 
@@ -88,7 +119,7 @@ impl<T: Float> GaussianNB<T> {
             let diff = xij - mean;
 
             // log N(x|μ,σ²) = -0.5 * (log(2π) + log(σ²) + (x-μ)²/σ²)
-            log_prob = log_prob - T::HALF * (two_pi.ln() + var.ln() + diff * diff / var);
+            log_prob -= T::HALF * (two_pi.ln() + var.ln() + diff * diff / var);
         }
 
         Ok(log_prob)
@@ -146,4 +177,25 @@ mod tests {
             assert!((pred.data()[i] - y.data()[i]).abs() < 0.5);
         }
     }
+
+    #[test]
+    fn test_gaussian_nb_partial_fit_matches_fit() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![0.0, 0.3], vec![0.5, 0.5], vec![1.0, 0.0], vec![5.0, 5.0], vec![5.5, 5.5], vec![6.0, 5.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        let mut whole = GaussianNB::new();
+        whole.fit(&x, &y).unwrap();
+
+        let mut streamed = GaussianNB::new();
+        for rows in [vec![0, 3], vec![1, 2], vec![4, 5]] {
+            streamed.partial_fit(&x.index_select(0, &rows).unwrap(), &y.index_select(0, &rows).unwrap()).unwrap();
+        }
+        for (a, b) in [(&whole.class_means, &streamed.class_means), (&whole.class_vars, &streamed.class_vars)] {
+            for (u, v) in a.iter().flatten().zip(b.iter().flatten()) {
+                assert!((u - v).abs() < 1e-12);
+            }
+        }
+        assert_eq!(whole.class_priors, streamed.class_priors);
+    }
 }
@@ -0,0 +1,151 @@
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+use crate::nearest::KBest;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+struct Ball<T: Float> {
+    center: Vec<T>,
+    /// Largest distance from `center` to a point in the ball.
+    radius: T,
+    /// Points `indices[start..end]`.
+    start: usize,
+    end: usize,
+    children: Option<(usize, usize)>,
+}
+
+/// A ball tree: nested hyperspheres, each split at the median of its
+/// widest-spread coordinate. Pruning only relies on the triangle
+/// inequality, so it works for any true metric (`DistanceMetric::is_metric`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct BallTree<T: Float> {
+    pub metric: DistanceMetric<T>,
+    pub leaf_size: usize,
+    data: Vec<T>,
+    dim: usize,
+    indices: Vec<usize>,
+    balls: Vec<Ball<T>>,
+}
+
+impl<T: Float> BallTree<T> {
+    /// Index the rows of `x` (`[n, d]`).
+    pub fn new(x: &Tensor<T>, metric: DistanceMetric<T>, leaf_size: usize) -> TensorResult<Self> {
+        if !metric.is_metric() {
+            return Err(TensorError::InvalidOperation(format!("BallTree does not support the {:?} metric", metric)));
+        }
+        let n = x.shape().dim(0)?;
+        let mut tree = BallTree {
+            metric,
+            leaf_size: leaf_size.max(1),
+            data: x.data().to_vec(),
+            dim: x.shape().dim(1)?,
+            indices: (0..n).collect(),
+            balls: Vec::new(),
+        };
+        if n > 0 {
+            tree.build(0, n);
+        }
+        Ok(tree)
+    }
+
+    /// Number of indexed points.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    fn point(&self, i: usize) -> &[T] {
+        &self.data[i * self.dim..(i + 1) * self.dim]
+    }
+
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let count = T::from_usize(end - start);
+        let mut center = vec![T::ZERO; self.dim];
+        for &i in &self.indices[start..end] {
+            for (c, &v) in center.iter_mut().zip(self.point(i)) {
+                *c += v;
+            }
+        }
+        for c in &mut center {
+            *c /= count;
+        }
+        let radius = self.indices[start..end]
+            .iter()
+            .map(|&i| self.metric.distance(&center, self.point(i)))
+            .fold(T::ZERO, |a, b| if b > a { b } else { a });
+
+        let id = self.balls.len();
+        self.balls.push(Ball { center, radius, start, end, children: None });
+        if end - start <= self.leaf_size || radius <= T::ZERO {
+            return id;
+        }
+
+        let (data, dim) = (&self.data, self.dim);
+        let coord = |i: usize, axis: usize| data[i * dim + axis].to_f64();
+        let spread = |axis: usize| {
+            let values = self.indices[start..end].iter().map(|&i| coord(i, axis));
+            let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+            hi - lo
+        };
+        let axis = (0..dim).max_by(|&a, &b| spread(a).total_cmp(&spread(b))).unwrap_or(0);
+        let mid = start + (end - start) / 2;
+        self.indices[start..end].select_nth_unstable_by(mid - start, |&a, &b| coord(a, axis).total_cmp(&coord(b, axis)));
+
+        let left = self.build(start, mid);
+        let right = self.build(mid, end);
+        self.balls[id].children = Some((left, right));
+        id
+    }
+
+    /// Smallest possible distance from `point` to anything in ball `id`.
+    fn lower_bound(&self, id: usize, point: &[T]) -> T {
+        let ball = &self.balls[id];
+        let gap = self.metric.distance(point, &ball.center) - ball.radius;
+        if gap > T::ZERO { gap } else { T::ZERO }
+    }
+
+    fn search(&self, id: usize, point: &[T], best: &mut KBest<T>) {
+        let ball = &self.balls[id];
+        match ball.children {
+            None => {
+                for &i in &self.indices[ball.start..ball.end] {
+                    best.push(self.metric.distance(point, self.point(i)), i);
+                }
+            }
+            Some((left, right)) => {
+                let (lb, rb) = (self.lower_bound(left, point), self.lower_bound(right, point));
+                let order = if lb <= rb { [(left, lb), (right, rb)] } else { [(right, rb), (left, lb)] };
+                for (child, bound) in order {
+                    if best.admits(bound) {
+                        self.search(child, point, best);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The `k` nearest indexed points to `point` as `(row, distance)`, by
+    /// increasing distance; ties go to the lower row.
+    pub fn query(&self, point: &[T], k: usize) -> Vec<(usize, T)> {
+        let mut best = KBest::new(k);
+        if !self.balls.is_empty() {
+            self.search(0, point, &mut best);
+        }
+        best.into_sorted()
+    }
+
+    /// Every indexed point within `radius` of `point`, sorted like `query`.
+    pub fn query_radius(&self, point: &[T], radius: T) -> Vec<(usize, T)> {
+        let mut best = KBest::within(radius);
+        if !self.balls.is_empty() {
+            self.search(0, point, &mut best);
+        }
+        best.into_sorted()
+    }
+}
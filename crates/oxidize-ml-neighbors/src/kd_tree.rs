@@ -0,0 +1,140 @@
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+use crate::nearest::KBest;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+enum KdNode<T: Float> {
+    /// Points `indices[start..end]`.
+    Leaf { start: usize, end: usize },
+    /// Points with `x[axis] < value` are under `left`; points on or above
+    /// it under `right`.
+    Split { axis: usize, value: T, left: usize, right: usize },
+}
+
+/// A KD-tree: recursively splits the points at the median of their
+/// widest-spread coordinate. Works for metrics where the gap along one axis
+/// bounds the distance (`DistanceMetric::axis_bounded`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct KdTree<T: Float> {
+    pub metric: DistanceMetric<T>,
+    pub leaf_size: usize,
+    data: Vec<T>,
+    dim: usize,
+    indices: Vec<usize>,
+    nodes: Vec<KdNode<T>>,
+}
+
+impl<T: Float> KdTree<T> {
+    /// Index the rows of `x` (`[n, d]`).
+    pub fn new(x: &Tensor<T>, metric: DistanceMetric<T>, leaf_size: usize) -> TensorResult<Self> {
+        if !metric.axis_bounded() {
+            return Err(TensorError::InvalidOperation(format!("KdTree does not support the {:?} metric", metric)));
+        }
+        let n = x.shape().dim(0)?;
+        let mut tree = KdTree {
+            metric,
+            leaf_size: leaf_size.max(1),
+            data: x.data().to_vec(),
+            dim: x.shape().dim(1)?,
+            indices: (0..n).collect(),
+            nodes: Vec::new(),
+        };
+        tree.build(0, n);
+        Ok(tree)
+    }
+
+    /// Number of indexed points.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    fn point(&self, i: usize) -> &[T] {
+        &self.data[i * self.dim..(i + 1) * self.dim]
+    }
+
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(KdNode::Leaf { start, end });
+        if end - start <= self.leaf_size || self.dim == 0 {
+            return id;
+        }
+
+        let (data, dim) = (&self.data, self.dim);
+        let coord = |i: usize, axis: usize| data[i * dim + axis];
+        let spread = |axis: usize| {
+            let values = self.indices[start..end].iter().map(|&i| coord(i, axis).to_f64());
+            let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+            hi - lo
+        };
+        let axis = (0..dim).max_by(|&a, &b| spread(a).total_cmp(&spread(b))).unwrap_or(0);
+        if spread(axis) <= 0.0 {
+            return id;
+        }
+
+        let mid = start + (end - start) / 2;
+        self.indices[start..end]
+            .select_nth_unstable_by(mid - start, |&a, &b| coord(a, axis).to_f64().total_cmp(&coord(b, axis).to_f64()));
+        let value = coord(self.indices[mid], axis);
+        // Move every point equal to the median right of the split.
+        let mut split = start;
+        for j in start..end {
+            if coord(self.indices[j], axis) < value {
+                self.indices.swap(j, split);
+                split += 1;
+            }
+        }
+        if split == start {
+            return id;
+        }
+
+        let left = self.build(start, split);
+        let right = self.build(split, end);
+        self.nodes[id] = KdNode::Split { axis, value, left, right };
+        id
+    }
+
+    fn search(&self, node: usize, point: &[T], best: &mut KBest<T>) {
+        match self.nodes[node] {
+            KdNode::Leaf { start, end } => {
+                for &i in &self.indices[start..end] {
+                    best.push(self.metric.distance(point, self.point(i)), i);
+                }
+            }
+            KdNode::Split { axis, value, left, right } => {
+                let gap = point[axis] - value;
+                let (near, far) = if gap < T::ZERO { (left, right) } else { (right, left) };
+                self.search(near, point, best);
+                if best.admits(gap.abs()) {
+                    self.search(far, point, best);
+                }
+            }
+        }
+    }
+
+    /// The `k` nearest indexed points to `point` as `(row, distance)`, by
+    /// increasing distance; ties go to the lower row.
+    pub fn query(&self, point: &[T], k: usize) -> Vec<(usize, T)> {
+        let mut best = KBest::new(k);
+        if !self.nodes.is_empty() {
+            self.search(0, point, &mut best);
+        }
+        best.into_sorted()
+    }
+
+    /// Every indexed point within `radius` of `point`, sorted like `query`.
+    pub fn query_radius(&self, point: &[T], radius: T) -> Vec<(usize, T)> {
+        let mut best = KBest::within(radius);
+        if !self.nodes.is_empty() {
+            self.search(0, point, &mut best);
+        }
+        best.into_sorted()
+    }
+}
@@ -1,2 +1,15 @@
+pub mod nearest;
+pub mod kd_tree;
+pub mod ball_tree;
 pub mod knn;
+pub mod radius;
+pub mod lof;
+
+pub use nearest::*;
+pub use kd_tree::*;
+pub use ball_tree::*;
 pub use knn::*;
+pub use radius::*;
+pub use lof::*;
+
+pub use oxidize_ml_core::DistanceMetric;
@@ -0,0 +1,135 @@
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+use crate::nearest::{Algorithm, NearestNeighbors};
+
+/// Local Outlier Factor: flags points whose local density is much lower
+/// than that of their `n_neighbors` nearest neighbours.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct LocalOutlierFactor<T: Float> {
+    pub n_neighbors: usize,
+    pub metric: DistanceMetric<T>,
+    pub algorithm: Algorithm,
+    /// Expected share of outliers, which sets the score threshold; `None`
+    /// flags points with an outlier factor above 1.5.
+    pub contamination: Option<f64>,
+    /// `-LOF` of each training point (lower is more abnormal); set by
+    /// `fit_predict`.
+    pub negative_outlier_factor: Option<Tensor<T>>,
+    /// Scores below this are outliers.
+    pub offset: f64,
+}
+
+impl<T: Float> LocalOutlierFactor<T> {
+    pub fn new(n_neighbors: usize, metric: DistanceMetric<T>) -> Self {
+        LocalOutlierFactor {
+            n_neighbors,
+            metric,
+            algorithm: Algorithm::Auto,
+            contamination: None,
+            negative_outlier_factor: None,
+            offset: -1.5,
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Flag the `contamination` share of points with the lowest scores.
+    pub fn with_contamination(mut self, contamination: f64) -> Self {
+        self.contamination = Some(contamination);
+        self
+    }
+
+    /// Score the rows of `x` against each other: -1 for outliers, +1 for
+    /// inliers.
+    pub fn fit_predict(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let n = x.shape().dim(0)?;
+        if n < 2 {
+            return Err(TensorError::InvalidOperation("LocalOutlierFactor needs at least 2 points".into()));
+        }
+        if let Some(c) = self.contamination {
+            if !(0.0..=0.5).contains(&c) {
+                return Err(TensorError::InvalidOperation("contamination must be in [0, 0.5]".into()));
+            }
+        }
+        let k = self.n_neighbors.clamp(1, n - 1);
+        let d = x.shape().dim(1)?;
+        let mut index = NearestNeighbors::new(k, self.metric.clone()).with_algorithm(self.algorithm);
+        index.fit(x)?;
+
+        // Each point's k nearest other points.
+        let mut neighbors = Vec::with_capacity(n);
+        for (i, row) in x.data().chunks(d.max(1)).enumerate() {
+            let mut found = index.query(row, k + 1)?;
+            match found.iter().position(|&(j, _)| j == i) {
+                Some(pos) => {
+                    found.remove(pos);
+                }
+                None => {
+                    found.pop();
+                }
+            }
+            neighbors.push(found);
+        }
+        let k_distance: Vec<f64> = neighbors.iter().map(|nb| nb.last().map_or(0.0, |&(_, dist)| dist.to_f64())).collect();
+
+        // Local reachability density: inverse mean reachability distance.
+        let lrd: Vec<f64> = neighbors
+            .iter()
+            .map(|nb| {
+                let reach: f64 = nb.iter().map(|&(j, dist)| dist.to_f64().max(k_distance[j])).sum();
+                1.0 / (reach / nb.len() as f64 + 1e-10)
+            })
+            .collect();
+        let scores: Vec<f64> = neighbors
+            .iter()
+            .zip(&lrd)
+            .map(|(nb, &own)| -(nb.iter().map(|&(j, _)| lrd[j]).sum::<f64>() / nb.len() as f64) / own)
+            .collect();
+
+        self.offset = match self.contamination {
+            Some(c) => {
+                // Halfway between the lowest `c` share of scores and the rest.
+                let mut sorted = scores.clone();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let cut = ((c * n as f64).round() as usize).min(n - 1);
+                if cut == 0 { f64::NEG_INFINITY } else { (sorted[cut - 1] + sorted[cut]) / 2.0 }
+            }
+            None => -1.5,
+        };
+
+        let labels = scores.iter().map(|&s| if s < self.offset { -T::ONE } else { T::ONE }).collect();
+        self.negative_outlier_factor = Some(Tensor::new(scores.into_iter().map(T::from_f64).collect(), vec![n])?);
+        Tensor::new(labels, vec![n])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_outlier_factor() {
+        let mut rows: Vec<Vec<f64>> = (0..20).map(|i| vec![(i % 5) as f64 * 0.1, (i / 5) as f64 * 0.1]).collect();
+        rows.push(vec![3.0, 3.0]);
+        let x = Tensor::from_vec2d(&rows).unwrap();
+
+        let mut lof = LocalOutlierFactor::new(5, DistanceMetric::Euclidean);
+        let labels = lof.fit_predict(&x).unwrap();
+        assert_eq!(labels.data()[20], -1.0);
+        assert!(labels.data()[..20].iter().all(|&l| l == 1.0));
+        let scores = lof.negative_outlier_factor.as_ref().unwrap().data();
+        assert!(scores[20] < -5.0);
+        assert!(scores[..20].iter().all(|&s| s > -1.5));
+
+        let mut top = LocalOutlierFactor::new(5, DistanceMetric::Euclidean).with_contamination(0.1);
+        let labels = top.fit_predict(&x).unwrap();
+        assert_eq!(labels.data().iter().filter(|&&l| l == -1.0).count(), 2);
+        assert_eq!(labels.data()[20], -1.0);
+    }
+}
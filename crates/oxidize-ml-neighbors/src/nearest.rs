@@ -0,0 +1,256 @@
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::ball_tree::BallTree;
+use crate::kd_tree::KdTree;
+
+/// The best neighbours seen so far during a query: the `k` nearest, or
+/// every point within `radius`. Kept sorted by `(distance, row)`.
+pub(crate) struct KBest<T: Float> {
+    k: usize,
+    radius: T,
+    items: Vec<(T, usize)>,
+}
+
+fn by_distance<T: Float>(a: &(T, usize), b: &(T, usize)) -> Ordering {
+    a.0.to_f64().total_cmp(&b.0.to_f64()).then(a.1.cmp(&b.1))
+}
+
+impl<T: Float> KBest<T> {
+    pub(crate) fn new(k: usize) -> Self {
+        KBest { k, radius: T::from_f64(f64::INFINITY), items: Vec::new() }
+    }
+
+    pub(crate) fn within(radius: T) -> Self {
+        KBest { k: usize::MAX, radius, items: Vec::new() }
+    }
+
+    /// Whether a region at least `bound` away could still hold a neighbour.
+    pub(crate) fn admits(&self, bound: T) -> bool {
+        match self.items.last() {
+            Some(&(worst, _)) if self.items.len() >= self.k => bound <= worst,
+            _ => bound <= self.radius,
+        }
+    }
+
+    pub(crate) fn push(&mut self, distance: T, row: usize) {
+        if distance > self.radius || self.k == 0 {
+            return;
+        }
+        let item = (distance, row);
+        if self.items.len() >= self.k && by_distance(&item, self.items.last().unwrap()) != Ordering::Less {
+            return;
+        }
+        let pos = self.items.partition_point(|x| by_distance(x, &item) == Ordering::Less);
+        self.items.insert(pos, item);
+        self.items.truncate(self.k);
+    }
+
+    pub(crate) fn into_sorted(self) -> Vec<(usize, T)> {
+        self.items.into_iter().map(|(d, i)| (i, d)).collect()
+    }
+}
+
+/// Per-row neighbour indices and their distances, from `radius_neighbors`.
+pub type RadiusNeighbors<T> = (Vec<Vec<usize>>, Vec<Vec<T>>);
+
+/// How neighbour queries search the training points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    /// `BruteForce` for small or high-dimensional data, else `KdTree` when
+    /// the metric allows it, else `BallTree` when the metric obeys the
+    /// triangle inequality, else `BruteForce`.
+    Auto,
+    /// Compare against every training point.
+    BruteForce,
+    KdTree,
+    BallTree,
+}
+
+/// Above this many features, trees prune too little to beat brute force.
+const AUTO_MAX_TREE_DIM: usize = 15;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+enum Index<T: Float> {
+    Brute(Tensor<T>),
+    KdTree(KdTree<T>),
+    BallTree(BallTree<T>),
+}
+
+/// Unsupervised nearest-neighbour search over a fitted set of points.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct NearestNeighbors<T: Float> {
+    pub n_neighbors: usize,
+    pub metric: DistanceMetric<T>,
+    pub algorithm: Algorithm,
+    /// Points per tree leaf, below which a tree stops splitting.
+    pub leaf_size: usize,
+    index: Option<Index<T>>,
+}
+
+impl<T: Float> NearestNeighbors<T> {
+    pub fn new(n_neighbors: usize, metric: DistanceMetric<T>) -> Self {
+        NearestNeighbors {
+            n_neighbors,
+            metric,
+            algorithm: Algorithm::Auto,
+            leaf_size: 30,
+            index: None,
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_leaf_size(mut self, leaf_size: usize) -> Self {
+        self.leaf_size = leaf_size;
+        self
+    }
+
+    /// Index the rows of `x` (`[n, d]`).
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        if x.ndim() != 2 {
+            return Err(TensorError::InvalidOperation("NearestNeighbors expects a 2-D tensor".into()));
+        }
+        let (n, d) = (x.shape().dim(0)?, x.shape().dim(1)?);
+        let algorithm = match self.algorithm {
+            Algorithm::Auto if n <= self.leaf_size || d > AUTO_MAX_TREE_DIM => Algorithm::BruteForce,
+            Algorithm::Auto if self.metric.axis_bounded() => Algorithm::KdTree,
+            Algorithm::Auto if self.metric.is_metric() => Algorithm::BallTree,
+            Algorithm::Auto => Algorithm::BruteForce,
+            other => other,
+        };
+        self.index = Some(match algorithm {
+            Algorithm::KdTree => Index::KdTree(KdTree::new(x, self.metric.clone(), self.leaf_size)?),
+            Algorithm::BallTree => Index::BallTree(BallTree::new(x, self.metric.clone(), self.leaf_size)?),
+            _ => Index::Brute(x.clone()),
+        });
+        Ok(())
+    }
+
+    /// The algorithm `fit` picked, with `Auto` resolved.
+    pub fn fitted_algorithm(&self) -> Option<Algorithm> {
+        self.index.as_ref().map(|index| match index {
+            Index::Brute(_) => Algorithm::BruteForce,
+            Index::KdTree(_) => Algorithm::KdTree,
+            Index::BallTree(_) => Algorithm::BallTree,
+        })
+    }
+
+    fn fitted(&self) -> TensorResult<&Index<T>> {
+        self.index.as_ref().ok_or_else(|| TensorError::InvalidOperation("Model not fitted".into()))
+    }
+
+    /// Number of fitted points.
+    pub fn n_samples(&self) -> usize {
+        match &self.index {
+            Some(Index::Brute(x)) => x.shape_vec()[0],
+            Some(Index::KdTree(t)) => t.len(),
+            Some(Index::BallTree(t)) => t.len(),
+            None => 0,
+        }
+    }
+
+    /// The `k` fitted points nearest to `point`, as `(row, distance)` by
+    /// increasing distance; ties go to the lower row.
+    pub fn query(&self, point: &[T], k: usize) -> TensorResult<Vec<(usize, T)>> {
+        Ok(match self.fitted()? {
+            Index::Brute(x) => {
+                let d = x.shape().dim(1)?;
+                let mut best = KBest::new(k);
+                for (i, row) in x.data().chunks(d.max(1)).enumerate() {
+                    best.push(self.metric.distance(point, row), i);
+                }
+                best.into_sorted()
+            }
+            Index::KdTree(tree) => tree.query(point, k),
+            Index::BallTree(tree) => tree.query(point, k),
+        })
+    }
+
+    /// Every fitted point within `radius` of `point`, sorted like `query`.
+    pub fn query_radius(&self, point: &[T], radius: T) -> TensorResult<Vec<(usize, T)>> {
+        Ok(match self.fitted()? {
+            Index::Brute(x) => {
+                let d = x.shape().dim(1)?;
+                let mut best = KBest::within(radius);
+                for (i, row) in x.data().chunks(d.max(1)).enumerate() {
+                    best.push(self.metric.distance(point, row), i);
+                }
+                best.into_sorted()
+            }
+            Index::KdTree(tree) => tree.query_radius(point, radius),
+            Index::BallTree(tree) => tree.query_radius(point, radius),
+        })
+    }
+
+    /// Indices and distances of the fitted points within `radius` of each
+    /// row of `x`, nearest first.
+    pub fn radius_neighbors(&self, x: &Tensor<T>, radius: T) -> TensorResult<RadiusNeighbors<T>> {
+        let d = x.shape().dim(1)?;
+        let mut indices = Vec::new();
+        let mut distances = Vec::new();
+        for row in x.data().chunks(d.max(1)) {
+            let found = self.query_radius(row, radius)?;
+            indices.push(found.iter().map(|&(j, _)| j).collect());
+            distances.push(found.iter().map(|&(_, dist)| dist).collect());
+        }
+        Ok((indices, distances))
+    }
+
+    /// Indices (`n_neighbors` per row of `x`, nearest first) and distances
+    /// (`[n, n_neighbors]`) of each row's nearest fitted points.
+    pub fn kneighbors(&self, x: &Tensor<T>) -> TensorResult<(Vec<Vec<usize>>, Tensor<T>)> {
+        let k = self.n_neighbors.min(self.n_samples());
+        let d = x.shape().dim(1)?;
+        let n = x.shape().dim(0)?;
+        let mut indices = Vec::with_capacity(n);
+        let mut distances = Vec::with_capacity(n * k);
+        for i in 0..n {
+            let found = self.query(&x.data()[i * d..(i + 1) * d], k)?;
+            indices.push(found.iter().map(|&(j, _)| j).collect());
+            distances.extend(found.iter().map(|&(_, dist)| dist));
+        }
+        Ok((indices, Tensor::new(distances, vec![n, k])?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trees_match_brute_force() {
+        // A deterministic scatter with repeated coordinates to exercise ties.
+        let n = 200;
+        let data: Vec<f64> = (0..n * 3).map(|i| ((i * 7919) % 101) as f64 / 10.0).collect();
+        let x = Tensor::new(data, vec![n, 3]).unwrap();
+        let queries = Tensor::new((0..30).map(|i| ((i * 31) % 97) as f64 / 9.0).collect(), vec![10, 3]).unwrap();
+
+        for metric in [DistanceMetric::Euclidean, DistanceMetric::Manhattan, DistanceMetric::Chebyshev, DistanceMetric::Minkowski(3.0)] {
+            let mut brute = NearestNeighbors::new(7, metric.clone()).with_algorithm(Algorithm::BruteForce);
+            brute.fit(&x).unwrap();
+            let expected = brute.kneighbors(&queries).unwrap();
+            for algorithm in [Algorithm::KdTree, Algorithm::BallTree, Algorithm::Auto] {
+                let mut nn = NearestNeighbors::new(7, metric.clone()).with_algorithm(algorithm).with_leaf_size(5);
+                nn.fit(&x).unwrap();
+                let (idx, dist) = nn.kneighbors(&queries).unwrap();
+                assert_eq!(idx, expected.0, "{:?} {:?}", metric, algorithm);
+                assert!(dist.data().iter().zip(expected.1.data()).all(|(a, b)| (a - b).abs() < 1e-12));
+            }
+        }
+
+        let mut auto = NearestNeighbors::new(3, DistanceMetric::Euclidean);
+        auto.fit(&x).unwrap();
+        assert_eq!(auto.fitted_algorithm(), Some(Algorithm::KdTree));
+        let (idx, dist) = auto.kneighbors(&x.index_select(0, &[5]).unwrap()).unwrap();
+        assert_eq!(idx[0][0], 5);
+        assert_eq!(dist.data()[0], 0.0);
+    }
+}
@@ -0,0 +1,167 @@
+use oxidize_ml_core::{DistanceMetric, Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+use crate::nearest::{Algorithm, NearestNeighbors};
+
+fn fit_neighbors<T: Float>(x: &Tensor<T>, metric: &DistanceMetric<T>, algorithm: Algorithm) -> TensorResult<NearestNeighbors<T>> {
+    let mut neighbors = NearestNeighbors::new(1, metric.clone()).with_algorithm(algorithm);
+    neighbors.fit(x)?;
+    Ok(neighbors)
+}
+
+/// Classifier voting among all training points within `radius`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct RadiusNeighborsClassifier<T: Float> {
+    pub radius: T,
+    pub metric: DistanceMetric<T>,
+    pub algorithm: Algorithm,
+    /// Label for rows with no training point in range; `None` makes such
+    /// rows an error.
+    pub outlier_label: Option<T>,
+    neighbors: Option<NearestNeighbors<T>>,
+    y_train: Option<Tensor<T>>,
+    pub n_classes: usize,
+}
+
+impl<T: Float> RadiusNeighborsClassifier<T> {
+    pub fn new(radius: T, metric: DistanceMetric<T>) -> Self {
+        RadiusNeighborsClassifier {
+            radius,
+            metric,
+            algorithm: Algorithm::Auto,
+            outlier_label: None,
+            neighbors: None,
+            y_train: None,
+            n_classes: 0,
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_outlier_label(mut self, label: T) -> Self {
+        self.outlier_label = Some(label);
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.neighbors = Some(fit_neighbors(x, &self.metric, self.algorithm)?);
+        self.y_train = Some(y.clone());
+        let max_label = y.data().iter().map(|v| v.to_f64().round() as usize).max().unwrap_or(0);
+        self.n_classes = max_label + 1;
+        Ok(())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let neighbors = self.neighbors.as_ref().ok_or_else(|| {
+            TensorError::InvalidOperation("Model not fitted".into())
+        })?;
+        let y_train = self.y_train.as_ref().unwrap();
+        let (indices, _) = neighbors.radius_neighbors(x, self.radius)?;
+
+        let mut predictions = Vec::with_capacity(indices.len());
+        for (i, found) in indices.iter().enumerate() {
+            if found.is_empty() {
+                predictions.push(self.outlier_label.ok_or_else(|| {
+                    TensorError::InvalidOperation(format!("no training point within radius of row {}; set an outlier label", i))
+                })?);
+                continue;
+            }
+            let mut votes = vec![0usize; self.n_classes];
+            for &j in found {
+                let cls = y_train.data()[j].to_f64().round() as usize;
+                if cls < self.n_classes {
+                    votes[cls] += 1;
+                }
+            }
+            let best = votes.iter().enumerate().max_by_key(|(_, &c)| c).map(|(i, _)| i).unwrap_or(0);
+            predictions.push(T::from_usize(best));
+        }
+        Tensor::new(predictions, vec![indices.len()])
+    }
+}
+
+/// Regressor averaging the targets of all training points within `radius`.
+/// Rows with no point in range predict NaN.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct RadiusNeighborsRegressor<T: Float> {
+    pub radius: T,
+    pub metric: DistanceMetric<T>,
+    pub algorithm: Algorithm,
+    neighbors: Option<NearestNeighbors<T>>,
+    y_train: Option<Tensor<T>>,
+}
+
+impl<T: Float> RadiusNeighborsRegressor<T> {
+    pub fn new(radius: T, metric: DistanceMetric<T>) -> Self {
+        RadiusNeighborsRegressor {
+            radius,
+            metric,
+            algorithm: Algorithm::Auto,
+            neighbors: None,
+            y_train: None,
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.neighbors = Some(fit_neighbors(x, &self.metric, self.algorithm)?);
+        self.y_train = Some(y.clone());
+        Ok(())
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let neighbors = self.neighbors.as_ref().ok_or_else(|| {
+            TensorError::InvalidOperation("Model not fitted".into())
+        })?;
+        let y_train = self.y_train.as_ref().unwrap();
+        let (indices, _) = neighbors.radius_neighbors(x, self.radius)?;
+        let predictions = indices
+            .iter()
+            .map(|found| {
+                if found.is_empty() {
+                    return T::from_f64(f64::NAN);
+                }
+                let sum: T = found.iter().map(|&j| y_train.data()[j]).sum();
+                sum / T::from_usize(found.len())
+            })
+            .collect();
+        Tensor::new(predictions, vec![indices.len()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radius_neighbors() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![0.0, 0.0], vec![0.5, 0.0], vec![1.0, 0.0],
+            vec![5.0, 5.0], vec![5.5, 5.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0, 1.0]);
+        let queries = Tensor::from_vec2d(&[vec![0.4, 0.0], vec![5.2, 5.0], vec![20.0, 0.0]]).unwrap();
+
+        let mut clf = RadiusNeighborsClassifier::new(1.0, DistanceMetric::Euclidean);
+        clf.fit(&x, &y).unwrap();
+        assert!(clf.predict(&queries).is_err());
+        let clf = clf.with_outlier_label(-1.0);
+        assert_eq!(clf.predict(&queries).unwrap().data(), &[0.0, 1.0, -1.0]);
+
+        let mut reg = RadiusNeighborsRegressor::new(0.55, DistanceMetric::Euclidean).with_algorithm(Algorithm::KdTree);
+        reg.fit(&x, &y).unwrap();
+        let pred = reg.predict(&queries).unwrap();
+        assert_eq!(&pred.data()[..2], &[0.0, 1.0]);
+        assert!(pred.data()[2].is_nan());
+    }
+}
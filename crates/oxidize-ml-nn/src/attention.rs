@@ -0,0 +1,235 @@
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::Variable;
+use crate::layers::{Layer, Linear};
+use crate::norm::LayerNorm;
+use crate::state_dict::NamedParameters;
+
+/// Additive bias standing in for -∞ on masked scores; finite so a fully
+/// masked row stays a (uniform) distribution instead of NaN.
+const MASKED: f64 = -1e9;
+
+/// `[len, len]` attention mask letting position `i` see positions `0..=i`.
+///
+/// Masks hold 1 where a query may attend to a key and 0 where it may not;
+/// combine them with `Tensor::mul`.
+pub fn causal_mask<T: Float>(len: usize) -> Tensor<T> {
+    let data = (0..len * len).map(|i| if i % len <= i / len { T::ONE } else { T::ZERO }).collect();
+    Tensor::new(data, vec![len, len]).expect("causal mask")
+}
+
+/// `[q_len, keep.len()]` attention mask hiding the keys whose `keep` is
+/// false, such as padding tokens.
+pub fn padding_mask<T: Float>(q_len: usize, keep: &[bool]) -> Tensor<T> {
+    let data = (0..q_len).flat_map(|_| keep.iter().map(|&k| if k { T::ONE } else { T::ZERO })).collect();
+    Tensor::new(data, vec![q_len, keep.len()]).expect("padding mask")
+}
+
+/// Multi-head scaled dot-product attention over one sequence.
+///
+/// Queries `[q_len, d_model]` attend over keys and values projected from a
+/// `[k_len, d_model]` memory (the queries themselves for self-attention).
+/// Each head attends with its own `d_k`-wide slice of the projections:
+/// `softmax(Q_h K_hᵀ / √d_k + M) V_h`, where `M` is 0 where the mask allows
+/// attention and effectively -∞ elsewhere; the heads are concatenated and
+/// projected by `w_o`.
+pub struct MultiHeadAttention<T: Float = f64> {
+    pub num_heads: usize,
+    pub d_model: usize,
+    pub d_k: usize,
+    pub w_q: Linear<T>,
+    pub w_k: Linear<T>,
+    pub w_v: Linear<T>,
+    pub w_o: Linear<T>,
+}
+
+impl<T: Float> MultiHeadAttention<T> {
+    pub fn new(d_model: usize, num_heads: usize) -> Self {
+        assert!(num_heads > 0 && d_model.is_multiple_of(num_heads), "d_model must be divisible by num_heads");
+        MultiHeadAttention {
+            num_heads,
+            d_model,
+            d_k: d_model / num_heads,
+            w_q: Linear::new(d_model, d_model),
+            w_k: Linear::new(d_model, d_model),
+            w_v: Linear::new(d_model, d_model),
+            w_o: Linear::new(d_model, d_model),
+        }
+    }
+
+    /// Attend from `query` over `memory` under an optional `[q_len, k_len]`
+    /// mask (see `causal_mask`).
+    pub fn attend(&self, query: &Variable<T>, memory: &Variable<T>, mask: Option<&Tensor<T>>) -> Variable<T> {
+        let q = self.w_q.forward(query);
+        let k = self.w_k.forward(memory);
+        let v = self.w_v.forward(memory);
+        let scale = 1.0 / (self.d_k as f64).sqrt();
+        let bias = mask.map(|mask| {
+            let expected = vec![q.shape_vec()[0], k.shape_vec()[0]];
+            assert_eq!(mask.shape_vec(), expected, "attention mask must be [q_len, k_len]");
+            q.constant(mask.apply(|keep| if keep > T::ZERO { T::ZERO } else { T::from_f64(MASKED) }))
+        });
+
+        let heads: Vec<Variable<T>> = (0..self.num_heads)
+            .map(|h| {
+                let cols = (h * self.d_k, (h + 1) * self.d_k);
+                let (q_h, k_h, v_h) = (q.slice(1, cols.0, cols.1), k.slice(1, cols.0, cols.1), v.slice(1, cols.0, cols.1));
+                let scores = q_h.matmul(&k_h.t()).mul_scalar(scale);
+                let scores = match &bias {
+                    Some(bias) => scores.add(bias),
+                    None => scores,
+                };
+                scores.softmax().matmul(&v_h)
+            })
+            .collect();
+        self.w_o.forward(&Variable::concat(&heads.iter().collect::<Vec<_>>(), 1))
+    }
+}
+
+/// Unmasked self-attention.
+impl<T: Float> Layer<T> for MultiHeadAttention<T> {
+    fn forward(&self, x: &Variable<T>) -> Variable<T> {
+        self.attend(x, x, None)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        [&self.w_q, &self.w_k, &self.w_v, &self.w_o]
+            .iter()
+            .flat_map(|l| l.parameters())
+            .collect()
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        NamedParameters::new()
+            .register_module("w_q", self.w_q.named_parameters())
+            .register_module("w_k", self.w_k.named_parameters())
+            .register_module("w_v", self.w_v.named_parameters())
+            .register_module("w_o", self.w_o.named_parameters())
+            .into_vec()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        NamedParameters::new()
+            .register_module("w_q", self.w_q.named_parameters_mut())
+            .register_module("w_k", self.w_k.named_parameters_mut())
+            .register_module("w_v", self.w_v.named_parameters_mut())
+            .register_module("w_o", self.w_o.named_parameters_mut())
+            .into_vec()
+    }
+}
+
+/// Nonlinearity between the two layers of a feed-forward network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    ReLU,
+    /// Exact (erf) GELU, as in BERT.
+    Gelu,
+}
+
+/// Transformer encoder block: self-attention and a feed-forward network
+/// (ReLU by default), each wrapped in a residual connection followed by
+/// LayerNorm (post-norm).
+///
+/// `y = norm1(x + attn(x))`, then `norm2(y + W₂ act(W₁ y))`.
+pub struct TransformerBlock<T: Float = f64> {
+    pub attention: MultiHeadAttention<T>,
+    pub norm1: LayerNorm<T>,
+    pub ff1: Linear<T>,
+    pub ff2: Linear<T>,
+    pub norm2: LayerNorm<T>,
+    pub activation: Activation,
+}
+
+impl<T: Float> TransformerBlock<T> {
+    pub fn new(d_model: usize, num_heads: usize, d_ff: usize) -> Self {
+        TransformerBlock {
+            attention: MultiHeadAttention::new(d_model, num_heads),
+            norm1: LayerNorm::new(d_model),
+            ff1: Linear::new(d_model, d_ff),
+            ff2: Linear::new(d_ff, d_model),
+            norm2: LayerNorm::new(d_model),
+            activation: Activation::ReLU,
+        }
+    }
+
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    /// The block with self-attention restricted by `mask`.
+    pub fn forward_masked(&self, x: &Variable<T>, mask: Option<&Tensor<T>>) -> Variable<T> {
+        let y = self.norm1.forward(&x.add(&self.attention.attend(x, x, mask)));
+        let hidden = self.ff1.forward(&y);
+        let hidden = match self.activation {
+            Activation::ReLU => hidden.relu(),
+            Activation::Gelu => hidden.gelu(),
+        };
+        self.norm2.forward(&y.add(&self.ff2.forward(&hidden)))
+    }
+}
+
+impl<T: Float> Layer<T> for TransformerBlock<T> {
+    fn forward(&self, x: &Variable<T>) -> Variable<T> {
+        self.forward_masked(x, None)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        let mut params = self.attention.parameters();
+        params.extend(self.norm1.parameters());
+        params.extend(self.ff1.parameters());
+        params.extend(self.ff2.parameters());
+        params.extend(self.norm2.parameters());
+        params
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        NamedParameters::new()
+            .register_module("attention", self.attention.named_parameters())
+            .register_module("norm1", self.norm1.named_parameters())
+            .register_module("ff1", self.ff1.named_parameters())
+            .register_module("ff2", self.ff2.named_parameters())
+            .register_module("norm2", self.norm2.named_parameters())
+            .into_vec()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        NamedParameters::new()
+            .register_module("attention", self.attention.named_parameters_mut())
+            .register_module("norm1", self.norm1.named_parameters_mut())
+            .register_module("ff1", self.ff1.named_parameters_mut())
+            .register_module("ff2", self.ff2.named_parameters_mut())
+            .register_module("norm2", self.norm2.named_parameters_mut())
+            .into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recurrent::GRUCell;
+    use oxidize_ml_core::Tensor;
+    use oxidize_ml_autodiff::backward::backward;
+
+    #[test]
+    fn test_transformer_block_mixes_with_rnn_cells() {
+        // A GRU encodes the sequence, a transformer block attends over the
+        // hidden states, and gradients reach both.
+        let gru = GRUCell::new(3, 4);
+        let block = TransformerBlock::new(4, 2, 8);
+        let steps: Vec<Variable> = (0..5).map(|i| Variable::input(Tensor::rand(vec![1, 3], Some(i)))).collect();
+        let states = gru.forward_seq(&steps, &Variable::input(Tensor::zeros(vec![1, 4])));
+
+        let seq = Variable::concat(&states.iter().collect::<Vec<_>>(), 0);
+        let out = block.forward(&seq);
+        assert_eq!(out.shape_vec(), vec![5, 4]);
+
+        let grads = backward(&out.pow(2.0).mean());
+        for p in block.parameters().iter().chain(&gru.parameters()) {
+            let g = &grads[&p.node_id];
+            assert_eq!(g.shape_vec(), p.shape_vec());
+            assert!(g.data().iter().all(|v| v.is_finite()));
+        }
+        assert!(grads[&gru.w_z_x.node_id].data().iter().any(|v| v.abs() > 0.0));
+        assert!(grads[&block.attention.w_q.weight.node_id].data().iter().any(|v| v.abs() > 0.0));
+    }
+}
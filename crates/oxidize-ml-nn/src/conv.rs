@@ -1,26 +1,27 @@
-use oxidize_ml_core::Tensor;
-use oxidize_ml_autodiff::Variable;
-use super::layers::Layer;
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::{no_grad, Conv2dOptions, Variable};
+use crate::layers::{Layer, LayerKind};
+use crate::init::{self, Init};
+use crate::state_dict::named_fields;
 
 /// 1D Convolution layer.
 ///
 /// Input shape:  [batch, in_channels, length]
 /// Output shape: [batch, out_channels, out_length]
 /// where out_length = (length - kernel_size) / stride + 1
-pub struct Conv1D {
+pub struct Conv1D<T: Float = f64> {
     pub in_channels: usize,
     pub out_channels: usize,
     pub kernel_size: usize,
     pub stride: usize,
-    pub weight: Variable, // [out_channels, in_channels, kernel_size]
-    pub bias: Variable,   // [out_channels]
+    pub weight: Variable<T>, // [out_channels, in_channels, kernel_size]
+    pub bias: Variable<T>,   // [out_channels]
 }
 
-impl Conv1D {
+impl<T: Float> Conv1D<T> {
     pub fn new(in_channels: usize, out_channels: usize, kernel_size: usize, stride: usize) -> Self {
         let k = (2.0 / (in_channels * kernel_size) as f64).sqrt();
-        let w = Tensor::rand(vec![out_channels, in_channels, kernel_size], Some(42))
-            .mul_scalar(2.0 * k).add_scalar(-k);
+        let w = init::uniform(vec![out_channels, in_channels, kernel_size], -k, k);
         let b = Tensor::zeros(vec![out_channels]);
 
         Conv1D {
@@ -30,13 +31,19 @@ impl Conv1D {
         }
     }
 
+    /// Re-draw the kernel from `init`; the bias stays zero.
+    pub fn with_init(mut self, init: Init) -> Self {
+        self.weight.set_data(init.tensor(self.weight.shape_vec()));
+        self
+    }
+
     /// Forward pass using im2col-style unrolling.
-    pub fn forward_tensor(&self, input: &Tensor<f64>) -> Tensor<f64> {
+    pub fn forward_tensor(&self, input: &Tensor<T>) -> Tensor<T> {
         let shape = input.shape_vec();
         let (batch, _in_ch, length) = (shape[0], shape[1], shape[2]);
         let out_len = (length - self.kernel_size) / self.stride + 1;
 
-        let mut output = vec![0.0f64; batch * self.out_channels * out_len];
+        let mut output = vec![T::ZERO; batch * self.out_channels * out_len];
 
         for b in 0..batch {
             for oc in 0..self.out_channels {
@@ -58,26 +65,69 @@ impl Conv1D {
         Tensor::new(output, vec![batch, self.out_channels, out_len]).unwrap()
     }
 
-    pub fn parameters(&self) -> Vec<Variable> {
+    pub fn parameters(&self) -> Vec<Variable<T>> {
         vec![self.weight.clone(), self.bias.clone()]
     }
+
+    pub fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; weight, bias)
+    }
+
+    pub fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; weight, bias)
+    }
+}
+
+/// Runs as a 2-D convolution over a height-1 image, so gradients reach
+/// the input, kernel and bias.
+impl<T: Float> Layer<T> for Conv1D<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let shape = input.shape_vec();
+        let image = input.reshape(vec![shape[0], shape[1], 1, shape[2]]);
+        let kernel = self.weight.reshape(vec![self.out_channels, self.in_channels, 1, self.kernel_size]);
+        let out = image.conv2d(&kernel, Some(&self.bias), &Conv2dOptions::new().with_stride((1, self.stride)));
+        let out_len = out.shape_vec()[3];
+        out.reshape(vec![shape[0], self.out_channels, out_len])
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        Conv1D::parameters(self)
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        Conv1D::named_parameters(self)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        Conv1D::named_parameters_mut(self)
+    }
 }
 
 /// 2D Convolution layer.
 ///
 /// Input shape:  [batch, in_channels, height, width]
 /// Output shape: [batch, out_channels, out_h, out_w]
-pub struct Conv2D {
+/// where out_h = (height + 2·padding - dilation·(kernel - 1) - 1) / stride + 1
+///
+/// Kernel, stride, padding and dilation are `(height, width)` pairs. With
+/// `groups = g` each block of `out_channels / g` outputs sees only its
+/// `in_channels / g` inputs; `groups = in_channels` is a depthwise
+/// convolution.
+pub struct Conv2D<T: Float = f64> {
     pub in_channels: usize,
     pub out_channels: usize,
-    pub kernel_size: usize,
-    pub stride: usize,
-    pub padding: usize,
-    pub weight: Variable, // [out_channels, in_channels, kH, kW]
-    pub bias: Variable,   // [out_channels]
+    pub kernel_size: (usize, usize),
+    pub stride: (usize, usize),
+    pub padding: (usize, usize),
+    pub dilation: (usize, usize),
+    pub groups: usize,
+    pub weight: Variable<T>, // [out_channels, in_channels / groups, kH, kW]
+    pub bias: Variable<T>,   // [out_channels]
 }
 
-impl Conv2D {
+impl<T: Float> Conv2D<T> {
+    /// A square `kernel_size` kernel with the same stride and padding on
+    /// both axes.
     pub fn new(
         in_channels: usize,
         out_channels: usize,
@@ -85,68 +135,238 @@ impl Conv2D {
         stride: usize,
         padding: usize,
     ) -> Self {
-        let fan_in = in_channels * kernel_size * kernel_size;
-        let k = (2.0 / fan_in as f64).sqrt();
-        let w = Tensor::rand(
-            vec![out_channels, in_channels, kernel_size, kernel_size],
-            Some(42),
-        ).mul_scalar(2.0 * k).add_scalar(-k);
-        let b = Tensor::zeros(vec![out_channels]);
+        Self::rectangular(in_channels, out_channels, (kernel_size, kernel_size), (stride, stride), (padding, padding))
+    }
 
-        Conv2D {
+    /// A `kernel_size = (kH, kW)` kernel with per-axis stride and padding.
+    pub fn rectangular(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: (usize, usize),
+        stride: (usize, usize),
+        padding: (usize, usize),
+    ) -> Self {
+        let mut conv = Conv2D {
             in_channels, out_channels, kernel_size, stride, padding,
-            weight: Variable::param(w),
-            bias: Variable::param(b),
-        }
+            dilation: (1, 1),
+            groups: 1,
+            weight: Variable::param(Tensor::zeros(vec![0])),
+            bias: Variable::param(Tensor::zeros(vec![out_channels])),
+        };
+        conv.reset_weight();
+        conv
     }
 
-    /// Compute output spatial dimension.
-    fn out_dim(input_dim: usize, kernel: usize, stride: usize, padding: usize) -> usize {
-        (input_dim + 2 * padding - kernel) / stride + 1
+    /// Space the kernel taps `dilation` apart.
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        self.dilation = dilation;
+        self
     }
 
-    /// Forward pass.
-    pub fn forward_tensor(&self, input: &Tensor<f64>) -> Tensor<f64> {
-        let shape = input.shape_vec();
-        let (batch, _ic, h, w) = (shape[0], shape[1], shape[2], shape[3]);
-        let out_h = Self::out_dim(h, self.kernel_size, self.stride, self.padding);
-        let out_w = Self::out_dim(w, self.kernel_size, self.stride, self.padding);
+    /// Split the channels into `groups`; the kernel is re-drawn for its new
+    /// `[out, in / groups, kH, kW]` shape.
+    pub fn with_groups(mut self, groups: usize) -> Self {
+        assert!(
+            groups > 0 && self.in_channels.is_multiple_of(groups) && self.out_channels.is_multiple_of(groups),
+            "groups must divide in_channels and out_channels"
+        );
+        self.groups = groups;
+        self.reset_weight();
+        self
+    }
 
-        let mut output = vec![0.0f64; batch * self.out_channels * out_h * out_w];
+    /// Re-draw the kernel from `init`; the bias stays zero.
+    pub fn with_init(mut self, init: Init) -> Self {
+        self.weight.set_data(init.tensor(self.weight.shape_vec()));
+        self
+    }
 
-        for b in 0..batch {
-            for oc in 0..self.out_channels {
-                for oh in 0..out_h {
-                    for ow in 0..out_w {
-                        let mut sum = self.bias.data.data()[oc];
-                        for ic in 0..self.in_channels {
-                            for kh in 0..self.kernel_size {
-                                for kw in 0..self.kernel_size {
-                                    let ih = (oh * self.stride + kh) as isize - self.padding as isize;
-                                    let iw = (ow * self.stride + kw) as isize - self.padding as isize;
-
-                                    if ih >= 0 && ih < h as isize && iw >= 0 && iw < w as isize {
-                                        let w_val = self.weight.data.get(&[oc, ic, kh, kw]).unwrap();
-                                        let x_val = input.get(&[b, ic, ih as usize, iw as usize]).unwrap();
-                                        sum += w_val * x_val;
-                                    }
-                                }
-                            }
-                        }
-                        let idx = b * self.out_channels * out_h * out_w
-                            + oc * out_h * out_w + oh * out_w + ow;
-                        output[idx] = sum;
-                    }
-                }
-            }
+    fn reset_weight(&mut self) {
+        let (kh, kw) = self.kernel_size;
+        let fan_in = self.in_channels / self.groups * kh * kw;
+        let k = (2.0 / fan_in as f64).sqrt();
+        self.weight.set_data(init::uniform(vec![self.out_channels, self.in_channels / self.groups, kh, kw], -k, k));
+    }
+
+    fn options(&self) -> Conv2dOptions {
+        Conv2dOptions::new()
+            .with_stride(self.stride)
+            .with_padding(self.padding)
+            .with_dilation(self.dilation)
+            .with_groups(self.groups)
+    }
+
+    /// Forward pass without recording on the graph.
+    pub fn forward_tensor(&self, input: &Tensor<T>) -> Tensor<T> {
+        no_grad(|| Layer::forward(self, &Variable::input(input.clone())).data)
+    }
+
+    pub fn parameters(&self) -> Vec<Variable<T>> {
+        vec![self.weight.clone(), self.bias.clone()]
+    }
+}
+
+/// Recorded on the graph through `Variable::conv2d` (im2col and a matmul
+/// per group), so gradients reach the input, kernel and bias.
+impl<T: Float> Layer<T> for Conv2D<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        input.conv2d(&self.weight, Some(&self.bias), &self.options())
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        Conv2D::parameters(self)
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; weight, bias)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; weight, bias)
+    }
+
+    fn kind(&self) -> LayerKind {
+        LayerKind::Conv2D {
+            kernel_size: self.kernel_size,
+            stride: self.stride,
+            padding: self.padding,
+            dilation: self.dilation,
+            groups: self.groups,
         }
+    }
+}
+
+/// 2D transposed convolution, the adjoint of `Conv2D`: each input pixel
+/// scatters a weighted copy of the kernel into the output, so a stride
+/// upsamples. Used in decoders and upsampling paths.
+///
+/// Input shape:  [batch, in_channels, height, width]
+/// Output shape: [batch, out_channels, out_h, out_w]
+/// where out_h = (height - 1)·stride - 2·padding + dilation·(kernel - 1)
+///               + output_padding + 1
+pub struct ConvTranspose2D<T: Float = f64> {
+    pub in_channels: usize,
+    pub out_channels: usize,
+    pub kernel_size: (usize, usize),
+    pub stride: (usize, usize),
+    pub padding: (usize, usize),
+    pub output_padding: (usize, usize),
+    pub dilation: (usize, usize),
+    pub groups: usize,
+    pub weight: Variable<T>, // [in_channels, out_channels / groups, kH, kW]
+    pub bias: Variable<T>,   // [out_channels]
+}
 
-        Tensor::new(output, vec![batch, self.out_channels, out_h, out_w]).unwrap()
+impl<T: Float> ConvTranspose2D<T> {
+    /// A square `kernel_size` kernel with the same stride and padding on
+    /// both axes.
+    pub fn new(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: usize,
+        stride: usize,
+        padding: usize,
+    ) -> Self {
+        Self::rectangular(in_channels, out_channels, (kernel_size, kernel_size), (stride, stride), (padding, padding))
+    }
+
+    /// A `kernel_size = (kH, kW)` kernel with per-axis stride and padding.
+    pub fn rectangular(
+        in_channels: usize,
+        out_channels: usize,
+        kernel_size: (usize, usize),
+        stride: (usize, usize),
+        padding: (usize, usize),
+    ) -> Self {
+        let mut conv = ConvTranspose2D {
+            in_channels, out_channels, kernel_size, stride, padding,
+            output_padding: (0, 0),
+            dilation: (1, 1),
+            groups: 1,
+            weight: Variable::param(Tensor::zeros(vec![0])),
+            bias: Variable::param(Tensor::zeros(vec![out_channels])),
+        };
+        conv.reset_weight();
+        conv
+    }
+
+    /// Grow the output by `output_padding` rows/columns (each smaller than
+    /// the stride), e.g. to invert a stride-2 `Conv2D` on an odd-sized input.
+    pub fn with_output_padding(mut self, output_padding: (usize, usize)) -> Self {
+        self.output_padding = output_padding;
+        self
+    }
+
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        self.dilation = dilation;
+        self
+    }
+
+    /// Split the channels into `groups`; the kernel is re-drawn for its new
+    /// `[in, out / groups, kH, kW]` shape.
+    pub fn with_groups(mut self, groups: usize) -> Self {
+        assert!(
+            groups > 0 && self.in_channels.is_multiple_of(groups) && self.out_channels.is_multiple_of(groups),
+            "groups must divide in_channels and out_channels"
+        );
+        self.groups = groups;
+        self.reset_weight();
+        self
+    }
+
+    /// Re-draw the kernel from `init`; the bias stays zero.
+    pub fn with_init(mut self, init: Init) -> Self {
+        self.weight.set_data(init.tensor(self.weight.shape_vec()));
+        self
+    }
+
+    fn reset_weight(&mut self) {
+        // Each output pixel of a stride-1 transpose sums `in / groups · kH · kW`
+        // products, the same fan-in as the matching conv.
+        let (kh, kw) = self.kernel_size;
+        let k = (2.0 / (self.in_channels / self.groups * kh * kw) as f64).sqrt();
+        self.weight.set_data(init::uniform(vec![self.in_channels, self.out_channels / self.groups, kh, kw], -k, k));
     }
 
-    pub fn parameters(&self) -> Vec<Variable> {
+    /// Forward pass without recording on the graph.
+    pub fn forward_tensor(&self, input: &Tensor<T>) -> Tensor<T> {
+        no_grad(|| Layer::forward(self, &Variable::input(input.clone())).data)
+    }
+}
+
+impl<T: Float> Layer<T> for ConvTranspose2D<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let options = Conv2dOptions::new()
+            .with_stride(self.stride)
+            .with_padding(self.padding)
+            .with_dilation(self.dilation)
+            .with_groups(self.groups)
+            .with_output_padding(self.output_padding);
+        input.conv_transpose2d(&self.weight, Some(&self.bias), &options)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
         vec![self.weight.clone(), self.bias.clone()]
     }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; weight, bias)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; weight, bias)
+    }
+
+    fn kind(&self) -> LayerKind {
+        LayerKind::ConvTranspose2D {
+            kernel_size: self.kernel_size,
+            stride: self.stride,
+            padding: self.padding,
+            output_padding: self.output_padding,
+            dilation: self.dilation,
+            groups: self.groups,
+        }
+    }
 }
 
 /// Max Pooling 1D.
@@ -160,17 +380,17 @@ impl MaxPool1D {
         MaxPool1D { kernel_size, stride: kernel_size }
     }
 
-    pub fn forward_tensor(&self, input: &Tensor<f64>) -> Tensor<f64> {
+    pub fn forward_tensor<T: Float>(&self, input: &Tensor<T>) -> Tensor<T> {
         let shape = input.shape_vec();
         let (batch, channels, length) = (shape[0], shape[1], shape[2]);
         let out_len = (length - self.kernel_size) / self.stride + 1;
 
-        let mut output = vec![f64::NEG_INFINITY; batch * channels * out_len];
+        let mut output = vec![T::NEG_INFINITY; batch * channels * out_len];
 
         for b in 0..batch {
             for c in 0..channels {
                 for ol in 0..out_len {
-                    let mut max_val = f64::NEG_INFINITY;
+                    let mut max_val = T::NEG_INFINITY;
                     for k in 0..self.kernel_size {
                         let val = input.get(&[b, c, ol * self.stride + k]).unwrap();
                         if val > max_val { max_val = val; }
@@ -184,33 +404,63 @@ impl MaxPool1D {
     }
 }
 
-/// Max Pooling 2D.
+impl<T: Float> Layer<T> for MaxPool1D {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let shape = input.shape_vec();
+        let out = input
+            .reshape(vec![shape[0], shape[1], 1, shape[2]])
+            .max_pool2d((1, self.kernel_size), (1, self.stride), (0, 0));
+        let out_len = out.shape_vec()[3];
+        out.reshape(vec![shape[0], shape[1], out_len])
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+/// Max Pooling 2D. As a `Layer`, each output's gradient goes to the first
+/// maximum of its window. Padded positions are skipped, never selected.
 pub struct MaxPool2D {
     pub kernel_size: usize,
     pub stride: usize,
+    pub padding: usize,
 }
 
 impl MaxPool2D {
     pub fn new(kernel_size: usize) -> Self {
-        MaxPool2D { kernel_size, stride: kernel_size }
+        MaxPool2D { kernel_size, stride: kernel_size, padding: 0 }
     }
 
-    pub fn forward_tensor(&self, input: &Tensor<f64>) -> Tensor<f64> {
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn forward_tensor<T: Float>(&self, input: &Tensor<T>) -> Tensor<T> {
         let shape = input.shape_vec();
         let (batch, channels, h, w) = (shape[0], shape[1], shape[2], shape[3]);
-        let out_h = (h - self.kernel_size) / self.stride + 1;
-        let out_w = (w - self.kernel_size) / self.stride + 1;
+        let p = self.padding;
+        let out_h = (h + 2 * p - self.kernel_size) / self.stride + 1;
+        let out_w = (w + 2 * p - self.kernel_size) / self.stride + 1;
 
-        let mut output = vec![f64::NEG_INFINITY; batch * channels * out_h * out_w];
+        let mut output = vec![T::NEG_INFINITY; batch * channels * out_h * out_w];
 
         for b in 0..batch {
             for c in 0..channels {
                 for oh in 0..out_h {
                     for ow in 0..out_w {
-                        let mut max_val = f64::NEG_INFINITY;
+                        let mut max_val = T::NEG_INFINITY;
                         for kh in 0..self.kernel_size {
                             for kw in 0..self.kernel_size {
-                                let val = input.get(&[b, c, oh * self.stride + kh, ow * self.stride + kw]).unwrap();
+                                let (y, x) = (oh * self.stride + kh, ow * self.stride + kw);
+                                if y < p || x < p || y >= h + p || x >= w + p {
+                                    continue;
+                                }
+                                let val = input.get(&[b, c, y - p, x - p]).unwrap();
                                 if val > max_val { max_val = val; }
                             }
                         }
@@ -225,6 +475,15 @@ impl MaxPool2D {
     }
 }
 
+impl<T: Float> Layer<T> for MaxPool2D {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let (k, s, p) = (self.kernel_size, self.stride, self.padding);
+        input.max_pool2d((k, k), (s, s), (p, p))
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
 /// Average Pooling 2D.
 pub struct AvgPool2D {
     pub kernel_size: usize,
@@ -236,20 +495,20 @@ impl AvgPool2D {
         AvgPool2D { kernel_size, stride: kernel_size }
     }
 
-    pub fn forward_tensor(&self, input: &Tensor<f64>) -> Tensor<f64> {
+    pub fn forward_tensor<T: Float>(&self, input: &Tensor<T>) -> Tensor<T> {
         let shape = input.shape_vec();
         let (batch, channels, h, w) = (shape[0], shape[1], shape[2], shape[3]);
         let out_h = (h - self.kernel_size) / self.stride + 1;
         let out_w = (w - self.kernel_size) / self.stride + 1;
-        let pool_area = (self.kernel_size * self.kernel_size) as f64;
+        let pool_area = T::from_usize(self.kernel_size * self.kernel_size);
 
-        let mut output = vec![0.0f64; batch * channels * out_h * out_w];
+        let mut output = vec![T::ZERO; batch * channels * out_h * out_w];
 
         for b in 0..batch {
             for c in 0..channels {
                 for oh in 0..out_h {
                     for ow in 0..out_w {
-                        let mut sum = 0.0;
+                        let mut sum = T::ZERO;
                         for kh in 0..self.kernel_size {
                             for kw in 0..self.kernel_size {
                                 sum += input.get(&[b, c, oh * self.stride + kh, ow * self.stride + kw]).unwrap();
@@ -266,6 +525,36 @@ impl AvgPool2D {
     }
 }
 
+impl<T: Float> Layer<T> for AvgPool2D {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let (k, s) = (self.kernel_size, self.stride);
+        input.avg_pool2d((k, k), (s, s))
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+/// Global average pooling: `[batch, channels, h, w]` to `[batch, channels]`,
+/// the mean of each feature map, for any input size.
+pub struct GlobalAvgPool2D;
+
+impl GlobalAvgPool2D {
+    pub fn new() -> Self { GlobalAvgPool2D }
+}
+
+impl<T: Float> Layer<T> for GlobalAvgPool2D {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let shape = input.shape_vec();
+        input.reshape(vec![shape[0], shape[1], shape[2] * shape[3]]).mean_axis(2)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+impl Default for GlobalAvgPool2D {
+    fn default() -> Self { Self::new() }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -298,6 +587,40 @@ mod tests {
         assert_eq!(out.shape_vec(), vec![1, 1, 4, 4]); // same size!
     }
 
+    #[test]
+    fn test_conv2d_padding_matches_explicit_pad() {
+        let input: Tensor<f64> = Tensor::rand(vec![2, 2, 5, 5], Some(3));
+        let padded_conv = Conv2D::new(2, 3, 3, 2, 1);
+        let mut plain_conv = Conv2D::new(2, 3, 3, 2, 0);
+        plain_conv.weight = padded_conv.weight.clone();
+
+        let explicit = input.pad(&[(0, 0), (0, 0), (1, 1), (1, 1)], 0.0).unwrap();
+        let a = padded_conv.forward_tensor(&input);
+        let b = plain_conv.forward_tensor(&explicit);
+        assert_eq!(a.shape_vec(), vec![2, 3, 3, 3]);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_depthwise_dilated_and_transposed_layers() {
+        let input: Tensor<f64> = Tensor::rand(vec![1, 3, 9, 8], Some(11));
+        // A depthwise conv convolves each channel with its own kernel.
+        let depthwise = Conv2D::rectangular(3, 3, (3, 2), (2, 1), (1, 0)).with_dilation((2, 2)).with_groups(3);
+        assert_eq!(depthwise.weight.shape_vec(), vec![3, 1, 3, 2]);
+        let out = depthwise.forward_tensor(&input);
+        assert_eq!(out.shape_vec(), vec![1, 3, 4, 6]);
+        let mut single = Conv2D::rectangular(1, 1, (3, 2), (2, 1), (1, 0)).with_dilation((2, 2));
+        single.weight.set_data(depthwise.weight.data.index_select(0, &[1]).unwrap());
+        let channel = single.forward_tensor(&input.index_select(1, &[1]).unwrap());
+        assert!(channel.data().iter().zip(out.index_select(1, &[1]).unwrap().data()).all(|(a, b)| (a - b).abs() < 1e-12));
+
+        // A stride-2 transpose undoes the downsampling of a stride-2 conv.
+        let down = Conv2D::new(3, 4, 3, 2, 1);
+        let up = ConvTranspose2D::new(4, 3, 3, 2, 1).with_output_padding((0, 1));
+        let restored = up.forward_tensor(&down.forward_tensor(&input));
+        assert_eq!(restored.shape_vec(), input.shape_vec());
+    }
+
     #[test]
     fn test_maxpool2d() {
         let data: Vec<f64> = (0..16).map(|i| i as f64).collect();
@@ -311,4 +634,46 @@ mod tests {
         assert_eq!(out.data()[2], 13.0);
         assert_eq!(out.data()[3], 15.0);
     }
+
+    #[test]
+    fn test_layers_match_forward_tensor_and_train() {
+        use crate::layers::{FlattenLayer, Linear, ReLULayer};
+        use crate::sequential::Sequential;
+
+        let image: Tensor<f64> = Tensor::rand(vec![2, 2, 6, 6], Some(4));
+        let x = Variable::input(image.clone());
+        let conv = Conv2D::new(2, 3, 3, 1, 1);
+        assert!(conv.forward(&x).data.data().iter().zip(conv.forward_tensor(&image).data()).all(|(a, b)| (a - b).abs() < 1e-12));
+        assert_eq!(MaxPool2D::new(2).forward(&x).data, MaxPool2D::new(2).forward_tensor(&image));
+        let avg = AvgPool2D::new(3).forward(&x).data;
+        assert!(avg.data().iter().zip(AvgPool2D::new(3).forward_tensor(&image).data()).all(|(a, b)| (a - b).abs() < 1e-12));
+        let signal: Tensor<f64> = Tensor::rand(vec![2, 2, 7], Some(5));
+        let conv1 = Conv1D::new(2, 3, 3, 2);
+        let out = conv1.forward(&Variable::input(signal.clone())).data;
+        assert!(out.data().iter().zip(conv1.forward_tensor(&signal).data()).all(|(a, b)| (a - b).abs() < 1e-12));
+        assert_eq!(MaxPool1D::new(2).forward(&Variable::input(signal.clone())).data, MaxPool1D::new(2).forward_tensor(&signal));
+
+        // A small CNN fits a fixed target by gradient descent.
+        let mut model = Sequential::new()
+            .add(Box::new(Conv2D::new(2, 4, 3, 1, 1)))
+            .add(Box::new(ReLULayer::new()))
+            .add(Box::new(MaxPool2D::new(2)))
+            .add(Box::new(FlattenLayer::new()))
+            .add(Box::new(Linear::new(36, 1)));
+        let target = Tensor::new(vec![1.0, -1.0], vec![2, 1]).unwrap();
+        let loss = |m: &Sequential<f64>| {
+            let out = m.forward(&x);
+            out.sub(&out.constant(target.clone())).pow(2.0).mean()
+        };
+        let first = loss(&model).data.item().unwrap();
+        for _ in 0..20 {
+            let l = loss(&model);
+            let grads = oxidize_ml_autodiff::backward::backward(&l);
+            for (_, p) in model.named_parameters_mut() {
+                let step = p.data.sub(&grads[&p.node_id].mul_scalar(0.05)).unwrap();
+                p.set_data(step);
+            }
+        }
+        assert!(loss(&model).data.item().unwrap() < first * 0.5);
+    }
 }
@@ -0,0 +1,151 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::Variable;
+use crate::layers::Layer;
+use crate::init::{self, Init};
+use crate::nlp::tokenizer::Vocab;
+use crate::state_dict::named_fields;
+
+/// Token embedding: a trainable `[num_embeddings, embedding_dim]` lookup
+/// table.
+///
+/// The input holds integer token ids stored as floats, of any shape; the
+/// output has one row per id, `[numel, embedding_dim]`. The lookup is
+/// recorded as a row gather, whose backward scatter-adds the output
+/// gradient into the rows that were used.
+pub struct Embedding<T: Float = f64> {
+    pub num_embeddings: usize,
+    pub embedding_dim: usize,
+    pub weight: Variable<T>,
+}
+
+impl<T: Float> Embedding<T> {
+    /// Create an embedding table initialized from a standard normal.
+    pub fn new(num_embeddings: usize, embedding_dim: usize) -> Self {
+        Embedding {
+            num_embeddings,
+            embedding_dim,
+            weight: Variable::param(init::normal(vec![num_embeddings, embedding_dim], 0.0, 1.0)),
+        }
+    }
+
+    /// Wrap a `[num_embeddings, embedding_dim]` table; with `freeze` it is
+    /// not trained.
+    pub fn from_pretrained(weight: Tensor<T>, freeze: bool) -> Self {
+        let shape = weight.shape_vec();
+        assert_eq!(shape.len(), 2, "Embedding::from_pretrained: expected a 2-D table, got {:?}", shape);
+        let weight = Variable::param(weight);
+        weight.set_requires_grad(!freeze);
+        Embedding { num_embeddings: shape[0], embedding_dim: shape[1], weight }
+    }
+
+    /// Read word vectors in the GloVe or word2vec text format (one
+    /// `word v1 … vd` line per word; word2vec's `count dim` header line is
+    /// skipped). Row `i` of the table is the vector of `vocab.token(i)`.
+    pub fn from_word_vectors<P: AsRef<Path>>(path: P, freeze: bool) -> io::Result<(Self, Vocab)> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let text = fs::read_to_string(path)?;
+        let mut vocab = Vocab::new();
+        let mut data = Vec::new();
+        let mut dim = None;
+        for (n, line) in text.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let Some(word) = fields.next() else { continue };
+            let values: Vec<f64> = fields
+                .map(|v| v.parse().map_err(|_| invalid(format!("line {}: bad value '{}'", n + 1, v))))
+                .collect::<io::Result<_>>()?;
+            if n == 0 && values.len() == 1 && word.parse::<usize>().is_ok() {
+                continue;
+            }
+            match dim {
+                None => dim = Some(values.len()),
+                Some(d) if d != values.len() => {
+                    return Err(invalid(format!("line {}: {} values, expected {}", n + 1, values.len(), d)))
+                }
+                Some(_) => {}
+            }
+            if vocab.contains(word) {
+                return Err(invalid(format!("line {}: duplicate word '{}'", n + 1, word)));
+            }
+            vocab.add(word);
+            data.extend(values.into_iter().map(T::from_f64));
+        }
+        let dim = dim.ok_or_else(|| invalid("no word vectors".to_string()))?;
+        let table = Tensor::new(data, vec![vocab.len(), dim]).map_err(|e| invalid(e.to_string()))?;
+        Ok((Self::from_pretrained(table, freeze), vocab))
+    }
+
+    /// Re-draw the table from `init`.
+    pub fn with_init(mut self, init: Init) -> Self {
+        self.weight.set_data(init.tensor(vec![self.num_embeddings, self.embedding_dim]));
+        self
+    }
+}
+
+impl<T: Float> Layer<T> for Embedding<T> {
+    fn forward(&self, ids: &Variable<T>) -> Variable<T> {
+        let flat = ids.data.reshape(vec![ids.numel()]).unwrap();
+        if let Some(&bad) = flat.data().iter().find(|v| v.to_f64().round() as usize >= self.num_embeddings || v.to_f64() < 0.0) {
+            panic!("token id {} out of range for {} embeddings", bad, self.num_embeddings);
+        }
+        let ids: Vec<usize> = flat.data().iter().map(|v| v.to_f64().round() as usize).collect();
+        self.weight.index_select(0, &ids)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        vec![self.weight.clone()]
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; weight)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::Tensor;
+    use oxidize_ml_autodiff::backward::backward;
+
+    #[test]
+    fn test_embedding_lookup_and_gradient() {
+        let emb = Embedding::new(5, 3);
+        let ids = Variable::input(Tensor::new(vec![4.0, 1.0, 4.0], vec![3]).unwrap());
+        let out = emb.forward(&ids);
+        assert_eq!(out.shape_vec(), vec![3, 3]);
+        assert_eq!(&out.data.data()[..3], &emb.weight.data.data()[12..15]);
+
+        let grads = backward(&out.sum());
+        let g = grads[&emb.weight.node_id].data();
+        // Row 4 was looked up twice, row 1 once, the rest never.
+        assert_eq!(&g[12..15], &[2.0, 2.0, 2.0]);
+        assert_eq!(&g[3..6], &[1.0, 1.0, 1.0]);
+        assert!(g[..3].iter().chain(&g[6..12]).all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn test_from_pretrained_and_word_vectors() {
+        let path = std::env::temp_dir().join(format!("oxidize_ml_{}_vectors.txt", std::process::id()));
+        fs::write(&path, "3 2\nthe 0.5 -1\ncat 2 3\nsat 0 1.5\n").unwrap();
+        let (emb, vocab) = Embedding::<f64>::from_word_vectors(&path, true).unwrap();
+        assert_eq!((emb.num_embeddings, emb.embedding_dim), (3, 2));
+        assert_eq!(vocab.id("cat"), Some(1));
+        assert!(!emb.weight.requires_grad());
+        let ids = Variable::input(Tensor::new(vec![1.0, 0.0], vec![2]).unwrap());
+        assert_eq!(emb.forward(&ids).data.data(), &[2.0, 3.0, 0.5, -1.0]);
+
+        fs::write(&path, "the 0.5 -1\ncat 2\n").unwrap();
+        assert!(Embedding::<f64>::from_word_vectors(&path, false).is_err());
+        fs::remove_file(&path).unwrap();
+
+        let trainable = Embedding::from_pretrained(Tensor::<f64>::ones(vec![4, 2]), false);
+        assert!(trainable.weight.requires_grad());
+    }
+}
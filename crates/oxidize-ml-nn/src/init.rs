@@ -0,0 +1,174 @@
+//! Weight initialization schemes.
+//!
+//! Every random initializer draws from the global RNG (`RngHandle::global`),
+//! so two layers get different weights and `set_global_seed` makes a model's
+//! initialization reproducible.
+//!
+//! Fans follow this crate's weight layouts: a 2-D weight is `[in, out]`
+//! (`y = xW`), a convolution kernel `[out_channels, in_channels, k…]`.
+
+use oxidize_ml_core::{Float, RngHandle, Tensor};
+use rand::Rng;
+
+/// An initialization scheme, for the layers' `with_init` builders.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Init {
+    Constant(f64),
+    Uniform { low: f64, high: f64 },
+    Normal { mean: f64, std: f64 },
+    XavierUniform { gain: f64 },
+    XavierNormal { gain: f64 },
+    /// He initialization, for ReLU-like activations with `gain = √2`.
+    KaimingUniform { gain: f64 },
+    KaimingNormal { gain: f64 },
+    Orthogonal { gain: f64 },
+}
+
+impl Init {
+    /// A tensor of `shape` drawn from this scheme.
+    pub fn tensor<T: Float>(&self, shape: Vec<usize>) -> Tensor<T> {
+        match *self {
+            Init::Constant(value) => constant(shape, value),
+            Init::Uniform { low, high } => uniform(shape, low, high),
+            Init::Normal { mean, std } => normal(shape, mean, std),
+            Init::XavierUniform { gain } => xavier_uniform(shape, gain),
+            Init::XavierNormal { gain } => xavier_normal(shape, gain),
+            Init::KaimingUniform { gain } => kaiming_uniform(shape, gain),
+            Init::KaimingNormal { gain } => kaiming_normal(shape, gain),
+            Init::Orthogonal { gain } => orthogonal(shape, gain),
+        }
+    }
+}
+
+/// `(fan_in, fan_out)` of a weight of `shape`.
+pub fn fans(shape: &[usize]) -> (usize, usize) {
+    match shape {
+        [] => (1, 1),
+        [n] => (*n, *n),
+        [fan_in, fan_out] => (*fan_in, *fan_out),
+        [out_ch, in_ch, kernel @ ..] => {
+            let receptive: usize = kernel.iter().product();
+            (in_ch * receptive, out_ch * receptive)
+        }
+    }
+}
+
+pub fn constant<T: Float>(shape: Vec<usize>, value: f64) -> Tensor<T> {
+    Tensor::full(shape, T::from_f64(value))
+}
+
+/// Uniform on `[low, high)`.
+pub fn uniform<T: Float>(shape: Vec<usize>, low: f64, high: f64) -> Tensor<T> {
+    Tensor::rand(shape, None)
+        .mul_scalar(T::from_f64(high - low))
+        .add_scalar(T::from_f64(low))
+}
+
+pub fn normal<T: Float>(shape: Vec<usize>, mean: f64, std: f64) -> Tensor<T> {
+    Tensor::randn(shape, None)
+        .mul_scalar(T::from_f64(std))
+        .add_scalar(T::from_f64(mean))
+}
+
+/// Glorot uniform: `U(-a, a)` with `a = gain·√(6 / (fan_in + fan_out))`.
+pub fn xavier_uniform<T: Float>(shape: Vec<usize>, gain: f64) -> Tensor<T> {
+    let (fan_in, fan_out) = fans(&shape);
+    let a = gain * (6.0 / (fan_in + fan_out) as f64).sqrt();
+    uniform(shape, -a, a)
+}
+
+/// Glorot normal: `N(0, σ²)` with `σ = gain·√(2 / (fan_in + fan_out))`.
+pub fn xavier_normal<T: Float>(shape: Vec<usize>, gain: f64) -> Tensor<T> {
+    let (fan_in, fan_out) = fans(&shape);
+    let std = gain * (2.0 / (fan_in + fan_out) as f64).sqrt();
+    normal(shape, 0.0, std)
+}
+
+/// He uniform: `U(-a, a)` with `a = gain·√(3 / fan_in)`.
+pub fn kaiming_uniform<T: Float>(shape: Vec<usize>, gain: f64) -> Tensor<T> {
+    let (fan_in, _) = fans(&shape);
+    let a = gain * (3.0 / fan_in as f64).sqrt();
+    uniform(shape, -a, a)
+}
+
+/// He normal: `N(0, σ²)` with `σ = gain / √fan_in`.
+pub fn kaiming_normal<T: Float>(shape: Vec<usize>, gain: f64) -> Tensor<T> {
+    let (fan_in, _) = fans(&shape);
+    normal(shape, 0.0, gain / (fan_in as f64).sqrt())
+}
+
+/// A (semi-)orthogonal matrix scaled by `gain`: the first dimension against
+/// the rest flattened, with orthonormal rows or columns, whichever are
+/// fewer.
+///
+/// Built by Gram-Schmidt on a Gaussian matrix, which makes it uniformly
+/// (Haar) distributed.
+pub fn orthogonal<T: Float>(shape: Vec<usize>, gain: f64) -> Tensor<T> {
+    assert!(shape.len() >= 2, "orthogonal init needs at least 2 dimensions");
+    let rows = shape[0];
+    let cols: usize = shape[1..].iter().product();
+    // Orthonormalize the shorter side as vectors of the longer length.
+    let (count, len) = if rows <= cols { (rows, cols) } else { (cols, rows) };
+    let mut rng = RngHandle::global();
+    let mut vectors: Vec<Vec<f64>> = Vec::with_capacity(count);
+    while vectors.len() < count {
+        let mut v: Vec<f64> = (0..len).map(|_| std_normal(&mut rng)).collect();
+        for q in &vectors {
+            let dot: f64 = v.iter().zip(q).map(|(a, b)| a * b).sum();
+            v.iter_mut().zip(q).for_each(|(a, b)| *a -= dot * b);
+        }
+        let norm = v.iter().map(|a| a * a).sum::<f64>().sqrt();
+        // A draw (numerically) in the span of the others is redrawn.
+        if norm > 1e-8 {
+            vectors.push(v.into_iter().map(|a| a / norm).collect());
+        }
+    }
+
+    let mut data = vec![T::ZERO; rows * cols];
+    for (i, v) in vectors.iter().enumerate() {
+        for (j, &a) in v.iter().enumerate() {
+            let (r, c) = if rows <= cols { (i, j) } else { (j, i) };
+            data[r * cols + c] = T::from_f64(gain * a);
+        }
+    }
+    Tensor::new(data, shape).expect("orthogonal init")
+}
+
+fn std_normal(rng: &mut RngHandle) -> f64 {
+    let u1: f64 = rng.gen::<f64>().max(1e-10);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::Linear;
+
+    #[test]
+    fn test_layers_get_distinct_weights() {
+        // Other tests draw from the global RNG concurrently, so only
+        // distinctness (not replay after `set_global_seed`) is checked here.
+        let (a, b) = (Linear::<f64>::new(3, 4), Linear::<f64>::new(3, 4));
+        assert_ne!(a.weight.data.data(), b.weight.data.data());
+
+        let lin = Linear::<f64>::new(3, 4).with_init(Init::Constant(0.5));
+        assert!(lin.weight.data.data().iter().all(|&w| w == 0.5));
+    }
+
+    #[test]
+    fn test_orthogonal_has_orthonormal_rows() {
+        let w: Tensor<f64> = orthogonal(vec![3, 5], 2.0);
+        let d = w.data();
+        for i in 0..3 {
+            for j in 0..3 {
+                let dot: f64 = (0..5).map(|k| d[i * 5 + k] * d[j * 5 + k]).sum();
+                let expected = if i == j { 4.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-10);
+            }
+        }
+        let k: Tensor<f64> = kaiming_uniform(vec![8, 2, 3, 3], 2f64.sqrt());
+        let bound = (6.0 / 18.0f64).sqrt();
+        assert!(k.data().iter().all(|v| v.abs() <= bound));
+    }
+}
@@ -1,29 +1,152 @@
-use oxidize_ml_core::Tensor;
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
 use oxidize_ml_autodiff::Variable;
+use crate::init::{self, Init};
+use crate::state_dict::{named_fields, LoadReport, StateDict};
 
-/// Trait for a neural network layer.
-pub trait Layer {
+/// Trait for a neural network layer over element type `T` (`f64` by default).
+pub trait Layer<T: Float = f64> {
     /// Forward pass.
-    fn forward(&self, input: &Variable) -> Variable;
+    fn forward(&self, input: &Variable<T>) -> Variable<T>;
     /// Return all trainable parameters.
-    fn parameters(&self) -> Vec<Variable>;
+    fn parameters(&self) -> Vec<Variable<T>>;
+    /// What this layer computes, for exporters.
+    fn kind(&self) -> LayerKind {
+        LayerKind::Other
+    }
+
+    /// The parameters keyed by dotted path (`attention.w_q.weight`), in the
+    /// order of `parameters`. Defaults to their positions as names.
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        self.parameters().into_iter().enumerate().map(|(i, p)| (i.to_string(), p)).collect()
+    }
+
+    /// Mutable access to the same parameters, for `load_state_dict`. Layers
+    /// that don't override it cannot be loaded.
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        Vec::new()
+    }
+
+    /// Non-trainable state saved alongside the parameters, such as
+    /// BatchNorm's running statistics, keyed like `named_parameters`.
+    fn named_buffers(&self) -> Vec<(String, Tensor<T>)> {
+        Vec::new()
+    }
+
+    /// Overwrite the buffer `name`; false if this layer has none by that name.
+    fn set_buffer(&mut self, _name: &str, _value: Tensor<T>) -> bool {
+        false
+    }
+
+    /// Snapshot of every parameter and buffer tensor keyed by its path.
+    fn state_dict(&self) -> StateDict<T> {
+        let params = self.named_parameters().into_iter().map(|(name, p)| (name, p.data));
+        params.chain(self.named_buffers()).collect()
+    }
+
+    /// Copy tensors from `state` into the parameters with matching names,
+    /// in place (node ids, and so optimizers, stay valid).
+    ///
+    /// With `strict`, any missing or unexpected key is an error and nothing
+    /// is loaded; otherwise the matching keys are loaded. A shape mismatch
+    /// on a matching key is always an error.
+    fn load_state_dict(&mut self, state: &StateDict<T>, strict: bool) -> TensorResult<LoadReport> {
+        let buffers: Vec<(String, Vec<usize>)> =
+            self.named_buffers().into_iter().map(|(name, b)| (name, b.shape_vec())).collect();
+        let mut params = self.named_parameters_mut();
+        let expected = params.iter().map(|(name, p)| (name.clone(), p.shape_vec())).chain(buffers.iter().cloned());
+        let mut report = LoadReport::default();
+        for (name, shape) in expected {
+            match state.get(&name) {
+                None => report.missing_keys.push(name),
+                Some(t) if t.shape_vec() != shape => {
+                    return Err(TensorError::ShapeMismatch { expected: shape, got: t.shape_vec() })
+                }
+                Some(_) => {}
+            }
+        }
+        report.unexpected_keys = state
+            .keys()
+            .filter(|k| !params.iter().any(|(name, _)| name == *k) && !buffers.iter().any(|(name, _)| name == *k))
+            .cloned()
+            .collect();
+        if strict && !report.is_exact() {
+            return Err(TensorError::InvalidOperation(format!(
+                "error(s) in loading state dict: {}",
+                report
+            )));
+        }
+        for (name, param) in params.iter_mut() {
+            if let Some(t) = state.get(name) {
+                param.set_data(t.clone());
+            }
+        }
+        drop(params);
+        for (name, _) in &buffers {
+            if let Some(t) = state.get(name) {
+                self.set_buffer(name, t.clone());
+            }
+        }
+        Ok(report)
+    }
+
+    /// Switch between training (`true`) and eval behaviour, for layers such
+    /// as Dropout and BatchNorm; containers forward it to their children.
+    fn set_training(&mut self, _training: bool) {}
+
+    /// Freeze (`false`) or unfreeze every parameter of this layer.
+    fn set_requires_grad(&self, requires_grad: bool) {
+        for p in self.parameters() {
+            p.set_requires_grad(requires_grad);
+        }
+    }
+}
+
+/// Structural description of a layer. Weights come from `Layer::parameters`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerKind {
+    /// `parameters()` = `[weight [in, out], bias [1, out]]`.
+    Linear,
+    ReLU,
+    Sigmoid,
+    Tanh,
+    LeakyReLU { alpha: f64 },
+    /// Identity at inference time.
+    Dropout,
+    Flatten,
+    /// `parameters()` = `[weight [out, in / groups, kH, kW], bias [out]]`.
+    Conv2D {
+        kernel_size: (usize, usize),
+        stride: (usize, usize),
+        padding: (usize, usize),
+        dilation: (usize, usize),
+        groups: usize,
+    },
+    /// `parameters()` = `[weight [in, out / groups, kH, kW], bias [out]]`.
+    ConvTranspose2D {
+        kernel_size: (usize, usize),
+        stride: (usize, usize),
+        padding: (usize, usize),
+        output_padding: (usize, usize),
+        dilation: (usize, usize),
+        groups: usize,
+    },
+    /// A layer with no exportable description.
+    Other,
 }
 
 /// Fully connected (dense) layer: y = xW + b.
-pub struct Linear {
-    pub weight: Variable,
-    pub bias: Variable,
+pub struct Linear<T: Float = f64> {
+    pub weight: Variable<T>,
+    pub bias: Variable<T>,
     pub in_features: usize,
     pub out_features: usize,
 }
 
-impl Linear {
+impl<T: Float> Linear<T> {
     /// Create a new linear layer with Xavier-uniform initialization.
     pub fn new(in_features: usize, out_features: usize) -> Self {
-        let scale = (6.0 / (in_features + out_features) as f64).sqrt();
-        let w_data = Tensor::rand(vec![in_features, out_features], Some(42))
-            .mul_scalar(2.0 * scale)
-            .add_scalar(-scale);
+        let w_data = init::xavier_uniform(vec![in_features, out_features], 1.0);
         let b_data = Tensor::zeros(vec![1, out_features]);
 
         Linear {
@@ -33,17 +156,33 @@ impl Linear {
             out_features,
         }
     }
+
+    /// Re-draw the weight from `init`; the bias stays zero.
+    pub fn with_init(mut self, init: Init) -> Self {
+        self.weight.set_data(init.tensor(vec![self.in_features, self.out_features]));
+        self
+    }
 }
 
-impl Layer for Linear {
-    fn forward(&self, input: &Variable) -> Variable {
+impl<T: Float> Layer<T> for Linear<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
         let xw = input.matmul(&self.weight);
         xw.add(&self.bias)
     }
 
-    fn parameters(&self) -> Vec<Variable> {
+    fn parameters(&self) -> Vec<Variable<T>> {
         vec![self.weight.clone(), self.bias.clone()]
     }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; weight, bias)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; weight, bias)
+    }
+
+    fn kind(&self) -> LayerKind { LayerKind::Linear }
 }
 
 /// ReLU activation layer.
@@ -53,9 +192,10 @@ impl ReLULayer {
     pub fn new() -> Self { ReLULayer }
 }
 
-impl Layer for ReLULayer {
-    fn forward(&self, input: &Variable) -> Variable { input.relu() }
-    fn parameters(&self) -> Vec<Variable> { vec![] }
+impl<T: Float> Layer<T> for ReLULayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> { input.relu() }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+    fn kind(&self) -> LayerKind { LayerKind::ReLU }
 }
 
 impl Default for ReLULayer {
@@ -69,9 +209,10 @@ impl SigmoidLayer {
     pub fn new() -> Self { SigmoidLayer }
 }
 
-impl Layer for SigmoidLayer {
-    fn forward(&self, input: &Variable) -> Variable { input.sigmoid() }
-    fn parameters(&self) -> Vec<Variable> { vec![] }
+impl<T: Float> Layer<T> for SigmoidLayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> { input.sigmoid() }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+    fn kind(&self) -> LayerKind { LayerKind::Sigmoid }
 }
 
 impl Default for SigmoidLayer {
@@ -85,9 +226,10 @@ impl TanhLayer {
     pub fn new() -> Self { TanhLayer }
 }
 
-impl Layer for TanhLayer {
-    fn forward(&self, input: &Variable) -> Variable { input.tanh_act() }
-    fn parameters(&self) -> Vec<Variable> { vec![] }
+impl<T: Float> Layer<T> for TanhLayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> { input.tanh_act() }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+    fn kind(&self) -> LayerKind { LayerKind::Tanh }
 }
 
 impl Default for TanhLayer {
@@ -107,8 +249,8 @@ impl LeakyReLULayer {
     }
 }
 
-impl Layer for LeakyReLULayer {
-    fn forward(&self, input: &Variable) -> Variable {
+impl<T: Float> Layer<T> for LeakyReLULayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
         // Approximate: use relu with a small leak
         // leaky_relu(x) = relu(x) + alpha * (x - relu(x))
         // = (1-alpha)*relu(x) + alpha*x
@@ -118,13 +260,107 @@ impl Layer for LeakyReLULayer {
         scaled_relu.add(&scaled_input)
     }
 
-    fn parameters(&self) -> Vec<Variable> { vec![] }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+    fn kind(&self) -> LayerKind { LayerKind::LeakyReLU { alpha: self.alpha } }
 }
 
 impl Default for LeakyReLULayer {
     fn default() -> Self { Self::new(0.01) }
 }
 
+/// GELU activation, exact (x·Φ(x)) or with the tanh approximation.
+pub struct GELULayer {
+    pub approximate: bool,
+}
+
+impl GELULayer {
+    pub fn new() -> Self { GELULayer { approximate: false } }
+    /// Use the tanh approximation instead of the exact error function.
+    pub fn with_approximate(mut self, approximate: bool) -> Self {
+        self.approximate = approximate;
+        self
+    }
+}
+
+impl<T: Float> Layer<T> for GELULayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        if self.approximate { input.gelu_tanh() } else { input.gelu() }
+    }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+impl Default for GELULayer {
+    fn default() -> Self { Self::new() }
+}
+
+/// SiLU (Swish) activation: x·σ(x).
+pub struct SiLULayer;
+
+impl SiLULayer {
+    pub fn new() -> Self { SiLULayer }
+}
+
+impl<T: Float> Layer<T> for SiLULayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> { input.silu() }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+impl Default for SiLULayer {
+    fn default() -> Self { Self::new() }
+}
+
+/// ELU activation: x for x > 0, alpha·(exp(x) - 1) otherwise.
+pub struct ELULayer {
+    pub alpha: f64,
+}
+
+impl ELULayer {
+    pub fn new(alpha: f64) -> Self { ELULayer { alpha } }
+}
+
+impl<T: Float> Layer<T> for ELULayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> { input.elu(self.alpha) }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+impl Default for ELULayer {
+    fn default() -> Self { Self::new(1.0) }
+}
+
+/// Softplus activation: ln(1 + exp(beta·x)) / beta, a smooth ReLU.
+pub struct SoftplusLayer {
+    pub beta: f64,
+}
+
+impl SoftplusLayer {
+    pub fn new(beta: f64) -> Self { SoftplusLayer { beta } }
+}
+
+impl<T: Float> Layer<T> for SoftplusLayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> { input.softplus(self.beta) }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+impl Default for SoftplusLayer {
+    fn default() -> Self { Self::new(1.0) }
+}
+
+/// Mish activation: x·tanh(softplus(x)).
+pub struct MishLayer;
+
+impl MishLayer {
+    pub fn new() -> Self { MishLayer }
+}
+
+impl<T: Float> Layer<T> for MishLayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> { input.mish() }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+}
+
+impl Default for MishLayer {
+    fn default() -> Self { Self::new() }
+}
+
 /// Dropout layer — randomly zeros elements during training.
 /// During inference (default), acts as identity.
 pub struct Dropout {
@@ -140,24 +376,26 @@ impl Dropout {
     pub fn eval(&mut self) { self.training = false; }
 }
 
-impl Layer for Dropout {
-    fn forward(&self, input: &Variable) -> Variable {
+impl<T: Float> Layer<T> for Dropout {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
         if !self.training {
             return input.clone();
         }
         // Create a mask: each element has prob (1-p) of being kept
-        let mask_data = Tensor::<f64>::rand(input.data.shape_vec(), None);
-        let threshold = self.p;
-        let scale = 1.0 / (1.0 - self.p);
-        let mask: Vec<f64> = mask_data.data().iter()
-            .map(|&v| if v > threshold { scale } else { 0.0 })
+        let mask_data = Tensor::<T>::rand(input.data.shape_vec(), None);
+        let threshold = T::from_f64(self.p);
+        let scale = T::from_f64(1.0 / (1.0 - self.p));
+        let mask: Vec<T> = mask_data.data().iter()
+            .map(|&v| if v > threshold { scale } else { T::ZERO })
             .collect();
         let mask_tensor = Tensor::new(mask, input.data.shape_vec()).unwrap();
-        let mask_var = Variable::input(mask_tensor);
+        let mask_var = input.constant(mask_tensor);
         input.mul(&mask_var)
     }
 
-    fn parameters(&self) -> Vec<Variable> { vec![] }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+    fn kind(&self) -> LayerKind { LayerKind::Dropout }
+    fn set_training(&mut self, training: bool) { self.training = training; }
 }
 
 impl Default for Dropout {
@@ -171,75 +409,53 @@ impl FlattenLayer {
     pub fn new() -> Self { FlattenLayer }
 }
 
-impl Layer for FlattenLayer {
-    fn forward(&self, input: &Variable) -> Variable {
+impl<T: Float> Layer<T> for FlattenLayer {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
         let shape = input.data.shape_vec();
         if shape.len() <= 2 {
             return input.clone();
         }
         let batch = shape[0];
         let features: usize = shape[1..].iter().product();
-        let new_data = input.data.reshape(vec![batch, features]).unwrap();
-        Variable::input(new_data)
+        input.reshape(vec![batch, features])
     }
 
-    fn parameters(&self) -> Vec<Variable> { vec![] }
+    fn parameters(&self) -> Vec<Variable<T>> { vec![] }
+    fn kind(&self) -> LayerKind { LayerKind::Flatten }
 }
 
 impl Default for FlattenLayer {
     fn default() -> Self { Self::new() }
 }
 
-/// Batch Normalization layer.
-///
-/// Normalizes across the batch dimension: y = (x - μ) / √(σ² + ε) * γ + β
-pub struct BatchNorm {
-    pub num_features: usize,
-    pub eps: f64,
-    pub gamma: Variable,
-    pub beta: Variable,
-    pub training: bool,
-}
-
-impl BatchNorm {
-    pub fn new(num_features: usize) -> Self {
-        BatchNorm {
-            num_features,
-            eps: 1e-5,
-            gamma: Variable::param(Tensor::ones(vec![1, num_features])),
-            beta: Variable::param(Tensor::zeros(vec![1, num_features])),
-            training: true,
-        }
-    }
-    pub fn train(&mut self) { self.training = true; }
-    pub fn eval(&mut self) { self.training = false; }
-}
-
-impl Layer for BatchNorm {
-    fn forward(&self, input: &Variable) -> Variable {
-        let x = &input.data;
-        let batch_size = x.shape().dim(0).unwrap_or(1);
-
-        if batch_size > 1 {
-            // Compute batch mean and variance
-            let mean = x.mean_axis(0).unwrap();
-            let centered = x.sub(&mean).unwrap_or_else(|_| x.clone());
-
-            let var_tensor = centered.mul(&centered).unwrap().mean_axis(0).unwrap();
-            let std = var_tensor.add_scalar(self.eps).sqrt();
-            let normalized = centered.div(&std).unwrap_or_else(|_| centered.clone());
-
-            // Scale and shift
-            let scaled = normalized.mul(&self.gamma.data).unwrap();
-            let output = scaled.add(&self.beta.data).unwrap();
-
-            Variable::input(output)
-        } else {
-            input.clone()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequential::Sequential;
+
+    #[test]
+    fn test_modern_activations_train_in_a_model() {
+        let x = Tensor::new(vec![-2.0, -0.5, 0.0, 1.5], vec![1, 4]).unwrap();
+        let gelu: Tensor<f64> = x.gelu();
+        // GELU(0) = 0 and GELU(x) → x for large x; the tanh form is close.
+        assert_eq!(gelu.data()[2], 0.0);
+        for (a, b) in gelu.data().iter().zip(x.gelu_tanh().data()) {
+            assert!((a - b).abs() < 1e-3);
         }
-    }
-
-    fn parameters(&self) -> Vec<Variable> {
-        vec![self.gamma.clone(), self.beta.clone()]
+        assert!((x.elu(1.0).data()[0] - ((-2.0f64).exp() - 1.0)).abs() < 1e-12);
+        assert!((x.softplus(1.0).data()[2] - 2f64.ln()).abs() < 1e-12);
+
+        let model = Sequential::<f64>::new()
+            .add(Box::new(Linear::new(4, 4)))
+            .add(Box::new(GELULayer::new().with_approximate(true)))
+            .add(Box::new(Linear::new(4, 4)))
+            .add(Box::new(SiLULayer::new()))
+            .add(Box::new(ELULayer::default()))
+            .add(Box::new(SoftplusLayer::default()))
+            .add(Box::new(MishLayer::new()));
+        let params = model.parameters();
+        model.forward(&params[0].constant(x)).sum().backward();
+        assert!(params.iter().all(|p| p.grad().is_some_and(|g| g.data().iter().all(|v| v.is_finite()))));
+        assert!(params[0].grad().unwrap().data().iter().any(|v| v.abs() > 0.0));
     }
 }
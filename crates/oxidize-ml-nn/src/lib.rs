@@ -1,7 +1,28 @@
 pub mod layers;
+pub mod norm;
 pub mod sequential;
 pub mod recurrent;
+pub mod sequence;
+pub mod conv;
+pub mod attention;
+pub mod transformer;
+pub mod embedding;
+pub mod state_dict;
+pub mod init;
+pub mod models;
+pub mod nlp;
+pub mod quantize;
 
 pub use layers::*;
+pub use norm::*;
 pub use sequential::*;
 pub use recurrent::*;
+pub use sequence::*;
+pub use conv::*;
+pub use attention::*;
+pub use transformer::*;
+pub use embedding::*;
+pub use state_dict::*;
+pub use init::Init;
+pub use models::{BasicBlock, BertConfig, BertEncoder, ResNet};
+pub use quantize::*;
@@ -0,0 +1,482 @@
+//! Prebuilt architectures composed of this crate's layers: computer-vision
+//! networks and a BERT-style text encoder.
+//!
+//! Parameters and buffers of the vision models are named like torchvision's
+//! modules (`layer2.0.downsample.1.running_mean`, `fc.weight`), with this
+//! crate's layouts: BatchNorm's scale and shift are `gamma`/`beta` and a Linear
+//! weight is `[in, out]`. Weights saved from these models with
+//! `oxidize_ml_io::save_safetensors` load back through
+//! `oxidize_ml_io::load_safetensors_into` or `Layer::load_state_dict`.
+
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::Variable;
+use crate::attention::{Activation, TransformerBlock};
+use crate::conv::{AvgPool2D, Conv2D, GlobalAvgPool2D, MaxPool2D};
+use crate::embedding::Embedding;
+use crate::layers::{FlattenLayer, Layer, Linear, ReLULayer, TanhLayer};
+use crate::norm::{BatchNorm, LayerNorm};
+use crate::sequential::Sequential;
+use crate::state_dict::NamedParameters;
+use crate::transformer::TransformerEncoder;
+
+/// A fixed set of named sublayers, from which the `Layer` bookkeeping
+/// (names, buffers, training mode) is derived.
+trait Children<T: Float> {
+    fn children(&self) -> Vec<(&'static str, &dyn Layer<T>)>;
+    fn children_mut(&mut self) -> Vec<(&'static str, &mut dyn Layer<T>)>;
+
+    fn child_parameters(&self) -> Vec<(String, Variable<T>)> {
+        self.children()
+            .into_iter()
+            .fold(NamedParameters::new(), |acc, (name, layer)| acc.register_module(name, layer.named_parameters()))
+            .into_vec()
+    }
+
+    fn child_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        self.children_mut()
+            .into_iter()
+            .fold(NamedParameters::new(), |acc, (name, layer)| acc.register_module(name, layer.named_parameters_mut()))
+            .into_vec()
+    }
+
+    fn child_buffers(&self) -> Vec<(String, Tensor<T>)> {
+        self.children()
+            .into_iter()
+            .fold(NamedParameters::new(), |acc, (name, layer)| acc.register_module(name, layer.named_buffers()))
+            .into_vec()
+    }
+
+    fn set_child_buffer(&mut self, name: &str, value: Tensor<T>) -> bool {
+        let Some((child, rest)) = name.split_once('.') else { return false };
+        match self.children_mut().into_iter().find(|(n, _)| *n == child) {
+            Some((_, layer)) => layer.set_buffer(rest, value),
+            None => false,
+        }
+    }
+}
+
+/// Implements `Layer` for a `Children` type given its forward pass.
+macro_rules! composite_layer {
+    ($ty:ident, |$self_:ident, $x:ident| $forward:expr) => {
+        impl<T: Float> Layer<T> for $ty<T> {
+            fn forward(&$self_, $x: &Variable<T>) -> Variable<T> {
+                $forward
+            }
+
+            fn parameters(&self) -> Vec<Variable<T>> {
+                self.child_parameters().into_iter().map(|(_, p)| p).collect()
+            }
+
+            fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+                self.child_parameters()
+            }
+
+            fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+                self.child_parameters_mut()
+            }
+
+            fn named_buffers(&self) -> Vec<(String, Tensor<T>)> {
+                self.child_buffers()
+            }
+
+            fn set_buffer(&mut self, name: &str, value: Tensor<T>) -> bool {
+                self.set_child_buffer(name, value)
+            }
+
+            fn set_training(&mut self, training: bool) {
+                for (_, layer) in self.children_mut() {
+                    layer.set_training(training);
+                }
+            }
+        }
+    };
+}
+
+/// ResNet basic block: two 3×3 conv + BatchNorm stages and a residual
+/// connection, `relu(bn2(conv2(relu(bn1(conv1(x))))) + shortcut(x))`.
+///
+/// The shortcut is the identity, or a strided 1×1 conv + BatchNorm
+/// (`downsample`) when the block changes the stride or channel count.
+pub struct BasicBlock<T: Float = f64> {
+    pub conv1: Conv2D<T>,
+    pub bn1: BatchNorm<T>,
+    pub conv2: Conv2D<T>,
+    pub bn2: BatchNorm<T>,
+    pub downsample: Option<Sequential<T>>,
+}
+
+impl<T: Float> BasicBlock<T> {
+    pub fn new(in_channels: usize, out_channels: usize, stride: usize) -> Self {
+        let downsample = (stride != 1 || in_channels != out_channels).then(|| {
+            Sequential::new()
+                .add(Box::new(Conv2D::new(in_channels, out_channels, 1, stride, 0)))
+                .add(Box::new(BatchNorm::new(out_channels)))
+        });
+        BasicBlock {
+            conv1: Conv2D::new(in_channels, out_channels, 3, stride, 1),
+            bn1: BatchNorm::new(out_channels),
+            conv2: Conv2D::new(out_channels, out_channels, 3, 1, 1),
+            bn2: BatchNorm::new(out_channels),
+            downsample,
+        }
+    }
+}
+
+impl<T: Float> Children<T> for BasicBlock<T> {
+    fn children(&self) -> Vec<(&'static str, &dyn Layer<T>)> {
+        let mut children: Vec<(&'static str, &dyn Layer<T>)> =
+            vec![("conv1", &self.conv1), ("bn1", &self.bn1), ("conv2", &self.conv2), ("bn2", &self.bn2)];
+        if let Some(downsample) = &self.downsample {
+            children.push(("downsample", downsample));
+        }
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<(&'static str, &mut dyn Layer<T>)> {
+        let mut children: Vec<(&'static str, &mut dyn Layer<T>)> =
+            vec![("conv1", &mut self.conv1), ("bn1", &mut self.bn1), ("conv2", &mut self.conv2), ("bn2", &mut self.bn2)];
+        if let Some(downsample) = &mut self.downsample {
+            children.push(("downsample", downsample));
+        }
+        children
+    }
+}
+
+composite_layer!(BasicBlock, |self, x| {
+    let out = self.bn1.forward(&self.conv1.forward(x)).relu();
+    let out = self.bn2.forward(&self.conv2.forward(&out));
+    let shortcut = match &self.downsample {
+        Some(downsample) => downsample.forward(x),
+        None => x.clone(),
+    };
+    out.add(&shortcut).relu()
+});
+
+/// ResNet over `[batch, 3, h, w]` images: a 7×7 stride-2 stem and 3×3
+/// stride-2 max pool, four stages of basic blocks (64, 128, 256 and 512
+/// channels, each after the first halving the resolution), global average
+/// pooling and a linear classifier giving `[batch, num_classes]` logits.
+///
+/// Any input of at least 32×32 works; 224×224 is the usual ImageNet size.
+pub struct ResNet<T: Float = f64> {
+    pub conv1: Conv2D<T>,
+    pub bn1: BatchNorm<T>,
+    pub maxpool: MaxPool2D,
+    pub layer1: Sequential<T>,
+    pub layer2: Sequential<T>,
+    pub layer3: Sequential<T>,
+    pub layer4: Sequential<T>,
+    pub avgpool: GlobalAvgPool2D,
+    pub fc: Linear<T>,
+}
+
+impl<T: Float> ResNet<T> {
+    /// `blocks[i]` basic blocks in stage `i`.
+    pub fn new(blocks: [usize; 4], num_classes: usize) -> Self {
+        let stage = |in_channels: usize, out_channels: usize, count: usize, stride: usize| {
+            (0..count).fold(Sequential::new(), |seq, i| {
+                let block = if i == 0 {
+                    BasicBlock::new(in_channels, out_channels, stride)
+                } else {
+                    BasicBlock::new(out_channels, out_channels, 1)
+                };
+                seq.add(Box::new(block))
+            })
+        };
+        ResNet {
+            conv1: Conv2D::new(3, 64, 7, 2, 3),
+            bn1: BatchNorm::new(64),
+            maxpool: MaxPool2D::new(3).with_stride(2).with_padding(1),
+            layer1: stage(64, 64, blocks[0], 1),
+            layer2: stage(64, 128, blocks[1], 2),
+            layer3: stage(128, 256, blocks[2], 2),
+            layer4: stage(256, 512, blocks[3], 2),
+            avgpool: GlobalAvgPool2D::new(),
+            fc: Linear::new(512, num_classes),
+        }
+    }
+}
+
+impl<T: Float> Children<T> for ResNet<T> {
+    fn children(&self) -> Vec<(&'static str, &dyn Layer<T>)> {
+        vec![
+            ("conv1", &self.conv1),
+            ("bn1", &self.bn1),
+            ("layer1", &self.layer1),
+            ("layer2", &self.layer2),
+            ("layer3", &self.layer3),
+            ("layer4", &self.layer4),
+            ("fc", &self.fc),
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<(&'static str, &mut dyn Layer<T>)> {
+        vec![
+            ("conv1", &mut self.conv1),
+            ("bn1", &mut self.bn1),
+            ("layer1", &mut self.layer1),
+            ("layer2", &mut self.layer2),
+            ("layer3", &mut self.layer3),
+            ("layer4", &mut self.layer4),
+            ("fc", &mut self.fc),
+        ]
+    }
+}
+
+composite_layer!(ResNet, |self, x| {
+    let x = self.bn1.forward(&self.conv1.forward(x)).relu();
+    let x = Layer::<T>::forward(&self.maxpool, &x);
+    let x = [&self.layer1, &self.layer2, &self.layer3, &self.layer4]
+        .iter()
+        .fold(x, |x, stage| stage.forward(&x));
+    self.fc.forward(&Layer::<T>::forward(&self.avgpool, &x))
+});
+
+/// ResNet-18: two basic blocks per stage.
+pub fn resnet18<T: Float>(num_classes: usize) -> ResNet<T> {
+    ResNet::new([2, 2, 2, 2], num_classes)
+}
+
+/// ResNet-34: 3, 4, 6 and 3 basic blocks per stage.
+pub fn resnet34<T: Float>(num_classes: usize) -> ResNet<T> {
+    ResNet::new([3, 4, 6, 3], num_classes)
+}
+
+/// LeNet-5 for `[batch, 1, 28, 28]` digits: two 5×5 conv + tanh + 2×2
+/// average-pool stages (the first padded to keep 28×28), then 120-, 84- and
+/// 10-unit linear layers.
+pub fn lenet5<T: Float>() -> Sequential<T> {
+    Sequential::new()
+        .add(Box::new(Conv2D::new(1, 6, 5, 1, 2)))
+        .add(Box::new(TanhLayer::new()))
+        .add(Box::new(AvgPool2D::new(2)))
+        .add(Box::new(Conv2D::new(6, 16, 5, 1, 0)))
+        .add(Box::new(TanhLayer::new()))
+        .add(Box::new(AvgPool2D::new(2)))
+        .add(Box::new(FlattenLayer::new()))
+        .add(Box::new(Linear::new(16 * 5 * 5, 120)))
+        .add(Box::new(TanhLayer::new()))
+        .add(Box::new(Linear::new(120, 84)))
+        .add(Box::new(TanhLayer::new()))
+        .add(Box::new(Linear::new(84, 10)))
+}
+
+/// A VGG-style network: each entry of `stages` lists the channel counts of
+/// its 3×3 conv + BatchNorm + ReLU layers and is followed by a 2×2 max
+/// pool; global average pooling and a linear layer give the logits.
+pub fn vgg<T: Float>(stages: &[&[usize]], in_channels: usize, num_classes: usize) -> Sequential<T> {
+    let mut model = Sequential::new();
+    let mut channels = in_channels;
+    for stage in stages {
+        for &out in stage.iter() {
+            model = model
+                .add(Box::new(Conv2D::new(channels, out, 3, 1, 1)))
+                .add(Box::new(BatchNorm::new(out)))
+                .add(Box::new(ReLULayer::new()));
+            channels = out;
+        }
+        model = model.add(Box::new(MaxPool2D::new(2)));
+    }
+    model.add(Box::new(GlobalAvgPool2D::new())).add(Box::new(Linear::new(channels, num_classes)))
+}
+
+/// A small VGG for 32×32 RGB images (CIFAR-sized): stages of 32, 64 and
+/// 128 channels with two convs each.
+pub fn vgg_small<T: Float>(num_classes: usize) -> Sequential<T> {
+    vgg(&[&[32, 32], &[64, 64], &[128, 128]], 3, num_classes)
+}
+
+/// Sizes of a `BertEncoder`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BertConfig {
+    pub vocab_size: usize,
+    pub d_model: usize,
+    pub num_heads: usize,
+    pub d_ff: usize,
+    pub num_layers: usize,
+    /// Longest sequence the position table covers.
+    pub max_positions: usize,
+    pub type_vocab_size: usize,
+    pub layer_norm_eps: f64,
+}
+
+impl BertConfig {
+    /// BERT's defaults for the rest: 512 positions, 2 token types and a
+    /// LayerNorm epsilon of 1e-12.
+    pub fn new(vocab_size: usize, d_model: usize, num_heads: usize, d_ff: usize, num_layers: usize) -> Self {
+        BertConfig {
+            vocab_size,
+            d_model,
+            num_heads,
+            d_ff,
+            num_layers,
+            max_positions: 512,
+            type_vocab_size: 2,
+            layer_norm_eps: 1e-12,
+        }
+    }
+
+    pub fn with_max_positions(mut self, max_positions: usize) -> Self {
+        self.max_positions = max_positions;
+        self
+    }
+
+    pub fn with_type_vocab_size(mut self, type_vocab_size: usize) -> Self {
+        self.type_vocab_size = type_vocab_size;
+        self
+    }
+
+    pub fn with_layer_norm_eps(mut self, eps: f64) -> Self {
+        self.layer_norm_eps = eps;
+        self
+    }
+}
+
+/// BERT-style encoder over one sequence of token ids (stored as floats, as
+/// for `Embedding`): token, learned position and token-type embeddings,
+/// summed and normalized, then a stack of post-norm GELU
+/// `TransformerBlock`s. Every token has type 0.
+///
+/// The output is `[seq_len, d_model]`, one hidden state per token; pool it
+/// (e.g. the `[CLS]` row or the masked mean) for a sentence embedding.
+pub struct BertEncoder<T: Float = f64> {
+    pub token_embedding: Embedding<T>,
+    pub position_embedding: Embedding<T>,
+    pub token_type_embedding: Embedding<T>,
+    pub embedding_norm: LayerNorm<T>,
+    pub encoder: TransformerEncoder<T>,
+}
+
+impl<T: Float> BertEncoder<T> {
+    pub fn new(config: &BertConfig) -> Self {
+        let norm = || {
+            let mut norm = LayerNorm::new(config.d_model);
+            norm.eps = config.layer_norm_eps;
+            norm
+        };
+        let layers = (0..config.num_layers)
+            .map(|_| {
+                let mut block = TransformerBlock::new(config.d_model, config.num_heads, config.d_ff)
+                    .with_activation(Activation::Gelu);
+                block.norm1 = norm();
+                block.norm2 = norm();
+                block
+            })
+            .collect();
+        BertEncoder {
+            token_embedding: Embedding::new(config.vocab_size, config.d_model),
+            position_embedding: Embedding::new(config.max_positions, config.d_model),
+            token_type_embedding: Embedding::new(config.type_vocab_size, config.d_model),
+            embedding_norm: norm(),
+            encoder: TransformerEncoder { layers },
+        }
+    }
+
+    /// Encode `[seq_len]` token ids with self-attention restricted by
+    /// `mask` (e.g. a `padding_mask`).
+    pub fn forward_masked(&self, ids: &Variable<T>, mask: Option<&Tensor<T>>) -> Variable<T> {
+        let tokens = self.token_embedding.forward(ids);
+        let len = tokens.shape_vec()[0];
+        assert!(
+            len <= self.position_embedding.num_embeddings,
+            "sequence of {} exceeds {} positions",
+            len,
+            self.position_embedding.num_embeddings
+        );
+        let x = tokens
+            .add(&self.position_embedding.weight.slice(0, 0, len))
+            .add(&self.token_type_embedding.weight.slice(0, 0, 1));
+        self.encoder.forward_masked(&self.embedding_norm.forward(&x), mask)
+    }
+}
+
+impl<T: Float> Children<T> for BertEncoder<T> {
+    fn children(&self) -> Vec<(&'static str, &dyn Layer<T>)> {
+        vec![
+            ("token_embedding", &self.token_embedding),
+            ("position_embedding", &self.position_embedding),
+            ("token_type_embedding", &self.token_type_embedding),
+            ("embedding_norm", &self.embedding_norm),
+            ("encoder", &self.encoder),
+        ]
+    }
+
+    fn children_mut(&mut self) -> Vec<(&'static str, &mut dyn Layer<T>)> {
+        vec![
+            ("token_embedding", &mut self.token_embedding),
+            ("position_embedding", &mut self.position_embedding),
+            ("token_type_embedding", &mut self.token_type_embedding),
+            ("embedding_norm", &mut self.embedding_norm),
+            ("encoder", &mut self.encoder),
+        ]
+    }
+}
+
+composite_layer!(BertEncoder, |self, x| self.forward_masked(x, None));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_autodiff::backward::backward;
+
+    #[test]
+    fn test_resnet_shapes_names_and_gradients() {
+        // One block per stage keeps the test fast; resnet18 only repeats them.
+        let mut model: ResNet = ResNet::new([1, 1, 1, 1], 10);
+        let state = model.state_dict();
+        assert!(state.contains_key("layer2.0.downsample.1.running_mean"));
+        assert!(state.contains_key("layer4.0.conv2.weight"));
+        assert!(!state.contains_key("layer1.0.downsample.0.weight"));
+        assert_eq!(state["fc.weight"].shape_vec(), vec![512, 10]);
+
+        let x = Variable::input(Tensor::rand(vec![2, 3, 32, 32], Some(1)));
+        let out = model.forward(&x);
+        assert_eq!(out.shape_vec(), vec![2, 10]);
+        let grads = backward(&out.pow(2.0).mean());
+        assert!(grads[&model.conv1.weight.node_id].data().iter().any(|v| v.abs() > 0.0));
+
+        // Eval mode uses (and leaves) the running statistics.
+        model.set_training(false);
+        let before = model.named_buffers();
+        let a = model.forward(&x.constant(Tensor::rand(vec![1, 3, 32, 32], Some(2))));
+        assert_eq!(a.shape_vec(), vec![1, 10]);
+        assert_eq!(before, model.named_buffers());
+    }
+
+    #[test]
+    fn test_lenet_and_vgg_run_and_reload() {
+        let lenet: Sequential = lenet5();
+        let digits = Variable::input(Tensor::rand(vec![3, 1, 28, 28], Some(3)));
+        assert_eq!(lenet.forward(&digits).shape_vec(), vec![3, 10]);
+
+        let vgg: Sequential = vgg_small(5);
+        let images = Variable::input(Tensor::rand(vec![2, 3, 32, 32], Some(4)));
+        let out = vgg.forward(&images);
+        assert_eq!(out.shape_vec(), vec![2, 5]);
+
+        let mut copy: Sequential = vgg_small(5);
+        copy.load_state_dict(&vgg.state_dict(), true).unwrap();
+        let (a, b) = (out.data, copy.forward(&images).data);
+        assert!(a.data().iter().zip(b.data()).all(|(x, y)| (x - y).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_bert_encoder_names_and_padding() {
+        let config = BertConfig::new(20, 8, 2, 16, 2).with_max_positions(6);
+        let model: BertEncoder = BertEncoder::new(&config);
+        let state = model.state_dict();
+        assert_eq!(state["position_embedding.weight"].shape_vec(), vec![6, 8]);
+        assert!(state.contains_key("encoder.layers.1.attention.w_v.bias"));
+        assert_eq!(model.encoder.layers[0].norm2.eps, 1e-12);
+
+        // Masking out trailing padding leaves the real tokens' states as
+        // if the padding were absent.
+        let ids = |v: Vec<f64>| Variable::input(Tensor::new(v.clone(), vec![v.len()]).unwrap());
+        let short = model.forward(&ids(vec![2.0, 7.0, 5.0]));
+        assert_eq!(short.shape_vec(), vec![3, 8]);
+        let mask = crate::attention::padding_mask(5, &[true, true, true, false, false]);
+        let padded = model.forward_masked(&ids(vec![2.0, 7.0, 5.0, 0.0, 0.0]), Some(&mask));
+        let (a, b) = (short.data.data(), &padded.data.data()[..24]);
+        assert!(a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-6));
+    }
+}
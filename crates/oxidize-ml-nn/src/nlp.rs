@@ -0,0 +1,3 @@
+//! Text processing for the sequence models.
+
+pub mod tokenizer;
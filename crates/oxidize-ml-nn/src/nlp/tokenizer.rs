@@ -0,0 +1,676 @@
+//! Tokenizers turning text into the token ids `Embedding` consumes.
+//!
+//! Every tokenizer first splits text with a `PreTokenizer` (a regex over
+//! optionally lowercased text), then maps the pieces to vocabulary entries:
+//! whole words (`WordTokenizer`), byte-pair-encoding merges (`Bpe`) or
+//! greedy longest-match subwords (`WordPiece`). Vocabularies start with the
+//! special tokens `[PAD]`, `[UNK]`, `[CLS]` and `[SEP]` (ids 0–3) and are
+//! saved as one token per line, the `vocab.txt` format of BERT.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use oxidize_ml_core::{Float, Tensor};
+use regex::Regex;
+
+use crate::attention::padding_mask;
+
+pub const PAD: &str = "[PAD]";
+pub const UNK: &str = "[UNK]";
+pub const CLS: &str = "[CLS]";
+pub const SEP: &str = "[SEP]";
+const SPECIAL_TOKENS: [&str; 4] = [PAD, UNK, CLS, SEP];
+
+/// Marks the last symbol of a word in `Bpe`, so merges do not cross words.
+const END_OF_WORD: &str = "</w>";
+/// Prefixes a `WordPiece` subword that continues a word.
+const CONTINUATION: &str = "##";
+
+/// A bidirectional token ↔ id mapping.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Vocab {
+    tokens: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl Vocab {
+    /// An empty vocabulary.
+    pub fn new() -> Self {
+        Vocab::default()
+    }
+
+    /// A vocabulary holding just the special tokens, `[PAD]` as id 0.
+    pub fn with_special_tokens() -> Self {
+        let mut vocab = Vocab::new();
+        for token in SPECIAL_TOKENS {
+            vocab.add(token);
+        }
+        vocab
+    }
+
+    /// The id of `token`, adding it if new.
+    pub fn add(&mut self, token: &str) -> usize {
+        if let Some(&id) = self.ids.get(token) {
+            return id;
+        }
+        self.tokens.push(token.to_string());
+        self.ids.insert(token.to_string(), self.tokens.len() - 1);
+        self.tokens.len() - 1
+    }
+
+    pub fn id(&self, token: &str) -> Option<usize> {
+        self.ids.get(token).copied()
+    }
+
+    /// The id of `token`, or of `[UNK]` when it is not in the vocabulary.
+    pub fn id_or_unk(&self, token: &str) -> usize {
+        self.id(token).unwrap_or_else(|| self.id(UNK).expect("vocabulary has no [UNK] token"))
+    }
+
+    pub fn token(&self, id: usize) -> Option<&str> {
+        self.tokens.get(id).map(String::as_str)
+    }
+
+    pub fn contains(&self, token: &str) -> bool {
+        self.ids.contains_key(token)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// The tokens in id order.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Write one token per line, line `i` holding id `i`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut text = self.tokens.join("\n");
+        text.push('\n');
+        fs::write(path, text)
+    }
+
+    /// Read a vocabulary written by `save` (or a BERT `vocab.txt`).
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut vocab = Vocab::new();
+        for line in fs::read_to_string(path)?.lines() {
+            vocab.add(line);
+        }
+        Ok(vocab)
+    }
+}
+
+/// The first step of every tokenizer: optional lowercasing, then the
+/// non-overlapping matches of `pattern`.
+#[derive(Debug, Clone)]
+pub struct PreTokenizer {
+    pub pattern: Regex,
+    pub lowercase: bool,
+}
+
+impl PreTokenizer {
+    /// Words and single punctuation marks: `\w+|[^\w\s]`.
+    pub fn new() -> Self {
+        PreTokenizer { pattern: Regex::new(r"\w+|[^\w\s]").expect("default pattern"), lowercase: false }
+    }
+
+    /// Runs of non-whitespace.
+    pub fn whitespace() -> Self {
+        PreTokenizer { pattern: Regex::new(r"\S+").expect("whitespace pattern"), lowercase: false }
+    }
+
+    pub fn with_pattern(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(PreTokenizer { pattern: Regex::new(pattern)?, lowercase: false })
+    }
+
+    pub fn with_lowercase(mut self, lowercase: bool) -> Self {
+        self.lowercase = lowercase;
+        self
+    }
+
+    pub fn split(&self, text: &str) -> Vec<String> {
+        let text = if self.lowercase { text.to_lowercase() } else { text.to_string() };
+        self.pattern.find_iter(&text).map(|m| m.as_str().to_string()).collect()
+    }
+
+    /// Occurrences of each piece in `texts`, sorted by piece so training is
+    /// deterministic.
+    fn word_counts(&self, texts: &[&str]) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for text in texts {
+            for word in self.split(text) {
+                *counts.entry(word).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort();
+        counts
+    }
+}
+
+impl Default for PreTokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options of `Tokenizer::encode_batch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Wrap each sequence as `[CLS] … [SEP]`.
+    pub add_special_tokens: bool,
+    /// Truncate longer sequences (special tokens included).
+    pub max_length: Option<usize>,
+}
+
+impl BatchOptions {
+    pub fn new() -> Self {
+        BatchOptions { add_special_tokens: false, max_length: None }
+    }
+
+    pub fn with_special_tokens(mut self, add: bool) -> Self {
+        self.add_special_tokens = add;
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sequences padded with `[PAD]` to the longest one, and which positions
+/// hold real tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEncoding {
+    pub ids: Vec<Vec<usize>>,
+    pub attention_mask: Vec<Vec<bool>>,
+    /// Unpadded length of each sequence.
+    pub lengths: Vec<usize>,
+}
+
+impl BatchEncoding {
+    /// `[batch, max_len]` ids stored as floats, the input of `Embedding`.
+    pub fn ids_tensor<T: Float>(&self) -> Tensor<T> {
+        let data = self.ids.iter().flatten().map(|&id| T::from_usize(id)).collect();
+        Tensor::new(data, vec![self.ids.len(), self.max_len()]).expect("ids tensor")
+    }
+
+    /// `[batch, max_len]` attention mask of ones (tokens) and zeros (padding).
+    pub fn mask_tensor<T: Float>(&self) -> Tensor<T> {
+        let data = self.attention_mask.iter().flatten().map(|&m| if m { T::ONE } else { T::ZERO }).collect();
+        Tensor::new(data, vec![self.ids.len(), self.max_len()]).expect("mask tensor")
+    }
+
+    /// The `[max_len, max_len]` self-attention mask of sequence `i`, hiding
+    /// its padding from `MultiHeadAttention::attend`.
+    pub fn self_attention_mask<T: Float>(&self, i: usize) -> Tensor<T> {
+        padding_mask(self.max_len(), &self.attention_mask[i])
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.ids.first().map_or(0, Vec::len)
+    }
+}
+
+/// Text to token ids and back.
+pub trait Tokenizer {
+    fn vocab(&self) -> &Vocab;
+
+    /// Split `text` into vocabulary tokens (`[UNK]` for pieces it cannot
+    /// represent).
+    fn tokenize(&self, text: &str) -> Vec<String>;
+
+    /// Text from ids, dropping `[PAD]`, `[CLS]` and `[SEP]`.
+    fn decode(&self, ids: &[usize]) -> String;
+
+    fn encode(&self, text: &str) -> Vec<usize> {
+        self.tokenize(text).iter().map(|t| self.vocab().id_or_unk(t)).collect()
+    }
+
+    /// Encode each text and pad the batch to its longest sequence.
+    fn encode_batch(&self, texts: &[&str], options: &BatchOptions) -> BatchEncoding {
+        let vocab = self.vocab();
+        let mut sequences: Vec<Vec<usize>> = texts
+            .iter()
+            .map(|text| {
+                let mut ids = self.encode(text);
+                if options.add_special_tokens {
+                    ids.insert(0, vocab.id_or_unk(CLS));
+                    ids.push(vocab.id_or_unk(SEP));
+                }
+                if let Some(max) = options.max_length {
+                    if ids.len() > max {
+                        ids.truncate(max);
+                        // Keep the closing [SEP] of a truncated sequence.
+                        if options.add_special_tokens && max > 1 {
+                            ids[max - 1] = vocab.id_or_unk(SEP);
+                        }
+                    }
+                }
+                ids
+            })
+            .collect();
+        let lengths: Vec<usize> = sequences.iter().map(Vec::len).collect();
+        let max_len = lengths.iter().copied().max().unwrap_or(0);
+        let pad = vocab.id_or_unk(PAD);
+        let attention_mask = lengths.iter().map(|&len| (0..max_len).map(|i| i < len).collect()).collect();
+        for ids in &mut sequences {
+            ids.resize(max_len, pad);
+        }
+        BatchEncoding { ids: sequences, attention_mask, lengths }
+    }
+}
+
+fn is_special(token: &str) -> bool {
+    matches!(token, PAD | CLS | SEP)
+}
+
+fn special_vocab(vocab_size: usize) -> Vocab {
+    assert!(vocab_size >= SPECIAL_TOKENS.len(), "vocab_size must leave room for the special tokens");
+    Vocab::with_special_tokens()
+}
+
+/// One token per pre-tokenized word; words outside the vocabulary become
+/// `[UNK]`.
+#[derive(Debug, Clone)]
+pub struct WordTokenizer {
+    pub vocab: Vocab,
+    pub pre_tokenizer: PreTokenizer,
+}
+
+impl WordTokenizer {
+    pub fn new(vocab: Vocab) -> Self {
+        WordTokenizer { vocab, pre_tokenizer: PreTokenizer::new() }
+    }
+
+    pub fn with_pre_tokenizer(mut self, pre_tokenizer: PreTokenizer) -> Self {
+        self.pre_tokenizer = pre_tokenizer;
+        self
+    }
+
+    /// Keep the `vocab_size - 4` most frequent words of `texts` (ties in
+    /// alphabetical order) after the special tokens.
+    pub fn train(texts: &[&str], vocab_size: usize, pre_tokenizer: PreTokenizer) -> Self {
+        let mut vocab = special_vocab(vocab_size);
+        let mut counts = pre_tokenizer.word_counts(texts);
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (word, _) in counts.into_iter().take(vocab_size - vocab.len()) {
+            vocab.add(&word);
+        }
+        WordTokenizer { vocab, pre_tokenizer }
+    }
+}
+
+impl Tokenizer for WordTokenizer {
+    fn vocab(&self) -> &Vocab {
+        &self.vocab
+    }
+
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        self.pre_tokenizer
+            .split(text)
+            .into_iter()
+            .map(|w| if self.vocab.contains(&w) { w } else { UNK.to_string() })
+            .collect()
+    }
+
+    fn decode(&self, ids: &[usize]) -> String {
+        let tokens = ids.iter().filter_map(|&id| self.vocab.token(id)).filter(|t| !is_special(t));
+        tokens.collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Byte-pair encoding (Sennrich et al.): words start as characters, the
+/// last marked with `</w>`, and learned merges of adjacent symbols are
+/// applied in the order they were learned.
+#[derive(Debug, Clone)]
+pub struct Bpe {
+    pub vocab: Vocab,
+    pub merges: Vec<(String, String)>,
+    pub pre_tokenizer: PreTokenizer,
+    ranks: HashMap<(String, String), usize>,
+}
+
+impl Bpe {
+    /// A tokenizer from a vocabulary and its merges, in priority order.
+    pub fn new(vocab: Vocab, merges: Vec<(String, String)>) -> Self {
+        let ranks = merges.iter().cloned().enumerate().map(|(i, m)| (m, i)).collect();
+        Bpe { vocab, merges, pre_tokenizer: PreTokenizer::new(), ranks }
+    }
+
+    pub fn with_pre_tokenizer(mut self, pre_tokenizer: PreTokenizer) -> Self {
+        self.pre_tokenizer = pre_tokenizer;
+        self
+    }
+
+    /// Learn merges from `texts` until the vocabulary holds `vocab_size`
+    /// tokens or no pair occurs twice: each step merges the most frequent
+    /// adjacent pair (ties broken alphabetically).
+    pub fn train(texts: &[&str], vocab_size: usize, pre_tokenizer: PreTokenizer) -> Self {
+        let mut vocab = special_vocab(vocab_size);
+        let mut words: Vec<(Vec<String>, usize)> =
+            pre_tokenizer.word_counts(texts).into_iter().map(|(w, n)| (Self::symbols(&w), n)).collect();
+        for (symbols, _) in &words {
+            for s in symbols {
+                vocab.add(s);
+            }
+        }
+
+        let mut merges = Vec::new();
+        while vocab.len() < vocab_size {
+            let mut pairs: HashMap<(&str, &str), usize> = HashMap::new();
+            for (symbols, count) in &words {
+                for pair in symbols.windows(2) {
+                    *pairs.entry((&pair[0], &pair[1])).or_default() += count;
+                }
+            }
+            let best = pairs
+                .into_iter()
+                .filter(|&(_, n)| n >= 2)
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|((a, b), _)| (a.to_string(), b.to_string()));
+            let Some((a, b)) = best else { break };
+            for (symbols, _) in &mut words {
+                Self::merge(symbols, &a, &b);
+            }
+            vocab.add(&format!("{}{}", a, b));
+            merges.push((a, b));
+        }
+        Bpe::new(vocab, merges).with_pre_tokenizer(pre_tokenizer)
+    }
+
+    fn symbols(word: &str) -> Vec<String> {
+        let mut symbols: Vec<String> = word.chars().map(String::from).collect();
+        if let Some(last) = symbols.last_mut() {
+            last.push_str(END_OF_WORD);
+        }
+        symbols
+    }
+
+    /// Replace every adjacent `a b` in `symbols` with `ab`.
+    fn merge(symbols: &mut Vec<String>, a: &str, b: &str) {
+        let mut i = 0;
+        while i + 1 < symbols.len() {
+            if symbols[i] == a && symbols[i + 1] == b {
+                symbols[i].push_str(b);
+                symbols.remove(i + 1);
+            }
+            i += 1;
+        }
+    }
+
+    fn encode_word(&self, word: &str) -> Vec<String> {
+        let mut symbols = Self::symbols(word);
+        // Apply the earliest-learned merge present until none applies.
+        loop {
+            let best = symbols
+                .windows(2)
+                .filter_map(|p| self.ranks.get(&(p[0].clone(), p[1].clone())).map(|&rank| (rank, p[0].clone(), p[1].clone())))
+                .min();
+            let Some((_, a, b)) = best else { break };
+            Self::merge(&mut symbols, &a, &b);
+        }
+        symbols.into_iter().map(|s| if self.vocab.contains(&s) { s } else { UNK.to_string() }).collect()
+    }
+
+    /// Write `vocab.txt` and `merges.txt` (one `a b` pair per line) into `dir`.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        self.vocab.save(dir.join("vocab.txt"))?;
+        let merges: String = self.merges.iter().map(|(a, b)| format!("{} {}\n", a, b)).collect();
+        fs::write(dir.join("merges.txt"), merges)
+    }
+
+    /// Read a tokenizer written by `save`, with the default pre-tokenizer.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let vocab = Vocab::load(dir.join("vocab.txt"))?;
+        let merges = fs::read_to_string(dir.join("merges.txt"))?
+            .lines()
+            .map(|line| {
+                line.split_once(' ')
+                    .map(|(a, b)| (a.to_string(), b.to_string()))
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("bad merge line '{}'", line)))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Bpe::new(vocab, merges))
+    }
+}
+
+impl Tokenizer for Bpe {
+    fn vocab(&self) -> &Vocab {
+        &self.vocab
+    }
+
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        self.pre_tokenizer.split(text).iter().flat_map(|w| self.encode_word(w)).collect()
+    }
+
+    fn decode(&self, ids: &[usize]) -> String {
+        let text: String = ids
+            .iter()
+            .filter_map(|&id| self.vocab.token(id))
+            .filter(|t| !is_special(t))
+            .map(|t| if t == UNK { format!("{} ", UNK) } else { t.replace(END_OF_WORD, " ") })
+            .collect();
+        text.trim_end().to_string()
+    }
+}
+
+/// WordPiece (as in BERT): each word is split greedily into the longest
+/// vocabulary prefixes, pieces after the first marked with `##`; a word
+/// that cannot be split becomes `[UNK]`.
+#[derive(Debug, Clone)]
+pub struct WordPiece {
+    pub vocab: Vocab,
+    pub pre_tokenizer: PreTokenizer,
+    /// Longer words become `[UNK]` without being split.
+    pub max_word_chars: usize,
+}
+
+impl WordPiece {
+    pub fn new(vocab: Vocab) -> Self {
+        WordPiece { vocab, pre_tokenizer: PreTokenizer::new(), max_word_chars: 100 }
+    }
+
+    pub fn with_pre_tokenizer(mut self, pre_tokenizer: PreTokenizer) -> Self {
+        self.pre_tokenizer = pre_tokenizer;
+        self
+    }
+
+    /// Learn a vocabulary from `texts`: start from the characters (`##`-
+    /// prefixed inside words) and repeatedly merge the adjacent pair with
+    /// the highest `count(ab) / (count(a) · count(b))`, until the
+    /// vocabulary holds `vocab_size` tokens or no pair is left.
+    pub fn train(texts: &[&str], vocab_size: usize, pre_tokenizer: PreTokenizer) -> Self {
+        let mut vocab = special_vocab(vocab_size);
+        let mut words: Vec<(Vec<String>, usize)> = pre_tokenizer
+            .word_counts(texts)
+            .into_iter()
+            .map(|(w, n)| {
+                let pieces = w
+                    .chars()
+                    .enumerate()
+                    .map(|(i, c)| if i == 0 { c.to_string() } else { format!("{}{}", CONTINUATION, c) })
+                    .collect();
+                (pieces, n)
+            })
+            .collect();
+        for (pieces, _) in &words {
+            for p in pieces {
+                vocab.add(p);
+            }
+        }
+
+        while vocab.len() < vocab_size {
+            let mut singles: HashMap<&str, usize> = HashMap::new();
+            let mut pairs: HashMap<(&str, &str), usize> = HashMap::new();
+            for (pieces, count) in &words {
+                for p in pieces {
+                    *singles.entry(p).or_default() += count;
+                }
+                for pair in pieces.windows(2) {
+                    *pairs.entry((&pair[0], &pair[1])).or_default() += count;
+                }
+            }
+            let score = |(a, b): (&str, &str), n: usize| n as f64 / (singles[a] * singles[b]) as f64;
+            let best = pairs
+                .into_iter()
+                .max_by(|x, y| score(x.0, x.1).total_cmp(&score(y.0, y.1)).then_with(|| y.0.cmp(&x.0)))
+                .map(|((a, b), _)| (a.to_string(), b.to_string()));
+            let Some((a, b)) = best else { break };
+            let merged = format!("{}{}", a, b.trim_start_matches(CONTINUATION));
+            for (pieces, _) in &mut words {
+                let mut i = 0;
+                while i + 1 < pieces.len() {
+                    if pieces[i] == a && pieces[i + 1] == b {
+                        pieces[i] = merged.clone();
+                        pieces.remove(i + 1);
+                    }
+                    i += 1;
+                }
+            }
+            vocab.add(&merged);
+        }
+        WordPiece::new(vocab).with_pre_tokenizer(pre_tokenizer)
+    }
+
+    fn encode_word(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() > self.max_word_chars {
+            return vec![UNK.to_string()];
+        }
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let prefix = if start == 0 { "" } else { CONTINUATION };
+            let piece = (start + 1..=chars.len())
+                .rev()
+                .map(|end| format!("{}{}", prefix, chars[start..end].iter().collect::<String>()))
+                .zip((start + 1..=chars.len()).rev())
+                .find(|(piece, _)| self.vocab.contains(piece));
+            match piece {
+                Some((piece, end)) => {
+                    pieces.push(piece);
+                    start = end;
+                }
+                None => return vec![UNK.to_string()],
+            }
+        }
+        pieces
+    }
+
+    /// Write `vocab.txt` into `dir`.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        fs::create_dir_all(dir.as_ref())?;
+        self.vocab.save(dir.as_ref().join("vocab.txt"))
+    }
+
+    /// Read `vocab.txt` from `dir`, with the default pre-tokenizer.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        Ok(WordPiece::new(Vocab::load(dir.as_ref().join("vocab.txt"))?))
+    }
+}
+
+impl Tokenizer for WordPiece {
+    fn vocab(&self) -> &Vocab {
+        &self.vocab
+    }
+
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        self.pre_tokenizer.split(text).iter().flat_map(|w| self.encode_word(w)).collect()
+    }
+
+    fn decode(&self, ids: &[usize]) -> String {
+        let mut text = String::new();
+        for token in ids.iter().filter_map(|&id| self.vocab.token(id)).filter(|t| !is_special(t)) {
+            match token.strip_prefix(CONTINUATION) {
+                Some(rest) => text.push_str(rest),
+                None => {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push_str(token);
+                }
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: [&str; 4] = [
+        "the lower the slower",
+        "the newest and the widest",
+        "low lower lowest",
+        "new newer newest",
+    ];
+
+    #[test]
+    fn test_bpe_and_wordpiece_round_trip() {
+        let bpe = Bpe::train(&CORPUS, 40, PreTokenizer::new());
+        // Training stops once no pair occurs twice, short of 40 tokens.
+        assert!(bpe.vocab.len() < 40);
+        assert_eq!(bpe.merges[0], ("w".to_string(), "e".to_string()));
+        assert_eq!(bpe.vocab.id(PAD), Some(0));
+        // Frequent words collapse to single tokens.
+        assert_eq!(bpe.tokenize("the"), vec!["the</w>"]);
+        let ids = bpe.encode("the newest lower");
+        assert_eq!(bpe.decode(&ids), "the newest lower");
+        assert_eq!(bpe.tokenize("zoo"), vec![UNK, "o", UNK]);
+
+        let wp = WordPiece::train(&CORPUS, 40, PreTokenizer::new().with_lowercase(true));
+        let tokens = wp.tokenize("The lowest");
+        assert!(tokens.iter().skip(1).any(|t| t.starts_with(CONTINUATION)));
+        assert_eq!(wp.decode(&wp.encode("The lowest")), "the lowest");
+        assert_eq!(wp.tokenize("quiz"), vec![UNK]);
+
+        let dir = std::env::temp_dir().join(format!("oxidize_ml_{}_bpe", std::process::id()));
+        bpe.save(&dir).unwrap();
+        let loaded = Bpe::load(&dir).unwrap();
+        assert_eq!(loaded.encode("slower newest"), bpe.encode("slower newest"));
+        wp.save(&dir).unwrap();
+        assert_eq!(WordPiece::load(&dir).unwrap().vocab, wp.vocab);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_batch_encoding_pads_and_masks() {
+        let words = WordTokenizer::train(&CORPUS, 20, PreTokenizer::whitespace());
+        assert_eq!(words.vocab.token(4), Some("the"));
+        let batch = words.encode_batch(&["the new", "the lower the unknown"], &BatchOptions::new().with_special_tokens(true).with_max_length(5));
+        let (cls, sep, unk) = (words.vocab.id_or_unk(CLS), words.vocab.id_or_unk(SEP), words.vocab.id_or_unk(UNK));
+        assert_eq!(batch.lengths, vec![4, 5]);
+        assert_eq!(batch.ids[0], vec![cls, 4, words.vocab.id_or_unk("new"), sep, 0]);
+        assert_eq!(batch.ids[1][4], sep);
+        assert_eq!(batch.ids[1][..4], [cls, 4, words.vocab.id_or_unk("lower"), 4]);
+        assert!(!batch.ids[1].contains(&unk), "truncation drops the unknown word");
+        assert_eq!(batch.attention_mask[0], vec![true, true, true, true, false]);
+
+        let ids: Tensor<f64> = batch.ids_tensor();
+        assert_eq!(ids.shape_vec(), vec![2, 5]);
+        assert_eq!(batch.mask_tensor::<f64>().data()[4], 0.0);
+        let mask: Tensor<f64> = batch.self_attention_mask(0);
+        assert_eq!(mask.shape_vec(), vec![5, 5]);
+        assert_eq!(&mask.data()[..5], &[1.0, 1.0, 1.0, 1.0, 0.0]);
+        assert_eq!(words.decode(&batch.ids[0]), "the new");
+    }
+}
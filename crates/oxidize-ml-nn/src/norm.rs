@@ -0,0 +1,323 @@
+use std::sync::RwLock;
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::Variable;
+use crate::layers::Layer;
+use crate::state_dict::named_fields;
+
+/// `[1, channels, 1, …]` for an input of `ndim` dimensions, so per-channel
+/// tensors broadcast over `[batch, channels, …]`.
+fn channel_shape(ndim: usize, channels: usize) -> Vec<usize> {
+    let mut shape = vec![1; ndim];
+    shape[1] = channels;
+    shape
+}
+
+/// Mean over every axis but 1, giving `[channels]`. Each step averages
+/// equally sized slices, so this is the mean over all of them.
+fn channel_mean<T: Float>(x: &Variable<T>) -> Variable<T> {
+    let mut mean = x.mean_axis(0);
+    while mean.shape_vec().len() > 1 {
+        mean = mean.mean_axis(1);
+    }
+    mean
+}
+
+/// Batch Normalization layer.
+///
+/// Normalizes each channel (axis 1) of `[batch, channels, …]` over the
+/// other axes: y = (x - μ) / √(σ² + ε) * γ + β.
+///
+/// In training mode the batch statistics are used and folded into running
+/// estimates (`momentum` weights the new batch; the variance is unbiased);
+/// in eval mode the running estimates are used instead.
+pub struct BatchNorm<T: Float = f64> {
+    pub num_features: usize,
+    pub eps: f64,
+    pub momentum: f64,
+    pub gamma: Variable<T>,
+    pub beta: Variable<T>,
+    pub training: bool,
+    running_mean: RwLock<Tensor<T>>,
+    running_var: RwLock<Tensor<T>>,
+}
+
+impl<T: Float> BatchNorm<T> {
+    pub fn new(num_features: usize) -> Self {
+        BatchNorm {
+            num_features,
+            eps: 1e-5,
+            momentum: 0.1,
+            gamma: Variable::param(Tensor::ones(vec![1, num_features])),
+            beta: Variable::param(Tensor::zeros(vec![1, num_features])),
+            training: true,
+            running_mean: RwLock::new(Tensor::zeros(vec![num_features])),
+            running_var: RwLock::new(Tensor::ones(vec![num_features])),
+        }
+    }
+    pub fn train(&mut self) { self.training = true; }
+    pub fn eval(&mut self) { self.training = false; }
+
+    pub fn running_mean(&self) -> Tensor<T> {
+        self.running_mean.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn running_var(&self) -> Tensor<T> {
+        self.running_var.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// running = (1 - momentum) · running + momentum · batch
+    fn update_running(&self, running: &RwLock<Tensor<T>>, batch: &Tensor<T>) {
+        let m = T::from_f64(self.momentum);
+        running
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .zip_apply_mut(batch, |r, b| (T::ONE - m) * r + m * b)
+            .expect("batchnorm running update");
+    }
+}
+
+impl<T: Float> Layer<T> for BatchNorm<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let shape = input.shape_vec();
+        assert!(shape.len() >= 2 && shape[1] == self.num_features, "BatchNorm: expected [batch, {}, ...]", self.num_features);
+        let param_shape = channel_shape(shape.len(), self.num_features);
+        let count = input.numel() / self.num_features;
+
+        let normalized = if self.training && count > 1 {
+            // Normalize with the batch statistics, recorded on the graph so
+            // gradients reach the input, gamma and beta
+            let mean = channel_mean(input);
+            let centered = input.sub(&mean.reshape(param_shape.clone()));
+            let var = channel_mean(&centered.mul(&centered));
+            let normalized = centered.div(&var.reshape(param_shape.clone()).add_scalar(self.eps).pow(0.5));
+
+            self.update_running(&self.running_mean, &mean.data);
+            let unbiased = var.data.mul_scalar(T::from_f64(count as f64 / (count - 1) as f64));
+            self.update_running(&self.running_var, &unbiased);
+            normalized
+        } else {
+            let mean = self.running_mean().reshape(param_shape.clone()).expect("running mean shape");
+            let std = self.running_var().add_scalar(T::from_f64(self.eps)).sqrt();
+            let std = std.reshape(param_shape.clone()).expect("running var shape");
+            input.sub(&input.constant(mean)).div(&input.constant(std))
+        };
+
+        // Scale and shift
+        if shape.len() == 2 {
+            normalized.mul(&self.gamma).add(&self.beta)
+        } else {
+            normalized.mul(&self.gamma.reshape(param_shape.clone())).add(&self.beta.reshape(param_shape))
+        }
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        vec![self.gamma.clone(), self.beta.clone()]
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; gamma, beta)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; gamma, beta)
+    }
+
+    fn named_buffers(&self) -> Vec<(String, Tensor<T>)> {
+        vec![("running_mean".to_string(), self.running_mean()), ("running_var".to_string(), self.running_var())]
+    }
+
+    fn set_buffer(&mut self, name: &str, value: Tensor<T>) -> bool {
+        let buffer = match name {
+            "running_mean" => &mut self.running_mean,
+            "running_var" => &mut self.running_var,
+            _ => return false,
+        };
+        *buffer.get_mut().unwrap_or_else(|e| e.into_inner()) = value;
+        true
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
+
+/// Layer Normalization over the last axis, of size `normalized_shape`:
+/// each row is normalized on its own, y = (x - μ) / √(σ² + ε) * γ + β.
+///
+/// Independent of the batch, so it behaves the same in training and eval.
+pub struct LayerNorm<T: Float = f64> {
+    pub normalized_shape: usize,
+    pub eps: f64,
+    pub gamma: Variable<T>,
+    pub beta: Variable<T>,
+}
+
+impl<T: Float> LayerNorm<T> {
+    pub fn new(normalized_shape: usize) -> Self {
+        LayerNorm {
+            normalized_shape,
+            eps: 1e-5,
+            gamma: Variable::param(Tensor::ones(vec![normalized_shape])),
+            beta: Variable::param(Tensor::zeros(vec![normalized_shape])),
+        }
+    }
+}
+
+impl<T: Float> Layer<T> for LayerNorm<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let shape = input.shape_vec();
+        let last = shape.len() - 1;
+        assert_eq!(shape[last], self.normalized_shape, "LayerNorm: last axis must have size {}", self.normalized_shape);
+        let mut kept = shape.clone();
+        kept[last] = 1;
+
+        let centered = input.sub(&input.mean_axis(last).reshape(kept.clone()));
+        let var = centered.mul(&centered).mean_axis(last).reshape(kept);
+        centered.div(&var.add_scalar(self.eps).pow(0.5)).mul(&self.gamma).add(&self.beta)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        vec![self.gamma.clone(), self.beta.clone()]
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; gamma, beta)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; gamma, beta)
+    }
+}
+
+/// Group Normalization: the channels of `[batch, channels, …]` are split
+/// into `num_groups` groups, each normalized per sample over its channels
+/// and spatial positions, then scaled and shifted per channel.
+///
+/// One group is LayerNorm over everything but the batch; one group per
+/// channel is InstanceNorm.
+pub struct GroupNorm<T: Float = f64> {
+    pub num_groups: usize,
+    pub num_channels: usize,
+    pub eps: f64,
+    pub gamma: Variable<T>,
+    pub beta: Variable<T>,
+}
+
+impl<T: Float> GroupNorm<T> {
+    pub fn new(num_groups: usize, num_channels: usize) -> Self {
+        assert!(num_groups > 0 && num_channels.is_multiple_of(num_groups), "num_channels must be divisible by num_groups");
+        GroupNorm {
+            num_groups,
+            num_channels,
+            eps: 1e-5,
+            gamma: Variable::param(Tensor::ones(vec![num_channels])),
+            beta: Variable::param(Tensor::zeros(vec![num_channels])),
+        }
+    }
+}
+
+impl<T: Float> Layer<T> for GroupNorm<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let shape = input.shape_vec();
+        assert!(shape.len() >= 2 && shape[1] == self.num_channels, "GroupNorm: expected [batch, {}, ...]", self.num_channels);
+        let (batch, groups) = (shape[0], self.num_groups);
+        let grouped = input.reshape(vec![batch, groups, input.numel() / (batch * groups)]);
+
+        let centered = grouped.sub(&grouped.mean_axis(2).reshape(vec![batch, groups, 1]));
+        let var = centered.mul(&centered).mean_axis(2).reshape(vec![batch, groups, 1]);
+        let normalized = centered.div(&var.add_scalar(self.eps).pow(0.5)).reshape(shape.clone());
+
+        let param_shape = channel_shape(shape.len(), self.num_channels);
+        normalized.mul(&self.gamma.reshape(param_shape.clone())).add(&self.beta.reshape(param_shape))
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        vec![self.gamma.clone(), self.beta.clone()]
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; gamma, beta)
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; gamma, beta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::Linear;
+    use crate::sequential::Sequential;
+    use oxidize_ml_autodiff::backward::backward;
+
+    #[test]
+    fn test_batchnorm_gradients_reach_gamma_and_beta() {
+        let bn = BatchNorm::new(2);
+        let x = Variable::param(Tensor::new(vec![1.0, 5.0, 3.0, 2.0, 5.0, -1.0], vec![3, 2]).unwrap());
+        let out = bn.forward(&x);
+        // Each column is normalized to zero mean and unit variance.
+        for c in 0..2 {
+            let col: Vec<f64> = (0..3).map(|r| out.data.data()[r * 2 + c]).collect();
+            assert!(col.iter().sum::<f64>().abs() < 1e-10);
+            assert!((col.iter().map(|v| v * v).sum::<f64>() / 3.0 - 1.0).abs() < 1e-4);
+        }
+
+        let weights = x.constant(Tensor::new(vec![1.0, 0.0, 2.0, 1.0, 0.5, 3.0], vec![3, 2]).unwrap());
+        let grads = backward(&out.mul(&weights).sum());
+        assert_eq!(grads[&bn.beta.node_id].data(), &[3.5, 4.0]);
+        assert!(grads[&bn.gamma.node_id].data().iter().all(|g| g.abs() > 1e-3));
+        // The normalized output is invariant to shifting a column.
+        let dx = grads[&x.node_id].data();
+        assert!((dx[0] + dx[2] + dx[4]).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_batchnorm_running_stats_drive_eval_and_round_trip() {
+        let mut bn = BatchNorm::<f64>::new(2);
+        bn.momentum = 1.0;
+        // [batch 2, channels 2, length 2]; channel 0 is 1..4, channel 1 is 10·that.
+        let x = Variable::input(Tensor::new(vec![1.0, 2.0, 10.0, 20.0, 3.0, 4.0, 30.0, 40.0], vec![2, 2, 2]).unwrap());
+        bn.forward(&x);
+        assert_eq!(bn.running_mean().data(), &[2.5, 25.0]);
+        assert!((bn.running_var().data()[0] - 5.0 / 3.0).abs() < 1e-12);
+
+        bn.eval();
+        let out = bn.forward(&x);
+        let expected = (1.0 - 2.5) / (5.0f64 / 3.0 + 1e-5).sqrt();
+        assert!((out.data.data()[0] - expected).abs() < 1e-12);
+
+        let mut model = Sequential::new().add(Box::new(Linear::new(2, 2))).add(Box::new(bn));
+        let state = model.state_dict();
+        assert_eq!(state["1.running_mean"].data(), &[2.5, 25.0]);
+        let mut fresh = state.clone();
+        fresh.insert("1.running_mean".to_string(), Tensor::zeros(vec![2]));
+        assert!(model.load_state_dict(&fresh, true).unwrap().is_exact());
+        assert_eq!(model.state_dict()["1.running_mean"].data(), &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_layer_and_group_norm_normalize_and_train() {
+        let ln = LayerNorm::new(3);
+        let x = Variable::param(Tensor::new(vec![1.0, 2.0, 6.0, -1.0, 0.0, 4.0], vec![2, 3]).unwrap());
+        let out = ln.forward(&x);
+        for row in out.data.data().chunks(3) {
+            assert!(row.iter().sum::<f64>().abs() < 1e-10);
+            assert!((row.iter().map(|v| v * v).sum::<f64>() / 3.0 - 1.0).abs() < 1e-4);
+        }
+        let grads = backward(&out.mul(&out.constant(Tensor::new(vec![1.0, 2.0, 3.0, 0.0, 1.0, 0.0], vec![2, 3]).unwrap())).sum());
+        assert_eq!(grads[&ln.beta.node_id].data(), &[1.0, 3.0, 3.0]);
+        assert!(grads[&ln.gamma.node_id].data().iter().any(|g| g.abs() > 1e-3));
+
+        // With one group, GroupNorm matches LayerNorm over the flattened sample.
+        let gn = GroupNorm::new(1, 2);
+        let img = Variable::input(Tensor::new((0..8).map(|v| (v * v) as f64).collect(), vec![1, 2, 2, 2]).unwrap());
+        let flat = LayerNorm::new(8).forward(&img.reshape(vec![1, 8]));
+        let grouped = gn.forward(&img);
+        for (a, b) in grouped.data.data().iter().zip(flat.data.data()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        let grads = backward(&grouped.pow(2.0).sum());
+        assert_eq!(grads[&gn.gamma.node_id].shape_vec(), vec![2]);
+    }
+}
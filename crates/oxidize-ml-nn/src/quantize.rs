@@ -0,0 +1,466 @@
+use std::fmt;
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_autodiff::conv::im2col;
+use oxidize_ml_autodiff::{no_grad, ConvGeometry, Variable};
+use crate::layers::{Layer, LayerKind};
+use crate::sequential::Sequential;
+
+/// How many scales a quantized weight gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Granularity {
+    /// One scale for the whole tensor.
+    PerTensor,
+    /// One scale per output channel, which keeps small channels from being
+    /// rounded away next to large ones.
+    #[default]
+    PerChannel,
+}
+
+/// Symmetric int8 tensor: element `i` is `scales[c] · values[i]`, `c` its
+/// index along `axis` (or 0 for a single scale), with `values` in
+/// `[-127, 127]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedTensor {
+    pub values: Vec<i8>,
+    pub scales: Vec<f64>,
+    /// The channel axis of per-channel scales.
+    pub axis: Option<usize>,
+    pub shape: Vec<usize>,
+}
+
+fn scale_for(max_abs: f64) -> f64 {
+    if max_abs > 0.0 { max_abs / 127.0 } else { 1.0 }
+}
+
+fn quantize_value(v: f64, scale: f64) -> i8 {
+    (v / scale).round().clamp(-127.0, 127.0) as i8
+}
+
+fn max_abs<T: Float>(data: &[T]) -> f64 {
+    data.iter().fold(0.0, |m, v| m.max(v.to_f64().abs()))
+}
+
+impl QuantizedTensor {
+    /// Quantize `t`, per channel along `axis` or as a whole.
+    pub fn quantize<T: Float>(t: &Tensor<T>, granularity: Granularity, axis: usize) -> Self {
+        let shape = t.shape_vec();
+        let data = t.data();
+        match granularity {
+            Granularity::PerTensor => {
+                let scale = scale_for(max_abs(data));
+                QuantizedTensor {
+                    values: data.iter().map(|v| quantize_value(v.to_f64(), scale)).collect(),
+                    scales: vec![scale],
+                    axis: None,
+                    shape,
+                }
+            }
+            Granularity::PerChannel => {
+                let (channels, inner) = (shape[axis], shape[axis + 1..].iter().product::<usize>());
+                let channel = |i: usize| (i / inner) % channels;
+                let mut peaks = vec![0.0f64; channels];
+                for (i, v) in data.iter().enumerate() {
+                    peaks[channel(i)] = peaks[channel(i)].max(v.to_f64().abs());
+                }
+                let scales: Vec<f64> = peaks.into_iter().map(scale_for).collect();
+                QuantizedTensor {
+                    values: data.iter().enumerate().map(|(i, v)| quantize_value(v.to_f64(), scales[channel(i)])).collect(),
+                    scales,
+                    axis: Some(axis),
+                    shape,
+                }
+            }
+        }
+    }
+
+    pub fn dequantize<T: Float>(&self) -> Tensor<T> {
+        let inner = self.axis.map_or(1, |a| self.shape[a + 1..].iter().product());
+        let data = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, &q)| T::from_f64(self.scales[(i / inner) % self.scales.len()] * q as f64))
+            .collect();
+        Tensor::new(data, self.shape.clone()).expect("dequantize")
+    }
+
+    /// Storage: one byte per value plus the scales as `f32`.
+    pub fn nbytes(&self) -> usize {
+        self.values.len() + 4 * self.scales.len()
+    }
+}
+
+/// `[rows, k] · [cols, k]ᵀ` over int8 with i32 accumulation, scaled back to
+/// floats by `row_scale · col_scales[j]`.
+fn int8_matmul_bt(a: &[i8], b: &[i8], k: usize, row_scale: f64, col_scales: &[f64]) -> Vec<f64> {
+    let (rows, cols) = (a.len() / k, b.len() / k);
+    let mut out = Vec::with_capacity(rows * cols);
+    for r in 0..rows {
+        let x = &a[r * k..(r + 1) * k];
+        for c in 0..cols {
+            let w = &b[c * k..(c + 1) * k];
+            let acc: i32 = x.iter().zip(w).map(|(&p, &q)| p as i32 * q as i32).sum();
+            out.push(acc as f64 * row_scale * col_scales[c]);
+        }
+    }
+    out
+}
+
+/// The scale the input is quantized with: the calibrated one, or its own
+/// range when dynamic.
+fn input_scale<T: Float>(calibrated: Option<f64>, x: &[T]) -> f64 {
+    calibrated.unwrap_or_else(|| scale_for(max_abs(x)))
+}
+
+/// `Linear` with int8 weights (stored `[out, in]`) and int8 inputs.
+#[derive(Debug, Clone)]
+pub struct QuantizedLinear<T: Float = f64> {
+    pub weight: QuantizedTensor,
+    pub bias: Tensor<T>,
+    /// Calibrated input scale; `None` quantizes each batch by its range.
+    pub input_scale: Option<f64>,
+}
+
+impl<T: Float> QuantizedLinear<T> {
+    /// From a layer whose `parameters()` are `[weight [in, out], bias [1, out]]`.
+    pub fn from_parameters(params: &[Variable<T>], granularity: Granularity) -> TensorResult<Self> {
+        let weight = params[0].data.t()?;
+        Ok(QuantizedLinear {
+            weight: QuantizedTensor::quantize(&weight, granularity, 0),
+            bias: params[1].data.flatten(),
+            input_scale: None,
+        })
+    }
+
+    /// `[n, in]` to `[n, out]`.
+    pub fn forward(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let (out_features, in_features) = (self.weight.shape[0], self.weight.shape[1]);
+        let shape = x.shape_vec();
+        if shape.len() != 2 || shape[1] != in_features {
+            return Err(TensorError::ShapeMismatch { expected: vec![shape[0], in_features], got: shape });
+        }
+        let scale = input_scale(self.input_scale, x.data());
+        let xq: Vec<i8> = x.data().iter().map(|v| quantize_value(v.to_f64(), scale)).collect();
+        let scales = per_row_scales(&self.weight);
+        let out = int8_matmul_bt(&xq, &self.weight.values, in_features, scale, &scales);
+        let bias = self.bias.data();
+        let data = out.iter().enumerate().map(|(i, &v)| T::from_f64(v) + bias[i % out_features]).collect();
+        Tensor::new(data, vec![shape[0], out_features])
+    }
+}
+
+/// The scale of every row of a weight quantized along axis 0.
+fn per_row_scales(w: &QuantizedTensor) -> Vec<f64> {
+    if w.scales.len() == 1 { vec![w.scales[0]; w.shape[0]] } else { w.scales.clone() }
+}
+
+/// `Conv2D` with int8 kernels and int8 inputs.
+#[derive(Debug, Clone)]
+pub struct QuantizedConv2d<T: Float = f64> {
+    /// `[out, in / groups, kh, kw]`, per-channel along the output axis.
+    pub weight: QuantizedTensor,
+    pub bias: Tensor<T>,
+    pub stride: (usize, usize),
+    pub padding: (usize, usize),
+    pub dilation: (usize, usize),
+    pub groups: usize,
+    pub input_scale: Option<f64>,
+}
+
+impl<T: Float> QuantizedConv2d<T> {
+    pub fn from_parameters(params: &[Variable<T>], kind: &LayerKind, granularity: Granularity) -> Self {
+        let LayerKind::Conv2D { stride, padding, dilation, groups, .. } = *kind else {
+            panic!("QuantizedConv2d: not a Conv2D layer");
+        };
+        QuantizedConv2d {
+            weight: QuantizedTensor::quantize(&params[0].data, granularity, 0),
+            bias: params[1].data.flatten(),
+            stride,
+            padding,
+            dilation,
+            groups,
+            input_scale: None,
+        }
+    }
+
+    /// `[batch, in, h, w]` to `[batch, out, out_h, out_w]`.
+    pub fn forward(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let shape = x.shape_vec();
+        let w = &self.weight.shape;
+        if shape.len() != 4 || shape[1] != w[1] * self.groups {
+            return Err(TensorError::InvalidOperation(format!(
+                "quantized conv2d: kernel {:?} with {} groups does not match input {:?}",
+                w, self.groups, shape
+            )));
+        }
+        let geometry = ConvGeometry::new(&shape, (w[2], w[3]), self.stride, self.padding).with_dilation(self.dilation);
+        let (out_h, out_w) = geometry.out_size();
+        let scale = input_scale(self.input_scale, x.data());
+        let cols: Vec<i8> = im2col(x, &geometry).data().iter().map(|v| quantize_value(v.to_f64(), scale)).collect();
+
+        let (out_ch, window) = (w[0], geometry.window_len());
+        let (group_cols, group_out) = (window / self.groups, out_ch / self.groups);
+        let rows = cols.len() / window;
+        let scales = per_row_scales(&self.weight);
+        // `[rows, out_ch]`, each group multiplying its own column block.
+        let mut acc = vec![0.0; rows * out_ch];
+        for g in 0..self.groups {
+            let x: Vec<i8> = (0..rows)
+                .flat_map(|r| cols[r * window + g * group_cols..r * window + (g + 1) * group_cols].iter().copied())
+                .collect();
+            let kernel = &self.weight.values[g * group_out * group_cols..(g + 1) * group_out * group_cols];
+            let out = int8_matmul_bt(&x, kernel, group_cols, scale, &scales[g * group_out..(g + 1) * group_out]);
+            for r in 0..rows {
+                acc[r * out_ch + g * group_out..r * out_ch + (g + 1) * group_out]
+                    .copy_from_slice(&out[r * group_out..(r + 1) * group_out]);
+            }
+        }
+        let (batch, pixels) = (shape[0], out_h * out_w);
+        let bias = self.bias.data();
+        let mut data = vec![T::ZERO; batch * out_ch * pixels];
+        for b in 0..batch {
+            for p in 0..pixels {
+                for c in 0..out_ch {
+                    data[(b * out_ch + c) * pixels + p] = T::from_f64(acc[(b * pixels + p) * out_ch + c]) + bias[c];
+                }
+            }
+        }
+        Tensor::new(data, vec![batch, out_ch, out_h, out_w])
+    }
+}
+
+/// One step of a `QuantizedSequential`.
+#[derive(Debug, Clone)]
+pub enum QuantizedOp<T: Float = f64> {
+    Linear(QuantizedLinear<T>),
+    Conv2D(QuantizedConv2d<T>),
+    /// A parameter-free layer run in floating point.
+    Float(LayerKind),
+}
+
+/// An int8 copy of a `Sequential` for inference.
+///
+/// `Linear` and `Conv2D` layers become int8 kernels; `ReLU`, `LeakyReLU`,
+/// `Sigmoid`, `Tanh`, `Flatten` and `Dropout` (the identity) run in
+/// floating point between them. Other layers cannot be quantized.
+#[derive(Debug, Clone)]
+pub struct QuantizedSequential<T: Float = f64> {
+    pub ops: Vec<QuantizedOp<T>>,
+}
+
+impl<T: Float> QuantizedSequential<T> {
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.ops.iter().try_fold(x.clone(), |x, op| match op {
+            QuantizedOp::Linear(layer) => layer.forward(&x),
+            QuantizedOp::Conv2D(layer) => layer.forward(&x),
+            QuantizedOp::Float(kind) => apply_kind(kind, &x),
+        })
+    }
+
+    /// Bytes of weight storage.
+    pub fn nbytes(&self) -> usize {
+        let size = std::mem::size_of::<T>();
+        self.ops
+            .iter()
+            .map(|op| match op {
+                QuantizedOp::Linear(l) => l.weight.nbytes() + l.bias.numel() * size,
+                QuantizedOp::Conv2D(c) => c.weight.nbytes() + c.bias.numel() * size,
+                QuantizedOp::Float(_) => 0,
+            })
+            .sum()
+    }
+}
+
+/// For inference only: outputs are constants with no gradient.
+impl<T: Float> Layer<T> for QuantizedSequential<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        input.constant(self.predict(&input.data).expect("quantized forward"))
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        Vec::new()
+    }
+}
+
+fn apply_kind<T: Float>(kind: &LayerKind, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+    Ok(match *kind {
+        LayerKind::ReLU => x.relu(),
+        LayerKind::Sigmoid => x.sigmoid(),
+        LayerKind::Tanh => x.tanh_elem(),
+        LayerKind::LeakyReLU { alpha } => {
+            let alpha = T::from_f64(alpha);
+            x.apply(|v| if v > T::ZERO { v } else { alpha * v })
+        }
+        LayerKind::Flatten if x.ndim() > 2 => {
+            let shape = x.shape_vec();
+            x.reshape(vec![shape[0], shape[1..].iter().product()])?
+        }
+        LayerKind::Flatten | LayerKind::Dropout => x.clone(),
+        _ => return Err(TensorError::InvalidOperation(format!("{:?} cannot run in a quantized model", kind))),
+    })
+}
+
+/// Quantize the weights of `model` to int8; each input is quantized with
+/// the scale of its own range as it arrives.
+pub fn quantize_dynamic<T: Float>(model: &Sequential<T>, granularity: Granularity) -> TensorResult<QuantizedSequential<T>> {
+    let ops = model
+        .layers()
+        .iter()
+        .enumerate()
+        .map(|(i, layer)| match layer.kind() {
+            LayerKind::Linear => QuantizedLinear::from_parameters(&layer.parameters(), granularity).map(QuantizedOp::Linear),
+            kind @ LayerKind::Conv2D { .. } => {
+                Ok(QuantizedOp::Conv2D(QuantizedConv2d::from_parameters(&layer.parameters(), &kind, granularity)))
+            }
+            kind @ (LayerKind::ReLU
+            | LayerKind::Sigmoid
+            | LayerKind::Tanh
+            | LayerKind::LeakyReLU { .. }
+            | LayerKind::Flatten
+            | LayerKind::Dropout) => Ok(QuantizedOp::Float(kind)),
+            kind => Err(TensorError::InvalidOperation(format!("layer {} ({:?}) cannot be quantized", i, kind))),
+        })
+        .collect::<TensorResult<_>>()?;
+    Ok(QuantizedSequential { ops })
+}
+
+/// Like `quantize_dynamic`, but with the input scale of every int8 layer
+/// fixed from the largest magnitude it sees while the float model runs
+/// over the `calibration` batches, so inference needs no range pass.
+pub fn quantize_static<T: Float>(
+    model: &Sequential<T>,
+    calibration: &[Tensor<T>],
+    granularity: Granularity,
+) -> TensorResult<QuantizedSequential<T>> {
+    let mut quantized = quantize_dynamic(model, granularity)?;
+    let mut peaks = vec![0.0f64; model.layers().len()];
+    no_grad(|| {
+        for batch in calibration {
+            let mut x = Variable::input(batch.clone());
+            for (peak, layer) in peaks.iter_mut().zip(model.layers()) {
+                *peak = peak.max(max_abs(x.data.data()));
+                x = layer.forward(&x);
+            }
+        }
+    });
+    for (op, peak) in quantized.ops.iter_mut().zip(peaks) {
+        match op {
+            QuantizedOp::Linear(l) => l.input_scale = Some(scale_for(peak)),
+            QuantizedOp::Conv2D(c) => c.input_scale = Some(scale_for(peak)),
+            QuantizedOp::Float(_) => {}
+        }
+    }
+    Ok(quantized)
+}
+
+/// How far a quantized model's outputs are from the float model's.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizationReport {
+    pub max_abs_error: f64,
+    pub mean_abs_error: f64,
+    /// Share of rows whose largest output is at the same position in both
+    /// models: the fraction of classifier predictions kept.
+    pub agreement: f64,
+    pub float_bytes: usize,
+    pub quantized_bytes: usize,
+}
+
+impl QuantizationReport {
+    /// How many times smaller the weights got.
+    pub fn compression(&self) -> f64 {
+        self.float_bytes as f64 / self.quantized_bytes as f64
+    }
+}
+
+impl fmt::Display for QuantizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "agreement {:.2}%, max |error| {:.3e}, mean |error| {:.3e}, {} -> {} bytes ({:.1}x)",
+            100.0 * self.agreement,
+            self.max_abs_error,
+            self.mean_abs_error,
+            self.float_bytes,
+            self.quantized_bytes,
+            self.compression()
+        )
+    }
+}
+
+/// Run `float` and `quantized` over `inputs` and compare their outputs.
+pub fn compare_quantized<T: Float>(
+    float: &Sequential<T>,
+    quantized: &QuantizedSequential<T>,
+    inputs: &[Tensor<T>],
+) -> TensorResult<QuantizationReport> {
+    let (mut max_err, mut sum_err, mut count, mut agree, mut rows) = (0.0f64, 0.0, 0usize, 0usize, 0usize);
+    for x in inputs {
+        let expected = no_grad(|| float.forward(&Variable::input(x.clone())).data);
+        let got = quantized.predict(x)?;
+        for (a, b) in expected.data().iter().zip(got.data()) {
+            let err = (a.to_f64() - b.to_f64()).abs();
+            max_err = max_err.max(err);
+            sum_err += err;
+            count += 1;
+        }
+        let width = expected.shape_vec().last().copied().unwrap_or(1);
+        for (a, b) in expected.data().chunks(width).zip(got.data().chunks(width)) {
+            agree += (argmax(a) == argmax(b)) as usize;
+            rows += 1;
+        }
+    }
+    let float_bytes = float.parameters().iter().map(|p| p.data.numel()).sum::<usize>() * std::mem::size_of::<T>();
+    Ok(QuantizationReport {
+        max_abs_error: max_err,
+        mean_abs_error: sum_err / count.max(1) as f64,
+        agreement: agree as f64 / rows.max(1) as f64,
+        float_bytes,
+        quantized_bytes: quantized.nbytes(),
+    })
+}
+
+fn argmax<T: Float>(row: &[T]) -> usize {
+    (0..row.len()).fold(0, |best, i| if row[i] > row[best] { i } else { best })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::set_global_seed;
+    use crate::conv::Conv2D;
+    use crate::layers::{FlattenLayer, Linear, ReLULayer};
+
+    #[test]
+    fn test_quantized_tensor_per_channel_keeps_small_channels() {
+        let t = Tensor::new(vec![100.0, 0.01, -50.0, -0.02], vec![2, 2]).unwrap();
+        let per_tensor = QuantizedTensor::quantize(&t, Granularity::PerTensor, 1);
+        let per_channel = QuantizedTensor::quantize(&t, Granularity::PerChannel, 1);
+        assert_eq!(per_tensor.dequantize::<f64>().data()[1], 0.0);
+        let back: Tensor<f64> = per_channel.dequantize();
+        assert!((back.data()[1] - 0.01).abs() < 1e-3 && (back.data()[2] + 50.0).abs() < 0.5);
+        assert_eq!((per_channel.scales.len(), per_channel.nbytes()), (2, 12));
+    }
+
+    #[test]
+    fn test_quantized_models_track_the_float_model() {
+        set_global_seed(3);
+        let model: Sequential = Sequential::new()
+            .add(Box::new(Conv2D::new(1, 4, 3, 1, 1)))
+            .add(Box::new(ReLULayer))
+            .add(Box::new(FlattenLayer))
+            .add(Box::new(Linear::new(4 * 5 * 5, 3)));
+        let inputs: Vec<Tensor<f64>> = (0..4).map(|i| Tensor::randn(vec![8, 1, 5, 5], Some(i))).collect();
+
+        let dynamic = quantize_dynamic(&model, Granularity::PerChannel).unwrap();
+        let report = compare_quantized(&model, &dynamic, &inputs).unwrap();
+        assert!(report.agreement >= 0.9, "{}", report);
+        assert!(report.max_abs_error < 0.1, "{}", report);
+        assert!(report.compression() > 5.0, "{}", report);
+
+        let calibrated = quantize_static(&model, &inputs[..2], Granularity::PerTensor).unwrap();
+        assert!(matches!(&calibrated.ops[0], QuantizedOp::Conv2D(c) if c.input_scale.is_some()));
+        let report = compare_quantized(&model, &calibrated, &inputs[2..]).unwrap();
+        assert!(report.agreement >= 0.9 && report.mean_abs_error < 0.05, "{}", report);
+    }
+}
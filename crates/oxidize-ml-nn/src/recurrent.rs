@@ -1,24 +1,28 @@
-use oxidize_ml_core::Tensor;
+use std::marker::PhantomData;
+
+use oxidize_ml_core::{Float, Tensor};
 use oxidize_ml_autodiff::Variable;
+use crate::init::{self, Init};
+use crate::layers::{Dropout, Layer};
+use crate::sequence::PackedSequence;
+use crate::state_dict::{named_fields, NamedParameters};
 
 /// Simple RNN Cell.
 ///
 /// h_t = tanh(W_ih @ x_t + W_hh @ h_{t-1} + b)
-pub struct RNNCell {
-    pub w_ih: Variable,  // [hidden_size, input_size]
-    pub w_hh: Variable,  // [hidden_size, hidden_size]
-    pub bias: Variable,  // [1, hidden_size]
+pub struct RNNCell<T: Float = f64> {
+    pub w_ih: Variable<T>,  // [hidden_size, input_size]
+    pub w_hh: Variable<T>,  // [hidden_size, hidden_size]
+    pub bias: Variable<T>,  // [1, hidden_size]
     pub hidden_size: usize,
     pub input_size: usize,
 }
 
-impl RNNCell {
+impl<T: Float> RNNCell<T> {
     pub fn new(input_size: usize, hidden_size: usize) -> Self {
         let scale = (1.0 / hidden_size as f64).sqrt();
-        let w_ih = Tensor::rand(vec![input_size, hidden_size], Some(42))
-            .mul_scalar(2.0 * scale).add_scalar(-scale);
-        let w_hh = Tensor::rand(vec![hidden_size, hidden_size], Some(43))
-            .mul_scalar(2.0 * scale).add_scalar(-scale);
+        let w_ih = init::uniform(vec![input_size, hidden_size], -scale, scale);
+        let w_hh = init::uniform(vec![hidden_size, hidden_size], -scale, scale);
         let bias = Tensor::zeros(vec![1, hidden_size]);
 
         RNNCell {
@@ -33,7 +37,7 @@ impl RNNCell {
     /// Forward one time step.
     /// x: [batch, input_size], h: [batch, hidden_size]
     /// Returns new h: [batch, hidden_size]
-    pub fn forward(&self, x: &Variable, h: &Variable) -> Variable {
+    pub fn forward(&self, x: &Variable<T>, h: &Variable<T>) -> Variable<T> {
         let xw = x.matmul(&self.w_ih);       // [batch, hidden_size]
         let hw = h.matmul(&self.w_hh);        // [batch, hidden_size]
         let pre_act = xw.add(&hw).add(&self.bias);
@@ -43,7 +47,7 @@ impl RNNCell {
     /// Forward over a sequence.
     /// x: [seq_len, batch, input_size]
     /// Returns final hidden state [batch, hidden_size]
-    pub fn forward_seq(&self, inputs: &[Variable], h0: &Variable) -> Vec<Variable> {
+    pub fn forward_seq(&self, inputs: &[Variable<T>], h0: &Variable<T>) -> Vec<Variable<T>> {
         let mut h = h0.clone();
         let mut outputs = Vec::with_capacity(inputs.len());
         for x in inputs {
@@ -53,9 +57,35 @@ impl RNNCell {
         outputs
     }
 
-    pub fn parameters(&self) -> Vec<Variable> {
+    /// Forward over a packed batch; `h0` is `[batch, hidden_size]` in batch
+    /// order. Returns the packed outputs and each sequence's hidden state
+    /// after its last step.
+    pub fn forward_packed(&self, input: &PackedSequence<T>, h0: &Variable<T>) -> (PackedSequence<T>, Variable<T>) {
+        let (outputs, mut finals) = input.scan(std::slice::from_ref(h0), |x, h| vec![self.forward(x, &h[0])]);
+        (outputs, finals.remove(0))
+    }
+
+    pub fn parameters(&self) -> Vec<Variable<T>> {
         vec![self.w_ih.clone(), self.w_hh.clone(), self.bias.clone()]
     }
+
+    pub fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; w_ih, w_hh, bias)
+    }
+
+    pub fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; w_ih, w_hh, bias)
+    }
+
+    /// Re-draw every weight matrix from `init`; biases are kept.
+    pub fn with_init(mut self, init: Init) -> Self {
+        for (name, w) in self.named_parameters_mut() {
+            if name.starts_with("w_") {
+                w.set_data(init.tensor(w.shape_vec()));
+            }
+        }
+        self
+    }
 }
 
 /// GRU Cell (Gated Recurrent Unit).
@@ -64,48 +94,42 @@ impl RNNCell {
 /// r_t = σ(W_r @ [h_{t-1}, x_t])    -- reset gate
 /// ĥ_t = tanh(W @ [r_t * h_{t-1}, x_t])  -- candidate
 /// h_t = (1 - z_t) * h_{t-1} + z_t * ĥ_t
-pub struct GRUCell {
+pub struct GRUCell<T: Float = f64> {
     pub input_size: usize,
     pub hidden_size: usize,
     // Update gate
-    pub w_z_x: Variable,  // [input_size, hidden_size]
-    pub w_z_h: Variable,  // [hidden_size, hidden_size]
-    pub b_z: Variable,    // [1, hidden_size]
+    pub w_z_x: Variable<T>,  // [input_size, hidden_size]
+    pub w_z_h: Variable<T>,  // [hidden_size, hidden_size]
+    pub b_z: Variable<T>,    // [1, hidden_size]
     // Reset gate
-    pub w_r_x: Variable,
-    pub w_r_h: Variable,
-    pub b_r: Variable,
+    pub w_r_x: Variable<T>,
+    pub w_r_h: Variable<T>,
+    pub b_r: Variable<T>,
     // Candidate
-    pub w_n_x: Variable,
-    pub w_n_h: Variable,
-    pub b_n: Variable,
+    pub w_n_x: Variable<T>,
+    pub w_n_h: Variable<T>,
+    pub b_n: Variable<T>,
 }
 
-impl GRUCell {
+impl<T: Float> GRUCell<T> {
     pub fn new(input_size: usize, hidden_size: usize) -> Self {
         let scale = (1.0 / hidden_size as f64).sqrt();
-        let mk = |seed: u64| {
-            Tensor::rand(vec![input_size, hidden_size], Some(seed))
-                .mul_scalar(2.0 * scale).add_scalar(-scale)
-        };
-        let mkh = |seed: u64| {
-            Tensor::rand(vec![hidden_size, hidden_size], Some(seed))
-                .mul_scalar(2.0 * scale).add_scalar(-scale)
-        };
+        let mk = || init::uniform(vec![input_size, hidden_size], -scale, scale);
+        let mkh = || init::uniform(vec![hidden_size, hidden_size], -scale, scale);
 
         GRUCell {
             input_size, hidden_size,
-            w_z_x: Variable::param(mk(10)), w_z_h: Variable::param(mkh(11)),
+            w_z_x: Variable::param(mk()), w_z_h: Variable::param(mkh()),
             b_z: Variable::param(Tensor::zeros(vec![1, hidden_size])),
-            w_r_x: Variable::param(mk(20)), w_r_h: Variable::param(mkh(21)),
+            w_r_x: Variable::param(mk()), w_r_h: Variable::param(mkh()),
             b_r: Variable::param(Tensor::zeros(vec![1, hidden_size])),
-            w_n_x: Variable::param(mk(30)), w_n_h: Variable::param(mkh(31)),
+            w_n_x: Variable::param(mk()), w_n_h: Variable::param(mkh()),
             b_n: Variable::param(Tensor::zeros(vec![1, hidden_size])),
         }
     }
 
     /// Forward one time step.
-    pub fn forward(&self, x: &Variable, h: &Variable) -> Variable {
+    pub fn forward(&self, x: &Variable<T>, h: &Variable<T>) -> Variable<T> {
         // Update gate: z = σ(x @ W_z_x + h @ W_z_h + b_z)
         let z = x.matmul(&self.w_z_x).add(&h.matmul(&self.w_z_h)).add(&self.b_z).sigmoid();
 
@@ -117,12 +141,12 @@ impl GRUCell {
         let n = x.matmul(&self.w_n_x).add(&rh.matmul(&self.w_n_h)).add(&self.b_n).tanh_act();
 
         // h_new = (1 - z) * h + z * n
-        let one = Variable::input(Tensor::ones(z.data.shape_vec()));
+        let one = z.constant(Tensor::ones(z.data.shape_vec()));
         let one_minus_z = one.sub(&z);
         one_minus_z.mul(h).add(&z.mul(&n))
     }
 
-    pub fn forward_seq(&self, inputs: &[Variable], h0: &Variable) -> Vec<Variable> {
+    pub fn forward_seq(&self, inputs: &[Variable<T>], h0: &Variable<T>) -> Vec<Variable<T>> {
         let mut h = h0.clone();
         let mut outputs = Vec::with_capacity(inputs.len());
         for x in inputs {
@@ -132,13 +156,39 @@ impl GRUCell {
         outputs
     }
 
-    pub fn parameters(&self) -> Vec<Variable> {
+    /// Forward over a packed batch; `h0` is `[batch, hidden_size]` in batch
+    /// order. Returns the packed outputs and each sequence's hidden state
+    /// after its last step.
+    pub fn forward_packed(&self, input: &PackedSequence<T>, h0: &Variable<T>) -> (PackedSequence<T>, Variable<T>) {
+        let (outputs, mut finals) = input.scan(std::slice::from_ref(h0), |x, h| vec![self.forward(x, &h[0])]);
+        (outputs, finals.remove(0))
+    }
+
+    pub fn parameters(&self) -> Vec<Variable<T>> {
         vec![
             self.w_z_x.clone(), self.w_z_h.clone(), self.b_z.clone(),
             self.w_r_x.clone(), self.w_r_h.clone(), self.b_r.clone(),
             self.w_n_x.clone(), self.w_n_h.clone(), self.b_n.clone(),
         ]
     }
+
+    pub fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; w_z_x, w_z_h, b_z, w_r_x, w_r_h, b_r, w_n_x, w_n_h, b_n)
+    }
+
+    pub fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; w_z_x, w_z_h, b_z, w_r_x, w_r_h, b_r, w_n_x, w_n_h, b_n)
+    }
+
+    /// Re-draw every weight matrix from `init`; biases are kept.
+    pub fn with_init(mut self, init: Init) -> Self {
+        for (name, w) in self.named_parameters_mut() {
+            if name.starts_with("w_") {
+                w.set_data(init.tensor(w.shape_vec()));
+            }
+        }
+        self
+    }
 }
 
 /// LSTM Cell (Long Short-Term Memory).
@@ -149,44 +199,38 @@ impl GRUCell {
 /// g_t = tanh(W_g @ [h_{t-1}, x_t] + b_g) -- cell candidate
 /// c_t = f_t * c_{t-1} + i_t * g_t         -- cell state
 /// h_t = o_t * tanh(c_t)                   -- hidden state
-pub struct LSTMCell {
+pub struct LSTMCell<T: Float = f64> {
     pub input_size: usize,
     pub hidden_size: usize,
     // Input gate
-    pub w_i_x: Variable, pub w_i_h: Variable, pub b_i: Variable,
+    pub w_i_x: Variable<T>, pub w_i_h: Variable<T>, pub b_i: Variable<T>,
     // Forget gate
-    pub w_f_x: Variable, pub w_f_h: Variable, pub b_f: Variable,
+    pub w_f_x: Variable<T>, pub w_f_h: Variable<T>, pub b_f: Variable<T>,
     // Output gate
-    pub w_o_x: Variable, pub w_o_h: Variable, pub b_o: Variable,
+    pub w_o_x: Variable<T>, pub w_o_h: Variable<T>, pub b_o: Variable<T>,
     // Cell candidate
-    pub w_g_x: Variable, pub w_g_h: Variable, pub b_g: Variable,
+    pub w_g_x: Variable<T>, pub w_g_h: Variable<T>, pub b_g: Variable<T>,
 }
 
-impl LSTMCell {
+impl<T: Float> LSTMCell<T> {
     pub fn new(input_size: usize, hidden_size: usize) -> Self {
         let scale = (1.0 / hidden_size as f64).sqrt();
-        let mk = |seed: u64| {
-            Tensor::rand(vec![input_size, hidden_size], Some(seed))
-                .mul_scalar(2.0 * scale).add_scalar(-scale)
-        };
-        let mkh = |seed: u64| {
-            Tensor::rand(vec![hidden_size, hidden_size], Some(seed))
-                .mul_scalar(2.0 * scale).add_scalar(-scale)
-        };
+        let mk = || init::uniform(vec![input_size, hidden_size], -scale, scale);
+        let mkh = || init::uniform(vec![hidden_size, hidden_size], -scale, scale);
         let bz = || Tensor::zeros(vec![1, hidden_size]);
 
         LSTMCell {
             input_size, hidden_size,
-            w_i_x: Variable::param(mk(40)), w_i_h: Variable::param(mkh(41)), b_i: Variable::param(bz()),
-            w_f_x: Variable::param(mk(50)), w_f_h: Variable::param(mkh(51)),
+            w_i_x: Variable::param(mk()), w_i_h: Variable::param(mkh()), b_i: Variable::param(bz()),
+            w_f_x: Variable::param(mk()), w_f_h: Variable::param(mkh()),
             b_f: Variable::param(Tensor::ones(vec![1, hidden_size])), // Forget gate bias init to 1
-            w_o_x: Variable::param(mk(60)), w_o_h: Variable::param(mkh(61)), b_o: Variable::param(bz()),
-            w_g_x: Variable::param(mk(70)), w_g_h: Variable::param(mkh(71)), b_g: Variable::param(bz()),
+            w_o_x: Variable::param(mk()), w_o_h: Variable::param(mkh()), b_o: Variable::param(bz()),
+            w_g_x: Variable::param(mk()), w_g_h: Variable::param(mkh()), b_g: Variable::param(bz()),
         }
     }
 
     /// Forward one step. Returns (h_new, c_new).
-    pub fn forward(&self, x: &Variable, h: &Variable, c: &Variable) -> (Variable, Variable) {
+    pub fn forward(&self, x: &Variable<T>, h: &Variable<T>, c: &Variable<T>) -> (Variable<T>, Variable<T>) {
         let i = x.matmul(&self.w_i_x).add(&h.matmul(&self.w_i_h)).add(&self.b_i).sigmoid();
         let f = x.matmul(&self.w_f_x).add(&h.matmul(&self.w_f_h)).add(&self.b_f).sigmoid();
         let o = x.matmul(&self.w_o_x).add(&h.matmul(&self.w_o_h)).add(&self.b_o).sigmoid();
@@ -198,8 +242,8 @@ impl LSTMCell {
     }
 
     /// Forward over a sequence.
-    pub fn forward_seq(&self, inputs: &[Variable], h0: &Variable, c0: &Variable)
-        -> (Vec<Variable>, Variable, Variable)
+    pub fn forward_seq(&self, inputs: &[Variable<T>], h0: &Variable<T>, c0: &Variable<T>)
+        -> (Vec<Variable<T>>, Variable<T>, Variable<T>)
     {
         let mut h = h0.clone();
         let mut c = c0.clone();
@@ -213,7 +257,21 @@ impl LSTMCell {
         (outputs, h, c)
     }
 
-    pub fn parameters(&self) -> Vec<Variable> {
+    /// Forward over a packed batch; `h0` and `c0` are `[batch, hidden_size]`
+    /// in batch order. Returns the packed outputs and each sequence's hidden
+    /// and cell states after its last step.
+    pub fn forward_packed(&self, input: &PackedSequence<T>, h0: &Variable<T>, c0: &Variable<T>)
+        -> (PackedSequence<T>, Variable<T>, Variable<T>)
+    {
+        let (outputs, mut finals) = input.scan(&[h0.clone(), c0.clone()], |x, s| {
+            let (h, c) = self.forward(x, &s[0], &s[1]);
+            vec![h, c]
+        });
+        let c = finals.remove(1);
+        (outputs, finals.remove(0), c)
+    }
+
+    pub fn parameters(&self) -> Vec<Variable<T>> {
         vec![
             self.w_i_x.clone(), self.w_i_h.clone(), self.b_i.clone(),
             self.w_f_x.clone(), self.w_f_h.clone(), self.b_f.clone(),
@@ -221,6 +279,246 @@ impl LSTMCell {
             self.w_g_x.clone(), self.w_g_h.clone(), self.b_g.clone(),
         ]
     }
+
+    pub fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        named_fields!(self; w_i_x, w_i_h, b_i, w_f_x, w_f_h, b_f, w_o_x, w_o_h, b_o, w_g_x, w_g_h, b_g)
+    }
+
+    pub fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        named_fields!(mut self; w_i_x, w_i_h, b_i, w_f_x, w_f_h, b_f, w_o_x, w_o_h, b_o, w_g_x, w_g_h, b_g)
+    }
+
+    /// Re-draw every weight matrix from `init`; biases are kept.
+    pub fn with_init(mut self, init: Init) -> Self {
+        for (name, w) in self.named_parameters_mut() {
+            if name.starts_with("w_") {
+                w.set_data(init.tensor(w.shape_vec()));
+            }
+        }
+        self
+    }
+}
+
+/// A cell that `Recurrent` can run and stack: one step over
+/// `[batch, hidden_size]` state tensors, `[h]` or `[h, c]` for the LSTM.
+pub trait RecurrentCell<T: Float>: Sized {
+    /// Number of state tensors; the first is the hidden state (the output).
+    const STATES: usize;
+    fn create(input_size: usize, hidden_size: usize) -> Self;
+    fn step(&self, x: &Variable<T>, state: &[Variable<T>]) -> Vec<Variable<T>>;
+    fn cell_parameters(&self) -> Vec<Variable<T>>;
+    fn cell_named_parameters(&self) -> Vec<(String, Variable<T>)>;
+    fn cell_named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)>;
+}
+
+impl<T: Float> RecurrentCell<T> for RNNCell<T> {
+    const STATES: usize = 1;
+    fn create(input_size: usize, hidden_size: usize) -> Self { RNNCell::new(input_size, hidden_size) }
+    fn step(&self, x: &Variable<T>, state: &[Variable<T>]) -> Vec<Variable<T>> { vec![self.forward(x, &state[0])] }
+    fn cell_parameters(&self) -> Vec<Variable<T>> { self.parameters() }
+    fn cell_named_parameters(&self) -> Vec<(String, Variable<T>)> { self.named_parameters() }
+    fn cell_named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> { self.named_parameters_mut() }
+}
+
+impl<T: Float> RecurrentCell<T> for GRUCell<T> {
+    const STATES: usize = 1;
+    fn create(input_size: usize, hidden_size: usize) -> Self { GRUCell::new(input_size, hidden_size) }
+    fn step(&self, x: &Variable<T>, state: &[Variable<T>]) -> Vec<Variable<T>> { vec![self.forward(x, &state[0])] }
+    fn cell_parameters(&self) -> Vec<Variable<T>> { self.parameters() }
+    fn cell_named_parameters(&self) -> Vec<(String, Variable<T>)> { self.named_parameters() }
+    fn cell_named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> { self.named_parameters_mut() }
+}
+
+impl<T: Float> RecurrentCell<T> for LSTMCell<T> {
+    const STATES: usize = 2;
+    fn create(input_size: usize, hidden_size: usize) -> Self { LSTMCell::new(input_size, hidden_size) }
+    fn step(&self, x: &Variable<T>, state: &[Variable<T>]) -> Vec<Variable<T>> {
+        let (h, c) = self.forward(x, &state[0], &state[1]);
+        vec![h, c]
+    }
+    fn cell_parameters(&self) -> Vec<Variable<T>> { self.parameters() }
+    fn cell_named_parameters(&self) -> Vec<(String, Variable<T>)> { self.named_parameters() }
+    fn cell_named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> { self.named_parameters_mut() }
+}
+
+/// Multi-layer, optionally bidirectional RNN over `C` cells; use the `RNN`,
+/// `GRU` and `LSTM` aliases.
+///
+/// Layer `l > 0` reads the outputs of layer `l - 1` (both directions
+/// concatenated), with `dropout` applied in between while training. States
+/// are stacked as `[num_layers * directions, batch, hidden_size]`,
+/// layer-major with the forward direction first, as in PyTorch.
+pub struct Recurrent<C, T: Float = f64> {
+    pub input_size: usize,
+    pub hidden_size: usize,
+    pub num_layers: usize,
+    pub bidirectional: bool,
+    pub dropout: Dropout,
+    /// `layers[l][d]`: direction `d` (0 forward, 1 backward) of layer `l`.
+    pub layers: Vec<Vec<C>>,
+    _element: PhantomData<T>,
+}
+
+pub type RNN<T = f64> = Recurrent<RNNCell<T>, T>;
+pub type GRU<T = f64> = Recurrent<GRUCell<T>, T>;
+pub type LSTM<T = f64> = Recurrent<LSTMCell<T>, T>;
+
+impl<T: Float, C: RecurrentCell<T>> Recurrent<C, T> {
+    /// One unidirectional layer without dropout.
+    pub fn new(input_size: usize, hidden_size: usize) -> Self {
+        Recurrent {
+            input_size,
+            hidden_size,
+            num_layers: 1,
+            bidirectional: false,
+            dropout: Dropout::new(0.0),
+            layers: Vec::new(),
+            _element: PhantomData,
+        }
+        .build()
+    }
+
+    /// Stack `num_layers` layers; re-draws every cell.
+    pub fn with_num_layers(mut self, num_layers: usize) -> Self {
+        assert!(num_layers >= 1, "Recurrent: num_layers must be at least 1");
+        self.num_layers = num_layers;
+        self.build()
+    }
+
+    /// Add a backward direction to every layer; re-draws every cell.
+    pub fn with_bidirectional(mut self, bidirectional: bool) -> Self {
+        self.bidirectional = bidirectional;
+        self.build()
+    }
+
+    /// Dropout probability on the outputs of every layer but the last.
+    pub fn with_dropout(mut self, p: f64) -> Self {
+        self.dropout.p = p;
+        self
+    }
+
+    fn build(mut self) -> Self {
+        let directions = self.directions();
+        self.layers = (0..self.num_layers)
+            .map(|l| {
+                let input = if l == 0 { self.input_size } else { self.hidden_size * directions };
+                (0..directions).map(|_| C::create(input, self.hidden_size)).collect()
+            })
+            .collect();
+        self
+    }
+
+    pub fn directions(&self) -> usize {
+        if self.bidirectional { 2 } else { 1 }
+    }
+
+    /// Forward over a packed batch. `initial` holds `C::STATES` tensors of
+    /// stacked states (zeros when `None`). Returns the packed outputs of the
+    /// last layer, `[batch_sizes[t], directions * hidden_size]` per step, and
+    /// every sequence's final states in the layout of `initial`.
+    pub fn forward_packed(&self, input: &PackedSequence<T>, initial: Option<&[Variable<T>]>)
+        -> (PackedSequence<T>, Vec<Variable<T>>)
+    {
+        let (batch, directions) = (input.batch_size(), self.directions());
+        let initial = match initial {
+            Some(states) => {
+                assert_eq!(states.len(), C::STATES, "Recurrent: expected {} initial states", C::STATES);
+                states.to_vec()
+            }
+            None => {
+                let shape = vec![self.num_layers * directions, batch, self.hidden_size];
+                vec![input.steps[0].constant(Tensor::zeros(shape)); C::STATES]
+            }
+        };
+
+        let mut finals: Vec<Vec<Variable<T>>> = vec![Vec::new(); C::STATES];
+        let mut x = input.clone();
+        for (l, cells) in self.layers.iter().enumerate() {
+            if l > 0 && self.dropout.training && self.dropout.p > 0.0 {
+                x.steps = x.steps.iter().map(|s| self.dropout.forward(s)).collect();
+            }
+            let mut outputs = Vec::with_capacity(directions);
+            for (d, cell) in cells.iter().enumerate() {
+                let k = l * directions + d;
+                let state: Vec<Variable<T>> =
+                    initial.iter().map(|s| s.slice(0, k, k + 1).reshape(vec![batch, self.hidden_size])).collect();
+                let (out, last) = if d == 0 {
+                    x.scan(&state, |xt, s| cell.step(xt, s))
+                } else {
+                    let (out, last) = x.reversed().scan(&state, |xt, s| cell.step(xt, s));
+                    (out.reversed(), last)
+                };
+                outputs.push(out);
+                for (f, s) in finals.iter_mut().zip(last) {
+                    f.push(s);
+                }
+            }
+            let mut top = outputs.remove(0);
+            if let Some(backward) = outputs.pop() {
+                top.steps = top.steps.iter().zip(&backward.steps).map(|(f, b)| Variable::concat(&[f, b], 1)).collect();
+            }
+            x = top;
+        }
+        let finals = finals.iter().map(|f| Variable::stack(&f.iter().collect::<Vec<_>>(), 0)).collect();
+        (x, finals)
+    }
+
+    /// Forward over equal-length `[batch, input_size]` steps; see
+    /// `forward_packed`.
+    pub fn forward_seq(&self, inputs: &[Variable<T>], initial: Option<&[Variable<T>]>)
+        -> (Vec<Variable<T>>, Vec<Variable<T>>)
+    {
+        let batch = inputs.first().expect("Recurrent: empty sequence").shape_vec()[0];
+        let packed = PackedSequence::from_padded(inputs, &vec![inputs.len(); batch]);
+        let (outputs, finals) = self.forward_packed(&packed, initial);
+        (outputs.unpack().0, finals)
+    }
+
+    fn direction_name(d: usize) -> &'static str {
+        if d == 0 { "forward" } else { "backward" }
+    }
+}
+
+impl<T: Float, C: RecurrentCell<T>> Layer<T> for Recurrent<C, T> {
+    /// `[seq_len, batch, input_size]` to `[seq_len, batch, directions *
+    /// hidden_size]`, from zero initial states.
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        let shape = input.shape_vec();
+        let steps: Vec<Variable<T>> =
+            (0..shape[0]).map(|t| input.slice(0, t, t + 1).reshape(shape[1..].to_vec())).collect();
+        let (outputs, _) = self.forward_seq(&steps, None);
+        Variable::stack(&outputs.iter().collect::<Vec<_>>(), 0)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        self.layers.iter().flatten().flat_map(|c| c.cell_parameters()).collect()
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        let mut params = NamedParameters::new();
+        for (l, cells) in self.layers.iter().enumerate() {
+            for (d, cell) in cells.iter().enumerate() {
+                let name = format!("layers.{}.{}", l, Self::direction_name(d));
+                params = params.register_module(&name, cell.cell_named_parameters());
+            }
+        }
+        params.into_vec()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        let mut params = NamedParameters::new();
+        for (l, cells) in self.layers.iter_mut().enumerate() {
+            for (d, cell) in cells.iter_mut().enumerate() {
+                let name = format!("layers.{}.{}", l, Self::direction_name(d));
+                params = params.register_module(&name, cell.cell_named_parameters_mut());
+            }
+        }
+        params.into_vec()
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.dropout.training = training;
+    }
 }
 
 #[cfg(test)]
@@ -229,7 +527,7 @@ mod tests {
 
     #[test]
     fn test_rnn_cell() {
-        let cell = RNNCell::new(4, 8);
+        let cell: RNNCell = RNNCell::new(4, 8);
         let x = Variable::input(Tensor::rand(vec![2, 4], Some(1)));
         let h = Variable::input(Tensor::zeros(vec![2, 8]));
         let h_new = cell.forward(&x, &h);
@@ -238,7 +536,7 @@ mod tests {
 
     #[test]
     fn test_gru_cell() {
-        let cell = GRUCell::new(4, 8);
+        let cell: GRUCell = GRUCell::new(4, 8);
         let x = Variable::input(Tensor::rand(vec![2, 4], Some(1)));
         let h = Variable::input(Tensor::zeros(vec![2, 8]));
         let h_new = cell.forward(&x, &h);
@@ -247,7 +545,7 @@ mod tests {
 
     #[test]
     fn test_lstm_cell() {
-        let cell = LSTMCell::new(4, 8);
+        let cell: LSTMCell = LSTMCell::new(4, 8);
         let x = Variable::input(Tensor::rand(vec![2, 4], Some(1)));
         let h = Variable::input(Tensor::zeros(vec![2, 8]));
         let c = Variable::input(Tensor::zeros(vec![2, 8]));
@@ -268,4 +566,58 @@ mod tests {
         assert_eq!(outputs.len(), 4);
         assert_eq!(h_final.data.shape_vec(), vec![1, 5]);
     }
+
+    #[test]
+    fn test_gru_cell_f32() {
+        let cell = GRUCell::<f32>::new(4, 8);
+        let x = Variable::input(Tensor::<f32>::rand(vec![2, 4], Some(1)));
+        let h = Variable::input(Tensor::zeros(vec![2, 8]));
+        let h_new = cell.forward(&x, &h);
+        assert_eq!(h_new.data.shape_vec(), vec![2, 8]);
+        assert!(h_new.data.data().iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_bidirectional_packed_gru_matches_cells() {
+        let gru: GRU = GRU::new(4, 3).with_bidirectional(true);
+        let seqs: Vec<Variable> = [2, 4].iter().map(|&len| Variable::input(Tensor::rand(vec![len, 4], Some(len as u64)))).collect();
+        let (out, finals) = gru.forward_packed(&PackedSequence::pack(&seqs), None);
+        assert_eq!(finals[0].shape_vec(), vec![2, 2, 3]);
+        let (outputs, _) = out.unpack();
+        assert_eq!(outputs[0].shape_vec(), vec![2, 6]);
+
+        // Sequence 0 alone: forward over its 2 steps, backward over them reversed.
+        let steps: Vec<Variable> = (0..2).map(|t| seqs[0].slice(0, t, t + 1)).collect();
+        let reversed: Vec<Variable> = steps.iter().rev().cloned().collect();
+        let h0 = Variable::input(Tensor::zeros(vec![1, 3]));
+        let forward = gru.layers[0][0].forward_seq(&steps, &h0);
+        let backward = gru.layers[0][1].forward_seq(&reversed, &h0);
+        let close = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-12);
+        assert!(close(&finals[0].data.data()[..3], forward[1].data.data()));
+        assert!(close(&finals[0].data.data()[6..9], backward[1].data.data()));
+        // The backward half of step 0 is the backward direction's final state.
+        assert!(close(&outputs[0].data.data()[3..6], backward[1].data.data()));
+        assert!(outputs[2].data.data()[..6].iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn test_stacked_bidirectional_lstm_layer() {
+        let mut lstm: LSTM = LSTM::new(4, 3).with_num_layers(2).with_bidirectional(true).with_dropout(0.5);
+        lstm.set_training(true);
+        let x = Variable::input(Tensor::rand(vec![5, 2, 4], Some(3)));
+        let y = lstm.forward(&x);
+        assert_eq!(y.shape_vec(), vec![5, 2, 6]);
+        assert_eq!(lstm.layers[1][0].input_size, 6);
+        assert_eq!(lstm.parameters().len(), 4 * 12);
+        assert_eq!(lstm.named_parameters()[0].0, "layers.0.forward.w_i_x");
+        assert_eq!(lstm.named_parameters()[12].0, "layers.0.backward.w_i_x");
+
+        let h0 = Variable::input(Tensor::rand(vec![4, 2, 3], Some(4)));
+        let steps: Vec<Variable> = (0..5).map(|t| x.slice(0, t, t + 1).reshape(vec![2, 4])).collect();
+        let (outputs, finals) = lstm.forward_seq(&steps, Some(&[h0.clone(), h0]));
+        assert_eq!((outputs.len(), finals.len()), (5, 2));
+        assert_eq!(finals[1].shape_vec(), vec![4, 2, 3]);
+        y.sum().backward();
+        assert!(lstm.layers[0][1].w_i_x.grad().is_some());
+    }
 }
@@ -0,0 +1,242 @@
+//! Variable-length batches for the recurrent cells.
+//!
+//! The cells' `forward_seq` takes one `[batch, features]` step per time
+//! step, so a batch of sequences of different lengths is first padded to
+//! the longest one (`pad_sequences`). A `PackedSequence` goes further and
+//! drops the padding: sequences are sorted by decreasing length and step
+//! `t` holds only the `batch_sizes[t]` sequences still running, so the
+//! cells' `forward_packed` never computes on padding and returns each
+//! sequence's hidden state at its own last step.
+
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::Variable;
+
+use crate::attention::padding_mask;
+
+/// Pad sequences of shape `[len_i, features]` with `padding_value` into
+/// `max_len` time steps of shape `[batch, features]`.
+pub fn pad_sequences<T: Float>(sequences: &[Variable<T>], padding_value: f64) -> Vec<Variable<T>> {
+    let first = sequences.first().expect("pad_sequences: no sequences");
+    let features = first.shape_vec()[1];
+    let max_len = sequences.iter().map(|s| s.shape_vec()[0]).max().unwrap_or(0);
+    let padding = first.constant(Tensor::full(vec![1, features], T::from_f64(padding_value)));
+    (0..max_len)
+        .map(|t| {
+            let rows: Vec<Variable<T>> = sequences
+                .iter()
+                .map(|s| if t < s.shape_vec()[0] { s.slice(0, t, t + 1) } else { padding.clone() })
+                .collect();
+            Variable::concat(&rows.iter().collect::<Vec<_>>(), 0)
+        })
+        .collect()
+}
+
+/// `[batch, max_len]` mask of ones at real steps and zeros at padding.
+pub fn length_mask<T: Float>(lengths: &[usize], max_len: usize) -> Tensor<T> {
+    let data = lengths
+        .iter()
+        .flat_map(|&len| (0..max_len).map(move |t| if t < len { T::ONE } else { T::ZERO }))
+        .collect();
+    Tensor::new(data, vec![lengths.len(), max_len]).expect("length mask")
+}
+
+/// One `[max_len, max_len]` self-attention mask per sequence, hiding its
+/// padded positions from `MultiHeadAttention::attend`.
+pub fn attention_masks<T: Float>(lengths: &[usize], max_len: usize) -> Vec<Tensor<T>> {
+    lengths
+        .iter()
+        .map(|&len| padding_mask(max_len, &(0..max_len).map(|t| t < len).collect::<Vec<_>>()))
+        .collect()
+}
+
+/// A batch of variable-length sequences without padding.
+#[derive(Clone)]
+pub struct PackedSequence<T: Float = f64> {
+    /// Step `t`: `[batch_sizes[t], features]`, rows in sorted order.
+    pub steps: Vec<Variable<T>>,
+    /// Sequences still running at each step; non-increasing.
+    pub batch_sizes: Vec<usize>,
+    /// `sorted_indices[k]` is the batch index of the `k`-th longest sequence.
+    pub sorted_indices: Vec<usize>,
+    /// Length of each sequence, in batch order.
+    pub lengths: Vec<usize>,
+}
+
+impl<T: Float> PackedSequence<T> {
+    /// Pack sequences of shape `[len_i, features]`, each at least one step
+    /// long.
+    pub fn pack(sequences: &[Variable<T>]) -> Self {
+        let lengths: Vec<usize> = sequences.iter().map(|s| s.shape_vec()[0]).collect();
+        Self::from_padded(&pad_sequences(sequences, 0.0), &lengths)
+    }
+
+    /// Pack `[batch, features]` steps (as from `pad_sequences`) given each
+    /// sequence's real length.
+    pub fn from_padded(steps: &[Variable<T>], lengths: &[usize]) -> Self {
+        assert!(lengths.iter().all(|&len| len >= 1), "PackedSequence: empty sequence");
+        assert!(
+            lengths.iter().all(|&len| len <= steps.len()),
+            "PackedSequence: a length exceeds the {} padded steps",
+            steps.len()
+        );
+        let mut sorted_indices: Vec<usize> = (0..lengths.len()).collect();
+        // Stable, so equal lengths keep their batch order.
+        sorted_indices.sort_by(|&a, &b| lengths[b].cmp(&lengths[a]));
+        let max_len = lengths.iter().copied().max().unwrap_or(0);
+        let batch_sizes: Vec<usize> = (0..max_len).map(|t| lengths.iter().filter(|&&len| len > t).count()).collect();
+        let steps = steps
+            .iter()
+            .zip(&batch_sizes)
+            .map(|(step, &bs)| step.index_select(0, &sorted_indices[..bs]))
+            .collect();
+        PackedSequence { steps, batch_sizes, sorted_indices, lengths: lengths.to_vec() }
+    }
+
+    /// Back to `[batch, features]` steps in batch order, zeros at padding,
+    /// with each sequence's length.
+    pub fn unpack(&self) -> (Vec<Variable<T>>, Vec<usize>) {
+        let batch = self.batch_size();
+        let steps = self
+            .steps
+            .iter()
+            .zip(&self.batch_sizes)
+            .map(|(step, &bs)| step.index_add(0, &self.sorted_indices[..bs], batch))
+            .collect();
+        (steps, self.lengths.clone())
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.batch_sizes.len()
+    }
+
+    /// Every sequence reversed in time within its own length, for the
+    /// backward direction of a bidirectional RNN. Its own inverse.
+    pub fn reversed(&self) -> Self {
+        let sorted_lengths: Vec<usize> = self.sorted_indices.iter().map(|&i| self.lengths[i]).collect();
+        let steps = self
+            .batch_sizes
+            .iter()
+            .enumerate()
+            .map(|(t, &bs)| {
+                let rows: Vec<Variable<T>> =
+                    (0..bs).map(|k| self.steps[sorted_lengths[k] - 1 - t].slice(0, k, k + 1)).collect();
+                Variable::concat(&rows.iter().collect::<Vec<_>>(), 0)
+            })
+            .collect();
+        PackedSequence { steps, ..self.clone() }
+    }
+
+    /// Rows of a `[batch, n]` tensor-valued variable reordered into sorted
+    /// order, as for an initial hidden state.
+    pub(crate) fn sort_rows(&self, v: &Variable<T>) -> Variable<T> {
+        v.index_select(0, &self.sorted_indices)
+    }
+
+    /// The reverse of `sort_rows`.
+    pub(crate) fn unsort_rows(&self, v: &Variable<T>) -> Variable<T> {
+        v.index_add(0, &self.sorted_indices, self.batch_size())
+    }
+
+    /// Run a recurrence over the packed steps. `state` holds `[batch, n]`
+    /// tensors in batch order (`[h]` or `[h, c]`); `step` maps an input step
+    /// and the running rows of the state to the new state, whose first entry
+    /// is the output. Returns the packed outputs and each sequence's state
+    /// after its last step, in batch order.
+    pub(crate) fn scan<F>(&self, state: &[Variable<T>], mut step: F) -> (PackedSequence<T>, Vec<Variable<T>>)
+    where
+        F: FnMut(&Variable<T>, &[Variable<T>]) -> Vec<Variable<T>>,
+    {
+        let mut running: Vec<Variable<T>> = state.iter().map(|s| self.sort_rows(s)).collect();
+        // Rows of sequences that have ended, latest-finished first.
+        let mut finished: Vec<Vec<Variable<T>>> = vec![Vec::new(); state.len()];
+        let mut outputs = Vec::with_capacity(self.max_len());
+        for (x, &bs) in self.steps.iter().zip(&self.batch_sizes) {
+            let rows = running[0].shape_vec()[0];
+            if bs < rows {
+                for (s, done) in running.iter_mut().zip(&mut finished) {
+                    done.insert(0, s.slice(0, bs, rows));
+                    *s = s.slice(0, 0, bs);
+                }
+            }
+            running = step(x, &running);
+            outputs.push(running[0].clone());
+        }
+        let finals = running
+            .into_iter()
+            .zip(finished)
+            .map(|(s, done)| {
+                let parts: Vec<&Variable<T>> = std::iter::once(&s).chain(&done).collect();
+                self.unsort_rows(&Variable::concat(&parts, 0))
+            })
+            .collect();
+        let packed = PackedSequence {
+            steps: outputs,
+            batch_sizes: self.batch_sizes.clone(),
+            sorted_indices: self.sorted_indices.clone(),
+            lengths: self.lengths.clone(),
+        };
+        (packed, finals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GRUCell, LSTMCell};
+
+    fn sequences() -> Vec<Variable> {
+        [2, 5, 3].iter().map(|&len| Variable::input(Tensor::rand(vec![len, 4], Some(len as u64)))).collect()
+    }
+
+    #[test]
+    fn test_pad_pack_and_masks() {
+        let seqs = sequences();
+        let padded = pad_sequences(&seqs, -1.0);
+        assert_eq!(padded.len(), 5);
+        assert_eq!(padded[3].shape_vec(), vec![3, 4]);
+        assert_eq!(padded[3].data.data()[0], -1.0);
+
+        let packed = PackedSequence::pack(&seqs);
+        assert_eq!(packed.sorted_indices, vec![1, 2, 0]);
+        assert_eq!(packed.batch_sizes, vec![3, 3, 2, 1, 1]);
+        let (steps, lengths) = packed.unpack();
+        assert_eq!(lengths, vec![2, 5, 3]);
+        assert_eq!(steps[1].data.data(), padded[1].data.data());
+        assert!(steps[2].data.data()[..4].iter().all(|&v| v == 0.0));
+
+        let mask: Tensor<f64> = length_mask(&lengths, 5);
+        assert_eq!(&mask.data()[..5], &[1.0, 1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(attention_masks::<f64>(&lengths, 5)[2].data()[3], 0.0);
+    }
+
+    #[test]
+    fn test_packed_final_states_match_unbatched_runs() {
+        let seqs = sequences();
+        let packed = PackedSequence::pack(&seqs);
+        let gru: GRUCell = GRUCell::new(4, 3);
+        let lstm: LSTMCell = LSTMCell::new(4, 3);
+        let zeros = |rows| Variable::input(Tensor::zeros(vec![rows, 3]));
+        let (out, h) = gru.forward_packed(&packed, &zeros(3));
+        let (_, h_lstm, c_lstm) = lstm.forward_packed(&packed, &zeros(3), &zeros(3));
+        assert_eq!(out.max_len(), 5);
+
+        for (i, seq) in seqs.iter().enumerate() {
+            let len = seq.shape_vec()[0];
+            let steps: Vec<Variable> = (0..len).map(|t| seq.slice(0, t, t + 1)).collect();
+            let alone = gru.forward_seq(&steps, &zeros(1));
+            let (_, h1, c1) = lstm.forward_seq(&steps, &zeros(1), &zeros(1));
+            for (packed, single) in [(&h, &alone[len - 1]), (&h_lstm, &h1), (&c_lstm, &c1)] {
+                let row = &packed.data.data()[i * 3..(i + 1) * 3];
+                for (a, b) in row.iter().zip(single.data.data()) {
+                    assert!((a - b).abs() < 1e-12);
+                }
+            }
+        }
+        h.sum().backward();
+        assert!(gru.w_z_x.grad().is_some());
+    }
+}
@@ -1,24 +1,27 @@
 use crate::layers::Layer;
+use crate::state_dict::NamedParameters;
 use oxidize_ml_autodiff::Variable;
+use oxidize_ml_core::{Float, Tensor};
 
 /// Sequential model — chains layers in order.
-pub struct Sequential {
-    layers: Vec<Box<dyn Layer>>,
+pub struct Sequential<T: Float = f64> {
+    layers: Vec<Box<dyn Layer<T>>>,
 }
 
-impl Sequential {
+impl<T: Float> Sequential<T> {
     pub fn new() -> Self {
         Sequential { layers: Vec::new() }
     }
 
     /// Add a layer to the model.
-    pub fn add(mut self, layer: Box<dyn Layer>) -> Self {
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, layer: Box<dyn Layer<T>>) -> Self {
         self.layers.push(layer);
         self
     }
 
     /// Forward pass through all layers.
-    pub fn forward(&self, input: &Variable) -> Variable {
+    pub fn forward(&self, input: &Variable<T>) -> Variable<T> {
         let mut x = input.clone();
         for layer in &self.layers {
             x = layer.forward(&x);
@@ -26,8 +29,13 @@ impl Sequential {
         x
     }
 
+    /// The layers, in forward order.
+    pub fn layers(&self) -> &[Box<dyn Layer<T>>] {
+        &self.layers
+    }
+
     /// Collect all trainable parameters from all layers.
-    pub fn parameters(&self) -> Vec<Variable> {
+    pub fn parameters(&self) -> Vec<Variable<T>> {
         let mut params = Vec::new();
         for layer in &self.layers {
             params.extend(layer.parameters());
@@ -36,7 +44,57 @@ impl Sequential {
     }
 }
 
-impl Default for Sequential {
+/// Lets a `Sequential` nest inside other models; its parameters are named
+/// by layer position (`0.weight`, `2.bias`).
+impl<T: Float> Layer<T> for Sequential<T> {
+    fn forward(&self, input: &Variable<T>) -> Variable<T> {
+        Sequential::forward(self, input)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        Sequential::parameters(self)
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .fold(NamedParameters::new(), |acc, (i, layer)| acc.register_module(&i.to_string(), layer.named_parameters()))
+            .into_vec()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .fold(NamedParameters::new(), |acc, (i, layer)| acc.register_module(&i.to_string(), layer.named_parameters_mut()))
+            .into_vec()
+    }
+
+    fn named_buffers(&self) -> Vec<(String, Tensor<T>)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .fold(NamedParameters::new(), |acc, (i, layer)| acc.register_module(&i.to_string(), layer.named_buffers()))
+            .into_vec()
+    }
+
+    fn set_buffer(&mut self, name: &str, value: Tensor<T>) -> bool {
+        let Some((index, rest)) = name.split_once('.') else { return false };
+        match index.parse::<usize>().ok().and_then(|i| self.layers.get_mut(i)) {
+            Some(layer) => layer.set_buffer(rest, value),
+            None => false,
+        }
+    }
+
+    fn set_training(&mut self, training: bool) {
+        for layer in &mut self.layers {
+            layer.set_training(training);
+        }
+    }
+}
+
+impl<T: Float> Default for Sequential<T> {
     fn default() -> Self {
         Self::new()
     }
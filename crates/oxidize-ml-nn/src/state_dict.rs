@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use oxidize_ml_core::Tensor;
+
+/// Parameter tensors keyed by their dotted path within a layer
+/// (`encoder.0.weight`), as produced by `Layer::state_dict`.
+pub type StateDict<T = f64> = BTreeMap<String, Tensor<T>>;
+
+/// Outcome of `Layer::load_state_dict`: keys the layer expected but the
+/// state dict lacked, and keys the state dict had but the layer doesn't.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    pub missing_keys: Vec<String>,
+    pub unexpected_keys: Vec<String>,
+}
+
+impl LoadReport {
+    /// True when every key matched on both sides.
+    pub fn is_exact(&self) -> bool {
+        self.missing_keys.is_empty() && self.unexpected_keys.is_empty()
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_exact() {
+            return write!(f, "all keys matched");
+        }
+        let mut parts = Vec::new();
+        if !self.missing_keys.is_empty() {
+            parts.push(format!("missing keys: [{}]", self.missing_keys.join(", ")));
+        }
+        if !self.unexpected_keys.is_empty() {
+            parts.push(format!("unexpected keys: [{}]", self.unexpected_keys.join(", ")));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// Builds the `(name, parameter)` list of a layer: its own parameters, then
+/// its submodules' under `name.`.
+///
+/// `V` is `Variable<T>` for `named_parameters` and `&mut Variable<T>` for
+/// `named_parameters_mut`.
+pub struct NamedParameters<V> {
+    entries: Vec<(String, V)>,
+}
+
+impl<V> NamedParameters<V> {
+    pub fn new() -> Self {
+        NamedParameters { entries: Vec::new() }
+    }
+
+    /// Add a parameter of this layer.
+    pub fn register_parameter(mut self, name: &str, param: V) -> Self {
+        self.entries.push((name.to_string(), param));
+        self
+    }
+
+    /// Add a submodule's named parameters under `name`.
+    pub fn register_module(mut self, name: &str, params: Vec<(String, V)>) -> Self {
+        self.entries
+            .extend(params.into_iter().map(|(inner, p)| (format!("{}.{}", name, inner), p)));
+        self
+    }
+
+    pub fn into_vec(self) -> Vec<(String, V)> {
+        self.entries
+    }
+}
+
+impl<V> Default for NamedParameters<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `(field name, parameter)` pairs for a list of `Variable` fields; `mut`
+/// gives mutable references.
+macro_rules! named_fields {
+    (mut $self:ident; $($field:ident),* $(,)?) => {
+        vec![$((stringify!($field).to_string(), &mut $self.$field)),*]
+    };
+    ($self:ident; $($field:ident),* $(,)?) => {
+        vec![$((stringify!($field).to_string(), $self.$field.clone())),*]
+    };
+}
+pub(crate) use named_fields;
+
+#[cfg(test)]
+mod tests {
+    use crate::attention::TransformerBlock;
+    use crate::layers::{Layer, Linear, ReLULayer};
+    use crate::sequential::Sequential;
+
+    #[test]
+    fn test_state_dict_round_trip_and_freezing() {
+        let model = Sequential::<f64>::new()
+            .add(Box::new(Linear::new(3, 4)))
+            .add(Box::new(ReLULayer::new()))
+            .add(Box::new(Linear::new(4, 2)));
+        let names: Vec<String> = model.named_parameters().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, ["0.weight", "0.bias", "2.weight", "2.bias"]);
+
+        let block = TransformerBlock::<f64>::new(4, 2, 8);
+        let names: Vec<String> = block.named_parameters().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names[0], "attention.w_q.weight");
+        assert_eq!(names.last().unwrap(), "norm2.beta");
+
+        // Loading into a fresh model reproduces the weights without new nodes.
+        let mut other = Sequential::<f64>::new()
+            .add(Box::new(Linear::new(3, 4)))
+            .add(Box::new(ReLULayer::new()))
+            .add(Box::new(Linear::new(4, 2)));
+        let ids: Vec<_> = other.parameters().iter().map(|p| p.node_id).collect();
+        let report = other.load_state_dict(&model.state_dict(), true).unwrap();
+        assert!(report.is_exact());
+        assert_eq!(other.state_dict(), model.state_dict());
+        assert_eq!(other.parameters().iter().map(|p| p.node_id).collect::<Vec<_>>(), ids);
+
+        // Missing keys fail a strict load and are reported by a lenient one.
+        let mut partial = model.state_dict();
+        partial.remove("2.bias");
+        assert!(other.load_state_dict(&partial, true).is_err());
+        let report = other.load_state_dict(&partial, false).unwrap();
+        assert_eq!(report.missing_keys, ["2.bias"]);
+
+        // Frozen parameters collect no gradient.
+        for (name, p) in other.named_parameters() {
+            p.set_requires_grad(!name.starts_with("0."));
+        }
+        let x = other.parameters()[0].constant(oxidize_ml_core::Tensor::ones(vec![2, 3]));
+        other.forward(&x).sum().backward();
+        let grads: Vec<bool> = other.parameters().iter().map(|p| p.grad().is_some()).collect();
+        assert_eq!(grads, [false, false, true, true]);
+    }
+}
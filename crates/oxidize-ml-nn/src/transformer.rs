@@ -0,0 +1,406 @@
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::Variable;
+use crate::attention::{causal_mask, MultiHeadAttention, TransformerBlock};
+use crate::init;
+use crate::layers::{Layer, Linear};
+use crate::norm::LayerNorm;
+use crate::state_dict::NamedParameters;
+
+/// Position table of a `PositionalEncoding`.
+pub enum Positions<T: Float = f64> {
+    /// Fixed `sin`/`cos` features of geometrically spaced frequencies.
+    Sinusoidal(Tensor<T>),
+    /// A trainable `[max_len, d_model]` table.
+    Learned(Variable<T>),
+}
+
+/// Adds a position-dependent vector to each row of a `[seq_len, d_model]`
+/// sequence, for `seq_len <= max_len`.
+pub struct PositionalEncoding<T: Float = f64> {
+    pub max_len: usize,
+    pub d_model: usize,
+    pub positions: Positions<T>,
+}
+
+impl<T: Float> PositionalEncoding<T> {
+    /// `PE[p, 2i] = sin(p / 10000^(2i/d))`, `PE[p, 2i+1] = cos(p / 10000^(2i/d))`.
+    pub fn sinusoidal(max_len: usize, d_model: usize) -> Self {
+        let data = (0..max_len * d_model)
+            .map(|idx| {
+                let (pos, i) = (idx / d_model, idx % d_model);
+                let angle = pos as f64 / 10000f64.powf((i - i % 2) as f64 / d_model as f64);
+                T::from_f64(if i % 2 == 0 { angle.sin() } else { angle.cos() })
+            })
+            .collect();
+        let table = Tensor::new(data, vec![max_len, d_model]).expect("positional table");
+        PositionalEncoding { max_len, d_model, positions: Positions::Sinusoidal(table) }
+    }
+
+    /// A learned table initialized from `N(0, 0.02²)`.
+    pub fn learned(max_len: usize, d_model: usize) -> Self {
+        let table = Variable::param(init::normal(vec![max_len, d_model], 0.0, 0.02));
+        PositionalEncoding { max_len, d_model, positions: Positions::Learned(table) }
+    }
+}
+
+impl<T: Float> Layer<T> for PositionalEncoding<T> {
+    fn forward(&self, x: &Variable<T>) -> Variable<T> {
+        let len = x.shape_vec()[0];
+        assert!(len <= self.max_len, "sequence of {} exceeds max_len {}", len, self.max_len);
+        match &self.positions {
+            Positions::Sinusoidal(table) => x.add(&x.constant(table.slice_rows(0, len).expect("positional rows"))),
+            Positions::Learned(table) => x.add(&table.slice(0, 0, len)),
+        }
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        match &self.positions {
+            Positions::Sinusoidal(_) => vec![],
+            Positions::Learned(table) => vec![table.clone()],
+        }
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        self.parameters().into_iter().map(|p| ("weight".to_string(), p)).collect()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        match &mut self.positions {
+            Positions::Sinusoidal(_) => vec![],
+            Positions::Learned(table) => vec![("weight".to_string(), table)],
+        }
+    }
+}
+
+/// Transformer decoder block (post-norm): causal self-attention,
+/// cross-attention over the encoder output (`memory`) and a ReLU
+/// feed-forward network, each in a residual connection followed by
+/// LayerNorm.
+pub struct TransformerDecoderBlock<T: Float = f64> {
+    pub self_attention: MultiHeadAttention<T>,
+    pub norm1: LayerNorm<T>,
+    pub cross_attention: MultiHeadAttention<T>,
+    pub norm2: LayerNorm<T>,
+    pub ff1: Linear<T>,
+    pub ff2: Linear<T>,
+    pub norm3: LayerNorm<T>,
+}
+
+impl<T: Float> TransformerDecoderBlock<T> {
+    pub fn new(d_model: usize, num_heads: usize, d_ff: usize) -> Self {
+        TransformerDecoderBlock {
+            self_attention: MultiHeadAttention::new(d_model, num_heads),
+            norm1: LayerNorm::new(d_model),
+            cross_attention: MultiHeadAttention::new(d_model, num_heads),
+            norm2: LayerNorm::new(d_model),
+            ff1: Linear::new(d_model, d_ff),
+            ff2: Linear::new(d_ff, d_model),
+            norm3: LayerNorm::new(d_model),
+        }
+    }
+
+    /// Decode the `[tgt_len, d_model]` targets `x`. Self-attention is
+    /// causal, further restricted by `self_mask`; without `memory` the
+    /// cross-attention sublayer is skipped (decoder-only use).
+    pub fn decode(
+        &self,
+        x: &Variable<T>,
+        memory: Option<&Variable<T>>,
+        self_mask: Option<&Tensor<T>>,
+        memory_mask: Option<&Tensor<T>>,
+    ) -> Variable<T> {
+        let mut mask = causal_mask(x.shape_vec()[0]);
+        if let Some(self_mask) = self_mask {
+            mask = mask.mul(self_mask).expect("self mask must be [tgt_len, tgt_len]");
+        }
+        let mut y = self.norm1.forward(&x.add(&self.self_attention.attend(x, x, Some(&mask))));
+        if let Some(memory) = memory {
+            y = self.norm2.forward(&y.add(&self.cross_attention.attend(&y, memory, memory_mask)));
+        }
+        self.norm3.forward(&y.add(&self.ff2.forward(&self.ff1.forward(&y).relu())))
+    }
+
+    fn modules(&self) -> [(&'static str, &dyn Layer<T>); 7] {
+        [
+            ("self_attention", &self.self_attention),
+            ("norm1", &self.norm1),
+            ("cross_attention", &self.cross_attention),
+            ("norm2", &self.norm2),
+            ("ff1", &self.ff1),
+            ("ff2", &self.ff2),
+            ("norm3", &self.norm3),
+        ]
+    }
+}
+
+/// Decoder-only: `decode` without memory.
+impl<T: Float> Layer<T> for TransformerDecoderBlock<T> {
+    fn forward(&self, x: &Variable<T>) -> Variable<T> {
+        self.decode(x, None, None, None)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        self.modules().iter().flat_map(|(_, m)| m.parameters()).collect()
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        self.modules()
+            .iter()
+            .fold(NamedParameters::new(), |acc, (name, m)| acc.register_module(name, m.named_parameters()))
+            .into_vec()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        NamedParameters::new()
+            .register_module("self_attention", self.self_attention.named_parameters_mut())
+            .register_module("norm1", self.norm1.named_parameters_mut())
+            .register_module("cross_attention", self.cross_attention.named_parameters_mut())
+            .register_module("norm2", self.norm2.named_parameters_mut())
+            .register_module("ff1", self.ff1.named_parameters_mut())
+            .register_module("ff2", self.ff2.named_parameters_mut())
+            .register_module("norm3", self.norm3.named_parameters_mut())
+            .into_vec()
+    }
+}
+
+/// A stack of `TransformerBlock`s sharing one attention mask.
+pub struct TransformerEncoder<T: Float = f64> {
+    pub layers: Vec<TransformerBlock<T>>,
+}
+
+impl<T: Float> TransformerEncoder<T> {
+    pub fn new(num_layers: usize, d_model: usize, num_heads: usize, d_ff: usize) -> Self {
+        TransformerEncoder { layers: (0..num_layers).map(|_| TransformerBlock::new(d_model, num_heads, d_ff)).collect() }
+    }
+
+    /// Encode `[seq_len, d_model]`, every layer's self-attention restricted
+    /// by `mask` (e.g. a `padding_mask`).
+    pub fn forward_masked(&self, x: &Variable<T>, mask: Option<&Tensor<T>>) -> Variable<T> {
+        self.layers.iter().fold(x.clone(), |x, layer| layer.forward_masked(&x, mask))
+    }
+}
+
+impl<T: Float> Layer<T> for TransformerEncoder<T> {
+    fn forward(&self, x: &Variable<T>) -> Variable<T> {
+        self.forward_masked(x, None)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        self.layers.iter().flat_map(|l| l.parameters()).collect()
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .fold(NamedParameters::new(), |acc, (i, l)| acc.register_module(&format!("layers.{}", i), l.named_parameters()))
+            .into_vec()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .fold(NamedParameters::new(), |acc, (i, l)| acc.register_module(&format!("layers.{}", i), l.named_parameters_mut()))
+            .into_vec()
+    }
+}
+
+/// A stack of `TransformerDecoderBlock`s attending to the same memory.
+pub struct TransformerDecoder<T: Float = f64> {
+    pub layers: Vec<TransformerDecoderBlock<T>>,
+}
+
+impl<T: Float> TransformerDecoder<T> {
+    pub fn new(num_layers: usize, d_model: usize, num_heads: usize, d_ff: usize) -> Self {
+        TransformerDecoder {
+            layers: (0..num_layers).map(|_| TransformerDecoderBlock::new(d_model, num_heads, d_ff)).collect(),
+        }
+    }
+
+    /// Run every block's `decode` with the same memory and masks.
+    pub fn decode(
+        &self,
+        x: &Variable<T>,
+        memory: Option<&Variable<T>>,
+        self_mask: Option<&Tensor<T>>,
+        memory_mask: Option<&Tensor<T>>,
+    ) -> Variable<T> {
+        self.layers.iter().fold(x.clone(), |x, layer| layer.decode(&x, memory, self_mask, memory_mask))
+    }
+}
+
+/// Decoder-only: `decode` without memory.
+impl<T: Float> Layer<T> for TransformerDecoder<T> {
+    fn forward(&self, x: &Variable<T>) -> Variable<T> {
+        self.decode(x, None, None, None)
+    }
+
+    fn parameters(&self) -> Vec<Variable<T>> {
+        self.layers.iter().flat_map(|l| l.parameters()).collect()
+    }
+
+    fn named_parameters(&self) -> Vec<(String, Variable<T>)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .fold(NamedParameters::new(), |acc, (i, l)| acc.register_module(&format!("layers.{}", i), l.named_parameters()))
+            .into_vec()
+    }
+
+    fn named_parameters_mut(&mut self) -> Vec<(String, &mut Variable<T>)> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .fold(NamedParameters::new(), |acc, (i, l)| acc.register_module(&format!("layers.{}", i), l.named_parameters_mut()))
+            .into_vec()
+    }
+}
+
+/// Search strategy of `generate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoding {
+    /// Take the most likely token at every step.
+    Greedy,
+    /// Keep the `width` most likely sequences at every step.
+    Beam { width: usize },
+}
+
+/// Options of `generate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationConfig {
+    pub max_new_tokens: usize,
+    /// A sequence that emits this token is finished.
+    pub eos_token: Option<usize>,
+    pub decoding: Decoding,
+}
+
+impl GenerationConfig {
+    /// Greedy decoding of up to `max_new_tokens` tokens, with no end token.
+    pub fn new(max_new_tokens: usize) -> Self {
+        GenerationConfig { max_new_tokens, eos_token: None, decoding: Decoding::Greedy }
+    }
+
+    pub fn with_eos(mut self, eos_token: usize) -> Self {
+        self.eos_token = Some(eos_token);
+        self
+    }
+
+    pub fn with_beam(mut self, width: usize) -> Self {
+        assert!(width > 0, "beam width must be positive");
+        self.decoding = Decoding::Beam { width };
+        self
+    }
+}
+
+/// Extend `prompt` token by token. `next_logits` maps a sequence to the
+/// (unnormalized) logits of its next token, e.g. the last row of a
+/// decoder's output projection.
+///
+/// Sequences are ranked by their summed log-probabilities, with no length
+/// penalty; greedy decoding is a beam of width 1. Runs under `no_grad`, so
+/// nothing is recorded on the graph. Returns the prompt followed by the
+/// best sequence's new tokens (ending at `eos_token` if it was emitted).
+pub fn generate<T: Float>(
+    prompt: &[usize],
+    config: &GenerationConfig,
+    mut next_logits: impl FnMut(&[usize]) -> Tensor<T>,
+) -> Vec<usize> {
+    let width = match config.decoding {
+        Decoding::Greedy => 1,
+        Decoding::Beam { width } => width,
+    };
+    let finished = |seq: &[usize]| seq.len() > prompt.len() && config.eos_token.is_some_and(|eos| seq.last() == Some(&eos));
+    // (sequence, summed log-probability)
+    let mut beams: Vec<(Vec<usize>, f64)> = vec![(prompt.to_vec(), 0.0)];
+    oxidize_ml_autodiff::no_grad(|| {
+        for _ in 0..config.max_new_tokens {
+            if beams.iter().all(|(seq, _)| finished(seq)) {
+                break;
+            }
+            let mut candidates = Vec::new();
+            for (seq, score) in &beams {
+                if finished(seq) {
+                    candidates.push((seq.clone(), *score));
+                    continue;
+                }
+                let logits: Vec<f64> = next_logits(seq).data().iter().map(|v| v.to_f64()).collect();
+                let max = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let log_norm = max + logits.iter().map(|l| (l - max).exp()).sum::<f64>().ln();
+                let mut tokens: Vec<usize> = (0..logits.len()).collect();
+                tokens.sort_by(|&a, &b| logits[b].total_cmp(&logits[a]));
+                for &token in tokens.iter().take(width) {
+                    let mut next = seq.clone();
+                    next.push(token);
+                    candidates.push((next, score + logits[token] - log_norm));
+                }
+            }
+            // Stable, so ties keep the earlier (higher-ranked) candidate.
+            candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+            candidates.truncate(width);
+            beams = candidates;
+        }
+    });
+    beams.swap_remove(0).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attention::padding_mask;
+    use oxidize_ml_autodiff::backward::backward;
+
+    #[test]
+    fn test_encoder_decoder_masks_and_gradients() {
+        let pe = PositionalEncoding::<f64>::sinusoidal(8, 4);
+        let zeros = Variable::input(Tensor::zeros(vec![3, 4]));
+        let encoded = pe.forward(&zeros).data;
+        // Position 0 is [sin 0, cos 0, sin 0, cos 0]; position 1 starts with sin 1.
+        assert_eq!(&encoded.data()[..4], &[0.0, 1.0, 0.0, 1.0]);
+        assert!((encoded.data()[4] - 1f64.sin()).abs() < 1e-12);
+
+        let encoder = TransformerEncoder::<f64>::new(2, 4, 2, 8);
+        let decoder = TransformerDecoder::<f64>::new(2, 4, 2, 8);
+        let source = Variable::input(Tensor::rand(vec![5, 4], Some(1)));
+        // The padded last source token does not affect the encoding of the rest.
+        let keep = padding_mask(5, &[true, true, true, true, false]);
+        let memory = encoder.forward_masked(&source, Some(&keep));
+        let mut altered = source.data.clone();
+        altered.data_mut()[16..].iter_mut().for_each(|v| *v += 10.0);
+        let altered = encoder.forward_masked(&Variable::input(altered), Some(&keep));
+        assert!(memory.data.data()[..16].iter().zip(&altered.data.data()[..16]).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        // Causal: changing the last target leaves earlier outputs unchanged.
+        let target = Tensor::rand(vec![3, 4], Some(2));
+        let memory_mask = padding_mask(3, &[true, true, true, true, false]);
+        let out = decoder.decode(&Variable::input(target.clone()), Some(&memory), None, Some(&memory_mask));
+        let mut later = target.clone();
+        later.data_mut()[8..].iter_mut().for_each(|v| *v -= 5.0);
+        let changed = decoder.decode(&Variable::input(later), Some(&memory), None, Some(&memory_mask));
+        assert!(out.data.data()[..8].iter().zip(&changed.data.data()[..8]).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        let grads = backward(&out.pow(2.0).sum());
+        let cross = &decoder.layers[1].cross_attention.w_k.weight;
+        assert!(grads[&cross.node_id].data().iter().any(|v| v.abs() > 0.0));
+        assert!(grads[&encoder.layers[0].attention.w_q.weight.node_id].data().iter().any(|v| v.abs() > 0.0));
+        assert!(decoder.named_parameters()[0].0.starts_with("layers.0.self_attention.w_q"));
+    }
+
+    #[test]
+    fn test_beam_search_beats_greedy() {
+        // Token 0 ends a sequence. After the prompt, 1 is likelier than 2,
+        // but 1 leads to a flat distribution while 2 almost surely ends.
+        let probs = |seq: &[usize]| -> Tensor<f64> {
+            let p = match seq.last() {
+                Some(1) => [0.34, 0.33, 0.33],
+                Some(2) => [0.9, 0.05, 0.05],
+                _ => [0.0, 0.6, 0.4],
+            };
+            Tensor::from_slice(&p.map(|p: f64| p.max(1e-12).ln()))
+        };
+        let config = GenerationConfig::new(4).with_eos(0);
+        assert_eq!(generate(&[7], &config, probs), vec![7, 1, 0]);
+        assert_eq!(generate(&[7], &config.with_beam(2), probs), vec![7, 2, 0]);
+        assert_eq!(generate(&[7], &GenerationConfig::new(2), probs), vec![7, 1, 0]);
+    }
+}
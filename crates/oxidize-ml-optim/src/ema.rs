@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_autodiff::Variable;
+
+/// Exponential moving average of a model's parameters, keyed by the names
+/// of `Layer::named_parameters`.
+///
+/// ema = decay * ema + (1 - decay) * param
+///
+/// Call `update` after every optimizer step; evaluate with `apply` (the
+/// averaged weights are swapped in) and go back to training with `restore`.
+/// With warmup the decay starts low, `min(decay, (1 + n) / (10 + n))` after
+/// `n` updates, so early weights are not averaged in for long.
+pub struct EMA<T: Float = f64> {
+    pub decay: f64,
+    pub warmup: bool,
+    pub num_updates: usize,
+    shadow: HashMap<String, Tensor<T>>,
+    backup: Option<HashMap<String, Tensor<T>>>,
+}
+
+impl<T: Float> EMA<T> {
+    /// Start the average at the current parameter values.
+    pub fn new(params: Vec<(String, Variable<T>)>, decay: f64) -> Self {
+        assert!((0.0..=1.0).contains(&decay), "EMA: decay must be in [0, 1]");
+        EMA {
+            decay,
+            warmup: false,
+            num_updates: 0,
+            shadow: params.into_iter().map(|(name, p)| (name, p.data)).collect(),
+            backup: None,
+        }
+    }
+
+    pub fn with_warmup(mut self, warmup: bool) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// The decay used by the next `update`.
+    pub fn current_decay(&self) -> f64 {
+        if self.warmup {
+            let n = self.num_updates as f64;
+            self.decay.min((1.0 + n) / (10.0 + n))
+        } else {
+            self.decay
+        }
+    }
+
+    /// Fold the current parameter values into the average.
+    pub fn update(&mut self, params: &[(String, Variable<T>)]) {
+        let decay = T::from_f64(self.current_decay());
+        for (name, p) in params {
+            let shadow = self.shadow.get_mut(name).unwrap_or_else(|| panic!("EMA: unknown parameter '{}'", name));
+            shadow
+                .zip_apply_mut(&p.data, |e, v| decay * e + (T::ONE - decay) * v)
+                .expect("ema update");
+        }
+        self.num_updates += 1;
+    }
+
+    /// The averaged value of `name`.
+    pub fn get(&self, name: &str) -> Option<&Tensor<T>> {
+        self.shadow.get(name)
+    }
+
+    /// Load the averaged weights into `params`, keeping the live ones for
+    /// `restore`.
+    pub fn apply(&mut self, params: Vec<(String, &mut Variable<T>)>) {
+        assert!(self.backup.is_none(), "EMA: weights already applied; call restore first");
+        let mut backup = HashMap::new();
+        for (name, p) in params {
+            if let Some(shadow) = self.shadow.get(&name) {
+                backup.insert(name, p.data.clone());
+                p.set_data(shadow.clone());
+            }
+        }
+        self.backup = Some(backup);
+    }
+
+    /// Put back the weights replaced by `apply`.
+    pub fn restore(&mut self, params: Vec<(String, &mut Variable<T>)>) {
+        let mut backup = self.backup.take().expect("EMA: restore without apply");
+        for (name, p) in params {
+            if let Some(live) = backup.remove(&name) {
+                p.set_data(live);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema_update_apply_and_restore() {
+        let mut w = Variable::param(Tensor::from_slice(&[0.0, 10.0]));
+        let named = |w: &Variable<f64>| vec![("w".to_string(), w.clone())];
+        let mut ema = EMA::new(named(&w), 0.9);
+        w.set_data(Tensor::from_slice(&[1.0, 0.0]));
+        ema.update(&named(&w));
+        ema.update(&named(&w));
+        // 0 → 0.1 → 0.19 and 10 → 9 → 8.1.
+        let avg = ema.get("w").unwrap().data().to_vec();
+        assert!((avg[0] - 0.19).abs() < 1e-12 && (avg[1] - 8.1).abs() < 1e-12);
+
+        ema.apply(vec![("w".to_string(), &mut w)]);
+        assert_eq!(w.data.data(), avg.as_slice());
+        ema.restore(vec![("w".to_string(), &mut w)]);
+        assert_eq!(w.data.data(), &[1.0, 0.0]);
+
+        let warm: EMA = EMA::new(named(&w), 0.999).with_warmup(true);
+        assert!((warm.current_decay() - 0.1).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{BTreeMap, HashMap};
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_autodiff::graph::NodeId;
+use oxidize_ml_autodiff::Variable;
+
+use crate::optimizer::Optimizer;
+
+/// Dynamic loss scaling for training under `autocast`.
+///
+/// Small gradients underflow in 16-bit formats, so the loss is multiplied
+/// by `scale` before backward and the gradients divided by it before the
+/// step. A step whose gradients hold an inf or NaN is skipped and the
+/// scale multiplied by `backoff_factor`; after `growth_interval` clean
+/// steps in a row it is multiplied by `growth_factor`.
+#[derive(Debug, Clone)]
+pub struct GradScaler {
+    pub scale: f64,
+    pub growth_factor: f64,
+    pub backoff_factor: f64,
+    pub growth_interval: usize,
+    /// Clean steps since the scale last changed.
+    pub growth_tracker: usize,
+    /// Steps skipped for non-finite gradients.
+    pub skipped_steps: usize,
+}
+
+impl Default for GradScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GradScaler {
+    pub fn new() -> Self {
+        GradScaler {
+            scale: 65536.0,
+            growth_factor: 2.0,
+            backoff_factor: 0.5,
+            growth_interval: 2000,
+            growth_tracker: 0,
+            skipped_steps: 0,
+        }
+    }
+
+    pub fn with_init_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_growth_factor(mut self, growth_factor: f64) -> Self {
+        self.growth_factor = growth_factor;
+        self
+    }
+
+    pub fn with_backoff_factor(mut self, backoff_factor: f64) -> Self {
+        self.backoff_factor = backoff_factor;
+        self
+    }
+
+    pub fn with_growth_interval(mut self, growth_interval: usize) -> Self {
+        self.growth_interval = growth_interval;
+        self
+    }
+
+    /// The loss to call `backward` on.
+    pub fn scale_loss<T: Float>(&self, loss: &Variable<T>) -> Variable<T> {
+        loss.mul_scalar(self.scale)
+    }
+
+    /// Divide scaled gradients by the scale; `None` if any is inf or NaN.
+    pub fn unscale<T: Float>(&self, grads: &HashMap<NodeId, Tensor<T>>) -> Option<HashMap<NodeId, Tensor<T>>> {
+        let inv = T::from_f64(1.0 / self.scale);
+        let mut unscaled = HashMap::with_capacity(grads.len());
+        for (&id, g) in grads {
+            if g.data().iter().any(|v| !v.to_f64().is_finite()) {
+                return None;
+            }
+            unscaled.insert(id, g.mul_scalar(inv));
+        }
+        Some(unscaled)
+    }
+
+    /// Unscale `grads` and step `optimizer` with them, then adjust the
+    /// scale. Returns the updated parameters, or `None` if the step was
+    /// skipped.
+    pub fn step<T: Float>(
+        &mut self,
+        optimizer: &mut dyn Optimizer<T>,
+        grads: &HashMap<NodeId, Tensor<T>>,
+    ) -> Option<Vec<Tensor<T>>> {
+        let unscaled = self.unscale(grads);
+        self.update(unscaled.is_none());
+        unscaled.map(|g| optimizer.step(&g))
+    }
+
+    /// `step` with the gradients accumulated on `params` by
+    /// `Variable::backward`.
+    pub fn step_params<T: Float>(&mut self, optimizer: &mut dyn Optimizer<T>, params: &[Variable<T>]) -> Option<Vec<Tensor<T>>> {
+        let grads = params.iter().filter_map(|p| p.grad().map(|g| (p.node_id, g))).collect();
+        self.step(optimizer, &grads)
+    }
+
+    /// Back off after an overflow, or count a clean step towards growth.
+    pub fn update(&mut self, found_inf: bool) {
+        if found_inf {
+            self.scale *= self.backoff_factor;
+            self.growth_tracker = 0;
+            self.skipped_steps += 1;
+        } else {
+            self.growth_tracker += 1;
+            if self.growth_tracker >= self.growth_interval {
+                self.scale *= self.growth_factor;
+                self.growth_tracker = 0;
+            }
+        }
+    }
+
+    /// The scale and counters, for checkpoints.
+    pub fn state(&self) -> BTreeMap<String, f64> {
+        BTreeMap::from([
+            ("scale".to_string(), self.scale),
+            ("growth_tracker".to_string(), self.growth_tracker as f64),
+            ("skipped_steps".to_string(), self.skipped_steps as f64),
+        ])
+    }
+
+    pub fn load_state(&mut self, state: &BTreeMap<String, f64>) -> TensorResult<()> {
+        let get = |key: &str| {
+            state
+                .get(key)
+                .copied()
+                .ok_or_else(|| TensorError::InvalidOperation(format!("grad scaler state has no '{}'", key)))
+        };
+        let (scale, growth_tracker, skipped_steps) = (get("scale")?, get("growth_tracker")?, get("skipped_steps")?);
+        self.scale = scale;
+        self.growth_tracker = growth_tracker as usize;
+        self.skipped_steps = skipped_steps as usize;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizer::SGD;
+    use oxidize_ml_autodiff::Graph;
+
+    #[test]
+    fn test_grad_scaler_skips_overflow_and_grows() {
+        let graph = Graph::new();
+        let w = graph.param(Tensor::from_slice(&[1.0]));
+        let mut sgd = SGD::new(vec![w.node_id], vec![w.data.clone()], 0.1, 0.0);
+        let mut scaler = GradScaler::new().with_init_scale(8.0).with_growth_interval(2);
+
+        let loss = w.mul(&w).sum();
+        scaler.scale_loss(&loss).backward();
+        assert_eq!(w.grad().unwrap().data(), &[16.0]);
+        let updated = scaler.step_params(&mut sgd, std::slice::from_ref(&w)).unwrap();
+        // The unscaled gradient 2 gives 1 - 0.1 · 2.
+        assert!((updated[0].data()[0] - 0.8).abs() < 1e-12);
+
+        let overflow = HashMap::from([(w.node_id, Tensor::from_slice(&[f64::INFINITY]))]);
+        assert!(scaler.step(&mut sgd, &overflow).is_none());
+        assert_eq!((scaler.scale, scaler.skipped_steps, scaler.growth_tracker), (4.0, 1, 0));
+        let clean = HashMap::from([(w.node_id, Tensor::from_slice(&[4.0]))]);
+        scaler.step(&mut sgd, &clean).unwrap();
+        scaler.step(&mut sgd, &clean).unwrap();
+        assert_eq!(scaler.scale, 8.0);
+
+        let mut restored = GradScaler::new();
+        restored.load_state(&scaler.state()).unwrap();
+        assert_eq!((restored.scale, restored.skipped_steps), (8.0, 1));
+    }
+}
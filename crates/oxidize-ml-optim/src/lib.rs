@@ -1,5 +1,9 @@
 pub mod optimizer;
 pub mod scheduler;
+pub mod ema;
+pub mod grad_scaler;
 
 pub use optimizer::*;
 pub use scheduler::*;
+pub use ema::*;
+pub use grad_scaler::*;
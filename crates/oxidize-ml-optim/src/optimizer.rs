@@ -1,24 +1,158 @@
-use std::collections::HashMap;
-use oxidize_ml_core::Tensor;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
 use oxidize_ml_autodiff::graph::NodeId;
+use oxidize_ml_autodiff::Variable;
 
-/// Trait for optimizers.
-pub trait Optimizer {
+/// Trait for optimizers over element type `T` (`f64` by default).
+pub trait Optimizer<T: Float = f64> {
     /// Perform one optimization step using computed gradients.
-    fn step(&mut self, grads: &HashMap<NodeId, Tensor<f64>>) -> Vec<Tensor<f64>>;
+    ///
+    /// The returned tensors share buffers with the optimizer's own copies of
+    /// the parameters. A step updates a buffer in place only if nothing else
+    /// still holds it; otherwise it copies the buffer first. Drop the
+    /// returned values, or overwrite them with the next step's, to let the
+    /// optimizer reuse its buffers.
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>>;
+
+    /// Perform one step using the gradients accumulated on `params` by
+    /// `Variable::backward`; parameters without a gradient are left alone.
+    fn step_params(&mut self, params: &[Variable<T>]) -> Vec<Tensor<T>> {
+        let grads = params.iter().filter_map(|p| p.grad().map(|g| (p.node_id, g))).collect();
+        self.step(&grads)
+    }
+
+    /// The current learning rate.
+    fn lr(&self) -> f64;
+
+    /// Change the learning rate, as learning-rate schedules do between
+    /// epochs.
+    fn set_lr(&mut self, lr: f64);
+
+    /// Everything a later `load_state` needs to continue exactly where this
+    /// optimizer is: its moments, step counters, parameter copies and
+    /// learning rate.
+    fn state(&self) -> OptimizerState<T>;
+
+    /// Restore a `state` taken from the same kind of optimizer over the
+    /// same parameters. On a missing entry or a shape mismatch nothing is
+    /// changed.
+    fn load_state(&mut self, state: &OptimizerState<T>) -> TensorResult<()>;
+}
+
+/// An optimizer's internal state: per-parameter tensors keyed `name.index`
+/// (`m.0`, `v.0`, `param_values.0`, …) and scalars such as the step `t`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct OptimizerState<T: Float = f64> {
+    pub tensors: BTreeMap<String, Tensor<T>>,
+    pub scalars: BTreeMap<String, f64>,
+}
+
+impl<T: Float> Default for OptimizerState<T> {
+    fn default() -> Self {
+        OptimizerState { tensors: BTreeMap::new(), scalars: BTreeMap::new() }
+    }
+}
+
+impl<T: Float> OptimizerState<T> {
+    fn put_tensors(&mut self, name: &str, tensors: &[Tensor<T>]) {
+        for (i, t) in tensors.iter().enumerate() {
+            self.tensors.insert(format!("{}.{}", name, i), t.clone());
+        }
+    }
+
+    /// The saved `name.*` tensors, checked against the current ones.
+    fn tensors_like(&self, name: &str, current: &[Tensor<T>]) -> TensorResult<Vec<Tensor<T>>> {
+        let saved = self
+            .tensors
+            .keys()
+            .filter(|k| k.strip_prefix(name).and_then(|rest| rest.strip_prefix('.')).is_some_and(|i| i.parse::<usize>().is_ok()))
+            .count();
+        if saved != current.len() {
+            return Err(TensorError::InvalidOperation(format!(
+                "optimizer state has {} '{}' tensors, expected {}",
+                saved,
+                name,
+                current.len()
+            )));
+        }
+        current
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let key = format!("{}.{}", name, i);
+                let t = self.tensors.get(&key).ok_or_else(|| TensorError::InvalidOperation(format!("optimizer state is missing '{}'", key)))?;
+                if t.shape_vec() != c.shape_vec() {
+                    return Err(TensorError::ShapeMismatch { expected: c.shape_vec(), got: t.shape_vec() });
+                }
+                Ok(t.clone())
+            })
+            .collect()
+    }
+
+    fn scalar(&self, name: &str) -> TensorResult<f64> {
+        self.scalars
+            .get(name)
+            .copied()
+            .ok_or_else(|| TensorError::InvalidOperation(format!("optimizer state is missing '{}'", name)))
+    }
+
+    /// The entries under `prefix.`, without the prefix.
+    fn nested(&self, prefix: &str) -> OptimizerState<T> {
+        let strip = |k: &String| k.strip_prefix(prefix).and_then(|rest| rest.strip_prefix('.')).map(str::to_string);
+        OptimizerState {
+            tensors: self.tensors.iter().filter_map(|(k, t)| strip(k).map(|k| (k, t.clone()))).collect(),
+            scalars: self.scalars.iter().filter_map(|(k, &v)| strip(k).map(|k| (k, v))).collect(),
+        }
+    }
+
+    fn insert_nested(&mut self, prefix: &str, other: OptimizerState<T>) {
+        self.tensors.extend(other.tensors.into_iter().map(|(k, t)| (format!("{}.{}", prefix, k), t)));
+        self.scalars.extend(other.scalars.into_iter().map(|(k, v)| (format!("{}.{}", prefix, k), v)));
+    }
+}
+
+/// `state` and `load_state` for an optimizer whose state is the listed
+/// per-parameter tensors, `usize` step counters and `f64` scalars, plus its
+/// learning rate.
+macro_rules! optimizer_state {
+    (tensors: [$($buf:ident),*], steps: [$($step:ident),*], floats: [$($float:ident),*]) => {
+        fn state(&self) -> OptimizerState<T> {
+            let mut state = OptimizerState::default();
+            state.scalars.insert("lr".to_string(), self.lr);
+            $(state.scalars.insert(stringify!($step).to_string(), self.$step as f64);)*
+            $(state.scalars.insert(stringify!($float).to_string(), self.$float);)*
+            $(state.put_tensors(stringify!($buf), &self.$buf);)*
+            state
+        }
+
+        fn load_state(&mut self, state: &OptimizerState<T>) -> TensorResult<()> {
+            $(let $buf = state.tensors_like(stringify!($buf), &self.$buf)?;)*
+            $(let $step = state.scalar(stringify!($step))? as usize;)*
+            $(let $float = state.scalar(stringify!($float))?;)*
+            self.lr = state.scalar("lr")?;
+            $(self.$buf = $buf;)*
+            $(self.$step = $step;)*
+            $(self.$float = $float;)*
+            Ok(())
+        }
+    };
 }
 
 /// Stochastic Gradient Descent with optional momentum.
-pub struct SGD {
+pub struct SGD<T: Float = f64> {
     pub lr: f64,
     pub momentum: f64,
     param_ids: Vec<NodeId>,
-    param_values: Vec<Tensor<f64>>,
-    velocities: Vec<Tensor<f64>>,
+    param_values: Vec<Tensor<T>>,
+    velocities: Vec<Tensor<T>>,
 }
 
-impl SGD {
-    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<f64>>, lr: f64, momentum: f64) -> Self {
+impl<T: Float> SGD<T> {
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64, momentum: f64) -> Self {
         let velocities = param_values.iter().map(|p| Tensor::zeros(p.shape_vec())).collect();
         SGD {
             lr,
@@ -30,19 +164,29 @@ impl SGD {
     }
 }
 
-impl Optimizer for SGD {
-    fn step(&mut self, grads: &HashMap<NodeId, Tensor<f64>>) -> Vec<Tensor<f64>> {
+impl<T: Float> Optimizer<T> for SGD<T> {
+    optimizer_state!(tensors: [param_values, velocities], steps: [], floats: []);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        let (lr, momentum) = (T::from_f64(self.lr), T::from_f64(self.momentum));
         for (i, id) in self.param_ids.iter().enumerate() {
             if let Some(grad) = grads.get(id) {
                 // v = momentum * v - lr * grad
-                self.velocities[i] = self.velocities[i]
-                    .mul_scalar(self.momentum)
-                    .sub(&grad.mul_scalar(self.lr))
+                self.velocities[i]
+                    .zip_apply_mut(grad, |v, g| momentum * v - lr * g)
                     .expect("sgd velocity update");
 
                 // param += v
-                self.param_values[i] = self.param_values[i]
-                    .add(&self.velocities[i])
+                self.param_values[i]
+                    .add_(&self.velocities[i])
                     .expect("sgd param update");
             }
         }
@@ -50,21 +194,23 @@ impl Optimizer for SGD {
     }
 }
 
-/// Adam optimizer.
-pub struct Adam {
+/// Adam optimizer, with optional L2 weight decay added to the gradient
+/// (see `AdamW` for the decoupled form).
+pub struct Adam<T: Float = f64> {
     pub lr: f64,
     pub beta1: f64,
     pub beta2: f64,
     pub epsilon: f64,
+    pub weight_decay: f64,
     pub t: usize,
     param_ids: Vec<NodeId>,
-    param_values: Vec<Tensor<f64>>,
-    m: Vec<Tensor<f64>>, // first moment
-    v: Vec<Tensor<f64>>, // second moment
+    param_values: Vec<Tensor<T>>,
+    m: Vec<Tensor<T>>, // first moment
+    v: Vec<Tensor<T>>, // second moment
 }
 
-impl Adam {
-    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<f64>>, lr: f64) -> Self {
+impl<T: Float> Adam<T> {
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64) -> Self {
         let m = param_values.iter().map(|p| Tensor::zeros(p.shape_vec())).collect();
         let v = param_values.iter().map(|p| Tensor::zeros(p.shape_vec())).collect();
         Adam {
@@ -72,6 +218,7 @@ impl Adam {
             beta1: 0.9,
             beta2: 0.999,
             epsilon: 1e-8,
+            weight_decay: 0.0,
             t: 0,
             param_ids,
             param_values,
@@ -79,35 +226,47 @@ impl Adam {
             v,
         }
     }
+
+    pub fn with_weight_decay(mut self, wd: f64) -> Self {
+        self.weight_decay = wd;
+        self
+    }
 }
 
-impl Optimizer for Adam {
-    fn step(&mut self, grads: &HashMap<NodeId, Tensor<f64>>) -> Vec<Tensor<f64>> {
+impl<T: Float> Optimizer<T> for Adam<T> {
+    optimizer_state!(tensors: [param_values, m, v], steps: [t], floats: []);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
         self.t += 1;
         let bias_correction1 = 1.0 - self.beta1.powi(self.t as i32);
         let bias_correction2 = 1.0 - self.beta2.powi(self.t as i32);
+        let (beta1, beta2) = (T::from_f64(self.beta1), T::from_f64(self.beta2));
+        let (bc1, bc2) = (T::from_f64(bias_correction1), T::from_f64(bias_correction2));
+        let (lr, eps) = (T::from_f64(self.lr), T::from_f64(self.epsilon));
 
         for (i, id) in self.param_ids.iter().enumerate() {
             if let Some(grad) = grads.get(id) {
-                self.m[i] = self.m[i]
-                    .mul_scalar(self.beta1)
-                    .add(&grad.mul_scalar(1.0 - self.beta1))
+                let grad = &*with_l2(grad, &self.param_values[i], self.weight_decay);
+                self.m[i]
+                    .zip_apply_mut(grad, |m, g| beta1 * m + (T::ONE - beta1) * g)
                     .expect("adam m update");
-
-                let grad_sq = grad.mul(grad).expect("grad²");
-                self.v[i] = self.v[i]
-                    .mul_scalar(self.beta2)
-                    .add(&grad_sq.mul_scalar(1.0 - self.beta2))
+                self.v[i]
+                    .zip_apply_mut(grad, |v, g| beta2 * v + (T::ONE - beta2) * g * g)
                     .expect("adam v update");
 
-                let m_hat = self.m[i].mul_scalar(1.0 / bias_correction1);
-                let v_hat = self.v[i].mul_scalar(1.0 / bias_correction2);
-
-                let denom = v_hat.sqrt().add_scalar(self.epsilon);
-                let update = m_hat.div(&denom).expect("adam update").mul_scalar(self.lr);
-                self.param_values[i] = self.param_values[i]
-                    .sub(&update)
-                    .expect("adam param update");
+                // param -= lr * m̂ / (√v̂ + ε)
+                let (m, v) = (self.m[i].data(), self.v[i].data());
+                for ((p, &m), &v) in self.param_values[i].data_mut().iter_mut().zip(m).zip(v) {
+                    *p -= lr * (m / bc1) / ((v / bc2).sqrt() + eps);
+                }
             }
         }
         self.param_values.clone()
@@ -121,18 +280,18 @@ impl Optimizer for Adam {
 ///
 /// v = α * v + (1 - α) * grad²
 /// param -= lr * grad / (√v + ε)
-pub struct RMSProp {
+pub struct RMSProp<T: Float = f64> {
     pub lr: f64,
     pub alpha: f64,
     pub epsilon: f64,
     pub weight_decay: f64,
     param_ids: Vec<NodeId>,
-    param_values: Vec<Tensor<f64>>,
-    v: Vec<Tensor<f64>>,
+    param_values: Vec<Tensor<T>>,
+    v: Vec<Tensor<T>>,
 }
 
-impl RMSProp {
-    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<f64>>, lr: f64) -> Self {
+impl<T: Float> RMSProp<T> {
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64) -> Self {
         let v = param_values.iter().map(|p| Tensor::zeros(p.shape_vec())).collect();
         RMSProp {
             lr,
@@ -151,31 +310,44 @@ impl RMSProp {
     }
 }
 
-impl Optimizer for RMSProp {
-    fn step(&mut self, grads: &HashMap<NodeId, Tensor<f64>>) -> Vec<Tensor<f64>> {
+impl<T: Float> Optimizer<T> for RMSProp<T> {
+    optimizer_state!(tensors: [param_values, v], steps: [], floats: []);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        let (lr, alpha) = (T::from_f64(self.lr), T::from_f64(self.alpha));
+        let (eps, decay) = (T::from_f64(self.epsilon), T::from_f64(self.weight_decay));
+
         for (i, id) in self.param_ids.iter().enumerate() {
             if let Some(grad) = grads.get(id) {
                 // Apply weight decay
+                let decayed;
                 let grad = if self.weight_decay > 0.0 {
-                    grad.add(&self.param_values[i].mul_scalar(self.weight_decay))
-                        .expect("weight decay")
+                    let mut g = grad.clone();
+                    g.add_scaled_(&self.param_values[i], decay).expect("weight decay");
+                    decayed = g;
+                    &decayed
                 } else {
-                    grad.clone()
+                    grad
                 };
 
                 // v = α * v + (1 - α) * grad²
-                let grad_sq = grad.mul(&grad).expect("grad²");
-                self.v[i] = self.v[i]
-                    .mul_scalar(self.alpha)
-                    .add(&grad_sq.mul_scalar(1.0 - self.alpha))
+                self.v[i]
+                    .zip_apply_mut(grad, |v, g| alpha * v + (T::ONE - alpha) * g * g)
                     .expect("rmsprop v update");
 
                 // param -= lr * grad / (√v + ε)
-                let denom = self.v[i].sqrt().add_scalar(self.epsilon);
-                let update = grad.div(&denom).expect("rmsprop div").mul_scalar(self.lr);
-                self.param_values[i] = self.param_values[i]
-                    .sub(&update)
-                    .expect("rmsprop param update");
+                let (g, v) = (grad.data(), self.v[i].data());
+                for ((p, &g), &v) in self.param_values[i].data_mut().iter_mut().zip(g).zip(v) {
+                    *p -= lr * g / (v.sqrt() + eps);
+                }
             }
         }
         self.param_values.clone()
@@ -189,16 +361,16 @@ impl Optimizer for RMSProp {
 ///
 /// G += grad²
 /// param -= lr * grad / (√G + ε)
-pub struct AdaGrad {
+pub struct AdaGrad<T: Float = f64> {
     pub lr: f64,
     pub epsilon: f64,
     param_ids: Vec<NodeId>,
-    param_values: Vec<Tensor<f64>>,
-    g: Vec<Tensor<f64>>,
+    param_values: Vec<Tensor<T>>,
+    g: Vec<Tensor<T>>,
 }
 
-impl AdaGrad {
-    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<f64>>, lr: f64) -> Self {
+impl<T: Float> AdaGrad<T> {
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64) -> Self {
         let g = param_values.iter().map(|p| Tensor::zeros(p.shape_vec())).collect();
         AdaGrad {
             lr,
@@ -210,23 +382,598 @@ impl AdaGrad {
     }
 }
 
-impl Optimizer for AdaGrad {
-    fn step(&mut self, grads: &HashMap<NodeId, Tensor<f64>>) -> Vec<Tensor<f64>> {
+impl<T: Float> Optimizer<T> for AdaGrad<T> {
+    optimizer_state!(tensors: [param_values, g], steps: [], floats: []);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        let (lr, eps) = (T::from_f64(self.lr), T::from_f64(self.epsilon));
         for (i, id) in self.param_ids.iter().enumerate() {
             if let Some(grad) = grads.get(id) {
                 // G += grad²
-                let grad_sq = grad.mul(grad).expect("grad²");
-                self.g[i] = self.g[i].add(&grad_sq).expect("adagrad g update");
+                self.g[i]
+                    .zip_apply_mut(grad, |acc, g| acc + g * g)
+                    .expect("adagrad g update");
 
                 // param -= lr * grad / (√G + ε)
-                let denom = self.g[i].sqrt().add_scalar(self.epsilon);
-                let update = grad.div(&denom).expect("adagrad div").mul_scalar(self.lr);
-                self.param_values[i] = self.param_values[i]
-                    .sub(&update)
-                    .expect("adagrad param update");
+                let (g, acc) = (grad.data(), self.g[i].data());
+                for ((p, &g), &acc) in self.param_values[i].data_mut().iter_mut().zip(g).zip(acc) {
+                    *p -= lr * g / (acc.sqrt() + eps);
+                }
+            }
+        }
+        self.param_values.clone()
+    }
+}
+
+/// AdamW: Adam with decoupled weight decay (Loshchilov and Hutter).
+///
+/// The decay shrinks the parameters directly, `param -= lr * wd * param`,
+/// instead of being added to the gradient, so it is not rescaled by the
+/// adaptive step.
+pub struct AdamW<T: Float = f64> {
+    pub lr: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+    pub weight_decay: f64,
+    pub t: usize,
+    param_ids: Vec<NodeId>,
+    param_values: Vec<Tensor<T>>,
+    m: Vec<Tensor<T>>,
+    v: Vec<Tensor<T>>,
+}
+
+impl<T: Float> AdamW<T> {
+    /// Weight decay defaults to 0.01.
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64) -> Self {
+        let (m, v) = (zeros_like(&param_values), zeros_like(&param_values));
+        AdamW { lr, beta1: 0.9, beta2: 0.999, epsilon: 1e-8, weight_decay: 0.01, t: 0, param_ids, param_values, m, v }
+    }
+
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, wd: f64) -> Self {
+        self.weight_decay = wd;
+        self
+    }
+}
+
+impl<T: Float> Optimizer<T> for AdamW<T> {
+    optimizer_state!(tensors: [param_values, m, v], steps: [t], floats: []);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        self.t += 1;
+        let bc1 = T::from_f64(1.0 - self.beta1.powi(self.t as i32));
+        let bc2 = T::from_f64(1.0 - self.beta2.powi(self.t as i32));
+        let (lr, eps) = (T::from_f64(self.lr), T::from_f64(self.epsilon));
+        let shrink = T::from_f64(1.0 - self.lr * self.weight_decay);
+
+        for (i, id) in self.param_ids.iter().enumerate() {
+            if let Some(grad) = grads.get(id) {
+                update_moments(&mut self.m[i], &mut self.v[i], grad, self.beta1, self.beta2);
+                let (m, v) = (self.m[i].data(), self.v[i].data());
+                for ((p, &m), &v) in self.param_values[i].data_mut().iter_mut().zip(m).zip(v) {
+                    *p = *p * shrink - lr * (m / bc1) / ((v / bc2).sqrt() + eps);
+                }
             }
         }
         self.param_values.clone()
     }
 }
 
+/// NAdam: Adam with Nesterov momentum (Dozat), with the momentum schedule
+/// `μ_t = β1 (1 - 0.5 · 0.96^(t · momentum_decay))` of PyTorch.
+pub struct NAdam<T: Float = f64> {
+    pub lr: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+    pub momentum_decay: f64,
+    pub weight_decay: f64,
+    pub t: usize,
+    mu_product: f64,
+    param_ids: Vec<NodeId>,
+    param_values: Vec<Tensor<T>>,
+    m: Vec<Tensor<T>>,
+    v: Vec<Tensor<T>>,
+}
+
+impl<T: Float> NAdam<T> {
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64) -> Self {
+        let (m, v) = (zeros_like(&param_values), zeros_like(&param_values));
+        NAdam {
+            lr,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+            momentum_decay: 0.004,
+            weight_decay: 0.0,
+            t: 0,
+            mu_product: 1.0,
+            param_ids,
+            param_values,
+            m,
+            v,
+        }
+    }
+
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    /// L2 penalty added to the gradient.
+    pub fn with_weight_decay(mut self, wd: f64) -> Self {
+        self.weight_decay = wd;
+        self
+    }
+
+    fn mu(&self, t: usize) -> f64 {
+        self.beta1 * (1.0 - 0.5 * 0.96f64.powf(t as f64 * self.momentum_decay))
+    }
+}
+
+impl<T: Float> Optimizer<T> for NAdam<T> {
+    optimizer_state!(tensors: [param_values, m, v], steps: [t], floats: [mu_product]);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        self.t += 1;
+        let (mu, mu_next) = (self.mu(self.t), self.mu(self.t + 1));
+        self.mu_product *= mu;
+        // Weights of the current gradient and of the look-ahead momentum.
+        let grad_coef = T::from_f64(self.lr * (1.0 - mu) / (1.0 - self.mu_product));
+        let momentum_coef = T::from_f64(self.lr * mu_next / (1.0 - self.mu_product * mu_next));
+        let bc2 = T::from_f64(1.0 - self.beta2.powi(self.t as i32));
+        let eps = T::from_f64(self.epsilon);
+
+        for (i, id) in self.param_ids.iter().enumerate() {
+            if let Some(grad) = grads.get(id) {
+                let grad = with_l2(grad, &self.param_values[i], self.weight_decay);
+                update_moments(&mut self.m[i], &mut self.v[i], &grad, self.beta1, self.beta2);
+                let (g, m, v) = (grad.data(), self.m[i].data(), self.v[i].data());
+                for (((p, &g), &m), &v) in self.param_values[i].data_mut().iter_mut().zip(g).zip(m).zip(v) {
+                    let denom = (v / bc2).sqrt() + eps;
+                    *p -= grad_coef * g / denom + momentum_coef * m / denom;
+                }
+            }
+        }
+        self.param_values.clone()
+    }
+}
+
+/// LAMB (You et al.) for large-batch training: the Adam step plus
+/// decoupled weight decay, `r = m̂ / (√v̂ + ε) + wd · param`, rescaled per
+/// parameter tensor by the trust ratio `‖param‖ / ‖r‖` (1 when either norm
+/// is zero).
+pub struct LAMB<T: Float = f64> {
+    pub lr: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+    pub weight_decay: f64,
+    pub t: usize,
+    param_ids: Vec<NodeId>,
+    param_values: Vec<Tensor<T>>,
+    m: Vec<Tensor<T>>,
+    v: Vec<Tensor<T>>,
+}
+
+impl<T: Float> LAMB<T> {
+    /// Weight decay defaults to 0.01.
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64) -> Self {
+        let (m, v) = (zeros_like(&param_values), zeros_like(&param_values));
+        LAMB { lr, beta1: 0.9, beta2: 0.999, epsilon: 1e-6, weight_decay: 0.01, t: 0, param_ids, param_values, m, v }
+    }
+
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, wd: f64) -> Self {
+        self.weight_decay = wd;
+        self
+    }
+}
+
+impl<T: Float> Optimizer<T> for LAMB<T> {
+    optimizer_state!(tensors: [param_values, m, v], steps: [t], floats: []);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        self.t += 1;
+        let bc1 = T::from_f64(1.0 - self.beta1.powi(self.t as i32));
+        let bc2 = T::from_f64(1.0 - self.beta2.powi(self.t as i32));
+        let (eps, decay) = (T::from_f64(self.epsilon), T::from_f64(self.weight_decay));
+
+        for (i, id) in self.param_ids.iter().enumerate() {
+            if let Some(grad) = grads.get(id) {
+                update_moments(&mut self.m[i], &mut self.v[i], grad, self.beta1, self.beta2);
+                let (m, v) = (self.m[i].data(), self.v[i].data());
+                let params = self.param_values[i].data();
+                let r: Vec<T> = params
+                    .iter()
+                    .zip(m)
+                    .zip(v)
+                    .map(|((&p, &m), &v)| (m / bc1) / ((v / bc2).sqrt() + eps) + decay * p)
+                    .collect();
+                let norm = |xs: &[T]| xs.iter().map(|x| x.to_f64() * x.to_f64()).sum::<f64>().sqrt();
+                let (p_norm, r_norm) = (norm(params), norm(&r));
+                let trust = if p_norm > 0.0 && r_norm > 0.0 { p_norm / r_norm } else { 1.0 };
+                let step = T::from_f64(self.lr * trust);
+                for (p, r) in self.param_values[i].data_mut().iter_mut().zip(r) {
+                    *p -= step * r;
+                }
+            }
+        }
+        self.param_values.clone()
+    }
+}
+
+/// Lion (Chen et al.): steps by the sign of an interpolation of momentum
+/// and gradient, with decoupled weight decay.
+///
+/// c = β1 * m + (1 - β1) * grad
+/// param -= lr * (sign(c) + wd * param)
+/// m = β2 * m + (1 - β2) * grad
+///
+/// Every coordinate moves by `lr`, so it wants a learning rate 3–10x
+/// smaller than AdamW's.
+pub struct Lion<T: Float = f64> {
+    pub lr: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub weight_decay: f64,
+    param_ids: Vec<NodeId>,
+    param_values: Vec<Tensor<T>>,
+    m: Vec<Tensor<T>>,
+}
+
+impl<T: Float> Lion<T> {
+    pub fn new(param_ids: Vec<NodeId>, param_values: Vec<Tensor<T>>, lr: f64) -> Self {
+        let m = zeros_like(&param_values);
+        Lion { lr, beta1: 0.9, beta2: 0.99, weight_decay: 0.0, param_ids, param_values, m }
+    }
+
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, wd: f64) -> Self {
+        self.weight_decay = wd;
+        self
+    }
+}
+
+impl<T: Float> Optimizer<T> for Lion<T> {
+    optimizer_state!(tensors: [param_values, m], steps: [], floats: []);
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        let (beta1, beta2) = (T::from_f64(self.beta1), T::from_f64(self.beta2));
+        let (lr, shrink) = (T::from_f64(self.lr), T::from_f64(1.0 - self.lr * self.weight_decay));
+        for (i, id) in self.param_ids.iter().enumerate() {
+            if let Some(grad) = grads.get(id) {
+                let m = self.m[i].data_mut();
+                for ((p, m), &g) in self.param_values[i].data_mut().iter_mut().zip(m).zip(grad.data()) {
+                    let c = beta1 * *m + (T::ONE - beta1) * g;
+                    let sign = if c > T::ZERO { T::ONE } else if c < T::ZERO { -T::ONE } else { T::ZERO };
+                    *p = *p * shrink - lr * sign;
+                    *m = beta2 * *m + (T::ONE - beta2) * g;
+                }
+            }
+        }
+        self.param_values.clone()
+    }
+}
+
+fn zeros_like<T: Float>(values: &[Tensor<T>]) -> Vec<Tensor<T>> {
+    values.iter().map(|p| Tensor::zeros(p.shape_vec())).collect()
+}
+
+/// Adam's moment estimates: `m = β1 m + (1 - β1) g`, `v = β2 v + (1 - β2) g²`.
+fn update_moments<T: Float>(m: &mut Tensor<T>, v: &mut Tensor<T>, grad: &Tensor<T>, beta1: f64, beta2: f64) {
+    let (beta1, beta2) = (T::from_f64(beta1), T::from_f64(beta2));
+    m.zip_apply_mut(grad, |m, g| beta1 * m + (T::ONE - beta1) * g).expect("first moment update");
+    v.zip_apply_mut(grad, |v, g| beta2 * v + (T::ONE - beta2) * g * g).expect("second moment update");
+}
+
+/// `grad + wd * param`, the coupled (L2) weight decay; `grad` itself when
+/// there is no decay.
+fn with_l2<'a, T: Float>(grad: &'a Tensor<T>, param: &Tensor<T>, weight_decay: f64) -> Cow<'a, Tensor<T>> {
+    if weight_decay == 0.0 {
+        return Cow::Borrowed(grad);
+    }
+    let mut g = grad.clone();
+    g.add_scaled_(param, T::from_f64(weight_decay)).expect("weight decay");
+    Cow::Owned(g)
+}
+
+/// Several optimizers stepped together, one per parameter group; typically
+/// built from `named_parameters` filtered by name, e.g. a lower learning
+/// rate for a pretrained backbone than for a new head.
+///
+/// `step` returns the updated values of every group, in group order.
+pub struct ParamGroups<T: Float = f64> {
+    pub groups: Vec<Box<dyn Optimizer<T>>>,
+}
+
+impl<T: Float> ParamGroups<T> {
+    pub fn new() -> Self {
+        ParamGroups { groups: Vec::new() }
+    }
+
+    pub fn with_group(mut self, optimizer: impl Optimizer<T> + 'static) -> Self {
+        self.groups.push(Box::new(optimizer));
+        self
+    }
+}
+
+impl<T: Float> Default for ParamGroups<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> Optimizer<T> for ParamGroups<T> {
+    fn step(&mut self, grads: &HashMap<NodeId, Tensor<T>>) -> Vec<Tensor<T>> {
+        self.groups.iter_mut().flat_map(|group| group.step(grads)).collect()
+    }
+
+    /// The first group's rate.
+    fn lr(&self) -> f64 {
+        self.groups.first().map_or(0.0, |group| group.lr())
+    }
+
+    /// Scale every group's rate so the first group's becomes `lr`, keeping
+    /// the ratios between groups.
+    fn set_lr(&mut self, lr: f64) {
+        let base = self.lr();
+        for group in &mut self.groups {
+            let scaled = if base == 0.0 { lr } else { group.lr() * lr / base };
+            group.set_lr(scaled);
+        }
+    }
+
+    /// Each group's state under `groups.{index}`.
+    fn state(&self) -> OptimizerState<T> {
+        let mut state = OptimizerState::default();
+        for (i, group) in self.groups.iter().enumerate() {
+            state.insert_nested(&format!("groups.{}", i), group.state());
+        }
+        state
+    }
+
+    fn load_state(&mut self, state: &OptimizerState<T>) -> TensorResult<()> {
+        for (i, group) in self.groups.iter_mut().enumerate() {
+            group.load_state(&state.nested(&format!("groups.{}", i)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference Adam update written with allocating tensor ops.
+    fn adam_reference(p: &Tensor<f64>, g: &Tensor<f64>, steps: usize) -> Tensor<f64> {
+        let (b1, b2, lr, eps) = (0.9f64, 0.999f64, 0.1, 1e-8);
+        let mut m = Tensor::zeros(p.shape_vec());
+        let mut v = Tensor::zeros(p.shape_vec());
+        let mut p = p.clone();
+        for t in 1..=steps {
+            m = m.mul_scalar(b1).add(&g.mul_scalar(1.0 - b1)).unwrap();
+            v = v.mul_scalar(b2).add(&g.mul(g).unwrap().mul_scalar(1.0 - b2)).unwrap();
+            let m_hat = m.mul_scalar(1.0 / (1.0 - b1.powi(t as i32)));
+            let v_hat = v.mul_scalar(1.0 / (1.0 - b2.powi(t as i32)));
+            let update = m_hat.div(&v_hat.sqrt().add_scalar(eps)).unwrap().mul_scalar(lr);
+            p = p.sub(&update).unwrap();
+        }
+        p
+    }
+
+    #[test]
+    fn test_adam_matches_reference() {
+        let p: Tensor<f64> = Tensor::from_slice(&[1.0, -2.0, 0.5]);
+        let g: Tensor<f64> = Tensor::from_slice(&[0.3, -0.1, 2.0]);
+        let id = NodeId(0);
+        let grads: HashMap<NodeId, Tensor<f64>> = [(id, g.clone())].into_iter().collect();
+
+        let mut adam = Adam::new(vec![id], vec![p.clone()], 0.1);
+        let mut out = Vec::new();
+        for _ in 0..3 {
+            out = adam.step(&grads);
+        }
+        let expected = adam_reference(&p, &g, 3);
+        for (a, b) in out[0].data().iter().zip(expected.data()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_adam_reuses_buffers_between_steps() {
+        let id = NodeId(0);
+        let grads: HashMap<NodeId, Tensor<f64>> =
+            [(id, Tensor::from_slice(&[0.3, -0.1, 2.0]))].into_iter().collect();
+        let mut adam = Adam::new(vec![id], vec![Tensor::from_slice(&[1.0, -2.0, 0.5])], 0.1);
+
+        // The first step copies the caller's tensor; once its result is
+        // dropped, later steps update that same buffer in place.
+        let buffer = adam.step(&grads)[0].data().as_ptr();
+        let kept = adam.step(&grads);
+        assert_eq!(kept[0].data().as_ptr(), buffer);
+        let before = kept[0].data().to_vec();
+        drop(kept);
+        let out = adam.step(&grads);
+        assert_eq!(out[0].data().as_ptr(), buffer);
+        assert_ne!(out[0].data().to_vec(), before);
+
+        // A result still alive during the next step is left untouched.
+        let next = adam.step(&grads);
+        assert!(!next[0].shares_storage(&out[0]));
+        assert_ne!(next[0].data().to_vec(), out[0].data().to_vec());
+    }
+
+    #[test]
+    fn test_sgd_momentum() {
+        let id = NodeId(0);
+        let grads: HashMap<NodeId, Tensor<f64>> =
+            [(id, Tensor::from_slice(&[1.0, 2.0]))].into_iter().collect();
+        let mut sgd = SGD::new(vec![id], vec![Tensor::from_slice(&[0.0, 0.0])], 0.1, 0.5);
+        sgd.step(&grads);
+        let out = sgd.step(&grads);
+        // v1 = -0.1 g, v2 = 0.5 v1 - 0.1 g = -0.15 g; p = v1 + v2 = -0.25 g
+        assert!((out[0].data()[0] + 0.25).abs() < 1e-12);
+        assert!((out[0].data()[1] + 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_step_params_reads_accumulated_grads() {
+        let w = Variable::param(Tensor::from_slice(&[1.0, -2.0]));
+        w.pow(2.0).sum().backward();
+        w.pow(2.0).sum().backward();
+        let mut sgd = SGD::new(vec![w.node_id], vec![w.data.clone()], 0.1, 0.0);
+        // Two accumulated passes: grad = 2 · 2w
+        let out = sgd.step_params(std::slice::from_ref(&w));
+        assert_eq!(out[0].data(), &[0.6, -1.2]);
+    }
+
+    #[test]
+    fn test_state_round_trip_continues_exactly() {
+        let (a, b) = (NodeId(0), NodeId(1));
+        let grads: HashMap<NodeId, Tensor<f64>> =
+            [(a, Tensor::from_slice(&[0.5, -1.0])), (b, Tensor::from_slice(&[2.0]))].into_iter().collect();
+        let build = || {
+            ParamGroups::new()
+                .with_group(NAdam::new(vec![a], vec![Tensor::from_slice(&[1.0, 1.0])], 0.1))
+                .with_group(SGD::new(vec![b], vec![Tensor::from_slice(&[0.0])], 0.01, 0.9))
+        };
+        let mut original = build();
+        original.step(&grads);
+        original.step(&grads);
+        let state = original.state();
+        assert_eq!(state.scalars["groups.0.t"], 2.0);
+
+        let mut restored = build();
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.step(&grads), original.step(&grads));
+
+        let mut other = Adam::new(vec![a], vec![Tensor::from_slice(&[1.0, 1.0, 1.0])], 0.1);
+        assert!(other.load_state(&state.nested("groups.0")).is_err());
+        assert_eq!(other.t, 0);
+    }
+
+    #[test]
+    fn test_param_groups_use_their_own_learning_rates() {
+        let (a, b) = (NodeId(0), NodeId(1));
+        let grads: HashMap<NodeId, Tensor<f64>> =
+            [(a, Tensor::from_slice(&[1.0])), (b, Tensor::from_slice(&[1.0]))].into_iter().collect();
+        let mut groups = ParamGroups::new()
+            .with_group(SGD::new(vec![a], vec![Tensor::from_slice(&[0.0])], 0.01, 0.0))
+            .with_group(SGD::new(vec![b], vec![Tensor::from_slice(&[0.0])], 0.1, 0.0));
+        let out = groups.step(&grads);
+        assert_eq!(out.len(), 2);
+        assert!((out[0].data()[0] + 0.01).abs() < 1e-12);
+        assert!((out[1].data()[0] + 0.1).abs() < 1e-12);
+    }
+
+    fn run(opt: &mut dyn Optimizer<f64>, grads: &HashMap<NodeId, Tensor<f64>>, steps: usize) -> Vec<Tensor<f64>> {
+        (0..steps).map(|_| opt.step(grads)).last().unwrap()
+    }
+
+    #[test]
+    fn test_decoupled_decay_and_sign_updates() {
+        let id = NodeId(0);
+        let p: Tensor<f64> = Tensor::from_slice(&[1.0, -2.0, 0.5]);
+        let g: Tensor<f64> = Tensor::from_slice(&[0.3, -0.1, 2.0]);
+        let grads: HashMap<NodeId, Tensor<f64>> = [(id, g.clone())].into_iter().collect();
+
+        // Without decay AdamW is Adam; with it, each step first shrinks by 1 - lr·wd.
+        let adam = run(&mut Adam::new(vec![id], vec![p.clone()], 0.1), &grads, 3);
+        let adamw = run(&mut AdamW::new(vec![id], vec![p.clone()], 0.1).with_weight_decay(0.0), &grads, 3);
+        assert_eq!(adam[0].data(), adamw[0].data());
+        let zero: HashMap<NodeId, Tensor<f64>> = [(id, Tensor::zeros(vec![3]))].into_iter().collect();
+        let decayed = run(&mut AdamW::new(vec![id], vec![p.clone()], 0.1).with_weight_decay(0.5), &zero, 2);
+        assert!((decayed[0].data()[1] + 2.0 * 0.95 * 0.95).abs() < 1e-12);
+        // Adam's coupled decay goes through the adaptive step instead.
+        let coupled = run(&mut Adam::new(vec![id], vec![p.clone()], 0.1).with_weight_decay(0.5), &zero, 1);
+        assert!((coupled[0].data()[1] + 1.9).abs() < 1e-6);
+
+        // Lion moves every coordinate by exactly lr.
+        let lion = run(&mut Lion::new(vec![id], vec![p.clone()], 0.01), &grads, 1);
+        assert_eq!(lion[0].data(), &[0.99, -1.99, 0.49]);
+
+        // LAMB's first step has length lr · ‖p‖ (trust ratio × ‖r‖).
+        let lamb = run(&mut LAMB::new(vec![id], vec![p.clone()], 0.1).with_weight_decay(0.0), &grads, 1);
+        let step = lamb[0].sub(&p).unwrap().data().iter().map(|d| d * d).sum::<f64>().sqrt();
+        let p_norm = p.data().iter().map(|d| d * d).sum::<f64>().sqrt();
+        assert!((step - 0.1 * p_norm).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_new_optimizers_minimise_a_quadratic_in_groups() {
+        // f(w) = Σ w², gradient 2w; one group per optimizer.
+        let ids: Vec<NodeId> = (0..4).map(NodeId).collect();
+        let start = || vec![Tensor::from_slice(&[1.0, -1.5])];
+        let mut groups = ParamGroups::new()
+            .with_group(AdamW::new(vec![ids[0]], start(), 0.05))
+            .with_group(NAdam::new(vec![ids[1]], start(), 0.05))
+            .with_group(LAMB::new(vec![ids[2]], start(), 0.05))
+            .with_group(Lion::new(vec![ids[3]], start(), 0.01));
+        let mut values: Vec<Tensor<f64>> = (0..4).flat_map(|_| start()).collect();
+        for _ in 0..1000 {
+            let grads = ids.iter().zip(&values).map(|(&id, v)| (id, v.mul_scalar(2.0))).collect();
+            values = groups.step(&grads);
+        }
+        for v in &values {
+            assert!(v.data().iter().all(|w| w.abs() < 0.05), "did not converge: {:?}", v.data());
+        }
+    }
+}
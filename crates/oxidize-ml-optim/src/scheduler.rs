@@ -1,6 +1,10 @@
-/// Learning rate schedulers for optimizers.
-///
-/// These modify the learning rate over training steps.
+//! Learning rate schedulers for optimizers.
+//!
+//! These modify the learning rate over training steps.
+
+use std::collections::BTreeMap;
+use oxidize_ml_core::TensorError;
+use oxidize_ml_core::error::TensorResult;
 
 /// Step decay: multiply LR by gamma every step_size epochs.
 pub struct StepLR {
@@ -131,6 +135,86 @@ impl ReduceLROnPlateau {
     pub fn get_lr(&self) -> f64 { self.lr }
 }
 
+/// The schedulers behind one interface, for training loops that step
+/// whichever one they are given once per epoch.
+pub trait LrScheduler {
+    /// Advance one epoch. `metric` is the monitored value (lower is better);
+    /// only `ReduceLROnPlateau` uses it, and requires it.
+    fn step_epoch(&mut self, metric: Option<f64>);
+
+    /// The learning rate for the coming epoch.
+    fn lr(&self) -> f64;
+
+    /// The scheduler's progress, for checkpoints.
+    fn state(&self) -> BTreeMap<String, f64>;
+
+    /// Continue from a `state` of the same kind of scheduler.
+    fn load_state(&mut self, state: &BTreeMap<String, f64>) -> TensorResult<()>;
+}
+
+fn entry(state: &BTreeMap<String, f64>, key: &str) -> TensorResult<f64> {
+    state
+        .get(key)
+        .copied()
+        .ok_or_else(|| TensorError::InvalidOperation(format!("scheduler state is missing '{}'", key)))
+}
+
+macro_rules! epoch_scheduler {
+    ($($name:ident => $counter:ident),*) => {$(
+        impl LrScheduler for $name {
+            fn step_epoch(&mut self, _metric: Option<f64>) {
+                self.step();
+            }
+
+            fn lr(&self) -> f64 {
+                self.get_lr()
+            }
+
+            fn state(&self) -> BTreeMap<String, f64> {
+                BTreeMap::from([(stringify!($counter).to_string(), self.$counter as f64)])
+            }
+
+            fn load_state(&mut self, state: &BTreeMap<String, f64>) -> TensorResult<()> {
+                self.$counter = entry(state, stringify!($counter))? as usize;
+                Ok(())
+            }
+        }
+    )*};
+}
+
+epoch_scheduler!(
+    StepLR => current_epoch,
+    ExponentialLR => current_epoch,
+    CosineAnnealingLR => current_epoch,
+    WarmupLR => current_step
+);
+
+impl LrScheduler for ReduceLROnPlateau {
+    fn step_epoch(&mut self, metric: Option<f64>) {
+        self.step(metric.expect("ReduceLROnPlateau needs a metric to monitor"));
+    }
+
+    fn lr(&self) -> f64 {
+        self.get_lr()
+    }
+
+    fn state(&self) -> BTreeMap<String, f64> {
+        BTreeMap::from([
+            ("lr".to_string(), self.lr),
+            ("best_metric".to_string(), self.best_metric),
+            ("epochs_without_improvement".to_string(), self.epochs_without_improvement as f64),
+        ])
+    }
+
+    fn load_state(&mut self, state: &BTreeMap<String, f64>) -> TensorResult<()> {
+        let (lr, best) = (entry(state, "lr")?, entry(state, "best_metric")?);
+        self.epochs_without_improvement = entry(state, "epochs_without_improvement")? as usize;
+        self.lr = lr;
+        self.best_metric = best;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,253 @@
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::{Tensor, TensorError};
+use serde::{Deserialize, Serialize};
+
+use crate::persist::{Persist, SavedModel};
+use crate::pipeline::Transformer;
+
+/// A subset of input columns, by position or by name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Columns {
+    Indices(Vec<usize>),
+    /// Resolved against the names given to `ColumnTransformer::with_feature_names`.
+    Names(Vec<String>),
+}
+
+impl From<Vec<usize>> for Columns {
+    fn from(indices: Vec<usize>) -> Self {
+        Columns::Indices(indices)
+    }
+}
+
+impl From<&[usize]> for Columns {
+    fn from(indices: &[usize]) -> Self {
+        Columns::Indices(indices.to_vec())
+    }
+}
+
+impl From<Vec<&str>> for Columns {
+    fn from(names: Vec<&str>) -> Self {
+        Columns::Names(names.into_iter().map(String::from).collect())
+    }
+}
+
+impl From<&[&str]> for Columns {
+    fn from(names: &[&str]) -> Self {
+        Columns::Names(names.iter().map(|n| n.to_string()).collect())
+    }
+}
+
+/// What happens to a column subset.
+pub enum ColumnStep {
+    Transform(Box<dyn Transformer>),
+    /// Copied to the output unchanged.
+    Passthrough,
+    /// Left out of the output.
+    Drop,
+}
+
+/// Applies a different transformer to each subset of columns and
+/// concatenates the results side by side, in step order.
+///
+/// Columns not named by any step are the remainder: dropped by default, or
+/// appended after the steps' output with `with_remainder_passthrough`. A
+/// column may appear in several steps. Every transformer must return a 2-D
+/// tensor with one row per input row.
+pub struct ColumnTransformer {
+    pub(crate) steps: Vec<(String, ColumnStep, Columns)>,
+    pub(crate) remainder_passthrough: bool,
+    pub(crate) feature_names: Option<Vec<String>>,
+    /// Resolved column indices of each step, then of the remainder; set by `fit`.
+    pub(crate) resolved: Option<(Vec<Vec<usize>>, Vec<usize>)>,
+}
+
+impl ColumnTransformer {
+    pub fn new() -> Self {
+        ColumnTransformer {
+            steps: Vec::new(),
+            remainder_passthrough: false,
+            feature_names: None,
+            resolved: None,
+        }
+    }
+
+    /// Fit and apply `transformer` to `columns`.
+    pub fn add<C: Into<Columns>>(mut self, name: &str, transformer: Box<dyn Transformer>, columns: C) -> Self {
+        self.steps.push((name.to_string(), ColumnStep::Transform(transformer), columns.into()));
+        self
+    }
+
+    /// Copy `columns` to the output unchanged.
+    pub fn passthrough<C: Into<Columns>>(mut self, name: &str, columns: C) -> Self {
+        self.steps.push((name.to_string(), ColumnStep::Passthrough, columns.into()));
+        self
+    }
+
+    /// Exclude `columns` from the output, even if the remainder is passed through.
+    pub fn drop<C: Into<Columns>>(mut self, name: &str, columns: C) -> Self {
+        self.steps.push((name.to_string(), ColumnStep::Drop, columns.into()));
+        self
+    }
+
+    /// Append columns not claimed by any step to the output.
+    pub fn with_remainder_passthrough(mut self) -> Self {
+        self.remainder_passthrough = true;
+        self
+    }
+
+    /// Names of the input columns, enabling selection by name.
+    pub fn with_feature_names(mut self, names: Vec<&str>) -> Self {
+        self.feature_names = Some(names.into_iter().map(String::from).collect());
+        self
+    }
+
+    /// The step names, in order.
+    pub fn step_names(&self) -> Vec<&str> {
+        self.steps.iter().map(|(n, _, _)| n.as_str()).collect()
+    }
+
+    fn resolve(&self, columns: &Columns, n_cols: usize) -> TensorResult<Vec<usize>> {
+        let indices = match columns {
+            Columns::Indices(indices) => indices.clone(),
+            Columns::Names(names) => {
+                let features = self.feature_names.as_ref().ok_or_else(|| {
+                    TensorError::InvalidOperation("selecting columns by name needs with_feature_names".into())
+                })?;
+                names
+                    .iter()
+                    .map(|name| {
+                        features.iter().position(|f| f == name).ok_or_else(|| {
+                            TensorError::InvalidOperation(format!("no column named '{}'", name))
+                        })
+                    })
+                    .collect::<TensorResult<Vec<usize>>>()?
+            }
+        };
+        if let Some(&bad) = indices.iter().find(|&&i| i >= n_cols) {
+            return Err(TensorError::IndexOutOfBounds { index: bad, axis: 1, size: n_cols });
+        }
+        Ok(indices)
+    }
+}
+
+impl Default for ColumnTransformer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transformer for ColumnTransformer {
+    fn fit(&mut self, x: &Tensor<f64>) -> TensorResult<()> {
+        let n_cols = x.shape().dim(1)?;
+        let mut step_columns = Vec::with_capacity(self.steps.len());
+        let mut claimed = vec![false; n_cols];
+        for (_, _, columns) in &self.steps {
+            let indices = self.resolve(columns, n_cols)?;
+            for &i in &indices {
+                claimed[i] = true;
+            }
+            step_columns.push(indices);
+        }
+        for ((_, step, _), indices) in self.steps.iter_mut().zip(&step_columns) {
+            if let ColumnStep::Transform(t) = step {
+                t.fit(&x.index_select(1, indices)?)?;
+            }
+        }
+        let remainder = (0..n_cols).filter(|&i| !claimed[i]).collect();
+        self.resolved = Some((step_columns, remainder));
+        Ok(())
+    }
+
+    fn transform(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        let (step_columns, remainder) = self
+            .resolved
+            .as_ref()
+            .ok_or_else(|| TensorError::InvalidOperation("ColumnTransformer has not been fitted".into()))?;
+        let n_rows = x.shape().dim(0)?;
+        let mut blocks = Vec::new();
+        for ((name, step, _), indices) in self.steps.iter().zip(step_columns) {
+            let block = match step {
+                ColumnStep::Transform(t) => t.transform(&x.index_select(1, indices)?)?,
+                ColumnStep::Passthrough => x.index_select(1, indices)?,
+                ColumnStep::Drop => continue,
+            };
+            if block.ndim() != 2 || block.shape().dim(0)? != n_rows {
+                return Err(TensorError::InvalidOperation(format!(
+                    "step '{}' returned shape {:?}, expected {} rows",
+                    name,
+                    block.shape_vec(),
+                    n_rows
+                )));
+            }
+            blocks.push(block);
+        }
+        if self.remainder_passthrough && !remainder.is_empty() {
+            blocks.push(x.index_select(1, remainder)?);
+        }
+        if blocks.is_empty() {
+            return Tensor::new(Vec::new(), vec![n_rows, 0]);
+        }
+        Tensor::concatenate(&blocks.iter().collect::<Vec<_>>(), 1)
+    }
+
+    fn snapshot(&self) -> Option<SavedModel> {
+        self.to_saved().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{Estimator, Pipeline};
+    use oxidize_ml_linear::LinearRegression;
+    use oxidize_ml_preprocessing::{OneHotEncoder, StandardScaler};
+
+    // age, color code, id
+    fn data() -> Tensor<f64> {
+        Tensor::from_vec2d(&[
+            vec![20.0, 0.0, 1.0],
+            vec![30.0, 1.0, 2.0],
+            vec![40.0, 2.0, 3.0],
+            vec![50.0, 1.0, 4.0],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mixed_columns() {
+        let mut ct = ColumnTransformer::new()
+            .with_feature_names(vec!["age", "color", "id"])
+            .add("num", Box::new(StandardScaler::new()), vec!["age"])
+            .add("cat", Box::new(OneHotEncoder::new()), vec![1])
+            .drop("id", vec!["id"]);
+        let out = ct.fit_transform(&data()).unwrap();
+        assert_eq!(out.shape_vec(), vec![4, 4]);
+        assert_eq!(&out.data()[1..4], &[1.0, 0.0, 0.0]);
+        assert!(out.data()[0] < 0.0 && out.data()[12] > 0.0);
+
+        let mut keep = ColumnTransformer::new().passthrough("color", vec![1]).with_remainder_passthrough();
+        let out = keep.fit_transform(&data()).unwrap();
+        assert_eq!(out.data()[..3], [0.0, 20.0, 1.0]);
+
+        let mut unnamed = ColumnTransformer::new().passthrough("age", vec!["age"]);
+        assert!(unnamed.fit(&data()).is_err());
+    }
+
+    #[test]
+    fn test_in_pipeline() {
+        let x = data();
+        let y = Tensor::from_slice(&[1.0, 5.0, 9.0, 9.0]);
+        let ct = ColumnTransformer::new()
+            .add("num", Box::new(StandardScaler::new()), vec![0])
+            .add("cat", Box::new(OneHotEncoder::new()), vec![1]);
+        let mut pipe = Pipeline::new()
+            .add_transformer(Box::new(ct))
+            // The one-hot block already spans a constant column.
+            .set_estimator(Box::new(LinearRegression::new(false)) as Box<dyn Estimator>);
+        pipe.fit(&x, &y).unwrap();
+        let pred = pipe.predict(&x).unwrap();
+        for (p, t) in pred.data().iter().zip(y.data()) {
+            assert!((p - t).abs() < 1e-6, "{} vs {}", p, t);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! `Estimator` and `Transformer` implementations for the library's models
+//! and preprocessors, so they can be used as `Pipeline` steps.
+
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::{Tensor, TensorError};
+use oxidize_ml_gaussian_process::GaussianProcessRegressor;
+use oxidize_ml_linear::{
+    ElasticNet, ElasticNetCV, GammaRegressor, HuberRegressor, Lasso, LassoCV, LinearDiscriminantAnalysis,
+    LinearRegression, LogisticRegression, Perceptron, PoissonRegressor, QuadraticDiscriminantAnalysis,
+    QuantileRegressor, Ridge, RidgeCV, TweedieRegressor,
+};
+use oxidize_ml_naive_bayes::{BernoulliNB, CategoricalNB, ComplementNB, GaussianNB, MultinomialNB};
+use oxidize_ml_neighbors::{KNNClassifier, KNNRegressor};
+use oxidize_ml_preprocessing::{
+    IncrementalPCA, KNNImputer, LogTransformer, MaxAbsScaler, MinMaxScaler, OneHotEncoder, OrdinalEncoder, PolynomialFeatures,
+    PowerTransformer, QuantileTransformer, RobustScaler, SimpleImputer, SplineTransformer, StandardScaler,
+    TruncatedSVD, PCA,
+};
+use oxidize_ml_svm::{SVC, SVR};
+use oxidize_ml_tree::{
+    DecisionTreeClassifier, DecisionTreeRegressor, ExtraTreesClassifier, ExtraTreesRegressor, GradientBoostingClassifier,
+    GradientBoostingRegressor, RandomForestClassifier, RandomForestRegressor,
+};
+
+use crate::persist::SavedModel;
+use crate::pipeline::{DecisionScorer, Estimator, InvertibleTransformer, Transformer};
+
+macro_rules! impl_estimator {
+    ($($model:ident),* $(,)?) => {$(
+        impl Estimator for $model<f64> {
+            fn fit(&mut self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<()> {
+                $model::fit(self, x, y)
+            }
+
+            fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+                $model::predict(self, x)
+            }
+
+            fn snapshot(&self) -> Option<SavedModel> {
+                Some(SavedModel::$model(self.clone()))
+            }
+        }
+    )*};
+}
+
+impl_estimator!(
+    LinearRegression,
+    Ridge,
+    Lasso,
+    ElasticNet,
+    LogisticRegression,
+    Perceptron,
+    LinearDiscriminantAnalysis,
+    QuadraticDiscriminantAnalysis,
+    PoissonRegressor,
+    GammaRegressor,
+    TweedieRegressor,
+    QuantileRegressor,
+    HuberRegressor,
+    RidgeCV,
+    LassoCV,
+    ElasticNetCV,
+    DecisionTreeClassifier,
+    DecisionTreeRegressor,
+    RandomForestClassifier,
+    RandomForestRegressor,
+    GradientBoostingClassifier,
+    GradientBoostingRegressor,
+    ExtraTreesClassifier,
+    ExtraTreesRegressor,
+    SVC,
+    SVR,
+    KNNClassifier,
+    KNNRegressor,
+    GaussianNB,
+    MultinomialNB,
+    BernoulliNB,
+    ComplementNB,
+    CategoricalNB,
+    GaussianProcessRegressor,
+);
+
+impl DecisionScorer for SVC<f64> {
+    fn decision_scores(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        self.decision_function(x)
+    }
+}
+
+impl DecisionScorer for LogisticRegression<f64> {
+    fn decision_scores(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        positive_column(self.predict_proba(x)?)
+    }
+}
+
+impl DecisionScorer for GradientBoostingClassifier<f64> {
+    fn decision_scores(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        self.predict_proba(x)
+    }
+}
+
+/// The forest's vote fraction for class 1.
+impl DecisionScorer for RandomForestClassifier<f64> {
+    fn decision_scores(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        positive_column(self.predict_proba(x)?)
+    }
+}
+
+/// Column 1 of `[n, k]` class probabilities; `[n]` input is returned as is.
+fn positive_column(proba: Tensor<f64>) -> TensorResult<Tensor<f64>> {
+    match proba.shape_vec().as_slice() {
+        [_] => Ok(proba),
+        &[n, k] if k >= 2 => Tensor::new(proba.data().iter().skip(1).step_by(k).copied().collect(), vec![n]),
+        _ => Err(TensorError::InvalidOperation("decision scores need a binary classifier".into())),
+    }
+}
+
+macro_rules! impl_transformer {
+    ($($model:ident),* $(,)?) => {$(
+        impl Transformer for $model<f64> {
+            fn fit(&mut self, x: &Tensor<f64>) -> TensorResult<()> {
+                $model::fit(self, x)
+            }
+
+            fn transform(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+                $model::transform(self, x)
+            }
+
+            fn snapshot(&self) -> Option<SavedModel> {
+                Some(SavedModel::$model(self.clone()))
+            }
+        }
+    )*};
+}
+
+impl_transformer!(
+    StandardScaler,
+    MinMaxScaler,
+    RobustScaler,
+    MaxAbsScaler,
+    QuantileTransformer,
+    PowerTransformer,
+    PCA,
+    IncrementalPCA,
+    TruncatedSVD,
+    OneHotEncoder,
+    OrdinalEncoder,
+    SimpleImputer,
+    KNNImputer,
+    PolynomialFeatures,
+    SplineTransformer,
+    LogTransformer,
+);
+
+macro_rules! impl_invertible {
+    ($($model:ident),* $(,)?) => {$(
+        impl InvertibleTransformer for $model<f64> {
+            fn inverse_transform(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+                $model::inverse_transform(self, x)
+            }
+        }
+    )*};
+}
+
+impl_invertible!(
+    StandardScaler,
+    MinMaxScaler,
+    RobustScaler,
+    MaxAbsScaler,
+    QuantileTransformer,
+    PowerTransformer,
+    LogTransformer,
+);
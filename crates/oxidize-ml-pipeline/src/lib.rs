@@ -1,2 +1,9 @@
 pub mod pipeline;
+pub mod estimators;
+pub mod column_transformer;
+pub mod persist;
+pub mod target;
 pub use pipeline::*;
+pub use column_transformer::*;
+pub use persist::*;
+pub use target::*;
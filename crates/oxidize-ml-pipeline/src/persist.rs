@@ -0,0 +1,474 @@
+//! Saving fitted models and whole pipelines to `.oxml` files.
+//!
+//! An `.oxml` file is a header line `OXML <format version>` followed by the
+//! model as JSON, tagged with its kind so a file can only be loaded back as
+//! the type that wrote it.
+
+use std::fs;
+use std::path::Path;
+
+use oxidize_ml_cluster::KMeans;
+use oxidize_ml_gaussian_process::GaussianProcessRegressor;
+use oxidize_ml_linear::{
+    ElasticNet, ElasticNetCV, GammaRegressor, HuberRegressor, Lasso, LassoCV, LinearDiscriminantAnalysis,
+    LinearRegression, LogisticRegression, Perceptron, PoissonRegressor, QuadraticDiscriminantAnalysis,
+    QuantileRegressor, Ridge, RidgeCV, TweedieRegressor,
+};
+use oxidize_ml_naive_bayes::{BernoulliNB, CategoricalNB, ComplementNB, GaussianNB, MultinomialNB};
+use oxidize_ml_neighbors::{KNNClassifier, KNNRegressor};
+use oxidize_ml_preprocessing::{
+    IncrementalPCA, KNNImputer, LogTransformer, MaxAbsScaler, MinMaxScaler, OneHotEncoder, OrdinalEncoder, PolynomialFeatures,
+    PowerTransformer, QuantileTransformer, RobustScaler, SimpleImputer, SplineTransformer, StandardScaler,
+    TruncatedSVD, PCA,
+};
+use oxidize_ml_svm::{SVC, SVR};
+use oxidize_ml_tree::{
+    DecisionTreeClassifier, DecisionTreeRegressor, ExtraTreesClassifier, ExtraTreesRegressor, GradientBoostingClassifier,
+    GradientBoostingRegressor, RandomForestClassifier, RandomForestRegressor,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::column_transformer::{ColumnStep, ColumnTransformer, Columns};
+use crate::pipeline::{Estimator, InvertibleTransformer, Pipeline, Transformer};
+use crate::target::TransformedTargetRegressor;
+
+/// The `.oxml` format version written by this build. Files with a newer
+/// version are rejected rather than misread.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MAGIC: &str = "OXML";
+
+/// Errors from saving or loading `.oxml` files.
+#[derive(Debug, Error)]
+pub enum PersistError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Not an .oxml file: missing '{MAGIC} <version>' header")]
+    NotOxml,
+
+    #[error("Unsupported .oxml format version {found}: this build reads versions up to {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("Malformed model data: {0}")]
+    Format(#[from] serde_json::Error),
+
+    #[error("Model mismatch: expected {expected}, file holds {found}")]
+    WrongModel { expected: String, found: String },
+
+    #[error("Cannot save {0}: it has no saved form")]
+    Unsupported(String),
+}
+
+/// Serialized form of a `ColumnTransformer`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedColumnTransformer {
+    steps: Vec<(String, SavedColumnStep, Columns)>,
+    remainder_passthrough: bool,
+    feature_names: Option<Vec<String>>,
+    resolved: Option<(Vec<Vec<usize>>, Vec<usize>)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum SavedColumnStep {
+    Transform(Box<SavedModel>),
+    Passthrough,
+    Drop,
+}
+
+/// Serialized form of a `Pipeline`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPipeline {
+    transformers: Vec<SavedModel>,
+    estimator: Option<Box<SavedModel>>,
+}
+
+/// Serialized form of a `TransformedTargetRegressor`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTransformedTarget {
+    pub(crate) regressor: Box<SavedModel>,
+    pub(crate) transformer: Box<SavedModel>,
+}
+
+/// Anything that can round-trip through an `.oxml` file.
+pub trait Persist: Sized {
+    fn to_saved(&self) -> Result<SavedModel, PersistError>;
+    fn from_saved(model: SavedModel) -> Result<Self, PersistError>;
+
+    /// Encode as `.oxml` bytes.
+    fn to_bytes(&self) -> Result<Vec<u8>, PersistError> {
+        let mut bytes = format!("{} {}\n", MAGIC, FORMAT_VERSION).into_bytes();
+        serde_json::to_writer(&mut bytes, &self.to_saved()?)?;
+        Ok(bytes)
+    }
+
+    /// Decode `.oxml` bytes, checking the header and the model kind.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, PersistError> {
+        Self::from_saved(decode(bytes)?)
+    }
+
+    fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), PersistError> {
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    fn load<P: AsRef<Path>>(path: P) -> Result<Self, PersistError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<SavedModel, PersistError> {
+    let newline = bytes.iter().position(|&b| b == b'\n').ok_or(PersistError::NotOxml)?;
+    let header = std::str::from_utf8(&bytes[..newline]).map_err(|_| PersistError::NotOxml)?;
+    let version = header
+        .strip_prefix(MAGIC)
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .ok_or(PersistError::NotOxml)?;
+    if version > FORMAT_VERSION {
+        return Err(PersistError::UnsupportedVersion {
+            found: version,
+            supported: FORMAT_VERSION,
+        });
+    }
+    Ok(serde_json::from_slice(&bytes[newline + 1..])?)
+}
+
+fn mismatch(expected: &str, found: &SavedModel) -> PersistError {
+    PersistError::WrongModel {
+        expected: expected.to_string(),
+        found: found.kind().to_string(),
+    }
+}
+
+macro_rules! saved_models {
+    (
+        estimators: [$($est:ident),* $(,)?],
+        transformers: [$($tr:ident),* $(,)?],
+        models: [$($other:ident),* $(,)?] $(,)?
+    ) => {
+        /// A fitted model, preprocessor or pipeline in serializable form.
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(tag = "kind", content = "model")]
+        pub enum SavedModel {
+            $($est($est<f64>),)*
+            $($tr($tr<f64>),)*
+            $($other($other<f64>),)*
+            ColumnTransformer(SavedColumnTransformer),
+            Pipeline(SavedPipeline),
+            TransformedTargetRegressor(SavedTransformedTarget),
+        }
+
+        impl SavedModel {
+            /// Name of the saved type, e.g. `"RandomForestClassifier"`.
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $(SavedModel::$est(_) => stringify!($est),)*
+                    $(SavedModel::$tr(_) => stringify!($tr),)*
+                    $(SavedModel::$other(_) => stringify!($other),)*
+                    SavedModel::ColumnTransformer(_) => "ColumnTransformer",
+                    SavedModel::Pipeline(_) => "Pipeline",
+                    SavedModel::TransformedTargetRegressor(_) => "TransformedTargetRegressor",
+                }
+            }
+
+            fn into_estimator(self) -> Result<Box<dyn Estimator>, PersistError> {
+                match self {
+                    $(SavedModel::$est(m) => Ok(Box::new(m)),)*
+                    SavedModel::TransformedTargetRegressor(_) => {
+                        Ok(Box::new(TransformedTargetRegressor::from_saved(self)?))
+                    }
+                    other => Err(mismatch("an estimator", &other)),
+                }
+            }
+
+            fn into_transformer(self) -> Result<Box<dyn Transformer>, PersistError> {
+                match self {
+                    $(SavedModel::$tr(m) => Ok(Box::new(m)),)*
+                    SavedModel::ColumnTransformer(_) => Ok(Box::new(ColumnTransformer::from_saved(self)?)),
+                    other => Err(mismatch("a transformer", &other)),
+                }
+            }
+        }
+
+        $(saved_models!(@persist $est);)*
+        $(saved_models!(@persist $tr);)*
+        $(saved_models!(@persist $other);)*
+    };
+    (@persist $model:ident) => {
+        impl Persist for $model<f64> {
+            fn to_saved(&self) -> Result<SavedModel, PersistError> {
+                Ok(SavedModel::$model(self.clone()))
+            }
+
+            fn from_saved(model: SavedModel) -> Result<Self, PersistError> {
+                match model {
+                    SavedModel::$model(m) => Ok(m),
+                    other => Err(mismatch(stringify!($model), &other)),
+                }
+            }
+        }
+    };
+}
+
+saved_models! {
+    estimators: [
+        LinearRegression,
+        Ridge,
+        Lasso,
+        ElasticNet,
+        LogisticRegression,
+        Perceptron,
+        LinearDiscriminantAnalysis,
+        QuadraticDiscriminantAnalysis,
+        PoissonRegressor,
+        GammaRegressor,
+        TweedieRegressor,
+        QuantileRegressor,
+        HuberRegressor,
+        RidgeCV,
+        LassoCV,
+        ElasticNetCV,
+        DecisionTreeClassifier,
+        DecisionTreeRegressor,
+        RandomForestClassifier,
+        RandomForestRegressor,
+        GradientBoostingClassifier,
+        GradientBoostingRegressor,
+        ExtraTreesClassifier,
+        ExtraTreesRegressor,
+        SVC,
+        SVR,
+        KNNClassifier,
+        KNNRegressor,
+        GaussianNB,
+        MultinomialNB,
+        BernoulliNB,
+        ComplementNB,
+        CategoricalNB,
+        GaussianProcessRegressor,
+    ],
+    transformers: [
+        StandardScaler,
+        MinMaxScaler,
+        RobustScaler,
+        MaxAbsScaler,
+        QuantileTransformer,
+        PowerTransformer,
+        PCA,
+        IncrementalPCA,
+        TruncatedSVD,
+        OneHotEncoder,
+        OrdinalEncoder,
+        SimpleImputer,
+        KNNImputer,
+        PolynomialFeatures,
+        SplineTransformer,
+        LogTransformer,
+    ],
+    models: [KMeans],
+}
+
+/// Decode `.oxml` bytes holding any saved estimator (not a pipeline or a
+/// transformer), for code that does not know the concrete type.
+pub fn estimator_from_bytes(bytes: &[u8]) -> Result<Box<dyn Estimator>, PersistError> {
+    decode(bytes)?.into_estimator()
+}
+
+impl SavedModel {
+    fn into_invertible(self) -> Result<Box<dyn InvertibleTransformer>, PersistError> {
+        match self {
+            SavedModel::StandardScaler(m) => Ok(Box::new(m)),
+            SavedModel::MinMaxScaler(m) => Ok(Box::new(m)),
+            SavedModel::RobustScaler(m) => Ok(Box::new(m)),
+            SavedModel::MaxAbsScaler(m) => Ok(Box::new(m)),
+            SavedModel::QuantileTransformer(m) => Ok(Box::new(m)),
+            SavedModel::PowerTransformer(m) => Ok(Box::new(m)),
+            SavedModel::LogTransformer(m) => Ok(Box::new(m)),
+            other => Err(mismatch("an invertible transformer", &other)),
+        }
+    }
+}
+
+impl Persist for ColumnTransformer {
+    fn to_saved(&self) -> Result<SavedModel, PersistError> {
+        let steps = self
+            .steps
+            .iter()
+            .map(|(name, step, columns)| {
+                let step = match step {
+                    ColumnStep::Transform(t) => SavedColumnStep::Transform(Box::new(
+                        t.snapshot()
+                            .ok_or_else(|| PersistError::Unsupported(format!("column step '{}'", name)))?,
+                    )),
+                    ColumnStep::Passthrough => SavedColumnStep::Passthrough,
+                    ColumnStep::Drop => SavedColumnStep::Drop,
+                };
+                Ok((name.clone(), step, columns.clone()))
+            })
+            .collect::<Result<_, PersistError>>()?;
+        Ok(SavedModel::ColumnTransformer(SavedColumnTransformer {
+            steps,
+            remainder_passthrough: self.remainder_passthrough,
+            feature_names: self.feature_names.clone(),
+            resolved: self.resolved.clone(),
+        }))
+    }
+
+    fn from_saved(model: SavedModel) -> Result<Self, PersistError> {
+        let saved = match model {
+            SavedModel::ColumnTransformer(s) => s,
+            other => return Err(mismatch("ColumnTransformer", &other)),
+        };
+        let steps = saved
+            .steps
+            .into_iter()
+            .map(|(name, step, columns)| {
+                let step = match step {
+                    SavedColumnStep::Transform(m) => ColumnStep::Transform(m.into_transformer()?),
+                    SavedColumnStep::Passthrough => ColumnStep::Passthrough,
+                    SavedColumnStep::Drop => ColumnStep::Drop,
+                };
+                Ok((name, step, columns))
+            })
+            .collect::<Result<_, PersistError>>()?;
+        Ok(ColumnTransformer {
+            steps,
+            remainder_passthrough: saved.remainder_passthrough,
+            feature_names: saved.feature_names,
+            resolved: saved.resolved,
+        })
+    }
+}
+
+impl Persist for Pipeline {
+    fn to_saved(&self) -> Result<SavedModel, PersistError> {
+        let transformers = self
+            .transformers
+            .iter()
+            .enumerate()
+            .map(|(i, t)| t.snapshot().ok_or_else(|| PersistError::Unsupported(format!("pipeline step {}", i))))
+            .collect::<Result<_, PersistError>>()?;
+        let estimator = match &self.estimator {
+            Some(e) => Some(Box::new(
+                e.snapshot()
+                    .ok_or_else(|| PersistError::Unsupported("the pipeline's estimator".into()))?,
+            )),
+            None => None,
+        };
+        Ok(SavedModel::Pipeline(SavedPipeline { transformers, estimator }))
+    }
+
+    fn from_saved(model: SavedModel) -> Result<Self, PersistError> {
+        let saved = match model {
+            SavedModel::Pipeline(s) => s,
+            other => return Err(mismatch("Pipeline", &other)),
+        };
+        Ok(Pipeline {
+            transformers: saved
+                .transformers
+                .into_iter()
+                .map(SavedModel::into_transformer)
+                .collect::<Result<_, _>>()?,
+            estimator: saved.estimator.map(|e| e.into_estimator()).transpose()?,
+        })
+    }
+}
+
+impl Persist for TransformedTargetRegressor {
+    fn to_saved(&self) -> Result<SavedModel, PersistError> {
+        self.snapshot()
+            .ok_or_else(|| PersistError::Unsupported("the target regressor or transformer".into()))
+    }
+
+    fn from_saved(model: SavedModel) -> Result<Self, PersistError> {
+        match model {
+            SavedModel::TransformedTargetRegressor(s) => Ok(TransformedTargetRegressor::new(
+                s.regressor.into_estimator()?,
+                s.transformer.into_invertible()?,
+            )),
+            other => Err(mismatch("TransformedTargetRegressor", &other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::error::TensorResult;
+    use oxidize_ml_core::Tensor;
+
+    fn data() -> (Tensor<f64>, Tensor<f64>) {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        for i in 0..40 {
+            let class = i % 2;
+            xs.extend([class as f64 * 4.0 + (i % 7) as f64 * 0.3, (i % 3) as f64, (i % 5) as f64]);
+            ys.push(class as f64);
+        }
+        (Tensor::new(xs, vec![40, 3]).unwrap(), Tensor::new(ys, vec![40]).unwrap())
+    }
+
+    #[test]
+    fn test_pipeline_roundtrip() {
+        let (x, y) = data();
+        let columns = ColumnTransformer::new()
+            .add("num", Box::new(StandardScaler::new()), vec![0, 2])
+            .add("cat", Box::new(OneHotEncoder::new()), vec![1]);
+        let mut pipe = Pipeline::new()
+            .add_transformer(Box::new(columns))
+            .set_estimator(Box::new(RandomForestClassifier::new(5, 4, 0.7)));
+        pipe.fit(&x, &y).unwrap();
+
+        let path = std::env::temp_dir().join(format!("oxidize_ml_pipeline_{}.oxml", std::process::id()));
+        pipe.save(&path).unwrap();
+        let loaded = Pipeline::load(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(loaded.predict(&x).unwrap().data(), pipe.predict(&x).unwrap().data());
+
+        let mut svc = SVC::new(1.0, oxidize_ml_svm::Kernel::RBF { gamma: 0.5 }, 200);
+        svc.fit(&x, &y).unwrap();
+        let back = SVC::from_bytes(&svc.to_bytes().unwrap()).unwrap();
+        assert_eq!(back.predict(&x).unwrap().data(), svc.predict(&x).unwrap().data());
+
+        let mut km = KMeans::new(2, 50);
+        km.fit(&x).unwrap();
+        let back = KMeans::from_bytes(&km.to_bytes().unwrap()).unwrap();
+        assert_eq!(back.centroids.unwrap().data(), km.centroids.unwrap().data());
+    }
+
+    struct Custom;
+
+    impl Transformer for Custom {
+        fn fit(&mut self, _x: &Tensor<f64>) -> TensorResult<()> {
+            Ok(())
+        }
+
+        fn transform(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+            Ok(x.clone())
+        }
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let (x, y) = data();
+        let mut model = LinearRegression::new(true);
+        model.fit(&x, &y).unwrap();
+        let bytes = model.to_bytes().unwrap();
+        assert!(bytes.starts_with(b"OXML 1\n"));
+
+        assert!(matches!(Ridge::<f64>::from_bytes(&bytes), Err(PersistError::WrongModel { .. })));
+        assert!(matches!(LinearRegression::<f64>::from_bytes(b"{}"), Err(PersistError::NotOxml)));
+        let mut newer = b"OXML 99\n".to_vec();
+        newer.extend_from_slice(&bytes[7..]);
+        assert!(matches!(
+            LinearRegression::<f64>::from_bytes(&newer),
+            Err(PersistError::UnsupportedVersion { found: 99, supported: 1 })
+        ));
+        let unknown = b"OXML 1\n{\"kind\":\"Teleporter\",\"model\":{}}";
+        assert!(matches!(Pipeline::from_bytes(unknown), Err(PersistError::Format(_))));
+
+        let custom = Pipeline::new().add_transformer(Box::new(Custom));
+        assert!(matches!(custom.to_bytes(), Err(PersistError::Unsupported(_))));
+    }
+}
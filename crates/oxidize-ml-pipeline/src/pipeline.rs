@@ -1,6 +1,8 @@
 use oxidize_ml_core::{Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
 
+use crate::persist::SavedModel;
+
 /// Trait for unsupervised transformers (scalers, encoders, etc.).
 pub trait Transformer {
     fn fit(&mut self, x: &Tensor<f64>) -> TensorResult<()>;
@@ -9,18 +11,41 @@ pub trait Transformer {
         self.fit(x)?;
         self.transform(x)
     }
+    /// The fitted state in serializable form, or `None` if this transformer
+    /// cannot be saved.
+    fn snapshot(&self) -> Option<SavedModel> {
+        None
+    }
+}
+
+/// A transformer that can map its output back to its input, as needed for
+/// transforming regression targets.
+pub trait InvertibleTransformer: Transformer {
+    fn inverse_transform(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>>;
 }
 
 /// Trait for supervised estimators.
 pub trait Estimator {
     fn fit(&mut self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<()>;
     fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>>;
+    /// The fitted state in serializable form, or `None` if this estimator
+    /// cannot be saved.
+    fn snapshot(&self) -> Option<SavedModel> {
+        None
+    }
+}
+
+/// A binary classifier with a continuous score per row, higher meaning
+/// class 1 is more likely. Scores need not be probabilities; see
+/// `CalibratedClassifier`.
+pub trait DecisionScorer: Estimator {
+    fn decision_scores(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>>;
 }
 
 /// A machine learning pipeline: chain transformers + final estimator.
 pub struct Pipeline {
-    transformers: Vec<Box<dyn Transformer>>,
-    estimator: Option<Box<dyn Estimator>>,
+    pub(crate) transformers: Vec<Box<dyn Transformer>>,
+    pub(crate) estimator: Option<Box<dyn Estimator>>,
 }
 
 impl Pipeline {
@@ -0,0 +1,100 @@
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_core::{Tensor, TensorError};
+
+use crate::persist::{SavedModel, SavedTransformedTarget};
+use crate::pipeline::{Estimator, InvertibleTransformer};
+
+/// Fits a regressor on a transformed target and maps its predictions back,
+/// e.g. regressing on `log(y)` while predicting on the original scale.
+///
+/// The transformer sees `y` as a single-column matrix and is fitted in
+/// `fit`, so statistics such as a scaler's mean come from the training
+/// targets only.
+pub struct TransformedTargetRegressor {
+    pub(crate) regressor: Box<dyn Estimator>,
+    pub(crate) transformer: Box<dyn InvertibleTransformer>,
+}
+
+impl TransformedTargetRegressor {
+    pub fn new(regressor: Box<dyn Estimator>, transformer: Box<dyn InvertibleTransformer>) -> Self {
+        TransformedTargetRegressor { regressor, transformer }
+    }
+
+    /// The inner regressor, fitted on the transformed target.
+    pub fn regressor(&self) -> &dyn Estimator {
+        self.regressor.as_ref()
+    }
+
+    pub fn transformer(&self) -> &dyn InvertibleTransformer {
+        self.transformer.as_ref()
+    }
+}
+
+/// `y` as an `[n, 1]` column.
+fn as_column(y: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+    match y.shape_vec().as_slice() {
+        [n] | [n, 1] => y.reshape(vec![*n, 1]),
+        _ => Err(TensorError::DimensionMismatch(format!(
+            "TransformedTargetRegressor needs a 1-D target, got shape {:?}", y.shape_vec()
+        ))),
+    }
+}
+
+impl Estimator for TransformedTargetRegressor {
+    fn fit(&mut self, x: &Tensor<f64>, y: &Tensor<f64>) -> TensorResult<()> {
+        let y_t = self.transformer.fit_transform(&as_column(y)?)?;
+        let n = y_t.numel();
+        self.regressor.fit(x, &y_t.reshape(vec![n])?)
+    }
+
+    fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        let pred = self.regressor.predict(x)?;
+        let n = pred.numel();
+        self.transformer.inverse_transform(&as_column(&pred)?)?.reshape(vec![n])
+    }
+
+    fn snapshot(&self) -> Option<SavedModel> {
+        Some(SavedModel::TransformedTargetRegressor(SavedTransformedTarget {
+            regressor: Box::new(self.regressor.snapshot()?),
+            transformer: Box::new(self.transformer.snapshot()?),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persist::Persist;
+    use oxidize_ml_linear::LinearRegression;
+    use oxidize_ml_preprocessing::{LogTransformer, StandardScaler};
+
+    #[test]
+    fn test_transformed_target_regressor() {
+        // y = exp(0.5 x + 1): linear in x after a log.
+        let xs: Vec<f64> = (0..20).map(|i| i as f64 * 0.25).collect();
+        let ys: Vec<f64> = xs.iter().map(|v| (0.5 * v + 1.0).exp()).collect();
+        let x = Tensor::new(xs, vec![20, 1]).unwrap();
+        let y = Tensor::new(ys.clone(), vec![20]).unwrap();
+
+        let mut model = TransformedTargetRegressor::new(
+            Box::new(LinearRegression::new(true)),
+            Box::new(LogTransformer::new()),
+        );
+        model.fit(&x, &y).unwrap();
+        let pred = model.predict(&x).unwrap();
+        assert_eq!(pred.shape_vec(), vec![20]);
+        assert!(pred.data().iter().zip(&ys).all(|(p, t)| (p - t).abs() < 1e-6 * t));
+
+        let back = TransformedTargetRegressor::from_bytes(&model.to_bytes().unwrap()).unwrap();
+        assert_eq!(back.predict(&x).unwrap().data(), pred.data());
+
+        let mut scaled = TransformedTargetRegressor::new(
+            Box::new(LinearRegression::new(true)),
+            Box::new(StandardScaler::new()),
+        );
+        scaled.fit(&x, &y).unwrap();
+        let mean = ys.iter().sum::<f64>() / 20.0;
+        let pred_mean = scaled.predict(&x).unwrap().data().iter().sum::<f64>() / 20.0;
+        assert!((pred_mean - mean).abs() < 1e-8);
+    }
+}
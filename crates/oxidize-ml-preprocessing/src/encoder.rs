@@ -1,6 +1,8 @@
-use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
 use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 
 /// Encode categorical string labels as integer indices.
 pub struct LabelEncoder {
@@ -84,6 +86,230 @@ where
     Tensor::new(data, vec![n, n_classes]).expect("one_hot shape")
 }
 
+/// What `OneHotEncoder::transform` does with a category not seen in `fit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandleUnknown {
+    /// Return an error.
+    #[default]
+    Error,
+    /// Encode the value as all zeros in that column's block.
+    Ignore,
+}
+
+/// Sorted distinct values of each column of `x`.
+fn column_categories<T: Float>(x: &Tensor<T>) -> TensorResult<Vec<Vec<T>>> {
+    let (rows, cols) = (x.shape().dim(0)?, x.shape().dim(1)?);
+    let mut categories = Vec::with_capacity(cols);
+    for j in 0..cols {
+        let column = (0..rows).map(|i| x.get(&[i, j])).collect::<TensorResult<Vec<T>>>()?;
+        categories.push(Tensor::from_slice(&column).unique().data().to_vec());
+    }
+    Ok(categories)
+}
+
+fn check_columns<T: Float>(x: &Tensor<T>, expected: usize) -> TensorResult<usize> {
+    let (rows, cols) = (x.shape().dim(0)?, x.shape().dim(1)?);
+    if cols != expected {
+        return Err(TensorError::ShapeMismatch {
+            expected: vec![rows, expected],
+            got: x.shape_vec(),
+        });
+    }
+    Ok(rows)
+}
+
+/// One-hot encode feature columns holding category codes.
+///
+/// Each input column becomes one 0/1 column per category seen in `fit`,
+/// in ascending order. With `drop_first` the first category of each column
+/// is left out (encoded as all zeros). Unseen categories are an error in
+/// `transform` unless `handle_unknown` is `Ignore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct OneHotEncoder<T: Float> {
+    /// Sorted categories of each input column.
+    pub categories: Option<Vec<Vec<T>>>,
+    #[serde(default)]
+    pub handle_unknown: HandleUnknown,
+    #[serde(default)]
+    pub drop_first: bool,
+}
+
+impl<T: Float> Default for OneHotEncoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> OneHotEncoder<T> {
+    pub fn new() -> Self {
+        OneHotEncoder { categories: None, handle_unknown: HandleUnknown::Error, drop_first: false }
+    }
+
+    pub fn with_handle_unknown(mut self, handle_unknown: HandleUnknown) -> Self {
+        self.handle_unknown = handle_unknown;
+        self
+    }
+
+    pub fn with_drop_first(mut self, drop_first: bool) -> Self {
+        self.drop_first = drop_first;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        self.categories = Some(column_categories(x)?);
+        Ok(())
+    }
+
+    /// Output columns for each input column.
+    fn block_widths(&self, categories: &[Vec<T>]) -> Vec<usize> {
+        let dropped = usize::from(self.drop_first);
+        categories.iter().map(|c| c.len().saturating_sub(dropped)).collect()
+    }
+
+    /// Total number of output columns.
+    pub fn n_features_out(&self) -> Option<usize> {
+        self.categories.as_ref().map(|c| self.block_widths(c).iter().sum())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let categories = self.categories.as_ref().expect("fit() must be called before transform()");
+        let rows = check_columns(x, categories.len())?;
+        let widths = self.block_widths(categories);
+        let width: usize = widths.iter().sum();
+        let dropped = usize::from(self.drop_first);
+        let mut data = vec![T::ZERO; rows * width];
+        for i in 0..rows {
+            let mut offset = 0;
+            for (j, cats) in categories.iter().enumerate() {
+                let v = x.get(&[i, j])?;
+                match cats.iter().position(|&c| c == v) {
+                    Some(k) if k >= dropped => data[i * width + offset + k - dropped] = T::ONE,
+                    Some(_) => {}
+                    None if self.handle_unknown == HandleUnknown::Ignore => {}
+                    None => {
+                        return Err(TensorError::InvalidOperation(format!(
+                            "unknown category {} in column {}", v.to_f64(), j
+                        )))
+                    }
+                }
+                offset += widths[j];
+            }
+        }
+        Tensor::new(data, vec![rows, width])
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+
+    /// Map one-hot rows back to category codes. An all-zero block decodes
+    /// to the dropped first category with `drop_first`, otherwise (an
+    /// ignored unknown) to NaN.
+    pub fn inverse_transform(&self, encoded: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let categories = self.categories.as_ref().expect("fit() must be called before inverse_transform()");
+        let widths = self.block_widths(categories);
+        let rows = check_columns(encoded, widths.iter().sum())?;
+        let width: usize = widths.iter().sum();
+        let dropped = usize::from(self.drop_first);
+        let mut data = Vec::with_capacity(rows * categories.len());
+        for i in 0..rows {
+            let mut offset = 0;
+            for (j, cats) in categories.iter().enumerate() {
+                let block = &encoded.data()[i * width + offset..i * width + offset + widths[j]];
+                let hot = block.iter().enumerate().filter(|(_, &v)| v > T::ZERO).max_by(|a, b| a.1.to_f64().total_cmp(&b.1.to_f64()));
+                data.push(match hot {
+                    Some((k, _)) => cats[k + dropped],
+                    None if self.drop_first => cats[0],
+                    None => T::from_f64(f64::NAN),
+                });
+                offset += widths[j];
+            }
+        }
+        Tensor::new(data, vec![rows, categories.len()])
+    }
+}
+
+/// Encode each feature column's categories as integers `0..n_categories`,
+/// in ascending order of category, keeping the column layout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct OrdinalEncoder<T: Float> {
+    /// Sorted categories of each input column.
+    pub categories: Option<Vec<Vec<T>>>,
+    /// Code for categories not seen in `fit`; `None` makes them an error.
+    pub unknown_value: Option<T>,
+}
+
+impl<T: Float> Default for OrdinalEncoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> OrdinalEncoder<T> {
+    pub fn new() -> Self {
+        OrdinalEncoder { categories: None, unknown_value: None }
+    }
+
+    pub fn with_unknown_value(mut self, value: T) -> Self {
+        self.unknown_value = Some(value);
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        self.categories = Some(column_categories(x)?);
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let categories = self.categories.as_ref().expect("fit() must be called before transform()");
+        let rows = check_columns(x, categories.len())?;
+        let mut data = Vec::with_capacity(rows * categories.len());
+        for i in 0..rows {
+            for (j, cats) in categories.iter().enumerate() {
+                let v = x.get(&[i, j])?;
+                data.push(match (cats.iter().position(|&c| c == v), self.unknown_value) {
+                    (Some(k), _) => T::from_usize(k),
+                    (None, Some(code)) => code,
+                    (None, None) => {
+                        return Err(TensorError::InvalidOperation(format!(
+                            "unknown category {} in column {}", v.to_f64(), j
+                        )))
+                    }
+                });
+            }
+        }
+        Tensor::new(data, vec![rows, categories.len()])
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+
+    /// Map codes back to categories; codes out of range (such as
+    /// `unknown_value`) decode to NaN.
+    pub fn inverse_transform(&self, encoded: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let categories = self.categories.as_ref().expect("fit() must be called before inverse_transform()");
+        let rows = check_columns(encoded, categories.len())?;
+        let cols = categories.len();
+        let data = (0..rows * cols)
+            .map(|idx| {
+                let code = encoded.data()[idx].to_f64();
+                let cats = &categories[idx % cols];
+                if code >= 0.0 && code.fract() == 0.0 && (code as usize) < cats.len() {
+                    cats[code as usize]
+                } else {
+                    T::from_f64(f64::NAN)
+                }
+            })
+            .collect();
+        Tensor::new(data, vec![rows, cols])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,4 +336,39 @@ mod tests {
         assert_eq!(oh.get(&[2, 2]).unwrap(), 1.0);
         assert_eq!(oh.get(&[3, 1]).unwrap(), 1.0);
     }
+
+    #[test]
+    fn test_one_hot_encoder() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![2.0, 10.0], vec![0.0, 10.0], vec![2.0, 20.0]]).unwrap();
+        let mut enc = OneHotEncoder::new();
+        let out = enc.fit_transform(&x).unwrap();
+        assert_eq!(out.shape_vec(), vec![3, 4]);
+        assert_eq!(out.data(), &[0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+        let unseen: Tensor<f64> = Tensor::from_vec2d(&[vec![1.0, 10.0]]).unwrap();
+        assert!(enc.transform(&unseen).is_err());
+        assert_eq!(enc.inverse_transform(&out).unwrap().data(), x.data());
+
+        let mut enc = OneHotEncoder::new().with_drop_first(true).with_handle_unknown(HandleUnknown::Ignore);
+        let out = enc.fit_transform(&x).unwrap();
+        assert_eq!(out.shape_vec(), vec![3, 2]);
+        assert_eq!(out.data(), &[1.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(enc.inverse_transform(&out).unwrap().data(), x.data());
+        assert_eq!(enc.transform(&unseen).unwrap().data(), &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_ordinal_encoder() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![5.0, 1.0], vec![3.0, 1.0], vec![5.0, 0.0]]).unwrap();
+        let mut enc = OrdinalEncoder::new();
+        let out = enc.fit_transform(&x).unwrap();
+        assert_eq!(out.data(), &[1.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(enc.inverse_transform(&out).unwrap().data(), x.data());
+
+        let unseen: Tensor<f64> = Tensor::from_vec2d(&[vec![4.0, 0.0]]).unwrap();
+        assert!(enc.transform(&unseen).is_err());
+        let enc = enc.with_unknown_value(-1.0);
+        let coded = enc.transform(&unseen).unwrap();
+        assert_eq!(coded.data(), &[-1.0, 0.0]);
+        assert!(enc.inverse_transform(&coded).unwrap().data()[0].is_nan());
+    }
 }
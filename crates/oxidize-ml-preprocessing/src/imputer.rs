@@ -0,0 +1,225 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+/// Which statistic `SimpleImputer` fills missing values with.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ImputeStrategy {
+    Mean,
+    Median,
+    /// The most common value; ties go to the smallest.
+    MostFrequent,
+    /// The given value for every column.
+    Constant(f64),
+}
+
+fn check_width<T: Float>(x: &Tensor<T>, expected: usize) -> TensorResult<(usize, usize)> {
+    let (rows, cols) = (x.shape().dim(0)?, x.shape().dim(1)?);
+    if cols != expected {
+        return Err(TensorError::ShapeMismatch {
+            expected: vec![rows, expected],
+            got: x.shape_vec(),
+        });
+    }
+    Ok((rows, cols))
+}
+
+/// Observed (non-NaN) values of column `j`.
+fn observed<T: Float>(x: &Tensor<T>, j: usize) -> TensorResult<Vec<f64>> {
+    let (rows, cols) = (x.shape().dim(0)?, x.shape().dim(1)?);
+    Ok((0..rows).map(|i| x.data()[i * cols + j].to_f64()).filter(|v| !v.is_nan()).collect())
+}
+
+fn column_mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Replace NaNs with a per-column statistic computed in `fit`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct SimpleImputer<T: Float> {
+    pub strategy: ImputeStrategy,
+    /// Fill value of each column. Columns with no observed values get 0
+    /// unless the strategy is `Constant`.
+    pub statistics: Option<Vec<T>>,
+}
+
+impl<T: Float> SimpleImputer<T> {
+    pub fn new(strategy: ImputeStrategy) -> Self {
+        SimpleImputer { strategy, statistics: None }
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let cols = x.shape().dim(1)?;
+        let mut statistics = Vec::with_capacity(cols);
+        for j in 0..cols {
+            let mut values = observed(x, j)?;
+            let stat = match self.strategy {
+                ImputeStrategy::Constant(c) => c,
+                _ if values.is_empty() => 0.0,
+                ImputeStrategy::Mean => column_mean(&values),
+                ImputeStrategy::Median => {
+                    values.sort_by(|a, b| a.total_cmp(b));
+                    let mid = values.len() / 2;
+                    if values.len() % 2 == 0 { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] }
+                }
+                ImputeStrategy::MostFrequent => {
+                    values.sort_by(|a, b| a.total_cmp(b));
+                    let (mut best, mut best_count) = (values[0], 0);
+                    for run in values.chunk_by(|a, b| a == b) {
+                        if run.len() > best_count {
+                            best = run[0];
+                            best_count = run.len();
+                        }
+                    }
+                    best
+                }
+            };
+            statistics.push(T::from_f64(stat));
+        }
+        self.statistics = Some(statistics);
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let statistics = self.statistics.as_ref().expect("fit() must be called before transform()");
+        let (_, cols) = check_width(x, statistics.len())?;
+        let data = x
+            .data()
+            .iter()
+            .enumerate()
+            .map(|(idx, &v)| if v.is_nan() { statistics[idx % cols] } else { v })
+            .collect();
+        Tensor::new(data, x.shape_vec())
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+/// Euclidean distance over the coordinates present in both rows, scaled up
+/// by `len / present` to make up for the missing ones; `None` if no
+/// coordinate is present in both.
+fn nan_euclidean(a: &[f64], b: &[f64]) -> Option<f64> {
+    let (mut sum, mut present) = (0.0, 0);
+    for (x, y) in a.iter().zip(b) {
+        if !x.is_nan() && !y.is_nan() {
+            sum += (x - y) * (x - y);
+            present += 1;
+        }
+    }
+    (present > 0).then(|| (sum * a.len() as f64 / present as f64).sqrt())
+}
+
+/// Fill each missing value with the mean of that feature over the
+/// `n_neighbors` nearest training rows that have it, measuring distance on
+/// the features both rows have. Falls back to the training column mean
+/// when no such row exists.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct KNNImputer<T: Float> {
+    pub n_neighbors: usize,
+    x_train: Option<Vec<f64>>,
+    column_means: Option<Vec<f64>>,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Float> KNNImputer<T> {
+    pub fn new(n_neighbors: usize) -> Self {
+        KNNImputer { n_neighbors, x_train: None, column_means: None, _marker: std::marker::PhantomData }
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let cols = x.shape().dim(1)?;
+        let means = (0..cols)
+            .map(|j| observed(x, j).map(|v| if v.is_empty() { 0.0 } else { column_mean(&v) }))
+            .collect::<TensorResult<Vec<f64>>>()?;
+        self.x_train = Some(x.data().iter().map(|v| v.to_f64()).collect());
+        self.column_means = Some(means);
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let (train, means) = match (&self.x_train, &self.column_means) {
+            (Some(t), Some(m)) => (t, m),
+            _ => return Err(TensorError::InvalidOperation("KNNImputer not fitted".into())),
+        };
+        let (_, cols) = check_width(x, means.len())?;
+        let mut out: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+        for row in out.chunks_mut(cols.max(1)) {
+            let missing: Vec<usize> = (0..cols).filter(|&j| row[j].is_nan()).collect();
+            if missing.is_empty() {
+                continue;
+            }
+            let query = row.to_vec();
+            let distances: Vec<Option<f64>> = train.chunks(cols.max(1)).map(|t| nan_euclidean(&query, t)).collect();
+            for j in missing {
+                let mut donors: Vec<(f64, f64)> = train
+                    .chunks(cols.max(1))
+                    .zip(&distances)
+                    .filter_map(|(t, d)| match d {
+                        Some(d) if !t[j].is_nan() => Some((*d, t[j])),
+                        _ => None,
+                    })
+                    .collect();
+                donors.sort_by(|a, b| a.0.total_cmp(&b.0));
+                donors.truncate(self.n_neighbors.max(1));
+                row[j] = if donors.is_empty() { means[j] } else { donors.iter().map(|&(_, v)| v).sum::<f64>() / donors.len() as f64 };
+            }
+        }
+        Tensor::new(out.into_iter().map(T::from_f64).collect(), x.shape_vec())
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_imputer() {
+        let nan = f64::NAN;
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![1.0, 7.0, nan],
+            vec![nan, 7.0, nan],
+            vec![3.0, 2.0, nan],
+            vec![8.0, nan, nan],
+        ]).unwrap();
+        let fill = |strategy| {
+            let mut imp = SimpleImputer::new(strategy);
+            let out = imp.fit_transform(&x).unwrap();
+            (out.data()[3], out.data()[10], out.data()[2])
+        };
+        assert_eq!(fill(ImputeStrategy::Mean), (4.0, 16.0 / 3.0, 0.0));
+        assert_eq!(fill(ImputeStrategy::Median), (3.0, 7.0, 0.0));
+        assert_eq!(fill(ImputeStrategy::MostFrequent), (1.0, 7.0, 0.0));
+        assert_eq!(fill(ImputeStrategy::Constant(-1.0)), (-1.0, -1.0, -1.0));
+    }
+
+    #[test]
+    fn test_knn_imputer() {
+        let nan = f64::NAN;
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![0.0, 0.0, 1.0],
+            vec![0.1, 0.0, 3.0],
+            vec![10.0, 10.0, 100.0],
+            vec![0.0, 0.1, nan],
+        ]).unwrap();
+        let mut imp = KNNImputer::new(2);
+        let out = imp.fit_transform(&x).unwrap();
+        assert_eq!(out.data()[11], 2.0);
+        assert_eq!(&out.data()[..9], &x.data()[..9]);
+
+        let unseen: Tensor<f64> = Tensor::from_vec2d(&[vec![nan, nan, nan]]).unwrap();
+        let means = imp.transform(&unseen).unwrap();
+        assert!((means.data()[0] - 10.1 / 4.0).abs() < 1e-12);
+        assert!((means.data()[2] - 104.0 / 3.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,224 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_linalg::thin_svd;
+use serde::{Deserialize, Serialize};
+
+/// Incremental PCA: fits principal components one batch at a time, so
+/// data that does not fit in memory can be streamed through `partial_fit`.
+///
+/// Each batch is stacked under the current components (scaled by their
+/// singular values) and a mean-shift correction row, and the small stacked
+/// matrix is re-decomposed; memory is `O(batch_size · n_features)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct IncrementalPCA<T: Float> {
+    pub n_components: usize,
+    /// Rows per batch in `fit`; `5 · n_features` when `None`.
+    pub batch_size: Option<usize>,
+    pub components: Option<Tensor<T>>,   // [n_components, n_features]
+    pub singular_values: Option<Vec<f64>>,
+    pub explained_variance: Option<Vec<f64>>,
+    pub mean: Option<Tensor<T>>,
+    /// Per-feature variance of everything seen so far.
+    pub var: Option<Vec<f64>>,
+    pub n_samples_seen: usize,
+}
+
+impl<T: Float> IncrementalPCA<T> {
+    pub fn new(n_components: usize) -> Self {
+        IncrementalPCA {
+            n_components,
+            batch_size: None,
+            components: None,
+            singular_values: None,
+            explained_variance: None,
+            mean: None,
+            var: None,
+            n_samples_seen: 0,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Forget everything and fit on `x` in batches of `batch_size` rows.
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        *self = IncrementalPCA { batch_size: self.batch_size, ..IncrementalPCA::new(self.n_components) };
+        let batch = self.batch_size.unwrap_or(5 * p).max(self.n_components).max(1);
+        let mut start = 0;
+        while start < n {
+            // Fold a short final batch into the previous one so no batch
+            // has fewer rows than components.
+            let mut end = (start + batch).min(n);
+            if n - end < self.n_components {
+                end = n;
+            }
+            let rows: Vec<usize> = (start..end).collect();
+            self.partial_fit(&x.index_select(0, &rows)?)?;
+            start = end;
+        }
+        Ok(())
+    }
+
+    /// Update the components with one more batch of rows.
+    pub fn partial_fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let n_b = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        let k = self.n_components;
+        if k == 0 || k > p {
+            return Err(TensorError::InvalidOperation(format!(
+                "n_components must be between 1 and the number of features ({}), got {}", p, k
+            )));
+        }
+        if self.n_samples_seen == 0 && n_b < k {
+            return Err(TensorError::InvalidOperation(format!(
+                "the first batch needs at least n_components ({}) rows, got {}", k, n_b
+            )));
+        }
+        if let Some(mean) = &self.mean {
+            if mean.numel() != p {
+                return Err(TensorError::DimensionMismatch(format!("expected {} features, got {}", mean.numel(), p)));
+            }
+        }
+        if n_b == 0 {
+            return Ok(());
+        }
+
+        let data: Vec<f64> = x.data().iter().map(|v| v.to_f64()).collect();
+        let mut batch_mean = vec![0.0; p];
+        for row in data.chunks(p) {
+            for (m, v) in batch_mean.iter_mut().zip(row) {
+                *m += v / n_b as f64;
+            }
+        }
+        let mut batch_m2 = vec![0.0; p];
+        for row in data.chunks(p) {
+            for j in 0..p {
+                batch_m2[j] += (row[j] - batch_mean[j]).powi(2);
+            }
+        }
+
+        let seen = self.n_samples_seen as f64;
+        let total = seen + n_b as f64;
+        let old_mean: Vec<f64> = match &self.mean {
+            Some(m) => m.data().iter().map(|v| v.to_f64()).collect(),
+            None => vec![0.0; p],
+        };
+        let old_var = self.var.clone().unwrap_or_else(|| vec![0.0; p]);
+        let mut new_mean = vec![0.0; p];
+        let mut new_var = vec![0.0; p];
+        for j in 0..p {
+            let delta = batch_mean[j] - old_mean[j];
+            new_mean[j] = old_mean[j] + delta * n_b as f64 / total;
+            new_var[j] = (old_var[j] * seen + batch_m2[j] + delta * delta * seen * n_b as f64 / total) / total;
+        }
+
+        // Rows to decompose: previous components scaled by their singular
+        // values, the centred batch, and a row correcting for the mean shift.
+        let mut stacked = Vec::new();
+        if let (Some(components), Some(sv)) = (&self.components, &self.singular_values) {
+            for (c, &s) in sv.iter().enumerate() {
+                stacked.extend(components.data()[c * p..(c + 1) * p].iter().map(|v| v.to_f64() * s));
+            }
+        }
+        for row in data.chunks(p) {
+            stacked.extend(row.iter().zip(&batch_mean).map(|(v, m)| v - m));
+        }
+        if self.n_samples_seen > 0 {
+            let scale = (seen * n_b as f64 / total).sqrt();
+            stacked.extend(old_mean.iter().zip(&batch_mean).map(|(o, b)| scale * (o - b)));
+        }
+        let n_rows = stacked.len() / p;
+        let (_, sigma, v) = thin_svd(&Tensor::new(stacked, vec![n_rows, p])?)?;
+        let kk = v.shape().dim(1)?;
+
+        let mut components = Vec::with_capacity(k * p);
+        for c in 0..k {
+            components.extend((0..p).map(|j| T::from_f64(v.data()[j * kk + c])));
+        }
+        let singular: Vec<f64> = sigma.data()[..k].to_vec();
+        self.explained_variance = Some(singular.iter().map(|s| s * s / total).collect());
+        self.singular_values = Some(singular);
+        self.components = Some(Tensor::new(components, vec![k, p])?);
+        self.mean = Some(Tensor::new(new_mean.into_iter().map(T::from_f64).collect(), vec![p])?);
+        self.var = Some(new_var);
+        self.n_samples_seen += n_b;
+        Ok(())
+    }
+
+    /// Transform data by projecting onto principal components.
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let mean = self.mean.as_ref().ok_or_else(|| {
+            TensorError::InvalidOperation("IncrementalPCA not fitted".into())
+        })?;
+        let components = self.components.as_ref().unwrap();
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        let mut centered_data = Vec::with_capacity(n * p);
+        for i in 0..n {
+            for j in 0..p {
+                centered_data.push(x.get(&[i, j])? - mean.data()[j]);
+            }
+        }
+        Tensor::new(centered_data, vec![n, p])?.matmul(&components.t()?)
+    }
+
+    /// Fit and transform in one step.
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+
+    /// Share of the total variance explained by each component.
+    pub fn explained_variance_ratio(&self) -> Option<Vec<f64>> {
+        let (ev, var) = (self.explained_variance.as_ref()?, self.var.as_ref()?);
+        let total: f64 = var.iter().sum();
+        Some(ev.iter().map(|&v| if total > 0.0 { v / total } else { 0.0 }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incremental_pca_matches_batch() {
+        let rows: Vec<Vec<f64>> = (0..30)
+            .map(|i| {
+                let t = i as f64 * 0.37;
+                vec![t.sin() * 3.0, t.cos() + 0.5 * t.sin(), (t * 1.7).sin() * 0.3, t * 0.1]
+            })
+            .collect();
+        let x = Tensor::from_vec2d(&rows).unwrap();
+
+        // Keeping every component loses nothing between batches, so the
+        // result matches a single decomposition exactly.
+        let mut ipca = IncrementalPCA::new(4).with_batch_size(7);
+        ipca.fit(&x).unwrap();
+        assert_eq!(ipca.n_samples_seen, 30);
+
+        // Reference: SVD of the whole centred matrix.
+        let mean = x.mean_axis(0).unwrap();
+        let centered: Vec<f64> = x.data().chunks(4).flat_map(|r| r.iter().zip(mean.data()).map(|(v, m)| v - m).collect::<Vec<_>>()).collect();
+        let (_, sigma, v) = thin_svd(&Tensor::new(centered, vec![30, 4]).unwrap()).unwrap();
+
+        let sv = ipca.singular_values.as_ref().unwrap();
+        let components = ipca.components.as_ref().unwrap().data();
+        for c in 0..2 {
+            assert!((sv[c] - sigma.data()[c]).abs() < 1e-8);
+            let dot: f64 = (0..4).map(|j| components[c * 4 + j] * v.data()[j * 4 + c]).sum();
+            assert!((dot.abs() - 1.0).abs() < 1e-8);
+        }
+        let ratio = ipca.explained_variance_ratio().unwrap();
+        assert!((ratio.iter().sum::<f64>() - 1.0).abs() < 1e-8);
+
+        let mut truncated = IncrementalPCA::new(2).with_batch_size(7);
+        assert_eq!(truncated.fit_transform(&x).unwrap().shape_vec(), vec![30, 2]);
+        let sv = truncated.singular_values.as_ref().unwrap();
+        assert!((sv[0] - sigma.data()[0]).abs() < 0.05 * sigma.data()[0]);
+    }
+}
@@ -1,11 +1,19 @@
 pub mod scaler;
 pub mod encoder;
+pub mod imputer;
+pub mod polynomial;
 pub mod split;
 pub mod pca;
+pub mod incremental_pca;
+pub mod truncated_svd;
 pub mod tsne;
 
 pub use scaler::*;
 pub use encoder::*;
+pub use imputer::*;
+pub use polynomial::*;
 pub use split::*;
 pub use pca::*;
+pub use incremental_pca::*;
+pub use truncated_svd::*;
 pub use tsne::*;
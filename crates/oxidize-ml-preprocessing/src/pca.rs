@@ -1,5 +1,18 @@
 use oxidize_ml_core::{Float, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_linalg::randomized_svd;
+use serde::{Deserialize, Serialize};
+
+/// How `PCA` finds its components.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SvdSolver {
+    /// Power iteration on the full `[p, p]` covariance matrix.
+    #[default]
+    Full,
+    /// Randomized SVD of the centred data; much faster when there are
+    /// thousands of features and only a few components are wanted.
+    Randomized,
+}
 
 /// Principal Component Analysis (PCA).
 ///
@@ -7,12 +20,20 @@ use oxidize_ml_core::error::TensorResult;
 /// principal components (directions of maximum variance).
 ///
 /// Uses the power iteration method for eigendecomposition of the
-/// covariance matrix (pure Rust, no external BLAS).
+/// covariance matrix (pure Rust, no external BLAS), or a randomized SVD
+/// with `SvdSolver::Randomized`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct PCA<T: Float> {
     pub n_components: usize,
     pub components: Option<Tensor<T>>,   // [n_components, n_features]
     pub explained_variance: Option<Vec<f64>>,
     pub mean: Option<Tensor<T>>,
+    #[serde(default)]
+    pub svd_solver: SvdSolver,
+    /// Seed for the randomized solver.
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 impl<T: Float> PCA<T> {
@@ -22,9 +43,16 @@ impl<T: Float> PCA<T> {
             components: None,
             explained_variance: None,
             mean: None,
+            svd_solver: SvdSolver::Full,
+            seed: None,
         }
     }
 
+    pub fn with_svd_solver(mut self, svd_solver: SvdSolver) -> Self {
+        self.svd_solver = svd_solver;
+        self
+    }
+
     pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
@@ -44,6 +72,15 @@ impl<T: Float> PCA<T> {
         }
         let centered = Tensor::new(centered_data, vec![n, p])?;
 
+        if self.svd_solver == SvdSolver::Randomized {
+            // Components are the top right singular vectors; variances
+            // follow from the singular values.
+            let (_, sigma, v) = randomized_svd(&centered, k, 10, 4, self.seed)?;
+            self.components = Some(v.t()?);
+            self.explained_variance = Some(sigma.data().iter().map(|s| s.to_f64().powi(2) / n as f64).collect());
+            return Ok(());
+        }
+
         // 2. Compute covariance matrix: C = (1/n) * Xᵀ X
         let ct = centered.t()?;
         let cov = ct.matmul(&centered)?;
@@ -59,7 +96,7 @@ impl<T: Float> PCA<T> {
 
             // Normalize
             let mut norm: T = v.iter().map(|&x| x * x).sum::<T>().sqrt();
-            for x in v.iter_mut() { *x = *x / norm; }
+            for x in v.iter_mut() { *x /= norm; }
 
             // Power iteration
             for _ in 0..200 {
@@ -67,7 +104,7 @@ impl<T: Float> PCA<T> {
                 let mut w = vec![T::ZERO; p];
                 for i in 0..p {
                     for j in 0..p {
-                        w[i] = w[i] + T::from_f64(cov_data[i * p + j].to_f64()) * v[j];
+                        w[i] += T::from_f64(cov_data[i * p + j].to_f64()) * v[j];
                     }
                 }
 
@@ -96,7 +133,7 @@ impl<T: Float> PCA<T> {
 
             for i in 0..p {
                 for j in 0..p {
-                    cov_data[i * p + j] = cov_data[i * p + j] - T::from_f64(lambda) * v[i] * v[j];
+                    cov_data[i * p + j] -= T::from_f64(lambda) * v[i] * v[j];
                 }
             }
         }
@@ -175,5 +212,12 @@ mod tests {
 
         let ev = pca.explained_variance.as_ref().unwrap();
         assert!(ev[0] > 0.0, "First eigenvalue should be positive");
+
+        let mut randomized = PCA::new(1).with_svd_solver(SvdSolver::Randomized);
+        let projected = randomized.fit_transform(&x).unwrap();
+        assert!((randomized.explained_variance.as_ref().unwrap()[0] - ev[0]).abs() < 1e-6);
+        for (a, b) in projected.data().iter().zip(x_reduced.data()) {
+            assert!((a.abs() - b.abs()).abs() < 1e-4);
+        }
     }
 }
@@ -0,0 +1,278 @@
+use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
+
+fn check_width<T: Float>(x: &Tensor<T>, expected: usize) -> TensorResult<usize> {
+    let (rows, cols) = (x.shape().dim(0)?, x.shape().dim(1)?);
+    if cols != expected {
+        return Err(TensorError::ShapeMismatch {
+            expected: vec![rows, expected],
+            got: x.shape_vec(),
+        });
+    }
+    Ok(rows)
+}
+
+/// Products of the input features up to `degree`.
+///
+/// Columns come in order of degree, and within a degree in lexicographic
+/// order of the features involved: for `[a, b]` and degree 2 that is
+/// `1, a, b, a², ab, b²`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct PolynomialFeatures<T: Float> {
+    pub degree: usize,
+    /// Only products of distinct features (no `a²`).
+    pub interaction_only: bool,
+    /// Start with a constant column of ones.
+    pub include_bias: bool,
+    /// Exponent of each input feature in each output column.
+    pub powers: Option<Vec<Vec<usize>>>,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Float> PolynomialFeatures<T> {
+    pub fn new(degree: usize) -> Self {
+        PolynomialFeatures {
+            degree,
+            interaction_only: false,
+            include_bias: true,
+            powers: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn with_interaction_only(mut self, interaction_only: bool) -> Self {
+        self.interaction_only = interaction_only;
+        self
+    }
+
+    pub fn with_include_bias(mut self, include_bias: bool) -> Self {
+        self.include_bias = include_bias;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let p = x.shape().dim(1)?;
+        let mut powers = Vec::new();
+        if self.include_bias {
+            powers.push(vec![0; p]);
+        }
+        // Non-decreasing feature index sequences of each length.
+        let mut level: Vec<Vec<usize>> = vec![Vec::new()];
+        for _ in 0..self.degree {
+            let mut next = Vec::new();
+            for combo in &level {
+                let start = match combo.last() {
+                    Some(&last) if self.interaction_only => last + 1,
+                    Some(&last) => last,
+                    None => 0,
+                };
+                for f in start..p {
+                    let mut c = combo.clone();
+                    c.push(f);
+                    next.push(c);
+                }
+            }
+            for combo in &next {
+                let mut row = vec![0; p];
+                for &f in combo {
+                    row[f] += 1;
+                }
+                powers.push(row);
+            }
+            level = next;
+        }
+        self.powers = Some(powers);
+        Ok(())
+    }
+
+    /// Number of output columns.
+    pub fn n_output_features(&self) -> Option<usize> {
+        self.powers.as_ref().map(|p| p.len())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let powers = self.powers.as_ref().expect("fit() must be called before transform()");
+        let p = powers.first().map_or(x.shape().dim(1)?, |row| row.len());
+        let rows = check_width(x, p)?;
+        let mut data = Vec::with_capacity(rows * powers.len());
+        for row in x.data().chunks(p.max(1)).take(rows) {
+            for exps in powers {
+                let mut v = T::ONE;
+                for (&xi, &e) in row.iter().zip(exps) {
+                    for _ in 0..e {
+                        v *= xi;
+                    }
+                }
+                data.push(v);
+            }
+        }
+        Tensor::new(data, vec![rows, powers.len()])
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+/// Values at `x` of the B-spline basis functions of `degree` on the
+/// `knots`, via the Cox–de Boor recursion. `x` is clamped to the base
+/// interval `[knots[degree], knots[len - degree - 1]]`.
+fn bspline_basis(knots: &[f64], degree: usize, x: f64) -> Vec<f64> {
+    let n_basis = knots.len() - degree - 1;
+    let (lo, hi) = (knots[degree], knots[n_basis]);
+    let x = x.clamp(lo, hi);
+    // Knot span with knots[span] <= x < knots[span + 1]; the right end
+    // belongs to the last span.
+    let mut span = degree;
+    while span + 1 < n_basis && knots[span + 1] <= x {
+        span += 1;
+    }
+
+    let mut values = vec![0.0; degree + 1];
+    let mut left = vec![0.0; degree + 1];
+    let mut right = vec![0.0; degree + 1];
+    values[0] = 1.0;
+    for j in 1..=degree {
+        left[j] = x - knots[span + 1 - j];
+        right[j] = knots[span + j] - x;
+        let mut saved = 0.0;
+        for r in 0..j {
+            let temp = values[r] / (right[r + 1] + left[j - r]);
+            values[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        values[j] = saved;
+    }
+
+    let mut out = vec![0.0; n_basis];
+    for (r, v) in values.into_iter().enumerate() {
+        out[span - degree + r] = v;
+    }
+    out
+}
+
+/// B-spline basis expansion of each feature: `n_knots` evenly spaced knots
+/// between the feature's training minimum and maximum give
+/// `n_knots + degree - 1` smooth bumps per feature. Values outside the
+/// training range are clamped to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct SplineTransformer<T: Float> {
+    pub n_knots: usize,
+    pub degree: usize,
+    /// Keep every basis function. The functions of a feature sum to one,
+    /// so with an intercept in the model the last one is redundant;
+    /// `false` drops it.
+    pub include_bias: bool,
+    /// Full knot vector of each feature, including the `degree` extra knots
+    /// on each side.
+    pub knots: Option<Vec<Vec<f64>>>,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Float> SplineTransformer<T> {
+    pub fn new(n_knots: usize, degree: usize) -> Self {
+        SplineTransformer {
+            n_knots,
+            degree,
+            include_bias: true,
+            knots: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn with_include_bias(mut self, include_bias: bool) -> Self {
+        self.include_bias = include_bias;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        if self.n_knots < 2 {
+            return Err(TensorError::InvalidOperation("SplineTransformer needs at least 2 knots".into()));
+        }
+        let (rows, p) = (x.shape().dim(0)?, x.shape().dim(1)?);
+        let mut knots = Vec::with_capacity(p);
+        for j in 0..p {
+            let column = (0..rows).map(|i| x.data()[i * p + j].to_f64());
+            let (lo, hi) = column.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+            if !lo.is_finite() || !hi.is_finite() {
+                return Err(TensorError::InvalidOperation(format!("column {} has no finite values", j)));
+            }
+            // A constant column still needs distinct knots.
+            let (lo, hi) = if hi > lo { (lo, hi) } else { (lo - 0.5, hi + 0.5) };
+            let step = (hi - lo) / (self.n_knots - 1) as f64;
+            let d = self.degree as isize;
+            let k = (-d..self.n_knots as isize + d).map(|i| lo + i as f64 * step).collect();
+            knots.push(k);
+        }
+        self.knots = Some(knots);
+        Ok(())
+    }
+
+    /// Output columns per input feature.
+    pub fn n_splines(&self) -> usize {
+        self.n_knots + self.degree - 1 - usize::from(!self.include_bias)
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let knots = self.knots.as_ref().expect("fit() must be called before transform()");
+        let p = knots.len();
+        let rows = check_width(x, p)?;
+        let per = self.n_splines();
+        let mut data = Vec::with_capacity(rows * p * per);
+        for i in 0..rows {
+            for (j, k) in knots.iter().enumerate() {
+                let basis = bspline_basis(k, self.degree, x.data()[i * p + j].to_f64());
+                data.extend(basis[..per].iter().map(|&v| T::from_f64(v)));
+            }
+        }
+        Tensor::new(data, vec![rows, p * per])
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polynomial_features() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![2.0, 3.0]]).unwrap();
+        let mut poly = PolynomialFeatures::new(2);
+        assert_eq!(poly.fit_transform(&x).unwrap().data(), &[1.0, 2.0, 3.0, 4.0, 6.0, 9.0]);
+
+        let mut inter = PolynomialFeatures::new(3).with_interaction_only(true).with_include_bias(false);
+        assert_eq!(inter.fit_transform(&x).unwrap().data(), &[2.0, 3.0, 6.0]);
+    }
+
+    #[test]
+    fn test_spline_transformer() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![0.0], vec![0.3], vec![0.5], vec![1.0], vec![2.0]]).unwrap();
+        let mut spline = SplineTransformer::new(5, 3);
+        spline.fit(&x).unwrap();
+        let out = spline.transform(&x).unwrap();
+        assert_eq!(out.shape_vec(), vec![5, 7]);
+        for row in out.data().chunks(7) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            assert!(row.iter().all(|&v| v >= 0.0));
+        }
+        // At a knot a cubic basis is (1/6, 2/3, 1/6) on three functions.
+        let at_knot = &out.data()[14..21];
+        assert!((at_knot[1] - 1.0 / 6.0).abs() < 1e-12 && (at_knot[2] - 2.0 / 3.0).abs() < 1e-12);
+
+        let beyond: Tensor<f64> = Tensor::from_vec2d(&[vec![5.0]]).unwrap();
+        assert_eq!(spline.transform(&beyond).unwrap().data(), &out.data()[28..35]);
+
+        let mut no_bias = SplineTransformer::new(5, 3).with_include_bias(false);
+        assert_eq!(no_bias.fit_transform(&x).unwrap().shape_vec(), vec![5, 6]);
+    }
+}
@@ -1,13 +1,25 @@
-use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_core::{Float, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
 
 /// Standardize features by removing the mean and scaling to unit variance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct StandardScaler<T: Float> {
     pub mean: Option<Tensor<T>>,
     pub std: Option<Tensor<T>>,
 }
 
+impl<T: Float> Default for StandardScaler<T>
+where
+    Standard: Distribution<T>,
+ {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Float> StandardScaler<T>
 where
     Standard: Distribution<T>,
@@ -41,6 +53,14 @@ where
         centered.div(&std_safe)
     }
 
+    /// Map standardized data back to the original scale.
+    pub fn inverse_transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let mean = self.mean.as_ref().expect("fit() must be called before inverse_transform()");
+        let std = self.std.as_ref().expect("fit() must be called before inverse_transform()");
+        let std_safe = std.unsqueeze(0)?.apply(|v| if v.abs() < T::EPSILON { T::ONE } else { v });
+        x.mul(&std_safe)?.add(&mean.unsqueeze(0)?)
+    }
+
     /// Fit and transform in one step.
     pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
         self.fit(x)?;
@@ -49,11 +69,22 @@ where
 }
 
 /// Scale features to [0, 1] range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct MinMaxScaler<T: Float> {
     pub min: Option<Tensor<T>>,
     pub max: Option<Tensor<T>>,
 }
 
+impl<T: Float> Default for MinMaxScaler<T>
+where
+    Standard: Distribution<T>,
+ {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Float> MinMaxScaler<T>
 where
     Standard: Distribution<T>,
@@ -99,6 +130,553 @@ where
         centered.div(&range_safe)
     }
 
+    pub fn inverse_transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let min = self.min.as_ref().expect("fit() first").unsqueeze(0)?;
+        let max = self.max.as_ref().expect("fit() first").unsqueeze(0)?;
+
+        let range = max.sub(&min)?;
+        let range_safe = range.apply(|v| if v.abs() < T::EPSILON { T::ONE } else { v });
+        x.mul(&range_safe)?.add(&min)
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+fn check_width<T: Float>(x: &Tensor<T>, expected: usize) -> TensorResult<(usize, usize)> {
+    let (rows, cols) = (x.shape().dim(0)?, x.shape().dim(1)?);
+    if cols != expected {
+        return Err(TensorError::ShapeMismatch {
+            expected: vec![rows, expected],
+            got: x.shape_vec(),
+        });
+    }
+    Ok((rows, cols))
+}
+
+/// Column `j` of `x` as `f64`.
+fn column<T: Float>(x: &Tensor<T>, j: usize) -> Vec<f64> {
+    let cols = x.shape_vec()[1];
+    x.data().iter().skip(j).step_by(cols).map(|v| v.to_f64()).collect()
+}
+
+/// Apply `f(value, column)` to every element of `x`.
+fn map_columns<T: Float, F: Fn(f64, usize) -> f64>(x: &Tensor<T>, f: F) -> TensorResult<Tensor<T>> {
+    let cols = x.shape().dim(1)?.max(1);
+    let data = x.data().iter().enumerate().map(|(idx, v)| T::from_f64(f(v.to_f64(), idx % cols))).collect();
+    Tensor::new(data, x.shape_vec())
+}
+
+/// The `q`-th quantile (`0..=1`) of sorted values, interpolating linearly.
+fn quantile_sorted(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let pos = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+/// Scale factor that leaves constant columns unchanged.
+fn safe_scale(s: f64) -> f64 {
+    if s.abs() < f64::EPSILON { 1.0 } else { s }
+}
+
+/// Scale features with statistics robust to outliers: subtract the median
+/// and divide by the interquartile range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct RobustScaler<T: Float> {
+    pub with_centering: bool,
+    pub with_scaling: bool,
+    /// Percentiles (`0..=100`) spanning the scale; `(25, 75)` is the IQR.
+    pub quantile_range: (f64, f64),
+    pub center: Option<Tensor<T>>,
+    pub scale: Option<Tensor<T>>,
+}
+
+impl<T: Float> Default for RobustScaler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> RobustScaler<T> {
+    pub fn new() -> Self {
+        RobustScaler {
+            with_centering: true,
+            with_scaling: true,
+            quantile_range: (25.0, 75.0),
+            center: None,
+            scale: None,
+        }
+    }
+
+    pub fn with_quantile_range(mut self, low: f64, high: f64) -> Self {
+        self.quantile_range = (low, high);
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let (low, high) = self.quantile_range;
+        if !(0.0..=100.0).contains(&low) || !(low..=100.0).contains(&high) {
+            return Err(TensorError::InvalidOperation(format!("invalid quantile range ({}, {})", low, high)));
+        }
+        let cols = x.shape().dim(1)?;
+        let (mut center, mut scale) = (Vec::with_capacity(cols), Vec::with_capacity(cols));
+        for j in 0..cols {
+            let mut values = column(x, j);
+            values.sort_by(|a, b| a.total_cmp(b));
+            center.push(if self.with_centering { quantile_sorted(&values, 0.5) } else { 0.0 });
+            let spread = quantile_sorted(&values, high / 100.0) - quantile_sorted(&values, low / 100.0);
+            scale.push(if self.with_scaling { safe_scale(spread) } else { 1.0 });
+        }
+        let to_t = |v: Vec<f64>| Tensor::new(v.into_iter().map(T::from_f64).collect(), vec![cols]);
+        self.center = Some(to_t(center)?);
+        self.scale = Some(to_t(scale)?);
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let center = self.center.as_ref().expect("fit() must be called before transform()").data();
+        let scale = self.scale.as_ref().expect("fit() must be called before transform()").data();
+        check_width(x, center.len())?;
+        map_columns(x, |v, j| (v - center[j].to_f64()) / scale[j].to_f64())
+    }
+
+    pub fn inverse_transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let center = self.center.as_ref().expect("fit() must be called before inverse_transform()").data();
+        let scale = self.scale.as_ref().expect("fit() must be called before inverse_transform()").data();
+        check_width(x, center.len())?;
+        map_columns(x, |v, j| v * scale[j].to_f64() + center[j].to_f64())
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+/// Scale each feature by its largest absolute value, into `[-1, 1]`.
+/// Does not shift the data, so sparsity is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct MaxAbsScaler<T: Float> {
+    pub max_abs: Option<Tensor<T>>,
+}
+
+impl<T: Float> Default for MaxAbsScaler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> MaxAbsScaler<T> {
+    pub fn new() -> Self {
+        MaxAbsScaler { max_abs: None }
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let cols = x.shape().dim(1)?;
+        let max_abs = (0..cols).map(|j| T::from_f64(column(x, j).iter().fold(0.0f64, |m, v| m.max(v.abs())))).collect();
+        self.max_abs = Some(Tensor::new(max_abs, vec![cols])?);
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let max_abs = self.max_abs.as_ref().expect("fit() must be called before transform()").data();
+        check_width(x, max_abs.len())?;
+        map_columns(x, |v, j| v / safe_scale(max_abs[j].to_f64()))
+    }
+
+    pub fn inverse_transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let max_abs = self.max_abs.as_ref().expect("fit() must be called before inverse_transform()").data();
+        check_width(x, max_abs.len())?;
+        map_columns(x, |v, j| v * safe_scale(max_abs[j].to_f64()))
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+/// Standard normal CDF.
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Numerical Recipes' Chebyshev fit,
+/// relative error below 1.2e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * poly.exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Standard normal quantile function (Acklam's rational approximation).
+fn normal_ppf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+        1.38357751867269e2, -3.066479806614716e1, 2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+        6.680131188771972e1, -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+        -2.549732539343734, 4.374664141464968, 2.938163982698783,
+    ];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Piecewise-linear interpolation of `(xs, ys)` at `x`, with `xs`
+/// non-decreasing; clamps outside the range. Where `xs` repeats a value,
+/// the result is the average of the first and last matching `ys`.
+fn interp(x: f64, xs: &[f64], ys: &[f64]) -> f64 {
+    let n = xs.len();
+    if x <= xs[0] {
+        let last = xs.iter().rposition(|&v| v == xs[0]).unwrap_or(0);
+        return if x == xs[0] { 0.5 * (ys[0] + ys[last]) } else { ys[0] };
+    }
+    if x >= xs[n - 1] {
+        let first = xs.iter().position(|&v| v == xs[n - 1]).unwrap_or(n - 1);
+        return if x == xs[n - 1] { 0.5 * (ys[first] + ys[n - 1]) } else { ys[n - 1] };
+    }
+    let hi = xs.partition_point(|&v| v <= x);
+    let lo = hi - 1;
+    if xs[lo] == x {
+        let first = xs.partition_point(|&v| v < x);
+        return 0.5 * (ys[first] + ys[lo]);
+    }
+    ys[lo] + (ys[hi] - ys[lo]) * (x - xs[lo]) / (xs[hi] - xs[lo])
+}
+
+/// Output distribution of `QuantileTransformer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputDistribution {
+    Uniform,
+    Normal,
+}
+
+/// Map each feature through its empirical CDF, onto a uniform `[0, 1]` or
+/// a standard normal distribution. Robust to outliers and turns any
+/// monotone distortion of a feature into the same output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct QuantileTransformer<T: Float> {
+    /// Landmarks of the CDF; capped at the number of training rows.
+    pub n_quantiles: usize,
+    pub output_distribution: OutputDistribution,
+    /// Training values at each of the evenly spaced `references`, per
+    /// column.
+    pub quantiles: Option<Vec<Vec<f64>>>,
+    pub references: Option<Vec<f64>>,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<T>,
+}
+
+/// Normal outputs are clipped to the quantiles of these probabilities.
+const QUANTILE_CLIP: f64 = 1e-7;
+
+impl<T: Float> QuantileTransformer<T> {
+    pub fn new(n_quantiles: usize, output_distribution: OutputDistribution) -> Self {
+        QuantileTransformer {
+            n_quantiles,
+            output_distribution,
+            quantiles: None,
+            references: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let (rows, cols) = (x.shape().dim(0)?, x.shape().dim(1)?);
+        let nq = self.n_quantiles.min(rows);
+        if nq < 2 {
+            return Err(TensorError::InvalidOperation("QuantileTransformer needs at least 2 quantiles and rows".into()));
+        }
+        let references: Vec<f64> = (0..nq).map(|i| i as f64 / (nq - 1) as f64).collect();
+        let quantiles = (0..cols)
+            .map(|j| {
+                let mut values = column(x, j);
+                values.sort_by(|a, b| a.total_cmp(b));
+                references.iter().map(|&q| quantile_sorted(&values, q)).collect()
+            })
+            .collect();
+        self.quantiles = Some(quantiles);
+        self.references = Some(references);
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let quantiles = self.quantiles.as_ref().expect("fit() must be called before transform()");
+        let references = self.references.as_ref().unwrap();
+        check_width(x, quantiles.len())?;
+        map_columns(x, |v, j| {
+            let p = interp(v, &quantiles[j], references);
+            match self.output_distribution {
+                OutputDistribution::Uniform => p,
+                OutputDistribution::Normal => normal_ppf(p.clamp(QUANTILE_CLIP, 1.0 - QUANTILE_CLIP)),
+            }
+        })
+    }
+
+    pub fn inverse_transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let quantiles = self.quantiles.as_ref().expect("fit() must be called before inverse_transform()");
+        let references = self.references.as_ref().unwrap();
+        check_width(x, quantiles.len())?;
+        map_columns(x, |v, j| {
+            let p = match self.output_distribution {
+                OutputDistribution::Uniform => v,
+                OutputDistribution::Normal => normal_cdf(v),
+            };
+            interp(p, references, &quantiles[j])
+        })
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+/// Transform family of `PowerTransformer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerMethod {
+    /// Works for any real value.
+    YeoJohnson,
+    /// Strictly positive values only.
+    BoxCox,
+}
+
+impl PowerMethod {
+    fn apply(self, x: f64, lambda: f64) -> f64 {
+        match self {
+            PowerMethod::BoxCox if lambda.abs() < 1e-12 => x.ln(),
+            PowerMethod::BoxCox => (x.powf(lambda) - 1.0) / lambda,
+            PowerMethod::YeoJohnson if x >= 0.0 => {
+                if lambda.abs() < 1e-12 { x.ln_1p() } else { ((x + 1.0).powf(lambda) - 1.0) / lambda }
+            }
+            PowerMethod::YeoJohnson => {
+                if (lambda - 2.0).abs() < 1e-12 { -(-x).ln_1p() } else { -((1.0 - x).powf(2.0 - lambda) - 1.0) / (2.0 - lambda) }
+            }
+        }
+    }
+
+    fn invert(self, y: f64, lambda: f64) -> f64 {
+        match self {
+            PowerMethod::BoxCox if lambda.abs() < 1e-12 => y.exp(),
+            PowerMethod::BoxCox => (y * lambda + 1.0).powf(1.0 / lambda),
+            PowerMethod::YeoJohnson if y >= 0.0 => {
+                if lambda.abs() < 1e-12 { y.exp_m1() } else { (y * lambda + 1.0).powf(1.0 / lambda) - 1.0 }
+            }
+            PowerMethod::YeoJohnson => {
+                if (lambda - 2.0).abs() < 1e-12 { -(-y).exp_m1() } else { 1.0 - (1.0 - (2.0 - lambda) * y).powf(1.0 / (2.0 - lambda)) }
+            }
+        }
+    }
+
+    /// Profile log-likelihood of `lambda` for a normal model of the
+    /// transformed values.
+    fn log_likelihood(self, values: &[f64], lambda: f64) -> f64 {
+        let n = values.len() as f64;
+        let transformed: Vec<f64> = values.iter().map(|&x| self.apply(x, lambda)).collect();
+        let mean = transformed.iter().sum::<f64>() / n;
+        let var = transformed.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+        let jacobian: f64 = match self {
+            PowerMethod::BoxCox => values.iter().map(|x| x.ln()).sum(),
+            PowerMethod::YeoJohnson => values.iter().map(|x| x.signum() * x.abs().ln_1p()).sum(),
+        };
+        -0.5 * n * var.max(f64::MIN_POSITIVE).ln() + (lambda - 1.0) * jacobian
+    }
+}
+
+/// Make each feature more Gaussian with a per-feature power transform
+/// (Yeo–Johnson or Box–Cox), choosing the exponent by maximum likelihood,
+/// then (by default) standardize the result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct PowerTransformer<T: Float> {
+    pub method: PowerMethod,
+    pub standardize: bool,
+    pub lambdas: Option<Vec<f64>>,
+    /// Mean and standard deviation of each transformed column, when
+    /// standardizing.
+    pub means: Option<Vec<f64>>,
+    pub stds: Option<Vec<f64>>,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Float> PowerTransformer<T> {
+    pub fn new(method: PowerMethod) -> Self {
+        PowerTransformer {
+            method,
+            standardize: true,
+            lambdas: None,
+            means: None,
+            stds: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn with_standardize(mut self, standardize: bool) -> Self {
+        self.standardize = standardize;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let cols = x.shape().dim(1)?;
+        let (mut lambdas, mut means, mut stds) = (Vec::new(), Vec::new(), Vec::new());
+        for j in 0..cols {
+            let values = column(x, j);
+            if self.method == PowerMethod::BoxCox && values.iter().any(|&v| v <= 0.0) {
+                return Err(TensorError::InvalidOperation(format!(
+                    "Box-Cox needs strictly positive values; column {} has some that are not", j
+                )));
+            }
+            // Golden-section search for the most likely exponent.
+            let phi = (5f64.sqrt() - 1.0) / 2.0;
+            let (mut a, mut b) = (-5.0, 5.0);
+            let llf = |l: f64| self.method.log_likelihood(&values, l);
+            let (mut c, mut d) = (b - phi * (b - a), a + phi * (b - a));
+            let (mut fc, mut fd) = (llf(c), llf(d));
+            while b - a > 1e-8 {
+                if fc > fd {
+                    b = d;
+                    d = c;
+                    fd = fc;
+                    c = b - phi * (b - a);
+                    fc = llf(c);
+                } else {
+                    a = c;
+                    c = d;
+                    fc = fd;
+                    d = a + phi * (b - a);
+                    fd = llf(d);
+                }
+            }
+            let lambda = (a + b) / 2.0;
+            let transformed: Vec<f64> = values.iter().map(|&v| self.method.apply(v, lambda)).collect();
+            let mean = transformed.iter().sum::<f64>() / transformed.len().max(1) as f64;
+            let var = transformed.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / transformed.len().max(1) as f64;
+            lambdas.push(lambda);
+            means.push(mean);
+            stds.push(safe_scale(var.sqrt()));
+        }
+        self.lambdas = Some(lambdas);
+        if self.standardize {
+            self.means = Some(means);
+            self.stds = Some(stds);
+        } else {
+            self.means = None;
+            self.stds = None;
+        }
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let lambdas = self.lambdas.as_ref().expect("fit() must be called before transform()");
+        check_width(x, lambdas.len())?;
+        if self.method == PowerMethod::BoxCox && x.data().iter().any(|&v| v <= T::ZERO) {
+            return Err(TensorError::InvalidOperation("Box-Cox needs strictly positive values".into()));
+        }
+        map_columns(x, |v, j| {
+            let t = self.method.apply(v, lambdas[j]);
+            match (&self.means, &self.stds) {
+                (Some(m), Some(s)) => (t - m[j]) / s[j],
+                _ => t,
+            }
+        })
+    }
+
+    pub fn inverse_transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let lambdas = self.lambdas.as_ref().expect("fit() must be called before inverse_transform()");
+        check_width(x, lambdas.len())?;
+        map_columns(x, |v, j| {
+            let t = match (&self.means, &self.stds) {
+                (Some(m), Some(s)) => v * s[j] + m[j],
+                _ => v,
+            };
+            self.method.invert(t, lambdas[j])
+        })
+    }
+
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+}
+
+/// Natural logarithm of every value, after adding `offset`; a target
+/// transform for right-skewed, positive data. Has nothing to fit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct LogTransformer<T: Float> {
+    /// Added before taking the log; `1.0` gives `log1p`, for data with zeros.
+    pub offset: f64,
+    #[serde(skip)]
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Float> Default for LogTransformer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> LogTransformer<T> {
+    pub fn new() -> Self {
+        LogTransformer { offset: 0.0, _marker: std::marker::PhantomData }
+    }
+
+    pub fn with_offset(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn fit(&mut self, _x: &Tensor<T>) -> TensorResult<()> {
+        Ok(())
+    }
+
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        if x.data().iter().any(|v| v.to_f64() + self.offset <= 0.0) {
+            return Err(TensorError::InvalidOperation(format!(
+                "LogTransformer needs values above {}", -self.offset
+            )));
+        }
+        Ok(x.apply(|v| T::from_f64((v.to_f64() + self.offset).ln())))
+    }
+
+    pub fn inverse_transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        Ok(x.apply(|v| T::from_f64(v.to_f64().exp() - self.offset)))
+    }
+
     pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
         self.fit(x)?;
         self.transform(x)
@@ -146,4 +724,58 @@ mod tests {
             assert!((max - 1.0).abs() < 1e-10);
         }
     }
+
+    #[test]
+    fn test_robust_and_max_abs_scalers() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![1.0, -4.0], vec![2.0, 2.0], vec![3.0, 1.0], vec![100.0, 0.0]]).unwrap();
+
+        let mut robust = RobustScaler::new();
+        let out = robust.fit_transform(&x).unwrap();
+        // Column 0: median 2.5, IQR 27.25 - 1.75 = 25.5.
+        assert!((out.data()[0] - (1.0 - 2.5) / 25.5).abs() < 1e-12);
+        let back = robust.inverse_transform(&out).unwrap();
+        assert!(back.data().iter().zip(x.data()).all(|(a, b)| (a - b).abs() < 1e-12));
+
+        let mut max_abs = MaxAbsScaler::new();
+        let out = max_abs.fit_transform(&x).unwrap();
+        assert_eq!(out.data(), &[0.01, -1.0, 0.02, 0.5, 0.03, 0.25, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_quantile_transformer() {
+        let rows: Vec<Vec<f64>> = (0..101).map(|i| vec![(i as f64 / 10.0).exp()]).collect();
+        let x = Tensor::from_vec2d(&rows).unwrap();
+
+        let mut uniform = QuantileTransformer::new(1000, OutputDistribution::Uniform);
+        let out = uniform.fit_transform(&x).unwrap();
+        assert!((out.data()[50] - 0.5).abs() < 1e-12);
+        assert!(out.data().windows(2).all(|w| w[0] < w[1]));
+
+        let mut normal = QuantileTransformer::new(1000, OutputDistribution::Normal);
+        let out = normal.fit_transform(&x).unwrap();
+        assert!(out.data()[50].abs() < 1e-9);
+        assert!((out.data()[84] - 0.994_457_883_209_753).abs() < 1e-3);
+        let back = normal.inverse_transform(&out).unwrap();
+        assert!((back.data()[30] - x.data()[30]).abs() < 1e-4 * x.data()[30]);
+    }
+
+    #[test]
+    fn test_power_transformer() {
+        // Log-normal data: Box-Cox should pick lambda close to 0.
+        let rows: Vec<Vec<f64>> = (1..200).map(|i| vec![normal_ppf(i as f64 / 200.0).exp(), normal_ppf(i as f64 / 200.0) - 3.0]).collect();
+        let x = Tensor::from_vec2d(&rows).unwrap();
+
+        let mut box_cox = PowerTransformer::new(PowerMethod::BoxCox).with_standardize(false);
+        let first: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0]]).collect();
+        box_cox.fit(&Tensor::from_vec2d(&first).unwrap()).unwrap();
+        assert!(box_cox.lambdas.as_ref().unwrap()[0].abs() < 1e-3);
+        assert!(box_cox.fit(&x).is_err());
+
+        let mut yeo_johnson = PowerTransformer::new(PowerMethod::YeoJohnson);
+        let out = yeo_johnson.fit_transform(&x).unwrap();
+        let mean: f64 = out.data().iter().skip(1).step_by(2).sum::<f64>() / 199.0;
+        assert!(mean.abs() < 1e-9);
+        let back = yeo_johnson.inverse_transform(&out).unwrap();
+        assert!(back.data().iter().zip(x.data()).all(|(a, b)| (a - b).abs() < 1e-8 * b.abs().max(1.0)));
+    }
 }
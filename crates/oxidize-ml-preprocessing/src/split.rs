@@ -1,18 +1,22 @@
-use oxidize_ml_core::{Float, Tensor};
+use oxidize_ml_core::{Float, RngHandle, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
-use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::SeedableRng;
 
 /// Split data into training and test sets.
 ///
+/// With `stratify`, usually `Some(&y)`, each class of the given labels is
+/// split in the same proportion, so both sides keep the overall class
+/// balance.
+///
 /// Returns `(X_train, X_test, y_train, y_test)`.
+#[allow(clippy::type_complexity)]
 pub fn train_test_split<T: Float>(
     x: &Tensor<T>,
     y: &Tensor<T>,
     test_ratio: f64,
     seed: Option<u64>,
+    stratify: Option<&Tensor<T>>,
 ) -> TensorResult<(Tensor<T>, Tensor<T>, Tensor<T>, Tensor<T>)>
 where
     Standard: Distribution<T>,
@@ -20,14 +24,16 @@ where
     let n = x.shape().dim(0)?;
     assert_eq!(n, y.numel(), "X rows must match y length");
 
-    let mut indices: Vec<usize> = (0..n).collect();
-    let mut rng = match seed {
-        Some(s) => StdRng::seed_from_u64(s),
-        None => StdRng::from_entropy(),
-    };
-    indices.shuffle(&mut rng);
-
+    let mut rng = RngHandle::new(seed);
     let test_size = (n as f64 * test_ratio).round() as usize;
+    let indices = match stratify {
+        None => {
+            let mut indices: Vec<usize> = (0..n).collect();
+            indices.shuffle(&mut rng);
+            indices
+        }
+        Some(labels) => stratified_order(labels, n, test_size, &mut rng)?,
+    };
     let train_size = n - test_size;
 
     let cols = x.shape().dim(1)?;
@@ -59,6 +65,48 @@ where
     ))
 }
 
+/// A row order whose last `test_size` rows hold each class of `labels` in
+/// proportion: every class gives `count · test_size / n` rows to the test
+/// side, rounded so the total comes out exact.
+fn stratified_order<T: Float>(
+    labels: &Tensor<T>,
+    n: usize,
+    test_size: usize,
+    rng: &mut RngHandle,
+) -> TensorResult<Vec<usize>> {
+    if labels.numel() != n {
+        return Err(TensorError::ShapeMismatch { expected: vec![n], got: labels.shape_vec() });
+    }
+    let mut classes: Vec<T> = labels.data().to_vec();
+    classes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    classes.dedup();
+    let members: Vec<Vec<usize>> = classes
+        .iter()
+        .map(|&c| (0..n).filter(|&i| labels.data()[i] == c).collect())
+        .collect();
+
+    // Largest-remainder rounding of each class's share of the test set.
+    let exact: Vec<f64> = members.iter().map(|m| m.len() as f64 * test_size as f64 / n.max(1) as f64).collect();
+    let mut take: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..classes.len()).collect();
+    by_remainder.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    let short = test_size - take.iter().sum::<usize>();
+    for &c in by_remainder.iter().take(short) {
+        take[c] += 1;
+    }
+
+    let (mut train, mut test) = (Vec::with_capacity(n - test_size), Vec::with_capacity(test_size));
+    for (mut rows, k) in members.into_iter().zip(take) {
+        rows.shuffle(rng);
+        test.extend_from_slice(&rows[..k]);
+        train.extend_from_slice(&rows[k..]);
+    }
+    train.shuffle(rng);
+    test.shuffle(rng);
+    train.extend(test);
+    Ok(train)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,11 +123,25 @@ mod tests {
         let y: Tensor<f64> = Tensor::from_slice(&[0.0, 1.0, 0.0, 1.0, 0.0]);
 
         let (x_train, x_test, y_train, y_test) =
-            train_test_split(&x, &y, 0.4, Some(42)).unwrap();
+            train_test_split(&x, &y, 0.4, Some(42), None).unwrap();
 
         assert_eq!(x_train.shape().dim(0).unwrap(), 3);
         assert_eq!(x_test.shape().dim(0).unwrap(), 2);
         assert_eq!(y_train.numel(), 3);
         assert_eq!(y_test.numel(), 2);
     }
+
+    #[test]
+    fn test_stratified_split() {
+        // 15 of class 0, 5 of class 1.
+        let x: Tensor<f64> = Tensor::new((0..20).map(|i| i as f64).collect(), vec![20, 1]).unwrap();
+        let y: Tensor<f64> = Tensor::new((0..20).map(|i| (i % 4 == 0) as u8 as f64).collect(), vec![20]).unwrap();
+
+        let (x_train, _, y_train, y_test) = train_test_split(&x, &y, 0.2, Some(3), Some(&y)).unwrap();
+        assert_eq!(y_test.numel(), 4);
+        assert_eq!(y_test.data().iter().filter(|&&v| v == 1.0).count(), 1);
+        assert_eq!(y_train.data().iter().filter(|&&v| v == 1.0).count(), 4);
+        // Rows stay paired with their labels.
+        assert!(x_train.data().iter().zip(y_train.data()).all(|(&xi, &yi)| (xi as usize).is_multiple_of(4) == (yi == 1.0)));
+    }
 }
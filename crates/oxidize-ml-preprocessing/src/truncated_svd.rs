@@ -0,0 +1,227 @@
+use oxidize_ml_core::{Float, SparseTensor, Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_linalg::{randomized_svd, randomized_svd_sparse};
+use serde::{Deserialize, Serialize};
+
+/// Truncated SVD, also known as latent semantic analysis (LSA) on
+/// TF-IDF matrices.
+///
+/// Unlike `PCA` the data is not centred, so sparse inputs can be
+/// decomposed as-is through `fit_sparse` without ever densifying them.
+/// Components come from a randomized SVD.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct TruncatedSVD<T: Float> {
+    pub n_components: usize,
+    /// Power iterations of the randomized solver.
+    pub n_iter: usize,
+    /// Extra random directions sketched beyond `n_components`.
+    pub n_oversamples: usize,
+    pub seed: Option<u64>,
+    pub components: Option<Tensor<T>>,   // [n_components, n_features]
+    pub singular_values: Option<Vec<f64>>,
+    /// Variance of each column of the transformed training data.
+    pub explained_variance: Option<Vec<f64>>,
+    /// Summed per-feature variance of the training data.
+    pub total_variance: Option<f64>,
+}
+
+impl<T: Float> TruncatedSVD<T> {
+    pub fn new(n_components: usize) -> Self {
+        TruncatedSVD {
+            n_components,
+            n_iter: 5,
+            n_oversamples: 10,
+            seed: None,
+            components: None,
+            singular_values: None,
+            explained_variance: None,
+            total_variance: None,
+        }
+    }
+
+    pub fn with_n_iter(mut self, n_iter: usize) -> Self {
+        self.n_iter = n_iter;
+        self
+    }
+
+    pub fn with_n_oversamples(mut self, n_oversamples: usize) -> Self {
+        self.n_oversamples = n_oversamples;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let p = x.shape().dim(1)?;
+        self.check_components(p)?;
+        let (u, sigma, v) = randomized_svd(x, self.n_components, self.n_oversamples, self.n_iter, self.seed)?;
+
+        let (mut sums, mut sq_sums) = (vec![0.0; p], vec![0.0; p]);
+        for row in x.data().chunks(p) {
+            for (j, &v) in row.iter().enumerate() {
+                sums[j] += v.to_f64();
+                sq_sums[j] += v.to_f64().powi(2);
+            }
+        }
+        self.store(n, &u, &sigma, &v, &sums, &sq_sums)
+    }
+
+    /// Fit on a CSR matrix, using only its non-zero entries.
+    pub fn fit_sparse(&mut self, x: &SparseTensor<T>) -> TensorResult<()> {
+        let (n, p) = (x.n_rows(), x.n_cols());
+        self.check_components(p)?;
+        let (u, sigma, v) = randomized_svd_sparse(x, self.n_components, self.n_oversamples, self.n_iter, self.seed)?;
+
+        let mut sums = vec![0.0; p];
+        for (&j, &v) in x.indices().iter().zip(x.values()) {
+            sums[j] += v.to_f64();
+        }
+        let sq_sums: Vec<f64> = x.col_sq_norms().iter().map(|v| v.to_f64()).collect();
+        self.store(n, &u, &sigma, &v, &sums, &sq_sums)
+    }
+
+    fn check_components(&self, p: usize) -> TensorResult<()> {
+        if self.n_components == 0 || self.n_components > p {
+            return Err(TensorError::InvalidOperation(format!(
+                "n_components must be between 1 and the number of features ({}), got {}", p, self.n_components
+            )));
+        }
+        Ok(())
+    }
+
+    /// Record the fitted components and variances from `X ≈ U Σ Vᵀ` and the
+    /// column sums and sums of squares of `X`.
+    fn store(
+        &mut self,
+        n: usize,
+        u: &Tensor<T>,
+        sigma: &Tensor<T>,
+        v: &Tensor<T>,
+        sums: &[f64],
+        sq_sums: &[f64],
+    ) -> TensorResult<()> {
+        let n_f = n as f64;
+        let k = sigma.numel();
+        let sigma: Vec<f64> = sigma.data().iter().map(|s| s.to_f64()).collect();
+
+        // The transformed data is U Σ; U has unit columns, so column j has
+        // variance σ_j² (1/n - ū_j²).
+        let explained = (0..k)
+            .map(|j| {
+                let mean = (0..n).map(|i| u.data()[i * k + j].to_f64()).sum::<f64>() / n_f;
+                sigma[j].powi(2) * (1.0 / n_f - mean * mean)
+            })
+            .collect();
+        let total = sums.iter().zip(sq_sums).map(|(s, sq)| sq / n_f - (s / n_f).powi(2)).sum();
+
+        self.components = Some(v.t()?);
+        self.singular_values = Some(sigma);
+        self.explained_variance = Some(explained);
+        self.total_variance = Some(total);
+        Ok(())
+    }
+
+    fn fitted_components(&self) -> TensorResult<&Tensor<T>> {
+        self.components.as_ref().ok_or_else(|| {
+            TensorError::InvalidOperation("TruncatedSVD not fitted".into())
+        })
+    }
+
+    /// Project `x` onto the components: `X Vᵀ`.
+    pub fn transform(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        x.matmul(&self.fitted_components()?.t()?)
+    }
+
+    /// Project a CSR matrix onto the components, giving a dense result.
+    pub fn transform_sparse(&self, x: &SparseTensor<T>) -> TensorResult<Tensor<T>> {
+        x.spmm(&self.fitted_components()?.t()?)
+    }
+
+    /// Fit and transform in one step.
+    pub fn fit_transform(&mut self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit(x)?;
+        self.transform(x)
+    }
+
+    pub fn fit_transform_sparse(&mut self, x: &SparseTensor<T>) -> TensorResult<Tensor<T>> {
+        self.fit_sparse(x)?;
+        self.transform_sparse(x)
+    }
+
+    /// Map reduced data back to feature space: `Z V`.
+    pub fn inverse_transform(&self, z: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        z.matmul(self.fitted_components()?)
+    }
+
+    /// Fraction of the training data's total variance captured by each
+    /// component. Without centring these need not be sorted, and the
+    /// first component often mostly tracks the mean.
+    pub fn explained_variance_ratio(&self) -> Option<Vec<f64>> {
+        let (ev, total) = (self.explained_variance.as_ref()?, self.total_variance?);
+        Some(ev.iter().map(|&v| if total > 0.0 { v / total } else { 0.0 }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncated_svd_dense_and_sparse() {
+        // A sparse term-document-like matrix of rank 3.
+        let (n, p) = (30, 12);
+        let mut data = vec![0.0; n * p];
+        for i in 0..n {
+            let topic = i % 3;
+            let weight = 1.0 + (i % 4) as f64 * 0.5;
+            for j in 0..4 {
+                data[i * p + topic * 4 + j] = weight * (1.0 + 0.3 * j as f64 + 0.2 * topic as f64);
+            }
+        }
+        let x: Tensor<f64> = Tensor::new(data, vec![n, p]).unwrap();
+        let xs = SparseTensor::from_dense(&x).unwrap();
+
+        let mut dense = TruncatedSVD::new(3).with_seed(Some(0));
+        let z = dense.fit_transform(&x).unwrap();
+        assert_eq!(z.shape_vec(), vec![n, 3]);
+        let mut sparse = TruncatedSVD::new(3).with_seed(Some(0));
+        let zs = sparse.fit_transform_sparse(&xs).unwrap();
+
+        let (sd, ss) = (dense.singular_values.as_ref().unwrap(), sparse.singular_values.as_ref().unwrap());
+        for (a, b) in sd.iter().zip(ss) {
+            assert!((a - b).abs() < 1e-8 * sd[0]);
+        }
+        for (a, b) in z.data().iter().zip(zs.data()) {
+            assert!((a.abs() - b.abs()).abs() < 1e-8);
+        }
+
+        // The rank-3 data is reconstructed exactly, so the three components
+        // carry all of its variance.
+        let back = dense.inverse_transform(&z).unwrap();
+        for (a, b) in back.data().iter().zip(x.data()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+        let ratio = sparse.explained_variance_ratio().unwrap();
+        let dense_ratio = dense.explained_variance_ratio().unwrap();
+        for (a, b) in ratio.iter().zip(&dense_ratio) {
+            assert!((a - b).abs() < 1e-8);
+        }
+        let mut col_var = [0.0; 3];
+        for (j, var) in col_var.iter_mut().enumerate() {
+            let mean = (0..n).map(|i| z.data()[i * 3 + j]).sum::<f64>() / n as f64;
+            *var = (0..n).map(|i| (z.data()[i * 3 + j] - mean).powi(2)).sum::<f64>() / n as f64;
+        }
+        for (a, b) in col_var.iter().zip(dense.explained_variance.as_ref().unwrap()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+        assert!((ratio.iter().sum::<f64>() - 1.0).abs() < 1e-8);
+
+        assert!(TruncatedSVD::<f64>::new(0).fit(&x).is_err());
+        assert!(TruncatedSVD::<f64>::new(3).transform(&x).is_err());
+    }
+}
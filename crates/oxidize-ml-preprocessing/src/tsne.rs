@@ -1,4 +1,4 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{Float, Tensor};
 use oxidize_ml_core::error::TensorResult;
 use std::marker::PhantomData;
 
@@ -0,0 +1,7 @@
+pub mod http;
+pub mod msgpack;
+pub mod model;
+pub mod server;
+
+pub use model::*;
+pub use server::*;
@@ -0,0 +1,42 @@
+use std::error::Error;
+use std::process;
+use std::time::Duration;
+use oxidize_ml_serve::{load_oxml, ServeConfig, Server};
+
+const USAGE: &str = "usage: oxidize-ml-serve --model <file.oxml> [--addr 127.0.0.1:8080] [--workers 1] \
+[--max-batch 64] [--batch-delay-ms 5] [--name model] [--version 1]";
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}\n{}", e, USAGE);
+        process::exit(2);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut config = ServeConfig::new();
+    let mut model = None;
+    let (mut name, mut version) = (config.name.clone(), config.version.clone());
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", flag));
+        match flag.as_str() {
+            "--model" => model = Some(value()?),
+            "--addr" => config = config.with_addr(&value()?),
+            "--workers" => config = config.with_workers(value()?.parse()?),
+            "--max-batch" => config = config.with_max_batch_size(value()?.parse()?),
+            "--batch-delay-ms" => config = config.with_max_batch_delay(Duration::from_millis(value()?.parse()?)),
+            "--name" => name = value()?,
+            "--version" => version = value()?,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            other => return Err(format!("unknown argument '{}'", other).into()),
+        }
+    }
+    let model = model.ok_or("--model is required")?;
+    let config = config.with_name(&name, &version);
+    eprintln!("serving {} on {} with {} worker(s)", model, config.addr, config.workers);
+    Server::new(config, move || load_oxml(&model)).serve()
+}
@@ -0,0 +1,121 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use oxidize_ml_core::Tensor;
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_autodiff::{no_grad, Graph};
+use oxidize_ml_nn::{Layer, StateDict};
+use oxidize_ml_pipeline::{estimator_from_bytes, Estimator, Persist, PersistError, Pipeline};
+
+/// Something the server can run: a batch of samples in, one output row
+/// per sample out.
+pub trait Model {
+    fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>>;
+
+    /// What the model is, for `/metadata`.
+    fn kind(&self) -> String {
+        "model".to_string()
+    }
+}
+
+impl Model for Pipeline {
+    fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        Pipeline::predict(self, x)
+    }
+
+    fn kind(&self) -> String {
+        "Pipeline".to_string()
+    }
+}
+
+/// A single saved estimator.
+pub struct EstimatorModel {
+    pub estimator: Box<dyn Estimator>,
+    pub kind: String,
+}
+
+impl Model for EstimatorModel {
+    fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        self.estimator.predict(x)
+    }
+
+    fn kind(&self) -> String {
+        self.kind.clone()
+    }
+}
+
+/// A network run in inference mode under `no_grad`, so requests do not
+/// grow its graph's tape.
+pub struct LayerModel<L: Layer> {
+    pub layer: L,
+    graph: Graph,
+}
+
+impl<L: Layer> LayerModel<L> {
+    /// Switches `layer` to eval behaviour (Dropout off, BatchNorm on its
+    /// running statistics).
+    pub fn new(mut layer: L) -> Self {
+        layer.set_training(false);
+        // Inputs must live on the graph the parameters were created on.
+        let graph = layer.parameters().first().map(|p| p.graph.clone()).unwrap_or_else(Graph::current);
+        LayerModel { layer, graph }
+    }
+
+    /// `layer` with the weights of `state` loaded strictly.
+    pub fn with_state(mut layer: L, state: &StateDict) -> TensorResult<Self> {
+        layer.load_state_dict(state, true)?;
+        Ok(Self::new(layer))
+    }
+}
+
+impl<L: Layer> Model for LayerModel<L> {
+    fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+        Ok(no_grad(|| self.layer.forward(&self.graph.input(x.clone())).data))
+    }
+
+    fn kind(&self) -> String {
+        "nn".to_string()
+    }
+}
+
+/// Load an `.oxml` file holding a pipeline or any saved estimator.
+pub fn load_oxml(path: impl AsRef<Path>) -> Result<Box<dyn Model>, Box<dyn Error + Send + Sync>> {
+    let bytes = fs::read(path)?;
+    match Pipeline::from_bytes(&bytes) {
+        Ok(pipeline) => Ok(Box::new(pipeline)),
+        Err(PersistError::WrongModel { found, .. }) => {
+            Ok(Box::new(EstimatorModel { estimator: estimator_from_bytes(&bytes)?, kind: found }))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_linear::LinearRegression;
+    use oxidize_ml_nn::{Linear, ReLULayer, Sequential};
+
+    #[test]
+    fn test_load_oxml_and_layer_model() {
+        let x = Tensor::new(vec![0.0, 1.0, 1.0, 0.0, 2.0, 1.0, 3.0, 2.0], vec![4, 2]).unwrap();
+        let y = Tensor::new(vec![1.0, 3.0, 5.0, 8.0], vec![4]).unwrap();
+        let mut reg = LinearRegression::new(true);
+        reg.fit(&x, &y).unwrap();
+        let path = std::env::temp_dir().join(format!("oxidize_ml_serve_{}.oxml", std::process::id()));
+        reg.save(&path).unwrap();
+        let model = load_oxml(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(model.kind(), "LinearRegression");
+        assert_eq!(model.predict(&x).unwrap().data(), reg.predict(&x).unwrap().data());
+
+        let net = Sequential::new().add(Box::new(Linear::new(2, 3))).add(Box::new(ReLULayer::new())).add(Box::new(Linear::new(3, 1)));
+        let state = net.state_dict();
+        let expected = net.forward(&Graph::current().input(x.clone())).data;
+        let fresh = Sequential::new().add(Box::new(Linear::new(2, 3))).add(Box::new(ReLULayer::new())).add(Box::new(Linear::new(3, 1)));
+        let served = LayerModel::with_state(fresh, &state).unwrap();
+        let before = served.graph.read().nodes.len();
+        assert_eq!(served.predict(&x).unwrap().data(), expected.data());
+        assert_eq!(served.graph.read().nodes.len(), before);
+    }
+}
@@ -0,0 +1,199 @@
+//! MessagePack encoding of JSON values, for clients that send tensors in
+//! binary rather than as decimal text.
+//!
+//! Covers nil, booleans, integers, floats, strings, arrays and maps; binary
+//! and extension types are rejected. Floats decode to JSON numbers, so NaN
+//! and infinities are rejected too.
+
+use serde_json::{Map, Number, Value};
+
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                out.push(0xd3);
+                out.extend_from_slice(&i.to_be_bytes());
+            } else if let Some(u) = n.as_u64() {
+                out.push(0xcf);
+                out.extend_from_slice(&u.to_be_bytes());
+            } else {
+                out.push(0xcb);
+                out.extend_from_slice(&n.as_f64().unwrap_or(0.0).to_be_bytes());
+            }
+        }
+        Value::String(s) => {
+            write_len(out, s.len(), [0xa0, 0xd9, 0xda, 0xdb], 31);
+            out.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(items) => {
+            write_len(out, items.len(), [0x90, 0, 0xdc, 0xdd], 15);
+            items.iter().for_each(|v| write_value(out, v));
+        }
+        Value::Object(map) => {
+            write_len(out, map.len(), [0x80, 0, 0xde, 0xdf], 15);
+            for (k, v) in map {
+                write_value(out, &Value::String(k.clone()));
+                write_value(out, v);
+            }
+        }
+    }
+}
+
+/// A length prefix: the fix form up to `fix_max`, then 8-bit (strings
+/// only), 16-bit and 32-bit forms.
+fn write_len(out: &mut Vec<u8>, len: usize, [fix, l8, l16, l32]: [u8; 4], fix_max: usize) {
+    if len <= fix_max {
+        out.push(fix | len as u8);
+    } else if l8 != 0 && len <= u8::MAX as usize {
+        out.extend_from_slice(&[l8, len as u8]);
+    } else if len <= u16::MAX as usize {
+        out.push(l16);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(l32);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let value = reader.value(0)?;
+    if reader.pos != bytes.len() {
+        return Err(format!("{} trailing bytes after the value", bytes.len() - reader.pos));
+    }
+    Ok(value)
+}
+
+/// Deeper nesting than this is rejected rather than risking the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len()).ok_or("unexpected end of input")?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn uint(&mut self, n: usize) -> Result<u64, String> {
+        Ok(self.take(n)?.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("nesting too deep".to_string());
+        }
+        let tag = self.take(1)?[0];
+        Ok(match tag {
+            0x00..=0x7f => Value::from(tag),
+            0x80..=0x8f => self.map((tag & 0x0f) as usize, depth)?,
+            0x90..=0x9f => self.array((tag & 0x0f) as usize, depth)?,
+            0xa0..=0xbf => self.string((tag & 0x1f) as usize)?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xca => float(f32::from_bits(self.uint(4)? as u32) as f64)?,
+            0xcb => float(f64::from_bits(self.uint(8)?))?,
+            0xcc => Value::from(self.uint(1)?),
+            0xcd => Value::from(self.uint(2)?),
+            0xce => Value::from(self.uint(4)?),
+            0xcf => Value::from(self.uint(8)?),
+            0xd0 => Value::from(self.uint(1)? as u8 as i8),
+            0xd1 => Value::from(self.uint(2)? as u16 as i16),
+            0xd2 => Value::from(self.uint(4)? as u32 as i32),
+            0xd3 => Value::from(self.uint(8)? as i64),
+            0xd9 => {
+                let n = self.uint(1)? as usize;
+                self.string(n)?
+            }
+            0xda => {
+                let n = self.uint(2)? as usize;
+                self.string(n)?
+            }
+            0xdb => {
+                let n = self.uint(4)? as usize;
+                self.string(n)?
+            }
+            0xdc => {
+                let n = self.uint(2)? as usize;
+                self.array(n, depth)?
+            }
+            0xdd => {
+                let n = self.uint(4)? as usize;
+                self.array(n, depth)?
+            }
+            0xde => {
+                let n = self.uint(2)? as usize;
+                self.map(n, depth)?
+            }
+            0xdf => {
+                let n = self.uint(4)? as usize;
+                self.map(n, depth)?
+            }
+            0xe0..=0xff => Value::from(tag as i8),
+            other => return Err(format!("unsupported MessagePack type 0x{:02x}", other)),
+        })
+    }
+
+    fn string(&mut self, n: usize) -> Result<Value, String> {
+        let bytes = self.take(n)?;
+        Ok(Value::String(std::str::from_utf8(bytes).map_err(|e| e.to_string())?.to_string()))
+    }
+
+    fn array(&mut self, n: usize, depth: usize) -> Result<Value, String> {
+        // Every element takes at least a byte; a bigger count is a lie.
+        if n > self.bytes.len() - self.pos {
+            return Err("unexpected end of input".to_string());
+        }
+        (0..n).map(|_| self.value(depth + 1)).collect::<Result<_, _>>().map(Value::Array)
+    }
+
+    fn map(&mut self, n: usize, depth: usize) -> Result<Value, String> {
+        let mut map = Map::new();
+        for _ in 0..n {
+            let key = match self.value(depth + 1)? {
+                Value::String(s) => s,
+                other => return Err(format!("map keys must be strings, got {}", other)),
+            };
+            map.insert(key, self.value(depth + 1)?);
+        }
+        Ok(Value::Object(map))
+    }
+}
+
+fn float(v: f64) -> Result<Value, String> {
+    Number::from_f64(v).map(Value::Number).ok_or_else(|| format!("non-finite float {}", v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip_and_foreign_encodings() {
+        let long: Vec<f64> = (0..20).map(|i| i as f64 * 0.5).collect();
+        let value = json!({"inputs": [[1.5, -2.0], [0.1, 3e300]], "name": "x".repeat(40), "n": -7, "ok": true, "long": long});
+        assert_eq!(decode(&encode(&value)).unwrap(), value);
+
+        // The compact forms other encoders use: fixmap, fixstr, fixint,
+        // negative fixint, float32.
+        let foreign = [0x82, 0xa1, b'a', 0x05, 0xa1, b'b', 0x92, 0xff, 0xca, 0x3f, 0xc0, 0x00, 0x00];
+        assert_eq!(decode(&foreign).unwrap(), json!({"a": 5, "b": [-1, 1.5]}));
+        assert!(decode(&[0x92, 0x01]).is_err());
+        assert!(decode(&[0xdd, 0xff, 0xff, 0xff, 0xff]).is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! The HTTP server: connections are read on their own threads, requests
+//! to `/predict` queue for a batcher, which merges those arriving within
+//! `max_batch_delay` (up to `max_batch_size` samples) into one batch for
+//! the next free worker. Each worker owns a copy of the model from the
+//! loader, so models need not be thread-safe.
+//!
+//! - `GET /health`: `{"status": "ok"}`.
+//! - `GET /metadata`: the model kind, name, version and batching settings.
+//! - `POST /predict`: `{"inputs": [sample, …]}` in, `{"outputs": [row, …]}`
+//!   out, each sample a number or a nested array. JSON, or MessagePack
+//!   with `Content-Type: application/msgpack` (answered in kind).
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use oxidize_ml_core::Tensor;
+use serde_json::{json, Value};
+
+use crate::http::{read_request, write_response, Request, Response};
+use crate::model::Model;
+use crate::msgpack;
+
+/// Builds one model per worker.
+pub type ModelLoader = dyn Fn() -> Result<Box<dyn Model>, Box<dyn Error + Send + Sync>> + Send + Sync;
+
+#[derive(Debug, Clone)]
+pub struct ServeConfig {
+    pub addr: String,
+    pub workers: usize,
+    /// Most samples merged into one batch.
+    pub max_batch_size: usize,
+    /// How long the first request of a batch waits for company.
+    pub max_batch_delay: Duration,
+    pub max_body_bytes: usize,
+    pub name: String,
+    pub version: String,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        ServeConfig {
+            addr: "127.0.0.1:8080".to_string(),
+            workers: 1,
+            max_batch_size: 64,
+            max_batch_delay: Duration::from_millis(5),
+            max_body_bytes: 64 << 20,
+            name: "model".to_string(),
+            version: "1".to_string(),
+        }
+    }
+}
+
+impl ServeConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_addr(mut self, addr: &str) -> Self {
+        self.addr = addr.to_string();
+        self
+    }
+
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    pub fn with_max_batch_delay(mut self, max_batch_delay: Duration) -> Self {
+        self.max_batch_delay = max_batch_delay;
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn with_name(mut self, name: &str, version: &str) -> Self {
+        self.name = name.to_string();
+        self.version = version.to_string();
+        self
+    }
+}
+
+/// A request's samples and where to send its rows of the output.
+struct Job {
+    input: Tensor<f64>,
+    reply: Sender<Result<Tensor<f64>, String>>,
+}
+
+pub struct Server {
+    config: ServeConfig,
+    loader: Arc<ModelLoader>,
+}
+
+/// A running server; `shutdown` (or dropping it) stops accepting and
+/// joins its threads.
+pub struct ServerHandle {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    pub fn new(
+        config: ServeConfig,
+        loader: impl Fn() -> Result<Box<dyn Model>, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    ) -> Self {
+        Server { config, loader: Arc::new(loader) }
+    }
+
+    /// Load the workers' models, bind, and serve in the background.
+    pub fn spawn(self) -> Result<ServerHandle, Box<dyn Error + Send + Sync>> {
+        let config = self.config;
+        if config.workers == 0 || config.max_batch_size == 0 {
+            return Err("workers and max_batch_size must be positive".into());
+        }
+        let (batch_tx, batch_rx) = channel::<Vec<Job>>();
+        let batch_rx = Arc::new(Mutex::new(batch_rx));
+        let (ready_tx, ready_rx) = channel();
+        let mut kinds = Vec::new();
+        for i in 0..config.workers {
+            let (loader, batches, ready) = (Arc::clone(&self.loader), Arc::clone(&batch_rx), ready_tx.clone());
+            thread::Builder::new().name(format!("serve-worker-{}", i)).spawn(move || {
+                let model = match loader() {
+                    Ok(model) => model,
+                    Err(e) => {
+                        let _ = ready.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+                let _ = ready.send(Ok(model.kind()));
+                drop(ready);
+                worker(model.as_ref(), &batches);
+            })?;
+        }
+        drop(ready_tx);
+        for _ in 0..config.workers {
+            kinds.push(ready_rx.recv().map_err(|_| "a worker died while loading its model")??);
+        }
+
+        let listener = TcpListener::bind(
+            config.addr.to_socket_addrs()?.next().ok_or_else(|| format!("cannot resolve '{}'", config.addr))?,
+        )?;
+        let addr = listener.local_addr()?;
+        let (job_tx, job_rx) = channel::<Job>();
+        let (max_rows, delay) = (config.max_batch_size, config.max_batch_delay);
+        thread::Builder::new().name("serve-batcher".to_string()).spawn(move || batcher(job_rx, batch_tx, max_rows, delay))?;
+
+        let metadata = Arc::new(json!({
+            "name": config.name,
+            "version": config.version,
+            "kind": kinds[0],
+            "workers": config.workers,
+            "max_batch_size": config.max_batch_size,
+            "max_batch_delay_ms": config.max_batch_delay.as_secs_f64() * 1000.0,
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let accept = {
+            let (stop, max_body) = (Arc::clone(&stop), config.max_body_bytes);
+            thread::Builder::new().name("serve-accept".to_string()).spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let (jobs, metadata) = (job_tx.clone(), Arc::clone(&metadata));
+                    thread::spawn(move || handle_connection(stream, &jobs, &metadata, max_body));
+                }
+            })?
+        };
+        Ok(ServerHandle { addr, stop, accept: Some(accept) })
+    }
+
+    /// `spawn`, then block for as long as the server runs.
+    pub fn serve(self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut handle = self.spawn()?;
+        if let Some(accept) = handle.accept.take() {
+            let _ = accept.join();
+        }
+        Ok(())
+    }
+}
+
+impl ServerHandle {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn shutdown(mut self) {
+        self.stop_accepting();
+    }
+
+    fn stop_accepting(&mut self) {
+        let Some(accept) = self.accept.take() else { return };
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        let _ = accept.join();
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        self.stop_accepting();
+    }
+}
+
+/// Gather jobs into batches until every sender (the accept loop and open
+/// connections) is gone.
+fn batcher(jobs: Receiver<Job>, batches: Sender<Vec<Job>>, max_rows: usize, delay: Duration) {
+    while let Ok(first) = jobs.recv() {
+        let deadline = Instant::now() + delay;
+        let mut rows = first.input.shape_vec()[0];
+        let mut pending = vec![first];
+        while rows < max_rows {
+            match jobs.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(job) => {
+                    rows += job.input.shape_vec()[0];
+                    pending.push(job);
+                }
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        // Only samples of the same shape can share a batch.
+        let mut by_shape: BTreeMap<Vec<usize>, Vec<Job>> = BTreeMap::new();
+        for job in pending {
+            by_shape.entry(job.input.shape_vec()[1..].to_vec()).or_default().push(job);
+        }
+        for batch in by_shape.into_values() {
+            if batches.send(batch).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn worker(model: &dyn Model, batches: &Mutex<Receiver<Vec<Job>>>) {
+    loop {
+        let batch = match batches.lock().expect("batch queue poisoned").recv() {
+            Ok(batch) => batch,
+            Err(_) => return,
+        };
+        let inputs: Vec<&Tensor<f64>> = batch.iter().map(|job| &job.input).collect();
+        let result = Tensor::concatenate(&inputs, 0).and_then(|x| model.predict(&x)).map_err(|e| e.to_string());
+        let total: usize = batch.iter().map(|job| job.input.shape_vec()[0]).sum();
+        let mut start = 0;
+        for job in batch {
+            let rows = job.input.shape_vec()[0];
+            let reply = match &result {
+                Ok(out) if out.ndim() > 0 && out.shape_vec()[0] == total => {
+                    out.index_select(0, &(start..start + rows).collect::<Vec<_>>()).map_err(|e| e.to_string())
+                }
+                Ok(out) => Err(format!("model returned shape {:?} for {} samples", out.shape_vec(), total)),
+                Err(e) => Err(e.clone()),
+            };
+            start += rows;
+            let _ = job.reply.send(reply);
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, jobs: &Sender<Job>, metadata: &Value, max_body: usize) {
+    let response = match read_request(&mut stream, max_body) {
+        Ok(request) => route(&request, jobs, metadata),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::error(400, &e.to_string()),
+        Err(_) => return,
+    };
+    let _ = write_response(&mut stream, &response);
+}
+
+fn route(request: &Request, jobs: &Sender<Job>, metadata: &Value) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => Response::json(200, &json!({ "status": "ok" })),
+        ("GET", "/metadata") => Response::json(200, metadata),
+        ("POST", "/predict") => predict(request, jobs),
+        (_, "/health" | "/metadata" | "/predict") => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn predict(request: &Request, jobs: &Sender<Job>) -> Response {
+    let content_type = request.header("content-type").unwrap_or("application/json");
+    let binary = content_type.starts_with("application/msgpack") || content_type.starts_with("application/x-msgpack");
+    if !binary && !content_type.starts_with("application/json") {
+        return Response::error(415, "send application/json or application/msgpack");
+    }
+    let body = if binary { msgpack::decode(&request.body) } else { serde_json::from_slice(&request.body).map_err(|e| e.to_string()) };
+    let input = match body.and_then(|v| inputs(&v)) {
+        Ok(input) => input,
+        Err(e) => return Response::error(400, &e),
+    };
+
+    let (reply, outcome) = channel();
+    if jobs.send(Job { input, reply }).is_err() {
+        return Response::error(503, "server is shutting down");
+    }
+    match outcome.recv() {
+        Ok(Ok(out)) => {
+            let value = json!({ "outputs": to_nested(out.data(), &out.shape_vec()) });
+            if binary {
+                Response::new(200, "application/msgpack", msgpack::encode(&value))
+            } else {
+                Response::json(200, &value)
+            }
+        }
+        Ok(Err(e)) => Response::error(500, &e),
+        Err(_) => Response::error(503, "no worker answered"),
+    }
+}
+
+/// The `inputs` of a request as a `[samples, …]` tensor.
+fn inputs(body: &Value) -> Result<Tensor<f64>, String> {
+    let samples = body.get("inputs").ok_or("missing 'inputs'")?;
+    let mut data = Vec::new();
+    let shape = flatten(samples, &mut data)?;
+    if shape.is_empty() || shape[0] == 0 {
+        return Err("'inputs' must be a non-empty list of samples".to_string());
+    }
+    Tensor::new(data, shape).map_err(|e| e.to_string())
+}
+
+/// Append the numbers of a rectangular nested array to `data`, returning
+/// its shape.
+fn flatten(value: &Value, data: &mut Vec<f64>) -> Result<Vec<usize>, String> {
+    match value {
+        Value::Number(n) => {
+            data.push(n.as_f64().ok_or("number out of range")?);
+            Ok(Vec::new())
+        }
+        Value::Array(items) => {
+            let mut inner: Option<Vec<usize>> = None;
+            for item in items {
+                let shape = flatten(item, data)?;
+                match &inner {
+                    Some(expected) if *expected != shape => return Err("ragged 'inputs'".to_string()),
+                    _ => inner = Some(shape),
+                }
+            }
+            let mut shape = vec![items.len()];
+            shape.extend(inner.unwrap_or_default());
+            Ok(shape)
+        }
+        other => Err(format!("expected numbers or arrays, got {}", other)),
+    }
+}
+
+fn to_nested(data: &[f64], shape: &[usize]) -> Value {
+    match shape {
+        [] => json!(data[0]),
+        [_] => json!(data),
+        [n, rest @ ..] => {
+            let stride = rest.iter().product::<usize>();
+            Value::Array((0..*n).map(|i| to_nested(&data[i * stride..(i + 1) * stride], rest)).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use oxidize_ml_core::error::TensorResult;
+
+    /// Doubles its input.
+    struct Doubler;
+
+    impl Model for Doubler {
+        fn predict(&self, x: &Tensor<f64>) -> TensorResult<Tensor<f64>> {
+            Ok(x.mul_scalar(2.0))
+        }
+
+        fn kind(&self) -> String {
+            "Doubler".to_string()
+        }
+    }
+
+    fn call(addr: SocketAddr, method: &str, path: &str, content_type: &str, body: &[u8]) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let head = format!(
+            "{} {} HTTP/1.1\r\nHost: test\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+            method,
+            path,
+            content_type,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(body).unwrap();
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).unwrap();
+        let split = raw.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let status = std::str::from_utf8(&raw[9..12]).unwrap().parse().unwrap();
+        (status, raw[split + 4..].to_vec())
+    }
+
+    #[test]
+    fn test_endpoints_batching_and_msgpack() {
+        let config = ServeConfig::new()
+            .with_addr("127.0.0.1:0")
+            .with_workers(2)
+            .with_max_batch_delay(Duration::from_millis(20))
+            .with_name("doubler", "0.3");
+        let server = Server::new(config, || Ok(Box::new(Doubler) as Box<dyn Model>)).spawn().unwrap();
+        let addr = server.local_addr();
+
+        assert_eq!(call(addr, "GET", "/health", "text/plain", b""), (200, br#"{"status":"ok"}"#.to_vec()));
+        let (status, body) = call(addr, "GET", "/metadata", "text/plain", b"");
+        let metadata: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!((status, metadata["kind"].as_str(), metadata["version"].as_str()), (200, Some("Doubler"), Some("0.3")));
+        assert_eq!(call(addr, "GET", "/nope", "text/plain", b"").0, 404);
+        assert_eq!(call(addr, "GET", "/predict", "text/plain", b"").0, 405);
+
+        // Concurrent requests, batched together, each get their own rows.
+        let answers: Vec<Value> = thread::scope(|s| {
+            let handles: Vec<_> = (0..6)
+                .map(|i| {
+                    s.spawn(move || {
+                        let body = json!({ "inputs": [[i as f64, 1.0], [0.5, -(i as f64)]] }).to_string();
+                        let (status, out) = call(addr, "POST", "/predict", "application/json", body.as_bytes());
+                        assert_eq!(status, 200);
+                        serde_json::from_slice(&out).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for (i, answer) in answers.iter().enumerate() {
+            assert_eq!(answer["outputs"], json!([[2.0 * i as f64, 2.0], [1.0, -2.0 * i as f64]]));
+        }
+
+        let packed = msgpack::encode(&json!({ "inputs": [1.5, 2.0] }));
+        let (status, out) = call(addr, "POST", "/predict", "application/msgpack", &packed);
+        assert_eq!((status, msgpack::decode(&out).unwrap()), (200, json!({ "outputs": [3.0, 4.0] })));
+
+        assert_eq!(call(addr, "POST", "/predict", "application/json", br#"{"inputs": [[1], [2, 3]]}"#).0, 400);
+        server.shutdown();
+    }
+}
@@ -1,5 +1,8 @@
 pub mod svm;
 pub mod svr;
+pub mod platt;
+mod smo;
 
 pub use svm::*;
 pub use svr::*;
+pub use platt::*;
@@ -0,0 +1,38 @@
+/// Platt scaling: fit `P(y = 1 | s) = 1 / (1 + exp(a·s + b))` to decision
+/// scores `s` and 0/1 labels by Newton's method on the log-loss, against
+/// Platt's smoothed targets `(N₊ + 1) / (N₊ + 2)` and `1 / (N₋ + 2)`.
+///
+/// Returns `(a, b)`; `a` is negative when higher scores mean class 1.
+pub fn platt_scaling(scores: &[f64], labels: &[f64]) -> (f64, f64) {
+    let n_pos = labels.iter().filter(|&&y| y == 1.0).count() as f64;
+    let n_neg = labels.len() as f64 - n_pos;
+    let (hi, lo) = ((n_pos + 1.0) / (n_pos + 2.0), 1.0 / (n_neg + 2.0));
+    let targets: Vec<f64> = labels.iter().map(|&y| if y == 1.0 { hi } else { lo }).collect();
+
+    let (mut a, mut b) = (0.0, ((n_neg + 1.0) / (n_pos + 1.0)).ln());
+    for _ in 0..100 {
+        let (mut g_a, mut g_b, mut h_aa, mut h_ab, mut h_bb) = (0.0, 0.0, 1e-12, 0.0, 1e-12);
+        for (&s, &t) in scores.iter().zip(&targets) {
+            let p = 1.0 / (1.0 + (a * s + b).exp());
+            // d(loss)/d(a·s + b) = t - p for this parametrisation.
+            let (g, w) = (t - p, p * (1.0 - p));
+            g_a += g * s;
+            g_b += g;
+            h_aa += w * s * s;
+            h_ab += w * s;
+            h_bb += w;
+        }
+        let det = h_aa * h_bb - h_ab * h_ab;
+        if det.abs() < 1e-300 {
+            break;
+        }
+        let step_a = (h_bb * g_a - h_ab * g_b) / det;
+        let step_b = (h_aa * g_b - h_ab * g_a) / det;
+        a -= step_a;
+        b -= step_b;
+        if step_a.abs() < 1e-10 && step_b.abs() < 1e-10 {
+            break;
+        }
+    }
+    (a, b)
+}
@@ -0,0 +1,469 @@
+//! Sequential minimal optimization for the SVM dual problems, in the
+//! general form solved by LIBSVM:
+//!
+//! minimize `½ αᵀQα + pᵀα` subject to `yᵀα = 0`, `0 ≤ αₜ ≤ Cₜ`,
+//!
+//! with `yₜ = ±1`. Each step picks a working pair — the maximal violator
+//! of the optimality conditions, and the partner promising the largest
+//! decrease of the objective (the second-order refinement of choosing the
+//! largest `|Eᵢ - Eⱼ|`) — and solves for it analytically. Rows of `Q` are
+//! kept in an LRU cache, and variables stuck at a bound are periodically
+//! shrunk out of the search.
+
+use std::rc::Rc;
+
+/// Optimal `α` and the offset `ρ` of the decision function.
+pub(crate) struct SmoSolution {
+    pub alpha: Vec<f64>,
+    pub rho: f64,
+    pub n_iter: usize,
+    pub converged: bool,
+    /// Final value of the dual objective.
+    pub objective: f64,
+}
+
+pub(crate) struct SmoParams {
+    /// Stop once the maximal violation drops below this.
+    pub tol: f64,
+    pub max_iter: usize,
+    /// Rows of `Q` the cache may hold.
+    pub cache_rows: usize,
+    pub shrinking: bool,
+}
+
+/// Lower bound on a pair's curvature, for non-positive-definite kernels.
+const TAU: f64 = 1e-12;
+
+/// A cached row and, for a partial one, the epoch it was computed in.
+type CachedRow = (Rc<[f64]>, Option<usize>);
+
+/// LRU cache of rows of `Q`, computed only on the columns still active.
+///
+/// A row computed while some variables were shrunk holds zeros in their
+/// columns; it stays valid until the next unshrink (`grow`), since the
+/// active set only loses variables in between. Recency is a doubly linked
+/// list over row ids, with id `l` as the sentinel, so hits are O(1).
+struct RowCache<F> {
+    compute: F,
+    rows: Vec<Option<CachedRow>>,
+    prev: Vec<usize>,
+    next: Vec<usize>,
+    len: usize,
+    capacity: usize,
+    epoch: usize,
+    all: Vec<usize>,
+}
+
+impl<F: FnMut(usize, &[usize]) -> Vec<f64>> RowCache<F> {
+    fn new(compute: F, l: usize, capacity: usize) -> Self {
+        RowCache {
+            compute,
+            rows: vec![None; l],
+            prev: vec![l; l + 1],
+            next: vec![l; l + 1],
+            len: 0,
+            capacity,
+            epoch: 0,
+            all: (0..l).collect(),
+        }
+    }
+
+    fn unlink(&mut self, t: usize) {
+        let (p, n) = (self.prev[t], self.next[t]);
+        self.next[p] = n;
+        self.prev[n] = p;
+    }
+
+    fn push_back(&mut self, t: usize) {
+        let sentinel = self.rows.len();
+        let last = self.prev[sentinel];
+        self.next[last] = t;
+        self.prev[t] = last;
+        self.next[t] = sentinel;
+        self.prev[sentinel] = t;
+    }
+
+    /// Row `t`, valid at least on the columns in `active`.
+    fn row(&mut self, t: usize, active: &[usize]) -> Rc<[f64]> {
+        let l = self.rows.len();
+        let full = active.len() == l;
+        if let Some((row, epoch)) = &self.rows[t] {
+            if epoch.is_none() || (!full && *epoch == Some(self.epoch)) {
+                let row = row.clone();
+                self.unlink(t);
+                self.push_back(t);
+                return row;
+            }
+            self.rows[t] = None;
+            self.unlink(t);
+            self.len -= 1;
+        }
+        let values = (self.compute)(t, active);
+        let row: Rc<[f64]> = if full {
+            values.into()
+        } else {
+            let mut row = vec![0.0; l];
+            for (&s, v) in active.iter().zip(values) {
+                row[s] = v;
+            }
+            row.into()
+        };
+        if self.capacity > 0 {
+            if self.len >= self.capacity {
+                let oldest = self.next[l];
+                self.unlink(oldest);
+                self.rows[oldest] = None;
+                self.len -= 1;
+            }
+            self.rows[t] = Some((row.clone(), (!full).then_some(self.epoch)));
+            self.push_back(t);
+            self.len += 1;
+        }
+        row
+    }
+
+    fn full_row(&mut self, t: usize) -> Rc<[f64]> {
+        let all = std::mem::take(&mut self.all);
+        let row = self.row(t, &all);
+        self.all = all;
+        row
+    }
+
+    /// Invalidate the partial rows, before the active set grows again.
+    fn grow(&mut self) {
+        self.epoch += 1;
+    }
+}
+
+/// Solve the problem with `l = p.len()` variables. `q_row(t, cols)`
+/// returns row `t` of `Q` (with the `yₛyₜ` signs applied) at the columns
+/// `cols`, and `q_diag` is its diagonal.
+///
+/// While variables are shrunk, only the active part of the gradient is
+/// updated and only active columns of `Q` are computed. As in LIBSVM,
+/// `ḡ = Σ_{αₛ = Cₛ} Cₛ Q[·][s]` is kept for every variable so the
+/// inactive gradients can be rebuilt on unshrinking from the free
+/// variables alone.
+pub(crate) fn solve<F: FnMut(usize, &[usize]) -> Vec<f64>>(
+    q_row: F,
+    q_diag: &[f64],
+    p: &[f64],
+    y: &[f64],
+    c: &[f64],
+    params: &SmoParams,
+) -> SmoSolution {
+    let l = p.len();
+    let mut cache = RowCache::new(q_row, l, params.cache_rows);
+    let mut alpha = vec![0.0; l];
+    let mut grad = p.to_vec();
+    let mut g_bar = vec![0.0; l];
+    let mut active: Vec<usize> = (0..l).collect();
+    let up = |a: f64, t: usize| (y[t] > 0.0 && a < c[t]) || (y[t] < 0.0 && a > 0.0);
+    let low = |a: f64, t: usize| (y[t] > 0.0 && a > 0.0) || (y[t] < 0.0 && a < c[t]);
+
+    let mut n_iter = 0;
+    let mut converged = false;
+    let mut unshrunk = false;
+    let shrink_every = l.clamp(1, 1000);
+    let mut countdown = shrink_every;
+    while n_iter < params.max_iter {
+        countdown -= 1;
+        if countdown == 0 {
+            countdown = shrink_every;
+            if params.shrinking {
+                let (g_up, g_low) = violations(&active, &alpha, &grad, y, &up, &low);
+                if !unshrunk && g_up + g_low <= 10.0 * params.tol {
+                    // Close to the end: bring everything back once so that
+                    // early, wrong shrinking decisions get revisited.
+                    unshrunk = true;
+                    reconstruct_gradient(&mut cache, &active, &alpha, p, c, &g_bar, &mut grad);
+                    active = (0..l).collect();
+                }
+                active.retain(|&t| !shrinkable(alpha[t], grad[t], y[t], c[t], g_up, g_low));
+            }
+        }
+
+        let Some((i, j)) = select_pair(&mut cache, &active, &alpha, &grad, q_diag, y, &up, &low, params.tol) else {
+            if active.len() == l {
+                converged = true;
+                break;
+            }
+            // Optimal on the shrunk problem; check the full one.
+            reconstruct_gradient(&mut cache, &active, &alpha, p, c, &g_bar, &mut grad);
+            active = (0..l).collect();
+            countdown = shrink_every;
+            continue;
+        };
+        n_iter += 1;
+
+        let (q_i, q_j) = (cache.row(i, &active), cache.row(j, &active));
+        let (old_i, old_j) = (alpha[i], alpha[j]);
+        update_pair(&mut alpha, &grad, q_diag, &q_i, y, c, i, j);
+        let (d_i, d_j) = (alpha[i] - old_i, alpha[j] - old_j);
+        for &t in &active {
+            grad[t] += q_i[t] * d_i + q_j[t] * d_j;
+        }
+        if params.shrinking {
+            for (k, old) in [(i, old_i), (j, old_j)] {
+                let (was_upper, is_upper) = (old >= c[k], alpha[k] >= c[k]);
+                if was_upper != is_upper {
+                    let row = cache.full_row(k);
+                    let step = if is_upper { c[k] } else { -c[k] };
+                    for (g, q) in g_bar.iter_mut().zip(row.iter()) {
+                        *g += step * q;
+                    }
+                }
+            }
+        }
+    }
+    if active.len() < l {
+        reconstruct_gradient(&mut cache, &active, &alpha, p, c, &g_bar, &mut grad);
+    }
+
+    // ρ: the mean of y∇f over free variables, or the middle of its feasible
+    // range when every variable sits at a bound.
+    let (mut ub, mut lb) = (f64::INFINITY, f64::NEG_INFINITY);
+    let (mut sum_free, mut n_free) = (0.0, 0);
+    for t in 0..l {
+        let yg = y[t] * grad[t];
+        if alpha[t] >= c[t] {
+            if y[t] < 0.0 { ub = ub.min(yg) } else { lb = lb.max(yg) }
+        } else if alpha[t] <= 0.0 {
+            if y[t] > 0.0 { ub = ub.min(yg) } else { lb = lb.max(yg) }
+        } else {
+            sum_free += yg;
+            n_free += 1;
+        }
+    }
+    let rho = if n_free > 0 { sum_free / n_free as f64 } else { (ub + lb) / 2.0 };
+    let objective = 0.5 * (0..l).map(|t| alpha[t] * (grad[t] + p[t])).sum::<f64>();
+    SmoSolution { alpha, rho, n_iter, converged, objective }
+}
+
+/// Largest violations `max_{up} -y∇f` and `max_{low} y∇f` over `active`.
+fn violations(
+    active: &[usize],
+    alpha: &[f64],
+    grad: &[f64],
+    y: &[f64],
+    up: &impl Fn(f64, usize) -> bool,
+    low: &impl Fn(f64, usize) -> bool,
+) -> (f64, f64) {
+    let (mut g_up, mut g_low) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for &t in active {
+        if up(alpha[t], t) {
+            g_up = g_up.max(-y[t] * grad[t]);
+        }
+        if low(alpha[t], t) {
+            g_low = g_low.max(y[t] * grad[t]);
+        }
+    }
+    (g_up, g_low)
+}
+
+/// Whether a bounded variable is unlikely to move again.
+fn shrinkable(a: f64, g: f64, y: f64, c: f64, g_up: f64, g_low: f64) -> bool {
+    if a >= c {
+        if y > 0.0 { -g > g_up } else { -g > g_low }
+    } else if a <= 0.0 {
+        if y > 0.0 { g > g_low } else { g > g_up }
+    } else {
+        false
+    }
+}
+
+/// Rebuild the gradient of the variables outside `active`, which went
+/// stale while they were shrunk: `∇fₜ = ḡₜ + pₜ + Σ_{free s} αₛ Q[t][s]`.
+/// Shrunk variables sit at a bound, so the free ones are all active.
+#[allow(clippy::too_many_arguments)]
+fn reconstruct_gradient<F: FnMut(usize, &[usize]) -> Vec<f64>>(
+    cache: &mut RowCache<F>,
+    active: &[usize],
+    alpha: &[f64],
+    p: &[f64],
+    c: &[f64],
+    g_bar: &[f64],
+    grad: &mut [f64],
+) {
+    let mut is_active = vec![false; p.len()];
+    for &t in active {
+        is_active[t] = true;
+    }
+    let inactive: Vec<usize> = (0..p.len()).filter(|&t| !is_active[t]).collect();
+    for &t in &inactive {
+        grad[t] = g_bar[t] + p[t];
+    }
+    for &s in active {
+        if alpha[s] > 0.0 && alpha[s] < c[s] {
+            // Q is symmetric: row s at the inactive columns.
+            for (&t, q) in inactive.iter().zip((cache.compute)(s, &inactive)) {
+                grad[t] += alpha[s] * q;
+            }
+        }
+    }
+    cache.grow();
+}
+
+/// The working pair, or `None` when `active` satisfies the optimality
+/// conditions within `tol`.
+#[allow(clippy::too_many_arguments)]
+fn select_pair<F: FnMut(usize, &[usize]) -> Vec<f64>>(
+    cache: &mut RowCache<F>,
+    active: &[usize],
+    alpha: &[f64],
+    grad: &[f64],
+    q_diag: &[f64],
+    y: &[f64],
+    up: &impl Fn(f64, usize) -> bool,
+    low: &impl Fn(f64, usize) -> bool,
+    tol: f64,
+) -> Option<(usize, usize)> {
+    let (mut i, mut g_max) = (None, f64::NEG_INFINITY);
+    for &t in active {
+        if up(alpha[t], t) && -y[t] * grad[t] >= g_max {
+            i = Some(t);
+            g_max = -y[t] * grad[t];
+        }
+    }
+    let i = i?;
+    let q_i = cache.row(i, active);
+
+    let (mut j, mut g_max2, mut best_gain) = (None, f64::NEG_INFINITY, f64::INFINITY);
+    for &t in active {
+        if !low(alpha[t], t) {
+            continue;
+        }
+        let v = y[t] * grad[t];
+        g_max2 = g_max2.max(v);
+        let grad_diff = g_max + v;
+        if grad_diff > 0.0 {
+            let quad = (q_diag[i] + q_diag[t] - 2.0 * y[i] * y[t] * q_i[t]).max(TAU);
+            let gain = -grad_diff * grad_diff / quad;
+            if gain <= best_gain {
+                j = Some(t);
+                best_gain = gain;
+            }
+        }
+    }
+    if g_max + g_max2 < tol {
+        return None;
+    }
+    j.map(|j| (i, j))
+}
+
+/// Minimize the objective over `αᵢ, αⱼ` with the rest fixed, then clip to
+/// the box while keeping `yᵀα` unchanged.
+#[allow(clippy::too_many_arguments)]
+fn update_pair(alpha: &mut [f64], grad: &[f64], q_diag: &[f64], q_i: &[f64], y: &[f64], c: &[f64], i: usize, j: usize) {
+    let (c_i, c_j) = (c[i], c[j]);
+    if y[i] != y[j] {
+        let quad = (q_diag[i] + q_diag[j] + 2.0 * q_i[j]).max(TAU);
+        let delta = (-grad[i] - grad[j]) / quad;
+        let diff = alpha[i] - alpha[j];
+        alpha[i] += delta;
+        alpha[j] += delta;
+        if diff > 0.0 {
+            if alpha[j] < 0.0 {
+                alpha[j] = 0.0;
+                alpha[i] = diff;
+            }
+        } else if alpha[i] < 0.0 {
+            alpha[i] = 0.0;
+            alpha[j] = -diff;
+        }
+        if diff > c_i - c_j {
+            if alpha[i] > c_i {
+                alpha[i] = c_i;
+                alpha[j] = c_i - diff;
+            }
+        } else if alpha[j] > c_j {
+            alpha[j] = c_j;
+            alpha[i] = c_j + diff;
+        }
+    } else {
+        let quad = (q_diag[i] + q_diag[j] - 2.0 * q_i[j]).max(TAU);
+        let delta = (grad[i] - grad[j]) / quad;
+        let sum = alpha[i] + alpha[j];
+        alpha[i] -= delta;
+        alpha[j] += delta;
+        if sum > c_i {
+            if alpha[i] > c_i {
+                alpha[i] = c_i;
+                alpha[j] = sum - c_i;
+            }
+        } else if alpha[j] < 0.0 {
+            alpha[j] = 0.0;
+            alpha[i] = sum;
+        }
+        if sum > c_j {
+            if alpha[j] > c_j {
+                alpha[j] = c_j;
+                alpha[i] = sum - c_j;
+            }
+        } else if alpha[i] < 0.0 {
+            alpha[i] = 0.0;
+            alpha[j] = sum;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_row_cache_lru() {
+        let computed = Cell::new(0);
+        let compute = |t: usize, cols: &[usize]| {
+            computed.set(computed.get() + 1);
+            cols.iter().map(|&s| (t * 10 + s) as f64).collect()
+        };
+        let mut cache = RowCache::new(compute, 4, 2);
+        let all = [0, 1, 2, 3];
+        assert_eq!(&cache.row(0, &all)[..], &[0.0, 1.0, 2.0, 3.0]);
+        cache.row(1, &all);
+        cache.row(0, &all);
+        assert_eq!(computed.get(), 2);
+        // Row 1 is the least recently used and makes room for row 2.
+        cache.row(2, &all);
+        cache.row(0, &all);
+        assert_eq!(computed.get(), 3);
+        cache.row(1, &all);
+        assert_eq!(computed.get(), 4);
+
+        // A partial row serves partial requests until the set grows.
+        assert_eq!(&cache.row(3, &[1, 3])[..], &[0.0, 31.0, 0.0, 33.0]);
+        cache.row(3, &[3]);
+        assert_eq!(computed.get(), 5);
+        cache.row(3, &all);
+        assert_eq!(computed.get(), 6);
+        cache.grow();
+        cache.row(3, &[1, 3]);
+        assert_eq!(computed.get(), 6);
+    }
+
+    #[test]
+    fn test_shrinking_matches_full_solve() {
+        // Linear-kernel C-SVC on two overlapping clouds.
+        let l = 60;
+        let mut seed = 7u64;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        };
+        let y: Vec<f64> = (0..l).map(|t| if t % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        let x: Vec<[f64; 2]> = y.iter().map(|&yt| [yt + 2.0 * rand(), yt + 2.0 * rand()]).collect();
+        let q = |t: usize, s: usize| y[t] * y[s] * (x[t][0] * x[s][0] + x[t][1] * x[s][1]);
+        let q_row = |t: usize, cols: &[usize]| -> Vec<f64> { cols.iter().map(|&s| q(t, s)).collect() };
+        let q_diag: Vec<f64> = (0..l).map(|t| q(t, t)).collect();
+        let (p, c) = (vec![-1.0; l], vec![1.0; l]);
+        let params = |shrinking, cache_rows| SmoParams { tol: 1e-6, max_iter: 100_000, cache_rows, shrinking };
+
+        let full = solve(q_row, &q_diag, &p, &y, &c, &params(false, l));
+        let shrunk = solve(q_row, &q_diag, &p, &y, &c, &params(true, 4));
+        assert!(full.converged && shrunk.converged);
+        assert!((full.objective - shrunk.objective).abs() < 1e-6);
+        assert!((full.rho - shrunk.rho).abs() < 1e-3);
+    }
+}
@@ -1,26 +1,111 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{sample_weights, ClassWeight, Float, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
+
+use crate::smo::{self, SmoParams};
 
 /// Kernel type for SVM.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub enum Kernel<T: Float> {
     Linear,
     RBF { gamma: T },
     Polynomial { degree: usize, coef0: T },
 }
 
-/// Support Vector Classifier using simplified SMO.
+impl<T: Float> Kernel<T> {
+    /// `K(a, b)` for two feature rows.
+    pub fn compute(&self, a: &[T], b: &[T]) -> T {
+        match self {
+            Kernel::Linear => a.iter().zip(b).map(|(&u, &v)| u * v).sum(),
+            Kernel::RBF { gamma } => {
+                let sq_dist: T = a.iter().zip(b).map(|(&u, &v)| (u - v) * (u - v)).sum();
+                (-*gamma * sq_dist).exp()
+            }
+            Kernel::Polynomial { degree, coef0 } => {
+                let dot: T = a.iter().zip(b).map(|(&u, &v)| u * v).sum();
+                (dot + *coef0).powi(*degree as i32)
+            }
+        }
+    }
+}
+
+/// How `SVC` handles more than two classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MultiClass {
+    /// One machine per class against all others; `k` machines.
+    OneVsRest,
+    /// One machine per pair of classes, combined by voting; `k(k-1)/2`
+    /// machines, each on a smaller subset.
+    #[default]
+    OneVsOne,
+}
+
+/// Rows of an `n`-column kernel matrix that fit in `cache_mb` megabytes
+/// (at least two, for the working pair).
+pub(crate) fn cache_rows(cache_mb: f64, n: usize) -> usize {
+    ((cache_mb * 1024.0 * 1024.0 / (8 * n.max(1)) as f64) as usize).max(2)
+}
+
+fn default_cache_size() -> f64 {
+    200.0
+}
+
+fn default_shrinking() -> bool {
+    true
+}
+
+/// Support Vector Classifier trained by SMO (see the `smo` module): the
+/// working pair is chosen from the optimality violations with second-order
+/// information, kernel rows are cached, and bounded variables are shrunk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct SVC<T: Float> {
     pub c: T,
     pub kernel: Kernel<T>,
+    /// Cap on optimisation work: at most `max_iter × n_samples` SMO steps.
     pub max_iter: usize,
+    /// Stop once no pair violates the optimality conditions by more.
     pub tol: T,
+    pub class_weight: Option<ClassWeight>,
+    /// Kernel cache budget in megabytes.
+    #[serde(default = "default_cache_size")]
+    pub cache_size: f64,
+    /// Drop variables stuck at a bound from the search while optimising.
+    #[serde(default = "default_shrinking")]
+    pub shrinking: bool,
+    /// Fit Platt scaling for `predict_proba`; see `with_probability`.
+    #[serde(default)]
+    pub probability: bool,
+    /// Strategy for targets with more than two classes.
+    #[serde(default)]
+    pub multi_class: MultiClass,
     // Trained parameters
     alphas: Option<Vec<T>>,
     bias: T,
     x_train: Option<Tensor<T>>,
     y_train: Option<Vec<T>>,
+    /// Platt's `(a, b)` for `predict_proba`.
+    #[serde(default)]
+    platt: Option<(f64, f64)>,
+    #[serde(default)]
+    converged: bool,
+    #[serde(default)]
+    n_iter: usize,
+    #[serde(default)]
+    objective: f64,
+    /// Sorted class labels of a multi-class fit; `None` for binary.
+    #[serde(default)]
+    classes: Option<Vec<T>>,
+    /// Binary machines of a multi-class fit, one per class (one-vs-rest)
+    /// or per entry of `pairs` (one-vs-one).
+    #[serde(default)]
+    machines: Vec<SVC<T>>,
+    /// Class indices `(a, b)` of each one-vs-one machine; class `a` is its
+    /// positive side.
+    #[serde(default)]
+    pairs: Vec<(usize, usize)>,
 }
 
 impl<T: Float> SVC<T>
@@ -33,175 +118,261 @@ where
             kernel,
             max_iter,
             tol: T::from_f64(1e-3),
+            class_weight: None,
+            cache_size: default_cache_size(),
+            shrinking: true,
+            probability: false,
+            multi_class: MultiClass::default(),
             alphas: None,
             bias: T::ZERO,
             x_train: None,
             y_train: None,
+            platt: None,
+            converged: false,
+            n_iter: 0,
+            objective: 0.0,
+            classes: None,
+            machines: Vec::new(),
+            pairs: Vec::new(),
         }
     }
 
-    fn kernel_eval(&self, x: &Tensor<T>, i: usize, j: usize, d: usize) -> TensorResult<T> {
-        match &self.kernel {
-            Kernel::Linear => {
-                let mut dot = T::ZERO;
-                for k in 0..d {
-                    dot = dot + x.get(&[i, k])? * x.get(&[j, k])?;
-                }
-                Ok(dot)
-            }
-            Kernel::RBF { gamma } => {
-                let mut sq_dist = T::ZERO;
-                for k in 0..d {
-                    let diff = x.get(&[i, k])? - x.get(&[j, k])?;
-                    sq_dist = sq_dist + diff * diff;
-                }
-                Ok((-*gamma * sq_dist).exp())
-            }
-            Kernel::Polynomial { degree, coef0 } => {
-                let mut dot = T::ZERO;
-                for k in 0..d {
-                    dot = dot + x.get(&[i, k])? * x.get(&[j, k])?;
-                }
-                Ok((dot + *coef0).powi(*degree as i32))
-            }
-        }
+    /// Scale `c` per class, e.g. `ClassWeight::Balanced` for imbalanced targets.
+    pub fn with_class_weight(mut self, class_weight: ClassWeight) -> Self {
+        self.class_weight = Some(class_weight);
+        self
     }
 
-    fn kernel_eval_xy(&self, x1: &Tensor<T>, i: usize, x2: &Tensor<T>, j: usize, d: usize) -> TensorResult<T> {
-        match &self.kernel {
-            Kernel::Linear => {
-                let mut dot = T::ZERO;
-                for k in 0..d {
-                    dot = dot + x1.get(&[i, k])? * x2.get(&[j, k])?;
-                }
-                Ok(dot)
-            }
-            Kernel::RBF { gamma } => {
-                let mut sq_dist = T::ZERO;
-                for k in 0..d {
-                    let diff = x1.get(&[i, k])? - x2.get(&[j, k])?;
-                    sq_dist = sq_dist + diff * diff;
-                }
-                Ok((-*gamma * sq_dist).exp())
-            }
-            Kernel::Polynomial { degree, coef0 } => {
-                let mut dot = T::ZERO;
-                for k in 0..d {
-                    dot = dot + x1.get(&[i, k])? * x2.get(&[j, k])?;
-                }
-                Ok((dot + *coef0).powi(*degree as i32))
-            }
-        }
+    pub fn with_cache_size(mut self, megabytes: f64) -> Self {
+        self.cache_size = megabytes;
+        self
     }
 
-    /// Fit using simplified SMO algorithm.
+    pub fn with_shrinking(mut self, shrinking: bool) -> Self {
+        self.shrinking = shrinking;
+        self
+    }
+
+    pub fn with_multi_class(mut self, multi_class: MultiClass) -> Self {
+        self.multi_class = multi_class;
+        self
+    }
+
+    /// Enable `predict_proba`. Fitting then also trains the SVC on three
+    /// folds of the data and fits Platt scaling to the out-of-fold decision
+    /// values, so training costs about twice as much.
+    pub fn with_probability(mut self) -> Self {
+        self.probability = true;
+        self
+    }
+
+    /// Solve the dual problem with SMO.
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.fit_weighted(x, y, None)
+    }
+
+    /// Fit with per-row weights: row `i` gets box constraint `c * weight_i`
+    /// (combined with `class_weight`).
+    ///
+    /// Labels with two distinct values train one machine (values above 0
+    /// are the positive class); more values train several, per
+    /// `multi_class`.
+    pub fn fit_weighted(&mut self, x: &Tensor<T>, y: &Tensor<T>, sample_weight: Option<&Tensor<T>>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let d = x.shape().dim(1)?;
+        if y.numel() != n {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} rows but y has {} labels", n, y.numel()
+            )));
+        }
+        let mut classes = y.data().to_vec();
+        classes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        classes.dedup();
+        if classes.len() > 2 {
+            return self.fit_multiclass(x, y, sample_weight, classes);
+        }
+        self.classes = None;
+        self.machines.clear();
+        self.pairs.clear();
+        let bounds: Vec<T> = sample_weights(y, self.class_weight.as_ref(), sample_weight)?
+            .into_iter()
+            .map(|w| self.c * w)
+            .collect();
 
         // Convert labels to +1/-1
         let labels: Vec<T> = y.data().iter().map(|&v| {
             if v > T::ZERO { T::ONE } else { T::NEG_ONE }
         }).collect();
 
-        let mut alphas = vec![T::ZERO; n];
-        let mut b = T::ZERO;
-
-        for _pass in 0..self.max_iter {
-            let mut num_changed = 0;
+        let row = |i: usize| &x.data()[i * d..(i + 1) * d];
+        let signs: Vec<f64> = labels.iter().map(|v| v.to_f64()).collect();
+        let q_row = |i: usize, cols: &[usize]| -> Vec<f64> {
+            cols.iter().map(|&j| signs[i] * signs[j] * self.kernel.compute(row(i), row(j)).to_f64()).collect()
+        };
+        let q_diag: Vec<f64> = (0..n).map(|i| self.kernel.compute(row(i), row(i)).to_f64()).collect();
+        let params = SmoParams {
+            tol: self.tol.to_f64(),
+            max_iter: self.max_iter.saturating_mul(n.max(1)),
+            cache_rows: cache_rows(self.cache_size, n),
+            shrinking: self.shrinking,
+        };
+        let bounds: Vec<f64> = bounds.iter().map(|v| v.to_f64()).collect();
+        let solution = smo::solve(q_row, &q_diag, &vec![-1.0; n], &signs, &bounds, &params);
+        let alphas: Vec<T> = solution.alpha.iter().map(|&a| T::from_f64(a)).collect();
+        let b = T::from_f64(-solution.rho);
+        self.converged = solution.converged;
+        self.n_iter = solution.n_iter;
+        self.objective = solution.objective;
 
-            for i in 0..n {
-                let mut ei = -labels[i];
-                for j in 0..n {
-                    ei = ei + alphas[j] * labels[j] * self.kernel_eval(x, j, i, d)?;
-                }
-                ei = ei + b - labels[i] + labels[i]; // E_i = f(x_i) - y_i
-                // Recompute properly
-                let mut fi = b;
-                for j in 0..n {
-                    fi = fi + alphas[j] * labels[j] * self.kernel_eval(x, j, i, d)?;
-                }
-                ei = fi - labels[i];
-
-                let yi = labels[i];
-                if (yi * ei < -self.tol && alphas[i] < self.c)
-                    || (yi * ei > self.tol && alphas[i] > T::ZERO)
-                {
-                    // Select j randomly (simplified SMO picks any j != i)
-                    let j = if i == 0 { 1 } else { 0 };
-                    let yj = labels[j];
-
-                    let mut fj = b;
-                    for k in 0..n {
-                        fj = fj + alphas[k] * labels[k] * self.kernel_eval(x, k, j, d)?;
-                    }
-                    let ej = fj - yj;
-
-                    let ai_old = alphas[i];
-                    let aj_old = alphas[j];
-
-                    // Compute bounds
-                    let (lo, hi) = if yi != yj {
-                        let lo = T::ZERO.max(alphas[j] - alphas[i]);
-                        let hi = self.c.min(self.c + alphas[j] - alphas[i]);
-                        (lo, hi)
-                    } else {
-                        let lo = T::ZERO.max(alphas[i] + alphas[j] - self.c);
-                        let hi = self.c.min(alphas[i] + alphas[j]);
-                        (lo, hi)
-                    };
-
-                    if (lo - hi).abs() < T::EPSILON {
-                        continue;
-                    }
-
-                    let kii = self.kernel_eval(x, i, i, d)?;
-                    let kjj = self.kernel_eval(x, j, j, d)?;
-                    let kij = self.kernel_eval(x, i, j, d)?;
-                    let eta = T::TWO * kij - kii - kjj;
+        self.alphas = Some(alphas);
+        self.bias = b;
+        self.x_train = Some(x.clone());
+        self.y_train = Some(labels);
+        self.platt = None;
+        if self.probability {
+            self.platt = Some(self.fit_platt(x, y, sample_weight)?);
+        }
 
-                    if eta >= T::ZERO {
-                        continue;
-                    }
+        Ok(())
+    }
 
-                    alphas[j] = alphas[j] - yj * (ei - ej) / eta;
-                    alphas[j] = alphas[j].max(lo).min(hi);
+    /// A fresh binary machine with the same settings.
+    fn binary_machine(&self) -> SVC<T> {
+        SVC {
+            tol: self.tol,
+            class_weight: None,
+            cache_size: self.cache_size,
+            shrinking: self.shrinking,
+            probability: self.probability,
+            ..SVC::new(self.c, self.kernel.clone(), self.max_iter)
+        }
+    }
 
-                    if (alphas[j] - aj_old).abs() < T::from_f64(1e-5) {
-                        continue;
+    fn fit_multiclass(
+        &mut self,
+        x: &Tensor<T>,
+        y: &Tensor<T>,
+        sample_weight: Option<&Tensor<T>>,
+        classes: Vec<T>,
+    ) -> TensorResult<()> {
+        // Class weights are resolved once against all classes, then passed
+        // to every machine as row weights.
+        let weights = Tensor::new(sample_weights(y, self.class_weight.as_ref(), sample_weight)?, vec![y.numel()])?;
+        let label = |v: T| classes.iter().position(|&c| c == v).unwrap();
+        let labels: Vec<usize> = y.data().iter().map(|&v| label(v)).collect();
+        let k = classes.len();
+
+        self.machines.clear();
+        self.pairs.clear();
+        match self.multi_class {
+            MultiClass::OneVsRest => {
+                for c in 0..k {
+                    let target: Vec<T> = labels.iter().map(|&l| if l == c { T::ONE } else { T::ZERO }).collect();
+                    let mut machine = self.binary_machine();
+                    machine.fit_weighted(x, &Tensor::new(target, vec![labels.len()])?, Some(&weights))?;
+                    self.machines.push(machine);
+                }
+            }
+            MultiClass::OneVsOne => {
+                for a in 0..k {
+                    for b in a + 1..k {
+                        let rows: Vec<usize> = (0..labels.len()).filter(|&i| labels[i] == a || labels[i] == b).collect();
+                        let target: Vec<T> = rows.iter().map(|&i| if labels[i] == a { T::ONE } else { T::ZERO }).collect();
+                        let mut machine = self.binary_machine();
+                        machine.fit_weighted(
+                            &x.index_select(0, &rows)?,
+                            &Tensor::new(target, vec![rows.len()])?,
+                            Some(&weights.index_select(0, &rows)?),
+                        )?;
+                        self.machines.push(machine);
+                        self.pairs.push((a, b));
                     }
-
-                    alphas[i] = alphas[i] + yi * yj * (aj_old - alphas[j]);
-
-                    let b1 = b - ei - yi * (alphas[i] - ai_old) * kii - yj * (alphas[j] - aj_old) * kij;
-                    let b2 = b - ej - yi * (alphas[i] - ai_old) * kij - yj * (alphas[j] - aj_old) * kjj;
-
-                    b = if alphas[i] > T::ZERO && alphas[i] < self.c {
-                        b1
-                    } else if alphas[j] > T::ZERO && alphas[j] < self.c {
-                        b2
-                    } else {
-                        (b1 + b2) / T::TWO
-                    };
-
-                    num_changed += 1;
                 }
             }
+        }
+        self.classes = Some(classes);
+        self.alphas = None;
+        self.x_train = None;
+        self.y_train = None;
+        self.platt = None;
+        self.converged = self.machines.iter().all(|m| m.converged);
+        self.n_iter = self.machines.iter().map(|m| m.n_iter).sum();
+        self.objective = self.machines.iter().map(|m| m.objective).sum();
+        Ok(())
+    }
 
-            if num_changed == 0 {
-                break;
+    /// Per-class scores `[n, k]` of a multi-class fit. One-vs-rest gives
+    /// each machine's decision value; one-vs-one gives each class's votes
+    /// plus a tie-breaking confidence in `(-⅓, ⅓)` from the summed
+    /// decision values.
+    fn multiclass_scores(&self, x: &Tensor<T>, k: usize) -> TensorResult<Tensor<T>> {
+        let n = x.shape().dim(0)?;
+        let mut scores = vec![0.0; n * k];
+        if self.multi_class == MultiClass::OneVsRest || self.pairs.is_empty() {
+            for (c, machine) in self.machines.iter().enumerate() {
+                for (i, f) in machine.decision_function(x)?.data().iter().enumerate() {
+                    scores[i * k + c] = f.to_f64();
+                }
+            }
+        } else {
+            let mut confidence = vec![0.0; n * k];
+            for (machine, &(a, b)) in self.machines.iter().zip(&self.pairs) {
+                for (i, f) in machine.decision_function(x)?.data().iter().enumerate() {
+                    let f = f.to_f64();
+                    scores[i * k + if f >= 0.0 { a } else { b }] += 1.0;
+                    confidence[i * k + a] += f;
+                    confidence[i * k + b] -= f;
+                }
+            }
+            for (s, c) in scores.iter_mut().zip(&confidence) {
+                *s += c / (3.0 * (c.abs() + 1.0));
             }
         }
+        Tensor::new(scores.into_iter().map(T::from_f64).collect(), vec![n, k])
+    }
 
-        self.alphas = Some(alphas);
-        self.bias = b;
-        self.x_train = Some(x.clone());
-        self.y_train = Some(labels);
+    /// Platt parameters from out-of-fold decision values: rows of each
+    /// class are dealt to three folds in turn. Data too small for that
+    /// falls back to the in-sample decision values.
+    fn fit_platt(&self, x: &Tensor<T>, y: &Tensor<T>, sample_weight: Option<&Tensor<T>>) -> TensorResult<(f64, f64)> {
+        const FOLDS: usize = 3;
+        let n = y.numel();
+        let labels: Vec<f64> = y.data().iter().map(|&v| if v > T::ZERO { 1.0 } else { 0.0 }).collect();
+        let n_pos = labels.iter().filter(|&&v| v == 1.0).count();
+        if n_pos < FOLDS || n - n_pos < FOLDS {
+            let scores: Vec<f64> = self.decision_function(x)?.data().iter().map(|v| v.to_f64()).collect();
+            return Ok(crate::platt::platt_scaling(&scores, &labels));
+        }
 
-        Ok(())
+        let mut fold = vec![0; n];
+        let mut next = 0;
+        for class in [0.0, 1.0] {
+            for i in (0..n).filter(|&i| labels[i] == class) {
+                fold[i] = next % FOLDS;
+                next += 1;
+            }
+        }
+        let mut scores = vec![0.0; n];
+        for k in 0..FOLDS {
+            let (test, train): (Vec<usize>, Vec<usize>) = (0..n).partition(|&i| fold[i] == k);
+            let mut model = SVC { probability: false, ..self.clone() };
+            let weights = sample_weight.map(|w| w.index_select(0, &train)).transpose()?;
+            model.fit_weighted(&x.index_select(0, &train)?, &y.index_select(0, &train)?, weights.as_ref())?;
+            let fold_scores = model.decision_function(&x.index_select(0, &test)?)?;
+            for (&i, s) in test.iter().zip(fold_scores.data()) {
+                scores[i] = s.to_f64();
+            }
+        }
+        Ok(crate::platt::platt_scaling(&scores, &labels))
     }
 
-    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+    /// Signed distance-like score `f(x) = Σ αᵢ yᵢ K(xᵢ, x) + b`; positive
+    /// means class 1. A multi-class fit returns per-class scores `[n, k]`
+    /// instead, highest for the predicted class.
+    pub fn decision_function(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        if let Some(classes) = &self.classes {
+            return self.multiclass_scores(x, classes.len());
+        }
         let x_train = self.x_train.as_ref().ok_or_else(|| {
             TensorError::InvalidOperation("Model not fitted".into())
         })?;
@@ -211,18 +382,117 @@ where
         let n_train = x_train.shape().dim(0)?;
         let d = x.shape().dim(1)?;
 
-        let mut predictions = Vec::with_capacity(n_test);
-        for i in 0..n_test {
+        let mut scores = Vec::with_capacity(n_test);
+        for row in x.data().chunks(d.max(1)).take(n_test) {
             let mut f = self.bias;
             for j in 0..n_train {
                 if alphas[j].abs() > T::EPSILON {
-                    f = f + alphas[j] * labels[j] * self.kernel_eval_xy(x_train, j, x, i, d)?;
+                    f += alphas[j] * labels[j] * self.kernel.compute(&x_train.data()[j * d..(j + 1) * d], row);
+                }
+            }
+            scores.push(f);
+        }
+
+        Tensor::new(scores, vec![n_test])
+    }
+
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        if let Some(classes) = &self.classes {
+            let k = classes.len();
+            let scores = self.multiclass_scores(x, k)?;
+            let labels: Vec<T> = scores
+                .data()
+                .chunks(k)
+                .map(|row| {
+                    let best = (0..k).fold(0, |best, c| if row[c] > row[best] { c } else { best });
+                    classes[best]
+                })
+                .collect();
+            return Tensor::new(labels.clone(), vec![labels.len()]);
+        }
+        Ok(self.decision_function(x)?.apply(|f| if f >= T::ZERO { T::ONE } else { T::ZERO }))
+    }
+
+    /// Probability of class 1 for each row, `[n]`, from Platt scaling of
+    /// the decision values. Needs `with_probability`.
+    ///
+    /// A multi-class fit returns `[n, k]`: one-vs-rest normalizes the
+    /// machines' probabilities to sum to one, one-vs-one averages each
+    /// class's pairwise probabilities.
+    pub fn predict_proba(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        if let Some(classes) = &self.classes {
+            return self.multiclass_proba(x, classes.len());
+        }
+        let (a, b) = self.platt.ok_or_else(|| {
+            TensorError::InvalidOperation("predict_proba needs an SVC fitted with with_probability()".into())
+        })?;
+        Ok(self.decision_function(x)?.apply(|f| T::from_f64(1.0 / (1.0 + (a * f.to_f64() + b).exp()))))
+    }
+
+    fn multiclass_proba(&self, x: &Tensor<T>, k: usize) -> TensorResult<Tensor<T>> {
+        let n = x.shape().dim(0)?;
+        let mut proba = vec![0.0; n * k];
+        if self.pairs.is_empty() {
+            for (c, machine) in self.machines.iter().enumerate() {
+                for (i, p) in machine.predict_proba(x)?.data().iter().enumerate() {
+                    proba[i * k + c] = p.to_f64();
+                }
+            }
+            for row in proba.chunks_mut(k) {
+                let total: f64 = row.iter().sum();
+                row.iter_mut().for_each(|p| *p = if total > 0.0 { *p / total } else { 1.0 / k as f64 });
+            }
+        } else {
+            let share = 2.0 / (k * (k - 1)) as f64;
+            for (machine, &(a, b)) in self.machines.iter().zip(&self.pairs) {
+                for (i, p) in machine.predict_proba(x)?.data().iter().enumerate() {
+                    proba[i * k + a] += share * p.to_f64();
+                    proba[i * k + b] += share * (1.0 - p.to_f64());
                 }
             }
-            predictions.push(if f >= T::ZERO { T::ONE } else { T::ZERO });
         }
+        Tensor::new(proba.into_iter().map(T::from_f64).collect(), vec![n, k])
+    }
+
+    /// Sorted class labels of a multi-class fit.
+    pub fn classes(&self) -> Option<&[T]> {
+        self.classes.as_deref()
+    }
+
+    /// Binary machines of a multi-class fit: one per class for one-vs-rest,
+    /// one per pair in `pairs()` for one-vs-one.
+    pub fn estimators(&self) -> &[SVC<T>] {
+        &self.machines
+    }
+
+    /// Class indices `(positive, negative)` of each one-vs-one machine.
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
 
-        Tensor::new(predictions, vec![n_test])
+    /// Whether the last `fit` reached `tol` within `max_iter` (in every
+    /// machine, for multi-class).
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// SMO steps taken by the last `fit`.
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+
+    /// Final dual objective `½ αᵀQα - Σα` of the last `fit`.
+    pub fn dual_objective(&self) -> f64 {
+        self.objective
+    }
+
+    /// Number of support vectors (rows with `α > 0`), summed over the
+    /// machines of a multi-class fit.
+    pub fn n_support(&self) -> usize {
+        if !self.machines.is_empty() {
+            return self.machines.iter().map(|m| m.n_support()).sum();
+        }
+        self.alphas.as_ref().map_or(0, |a| a.iter().filter(|v| v.abs() > T::EPSILON).count())
     }
 }
 
@@ -248,4 +518,80 @@ mod tests {
             .count();
         assert!(correct >= 4, "SVM classified {} out of 6", correct);
     }
+
+    #[test]
+    fn test_svc_predict_proba() {
+        let rows: Vec<Vec<f64>> = (0..30).map(|i| vec![i as f64 / 3.0, ((i * 7) % 5) as f64 * 0.2]).collect();
+        let x = Tensor::from_vec2d(&rows).unwrap();
+        let y: Tensor<f64> = Tensor::new((0..30).map(|i| if i >= 15 { 1.0 } else { 0.0 }).collect(), vec![30]).unwrap();
+
+        let mut svc = SVC::new(1.0, Kernel::Linear, 100).with_probability();
+        svc.fit(&x, &y).unwrap();
+        let scores = svc.decision_function(&x).unwrap();
+        let proba = svc.predict_proba(&x).unwrap();
+        assert!(proba.data()[0] < 0.5 && proba.data()[29] > 0.5);
+        // Probabilities follow the decision values.
+        for i in 0..29 {
+            if scores.data()[i] < scores.data()[i + 1] {
+                assert!(proba.data()[i] <= proba.data()[i + 1]);
+            }
+        }
+        assert!(SVC::new(1.0, Kernel::Linear, 100).predict_proba(&x).is_err());
+    }
+
+    #[test]
+    fn test_svc_smo_converges() {
+        // Inner disc against an outer ring: needs the RBF kernel.
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+        for i in 0..60 {
+            let angle = i as f64 * 0.7;
+            let radius = if i % 2 == 0 { 0.5 + 0.02 * (i % 5) as f64 } else { 2.0 + 0.05 * (i % 3) as f64 };
+            rows.push(vec![radius * angle.cos(), radius * angle.sin()]);
+            labels.push((i % 2) as f64);
+        }
+        let x = Tensor::from_vec2d(&rows).unwrap();
+        let y = Tensor::new(labels, vec![60]).unwrap();
+
+        let mut svc = SVC::new(10.0, Kernel::RBF { gamma: 1.0 }, 100);
+        svc.fit(&x, &y).unwrap();
+        assert!(svc.converged());
+        assert_eq!(svc.predict(&x).unwrap().data(), y.data());
+        assert!(svc.n_support() < 60);
+
+        // Shrinking and a tiny cache change the path, not the optimum.
+        let mut plain = SVC::new(10.0, Kernel::RBF { gamma: 1.0 }, 100).with_shrinking(false).with_cache_size(0.0);
+        plain.fit(&x, &y).unwrap();
+        assert!(plain.converged());
+        assert!((plain.dual_objective() - svc.dual_objective()).abs() < 1e-3 * svc.dual_objective().abs());
+    }
+
+    #[test]
+    fn test_svc_multi_class() {
+        // Three blobs, labelled 1, 4 and 7.
+        let centers = [(0.0, 0.0, 1.0), (5.0, 0.0, 4.0), (0.0, 5.0, 7.0)];
+        let (mut rows, mut labels) = (Vec::new(), Vec::new());
+        for i in 0..36 {
+            let (cx, cy, label) = centers[i % 3];
+            rows.push(vec![cx + (i % 4) as f64 * 0.3, cy + (i % 5) as f64 * 0.2]);
+            labels.push(label);
+        }
+        let x = Tensor::from_vec2d(&rows).unwrap();
+        let y = Tensor::new(labels, vec![36]).unwrap();
+
+        for strategy in [MultiClass::OneVsRest, MultiClass::OneVsOne] {
+            let mut svc = SVC::new(1.0, Kernel::Linear, 100).with_multi_class(strategy).with_probability();
+            svc.fit(&x, &y).unwrap();
+            assert_eq!(svc.predict(&x).unwrap().data(), y.data());
+            assert_eq!(svc.estimators().len(), 3);
+            assert_eq!(svc.classes().unwrap(), &[1.0, 4.0, 7.0]);
+            assert_eq!(svc.decision_function(&x).unwrap().shape_vec(), vec![36, 3]);
+            let proba = svc.predict_proba(&x).unwrap();
+            for (row, label) in proba.data().chunks(3).zip(y.data()) {
+                assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+                let best = (0..3).fold(0, |b, c| if row[c] > row[b] { c } else { b });
+                assert_eq!([1.0, 4.0, 7.0][best], *label);
+            }
+        }
+    }
 }
@@ -1,126 +1,147 @@
 use oxidize_ml_core::{Float, Tensor, TensorError};
 use oxidize_ml_core::error::TensorResult;
+use serde::{Deserialize, Serialize};
 
-/// Support Vector Regression using Simplified SMO.
+use crate::smo::{self, SmoParams};
+use crate::svm::{cache_rows, Kernel};
+
+/// The kernel of an `SVR`; the former name of `Kernel`, kept so existing
+/// `SVRKernel::Linear` call sites still compile.
+pub type SVRKernel<T> = Kernel<T>;
+
+/// ε-Support Vector Regression, trained by SMO on the dual problem.
 ///
-/// Uses ε-insensitive loss: L(y, f(x)) = max(0, |y - f(x)| - ε)
+/// Uses ε-insensitive loss: L(y, f(x)) = max(0, |y - f(x)| - ε), so rows
+/// predicted within `epsilon` of their target cost nothing and do not
+/// become support vectors. Kernels are those of `SVC`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct SVR<T: Float> {
     pub c: T,
     pub epsilon: T,
-    pub kernel: SVRKernel,
+    pub kernel: Kernel<T>,
+    /// Cap on optimisation work: at most `max_iter × n_samples` SMO steps.
     pub max_iter: usize,
     pub tol: T,
-    alphas: Vec<T>,
+    /// Kernel cache budget in megabytes.
+    pub cache_size: f64,
+    pub shrinking: bool,
+    /// `αᵢ - αᵢ*` of each support vector.
+    dual_coef: Vec<T>,
+    support_vectors: Option<Tensor<T>>,
     bias: T,
-    x_train: Option<Tensor<T>>,
-    y_train: Option<Tensor<T>>,
-}
-
-#[derive(Clone, Debug)]
-pub enum SVRKernel {
-    Linear,
-    RBF { gamma: f64 },
-    Polynomial { degree: usize, coef0: f64 },
+    converged: bool,
+    n_iter: usize,
 }
 
 impl<T: Float> SVR<T> {
-    pub fn new(c: T, epsilon: T, kernel: SVRKernel, max_iter: usize) -> Self {
+    pub fn new(c: T, epsilon: T, kernel: Kernel<T>, max_iter: usize) -> Self {
         SVR {
-            c, epsilon, kernel, max_iter,
+            c,
+            epsilon,
+            kernel,
+            max_iter,
             tol: T::from_f64(1e-3),
-            alphas: Vec::new(), bias: T::ZERO,
-            x_train: None, y_train: None,
+            cache_size: 200.0,
+            shrinking: true,
+            dual_coef: Vec::new(),
+            support_vectors: None,
+            bias: T::ZERO,
+            converged: false,
+            n_iter: 0,
         }
     }
 
-    fn kernel_value(&self, x: &Tensor<T>, i: usize, j: usize, p: usize) -> TensorResult<T> {
-        match &self.kernel {
-            SVRKernel::Linear => {
-                let mut sum = T::ZERO;
-                for k in 0..p {
-                    sum = sum + x.get(&[i, k])? * x.get(&[j, k])?;
-                }
-                Ok(sum)
-            }
-            SVRKernel::RBF { gamma } => {
-                let mut sq_dist = T::ZERO;
-                for k in 0..p {
-                    let diff = x.get(&[i, k])? - x.get(&[j, k])?;
-                    sq_dist = sq_dist + diff * diff;
-                }
-                Ok((-T::from_f64(*gamma) * sq_dist).exp())
-            }
-            SVRKernel::Polynomial { degree, coef0 } => {
-                let mut dot = T::ZERO;
-                for k in 0..p {
-                    dot = dot + x.get(&[i, k])? * x.get(&[j, k])?;
-                }
-                Ok((dot + T::from_f64(*coef0)).powi(*degree as i32))
-            }
-        }
+    pub fn with_cache_size(mut self, megabytes: f64) -> Self {
+        self.cache_size = megabytes;
+        self
+    }
+
+    pub fn with_shrinking(mut self, shrinking: bool) -> Self {
+        self.shrinking = shrinking;
+        self
     }
 
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
+        if y.numel() != n {
+            return Err(TensorError::DimensionMismatch(format!(
+                "x has {} rows but y has {} targets", n, y.numel()
+            )));
+        }
+        let row = |i: usize| &x.data()[i * p..(i + 1) * p];
 
-        // Use dual variables: alpha_pos and alpha_neg (stored interleaved)
-        // For simplicity, use a single alpha vector approach for linear SVR
-        let mut w = vec![T::ZERO; p];
-        let mut b = T::ZERO;
-
-        // Gradient descent approach for SVR with epsilon-insensitive loss
-        let lr = T::from_f64(0.001);
-        for _iter in 0..self.max_iter {
-            for i in 0..n {
-                let mut pred = b;
-                for j in 0..p {
-                    pred = pred + w[j] * x.get(&[i, j])?;
-                }
-                let error = y.data()[i] - pred;
-
-                if error > self.epsilon {
-                    // Below epsilon tube — push up
-                    for j in 0..p {
-                        w[j] = w[j] + lr * (x.get(&[i, j])? - T::from_f64(2.0) * (T::ONE / self.c) * w[j]);
-                    }
-                    b = b + lr;
-                } else if error < -self.epsilon {
-                    // Above epsilon tube — push down
-                    for j in 0..p {
-                        w[j] = w[j] - lr * (x.get(&[i, j])? + T::from_f64(2.0) * (T::ONE / self.c) * w[j]);
-                    }
-                    b = b - lr;
-                } else {
-                    // Inside epsilon tube — only regularize
-                    for j in 0..p {
-                        w[j] = w[j] - lr * T::from_f64(2.0) * (T::ONE / self.c) * w[j];
-                    }
-                }
+        // Variables 0..n are αᵢ (y = +1), n..2n are αᵢ* (y = -1).
+        let eps = self.epsilon.to_f64();
+        let targets: Vec<f64> = y.data().iter().map(|v| v.to_f64()).collect();
+        let p_vec: Vec<f64> = targets.iter().map(|&t| eps - t).chain(targets.iter().map(|&t| eps + t)).collect();
+        let signs: Vec<f64> = (0..2 * n).map(|t| if t < n { 1.0 } else { -1.0 }).collect();
+        let diag: Vec<f64> = (0..n).map(|i| self.kernel.compute(row(i), row(i)).to_f64()).collect();
+        let q_diag: Vec<f64> = diag.iter().chain(&diag).copied().collect();
+        let bounds = vec![self.c.to_f64(); 2 * n];
+        let q_row = |t: usize, cols: &[usize]| -> Vec<f64> {
+            cols.iter().map(|&s| signs[t] * signs[s] * self.kernel.compute(row(t % n), row(s % n)).to_f64()).collect()
+        };
+        let params = SmoParams {
+            tol: self.tol.to_f64(),
+            max_iter: self.max_iter.saturating_mul(n.max(1)),
+            cache_rows: cache_rows(self.cache_size, 2 * n),
+            shrinking: self.shrinking,
+        };
+        let solution = smo::solve(q_row, &q_diag, &p_vec, &signs, &bounds, &params);
+
+        let mut support = Vec::new();
+        let mut coef = Vec::new();
+        for i in 0..n {
+            let beta = solution.alpha[i] - solution.alpha[i + n];
+            if beta.abs() > 1e-12 {
+                support.extend_from_slice(row(i));
+                coef.push(T::from_f64(beta));
             }
         }
-
-        self.alphas = w;
-        self.bias = b;
-        self.x_train = Some(x.clone());
-        self.y_train = Some(y.clone());
+        self.support_vectors = Some(Tensor::new(support, vec![coef.len(), p])?);
+        self.dual_coef = coef;
+        self.bias = T::from_f64(-solution.rho);
+        self.converged = solution.converged;
+        self.n_iter = solution.n_iter;
         Ok(())
     }
 
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let sv = self.support_vectors.as_ref().ok_or_else(|| {
+            TensorError::InvalidOperation("Model not fitted".into())
+        })?;
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
-        let mut preds = Vec::with_capacity(n);
-
-        for i in 0..n {
-            let mut pred = self.bias;
-            for j in 0..p {
-                pred = pred + self.alphas[j] * x.get(&[i, j])?;
-            }
-            preds.push(pred);
-        }
+        let preds = x
+            .data()
+            .chunks(p.max(1))
+            .take(n)
+            .map(|row| {
+                self.dual_coef
+                    .iter()
+                    .zip(sv.data().chunks(p.max(1)))
+                    .fold(self.bias, |acc, (&beta, s)| acc + beta * self.kernel.compute(s, row))
+            })
+            .collect();
         Tensor::new(preds, vec![n])
     }
+
+    /// Number of support vectors: rows on or outside the ε-tube.
+    pub fn n_support(&self) -> usize {
+        self.dual_coef.len()
+    }
+
+    /// Whether SMO met `tol` within `max_iter` steps.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// SMO steps taken by the last `fit`.
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
 }
 
 #[cfg(test)]
@@ -143,4 +164,38 @@ mod tests {
                 "SVR pred {} vs expected {}", pred.data()[i], y.data()[i]);
         }
     }
+
+    #[test]
+    fn test_svr_linear_within_tube() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![1.0], vec![2.0], vec![3.0], vec![4.0], vec![5.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[2.0, 4.0, 6.0, 8.0, 10.0]);
+
+        // A large enough C lets the fit reach every target within ε.
+        let mut model = SVR::new(10.0, 0.1, Kernel::Linear, 500);
+        model.fit(&x, &y).unwrap();
+
+        let pred = model.predict(&x).unwrap();
+        for i in 0..5 {
+            assert!((pred.data()[i] - y.data()[i]).abs() < 0.1 + 1e-2,
+                "SVR pred {} vs expected {}", pred.data()[i], y.data()[i]);
+        }
+        assert!(model.converged());
+    }
+
+    #[test]
+    fn test_svr_rbf() {
+        let rows: Vec<Vec<f64>> = (0..40).map(|i| vec![i as f64 * 0.15]).collect();
+        let x = Tensor::from_vec2d(&rows).unwrap();
+        let y: Tensor<f64> = Tensor::new(rows.iter().map(|r| r[0].sin()).collect(), vec![40]).unwrap();
+
+        let mut model = SVR::new(10.0, 0.05, Kernel::RBF { gamma: 1.0 }, 200);
+        model.fit(&x, &y).unwrap();
+        let pred = model.predict(&x).unwrap();
+        let worst = pred.data().iter().zip(y.data()).map(|(p, t)| (p - t).abs()).fold(0.0, f64::max);
+        assert!(worst < 0.06, "worst error {}", worst);
+        // Rows strictly inside the tube are not support vectors.
+        assert!(model.n_support() < 40);
+    }
 }
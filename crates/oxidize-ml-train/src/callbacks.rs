@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use oxidize_ml_core::TensorError;
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_nn::{Layer, StateDict};
+use oxidize_ml_optim::{LrScheduler, Optimizer};
+
+use crate::checkpoint::Checkpoint;
+
+/// Scalars recorded for one epoch: `loss` and each metric on the training
+/// batches, `val_loss` and `val_<metric>` when there is validation data,
+/// and the learning rate as `lr`.
+pub type Logs = BTreeMap<String, f64>;
+
+/// The logs of every epoch run so far.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    pub epochs: Vec<Logs>,
+}
+
+impl History {
+    /// The value of `key` at each epoch that recorded it.
+    pub fn get(&self, key: &str) -> Vec<f64> {
+        self.epochs.iter().filter_map(|logs| logs.get(key).copied()).collect()
+    }
+
+    pub fn last(&self) -> Option<&Logs> {
+        self.epochs.last()
+    }
+
+    pub fn len(&self) -> usize {
+        self.epochs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epochs.is_empty()
+    }
+}
+
+/// What a callback can see and change of a training run.
+pub struct TrainContext<'a> {
+    pub model: &'a mut dyn Layer,
+    pub optimizer: &'a mut dyn Optimizer,
+    /// The current epoch, from 0.
+    pub epoch: usize,
+    /// Optimizer steps taken so far.
+    pub global_step: usize,
+    /// Set to end `fit` after the current epoch.
+    pub stop_training: bool,
+}
+
+/// Hooks run by `Trainer::fit`. Every method defaults to doing nothing.
+pub trait Callback {
+    fn on_train_begin(&mut self, _ctx: &mut TrainContext) {}
+    fn on_epoch_begin(&mut self, _ctx: &mut TrainContext) {}
+    /// After each optimizer step, with that batch's loss.
+    fn on_batch_end(&mut self, _ctx: &mut TrainContext, _loss: f64) {}
+    /// After training and validation of an epoch, with its logs.
+    fn on_epoch_end(&mut self, _ctx: &mut TrainContext, _logs: &Logs) {}
+    fn on_train_end(&mut self, _ctx: &mut TrainContext) {}
+
+    /// Progress to keep in a `Checkpoint`, such as a scheduler's.
+    fn state(&self) -> BTreeMap<String, f64> {
+        BTreeMap::new()
+    }
+
+    fn load_state(&mut self, _state: &BTreeMap<String, f64>) -> TensorResult<()> {
+        Ok(())
+    }
+}
+
+/// Sets the optimizer's learning rate from a scheduler, stepped once per
+/// epoch with the `monitor`ed value of the epoch's logs.
+pub struct LearningRateScheduler<S: LrScheduler> {
+    pub scheduler: S,
+    pub monitor: String,
+}
+
+impl<S: LrScheduler> LearningRateScheduler<S> {
+    /// Monitors `val_loss`, falling back to `loss` without validation data.
+    pub fn new(scheduler: S) -> Self {
+        LearningRateScheduler { scheduler, monitor: "val_loss".to_string() }
+    }
+
+    pub fn with_monitor(mut self, monitor: &str) -> Self {
+        self.monitor = monitor.to_string();
+        self
+    }
+}
+
+impl<S: LrScheduler> Callback for LearningRateScheduler<S> {
+    fn on_train_begin(&mut self, ctx: &mut TrainContext) {
+        ctx.optimizer.set_lr(self.scheduler.lr());
+    }
+
+    fn on_epoch_end(&mut self, ctx: &mut TrainContext, logs: &Logs) {
+        self.scheduler.step_epoch(monitored(logs, &self.monitor));
+        ctx.optimizer.set_lr(self.scheduler.lr());
+    }
+
+    fn state(&self) -> BTreeMap<String, f64> {
+        self.scheduler.state()
+    }
+
+    fn load_state(&mut self, state: &BTreeMap<String, f64>) -> TensorResult<()> {
+        self.scheduler.load_state(state)
+    }
+}
+
+/// The `key` entry of `logs`, or `loss` when `key` is absent (no
+/// validation data for `val_loss`, say).
+fn monitored(logs: &Logs, key: &str) -> Option<f64> {
+    logs.get(key).or_else(|| logs.get("loss")).copied()
+}
+
+/// Whether lower or higher values of a monitored metric are better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Min,
+    Max,
+}
+
+impl Mode {
+    /// Whether `value` beats `best` by more than `min_delta`.
+    fn improves(self, value: f64, best: f64, min_delta: f64) -> bool {
+        match self {
+            Mode::Min => value < best - min_delta,
+            Mode::Max => value > best + min_delta,
+        }
+    }
+
+    fn worst(self) -> f64 {
+        match self {
+            Mode::Min => f64::INFINITY,
+            Mode::Max => f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Stop when the monitored metric has not improved by more than
+/// `min_delta` for `patience` epochs.
+///
+/// With `restore_best_weights` the model's state dict is kept at every
+/// improvement and loaded back when training ends. The kept weights are
+/// not part of a `Checkpoint`; the counters are.
+pub struct EarlyStopping {
+    pub monitor: String,
+    pub patience: usize,
+    pub min_delta: f64,
+    pub mode: Mode,
+    pub restore_best_weights: bool,
+    pub best: f64,
+    /// Epochs since the last improvement.
+    pub wait: usize,
+    /// Epoch (from 0) of the best value, and of the stop.
+    pub best_epoch: Option<usize>,
+    pub stopped_epoch: Option<usize>,
+    epochs_seen: usize,
+    best_weights: Option<StateDict>,
+}
+
+impl EarlyStopping {
+    /// Monitors `val_loss` (falling back to `loss`), lower being better.
+    pub fn new(patience: usize) -> Self {
+        EarlyStopping {
+            monitor: "val_loss".to_string(),
+            patience,
+            min_delta: 0.0,
+            mode: Mode::Min,
+            restore_best_weights: false,
+            best: f64::INFINITY,
+            wait: 0,
+            best_epoch: None,
+            stopped_epoch: None,
+            epochs_seen: 0,
+            best_weights: None,
+        }
+    }
+
+    pub fn with_monitor(mut self, monitor: &str, mode: Mode) -> Self {
+        self.monitor = monitor.to_string();
+        self.mode = mode;
+        self.best = mode.worst();
+        self
+    }
+
+    pub fn with_min_delta(mut self, min_delta: f64) -> Self {
+        self.min_delta = min_delta;
+        self
+    }
+
+    pub fn with_restore_best_weights(mut self, restore: bool) -> Self {
+        self.restore_best_weights = restore;
+        self
+    }
+
+    /// Record one epoch's value of the metric; true once training should
+    /// stop. For hand-written loops, which then call `restore_best`.
+    pub fn update(&mut self, value: f64, model: &dyn Layer) -> bool {
+        let epoch = self.epochs_seen;
+        self.epochs_seen += 1;
+        if self.mode.improves(value, self.best, self.min_delta) {
+            self.best = value;
+            self.best_epoch = Some(epoch);
+            self.wait = 0;
+            if self.restore_best_weights {
+                self.best_weights = Some(model.state_dict());
+            }
+            return false;
+        }
+        self.wait += 1;
+        if self.wait > self.patience {
+            self.stopped_epoch = Some(epoch);
+            return true;
+        }
+        false
+    }
+
+    /// Load the weights kept at the best epoch; false if none were kept.
+    pub fn restore_best(&self, model: &mut dyn Layer) -> TensorResult<bool> {
+        match &self.best_weights {
+            Some(weights) => model.load_state_dict(weights, true).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+impl Callback for EarlyStopping {
+    fn on_epoch_end(&mut self, ctx: &mut TrainContext, logs: &Logs) {
+        let value = monitored(logs, &self.monitor).expect("EarlyStopping: no loss in the logs");
+        if self.update(value, &*ctx.model) {
+            ctx.stop_training = true;
+        }
+    }
+
+    fn on_train_end(&mut self, ctx: &mut TrainContext) {
+        if self.restore_best_weights {
+            self.restore_best(ctx.model).expect("EarlyStopping: best weights no longer fit the model");
+        }
+    }
+
+    fn state(&self) -> BTreeMap<String, f64> {
+        BTreeMap::from([
+            ("best".to_string(), self.best),
+            ("wait".to_string(), self.wait as f64),
+            ("best_epoch".to_string(), self.best_epoch.map_or(-1.0, |e| e as f64)),
+            ("epochs_seen".to_string(), self.epochs_seen as f64),
+        ])
+    }
+
+    fn load_state(&mut self, state: &BTreeMap<String, f64>) -> TensorResult<()> {
+        let entry = |key: &str| {
+            state.get(key).copied().ok_or_else(|| {
+                TensorError::InvalidOperation(format!("EarlyStopping state is missing '{}'", key))
+            })
+        };
+        let (best, wait, best_epoch, seen) = (entry("best")?, entry("wait")?, entry("best_epoch")?, entry("epochs_seen")?);
+        self.best = best;
+        self.wait = wait as usize;
+        self.best_epoch = (best_epoch >= 0.0).then_some(best_epoch as usize);
+        self.epochs_seen = seen as usize;
+        Ok(())
+    }
+}
+
+/// Save a `Checkpoint` of the model and optimizer after an epoch: only
+/// when the monitored metric improves (`save_best_only`, the default), or
+/// after every epoch. `{epoch}` in the path is replaced by the epoch
+/// number (from 1), so every save can keep its own file.
+///
+/// Write errors abort training with a panic.
+pub struct ModelCheckpoint {
+    pub path: String,
+    pub monitor: String,
+    pub mode: Mode,
+    pub save_best_only: bool,
+    pub best: f64,
+    /// Files written so far.
+    pub saved: Vec<PathBuf>,
+}
+
+impl ModelCheckpoint {
+    /// Best-only on `val_loss` (falling back to `loss`), lower being better.
+    pub fn new(path: &str) -> Self {
+        ModelCheckpoint {
+            path: path.to_string(),
+            monitor: "val_loss".to_string(),
+            mode: Mode::Min,
+            save_best_only: true,
+            best: f64::INFINITY,
+            saved: Vec::new(),
+        }
+    }
+
+    pub fn with_monitor(mut self, monitor: &str, mode: Mode) -> Self {
+        self.monitor = monitor.to_string();
+        self.mode = mode;
+        self.best = mode.worst();
+        self
+    }
+
+    pub fn with_save_best_only(mut self, best_only: bool) -> Self {
+        self.save_best_only = best_only;
+        self
+    }
+
+    /// Record the metric after `epoch` epochs and save if it calls for it;
+    /// the path written, if any. For hand-written loops.
+    pub fn update(
+        &mut self,
+        value: f64,
+        model: &dyn Layer,
+        optimizer: &dyn Optimizer,
+        epoch: usize,
+    ) -> Result<Option<PathBuf>, Box<dyn Error>> {
+        let improved = self.mode.improves(value, self.best, 0.0);
+        if improved {
+            self.best = value;
+        }
+        if self.save_best_only && !improved {
+            return Ok(None);
+        }
+        let path = PathBuf::from(self.path.replace("{epoch}", &epoch.to_string()));
+        Checkpoint::capture(model, optimizer, epoch).save(&path)?;
+        self.saved.push(path.clone());
+        Ok(Some(path))
+    }
+}
+
+impl Callback for ModelCheckpoint {
+    fn on_epoch_end(&mut self, ctx: &mut TrainContext, logs: &Logs) {
+        let value = monitored(logs, &self.monitor).expect("ModelCheckpoint: no loss in the logs");
+        self.update(value, &*ctx.model, &*ctx.optimizer, ctx.epoch + 1)
+            .expect("ModelCheckpoint: save failed");
+    }
+
+    fn state(&self) -> BTreeMap<String, f64> {
+        BTreeMap::from([("best".to_string(), self.best)])
+    }
+
+    fn load_state(&mut self, state: &BTreeMap<String, f64>) -> TensorResult<()> {
+        if let Some(&best) = state.get("best") {
+            self.best = best;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::Tensor;
+    use oxidize_ml_nn::Linear;
+    use oxidize_ml_optim::SGD;
+
+    #[test]
+    fn test_early_stopping_restores_best_weights() {
+        let mut model: Linear = Linear::new(2, 1);
+        let mut stopper = EarlyStopping::new(1).with_min_delta(0.05).with_restore_best_weights(true);
+        let mut stopped = None;
+        for (epoch, loss) in [1.0, 0.5, 0.48, 0.6, 0.3].into_iter().enumerate() {
+            model.weight.set_data(Tensor::full(vec![2, 1], epoch as f64));
+            if stopper.update(loss, &model) {
+                stopped = Some(epoch);
+                break;
+            }
+        }
+        // 0.48 is within min_delta of 0.5, so 0.5 stays best; two epochs
+        // without improvement exceed a patience of 1.
+        assert_eq!((stopped, stopper.best_epoch, stopper.best), (Some(3), Some(1), 0.5));
+        assert!(stopper.restore_best(&mut model).unwrap());
+        assert_eq!(model.weight.data.data(), &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_model_checkpoint_saves_only_improvements() {
+        let model: Linear = Linear::new(2, 1);
+        let params = model.parameters();
+        let sgd = SGD::new(params.iter().map(|p| p.node_id).collect(), params.iter().map(|p| p.data.clone()).collect(), 0.1, 0.0);
+        let dir = std::env::temp_dir().join(format!("oxidize_ml_train_model_checkpoint_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pattern = dir.join("epoch-{epoch}.json");
+        let mut saver = ModelCheckpoint::new(pattern.to_str().unwrap()).with_monitor("accuracy", Mode::Max);
+        for (epoch, accuracy) in [0.6, 0.8, 0.7, 0.9].into_iter().enumerate() {
+            saver.update(accuracy, &model, &sgd, epoch + 1).unwrap();
+        }
+        let names: Vec<String> = saver.saved.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, ["epoch-1.json", "epoch-2.json", "epoch-4.json"]);
+        let restored = Checkpoint::load(&saver.saved[2]).unwrap();
+        assert_eq!((restored.epoch, &restored.model), (4, &model.state_dict()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
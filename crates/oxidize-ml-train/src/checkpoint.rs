@@ -0,0 +1,235 @@
+//! Snapshots of a training run to resume it exactly after a crash.
+//!
+//! A `Checkpoint` holds the model's state dict, the optimizer's moments and
+//! counters, the scheduler's (or every callback's) progress, the epoch and
+//! step counters, the history and random-stream positions: the process-wide
+//! generator as `global`, plus any stream added with `with_rng` (the
+//! shuffling stream of the training `DataLoader`, say). Streams of their
+//! own that are not added, such as Dropout's, do not resume.
+//!
+//! Saved as JSON, with floats written so they read back bit for bit.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use oxidize_ml_core::{global_rng_state, set_global_rng_state, RngState, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_nn::{Layer, StateDict};
+use oxidize_ml_optim::{LrScheduler, Optimizer, OptimizerState};
+
+use crate::callbacks::History;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// Epochs completed.
+    pub epoch: usize,
+    pub global_step: usize,
+    pub model: StateDict,
+    pub optimizer: OptimizerState,
+    pub scheduler: Option<BTreeMap<String, f64>>,
+    /// Each callback's state, in the trainer's callback order.
+    pub callbacks: Vec<BTreeMap<String, f64>>,
+    pub history: History,
+    pub rng: BTreeMap<String, RngState>,
+}
+
+impl Checkpoint {
+    /// Snapshot `model` and `optimizer` after `epoch` epochs, with the
+    /// global generator's position.
+    pub fn capture(model: &dyn Layer, optimizer: &dyn Optimizer, epoch: usize) -> Self {
+        Checkpoint {
+            epoch,
+            global_step: 0,
+            model: model.state_dict(),
+            optimizer: optimizer.state(),
+            scheduler: None,
+            callbacks: Vec::new(),
+            history: History::default(),
+            rng: BTreeMap::from([("global".to_string(), global_rng_state())]),
+        }
+    }
+
+    pub fn with_global_step(mut self, global_step: usize) -> Self {
+        self.global_step = global_step;
+        self
+    }
+
+    pub fn with_scheduler(mut self, scheduler: &dyn LrScheduler) -> Self {
+        self.scheduler = Some(scheduler.state());
+        self
+    }
+
+    /// Also save a random stream under `name`.
+    pub fn with_rng(mut self, name: &str, state: RngState) -> Self {
+        self.rng.insert(name.to_string(), state);
+        self
+    }
+
+    /// Load the model (strictly) and optimizer state, and put the global
+    /// generator back where it was.
+    pub fn restore(&self, model: &mut dyn Layer, optimizer: &mut dyn Optimizer) -> TensorResult<()> {
+        model.load_state_dict(&self.model, true)?;
+        optimizer.load_state(&self.optimizer)?;
+        if let Some(global) = self.rng.get("global") {
+            set_global_rng_state(global);
+        }
+        Ok(())
+    }
+
+    pub fn restore_scheduler(&self, scheduler: &mut dyn LrScheduler) -> TensorResult<()> {
+        let state = self
+            .scheduler
+            .as_ref()
+            .ok_or_else(|| TensorError::InvalidOperation("checkpoint has no scheduler state".to_string()))?;
+        scheduler.load_state(state)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_vec(&CheckpointFile::from(self))?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let file: CheckpointFile = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(file.into())
+    }
+}
+
+/// A float that survives JSON even when infinite or NaN (as a best metric
+/// often starts out), written as a string then.
+#[derive(Clone, Copy)]
+struct Scalar(f64);
+
+impl Serialize for Scalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.is_finite() {
+            serializer.serialize_f64(self.0)
+        } else {
+            serializer.serialize_str(&self.0.to_string())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(f64),
+            Text(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(v) => Ok(Scalar(v)),
+            Repr::Text(text) => text.parse().map(Scalar).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+type Scalars = BTreeMap<String, Scalar>;
+
+fn to_scalars(map: &BTreeMap<String, f64>) -> Scalars {
+    map.iter().map(|(k, &v)| (k.clone(), Scalar(v))).collect()
+}
+
+fn from_scalars(map: Scalars) -> BTreeMap<String, f64> {
+    map.into_iter().map(|(k, v)| (k, v.0)).collect()
+}
+
+/// On-disk form of a `Checkpoint`.
+#[derive(Serialize, Deserialize)]
+struct CheckpointFile {
+    epoch: usize,
+    global_step: usize,
+    model: StateDict,
+    optimizer: OptimizerState,
+    scheduler: Option<Scalars>,
+    callbacks: Vec<Scalars>,
+    history: Vec<Scalars>,
+    rng: BTreeMap<String, RngState>,
+}
+
+impl From<&Checkpoint> for CheckpointFile {
+    fn from(c: &Checkpoint) -> Self {
+        CheckpointFile {
+            epoch: c.epoch,
+            global_step: c.global_step,
+            model: c.model.clone(),
+            optimizer: c.optimizer.clone(),
+            scheduler: c.scheduler.as_ref().map(to_scalars),
+            callbacks: c.callbacks.iter().map(to_scalars).collect(),
+            history: c.history.epochs.iter().map(to_scalars).collect(),
+            rng: c.rng.clone(),
+        }
+    }
+}
+
+impl From<CheckpointFile> for Checkpoint {
+    fn from(f: CheckpointFile) -> Self {
+        Checkpoint {
+            epoch: f.epoch,
+            global_step: f.global_step,
+            model: f.model,
+            optimizer: f.optimizer,
+            scheduler: f.scheduler.map(from_scalars),
+            callbacks: f.callbacks.into_iter().map(from_scalars).collect(),
+            history: History { epochs: f.history.into_iter().map(from_scalars).collect() },
+            rng: f.rng,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::{set_global_seed, Tensor};
+    use oxidize_ml_data::{DataLoader, TensorDataset};
+    use oxidize_ml_loss::mse_loss;
+    use oxidize_ml_nn::{Linear, ReLULayer, Sequential};
+    use oxidize_ml_optim::{Adam, ReduceLROnPlateau};
+    use crate::{LearningRateScheduler, Trainer};
+
+    fn data() -> TensorDataset {
+        let x: Vec<f64> = (0..40).map(|k| (k as f64 * 0.37).sin()).collect();
+        let y = x.chunks(2).map(|p| p[0] - 2.0 * p[1]).collect();
+        TensorDataset::new(Tensor::new(x, vec![20, 2]).unwrap(), Tensor::new(y, vec![20]).unwrap())
+    }
+
+    fn trainer(seed: u64) -> Trainer<Sequential, Adam> {
+        set_global_seed(seed);
+        let model: Sequential =
+            Sequential::new().add(Box::new(Linear::new(2, 6))).add(Box::new(ReLULayer)).add(Box::new(Linear::new(6, 1)));
+        let params = model.parameters();
+        let adam = Adam::new(params.iter().map(|p| p.node_id).collect(), params.iter().map(|p| p.data.clone()).collect(), 0.02);
+        Trainer::new(model, adam, |out, y| mse_loss(out, &out.constant(y.reshape(vec![y.numel(), 1]).unwrap())))
+            .with_callback(LearningRateScheduler::new(ReduceLROnPlateau::new(0.02, 0.5, 1)).with_monitor("loss"))
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted_run() {
+        let data = data();
+        let mut straight = trainer(1).with_epochs(6);
+        let mut loader = DataLoader::new(&data, 4, true);
+        straight.fit(&mut loader);
+
+        let mut first = trainer(1).with_epochs(3);
+        let mut loader = DataLoader::new(&data, 4, true);
+        first.fit(&mut loader);
+        let path = std::env::temp_dir().join(format!("oxidize_ml_train_checkpoint_{}.json", std::process::id()));
+        first.checkpoint().with_rng("train", loader.rng_state()).save(&path).unwrap();
+
+        // A fresh process: different initial weights and streams.
+        let mut resumed = trainer(99).with_epochs(6);
+        let mut loader = DataLoader::new(&data, 4, true);
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        resumed.resume(&checkpoint).unwrap();
+        loader.set_rng_state(&checkpoint.rng["train"]);
+        assert_eq!(resumed.optimizer.t, 15);
+        resumed.fit(&mut loader);
+
+        assert_eq!(resumed.history, straight.history);
+        assert_eq!(resumed.model.state_dict(), straight.model.state_dict());
+    }
+}
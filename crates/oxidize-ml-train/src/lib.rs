@@ -0,0 +1,10 @@
+pub mod trainer;
+pub mod callbacks;
+pub mod checkpoint;
+pub mod logging;
+pub mod parallel;
+
+pub use trainer::*;
+pub use callbacks::*;
+pub use checkpoint::*;
+pub use parallel::*;
@@ -0,0 +1,378 @@
+//! Recording training curves to disk.
+//!
+//! A `Logger` takes scalars and histograms tagged with a step. `CsvLogger`
+//! and `JsonlLogger` write one line per value; `TensorBoardLogger` writes a
+//! `tfevents` file that TensorBoard reads from its log directory. Loggers
+//! work on their own in a hand-written loop; the `MetricLogger` callback
+//! feeds them from a `Trainer`.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::json;
+
+use crate::callbacks::{Callback, Logs, TrainContext};
+
+pub trait Logger {
+    fn log_scalar(&mut self, tag: &str, value: f64, step: usize) -> io::Result<()>;
+
+    /// Record the distribution of `values`. Defaults to its summary
+    /// statistics as the scalars `tag/min`, `tag/max`, `tag/mean` and
+    /// `tag/std`.
+    fn log_histogram(&mut self, tag: &str, values: &[f64], step: usize) -> io::Result<()> {
+        let stats = Summary::of(values);
+        self.log_scalar(&format!("{}/min", tag), stats.min, step)?;
+        self.log_scalar(&format!("{}/max", tag), stats.max, step)?;
+        self.log_scalar(&format!("{}/mean", tag), stats.sum / stats.count, step)?;
+        let variance = (stats.sum_squares / stats.count - (stats.sum / stats.count).powi(2)).max(0.0);
+        self.log_scalar(&format!("{}/std", tag), variance.sqrt(), step)
+    }
+
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+struct Summary {
+    min: f64,
+    max: f64,
+    count: f64,
+    sum: f64,
+    sum_squares: f64,
+}
+
+impl Summary {
+    fn of(values: &[f64]) -> Self {
+        Summary {
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            count: values.len() as f64,
+            sum: values.iter().sum(),
+            sum_squares: values.iter().map(|v| v * v).sum(),
+        }
+    }
+}
+
+/// `step,tag,value` rows under a header.
+pub struct CsvLogger {
+    out: BufWriter<File>,
+}
+
+impl CsvLogger {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "step,tag,value")?;
+        Ok(CsvLogger { out })
+    }
+}
+
+impl Logger for CsvLogger {
+    fn log_scalar(&mut self, tag: &str, value: f64, step: usize) -> io::Result<()> {
+        let tag = if tag.contains([',', '"']) { format!("\"{}\"", tag.replace('"', "\"\"")) } else { tag.to_string() };
+        writeln!(self.out, "{},{},{}", step, tag, value)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// One JSON object per line: `{"step", "tag", "value", "wall_time"}`.
+pub struct JsonlLogger {
+    out: BufWriter<File>,
+}
+
+impl JsonlLogger {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(JsonlLogger { out: BufWriter::new(File::create(path)?) })
+    }
+}
+
+impl Logger for JsonlLogger {
+    fn log_scalar(&mut self, tag: &str, value: f64, step: usize) -> io::Result<()> {
+        let line = json!({ "step": step, "tag": tag, "value": value, "wall_time": wall_time() });
+        writeln!(self.out, "{}", line)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn wall_time() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64())
+}
+
+/// TensorBoard event file in `log_dir`, one `Event` record per value.
+///
+/// Histograms are written with 30 equal-width buckets between their
+/// minimum and maximum.
+pub struct TensorBoardLogger {
+    out: BufWriter<File>,
+}
+
+impl TensorBoardLogger {
+    pub fn create(log_dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(&log_dir)?;
+        let time = wall_time();
+        let name = format!("events.out.tfevents.{}.oxidize-ml.{}", time as u64, std::process::id());
+        let mut logger = TensorBoardLogger { out: BufWriter::new(File::create(log_dir.as_ref().join(name))?) };
+        let mut event = Proto::default();
+        event.double(1, time).bytes(3, b"brain.Event:2");
+        logger.write_record(&event.0)?;
+        Ok(logger)
+    }
+
+    /// Write an `Event` whose summary holds one value.
+    fn write_value(&mut self, value: &Proto, step: usize) -> io::Result<()> {
+        let mut summary = Proto::default();
+        summary.bytes(1, &value.0);
+        let mut event = Proto::default();
+        event.double(1, wall_time()).varint(2, step as u64).bytes(5, &summary.0);
+        self.write_record(&event.0)
+    }
+
+    /// TFRecord framing: length, its masked CRC, data, its masked CRC.
+    fn write_record(&mut self, data: &[u8]) -> io::Result<()> {
+        let len = (data.len() as u64).to_le_bytes();
+        self.out.write_all(&len)?;
+        self.out.write_all(&masked_crc32c(&len).to_le_bytes())?;
+        self.out.write_all(data)?;
+        self.out.write_all(&masked_crc32c(data).to_le_bytes())
+    }
+}
+
+impl Logger for TensorBoardLogger {
+    fn log_scalar(&mut self, tag: &str, value: f64, step: usize) -> io::Result<()> {
+        let mut v = Proto::default();
+        v.bytes(1, tag.as_bytes()).float(2, value as f32);
+        self.write_value(&v, step)
+    }
+
+    fn log_histogram(&mut self, tag: &str, values: &[f64], step: usize) -> io::Result<()> {
+        const BUCKETS: usize = 30;
+        let stats = Summary::of(values);
+        let width = (stats.max - stats.min) / BUCKETS as f64;
+        let limits: Vec<f64> = (1..=BUCKETS).map(|i| stats.min + width * i as f64).collect();
+        let mut counts = vec![0.0; BUCKETS];
+        for &v in values {
+            let i = if width > 0.0 { ((v - stats.min) / width) as usize } else { 0 };
+            counts[i.min(BUCKETS - 1)] += 1.0;
+        }
+        let mut histo = Proto::default();
+        histo
+            .double(1, stats.min)
+            .double(2, stats.max)
+            .double(3, stats.count)
+            .double(4, stats.sum)
+            .double(5, stats.sum_squares)
+            .packed_doubles(6, &limits)
+            .packed_doubles(7, &counts);
+        let mut v = Proto::default();
+        v.bytes(1, tag.as_bytes()).bytes(5, &histo.0);
+        self.write_value(&v, step)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// A protobuf message being encoded, field by field.
+#[derive(Default)]
+struct Proto(Vec<u8>);
+
+impl Proto {
+    fn raw_varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.0.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        self.0.push(v as u8);
+    }
+
+    fn key(&mut self, field: u64, wire_type: u64) {
+        self.raw_varint((field << 3) | wire_type);
+    }
+
+    fn varint(&mut self, field: u64, v: u64) -> &mut Self {
+        self.key(field, 0);
+        self.raw_varint(v);
+        self
+    }
+
+    fn double(&mut self, field: u64, v: f64) -> &mut Self {
+        self.key(field, 1);
+        self.0.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    fn float(&mut self, field: u64, v: f32) -> &mut Self {
+        self.key(field, 5);
+        self.0.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    fn bytes(&mut self, field: u64, data: &[u8]) -> &mut Self {
+        self.key(field, 2);
+        self.raw_varint(data.len() as u64);
+        self.0.extend_from_slice(data);
+        self
+    }
+
+    fn packed_doubles(&mut self, field: u64, values: &[f64]) -> &mut Self {
+        let data: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.bytes(field, &data)
+    }
+}
+
+/// CRC-32C (Castagnoli), masked as TFRecord files store it.
+fn masked_crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0x82F6_3B78 } else { crc >> 1 };
+        }
+    }
+    let crc = !crc;
+    crc.rotate_right(15).wrapping_add(0xA282_EAD8)
+}
+
+/// Feeds `Trainer` logs to loggers: each epoch's logs at the epoch number,
+/// optionally the batch loss every `batch_every` steps (as `batch/loss`)
+/// and a histogram of every parameter per epoch.
+///
+/// Write errors abort training with a panic.
+pub struct MetricLogger {
+    pub loggers: Vec<Box<dyn Logger>>,
+    pub batch_every: Option<usize>,
+    pub histograms: bool,
+}
+
+impl MetricLogger {
+    pub fn new() -> Self {
+        MetricLogger { loggers: Vec::new(), batch_every: None, histograms: false }
+    }
+
+    pub fn with_logger(mut self, logger: impl Logger + 'static) -> Self {
+        self.loggers.push(Box::new(logger));
+        self
+    }
+
+    pub fn with_batch_every(mut self, steps: usize) -> Self {
+        assert!(steps > 0, "MetricLogger: batch_every must be positive");
+        self.batch_every = Some(steps);
+        self
+    }
+
+    pub fn with_histograms(mut self, histograms: bool) -> Self {
+        self.histograms = histograms;
+        self
+    }
+
+    fn each(&mut self, mut f: impl FnMut(&mut dyn Logger) -> io::Result<()>) {
+        for logger in &mut self.loggers {
+            f(logger.as_mut()).expect("MetricLogger: write failed");
+        }
+    }
+}
+
+impl Default for MetricLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Callback for MetricLogger {
+    fn on_batch_end(&mut self, ctx: &mut TrainContext, loss: f64) {
+        if self.batch_every.is_some_and(|every| ctx.global_step.is_multiple_of(every)) {
+            self.each(|logger| logger.log_scalar("batch/loss", loss, ctx.global_step));
+        }
+    }
+
+    fn on_epoch_end(&mut self, ctx: &mut TrainContext, logs: &Logs) {
+        let epoch = ctx.epoch + 1;
+        let params: Vec<(String, Vec<f64>)> = if self.histograms {
+            ctx.model.named_parameters().into_iter().map(|(name, p)| (name, p.data.data().to_vec())).collect()
+        } else {
+            Vec::new()
+        };
+        self.each(|logger| {
+            for (tag, &value) in logs {
+                logger.log_scalar(tag, value, epoch)?;
+            }
+            for (name, values) in &params {
+                logger.log_histogram(&format!("params/{}", name), values, epoch)?;
+            }
+            logger.flush()
+        });
+    }
+
+    fn on_train_end(&mut self, _ctx: &mut TrainContext) {
+        self.each(|logger| logger.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("oxidize_ml_train_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_csv_and_jsonl_loggers() {
+        let dir = dir("text_logs");
+        let mut csv = CsvLogger::create(dir.join("log.csv")).unwrap();
+        let mut jsonl = JsonlLogger::create(dir.join("log.jsonl")).unwrap();
+        for logger in [&mut csv as &mut dyn Logger, &mut jsonl] {
+            logger.log_scalar("loss", 0.5, 1).unwrap();
+            logger.log_histogram("w", &[1.0, 3.0], 1).unwrap();
+            logger.flush().unwrap();
+        }
+        let text = fs::read_to_string(dir.join("log.csv")).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[..3], ["step,tag,value", "1,loss,0.5", "1,w/min,1"]);
+        assert_eq!(lines[5], "1,w/std,1");
+
+        let text = fs::read_to_string(dir.join("log.jsonl")).unwrap();
+        let first: serde_json::Value = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+        assert_eq!((first["tag"].as_str(), first["value"].as_f64(), first["step"].as_u64()), (Some("loss"), Some(0.5), Some(1)));
+        assert_eq!(text.lines().count(), 5);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_tensorboard_records_are_framed() {
+        // The CRC-32C check value.
+        assert_eq!(masked_crc32c(b"123456789").wrapping_sub(0xA282_EAD8).rotate_left(15), 0xE306_9283);
+        let dir = dir("tfevents");
+        let mut tb = TensorBoardLogger::create(&dir).unwrap();
+        tb.log_scalar("loss", 0.25, 3).unwrap();
+        tb.log_histogram("w", &[0.0, 1.0, 2.0], 3).unwrap();
+        tb.flush().unwrap();
+
+        let path = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+        assert!(path.file_name().unwrap().to_str().unwrap().starts_with("events.out.tfevents."));
+        let bytes = fs::read(&path).unwrap();
+        let mut records = Vec::new();
+        let mut at = 0;
+        while at < bytes.len() {
+            let len = u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) as usize;
+            assert_eq!(masked_crc32c(&bytes[at..at + 8]).to_le_bytes(), bytes[at + 8..at + 12]);
+            let data = &bytes[at + 12..at + 12 + len];
+            assert_eq!(masked_crc32c(data).to_le_bytes(), bytes[at + 12 + len..at + 16 + len]);
+            records.push(data.to_vec());
+            at += 16 + len;
+        }
+        assert_eq!(records.len(), 3);
+        assert!(records[0].windows(13).any(|w| w == b"brain.Event:2"));
+        // Step 3 as field 2, and the tag and value inside the summary.
+        assert!(records[1].windows(2).any(|w| w == [0x10, 3]));
+        assert!(records[1].windows(4).any(|w| w == 0.25f32.to_le_bytes()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+//! Data-parallel training across CPU cores.
+//!
+//! `DataParallel` keeps one replica of the model per worker thread, each on
+//! a graph of its own so they record without contending for a lock. A step
+//! splits the batch by rows, sends every worker the current weights and
+//! its share, and averages the gradients they send back (weighted by rows,
+//! which for a mean-reduced loss is the gradient of the whole batch)
+//! before one optimizer step on the main model.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use oxidize_ml_core::{global_rng_state, set_global_rng_state, Tensor};
+use oxidize_ml_autodiff::{Graph, Variable};
+use oxidize_ml_data::{DataLoader, Dataset};
+use oxidize_ml_nn::Layer;
+use oxidize_ml_optim::Optimizer;
+
+/// A loss that worker threads can share.
+type SharedLossFn = Arc<dyn Fn(&Variable, &Tensor<f64>) -> Variable + Send + Sync>;
+
+struct Job {
+    weights: Arc<Vec<Tensor<f64>>>,
+    x: Tensor<f64>,
+    y: Tensor<f64>,
+}
+
+struct Outcome {
+    loss: f64,
+    grads: Vec<Option<Tensor<f64>>>,
+}
+
+struct Worker {
+    jobs: Option<Sender<Job>>,
+    outcomes: Receiver<Outcome>,
+    handle: Option<JoinHandle<()>>,
+}
+
+pub struct DataParallel<M: Layer> {
+    /// The model the optimizer updates; replicas copy its weights at every
+    /// step.
+    pub model: M,
+    workers: Vec<Worker>,
+}
+
+impl<M: Layer + 'static> DataParallel<M> {
+    /// `workers` threads, each building its replica with `build`. The main
+    /// model is built on the calling thread's current graph; build the
+    /// optimizer over its `parameters()`, in order.
+    pub fn new(
+        workers: usize,
+        build: impl Fn() -> M + Send + Sync + 'static,
+        loss: impl Fn(&Variable, &Tensor<f64>) -> Variable + Send + Sync + 'static,
+    ) -> Self {
+        assert!(workers > 0, "DataParallel: need at least one worker");
+        let model = build();
+        // Replicas are overwritten before every step, so the draws their
+        // initializers take from the global generator are given back.
+        let rng = global_rng_state();
+        let build = Arc::new(build);
+        let loss: SharedLossFn = Arc::new(loss);
+        let (ready_tx, ready_rx) = channel();
+        let workers: Vec<Worker> = (0..workers)
+            .map(|i| {
+                let (jobs, inbox) = channel::<Job>();
+                let (outbox, outcomes) = channel();
+                let ready = ready_tx.clone();
+                let (build, loss) = (Arc::clone(&build), Arc::clone(&loss));
+                let handle = thread::Builder::new()
+                    .name(format!("data-parallel-{}", i))
+                    .spawn(move || {
+                        let graph = Graph::new();
+                        let mut replica = graph.scope(|| build());
+                        if ready.send(()).is_err() {
+                            return;
+                        }
+                        for job in inbox {
+                            let outcome = graph.scope(|| replica_step(&mut replica, &graph, job, loss.as_ref()));
+                            if outbox.send(outcome).is_err() {
+                                break;
+                            }
+                        }
+                    })
+                    .expect("DataParallel: failed to spawn worker");
+                Worker { jobs: Some(jobs), outcomes, handle: Some(handle) }
+            })
+            .collect();
+        drop(ready_tx);
+        for _ in 0..workers.len() {
+            ready_rx.recv().expect("DataParallel: a worker failed to build its replica");
+        }
+        set_global_rng_state(&rng);
+        DataParallel { model, workers }
+    }
+}
+
+impl<M: Layer> DataParallel<M> {
+    pub fn num_workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Forward and backward over `x` split across the workers, then one
+    /// step of `optimizer`. Returns the batch loss (the row-weighted mean
+    /// of the workers' losses).
+    pub fn train_step(&mut self, optimizer: &mut dyn Optimizer, x: &Tensor<f64>, y: &Tensor<f64>) -> f64 {
+        let rows = x.shape_vec()[0];
+        let shares = self.workers.len().min(rows).max(1);
+        let weights: Arc<Vec<Tensor<f64>>> = Arc::new(self.model.parameters().into_iter().map(|p| p.data).collect());
+
+        let mut sizes = Vec::with_capacity(shares);
+        for (w, worker) in self.workers.iter().take(shares).enumerate() {
+            let (start, end) = (w * rows / shares, (w + 1) * rows / shares);
+            let indices: Vec<usize> = (start..end).collect();
+            let job = Job {
+                weights: Arc::clone(&weights),
+                x: x.index_select(0, &indices).expect("DataParallel: split inputs"),
+                y: y.index_select(0, &indices).expect("DataParallel: split targets"),
+            };
+            worker.jobs.as_ref().expect("worker running").send(job).expect("DataParallel: worker stopped");
+            sizes.push(end - start);
+        }
+
+        let params = self.model.parameters();
+        let mut loss = 0.0;
+        let mut grads: Vec<Option<Tensor<f64>>> = vec![None; params.len()];
+        for (w, worker) in self.workers.iter().take(shares).enumerate() {
+            let outcome = worker.outcomes.recv().unwrap_or_else(|_| panic!("DataParallel: worker {} panicked", w));
+            let share = sizes[w] as f64 / rows as f64;
+            loss += share * outcome.loss;
+            for (total, grad) in grads.iter_mut().zip(outcome.grads) {
+                let Some(grad) = grad else { continue };
+                let grad = grad.mul_scalar(share);
+                match total {
+                    Some(total) => total.add_(&grad).expect("DataParallel: gradient shapes"),
+                    None => *total = Some(grad),
+                }
+            }
+        }
+
+        let grads: HashMap<_, _> = params.iter().zip(grads).filter_map(|(p, g)| g.map(|g| (p.node_id, g))).collect();
+        let updated = optimizer.step(&grads);
+        let mut named = self.model.named_parameters_mut();
+        assert_eq!(
+            updated.len(),
+            named.len(),
+            "DataParallel: the optimizer must cover model.parameters(), in order, and the model must implement named_parameters_mut"
+        );
+        for ((_, p), value) in named.iter_mut().zip(updated) {
+            p.set_data(value);
+        }
+        loss
+    }
+
+    /// One pass over `loader`; returns the mean batch loss, weighted by
+    /// batch size.
+    pub fn fit_epoch<D: Dataset>(&mut self, optimizer: &mut dyn Optimizer, loader: &mut DataLoader<'_, D>) -> f64 {
+        self.model.set_training(true);
+        loader.reset();
+        let (mut total, mut rows) = (0.0, 0);
+        for (x, y) in &mut *loader {
+            let n = x.shape_vec()[0];
+            total += n as f64 * self.train_step(optimizer, &x, &y);
+            rows += n;
+        }
+        total / rows.max(1) as f64
+    }
+}
+
+/// Load the main model's weights into `replica`, run forward and backward
+/// on one share, and hand back the loss and gradients (in `parameters()`
+/// order), leaving the replica's graph empty for the next step.
+fn replica_step<M: Layer>(
+    replica: &mut M,
+    graph: &Graph,
+    job: Job,
+    loss: &(dyn Fn(&Variable, &Tensor<f64>) -> Variable + Send + Sync),
+) -> Outcome {
+    for ((_, p), w) in replica.named_parameters_mut().iter_mut().zip(job.weights.iter()) {
+        p.set_data(w.clone());
+    }
+    let out = replica.forward(&graph.input(job.x));
+    let value = loss(&out, &job.y);
+    value.backward();
+    let grads = replica.parameters().iter().map(|p| p.grad()).collect();
+    graph.zero_grad();
+    let loss = value.data.item().expect("scalar loss");
+    graph.clear();
+    Outcome { loss, grads }
+}
+
+impl<M: Layer> Drop for DataParallel<M> {
+    fn drop(&mut self) {
+        for worker in &mut self.workers {
+            // Closing the channel ends the worker's loop.
+            worker.jobs.take();
+            if let Some(handle) = worker.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::set_global_seed;
+    use oxidize_ml_loss::mse_loss;
+    use oxidize_ml_nn::{Linear, ReLULayer, Sequential};
+    use oxidize_ml_optim::SGD;
+
+    fn mlp() -> Sequential {
+        Sequential::new().add(Box::new(Linear::new(3, 5))).add(Box::new(ReLULayer)).add(Box::new(Linear::new(5, 1)))
+    }
+
+    fn sgd(model: &Sequential) -> SGD {
+        let params = model.parameters();
+        SGD::new(params.iter().map(|p| p.node_id).collect(), params.iter().map(|p| p.data.clone()).collect(), 0.1, 0.0)
+    }
+
+    fn mse(out: &Variable, y: &Tensor<f64>) -> Variable {
+        mse_loss(out, &out.constant(y.reshape(vec![y.numel(), 1]).unwrap()))
+    }
+
+    #[test]
+    fn test_data_parallel_step_matches_single_model() {
+        set_global_seed(4);
+        let x = Tensor::randn(vec![10, 3], Some(1));
+        let y = Tensor::randn(vec![10], Some(2));
+        let mut parallel = DataParallel::new(3, mlp, mse);
+        assert_eq!(parallel.num_workers(), 3);
+        let mut single = mlp();
+        single.load_state_dict(&parallel.model.state_dict(), true).unwrap();
+        let (mut opt_parallel, mut opt_single) = (sgd(&parallel.model), sgd(&single));
+
+        for _ in 0..3 {
+            let loss = parallel.train_step(&mut opt_parallel, &x, &y);
+            let graph = single.parameters()[0].graph.clone();
+            let out = single.forward(&graph.input(x.clone()));
+            let expected = mse(&out, &y);
+            expected.backward();
+            let updated = opt_single.step_params(&single.parameters());
+            graph.zero_grad();
+            for ((_, p), value) in single.named_parameters_mut().iter_mut().zip(updated) {
+                p.set_data(value);
+            }
+            assert!((loss - expected.data.item().unwrap()).abs() < 1e-12);
+            graph.clear();
+        }
+        for ((name, a), (_, b)) in parallel.model.named_parameters().iter().zip(single.named_parameters()) {
+            let diff = a.data.data().iter().zip(b.data.data()).fold(0.0f64, |m, (p, q)| m.max((p - q).abs()));
+            assert!(diff < 1e-12, "{} differs by {}", name, diff);
+        }
+    }
+}
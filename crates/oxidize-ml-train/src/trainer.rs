@@ -0,0 +1,323 @@
+//! The epoch / batch / backward / step loop behind one type.
+//!
+//! A `Trainer` owns a model, an optimizer built over `model.parameters()`
+//! (in that order) and a loss on the model's outputs and the batch targets.
+//! `fit` runs the loop over a `DataLoader`, with optional validation after
+//! every epoch, and hands control to its callbacks at each stage.
+//! `checkpoint` and `resume` save and restore a run between epochs.
+
+use std::time::Instant;
+use oxidize_ml_core::{Tensor, TensorError};
+use oxidize_ml_core::error::TensorResult;
+use oxidize_ml_autodiff::{Graph, Variable};
+use oxidize_ml_data::{DataLoader, Dataset};
+use oxidize_ml_nn::Layer;
+use oxidize_ml_optim::Optimizer;
+
+use crate::callbacks::{Callback, History, Logs, TrainContext};
+use crate::checkpoint::Checkpoint;
+
+/// Loss on a batch: model outputs and the loader's targets.
+pub type LossFn = Box<dyn Fn(&Variable, &Tensor<f64>) -> Variable>;
+
+/// Metric on a batch: output values and targets.
+pub type MetricFn = Box<dyn Fn(&Tensor<f64>, &Tensor<f64>) -> f64>;
+
+pub struct Trainer<M: Layer, O: Optimizer> {
+    pub model: M,
+    pub optimizer: O,
+    /// Total epochs of the run.
+    pub epochs: usize,
+    /// Print a line per epoch with its logs.
+    pub verbose: bool,
+    /// Epochs completed; `fit` continues from here.
+    pub epoch: usize,
+    pub global_step: usize,
+    pub history: History,
+    loss: LossFn,
+    metrics: Vec<(String, MetricFn)>,
+    callbacks: Vec<Box<dyn Callback>>,
+}
+
+impl<M: Layer, O: Optimizer> Trainer<M, O> {
+    pub fn new(model: M, optimizer: O, loss: impl Fn(&Variable, &Tensor<f64>) -> Variable + 'static) -> Self {
+        Trainer {
+            model,
+            optimizer,
+            epochs: 1,
+            verbose: false,
+            epoch: 0,
+            global_step: 0,
+            history: History::default(),
+            loss: Box::new(loss),
+            metrics: Vec::new(),
+            callbacks: Vec::new(),
+        }
+    }
+
+    pub fn with_epochs(mut self, epochs: usize) -> Self {
+        self.epochs = epochs;
+        self
+    }
+
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Also log `name`, averaged over the batches of each epoch (weighted
+    /// by batch size).
+    pub fn with_metric(mut self, name: &str, metric: impl Fn(&Tensor<f64>, &Tensor<f64>) -> f64 + 'static) -> Self {
+        self.metrics.push((name.to_string(), Box::new(metric)));
+        self
+    }
+
+    pub fn with_callback(mut self, callback: impl Callback + 'static) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Train until `epochs` epochs are complete.
+    pub fn fit<D: Dataset>(&mut self, train: &mut DataLoader<'_, D>) -> &History {
+        self.run::<D, D>(train, None)
+    }
+
+    /// Train until `epochs` epochs are complete, evaluating on `val` after
+    /// each; its results are logged with a `val_` prefix.
+    pub fn fit_with_validation<D: Dataset, V: Dataset>(
+        &mut self,
+        train: &mut DataLoader<'_, D>,
+        val: &mut DataLoader<'_, V>,
+    ) -> &History {
+        self.run(train, Some(val))
+    }
+
+    /// Loss and metrics over `loader` in eval mode, without recording a
+    /// graph. Leaves the model in training mode.
+    pub fn evaluate<D: Dataset>(&mut self, loader: &mut DataLoader<'_, D>) -> Logs {
+        loader.reset();
+        let graph = self.graph();
+        self.model.set_training(false);
+        let mut totals = Totals::new(self.metrics.len());
+        graph.inference_mode(|| {
+            for (x, y) in loader {
+                let out = self.model.forward(&graph.input(x));
+                let loss = (self.loss)(&out, &y).data.item().expect("scalar loss");
+                totals.add(loss, &out.data, &y, &self.metrics);
+            }
+        });
+        self.model.set_training(true);
+        totals.logs(&self.metrics)
+    }
+
+    /// Model outputs over `loader`, concatenated along the batch axis.
+    pub fn predict<D: Dataset>(&mut self, loader: &mut DataLoader<'_, D>) -> Tensor<f64> {
+        loader.reset();
+        let graph = self.graph();
+        self.model.set_training(false);
+        let outputs: Vec<Tensor<f64>> =
+            graph.inference_mode(|| loader.map(|(x, _)| self.model.forward(&graph.input(x)).data).collect());
+        self.model.set_training(true);
+        Tensor::concatenate(&outputs.iter().collect::<Vec<_>>(), 0).expect("predict: no batches")
+    }
+
+    fn run<D: Dataset, V: Dataset>(
+        &mut self,
+        train: &mut DataLoader<'_, D>,
+        mut val: Option<&mut DataLoader<'_, V>>,
+    ) -> &History {
+        let mut callbacks = std::mem::take(&mut self.callbacks);
+        self.notify(&mut callbacks, |cb, ctx| cb.on_train_begin(ctx));
+        let graph = self.graph();
+        let end = self.epochs;
+        while self.epoch < end {
+            let started = Instant::now();
+            self.model.set_training(true);
+            let mut stop = self.notify(&mut callbacks, |cb, ctx| cb.on_epoch_begin(ctx));
+
+            train.reset();
+            let mut totals = Totals::new(self.metrics.len());
+            for (x, y) in &mut *train {
+                let out = self.model.forward(&graph.input(x));
+                let loss = (self.loss)(&out, &y);
+                loss.backward();
+                self.apply_step();
+                graph.zero_grad();
+                let loss = loss.data.item().expect("scalar loss");
+                totals.add(loss, &out.data, &y, &self.metrics);
+                graph.clear();
+                self.global_step += 1;
+                stop |= self.notify(&mut callbacks, |cb, ctx| cb.on_batch_end(ctx, loss));
+            }
+
+            let mut logs = totals.logs(&self.metrics);
+            if let Some(val) = val.as_deref_mut() {
+                for (name, value) in self.evaluate(val) {
+                    logs.insert(format!("val_{}", name), value);
+                }
+            }
+            logs.insert("lr".to_string(), self.optimizer.lr());
+            if self.verbose {
+                let values: Vec<String> = logs.iter().map(|(k, v)| format!("{}: {:.4}", k, v)).collect();
+                println!(
+                    "epoch {}/{} - {} - {:.2}s",
+                    self.epoch + 1,
+                    end,
+                    values.join(" - "),
+                    started.elapsed().as_secs_f64()
+                );
+            }
+            stop |= self.notify(&mut callbacks, |cb, ctx| cb.on_epoch_end(ctx, &logs));
+            self.history.epochs.push(logs);
+            self.epoch += 1;
+            if stop {
+                break;
+            }
+        }
+        self.notify(&mut callbacks, |cb, ctx| cb.on_train_end(ctx));
+        self.callbacks = callbacks;
+        &self.history
+    }
+
+    /// Snapshot of the run after the epochs completed so far, including the
+    /// history and every callback's state.
+    pub fn checkpoint(&self) -> Checkpoint {
+        let mut checkpoint = Checkpoint::capture(&self.model, &self.optimizer, self.epoch).with_global_step(self.global_step);
+        checkpoint.callbacks = self.callbacks.iter().map(|cb| cb.state()).collect();
+        checkpoint.history = self.history.clone();
+        checkpoint
+    }
+
+    /// Continue the run saved in `checkpoint`; the trainer must have the
+    /// same model, optimizer and callbacks as the one that saved it.
+    pub fn resume(&mut self, checkpoint: &Checkpoint) -> TensorResult<()> {
+        if checkpoint.callbacks.len() != self.callbacks.len() {
+            return Err(TensorError::InvalidOperation(format!(
+                "checkpoint has state for {} callbacks, the trainer has {}",
+                checkpoint.callbacks.len(),
+                self.callbacks.len()
+            )));
+        }
+        checkpoint.restore(&mut self.model, &mut self.optimizer)?;
+        for (callback, state) in self.callbacks.iter_mut().zip(&checkpoint.callbacks) {
+            callback.load_state(state)?;
+        }
+        self.epoch = checkpoint.epoch;
+        self.global_step = checkpoint.global_step;
+        self.history = checkpoint.history.clone();
+        Ok(())
+    }
+
+    /// One optimizer step from the accumulated gradients, written back
+    /// into the model.
+    fn apply_step(&mut self) {
+        let updated = self.optimizer.step_params(&self.model.parameters());
+        let mut params = self.model.named_parameters_mut();
+        assert_eq!(
+            updated.len(),
+            params.len(),
+            "Trainer: the optimizer must cover model.parameters(), in order, and the model must implement named_parameters_mut"
+        );
+        for ((_, p), value) in params.iter_mut().zip(updated) {
+            p.set_data(value);
+        }
+    }
+
+    /// The graph the model's parameters live on.
+    fn graph(&self) -> Graph {
+        self.model.parameters().first().map_or_else(Graph::current, |p| p.graph.clone())
+    }
+
+    /// Run one hook on every callback; true if any asked to stop.
+    fn notify(&mut self, callbacks: &mut [Box<dyn Callback>], mut hook: impl FnMut(&mut dyn Callback, &mut TrainContext)) -> bool {
+        let mut ctx = TrainContext {
+            model: &mut self.model,
+            optimizer: &mut self.optimizer,
+            epoch: self.epoch,
+            global_step: self.global_step,
+            stop_training: false,
+        };
+        for callback in callbacks.iter_mut() {
+            hook(callback.as_mut(), &mut ctx);
+        }
+        ctx.stop_training
+    }
+}
+
+/// Batch-size-weighted sums of the loss and metrics over an epoch.
+struct Totals {
+    loss: f64,
+    metrics: Vec<f64>,
+    samples: usize,
+}
+
+impl Totals {
+    fn new(metrics: usize) -> Self {
+        Totals { loss: 0.0, metrics: vec![0.0; metrics], samples: 0 }
+    }
+
+    fn add(&mut self, loss: f64, out: &Tensor<f64>, y: &Tensor<f64>, metrics: &[(String, MetricFn)]) {
+        let n = y.shape_vec().first().copied().unwrap_or(1);
+        self.loss += loss * n as f64;
+        for (total, (_, metric)) in self.metrics.iter_mut().zip(metrics) {
+            *total += metric(out, y) * n as f64;
+        }
+        self.samples += n;
+    }
+
+    fn logs(&self, metrics: &[(String, MetricFn)]) -> Logs {
+        let n = self.samples.max(1) as f64;
+        let mut logs = Logs::new();
+        logs.insert("loss".to_string(), self.loss / n);
+        for (total, (name, _)) in self.metrics.iter().zip(metrics) {
+            logs.insert(name.clone(), total / n);
+        }
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callbacks::LearningRateScheduler;
+    use oxidize_ml_data::TensorDataset;
+    use oxidize_ml_loss::cross_entropy;
+    use oxidize_ml_metrics::accuracy;
+    use oxidize_ml_nn::{Linear, ReLULayer, Sequential};
+    use oxidize_ml_optim::{Adam, StepLR};
+
+    /// Points on a grid, labelled by which side of `x0 + x1 = 0` they fall.
+    fn halves() -> TensorDataset {
+        let points: Vec<[f64; 2]> =
+            (0..64).map(|k| [(k % 8) as f64 / 3.5 - 1.0, (k / 8) as f64 / 3.5 - 1.0]).collect();
+        let labels = points.iter().map(|p| if p[0] + p[1] > 0.0 { 1.0 } else { 0.0 }).collect();
+        let features = Tensor::new(points.iter().flatten().copied().collect(), vec![64, 2]).unwrap();
+        TensorDataset::new(features, Tensor::new(labels, vec![64]).unwrap())
+    }
+
+    #[test]
+    fn test_fit_evaluate_and_predict() {
+        let model: Sequential =
+            Sequential::new().add(Box::new(Linear::new(2, 8))).add(Box::new(ReLULayer)).add(Box::new(Linear::new(8, 2)));
+        let params = model.parameters();
+        let adam = Adam::new(params.iter().map(|p| p.node_id).collect(), params.iter().map(|p| p.data.clone()).collect(), 0.05);
+        let data = halves();
+        let (mut train, mut val) = (DataLoader::new(&data, 16, true), DataLoader::new(&data, 64, false));
+        let mut trainer = Trainer::new(model, adam, cross_entropy)
+            .with_epochs(30)
+            .with_metric("accuracy", |out, y| accuracy(y, &out.argmax_axis(1).unwrap()))
+            .with_callback(LearningRateScheduler::new(StepLR::new(0.05, 20, 0.1)));
+
+        let history = trainer.fit_with_validation(&mut train, &mut val);
+        assert_eq!(history.len(), 30);
+        let val_loss = history.get("val_loss");
+        assert!(val_loss[29] < val_loss[0] / 2.0);
+        assert!(history.get("val_accuracy")[29] > 0.9);
+        assert!((history.get("lr")[25] - 0.005).abs() < 1e-12);
+        assert_eq!(trainer.global_step, 30 * 4);
+
+        let logs = trainer.evaluate(&mut val);
+        assert!((logs["loss"] - val_loss[29]).abs() < 1e-12);
+        assert_eq!(trainer.predict(&mut val).shape_vec(), vec![64, 2]);
+    }
+}
@@ -1,28 +1,347 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{sample_weights, ClassWeight, Float, RngHandle, Tensor, TensorError};
+use rand::seq::SliceRandom;
+use rand::Rng;
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
 
 /// A node in the decision tree.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 enum TreeNode<T: Float> {
-    /// Internal node: splits on feature `feature_idx` at `threshold`.
+    /// Internal node: splits on feature `feature_idx` at `threshold`, or by
+    /// category membership when `categories` is set.
     Split {
         feature_idx: usize,
         threshold: T,
+        /// Categories sent left by a categorical split.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        categories: Option<Vec<T>>,
+        /// Whether rows missing the feature (NaN) go left.
+        #[serde(default)]
+        missing_left: bool,
         left: Box<TreeNode<T>>,
         right: Box<TreeNode<T>>,
+        /// What the node would predict as a leaf; kept for pruning.
+        #[serde(default)]
+        value: T,
+        #[serde(default)]
+        impurity: T,
+        #[serde(default)]
+        weight: T,
     },
     /// Leaf: predicts a class label or regression value.
+    Leaf {
+        value: T,
+        /// Impurity of the training rows reaching the node.
+        #[serde(default)]
+        impurity: T,
+        /// Total sample weight of those rows.
+        #[serde(default)]
+        weight: T,
+    },
+}
+
+impl<T: Float> TreeNode<T> {
+    /// Weighted impurity of the node on its own, `impurity * weight`.
+    fn cost(&self) -> f64 {
+        match self {
+            TreeNode::Split { impurity, weight, .. } | TreeNode::Leaf { impurity, weight, .. } => {
+                impurity.to_f64() * weight.to_f64()
+            }
+        }
+    }
+
+    /// Cost summed over the leaves below the node, and the number of leaves.
+    fn subtree_cost(&self) -> (f64, usize) {
+        match self {
+            TreeNode::Leaf { .. } => (self.cost(), 1),
+            TreeNode::Split { left, right, .. } => {
+                let (lc, ln) = left.subtree_cost();
+                let (rc, rn) = right.subtree_cost();
+                (lc + rc, ln + rn)
+            }
+        }
+    }
+
+    fn weight(&self) -> f64 {
+        match self {
+            TreeNode::Split { weight, .. } | TreeNode::Leaf { weight, .. } => weight.to_f64(),
+        }
+    }
+}
+
+/// A node of a fitted tree in flattened form. Nodes are in pre-order, so
+/// node 0 is the root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", bound = "T: Float")]
+pub enum FlatNode<T: Float> {
+    /// Rows with `x[feature] <= threshold` go to `left`, the rest to `right`.
+    /// Rows where `x[feature]` is NaN go left if `missing_left`.
+    Split { feature: usize, threshold: T, left: usize, right: usize, missing_left: bool },
+    /// Rows whose `x[feature]` is one of `categories` go to `left`, the rest
+    /// (including categories unseen in training) to `right`. NaN is handled
+    /// as in `Split`.
+    Categorical { feature: usize, categories: Vec<T>, left: usize, right: usize, missing_left: bool },
     Leaf { value: T },
 }
 
+/// Whether a row with value `v` goes to the left child of a split.
+fn goes_left<T: Float>(v: T, threshold: T, categories: &Option<Vec<T>>, missing_left: bool) -> bool {
+    if v.is_nan() {
+        return missing_left;
+    }
+    match categories {
+        Some(cats) => cats.contains(&v),
+        None => v <= threshold,
+    }
+}
+
+/// Split `indices` into the rows going left and right.
+fn partition<T: Float>(
+    x: &Tensor<T>,
+    feature: usize,
+    indices: &[usize],
+    threshold: T,
+    categories: &Option<Vec<T>>,
+    missing_left: bool,
+) -> (Vec<usize>, Vec<usize>) {
+    indices
+        .iter()
+        .partition(|&&i| goes_left(x.get(&[i, feature]).unwrap(), threshold, categories, missing_left))
+}
+
+/// Missing-value directions to try for a split: both when some rows miss
+/// the feature, else none (`None`; the direction is set afterwards).
+fn missing_directions(has_missing: bool) -> &'static [Option<bool>] {
+    if has_missing {
+        &[Some(false), Some(true)]
+    } else {
+        &[None]
+    }
+}
+
+/// Categorical features with at most this many categories in a node have
+/// every partition tried (multi-class classification only).
+const MAX_EXHAUSTIVE_CATEGORIES: usize = 8;
+
+/// Weighted mean of `target` over the rows of each category.
+fn category_means<T: Float>(
+    x: &Tensor<T>,
+    feature: usize,
+    indices: &[usize],
+    w: &[T],
+    categories: &[T],
+    target: impl Fn(usize) -> f64,
+) -> Vec<f64> {
+    let mut sums = vec![(0.0, 0.0); categories.len()];
+    for &i in indices {
+        let v = x.get(&[i, feature]).unwrap();
+        if let Some(k) = categories.iter().position(|&c| c == v) {
+            sums[k].0 += w[i].to_f64() * target(i);
+            sums[k].1 += w[i].to_f64();
+        }
+    }
+    sums.iter().map(|&(s, t)| if t > 0.0 { s / t } else { 0.0 }).collect()
+}
+
+/// Candidate left-child category sets for a node's distinct `categories`:
+/// every proper subset containing the first category when `exhaustive`,
+/// otherwise each prefix of the categories ordered by `scores` (optimal for
+/// regression and binary classification). The random splitter keeps one.
+fn category_subsets<T: Float>(
+    categories: &[T],
+    scores: &[f64],
+    exhaustive: bool,
+    splitter: Splitter,
+    rng: &mut Option<RngHandle>,
+) -> Vec<Vec<T>> {
+    let c = categories.len();
+    if c < 2 {
+        return Vec::new();
+    }
+    let mut subsets: Vec<Vec<T>> = if exhaustive {
+        (0..(1usize << (c - 1)) - 1)
+            .map(|mask| {
+                let mut set = vec![categories[0]];
+                set.extend((1..c).filter(|j| mask >> (j - 1) & 1 == 1).map(|j| categories[j]));
+                set
+            })
+            .collect()
+    } else {
+        let mut order: Vec<usize> = (0..c).collect();
+        order.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+        (1..c).map(|k| order[..k].iter().map(|&j| categories[j]).collect()).collect()
+    };
+    if let (Splitter::Random, Some(rng)) = (splitter, rng.as_mut()) {
+        let pick = rng.gen_range(0..subsets.len());
+        subsets = vec![subsets.swap_remove(pick)];
+    }
+    for set in &mut subsets {
+        set.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    }
+    subsets
+}
+
+/// How a node's split threshold is chosen for each candidate feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Splitter {
+    /// Try every midpoint between consecutive distinct values (CART).
+    Best,
+    /// Try one threshold drawn uniformly between the feature's min and max
+    /// in the node (extremely randomized trees).
+    Random,
+}
+
+/// Features to try at a node: all of them, or `max_features` drawn at random.
+fn candidate_features(p: usize, max_features: Option<usize>, rng: &mut Option<RngHandle>) -> Vec<usize> {
+    let mut features: Vec<usize> = (0..p).collect();
+    if let (Some(k), Some(rng)) = (max_features, rng.as_mut()) {
+        if k < p {
+            features.shuffle(rng);
+            features.truncate(k.max(1));
+        }
+    }
+    features
+}
+
+/// Thresholds to try for a feature with sorted distinct `values` in the node.
+fn candidate_thresholds<T: Float>(values: &[T], splitter: Splitter, rng: &mut Option<RngHandle>) -> Vec<T> {
+    if values.len() < 2 {
+        return Vec::new();
+    }
+    match (splitter, rng.as_mut()) {
+        (Splitter::Random, Some(rng)) => {
+            let (lo, hi) = (values[0], values[values.len() - 1]);
+            vec![lo + T::from_f64(rng.gen::<f64>()) * (hi - lo)]
+        }
+        _ => values.windows(2).map(|pair| (pair[0] + pair[1]) / T::TWO).collect(),
+    }
+}
+
+/// The generator a fit needs, if any: only random splits and per-node
+/// feature sampling draw from it.
+fn fit_rng(splitter: Splitter, max_features: Option<usize>, seed: Option<u64>) -> Option<RngHandle> {
+    (splitter == Splitter::Random || max_features.is_some()).then(|| RngHandle::new(seed))
+}
+
+/// Effective alphas of a tree's minimal cost-complexity pruning sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PruningPath {
+    /// Increasing; entry `i` is the smallest `ccp_alpha` that prunes the
+    /// tree to subtree `i`. Starts at 0 (the full tree) and ends with the
+    /// alpha that collapses it to the root.
+    pub ccp_alphas: Vec<f64>,
+    /// Total leaf impurity of each subtree, weighted by the share of
+    /// training weight in each leaf.
+    pub impurities: Vec<f64>,
+}
+
+/// Find the internal node with the smallest effective alpha, the increase
+/// in cost per leaf removed by collapsing it. Ties keep the first in pre-order.
+fn weakest_link<T: Float>(node: &TreeNode<T>, next: &mut usize, best: &mut Option<(f64, usize)>) {
+    let id = *next;
+    *next += 1;
+    if let TreeNode::Split { left, right, .. } = node {
+        let (cost, leaves) = node.subtree_cost();
+        let alpha = (node.cost() - cost) / (leaves - 1) as f64;
+        if best.is_none_or(|(a, _)| alpha < a) {
+            *best = Some((alpha, id));
+        }
+        weakest_link(left, next, best);
+        weakest_link(right, next, best);
+    }
+}
+
+/// Turn the node with pre-order index `target` into a leaf.
+fn collapse<T: Float>(node: &mut TreeNode<T>, target: usize, next: &mut usize) -> bool {
+    let id = *next;
+    *next += 1;
+    if id == target {
+        if let TreeNode::Split { value, impurity, weight, .. } = *node {
+            *node = TreeNode::Leaf { value, impurity, weight };
+        }
+        return true;
+    }
+    match node {
+        TreeNode::Split { left, right, .. } => collapse(left, target, next) || collapse(right, target, next),
+        TreeNode::Leaf { .. } => false,
+    }
+}
+
+/// Minimal cost-complexity pruning: collapse the weakest link until its
+/// effective alpha exceeds `ccp_alpha`, recording each step.
+fn prune<T: Float>(root: &mut TreeNode<T>, ccp_alpha: f64) -> PruningPath {
+    // Costs are normalized by the root's weight, so alphas do not scale
+    // with the number of rows.
+    let total = if root.weight() > 0.0 { root.weight() } else { 1.0 };
+    let mut path = PruningPath { ccp_alphas: vec![0.0], impurities: vec![root.subtree_cost().0 / total] };
+    loop {
+        let mut best = None;
+        weakest_link(root, &mut 0, &mut best);
+        let Some((alpha, id)) = best else { break };
+        let alpha = (alpha / total).max(0.0);
+        if alpha > ccp_alpha {
+            break;
+        }
+        collapse(root, id, &mut 0);
+        path.ccp_alphas.push(alpha);
+        path.impurities.push(root.subtree_cost().0 / total);
+    }
+    path
+}
+
+fn flatten<T: Float>(node: &TreeNode<T>, out: &mut Vec<FlatNode<T>>) -> usize {
+    let id = out.len();
+    match node {
+        TreeNode::Leaf { value, .. } => out.push(FlatNode::Leaf { value: *value }),
+        TreeNode::Split { feature_idx, threshold, categories, missing_left, left, right, .. } => {
+            // Reserve the slot, then patch in the children's ids.
+            out.push(FlatNode::Leaf { value: T::ZERO });
+            let l = flatten(left, out);
+            let r = flatten(right, out);
+            let (feature, missing_left) = (*feature_idx, *missing_left);
+            out[id] = match categories {
+                Some(cats) => FlatNode::Categorical { feature, categories: cats.clone(), left: l, right: r, missing_left },
+                None => FlatNode::Split { feature, threshold: *threshold, left: l, right: r, missing_left },
+            };
+        }
+    }
+    id
+}
+
+fn flat_nodes<T: Float>(tree: &Option<TreeNode<T>>) -> Vec<FlatNode<T>> {
+    let mut out = Vec::new();
+    if let Some(root) = tree {
+        flatten(root, &mut out);
+    }
+    out
+}
+
 /// Decision Tree Classifier using CART algorithm (Gini impurity).
+///
+/// Features may contain NaN. Each split sends missing values to the child
+/// that gives the lower impurity, or to the larger child if no training row
+/// at the node was missing the feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct DecisionTreeClassifier<T: Float> {
     pub max_depth: usize,
     pub min_samples_split: usize,
     pub min_samples_leaf: usize,
     tree: Option<TreeNode<T>>,
     pub n_classes: usize,
+    pub class_weight: Option<ClassWeight>,
+    pub splitter: Splitter,
+    /// Features tried at each node, drawn at random; `None` tries all.
+    pub max_features: Option<usize>,
+    /// Seed for random splits and feature sampling.
+    pub seed: Option<u64>,
+    /// Complexity penalty per leaf for post-pruning; 0 keeps the full tree.
+    pub ccp_alpha: f64,
+    /// Columns holding category codes, split by set membership instead of
+    /// by threshold.
+    pub categorical_features: Vec<usize>,
 }
 
 impl<T: Float> DecisionTreeClassifier<T>
@@ -36,19 +355,79 @@ where
             min_samples_leaf,
             tree: None,
             n_classes: 0,
+            class_weight: None,
+            splitter: Splitter::Best,
+            max_features: None,
+            seed: None,
+            ccp_alpha: 0.0,
+            categorical_features: Vec::new(),
         }
     }
 
+    /// Choose thresholds with `splitter`, seeding its random draws.
+    pub fn with_splitter(mut self, splitter: Splitter, seed: Option<u64>) -> Self {
+        self.splitter = splitter;
+        self.seed = seed;
+        self
+    }
+
+    /// Try only `max_features` randomly drawn features at each node.
+    pub fn with_max_features(mut self, max_features: usize) -> Self {
+        self.max_features = Some(max_features);
+        self
+    }
+
+    /// Treat the given columns as categorical: their values are category
+    /// codes, and splits send a subset of the categories left.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
+    /// Post-prune the fitted tree to the subtree minimizing
+    /// `impurity + ccp_alpha * n_leaves`.
+    pub fn with_ccp_alpha(mut self, ccp_alpha: f64) -> Self {
+        self.ccp_alpha = ccp_alpha;
+        self
+    }
+
+    /// Fit a full tree (ignoring `ccp_alpha`) and return its pruning path;
+    /// its alphas are the useful values of `ccp_alpha`.
+    pub fn cost_complexity_pruning_path(&self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<PruningPath> {
+        let mut full = self.clone().with_ccp_alpha(0.0);
+        full.fit(x, y)?;
+        let root = full.tree.as_mut().ok_or_else(|| TensorError::InvalidOperation("empty tree".into()))?;
+        Ok(prune(root, f64::INFINITY))
+    }
+
+    /// Reweight classes in the impurity, e.g. `ClassWeight::Balanced` for
+    /// imbalanced targets.
+    pub fn with_class_weight(mut self, class_weight: ClassWeight) -> Self {
+        self.class_weight = Some(class_weight);
+        self
+    }
+
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.fit_weighted(x, y, None)
+    }
+
+    /// Fit with per-row weights (combined with `class_weight`): impurities
+    /// and leaf votes use weighted class counts. `min_samples_*` still count rows.
+    pub fn fit_weighted(&mut self, x: &Tensor<T>, y: &Tensor<T>, sample_weight: Option<&Tensor<T>>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
-        let p = x.shape().dim(1)?;
+        let weights = sample_weights(y, self.class_weight.as_ref(), sample_weight)?;
 
         // Determine number of classes
         let max_label = y.data().iter().map(|v| v.to_f64().round() as usize).max().unwrap_or(0);
         self.n_classes = max_label + 1;
 
         let indices: Vec<usize> = (0..n).collect();
-        self.tree = Some(self.build_tree(x, y, &indices, p, 0)?);
+        let mut rng = fit_rng(self.splitter, self.max_features, self.seed);
+        let mut root = self.build_tree(x, y, &weights, &indices, 0, &mut rng)?;
+        if self.ccp_alpha > 0.0 {
+            prune(&mut root, self.ccp_alpha);
+        }
+        self.tree = Some(root);
         Ok(())
     }
 
@@ -56,117 +435,141 @@ where
         &self,
         x: &Tensor<T>,
         y: &Tensor<T>,
+        w: &[T],
         indices: &[usize],
-        n_features: usize,
         depth: usize,
+        rng: &mut Option<RngHandle>,
     ) -> TensorResult<TreeNode<T>> {
+        let value = self.majority_class(y, w, indices);
+        let (impurity, weight) = self.gini_impurity(y, w, indices);
+
         // Base cases
         if depth >= self.max_depth || indices.len() < self.min_samples_split || indices.len() < 2 {
-            return Ok(TreeNode::Leaf {
-                value: self.majority_class(y, indices),
-            });
+            return Ok(TreeNode::Leaf { value, impurity, weight });
         }
 
         // Check if all same class
         let first_class = y.data()[indices[0]];
         if indices.iter().all(|&i| (y.data()[i] - first_class).abs() < T::EPSILON) {
-            return Ok(TreeNode::Leaf { value: first_class });
+            return Ok(TreeNode::Leaf { value: first_class, impurity, weight });
         }
 
         // Find best split
         let mut best_gini = T::from_f64(f64::MAX);
         let mut best_feature = 0;
         let mut best_threshold = T::ZERO;
+        let mut best_categories = None;
+        let mut best_missing = None;
         let mut best_left = Vec::new();
         let mut best_right = Vec::new();
 
-        for feature in 0..n_features {
-            // Get unique sorted values for this feature
-            let mut values: Vec<T> = indices.iter().map(|&i| x.get(&[i, feature]).unwrap()).collect();
-            values.sort_by(|a, b| a.to_f64().partial_cmp(&b.to_f64()).unwrap());
-            values.dedup();
-
-            for w in values.windows(2) {
-                let threshold = (w[0] + w[1]) / T::TWO;
-
-                let mut left = Vec::new();
-                let mut right = Vec::new();
-                for &i in indices {
-                    if x.get(&[i, feature]).unwrap() <= threshold {
-                        left.push(i);
-                    } else {
-                        right.push(i);
+        for feature in candidate_features(x.shape().dim(1)?, self.max_features, rng) {
+            // Get unique sorted values for this feature, ignoring NaN
+            let values: Vec<T> = indices.iter().map(|&i| x.get(&[i, feature]).unwrap()).filter(|v| !v.is_nan()).collect();
+            let has_missing = values.len() < indices.len();
+            let values = Tensor::from_slice(&values).unique();
+
+            let splits: Vec<(T, Option<Vec<T>>)> = if self.categorical_features.contains(&feature) {
+                // Order categories by their share of the node's majority class.
+                let scores = category_means(x, feature, indices, w, values.data(), |i| (y.data()[i] == value) as u8 as f64);
+                let exhaustive = self.n_classes > 2 && values.numel() <= MAX_EXHAUSTIVE_CATEGORIES;
+                category_subsets(values.data(), &scores, exhaustive, self.splitter, rng)
+                    .into_iter()
+                    .map(|set| (T::ZERO, Some(set)))
+                    .collect()
+            } else {
+                candidate_thresholds(values.data(), self.splitter, rng).into_iter().map(|t| (t, None)).collect()
+            };
+
+            for (threshold, categories) in splits {
+                // Rows missing the feature go to whichever child scores better.
+                for &missing_left in missing_directions(has_missing) {
+                    let (left, right) = partition(x, feature, indices, threshold, &categories, missing_left.unwrap_or(false));
+                    if left.len() < self.min_samples_leaf || right.len() < self.min_samples_leaf {
+                        continue;
                     }
-                }
 
-                if left.len() < self.min_samples_leaf || right.len() < self.min_samples_leaf {
-                    continue;
-                }
-
-                let gini = self.weighted_gini(y, &left, &right, indices.len());
-                if gini < best_gini {
-                    best_gini = gini;
-                    best_feature = feature;
-                    best_threshold = threshold;
-                    best_left = left;
-                    best_right = right;
+                    let gini = self.weighted_gini(y, w, &left, &right);
+                    if gini < best_gini {
+                        best_gini = gini;
+                        best_feature = feature;
+                        best_threshold = threshold;
+                        best_categories = categories.clone();
+                        best_missing = missing_left;
+                        best_left = left;
+                        best_right = right;
+                    }
                 }
             }
         }
 
         if best_left.is_empty() || best_right.is_empty() {
-            return Ok(TreeNode::Leaf {
-                value: self.majority_class(y, indices),
-            });
+            return Ok(TreeNode::Leaf { value, impurity, weight });
         }
 
-        let left_node = self.build_tree(x, y, &best_left, n_features, depth + 1)?;
-        let right_node = self.build_tree(x, y, &best_right, n_features, depth + 1)?;
+        let left_node = self.build_tree(x, y, w, &best_left, depth + 1, rng)?;
+        let right_node = self.build_tree(x, y, w, &best_right, depth + 1, rng)?;
 
         Ok(TreeNode::Split {
             feature_idx: best_feature,
             threshold: best_threshold,
+            categories: best_categories,
+            // No missing values seen: send them to the larger child.
+            missing_left: best_missing.unwrap_or(best_left.len() >= best_right.len()),
             left: Box::new(left_node),
             right: Box::new(right_node),
+            value,
+            impurity,
+            weight,
         })
     }
 
-    fn gini_impurity(&self, y: &Tensor<T>, indices: &[usize]) -> T {
-        if indices.is_empty() {
-            return T::ZERO;
-        }
-        let n = T::from_usize(indices.len());
-        let mut counts = vec![0usize; self.n_classes];
+    /// Weighted count of each class among `indices`.
+    fn class_counts(&self, y: &Tensor<T>, w: &[T], indices: &[usize]) -> Vec<T> {
+        let mut counts = vec![T::ZERO; self.n_classes.max(1)];
         for &i in indices {
             let cls = y.data()[i].to_f64().round() as usize;
-            if cls < self.n_classes {
-                counts[cls] += 1;
+            if cls < counts.len() {
+                counts[cls] += w[i];
             }
         }
+        counts
+    }
+
+    /// Gini impurity and total weight of `indices`.
+    fn gini_impurity(&self, y: &Tensor<T>, w: &[T], indices: &[usize]) -> (T, T) {
+        let counts = self.class_counts(y, w, indices);
+        let n = counts.iter().fold(T::ZERO, |acc, &c| acc + c);
+        if n <= T::ZERO {
+            return (T::ZERO, T::ZERO);
+        }
         let mut gini = T::ONE;
         for &c in &counts {
-            let p = T::from_usize(c) / n;
-            gini = gini - p * p;
+            let p = c / n;
+            gini -= p * p;
         }
-        gini
+        (gini, n)
     }
 
-    fn weighted_gini(&self, y: &Tensor<T>, left: &[usize], right: &[usize], total: usize) -> T {
-        let total_t = T::from_usize(total);
-        let left_weight = T::from_usize(left.len()) / total_t;
-        let right_weight = T::from_usize(right.len()) / total_t;
-        left_weight * self.gini_impurity(y, left) + right_weight * self.gini_impurity(y, right)
+    fn weighted_gini(&self, y: &Tensor<T>, w: &[T], left: &[usize], right: &[usize]) -> T {
+        let (left_gini, left_total) = self.gini_impurity(y, w, left);
+        let (right_gini, right_total) = self.gini_impurity(y, w, right);
+        let total = left_total + right_total;
+        if total <= T::ZERO {
+            return T::ZERO;
+        }
+        (left_total / total) * left_gini + (right_total / total) * right_gini
     }
 
-    fn majority_class(&self, y: &Tensor<T>, indices: &[usize]) -> T {
-        let mut counts = vec![0usize; self.n_classes.max(1)];
-        for &i in indices {
-            let cls = y.data()[i].to_f64().round() as usize;
-            if cls < counts.len() {
-                counts[cls] += 1;
+    fn majority_class(&self, y: &Tensor<T>, w: &[T], indices: &[usize]) -> T {
+        let counts = self.class_counts(y, w, indices);
+        let mut best = 0;
+        for (i, &c) in counts.iter().enumerate() {
+            // `>=` so ties go to the highest label.
+            if c >= counts[best] {
+                best = i;
             }
         }
-        let best = counts.iter().enumerate().max_by_key(|(_, &c)| c).map(|(i, _)| i).unwrap_or(0);
         T::from_usize(best)
     }
 
@@ -174,20 +577,23 @@ where
         let tree = self.tree.as_ref().ok_or_else(|| {
             TensorError::InvalidOperation("Model not fitted".into())
         })?;
-        Ok(self.traverse(tree, x, row)?)
+        self.traverse(tree, x, row)
     }
 
     fn traverse(&self, node: &TreeNode<T>, x: &Tensor<T>, row: usize) -> TensorResult<T> {
         match node {
-            TreeNode::Leaf { value } => Ok(*value),
+            TreeNode::Leaf { value, .. } => Ok(*value),
             TreeNode::Split {
                 feature_idx,
                 threshold,
+                categories,
+                missing_left,
                 left,
                 right,
+                ..
             } => {
                 let val = x.get(&[row, *feature_idx])?;
-                if val <= *threshold {
+                if goes_left(val, *threshold, categories, *missing_left) {
                     self.traverse(left, x, row)
                 } else {
                     self.traverse(right, x, row)
@@ -206,12 +612,32 @@ where
     }
 }
 
-/// Decision Tree Regressor using CART (MSE criterion).
+impl<T: Float> DecisionTreeClassifier<T> {
+    /// The fitted tree in pre-order; leaves hold class labels. Empty before `fit`.
+    pub fn nodes(&self) -> Vec<FlatNode<T>> {
+        flat_nodes(&self.tree)
+    }
+}
+
+/// Decision Tree Regressor using CART (MSE criterion). Missing (NaN)
+/// feature values are handled as in `DecisionTreeClassifier`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct DecisionTreeRegressor<T: Float> {
     pub max_depth: usize,
     pub min_samples_split: usize,
     pub min_samples_leaf: usize,
     tree: Option<TreeNode<T>>,
+    pub splitter: Splitter,
+    /// Features tried at each node, drawn at random; `None` tries all.
+    pub max_features: Option<usize>,
+    /// Seed for random splits and feature sampling.
+    pub seed: Option<u64>,
+    /// Complexity penalty per leaf for post-pruning; 0 keeps the full tree.
+    pub ccp_alpha: f64,
+    /// Columns holding category codes, split by set membership instead of
+    /// by threshold.
+    pub categorical_features: Vec<usize>,
 }
 
 impl<T: Float> DecisionTreeRegressor<T>
@@ -224,14 +650,66 @@ where
             min_samples_split,
             min_samples_leaf,
             tree: None,
+            splitter: Splitter::Best,
+            max_features: None,
+            seed: None,
+            ccp_alpha: 0.0,
+            categorical_features: Vec::new(),
         }
     }
 
+    /// Choose thresholds with `splitter`, seeding its random draws.
+    pub fn with_splitter(mut self, splitter: Splitter, seed: Option<u64>) -> Self {
+        self.splitter = splitter;
+        self.seed = seed;
+        self
+    }
+
+    /// Try only `max_features` randomly drawn features at each node.
+    pub fn with_max_features(mut self, max_features: usize) -> Self {
+        self.max_features = Some(max_features);
+        self
+    }
+
+    /// Treat the given columns as categorical: their values are category
+    /// codes, and splits send a subset of the categories left.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
+    /// Post-prune the fitted tree to the subtree minimizing
+    /// `impurity + ccp_alpha * n_leaves`.
+    pub fn with_ccp_alpha(mut self, ccp_alpha: f64) -> Self {
+        self.ccp_alpha = ccp_alpha;
+        self
+    }
+
+    /// Fit a full tree (ignoring `ccp_alpha`) and return its pruning path;
+    /// its alphas are the useful values of `ccp_alpha`.
+    pub fn cost_complexity_pruning_path(&self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<PruningPath> {
+        let mut full = self.clone().with_ccp_alpha(0.0);
+        full.fit(x, y)?;
+        let root = full.tree.as_mut().ok_or_else(|| TensorError::InvalidOperation("empty tree".into()))?;
+        Ok(prune(root, f64::INFINITY))
+    }
+
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.fit_weighted(x, y, None)
+    }
+
+    /// Fit with per-row weights: splits minimize the weighted MSE and leaves
+    /// hold weighted means. `min_samples_*` still count rows.
+    pub fn fit_weighted(&mut self, x: &Tensor<T>, y: &Tensor<T>, sample_weight: Option<&Tensor<T>>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
-        let p = x.shape().dim(1)?;
+        let weights = sample_weights(y, None, sample_weight)?;
         let indices: Vec<usize> = (0..n).collect();
-        self.tree = Some(self.build_tree(x, y, &indices, p, 0)?);
+        let mut rng = fit_rng(self.splitter, self.max_features, self.seed);
+        let mut root = self.build_tree(x, y, &weights, &indices, 0, &mut rng)?;
+        if self.ccp_alpha > 0.0 {
+            prune(&mut root, self.ccp_alpha);
+        }
+        self.tree = Some(root);
         Ok(())
     }
 
@@ -239,92 +717,117 @@ where
         &self,
         x: &Tensor<T>,
         y: &Tensor<T>,
+        w: &[T],
         indices: &[usize],
-        n_features: usize,
         depth: usize,
+        rng: &mut Option<RngHandle>,
     ) -> TensorResult<TreeNode<T>> {
+        let value = Self::mean_value(y, w, indices);
+        let impurity = Self::mse_value(y, w, indices);
+        let weight = Self::total_weight(w, indices);
         if depth >= self.max_depth || indices.len() < self.min_samples_split || indices.len() < 2 {
-            return Ok(TreeNode::Leaf {
-                value: Self::mean_value(y, indices),
-            });
+            return Ok(TreeNode::Leaf { value, impurity, weight });
         }
 
         let mut best_mse = T::from_f64(f64::MAX);
         let mut best_feature = 0;
         let mut best_threshold = T::ZERO;
+        let mut best_categories = None;
+        let mut best_missing = None;
         let mut best_left = Vec::new();
         let mut best_right = Vec::new();
 
-        for feature in 0..n_features {
-            let mut values: Vec<T> = indices.iter().map(|&i| x.get(&[i, feature]).unwrap()).collect();
-            values.sort_by(|a, b| a.to_f64().partial_cmp(&b.to_f64()).unwrap());
-            values.dedup();
-
-            for w in values.windows(2) {
-                let threshold = (w[0] + w[1]) / T::TWO;
-                let mut left = Vec::new();
-                let mut right = Vec::new();
-                for &i in indices {
-                    if x.get(&[i, feature]).unwrap() <= threshold {
-                        left.push(i);
-                    } else {
-                        right.push(i);
+        for feature in candidate_features(x.shape().dim(1)?, self.max_features, rng) {
+            let values: Vec<T> = indices.iter().map(|&i| x.get(&[i, feature]).unwrap()).filter(|v| !v.is_nan()).collect();
+            let has_missing = values.len() < indices.len();
+            let values = Tensor::from_slice(&values).unique();
+
+            let splits: Vec<(T, Option<Vec<T>>)> = if self.categorical_features.contains(&feature) {
+                // Ordering categories by mean target finds the best partition.
+                let scores = category_means(x, feature, indices, w, values.data(), |i| y.data()[i].to_f64());
+                category_subsets(values.data(), &scores, false, self.splitter, rng)
+                    .into_iter()
+                    .map(|set| (T::ZERO, Some(set)))
+                    .collect()
+            } else {
+                candidate_thresholds(values.data(), self.splitter, rng).into_iter().map(|t| (t, None)).collect()
+            };
+
+            for (threshold, categories) in splits {
+                // Rows missing the feature go to whichever child scores better.
+                for &missing_left in missing_directions(has_missing) {
+                    let (left, right) = partition(x, feature, indices, threshold, &categories, missing_left.unwrap_or(false));
+                    if left.len() < self.min_samples_leaf || right.len() < self.min_samples_leaf {
+                        continue;
+                    }
+                    let mse = Self::weighted_mse(y, w, &left, &right);
+                    if mse < best_mse {
+                        best_mse = mse;
+                        best_feature = feature;
+                        best_threshold = threshold;
+                        best_categories = categories.clone();
+                        best_missing = missing_left;
+                        best_left = left;
+                        best_right = right;
                     }
-                }
-                if left.len() < self.min_samples_leaf || right.len() < self.min_samples_leaf {
-                    continue;
-                }
-                let mse = Self::weighted_mse(y, &left, &right, indices.len());
-                if mse < best_mse {
-                    best_mse = mse;
-                    best_feature = feature;
-                    best_threshold = threshold;
-                    best_left = left;
-                    best_right = right;
                 }
             }
         }
 
         if best_left.is_empty() || best_right.is_empty() {
-            return Ok(TreeNode::Leaf {
-                value: Self::mean_value(y, indices),
-            });
+            return Ok(TreeNode::Leaf { value, impurity, weight });
         }
 
-        let left = self.build_tree(x, y, &best_left, n_features, depth + 1)?;
-        let right = self.build_tree(x, y, &best_right, n_features, depth + 1)?;
+        let left = self.build_tree(x, y, w, &best_left, depth + 1, rng)?;
+        let right = self.build_tree(x, y, w, &best_right, depth + 1, rng)?;
 
         Ok(TreeNode::Split {
             feature_idx: best_feature,
             threshold: best_threshold,
+            categories: best_categories,
+            // No missing values seen: send them to the larger child.
+            missing_left: best_missing.unwrap_or(best_left.len() >= best_right.len()),
             left: Box::new(left),
             right: Box::new(right),
+            value,
+            impurity,
+            weight,
         })
     }
 
-    fn mean_value(y: &Tensor<T>, indices: &[usize]) -> T {
-        if indices.is_empty() {
+    fn total_weight(w: &[T], indices: &[usize]) -> T {
+        indices.iter().map(|&i| w[i]).sum()
+    }
+
+    fn mean_value(y: &Tensor<T>, w: &[T], indices: &[usize]) -> T {
+        let total = Self::total_weight(w, indices);
+        if total <= T::ZERO {
             return T::ZERO;
         }
-        let sum: T = indices.iter().map(|&i| y.data()[i]).sum();
-        sum / T::from_usize(indices.len())
+        let sum: T = indices.iter().map(|&i| w[i] * y.data()[i]).sum();
+        sum / total
     }
 
-    fn mse_value(y: &Tensor<T>, indices: &[usize]) -> T {
-        let mean = Self::mean_value(y, indices);
-        let n = T::from_usize(indices.len());
+    fn mse_value(y: &Tensor<T>, w: &[T], indices: &[usize]) -> T {
+        let total = Self::total_weight(w, indices);
+        if total <= T::ZERO {
+            return T::ZERO;
+        }
+        let mean = Self::mean_value(y, w, indices);
         let sum: T = indices.iter().map(|&i| {
             let d = y.data()[i] - mean;
-            d * d
+            w[i] * d * d
         }).sum();
-        sum / n
+        sum / total
     }
 
-    fn weighted_mse(y: &Tensor<T>, left: &[usize], right: &[usize], total: usize) -> T {
-        let t = T::from_usize(total);
-        let lw = T::from_usize(left.len()) / t;
-        let rw = T::from_usize(right.len()) / t;
-        lw * Self::mse_value(y, left) + rw * Self::mse_value(y, right)
+    fn weighted_mse(y: &Tensor<T>, w: &[T], left: &[usize], right: &[usize]) -> T {
+        let (lt, rt) = (Self::total_weight(w, left), Self::total_weight(w, right));
+        let t = lt + rt;
+        if t <= T::ZERO {
+            return T::ZERO;
+        }
+        (lt / t) * Self::mse_value(y, w, left) + (rt / t) * Self::mse_value(y, w, right)
     }
 
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
@@ -341,9 +844,9 @@ where
 
     fn traverse(&self, node: &TreeNode<T>, x: &Tensor<T>, row: usize) -> TensorResult<T> {
         match node {
-            TreeNode::Leaf { value } => Ok(*value),
-            TreeNode::Split { feature_idx, threshold, left, right } => {
-                if x.get(&[row, *feature_idx])? <= *threshold {
+            TreeNode::Leaf { value, .. } => Ok(*value),
+            TreeNode::Split { feature_idx, threshold, categories, missing_left, left, right, .. } => {
+                if goes_left(x.get(&[row, *feature_idx])?, *threshold, categories, *missing_left) {
                     self.traverse(left, x, row)
                 } else {
                     self.traverse(right, x, row)
@@ -353,6 +856,13 @@ where
     }
 }
 
+impl<T: Float> DecisionTreeRegressor<T> {
+    /// The fitted tree in pre-order. Empty before `fit`.
+    pub fn nodes(&self) -> Vec<FlatNode<T>> {
+        flat_nodes(&self.tree)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -390,4 +900,103 @@ mod tests {
             assert!((pred.data()[i] - y.data()[i]).abs() < 1.0);
         }
     }
+
+    #[test]
+    fn test_decision_tree_weights() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![0.0], vec![1.0], vec![2.0], vec![3.0]]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 0.0, 1.0]);
+
+        // A depth-0 tree predicts the (weighted) majority class.
+        let mut stump = DecisionTreeClassifier::new(0, 2, 1);
+        stump.fit(&x, &y).unwrap();
+        assert_eq!(stump.predict(&x).unwrap().data(), &[0.0; 4]);
+        let mut stump = DecisionTreeClassifier::new(0, 2, 1).with_class_weight(ClassWeight::Custom(vec![(1.0, 4.0)]));
+        stump.fit(&x, &y).unwrap();
+        assert_eq!(stump.predict(&x).unwrap().data(), &[1.0; 4]);
+
+        let mut reg = DecisionTreeRegressor::new(0, 2, 1);
+        reg.fit_weighted(&x, &y, Some(&Tensor::from_slice(&[1.0, 0.0, 0.0, 3.0]))).unwrap();
+        assert_eq!(reg.predict(&x).unwrap().data()[0], 0.75);
+    }
+
+    #[test]
+    fn test_categorical_split() {
+        // Codes 1, 3 and 4 are class 1: no single threshold separates them.
+        let codes = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 1.0, 3.0, 0.0, 4.0];
+        let x: Tensor<f64> = Tensor::new(codes.to_vec(), vec![10, 1]).unwrap();
+        let y: Tensor<f64> = Tensor::new(codes.iter().map(|&c| [1.0, 3.0, 4.0].contains(&c) as u8 as f64).collect(), vec![10]).unwrap();
+
+        let mut stump = DecisionTreeClassifier::new(1, 2, 1);
+        stump.fit(&x, &y).unwrap();
+        assert_ne!(stump.predict(&x).unwrap().data(), y.data());
+
+        let mut stump = DecisionTreeClassifier::new(1, 2, 1).with_categorical_features(&[0]);
+        stump.fit(&x, &y).unwrap();
+        assert_eq!(stump.predict(&x).unwrap().data(), y.data());
+        assert!(matches!(&stump.nodes()[0], FlatNode::Categorical { categories, .. } if categories.len() == 3));
+
+        // Labels up to 2 make the classifier search partitions exhaustively.
+        let y3 = Tensor::new(codes.iter().map(|&c| if c == 2.0 || c == 5.0 { 2.0 } else { 0.0 }).collect(), vec![10]).unwrap();
+        let mut reg = DecisionTreeRegressor::new(1, 2, 1).with_categorical_features(&[0]);
+        reg.fit(&x, &y3).unwrap();
+        assert_eq!(reg.predict(&x).unwrap().data(), y3.data());
+        let mut clf = DecisionTreeClassifier::new(1, 2, 1).with_categorical_features(&[0]);
+        clf.fit(&x, &y3).unwrap();
+        assert_eq!(clf.predict(&x).unwrap().data(), y3.data());
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let nan = f64::NAN;
+        let x: Tensor<f64> = Tensor::new(vec![0.0, 1.0, nan, 2.0, 7.0, nan, 8.0, 9.0], vec![8, 1]).unwrap();
+
+        // Missing rows follow the class they belong with.
+        for (y, missing_left) in [([0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0], false), ([0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0], true)] {
+            let y = Tensor::from_slice(&y);
+            let mut stump = DecisionTreeClassifier::new(1, 2, 1);
+            stump.fit(&x, &y).unwrap();
+            assert_eq!(stump.predict(&x).unwrap().data(), y.data());
+            assert!(matches!(stump.nodes()[0], FlatNode::Split { missing_left: m, .. } if m == missing_left));
+        }
+
+        let y = Tensor::from_slice(&[1.0, 1.0, 3.0, 1.0, 3.0, 3.0, 3.0, 3.0]);
+        let mut reg = DecisionTreeRegressor::new(1, 2, 1);
+        reg.fit(&x, &y).unwrap();
+        assert_eq!(reg.predict(&x).unwrap().data(), y.data());
+
+        // Without missing training values, NaN goes to the larger child.
+        let x = Tensor::new(vec![0.0, 1.0, 2.0, 3.0, 4.0], vec![5, 1]).unwrap();
+        let mut stump = DecisionTreeClassifier::new(1, 2, 1);
+        stump.fit(&x, &Tensor::from_slice(&[0.0, 0.0, 0.0, 1.0, 1.0])).unwrap();
+        assert_eq!(stump.predict(&Tensor::new(vec![nan], vec![1, 1]).unwrap()).unwrap().data(), &[0.0]);
+    }
+
+    #[test]
+    fn test_cost_complexity_pruning() {
+        // A clean split at 4.5 plus two mislabeled rows the full tree isolates.
+        let x: Tensor<f64> = Tensor::new((0..10).map(|i| i as f64).collect(), vec![10, 1]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+
+        let tree = DecisionTreeClassifier::new(10, 2, 1);
+        let path = tree.cost_complexity_pruning_path(&x, &y).unwrap();
+        assert_eq!(path.ccp_alphas[0], 0.0);
+        assert_eq!(path.impurities[0], 0.0);
+        assert!(path.ccp_alphas.windows(2).all(|a| a[0] <= a[1] + 1e-12));
+        assert!((path.impurities.last().unwrap() - 0.5).abs() < 1e-12);
+
+        let mut full = tree.clone();
+        full.fit(&x, &y).unwrap();
+        let n = path.ccp_alphas.len();
+        let mut pruned = tree.clone().with_ccp_alpha(path.ccp_alphas[n - 2]);
+        pruned.fit(&x, &y).unwrap();
+        assert_eq!(pruned.nodes().len(), 3);
+        assert!(pruned.nodes().len() < full.nodes().len());
+        let mut stump = tree.with_ccp_alpha(path.ccp_alphas[n - 1]);
+        stump.fit(&x, &y).unwrap();
+        assert_eq!(stump.nodes().len(), 1);
+
+        let reg = DecisionTreeRegressor::new(10, 2, 1);
+        let path = reg.cost_complexity_pruning_path(&x, &y).unwrap();
+        assert!((path.impurities.last().unwrap() - 0.25).abs() < 1e-12);
+    }
 }
@@ -0,0 +1,241 @@
+//! Human- and machine-readable dumps of fitted trees.
+//!
+//! The free functions work on the pre-order nodes returned by `nodes()` and
+//! take optional feature names, defaulting to `feature_<i>`. The tree and
+//! ensemble types wrap them as `export_text`, `export_graphviz` and
+//! `export_json` methods.
+
+use oxidize_ml_core::Float;
+use serde_json::json;
+
+use crate::decision_tree::{DecisionTreeClassifier, DecisionTreeRegressor, FlatNode};
+use crate::extra_trees::{ExtraTreesClassifier, ExtraTreesRegressor};
+use crate::gradient_boosting::{GradientBoostingClassifier, GradientBoostingRegressor};
+use crate::random_forest::{RandomForestClassifier, RandomForestRegressor};
+
+fn feature_name(feature: usize, names: Option<&[&str]>) -> String {
+    match names.and_then(|n| n.get(feature)) {
+        Some(name) => name.to_string(),
+        None => format!("feature_{}", feature),
+    }
+}
+
+fn category_list<T: Float>(categories: &[T]) -> String {
+    let items: Vec<String> = categories.iter().map(|c| c.to_f64().to_string()).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+/// The test a row passes to reach the `left` or right child of a split.
+fn condition<T: Float>(node: &FlatNode<T>, left: bool, names: Option<&[&str]>) -> String {
+    let (test, missing_left) = match node {
+        FlatNode::Split { feature, threshold, missing_left, .. } => {
+            let op = if left { "<=" } else { ">" };
+            (format!("{} {} {}", feature_name(*feature, names), op, threshold.to_f64()), *missing_left)
+        }
+        FlatNode::Categorical { feature, categories, missing_left, .. } => {
+            let op = if left { "in" } else { "not in" };
+            (format!("{} {} {}", feature_name(*feature, names), op, category_list(categories)), *missing_left)
+        }
+        FlatNode::Leaf { .. } => return String::new(),
+    };
+    if missing_left == left {
+        format!("{} or missing", test)
+    } else {
+        test
+    }
+}
+
+fn write_text<T: Float>(nodes: &[FlatNode<T>], id: usize, depth: usize, names: Option<&[&str]>, out: &mut String) {
+    let indent = "|   ".repeat(depth);
+    match &nodes[id] {
+        FlatNode::Leaf { value } => out.push_str(&format!("{}|--- value: {}\n", indent, value.to_f64())),
+        node @ (FlatNode::Split { left, right, .. } | FlatNode::Categorical { left, right, .. }) => {
+            for (child, is_left) in [(*left, true), (*right, false)] {
+                out.push_str(&format!("{}|--- {}\n", indent, condition(node, is_left, names)));
+                write_text(nodes, child, depth + 1, names, out);
+            }
+        }
+    }
+}
+
+/// Indented outline of a tree's rules, one line per branch and leaf.
+pub fn export_text<T: Float>(nodes: &[FlatNode<T>], feature_names: Option<&[&str]>) -> String {
+    let mut out = String::new();
+    if !nodes.is_empty() {
+        write_text(nodes, 0, 0, feature_names, &mut out);
+    }
+    out
+}
+
+/// A Graphviz `digraph` of a tree: split boxes show the test of their left
+/// (`True`) edge, leaves show their value.
+pub fn export_graphviz<T: Float>(nodes: &[FlatNode<T>], feature_names: Option<&[&str]>) -> String {
+    let mut out = String::from("digraph Tree {\nnode [shape=box];\n");
+    for (id, node) in nodes.iter().enumerate() {
+        match node {
+            FlatNode::Leaf { value } => {
+                out.push_str(&format!("{} [label=\"value = {}\"];\n", id, value.to_f64()));
+            }
+            FlatNode::Split { left, right, .. } | FlatNode::Categorical { left, right, .. } => {
+                let label = condition(node, true, feature_names).replace('"', "\\\"");
+                out.push_str(&format!("{} [label=\"{}\"];\n", id, label));
+                out.push_str(&format!("{} -> {} [label=\"True\"];\n", id, left));
+                out.push_str(&format!("{} -> {} [label=\"False\"];\n", id, right));
+            }
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// The nodes as a JSON array, in pre-order; each node is an object tagged
+/// by `kind` (`split`, `categorical` or `leaf`), children referenced by index.
+pub fn export_json<T: Float>(nodes: &[FlatNode<T>]) -> String {
+    serde_json::to_string_pretty(nodes).expect("tree nodes serialize to JSON")
+}
+
+/// A forest tree's nodes with features renumbered from its column subset to
+/// the input columns.
+fn in_columns<T: Float>(nodes: Vec<FlatNode<T>>, columns: &[usize]) -> Vec<FlatNode<T>> {
+    nodes
+        .into_iter()
+        .map(|mut node| {
+            if let FlatNode::Split { feature, .. } | FlatNode::Categorical { feature, .. } = &mut node {
+                *feature = columns[*feature];
+            }
+            node
+        })
+        .collect()
+}
+
+fn ensemble_json(value: serde_json::Value) -> String {
+    serde_json::to_string_pretty(&value).expect("tree nodes serialize to JSON")
+}
+
+macro_rules! impl_tree_export {
+    ($($model:ident),*) => {$(
+        impl<T: Float> $model<T> {
+            /// Indented outline of the fitted rules; see [`export_text`].
+            pub fn export_text(&self) -> String {
+                export_text(&self.nodes(), None)
+            }
+
+            /// Graphviz source for the fitted tree; see [`export_graphviz`].
+            pub fn export_graphviz(&self) -> String {
+                export_graphviz(&self.nodes(), None)
+            }
+
+            /// The fitted nodes as JSON; see [`export_json`].
+            pub fn export_json(&self) -> String {
+                export_json(&self.nodes())
+            }
+        }
+    )*};
+}
+
+impl_tree_export!(DecisionTreeClassifier, DecisionTreeRegressor);
+
+macro_rules! impl_forest_export {
+    ($($model:ident => $aggregate:literal),*) => {$(
+        impl<T: Float> $model<T> {
+            /// Every tree's nodes as JSON, features numbered by input column:
+            /// `{"aggregate": ..., "trees": [[node, ...], ...]}`.
+            pub fn export_json(&self) -> String {
+                let trees: Vec<_> = self.estimators().map(|(t, cols)| in_columns(t.nodes(), cols)).collect();
+                ensemble_json(json!({ "aggregate": $aggregate, "trees": trees }))
+            }
+        }
+    )*};
+}
+
+impl_forest_export!(RandomForestClassifier => "vote", RandomForestRegressor => "mean");
+
+macro_rules! impl_extra_trees_export {
+    ($($model:ident => $aggregate:literal),*) => {$(
+        impl<T: Float> $model<T> {
+            /// Every tree's nodes as JSON:
+            /// `{"aggregate": ..., "trees": [[node, ...], ...]}`.
+            pub fn export_json(&self) -> String {
+                let trees: Vec<_> = self.estimators().iter().map(|t| t.nodes()).collect();
+                ensemble_json(json!({ "aggregate": $aggregate, "trees": trees }))
+            }
+        }
+    )*};
+}
+
+impl_extra_trees_export!(ExtraTreesClassifier => "vote", ExtraTreesRegressor => "mean");
+
+impl<T: Float> GradientBoostingRegressor<T> {
+    /// The boosted trees as JSON; the prediction is
+    /// `initial_prediction + learning_rate * Σ tree(x)`.
+    pub fn export_json(&self) -> String {
+        let trees: Vec<_> = self.estimators().iter().map(|t| t.nodes()).collect();
+        ensemble_json(json!({
+            "initial_prediction": self.initial_prediction().to_f64(),
+            "learning_rate": self.learning_rate.to_f64(),
+            "trees": trees,
+        }))
+    }
+}
+
+impl<T: Float> GradientBoostingClassifier<T> {
+    /// The boosted trees as JSON; `P(y = 1)` is the sigmoid of
+    /// `initial_log_odds + learning_rate * Σ tree(x)`.
+    pub fn export_json(&self) -> String {
+        let trees: Vec<_> = self.estimators().iter().map(|t| t.nodes()).collect();
+        ensemble_json(json!({
+            "initial_log_odds": self.initial_log_odds().to_f64(),
+            "learning_rate": self.learning_rate.to_f64(),
+            "trees": trees,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_core::Tensor;
+
+    #[test]
+    fn test_export_tree() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![0.0, 1.0], vec![1.0, 0.0], vec![2.0, 1.0], vec![3.0, 0.0]]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0]);
+        let mut tree = DecisionTreeClassifier::new(3, 2, 1);
+        tree.fit(&x, &y).unwrap();
+
+        assert_eq!(
+            tree.export_text(),
+            "|--- feature_0 <= 1.5 or missing\n|   |--- value: 0\n|--- feature_0 > 1.5\n|   |--- value: 1\n"
+        );
+        assert!(export_text(&tree.nodes(), Some(&["age", "flag"])).starts_with("|--- age <= 1.5"));
+        let dot = tree.export_graphviz();
+        assert!(dot.starts_with("digraph Tree {") && dot.contains("0 -> 2 [label=\"False\"]"));
+
+        let json: serde_json::Value = serde_json::from_str(&tree.export_json()).unwrap();
+        assert_eq!(json[0]["kind"], "split");
+        assert_eq!(json[0]["threshold"], 1.5);
+        assert_eq!(json[2]["value"], 1.0);
+        let parsed: Vec<FlatNode<f64>> = serde_json::from_str(&tree.export_json()).unwrap();
+        assert_eq!(parsed, tree.nodes());
+    }
+
+    #[test]
+    fn test_export_forest_columns() {
+        let x: Tensor<f64> = Tensor::from_vec2d(&[vec![5.0, 0.0], vec![5.0, 1.0], vec![5.0, 2.0], vec![5.0, 3.0]]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 1.0, 1.0]);
+        let mut rf = RandomForestClassifier::new(3, 3, 0.5);
+        rf.seed = Some(4);
+        rf.fit(&x, &y).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&rf.export_json()).unwrap();
+        assert_eq!(json["trees"].as_array().unwrap().len(), 3);
+        // Only column 1 varies, so every split is on it, whatever its subset.
+        for tree in json["trees"].as_array().unwrap() {
+            for node in tree.as_array().unwrap() {
+                if node["kind"] == "split" {
+                    assert_eq!(node["feature"], 1);
+                }
+            }
+        }
+    }
+}
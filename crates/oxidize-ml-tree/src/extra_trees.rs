@@ -0,0 +1,284 @@
+use oxidize_ml_core::{parallel, Float, RngHandle, Tensor};
+use oxidize_ml_core::error::TensorResult;
+use rand::distributions::{Distribution, Standard};
+use rand::RngCore;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::decision_tree::{DecisionTreeClassifier, DecisionTreeRegressor, Splitter};
+
+/// Per-tree seeds and training rows: every row once, or a bootstrap sample.
+/// Seeds are forked from the master seed, so the ensemble depends only on
+/// `seed`, not on how many threads fit it.
+fn draw_trees(n_estimators: usize, n: usize, bootstrap: bool, seed: Option<u64>) -> Vec<(u64, Vec<usize>)> {
+    let mut base_rng = RngHandle::new(seed);
+    (0..n_estimators)
+        .map(|_| {
+            let mut rng = base_rng.fork();
+            let rows = if bootstrap {
+                (0..n).map(|_| ((rand::Rng::gen::<f64>(&mut rng) * n as f64) as usize).min(n - 1)).collect()
+            } else {
+                (0..n).collect()
+            };
+            (rng.next_u64(), rows)
+        })
+        .collect()
+}
+
+fn max_features(p: usize, ratio: f64) -> usize {
+    ((p as f64 * ratio).ceil() as usize).clamp(1, p.max(1))
+}
+
+/// Extremely randomized trees for classification.
+///
+/// Like a random forest, but each node tries one random threshold per
+/// candidate feature instead of searching every split, and by default each
+/// tree sees all training rows. `max_features_ratio` is the share of
+/// features drawn as candidates at every node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct ExtraTreesClassifier<T: Float> {
+    pub n_estimators: usize,
+    pub max_depth: usize,
+    pub min_samples_split: usize,
+    pub max_features_ratio: f64,
+    /// Fit each tree on a bootstrap sample instead of all rows.
+    pub bootstrap: bool,
+    pub seed: Option<u64>,
+    /// Columns holding category codes; see
+    /// `DecisionTreeClassifier::with_categorical_features`.
+    pub categorical_features: Vec<usize>,
+    trees: Vec<DecisionTreeClassifier<T>>,
+    pub n_classes: usize,
+}
+
+impl<T: Float> ExtraTreesClassifier<T>
+where
+    Standard: Distribution<T>,
+{
+    pub fn new(n_estimators: usize, max_depth: usize, max_features_ratio: f64) -> Self {
+        ExtraTreesClassifier {
+            n_estimators,
+            max_depth,
+            min_samples_split: 2,
+            max_features_ratio,
+            bootstrap: false,
+            seed: None,
+            categorical_features: Vec::new(),
+            trees: Vec::new(),
+            n_classes: 0,
+        }
+    }
+
+    /// Fit each tree on a bootstrap sample of the rows.
+    pub fn with_bootstrap(mut self) -> Self {
+        self.bootstrap = true;
+        self
+    }
+
+    /// Split the given columns by category membership in every tree.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let k = max_features(x.shape().dim(1)?, self.max_features_ratio);
+        let max_label = y.data().iter().map(|v| v.to_f64().round() as usize).max().unwrap_or(0);
+        self.n_classes = max_label + 1;
+
+        let draws = draw_trees(self.n_estimators, n, self.bootstrap, self.seed);
+        let (max_depth, min_samples_split, bootstrap) = (self.max_depth, self.min_samples_split, self.bootstrap);
+        let categorical = &self.categorical_features;
+        self.trees = parallel::install(|| {
+            par_iter!(&draws)
+                .map(|(seed, rows)| {
+                    let mut tree = DecisionTreeClassifier::new(max_depth, min_samples_split, 1)
+                        .with_splitter(Splitter::Random, Some(*seed))
+                        .with_max_features(k)
+                        .with_categorical_features(categorical);
+                    if bootstrap {
+                        tree.fit(&x.index_select(0, rows)?, &y.index_select(0, rows)?)?;
+                    } else {
+                        tree.fit(x, y)?;
+                    }
+                    Ok(tree)
+                })
+                .collect::<TensorResult<Vec<_>>>()
+        })?;
+        Ok(())
+    }
+
+    /// Majority vote over the trees.
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let n = x.shape().dim(0)?;
+        let preds = parallel::install(|| {
+            par_iter!(&self.trees).map(|t| t.predict(x)).collect::<TensorResult<Vec<_>>>()
+        })?;
+        let mut votes = vec![vec![0usize; self.n_classes.max(1)]; n];
+        for pred in preds {
+            for (row_votes, &p) in votes.iter_mut().zip(pred.data()) {
+                let cls = p.to_f64().round() as usize;
+                if cls < row_votes.len() {
+                    row_votes[cls] += 1;
+                }
+            }
+        }
+        let predictions = votes
+            .iter()
+            .map(|v| {
+                let best = v.iter().enumerate().max_by_key(|(_, &c)| c).map(|(i, _)| i).unwrap_or(0);
+                T::from_usize(best)
+            })
+            .collect();
+        Tensor::new(predictions, vec![n])
+    }
+}
+
+impl<T: Float> ExtraTreesClassifier<T> {
+    pub fn estimators(&self) -> &[DecisionTreeClassifier<T>] {
+        &self.trees
+    }
+}
+
+/// Extremely randomized trees for regression; see `ExtraTreesClassifier`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
+pub struct ExtraTreesRegressor<T: Float> {
+    pub n_estimators: usize,
+    pub max_depth: usize,
+    pub min_samples_split: usize,
+    pub max_features_ratio: f64,
+    /// Fit each tree on a bootstrap sample instead of all rows.
+    pub bootstrap: bool,
+    pub seed: Option<u64>,
+    /// Columns holding category codes; see
+    /// `DecisionTreeClassifier::with_categorical_features`.
+    pub categorical_features: Vec<usize>,
+    trees: Vec<DecisionTreeRegressor<T>>,
+}
+
+impl<T: Float> ExtraTreesRegressor<T>
+where
+    Standard: Distribution<T>,
+{
+    pub fn new(n_estimators: usize, max_depth: usize, max_features_ratio: f64) -> Self {
+        ExtraTreesRegressor {
+            n_estimators,
+            max_depth,
+            min_samples_split: 2,
+            max_features_ratio,
+            bootstrap: false,
+            seed: None,
+            categorical_features: Vec::new(),
+            trees: Vec::new(),
+        }
+    }
+
+    /// Fit each tree on a bootstrap sample of the rows.
+    pub fn with_bootstrap(mut self) -> Self {
+        self.bootstrap = true;
+        self
+    }
+
+    /// Split the given columns by category membership in every tree.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
+    pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let k = max_features(x.shape().dim(1)?, self.max_features_ratio);
+        let draws = draw_trees(self.n_estimators, n, self.bootstrap, self.seed);
+        let (max_depth, min_samples_split, bootstrap) = (self.max_depth, self.min_samples_split, self.bootstrap);
+        let categorical = &self.categorical_features;
+        self.trees = parallel::install(|| {
+            par_iter!(&draws)
+                .map(|(seed, rows)| {
+                    let mut tree = DecisionTreeRegressor::new(max_depth, min_samples_split, 1)
+                        .with_splitter(Splitter::Random, Some(*seed))
+                        .with_max_features(k)
+                        .with_categorical_features(categorical);
+                    if bootstrap {
+                        tree.fit(&x.index_select(0, rows)?, &y.index_select(0, rows)?)?;
+                    } else {
+                        tree.fit(x, y)?;
+                    }
+                    Ok(tree)
+                })
+                .collect::<TensorResult<Vec<_>>>()
+        })?;
+        Ok(())
+    }
+
+    /// Mean prediction over the trees.
+    pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let n = x.shape().dim(0)?;
+        let preds = parallel::install(|| {
+            par_iter!(&self.trees).map(|t| t.predict(x)).collect::<TensorResult<Vec<_>>>()
+        })?;
+        let mut sums = vec![T::ZERO; n];
+        for pred in &preds {
+            for (s, &p) in sums.iter_mut().zip(pred.data()) {
+                *s += p;
+            }
+        }
+        let n_trees = T::from_usize(preds.len().max(1));
+        Tensor::new(sums.into_iter().map(|s| s / n_trees).collect(), vec![n])
+    }
+}
+
+impl<T: Float> ExtraTreesRegressor<T> {
+    pub fn estimators(&self) -> &[DecisionTreeRegressor<T>] {
+        &self.trees
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_trees_classifier() {
+        // Two informative features and two noise features.
+        let n = 80;
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        for i in 0..n {
+            let class = i % 2;
+            let jitter = ((i * 37) % 11) as f64 * 0.1;
+            xs.extend([class as f64 * 3.0 + jitter, class as f64 * -2.0 + jitter, ((i * 13) % 7) as f64, ((i * 29) % 5) as f64]);
+            ys.push(class as f64);
+        }
+        let x = Tensor::new(xs, vec![n, 4]).unwrap();
+        let y = Tensor::new(ys, vec![n]).unwrap();
+
+        let mut et = ExtraTreesClassifier::new(15, 6, 0.5);
+        et.seed = Some(1);
+        et.fit(&x, &y).unwrap();
+        assert_eq!(et.estimators().len(), 15);
+        assert_eq!(et.predict(&x).unwrap().data(), y.data());
+
+        let mut again = ExtraTreesClassifier::new(15, 6, 0.5).with_bootstrap();
+        again.seed = Some(1);
+        again.fit(&x, &y).unwrap();
+        assert_ne!(again.estimators()[0].nodes(), et.estimators()[0].nodes());
+    }
+
+    #[test]
+    fn test_extra_trees_regressor() {
+        let n = 50;
+        let x = Tensor::new((0..n).map(|i| i as f64 / 10.0).collect(), vec![n, 1]).unwrap();
+        let y = Tensor::new((0..n).map(|i| (i as f64 / 10.0).sin()).collect(), vec![n]).unwrap();
+
+        let mut et = ExtraTreesRegressor::new(20, 8, 1.0);
+        et.seed = Some(3);
+        et.fit(&x, &y).unwrap();
+        let pred = et.predict(&x).unwrap();
+        let mse: f64 = pred.data().iter().zip(y.data()).map(|(p, t)| (p - t).powi(2)).sum::<f64>() / n as f64;
+        assert!(mse < 1e-3, "mse = {}", mse);
+    }
+}
@@ -1,12 +1,17 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{sample_weights, ClassWeight, Float, Tensor};
 use oxidize_ml_core::error::TensorResult;
 use crate::decision_tree::DecisionTreeRegressor;
 use rand::distributions::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
 
 /// Gradient Boosted Trees for Regression.
 ///
 /// Uses gradient descent in function space by sequentially fitting
 /// decision trees to the residuals (negative gradient of the loss).
+/// Features may contain NaN: every split learns which child missing values
+/// go to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct GradientBoostingRegressor<T: Float> {
     pub n_estimators: usize,
     pub learning_rate: T,
@@ -14,6 +19,9 @@ pub struct GradientBoostingRegressor<T: Float> {
     pub min_samples_split: usize,
     pub subsample: f64,
     trees: Vec<DecisionTreeRegressor<T>>,
+    /// Columns holding category codes; see
+    /// `DecisionTreeClassifier::with_categorical_features`.
+    pub categorical_features: Vec<usize>,
     initial_prediction: T,
 }
 
@@ -33,12 +41,19 @@ where
             learning_rate,
             max_depth: if max_depth == 0 { 3 } else { max_depth },
             min_samples_split: if min_samples_split == 0 { 2 } else { min_samples_split },
-            subsample: subsample.max(0.1).min(1.0),
+            subsample: subsample.clamp(0.1, 1.0),
             trees: Vec::new(),
+            categorical_features: Vec::new(),
             initial_prediction: T::ZERO,
         }
     }
 
+    /// Split the given columns by category membership in every tree.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let _p = x.shape().dim(1)?;
@@ -60,13 +75,14 @@ where
             let residual_tensor = Tensor::new(residuals, vec![n])?;
 
             // Fit tree to residuals
-            let mut tree = DecisionTreeRegressor::new(self.max_depth, self.min_samples_split, 1);
+            let mut tree = DecisionTreeRegressor::new(self.max_depth, self.min_samples_split, 1)
+                .with_categorical_features(&self.categorical_features);
             tree.fit(x, &residual_tensor)?;
 
             // Update predictions
             let tree_pred = tree.predict(x)?;
             for i in 0..n {
-                predictions[i] = predictions[i] + self.learning_rate * tree_pred.data()[i];
+                predictions[i] += self.learning_rate * tree_pred.data()[i];
             }
 
             self.trees.push(tree);
@@ -82,7 +98,7 @@ where
         for tree in &self.trees {
             let tree_pred = tree.predict(x)?;
             for i in 0..n {
-                predictions[i] = predictions[i] + self.learning_rate * tree_pred.data()[i];
+                predictions[i] += self.learning_rate * tree_pred.data()[i];
             }
         }
 
@@ -94,17 +110,35 @@ where
     }
 }
 
+impl<T: Float> GradientBoostingRegressor<T> {
+    /// Fitted trees; the prediction is `initial_prediction + learning_rate * Σ tree(x)`.
+    pub fn estimators(&self) -> &[DecisionTreeRegressor<T>] {
+        &self.trees
+    }
+
+    pub fn initial_prediction(&self) -> T {
+        self.initial_prediction
+    }
+}
+
 /// Gradient Boosted Trees for Binary Classification.
 ///
 /// Uses log-loss (binary cross-entropy) as the objective.
-/// Predictions are log-odds, converted with sigmoid.
+/// Predictions are log-odds, converted with sigmoid. Features may contain
+/// NaN, as in the regressor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct GradientBoostingClassifier<T: Float> {
     pub n_estimators: usize,
     pub learning_rate: T,
     pub max_depth: usize,
     pub min_samples_split: usize,
     trees: Vec<DecisionTreeRegressor<T>>,
+    /// Columns holding category codes; see
+    /// `DecisionTreeClassifier::with_categorical_features`.
+    pub categorical_features: Vec<usize>,
     initial_log_odds: T,
+    pub class_weight: Option<ClassWeight>,
 }
 
 impl<T: Float> GradientBoostingClassifier<T>
@@ -123,20 +157,43 @@ where
             max_depth: if max_depth == 0 { 3 } else { max_depth },
             min_samples_split: if min_samples_split == 0 { 2 } else { min_samples_split },
             trees: Vec::new(),
+            categorical_features: Vec::new(),
             initial_log_odds: T::ZERO,
+            class_weight: None,
         }
     }
 
+    /// Reweight classes in the log-loss, e.g. `ClassWeight::Balanced` for
+    /// imbalanced targets.
+    pub fn with_class_weight(mut self, class_weight: ClassWeight) -> Self {
+        self.class_weight = Some(class_weight);
+        self
+    }
+
+    /// Split the given columns by category membership in every tree.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
     fn sigmoid(x: T) -> T {
         T::ONE / (T::ONE + (-x).exp())
     }
 
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.fit_weighted(x, y, None)
+    }
+
+    /// Fit with per-row weights on the log-loss (combined with
+    /// `class_weight`); every residual tree is fitted with the same weights.
+    pub fn fit_weighted(&mut self, x: &Tensor<T>, y: &Tensor<T>, sample_weight: Option<&Tensor<T>>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
+        let weights = sample_weights(y, self.class_weight.as_ref(), sample_weight)?;
+        let weight_tensor = Tensor::new(weights.clone(), vec![weights.len()])?;
 
-        // Initial log-odds based on class proportions
-        let pos_count: f64 = y.data().iter().map(|v| v.to_f64()).sum();
-        let neg_count = n as f64 - pos_count;
+        // Initial log-odds based on (weighted) class proportions
+        let pos_count: f64 = y.data().iter().zip(&weights).map(|(v, w)| v.to_f64() * w.to_f64()).sum();
+        let neg_count = weights.iter().map(|w| w.to_f64()).sum::<f64>() - pos_count;
         self.initial_log_odds = if neg_count > 0.0 {
             T::from_f64((pos_count / neg_count).max(1e-10).ln())
         } else {
@@ -154,13 +211,14 @@ where
             let residual_tensor = Tensor::new(residuals, vec![n])?;
 
             // Fit tree to pseudo-residuals
-            let mut tree = DecisionTreeRegressor::new(self.max_depth, self.min_samples_split, 1);
-            tree.fit(x, &residual_tensor)?;
+            let mut tree = DecisionTreeRegressor::new(self.max_depth, self.min_samples_split, 1)
+                .with_categorical_features(&self.categorical_features);
+            tree.fit_weighted(x, &residual_tensor, Some(&weight_tensor))?;
 
             // Update raw predictions
             let tree_pred = tree.predict(x)?;
             for i in 0..n {
-                raw_predictions[i] = raw_predictions[i] + self.learning_rate * tree_pred.data()[i];
+                raw_predictions[i] += self.learning_rate * tree_pred.data()[i];
             }
 
             self.trees.push(tree);
@@ -176,7 +234,7 @@ where
         for tree in &self.trees {
             let tree_pred = tree.predict(x)?;
             for i in 0..n {
-                raw_predictions[i] = raw_predictions[i] + self.learning_rate * tree_pred.data()[i];
+                raw_predictions[i] += self.learning_rate * tree_pred.data()[i];
             }
         }
 
@@ -193,6 +251,17 @@ where
     }
 }
 
+impl<T: Float> GradientBoostingClassifier<T> {
+    /// Fitted trees; `P(y=1) = σ(initial_log_odds + learning_rate * Σ tree(x))`.
+    pub fn estimators(&self) -> &[DecisionTreeRegressor<T>] {
+        &self.trees
+    }
+
+    pub fn initial_log_odds(&self) -> T {
+        self.initial_log_odds
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,4 +301,18 @@ mod tests {
                 "prediction {} != expected {} at index {}", pred.data()[i], y.data()[i], i);
         }
     }
+
+    #[test]
+    fn test_gradient_boosting_missing_values() {
+        let nan = f64::NAN;
+        let x: Tensor<f64> = Tensor::from_vec2d(&[
+            vec![0.0, nan], vec![0.1, 0.1], vec![nan, 0.2],
+            vec![0.8, nan], vec![0.9, 0.9], vec![nan, 1.0],
+        ]).unwrap();
+        let y: Tensor<f64> = Tensor::from_slice(&[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+
+        let mut model = GradientBoostingClassifier::new(50, 0.1, 3, 2);
+        model.fit(&x, &y).unwrap();
+        assert_eq!(model.predict(&x).unwrap().data(), y.data());
+    }
 }
@@ -1,7 +1,23 @@
+/// `$items.par_iter()` with the `rayon` feature, `$items.iter()` without, so
+/// forests fit and predict their trees in parallel only when it is enabled.
+macro_rules! par_iter {
+    ($items:expr) => {{
+        #[cfg(feature = "rayon")]
+        let iter = rayon::prelude::IntoParallelRefIterator::par_iter($items);
+        #[cfg(not(feature = "rayon"))]
+        let iter = $items.iter();
+        iter
+    }};
+}
+
 pub mod decision_tree;
 pub mod random_forest;
 pub mod gradient_boosting;
+pub mod extra_trees;
+pub mod export;
 
 pub use decision_tree::*;
 pub use random_forest::*;
 pub use gradient_boosting::*;
+pub use extra_trees::*;
+pub use export::*;
@@ -1,13 +1,79 @@
-use oxidize_ml_core::{Float, Tensor, TensorError};
+use oxidize_ml_core::{parallel, sample_weights, ClassWeight, Float, RngHandle, Tensor};
 use oxidize_ml_core::error::TensorResult;
 use rand::distributions::{Distribution, Standard};
-use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::SeedableRng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use crate::decision_tree::{DecisionTreeClassifier, DecisionTreeRegressor};
+use serde::{Deserialize, Serialize};
+
+/// The random choices behind one tree of a forest.
+struct Draw {
+    /// Bootstrap rows, with repeats.
+    sample: Vec<usize>,
+    /// Rows never drawn (out of bag), in order.
+    oob: Vec<usize>,
+    features: Vec<usize>,
+}
+
+/// Draw the bootstrap rows and feature subset of each tree. Every tree gets
+/// its own stream forked from the master seed, so a forest depends only on
+/// `seed`, not on how many threads fit it.
+fn draw_trees(n_estimators: usize, n: usize, p: usize, max_features_ratio: f64, seed: Option<u64>) -> Vec<Draw> {
+    let max_features = ((p as f64 * max_features_ratio).ceil() as usize).max(1).min(p);
+    let mut base_rng = RngHandle::new(seed);
+    (0..n_estimators)
+        .map(|_| {
+            let mut rng = base_rng.fork();
+            let mut drawn = vec![false; n];
+            let sample: Vec<usize> = (0..n)
+                .map(|_| {
+                    let j = ((rand::Rng::gen::<f64>(&mut rng) * n as f64) as usize).min(n - 1);
+                    drawn[j] = true;
+                    j
+                })
+                .collect();
+            let oob = (0..n).filter(|&i| !drawn[i]).collect();
+            let mut features: Vec<usize> = (0..p).collect();
+            features.shuffle(&mut rng);
+            features.truncate(max_features);
+            Draw { sample, oob, features }
+        })
+        .collect()
+}
+
+/// Positions within a tree's feature subset of the categorical columns.
+fn subset_categorical(features: &[usize], categorical: &[usize]) -> Vec<usize> {
+    features.iter().enumerate().filter(|(_, f)| categorical.contains(f)).map(|(j, _)| j).collect()
+}
+
+/// Each tree's predictions on its own feature columns, in parallel with the
+/// `rayon` feature.
+fn tree_predictions<M, F>(
+    trees: &[M],
+    feature_subsets: &[Vec<usize>],
+    x: &Tensor<F>,
+    predict: fn(&M, &Tensor<F>) -> TensorResult<Tensor<F>>,
+) -> TensorResult<Vec<Tensor<F>>>
+where
+    M: Sync,
+    F: Float,
+{
+    parallel::install(|| {
+        par_iter!(trees)
+            .zip(feature_subsets)
+            .map(|(tree, features)| predict(tree, &x.index_select(1, features)?))
+            .collect()
+    })
+}
 
 /// Random Forest Classifier — ensemble of decision trees with bagging.
+///
+/// Features may contain NaN: every split learns which child missing values
+/// go to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct RandomForestClassifier<T: Float> {
     pub n_estimators: usize,
     pub max_depth: usize,
@@ -16,7 +82,20 @@ pub struct RandomForestClassifier<T: Float> {
     pub seed: Option<u64>,
     trees: Vec<DecisionTreeClassifier<T>>,
     feature_subsets: Vec<Vec<usize>>,
+    oob_indices: Vec<Vec<usize>>,
+    /// Columns holding category codes; see
+    /// `DecisionTreeClassifier::with_categorical_features`.
+    pub categorical_features: Vec<usize>,
     pub n_classes: usize,
+    pub class_weight: Option<ClassWeight>,
+    /// Compute `oob_score` and `oob_prediction` during `fit`.
+    pub compute_oob: bool,
+    /// Accuracy of the out-of-bag predictions, over rows that were out of
+    /// bag for at least one tree.
+    pub oob_score: Option<f64>,
+    // May hold NaN, which JSON cannot represent; not needed after fitting.
+    #[serde(skip)]
+    oob_prediction: Option<Tensor<T>>,
 }
 
 impl<T: Float> RandomForestClassifier<T>
@@ -29,92 +108,178 @@ where
             max_depth,
             min_samples_split: 2,
             max_features_ratio,
-            seed: Some(42),
+            seed: None,
             trees: Vec::new(),
             feature_subsets: Vec::new(),
+            oob_indices: Vec::new(),
+            categorical_features: Vec::new(),
             n_classes: 0,
+            class_weight: None,
+            compute_oob: false,
+            oob_score: None,
+            oob_prediction: None,
         }
     }
 
+    /// Score each training row with the trees that did not see it during
+    /// `fit`, filling `oob_score` and `oob_prediction`.
+    pub fn with_oob_score(mut self) -> Self {
+        self.compute_oob = true;
+        self
+    }
+
+    /// Split the given columns by category membership in every tree.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
+    /// Reweight classes in every tree, e.g. `ClassWeight::Balanced` for
+    /// imbalanced targets.
+    pub fn with_class_weight(mut self, class_weight: ClassWeight) -> Self {
+        self.class_weight = Some(class_weight);
+        self
+    }
+
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        self.fit_weighted(x, y, None)
+    }
+
+    /// Fit with per-row weights (combined with `class_weight`); each tree
+    /// sees the weights of its bootstrap rows.
+    pub fn fit_weighted(&mut self, x: &Tensor<T>, y: &Tensor<T>, sample_weight: Option<&Tensor<T>>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
-        let max_features = ((p as f64 * self.max_features_ratio).ceil() as usize).max(1).min(p);
+        let weights = Tensor::new(sample_weights(y, self.class_weight.as_ref(), sample_weight)?, vec![y.numel()])?;
 
         let max_label = y.data().iter().map(|v| v.to_f64().round() as usize).max().unwrap_or(0);
         self.n_classes = max_label + 1;
 
-        let mut base_rng = match self.seed {
-            Some(s) => StdRng::seed_from_u64(s),
-            None => StdRng::from_entropy(),
-        };
-
-        self.trees.clear();
-        self.feature_subsets.clear();
+        let draws = draw_trees(self.n_estimators, n, p, self.max_features_ratio, self.seed);
+        let (max_depth, min_samples_split, categorical) = (self.max_depth, self.min_samples_split, &self.categorical_features);
+        self.trees = parallel::install(|| {
+            par_iter!(&draws)
+                .map(|d| {
+                    let x_sub = x.index_select(0, &d.sample)?.index_select(1, &d.features)?;
+                    let y_sub = y.index_select(0, &d.sample)?;
+                    let w_sub = weights.index_select(0, &d.sample)?;
+                    let mut tree = DecisionTreeClassifier::new(max_depth, min_samples_split, 1)
+                        .with_categorical_features(&subset_categorical(&d.features, categorical));
+                    tree.fit_weighted(&x_sub, &y_sub, Some(&w_sub))?;
+                    Ok(tree)
+                })
+                .collect::<TensorResult<Vec<_>>>()
+        })?;
+        (self.feature_subsets, self.oob_indices) = draws.into_iter().map(|d| (d.features, d.oob)).unzip();
+
+        self.oob_score = None;
+        self.oob_prediction = None;
+        if self.compute_oob {
+            self.compute_oob_prediction(x, y)?;
+        }
+        Ok(())
+    }
 
-        for _ in 0..self.n_estimators {
-            // Bootstrap sample
-            let mut sample_indices: Vec<usize> = (0..n).collect();
-            for i in 0..n {
-                let j = (rand::Rng::gen::<f64>(&mut base_rng) * n as f64) as usize;
-                sample_indices[i] = j.min(n - 1);
+    fn compute_oob_prediction(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let mut votes = vec![vec![0usize; self.n_classes]; n];
+        for ((tree, features), rows) in self.trees.iter().zip(&self.feature_subsets).zip(&self.oob_indices) {
+            if rows.is_empty() {
+                continue;
             }
-
-            // Feature subsampling
-            let mut feature_indices: Vec<usize> = (0..p).collect();
-            feature_indices.shuffle(&mut base_rng);
-            let selected_features: Vec<usize> = feature_indices[..max_features].to_vec();
-
-            // Create subset data
-            let mut x_sub_data = Vec::with_capacity(n * max_features);
-            let mut y_sub_data = Vec::with_capacity(n);
-            for &i in &sample_indices {
-                for &f in &selected_features {
-                    x_sub_data.push(x.get(&[i, f])?);
+            let pred = tree.predict(&x.index_select(0, rows)?.index_select(1, features)?)?;
+            for (&row, &p) in rows.iter().zip(pred.data()) {
+                let cls = p.to_f64().round() as usize;
+                if cls < self.n_classes {
+                    votes[row][cls] += 1;
                 }
-                y_sub_data.push(y.data()[i]);
             }
-            let x_sub = Tensor::new(x_sub_data, vec![n, max_features])?;
-            let y_sub = Tensor::new(y_sub_data, vec![n])?;
-
-            let mut tree = DecisionTreeClassifier::new(self.max_depth, self.min_samples_split, 1);
-            tree.fit(&x_sub, &y_sub)?;
-
-            self.trees.push(tree);
-            self.feature_subsets.push(selected_features);
         }
 
+        let (mut correct, mut scored) = (0usize, 0usize);
+        let prediction: Vec<T> = votes
+            .iter()
+            .zip(y.data())
+            .map(|(v, &label)| {
+                if v.iter().all(|&c| c == 0) {
+                    return T::from_f64(f64::NAN);
+                }
+                let best = v.iter().enumerate().max_by_key(|(_, &c)| c).map(|(i, _)| i).unwrap_or(0);
+                scored += 1;
+                if (label.to_f64() - best as f64).abs() < 0.5 {
+                    correct += 1;
+                }
+                T::from_usize(best)
+            })
+            .collect();
+        self.oob_score = (scored > 0).then(|| correct as f64 / scored as f64);
+        self.oob_prediction = Some(Tensor::new(prediction, vec![n])?);
         Ok(())
     }
 
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
         let n = x.shape().dim(0)?;
-        let mut predictions = Vec::with_capacity(n);
-
-        for i in 0..n {
-            let mut votes = vec![0usize; self.n_classes];
-            for (tree_idx, tree) in self.trees.iter().enumerate() {
-                let features = &self.feature_subsets[tree_idx];
-                let mut row_data = Vec::with_capacity(features.len());
-                for &f in features {
-                    row_data.push(x.get(&[i, f])?);
-                }
-                let row = Tensor::new(row_data, vec![1, features.len()])?;
-                let pred = tree.predict(&row)?;
-                let cls = pred.data()[0].to_f64().round() as usize;
+        let mut votes = vec![vec![0usize; self.n_classes]; n];
+
+        for pred in tree_predictions(&self.trees, &self.feature_subsets, x, DecisionTreeClassifier::predict)? {
+            for (row_votes, &p) in votes.iter_mut().zip(pred.data()) {
+                let cls = p.to_f64().round() as usize;
                 if cls < self.n_classes {
-                    votes[cls] += 1;
+                    row_votes[cls] += 1;
                 }
             }
-            let best = votes.iter().enumerate().max_by_key(|(_, &c)| c).map(|(i, _)| i).unwrap_or(0);
-            predictions.push(T::from_usize(best));
         }
 
+        let predictions: Vec<T> = votes
+            .iter()
+            .map(|v| {
+                let best = v.iter().enumerate().max_by_key(|(_, &c)| c).map(|(i, _)| i).unwrap_or(0);
+                T::from_usize(best)
+            })
+            .collect();
         Tensor::new(predictions, vec![n])
     }
+
+    /// Share of trees voting for each class, `[n, n_classes]`.
+    pub fn predict_proba(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
+        let n = x.shape().dim(0)?;
+        let k = self.n_classes;
+        let mut votes = vec![T::ZERO; n * k];
+        let share = T::ONE / T::from_usize(self.trees.len().max(1));
+        for pred in tree_predictions(&self.trees, &self.feature_subsets, x, DecisionTreeClassifier::predict)? {
+            for (i, &p) in pred.data().iter().enumerate() {
+                let cls = p.to_f64().round() as usize;
+                if cls < k {
+                    votes[i * k + cls] += share;
+                }
+            }
+        }
+        Tensor::new(votes, vec![n, k])
+    }
 }
 
-/// Random Forest Regressor.
+impl<T: Float> RandomForestClassifier<T> {
+    /// Fitted trees with the feature columns each was trained on; a tree's
+    /// feature `j` is column `features[j]` of the input.
+    pub fn estimators(&self) -> impl Iterator<Item = (&DecisionTreeClassifier<T>, &[usize])> {
+        self.trees.iter().zip(self.feature_subsets.iter().map(|f| f.as_slice()))
+    }
+
+    /// Training rows left out of each tree's bootstrap sample.
+    pub fn oob_indices(&self) -> &[Vec<usize>] {
+        &self.oob_indices
+    }
+
+    /// Majority vote of the trees that did not train on each row; NaN for
+    /// rows in every bootstrap sample. `None` unless fitted `with_oob_score`.
+    pub fn oob_prediction(&self) -> Option<&Tensor<T>> {
+        self.oob_prediction.as_ref()
+    }
+}
+
+/// Random Forest Regressor. Features may contain NaN, as in the classifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Float")]
 pub struct RandomForestRegressor<T: Float> {
     pub n_estimators: usize,
     pub max_depth: usize,
@@ -123,6 +288,18 @@ pub struct RandomForestRegressor<T: Float> {
     pub seed: Option<u64>,
     trees: Vec<DecisionTreeRegressor<T>>,
     feature_subsets: Vec<Vec<usize>>,
+    oob_indices: Vec<Vec<usize>>,
+    /// Columns holding category codes; see
+    /// `DecisionTreeClassifier::with_categorical_features`.
+    pub categorical_features: Vec<usize>,
+    /// Compute `oob_score` and `oob_prediction` during `fit`.
+    pub compute_oob: bool,
+    /// R² of the out-of-bag predictions, over rows that were out of bag for
+    /// at least one tree.
+    pub oob_score: Option<f64>,
+    // May hold NaN, which JSON cannot represent; not needed after fitting.
+    #[serde(skip)]
+    oob_prediction: Option<Tensor<T>>,
 }
 
 impl<T: Float> RandomForestRegressor<T>
@@ -135,77 +312,126 @@ where
             max_depth,
             min_samples_split: 2,
             max_features_ratio,
-            seed: Some(42),
+            seed: None,
             trees: Vec::new(),
             feature_subsets: Vec::new(),
+            oob_indices: Vec::new(),
+            categorical_features: Vec::new(),
+            compute_oob: false,
+            oob_score: None,
+            oob_prediction: None,
         }
     }
 
+    /// Score each training row with the trees that did not see it during
+    /// `fit`, filling `oob_score` and `oob_prediction`.
+    pub fn with_oob_score(mut self) -> Self {
+        self.compute_oob = true;
+        self
+    }
+
+    /// Split the given columns by category membership in every tree.
+    pub fn with_categorical_features(mut self, features: &[usize]) -> Self {
+        self.categorical_features = features.to_vec();
+        self
+    }
+
     pub fn fit(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
         let n = x.shape().dim(0)?;
         let p = x.shape().dim(1)?;
-        let max_features = ((p as f64 * self.max_features_ratio).ceil() as usize).max(1).min(p);
-
-        let mut base_rng = match self.seed {
-            Some(s) => StdRng::seed_from_u64(s),
-            None => StdRng::from_entropy(),
-        };
-
-        self.trees.clear();
-        self.feature_subsets.clear();
+        let draws = draw_trees(self.n_estimators, n, p, self.max_features_ratio, self.seed);
+        let (max_depth, min_samples_split, categorical) = (self.max_depth, self.min_samples_split, &self.categorical_features);
+        self.trees = parallel::install(|| {
+            par_iter!(&draws)
+                .map(|d| {
+                    let x_sub = x.index_select(0, &d.sample)?.index_select(1, &d.features)?;
+                    let y_sub = y.index_select(0, &d.sample)?;
+                    let mut tree = DecisionTreeRegressor::new(max_depth, min_samples_split, 1)
+                        .with_categorical_features(&subset_categorical(&d.features, categorical));
+                    tree.fit(&x_sub, &y_sub)?;
+                    Ok(tree)
+                })
+                .collect::<TensorResult<Vec<_>>>()
+        })?;
+        (self.feature_subsets, self.oob_indices) = draws.into_iter().map(|d| (d.features, d.oob)).unzip();
+
+        self.oob_score = None;
+        self.oob_prediction = None;
+        if self.compute_oob {
+            self.compute_oob_prediction(x, y)?;
+        }
+        Ok(())
+    }
 
-        for _ in 0..self.n_estimators {
-            let mut sample_indices: Vec<usize> = Vec::with_capacity(n);
-            for _ in 0..n {
-                let j = (rand::Rng::gen::<f64>(&mut base_rng) * n as f64) as usize;
-                sample_indices.push(j.min(n - 1));
+    fn compute_oob_prediction(&mut self, x: &Tensor<T>, y: &Tensor<T>) -> TensorResult<()> {
+        let n = x.shape().dim(0)?;
+        let mut sums = vec![T::ZERO; n];
+        let mut counts = vec![0usize; n];
+        for ((tree, features), rows) in self.trees.iter().zip(&self.feature_subsets).zip(&self.oob_indices) {
+            if rows.is_empty() {
+                continue;
             }
-
-            let mut feature_indices: Vec<usize> = (0..p).collect();
-            feature_indices.shuffle(&mut base_rng);
-            let selected_features: Vec<usize> = feature_indices[..max_features].to_vec();
-
-            let mut x_sub_data = Vec::with_capacity(n * max_features);
-            let mut y_sub_data = Vec::with_capacity(n);
-            for &i in &sample_indices {
-                for &f in &selected_features {
-                    x_sub_data.push(x.get(&[i, f])?);
-                }
-                y_sub_data.push(y.data()[i]);
+            let pred = tree.predict(&x.index_select(0, rows)?.index_select(1, features)?)?;
+            for (&row, &p) in rows.iter().zip(pred.data()) {
+                sums[row] += p;
+                counts[row] += 1;
             }
-            let x_sub = Tensor::new(x_sub_data, vec![n, max_features])?;
-            let y_sub = Tensor::new(y_sub_data, vec![n])?;
-
-            let mut tree = DecisionTreeRegressor::new(self.max_depth, self.min_samples_split, 1);
-            tree.fit(&x_sub, &y_sub)?;
-
-            self.trees.push(tree);
-            self.feature_subsets.push(selected_features);
         }
 
+        let prediction: Vec<T> = sums
+            .iter()
+            .zip(&counts)
+            .map(|(&s, &c)| if c == 0 { T::from_f64(f64::NAN) } else { s / T::from_usize(c) })
+            .collect();
+        let scored: Vec<(f64, f64)> = prediction
+            .iter()
+            .zip(y.data())
+            .filter(|(p, _)| !p.is_nan())
+            .map(|(p, t)| (p.to_f64(), t.to_f64()))
+            .collect();
+        self.oob_score = if scored.is_empty() {
+            None
+        } else {
+            let mean = scored.iter().map(|(_, t)| t).sum::<f64>() / scored.len() as f64;
+            let ss_res: f64 = scored.iter().map(|(p, t)| (t - p).powi(2)).sum();
+            let ss_tot: f64 = scored.iter().map(|(_, t)| (t - mean).powi(2)).sum();
+            Some(if ss_tot > 0.0 { 1.0 - ss_res / ss_tot } else { 0.0 })
+        };
+        self.oob_prediction = Some(Tensor::new(prediction, vec![n])?);
         Ok(())
     }
 
     pub fn predict(&self, x: &Tensor<T>) -> TensorResult<Tensor<T>> {
         let n = x.shape().dim(0)?;
-        let mut predictions = Vec::with_capacity(n);
-
-        for i in 0..n {
-            let mut sum = T::ZERO;
-            for (tree_idx, tree) in self.trees.iter().enumerate() {
-                let features = &self.feature_subsets[tree_idx];
-                let mut row_data = Vec::with_capacity(features.len());
-                for &f in features {
-                    row_data.push(x.get(&[i, f])?);
-                }
-                let row = Tensor::new(row_data, vec![1, features.len()])?;
-                let pred = tree.predict(&row)?;
-                sum = sum + pred.data()[0];
+        let mut sums = vec![T::ZERO; n];
+
+        for pred in tree_predictions(&self.trees, &self.feature_subsets, x, DecisionTreeRegressor::predict)? {
+            for (s, &p) in sums.iter_mut().zip(pred.data()) {
+                *s += p;
             }
-            predictions.push(sum / T::from_usize(self.trees.len()));
         }
 
-        Tensor::new(predictions, vec![n])
+        let n_trees = T::from_usize(self.trees.len());
+        Tensor::new(sums.into_iter().map(|s| s / n_trees).collect(), vec![n])
+    }
+}
+
+impl<T: Float> RandomForestRegressor<T> {
+    /// Fitted trees with the feature columns each was trained on; a tree's
+    /// feature `j` is column `features[j]` of the input.
+    pub fn estimators(&self) -> impl Iterator<Item = (&DecisionTreeRegressor<T>, &[usize])> {
+        self.trees.iter().zip(self.feature_subsets.iter().map(|f| f.as_slice()))
+    }
+
+    /// Training rows left out of each tree's bootstrap sample.
+    pub fn oob_indices(&self) -> &[Vec<usize>] {
+        &self.oob_indices
+    }
+
+    /// Mean prediction of the trees that did not train on each row; NaN for
+    /// rows in every bootstrap sample. `None` unless fitted `with_oob_score`.
+    pub fn oob_prediction(&self) -> Option<&Tensor<T>> {
+        self.oob_prediction.as_ref()
     }
 }
 
@@ -229,4 +455,50 @@ mod tests {
             assert!((pred.data()[i] - y.data()[i]).abs() < 0.5);
         }
     }
+
+    #[test]
+    fn test_random_forest_oob() {
+        let n = 60;
+        let x = Tensor::new((0..n).map(|i| i as f64).collect(), vec![n, 1]).unwrap();
+        let labels = Tensor::new((0..n).map(|i| (i >= n / 2) as u8 as f64).collect(), vec![n]).unwrap();
+        let targets = Tensor::new((0..n).map(|i| 2.0 * i as f64).collect(), vec![n]).unwrap();
+
+        let mut rf = RandomForestClassifier::new(20, 5, 1.0).with_oob_score();
+        rf.seed = Some(4);
+        rf.fit(&x, &labels).unwrap();
+        for (rows, (tree, _)) in rf.oob_indices().iter().zip(rf.estimators()) {
+            assert!(!rows.is_empty() && rows.len() < n);
+            assert!(tree.nodes().len() > 1);
+        }
+        assert!(rf.oob_score.unwrap() > 0.9);
+        assert_eq!(rf.oob_prediction().unwrap().numel(), n);
+
+        let mut rf = RandomForestRegressor::new(20, 6, 1.0).with_oob_score();
+        rf.seed = Some(4);
+        rf.fit(&x, &targets).unwrap();
+        assert!(rf.oob_score.unwrap() > 0.95);
+        assert!(RandomForestRegressor::<f64>::new(2, 2, 1.0).oob_prediction().is_none());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_random_forest_same_result_for_any_thread_count() {
+        let n = 40;
+        let x = Tensor::new((0..n * 3).map(|i| ((i * 7919) % 97) as f64).collect(), vec![n, 3]).unwrap();
+        let y = Tensor::new((0..n).map(|i| (i % 3) as f64).collect(), vec![n]).unwrap();
+        let fit_with = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| {
+                let mut rf = RandomForestClassifier::new(12, 4, 0.7);
+                rf.seed = Some(9);
+                rf.fit(&x, &y).unwrap();
+                let nodes: Vec<_> = rf.estimators().map(|(t, f)| (t.nodes(), f.to_vec())).collect();
+                (nodes, rf.predict(&x).unwrap())
+            })
+        };
+        let (nodes_1, pred_1) = fit_with(1);
+        let (nodes_4, pred_4) = fit_with(4);
+        assert_eq!(nodes_1, nodes_4);
+        assert_eq!(pred_1.data(), pred_4.data());
+    }
 }
@@ -0,0 +1,104 @@
+//! WebAssembly bindings: load a saved `.oxml` classifier (a pipeline or a
+//! single estimator) from bytes fetched by JavaScript and predict in the
+//! browser.
+//!
+//! Build with `wasm-pack build --target web crates/oxidize-ml-wasm`; the
+//! demo page in `www/` loads the generated `pkg/`.
+
+use oxidize_ml_core::Tensor;
+use oxidize_ml_pipeline::{estimator_from_bytes, Estimator, Persist, PersistError, Pipeline};
+use wasm_bindgen::prelude::*;
+
+enum Model {
+    Pipeline(Pipeline),
+    Estimator(Box<dyn Estimator>),
+}
+
+#[wasm_bindgen]
+pub struct Classifier {
+    model: Model,
+    kind: String,
+}
+
+impl Classifier {
+    fn load(bytes: &[u8]) -> Result<Self, String> {
+        match Pipeline::from_bytes(bytes) {
+            Ok(pipeline) => Ok(Classifier { model: Model::Pipeline(pipeline), kind: "Pipeline".to_string() }),
+            Err(PersistError::WrongModel { found, .. }) => {
+                let estimator = estimator_from_bytes(bytes).map_err(|e| e.to_string())?;
+                Ok(Classifier { model: Model::Estimator(estimator), kind: found })
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn run(&self, x: &Tensor<f64>) -> Result<Vec<f64>, String> {
+        let y = match &self.model {
+            Model::Pipeline(p) => p.predict(x),
+            Model::Estimator(e) => e.predict(x),
+        };
+        y.map(|y| y.data().to_vec()).map_err(|e| e.to_string())
+    }
+
+    fn run_rows(&self, data: &[f64], n_features: usize) -> Result<Vec<f64>, String> {
+        if n_features == 0 || !data.len().is_multiple_of(n_features) {
+            return Err(format!("{} values do not split into samples of {} features", data.len(), n_features));
+        }
+        let x = Tensor::new(data.to_vec(), vec![data.len() / n_features, n_features]).map_err(|e| e.to_string())?;
+        self.run(&x)
+    }
+
+    fn run_csv(&self, text: &str) -> Result<Vec<f64>, String> {
+        let (x, _) = oxidize_ml_io::parse_csv(text.as_bytes()).map_err(|e| e.to_string())?;
+        self.run(&x)
+    }
+}
+
+#[wasm_bindgen]
+impl Classifier {
+    /// Load the contents of an `.oxml` file.
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<Classifier, JsError> {
+        Self::load(bytes).map_err(|e| JsError::new(&e))
+    }
+
+    /// The saved model type, e.g. `"LogisticRegression"` or `"Pipeline"`.
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.kind.clone()
+    }
+
+    /// One prediction per sample of `data`, row-major with `n_features`
+    /// values per sample (a `Float64Array` on the JavaScript side).
+    pub fn predict(&self, data: &[f64], n_features: usize) -> Result<Vec<f64>, JsError> {
+        self.run_rows(data, n_features).map_err(|e| JsError::new(&e))
+    }
+
+    /// Predict for the rows of CSV text with a header line.
+    #[wasm_bindgen(js_name = predictCsv)]
+    pub fn predict_csv(&self, text: &str) -> Result<Vec<f64>, JsError> {
+        self.run_csv(text).map_err(|e| JsError::new(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidize_ml_linear::LogisticRegression;
+
+    #[test]
+    fn test_classifier_from_bytes() {
+        let x = Tensor::new(vec![0.0, 0.2, 0.3, 0.1, 3.0, 2.8, 2.9, 3.2], vec![4, 2]).unwrap();
+        let y = Tensor::new(vec![0.0, 0.0, 1.0, 1.0], vec![4]).unwrap();
+        let mut model = LogisticRegression::new(0.5, 500);
+        model.fit(&x, &y).unwrap();
+        let expected = model.predict(&x).unwrap().data().to_vec();
+
+        let classifier = Classifier::load(&model.to_bytes().unwrap()).unwrap();
+        assert_eq!(classifier.kind(), "LogisticRegression");
+        assert_eq!(classifier.run_rows(x.data(), 2).unwrap(), expected);
+        assert_eq!(classifier.run_csv("a,b\n0,0.2\n0.3,0.1\n3,2.8\n2.9,3.2\n").unwrap(), expected);
+        assert!(classifier.run_rows(x.data(), 3).is_err());
+        assert!(Classifier::load(b"not a model").is_err());
+    }
+}
@@ -4,24 +4,29 @@
 //!
 //! ## Modules
 //!
-//! - **core** — Tensor engine: N-dimensional arrays with broadcasting, arithmetic, reductions
-//! - **linalg** — Linear algebra: LU, QR, Cholesky, SVD, matrix inverse, linear solvers
-//! - **autodiff** — Automatic differentiation: computation graph with reverse-mode AD
-//! - **preprocessing** — StandardScaler, MinMaxScaler, LabelEncoder, train/test split
-//! - **linear** — Linear models: OLS, Ridge, Lasso, ElasticNet, Logistic Regression
-//! - **tree** — Tree models: Decision Tree (CART), Random Forest, Gradient Boosting
-//! - **cluster** — Clustering: K-Means (with k-means++), DBSCAN
-//! - **neighbors** — KNN: classifier and regressor with Euclidean/Manhattan distance
-//! - **svm** — Support Vector Machines: SVC/SVR with kernel support
-//! - **naive_bayes** — Naive Bayes: Gaussian NB
-//! - **metrics** — Evaluation: accuracy, precision, recall, F1, MSE, RMSE, R²
-//! - **nn** — Neural networks: Linear layer, ReLU/Sigmoid/Tanh, Sequential
-//! - **optim** — Optimizers: SGD (momentum), Adam
-//! - **loss** — Loss functions: MSE, BCE
-//! - **data** — Data loading: Dataset trait, DataLoader with batching
-//! - **io** — I/O: CSV read/write, model serialization
-//! - **datasets** — Built-in: Iris, make_blobs, make_regression
-//! - **pipeline** — Pipeline: composable Transformer + Estimator chains
+//! - **core** — Tensor engine: N-dimensional arrays with broadcasting, arithmetic, reductions, complex and sparse tensors, f16/bf16 storage, FFT, seedable global RNG with saveable stream positions
+//! - **linalg** — Linear algebra: LU, QR, Cholesky, SVD (exact or randomized), symmetric and general eigendecomposition, matrix inverse, linear solvers (general, triangular, banded, rank-revealing least squares), Kronecker and block-diagonal construction, trace/diag/triu/tril
+//! - **autodiff** — Automatic differentiation: thread-safe computation graph handle with reverse-mode AD through elementwise, matmul, axis-reduction, shape and softmax ops, higher-order gradients (grad-of-grad, Hessian-vector products), per-parameter gradient accumulation with `zero_grad`, gradient checkpointing, `no_grad` and inference mode, f16/bf16 `autocast` regions
+//! - **preprocessing** — StandardScaler, MinMaxScaler, RobustScaler, MaxAbsScaler, QuantileTransformer, PowerTransformer (Yeo-Johnson/Box-Cox), LogTransformer, LabelEncoder, OneHotEncoder, OrdinalEncoder, SimpleImputer, KNNImputer, PolynomialFeatures, SplineTransformer, PCA (full or randomized solver), IncrementalPCA, TruncatedSVD (dense or sparse input), train/test split (optionally stratified)
+//! - **linear** — Linear models: OLS and Ridge with a choice of Cholesky, QR, SVD or LSQR solver, Lasso, ElasticNet, RidgeCV/LassoCV/ElasticNetCV choosing the penalty by cross-validation along a warm-started path, Logistic Regression (binary, multinomial, one-vs-rest), LDA (classifier and supervised projection) and QDA with covariance shrinkage, Poisson/Gamma/Tweedie GLMs fitted by IRLS, Quantile and Huber robust regression
+//! - **tree** — Tree models: Decision Tree (CART), Random Forest, Gradient Boosting, Extra Trees
+//! - **cluster** — Clustering: K-Means (with k-means++), DBSCAN, OPTICS, HDBSCAN, agglomerative (single, complete, average or Ward linkage, with the merge tree exposed), Gaussian mixtures fitted by EM with full, diagonal or spherical covariances
+//! - **neighbors** — KNN and radius-neighbour classifiers and regressors, local outlier factor, `NearestNeighbors` queries backed by brute force, a KD-tree or a ball tree; Euclidean, Manhattan, Minkowski, Chebyshev, cosine, Hamming, Mahalanobis or custom distances
+//! - **svm** — Support Vector Machines: SVC (binary, one-vs-rest or one-vs-one) with decision values and Platt probabilities, kernel ε-SVR
+//! - **naive_bayes** — Naive Bayes: Gaussian, Multinomial, Bernoulli, Complement and Categorical NB, incremental `partial_fit`
+//! - **gaussian_process** — Gaussian process regression: RBF, Matérn, constant and white-noise kernels (composable), predictive variances, marginal-likelihood hyperparameter tuning
+//! - **metrics** — Evaluation: accuracy, precision, recall, F1, calibration curve, MSE, RMSE, R²
+//! - **nn** — Neural networks: Linear layer, ReLU/Sigmoid/Tanh/GELU/SiLU/ELU/Softplus/Mish, BatchNorm/LayerNorm/GroupNorm, Conv1D/Conv2D (dilated, grouped/depthwise), ConvTranspose2D and pooling, Sequential, recurrent cells and stacked bidirectional RNN/GRU/LSTM over padded or packed variable-length batches, Embedding with pretrained word vectors, masked MultiHeadAttention, Transformer encoder/decoder stacks with positional encodings and greedy/beam generation, prebuilt ResNet/LeNet/VGG models and a BERT-style encoder, weight init schemes, word/BPE/WordPiece tokenizers, named parameters and state dicts, int8 post-training quantization
+//! - **optim** — Optimizers: SGD (momentum), Adam, AdamW, NAdam, LAMB, Lion, parameter groups, EMA of weights, `GradScaler` loss scaling
+//! - **loss** — Loss functions: MSE, BCE, fused cross-entropy on logits, weighted CrossEntropy/NLL with ignore_index, label smoothing and soft targets, L1/Huber/SmoothL1, Focal, Hinge, KL divergence, triplet/contrastive/cosine-embedding metric-learning losses, mean/sum/none reductions
+//! - **data** — Data loading: Dataset trait, DataLoader with batching, streaming CSV loader, Parquet and Arrow-style record batches, mixup/cutmix batch augmentation
+//! - **train** — Training loop: `Trainer` with fit/evaluate/predict over DataLoaders, per-epoch metrics and history, callbacks (learning-rate scheduling, early stopping, best-model checkpointing, metric logging); scalar/histogram logging to CSV, JSONL and TensorBoard event files, checkpoints that resume a run exactly, multi-threaded data-parallel steps
+//! - **distributed** — Multi-machine training: TCP ring all-reduce and broadcast, deterministic dataset sharding by rank, `DistributedTrainer` averaging gradients across ranks
+//! - **io** — I/O: CSV read/write, NumPy .npy/.npz, model serialization, safetensors, ONNX export, PMML export, GGUF import
+//! - **datasets** — Built-in: Iris, make_blobs, make_regression; downloadable MNIST, Fashion-MNIST, CIFAR-10
+//! - **pipeline** — Pipeline: composable Transformer + Estimator chains, ColumnTransformer, TransformedTargetRegressor, `.oxml` model persistence
+//! - **serve** — Inference server: `/predict`, `/health`, `/metadata` over HTTP with JSON or MessagePack bodies, batching and a worker pool
+//! - **model_selection** — Cross-validation: KFold, StratifiedKFold, GroupShuffleSplit, TimeSeriesSplit, LeaveOneOut, cross_val_score, GridSearchCV, RandomizedSearchCV, CalibratedClassifier (Platt/isotonic)
 
 /// Core tensor engine.
 pub use oxidize_ml_core as core;
@@ -53,6 +58,9 @@ pub use oxidize_ml_svm as svm;
 /// Naive Bayes classifiers.
 pub use oxidize_ml_naive_bayes as naive_bayes;
 
+/// Gaussian processes.
+pub use oxidize_ml_gaussian_process as gaussian_process;
+
 /// Evaluation metrics.
 pub use oxidize_ml_metrics as metrics;
 
@@ -68,6 +76,12 @@ pub use oxidize_ml_loss as loss;
 /// Data loading utilities.
 pub use oxidize_ml_data as data;
 
+/// Training loop and callbacks.
+pub use oxidize_ml_train as train;
+
+/// Multi-machine training over TCP.
+pub use oxidize_ml_distributed as distributed;
+
 /// I/O utilities.
 pub use oxidize_ml_io as io;
 
@@ -76,3 +90,9 @@ pub use oxidize_ml_datasets as datasets;
 
 /// Pipeline API.
 pub use oxidize_ml_pipeline as pipeline;
+
+/// HTTP inference server.
+pub use oxidize_ml_serve as serve;
+
+/// Cross-validation and model selection.
+pub use oxidize_ml_model_selection as model_selection;
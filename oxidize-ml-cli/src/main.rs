@@ -170,11 +170,12 @@ fn run_training_dashboard(stdout: &mut std::io::Stdout) -> Result<(), Box<dyn st
 
     // Live Training Loop
     for epoch in 0..=epochs {
-        dataloader.reset();
+        dataloader.reset()?;
         
         let mut sum_loss = 0.0;
 
-        for (b_idx, (batch_x, batch_y)) in dataloader.by_ref().enumerate() {
+        for (b_idx, batch) in dataloader.by_ref().enumerate() {
+            let (batch_x, batch_y) = batch?;
             // Attach inputs/targets to the current Graph Tape
             let x = Variable::input(batch_x, graph.clone());
             let target = Variable::input(batch_y, graph.clone());